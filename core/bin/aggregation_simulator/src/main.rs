@@ -0,0 +1,100 @@
+use anyhow::Context as _;
+use clap::Parser;
+use zksync_config::{ETHSenderConfig, PostgresConfig};
+use zksync_core::eth_sender::{simulate_aggregation, AggregationPolicyHandle, SimulatedL1Batch};
+use zksync_dal::ConnectionPool;
+use zksync_env_config::FromEnv;
+use zksync_types::api::AggregationPolicyUpdate;
+
+#[derive(Debug, Parser)]
+#[command(
+    author = "Matter Labs",
+    version,
+    about = "Simulates L1 batch aggregation policy over recent L1 batches",
+    long_about = None
+)]
+struct Cli {
+    /// Number of latest L1 batches to simulate aggregation for.
+    #[arg(long, default_value_t = 1_000)]
+    l1_batches: u32,
+    /// L1 gas price in wei used to estimate the cost of L1 transactions.
+    #[arg(long)]
+    l1_gas_price: u64,
+    /// Max number of L1 batches committed in a single L1 transaction. Defaults to the configured value.
+    #[arg(long)]
+    max_blocks_to_commit: Option<u32>,
+    /// Max number of L1 batches proven in a single L1 transaction. Defaults to the configured value.
+    #[arg(long)]
+    max_blocks_to_prove: Option<u32>,
+    /// Max number of L1 batches executed in a single L1 transaction. Defaults to the configured value.
+    #[arg(long)]
+    max_blocks_to_execute: Option<u32>,
+    /// Max gas limit of an aggregated L1 transaction. Defaults to the configured value.
+    #[arg(long)]
+    max_tx_gas: Option<u32>,
+    /// Max commit transaction calldata size in bytes. Defaults to the configured value.
+    #[arg(long)]
+    max_tx_data_size: Option<usize>,
+    /// Commit deadline in seconds. Defaults to the configured value.
+    #[arg(long)]
+    commit_deadline: Option<u64>,
+    /// Prove deadline in seconds. Defaults to the configured value.
+    #[arg(long)]
+    prove_deadline: Option<u64>,
+    /// Execute deadline in seconds. Defaults to the configured value.
+    #[arg(long)]
+    execute_deadline: Option<u64>,
+}
+
+impl Cli {
+    fn policy_update(&self) -> AggregationPolicyUpdate {
+        AggregationPolicyUpdate {
+            max_aggregated_blocks_to_commit: self.max_blocks_to_commit,
+            max_aggregated_blocks_to_prove: self.max_blocks_to_prove,
+            max_aggregated_blocks_to_execute: self.max_blocks_to_execute,
+            max_aggregated_tx_gas: self.max_tx_gas,
+            max_eth_tx_data_size: self.max_tx_data_size,
+            aggregated_block_commit_deadline: self.commit_deadline,
+            aggregated_block_prove_deadline: self.prove_deadline,
+            aggregated_block_execute_deadline: self.execute_deadline,
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    #[allow(deprecated)] // TODO (QIT-21): Use centralized configuration approach.
+    let log_format = vlog::log_format_from_env();
+    let _guard = vlog::ObservabilityBuilder::new()
+        .with_log_format(log_format)
+        .build();
+
+    let cli = Cli::parse();
+    let eth_sender = ETHSenderConfig::from_env().context("ETHSenderConfig::from_env()")?;
+    let postgres_config = PostgresConfig::from_env().context("PostgresConfig::from_env()")?;
+
+    let policy = AggregationPolicyHandle::new(&eth_sender.sender)
+        .context("configured aggregation policy is invalid")?;
+    let policy = policy
+        .update(cli.policy_update(), "command line")
+        .context("simulated aggregation policy is invalid")?;
+
+    let connection_pool = ConnectionPool::singleton(postgres_config.replica_url()?)
+        .build()
+        .await
+        .context("failed to build a connection pool")?;
+    let mut storage = connection_pool.access_storage().await?;
+    let l1_batches = SimulatedL1Batch::load_latest(&mut storage, cli.l1_batches)
+        .await
+        .context("failed loading L1 batches")?;
+    drop(storage);
+    tracing::info!("Loaded {} L1 batches for simulation", l1_batches.len());
+
+    let report = simulate_aggregation(&policy, &l1_batches, cli.l1_gas_price)?;
+    let output = serde_json::json!({
+        "policy": policy,
+        "report": report,
+    });
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
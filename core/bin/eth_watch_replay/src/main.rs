@@ -0,0 +1,81 @@
+use anyhow::Context as _;
+use clap::Parser;
+use zksync_config::{ContractsConfig, ETHClientConfig, PostgresConfig};
+use zksync_contracts::governance_contract;
+use zksync_core::eth_watch::L1EventsReplay;
+use zksync_dal::ConnectionPool;
+use zksync_env_config::FromEnv;
+use zksync_eth_client::clients::QueryClient;
+
+#[derive(Debug, Parser)]
+#[command(
+    author = "Matter Labs",
+    version,
+    about = "Re-scans L1 events and reconciles them with the priority queue and protocol versions in Postgres",
+    long_about = None
+)]
+struct Cli {
+    /// First L1 block to re-scan.
+    #[arg(long)]
+    from_block: u64,
+    /// Last L1 block to re-scan (inclusive).
+    #[arg(long)]
+    to_block: u64,
+    /// Number of L1 blocks re-scanned at once.
+    #[arg(long, default_value_t = 10_000)]
+    chunk_size: u64,
+    /// Repair found discrepancies: insert missing priority operations and protocol versions, and remove stale
+    /// priority operations unless they are executed or loaded into mempool.
+    #[arg(long)]
+    repair: bool,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    #[allow(deprecated)] // TODO (QIT-21): Use centralized configuration approach.
+    let log_format = vlog::log_format_from_env();
+    let _guard = vlog::ObservabilityBuilder::new()
+        .with_log_format(log_format)
+        .build();
+
+    let cli = Cli::parse();
+    anyhow::ensure!(cli.chunk_size > 0, "Chunk size must be positive");
+    let eth_client = ETHClientConfig::from_env().context("ETHClientConfig::from_env()")?;
+    let contracts = ContractsConfig::from_env().context("ContractsConfig::from_env()")?;
+    let postgres_config = PostgresConfig::from_env().context("PostgresConfig::from_env()")?;
+
+    let connection_pool = ConnectionPool::singleton(postgres_config.master_url()?)
+        .build()
+        .await
+        .context("failed to build a connection pool")?;
+    let query_client =
+        QueryClient::with_failover(&eth_client.web3_urls()).context("failed creating L1 client")?;
+    let replay = L1EventsReplay::new(
+        Box::new(query_client),
+        contracts.diamond_proxy_addr,
+        (governance_contract(), contracts.governance_addr),
+    );
+
+    let mut reports = vec![];
+    let mut is_consistent = true;
+    let mut from_block = cli.from_block;
+    while from_block <= cli.to_block {
+        let to_block = cli
+            .to_block
+            .min(from_block.saturating_add(cli.chunk_size - 1));
+        let report = replay
+            .run(&connection_pool, from_block, to_block, cli.repair)
+            .await
+            .with_context(|| format!("failed re-scanning L1 blocks {from_block}..={to_block}"))?;
+        is_consistent &= report.is_consistent();
+        reports.push(report);
+        from_block = to_block + 1;
+    }
+
+    println!("{}", serde_json::to_string_pretty(&reports)?);
+    if !is_consistent && !cli.repair {
+        tracing::warn!("Found discrepancies; re-run with `--repair` to fix them");
+        std::process::exit(1);
+    }
+    Ok(())
+}
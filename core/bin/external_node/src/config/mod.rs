@@ -3,12 +3,17 @@ use std::{env, time::Duration};
 use anyhow::Context;
 use serde::Deserialize;
 use url::Url;
-use zksync_basic_types::{Address, L1ChainId, L2ChainId, MiniblockNumber};
+use zksync_basic_types::{Address, L1BatchCommitmentMode, L1ChainId, L2ChainId, MiniblockNumber};
+use zksync_config::configs::{
+    api::SubscriptionBackpressurePolicy,
+    database::{MerkleTreeBackend, RocksdbCompactionStyle},
+    DbPrunerConfig, ObjectStoreConfig,
+};
 use zksync_core::api_server::{
     tx_sender::TxSenderConfig,
     web3::{state::InternalApiConfig, Namespace},
 };
-use zksync_types::api::BridgeAddresses;
+use zksync_types::api::{BridgeAddresses, FeeToken};
 use zksync_web3_decl::{
     jsonrpsee::http_client::{HttpClient, HttpClientBuilder},
     namespaces::{EnNamespaceClient, EthNamespaceClient, ZksNamespaceClient},
@@ -30,6 +35,7 @@ pub struct RemoteENConfig {
     pub l2_testnet_paymaster_addr: Option<Address>,
     pub l2_chain_id: L2ChainId,
     pub l1_chain_id: L1ChainId,
+    pub fee_token: FeeToken,
 
     pub fair_l2_gas_price: u64,
 }
@@ -63,6 +69,10 @@ impl RemoteENConfig {
                 .context("Failed to fetch L1 chain ID")?
                 .as_u64(),
         );
+        let fee_token = client
+            .get_fee_token()
+            .await
+            .context("Failed to fetch fee token")?;
         let current_miniblock = client
             .get_block_number()
             .await
@@ -82,6 +92,7 @@ impl RemoteENConfig {
             l2_weth_bridge_addr: bridges.l2_weth_bridge,
             l2_chain_id,
             l1_chain_id,
+            fee_token,
             fair_l2_gas_price: block_header.l2_fair_gas_price,
         })
     }
@@ -96,9 +107,26 @@ pub struct OptionalENConfig {
     /// Max possible limit of filters to be in the API state at once.
     #[serde(default = "OptionalENConfig::default_filters_limit")]
     pub filters_limit: usize,
+    /// Whether to store installed filters in Postgres, so that they are shared among API server replicas
+    /// using the same database.
+    #[serde(default)]
+    pub shared_filters: bool,
+    /// Whether to serve server-sent events with new miniblock headers and sync status on `GET /events`
+    /// of the HTTP server.
+    #[serde(default)]
+    pub sse_enabled: bool,
     /// Max possible limit of subscriptions to be in the API state at once.
     #[serde(default = "OptionalENConfig::default_subscriptions_limit")]
     pub subscriptions_limit: usize,
+    /// Max number of active subscriptions for a single WebSocket connection.
+    #[serde(default = "OptionalENConfig::default_max_subscriptions_per_connection")]
+    pub max_subscriptions_per_connection: u32,
+    /// Max number of notifications queued for a single subscription.
+    #[serde(default = "OptionalENConfig::default_subscription_queue_capacity")]
+    pub subscription_queue_capacity: usize,
+    /// Action taken if a subscription queue overflows.
+    #[serde(default)]
+    pub subscription_backpressure_policy: SubscriptionBackpressurePolicy,
     /// Max possible limit of entities to be requested via API at once.
     #[serde(default = "OptionalENConfig::default_req_entities_limit")]
     pub req_entities_limit: usize,
@@ -119,6 +147,25 @@ pub struct OptionalENConfig {
     /// Maximum response body size in MiBs. Default is 10 MiB.
     #[serde(default = "OptionalENConfig::default_max_response_body_size_mb")]
     pub max_response_body_size_mb: usize,
+    /// Whether `debug_traceBlock*` methods should re-execute the miniblock if call traces for it are missing.
+    #[serde(default)]
+    pub trace_block_reexecution_enabled: bool,
+    /// Weight of the latest sample when smoothing `eth_maxPriorityFeePerGas` suggestions; 1 disables smoothing.
+    #[serde(default = "OptionalENConfig::default_max_priority_fee_smoothing_factor")]
+    pub max_priority_fee_smoothing_factor: f64,
+    /// Maximum number of `eth_call` results for finalized blocks cached in memory. Set to 0 to disable caching.
+    #[serde(default = "OptionalENConfig::default_eth_call_cache_capacity")]
+    pub eth_call_cache_capacity: usize,
+    /// Maximum number of miniblocks in the range queried by `eth_getLogs`. If not set, the range is not limited.
+    pub get_logs_block_range_limit: Option<u64>,
+    /// Maximum number of logs returned by `eth_getLogs` and log filters. If not set, `req_entities_limit` is used.
+    pub get_logs_max_results: Option<usize>,
+    /// Maximum number of calls in a single `debug_traceCallMany` request.
+    #[serde(default = "OptionalENConfig::default_trace_call_many_max_calls")]
+    pub trace_call_many_max_calls: usize,
+    /// Maximum number of miniblocks in the range queried by `trace_filter`.
+    #[serde(default = "OptionalENConfig::default_trace_filter_block_range_limit")]
+    pub trace_filter_block_range_limit: u64,
 
     // Other API config settings
     /// Interval between polling DB for pubsub (in ms).
@@ -134,6 +181,10 @@ pub struct OptionalENConfig {
     /// This option can be tweaked down if the API server is running out of memory.
     #[serde(default = "OptionalENConfig::default_vm_concurrency_limit")]
     pub vm_concurrency_limit: usize,
+    /// Max number of VM instances to be concurrently spawned for a single client (identified by the IP address).
+    /// If not set, clients are not limited.
+    #[serde(default)]
+    pub vm_concurrency_limit_per_client: Option<usize>,
     /// Smart contract bytecode cache size for the API server. Default value is 128 MiB.
     #[serde(default = "OptionalENConfig::default_factory_deps_cache_size_mb")]
     factory_deps_cache_size_mb: usize,
@@ -154,6 +205,20 @@ pub struct OptionalENConfig {
     /// The max possible number of gas that `eth_estimateGas` is allowed to overestimate.
     #[serde(default = "OptionalENConfig::default_estimate_gas_acceptable_overestimation")]
     pub estimate_gas_acceptable_overestimation: u32,
+    /// Initial lower bound of the gas limit binary search used by gas estimation.
+    #[serde(default)]
+    pub estimate_gas_lower_bound: u32,
+    /// Initial upper bound of the gas limit binary search used by gas estimation. If not set,
+    /// the max gas limit of an L2 transaction is used.
+    pub estimate_gas_upper_bound: Option<u32>,
+    /// Maximum number of iterations of the gas limit binary search. If not set, the number is not limited.
+    pub estimate_gas_max_iterations: Option<usize>,
+    /// Maximum number of gas estimation results cached in memory. Set to 0 to disable caching.
+    #[serde(default = "OptionalENConfig::default_estimate_gas_cache_capacity")]
+    pub estimate_gas_cache_capacity: usize,
+    /// Gas limit for read-only calls (`eth_call`, `debug_traceCall` etc.). If not set, the max gas limit
+    /// of an L2 transaction is used.
+    pub call_gas_limit: Option<u32>,
     /// The multiplier to use when suggesting gas price. Should be higher than one,
     /// otherwise if the L1 prices soar, the suggested gas price won't be sufficient to be included in block
     #[serde(default = "OptionalENConfig::default_gas_price_scale_factor")]
@@ -168,6 +233,10 @@ pub struct OptionalENConfig {
         default = "OptionalENConfig::default_max_l1_batches_per_tree_iter"
     )]
     pub max_l1_batches_per_tree_iter: usize,
+    /// Storage backend for the Merkle tree. The in-memory backend doesn't persist the tree on disk, so the tree
+    /// is rebuilt from Postgres on each node restart; it should only be used for tests and dev nodes.
+    #[serde(default)]
+    pub merkle_tree_backend: MerkleTreeBackend,
     /// Chunk size for multi-get operations. Can speed up loading data for the Merkle tree on some environments,
     /// but the effects vary wildly depending on the setup (e.g., the filesystem used).
     #[serde(default = "OptionalENConfig::default_merkle_tree_multi_get_chunk_size")]
@@ -183,6 +252,69 @@ pub struct OptionalENConfig {
     /// Timeout to wait for the Merkle tree database to run compaction on stalled writes.
     #[serde(default = "OptionalENConfig::default_merkle_tree_stalled_writes_timeout_sec")]
     merkle_tree_stalled_writes_timeout_sec: u64,
+    /// Compaction style for the Merkle tree RocksDB.
+    #[serde(default)]
+    pub merkle_tree_compaction_style: RocksdbCompactionStyle,
+    /// Size of a single memtable in the Merkle tree RocksDB. If not specified, the size is derived
+    /// from `merkle_tree_memtable_capacity_mb`.
+    merkle_tree_write_buffer_size_mb: Option<usize>,
+    /// Maximum number of memtables per column family in the Merkle tree RocksDB. If not specified,
+    /// the number is derived from `merkle_tree_memtable_capacity_mb`.
+    pub merkle_tree_max_write_buffer_number: Option<usize>,
+    /// Maximum number of concurrent background jobs (compactions and flushes) in the Merkle tree RocksDB.
+    /// If not specified, the number is derived from the number of CPU cores.
+    pub merkle_tree_max_background_jobs: Option<usize>,
+    /// Capacity of the block cache for the state keeper RocksDB. If not specified, default RocksDB cache options
+    /// are used.
+    state_keeper_db_block_cache_size_mb: Option<usize>,
+    /// Compaction style for the state keeper RocksDB.
+    #[serde(default)]
+    pub state_keeper_db_compaction_style: RocksdbCompactionStyle,
+    /// Size of a single memtable in the state keeper RocksDB. If not specified, the RocksDB default is used.
+    state_keeper_db_write_buffer_size_mb: Option<usize>,
+    /// Maximum number of memtables per column family in the state keeper RocksDB. If not specified,
+    /// the RocksDB default is used.
+    pub state_keeper_db_max_write_buffer_number: Option<usize>,
+    /// Maximum number of concurrent background jobs (compactions and flushes) in the state keeper RocksDB.
+    /// If not specified, the number is derived from the number of CPU cores.
+    pub state_keeper_db_max_background_jobs: Option<usize>,
+    /// Number of threads in a dedicated thread pool used to compute hashes and Merkle proofs when updating
+    /// the Merkle tree. If not specified, the global thread pool sized by the number of CPU cores is used.
+    pub merkle_tree_thread_pool_size: Option<usize>,
+    /// Whether to prune old Merkle tree versions. Versions are only pruned for L1 batches executed on L1
+    /// and older than `merkle_tree_pruning_retained_l1_batches`.
+    #[serde(default)]
+    merkle_tree_pruning_enabled: bool,
+    /// Number of latest L1 batches for which Merkle tree versions are retained if pruning is enabled.
+    #[serde(default = "OptionalENConfig::default_merkle_tree_pruning_retained_l1_batches")]
+    merkle_tree_pruning_retained_l1_batches: u32,
+    /// Interval between checks for Merkle tree versions that can be pruned.
+    #[serde(default = "OptionalENConfig::default_merkle_tree_pruning_poll_interval_ms")]
+    merkle_tree_pruning_poll_interval_ms: u64,
+    /// Whether to periodically verify the Merkle tree against Postgres (root hashes of processed L1 batches
+    /// and a random sample of tree leaves).
+    #[serde(default)]
+    merkle_tree_consistency_check_enabled: bool,
+    /// Number of tree leaves checked against Postgres on each consistency check iteration.
+    #[serde(default = "OptionalENConfig::default_merkle_tree_consistency_check_sample_size")]
+    merkle_tree_consistency_check_sample_size: usize,
+    /// Interval between Merkle tree consistency check iterations.
+    #[serde(default = "OptionalENConfig::default_merkle_tree_consistency_check_interval_ms")]
+    merkle_tree_consistency_check_interval_ms: u64,
+
+    // Postgres pruning
+    /// Whether to prune old data (transactions, events, overwritten storage logs, etc.) from Postgres.
+    /// Data is only pruned for L1 batches executed on L1.
+    #[serde(default)]
+    pruning_enabled: bool,
+    /// Number of latest L1 batches executed on L1 for which data is retained if pruning is enabled.
+    pub pruning_data_retention_l1_batches: Option<u32>,
+    /// Minimum age of L1 batches (in seconds) for their data to be pruned.
+    pub pruning_data_retention_sec: Option<u64>,
+    /// Maximum number of L1 batches pruned in a single database transaction.
+    pub pruning_chunk_size: Option<u32>,
+    /// Interval between checks for L1 batches that can be pruned.
+    pub pruning_polling_interval_ms: Option<u64>,
 
     // Other config settings
     /// Port on which the Prometheus exporter server is listening.
@@ -195,6 +327,10 @@ pub struct OptionalENConfig {
     /// 0 means that sealing is synchronous; this is mostly useful for performance comparison, testing etc.
     #[serde(default = "OptionalENConfig::default_miniblock_seal_queue_capacity")]
     pub miniblock_seal_queue_capacity: usize,
+    /// Whether the main node publishes L1 batch pubdata on L1 (`Rollup`) or not (`Validium`). Must correspond
+    /// to the main node configuration; influences L1 batch metadata and checking consistency with L1.
+    #[serde(default)]
+    pub l1_batch_commit_data_generator_mode: L1BatchCommitmentMode,
 }
 
 impl OptionalENConfig {
@@ -206,6 +342,14 @@ impl OptionalENConfig {
         10_000
     }
 
+    const fn default_max_subscriptions_per_connection() -> u32 {
+        1_024
+    }
+
+    const fn default_subscription_queue_capacity() -> usize {
+        1_024
+    }
+
     const fn default_req_entities_limit() -> usize {
         1_024
     }
@@ -226,6 +370,10 @@ impl OptionalENConfig {
         1_000
     }
 
+    const fn default_estimate_gas_cache_capacity() -> usize {
+        1_024
+    }
+
     const fn default_gas_price_scale_factor() -> f64 {
         1.2
     }
@@ -277,10 +425,42 @@ impl OptionalENConfig {
         30
     }
 
+    const fn default_merkle_tree_pruning_retained_l1_batches() -> u32 {
+        1_000
+    }
+
+    const fn default_merkle_tree_pruning_poll_interval_ms() -> u64 {
+        60_000
+    }
+
+    const fn default_merkle_tree_consistency_check_sample_size() -> usize {
+        1_000
+    }
+
+    const fn default_merkle_tree_consistency_check_interval_ms() -> u64 {
+        60_000
+    }
+
     const fn default_fee_history_limit() -> u64 {
         1_024
     }
 
+    const fn default_max_priority_fee_smoothing_factor() -> f64 {
+        0.25
+    }
+
+    const fn default_eth_call_cache_capacity() -> usize {
+        1_024
+    }
+
+    const fn default_trace_call_many_max_calls() -> usize {
+        50
+    }
+
+    const fn default_trace_filter_block_range_limit() -> u64 {
+        10_000
+    }
+
     const fn default_max_batch_request_size() -> usize {
         500 // The default limit is chosen to be reasonably permissive.
     }
@@ -335,6 +515,58 @@ impl OptionalENConfig {
         Duration::from_secs(self.merkle_tree_stalled_writes_timeout_sec)
     }
 
+    /// Returns the size of a single memtable for Merkle tree in bytes, if specified.
+    pub fn merkle_tree_write_buffer_size(&self) -> Option<usize> {
+        self.merkle_tree_write_buffer_size_mb
+            .map(|size| size * BYTES_IN_MEGABYTE)
+    }
+
+    /// Returns the size of block cache for the state keeper RocksDB in bytes, if specified.
+    pub fn state_keeper_db_block_cache_size(&self) -> Option<usize> {
+        self.state_keeper_db_block_cache_size_mb
+            .map(|size| size * BYTES_IN_MEGABYTE)
+    }
+
+    /// Returns the size of a single memtable for the state keeper RocksDB in bytes, if specified.
+    pub fn state_keeper_db_write_buffer_size(&self) -> Option<usize> {
+        self.state_keeper_db_write_buffer_size_mb
+            .map(|size| size * BYTES_IN_MEGABYTE)
+    }
+
+    /// Returns the number of latest L1 batches for which Merkle tree versions are retained, or `None`
+    /// if pruning is disabled.
+    pub fn merkle_tree_pruning_retained_l1_batches(&self) -> Option<u32> {
+        self.merkle_tree_pruning_enabled
+            .then_some(self.merkle_tree_pruning_retained_l1_batches)
+    }
+
+    /// Returns the interval between checks for Merkle tree versions that can be pruned.
+    pub fn merkle_tree_pruning_poll_interval(&self) -> Duration {
+        Duration::from_millis(self.merkle_tree_pruning_poll_interval_ms)
+    }
+
+    /// Returns the number of Merkle tree leaves checked on each consistency check iteration, or `None`
+    /// if consistency checks are disabled.
+    pub fn merkle_tree_consistency_check_sample_size(&self) -> Option<usize> {
+        self.merkle_tree_consistency_check_enabled
+            .then_some(self.merkle_tree_consistency_check_sample_size)
+    }
+
+    /// Returns the interval between Merkle tree consistency check iterations.
+    pub fn merkle_tree_consistency_check_interval(&self) -> Duration {
+        Duration::from_millis(self.merkle_tree_consistency_check_interval_ms)
+    }
+
+    /// Returns the Postgres pruning configuration, or `None` if pruning is disabled.
+    pub fn db_pruner_config(&self) -> Option<DbPrunerConfig> {
+        self.pruning_enabled.then_some(DbPrunerConfig {
+            data_retention_l1_batches: self.pruning_data_retention_l1_batches,
+            data_retention_sec: self.pruning_data_retention_sec,
+            pruned_batch_chunk_size: self.pruning_chunk_size,
+            polling_interval_ms: self.pruning_polling_interval_ms,
+        })
+    }
+
     pub fn api_namespaces(&self) -> Vec<Namespace> {
         self.api_namespaces
             .clone()
@@ -413,6 +645,9 @@ pub struct ExternalNodeConfig {
     pub postgres: PostgresConfig,
     pub optional: OptionalENConfig,
     pub remote: RemoteENConfig,
+    /// Object store with node snapshots. If specified, the Merkle tree is recovered by streaming storage log chunks
+    /// of the snapshot from this store. Configured via `EN_SNAPSHOTS_OBJECT_STORE_*` env variables.
+    pub snapshots_object_store: Option<ObjectStoreConfig>,
 }
 
 impl ExternalNodeConfig {
@@ -472,12 +707,21 @@ impl ExternalNodeConfig {
         }
 
         let postgres = PostgresConfig::from_env()?;
+        let snapshots_object_store = if env::var_os("EN_SNAPSHOTS_OBJECT_STORE_MODE").is_some() {
+            let config = envy::prefixed("EN_SNAPSHOTS_OBJECT_STORE_")
+                .from_env::<ObjectStoreConfig>()
+                .context("could not load snapshots object store config")?;
+            Some(config)
+        } else {
+            None
+        };
 
         Ok(Self {
             remote,
             postgres,
             required,
             optional,
+            snapshots_object_store,
         })
     }
 }
@@ -511,8 +755,21 @@ impl From<ExternalNodeConfig> for InternalApiConfig {
             },
             diamond_proxy_addr: config.remote.diamond_proxy_addr,
             l2_testnet_paymaster_addr: config.remote.l2_testnet_paymaster_addr,
+            fee_token: config.remote.fee_token,
             req_entities_limit: config.optional.req_entities_limit,
             fee_history_limit: config.optional.fee_history_limit,
+            trace_block_reexecution_enabled: config.optional.trace_block_reexecution_enabled,
+            max_priority_fee_smoothing_factor: config.optional.max_priority_fee_smoothing_factor,
+            eth_call_cache_capacity: config.optional.eth_call_cache_capacity,
+            get_logs_block_range_limit: config.optional.get_logs_block_range_limit,
+            get_logs_max_results: config
+                .optional
+                .get_logs_max_results
+                .unwrap_or(config.optional.req_entities_limit),
+            trace_call_many_max_calls: config.optional.trace_call_many_max_calls,
+            trace_filter_block_range_limit: config.optional.trace_filter_block_range_limit,
+            // The external node doesn't serve proofs since it has no tree API.
+            max_tree_lag_for_proofs: None,
         }
     }
 }
@@ -527,6 +784,10 @@ impl From<ExternalNodeConfig> for TxSenderConfig {
                 .unwrap(),
             gas_price_scale_factor: config.optional.gas_price_scale_factor,
             max_nonce_ahead: config.optional.max_nonce_ahead,
+            // Transactions are proxied to the main node, which enforces replacement rules.
+            replacement_fee_bump_percent: 0,
+            max_pending_txs_per_account: None,
+            max_pending_txs_per_paymaster: None,
             fair_l2_gas_price: config.remote.fair_l2_gas_price,
             vm_execution_cache_misses_limit: config.optional.vm_execution_cache_misses_limit,
             // We set these values to the maximum since we don't know the actual values
@@ -534,6 +795,11 @@ impl From<ExternalNodeConfig> for TxSenderConfig {
             max_allowed_l2_tx_gas_limit: u32::MAX,
             validation_computational_gas_limit: u32::MAX,
             chain_id: config.remote.l2_chain_id,
+            estimate_gas_lower_bound: config.optional.estimate_gas_lower_bound,
+            estimate_gas_upper_bound: config.optional.estimate_gas_upper_bound,
+            estimate_gas_max_iterations: config.optional.estimate_gas_max_iterations,
+            estimate_gas_cache_capacity: config.optional.estimate_gas_cache_capacity,
+            call_gas_limit: config.optional.call_gas_limit,
         }
     }
 }
@@ -17,13 +17,14 @@ use zksync_core::{
     },
     block_reverter::{BlockReverter, BlockReverterFlags, L1ExecutedBatchesRevert},
     consistency_checker::ConsistencyChecker,
+    db_pruner::DbPruner,
     l1_gas_price::MainNodeFeeParamsFetcher,
     metadata_calculator::{MetadataCalculator, MetadataCalculatorConfig},
     reorg_detector::ReorgDetector,
     setup_sigint_handler,
     state_keeper::{
         seal_criteria::NoopSealer, L1BatchExecutorBuilder, MainBatchExecutorBuilder,
-        MiniblockSealer, MiniblockSealerHandle, ZkSyncStateKeeper,
+        MiniblockSealer, MiniblockSealerHandle, StateKeeperDbOptions, ZkSyncStateKeeper,
     },
     sync_layer::{
         batch_status_updater::BatchStatusUpdater, external_io::ExternalIO, fetcher::FetcherCursor,
@@ -32,6 +33,7 @@ use zksync_core::{
 };
 use zksync_dal::{healthcheck::ConnectionPoolHealthCheck, ConnectionPool};
 use zksync_health_check::CheckHealth;
+use zksync_object_store::ObjectStoreFactory;
 use zksync_state::PostgresStorageCaches;
 use zksync_storage::RocksDB;
 use zksync_utils::wait_for_tasks::wait_for_tasks;
@@ -63,11 +65,13 @@ async fn build_state_keeper(
     // node has already executed the transaction, then the external node must execute it too.
     let max_allowed_l2_tx_gas_limit = u32::MAX.into();
     let validation_computational_gas_limit = u32::MAX;
-    // We only need call traces on the external node if the `debug_` namespace is enabled.
-    let save_call_traces = config.optional.api_namespaces().contains(&Namespace::Debug);
+    // We only need call traces on the external node if the `debug_` or `trace_` namespace is enabled.
+    let api_namespaces = config.optional.api_namespaces();
+    let save_call_traces =
+        api_namespaces.contains(&Namespace::Debug) || api_namespaces.contains(&Namespace::Trace);
 
-    let batch_executor_base: Box<dyn L1BatchExecutorBuilder> =
-        Box::new(MainBatchExecutorBuilder::new(
+    let batch_executor_base: Box<dyn L1BatchExecutorBuilder> = Box::new(
+        MainBatchExecutorBuilder::new(
             state_keeper_db_path,
             connection_pool.clone(),
             max_allowed_l2_tx_gas_limit,
@@ -75,7 +79,15 @@ async fn build_state_keeper(
             false,
             config.optional.enum_index_migration_chunk_size,
             true,
-        ));
+        )
+        .with_state_keeper_db_options(StateKeeperDbOptions {
+            block_cache_capacity: config.optional.state_keeper_db_block_cache_size(),
+            compaction_style: config.optional.state_keeper_db_compaction_style,
+            write_buffer_size: config.optional.state_keeper_db_write_buffer_size(),
+            max_write_buffer_number: config.optional.state_keeper_db_max_write_buffer_number,
+            max_background_jobs: config.optional.state_keeper_db_max_background_jobs,
+        }),
+    );
 
     let main_node_url = config.required.main_node_url().unwrap();
     let main_node_client = <dyn MainNodeClient>::json_rpc(&main_node_url)
@@ -189,15 +201,35 @@ async fn init_tasks(
     let metadata_calculator_config = MetadataCalculatorConfig {
         db_path: config.required.merkle_tree_path.clone(),
         mode: MerkleTreeMode::Full,
+        backend: config.optional.merkle_tree_backend,
         delay_interval: config.optional.metadata_calculator_delay(),
         max_l1_batches_per_iter: config.optional.max_l1_batches_per_tree_iter,
         multi_get_chunk_size: config.optional.merkle_tree_multi_get_chunk_size,
         block_cache_capacity: config.optional.merkle_tree_block_cache_size(),
         memtable_capacity: config.optional.merkle_tree_memtable_capacity(),
         stalled_writes_timeout: config.optional.merkle_tree_stalled_writes_timeout(),
+        compaction_style: config.optional.merkle_tree_compaction_style,
+        write_buffer_size: config.optional.merkle_tree_write_buffer_size(),
+        max_write_buffer_number: config.optional.merkle_tree_max_write_buffer_number,
+        max_background_jobs: config.optional.merkle_tree_max_background_jobs,
+        thread_pool_size: config.optional.merkle_tree_thread_pool_size,
+        commitment_mode: config.optional.l1_batch_commit_data_generator_mode,
+        pruning_retained_l1_batches: config.optional.merkle_tree_pruning_retained_l1_batches(),
+        pruning_poll_interval: config.optional.merkle_tree_pruning_poll_interval(),
+        consistency_check_sample_size: config.optional.merkle_tree_consistency_check_sample_size(),
+        consistency_check_interval: config.optional.merkle_tree_consistency_check_interval(),
     };
-    let metadata_calculator = MetadataCalculator::new(metadata_calculator_config, None).await;
+    let mut metadata_calculator = MetadataCalculator::new(metadata_calculator_config, None).await;
+    if let Some(object_store_config) = config.snapshots_object_store.clone() {
+        let blob_store = ObjectStoreFactory::new(object_store_config)
+            .create_store()
+            .await;
+        metadata_calculator = metadata_calculator.with_snapshot_object_store(blob_store);
+    }
     healthchecks.push(Box::new(metadata_calculator.tree_health_check()));
+    if let Some(health_check) = metadata_calculator.consistency_check_health_check() {
+        healthchecks.push(Box::new(health_check));
+    }
 
     let consistency_checker = ConsistencyChecker::new(
         &config
@@ -209,6 +241,7 @@ async fn init_tasks(
             .build()
             .await
             .context("failed to build connection pool for ConsistencyChecker")?,
+        config.optional.l1_batch_commit_data_generator_mode,
     );
 
     let batch_status_updater = BatchStatusUpdater::new(
@@ -248,6 +281,12 @@ async fn init_tasks(
 
         let max_concurrency = config.optional.vm_concurrency_limit;
         let (vm_concurrency_limiter, vm_barrier) = VmConcurrencyLimiter::new(max_concurrency);
+        let vm_concurrency_limiter = vm_concurrency_limiter.with_per_client_limit(
+            config
+                .optional
+                .vm_concurrency_limit_per_client
+                .map(|limit| limit.min(max_concurrency)),
+        );
         let mut storage_caches = PostgresStorageCaches::new(
             config.optional.factory_deps_cache_size() as u64,
             config.optional.initial_writes_cache_size() as u64,
@@ -272,7 +311,7 @@ async fn init_tasks(
         (tx_sender, vm_barrier, cache_update_handle)
     };
 
-    let http_server_handles =
+    let mut http_server_builder =
         ApiBuilder::jsonrpsee_backend(config.clone().into(), connection_pool.clone())
             .http(config.required.http_port)
             .with_filter_limit(config.optional.filters_limit)
@@ -280,29 +319,47 @@ async fn init_tasks(
             .with_response_body_size_limit(config.optional.max_response_body_size())
             .with_tx_sender(tx_sender.clone(), vm_barrier.clone())
             .with_sync_state(sync_state.clone())
-            .enable_api_namespaces(config.optional.api_namespaces())
-            .build(stop_receiver.clone())
-            .await
-            .context("Failed initializing HTTP JSON-RPC server")?;
+            .enable_api_namespaces(config.optional.api_namespaces());
+    if config.optional.shared_filters {
+        http_server_builder = http_server_builder.with_shared_filters(connection_pool.clone());
+    }
+    if config.optional.sse_enabled {
+        http_server_builder = http_server_builder.enable_sse_endpoint();
+    }
+    let http_server_handles = http_server_builder
+        .build(stop_receiver.clone())
+        .await
+        .context("Failed initializing HTTP JSON-RPC server")?;
 
-    let ws_server_handles =
+    let mut ws_server_builder =
         ApiBuilder::jsonrpsee_backend(config.clone().into(), connection_pool.clone())
             .ws(config.required.ws_port)
             .with_filter_limit(config.optional.filters_limit)
             .with_subscriptions_limit(config.optional.subscriptions_limit)
+            .with_max_subscriptions_per_connection(config.optional.max_subscriptions_per_connection)
+            .with_subscription_queue(
+                config.optional.subscription_queue_capacity,
+                config.optional.subscription_backpressure_policy,
+            )
             .with_batch_request_size_limit(config.optional.max_batch_request_size)
             .with_response_body_size_limit(config.optional.max_response_body_size())
             .with_polling_interval(config.optional.polling_interval())
             .with_tx_sender(tx_sender, vm_barrier)
             .with_sync_state(sync_state)
-            .enable_api_namespaces(config.optional.api_namespaces())
-            .build(stop_receiver.clone())
-            .await
-            .context("Failed initializing WS JSON-RPC server")?;
+            .enable_api_namespaces(config.optional.api_namespaces());
+    if config.optional.shared_filters {
+        ws_server_builder = ws_server_builder.with_shared_filters(connection_pool.clone());
+    }
+    let ws_server_handles = ws_server_builder
+        .build(stop_receiver.clone())
+        .await
+        .context("Failed initializing WS JSON-RPC server")?;
 
     healthchecks.push(Box::new(ws_server_handles.health_check));
     healthchecks.push(Box::new(http_server_handles.health_check));
-    healthchecks.push(Box::new(ConnectionPoolHealthCheck::new(connection_pool)));
+    healthchecks.push(Box::new(ConnectionPoolHealthCheck::new(
+        connection_pool.clone(),
+    )));
     let healthcheck_handle = HealthCheckHandle::spawn_server(
         ([0, 0, 0, 0], config.required.healthcheck_port).into(),
         healthchecks,
@@ -324,6 +381,11 @@ async fn init_tasks(
     ]);
     task_handles.push(consistency_checker_handle);
 
+    if let Some(db_pruner_config) = config.optional.db_pruner_config() {
+        let db_pruner = DbPruner::new(connection_pool, db_pruner_config);
+        task_handles.push(tokio::spawn(db_pruner.run(stop_receiver.clone())));
+    }
+
     Ok((task_handles, stop_sender, healthcheck_handle, stop_receiver))
 }
 
@@ -0,0 +1,152 @@
+use std::path::Path;
+
+use anyhow::Context as _;
+use clap::{Parser, Subcommand};
+use zksync_config::{DBConfig, PostgresConfig};
+use zksync_core::metadata_calculator::{export_tree_snapshot, import_tree_snapshot};
+use zksync_dal::ConnectionPool;
+use zksync_env_config::{object_store::SnapshotsObjectStoreConfig, FromEnv};
+use zksync_merkle_tree::{domain::ZkSyncTree, RocksDBWrapper};
+use zksync_object_store::ObjectStoreFactory;
+use zksync_storage::RocksDB;
+use zksync_types::{L1BatchNumber, H256};
+
+#[derive(Debug, Parser)]
+#[command(
+    author = "Matter Labs",
+    version,
+    about = "Exports Merkle tree snapshots to the object store and imports them",
+    long_about = None
+)]
+struct Cli {
+    /// Path to the Merkle tree RocksDB directory. If not specified, the path is taken from the database config.
+    #[arg(long)]
+    db_path: Option<String>,
+    /// Number of snapshot chunks processed concurrently.
+    #[arg(long, default_value_t = 10)]
+    concurrency: usize,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Exports the tree state after the specified L1 batch.
+    Export {
+        /// L1 batch to export the tree for. If not specified, the latest L1 batch processed by the tree is used.
+        #[arg(long = "l1-batch")]
+        l1_batch: Option<u32>,
+        /// Desired number of tree leaves in a snapshot chunk.
+        #[arg(long, default_value_t = 200_000)]
+        chunk_size: u64,
+    },
+    /// Imports a tree snapshot into an empty tree, or resumes an interrupted import.
+    Import {
+        /// L1 batch of the snapshot to import.
+        #[arg(long = "l1-batch")]
+        l1_batch: u32,
+        /// Expected root hash of the tree. If not specified, the root hash is taken from Postgres if possible.
+        #[arg(long)]
+        expected_root_hash: Option<H256>,
+    },
+}
+
+/// Reads the root hash for the L1 batch from Postgres. Returns `None` if Postgres is not configured,
+/// or if it doesn't contain the root hash (e.g., because it's computed by the tree on the node).
+async fn root_hash_from_postgres(l1_batch_number: L1BatchNumber) -> anyhow::Result<Option<H256>> {
+    let Ok(postgres_config) = PostgresConfig::from_env() else {
+        return Ok(None);
+    };
+    let Ok(url) = postgres_config.master_url() else {
+        return Ok(None);
+    };
+    let pool = ConnectionPool::singleton(url)
+        .build()
+        .await
+        .context("failed to build a connection pool")?;
+    let mut storage = pool.access_storage().await?;
+    let root_hash = storage
+        .blocks_dal()
+        .get_l1_batch_state_root(l1_batch_number)
+        .await
+        .with_context(|| format!("failed getting root hash for L1 batch #{l1_batch_number}"))?;
+    Ok(root_hash)
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    #[allow(deprecated)] // TODO (QIT-21): Use centralized configuration approach.
+    let log_format = vlog::log_format_from_env();
+    let _guard = vlog::ObservabilityBuilder::new()
+        .with_log_format(log_format)
+        .build();
+
+    let cli = Cli::parse();
+    anyhow::ensure!(cli.concurrency > 0, "Concurrency must be positive");
+    let db_path = if let Some(path) = cli.db_path {
+        path
+    } else {
+        let db_config = DBConfig::from_env().context("DBConfig::from_env()")?;
+        db_config.merkle_tree.path
+    };
+    let object_store_config =
+        SnapshotsObjectStoreConfig::from_env().context("SnapshotsObjectStoreConfig::from_env()")?;
+    let blob_store = ObjectStoreFactory::new(object_store_config.0)
+        .create_store()
+        .await;
+
+    tracing::info!("Opening Merkle tree at {db_path}");
+    let db = RocksDBWrapper::from(RocksDB::new(Path::new(&db_path)));
+
+    match cli.command {
+        Command::Export {
+            l1_batch,
+            chunk_size,
+        } => {
+            let l1_batch_number = if let Some(number) = l1_batch {
+                L1BatchNumber(number)
+            } else {
+                let tree = ZkSyncTree::new_lightweight(db.clone());
+                let next_number = tree.next_l1_batch_number();
+                anyhow::ensure!(next_number > L1BatchNumber(0), "Merkle tree is empty");
+                next_number - 1
+            };
+            let header = export_tree_snapshot(
+                db,
+                l1_batch_number,
+                &*blob_store,
+                chunk_size,
+                cli.concurrency,
+            )
+            .await?;
+            tracing::info!("Exported snapshot: {header:?}");
+        }
+        Command::Import {
+            l1_batch,
+            expected_root_hash,
+        } => {
+            let l1_batch_number = L1BatchNumber(l1_batch);
+            let expected_root_hash = if let Some(hash) = expected_root_hash {
+                Some(hash)
+            } else {
+                root_hash_from_postgres(l1_batch_number).await?
+            };
+            if expected_root_hash.is_none() {
+                tracing::warn!(
+                    "Expected root hash for L1 batch #{l1_batch_number} is unknown; the snapshot will only be \
+                     checked against the root hash in its header"
+                );
+            }
+            let header = import_tree_snapshot(
+                db,
+                l1_batch_number,
+                &*blob_store,
+                expected_root_hash,
+                cli.concurrency,
+            )
+            .await?;
+            tracing::info!("Imported snapshot: {header:?}");
+        }
+    }
+    Ok(())
+}
@@ -11,8 +11,9 @@ use zksync_config::{
         },
         fri_prover_group::FriProverGroupConfig,
         house_keeper::HouseKeeperConfig,
-        FriProofCompressorConfig, FriProverConfig, FriWitnessGeneratorConfig, PrometheusConfig,
-        ProofDataHandlerConfig, WitnessGeneratorConfig,
+        DADispatcherConfig, DbPrunerConfig, FriProofCompressorConfig, FriProverConfig,
+        FriWitnessGeneratorConfig, PrometheusConfig, ProofDataHandlerConfig,
+        WitnessGeneratorConfig,
     },
     ApiConfig, ContractsConfig, DBConfig, ETHClientConfig, ETHSenderConfig, ETHWatchConfig,
     GasAdjusterConfig, ObjectStoreConfig, PostgresConfig,
@@ -21,7 +22,7 @@ use zksync_core::{
     genesis_init, initialize_components, is_genesis_needed, setup_sigint_handler,
     temp_config_store::TempConfigStore, Component, Components,
 };
-use zksync_env_config::FromEnv;
+use zksync_env_config::{object_store::DAObjectStoreConfig, FromEnv};
 use zksync_storage::RocksDB;
 use zksync_utils::wait_for_tasks::wait_for_tasks;
 
@@ -119,6 +120,9 @@ async fn main() -> anyhow::Result<()> {
         eth_watch_config: ETHWatchConfig::from_env().ok(),
         gas_adjuster_config: GasAdjusterConfig::from_env().ok(),
         object_store_config: ObjectStoreConfig::from_env().ok(),
+        da_dispatcher_config: DADispatcherConfig::from_env().ok(),
+        da_object_store_config: DAObjectStoreConfig::from_env().ok().map(|config| config.0),
+        db_pruner_config: DbPrunerConfig::from_env().ok(),
     };
 
     let postgres_config = configs.postgres_config.clone().context("PostgresConfig")?;
@@ -178,8 +182,22 @@ async fn main() -> anyhow::Result<()> {
     tokio::task::spawn_blocking(RocksDB::await_rocksdb_termination)
         .await
         .unwrap();
-    // Sleep for some time to let some components gracefully stop.
-    tokio::time::sleep(Duration::from_secs(5)).await;
+    // Sleep for some time to let some components gracefully stop. API servers may take longer to drain
+    // in-flight requests, so we additionally wait until all components drop their stop signal receivers.
+    let draining_timeout = configs
+        .api_config
+        .as_ref()
+        .map_or(Duration::ZERO, |config| {
+            config.web3_json_rpc.graceful_shutdown_timeout()
+        });
+    let shutdown_timeout = draining_timeout + Duration::from_secs(5);
+    let (_, stop_result) = tokio::join!(
+        tokio::time::sleep(Duration::from_secs(5)),
+        tokio::time::timeout(shutdown_timeout, stop_sender.closed())
+    );
+    if stop_result.is_err() {
+        tracing::warn!("Not all components have stopped after {shutdown_timeout:?}");
+    }
     health_check_handle.stop().await;
     tracing::info!("Stopped");
     Ok(())
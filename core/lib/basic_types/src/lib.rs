@@ -220,6 +220,18 @@ impl Default for PriorityOpId {
     }
 }
 
+/// Mode of publishing L1 batch data on L1.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum L1BatchCommitmentMode {
+    /// Pubdata of L1 batches (L2-to-L1 logs and messages, published bytecodes and state diffs) is published on L1,
+    /// so that the L2 state can be restored from L1 data.
+    #[default]
+    Rollup,
+    /// Pubdata is not published on L1; L1 batches are committed only with commitments to their data.
+    /// Requires L1 contracts that don't verify published pubdata.
+    Validium,
+}
+
 #[cfg(test)]
 mod tests {
     use serde_json::from_str;
@@ -0,0 +1,225 @@
+//! Encoding of L1 batch pubdata into EIP-4844 blobs and computation of KZG commitments for them.
+
+use c_kzg::{ethereum_kzg_settings, Blob, Bytes32, KzgCommitment, KzgProof};
+use zksync_crypto::hasher::{sha256::Sha256Hasher, Hasher};
+use zksync_types::{
+    eth_sender::{EthTxBlobSidecar, SidecarBlob},
+    web3::signing::keccak256,
+    H256,
+};
+
+/// Number of field elements in a blob.
+pub const FIELD_ELEMENTS_PER_BLOB: usize = 4_096;
+/// Size of a blob in bytes.
+pub const BYTES_PER_BLOB: usize = FIELD_ELEMENTS_PER_BLOB * 32;
+/// Number of pubdata bytes stored in a single field element. The most significant byte of each element is zeroed
+/// so that the element is always less than the BLS12-381 scalar field modulus.
+const PUBDATA_BYTES_PER_FIELD_ELEMENT: usize = 31;
+/// Maximum number of pubdata bytes that fit into a single blob.
+pub const MAX_PUBDATA_PER_BLOB: usize = FIELD_ELEMENTS_PER_BLOB * PUBDATA_BYTES_PER_FIELD_ELEMENT;
+/// Prefix of the pubdata commitments in the commit calldata signalling that pubdata is published in blobs.
+pub const PUBDATA_SOURCE_BLOBS: u8 = 1;
+/// Version byte of blob versioned hashes.
+const VERSIONED_HASH_VERSION_KZG: u8 = 1;
+
+/// Pubdata chunk encoded as a blob together with its KZG commitment and proofs.
+#[derive(Debug, Clone)]
+pub struct BlobInfo {
+    blob: Box<[u8; BYTES_PER_BLOB]>,
+    commitment: [u8; 48],
+    /// Proof used by the L1 node to verify the blob against its commitment.
+    blob_proof: [u8; 48],
+    versioned_hash: H256,
+    /// Point at which the blob polynomial is opened for the L1 contracts.
+    opening_point: [u8; 16],
+    /// Value of the blob polynomial at `opening_point`.
+    opening_value: [u8; 32],
+    /// Proof of the `opening_value`.
+    opening_proof: [u8; 48],
+}
+
+impl BlobInfo {
+    /// Encodes a pubdata chunk as a blob and computes its commitment and proofs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the chunk is larger than [`MAX_PUBDATA_PER_BLOB`].
+    pub fn new(pubdata: &[u8]) -> Self {
+        assert!(
+            pubdata.len() <= MAX_PUBDATA_PER_BLOB,
+            "Pubdata chunk ({} bytes) doesn't fit into a blob",
+            pubdata.len()
+        );
+
+        let mut blob = Box::new([0_u8; BYTES_PER_BLOB]);
+        for (element, chunk) in blob
+            .chunks_exact_mut(32)
+            .zip(pubdata.chunks(PUBDATA_BYTES_PER_FIELD_ELEMENT))
+        {
+            element[1..=chunk.len()].copy_from_slice(chunk);
+        }
+
+        let settings = ethereum_kzg_settings();
+        let kzg_blob = Blob::from_bytes(blob.as_slice()).expect("invalid blob size");
+        let commitment = KzgCommitment::blob_to_kzg_commitment(&kzg_blob, settings)
+            .expect("failed computing KZG commitment for a blob");
+        let commitment_bytes = commitment.to_bytes();
+        let blob_proof = KzgProof::compute_blob_kzg_proof(&kzg_blob, &commitment_bytes, settings)
+            .expect("failed computing blob KZG proof");
+
+        let mut versioned_hash = Sha256Hasher.hash_bytes(commitment_bytes.as_slice());
+        versioned_hash.0[0] = VERSIONED_HASH_VERSION_KZG;
+
+        // The opening point is derived from the blob contents so that it cannot be chosen by the operator.
+        let linear_hash = keccak256(blob.as_slice());
+        let mut preimage = [0_u8; 64];
+        preimage[..32].copy_from_slice(&linear_hash);
+        preimage[32..].copy_from_slice(versioned_hash.as_bytes());
+        let mut opening_point = [0_u8; 16];
+        opening_point.copy_from_slice(&keccak256(&preimage)[16..]);
+
+        let mut opening_point_bytes = [0_u8; 32];
+        opening_point_bytes[16..].copy_from_slice(&opening_point);
+        let opening_point_bytes =
+            Bytes32::from_bytes(&opening_point_bytes).expect("invalid opening point size");
+        let (opening_proof, opening_value) =
+            KzgProof::compute_kzg_proof(&kzg_blob, &opening_point_bytes, settings)
+                .expect("failed computing KZG opening proof");
+
+        Self {
+            blob,
+            commitment: *commitment_bytes,
+            blob_proof: *blob_proof.to_bytes(),
+            versioned_hash,
+            opening_point,
+            opening_value: *opening_value,
+            opening_proof: *opening_proof.to_bytes(),
+        }
+    }
+
+    pub fn versioned_hash(&self) -> H256 {
+        self.versioned_hash
+    }
+
+    /// Returns the commitment to the blob passed to the L1 contracts in the commit calldata:
+    /// `opening_point (16 bytes) ++ opening_value (32 bytes) ++ commitment (48 bytes) ++ opening_proof (48 bytes)`.
+    pub fn pubdata_commitment(&self) -> Vec<u8> {
+        let mut commitment = Vec::with_capacity(144);
+        commitment.extend_from_slice(&self.opening_point);
+        commitment.extend_from_slice(&self.opening_value);
+        commitment.extend_from_slice(&self.commitment);
+        commitment.extend_from_slice(&self.opening_proof);
+        commitment
+    }
+
+    fn into_sidecar_blob(self) -> SidecarBlob {
+        SidecarBlob {
+            blob: self.blob.to_vec(),
+            commitment: self.commitment.to_vec(),
+            proof: self.blob_proof.to_vec(),
+            versioned_hash: self.versioned_hash,
+        }
+    }
+}
+
+/// Returns the number of blobs required to publish pubdata of the specified length.
+pub fn blob_count(pubdata_len: usize) -> usize {
+    ((pubdata_len + MAX_PUBDATA_PER_BLOB - 1) / MAX_PUBDATA_PER_BLOB).max(1)
+}
+
+/// Splits pubdata into blobs. Empty pubdata is published in a single zero blob.
+pub fn pubdata_to_blobs(pubdata: &[u8]) -> Vec<BlobInfo> {
+    if pubdata.is_empty() {
+        return vec![BlobInfo::new(&[])];
+    }
+    pubdata
+        .chunks(MAX_PUBDATA_PER_BLOB)
+        .map(BlobInfo::new)
+        .collect()
+}
+
+/// Encodes pubdata commitments for the commit calldata, i.e. the pubdata source byte followed by commitments
+/// to all blobs.
+pub fn encode_pubdata_commitments(blobs: &[BlobInfo]) -> Vec<u8> {
+    let mut commitments = vec![PUBDATA_SOURCE_BLOBS];
+    for blob in blobs {
+        commitments.extend(blob.pubdata_commitment());
+    }
+    commitments
+}
+
+/// Converts blobs into a sidecar attached to the L1 transaction.
+pub fn blobs_to_sidecar(blobs: Vec<BlobInfo>) -> EthTxBlobSidecar {
+    EthTxBlobSidecar {
+        blobs: blobs.into_iter().map(BlobInfo::into_sidecar_blob).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use c_kzg::Bytes48;
+
+    use super::*;
+
+    #[test]
+    fn splitting_pubdata_into_blobs() {
+        assert_eq!(blob_count(0), 1);
+        assert_eq!(blob_count(MAX_PUBDATA_PER_BLOB), 1);
+        assert_eq!(blob_count(MAX_PUBDATA_PER_BLOB + 1), 2);
+
+        let pubdata: Vec<u8> = (0..MAX_PUBDATA_PER_BLOB + 100)
+            .map(|i| (i % 251) as u8 + 1)
+            .collect();
+        let blobs = pubdata_to_blobs(&pubdata);
+        assert_eq!(blobs.len(), 2);
+
+        let decoded: Vec<u8> = blobs
+            .iter()
+            .flat_map(|blob| {
+                blob.blob
+                    .chunks_exact(32)
+                    .flat_map(|element| {
+                        assert_eq!(element[0], 0);
+                        element[1..].iter().copied()
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .take(pubdata.len())
+            .collect();
+        assert_eq!(decoded, pubdata);
+
+        let commitments = encode_pubdata_commitments(&blobs);
+        assert_eq!(commitments.len(), 1 + 2 * 144);
+        assert_eq!(commitments[0], PUBDATA_SOURCE_BLOBS);
+    }
+
+    #[test]
+    fn blob_commitments_are_valid() {
+        let blob_info = BlobInfo::new(b"pubdata");
+        assert_eq!(blob_info.versioned_hash().0[0], VERSIONED_HASH_VERSION_KZG);
+
+        let settings = ethereum_kzg_settings();
+        let blob = Blob::from_bytes(blob_info.blob.as_slice()).unwrap();
+        let commitment = Bytes48::from_bytes(&blob_info.commitment).unwrap();
+        let proof = Bytes48::from_bytes(&blob_info.blob_proof).unwrap();
+        assert!(KzgProof::verify_blob_kzg_proof(&blob, &commitment, &proof, settings).unwrap());
+
+        let mut opening_point = [0_u8; 32];
+        opening_point[16..].copy_from_slice(&blob_info.opening_point);
+        let opening_point = Bytes32::from_bytes(&opening_point).unwrap();
+        let opening_value = Bytes32::from_bytes(&blob_info.opening_value).unwrap();
+        let opening_proof = Bytes48::from_bytes(&blob_info.opening_proof).unwrap();
+        assert!(KzgProof::verify_kzg_proof(
+            &commitment,
+            &opening_point,
+            &opening_value,
+            &opening_proof,
+            settings
+        )
+        .unwrap());
+
+        let sidecar = blobs_to_sidecar(vec![blob_info]);
+        assert_eq!(sidecar.blobs[0].blob.len(), BYTES_PER_BLOB);
+        assert_eq!(sidecar.blobs[0].commitment.len(), 48);
+    }
+}
@@ -5,6 +5,8 @@ use zkevm_test_harness::witness::utils::{
 use zksync_types::{LogQuery, H256, U256, USED_BOOTLOADER_MEMORY_BYTES};
 use zksync_utils::expand_memory_contents;
 
+pub mod kzg;
+
 pub fn events_queue_commitment(events_queue: &Vec<LogQuery>, is_pre_boojum: bool) -> Option<H256> {
     (!is_pre_boojum).then(|| H256(events_queue_commitment_fixed(events_queue)))
 }
@@ -1,6 +1,10 @@
-use std::{net::SocketAddr, num::NonZeroU32, time::Duration};
+use std::{
+    net::{IpAddr, SocketAddr},
+    num::{NonZeroU32, NonZeroUsize},
+    time::Duration,
+};
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use zksync_basic_types::H256;
 
 pub use crate::configs::PrometheusConfig;
@@ -20,6 +24,32 @@ pub struct ApiConfig {
     pub merkle_tree: MerkleTreeApiConfig,
 }
 
+/// Action taken by the WebSocket server if a subscription queue overflows because the client doesn't read
+/// notifications fast enough.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriptionBackpressurePolicy {
+    /// Drop the subscription. The client will need to resubscribe.
+    #[default]
+    Disconnect,
+    /// Drop the oldest queued notification to make room for the new one.
+    DropOldest,
+    /// Drop the new notification, keeping the queued ones.
+    DropNewest,
+}
+
+/// Method used to authenticate callers of protected API namespaces.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiAuthMethod {
+    /// The secret is passed as is in the `Authorization: Bearer <secret>` header.
+    #[default]
+    SharedSecret,
+    /// A JWT signed with the secret using HS256 is passed in the `Authorization: Bearer <token>` header.
+    /// The token must have the `exp` claim.
+    Jwt,
+}
+
 #[derive(Debug, Deserialize, Clone, PartialEq)]
 pub struct Web3JsonRpcConfig {
     /// Port to which the HTTP RPC server is listening.
@@ -30,27 +60,86 @@ pub struct Web3JsonRpcConfig {
     pub ws_port: u16,
     /// URL to access WebSocket RPC server.
     pub ws_url: String,
+    /// Port to which the gRPC streaming API server is listening. Required if the `grpc_api` component is run.
+    pub grpc_port: Option<u16>,
+    /// IP address the gRPC streaming API server binds to. Default is `0.0.0.0` (all interfaces).
+    pub grpc_bind_address: Option<IpAddr>,
+    /// Maximum number of concurrently open gRPC streams across all connections. Default is 1,000.
+    pub grpc_max_concurrent_streams: Option<usize>,
     /// Max possible limit of entities to be requested once.
     pub req_entities_limit: Option<u32>,
     /// Max possible limit of filters to be in the state at once.
     pub filters_limit: Option<u32>,
+    /// Whether to store installed filters in Postgres instead of the server memory. This allows filters
+    /// to be used with any API server replica sharing the same database, e.g. behind a load balancer.
+    /// Shared filters are pruned periodically: filters idle for more than 5 minutes are removed,
+    /// and `filters_limit` is enforced by removing the least recently requested filters.
+    #[serde(default)]
+    pub shared_filters: bool,
+    /// Whether to serve server-sent events with new miniblock headers and sync status on `GET /events`
+    /// of the HTTP server.
+    #[serde(default)]
+    pub sse_enabled: bool,
     /// Max possible limit of subscriptions to be in the state at once.
     pub subscriptions_limit: Option<u32>,
+    /// Max number of active subscriptions for a single WebSocket connection. Default is 1,024.
+    pub max_subscriptions_per_connection: Option<u32>,
+    /// Max number of notifications queued for a single subscription. Default is 1,024.
+    pub subscription_queue_capacity: Option<usize>,
+    /// Action taken if a subscription queue overflows. Default is to disconnect the subscription.
+    #[serde(default)]
+    pub subscription_backpressure_policy: SubscriptionBackpressurePolicy,
     /// Interval between polling db for pubsub (in ms).
     pub pubsub_polling_interval: Option<u64>,
-    /// Tx nonce: how far ahead from the committed nonce can it be.
+    /// Tx nonce: how far ahead from the committed nonce can it be. Transactions with a nonce gap are accepted
+    /// and queued in the mempool until the gap is filled.
     pub max_nonce_ahead: u32,
+    /// Minimum increase (in percent) of both max fee per gas and max priority fee per gas required to replace
+    /// a pending transaction with the same nonce. Default is 10.
+    pub replacement_fee_bump_percent: Option<u32>,
+    /// Maximum number of pending transactions sent by a single account. If not set, the number is not limited.
+    pub max_pending_txs_per_account: Option<u32>,
+    /// Maximum number of pending transactions using a single paymaster. If not set, the number is not limited.
+    pub max_pending_txs_per_paymaster: Option<u32>,
+    /// Path to the JSON file with the policy restricting senders, target contracts, function selectors
+    /// and paymasters of submitted transactions. If not set, no policy is applied.
+    pub tx_policy_path: Option<String>,
+    /// Interval between checks of the transaction policy file for updates (in s). Default is 10 s.
+    pub tx_policy_reload_interval: Option<u64>,
     /// The multiplier to use when suggesting gas price. Should be higher than one,
     /// otherwise if the L1 prices soar, the suggested gas price won't be sufficient to be included in block
     pub gas_price_scale_factor: f64,
     /// Timeout for requests (in s)
     pub request_timeout: Option<u64>,
+    /// Timeout for draining in-flight requests on API server shutdown (in s). After receiving a stop signal,
+    /// API servers stop accepting new connections and finish in-flight requests until this timeout elapses.
+    /// Default is 10 s.
+    pub graceful_shutdown_timeout: Option<u64>,
+    /// Max lag (in miniblocks) of the API read replica for it to serve queries for the latest block.
+    /// Only has an effect if the read replica URL is configured. Default is 10.
+    pub read_replica_max_lag: Option<u32>,
     /// Private keys for accounts managed by node
     pub account_pks: Option<Vec<H256>>,
     /// The factor by which to scale the gasLimit
     pub estimate_gas_scale_factor: f64,
     /// The max possible number of gas that `eth_estimateGas` is allowed to overestimate.
     pub estimate_gas_acceptable_overestimation: u32,
+    /// Initial lower bound of the gas limit binary search used by gas estimation. Default is 0.
+    pub estimate_gas_lower_bound: Option<u32>,
+    /// Initial upper bound of the gas limit binary search used by gas estimation. If not set or exceeds
+    /// the max gas limit of an L2 transaction, the latter is used.
+    pub estimate_gas_upper_bound: Option<u32>,
+    /// Maximum number of iterations of the gas limit binary search. If not set, the search continues
+    /// until it reaches `estimate_gas_acceptable_overestimation`.
+    pub estimate_gas_max_iterations: Option<usize>,
+    /// Maximum number of gas estimation results cached in memory. Results are keyed by the latest sealed miniblock
+    /// and the normalized transaction, so they are only reused within a single miniblock. Set to 0 to disable caching.
+    /// Default is 1,024.
+    pub estimate_gas_cache_capacity: Option<usize>,
+    /// Gas limit for read-only calls (`eth_call`, `debug_traceCall` etc.). Independent of the block gas limit
+    /// and the max gas limit of an L2 transaction, so it can be raised to allow expensive read-only calls.
+    /// If not set, the max gas limit of an L2 transaction is used.
+    pub call_gas_limit: Option<u32>,
     ///  Max possible size of an ABI encoded tx (in bytes).
     pub max_tx_size: usize,
     /// Max number of cache misses during one VM execution. If the number of cache misses exceeds this value, the API server panics.
@@ -60,6 +149,14 @@ pub struct Web3JsonRpcConfig {
     /// This option can be tweaked down if the API server is running out of memory.
     /// If not set, the VM concurrency limit will be efficiently disabled.
     pub vm_concurrency_limit: Option<usize>,
+    /// Max number of VM instances to be concurrently spawned for heavy calls (`debug_*` / `trace_*` methods
+    /// and gas estimation). Should be lower than `vm_concurrency_limit`, so that the remaining VM instances
+    /// are reserved for other calls (e.g., `eth_call`). If not set, heavy calls can take up all VM instances.
+    pub heavy_calls_vm_concurrency_limit: Option<usize>,
+    /// Max number of VM instances to be concurrently spawned for a single client (identified by the API key
+    /// or the IP address, same as for rate limiting). Should be lower than `vm_concurrency_limit`, so that a single
+    /// client cannot take up all VM instances. If not set, clients are not limited.
+    pub vm_concurrency_limit_per_client: Option<usize>,
     /// Smart contract cache size in MiBs. The default value is 128 MiB.
     pub factory_deps_cache_size_mb: Option<usize>,
     /// Initial writes cache size in MiBs. The default value is 32 MiB.
@@ -73,12 +170,86 @@ pub struct Web3JsonRpcConfig {
     pub max_batch_request_size: Option<usize>,
     /// Maximum response body size in MiBs. Default is 10 MiB.
     pub max_response_body_size_mb: Option<usize>,
+    /// Maximum cumulative size of responses to calls in a single batch HTTP request in MiBs.
+    /// If not set, batch responses are limited by `max_response_body_size_mb`.
+    pub max_batch_response_size_mb: Option<usize>,
+    /// Max number of calls in a batch HTTP request executed concurrently. If not set, calls in a batch
+    /// are executed one by one.
+    pub batch_request_parallelism: Option<NonZeroUsize>,
+    /// Max number of heavy calls (`debug_*` / `trace_*` methods and gas estimation) executed concurrently
+    /// by each API server. Excess heavy calls are queued, so that a burst of them doesn't affect latency
+    /// of other calls. If not set, heavy calls are executed in the same pool as other calls.
+    pub heavy_calls_concurrency_limit: Option<NonZeroUsize>,
+    /// Max number of light (i.e., non-heavy) calls executed concurrently by each API server. Only has an effect
+    /// if `heavy_calls_concurrency_limit` is set. If not set, light calls are not limited.
+    pub light_calls_concurrency_limit: Option<NonZeroUsize>,
+    /// Whether to compress HTTP responses using an encoding negotiated via the `Accept-Encoding` header
+    /// (e.g., gzip or brotli).
+    #[serde(default)]
+    pub response_compression_enabled: bool,
+    /// Min size of HTTP responses (in bytes) to be compressed. Default is 1,024 bytes.
+    pub response_compression_min_size: Option<u16>,
     /// Maximum number of requests per minute for the WebSocket server.
     /// The value is per active connection.
-    /// Note: For HTTP, rate limiting is expected to be configured on the infra level.
+    /// Note: For HTTP, use per-IP / per-API-key limits below or configure rate limiting on the infra level.
     pub websocket_requests_per_minute_limit: Option<NonZeroU32>,
+    /// Max number of JSON-RPC calls per minute from a single client IP address. Calls in a batch are counted
+    /// separately. If not set, calls are not limited per IP address.
+    pub rate_limit_requests_per_minute_per_ip: Option<NonZeroU32>,
+    /// Max number of compute units per minute spent by a single client IP address. Each call costs from 1
+    /// (simple getters) to 20 (`debug_*` and `trace_*` methods) compute units. If not set, compute units
+    /// are not limited per IP address.
+    pub rate_limit_compute_units_per_minute_per_ip: Option<NonZeroU32>,
+    /// Max number of JSON-RPC calls per minute for a single API key.
+    pub rate_limit_requests_per_minute_per_api_key: Option<NonZeroU32>,
+    /// Max number of compute units per minute spent by a single API key.
+    pub rate_limit_compute_units_per_minute_per_api_key: Option<NonZeroU32>,
+    /// API keys recognized by the rate limiter. Requests with a recognized key in the `X-API-Key` header
+    /// are subject to per-API-key limits instead of per-IP ones.
+    pub rate_limit_api_keys: Option<Vec<String>>,
+    /// Number of trusted reverse proxies in front of the server. Clients are identified by the IP address appended
+    /// to the `X-Forwarded-For` header by the outermost trusted proxy; entries added before it are controlled
+    /// by clients and are ignored. If not set or 0, clients are identified by the peer address of the connection.
+    pub rate_limit_trusted_proxy_hops: Option<usize>,
+    /// API namespaces (e.g., `debug`) whose methods require authentication on the HTTP server. Since WebSocket
+    /// calls cannot be authenticated, methods of these namespaces are not served by the WebSocket server.
+    /// The `admin` namespace is only served if it is listed here.
+    pub authenticated_namespaces: Option<Vec<String>>,
+    /// Method used to authenticate callers of `authenticated_namespaces`.
+    #[serde(default)]
+    pub auth_method: ApiAuthMethod,
+    /// Secret used to authenticate callers of `authenticated_namespaces`. Required if these namespaces are set.
+    pub auth_secret: Option<String>,
+    /// Methods rejected by the API servers even if their namespace is enabled. Entries are either method names
+    /// (e.g., `debug_traceBlockByNumber`) or namespace wildcards (e.g., `debug_*`).
+    pub denied_methods: Option<Vec<String>>,
+    /// Methods served even if they match `denied_methods` (e.g., `debug_traceTransaction` with `debug_*` denied).
+    /// Entries must be method names.
+    pub allowed_methods: Option<Vec<String>>,
     /// Tree API url, currently used to proxy `getProof` calls to the tree
     pub tree_api_url: Option<String>,
+    /// Maximum number of L1 batches the Merkle tree may lag behind the state keeper for proof methods
+    /// (`eth_getProof`, `zks_getProof`) to be served. If the lag is greater, these methods return an error
+    /// instead of proofs for a stale state. If not set, proofs are served regardless of the tree lag.
+    pub max_tree_lag_for_proofs: Option<u32>,
+    /// Whether `debug_traceBlock*` methods should re-execute the miniblock in the sandbox if call traces
+    /// for it are missing in the DB (e.g., for miniblocks sealed before trace storage was enabled, or pruned ones).
+    #[serde(default)]
+    pub trace_block_reexecution_enabled: bool,
+    /// Weight of the latest sample in the exponential moving average used to smooth `eth_maxPriorityFeePerGas`
+    /// suggestions. Must be in `(0, 1]`; 1 disables smoothing. Default is 0.25.
+    pub max_priority_fee_smoothing_factor: Option<f64>,
+    /// Maximum number of `eth_call` results for finalized blocks cached in memory. Set to 0 to disable caching.
+    /// Default is 1,024.
+    pub eth_call_cache_capacity: Option<usize>,
+    /// Maximum number of miniblocks in the range queried by `eth_getLogs`. If not set, the range is not limited.
+    pub get_logs_block_range_limit: Option<u64>,
+    /// Maximum number of logs returned by `eth_getLogs` and log filters. If not set, `req_entities_limit` is used.
+    pub get_logs_max_results: Option<usize>,
+    /// Maximum number of calls in a single `debug_traceCallMany` request. Default is 50.
+    pub trace_call_many_max_calls: Option<usize>,
+    /// Maximum number of miniblocks in the range queried by `trace_filter`. Default is 10,000.
+    pub trace_filter_block_range_limit: Option<u64>,
 }
 
 impl Web3JsonRpcConfig {
@@ -91,27 +262,74 @@ impl Web3JsonRpcConfig {
             http_url: "http://localhost:3050".into(),
             ws_port: 3051,
             ws_url: "ws://localhost:3051".into(),
+            grpc_port: None,
+            grpc_bind_address: None,
+            grpc_max_concurrent_streams: None,
             req_entities_limit: Some(10000),
             filters_limit: Some(10000),
+            shared_filters: false,
+            sse_enabled: false,
             subscriptions_limit: Some(10000),
+            max_subscriptions_per_connection: Default::default(),
+            subscription_queue_capacity: Default::default(),
+            subscription_backpressure_policy: SubscriptionBackpressurePolicy::default(),
             pubsub_polling_interval: Some(200),
             max_nonce_ahead: 50,
+            replacement_fee_bump_percent: None,
+            max_pending_txs_per_account: None,
+            max_pending_txs_per_paymaster: None,
+            tx_policy_path: None,
+            tx_policy_reload_interval: None,
             gas_price_scale_factor: 1.2,
             request_timeout: Default::default(),
+            graceful_shutdown_timeout: None,
+            read_replica_max_lag: None,
             account_pks: Default::default(),
             estimate_gas_scale_factor: 1.2,
             estimate_gas_acceptable_overestimation: 1000,
+            estimate_gas_lower_bound: None,
+            estimate_gas_upper_bound: None,
+            estimate_gas_max_iterations: None,
+            estimate_gas_cache_capacity: None,
+            call_gas_limit: None,
             max_tx_size: 1000000,
             vm_execution_cache_misses_limit: Default::default(),
             vm_concurrency_limit: Default::default(),
+            heavy_calls_vm_concurrency_limit: None,
+            vm_concurrency_limit_per_client: None,
             factory_deps_cache_size_mb: Default::default(),
             initial_writes_cache_size_mb: Default::default(),
             latest_values_cache_size_mb: Default::default(),
             fee_history_limit: Default::default(),
             max_batch_request_size: Default::default(),
             max_response_body_size_mb: Default::default(),
+            max_batch_response_size_mb: None,
+            batch_request_parallelism: None,
+            heavy_calls_concurrency_limit: None,
+            light_calls_concurrency_limit: None,
+            response_compression_enabled: false,
+            response_compression_min_size: None,
             websocket_requests_per_minute_limit: Default::default(),
+            rate_limit_requests_per_minute_per_ip: None,
+            rate_limit_compute_units_per_minute_per_ip: None,
+            rate_limit_requests_per_minute_per_api_key: None,
+            rate_limit_compute_units_per_minute_per_api_key: None,
+            rate_limit_api_keys: None,
+            rate_limit_trusted_proxy_hops: None,
+            authenticated_namespaces: None,
+            auth_method: ApiAuthMethod::default(),
+            auth_secret: None,
+            denied_methods: None,
+            allowed_methods: None,
             tree_api_url: None,
+            max_tree_lag_for_proofs: None,
+            trace_block_reexecution_enabled: false,
+            max_priority_fee_smoothing_factor: Default::default(),
+            eth_call_cache_capacity: Default::default(),
+            get_logs_block_range_limit: None,
+            get_logs_max_results: None,
+            trace_call_many_max_calls: None,
+            trace_filter_block_range_limit: None,
         }
     }
 
@@ -123,6 +341,18 @@ impl Web3JsonRpcConfig {
         SocketAddr::new("0.0.0.0".parse().unwrap(), self.ws_port)
     }
 
+    /// Returns the bind address for the gRPC streaming API server, or `None` if the gRPC port is not configured.
+    pub fn grpc_bind_addr(&self) -> Option<SocketAddr> {
+        let ip = self
+            .grpc_bind_address
+            .unwrap_or_else(|| "0.0.0.0".parse().unwrap());
+        Some(SocketAddr::new(ip, self.grpc_port?))
+    }
+
+    pub fn grpc_max_concurrent_streams(&self) -> usize {
+        self.grpc_max_concurrent_streams.unwrap_or(1_000)
+    }
+
     pub fn req_entities_limit(&self) -> usize {
         self.req_entities_limit.unwrap_or_else(|| 2u32.pow(10)) as usize
     }
@@ -135,6 +365,14 @@ impl Web3JsonRpcConfig {
         self.subscriptions_limit.unwrap_or(10000) as usize
     }
 
+    pub fn max_subscriptions_per_connection(&self) -> u32 {
+        self.max_subscriptions_per_connection.unwrap_or(1_024)
+    }
+
+    pub fn subscription_queue_capacity(&self) -> usize {
+        self.subscription_queue_capacity.unwrap_or(1_024)
+    }
+
     pub fn pubsub_interval(&self) -> Duration {
         Duration::from_millis(self.pubsub_polling_interval.unwrap_or(200))
     }
@@ -143,6 +381,22 @@ impl Web3JsonRpcConfig {
         Duration::from_secs(self.request_timeout.unwrap_or(10))
     }
 
+    pub fn graceful_shutdown_timeout(&self) -> Duration {
+        Duration::from_secs(self.graceful_shutdown_timeout.unwrap_or(10))
+    }
+
+    pub fn replacement_fee_bump_percent(&self) -> u32 {
+        self.replacement_fee_bump_percent.unwrap_or(10)
+    }
+
+    pub fn tx_policy_reload_interval(&self) -> Duration {
+        Duration::from_secs(self.tx_policy_reload_interval.unwrap_or(10))
+    }
+
+    pub fn read_replica_max_lag(&self) -> u32 {
+        self.read_replica_max_lag.unwrap_or(10)
+    }
+
     pub fn account_pks(&self) -> Vec<H256> {
         self.account_pks.clone().unwrap_or_default()
     }
@@ -154,6 +408,18 @@ impl Web3JsonRpcConfig {
         self.vm_concurrency_limit.unwrap_or(2_048)
     }
 
+    /// Returns the max number of VM instances for heavy calls, capped by [`Self::vm_concurrency_limit()`].
+    pub fn heavy_calls_vm_concurrency_limit(&self) -> Option<usize> {
+        self.heavy_calls_vm_concurrency_limit
+            .map(|limit| limit.min(self.vm_concurrency_limit()))
+    }
+
+    /// Returns the max number of VM instances for a single client, capped by [`Self::vm_concurrency_limit()`].
+    pub fn vm_concurrency_limit_per_client(&self) -> Option<usize> {
+        self.vm_concurrency_limit_per_client
+            .map(|limit| limit.min(self.vm_concurrency_limit()))
+    }
+
     /// Returns the size of factory dependencies cache in bytes.
     pub fn factory_deps_cache_size(&self) -> usize {
         self.factory_deps_cache_size_mb.unwrap_or(128) * super::BYTES_IN_MEGABYTE
@@ -173,6 +439,35 @@ impl Web3JsonRpcConfig {
         self.fee_history_limit.unwrap_or(1024)
     }
 
+    pub fn max_priority_fee_smoothing_factor(&self) -> f64 {
+        self.max_priority_fee_smoothing_factor.unwrap_or(0.25)
+    }
+
+    pub fn eth_call_cache_capacity(&self) -> usize {
+        self.eth_call_cache_capacity.unwrap_or(1_024)
+    }
+
+    pub fn estimate_gas_lower_bound(&self) -> u32 {
+        self.estimate_gas_lower_bound.unwrap_or(0)
+    }
+
+    pub fn estimate_gas_cache_capacity(&self) -> usize {
+        self.estimate_gas_cache_capacity.unwrap_or(1_024)
+    }
+
+    pub fn get_logs_max_results(&self) -> usize {
+        self.get_logs_max_results
+            .unwrap_or_else(|| self.req_entities_limit())
+    }
+
+    pub fn trace_call_many_max_calls(&self) -> usize {
+        self.trace_call_many_max_calls.unwrap_or(50)
+    }
+
+    pub fn trace_filter_block_range_limit(&self) -> u64 {
+        self.trace_filter_block_range_limit.unwrap_or(10_000)
+    }
+
     pub fn max_batch_request_size(&self) -> usize {
         // The default limit is chosen to be reasonably permissive.
         self.max_batch_request_size.unwrap_or(500)
@@ -182,6 +477,17 @@ impl Web3JsonRpcConfig {
         self.max_response_body_size_mb.unwrap_or(10) * super::BYTES_IN_MEGABYTE
     }
 
+    pub fn max_batch_response_size(&self) -> usize {
+        self.max_batch_response_size_mb.map_or_else(
+            || self.max_response_body_size(),
+            |size| size * super::BYTES_IN_MEGABYTE,
+        )
+    }
+
+    pub fn response_compression_min_size(&self) -> u16 {
+        self.response_compression_min_size.unwrap_or(1_024)
+    }
+
     pub fn websocket_requests_per_minute_limit(&self) -> NonZeroU32 {
         // The default limit is chosen to be reasonably permissive.
         self.websocket_requests_per_minute_limit
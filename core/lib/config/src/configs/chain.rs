@@ -1,7 +1,7 @@
 use std::{str::FromStr, time::Duration};
 
 use serde::Deserialize;
-use zksync_basic_types::{network::Network, Address, L2ChainId};
+use zksync_basic_types::{network::Network, Address, L1BatchCommitmentMode, L2ChainId};
 
 #[derive(Debug, Deserialize, Clone, PartialEq)]
 pub struct NetworkConfig {
@@ -39,6 +39,29 @@ pub struct StateKeeperConfig {
     /// sealing will block until some of the miniblocks from the queue are processed.
     /// 0 means that sealing is synchronous; this is mostly useful for performance comparison, testing etc.
     pub miniblock_seal_queue_capacity: usize,
+    /// If set, miniblocks are produced on a fixed timer with this interval (in seconds) rather than sealed
+    /// by the timeout sealer. Miniblock timestamps are deterministic in this mode: the timestamp of each miniblock
+    /// is the timestamp of the previous miniblock plus a multiple of the interval.
+    pub fixed_miniblock_interval_sec: Option<u64>,
+    /// Whether to produce empty miniblocks in the fixed-interval mode. If set, L1 batches are opened and
+    /// miniblocks are sealed on the timer even if there are no transactions in the mempool.
+    #[serde(default)]
+    pub fixed_interval_empty_miniblocks: bool,
+    /// If set, empty miniblocks are produced when the mempool is idle, so that miniblock timestamps keep progressing.
+    /// An empty miniblock is sealed once it has been open for this interval (in seconds); the interval doubles
+    /// for each consecutive empty miniblock up to `max_idle_gap_sec`. Ignored in the fixed-interval mode.
+    pub empty_miniblock_interval_sec: Option<u64>,
+    /// Maximum gap (in seconds) between consecutive empty miniblocks. If not set, empty miniblocks are produced
+    /// with the constant `empty_miniblock_interval_sec` interval.
+    pub max_idle_gap_sec: Option<u64>,
+
+    /// Max number of priority (L1) transactions in a miniblock after which L2 transactions are preferred
+    /// over priority ones until the miniblock is sealed. Priority transactions are still executed
+    /// if there are no pending L2 transactions.
+    pub max_priority_txs_per_miniblock: Option<usize>,
+    /// Max total gas limit of priority (L1) transactions in a miniblock; has the same semantics as
+    /// `max_priority_txs_per_miniblock`.
+    pub max_priority_gas_per_miniblock: Option<u64>,
 
     /// The max number of gas to spend on an L1 tx before its batch should be sealed by the gas sealer.
     pub max_single_tx_gas: u32,
@@ -63,8 +86,28 @@ pub struct StateKeeperConfig {
 
     pub fee_account_addr: Address,
 
-    /// The price the operator spends on 1 gas of computation in wei.
+    /// The price the operator spends on 1 gas of computation in wei (or in the smallest units of the base token
+    /// if the chain uses an ERC-20 token to pay fees).
     pub fair_l2_gas_price: u64,
+    /// If set, the fair L2 gas price is congestion-based: similarly to the EIP-1559 base fee, it is adjusted
+    /// after each miniblock depending on the miniblock gas usage, staying between `fair_l2_gas_price` and this value.
+    pub max_fair_l2_gas_price: Option<u64>,
+    /// Miniblock gas usage at which the congestion-based L2 gas price stays the same. Defaults to 80M.
+    pub target_miniblock_gas: Option<u64>,
+    /// Inverse of the max relative change of the congestion-based L2 gas price per miniblock. Defaults to 8
+    /// (i.e., the price changes by at most 12.5% per miniblock), as in EIP-1559.
+    pub l2_gas_price_change_denominator: Option<u64>,
+    /// Conversion ratio from ETH to the base token of the chain used to convert L1 gas and pubdata prices:
+    /// 1 wei is worth `base_token_conversion_numerator / base_token_conversion_denominator` base token units.
+    /// Both default to 1, which is correct for chains paying fees in ETH.
+    pub base_token_conversion_numerator: Option<u64>,
+    pub base_token_conversion_denominator: Option<u64>,
+
+    /// Whether L1 batch pubdata is published on L1 (`Rollup`) or not (`Validium`). In the validium mode,
+    /// L1 batches are committed without pubdata, and transactions are not charged for pubdata.
+    /// This setting must be the same for all components of the node.
+    #[serde(default)]
+    pub l1_batch_commit_data_generator_mode: L1BatchCommitmentMode,
 
     /// Max number of computational gas that validation step is allowed to take.
     pub validation_computational_gas_limit: u32,
@@ -77,6 +120,26 @@ pub struct StateKeeperConfig {
     /// NOTE: This will slow down StateKeeper, to be used in non-production environments!
     pub upload_witness_inputs_to_gcs: bool,
 
+    /// If set, storage values for up to this number of upcoming transactions are prefetched in the background
+    /// (one worker thread per transaction) by executing transactions speculatively. Transactions are still
+    /// applied to the batch sequentially, and never wait for prefetching.
+    pub storage_prefetch_workers: Option<usize>,
+    /// If set, each miniblock is additionally re-executed on the latest VM version in a separate thread, and
+    /// divergences from the main VM (in execution results, gas, events and storage writes) are reported.
+    /// Shadow execution uses base system contracts of the latest VM version and does not influence the produced state
+    /// or block L1 batch sealing; if the shadow VM lags behind by more than one L1 batch, some batches are skipped.
+    #[serde(default)]
+    pub shadow_vm_execution: bool,
+
+    /// If set, L1 batches are sealed earlier when the proving pipeline is saturated. Batches are sealed
+    /// at the estimated number of circuits linearly decreasing with the length of the proving queue (i.e.,
+    /// the number of L1 batches waiting for proofs), down to `min_batch_circuits_fraction` of the static limit
+    /// once the queue reaches this length.
+    pub saturated_proving_queue_len: Option<usize>,
+    /// Minimum fraction of the static circuits limit that L1 batches are sealed at if the proving pipeline
+    /// is saturated. Defaults to 0.25.
+    pub min_batch_circuits_fraction: Option<f64>,
+
     /// Number of keys that is processed by enum_index migration in State Keeper each L1 batch.
     pub enum_index_migration_chunk_size: Option<usize>,
 }
@@ -90,6 +153,12 @@ impl StateKeeperConfig {
             block_commit_deadline_ms: 2500,
             miniblock_commit_deadline_ms: 1000,
             miniblock_seal_queue_capacity: 10,
+            fixed_miniblock_interval_sec: None,
+            fixed_interval_empty_miniblocks: false,
+            empty_miniblock_interval_sec: None,
+            max_idle_gap_sec: None,
+            max_priority_txs_per_miniblock: None,
+            max_priority_gas_per_miniblock: None,
             max_single_tx_gas: 6000000,
             max_allowed_l2_tx_gas_limit: 4000000000,
             reject_tx_at_geometry_percentage: 0.95,
@@ -101,15 +170,49 @@ impl StateKeeperConfig {
             fee_account_addr: Address::from_str("0xde03a0B5963f75f1C8485B355fF6D30f3093BDE7")
                 .unwrap(),
             fair_l2_gas_price: 250000000,
+            max_fair_l2_gas_price: None,
+            target_miniblock_gas: None,
+            l2_gas_price_change_denominator: None,
+            base_token_conversion_numerator: None,
+            base_token_conversion_denominator: None,
+            l1_batch_commit_data_generator_mode: L1BatchCommitmentMode::Rollup,
             validation_computational_gas_limit: 300000,
             save_call_traces: true,
             virtual_blocks_interval: 1,
             virtual_blocks_per_miniblock: 1,
             upload_witness_inputs_to_gcs: false,
+            storage_prefetch_workers: None,
+            shadow_vm_execution: false,
+            saturated_proving_queue_len: None,
+            min_batch_circuits_fraction: None,
             enum_index_migration_chunk_size: None,
         }
     }
 
+    pub fn fixed_miniblock_interval(&self) -> Option<Duration> {
+        self.fixed_miniblock_interval_sec.map(Duration::from_secs)
+    }
+
+    pub fn empty_miniblock_interval(&self) -> Option<Duration> {
+        self.empty_miniblock_interval_sec.map(Duration::from_secs)
+    }
+
+    pub fn max_idle_gap(&self) -> Option<Duration> {
+        self.max_idle_gap_sec.map(Duration::from_secs)
+    }
+
+    pub fn target_miniblock_gas(&self) -> u64 {
+        self.target_miniblock_gas.unwrap_or(80_000_000)
+    }
+
+    pub fn l2_gas_price_change_denominator(&self) -> u64 {
+        self.l2_gas_price_change_denominator.unwrap_or(8)
+    }
+
+    pub fn min_batch_circuits_fraction(&self) -> f64 {
+        self.min_batch_circuits_fraction.unwrap_or(0.25)
+    }
+
     pub fn enum_index_migration_chunk_size(&self) -> usize {
         self.enum_index_migration_chunk_size.unwrap_or(1_000)
     }
@@ -153,6 +256,12 @@ pub struct MempoolConfig {
     pub stuck_tx_timeout: u64,
     pub remove_stuck_txs: bool,
     pub delay_interval: u64,
+    /// Time-to-live for pending L2 transactions (in s). Transactions not included into a miniblock
+    /// within this time are dropped from the mempool. If not set, transactions never expire.
+    pub tx_ttl: Option<u64>,
+    /// Maximum number of pending L2 transactions stored in the mempool. If exceeded, transactions
+    /// with the lowest max fee per gas are dropped. If not set, the number is not limited.
+    pub max_pending_txs: Option<usize>,
 }
 
 impl MempoolConfig {
@@ -164,6 +273,10 @@ impl MempoolConfig {
         Duration::from_secs(self.stuck_tx_timeout)
     }
 
+    pub fn tx_ttl(&self) -> Option<Duration> {
+        self.tx_ttl.map(Duration::from_secs)
+    }
+
     pub fn delay_interval(&self) -> Duration {
         Duration::from_millis(self.delay_interval)
     }
@@ -30,6 +30,8 @@ pub struct ContractsConfig {
     pub l2_weth_bridge_addr: Option<Address>,
     pub l1_allow_list_addr: Address,
     pub l2_testnet_paymaster_addr: Option<Address>,
+    /// L1 address of the ERC-20 token used to pay fees on the chain. If not set, fees are paid in ETH.
+    pub base_token_addr: Option<Address>,
     pub recursion_scheduler_level_vk_hash: H256,
     pub recursion_node_level_vk_hash: H256,
     pub recursion_leaf_level_vk_hash: H256,
@@ -66,6 +68,7 @@ impl ContractsConfig {
             l2_weth_bridge_addr: Some(Address::repeat_byte(0x0f)),
             l1_allow_list_addr: Address::repeat_byte(0x10),
             l2_testnet_paymaster_addr: Some(Address::repeat_byte(0x11)),
+            base_token_addr: None,
             recursion_scheduler_level_vk_hash: H256::repeat_byte(0x02),
             recursion_node_level_vk_hash: H256::repeat_byte(0x03),
             recursion_leaf_level_vk_hash: H256::repeat_byte(0x04),
@@ -0,0 +1,46 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+
+/// Configuration for the data availability dispatcher, which posts pubdata of L1 batches to an external
+/// data availability (DA) layer.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct DADispatcherConfig {
+    /// How often the dispatcher polls the database and the DA layer. Value in milliseconds.
+    pub polling_interval_ms: Option<u32>,
+    /// Maximum number of L1 batches dispatched to the DA layer in a single iteration.
+    pub max_rows_to_dispatch: Option<u32>,
+    /// Maximum number of retries for a failed call to the DA layer.
+    pub max_retries: Option<u16>,
+}
+
+impl DADispatcherConfig {
+    const DEFAULT_POLLING_INTERVAL_MS: u32 = 5_000;
+    const DEFAULT_MAX_ROWS_TO_DISPATCH: u32 = 100;
+    const DEFAULT_MAX_RETRIES: u16 = 5;
+
+    pub fn for_tests() -> Self {
+        Self {
+            polling_interval_ms: Some(100),
+            max_rows_to_dispatch: Some(10),
+            max_retries: Some(2),
+        }
+    }
+
+    pub fn polling_interval(&self) -> Duration {
+        Duration::from_millis(
+            self.polling_interval_ms
+                .unwrap_or(Self::DEFAULT_POLLING_INTERVAL_MS)
+                .into(),
+        )
+    }
+
+    pub fn max_rows_to_dispatch(&self) -> u32 {
+        self.max_rows_to_dispatch
+            .unwrap_or(Self::DEFAULT_MAX_ROWS_TO_DISPATCH)
+    }
+
+    pub fn max_retries(&self) -> u16 {
+        self.max_retries.unwrap_or(Self::DEFAULT_MAX_RETRIES)
+    }
+}
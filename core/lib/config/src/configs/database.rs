@@ -18,6 +18,29 @@ pub enum MerkleTreeMode {
     Lightweight,
 }
 
+/// Storage backend for the Merkle tree.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MerkleTreeBackend {
+    /// Tree data is persisted in RocksDB on disk.
+    #[default]
+    Rocksdb,
+    /// Tree data is kept in memory and is lost on node restart, after which the tree is rebuilt from Postgres.
+    /// Intended for tests and ephemeral dev nodes; should not be used in production.
+    InMemory,
+}
+
+/// Compaction style for a RocksDB instance.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RocksdbCompactionStyle {
+    /// Leveled compaction. Provides lower space amplification and is generally recommended for large DBs.
+    #[default]
+    Level,
+    /// Universal (tiered) compaction. Lowers write amplification at the cost of space amplification.
+    Universal,
+}
+
 #[derive(Debug, Clone, PartialEq, Deserialize)]
 pub struct MerkleTreeConfig {
     /// Path to the RocksDB data directory for Merkle tree.
@@ -26,6 +49,9 @@ pub struct MerkleTreeConfig {
     /// Operation mode for the Merkle tree. If not specified, the full mode will be used.
     #[serde(default)]
     pub mode: MerkleTreeMode,
+    /// Storage backend for the Merkle tree. If not specified, the tree is persisted in RocksDB on disk.
+    #[serde(default)]
+    pub backend: MerkleTreeBackend,
     /// Chunk size for multi-get operations. Can speed up loading data for the Merkle tree on some environments,
     /// but the effects vary wildly depending on the setup (e.g., the filesystem used).
     #[serde(default = "MerkleTreeConfig::default_multi_get_chunk_size")]
@@ -44,6 +70,47 @@ pub struct MerkleTreeConfig {
     /// Maximum number of L1 batches to be processed by the Merkle tree at a time.
     #[serde(default = "MerkleTreeConfig::default_max_l1_batches_per_iter")]
     pub max_l1_batches_per_iter: usize,
+    /// Compaction style for the Merkle tree RocksDB.
+    #[serde(default)]
+    pub compaction_style: RocksdbCompactionStyle,
+    /// Size of a single memtable in the Merkle tree RocksDB. If not specified, the size is derived
+    /// from `memtable_capacity_mb`.
+    #[serde(default)]
+    pub write_buffer_size_mb: Option<usize>,
+    /// Maximum number of memtables per column family in the Merkle tree RocksDB. If not specified,
+    /// the number is derived from `memtable_capacity_mb`.
+    #[serde(default)]
+    pub max_write_buffer_number: Option<usize>,
+    /// Maximum number of concurrent background jobs (compactions and flushes) in the Merkle tree RocksDB.
+    /// If not specified, the number is derived from the number of CPU cores.
+    #[serde(default)]
+    pub max_background_jobs: Option<usize>,
+    /// Number of threads in a dedicated thread pool used to compute hashes and Merkle proofs when updating
+    /// the tree. Hashing is parallelized across subtrees, so more threads reduce the tree lag on high-throughput
+    /// chains. If not specified, the global thread pool sized by the number of CPU cores is used.
+    #[serde(default)]
+    pub thread_pool_size: Option<usize>,
+    /// Whether to prune old Merkle tree versions. Versions are only pruned for L1 batches executed on L1
+    /// and older than `pruning_retained_l1_batches`.
+    #[serde(default)]
+    pub pruning_enabled: bool,
+    /// Number of latest L1 batches for which Merkle tree versions are retained if pruning is enabled
+    /// (e.g., to serve storage proofs for them).
+    #[serde(default = "MerkleTreeConfig::default_pruning_retained_l1_batches")]
+    pub pruning_retained_l1_batches: u32,
+    /// Interval between checks for Merkle tree versions that can be pruned.
+    #[serde(default = "MerkleTreeConfig::default_pruning_poll_interval_ms")]
+    pub pruning_poll_interval_ms: u64,
+    /// Whether to periodically verify the Merkle tree against Postgres. The verifier checks root hashes
+    /// of newly processed L1 batches and re-derives a random sample of tree leaves from storage logs.
+    #[serde(default)]
+    pub consistency_check_enabled: bool,
+    /// Number of tree leaves checked against Postgres on each consistency check iteration.
+    #[serde(default = "MerkleTreeConfig::default_consistency_check_sample_size")]
+    pub consistency_check_sample_size: usize,
+    /// Interval between consistency check iterations.
+    #[serde(default = "MerkleTreeConfig::default_consistency_check_interval_ms")]
+    pub consistency_check_interval_ms: u64,
 }
 
 impl Default for MerkleTreeConfig {
@@ -51,11 +118,23 @@ impl Default for MerkleTreeConfig {
         Self {
             path: Self::default_path(),
             mode: MerkleTreeMode::default(),
+            backend: MerkleTreeBackend::default(),
             multi_get_chunk_size: Self::default_multi_get_chunk_size(),
             block_cache_size_mb: Self::default_block_cache_size_mb(),
             memtable_capacity_mb: Self::default_memtable_capacity_mb(),
             stalled_writes_timeout_sec: Self::default_stalled_writes_timeout_sec(),
             max_l1_batches_per_iter: Self::default_max_l1_batches_per_iter(),
+            compaction_style: RocksdbCompactionStyle::default(),
+            write_buffer_size_mb: None,
+            max_write_buffer_number: None,
+            max_background_jobs: None,
+            thread_pool_size: None,
+            pruning_enabled: false,
+            pruning_retained_l1_batches: Self::default_pruning_retained_l1_batches(),
+            pruning_poll_interval_ms: Self::default_pruning_poll_interval_ms(),
+            consistency_check_enabled: false,
+            consistency_check_sample_size: Self::default_consistency_check_sample_size(),
+            consistency_check_interval_ms: Self::default_consistency_check_interval_ms(),
         }
     }
 }
@@ -85,6 +164,22 @@ impl MerkleTreeConfig {
         20
     }
 
+    const fn default_pruning_retained_l1_batches() -> u32 {
+        1_000
+    }
+
+    const fn default_pruning_poll_interval_ms() -> u64 {
+        60_000
+    }
+
+    const fn default_consistency_check_sample_size() -> usize {
+        1_000
+    }
+
+    const fn default_consistency_check_interval_ms() -> u64 {
+        60_000
+    }
+
     /// Returns the size of block cache size for Merkle tree in bytes.
     pub fn block_cache_size(&self) -> usize {
         self.block_cache_size_mb * super::BYTES_IN_MEGABYTE
@@ -99,6 +194,36 @@ impl MerkleTreeConfig {
     pub fn stalled_writes_timeout(&self) -> Duration {
         Duration::from_secs(self.stalled_writes_timeout_sec)
     }
+
+    /// Returns the size of a single memtable in bytes, if specified.
+    pub fn write_buffer_size(&self) -> Option<usize> {
+        self.write_buffer_size_mb
+            .map(|size| size * super::BYTES_IN_MEGABYTE)
+    }
+
+    /// Returns the number of latest L1 batches for which Merkle tree versions are retained, or `None`
+    /// if pruning is disabled.
+    pub fn pruning_retained_l1_batches(&self) -> Option<u32> {
+        self.pruning_enabled
+            .then_some(self.pruning_retained_l1_batches)
+    }
+
+    /// Returns the interval between checks for Merkle tree versions that can be pruned.
+    pub fn pruning_poll_interval(&self) -> Duration {
+        Duration::from_millis(self.pruning_poll_interval_ms)
+    }
+
+    /// Returns the number of tree leaves checked on each consistency check iteration, or `None`
+    /// if consistency checks are disabled.
+    pub fn consistency_check_sample_size(&self) -> Option<usize> {
+        self.consistency_check_enabled
+            .then_some(self.consistency_check_sample_size)
+    }
+
+    /// Returns the interval between consistency check iterations.
+    pub fn consistency_check_interval(&self) -> Duration {
+        Duration::from_millis(self.consistency_check_interval_ms)
+    }
 }
 
 /// Database configuration.
@@ -107,6 +232,24 @@ pub struct DBConfig {
     /// Path to the RocksDB data directory that serves state cache.
     #[serde(default = "DBConfig::default_state_keeper_db_path")]
     pub state_keeper_db_path: String,
+    /// Capacity of the block cache for the state keeper RocksDB. If not specified, default RocksDB cache options
+    /// are used.
+    #[serde(default)]
+    pub state_keeper_db_block_cache_size_mb: Option<usize>,
+    /// Compaction style for the state keeper RocksDB.
+    #[serde(default)]
+    pub state_keeper_db_compaction_style: RocksdbCompactionStyle,
+    /// Size of a single memtable in the state keeper RocksDB. If not specified, the RocksDB default is used.
+    #[serde(default)]
+    pub state_keeper_db_write_buffer_size_mb: Option<usize>,
+    /// Maximum number of memtables per column family in the state keeper RocksDB. If not specified,
+    /// the RocksDB default is used.
+    #[serde(default)]
+    pub state_keeper_db_max_write_buffer_number: Option<usize>,
+    /// Maximum number of concurrent background jobs (compactions and flushes) in the state keeper RocksDB.
+    /// If not specified, the number is derived from the number of CPU cores.
+    #[serde(default)]
+    pub state_keeper_db_max_background_jobs: Option<usize>,
     /// Merkle tree configuration.
     #[serde(skip)]
     // ^ Filled in separately in `Self::from_env()`. We cannot use `serde(flatten)` because it
@@ -118,6 +261,18 @@ impl DBConfig {
     fn default_state_keeper_db_path() -> String {
         "./db/state_keeper".to_owned()
     }
+
+    /// Returns the size of block cache for the state keeper RocksDB in bytes, if specified.
+    pub fn state_keeper_db_block_cache_size(&self) -> Option<usize> {
+        self.state_keeper_db_block_cache_size_mb
+            .map(|size| size * super::BYTES_IN_MEGABYTE)
+    }
+
+    /// Returns the size of a single memtable for the state keeper RocksDB in bytes, if specified.
+    pub fn state_keeper_db_write_buffer_size(&self) -> Option<usize> {
+        self.state_keeper_db_write_buffer_size_mb
+            .map(|size| size * super::BYTES_IN_MEGABYTE)
+    }
 }
 
 /// Collection of different database URLs and general PostgreSQL options.
@@ -129,6 +284,9 @@ pub struct PostgresConfig {
     pub master_url: Option<String>,
     /// URL for the replica database.
     pub replica_url: Option<String>,
+    /// URL for the read replica of the replica database. If set, API servers route read-only queries
+    /// (blocks, receipts, logs and traces) to this database if it's not lagging too much.
+    pub api_read_replica_url: Option<String>,
     /// URL for the prover database.
     pub prover_url: Option<String>,
     /// Maximum size of the connection pool.
@@ -0,0 +1,57 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+
+/// Configuration for the Postgres data pruner, which removes historical data (events, transactions,
+/// overwritten storage logs etc.) for old L1 batches executed on L1.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct DbPrunerConfig {
+    /// Number of latest L1 batches executed on L1 for which data is retained.
+    pub data_retention_l1_batches: Option<u32>,
+    /// Minimum age of L1 batches (counting from their timestamp) for their data to be pruned, in seconds.
+    pub data_retention_sec: Option<u64>,
+    /// Maximum number of L1 batches pruned in a single database transaction.
+    pub pruned_batch_chunk_size: Option<u32>,
+    /// How often the pruner checks for new L1 batches to prune. Value in milliseconds.
+    pub polling_interval_ms: Option<u64>,
+}
+
+impl DbPrunerConfig {
+    const DEFAULT_DATA_RETENTION_L1_BATCHES: u32 = 1_000;
+    const DEFAULT_DATA_RETENTION_SEC: u64 = 3_600;
+    const DEFAULT_PRUNED_BATCH_CHUNK_SIZE: u32 = 10;
+    const DEFAULT_POLLING_INTERVAL_MS: u64 = 60_000;
+
+    pub fn for_tests() -> Self {
+        Self {
+            data_retention_l1_batches: Some(1),
+            data_retention_sec: Some(0),
+            pruned_batch_chunk_size: Some(2),
+            polling_interval_ms: Some(10),
+        }
+    }
+
+    pub fn data_retention_l1_batches(&self) -> u32 {
+        self.data_retention_l1_batches
+            .unwrap_or(Self::DEFAULT_DATA_RETENTION_L1_BATCHES)
+    }
+
+    pub fn data_retention(&self) -> Duration {
+        Duration::from_secs(
+            self.data_retention_sec
+                .unwrap_or(Self::DEFAULT_DATA_RETENTION_SEC),
+        )
+    }
+
+    pub fn pruned_batch_chunk_size(&self) -> u32 {
+        self.pruned_batch_chunk_size
+            .unwrap_or(Self::DEFAULT_PRUNED_BATCH_CHUNK_SIZE)
+    }
+
+    pub fn polling_interval(&self) -> Duration {
+        Duration::from_millis(
+            self.polling_interval_ms
+                .unwrap_or(Self::DEFAULT_POLLING_INTERVAL_MS),
+        )
+    }
+}
@@ -8,4 +8,19 @@ pub struct ETHClientConfig {
     pub chain_id: u64,
     /// Address of the Ethereum node API.
     pub web3_url: String,
+    /// Addresses of fallback Ethereum node APIs in the order of preference. Requests are failed over
+    /// to these endpoints if the main one is unhealthy.
+    #[serde(default)]
+    pub fallback_web3_urls: Vec<String>,
+}
+
+impl ETHClientConfig {
+    /// Returns addresses of all configured Ethereum node APIs, starting from the main one.
+    pub fn web3_urls(&self) -> Vec<String> {
+        std::iter::once(&self.web3_url)
+            .chain(&self.fallback_web3_urls)
+            .filter(|url| !url.is_empty())
+            .cloned()
+            .collect()
+    }
 }
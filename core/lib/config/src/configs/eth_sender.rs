@@ -10,6 +10,12 @@ pub struct ETHSenderConfig {
     pub sender: SenderConfig,
     /// Options related to the `GasAdjuster` submodule.
     pub gas_adjuster: GasAdjusterConfig,
+    /// Policies of bumping fees of re-sent L1 transactions.
+    #[serde(default)]
+    pub gas_escalation: GasEscalationConfig,
+    /// Signer of transactions sent by the main operator account.
+    #[serde(default)]
+    pub signer: OperatorSignerConfig,
 }
 
 impl ETHSenderConfig {
@@ -35,17 +41,30 @@ impl ETHSenderConfig {
                 l1_batch_min_age_before_execute_seconds: None,
                 max_acceptable_priority_fee_in_gwei: 100000000000,
                 proof_loading_mode: ProofLoadingMode::OldProofFromDb,
+                pubdata_sending_mode: PubdataSendingMode::Calldata,
+                stuck_operator_nonce_blocks: None,
+                min_operator_balance_gwei: None,
+                dry_run: false,
+                stuck_tx_threshold_blocks: None,
+                stuck_tx_remediation: StuckTxRemediationPolicy::Alert,
             },
             gas_adjuster: GasAdjusterConfig {
                 default_priority_fee_per_gas: 1000000000,
                 max_base_fee_samples: 10000,
+                base_fee_percentile: None,
+                max_base_fee_spike_ratio: None,
                 pricing_formula_parameter_a: 1.5,
                 pricing_formula_parameter_b: 1.0005,
                 internal_l1_pricing_multiplier: 0.8,
                 internal_enforced_l1_gas_price: None,
                 poll_period: 5,
                 max_l1_gas_price: None,
+                internal_pubdata_pricing_multiplier: None,
+                max_blob_base_fee: None,
+                num_samples_for_blob_base_fee_estimate: None,
             },
+            gas_escalation: GasEscalationConfig::default(),
+            signer: OperatorSignerConfig::default(),
         }
     }
 }
@@ -63,6 +82,33 @@ pub enum ProofLoadingMode {
     FriProofFromGcs,
 }
 
+/// The way pubdata of L1 batches is published on L1.
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq)]
+pub enum PubdataSendingMode {
+    /// Pubdata is sent as calldata of commit transactions.
+    #[default]
+    Calldata,
+    /// Pubdata is sent in EIP-4844 blobs.
+    Blobs,
+    /// Pubdata is posted to an external data availability layer by the DA dispatcher. Commit transactions
+    /// reference the inclusion data received from the DA layer, so L1 batches are only committed after
+    /// their pubdata is included into the DA layer.
+    Custom,
+}
+
+/// Action taken on stuck in-flight L1 transactions.
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq)]
+pub enum StuckTxRemediationPolicy {
+    /// Stuck transactions are only reported via logs, metrics and the health check.
+    #[default]
+    Alert,
+    /// Fee-starved transactions are replaced with new attempts priced according to the current L1 fees.
+    Replace,
+    /// In addition to `Replace`, nonce gaps blocking transactions of an operator account are closed
+    /// with zero-value transfers to the operator account itself.
+    Cancel,
+}
+
 #[derive(Debug, Deserialize, Clone, PartialEq)]
 pub struct SenderConfig {
     pub aggregated_proof_sizes: Vec<usize>,
@@ -96,6 +142,24 @@ pub struct SenderConfig {
 
     /// The mode in which proofs are loaded, either from DB/GCS for FRI/Old proof.
     pub proof_loading_mode: ProofLoadingMode,
+    /// The way pubdata is published on L1. Defaults to calldata.
+    #[serde(default)]
+    pub pubdata_sending_mode: PubdataSendingMode,
+    /// If set, the active operator account is rotated to a backup one if its nonce doesn't increase
+    /// for this number of L1 blocks while it has pending transactions.
+    pub stuck_operator_nonce_blocks: Option<u64>,
+    /// If set, the active operator account is rotated to a backup one once its balance drops below this value.
+    pub min_operator_balance_gwei: Option<u64>,
+    /// If set, aggregated L1 transactions are only simulated via `eth_call` and logged; they are neither saved
+    /// to Postgres nor signed or sent to L1.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Number of L1 blocks after which an unmined transaction with fees below the next block minimum
+    /// is reported as fee-starved. Defaults to 10.
+    pub stuck_tx_threshold_blocks: Option<u64>,
+    /// Action taken on stuck transactions detected by the ETH tx manager.
+    #[serde(default)]
+    pub stuck_tx_remediation: StuckTxRemediationPolicy,
 }
 
 impl SenderConfig {
@@ -109,20 +173,42 @@ impl SenderConfig {
         Duration::from_secs(self.aggregate_tx_poll_period)
     }
 
+    pub fn stuck_tx_threshold_blocks(&self) -> u64 {
+        self.stuck_tx_threshold_blocks.unwrap_or(10)
+    }
+
     // Don't load private key, if it's not required.
     pub fn private_key(&self) -> Option<H256> {
         std::env::var("ETH_SENDER_SENDER_OPERATOR_PRIVATE_KEY")
             .ok()
             .map(|pk| pk.parse().unwrap())
     }
+
+    /// Private keys of backup operator accounts, which are used if the main operator account gets stuck
+    /// or runs low on funds. Keys are comma-separated.
+    pub fn backup_private_keys(&self) -> Vec<H256> {
+        std::env::var("ETH_SENDER_SENDER_OPERATOR_BACKUP_PRIVATE_KEYS")
+            .map(|keys| {
+                keys.split(',')
+                    .map(|pk| pk.trim().parse().unwrap())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
 }
 
 #[derive(Debug, Deserialize, Copy, Clone, PartialEq)]
 pub struct GasAdjusterConfig {
     /// Priority Fee to be used by GasAdjuster
     pub default_priority_fee_per_gas: u64,
-    /// Number of blocks collected by GasAdjuster from which base_fee median is taken
+    /// Number of blocks collected by GasAdjuster from which the base_fee estimate is taken
     pub max_base_fee_samples: usize,
+    /// Percentile (0..=100) of base fees in the last `max_base_fee_samples` blocks used as the base_fee estimate.
+    /// Defaults to 50 (i.e., the median).
+    pub base_fee_percentile: Option<f64>,
+    /// If set, a base fee sample is capped at this ratio of the current base_fee estimate before it's added
+    /// to the samples window. This smooths out short-lived base fee spikes.
+    pub max_base_fee_spike_ratio: Option<f64>,
     /// Parameter of the transaction base_fee_per_gas pricing formula
     pub pricing_formula_parameter_a: f64,
     /// Parameter of the transaction base_fee_per_gas pricing formula
@@ -135,6 +221,13 @@ pub struct GasAdjusterConfig {
     pub poll_period: u64,
     /// Max number of l1 gas price that is allowed to be used in state keeper.
     pub max_l1_gas_price: Option<u64>,
+    /// Parameter by which the blob base fee will be multiplied to get the pubdata price if pubdata is published
+    /// in blobs. Defaults to 1.
+    pub internal_pubdata_pricing_multiplier: Option<f64>,
+    /// Max blob base fee that is allowed to be used to price pubdata.
+    pub max_blob_base_fee: Option<u64>,
+    /// Number of blocks collected by GasAdjuster from which the blob base fee median is taken. Defaults to 10.
+    pub num_samples_for_blob_base_fee_estimate: Option<usize>,
 }
 
 impl GasAdjusterConfig {
@@ -146,4 +239,119 @@ impl GasAdjusterConfig {
     pub fn max_l1_gas_price(&self) -> u64 {
         self.max_l1_gas_price.unwrap_or(u64::MAX)
     }
+
+    pub fn internal_pubdata_pricing_multiplier(&self) -> f64 {
+        self.internal_pubdata_pricing_multiplier.unwrap_or(1.0)
+    }
+
+    pub fn max_blob_base_fee(&self) -> u64 {
+        self.max_blob_base_fee.unwrap_or(u64::MAX)
+    }
+
+    pub fn num_samples_for_blob_base_fee_estimate(&self) -> usize {
+        self.num_samples_for_blob_base_fee_estimate.unwrap_or(10)
+    }
+
+    pub fn base_fee_percentile(&self) -> f64 {
+        self.base_fee_percentile.unwrap_or(50.0)
+    }
+}
+
+/// Policy of bumping fees of re-sent L1 transactions.
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq)]
+pub enum GasEscalationPolicy {
+    /// The base fee follows the `GasAdjuster` estimate for the time the transaction spent in the mempool,
+    /// and the priority fee is increased by 20% on each resend.
+    #[default]
+    GasAdjuster,
+    /// Fees are increased by `bump_ratio` of the fees of the first attempt on each resend.
+    Linear,
+    /// Fees are multiplied by `1 + bump_ratio` on each resend.
+    Exponential,
+    /// Fees grow exponentially so that they reach `max_fee_per_gas` once the transaction spends
+    /// `deadline_blocks` L1 blocks in the mempool.
+    Deadline,
+}
+
+/// Fee escalation parameters for a certain type of L1 transactions.
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq)]
+pub struct GasEscalationParams {
+    #[serde(default)]
+    pub policy: GasEscalationPolicy,
+    /// Relative fee increase on each resend for the `Linear` and `Exponential` policies. Defaults to 0.125.
+    pub bump_ratio: Option<f64>,
+    /// Minimum number of L1 blocks between resends for all policies except for `GasAdjuster`. Defaults to 3.
+    pub resend_interval_blocks: Option<u64>,
+    /// Number of L1 blocks after which fees reach `max_fee_per_gas` for the `Deadline` policy.
+    pub deadline_blocks: Option<u64>,
+    /// Hard cap on the max fee per gas (i.e., base fee + priority fee) of transactions, in wei.
+    /// Transactions are not re-sent if that would require exceeding the cap.
+    pub max_fee_per_gas: Option<u64>,
+}
+
+impl GasEscalationParams {
+    pub fn bump_ratio(&self) -> f64 {
+        self.bump_ratio.unwrap_or(0.125)
+    }
+
+    pub fn resend_interval_blocks(&self) -> u64 {
+        self.resend_interval_blocks.unwrap_or(3)
+    }
+
+    pub fn max_fee_per_gas(&self) -> u64 {
+        self.max_fee_per_gas.unwrap_or(u64::MAX)
+    }
+}
+
+/// Fee escalation parameters for each type of L1 transactions.
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq)]
+pub struct GasEscalationConfig {
+    #[serde(default)]
+    pub commit: GasEscalationParams,
+    #[serde(default)]
+    pub prove: GasEscalationParams,
+    #[serde(default)]
+    pub execute: GasEscalationParams,
+}
+
+/// Source of signatures for L1 transactions sent by the main operator account.
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq)]
+pub enum OperatorSignerMode {
+    /// Transactions are signed in process with the private key from `ETH_SENDER_SENDER_OPERATOR_PRIVATE_KEY`.
+    #[default]
+    PrivateKey,
+    /// Transactions are signed by an `ECC_SECG_P256K1` key stored in AWS KMS. Credentials are discovered
+    /// using the default AWS credential provider chain.
+    AwsKms,
+    /// Transactions are signed by an `EC_SIGN_SECP256K1_SHA256` key stored in GCP Cloud KMS.
+    /// Credentials are discovered using Application Default Credentials.
+    GcpKms,
+    /// Transactions are signed by a remote signer exposing the Web3Signer `eth1` signing API.
+    Web3Signer,
+}
+
+/// Configuration of the signer used for the main operator account.
+#[derive(Debug, Default, Deserialize, Clone, PartialEq)]
+pub struct OperatorSignerConfig {
+    #[serde(default)]
+    pub mode: OperatorSignerMode,
+    /// ID or ARN of the AWS KMS key. Required for the `AwsKms` mode.
+    pub aws_kms_key_id: Option<String>,
+    /// AWS region of the KMS key. Required for the `AwsKms` mode.
+    pub aws_kms_region: Option<String>,
+    /// Full resource name of the GCP KMS key version, i.e.
+    /// `projects/*/locations/*/keyRings/*/cryptoKeys/*/cryptoKeyVersions/*`. Required for the `GcpKms` mode.
+    pub gcp_kms_key_version: Option<String>,
+    /// Base URL of the Web3Signer instance. Required for the `Web3Signer` mode.
+    pub web3signer_url: Option<String>,
+    /// Hex-encoded public key identifying the operator key in Web3Signer. Required for the `Web3Signer` mode.
+    pub web3signer_public_key: Option<String>,
+    /// Timeout for a single request to the remote signer in milliseconds. Defaults to 5,000.
+    pub request_timeout_ms: Option<u64>,
+}
+
+impl OperatorSignerConfig {
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_millis(self.request_timeout_ms.unwrap_or(5_000))
+    }
 }
@@ -8,6 +8,9 @@ pub struct ETHWatchConfig {
     /// Amount of confirmations for the priority operation to be processed.
     /// If not specified operation will be processed once its block is finalized.
     pub confirmations_for_eth_event: Option<u64>,
+    /// Amount of confirmations for the protocol upgrade proposal to be processed.
+    /// If not specified, `confirmations_for_eth_event` is used.
+    pub confirmations_for_upgrade_event: Option<u64>,
     /// How often we want to poll the Ethereum node.
     /// Value in milliseconds.
     pub eth_node_poll_interval: u64,
@@ -18,4 +21,11 @@ impl ETHWatchConfig {
     pub fn poll_interval(&self) -> Duration {
         Duration::from_millis(self.eth_node_poll_interval)
     }
+
+    /// Returns the amount of confirmations for protocol upgrade proposals, falling back
+    /// to the amount of confirmations for priority operations.
+    pub fn confirmations_for_upgrade_event(&self) -> Option<u64> {
+        self.confirmations_for_upgrade_event
+            .or(self.confirmations_for_eth_event)
+    }
 }
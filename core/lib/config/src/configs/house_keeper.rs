@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use serde::Deserialize;
 
 /// Configuration for the house keeper.
@@ -16,4 +18,15 @@ pub struct HouseKeeperConfig {
     pub fri_prover_stats_reporting_interval_ms: u64,
     pub fri_proof_compressor_job_retrying_interval_ms: u64,
     pub fri_proof_compressor_stats_reporting_interval_ms: u64,
+    /// Interval between runs of the operator revenue accounting. If not set, defaults to 10 seconds.
+    pub revenue_accounting_interval_ms: Option<u64>,
+}
+
+impl HouseKeeperConfig {
+    const DEFAULT_REVENUE_ACCOUNTING_INTERVAL: Duration = Duration::from_secs(10);
+
+    pub fn revenue_accounting_interval_ms(&self) -> u64 {
+        self.revenue_accounting_interval_ms
+            .unwrap_or(Self::DEFAULT_REVENUE_ACCOUNTING_INTERVAL.as_millis() as u64)
+    }
 }
@@ -4,9 +4,11 @@ pub use self::{
     api::ApiConfig,
     contract_verifier::ContractVerifierConfig,
     contracts::ContractsConfig,
+    da_dispatcher::DADispatcherConfig,
     database::{DBConfig, PostgresConfig},
+    db_pruner::DbPrunerConfig,
     eth_client::ETHClientConfig,
-    eth_sender::{ETHSenderConfig, GasAdjusterConfig},
+    eth_sender::{ETHSenderConfig, GasAdjusterConfig, GasEscalationConfig},
     eth_watch::ETHWatchConfig,
     fri_proof_compressor::FriProofCompressorConfig,
     fri_prover::FriProverConfig,
@@ -25,7 +27,9 @@ pub mod api;
 pub mod chain;
 pub mod contract_verifier;
 pub mod contracts;
+pub mod da_dispatcher;
 pub mod database;
+pub mod db_pruner;
 pub mod eth_client;
 pub mod eth_sender;
 pub mod eth_watch;
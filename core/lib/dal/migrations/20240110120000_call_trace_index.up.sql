@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS call_trace_index (
+    tx_hash BYTEA NOT NULL REFERENCES call_traces (tx_hash) ON DELETE CASCADE,
+    -- Position of the call frame in the depth-first traversal of the call tree.
+    trace_position INT NOT NULL,
+    miniblock_number BIGINT NOT NULL,
+    tx_index_in_block INT NOT NULL,
+    from_address BYTEA NOT NULL,
+    to_address BYTEA NOT NULL,
+    PRIMARY KEY (tx_hash, trace_position)
+);
+
+CREATE INDEX IF NOT EXISTS call_trace_index_location_idx ON call_trace_index (miniblock_number, tx_index_in_block, trace_position);
+CREATE INDEX IF NOT EXISTS call_trace_index_from_address_idx ON call_trace_index (from_address, miniblock_number);
+CREATE INDEX IF NOT EXISTS call_trace_index_to_address_idx ON call_trace_index (to_address, miniblock_number);
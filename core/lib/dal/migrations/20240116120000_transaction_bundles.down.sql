@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS transactions_bundle_hash_idx;
+ALTER TABLE transactions DROP COLUMN IF EXISTS bundle_index;
+ALTER TABLE transactions DROP COLUMN IF EXISTS bundle_hash;
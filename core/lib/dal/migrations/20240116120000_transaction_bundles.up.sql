@@ -0,0 +1,3 @@
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS bundle_hash BYTEA;
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS bundle_index INT;
+CREATE INDEX IF NOT EXISTS transactions_bundle_hash_idx ON transactions (bundle_hash) WHERE bundle_hash IS NOT NULL;
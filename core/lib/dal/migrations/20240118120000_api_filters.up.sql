@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS api_filters (
+    id BYTEA PRIMARY KEY,
+    filter JSONB NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+    last_requested_at TIMESTAMP NOT NULL
+);
+CREATE INDEX IF NOT EXISTS api_filters_last_requested_at_idx ON api_filters (last_requested_at);
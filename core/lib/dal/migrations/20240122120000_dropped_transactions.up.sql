@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS dropped_transactions (
+    hash BYTEA PRIMARY KEY,
+    reason TEXT NOT NULL,
+    dropped_at TIMESTAMP NOT NULL
+);
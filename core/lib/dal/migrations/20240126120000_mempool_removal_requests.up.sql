@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS mempool_removal_requests (
+    hash BYTEA PRIMARY KEY,
+    reason TEXT NOT NULL,
+    requested_at TIMESTAMP NOT NULL
+);
@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS l1_batch_revenue;
+
+ALTER TABLE eth_txs DROP COLUMN IF EXISTS effective_gas_price;
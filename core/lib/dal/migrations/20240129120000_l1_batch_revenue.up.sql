@@ -0,0 +1,11 @@
+ALTER TABLE eth_txs ADD COLUMN IF NOT EXISTS effective_gas_price BIGINT;
+
+CREATE TABLE IF NOT EXISTS l1_batch_revenue (
+    l1_batch_number BIGINT PRIMARY KEY REFERENCES l1_batches (number) ON DELETE CASCADE,
+    collected_fees NUMERIC(80, 0) NOT NULL,
+    pubdata_cost NUMERIC(80, 0) NOT NULL,
+    commit_cost NUMERIC(80, 0) NOT NULL,
+    prove_cost NUMERIC(80, 0) NOT NULL,
+    execute_cost NUMERIC(80, 0) NOT NULL,
+    created_at TIMESTAMP NOT NULL
+);
@@ -0,0 +1,2 @@
+ALTER TABLE eth_txs ADD COLUMN IF NOT EXISTS blob_sidecar BYTEA;
+ALTER TABLE eth_txs_history ADD COLUMN IF NOT EXISTS blob_base_fee_per_gas BIGINT;
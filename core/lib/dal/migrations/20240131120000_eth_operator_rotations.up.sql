@@ -0,0 +1,10 @@
+ALTER TABLE eth_txs ADD COLUMN IF NOT EXISTS from_addr BYTEA;
+
+CREATE TABLE IF NOT EXISTS eth_operator_rotations (
+    id BIGSERIAL PRIMARY KEY,
+    from_addr BYTEA,
+    to_addr BYTEA,
+    base_nonce BIGINT NOT NULL,
+    reason TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL
+);
@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS pruning_log (
+    pruned_l1_batch BIGINT PRIMARY KEY,
+    pruned_miniblock BIGINT NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP NOT NULL
+);
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS dynamic_l2_gas_price;
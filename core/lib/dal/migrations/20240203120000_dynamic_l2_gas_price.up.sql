@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS dynamic_l2_gas_price (
+    -- congestion-based minimal L2 gas price after sealing the miniblock below
+    price BIGINT NOT NULL,
+    miniblock_number BIGINT NOT NULL,
+    updated_at TIMESTAMP NOT NULL,
+    -- artificial primary key ensuring that the table contains at most 1 row.
+    fake_key BOOLEAN PRIMARY KEY,
+    CHECK (fake_key)
+);
@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS fee_model_settings (
+    minimal_l2_gas_price BIGINT NOT NULL,
+    l1_gas_price_scale_factor DOUBLE PRECISION NOT NULL,
+    l1_pubdata_price_scale_factor DOUBLE PRECISION NOT NULL,
+    updated_at TIMESTAMP NOT NULL,
+    -- artificial primary key ensuring that the table contains at most 1 row.
+    fake_key BOOLEAN PRIMARY KEY,
+    CHECK (fake_key)
+);
@@ -0,0 +1,221 @@
+//! Storage for Web3 API filters (`eth_newFilter` etc.) shared among API server replicas.
+
+use std::time::Duration;
+
+use zksync_types::H256;
+
+use crate::{time_utils::pg_interval_from_duration, StorageProcessor};
+
+#[derive(Debug)]
+pub struct ApiFiltersDal<'a, 'c> {
+    pub(crate) storage: &'a mut StorageProcessor<'c>,
+}
+
+impl ApiFiltersDal<'_, '_> {
+    /// Inserts a new filter. The filter is opaque for the DAL and is stored as JSON.
+    pub async fn insert_filter(
+        &mut self,
+        id: H256,
+        filter: &serde_json::Value,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO
+                api_filters (id, filter, created_at, last_requested_at)
+            VALUES
+                ($1, $2, NOW(), NOW())
+            "#,
+            id.as_bytes(),
+            filter
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    /// Returns a filter with the specified ID and marks it as requested.
+    pub async fn get_filter_and_touch(
+        &mut self,
+        id: H256,
+    ) -> sqlx::Result<Option<serde_json::Value>> {
+        let row = sqlx::query!(
+            r#"
+            UPDATE api_filters
+            SET
+                last_requested_at = NOW()
+            WHERE
+                id = $1
+            RETURNING
+                filter
+            "#,
+            id.as_bytes()
+        )
+        .fetch_optional(self.storage.conn())
+        .await?;
+        Ok(row.map(|row| row.filter))
+    }
+
+    /// Updates an existing filter. Does nothing if the filter does not exist (e.g., it was removed concurrently).
+    pub async fn update_filter(
+        &mut self,
+        id: H256,
+        filter: &serde_json::Value,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE api_filters
+            SET
+                filter = $2
+            WHERE
+                id = $1
+            "#,
+            id.as_bytes(),
+            filter
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    /// Removes a filter. Returns `false` if the filter does not exist.
+    pub async fn remove_filter(&mut self, id: H256) -> sqlx::Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM api_filters
+            WHERE
+                id = $1
+            "#,
+            id.as_bytes()
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Removes filters that were not requested for longer than `idle_timeout`. Returns the number of removed filters.
+    pub async fn remove_idle_filters(&mut self, idle_timeout: Duration) -> sqlx::Result<u64> {
+        let idle_timeout = pg_interval_from_duration(idle_timeout);
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM api_filters
+            WHERE
+                last_requested_at < NOW() - $1::INTERVAL
+            "#,
+            &idle_timeout
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Removes least recently requested filters so that at most `retained_count` filters remain.
+    /// Returns the number of removed filters.
+    pub async fn prune_filters(&mut self, retained_count: usize) -> sqlx::Result<u64> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM api_filters
+            WHERE
+                id IN (
+                    SELECT
+                        id
+                    FROM
+                        api_filters
+                    ORDER BY
+                        last_requested_at DESC
+                    OFFSET
+                        $1
+                )
+            "#,
+            retained_count as i64
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ConnectionPool;
+
+    #[tokio::test]
+    async fn api_filters_basics() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut conn = pool.access_storage().await.unwrap();
+        let mut dal = conn.api_filters_dal();
+
+        let id = H256::repeat_byte(1);
+        assert_eq!(dal.get_filter_and_touch(id).await.unwrap(), None);
+        let filter = serde_json::json!({ "Blocks": 1 });
+        dal.insert_filter(id, &filter).await.unwrap();
+        assert_eq!(dal.get_filter_and_touch(id).await.unwrap(), Some(filter));
+
+        let updated_filter = serde_json::json!({ "Blocks": 5 });
+        dal.update_filter(id, &updated_filter).await.unwrap();
+        assert_eq!(
+            dal.get_filter_and_touch(id).await.unwrap(),
+            Some(updated_filter)
+        );
+
+        assert!(dal.remove_filter(id).await.unwrap());
+        assert!(!dal.remove_filter(id).await.unwrap());
+        assert_eq!(dal.get_filter_and_touch(id).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn pruning_api_filters() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut conn = pool.access_storage().await.unwrap();
+        let mut dal = conn.api_filters_dal();
+
+        let filter = serde_json::json!({ "Blocks": 1 });
+        for byte in 1..=3 {
+            dal.insert_filter(H256::repeat_byte(byte), &filter)
+                .await
+                .unwrap();
+        }
+        // Make the first filter the most recently requested one.
+        dal.get_filter_and_touch(H256::repeat_byte(1))
+            .await
+            .unwrap();
+
+        let removed_count = dal.prune_filters(1).await.unwrap();
+        assert_eq!(removed_count, 2);
+        assert!(dal
+            .get_filter_and_touch(H256::repeat_byte(1))
+            .await
+            .unwrap()
+            .is_some());
+        assert!(dal
+            .get_filter_and_touch(H256::repeat_byte(2))
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn removing_idle_api_filters() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut conn = pool.access_storage().await.unwrap();
+        let mut dal = conn.api_filters_dal();
+
+        let filter = serde_json::json!({ "Blocks": 1 });
+        dal.insert_filter(H256::repeat_byte(1), &filter)
+            .await
+            .unwrap();
+        let removed_count = dal
+            .remove_idle_filters(Duration::from_secs(3_600))
+            .await
+            .unwrap();
+        assert_eq!(removed_count, 0);
+
+        let removed_count = dal.remove_idle_filters(Duration::ZERO).await.unwrap();
+        assert_eq!(removed_count, 1);
+        assert!(dal
+            .get_filter_and_touch(H256::repeat_byte(1))
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
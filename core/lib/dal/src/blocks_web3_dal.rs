@@ -1,9 +1,12 @@
+use std::{collections::HashMap, ops};
+
 use bigdecimal::BigDecimal;
 use sqlx::Row;
 use zksync_system_constants::EMPTY_UNCLES_HASH;
 use zksync_types::{
-    api,
+    api::{self, raw},
     ethabi::Address,
+    fee_model::BatchFeeInput,
     l2_to_l1_log::L2ToL1Log,
     vm_trace::Call,
     web3::types::{BlockHeader, U64},
@@ -160,6 +163,66 @@ impl BlocksWeb3Dal<'_, '_> {
         Ok(block)
     }
 
+    /// Returns the RLP-encoded miniblock with the specified number. See [`raw::encode_block()`]
+    /// for the encoding details.
+    pub async fn get_raw_block(
+        &mut self,
+        block_number: MiniblockNumber,
+    ) -> sqlx::Result<Option<Vec<u8>>> {
+        let Some(row) = sqlx::query!(
+            r#"
+            SELECT
+                miniblocks.number,
+                miniblocks.timestamp,
+                miniblocks.base_fee_per_gas,
+                prev_miniblock.hash AS "parent_hash?",
+                (
+                    SELECT
+                        SUM(transactions.gas_limit - transactions.refunded_gas)
+                    FROM
+                        transactions
+                    WHERE
+                        transactions.miniblock_number = miniblocks.number
+                ) AS "gas_used?"
+            FROM
+                miniblocks
+                LEFT JOIN miniblocks prev_miniblock ON prev_miniblock.number = miniblocks.number - 1
+            WHERE
+                miniblocks.number = $1
+            "#,
+            block_number.0 as i64
+        )
+        .instrument("get_raw_block")
+        .with_arg("block_number", &block_number)
+        .fetch_optional(self.storage.conn())
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let header = raw::RawBlockHeader {
+            parent_hash: row
+                .parent_hash
+                .map_or_else(H256::zero, |hash| H256::from_slice(&hash)),
+            uncles_hash: EMPTY_UNCLES_HASH,
+            number: row.number as u64,
+            gas_limit: BLOCK_GAS_LIMIT.into(),
+            gas_used: row.gas_used.map(bigdecimal_to_u256).unwrap_or_default(),
+            timestamp: row.timestamp as u64,
+            base_fee_per_gas: bigdecimal_to_u256(row.base_fee_per_gas),
+        };
+        let transactions = self
+            .storage
+            .transactions_web3_dal()
+            .get_raw_miniblock_transactions(block_number)
+            .await?
+            .iter()
+            .map(raw::encode_transaction)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| sqlx::Error::Decode(err.into()))?;
+        Ok(Some(raw::encode_block(&header, &transactions)))
+    }
+
     pub async fn get_block_tx_count(
         &mut self,
         block_id: api::BlockId,
@@ -441,47 +504,180 @@ impl BlocksWeb3Dal<'_, '_> {
         Ok(result)
     }
 
+    /// Returns call traces for all transactions in the specified miniblock together with
+    /// the transaction hashes. Traces are ordered by the transaction index in the miniblock.
     pub async fn get_trace_for_miniblock(
         &mut self,
         block_number: MiniblockNumber,
-    ) -> sqlx::Result<Vec<Call>> {
+    ) -> sqlx::Result<Vec<(H256, Call)>> {
         Ok(sqlx::query_as!(
             CallTrace,
             r#"
             SELECT
-                *
+                call_traces.tx_hash,
+                call_traces.call_trace
             FROM
                 call_traces
+                INNER JOIN transactions ON tx_hash = transactions.hash
             WHERE
-                tx_hash IN (
-                    SELECT
-                        hash
-                    FROM
-                        transactions
-                    WHERE
-                        miniblock_number = $1
-                )
+                transactions.miniblock_number = $1
+            ORDER BY
+                transactions.index_in_block
             "#,
             block_number.0 as i64
         )
         .fetch_all(self.storage.conn())
         .await?
         .into_iter()
-        .map(Call::from)
+        .map(|call_trace| (H256::from_slice(&call_trace.tx_hash), call_trace.into()))
         .collect())
     }
 
-    /// Returns `base_fee_per_gas` for miniblock range [min(newest_block - block_count + 1, 0), newest_block]
-    /// in descending order of miniblock numbers.
+    /// Returns flat call traces in the specified miniblock range matching the provided address filters.
+    /// Empty address filters match any address. Traces are ordered by miniblock number, transaction index
+    /// and the depth-first position in the call tree. If `cursor` is specified, only traces ordered after it
+    /// are returned.
+    pub async fn get_filtered_traces(
+        &mut self,
+        blocks: ops::RangeInclusive<MiniblockNumber>,
+        from_addresses: &[Address],
+        to_addresses: &[Address],
+        cursor: Option<api::trace::TraceCursor>,
+        offset: usize,
+        limit: usize,
+    ) -> sqlx::Result<Vec<api::trace::LocalizedTrace>> {
+        // `-1` precedes all valid positions, so this is equivalent to not having a cursor.
+        let (cursor_block, cursor_tx, cursor_trace) = cursor.map_or((-1, -1, -1), |cursor| {
+            (
+                cursor.block_number.as_u64() as i64,
+                cursor.transaction_position as i32,
+                cursor.trace_position as i32,
+            )
+        });
+        let from_addresses: Vec<_> = from_addresses
+            .iter()
+            .map(|address| address.as_bytes().to_vec())
+            .collect();
+        let to_addresses: Vec<_> = to_addresses
+            .iter()
+            .map(|address| address.as_bytes().to_vec())
+            .collect();
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                call_trace_index.tx_hash,
+                call_trace_index.trace_position,
+                call_trace_index.miniblock_number,
+                call_trace_index.tx_index_in_block,
+                miniblocks.hash AS block_hash
+            FROM
+                call_trace_index
+                INNER JOIN miniblocks ON miniblocks.number = call_trace_index.miniblock_number
+            WHERE
+                call_trace_index.miniblock_number BETWEEN $1 AND $2
+                AND (
+                    call_trace_index.miniblock_number,
+                    call_trace_index.tx_index_in_block,
+                    call_trace_index.trace_position
+                ) > ($7, $8, $9)
+                AND (
+                    CARDINALITY($3::bytea[]) = 0
+                    OR call_trace_index.from_address = ANY ($3)
+                )
+                AND (
+                    CARDINALITY($4::bytea[]) = 0
+                    OR call_trace_index.to_address = ANY ($4)
+                )
+            ORDER BY
+                call_trace_index.miniblock_number,
+                call_trace_index.tx_index_in_block,
+                call_trace_index.trace_position
+            OFFSET
+                $5
+            LIMIT
+                $6
+            "#,
+            i64::from(blocks.start().0),
+            i64::from(blocks.end().0),
+            &from_addresses,
+            &to_addresses,
+            offset as i64,
+            limit as i64,
+            cursor_block,
+            cursor_tx,
+            cursor_trace
+        )
+        .instrument("get_filtered_traces")
+        .with_arg("blocks", &blocks)
+        .with_arg("cursor", &cursor)
+        .report_latency()
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        let mut tx_hashes: Vec<_> = rows.iter().map(|row| row.tx_hash.clone()).collect();
+        tx_hashes.dedup();
+        let call_traces = sqlx::query_as!(
+            CallTrace,
+            r#"
+            SELECT
+                tx_hash,
+                call_trace
+            FROM
+                call_traces
+            WHERE
+                tx_hash = ANY ($1)
+            "#,
+            &tx_hashes
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+        let flat_traces: HashMap<_, _> = call_traces
+            .into_iter()
+            .map(|call_trace| {
+                let tx_hash = H256::from_slice(&call_trace.tx_hash);
+                (tx_hash, api::trace::Trace::flatten(&call_trace.into()))
+            })
+            .collect();
+
+        let traces = rows
+            .into_iter()
+            .filter_map(|row| {
+                let transaction_hash = H256::from_slice(&row.tx_hash);
+                let trace = flat_traces
+                    .get(&transaction_hash)?
+                    .get(row.trace_position as usize)?;
+                Some(api::trace::LocalizedTrace {
+                    trace: trace.clone(),
+                    block_hash: H256::from_slice(&row.block_hash),
+                    block_number: U64::from(row.miniblock_number as u64),
+                    transaction_hash,
+                    transaction_position: row.tx_index_in_block as usize,
+                    trace_position: row.trace_position as usize,
+                })
+            })
+            .collect();
+        Ok(traces)
+    }
+
+    /// Returns `base_fee_per_gas` and gas used for miniblock range
+    /// [min(newest_block - block_count + 1, 0), newest_block] in descending order of miniblock numbers.
     pub async fn get_fee_history(
         &mut self,
         newest_block: MiniblockNumber,
         block_count: u64,
-    ) -> sqlx::Result<Vec<U256>> {
+    ) -> sqlx::Result<Vec<(U256, U256)>> {
         let result: Vec<_> = sqlx::query!(
             r#"
             SELECT
-                base_fee_per_gas
+                base_fee_per_gas,
+                (
+                    SELECT
+                        SUM(transactions.gas_limit - transactions.refunded_gas)
+                    FROM
+                        transactions
+                    WHERE
+                        transactions.miniblock_number = miniblocks.number
+                ) AS "gas_used?"
             FROM
                 miniblocks
             WHERE
@@ -497,12 +693,63 @@ impl BlocksWeb3Dal<'_, '_> {
         .fetch_all(self.storage.conn())
         .await?
         .into_iter()
-        .map(|row| bigdecimal_to_u256(row.base_fee_per_gas))
+        .map(|row| {
+            let gas_used = row.gas_used.map(bigdecimal_to_u256).unwrap_or_default();
+            (bigdecimal_to_u256(row.base_fee_per_gas), gas_used)
+        })
         .collect();
 
         Ok(result)
     }
 
+    /// Returns gas used and effective priority fees for all transactions in the specified miniblock range, grouped
+    /// by miniblock. Since priority fees are not charged, the effective priority fee is the one offered
+    /// by the transaction, i.e. `min(max_priority_fee_per_gas, max_fee_per_gas - base_fee_per_gas)`.
+    /// Transactions in each miniblock are sorted by the ascending priority fee.
+    pub async fn get_priority_fees(
+        &mut self,
+        blocks: ops::RangeInclusive<MiniblockNumber>,
+    ) -> sqlx::Result<HashMap<MiniblockNumber, Vec<(U256, U256)>>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                transactions.miniblock_number AS "miniblock_number!",
+                transactions.gas_limit - transactions.refunded_gas AS "gas_used!",
+                GREATEST(
+                    LEAST(
+                        COALESCE(transactions.max_priority_fee_per_gas, 0),
+                        COALESCE(transactions.max_fee_per_gas, 0) - miniblocks.base_fee_per_gas
+                    ),
+                    0
+                ) AS "priority_fee!"
+            FROM
+                transactions
+                INNER JOIN miniblocks ON miniblocks.number = transactions.miniblock_number
+            WHERE
+                transactions.miniblock_number BETWEEN $1 AND $2
+            "#,
+            i64::from(blocks.start().0),
+            i64::from(blocks.end().0)
+        )
+        .instrument("get_priority_fees")
+        .with_arg("blocks", &blocks)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        let mut fees = HashMap::<_, Vec<_>>::new();
+        for row in rows {
+            let miniblock_number = MiniblockNumber(row.miniblock_number as u32);
+            fees.entry(miniblock_number).or_default().push((
+                bigdecimal_to_u256(row.gas_used),
+                bigdecimal_to_u256(row.priority_fee),
+            ));
+        }
+        for block_fees in fees.values_mut() {
+            block_fees.sort_unstable_by_key(|&(_, priority_fee)| priority_fee);
+        }
+        Ok(fees)
+    }
+
     pub async fn get_block_details(
         &mut self,
         block_number: MiniblockNumber,
@@ -623,17 +870,46 @@ impl BlocksWeb3Dal<'_, '_> {
             Ok(l1_batch_details.map(api::L1BatchDetails::from))
         }
     }
+
+    /// Returns fee model inputs that were used when sealing the specified L1 batch.
+    pub async fn get_l1_batch_fee_input(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+    ) -> sqlx::Result<Option<BatchFeeInput>> {
+        let fee_input = sqlx::query!(
+            r#"
+            SELECT
+                l1_gas_price,
+                l2_fair_gas_price
+            FROM
+                l1_batches
+            WHERE
+                number = $1
+            "#,
+            l1_batch_number.0 as i64
+        )
+        .instrument("get_l1_batch_fee_input")
+        .with_arg("l1_batch_number", &l1_batch_number)
+        .fetch_optional(self.storage.conn())
+        .await?
+        .map(|row| BatchFeeInput::l1_pegged(row.l1_gas_price as u64, row.l2_fair_gas_price as u64));
+        Ok(fee_input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use zksync_types::{
         block::{MiniblockHasher, MiniblockHeader},
+        fee::TransactionExecutionMetrics,
         MiniblockNumber, ProtocolVersion, ProtocolVersionId,
     };
 
     use super::*;
-    use crate::{tests::create_miniblock_header, ConnectionPool};
+    use crate::{
+        tests::{create_miniblock_header, mock_execution_result, mock_l2_transaction},
+        ConnectionPool,
+    };
 
     #[tokio::test]
     async fn getting_web3_block_and_tx_count() {
@@ -798,4 +1074,122 @@ mod tests {
             .await;
         assert_eq!(miniblock_number.unwrap(), None);
     }
+
+    #[tokio::test]
+    async fn getting_filtered_traces() {
+        let connection_pool = ConnectionPool::test_pool().await;
+        let mut conn = connection_pool.access_storage().await.unwrap();
+        conn.blocks_dal()
+            .delete_miniblocks(MiniblockNumber(0))
+            .await
+            .unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(ProtocolVersion::default())
+            .await;
+        let tx = mock_l2_transaction();
+        let tx_hash = tx.hash();
+        conn.transactions_dal()
+            .insert_transaction_l2(tx.clone(), TransactionExecutionMetrics::default())
+            .await;
+        conn.blocks_dal()
+            .insert_miniblock(&create_miniblock_header(0))
+            .await
+            .unwrap();
+        let miniblock_header = MiniblockHeader {
+            l2_tx_count: 1,
+            ..create_miniblock_header(1)
+        };
+        conn.blocks_dal()
+            .insert_miniblock(&miniblock_header)
+            .await
+            .unwrap();
+
+        let (first_address, second_address) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let mut subcall = Call::new_high_level(0, 0, U256::zero(), vec![], vec![], None, vec![]);
+        subcall.from = first_address;
+        subcall.to = second_address;
+        let mut tx_result = mock_execution_result(tx);
+        tx_result.call_traces = vec![subcall];
+        conn.transactions_dal()
+            .mark_txs_as_executed_in_miniblock(MiniblockNumber(1), &[tx_result], U256::from(1))
+            .await;
+
+        let blocks = MiniblockNumber(0)..=MiniblockNumber(1);
+        let traces = conn
+            .blocks_web3_dal()
+            .get_filtered_traces(blocks.clone(), &[], &[], None, 0, 10)
+            .await
+            .unwrap();
+        let trace_addresses: Vec<_> = traces
+            .iter()
+            .map(|trace| trace.trace.trace_address.clone())
+            .collect();
+        assert_eq!(trace_addresses, [vec![], vec![0]]);
+        assert!(traces.iter().all(|trace| trace.transaction_hash == tx_hash
+            && trace.block_number == U64::from(1)
+            && trace.block_hash == miniblock_header.hash));
+
+        let traces = conn
+            .blocks_web3_dal()
+            .get_filtered_traces(
+                blocks.clone(),
+                &[first_address],
+                &[second_address],
+                None,
+                0,
+                10,
+            )
+            .await
+            .unwrap();
+        assert_eq!(traces.len(), 1);
+        assert_eq!(traces[0].trace.trace_address, [0]);
+
+        let traces = conn
+            .blocks_web3_dal()
+            .get_filtered_traces(blocks.clone(), &[second_address], &[], None, 0, 10)
+            .await
+            .unwrap();
+        assert!(traces.is_empty());
+        let traces = conn
+            .blocks_web3_dal()
+            .get_filtered_traces(blocks.clone(), &[], &[], None, 1, 10)
+            .await
+            .unwrap();
+        assert_eq!(traces.len(), 1);
+        assert_eq!(traces[0].trace_position, 1);
+
+        let first_page = conn
+            .blocks_web3_dal()
+            .get_filtered_traces(blocks.clone(), &[], &[], None, 0, 1)
+            .await
+            .unwrap();
+        assert_eq!(first_page.len(), 1);
+        let cursor = first_page[0].cursor();
+        let second_page = conn
+            .blocks_web3_dal()
+            .get_filtered_traces(blocks.clone(), &[], &[], Some(cursor), 0, 10)
+            .await
+            .unwrap();
+        assert_eq!(second_page, traces);
+        let cursor = second_page[0].cursor();
+        let traces = conn
+            .blocks_web3_dal()
+            .get_filtered_traces(blocks, &[], &[], Some(cursor), 0, 10)
+            .await
+            .unwrap();
+        assert!(traces.is_empty());
+        let traces = conn
+            .blocks_web3_dal()
+            .get_filtered_traces(
+                MiniblockNumber(2)..=MiniblockNumber(3),
+                &[],
+                &[],
+                None,
+                0,
+                10,
+            )
+            .await
+            .unwrap();
+        assert!(traces.is_empty());
+    }
 }
@@ -0,0 +1,270 @@
+use sqlx::types::chrono::NaiveDateTime;
+use zksync_types::L1BatchNumber;
+
+use crate::StorageProcessor;
+
+/// Information about pubdata of an L1 batch posted to the data availability layer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataAvailabilityBlob {
+    pub l1_batch_number: L1BatchNumber,
+    /// ID of the blob assigned by the data availability layer.
+    pub blob_id: String,
+    /// Data proving inclusion of the blob into the data availability layer. `None` if the blob
+    /// isn't included yet.
+    pub inclusion_data: Option<Vec<u8>>,
+    pub sent_at: NaiveDateTime,
+}
+
+/// Pubdata of an L1 batch ready to be posted to the data availability layer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct L1BatchPubdata {
+    pub l1_batch_number: L1BatchNumber,
+    pub pubdata: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct DataAvailabilityDal<'a, 'c> {
+    pub(crate) storage: &'a mut StorageProcessor<'c>,
+}
+
+impl DataAvailabilityDal<'_, '_> {
+    /// Records that pubdata of the L1 batch was posted to the data availability layer.
+    pub async fn insert_l1_batch_da(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+        blob_id: &str,
+        sent_at: NaiveDateTime,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO
+                data_availability (l1_batch_number, blob_id, sent_at, created_at, updated_at)
+            VALUES
+                ($1, $2, $3, NOW(), NOW())
+            ON CONFLICT (l1_batch_number) DO NOTHING
+            "#,
+            i64::from(l1_batch_number.0),
+            blob_id,
+            sent_at
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    /// Saves the inclusion data for the L1 batch. Inclusion data is never overwritten.
+    pub async fn save_l1_batch_inclusion_data(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+        inclusion_data: &[u8],
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE data_availability
+            SET
+                inclusion_data = $1,
+                updated_at = NOW()
+            WHERE
+                l1_batch_number = $2
+                AND inclusion_data IS NULL
+            "#,
+            inclusion_data,
+            i64::from(l1_batch_number.0)
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    /// Returns the earliest blob posted to the data availability layer that isn't included yet.
+    pub async fn get_first_da_blob_awaiting_inclusion(
+        &mut self,
+    ) -> sqlx::Result<Option<DataAvailabilityBlob>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                l1_batch_number,
+                blob_id,
+                inclusion_data,
+                sent_at
+            FROM
+                data_availability
+            WHERE
+                inclusion_data IS NULL
+            ORDER BY
+                l1_batch_number
+            LIMIT
+                1
+            "#
+        )
+        .fetch_optional(self.storage.conn())
+        .await?;
+
+        Ok(row.map(|row| DataAvailabilityBlob {
+            l1_batch_number: L1BatchNumber(row.l1_batch_number as u32),
+            blob_id: row.blob_id,
+            inclusion_data: row.inclusion_data,
+            sent_at: row.sent_at,
+        }))
+    }
+
+    /// Returns the inclusion data for the L1 batch, or `None` if the batch isn't posted to the data availability
+    /// layer or isn't included yet.
+    pub async fn get_inclusion_data(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+    ) -> sqlx::Result<Option<Vec<u8>>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                inclusion_data
+            FROM
+                data_availability
+            WHERE
+                l1_batch_number = $1
+            "#,
+            i64::from(l1_batch_number.0)
+        )
+        .fetch_optional(self.storage.conn())
+        .await?;
+        Ok(row.and_then(|row| row.inclusion_data))
+    }
+
+    /// Returns pubdata of the earliest L1 batches that are not posted to the data availability layer yet.
+    pub async fn get_ready_for_da_dispatch_l1_batches(
+        &mut self,
+        limit: usize,
+    ) -> sqlx::Result<Vec<L1BatchPubdata>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                number,
+                pubdata_input
+            FROM
+                l1_batches
+                LEFT JOIN data_availability ON data_availability.l1_batch_number = l1_batches.number
+            WHERE
+                eth_commit_tx_id IS NULL
+                AND number != 0
+                AND data_availability.blob_id IS NULL
+                AND pubdata_input IS NOT NULL
+            ORDER BY
+                number
+            LIMIT
+                $1
+            "#,
+            limit as i64
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| L1BatchPubdata {
+                l1_batch_number: L1BatchNumber(row.number as u32),
+                // `unwrap` is safe due to the check in the query
+                pubdata: row.pubdata_input.unwrap(),
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::types::chrono::Utc;
+    use zksync_contracts::BaseSystemContractsHashes;
+    use zksync_types::{
+        block::{BlockGasCount, L1BatchHeader},
+        Address, ProtocolVersion, ProtocolVersionId,
+    };
+
+    use super::*;
+    use crate::ConnectionPool;
+
+    #[tokio::test]
+    async fn dispatching_l1_batches_to_da_layer() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut conn = pool.access_storage().await.unwrap();
+        conn.blocks_dal()
+            .delete_l1_batches(L1BatchNumber(0))
+            .await
+            .unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(ProtocolVersion::default())
+            .await;
+
+        for number in [1, 2] {
+            let mut header = L1BatchHeader::new(
+                L1BatchNumber(number),
+                100,
+                Address::default(),
+                BaseSystemContractsHashes::default(),
+                ProtocolVersionId::latest(),
+            );
+            header.pubdata_input = Some(vec![number as u8; 10]);
+            conn.blocks_dal()
+                .insert_l1_batch(&header, &[], BlockGasCount::default(), &[], &[], 0)
+                .await
+                .unwrap();
+        }
+
+        let ready_batches = conn
+            .data_availability_dal()
+            .get_ready_for_da_dispatch_l1_batches(10)
+            .await
+            .unwrap();
+        assert_eq!(
+            ready_batches,
+            [
+                L1BatchPubdata {
+                    l1_batch_number: L1BatchNumber(1),
+                    pubdata: vec![1; 10],
+                },
+                L1BatchPubdata {
+                    l1_batch_number: L1BatchNumber(2),
+                    pubdata: vec![2; 10],
+                },
+            ]
+        );
+
+        let sent_at = Utc::now().naive_utc();
+        conn.data_availability_dal()
+            .insert_l1_batch_da(L1BatchNumber(1), "blob", sent_at)
+            .await
+            .unwrap();
+        let ready_batches = conn
+            .data_availability_dal()
+            .get_ready_for_da_dispatch_l1_batches(10)
+            .await
+            .unwrap();
+        assert_eq!(ready_batches.len(), 1);
+        assert_eq!(ready_batches[0].l1_batch_number, L1BatchNumber(2));
+
+        let blob = conn
+            .data_availability_dal()
+            .get_first_da_blob_awaiting_inclusion()
+            .await
+            .unwrap()
+            .expect("no blob awaiting inclusion");
+        assert_eq!(blob.l1_batch_number, L1BatchNumber(1));
+        assert_eq!(blob.blob_id, "blob");
+        assert_eq!(blob.inclusion_data, None);
+
+        conn.data_availability_dal()
+            .save_l1_batch_inclusion_data(L1BatchNumber(1), b"proof")
+            .await
+            .unwrap();
+        let inclusion_data = conn
+            .data_availability_dal()
+            .get_inclusion_data(L1BatchNumber(1))
+            .await
+            .unwrap();
+        assert_eq!(inclusion_data.as_deref(), Some(b"proof".as_slice()));
+        let blob = conn
+            .data_availability_dal()
+            .get_first_da_blob_awaiting_inclusion()
+            .await
+            .unwrap();
+        assert!(blob.is_none());
+    }
+}
@@ -7,7 +7,7 @@ use sqlx::{
 };
 use zksync_types::{
     aggregated_operations::AggregatedActionType,
-    eth_sender::{EthTx, TxHistory, TxHistoryToSend},
+    eth_sender::{EthTx, EthTxBlobSidecar, TxHistory, TxHistoryToSend},
     Address, L1BatchNumber, H256, U256,
 };
 
@@ -18,6 +18,15 @@ use crate::{
     StorageProcessor,
 };
 
+/// Operator account that new L1 transactions are sent from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ActiveOperator {
+    /// Operator address; `None` corresponds to the main operator account.
+    pub address: Option<Address>,
+    /// Minimum nonce of new transactions sent from the account.
+    pub base_nonce: u64,
+}
+
 #[derive(Debug)]
 pub struct EthSenderDal<'a, 'c> {
     pub(crate) storage: &'a mut StorageProcessor<'c>,
@@ -174,8 +183,12 @@ impl EthSenderDal<'_, '_> {
         tx_type: AggregatedActionType,
         contract_address: Address,
         predicted_gas_cost: u32,
+        blob_sidecar: Option<EthTxBlobSidecar>,
+        from_addr: Option<Address>,
     ) -> sqlx::Result<EthTx> {
         let address = format!("{:#x}", contract_address);
+        let blob_sidecar = blob_sidecar
+            .map(|sidecar| bincode::serialize(&sidecar).expect("Failed to serialize blob sidecar"));
         let eth_tx = sqlx::query_as!(
             StorageEthTx,
             r#"
@@ -186,11 +199,13 @@ impl EthSenderDal<'_, '_> {
                     tx_type,
                     contract_address,
                     predicted_gas_cost,
+                    blob_sidecar,
+                    from_addr,
                     created_at,
                     updated_at
                 )
             VALUES
-                ($1, $2, $3, $4, $5, NOW(), NOW())
+                ($1, $2, $3, $4, $5, $6, $7, NOW(), NOW())
             RETURNING
                 *
             "#,
@@ -198,7 +213,9 @@ impl EthSenderDal<'_, '_> {
             nonce as i64,
             tx_type.to_string(),
             address,
-            predicted_gas_cost as i64
+            predicted_gas_cost as i64,
+            blob_sidecar,
+            from_addr.as_ref().map(Address::as_bytes)
         )
         .fetch_one(self.storage.conn())
         .await?;
@@ -212,11 +229,16 @@ impl EthSenderDal<'_, '_> {
         priority_fee_per_gas: u64,
         tx_hash: H256,
         raw_signed_tx: &[u8],
+        blob_base_fee_per_gas: Option<u64>,
     ) -> anyhow::Result<Option<u32>> {
         let priority_fee_per_gas =
             i64::try_from(priority_fee_per_gas).context("Can't convert u64 to i64")?;
         let base_fee_per_gas =
             i64::try_from(base_fee_per_gas).context("Can't convert u64 to i64")?;
+        let blob_base_fee_per_gas = blob_base_fee_per_gas
+            .map(i64::try_from)
+            .transpose()
+            .context("Can't convert u64 to i64")?;
         let tx_hash = format!("{:#x}", tx_hash);
 
         Ok(sqlx::query!(
@@ -228,11 +250,12 @@ impl EthSenderDal<'_, '_> {
                     priority_fee_per_gas,
                     tx_hash,
                     signed_raw_tx,
+                    blob_base_fee_per_gas,
                     created_at,
                     updated_at
                 )
             VALUES
-                ($1, $2, $3, $4, $5, NOW(), NOW())
+                ($1, $2, $3, $4, $5, $6, NOW(), NOW())
             ON CONFLICT (tx_hash) DO NOTHING
             RETURNING
                 id
@@ -241,7 +264,8 @@ impl EthSenderDal<'_, '_> {
             base_fee_per_gas,
             priority_fee_per_gas,
             tx_hash,
-            raw_signed_tx
+            raw_signed_tx,
+            blob_base_fee_per_gas
         )
         .fetch_optional(self.storage.conn())
         .await?
@@ -285,7 +309,14 @@ impl EthSenderDal<'_, '_> {
         Ok(())
     }
 
-    pub async fn confirm_tx(&mut self, tx_hash: H256, gas_used: U256) -> anyhow::Result<()> {
+    /// Marks the transaction as confirmed. `effective_gas_price` is the price per gas paid by the transaction,
+    /// as reported in its receipt.
+    pub async fn confirm_tx(
+        &mut self,
+        tx_hash: H256,
+        gas_used: U256,
+        effective_gas_price: Option<U256>,
+    ) -> anyhow::Result<()> {
         let mut transaction = self
             .storage
             .start_transaction()
@@ -293,6 +324,10 @@ impl EthSenderDal<'_, '_> {
             .context("start_transaction()")?;
         let gas_used = i64::try_from(gas_used)
             .map_err(|err| anyhow::anyhow!("Can't convert U256 to i64: {err}"))?;
+        let effective_gas_price = effective_gas_price
+            .map(i64::try_from)
+            .transpose()
+            .map_err(|err| anyhow::anyhow!("Can't convert U256 to i64: {err}"))?;
         let tx_hash = format!("{:#x}", tx_hash);
         let ids = sqlx::query!(
             r#"
@@ -316,11 +351,13 @@ impl EthSenderDal<'_, '_> {
             UPDATE eth_txs
             SET
                 gas_used = $1,
-                confirmed_eth_tx_history_id = $2
+                effective_gas_price = $2,
+                confirmed_eth_tx_history_id = $3
             WHERE
-                id = $3
+                id = $4
             "#,
             gas_used,
+            effective_gas_price,
             ids.id,
             ids.eth_tx_id
         )
@@ -510,13 +547,38 @@ impl EthSenderDal<'_, '_> {
         Ok(history_item.map(|tx| tx.into()))
     }
 
-    pub async fn get_next_nonce(&mut self) -> sqlx::Result<Option<u64>> {
+    /// Returns the next nonce for the specified operator account (`None` corresponds to the main account)
+    /// based on the transactions stored in the DB.
+    pub async fn get_next_nonce(
+        &mut self,
+        from_addr: Option<Address>,
+    ) -> sqlx::Result<Option<u64>> {
         let row = sqlx::query!(
             r#"
             SELECT
-                nonce
+                MAX(nonce) AS "nonce"
             FROM
                 eth_txs
+            WHERE
+                from_addr IS NOT DISTINCT FROM $1
+            "#,
+            from_addr.as_ref().map(Address::as_bytes)
+        )
+        .fetch_one(self.storage.conn())
+        .await?;
+        Ok(row.nonce.map(|nonce| nonce as u64 + 1))
+    }
+
+    /// Returns the operator account that new transactions are sent from, or `None` if operator accounts
+    /// were never rotated (i.e., the main account is active).
+    pub async fn get_active_operator(&mut self) -> sqlx::Result<Option<ActiveOperator>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                to_addr,
+                base_nonce
+            FROM
+                eth_operator_rotations
             ORDER BY
                 id DESC
             LIMIT
@@ -525,7 +587,83 @@ impl EthSenderDal<'_, '_> {
         )
         .fetch_optional(self.storage.conn())
         .await?;
-        Ok(row.map(|row| row.nonce as u64 + 1))
+        Ok(row.map(|row| ActiveOperator {
+            address: row.to_addr.map(|addr| Address::from_slice(&addr)),
+            base_nonce: row.base_nonce as u64,
+        }))
+    }
+
+    pub async fn insert_operator_rotation(
+        &mut self,
+        from_addr: Option<Address>,
+        to: ActiveOperator,
+        reason: &str,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO
+                eth_operator_rotations (from_addr, to_addr, base_nonce, reason, created_at)
+            VALUES
+                ($1, $2, $3, $4, NOW())
+            "#,
+            from_addr.as_ref().map(Address::as_bytes),
+            to.address.as_ref().map(Address::as_bytes),
+            to.base_nonce as i64,
+            reason
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    /// Reassigns unconfirmed transactions of the `from_addr` operator account with nonces `>= min_nonce`
+    /// to the `to_addr` account. Reassigned transactions get sequential nonces starting from `first_nonce`
+    /// in the order of their IDs.
+    pub async fn reassign_eth_txs(
+        &mut self,
+        from_addr: Option<Address>,
+        min_nonce: u64,
+        to_addr: Option<Address>,
+        first_nonce: u64,
+    ) -> sqlx::Result<Vec<EthTx>> {
+        let txs = sqlx::query_as!(
+            StorageEthTx,
+            r#"
+            UPDATE eth_txs
+            SET
+                from_addr = $3,
+                nonce = $4 + reassigned.index - 1,
+                updated_at = NOW()
+            FROM
+                (
+                    SELECT
+                        id,
+                        ROW_NUMBER() OVER (
+                            ORDER BY
+                                id
+                        ) AS index
+                    FROM
+                        eth_txs
+                    WHERE
+                        from_addr IS NOT DISTINCT FROM $1
+                        AND nonce >= $2
+                        AND confirmed_eth_tx_history_id IS NULL
+                ) AS reassigned
+            WHERE
+                eth_txs.id = reassigned.id
+            RETURNING
+                eth_txs.*
+            "#,
+            from_addr.as_ref().map(Address::as_bytes),
+            min_nonce as i64,
+            to_addr.as_ref().map(Address::as_bytes),
+            first_nonce as i64
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+        let mut txs: Vec<EthTx> = txs.into_iter().map(Into::into).collect();
+        txs.sort_unstable_by_key(|tx| tx.id);
+        Ok(txs)
     }
 
     pub async fn mark_failed_transaction(&mut self, eth_tx_id: u32) -> sqlx::Result<()> {
@@ -2,6 +2,7 @@ use std::fmt;
 
 use sqlx::types::chrono::Utc;
 use zksync_types::{
+    event::logs_bloom,
     l2_to_l1_log::{L2ToL1Log, UserL2ToL1Log},
     tx::IncludedTxLocation,
     MiniblockNumber, VmEvent, H256,
@@ -88,6 +89,25 @@ impl EventsDal<'_, '_> {
         copy.send(buffer.as_bytes()).await.unwrap();
         // note: all the time spent in this function is spent in `copy.finish()`
         copy.finish().await.unwrap();
+
+        let all_events = all_block_events
+            .iter()
+            .flat_map(|(_, events)| events.iter().copied());
+        let bloom = logs_bloom(all_events);
+        sqlx::query!(
+            r#"
+            UPDATE miniblocks
+            SET
+                logs_bloom = $1
+            WHERE
+                number = $2
+            "#,
+            bloom.as_bytes(),
+            i64::from(block_number.0)
+        )
+        .execute(self.storage.conn())
+        .await
+        .unwrap();
     }
 
     /// Removes events with a block number strictly greater than the specified `block_number`.
@@ -215,6 +235,40 @@ impl EventsDal<'_, '_> {
         .fetch_all(self.storage.conn())
         .await
     }
+
+    pub(crate) async fn miniblock_l2_to_l1_logs(
+        &mut self,
+        miniblock: MiniblockNumber,
+    ) -> Result<Vec<StorageL2ToL1Log>, SqlxError> {
+        sqlx::query_as!(
+            StorageL2ToL1Log,
+            r#"
+            SELECT
+                miniblock_number,
+                log_index_in_miniblock,
+                log_index_in_tx,
+                tx_hash,
+                NULL::bytea AS "block_hash",
+                NULL::BIGINT AS "l1_batch_number?",
+                shard_id,
+                is_service,
+                tx_index_in_miniblock,
+                tx_index_in_l1_batch,
+                sender,
+                key,
+                value
+            FROM
+                l2_to_l1_logs
+            WHERE
+                miniblock_number = $1
+            ORDER BY
+                log_index_in_miniblock ASC
+            "#,
+            miniblock.0 as i64
+        )
+        .fetch_all(self.storage.conn())
+        .await
+    }
 }
 
 #[cfg(test)]
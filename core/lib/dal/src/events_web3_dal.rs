@@ -1,6 +1,7 @@
 use sqlx::Row;
 use zksync_types::{
     api::{GetLogsFilter, Log},
+    event::bloom_bit_indices,
     Address, MiniblockNumber, H256,
 };
 
@@ -22,7 +23,10 @@ impl EventsWeb3Dal<'_, '_> {
         offset: usize,
     ) -> Result<Option<MiniblockNumber>, SqlxError> {
         {
-            let (where_sql, arg_index) = self.build_get_logs_where_clause(filter);
+            let (mut where_sql, arg_index) = self.build_get_logs_where_clause(filter);
+            if let Some(bloom_sql) = Self::build_bloom_where_clause(filter) {
+                where_sql += &bloom_sql;
+            }
 
             let query = format!(
                 r#"
@@ -59,14 +63,43 @@ impl EventsWeb3Dal<'_, '_> {
     }
 
     /// Returns logs for given filter.
-    #[allow(clippy::type_complexity)]
     pub async fn get_logs(
         &mut self,
         filter: GetLogsFilter,
         limit: usize,
+    ) -> Result<Vec<Log>, SqlxError> {
+        self.get_logs_inner(filter, None, limit).await
+    }
+
+    /// Returns logs for given filter starting from the log with the specified index in `filter.from_block`
+    /// (inclusive). Used for paginated log queries.
+    pub async fn get_logs_from_index(
+        &mut self,
+        filter: GetLogsFilter,
+        start_log_index: u32,
+        limit: usize,
+    ) -> Result<Vec<Log>, SqlxError> {
+        self.get_logs_inner(filter, Some(start_log_index), limit)
+            .await
+    }
+
+    async fn get_logs_inner(
+        &mut self,
+        filter: GetLogsFilter,
+        start_log_index: Option<u32>,
+        limit: usize,
     ) -> Result<Vec<Log>, SqlxError> {
         {
-            let (where_sql, arg_index) = self.build_get_logs_where_clause(&filter);
+            let (mut where_sql, arg_index) = self.build_get_logs_where_clause(&filter);
+            if let Some(bloom_sql) = Self::build_bloom_where_clause(&filter) {
+                where_sql += &bloom_sql;
+            }
+            if let Some(start_log_index) = start_log_index {
+                where_sql += &format!(
+                    " AND (miniblock_number > {} OR event_index_in_block >= {})",
+                    filter.from_block.0 as i64, start_log_index as i64
+                );
+            }
 
             let query = format!(
                 r#"
@@ -103,6 +136,7 @@ impl EventsWeb3Dal<'_, '_> {
                 .instrument("get_logs")
                 .report_latency()
                 .with_arg("filter", &filter)
+                .with_arg("start_log_index", &start_log_index)
                 .with_arg("limit", &limit)
                 .fetch_all(self.storage.conn())
                 .await?;
@@ -130,6 +164,58 @@ impl EventsWeb3Dal<'_, '_> {
         (where_sql, arg_index)
     }
 
+    /// Builds an additional `WHERE` condition restricting events to miniblocks whose logs bloom
+    /// may match the filter. Miniblocks without a bloom (e.g., ones sealed before blooms were introduced)
+    /// are never skipped. Returns `None` if the filter has no address or topic restrictions.
+    fn build_bloom_where_clause(filter: &GetLogsFilter) -> Option<String> {
+        if filter.addresses.is_empty() && filter.topics.is_empty() {
+            return None;
+        }
+
+        let addresses = filter.addresses.iter().map(Address::as_bytes);
+        let address_group =
+            (!filter.addresses.is_empty()).then(|| Self::bloom_group_sql(addresses));
+        let topic_groups = filter
+            .topics
+            .iter()
+            .map(|(_, topics)| Self::bloom_group_sql(topics.iter().map(H256::as_bytes)));
+        let bloom_condition = address_group
+            .into_iter()
+            .chain(topic_groups)
+            .collect::<Vec<_>>()
+            .join(" AND ");
+
+        Some(format!(
+            " AND (miniblock_number IN (SELECT number FROM miniblocks \
+             WHERE number BETWEEN {} AND {} AND (logs_bloom IS NULL OR {})))",
+            filter.from_block.0 as i64, filter.to_block.0 as i64, bloom_condition
+        ))
+    }
+
+    /// Builds a condition matching the logs bloom if it may contain any of the provided values.
+    fn bloom_group_sql<'a>(values: impl Iterator<Item = &'a [u8]>) -> String {
+        let value_conditions: Vec<_> = values
+            .map(|value| {
+                let bit_conditions: Vec<_> = bloom_bit_indices(value)
+                    .into_iter()
+                    .map(|bit| {
+                        // Postgres numbers bits from the least significant bit of the first byte,
+                        // while the bloom indices are counted from the end of the bloom.
+                        let pg_bit = (255 - bit / 8) * 8 + bit % 8;
+                        format!("get_bit(logs_bloom, {pg_bit}) = 1")
+                    })
+                    .collect();
+                format!("({})", bit_conditions.join(" AND "))
+            })
+            .collect();
+
+        if value_conditions.is_empty() {
+            "FALSE".to_owned()
+        } else {
+            format!("({})", value_conditions.join(" OR "))
+        }
+    }
+
     pub async fn get_all_logs(
         &mut self,
         from_block: MiniblockNumber,
@@ -193,10 +279,12 @@ impl EventsWeb3Dal<'_, '_> {
 
 #[cfg(test)]
 mod tests {
-    use zksync_types::{Address, H256};
+    use zksync_types::{
+        tx::IncludedTxLocation, Address, L1BatchNumber, ProtocolVersion, VmEvent, H2048, H256,
+    };
 
     use super::*;
-    use crate::connection::ConnectionPool;
+    use crate::{connection::ConnectionPool, tests::create_miniblock_header};
 
     #[tokio::test]
     async fn test_build_get_logs_where_clause() {
@@ -218,4 +306,88 @@ mod tests {
         assert_eq!(actual_sql, expected_sql);
         assert_eq!(actual_arg_index, expected_arg_index);
     }
+
+    #[tokio::test]
+    async fn logs_bloom_is_used_to_skip_miniblocks() {
+        let connection_pool = ConnectionPool::test_pool().await;
+        let mut conn = connection_pool.access_storage().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(ProtocolVersion::default())
+            .await;
+
+        let event = VmEvent {
+            location: (L1BatchNumber(1), 0),
+            address: Address::repeat_byte(1),
+            indexed_topics: vec![H256::repeat_byte(2)],
+            value: vec![],
+        };
+        for number in 1..=2 {
+            conn.blocks_dal()
+                .insert_miniblock(&create_miniblock_header(number))
+                .await
+                .unwrap();
+            let tx_location = IncludedTxLocation {
+                tx_hash: H256::repeat_byte(number as u8),
+                tx_index_in_miniblock: 0,
+                tx_initiator_address: Address::default(),
+            };
+            conn.events_dal()
+                .save_events(MiniblockNumber(number), &[(tx_location, vec![&event])])
+                .await;
+        }
+
+        let filter = GetLogsFilter {
+            from_block: MiniblockNumber(1),
+            to_block: MiniblockNumber(2),
+            addresses: vec![event.address],
+            topics: vec![(1, vec![H256::repeat_byte(2)])],
+        };
+        let logs = conn
+            .events_web3_dal()
+            .get_logs(filter.clone(), 10)
+            .await
+            .unwrap();
+        assert_eq!(logs.len(), 2);
+
+        // Blooms not matching the filter must lead to miniblocks being skipped...
+        sqlx::query("UPDATE miniblocks SET logs_bloom = $1 WHERE number = 1")
+            .bind(H2048::zero().as_bytes())
+            .execute(conn.conn())
+            .await
+            .unwrap();
+        let logs = conn
+            .events_web3_dal()
+            .get_logs(filter.clone(), 10)
+            .await
+            .unwrap();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].block_number, Some(2.into()));
+        let block_number = conn
+            .events_web3_dal()
+            .get_log_block_number(&filter, 0)
+            .await
+            .unwrap();
+        assert_eq!(block_number, Some(MiniblockNumber(2)));
+
+        // ...while missing blooms must not.
+        sqlx::query("UPDATE miniblocks SET logs_bloom = NULL WHERE number = 1")
+            .execute(conn.conn())
+            .await
+            .unwrap();
+        let logs = conn.events_web3_dal().get_logs(filter, 10).await.unwrap();
+        assert_eq!(logs.len(), 2);
+
+        let non_matching_filter = GetLogsFilter {
+            from_block: MiniblockNumber(1),
+            to_block: MiniblockNumber(2),
+            addresses: vec![Address::repeat_byte(3)],
+            topics: vec![],
+        };
+        let logs = conn
+            .events_web3_dal()
+            .get_logs(non_matching_filter, 10)
+            .await
+            .unwrap();
+        assert!(logs.is_empty());
+    }
 }
@@ -0,0 +1,161 @@
+use zksync_types::{api::FeeModelSettings, MiniblockNumber};
+
+use crate::StorageProcessor;
+
+/// DAL for the fee model state that must survive node restarts and be shared among node components.
+#[derive(Debug)]
+pub struct FeeModelDal<'a, 'c> {
+    pub(crate) storage: &'a mut StorageProcessor<'c>,
+}
+
+impl FeeModelDal<'_, '_> {
+    /// Returns the congestion-based minimal L2 gas price, or `None` if it was never persisted.
+    pub async fn get_dynamic_l2_gas_price(&mut self) -> sqlx::Result<Option<u64>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                price
+            FROM
+                dynamic_l2_gas_price
+            "#
+        )
+        .fetch_optional(self.storage.conn())
+        .await?;
+        Ok(row.map(|row| row.price as u64))
+    }
+
+    /// Persists the congestion-based minimal L2 gas price adjusted after sealing the specified miniblock.
+    pub async fn set_dynamic_l2_gas_price(
+        &mut self,
+        miniblock_number: MiniblockNumber,
+        price: u64,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO
+                dynamic_l2_gas_price (fake_key, price, miniblock_number, updated_at)
+            VALUES
+                (TRUE, $1, $2, NOW())
+            ON CONFLICT (fake_key) DO
+            UPDATE
+            SET
+                price = excluded.price,
+                miniblock_number = excluded.miniblock_number,
+                updated_at = excluded.updated_at
+            "#,
+            price as i64,
+            i64::from(miniblock_number.0)
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    /// Returns fee model settings changed via the admin API, or `None` if they were never changed.
+    pub async fn get_fee_model_settings(&mut self) -> sqlx::Result<Option<FeeModelSettings>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                minimal_l2_gas_price,
+                l1_gas_price_scale_factor,
+                l1_pubdata_price_scale_factor
+            FROM
+                fee_model_settings
+            "#
+        )
+        .fetch_optional(self.storage.conn())
+        .await?;
+        Ok(row.map(|row| FeeModelSettings {
+            minimal_l2_gas_price: (row.minimal_l2_gas_price as u64).into(),
+            l1_gas_price_scale_factor: row.l1_gas_price_scale_factor,
+            l1_pubdata_price_scale_factor: row.l1_pubdata_price_scale_factor,
+        }))
+    }
+
+    pub async fn set_fee_model_settings(
+        &mut self,
+        settings: &FeeModelSettings,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO
+                fee_model_settings (
+                    fake_key,
+                    minimal_l2_gas_price,
+                    l1_gas_price_scale_factor,
+                    l1_pubdata_price_scale_factor,
+                    updated_at
+                )
+            VALUES
+                (TRUE, $1, $2, $3, NOW())
+            ON CONFLICT (fake_key) DO
+            UPDATE
+            SET
+                minimal_l2_gas_price = excluded.minimal_l2_gas_price,
+                l1_gas_price_scale_factor = excluded.l1_gas_price_scale_factor,
+                l1_pubdata_price_scale_factor = excluded.l1_pubdata_price_scale_factor,
+                updated_at = excluded.updated_at
+            "#,
+            settings.minimal_l2_gas_price.as_u64() as i64,
+            settings.l1_gas_price_scale_factor,
+            settings.l1_pubdata_price_scale_factor
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ConnectionPool;
+
+    #[tokio::test]
+    async fn persisting_dynamic_l2_gas_price() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut conn = pool.access_storage().await.unwrap();
+        let price = conn
+            .fee_model_dal()
+            .get_dynamic_l2_gas_price()
+            .await
+            .unwrap();
+        assert_eq!(price, None);
+
+        for (number, price) in [(1, 1_000), (2, 1_125)] {
+            conn.fee_model_dal()
+                .set_dynamic_l2_gas_price(MiniblockNumber(number), price)
+                .await
+                .unwrap();
+            let loaded_price = conn
+                .fee_model_dal()
+                .get_dynamic_l2_gas_price()
+                .await
+                .unwrap();
+            assert_eq!(loaded_price, Some(price));
+        }
+    }
+
+    #[tokio::test]
+    async fn persisting_fee_model_settings() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut conn = pool.access_storage().await.unwrap();
+        let settings = conn.fee_model_dal().get_fee_model_settings().await.unwrap();
+        assert_eq!(settings, None);
+
+        let mut settings = FeeModelSettings {
+            minimal_l2_gas_price: 100_000_000.into(),
+            l1_gas_price_scale_factor: 1.2,
+            l1_pubdata_price_scale_factor: 0.5,
+        };
+        for _ in 0..2 {
+            conn.fee_model_dal()
+                .set_fee_model_settings(&settings)
+                .await
+                .unwrap();
+            let loaded_settings = conn.fee_model_dal().get_fee_model_settings().await.unwrap();
+            assert_eq!(loaded_settings, Some(settings));
+            settings.l1_gas_price_scale_factor = 2.0;
+        }
+    }
+}
@@ -21,7 +21,7 @@ use zksync_types::{
     Nonce, PackedEthSignature, PriorityOpId, Transaction, EIP_1559_TX_TYPE, EIP_2930_TX_TYPE,
     EIP_712_TX_TYPE, H160, H256, PRIORITY_OPERATION_L2_TX_TYPE, PROTOCOL_UPGRADE_TX_TYPE, U256,
 };
-use zksync_utils::bigdecimal_to_u256;
+use zksync_utils::{bigdecimal_to_u256, h256_to_account_address};
 
 use crate::BigDecimal;
 
@@ -70,6 +70,11 @@ pub struct StorageTransaction {
 
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+
+    pub conditions: Option<serde_json::Value>,
+    pub bundle_hash: Option<Vec<u8>>,
+    pub bundle_index: Option<i32>,
+    pub is_private: bool,
 }
 
 impl From<StorageTransaction> for L1TxCommonData {
@@ -228,6 +233,7 @@ impl From<StorageTransaction> for L2TxCommonData {
             signature,
             hash,
             input,
+            conditions,
             ..
         } = tx;
 
@@ -236,7 +242,7 @@ impl From<StorageTransaction> for L2TxCommonData {
             paymaster_input,
         };
 
-        L2TxCommonData::new(
+        let mut common_data = L2TxCommonData::new(
             nonce,
             fee,
             Address::from_slice(&initiator_address),
@@ -247,7 +253,11 @@ impl From<StorageTransaction> for L2TxCommonData {
             input.expect("input data is mandatory for l2 transactions"),
             H256::from_slice(&hash),
             paymaster_params,
-        )
+        );
+        common_data.conditions = conditions.map(|conditions| {
+            serde_json::from_value(conditions).expect("invalid transaction conditions in DB")
+        });
+        common_data
     }
 }
 
@@ -479,6 +489,80 @@ impl From<StorageTransactionDetails> for TransactionDetails {
     }
 }
 
+/// Row of a transaction receipt query. Logs are not included and must be loaded separately.
+#[derive(Debug, Clone)]
+pub(crate) struct StorageTransactionReceipt {
+    pub tx_hash: Vec<u8>,
+    pub index_in_block: Option<i32>,
+    pub l1_batch_tx_index: Option<i32>,
+    pub block_number: i64,
+    pub error: Option<String>,
+    pub effective_gas_price: Option<BigDecimal>,
+    pub initiator_address: Vec<u8>,
+    pub transfer_to: Option<serde_json::Value>,
+    pub execute_contract_address: Option<serde_json::Value>,
+    pub tx_format: Option<i32>,
+    pub refunded_gas: i64,
+    pub gas_limit: Option<BigDecimal>,
+    pub block_hash: Vec<u8>,
+    pub l1_batch_number: Option<i64>,
+    pub contract_address: Option<Vec<u8>>,
+}
+
+impl From<StorageTransactionReceipt> for api::TransactionReceipt {
+    fn from(db_row: StorageTransactionReceipt) -> Self {
+        let status = db_row.error.map(|_| U64::zero()).unwrap_or_else(U64::one);
+
+        let tx_type = db_row.tx_format.map(U64::from).unwrap_or_default();
+        let transaction_index = db_row.index_in_block.map(U64::from).unwrap_or_default();
+
+        let block_hash = H256::from_slice(&db_row.block_hash);
+        api::TransactionReceipt {
+            transaction_hash: H256::from_slice(&db_row.tx_hash),
+            transaction_index,
+            block_hash,
+            block_number: db_row.block_number.into(),
+            l1_batch_tx_index: db_row.l1_batch_tx_index.map(U64::from),
+            l1_batch_number: db_row.l1_batch_number.map(U64::from),
+            from: H160::from_slice(&db_row.initiator_address),
+            to: db_row
+                .transfer_to
+                .or(db_row.execute_contract_address)
+                .map(|addr| {
+                    serde_json::from_value::<Address>(addr)
+                        .expect("invalid address value in the database")
+                })
+                // For better compatibility with various clients, we never return null.
+                .or_else(|| Some(Address::default())),
+            cumulative_gas_used: Default::default(), // TODO: Should be actually calculated (SMA-1183).
+            gas_used: {
+                let refunded_gas: U256 = db_row.refunded_gas.into();
+                db_row.gas_limit.map(|val| {
+                    let gas_limit = bigdecimal_to_u256(val);
+                    gas_limit - refunded_gas
+                })
+            },
+            effective_gas_price: Some(
+                db_row
+                    .effective_gas_price
+                    .map(bigdecimal_to_u256)
+                    .unwrap_or_default(),
+            ),
+            contract_address: db_row
+                .contract_address
+                .map(|addr| h256_to_account_address(&H256::from_slice(&addr))),
+            logs: vec![],
+            l2_to_l1_logs: vec![],
+            status,
+            root: block_hash,
+            logs_bloom: Default::default(),
+            // Even though the Rust SDK recommends us to supply "None" for legacy transactions
+            // we always supply some number anyway to have the same behavior as most popular RPCs
+            transaction_type: Some(tx_type),
+        }
+    }
+}
+
 pub fn web3_transaction_select_sql() -> &'static str {
     r#"
          transactions.hash as tx_hash,
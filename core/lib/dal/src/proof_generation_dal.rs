@@ -161,6 +161,24 @@ impl ProofGenerationDal<'_, '_> {
         result
     }
 
+    /// Returns the number of L1 batches waiting for proofs, i.e., ready to be proven or being proven.
+    pub async fn get_proving_queue_len(&mut self) -> sqlx::Result<usize> {
+        let count = sqlx::query_scalar!(
+            r#"
+            SELECT
+                COUNT(*) AS "count!"
+            FROM
+                proof_generation_details
+            WHERE
+                status IN ('ready_to_be_proven', 'picked_by_prover')
+            "#
+        )
+        .fetch_one(self.storage.conn())
+        .await?;
+
+        Ok(count as usize)
+    }
+
     pub async fn get_oldest_not_generated_batch(&mut self) -> Option<L1BatchNumber> {
         let result: Option<L1BatchNumber> = sqlx::query!(
             r#"
@@ -133,6 +133,35 @@ impl ProtocolVersionsDal<'_, '_> {
         (contracts, (row.id as u16).try_into().unwrap())
     }
 
+    /// Returns the ID and activation timestamp of the protocol version that will be the next one to become active
+    /// after `current_version`, i.e., the newer version with the earliest activation timestamp.
+    pub async fn next_scheduled_version(
+        &mut self,
+        current_version: ProtocolVersionId,
+    ) -> Option<(ProtocolVersionId, u64)> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                id,
+                timestamp
+            FROM
+                protocol_versions
+            WHERE
+                id > $1
+            ORDER BY
+                timestamp,
+                id
+            LIMIT
+                1
+            "#,
+            current_version as i32
+        )
+        .fetch_optional(self.storage.conn())
+        .await
+        .unwrap()?;
+        Some(((row.id as u16).try_into().unwrap(), row.timestamp as u64))
+    }
+
     pub async fn load_base_system_contracts_by_version_id(
         &mut self,
         version_id: u16,
@@ -272,6 +301,53 @@ impl ProtocolVersionsDal<'_, '_> {
         Some((id as u16).try_into().unwrap())
     }
 
+    /// Removes the protocol version together with its upgrade transaction, provided that the version
+    /// is not used by any miniblock yet. Returns `false` if the version is absent or in use.
+    pub async fn delete_unused_protocol_version(&mut self, id: ProtocolVersionId) -> bool {
+        let mut transaction = self.storage.start_transaction().await.unwrap();
+        let row = sqlx::query!(
+            r#"
+            DELETE FROM protocol_versions
+            WHERE
+                id = $1
+                AND NOT EXISTS (
+                    SELECT
+                        1
+                    FROM
+                        miniblocks
+                    WHERE
+                        protocol_version = $1
+                )
+            RETURNING
+                upgrade_tx_hash
+            "#,
+            id as i32
+        )
+        .fetch_optional(transaction.conn())
+        .await
+        .unwrap();
+        let Some(row) = row else {
+            return false;
+        };
+
+        if let Some(tx_hash) = row.upgrade_tx_hash {
+            sqlx::query!(
+                r#"
+                DELETE FROM transactions
+                WHERE
+                    hash = $1
+                    AND miniblock_number IS NULL
+                "#,
+                &tx_hash
+            )
+            .execute(transaction.conn())
+            .await
+            .unwrap();
+        }
+        transaction.commit().await.unwrap();
+        true
+    }
+
     pub async fn last_used_version_id(&mut self) -> Option<ProtocolVersionId> {
         let id = sqlx::query!(
             r#"
@@ -48,4 +48,38 @@ impl ProtocolVersionsWeb3Dal<'_, '_> {
 
         ProtocolVersion::from(storage_protocol_version)
     }
+
+    /// Returns protocol versions that are not used by any miniblock yet, ordered by ID. The `timestamp` of each version
+    /// is the earliest time at which it can be activated.
+    pub async fn get_scheduled_protocol_versions(&mut self) -> Vec<ProtocolVersion> {
+        let storage_protocol_versions: Vec<StorageProtocolVersion> = sqlx::query_as!(
+            StorageProtocolVersion,
+            r#"
+            SELECT
+                *
+            FROM
+                protocol_versions
+            WHERE
+                id > COALESCE(
+                    (
+                        SELECT
+                            MAX(protocol_version)
+                        FROM
+                            miniblocks
+                    ),
+                    -1
+                )
+            ORDER BY
+                id
+            "#,
+        )
+        .fetch_all(self.storage.conn())
+        .await
+        .unwrap();
+
+        storage_protocol_versions
+            .into_iter()
+            .map(ProtocolVersion::from)
+            .collect()
+    }
 }
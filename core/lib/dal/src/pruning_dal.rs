@@ -0,0 +1,459 @@
+use std::ops;
+
+use zksync_types::{L1BatchNumber, MiniblockNumber};
+
+use crate::{instrument::InstrumentExt, StorageProcessor};
+
+/// Information about pruned Postgres data.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PruningInfo {
+    /// Last L1 batch for which data was pruned. `None` if no data was pruned yet.
+    pub last_pruned_l1_batch: Option<L1BatchNumber>,
+    /// Last miniblock for which data was pruned. `None` if no data was pruned yet.
+    pub last_pruned_miniblock: Option<MiniblockNumber>,
+}
+
+impl PruningInfo {
+    /// Returns the first miniblock with non-pruned data.
+    pub fn first_miniblock(&self) -> MiniblockNumber {
+        self.last_pruned_miniblock
+            .map_or(MiniblockNumber(0), |number| number + 1)
+    }
+
+    /// Returns the first L1 batch with non-pruned data.
+    pub fn first_l1_batch(&self) -> L1BatchNumber {
+        self.last_pruned_l1_batch
+            .map_or(L1BatchNumber(0), |number| number + 1)
+    }
+}
+
+/// Number of rows deleted during pruning of a range of L1 batches.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PruningStats {
+    pub deleted_events: u64,
+    pub deleted_l2_to_l1_logs: u64,
+    pub deleted_call_traces: u64,
+    pub deleted_transactions: u64,
+    pub deleted_storage_logs: u64,
+}
+
+/// DAL for pruning historical data in Postgres.
+///
+/// Pruning only removes data that can be large and is not required to continue node operation:
+/// events, L2-to-L1 logs, call traces, L2 transactions and storage logs overwritten by later logs.
+/// Miniblock and L1 batch headers, priority operations, protocol upgrade transactions and the latest
+/// storage log for each key are retained.
+#[derive(Debug)]
+pub struct PruningDal<'a, 'c> {
+    pub(crate) storage: &'a mut StorageProcessor<'c>,
+}
+
+impl PruningDal<'_, '_> {
+    pub async fn get_pruning_info(&mut self) -> sqlx::Result<PruningInfo> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                pruned_l1_batch,
+                pruned_miniblock
+            FROM
+                pruning_log
+            ORDER BY
+                pruned_l1_batch DESC
+            LIMIT
+                1
+            "#
+        )
+        .instrument("get_pruning_info")
+        .fetch_optional(self.storage.conn())
+        .await?;
+
+        Ok(row.map_or_else(PruningInfo::default, |row| PruningInfo {
+            last_pruned_l1_batch: Some(L1BatchNumber(row.pruned_l1_batch as u32)),
+            last_pruned_miniblock: Some(MiniblockNumber(row.pruned_miniblock as u32)),
+        }))
+    }
+
+    /// Returns the last L1 batch with the number not exceeding `max_l1_batch` that was sealed before
+    /// the specified UNIX timestamp (in seconds).
+    pub async fn get_last_l1_batch_sealed_before(
+        &mut self,
+        max_l1_batch: L1BatchNumber,
+        timestamp: u64,
+    ) -> sqlx::Result<Option<L1BatchNumber>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                number
+            FROM
+                l1_batches
+            WHERE
+                number <= $1
+                AND timestamp < $2
+            ORDER BY
+                number DESC
+            LIMIT
+                1
+            "#,
+            i64::from(max_l1_batch.0),
+            timestamp as i64
+        )
+        .instrument("get_last_l1_batch_sealed_before")
+        .with_arg("max_l1_batch", &max_l1_batch)
+        .fetch_optional(self.storage.conn())
+        .await?;
+
+        Ok(row.map(|row| L1BatchNumber(row.number as u32)))
+    }
+
+    /// Prunes data for all L1 batches up to and including `last_l1_batch_to_prune`, which must end
+    /// with `last_miniblock_to_prune`. The caller is responsible for ensuring that the pruned L1 batches
+    /// are executed on L1 and are not required by other components (e.g., the Merkle tree or the snapshot creator).
+    pub async fn prune_batches_range(
+        &mut self,
+        last_l1_batch_to_prune: L1BatchNumber,
+        last_miniblock_to_prune: MiniblockNumber,
+    ) -> sqlx::Result<PruningStats> {
+        let mut transaction = self.storage.start_transaction().await?;
+        let pruning_info = transaction.pruning_dal().get_pruning_info().await?;
+        let first_miniblock_to_prune = pruning_info.first_miniblock();
+        if first_miniblock_to_prune > last_miniblock_to_prune {
+            return Ok(PruningStats::default());
+        }
+        let miniblocks = first_miniblock_to_prune..=last_miniblock_to_prune;
+
+        let mut dal = transaction.pruning_dal();
+        let stats = PruningStats {
+            deleted_events: dal.delete_events(miniblocks.clone()).await?,
+            deleted_l2_to_l1_logs: dal.delete_l2_to_l1_logs(miniblocks.clone()).await?,
+            // Call traces must be deleted before transactions since they are looked up by the transaction hash.
+            deleted_call_traces: dal.delete_call_traces(miniblocks.clone()).await?,
+            deleted_transactions: dal.delete_transactions(miniblocks.clone()).await?,
+            deleted_storage_logs: dal.delete_overwritten_storage_logs(miniblocks).await?,
+        };
+        dal.insert_pruning_log(last_l1_batch_to_prune, last_miniblock_to_prune)
+            .await?;
+        transaction.commit().await?;
+        Ok(stats)
+    }
+
+    async fn delete_events(
+        &mut self,
+        miniblocks: ops::RangeInclusive<MiniblockNumber>,
+    ) -> sqlx::Result<u64> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM events
+            WHERE
+                miniblock_number BETWEEN $1 AND $2
+            "#,
+            i64::from(miniblocks.start().0),
+            i64::from(miniblocks.end().0)
+        )
+        .instrument("delete_events")
+        .with_arg("miniblocks", &miniblocks)
+        .report_latency()
+        .execute(self.storage.conn())
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn delete_l2_to_l1_logs(
+        &mut self,
+        miniblocks: ops::RangeInclusive<MiniblockNumber>,
+    ) -> sqlx::Result<u64> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM l2_to_l1_logs
+            WHERE
+                miniblock_number BETWEEN $1 AND $2
+            "#,
+            i64::from(miniblocks.start().0),
+            i64::from(miniblocks.end().0)
+        )
+        .instrument("delete_l2_to_l1_logs")
+        .with_arg("miniblocks", &miniblocks)
+        .report_latency()
+        .execute(self.storage.conn())
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn delete_call_traces(
+        &mut self,
+        miniblocks: ops::RangeInclusive<MiniblockNumber>,
+    ) -> sqlx::Result<u64> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM call_traces
+            WHERE
+                tx_hash IN (
+                    SELECT
+                        hash
+                    FROM
+                        transactions
+                    WHERE
+                        miniblock_number BETWEEN $1 AND $2
+                )
+            "#,
+            i64::from(miniblocks.start().0),
+            i64::from(miniblocks.end().0)
+        )
+        .instrument("delete_call_traces")
+        .with_arg("miniblocks", &miniblocks)
+        .report_latency()
+        .execute(self.storage.conn())
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Deletes L2 transactions in the specified miniblocks. Priority operations and protocol upgrade transactions
+    /// are retained since they are used to determine the next priority operation and to load upgrade transactions.
+    async fn delete_transactions(
+        &mut self,
+        miniblocks: ops::RangeInclusive<MiniblockNumber>,
+    ) -> sqlx::Result<u64> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM transactions
+            WHERE
+                miniblock_number BETWEEN $1 AND $2
+                AND NOT is_priority
+                AND hash NOT IN (
+                    SELECT
+                        upgrade_tx_hash
+                    FROM
+                        protocol_versions
+                    WHERE
+                        upgrade_tx_hash IS NOT NULL
+                )
+            "#,
+            i64::from(miniblocks.start().0),
+            i64::from(miniblocks.end().0)
+        )
+        .instrument("delete_transactions")
+        .with_arg("miniblocks", &miniblocks)
+        .report_latency()
+        .execute(self.storage.conn())
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Deletes storage logs that are overwritten by later logs with the miniblock number not exceeding the end
+    /// of the specified range. As a result, storage values remain correct for all non-pruned miniblocks.
+    async fn delete_overwritten_storage_logs(
+        &mut self,
+        miniblocks: ops::RangeInclusive<MiniblockNumber>,
+    ) -> sqlx::Result<u64> {
+        // First, retain only the latest log for each key in the range.
+        let in_range_result = sqlx::query!(
+            r#"
+            DELETE FROM storage_logs USING (
+                SELECT
+                    hashed_key,
+                    MAX(ARRAY[miniblock_number, operation_number::BIGINT]) AS op
+                FROM
+                    storage_logs
+                WHERE
+                    miniblock_number BETWEEN $1 AND $2
+                GROUP BY
+                    hashed_key
+            ) AS last_storage_logs
+            WHERE
+                storage_logs.miniblock_number BETWEEN $1 AND $2
+                AND last_storage_logs.hashed_key = storage_logs.hashed_key
+                AND (
+                    storage_logs.miniblock_number != last_storage_logs.op[1]
+                    OR storage_logs.operation_number != last_storage_logs.op[2]
+                )
+            "#,
+            i64::from(miniblocks.start().0),
+            i64::from(miniblocks.end().0)
+        )
+        .instrument("delete_overwritten_storage_logs_in_range")
+        .with_arg("miniblocks", &miniblocks)
+        .report_latency()
+        .execute(self.storage.conn())
+        .await?;
+
+        // Second, delete logs before the range for keys overwritten in the range. Since the previous query
+        // has retained a single log per key in the range, there are no duplicate keys in the subquery.
+        let before_range_result = sqlx::query!(
+            r#"
+            DELETE FROM storage_logs USING (
+                SELECT
+                    hashed_key
+                FROM
+                    storage_logs
+                WHERE
+                    miniblock_number BETWEEN $1 AND $2
+            ) AS keys_in_range
+            WHERE
+                storage_logs.miniblock_number < $1
+                AND storage_logs.hashed_key = keys_in_range.hashed_key
+            "#,
+            i64::from(miniblocks.start().0),
+            i64::from(miniblocks.end().0)
+        )
+        .instrument("delete_overwritten_storage_logs_before_range")
+        .with_arg("miniblocks", &miniblocks)
+        .report_latency()
+        .execute(self.storage.conn())
+        .await?;
+
+        Ok(in_range_result.rows_affected() + before_range_result.rows_affected())
+    }
+
+    async fn insert_pruning_log(
+        &mut self,
+        last_l1_batch_to_prune: L1BatchNumber,
+        last_miniblock_to_prune: MiniblockNumber,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO
+                pruning_log (pruned_l1_batch, pruned_miniblock, created_at, updated_at)
+            VALUES
+                ($1, $2, NOW(), NOW())
+            "#,
+            i64::from(last_l1_batch_to_prune.0),
+            i64::from(last_miniblock_to_prune.0)
+        )
+        .instrument("insert_pruning_log")
+        .with_arg("last_l1_batch_to_prune", &last_l1_batch_to_prune)
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_contracts::BaseSystemContractsHashes;
+    use zksync_types::{
+        block::{BlockGasCount, L1BatchHeader},
+        AccountTreeId, Address, ProtocolVersion, ProtocolVersionId, StorageKey, StorageLog, H256,
+    };
+
+    use super::*;
+    use crate::{tests::create_miniblock_header, ConnectionPool};
+
+    async fn insert_l1_batch(conn: &mut StorageProcessor<'_>, number: u32, logs: Vec<StorageLog>) {
+        let header = L1BatchHeader::new(
+            L1BatchNumber(number),
+            number.into(),
+            Address::default(),
+            BaseSystemContractsHashes::default(),
+            ProtocolVersionId::default(),
+        );
+        conn.blocks_dal()
+            .insert_l1_batch(&header, &[], BlockGasCount::default(), &[], &[], 0)
+            .await
+            .unwrap();
+        conn.blocks_dal()
+            .insert_miniblock(&create_miniblock_header(number))
+            .await
+            .unwrap();
+        conn.storage_logs_dal()
+            .insert_storage_logs(MiniblockNumber(number), &[(H256::zero(), logs)])
+            .await;
+        conn.blocks_dal()
+            .mark_miniblocks_as_executed_in_l1_batch(L1BatchNumber(number))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn pruning_storage_logs() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut conn = pool.access_storage().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(ProtocolVersion::default())
+            .await;
+
+        let account = AccountTreeId::new(Address::repeat_byte(1));
+        let keys: Vec<_> = (0..3)
+            .map(|i| StorageKey::new(account, H256::from_low_u64_be(i)))
+            .collect();
+        let logs = [
+            vec![
+                StorageLog::new_write_log(keys[0], H256::repeat_byte(1)),
+                StorageLog::new_write_log(keys[1], H256::repeat_byte(1)),
+                StorageLog::new_write_log(keys[0], H256::repeat_byte(2)),
+            ],
+            vec![StorageLog::new_write_log(keys[0], H256::repeat_byte(3))],
+            vec![
+                StorageLog::new_write_log(keys[0], H256::repeat_byte(4)),
+                StorageLog::new_write_log(keys[2], H256::repeat_byte(4)),
+            ],
+        ];
+        for (i, logs) in logs.into_iter().enumerate() {
+            insert_l1_batch(&mut conn, i as u32 + 1, logs).await;
+        }
+
+        let pruning_info = conn.pruning_dal().get_pruning_info().await.unwrap();
+        assert_eq!(pruning_info, PruningInfo::default());
+        assert_eq!(pruning_info.first_miniblock(), MiniblockNumber(0));
+
+        let stats = conn
+            .pruning_dal()
+            .prune_batches_range(L1BatchNumber(1), MiniblockNumber(1))
+            .await
+            .unwrap();
+        assert_eq!(stats.deleted_storage_logs, 1);
+        let logs = conn
+            .storage_logs_dal()
+            .get_miniblock_storage_logs(MiniblockNumber(1))
+            .await;
+        let logs: Vec<_> = logs
+            .into_iter()
+            .map(|(key, value, _)| (key, value))
+            .collect();
+        assert_eq!(
+            logs,
+            [
+                (keys[1].hashed_key(), H256::repeat_byte(1)),
+                (keys[0].hashed_key(), H256::repeat_byte(2)),
+            ]
+        );
+
+        let stats = conn
+            .pruning_dal()
+            .prune_batches_range(L1BatchNumber(2), MiniblockNumber(2))
+            .await
+            .unwrap();
+        assert_eq!(stats.deleted_storage_logs, 1);
+        let pruning_info = conn.pruning_dal().get_pruning_info().await.unwrap();
+        assert_eq!(pruning_info.first_miniblock(), MiniblockNumber(3));
+        assert_eq!(pruning_info.first_l1_batch(), L1BatchNumber(3));
+
+        let logs = conn
+            .storage_logs_dal()
+            .get_miniblock_storage_logs(MiniblockNumber(1))
+            .await;
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].0, keys[1].hashed_key());
+        for (key, expected_value) in [(&keys[0], 3), (&keys[1], 1), (&keys[2], 0)] {
+            let value = conn
+                .storage_web3_dal()
+                .get_historical_value_unchecked(key, MiniblockNumber(2))
+                .await
+                .unwrap();
+            assert_eq!(value, H256::repeat_byte(expected_value));
+        }
+
+        // Non-pruned logs must not be affected.
+        let logs = conn
+            .storage_logs_dal()
+            .get_miniblock_storage_logs(MiniblockNumber(3))
+            .await;
+        assert_eq!(logs.len(), 2);
+
+        // Repeated pruning is a no-op.
+        let stats = conn
+            .pruning_dal()
+            .prune_batches_range(L1BatchNumber(2), MiniblockNumber(2))
+            .await
+            .unwrap();
+        assert_eq!(stats, PruningStats::default());
+    }
+}
@@ -0,0 +1,384 @@
+use std::str::FromStr;
+
+use zksync_types::{
+    aggregated_operations::AggregatedActionType, api::L1BatchRevenue, L1BatchNumber, U256,
+};
+use zksync_utils::{bigdecimal_to_u256, u256_to_big_decimal};
+
+use crate::StorageProcessor;
+
+/// Cost of a confirmed L1 transaction processing an L1 batch.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct L1BatchEthTxCost {
+    pub tx_type: AggregatedActionType,
+    pub gas_used: u64,
+    /// Price per gas paid by the transaction. If the price wasn't recorded on confirmation, this is the max fee
+    /// per gas of the confirmed transaction attempt.
+    pub gas_price: u64,
+    /// Number of L1 batches processed by the transaction.
+    pub l1_batch_count: u64,
+    /// Max fee per blob gas of the confirmed transaction attempt. Set only if the transaction has published pubdata
+    /// in blobs.
+    pub blob_base_fee_per_gas: Option<u64>,
+}
+
+/// Data about an L1 batch required to compute its revenue.
+#[derive(Debug, Clone, PartialEq)]
+pub struct L1BatchRevenueInputs {
+    pub collected_fees: U256,
+    pub pubdata_len: usize,
+    /// Whether the batch pubdata was dispatched to an external data availability layer rather than to L1.
+    pub pubdata_in_da_layer: bool,
+    /// Costs of confirmed L1 transactions processing the batch.
+    pub eth_tx_costs: Vec<L1BatchEthTxCost>,
+}
+
+/// Revenue and expenditures summed over all L1 batches with computed revenue.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RevenueTotals {
+    pub collected_fees: U256,
+    pub pubdata_cost: U256,
+    pub l1_cost: U256,
+}
+
+#[derive(Debug)]
+pub struct RevenueDal<'a, 'c> {
+    pub(crate) storage: &'a mut StorageProcessor<'c>,
+}
+
+impl RevenueDal<'_, '_> {
+    pub async fn get_last_l1_batch_with_revenue(&mut self) -> sqlx::Result<Option<L1BatchNumber>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                MAX(l1_batch_number) AS "number"
+            FROM
+                l1_batch_revenue
+            "#
+        )
+        .fetch_one(self.storage.conn())
+        .await?;
+        Ok(row.number.map(|number| L1BatchNumber(number as u32)))
+    }
+
+    /// Loads data required to compute revenue for the specified L1 batch. Returns `None` if the batch doesn't exist.
+    pub async fn get_l1_batch_revenue_inputs(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+    ) -> sqlx::Result<Option<L1BatchRevenueInputs>> {
+        let Some(row) = sqlx::query!(
+            r#"
+            SELECT
+                COALESCE(LENGTH(pubdata_input), 0) AS "pubdata_len!",
+                (
+                    SELECT
+                        COALESCE(
+                            SUM(
+                                (gas_limit - refunded_gas) * COALESCE(effective_gas_price, 0)
+                            ),
+                            0
+                        )
+                    FROM
+                        transactions
+                    WHERE
+                        transactions.l1_batch_number = l1_batches.number
+                ) AS "collected_fees!",
+                EXISTS (
+                    SELECT
+                        1
+                    FROM
+                        data_availability
+                    WHERE
+                        data_availability.l1_batch_number = l1_batches.number
+                ) AS "pubdata_in_da_layer!"
+            FROM
+                l1_batches
+            WHERE
+                number = $1
+            "#,
+            i64::from(l1_batch_number.0)
+        )
+        .fetch_optional(self.storage.conn())
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let eth_tx_costs = sqlx::query!(
+            r#"
+            SELECT
+                eth_txs.tx_type,
+                eth_txs.gas_used AS "gas_used!",
+                COALESCE(
+                    eth_txs.effective_gas_price,
+                    eth_txs_history.base_fee_per_gas + eth_txs_history.priority_fee_per_gas
+                ) AS "gas_price!",
+                (
+                    SELECT
+                        COUNT(*)
+                    FROM
+                        l1_batches AS batches
+                    WHERE
+                        batches.eth_commit_tx_id = eth_txs.id
+                        OR batches.eth_prove_tx_id = eth_txs.id
+                        OR batches.eth_execute_tx_id = eth_txs.id
+                ) AS "l1_batch_count!",
+                CASE
+                    WHEN eth_txs.blob_sidecar IS NOT NULL THEN eth_txs_history.blob_base_fee_per_gas
+                END AS blob_base_fee_per_gas
+            FROM
+                l1_batches
+                INNER JOIN eth_txs ON eth_txs.id IN (
+                    l1_batches.eth_commit_tx_id,
+                    l1_batches.eth_prove_tx_id,
+                    l1_batches.eth_execute_tx_id
+                )
+                INNER JOIN eth_txs_history ON eth_txs_history.id = eth_txs.confirmed_eth_tx_history_id
+            WHERE
+                l1_batches.number = $1
+                AND eth_txs.gas_used IS NOT NULL
+            "#,
+            i64::from(l1_batch_number.0)
+        )
+        .fetch_all(self.storage.conn())
+        .await?
+        .into_iter()
+        .map(|row| L1BatchEthTxCost {
+            tx_type: AggregatedActionType::from_str(&row.tx_type)
+                .expect("Incorrect aggregated action type in DB"),
+            gas_used: row.gas_used as u64,
+            gas_price: row.gas_price as u64,
+            l1_batch_count: row.l1_batch_count as u64,
+            blob_base_fee_per_gas: row.blob_base_fee_per_gas.map(|fee| fee as u64),
+        })
+        .collect();
+
+        Ok(Some(L1BatchRevenueInputs {
+            collected_fees: bigdecimal_to_u256(row.collected_fees),
+            pubdata_len: row.pubdata_len as usize,
+            pubdata_in_da_layer: row.pubdata_in_da_layer,
+            eth_tx_costs,
+        }))
+    }
+
+    pub async fn insert_l1_batch_revenue(&mut self, revenue: &L1BatchRevenue) -> sqlx::Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO
+                l1_batch_revenue (
+                    l1_batch_number,
+                    collected_fees,
+                    pubdata_cost,
+                    commit_cost,
+                    prove_cost,
+                    execute_cost,
+                    created_at
+                )
+            VALUES
+                ($1, $2, $3, $4, $5, $6, NOW())
+            ON CONFLICT (l1_batch_number) DO NOTHING
+            "#,
+            i64::from(revenue.l1_batch_number.0),
+            u256_to_big_decimal(revenue.collected_fees),
+            u256_to_big_decimal(revenue.pubdata_cost),
+            u256_to_big_decimal(revenue.commit_cost),
+            u256_to_big_decimal(revenue.prove_cost),
+            u256_to_big_decimal(revenue.execute_cost)
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_l1_batch_revenue(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+    ) -> sqlx::Result<Option<L1BatchRevenue>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                collected_fees,
+                pubdata_cost,
+                commit_cost,
+                prove_cost,
+                execute_cost
+            FROM
+                l1_batch_revenue
+            WHERE
+                l1_batch_number = $1
+            "#,
+            i64::from(l1_batch_number.0)
+        )
+        .fetch_optional(self.storage.conn())
+        .await?;
+
+        Ok(row.map(|row| L1BatchRevenue {
+            l1_batch_number,
+            collected_fees: bigdecimal_to_u256(row.collected_fees),
+            pubdata_cost: bigdecimal_to_u256(row.pubdata_cost),
+            commit_cost: bigdecimal_to_u256(row.commit_cost),
+            prove_cost: bigdecimal_to_u256(row.prove_cost),
+            execute_cost: bigdecimal_to_u256(row.execute_cost),
+        }))
+    }
+
+    pub async fn get_revenue_totals(&mut self) -> sqlx::Result<RevenueTotals> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                COALESCE(SUM(collected_fees), 0) AS "collected_fees!",
+                COALESCE(SUM(pubdata_cost), 0) AS "pubdata_cost!",
+                COALESCE(SUM(pubdata_cost + commit_cost + prove_cost + execute_cost), 0) AS "l1_cost!"
+            FROM
+                l1_batch_revenue
+            "#
+        )
+        .fetch_one(self.storage.conn())
+        .await?;
+
+        Ok(RevenueTotals {
+            collected_fees: bigdecimal_to_u256(row.collected_fees),
+            pubdata_cost: bigdecimal_to_u256(row.pubdata_cost),
+            l1_cost: bigdecimal_to_u256(row.l1_cost),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_contracts::BaseSystemContractsHashes;
+    use zksync_types::{
+        block::{BlockGasCount, L1BatchHeader},
+        Address, ProtocolVersion, ProtocolVersionId, H256,
+    };
+
+    use super::*;
+    use crate::ConnectionPool;
+
+    #[tokio::test]
+    async fn loading_revenue_inputs() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut conn = pool.access_storage().await.unwrap();
+        conn.blocks_dal()
+            .delete_l1_batches(L1BatchNumber(0))
+            .await
+            .unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(ProtocolVersion::default())
+            .await;
+
+        for number in [1, 2] {
+            let header = L1BatchHeader::new(
+                L1BatchNumber(number),
+                100,
+                Address::default(),
+                BaseSystemContractsHashes::default(),
+                ProtocolVersionId::latest(),
+            );
+            conn.blocks_dal()
+                .insert_l1_batch(&header, &[], BlockGasCount::default(), &[], &[], 0)
+                .await
+                .unwrap();
+        }
+        let inputs = conn
+            .revenue_dal()
+            .get_l1_batch_revenue_inputs(L1BatchNumber(1))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(inputs.collected_fees, U256::zero());
+        assert!(!inputs.pubdata_in_da_layer);
+        assert!(inputs.eth_tx_costs.is_empty());
+        let inputs = conn
+            .revenue_dal()
+            .get_l1_batch_revenue_inputs(L1BatchNumber(3))
+            .await
+            .unwrap();
+        assert!(inputs.is_none());
+
+        // Commit both batches in a single transaction.
+        let eth_tx = conn
+            .eth_sender_dal()
+            .save_eth_tx(
+                0,
+                vec![],
+                AggregatedActionType::Commit,
+                Address::zero(),
+                0,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        conn.blocks_dal()
+            .set_eth_tx_id(
+                L1BatchNumber(1)..=L1BatchNumber(2),
+                eth_tx.id,
+                AggregatedActionType::Commit,
+            )
+            .await
+            .unwrap();
+        let tx_hash = H256::repeat_byte(1);
+        conn.eth_sender_dal()
+            .insert_tx_history(eth_tx.id, 100, 10, tx_hash, &[], None)
+            .await
+            .unwrap();
+        let inputs = conn
+            .revenue_dal()
+            .get_l1_batch_revenue_inputs(L1BatchNumber(1))
+            .await
+            .unwrap()
+            .unwrap();
+        // The transaction isn't confirmed yet.
+        assert!(inputs.eth_tx_costs.is_empty());
+
+        conn.eth_sender_dal()
+            .confirm_tx(tx_hash, 50_000.into(), None)
+            .await
+            .unwrap();
+        let inputs = conn
+            .revenue_dal()
+            .get_l1_batch_revenue_inputs(L1BatchNumber(1))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            inputs.eth_tx_costs,
+            [L1BatchEthTxCost {
+                tx_type: AggregatedActionType::Commit,
+                gas_used: 50_000,
+                gas_price: 110,
+                l1_batch_count: 2,
+                blob_base_fee_per_gas: None,
+            }]
+        );
+
+        let revenue = L1BatchRevenue {
+            l1_batch_number: L1BatchNumber(1),
+            collected_fees: 1_000_000.into(),
+            pubdata_cost: 250_000.into(),
+            commit_cost: 2_500_000.into(),
+            prove_cost: 0.into(),
+            execute_cost: 0.into(),
+        };
+        conn.revenue_dal()
+            .insert_l1_batch_revenue(&revenue)
+            .await
+            .unwrap();
+        let last_l1_batch = conn
+            .revenue_dal()
+            .get_last_l1_batch_with_revenue()
+            .await
+            .unwrap();
+        assert_eq!(last_l1_batch, Some(L1BatchNumber(1)));
+        let loaded_revenue = conn
+            .revenue_dal()
+            .get_l1_batch_revenue(L1BatchNumber(1))
+            .await
+            .unwrap();
+        assert_eq!(loaded_revenue, Some(revenue));
+        let totals = conn.revenue_dal().get_revenue_totals().await.unwrap();
+        assert_eq!(totals.collected_fees, 1_000_000.into());
+        assert_eq!(totals.l1_cost, 2_750_000.into());
+    }
+}
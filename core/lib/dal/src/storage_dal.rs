@@ -245,6 +245,41 @@ impl StorageDal<'_, '_> {
         .map(|row| H256::from_slice(&row.value))
     }
 
+    /// Gets the current storage values at the specified `keys` in a single query. The returned map is keyed
+    /// by hashed keys; keys missing from the storage are not included into it.
+    pub async fn get_by_keys(&mut self, keys: &[StorageKey]) -> HashMap<H256, H256> {
+        let hashed_keys: Vec<_> = keys.iter().map(StorageKey::hashed_key).collect();
+        let hashed_keys: Vec<_> = hashed_keys.iter().map(H256::as_bytes).collect();
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                hashed_key,
+                value
+            FROM
+                storage
+            WHERE
+                hashed_key = ANY ($1)
+            "#,
+            &hashed_keys as &[&[u8]]
+        )
+        .instrument("get_by_keys")
+        .report_latency()
+        .with_arg("keys.len", &keys.len())
+        .fetch_all(self.storage.conn())
+        .await
+        .unwrap();
+
+        rows.into_iter()
+            .map(|row| {
+                (
+                    H256::from_slice(&row.hashed_key),
+                    H256::from_slice(&row.value),
+                )
+            })
+            .collect()
+    }
+
     /// Removes all factory deps with a miniblock number strictly greater than the specified `block_number`.
     pub async fn rollback_factory_deps(&mut self, block_number: MiniblockNumber) {
         sqlx::query!(
@@ -502,6 +502,110 @@ impl StorageLogsDal<'_, '_> {
             .collect()
     }
 
+    /// Returns values for the specified keys right before the storage operation with the specified
+    /// number was applied in the miniblock, i.e., the state observed by the transaction that performed
+    /// this operation.
+    pub async fn get_storage_values_before_operation(
+        &mut self,
+        hashed_keys: &[H256],
+        miniblock_number: MiniblockNumber,
+        operation_number: u32,
+    ) -> sqlx::Result<HashMap<H256, Option<H256>>> {
+        let hashed_keys: Vec<_> = hashed_keys.iter().map(H256::as_bytes).collect();
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                u.hashed_key AS "hashed_key!",
+                (
+                    SELECT
+                        value
+                    FROM
+                        storage_logs
+                    WHERE
+                        hashed_key = u.hashed_key
+                        AND (
+                            miniblock_number < $2
+                            OR (
+                                miniblock_number = $2
+                                AND operation_number < $3
+                            )
+                        )
+                    ORDER BY
+                        miniblock_number DESC,
+                        operation_number DESC
+                    LIMIT
+                        1
+                ) AS "value?"
+            FROM
+                UNNEST($1::bytea[]) AS u (hashed_key)
+            "#,
+            &hashed_keys as &[&[u8]],
+            miniblock_number.0 as i64,
+            operation_number as i32
+        )
+        .instrument("get_storage_values_before_operation")
+        .with_arg("miniblock_number", &miniblock_number)
+        .with_arg("operation_number", &operation_number)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let key = H256::from_slice(&row.hashed_key);
+                let value = row.value.map(|value| H256::from_slice(&value));
+                (key, value)
+            })
+            .collect())
+    }
+
+    /// Returns storage keys written by the specified transaction, together with the miniblock
+    /// the transaction was included in and the operation number of each write. Writes are ordered
+    /// by their operation number. Returns an empty list if the transaction is not included in a miniblock.
+    pub async fn get_tx_storage_writes(
+        &mut self,
+        tx_hash: H256,
+    ) -> sqlx::Result<Vec<(StorageKey, MiniblockNumber, u32)>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                storage_logs.address,
+                storage_logs.key,
+                storage_logs.miniblock_number,
+                storage_logs.operation_number
+            FROM
+                transactions
+                INNER JOIN storage_logs ON storage_logs.miniblock_number = transactions.miniblock_number
+                AND storage_logs.tx_hash = transactions.hash
+            WHERE
+                transactions.hash = $1
+            ORDER BY
+                storage_logs.operation_number
+            "#,
+            tx_hash.as_bytes()
+        )
+        .instrument("get_tx_storage_writes")
+        .with_arg("tx_hash", &tx_hash)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let key = StorageKey::new(
+                    AccountTreeId::new(Address::from_slice(&row.address)),
+                    H256::from_slice(&row.key),
+                );
+                (
+                    key,
+                    MiniblockNumber(row.miniblock_number as u32),
+                    row.operation_number as u32,
+                )
+            })
+            .collect())
+    }
+
     pub async fn get_miniblock_storage_logs(
         &mut self,
         miniblock_number: MiniblockNumber,
@@ -621,6 +725,52 @@ impl StorageLogsDal<'_, '_> {
         Ok(rows.collect())
     }
 
+    /// Fetches up to `limit` tree entries with hashed keys not exceeding `max_hashed_key` as of the end
+    /// of the specified L1 batch, which must end with `last_miniblock_number`. Entries are ordered by
+    /// decreasing hashed key. This is used to cross-check the Merkle tree against Postgres.
+    pub async fn get_tree_entries_up_to_key(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+        last_miniblock_number: MiniblockNumber,
+        max_hashed_key: H256,
+        limit: usize,
+    ) -> sqlx::Result<Vec<StorageTreeEntry>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT DISTINCT
+                ON (storage_logs.hashed_key) storage_logs.hashed_key,
+                storage_logs.value,
+                initial_writes.index
+            FROM
+                storage_logs
+                INNER JOIN initial_writes ON storage_logs.hashed_key = initial_writes.hashed_key
+            WHERE
+                storage_logs.miniblock_number <= $1
+                AND storage_logs.hashed_key <= $2::bytea
+                AND initial_writes.l1_batch_number <= $3
+            ORDER BY
+                storage_logs.hashed_key DESC,
+                storage_logs.miniblock_number DESC,
+                storage_logs.operation_number DESC
+            LIMIT
+                $4
+            "#,
+            last_miniblock_number.0 as i64,
+            max_hashed_key.as_bytes(),
+            l1_batch_number.0 as i64,
+            limit as i64
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        let rows = rows.into_iter().map(|row| StorageTreeEntry {
+            key: U256::from_little_endian(&row.hashed_key),
+            value: H256::from_slice(&row.value),
+            leaf_index: row.index as u64,
+        });
+        Ok(rows.collect())
+    }
+
     pub async fn retain_storage_logs(
         &mut self,
         miniblock_number: MiniblockNumber,
@@ -1043,4 +1193,58 @@ mod tests {
             assert!(key_range.contains(&u256_to_h256_reversed(entry.key)));
         }
     }
+
+    #[tokio::test]
+    async fn getting_tree_entries_up_to_key() {
+        let pool = ConnectionPool::test_pool().await;
+        let mut conn = pool.access_storage().await.unwrap();
+        let sorted_hashed_keys = prepare_tree_entries(&mut conn, 10).await;
+
+        let tree_entries = conn
+            .storage_logs_dal()
+            .get_tree_entries_up_to_key(
+                L1BatchNumber(1),
+                MiniblockNumber(1),
+                H256::repeat_byte(0xff),
+                100,
+            )
+            .await
+            .unwrap();
+        let mut expected_keys = sorted_hashed_keys.clone();
+        expected_keys.reverse();
+        assert_eq!(
+            tree_entries
+                .iter()
+                .map(|entry| u256_to_h256_reversed(entry.key))
+                .collect::<Vec<_>>(),
+            expected_keys
+        );
+
+        // Overwrite one of the keys in the next L1 batch.
+        let account = AccountTreeId::new(Address::repeat_byte(1));
+        let updated_key = StorageKey::new(account, H256::repeat_byte(3));
+        let log = StorageLog::new_write_log(updated_key, H256::repeat_byte(0xaa));
+        insert_miniblock(&mut conn, 2, vec![log]).await;
+
+        let max_hashed_key = updated_key.hashed_key();
+        for (l1_batch_number, expected_value) in [(1, H256::repeat_byte(3)), (2, log.value)] {
+            let tree_entries = conn
+                .storage_logs_dal()
+                .get_tree_entries_up_to_key(
+                    L1BatchNumber(l1_batch_number),
+                    MiniblockNumber(l1_batch_number),
+                    max_hashed_key,
+                    2,
+                )
+                .await
+                .unwrap();
+            assert!(!tree_entries.is_empty() && tree_entries.len() <= 2);
+            let first_entry = &tree_entries[0];
+            assert_eq!(u256_to_h256_reversed(first_entry.key), max_hashed_key);
+            assert_eq!(first_entry.value, expected_value);
+            for entry in &tree_entries {
+                assert!(u256_to_h256_reversed(entry.key) <= max_hashed_key);
+            }
+        }
+    }
 }
@@ -1,5 +1,6 @@
-use std::time::Duration;
+use std::{collections::HashMap, time::Duration};
 
+use sqlx::types::chrono::NaiveDateTime;
 use zksync_contracts::BaseSystemContractsHashes;
 use zksync_types::{
     block::{MiniblockHasher, MiniblockHeader},
@@ -7,17 +8,18 @@ use zksync_types::{
     fee_model::BatchFeeInput,
     helpers::unix_timestamp_ms,
     l1::{L1Tx, OpProcessingType, PriorityQueueType},
-    l2::L2Tx,
+    l2::{bundle::TransactionBundle, conditions::TransactionConditions, L2Tx},
     tx::{tx_execution_info::TxExecutionStatus, ExecutionMetrics, TransactionExecutionResult},
-    Address, Execute, L1BlockNumber, L1TxCommonData, L2ChainId, MiniblockNumber, PriorityOpId,
-    ProtocolVersionId, H160, H256, MAX_GAS_PER_PUBDATA_BYTE, U256,
+    Address, Execute, ExecuteTransactionCommon, L1BlockNumber, L1TxCommonData, L2ChainId,
+    MiniblockNumber, PriorityOpId, ProtocolVersionId, Transaction, H160, H256,
+    MAX_GAS_PER_PUBDATA_BYTE, U256,
 };
 
 use crate::{
     blocks_dal::BlocksDal,
     connection::ConnectionPool,
     protocol_versions_dal::ProtocolVersionsDal,
-    transactions_dal::{L2TxSubmissionResult, TransactionsDal},
+    transactions_dal::{DroppedTxReason, L2TxSubmissionResult, TransactionsDal},
     transactions_web3_dal::TransactionsWeb3Dal,
 };
 
@@ -163,6 +165,213 @@ async fn workflow_with_submit_tx_diff_hashes() {
     assert_eq!(result, L2TxSubmissionResult::Replaced);
 }
 
+#[tokio::test]
+async fn getting_pending_l2_tx_fee() {
+    let connection_pool = ConnectionPool::test_pool().await;
+    let storage = &mut connection_pool.access_storage().await.unwrap();
+    let mut transactions_dal = TransactionsDal { storage };
+
+    let tx = mock_l2_transaction();
+    let initiator_address = tx.initiator_account();
+    let pending_fee = transactions_dal
+        .get_pending_l2_tx_fee(initiator_address, tx.common_data.nonce)
+        .await
+        .unwrap();
+    assert_eq!(pending_fee, None);
+
+    transactions_dal
+        .insert_transaction_l2(tx.clone(), mock_tx_execution_metrics())
+        .await;
+    let pending_fee = transactions_dal
+        .get_pending_l2_tx_fee(initiator_address, tx.common_data.nonce)
+        .await
+        .unwrap();
+    assert_eq!(pending_fee, Some((tx.hash(), tx.common_data.fee.clone())));
+
+    let other_nonce = zksync_types::Nonce(1);
+    let pending_fee = transactions_dal
+        .get_pending_l2_tx_fee(initiator_address, other_nonce)
+        .await
+        .unwrap();
+    assert_eq!(pending_fee, None);
+}
+
+#[tokio::test]
+async fn getting_pending_l2_txs_count() {
+    let connection_pool = ConnectionPool::test_pool().await;
+    let storage = &mut connection_pool.access_storage().await.unwrap();
+    let mut transactions_dal = TransactionsDal { storage };
+
+    let paymaster = Address::repeat_byte(0x11);
+    let tx = mock_l2_transaction();
+    let initiator_address = tx.initiator_account();
+    let mut sponsored_tx = mock_l2_transaction();
+    sponsored_tx.common_data.paymaster_params.paymaster = paymaster;
+    for tx in [tx, sponsored_tx.clone()] {
+        transactions_dal
+            .insert_transaction_l2(tx, mock_tx_execution_metrics())
+            .await;
+    }
+
+    let counts = transactions_dal
+        .get_pending_l2_txs_count(initiator_address)
+        .await
+        .unwrap();
+    assert_eq!(counts, (1, 0));
+    let counts = transactions_dal
+        .get_pending_l2_txs_count(paymaster)
+        .await
+        .unwrap();
+    assert_eq!(counts, (0, 1));
+    let counts = transactions_dal
+        .get_pending_l2_txs_count(sponsored_tx.initiator_account())
+        .await
+        .unwrap();
+    assert_eq!(counts, (1, 0));
+    let counts = transactions_dal
+        .get_pending_l2_txs_count(Address::repeat_byte(0x22))
+        .await
+        .unwrap();
+    assert_eq!(counts, (0, 0));
+}
+
+#[tokio::test]
+async fn persisting_tx_conditions() {
+    let connection_pool = ConnectionPool::test_pool().await;
+    let storage = &mut connection_pool.access_storage().await.unwrap();
+    let mut transactions_dal = TransactionsDal { storage };
+
+    let mut tx = mock_l2_transaction();
+    let conditions = TransactionConditions {
+        known_balances: [(Address::repeat_byte(1), U256::from(100))].into(),
+        block_number_max: Some(10.into()),
+        ..TransactionConditions::default()
+    };
+    tx.common_data.conditions = Some(Box::new(conditions.clone()));
+    transactions_dal
+        .insert_transaction_l2(tx.clone(), mock_tx_execution_metrics())
+        .await;
+    let unconditional_tx = mock_l2_transaction();
+    transactions_dal
+        .insert_transaction_l2(unconditional_tx.clone(), mock_tx_execution_metrics())
+        .await;
+    // Resubmitting the same transaction without conditions must not erase them.
+    let mut resubmitted_tx = tx.clone();
+    resubmitted_tx.common_data.conditions = None;
+    let result = transactions_dal
+        .insert_transaction_l2(resubmitted_tx, mock_tx_execution_metrics())
+        .await;
+    assert_eq!(result, L2TxSubmissionResult::Duplicate);
+
+    let (txs, _) = transactions_dal
+        .sync_mempool(vec![], vec![], 0, 0, 1000)
+        .await;
+    assert_eq!(txs.len(), 2);
+    for mempool_tx in txs {
+        let ExecuteTransactionCommon::L2(common_data) = mempool_tx.common_data else {
+            panic!("Unexpected tx: {mempool_tx:?}");
+        };
+        if common_data.hash() == tx.hash() {
+            assert_eq!(common_data.conditions.as_deref(), Some(&conditions));
+        } else {
+            assert_eq!(common_data.hash(), unconditional_tx.hash());
+            assert_eq!(common_data.conditions, None);
+        }
+    }
+}
+
+#[tokio::test]
+async fn hiding_private_txs() {
+    let connection_pool = ConnectionPool::test_pool().await;
+    let storage = &mut connection_pool.access_storage().await.unwrap();
+    let mut transactions_dal = TransactionsDal { storage };
+
+    let tx = mock_l2_transaction();
+    transactions_dal
+        .insert_transaction_l2(tx.clone(), mock_tx_execution_metrics())
+        .await;
+    let private_tx = mock_l2_transaction();
+    let result = transactions_dal
+        .insert_private_transaction_l2(private_tx.clone(), mock_tx_execution_metrics())
+        .await;
+    assert_eq!(result, L2TxSubmissionResult::Added);
+
+    let (txs, _) = transactions_dal
+        .get_mempool_transactions(1000)
+        .await
+        .unwrap();
+    let tx_hashes: Vec<_> = txs.iter().map(Transaction::hash).collect();
+    assert_eq!(tx_hashes, [tx.hash()]);
+
+    // Private transactions must still be picked up by the state keeper.
+    let (txs, _) = transactions_dal
+        .sync_mempool(vec![], vec![], 0, 0, 1000)
+        .await;
+    assert_eq!(txs.len(), 2);
+
+    let storage = transactions_dal.storage;
+    let from_timestamp = NaiveDateTime::from_timestamp_opt(0, 0).unwrap();
+    let (pending_hashes, _) = TransactionsWeb3Dal { storage }
+        .get_pending_txs_hashes_after(from_timestamp, None)
+        .await
+        .unwrap();
+    assert_eq!(pending_hashes, [tx.hash()]);
+}
+
+#[tokio::test]
+async fn inserting_and_syncing_tx_bundles() {
+    let connection_pool = ConnectionPool::test_pool().await;
+    let storage = &mut connection_pool.access_storage().await.unwrap();
+    let mut transactions_dal = TransactionsDal { storage };
+
+    let standalone_tx = mock_l2_transaction();
+    transactions_dal
+        .insert_transaction_l2(standalone_tx.clone(), mock_tx_execution_metrics())
+        .await;
+    let bundle_txs = [mock_l2_transaction(), mock_l2_transaction()];
+    let bundle_hash = TransactionBundle::compute_hash(bundle_txs.iter().map(L2Tx::hash));
+    let bundle_txs_with_metrics = bundle_txs
+        .iter()
+        .map(|tx| (tx.clone(), mock_tx_execution_metrics()))
+        .collect();
+    transactions_dal
+        .insert_transaction_bundle(bundle_hash, bundle_txs_with_metrics)
+        .await
+        .unwrap();
+
+    // A bundle with an already persisted transaction must not be inserted.
+    let new_tx = mock_l2_transaction();
+    let err = transactions_dal
+        .insert_transaction_bundle(
+            H256::zero(),
+            vec![
+                (new_tx.clone(), mock_tx_execution_metrics()),
+                (standalone_tx.clone(), mock_tx_execution_metrics()),
+            ],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.tx_hash, standalone_tx.hash());
+    assert_eq!(err.result, L2TxSubmissionResult::Replaced);
+
+    // Bundle transactions must not be returned as standalone ones.
+    let (txs, _) = transactions_dal
+        .sync_mempool(vec![], vec![], 0, 0, 1000)
+        .await;
+    let tx_hashes: Vec<_> = txs.iter().map(Transaction::hash).collect();
+    assert_eq!(tx_hashes, [standalone_tx.hash()]);
+
+    let bundles = transactions_dal.sync_mempool_bundles(1000).await;
+    assert_eq!(bundles.len(), 1);
+    assert_eq!(bundles[0].hash, bundle_hash);
+    let tx_hashes: Vec<_> = bundles[0].transactions.iter().map(L2Tx::hash).collect();
+    assert_eq!(tx_hashes, bundle_txs.map(|tx| tx.hash()));
+
+    // Bundles are only returned once.
+    let bundles = transactions_dal.sync_mempool_bundles(1000).await;
+    assert!(bundles.is_empty());
+}
+
 #[tokio::test]
 async fn remove_stuck_txs() {
     let connection_pool = ConnectionPool::test_pool().await;
@@ -254,3 +463,138 @@ async fn remove_stuck_txs() {
         .unwrap()
         .unwrap();
 }
+
+#[tokio::test]
+async fn evicting_mempool_txs() {
+    let connection_pool = ConnectionPool::test_pool().await;
+    let storage = &mut connection_pool.access_storage().await.unwrap();
+    let mut transactions_dal = TransactionsDal { storage };
+
+    let mut old_tx = mock_l2_transaction();
+    old_tx.received_timestamp_ms =
+        unix_timestamp_ms() - Duration::from_secs(1_000).as_millis() as u64;
+    let mut cheap_tx = mock_l2_transaction();
+    cheap_tx.common_data.fee.max_fee_per_gas = U256::from(1_000u32);
+    let tx = mock_l2_transaction();
+    for tx in [&old_tx, &cheap_tx, &tx] {
+        transactions_dal
+            .insert_transaction_l2(tx.clone(), mock_tx_execution_metrics())
+            .await;
+    }
+
+    let expired_txs = transactions_dal
+        .get_expired_mempool_txs(Duration::from_secs(500), 100)
+        .await
+        .unwrap();
+    assert_eq!(expired_txs.len(), 1);
+    assert_eq!(expired_txs[0].hash, old_tx.hash());
+    assert_eq!(expired_txs[0].initiator_address, old_tx.initiator_account());
+    assert!(!expired_txs[0].in_mempool);
+    let dropped_count = transactions_dal
+        .drop_mempool_txs(&[old_tx.hash()], &DroppedTxReason::Expired)
+        .await
+        .unwrap();
+    assert_eq!(dropped_count, 1);
+
+    let evicted_txs = transactions_dal
+        .get_lowest_fee_mempool_txs(1, 100)
+        .await
+        .unwrap();
+    assert_eq!(evicted_txs.len(), 1);
+    assert_eq!(evicted_txs[0].hash, cheap_tx.hash());
+    transactions_dal
+        .drop_mempool_txs(&[cheap_tx.hash()], &DroppedTxReason::Evicted)
+        .await
+        .unwrap();
+    let evicted_txs = transactions_dal
+        .get_lowest_fee_mempool_txs(1, 100)
+        .await
+        .unwrap();
+    assert!(evicted_txs.is_empty());
+
+    let reason = transactions_dal
+        .get_dropped_tx_reason(old_tx.hash())
+        .await
+        .unwrap();
+    assert_eq!(reason.as_deref(), Some("expired"));
+    let reason = transactions_dal
+        .get_dropped_tx_reason(cheap_tx.hash())
+        .await
+        .unwrap();
+    assert_eq!(reason.as_deref(), Some("evicted"));
+    let reason = transactions_dal
+        .get_dropped_tx_reason(tx.hash())
+        .await
+        .unwrap();
+    assert_eq!(reason, None);
+
+    transactions_dal.reset_mempool().await;
+    let txs = transactions_dal
+        .sync_mempool(vec![], vec![], 0, 0, 1000)
+        .await
+        .0;
+    assert_eq!(txs.len(), 1);
+    assert_eq!(txs[0].hash(), tx.hash());
+}
+
+#[tokio::test]
+async fn requesting_mempool_tx_removal() {
+    let connection_pool = ConnectionPool::test_pool().await;
+    let storage = &mut connection_pool.access_storage().await.unwrap();
+    let mut transactions_dal = TransactionsDal { storage };
+
+    let tx = mock_l2_transaction();
+    let other_tx = mock_l2_transaction();
+    for tx in [&tx, &other_tx] {
+        transactions_dal
+            .insert_transaction_l2(tx.clone(), mock_tx_execution_metrics())
+            .await;
+    }
+
+    let requested = transactions_dal
+        .request_mempool_tx_removal(tx.hash(), "malformed")
+        .await
+        .unwrap();
+    assert!(requested);
+    let requested = transactions_dal
+        .request_mempool_tx_removal(H256::repeat_byte(1), "unknown")
+        .await
+        .unwrap();
+    assert!(!requested);
+    let requested_hashes = transactions_dal
+        .request_mempool_initiator_txs_removal(other_tx.initiator_account(), "spam")
+        .await
+        .unwrap();
+    assert_eq!(requested_hashes, [other_tx.hash()]);
+
+    let requests = transactions_dal
+        .get_mempool_removal_requests(100)
+        .await
+        .unwrap();
+    let requests: HashMap<_, _> = requests
+        .into_iter()
+        .map(|(candidate, reason)| (candidate.hash, (candidate.in_mempool, reason)))
+        .collect();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[&tx.hash()], (false, "malformed".to_owned()));
+    assert_eq!(requests[&other_tx.hash()], (false, "spam".to_owned()));
+
+    let reason = DroppedTxReason::RemovedByOperator("malformed".to_owned());
+    transactions_dal
+        .drop_mempool_txs(&[tx.hash()], &reason)
+        .await
+        .unwrap();
+    // The request for the dropped transaction should be removed.
+    let requests = transactions_dal
+        .get_mempool_removal_requests(100)
+        .await
+        .unwrap();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].0.hash, other_tx.hash());
+
+    let reason = transactions_dal
+        .get_dropped_tx_reason(tx.hash())
+        .await
+        .unwrap();
+    assert_eq!(reason.as_deref(), Some("removed by operator: malformed"));
+}
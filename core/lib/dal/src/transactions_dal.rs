@@ -1,22 +1,23 @@
-use std::{collections::HashMap, fmt, time::Duration};
+use std::{collections::HashMap, fmt, ops, time::Duration};
 
 use anyhow::Context;
 use bigdecimal::BigDecimal;
 use itertools::Itertools;
 use sqlx::{error, types::chrono::NaiveDateTime};
 use zksync_types::{
+    api::trace::call_frames,
     block::MiniblockExecutionData,
-    fee::TransactionExecutionMetrics,
+    fee::{Fee, TransactionExecutionMetrics},
     get_nonce_key,
     l1::L1Tx,
-    l2::L2Tx,
+    l2::{bundle::TransactionBundle, L2Tx},
     protocol_version::ProtocolUpgradeTx,
     tx::{tx_execution_info::TxExecutionStatus, TransactionExecutionResult},
     vm_trace::{Call, VmExecutionTrace},
     Address, ExecuteTransactionCommon, L1BatchNumber, L1BlockNumber, MiniblockNumber, Nonce,
     PriorityOpId, Transaction, H256, PROTOCOL_UPGRADE_TX_TYPE, U256,
 };
-use zksync_utils::{h256_to_u32, u256_to_big_decimal};
+use zksync_utils::{bigdecimal_to_u256, h256_to_u32, u256_to_big_decimal};
 
 use crate::{
     instrument::InstrumentExt,
@@ -46,6 +47,44 @@ impl fmt::Display for L2TxSubmissionResult {
     }
 }
 
+/// Error returned by [`TransactionsDal::insert_transaction_bundle()`] if one of bundle transactions
+/// cannot be inserted as a new transaction. No bundle transactions are persisted in this case.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct BundleInsertionError {
+    pub tx_hash: H256,
+    pub result: L2TxSubmissionResult,
+}
+
+/// Reason for dropping a pending L2 transaction from the mempool.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum DroppedTxReason {
+    /// Transaction was not included into a miniblock within the configured TTL.
+    Expired,
+    /// Transaction was evicted because the mempool is full, and it has one of the lowest fees.
+    Evicted,
+    /// Transaction was removed by the node operator (e.g., via the admin API) with the specified reason.
+    RemovedByOperator(String),
+}
+
+impl fmt::Display for DroppedTxReason {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Expired => formatter.write_str("expired"),
+            Self::Evicted => formatter.write_str("evicted"),
+            Self::RemovedByOperator(reason) => write!(formatter, "removed by operator: {reason}"),
+        }
+    }
+}
+
+/// Pending L2 transaction that can be evicted from the mempool.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MempoolEvictionCandidate {
+    pub hash: H256,
+    pub initiator_address: Address,
+    /// Whether the transaction is loaded into the in-memory mempool.
+    pub in_mempool: bool,
+}
+
 #[derive(Debug)]
 pub struct TransactionsDal<'c, 'a> {
     pub(crate) storage: &'c mut StorageProcessor<'a>,
@@ -250,10 +289,105 @@ impl TransactionsDal<'_, '_> {
         }
     }
 
+    /// Returns the hash and fee of the pending L2 transaction with the specified initiator and nonce,
+    /// i.e., the transaction that would be replaced by [`Self::insert_transaction_l2()`].
+    pub async fn get_pending_l2_tx_fee(
+        &mut self,
+        initiator_address: Address,
+        nonce: Nonce,
+    ) -> sqlx::Result<Option<(H256, Fee)>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                hash,
+                gas_limit AS "gas_limit!",
+                max_fee_per_gas AS "max_fee_per_gas!",
+                max_priority_fee_per_gas AS "max_priority_fee_per_gas!",
+                gas_per_pubdata_limit AS "gas_per_pubdata_limit!"
+            FROM
+                transactions
+            WHERE
+                initiator_address = $1
+                AND nonce = $2
+                AND is_priority = FALSE
+                AND miniblock_number IS NULL
+            "#,
+            initiator_address.as_bytes(),
+            i64::from(nonce.0)
+        )
+        .fetch_optional(self.storage.conn())
+        .await?;
+
+        Ok(row.map(|row| {
+            let fee = Fee {
+                gas_limit: bigdecimal_to_u256(row.gas_limit),
+                max_fee_per_gas: bigdecimal_to_u256(row.max_fee_per_gas),
+                max_priority_fee_per_gas: bigdecimal_to_u256(row.max_priority_fee_per_gas),
+                gas_per_pubdata_limit: bigdecimal_to_u256(row.gas_per_pubdata_limit),
+            };
+            (H256::from_slice(&row.hash), fee)
+        }))
+    }
+
+    /// Returns the number of pending L2 transactions sent by the specified account, and the number
+    /// of pending L2 transactions using this account as a paymaster.
+    pub async fn get_pending_l2_txs_count(
+        &mut self,
+        address: Address,
+    ) -> sqlx::Result<(usize, usize)> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                COUNT(*) FILTER (
+                    WHERE
+                        initiator_address = $1
+                ) AS "sent_count!",
+                COUNT(*) FILTER (
+                    WHERE
+                        paymaster = $1
+                ) AS "sponsored_count!"
+            FROM
+                transactions
+            WHERE
+                (
+                    initiator_address = $1
+                    OR paymaster = $1
+                )
+                AND is_priority = FALSE
+                AND miniblock_number IS NULL
+                AND error IS NULL
+            "#,
+            address.as_bytes()
+        )
+        .fetch_one(self.storage.conn())
+        .await?;
+
+        Ok((row.sent_count as usize, row.sponsored_count as usize))
+    }
+
     pub async fn insert_transaction_l2(
         &mut self,
         tx: L2Tx,
         exec_info: TransactionExecutionMetrics,
+    ) -> L2TxSubmissionResult {
+        self.insert_l2_transaction_inner(tx, exec_info, false).await
+    }
+
+    /// Inserts a private L2 transaction. Private transactions are excluded from pending transaction feeds
+    /// and mempool introspection until they are included into a miniblock.
+    pub async fn insert_private_transaction_l2(
+        &mut self,
+        tx: L2Tx,
+        exec_info: TransactionExecutionMetrics,
+    ) -> L2TxSubmissionResult {
+        self.insert_l2_transaction_inner(tx, exec_info, true).await
+    }
+
+    async fn insert_l2_transaction_inner(
+        &mut self,
+        tx: L2Tx,
+        exec_info: TransactionExecutionMetrics,
+        is_private: bool,
     ) -> L2TxSubmissionResult {
         {
             let tx_hash = tx.hash();
@@ -277,11 +411,17 @@ impl TransactionsDal<'_, '_> {
             let secs = (tx.received_timestamp_ms / 1000) as i64;
             let nanosecs = ((tx.received_timestamp_ms % 1000) * 1_000_000) as u32;
             let received_at = NaiveDateTime::from_timestamp_opt(secs, nanosecs).unwrap();
+            let conditions = tx.common_data.conditions.map(|conditions| {
+                serde_json::to_value(conditions)
+                    .unwrap_or_else(|_| panic!("cannot serialize conditions of tx {tx_hash:?}"))
+            });
             // Besides just adding or updating(on conflict) the record, we want to extract some info
             // from the query below, to indicate what actually happened:
             // 1) transaction is added
             // 2) transaction is replaced
             // 3) WHERE clause conditions for DO UPDATE block were not met, so the transaction can't be replaced
+            //    (it is either executed, or the same transaction is resubmitted; in the latter case, the stored
+            //    transaction is kept as is, so that e.g. its conditions are not erased)
             // the subquery in RETURNING clause looks into pre-UPDATE state of the table. So if the subquery will return NULL
             // transaction is fresh and was added to db(the second condition of RETURNING clause checks it).
             // Otherwise, if the subquery won't return NULL it means that there is already tx with such nonce and `initiator_address` in DB
@@ -309,6 +449,8 @@ impl TransactionsDal<'_, '_> {
                         paymaster_input,
                         execution_info,
                         received_at,
+                        conditions,
+                        is_private,
                         created_at,
                         updated_at
                     )
@@ -332,6 +474,8 @@ impl TransactionsDal<'_, '_> {
                         $15,
                         JSONB_BUILD_OBJECT('gas_used', $16::BIGINT, 'storage_writes', $17::INT, 'contracts_used', $18::INT),
                         $19,
+                        $20,
+                        $21,
                         NOW(),
                         NOW()
                     )
@@ -354,12 +498,15 @@ impl TransactionsDal<'_, '_> {
                     execution_info = JSONB_BUILD_OBJECT('gas_used', $16::BIGINT, 'storage_writes', $17::INT, 'contracts_used', $18::INT),
                     in_mempool = FALSE,
                     received_at = $19,
+                    conditions = $20,
+                    is_private = $21,
                     created_at = NOW(),
                     updated_at = NOW(),
                     error = NULL
                 WHERE
                     transactions.is_priority = FALSE
                     AND transactions.miniblock_number IS NULL
+                    AND transactions.hash <> $1
                 RETURNING
                     (
                         SELECT
@@ -389,7 +536,9 @@ impl TransactionsDal<'_, '_> {
                 exec_info.gas_used as i64,
                 (exec_info.initial_storage_writes + exec_info.repeated_storage_writes) as i32,
                 exec_info.contracts_used as i32,
-                received_at
+                received_at,
+                conditions,
+                is_private
             )
                 .fetch_optional(self.storage.conn())
                 .await
@@ -399,7 +548,13 @@ impl TransactionsDal<'_, '_> {
                 Ok(option_query_result) => match option_query_result {
                     Some(true) => L2TxSubmissionResult::Replaced,
                     Some(false) => L2TxSubmissionResult::Added,
-                    None => L2TxSubmissionResult::AlreadyExecuted,
+                    None => {
+                        if self.is_pending_tx(tx_hash).await {
+                            L2TxSubmissionResult::Duplicate
+                        } else {
+                            L2TxSubmissionResult::AlreadyExecuted
+                        }
+                    }
                 },
                 Err(err) => {
                     // So, we consider a tx hash to be a primary key of the transaction
@@ -432,6 +587,71 @@ impl TransactionsDal<'_, '_> {
         }
     }
 
+    async fn is_pending_tx(&mut self, tx_hash: H256) -> bool {
+        sqlx::query!(
+            r#"
+            SELECT
+                EXISTS (
+                    SELECT
+                        1
+                    FROM
+                        transactions
+                    WHERE
+                        hash = $1
+                        AND miniblock_number IS NULL
+                ) AS "is_pending!"
+            "#,
+            tx_hash.as_bytes()
+        )
+        .fetch_one(self.storage.conn())
+        .await
+        .unwrap()
+        .is_pending
+    }
+
+    /// Atomically inserts an ordered bundle of L2 transactions. All bundle transactions must be new;
+    /// otherwise, no transactions are inserted.
+    pub async fn insert_transaction_bundle(
+        &mut self,
+        bundle_hash: H256,
+        transactions: Vec<(L2Tx, TransactionExecutionMetrics)>,
+    ) -> Result<(), BundleInsertionError> {
+        let mut transaction = self.storage.start_transaction().await.unwrap();
+        let mut tx_hashes = Vec::with_capacity(transactions.len());
+        for (tx, exec_info) in transactions {
+            let tx_hash = tx.hash();
+            let result = transaction
+                .transactions_dal()
+                .insert_transaction_l2(tx, exec_info)
+                .await;
+            if result != L2TxSubmissionResult::Added {
+                return Err(BundleInsertionError { tx_hash, result });
+            }
+            tx_hashes.push(tx_hash.as_bytes().to_vec());
+        }
+
+        sqlx::query!(
+            r#"
+            UPDATE transactions
+            SET
+                bundle_hash = $1,
+                bundle_index = (data_table.ordinal - 1)::INT,
+                updated_at = NOW()
+            FROM
+                UNNEST($2::bytea[]) WITH ORDINALITY AS data_table (hash, ordinal)
+            WHERE
+                transactions.hash = data_table.hash
+            "#,
+            bundle_hash.as_bytes(),
+            &tx_hashes
+        )
+        .execute(transaction.conn())
+        .await
+        .unwrap();
+        transaction.commit().await.unwrap();
+        Ok(())
+    }
+
     pub async fn mark_txs_as_executed_in_l1_batch(
         &mut self,
         block_number: L1BatchNumber,
@@ -511,6 +731,11 @@ impl TransactionsDal<'_, '_> {
 
             let mut call_traces_tx_hashes = Vec::with_capacity(transactions.len());
             let mut bytea_call_traces = Vec::with_capacity(transactions.len());
+            let mut call_frame_tx_hashes = vec![];
+            let mut call_frame_positions = vec![];
+            let mut call_frame_tx_indices = vec![];
+            let mut call_frame_from_addresses = vec![];
+            let mut call_frame_to_addresses = vec![];
             transactions
                 .iter()
                 .enumerate()
@@ -534,6 +759,15 @@ impl TransactionsDal<'_, '_> {
                     };
 
                     if let Some(call_trace) = tx_res.call_trace() {
+                        for (position, (_, frame)) in
+                            call_frames(&call_trace).into_iter().enumerate()
+                        {
+                            call_frame_tx_hashes.push(hash.0.to_vec());
+                            call_frame_positions.push(position as i32);
+                            call_frame_tx_indices.push(index_in_block as i32);
+                            call_frame_from_addresses.push(frame.from.as_bytes().to_vec());
+                            call_frame_to_addresses.push(frame.to.as_bytes().to_vec());
+                        }
                         bytea_call_traces.push(bincode::serialize(&call_trace).unwrap());
                         call_traces_tx_hashes.push(hash.0.to_vec());
                     }
@@ -790,6 +1024,46 @@ impl TransactionsDal<'_, '_> {
                 .execute(transaction.conn())
                 .await
                 .unwrap();
+
+                sqlx::query!(
+                    r#"
+                    INSERT INTO
+                        call_trace_index (
+                            tx_hash,
+                            trace_position,
+                            miniblock_number,
+                            tx_index_in_block,
+                            from_address,
+                            to_address
+                        )
+                    SELECT
+                        u.tx_hash,
+                        u.trace_position,
+                        $1::BIGINT,
+                        u.tx_index_in_block,
+                        u.from_address,
+                        u.to_address
+                    FROM
+                        UNNEST($2::bytea[], $3::INT[], $4::INT[], $5::bytea[], $6::bytea[]) AS u (
+                            tx_hash,
+                            trace_position,
+                            tx_index_in_block,
+                            from_address,
+                            to_address
+                        )
+                    "#,
+                    miniblock_number.0 as i64,
+                    &call_frame_tx_hashes,
+                    &call_frame_positions,
+                    &call_frame_tx_indices,
+                    &call_frame_from_addresses,
+                    &call_frame_to_addresses
+                )
+                .instrument("insert_call_trace_index")
+                .report_latency()
+                .execute(transaction.conn())
+                .await
+                .unwrap();
             }
             transaction.commit().await.unwrap();
         }
@@ -879,6 +1153,383 @@ impl TransactionsDal<'_, '_> {
         }
     }
 
+    /// Returns up to `limit` pending L2 transactions received more than `ttl` ago.
+    /// Transactions belonging to bundles are never returned, since bundles must be executed atomically.
+    pub async fn get_expired_mempool_txs(
+        &mut self,
+        ttl: Duration,
+        limit: usize,
+    ) -> sqlx::Result<Vec<MempoolEvictionCandidate>> {
+        let ttl = pg_interval_from_duration(ttl);
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                hash,
+                initiator_address,
+                in_mempool
+            FROM
+                transactions
+            WHERE
+                miniblock_number IS NULL
+                AND is_priority = FALSE
+                AND error IS NULL
+                AND bundle_hash IS NULL
+                AND received_at < NOW() - $1::INTERVAL
+            ORDER BY
+                received_at
+            LIMIT
+                $2
+            "#,
+            ttl,
+            limit as i64
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| MempoolEvictionCandidate {
+                hash: H256::from_slice(&row.hash),
+                initiator_address: Address::from_slice(&row.initiator_address),
+                in_mempool: row.in_mempool,
+            })
+            .collect())
+    }
+
+    /// Returns up to `limit` pending L2 transactions exceeding the mempool `capacity`, starting from transactions
+    /// with the lowest max fee per gas. Among transactions with the same fee, the most recently received
+    /// transactions are returned first. Transactions belonging to bundles are neither counted nor returned.
+    pub async fn get_lowest_fee_mempool_txs(
+        &mut self,
+        capacity: usize,
+        limit: usize,
+    ) -> sqlx::Result<Vec<MempoolEvictionCandidate>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                hash,
+                initiator_address,
+                in_mempool
+            FROM
+                transactions
+            WHERE
+                miniblock_number IS NULL
+                AND is_priority = FALSE
+                AND error IS NULL
+                AND bundle_hash IS NULL
+            ORDER BY
+                max_fee_per_gas DESC,
+                received_at
+            OFFSET
+                $1
+            LIMIT
+                $2
+            "#,
+            capacity as i64,
+            limit as i64
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        let mut candidates: Vec<_> = rows
+            .into_iter()
+            .map(|row| MempoolEvictionCandidate {
+                hash: H256::from_slice(&row.hash),
+                initiator_address: Address::from_slice(&row.initiator_address),
+                in_mempool: row.in_mempool,
+            })
+            .collect();
+        // Evict the lowest-fee transactions first.
+        candidates.reverse();
+        Ok(candidates)
+    }
+
+    /// Removes the specified pending L2 transactions and records them as dropped with the specified reason,
+    /// so that they can be distinguished from unknown transactions. Returns the number of dropped transactions.
+    pub async fn drop_mempool_txs(
+        &mut self,
+        tx_hashes: &[H256],
+        reason: &DroppedTxReason,
+    ) -> sqlx::Result<usize> {
+        let tx_hashes: Vec<_> = tx_hashes.iter().map(H256::as_bytes).collect();
+        let result = sqlx::query!(
+            r#"
+            WITH
+                removed AS (
+                    DELETE FROM transactions
+                    WHERE
+                        hash = ANY ($1)
+                        AND miniblock_number IS NULL
+                        AND is_priority = FALSE
+                    RETURNING
+                        hash
+                )
+            INSERT INTO
+                dropped_transactions (hash, reason, dropped_at)
+            SELECT
+                hash,
+                $2,
+                NOW()
+            FROM
+                removed
+            ON CONFLICT (hash) DO
+            UPDATE
+            SET
+                reason = excluded.reason,
+                dropped_at = excluded.dropped_at
+            "#,
+            &tx_hashes as &[&[u8]],
+            reason.to_string()
+        )
+        .execute(self.storage.conn())
+        .await?;
+
+        Ok(result.rows_affected() as usize)
+    }
+
+    /// Requests removing the specified pending L2 transaction from the mempool. Returns `false` if the transaction
+    /// is not pending (e.g., it is unknown or already included into a miniblock). Transactions belonging to bundles
+    /// cannot be removed, since bundles must be executed atomically.
+    ///
+    /// Removal is asynchronous: requested transactions are dropped by the mempool fetcher of the state keeper,
+    /// so that they are also removed from the in-memory mempool.
+    pub async fn request_mempool_tx_removal(
+        &mut self,
+        tx_hash: H256,
+        reason: &str,
+    ) -> sqlx::Result<bool> {
+        let row = sqlx::query!(
+            r#"
+            INSERT INTO
+                mempool_removal_requests (hash, reason, requested_at)
+            SELECT
+                hash,
+                $2,
+                NOW()
+            FROM
+                transactions
+            WHERE
+                hash = $1
+                AND miniblock_number IS NULL
+                AND is_priority = FALSE
+                AND error IS NULL
+                AND bundle_hash IS NULL
+            ON CONFLICT (hash) DO
+            UPDATE
+            SET
+                reason = excluded.reason,
+                requested_at = excluded.requested_at
+            RETURNING
+                hash
+            "#,
+            tx_hash.as_bytes(),
+            reason
+        )
+        .fetch_optional(self.storage.conn())
+        .await?;
+
+        Ok(row.is_some())
+    }
+
+    /// Requests removing all pending L2 transactions of the specified initiator from the mempool.
+    /// Returns hashes of the transactions requested to be removed. See [`Self::request_mempool_tx_removal()`]
+    /// for details.
+    pub async fn request_mempool_initiator_txs_removal(
+        &mut self,
+        initiator_address: Address,
+        reason: &str,
+    ) -> sqlx::Result<Vec<H256>> {
+        let rows = sqlx::query!(
+            r#"
+            INSERT INTO
+                mempool_removal_requests (hash, reason, requested_at)
+            SELECT
+                hash,
+                $2,
+                NOW()
+            FROM
+                transactions
+            WHERE
+                initiator_address = $1
+                AND miniblock_number IS NULL
+                AND is_priority = FALSE
+                AND error IS NULL
+                AND bundle_hash IS NULL
+            ON CONFLICT (hash) DO
+            UPDATE
+            SET
+                reason = excluded.reason,
+                requested_at = excluded.requested_at
+            RETURNING
+                hash
+            "#,
+            initiator_address.as_bytes(),
+            reason
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| H256::from_slice(&row.hash))
+            .collect())
+    }
+
+    /// Returns up to `limit` pending L2 transactions requested to be removed from the mempool together with
+    /// the removal reasons. Removal requests for transactions that are no longer pending are deleted.
+    pub async fn get_mempool_removal_requests(
+        &mut self,
+        limit: usize,
+    ) -> sqlx::Result<Vec<(MempoolEvictionCandidate, String)>> {
+        sqlx::query!(
+            r#"
+            DELETE FROM mempool_removal_requests
+            WHERE
+                NOT EXISTS (
+                    SELECT
+                        1
+                    FROM
+                        transactions
+                    WHERE
+                        transactions.hash = mempool_removal_requests.hash
+                        AND transactions.miniblock_number IS NULL
+                )
+            "#
+        )
+        .execute(self.storage.conn())
+        .await?;
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                transactions.hash,
+                transactions.initiator_address,
+                transactions.in_mempool,
+                mempool_removal_requests.reason
+            FROM
+                mempool_removal_requests
+                INNER JOIN transactions ON transactions.hash = mempool_removal_requests.hash
+            ORDER BY
+                mempool_removal_requests.requested_at
+            LIMIT
+                $1
+            "#,
+            limit as i64
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let candidate = MempoolEvictionCandidate {
+                    hash: H256::from_slice(&row.hash),
+                    initiator_address: Address::from_slice(&row.initiator_address),
+                    in_mempool: row.in_mempool,
+                };
+                (candidate, row.reason)
+            })
+            .collect())
+    }
+
+    /// Returns the reason the specified transaction was dropped from the mempool, or `None` if it wasn't dropped.
+    /// The record is retained if the transaction is resubmitted, so callers should look up the transaction itself first.
+    pub async fn get_dropped_tx_reason(&mut self, tx_hash: H256) -> sqlx::Result<Option<String>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                reason
+            FROM
+                dropped_transactions
+            WHERE
+                hash = $1
+            "#,
+            tx_hash.as_bytes()
+        )
+        .fetch_optional(self.storage.conn())
+        .await?;
+
+        Ok(row.map(|row| row.reason))
+    }
+
+    /// Returns transactions not yet included into a miniblock together with the current nonces of their initiators.
+    /// Transactions are ordered in the same way as in [`Self::sync_mempool()`]: L1 priority transactions first,
+    /// then L2 transactions by the time they were received. Unlike `sync_mempool()`, this method doesn't modify
+    /// any data, so it's safe to use for introspection. Private transactions are not returned.
+    pub async fn get_mempool_transactions(
+        &mut self,
+        limit: usize,
+    ) -> sqlx::Result<(Vec<Transaction>, HashMap<Address, Nonce>)> {
+        let transactions = sqlx::query_as!(
+            StorageTransaction,
+            r#"
+            SELECT
+                *
+            FROM
+                transactions
+            WHERE
+                miniblock_number IS NULL
+                AND error IS NULL
+                AND is_private = FALSE
+                AND tx_format != $2
+            ORDER BY
+                is_priority DESC,
+                priority_op_id,
+                received_at
+            LIMIT
+                $1
+            "#,
+            limit as i32,
+            PROTOCOL_UPGRADE_TX_TYPE as i32,
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        let initiators = transactions
+            .iter()
+            .filter(|tx| !tx.is_priority)
+            .map(|tx| Address::from_slice(&tx.initiator_address));
+        let nonces = self.get_nonces_for_addresses(initiators).await?;
+        Ok((transactions.into_iter().map(Into::into).collect(), nonces))
+    }
+
+    /// Returns nonces of the specified accounts as of the latest sealed miniblock. Accounts with the zero nonce
+    /// may be missing from the returned map.
+    async fn get_nonces_for_addresses(
+        &mut self,
+        addresses: impl IntoIterator<Item = Address>,
+    ) -> sqlx::Result<HashMap<Address, Nonce>> {
+        let nonce_keys: HashMap<_, _> = addresses
+            .into_iter()
+            .map(|address| (get_nonce_key(&address).hashed_key(), address))
+            .collect();
+
+        let storage_keys: Vec<_> = nonce_keys.keys().map(|key| key.0.to_vec()).collect();
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                hashed_key,
+                value AS "value!"
+            FROM
+                storage
+            WHERE
+                hashed_key = ANY ($1)
+            "#,
+            &storage_keys,
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let nonce_key = H256::from_slice(&row.hashed_key);
+                let nonce = Nonce(h256_to_u32(H256::from_slice(&row.value)));
+                (nonce_keys[&nonce_key], nonce)
+            })
+            .collect())
+    }
+
     /// Fetches new updates for mempool
     /// Returns new transactions and current nonces for related accounts
     /// Latter is only used to bootstrap mempool for given account
@@ -903,6 +1554,7 @@ impl TransactionsDal<'_, '_> {
                 WHERE
                     transactions.in_mempool = TRUE
                     AND transactions.initiator_address = s.address
+                    AND transactions.bundle_hash IS NULL
                 "#,
                 &stashed_addresses,
             )
@@ -918,6 +1570,7 @@ impl TransactionsDal<'_, '_> {
                 WHERE
                     in_mempool = TRUE
                     AND initiator_address = ANY ($1)
+                    AND bundle_hash IS NULL
                 "#,
                 &purged_addresses[..]
             )
@@ -954,6 +1607,7 @@ impl TransactionsDal<'_, '_> {
                                         )
                                     )
                                     AND tx_format != $4
+                                    AND bundle_hash IS NULL
                                 ORDER BY
                                     is_priority DESC,
                                     priority_op_id,
@@ -978,39 +1632,10 @@ impl TransactionsDal<'_, '_> {
             .await
             .unwrap();
 
-            let nonce_keys: HashMap<_, _> = transactions
+            let initiators = transactions
                 .iter()
-                .map(|tx| {
-                    let address = Address::from_slice(&tx.initiator_address);
-                    let nonce_key = get_nonce_key(&address).hashed_key();
-                    (nonce_key, address)
-                })
-                .collect();
-
-            let storage_keys: Vec<_> = nonce_keys.keys().map(|key| key.0.to_vec()).collect();
-            let nonces: HashMap<_, _> = sqlx::query!(
-                r#"
-                SELECT
-                    hashed_key,
-                    value AS "value!"
-                FROM
-                    storage
-                WHERE
-                    hashed_key = ANY ($1)
-                "#,
-                &storage_keys,
-            )
-            .fetch_all(self.storage.conn())
-            .await
-            .unwrap()
-            .into_iter()
-            .map(|row| {
-                let nonce_key = H256::from_slice(&row.hashed_key);
-                let nonce = Nonce(h256_to_u32(H256::from_slice(&row.value)));
-
-                (*nonce_keys.get(&nonce_key).unwrap(), nonce)
-            })
-            .collect();
+                .map(|tx| Address::from_slice(&tx.initiator_address));
+            let nonces = self.get_nonces_for_addresses(initiators).await.unwrap();
 
             (
                 transactions.into_iter().map(|tx| tx.into()).collect(),
@@ -1019,6 +1644,84 @@ impl TransactionsDal<'_, '_> {
         }
     }
 
+    /// Fetches new transaction bundles for the mempool. Returned bundles are ordered by the time
+    /// they were received.
+    pub async fn sync_mempool_bundles(&mut self, limit: usize) -> Vec<TransactionBundle> {
+        // Note, that transactions are updated in order of their hashes to avoid deadlocks with other UPDATE queries.
+        let transactions = sqlx::query_as!(
+            StorageTransaction,
+            r#"
+            UPDATE transactions
+            SET
+                in_mempool = TRUE
+            FROM
+                (
+                    SELECT
+                        hash
+                    FROM
+                        transactions
+                    WHERE
+                        bundle_hash IN (
+                            SELECT
+                                bundle_hash
+                            FROM
+                                transactions
+                            WHERE
+                                bundle_hash IS NOT NULL
+                                AND miniblock_number IS NULL
+                                AND in_mempool = FALSE
+                                AND error IS NULL
+                            GROUP BY
+                                bundle_hash
+                            ORDER BY
+                                MIN(received_at)
+                            LIMIT
+                                $1
+                        )
+                    ORDER BY
+                        hash
+                ) AS subquery
+            WHERE
+                transactions.hash = subquery.hash
+            RETURNING
+                transactions.*
+            "#,
+            limit as i32
+        )
+        .fetch_all(self.storage.conn())
+        .await
+        .unwrap();
+
+        let mut bundles = HashMap::<_, Vec<_>>::new();
+        for tx in transactions {
+            let bundle_hash = H256::from_slice(tx.bundle_hash.as_ref().unwrap());
+            let bundle_index = tx.bundle_index.unwrap();
+            let tx = L2Tx::try_from(Transaction::from(tx)).expect("bundle contains non-L2 tx");
+            bundles
+                .entry(bundle_hash)
+                .or_default()
+                .push((bundle_index, tx));
+        }
+        let mut bundles: Vec<_> = bundles
+            .into_iter()
+            .map(|(hash, mut transactions)| {
+                transactions.sort_unstable_by_key(|(index, _)| *index);
+                TransactionBundle {
+                    hash,
+                    transactions: transactions.into_iter().map(|(_, tx)| tx).collect(),
+                }
+            })
+            .collect();
+        bundles.sort_unstable_by_key(|bundle| {
+            bundle
+                .transactions
+                .iter()
+                .map(|tx| tx.received_timestamp_ms)
+                .min()
+        });
+        bundles
+    }
+
     pub async fn reset_mempool(&mut self) {
         {
             sqlx::query!(
@@ -1101,6 +1804,104 @@ impl TransactionsDal<'_, '_> {
         }
     }
 
+    /// Removes priority operations received in the specified L1 block or later that were
+    /// neither executed nor loaded into the state keeper mempool. Only the operations with
+    /// serial IDs greater than any retained operation are removed, so that no gaps are introduced.
+    /// Returns the number of removed operations.
+    pub async fn remove_unprocessed_priority_ops(&mut self, from_l1_block: L1BlockNumber) -> usize {
+        sqlx::query!(
+            r#"
+            DELETE FROM transactions
+            WHERE
+                is_priority = TRUE
+                AND priority_op_id IS NOT NULL
+                AND l1_block_number >= $1
+                AND miniblock_number IS NULL
+                AND in_mempool = FALSE
+                AND priority_op_id > (
+                    SELECT
+                        COALESCE(MAX(priority_op_id), -1)
+                    FROM
+                        transactions
+                    WHERE
+                        is_priority = TRUE
+                        AND (
+                            miniblock_number IS NOT NULL
+                            OR in_mempool = TRUE
+                        )
+                )
+            "#,
+            from_l1_block.0 as i32
+        )
+        .execute(self.storage.conn())
+        .await
+        .unwrap()
+        .rows_affected() as usize
+    }
+
+    /// Returns hashes of priority operations with the specified serial IDs or received in the specified L1 blocks.
+    pub async fn get_priority_op_hashes(
+        &mut self,
+        priority_op_ids: &[PriorityOpId],
+        l1_blocks: ops::RangeInclusive<L1BlockNumber>,
+    ) -> Vec<(PriorityOpId, H256)> {
+        let priority_op_ids: Vec<_> = priority_op_ids.iter().map(|id| id.0 as i64).collect();
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                priority_op_id AS "priority_op_id!",
+                hash
+            FROM
+                transactions
+            WHERE
+                is_priority = TRUE
+                AND priority_op_id IS NOT NULL
+                AND (
+                    priority_op_id = ANY ($1)
+                    OR l1_block_number BETWEEN $2 AND $3
+                )
+            ORDER BY
+                priority_op_id
+            "#,
+            &priority_op_ids,
+            l1_blocks.start().0 as i32,
+            l1_blocks.end().0 as i32
+        )
+        .fetch_all(self.storage.conn())
+        .await
+        .unwrap();
+
+        rows.into_iter()
+            .map(|row| {
+                (
+                    PriorityOpId(row.priority_op_id as u64),
+                    H256::from_slice(&row.hash),
+                )
+            })
+            .collect()
+    }
+
+    /// Removes the priority operation with the specified serial ID provided that it was neither executed
+    /// nor loaded into the state keeper mempool. Returns `false` if the operation is absent or cannot be removed.
+    pub async fn remove_unprocessed_priority_op(&mut self, priority_op_id: PriorityOpId) -> bool {
+        sqlx::query!(
+            r#"
+            DELETE FROM transactions
+            WHERE
+                is_priority = TRUE
+                AND priority_op_id = $1
+                AND miniblock_number IS NULL
+                AND in_mempool = FALSE
+            "#,
+            priority_op_id.0 as i64
+        )
+        .execute(self.storage.conn())
+        .await
+        .unwrap()
+        .rows_affected()
+            > 0
+    }
+
     pub async fn insert_trace(&mut self, hash: H256, trace: VmExecutionTrace) {
         {
             sqlx::query!(
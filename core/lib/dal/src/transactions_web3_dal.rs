@@ -1,9 +1,13 @@
-use sqlx::types::chrono::NaiveDateTime;
+use std::collections::HashMap;
+
+use bigdecimal::BigDecimal;
+use sqlx::{types::chrono::NaiveDateTime, Row};
 use zksync_types::{
-    api, Address, L2ChainId, MiniblockNumber, Transaction, ACCOUNT_CODE_STORAGE_ADDRESS,
-    FAILED_CONTRACT_DEPLOYMENT_BYTECODE_HASH, H160, H256, U256, U64,
+    api::{self, raw},
+    Address, L2ChainId, MiniblockNumber, Transaction, ACCOUNT_CODE_STORAGE_ADDRESS,
+    FAILED_CONTRACT_DEPLOYMENT_BYTECODE_HASH, H256, U256,
 };
-use zksync_utils::{bigdecimal_to_u256, h256_to_account_address};
+use zksync_utils::bigdecimal_to_u256;
 
 use crate::{
     instrument::InstrumentExt,
@@ -12,7 +16,7 @@ use crate::{
         storage_event::StorageWeb3Log,
         storage_transaction::{
             extract_web3_transaction, web3_transaction_select_sql, StorageTransaction,
-            StorageTransactionDetails,
+            StorageTransactionDetails, StorageTransactionReceipt,
         },
     },
     SqlxError, StorageProcessor,
@@ -29,7 +33,8 @@ impl TransactionsWeb3Dal<'_, '_> {
         hash: H256,
     ) -> Result<Option<api::TransactionReceipt>, SqlxError> {
         {
-            let receipt = sqlx::query!(
+            let receipt = sqlx::query_as!(
+                StorageTransactionReceipt,
                 r#"
                 WITH
                     sl AS (
@@ -77,57 +82,7 @@ impl TransactionsWeb3Dal<'_, '_> {
             .with_arg("hash", &hash)
             .fetch_optional(self.storage.conn())
             .await?
-            .map(|db_row| {
-                let status = db_row.error.map(|_| U64::zero()).unwrap_or_else(U64::one);
-
-                let tx_type = db_row.tx_format.map(U64::from).unwrap_or_default();
-                let transaction_index = db_row.index_in_block.map(U64::from).unwrap_or_default();
-
-                let block_hash = H256::from_slice(&db_row.block_hash);
-                api::TransactionReceipt {
-                    transaction_hash: H256::from_slice(&db_row.tx_hash),
-                    transaction_index,
-                    block_hash,
-                    block_number: db_row.block_number.into(),
-                    l1_batch_tx_index: db_row.l1_batch_tx_index.map(U64::from),
-                    l1_batch_number: db_row.l1_batch_number.map(U64::from),
-                    from: H160::from_slice(&db_row.initiator_address),
-                    to: db_row
-                        .transfer_to
-                        .or(db_row.execute_contract_address)
-                        .map(|addr| {
-                            serde_json::from_value::<Address>(addr)
-                                .expect("invalid address value in the database")
-                        })
-                        // For better compatibility with various clients, we never return null.
-                        .or_else(|| Some(Address::default())),
-                    cumulative_gas_used: Default::default(), // TODO: Should be actually calculated (SMA-1183).
-                    gas_used: {
-                        let refunded_gas: U256 = db_row.refunded_gas.into();
-                        db_row.gas_limit.map(|val| {
-                            let gas_limit = bigdecimal_to_u256(val);
-                            gas_limit - refunded_gas
-                        })
-                    },
-                    effective_gas_price: Some(
-                        db_row
-                            .effective_gas_price
-                            .map(bigdecimal_to_u256)
-                            .unwrap_or_default(),
-                    ),
-                    contract_address: db_row
-                        .contract_address
-                        .map(|addr| h256_to_account_address(&H256::from_slice(&addr))),
-                    logs: vec![],
-                    l2_to_l1_logs: vec![],
-                    status,
-                    root: block_hash,
-                    logs_bloom: Default::default(),
-                    // Even though the Rust SDK recommends us to supply "None" for legacy transactions
-                    // we always supply some number anyway to have the same behavior as most popular RPCs
-                    transaction_type: Some(tx_type),
-                }
-            });
+            .map(api::TransactionReceipt::from);
             match receipt {
                 Some(mut receipt) => {
                     let logs: Vec<_> = sqlx::query_as!(
@@ -191,6 +146,135 @@ impl TransactionsWeb3Dal<'_, '_> {
         }
     }
 
+    /// Returns receipts for all transactions in the specified miniblock, ordered by their index
+    /// in the miniblock.
+    pub async fn get_miniblock_receipts(
+        &mut self,
+        miniblock: MiniblockNumber,
+    ) -> Result<Vec<api::TransactionReceipt>, SqlxError> {
+        let mut receipts: Vec<_> = sqlx::query_as!(
+            StorageTransactionReceipt,
+            r#"
+            WITH
+                sl AS (
+                    SELECT DISTINCT
+                        ON (storage_logs.tx_hash) *
+                    FROM
+                        storage_logs
+                    WHERE
+                        storage_logs.address = $1
+                        AND storage_logs.miniblock_number = $2
+                    ORDER BY
+                        storage_logs.tx_hash,
+                        storage_logs.operation_number DESC
+                )
+            SELECT
+                transactions.hash AS tx_hash,
+                transactions.index_in_block AS index_in_block,
+                transactions.l1_batch_tx_index AS l1_batch_tx_index,
+                transactions.miniblock_number AS "block_number!",
+                transactions.error AS error,
+                transactions.effective_gas_price AS effective_gas_price,
+                transactions.initiator_address AS initiator_address,
+                transactions.data -> 'to' AS "transfer_to?",
+                transactions.data -> 'contractAddress' AS "execute_contract_address?",
+                transactions.tx_format AS "tx_format?",
+                transactions.refunded_gas AS refunded_gas,
+                transactions.gas_limit AS gas_limit,
+                miniblocks.hash AS "block_hash",
+                miniblocks.l1_batch_number AS "l1_batch_number?",
+                sl.key AS "contract_address?"
+            FROM
+                transactions
+                JOIN miniblocks ON miniblocks.number = transactions.miniblock_number
+                LEFT JOIN sl ON sl.tx_hash = transactions.hash
+                AND sl.value != $3
+            WHERE
+                transactions.miniblock_number = $2
+            ORDER BY
+                transactions.index_in_block
+            "#,
+            ACCOUNT_CODE_STORAGE_ADDRESS.as_bytes(),
+            miniblock.0 as i64,
+            FAILED_CONTRACT_DEPLOYMENT_BYTECODE_HASH.as_bytes()
+        )
+        .instrument("get_miniblock_receipts")
+        .with_arg("miniblock", &miniblock)
+        .fetch_all(self.storage.conn())
+        .await?
+        .into_iter()
+        .map(api::TransactionReceipt::from)
+        .collect();
+
+        let Some(first_receipt) = receipts.first() else {
+            return Ok(receipts);
+        };
+        let block_hash = first_receipt.block_hash;
+        let l1_batch_number = first_receipt.l1_batch_number;
+
+        let logs = sqlx::query_as!(
+            StorageWeb3Log,
+            r#"
+            SELECT
+                address,
+                topic1,
+                topic2,
+                topic3,
+                topic4,
+                value,
+                NULL::bytea AS "block_hash",
+                NULL::BIGINT AS "l1_batch_number?",
+                miniblock_number,
+                tx_hash,
+                tx_index_in_block,
+                event_index_in_block,
+                event_index_in_tx
+            FROM
+                events
+            WHERE
+                miniblock_number = $1
+            ORDER BY
+                event_index_in_block ASC
+            "#,
+            miniblock.0 as i64
+        )
+        .instrument("get_miniblock_receipts_events")
+        .with_arg("miniblock", &miniblock)
+        .fetch_all(self.storage.conn())
+        .await?;
+        let l2_to_l1_logs = self
+            .storage
+            .events_dal()
+            .miniblock_l2_to_l1_logs(miniblock)
+            .await?;
+
+        let mut logs_by_tx = HashMap::<_, Vec<_>>::new();
+        for log in logs {
+            let mut log = api::Log::from(log);
+            log.block_hash = Some(block_hash);
+            log.l1_batch_number = l1_batch_number;
+            let tx_hash = log.transaction_hash.unwrap();
+            logs_by_tx.entry(tx_hash).or_default().push(log);
+        }
+        let mut l2_to_l1_logs_by_tx = HashMap::<_, Vec<_>>::new();
+        for log in l2_to_l1_logs {
+            let mut log = api::L2ToL1Log::from(log);
+            log.block_hash = Some(block_hash);
+            log.l1_batch_number = l1_batch_number;
+            l2_to_l1_logs_by_tx
+                .entry(log.transaction_hash)
+                .or_default()
+                .push(log);
+        }
+
+        for receipt in &mut receipts {
+            let tx_hash = receipt.transaction_hash;
+            receipt.logs = logs_by_tx.remove(&tx_hash).unwrap_or_default();
+            receipt.l2_to_l1_logs = l2_to_l1_logs_by_tx.remove(&tx_hash).unwrap_or_default();
+        }
+        Ok(receipts)
+    }
+
     pub async fn get_transaction(
         &mut self,
         transaction_id: api::TransactionId,
@@ -287,6 +371,7 @@ impl TransactionsWeb3Dal<'_, '_> {
     }
 
     /// Returns hashes of txs which were received after `from_timestamp` and the time of receiving the last tx.
+    /// Private transactions are skipped until they are included into a miniblock.
     pub async fn get_pending_txs_hashes_after(
         &mut self,
         from_timestamp: NaiveDateTime,
@@ -302,6 +387,10 @@ impl TransactionsWeb3Dal<'_, '_> {
                 LEFT JOIN miniblocks ON miniblocks.number = miniblock_number
             WHERE
                 received_at > $1
+                AND (
+                    is_private = FALSE
+                    OR miniblock_number IS NOT NULL
+                )
             ORDER BY
                 received_at ASC
             LIMIT
@@ -321,6 +410,89 @@ impl TransactionsWeb3Dal<'_, '_> {
         Ok((hashes, last_loc))
     }
 
+    /// Same as [`Self::get_pending_txs_hashes_after()`], but returns full API transactions.
+    pub async fn get_pending_txs_after(
+        &mut self,
+        from_timestamp: NaiveDateTime,
+        limit: Option<usize>,
+        chain_id: L2ChainId,
+    ) -> Result<(Vec<api::Transaction>, Option<NaiveDateTime>), SqlxError> {
+        let query = format!(
+            "SELECT {}, transactions.received_at
+            FROM transactions
+            LEFT JOIN miniblocks ON miniblocks.number = transactions.miniblock_number
+            WHERE received_at > $1
+                AND (transactions.is_private = FALSE OR transactions.miniblock_number IS NOT NULL)
+            ORDER BY received_at ASC
+            LIMIT $2",
+            web3_transaction_select_sql()
+        );
+        let rows = sqlx::query(&query)
+            .bind(from_timestamp)
+            .bind(limit.map(|limit| limit as i64))
+            .instrument("get_pending_txs_after")
+            .with_arg("from_timestamp", &from_timestamp)
+            .fetch_all(self.storage.conn())
+            .await?;
+
+        let last_loc = rows
+            .last()
+            .map(|row| row.get::<NaiveDateTime, _>("received_at"));
+        let transactions = rows
+            .into_iter()
+            .map(|row| extract_web3_transaction(row, chain_id))
+            .collect();
+        Ok((transactions, last_loc))
+    }
+
+    /// Returns the specified percentile of effective priority fees for L2 transactions in the mempool
+    /// given the provided base fee. Only `limit` most recently received transactions are taken into account.
+    /// Returns `None` if the mempool is empty.
+    pub async fn get_mempool_priority_fee_percentile(
+        &mut self,
+        base_fee: u64,
+        percentile: f64,
+        limit: usize,
+    ) -> Result<Option<U256>, SqlxError> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                PERCENTILE_DISC($2) WITHIN GROUP (
+                    ORDER BY
+                        priority_fee
+                ) AS priority_fee
+            FROM
+                (
+                    SELECT
+                        GREATEST(
+                            LEAST(max_priority_fee_per_gas, max_fee_per_gas - $1),
+                            0
+                        ) AS priority_fee
+                    FROM
+                        transactions
+                    WHERE
+                        miniblock_number IS NULL
+                        AND is_priority = FALSE
+                        AND error IS NULL
+                    ORDER BY
+                        received_at DESC
+                    LIMIT
+                        $3
+                ) AS mempool_txs
+            "#,
+            BigDecimal::from(base_fee),
+            percentile,
+            limit as i64
+        )
+        .instrument("get_mempool_priority_fee_percentile")
+        .with_arg("base_fee", &base_fee)
+        .with_arg("percentile", &percentile)
+        .fetch_one(self.storage.conn())
+        .await?;
+
+        Ok(row.priority_fee.map(bigdecimal_to_u256))
+    }
+
     pub async fn next_nonce_by_initiator_account(
         &mut self,
         initiator_address: Address,
@@ -407,6 +579,154 @@ impl TransactionsWeb3Dal<'_, '_> {
 
         Ok(rows.into_iter().map(Into::into).collect())
     }
+
+    /// Same as [`Self::get_raw_miniblock_transactions()`], but only returns at most `limit` transactions
+    /// starting from the specified index in the miniblock.
+    pub async fn get_raw_miniblock_transactions_page(
+        &mut self,
+        miniblock: MiniblockNumber,
+        offset: usize,
+        limit: usize,
+    ) -> sqlx::Result<Vec<Transaction>> {
+        let rows = sqlx::query_as!(
+            StorageTransaction,
+            r#"
+            SELECT
+                *
+            FROM
+                transactions
+            WHERE
+                miniblock_number = $1
+                AND index_in_block >= $2
+            ORDER BY
+                index_in_block
+            LIMIT
+                $3
+            "#,
+            miniblock.0 as i64,
+            offset as i32,
+            limit as i64
+        )
+        .instrument("get_raw_miniblock_transactions_page")
+        .with_arg("miniblock", &miniblock)
+        .with_arg("offset", &offset)
+        .with_arg("limit", &limit)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Returns the raw payload of the transaction with the specified hash. See [`raw::encode_transaction()`]
+    /// for the encoding details. A transaction that cannot be encoded results in a decode error.
+    pub async fn get_raw_transaction(&mut self, hash: H256) -> sqlx::Result<Option<Vec<u8>>> {
+        let row = sqlx::query_as!(
+            StorageTransaction,
+            r#"
+            SELECT
+                *
+            FROM
+                transactions
+            WHERE
+                hash = $1
+            "#,
+            hash.as_bytes()
+        )
+        .instrument("get_raw_transaction")
+        .with_arg("hash", &hash)
+        .fetch_optional(self.storage.conn())
+        .await?;
+
+        row.map(|row| raw::encode_transaction(&row.into()))
+            .transpose()
+            .map_err(|err| sqlx::Error::Decode(err.into()))
+    }
+
+    /// Returns EIP-2718-encoded receipts of all transactions in the specified miniblock ordered by the transaction
+    /// index. See [`raw::encode_receipt()`] for the encoding details.
+    pub async fn get_raw_miniblock_receipts(
+        &mut self,
+        miniblock: MiniblockNumber,
+    ) -> sqlx::Result<Vec<Vec<u8>>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                index_in_block,
+                tx_format,
+                error,
+                gas_limit,
+                refunded_gas
+            FROM
+                transactions
+            WHERE
+                miniblock_number = $1
+            ORDER BY
+                index_in_block
+            "#,
+            miniblock.0 as i64
+        )
+        .instrument("get_raw_miniblock_receipts")
+        .with_arg("miniblock", &miniblock)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        let logs = sqlx::query_as!(
+            StorageWeb3Log,
+            r#"
+            SELECT
+                address,
+                topic1,
+                topic2,
+                topic3,
+                topic4,
+                value,
+                NULL::bytea AS "block_hash",
+                NULL::BIGINT AS "l1_batch_number?",
+                miniblock_number,
+                tx_hash,
+                tx_index_in_block,
+                event_index_in_block,
+                event_index_in_tx
+            FROM
+                events
+            WHERE
+                miniblock_number = $1
+            ORDER BY
+                event_index_in_block ASC
+            "#,
+            miniblock.0 as i64
+        )
+        .instrument("get_raw_miniblock_receipts_events")
+        .with_arg("miniblock", &miniblock)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        let mut logs_by_tx = HashMap::<_, Vec<_>>::new();
+        for log in logs {
+            let tx_index = log.tx_index_in_block;
+            logs_by_tx
+                .entry(tx_index)
+                .or_default()
+                .push(api::Log::from(log));
+        }
+
+        let mut cumulative_gas_used = U256::zero();
+        let receipts = rows.into_iter().map(|row| {
+            let gas_limit = row.gas_limit.map(bigdecimal_to_u256).unwrap_or_default();
+            cumulative_gas_used += gas_limit - U256::from(row.refunded_gas as u64);
+            let logs = row
+                .index_in_block
+                .and_then(|index| logs_by_tx.remove(&index))
+                .unwrap_or_default();
+            raw::encode_receipt(
+                row.tx_format.unwrap_or_default() as u8,
+                row.error.is_none(),
+                cumulative_gas_used,
+                &logs,
+            )
+        });
+        Ok(receipts.collect())
+    }
 }
 
 #[cfg(test)]
@@ -536,4 +856,117 @@ mod tests {
         assert_eq!(raw_txs.len(), 1);
         assert_eq!(raw_txs[0].hash(), tx_hash);
     }
+
+    #[tokio::test]
+    async fn getting_miniblock_receipts() {
+        let connection_pool = ConnectionPool::test_pool().await;
+        let mut conn = connection_pool.access_storage().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(ProtocolVersion::default())
+            .await;
+        let tx = mock_l2_transaction();
+        let tx_hash = tx.hash();
+        prepare_transaction(&mut conn, tx).await;
+
+        let receipts = conn
+            .transactions_web3_dal()
+            .get_miniblock_receipts(MiniblockNumber(0))
+            .await
+            .unwrap();
+        assert!(receipts.is_empty());
+
+        let receipts = conn
+            .transactions_web3_dal()
+            .get_miniblock_receipts(MiniblockNumber(1))
+            .await
+            .unwrap();
+        let expected_receipt = conn
+            .transactions_web3_dal()
+            .get_transaction_receipt(tx_hash)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(receipts, [expected_receipt]);
+    }
+
+    #[tokio::test]
+    async fn getting_raw_payloads() {
+        let connection_pool = ConnectionPool::test_pool().await;
+        let mut conn = connection_pool.access_storage().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(ProtocolVersion::default())
+            .await;
+        let tx = mock_l2_transaction();
+        let tx_hash = tx.hash();
+        let tx_input = tx.common_data.input_data().unwrap().to_vec();
+        prepare_transaction(&mut conn, tx).await;
+
+        let raw_tx = conn
+            .transactions_web3_dal()
+            .get_raw_transaction(tx_hash)
+            .await
+            .unwrap();
+        assert_eq!(raw_tx, Some(tx_input));
+        let raw_tx = conn
+            .transactions_web3_dal()
+            .get_raw_transaction(H256::zero())
+            .await
+            .unwrap();
+        assert_eq!(raw_tx, None);
+
+        let receipts = conn
+            .transactions_web3_dal()
+            .get_raw_miniblock_receipts(MiniblockNumber(1))
+            .await
+            .unwrap();
+        assert_eq!(receipts.len(), 1);
+
+        let raw_block = conn
+            .blocks_web3_dal()
+            .get_raw_block(MiniblockNumber(1))
+            .await
+            .unwrap();
+        assert!(raw_block.is_some());
+        let raw_block = conn
+            .blocks_web3_dal()
+            .get_raw_block(MiniblockNumber(42))
+            .await
+            .unwrap();
+        assert!(raw_block.is_none());
+    }
+
+    #[tokio::test]
+    async fn getting_mempool_priority_fee_percentile() {
+        let connection_pool = ConnectionPool::test_pool().await;
+        let mut conn = connection_pool.access_storage().await.unwrap();
+        let fee = conn
+            .transactions_web3_dal()
+            .get_mempool_priority_fee_percentile(100, 0.5, 10)
+            .await
+            .unwrap();
+        assert_eq!(fee, None);
+
+        // Effective priority fees are 0, 50 (capped by `max_fee_per_gas`) and 80.
+        for (max_fee, max_priority_fee) in [(100_u64, 0_u64), (150, 100), (500, 80)] {
+            let mut tx = mock_l2_transaction();
+            tx.common_data.fee.max_fee_per_gas = max_fee.into();
+            tx.common_data.fee.max_priority_fee_per_gas = max_priority_fee.into();
+            conn.transactions_dal()
+                .insert_transaction_l2(tx, TransactionExecutionMetrics::default())
+                .await;
+        }
+
+        let median_fee = conn
+            .transactions_web3_dal()
+            .get_mempool_priority_fee_percentile(100, 0.5, 10)
+            .await
+            .unwrap();
+        assert_eq!(median_fee, Some(50.into()));
+        let max_fee = conn
+            .transactions_web3_dal()
+            .get_mempool_priority_fee_percentile(100, 1.0, 10)
+            .await
+            .unwrap();
+        assert_eq!(max_fee, Some(80.into()));
+    }
 }
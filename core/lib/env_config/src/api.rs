@@ -48,7 +48,9 @@ impl FromEnv for MerkleTreeApiConfig {
 
 #[cfg(test)]
 mod tests {
-    use std::num::NonZeroU32;
+    use std::num::{NonZeroU32, NonZeroUsize};
+
+    use zksync_config::configs::api::{ApiAuthMethod, SubscriptionBackpressurePolicy};
 
     use super::*;
     use crate::test_utils::{hash, EnvMutex};
@@ -62,12 +64,27 @@ mod tests {
                 http_url: "http://127.0.0.1:3050".into(),
                 ws_port: 3051,
                 ws_url: "ws://127.0.0.1:3051".into(),
+                grpc_port: Some(3053),
+                grpc_bind_address: Some("127.0.0.1".parse().unwrap()),
+                grpc_max_concurrent_streams: Some(500),
                 req_entities_limit: Some(10000),
                 filters_limit: Some(10000),
+                shared_filters: true,
+                sse_enabled: true,
                 subscriptions_limit: Some(10000),
+                max_subscriptions_per_connection: Some(64),
+                subscription_queue_capacity: Some(512),
+                subscription_backpressure_policy: SubscriptionBackpressurePolicy::DropOldest,
                 pubsub_polling_interval: Some(200),
                 max_nonce_ahead: 5,
+                replacement_fee_bump_percent: Some(25),
+                max_pending_txs_per_account: Some(64),
+                max_pending_txs_per_paymaster: Some(1024),
+                tx_policy_path: Some("/etc/zksync/tx_policy.json".to_owned()),
+                tx_policy_reload_interval: Some(5),
                 request_timeout: Some(10),
+                graceful_shutdown_timeout: Some(30),
+                read_replica_max_lag: Some(5),
                 account_pks: Some(vec![
                     hash("0x0000000000000000000000000000000000000000000000000000000000000001"),
                     hash("0x0000000000000000000000000000000000000000000000000000000000000002"),
@@ -75,17 +92,49 @@ mod tests {
                 estimate_gas_scale_factor: 1.0f64,
                 gas_price_scale_factor: 1.2,
                 estimate_gas_acceptable_overestimation: 1000,
+                estimate_gas_lower_bound: Some(21000),
+                estimate_gas_upper_bound: Some(50000000),
+                estimate_gas_max_iterations: Some(24),
+                estimate_gas_cache_capacity: Some(2048),
+                call_gas_limit: Some(200000000),
                 max_tx_size: 1000000,
                 vm_execution_cache_misses_limit: None,
                 vm_concurrency_limit: Some(512),
+                heavy_calls_vm_concurrency_limit: Some(128),
+                vm_concurrency_limit_per_client: Some(64),
                 factory_deps_cache_size_mb: Some(128),
                 initial_writes_cache_size_mb: Some(32),
                 latest_values_cache_size_mb: Some(256),
                 fee_history_limit: Some(100),
                 max_batch_request_size: Some(200),
                 max_response_body_size_mb: Some(10),
+                max_batch_response_size_mb: Some(50),
+                batch_request_parallelism: NonZeroUsize::new(8),
+                heavy_calls_concurrency_limit: NonZeroUsize::new(16),
+                light_calls_concurrency_limit: NonZeroUsize::new(1024),
+                response_compression_enabled: true,
+                response_compression_min_size: Some(4096),
                 websocket_requests_per_minute_limit: Some(NonZeroU32::new(10).unwrap()),
+                rate_limit_requests_per_minute_per_ip: Some(NonZeroU32::new(600).unwrap()),
+                rate_limit_compute_units_per_minute_per_ip: Some(NonZeroU32::new(3000).unwrap()),
+                rate_limit_requests_per_minute_per_api_key: Some(NonZeroU32::new(6000).unwrap()),
+                rate_limit_compute_units_per_minute_per_api_key: None,
+                rate_limit_api_keys: Some(vec!["key1".into(), "key2".into()]),
+                rate_limit_trusted_proxy_hops: Some(1),
+                authenticated_namespaces: Some(vec!["debug".into(), "snapshots".into()]),
+                auth_method: ApiAuthMethod::Jwt,
+                auth_secret: Some("secret".into()),
+                denied_methods: Some(vec!["debug_*".into(), "eth_gasPrice".into()]),
+                allowed_methods: Some(vec!["debug_traceTransaction".into()]),
                 tree_api_url: None,
+                max_tree_lag_for_proofs: Some(10),
+                trace_block_reexecution_enabled: true,
+                max_priority_fee_smoothing_factor: Some(0.5),
+                eth_call_cache_capacity: Some(512),
+                get_logs_block_range_limit: Some(10000),
+                get_logs_max_results: Some(5000),
+                trace_call_many_max_calls: Some(20),
+                trace_filter_block_range_limit: Some(1000),
             },
             contract_verification: ContractVerificationApiConfig {
                 port: 3070,
@@ -108,28 +157,74 @@ mod tests {
             API_WEB3_JSON_RPC_HTTP_PORT="3050"
             API_WEB3_JSON_RPC_HTTP_URL="http://127.0.0.1:3050"
             API_WEB3_JSON_RPC_WS_PORT="3051"
+            API_WEB3_JSON_RPC_GRPC_PORT="3053"
+            API_WEB3_JSON_RPC_GRPC_BIND_ADDRESS="127.0.0.1"
+            API_WEB3_JSON_RPC_GRPC_MAX_CONCURRENT_STREAMS=500
             API_WEB3_JSON_RPC_WS_URL="ws://127.0.0.1:3051"
             API_WEB3_JSON_RPC_REQ_ENTITIES_LIMIT=10000
             API_WEB3_JSON_RPC_FILTERS_LIMIT=10000
+            API_WEB3_JSON_RPC_SHARED_FILTERS=true
+            API_WEB3_JSON_RPC_SSE_ENABLED=true
             API_WEB3_JSON_RPC_SUBSCRIPTIONS_LIMIT=10000
+            API_WEB3_JSON_RPC_MAX_SUBSCRIPTIONS_PER_CONNECTION=64
+            API_WEB3_JSON_RPC_SUBSCRIPTION_QUEUE_CAPACITY=512
+            API_WEB3_JSON_RPC_SUBSCRIPTION_BACKPRESSURE_POLICY=drop_oldest
             API_WEB3_JSON_RPC_PUBSUB_POLLING_INTERVAL=200
             API_WEB3_JSON_RPC_MAX_NONCE_AHEAD=5
+            API_WEB3_JSON_RPC_REPLACEMENT_FEE_BUMP_PERCENT=25
+            API_WEB3_JSON_RPC_MAX_PENDING_TXS_PER_ACCOUNT=64
+            API_WEB3_JSON_RPC_MAX_PENDING_TXS_PER_PAYMASTER=1024
+            API_WEB3_JSON_RPC_TX_POLICY_PATH="/etc/zksync/tx_policy.json"
+            API_WEB3_JSON_RPC_TX_POLICY_RELOAD_INTERVAL=5
             API_WEB3_JSON_RPC_GAS_PRICE_SCALE_FACTOR=1.2
             API_WEB3_JSON_RPC_REQUEST_TIMEOUT=10
+            API_WEB3_JSON_RPC_GRACEFUL_SHUTDOWN_TIMEOUT=30
+            API_WEB3_JSON_RPC_READ_REPLICA_MAX_LAG=5
             API_WEB3_JSON_RPC_ACCOUNT_PKS="0x0000000000000000000000000000000000000000000000000000000000000001,0x0000000000000000000000000000000000000000000000000000000000000002"
             API_WEB3_JSON_RPC_ESTIMATE_GAS_SCALE_FACTOR=1.0
             API_WEB3_JSON_RPC_ESTIMATE_GAS_ACCEPTABLE_OVERESTIMATION=1000
+            API_WEB3_JSON_RPC_ESTIMATE_GAS_LOWER_BOUND=21000
+            API_WEB3_JSON_RPC_ESTIMATE_GAS_UPPER_BOUND=50000000
+            API_WEB3_JSON_RPC_ESTIMATE_GAS_MAX_ITERATIONS=24
+            API_WEB3_JSON_RPC_ESTIMATE_GAS_CACHE_CAPACITY=2048
+            API_WEB3_JSON_RPC_CALL_GAS_LIMIT=200000000
             API_WEB3_JSON_RPC_MAX_TX_SIZE=1000000
             API_WEB3_JSON_RPC_VM_CONCURRENCY_LIMIT=512
+            API_WEB3_JSON_RPC_HEAVY_CALLS_VM_CONCURRENCY_LIMIT=128
+            API_WEB3_JSON_RPC_VM_CONCURRENCY_LIMIT_PER_CLIENT=64
             API_WEB3_JSON_RPC_FACTORY_DEPS_CACHE_SIZE_MB=128
             API_WEB3_JSON_RPC_INITIAL_WRITES_CACHE_SIZE_MB=32
             API_WEB3_JSON_RPC_LATEST_VALUES_CACHE_SIZE_MB=256
             API_WEB3_JSON_RPC_FEE_HISTORY_LIMIT=100
             API_WEB3_JSON_RPC_MAX_BATCH_REQUEST_SIZE=200
             API_WEB3_JSON_RPC_WEBSOCKET_REQUESTS_PER_MINUTE_LIMIT=10
+            API_WEB3_JSON_RPC_RATE_LIMIT_REQUESTS_PER_MINUTE_PER_IP=600
+            API_WEB3_JSON_RPC_RATE_LIMIT_COMPUTE_UNITS_PER_MINUTE_PER_IP=3000
+            API_WEB3_JSON_RPC_RATE_LIMIT_REQUESTS_PER_MINUTE_PER_API_KEY=6000
+            API_WEB3_JSON_RPC_RATE_LIMIT_API_KEYS="key1,key2"
+            API_WEB3_JSON_RPC_RATE_LIMIT_TRUSTED_PROXY_HOPS=1
+            API_WEB3_JSON_RPC_AUTHENTICATED_NAMESPACES="debug,snapshots"
+            API_WEB3_JSON_RPC_AUTH_METHOD=jwt
+            API_WEB3_JSON_RPC_AUTH_SECRET="secret"
+            API_WEB3_JSON_RPC_DENIED_METHODS="debug_*,eth_gasPrice"
+            API_WEB3_JSON_RPC_ALLOWED_METHODS="debug_traceTransaction"
+            API_WEB3_JSON_RPC_TRACE_BLOCK_REEXECUTION_ENABLED=true
+            API_WEB3_JSON_RPC_MAX_PRIORITY_FEE_SMOOTHING_FACTOR=0.5
+            API_WEB3_JSON_RPC_ETH_CALL_CACHE_CAPACITY=512
+            API_WEB3_JSON_RPC_GET_LOGS_BLOCK_RANGE_LIMIT=10000
+            API_WEB3_JSON_RPC_GET_LOGS_MAX_RESULTS=5000
+            API_WEB3_JSON_RPC_TRACE_CALL_MANY_MAX_CALLS=20
+            API_WEB3_JSON_RPC_TRACE_FILTER_BLOCK_RANGE_LIMIT=1000
+            API_WEB3_JSON_RPC_MAX_TREE_LAG_FOR_PROOFS=10
             API_CONTRACT_VERIFICATION_PORT="3070"
             API_CONTRACT_VERIFICATION_URL="http://127.0.0.1:3070"
             API_WEB3_JSON_RPC_MAX_RESPONSE_BODY_SIZE_MB=10
+            API_WEB3_JSON_RPC_MAX_BATCH_RESPONSE_SIZE_MB=50
+            API_WEB3_JSON_RPC_BATCH_REQUEST_PARALLELISM=8
+            API_WEB3_JSON_RPC_HEAVY_CALLS_CONCURRENCY_LIMIT=16
+            API_WEB3_JSON_RPC_LIGHT_CALLS_CONCURRENCY_LIMIT=1024
+            API_WEB3_JSON_RPC_RESPONSE_COMPRESSION_ENABLED=true
+            API_WEB3_JSON_RPC_RESPONSE_COMPRESSION_MIN_SIZE=4096
             API_PROMETHEUS_LISTENER_PORT="3312"
             API_PROMETHEUS_PUSHGATEWAY_URL="http://127.0.0.1:9091"
             API_PROMETHEUS_PUSH_INTERVAL_MS=100
@@ -36,7 +36,7 @@ impl FromEnv for MempoolConfig {
 
 #[cfg(test)]
 mod tests {
-    use zksync_basic_types::L2ChainId;
+    use zksync_basic_types::{L1BatchCommitmentMode, L2ChainId};
 
     use super::*;
     use crate::test_utils::{addr, EnvMutex};
@@ -71,6 +71,12 @@ mod tests {
             block_commit_deadline_ms: 2500,
             miniblock_commit_deadline_ms: 1000,
             miniblock_seal_queue_capacity: 10,
+            fixed_miniblock_interval_sec: Some(2),
+            fixed_interval_empty_miniblocks: true,
+            empty_miniblock_interval_sec: Some(10),
+            max_idle_gap_sec: Some(600),
+            max_priority_txs_per_miniblock: Some(10),
+            max_priority_gas_per_miniblock: Some(80_000_000),
             max_single_tx_gas: 1_000_000,
             max_allowed_l2_tx_gas_limit: 2_000_000_000,
             close_block_at_eth_params_percentage: 0.2,
@@ -81,11 +87,21 @@ mod tests {
             fee_account_addr: addr("de03a0B5963f75f1C8485B355fF6D30f3093BDE7"),
             reject_tx_at_gas_percentage: 0.5,
             fair_l2_gas_price: 250000000,
+            max_fair_l2_gas_price: Some(2_500_000_000),
+            target_miniblock_gas: Some(50_000_000),
+            l2_gas_price_change_denominator: Some(8),
+            base_token_conversion_numerator: Some(3),
+            base_token_conversion_denominator: Some(2),
+            l1_batch_commit_data_generator_mode: L1BatchCommitmentMode::Validium,
             validation_computational_gas_limit: 10_000_000,
             save_call_traces: false,
             virtual_blocks_interval: 1,
             virtual_blocks_per_miniblock: 1,
             upload_witness_inputs_to_gcs: false,
+            storage_prefetch_workers: Some(4),
+            shadow_vm_execution: true,
+            saturated_proving_queue_len: Some(20),
+            min_batch_circuits_fraction: Some(0.3),
             enum_index_migration_chunk_size: Some(2_000),
         }
     }
@@ -107,11 +123,27 @@ mod tests {
             CHAIN_STATE_KEEPER_BLOCK_COMMIT_DEADLINE_MS="2500"
             CHAIN_STATE_KEEPER_MINIBLOCK_COMMIT_DEADLINE_MS="1000"
             CHAIN_STATE_KEEPER_MINIBLOCK_SEAL_QUEUE_CAPACITY="10"
+            CHAIN_STATE_KEEPER_FIXED_MINIBLOCK_INTERVAL_SEC="2"
+            CHAIN_STATE_KEEPER_FIXED_INTERVAL_EMPTY_MINIBLOCKS="true"
+            CHAIN_STATE_KEEPER_EMPTY_MINIBLOCK_INTERVAL_SEC="10"
+            CHAIN_STATE_KEEPER_MAX_IDLE_GAP_SEC="600"
             CHAIN_STATE_KEEPER_FAIR_L2_GAS_PRICE="250000000"
+            CHAIN_STATE_KEEPER_MAX_FAIR_L2_GAS_PRICE="2500000000"
+            CHAIN_STATE_KEEPER_TARGET_MINIBLOCK_GAS="50000000"
+            CHAIN_STATE_KEEPER_L2_GAS_PRICE_CHANGE_DENOMINATOR="8"
+            CHAIN_STATE_KEEPER_BASE_TOKEN_CONVERSION_NUMERATOR="3"
+            CHAIN_STATE_KEEPER_BASE_TOKEN_CONVERSION_DENOMINATOR="2"
+            CHAIN_STATE_KEEPER_L1_BATCH_COMMIT_DATA_GENERATOR_MODE="Validium"
             CHAIN_STATE_KEEPER_VALIDATION_COMPUTATIONAL_GAS_LIMIT="10000000"
             CHAIN_STATE_KEEPER_SAVE_CALL_TRACES="false"
             CHAIN_STATE_KEEPER_UPLOAD_WITNESS_INPUTS_TO_GCS="false"
             CHAIN_STATE_KEEPER_ENUM_INDEX_MIGRATION_CHUNK_SIZE="2000"
+            CHAIN_STATE_KEEPER_STORAGE_PREFETCH_WORKERS="4"
+            CHAIN_STATE_KEEPER_SHADOW_VM_EXECUTION="true"
+            CHAIN_STATE_KEEPER_MAX_PRIORITY_TXS_PER_MINIBLOCK="10"
+            CHAIN_STATE_KEEPER_MAX_PRIORITY_GAS_PER_MINIBLOCK="80000000"
+            CHAIN_STATE_KEEPER_SATURATED_PROVING_QUEUE_LEN="20"
+            CHAIN_STATE_KEEPER_MIN_BATCH_CIRCUITS_FRACTION="0.3"
         "#;
         lock.set_env(config);
 
@@ -145,6 +177,8 @@ mod tests {
             stuck_tx_timeout: 10,
             remove_stuck_txs: true,
             delay_interval: 100,
+            tx_ttl: Some(3600),
+            max_pending_txs: Some(100_000),
         }
     }
 
@@ -158,6 +192,8 @@ mod tests {
             CHAIN_MEMPOOL_REMOVE_STUCK_TXS="true"
             CHAIN_MEMPOOL_DELAY_INTERVAL="100"
             CHAIN_MEMPOOL_CAPACITY="1000000"
+            CHAIN_MEMPOOL_TX_TTL="3600"
+            CHAIN_MEMPOOL_MAX_PENDING_TXS="100000"
         "#;
         lock.set_env(config);
 
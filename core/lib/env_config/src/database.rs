@@ -20,6 +20,7 @@ impl FromEnv for PostgresConfig {
         let replica_url = env::var("DATABASE_REPLICA_URL")
             .ok()
             .or_else(|| master_url.clone());
+        let api_read_replica_url = env::var("DATABASE_API_READ_REPLICA_URL").ok();
         let prover_url = env::var("DATABASE_PROVER_URL")
             .ok()
             .or_else(|| master_url.clone());
@@ -38,6 +39,7 @@ impl FromEnv for PostgresConfig {
         Ok(Self {
             master_url,
             replica_url,
+            api_read_replica_url,
             prover_url,
             max_connections,
             statement_timeout_sec,
@@ -49,7 +51,9 @@ impl FromEnv for PostgresConfig {
 mod tests {
     use std::time::Duration;
 
-    use zksync_config::configs::database::MerkleTreeMode;
+    use zksync_config::configs::database::{
+        MerkleTreeBackend, MerkleTreeMode, RocksdbCompactionStyle,
+    };
 
     use super::*;
     use crate::test_utils::EnvMutex;
@@ -61,23 +65,70 @@ mod tests {
         let mut lock = MUTEX.lock();
         let config = r#"
             DATABASE_STATE_KEEPER_DB_PATH="/db/state_keeper"
+            DATABASE_STATE_KEEPER_DB_BLOCK_CACHE_SIZE_MB=64
+            DATABASE_STATE_KEEPER_DB_COMPACTION_STYLE=universal
+            DATABASE_STATE_KEEPER_DB_MAX_BACKGROUND_JOBS=4
             DATABASE_MERKLE_TREE_PATH="/db/tree"
             DATABASE_MERKLE_TREE_MODE=lightweight
+            DATABASE_MERKLE_TREE_BACKEND=in_memory
             DATABASE_MERKLE_TREE_MULTI_GET_CHUNK_SIZE=250
             DATABASE_MERKLE_TREE_MEMTABLE_CAPACITY_MB=512
             DATABASE_MERKLE_TREE_STALLED_WRITES_TIMEOUT_SEC=60
             DATABASE_MERKLE_TREE_MAX_L1_BATCHES_PER_ITER=50
+            DATABASE_MERKLE_TREE_COMPACTION_STYLE=level
+            DATABASE_MERKLE_TREE_WRITE_BUFFER_SIZE_MB=128
+            DATABASE_MERKLE_TREE_MAX_WRITE_BUFFER_NUMBER=4
+            DATABASE_MERKLE_TREE_MAX_BACKGROUND_JOBS=6
+            DATABASE_MERKLE_TREE_THREAD_POOL_SIZE=8
+            DATABASE_MERKLE_TREE_PRUNING_ENABLED=true
+            DATABASE_MERKLE_TREE_PRUNING_RETAINED_L1_BATCHES=500
+            DATABASE_MERKLE_TREE_PRUNING_POLL_INTERVAL_MS=30000
+            DATABASE_MERKLE_TREE_CONSISTENCY_CHECK_ENABLED=true
+            DATABASE_MERKLE_TREE_CONSISTENCY_CHECK_SAMPLE_SIZE=500
+            DATABASE_MERKLE_TREE_CONSISTENCY_CHECK_INTERVAL_MS=10000
         "#;
         lock.set_env(config);
 
         let db_config = DBConfig::from_env().unwrap();
         assert_eq!(db_config.state_keeper_db_path, "/db/state_keeper");
+        assert_eq!(db_config.state_keeper_db_block_cache_size(), Some(64 << 20));
+        assert_eq!(
+            db_config.state_keeper_db_compaction_style,
+            RocksdbCompactionStyle::Universal
+        );
+        assert_eq!(db_config.state_keeper_db_write_buffer_size(), None);
+        assert_eq!(db_config.state_keeper_db_max_background_jobs, Some(4));
         assert_eq!(db_config.merkle_tree.path, "/db/tree");
         assert_eq!(db_config.merkle_tree.mode, MerkleTreeMode::Lightweight);
+        assert_eq!(db_config.merkle_tree.backend, MerkleTreeBackend::InMemory);
         assert_eq!(db_config.merkle_tree.multi_get_chunk_size, 250);
         assert_eq!(db_config.merkle_tree.max_l1_batches_per_iter, 50);
         assert_eq!(db_config.merkle_tree.memtable_capacity_mb, 512);
         assert_eq!(db_config.merkle_tree.stalled_writes_timeout_sec, 60);
+        assert_eq!(
+            db_config.merkle_tree.compaction_style,
+            RocksdbCompactionStyle::Level
+        );
+        assert_eq!(db_config.merkle_tree.write_buffer_size(), Some(128 << 20));
+        assert_eq!(db_config.merkle_tree.max_write_buffer_number, Some(4));
+        assert_eq!(db_config.merkle_tree.max_background_jobs, Some(6));
+        assert_eq!(db_config.merkle_tree.thread_pool_size, Some(8));
+        assert_eq!(
+            db_config.merkle_tree.pruning_retained_l1_batches(),
+            Some(500)
+        );
+        assert_eq!(
+            db_config.merkle_tree.pruning_poll_interval(),
+            Duration::from_secs(30)
+        );
+        assert_eq!(
+            db_config.merkle_tree.consistency_check_sample_size(),
+            Some(500)
+        );
+        assert_eq!(
+            db_config.merkle_tree.consistency_check_interval(),
+            Duration::from_secs(10)
+        );
     }
 
     #[test]
@@ -85,25 +136,53 @@ mod tests {
         let mut lock = MUTEX.lock();
         lock.remove_env(&[
             "DATABASE_STATE_KEEPER_DB_PATH",
+            "DATABASE_STATE_KEEPER_DB_BLOCK_CACHE_SIZE_MB",
+            "DATABASE_STATE_KEEPER_DB_COMPACTION_STYLE",
+            "DATABASE_STATE_KEEPER_DB_WRITE_BUFFER_SIZE_MB",
+            "DATABASE_STATE_KEEPER_DB_MAX_WRITE_BUFFER_NUMBER",
+            "DATABASE_STATE_KEEPER_DB_MAX_BACKGROUND_JOBS",
             "DATABASE_MERKLE_TREE_BACKUP_PATH",
             "DATABASE_MERKLE_TREE_PATH",
             "DATABASE_MERKLE_TREE_MODE",
+            "DATABASE_MERKLE_TREE_BACKEND",
             "DATABASE_MERKLE_TREE_MULTI_GET_CHUNK_SIZE",
             "DATABASE_MERKLE_TREE_BLOCK_CACHE_SIZE_MB",
             "DATABASE_MERKLE_TREE_MEMTABLE_CAPACITY_MB",
             "DATABASE_MERKLE_TREE_STALLED_WRITES_TIMEOUT_SEC",
             "DATABASE_MERKLE_TREE_MAX_L1_BATCHES_PER_ITER",
+            "DATABASE_MERKLE_TREE_COMPACTION_STYLE",
+            "DATABASE_MERKLE_TREE_WRITE_BUFFER_SIZE_MB",
+            "DATABASE_MERKLE_TREE_MAX_WRITE_BUFFER_NUMBER",
+            "DATABASE_MERKLE_TREE_MAX_BACKGROUND_JOBS",
+            "DATABASE_MERKLE_TREE_THREAD_POOL_SIZE",
+            "DATABASE_MERKLE_TREE_PRUNING_ENABLED",
+            "DATABASE_MERKLE_TREE_PRUNING_RETAINED_L1_BATCHES",
+            "DATABASE_MERKLE_TREE_PRUNING_POLL_INTERVAL_MS",
+            "DATABASE_MERKLE_TREE_CONSISTENCY_CHECK_ENABLED",
+            "DATABASE_MERKLE_TREE_CONSISTENCY_CHECK_SAMPLE_SIZE",
+            "DATABASE_MERKLE_TREE_CONSISTENCY_CHECK_INTERVAL_MS",
         ]);
 
         let db_config = DBConfig::from_env().unwrap();
         assert_eq!(db_config.state_keeper_db_path, "./db/state_keeper");
+        assert_eq!(db_config.state_keeper_db_block_cache_size(), None);
+        assert_eq!(
+            db_config.state_keeper_db_compaction_style,
+            RocksdbCompactionStyle::Level
+        );
         assert_eq!(db_config.merkle_tree.path, "./db/lightweight-new");
         assert_eq!(db_config.merkle_tree.mode, MerkleTreeMode::Full);
+        assert_eq!(db_config.merkle_tree.backend, MerkleTreeBackend::Rocksdb);
         assert_eq!(db_config.merkle_tree.multi_get_chunk_size, 500);
         assert_eq!(db_config.merkle_tree.max_l1_batches_per_iter, 20);
         assert_eq!(db_config.merkle_tree.block_cache_size_mb, 128);
         assert_eq!(db_config.merkle_tree.memtable_capacity_mb, 256);
         assert_eq!(db_config.merkle_tree.stalled_writes_timeout_sec, 30);
+        assert_eq!(db_config.merkle_tree.max_background_jobs, None);
+        assert_eq!(db_config.merkle_tree.thread_pool_size, None);
+        assert_eq!(db_config.merkle_tree.pruning_retained_l1_batches(), None);
+        assert_eq!(db_config.merkle_tree.pruning_retained_l1_batches, 1_000);
+        assert_eq!(db_config.merkle_tree.consistency_check_sample_size(), None);
 
         // Check that new env variable for Merkle tree path is supported
         lock.set_env("DATABASE_MERKLE_TREE_PATH=/db/tree/main");
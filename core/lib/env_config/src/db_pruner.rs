@@ -0,0 +1,40 @@
+use zksync_config::configs::DbPrunerConfig;
+
+use crate::{envy_load, FromEnv};
+
+impl FromEnv for DbPrunerConfig {
+    fn from_env() -> anyhow::Result<Self> {
+        envy_load("db_pruner", "DB_PRUNER_")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::EnvMutex;
+
+    static MUTEX: EnvMutex = EnvMutex::new();
+
+    fn expected_config() -> DbPrunerConfig {
+        DbPrunerConfig {
+            data_retention_l1_batches: Some(500),
+            data_retention_sec: Some(86_400),
+            pruned_batch_chunk_size: Some(5),
+            polling_interval_ms: Some(30_000),
+        }
+    }
+
+    #[test]
+    fn from_env() {
+        let config = r#"
+            DB_PRUNER_DATA_RETENTION_L1_BATCHES="500"
+            DB_PRUNER_DATA_RETENTION_SEC="86400"
+            DB_PRUNER_PRUNED_BATCH_CHUNK_SIZE="5"
+            DB_PRUNER_POLLING_INTERVAL_MS="30000"
+        "#;
+        let mut lock = MUTEX.lock();
+        lock.set_env(config);
+        let actual = DbPrunerConfig::from_env().unwrap();
+        assert_eq!(actual, expected_config());
+    }
+}
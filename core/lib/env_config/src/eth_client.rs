@@ -7,7 +7,8 @@ impl FromEnv for ETHClientConfig {
         let config: Self = envy_load("eth_client", "ETH_CLIENT_")?;
         if config.web3_url.find(',').is_some() {
             anyhow::bail!(
-                "Multiple web3 URLs aren't supported anymore. Provided invalid value: {}",
+                "Multiple web3 URLs aren't supported in `web3_url`; use `fallback_web3_urls` instead. \
+                 Provided invalid value: {}",
                 config.web3_url
             );
         }
@@ -26,6 +27,10 @@ mod tests {
         ETHClientConfig {
             chain_id: 9,
             web3_url: "http://127.0.0.1:8545".into(),
+            fallback_web3_urls: vec![
+                "http://127.0.0.1:8546".into(),
+                "http://127.0.0.1:8547".into(),
+            ],
         }
     }
 
@@ -35,6 +40,7 @@ mod tests {
         let config = r#"
             ETH_CLIENT_CHAIN_ID="9"
             ETH_CLIENT_WEB3_URL="http://127.0.0.1:8545"
+            ETH_CLIENT_FALLBACK_WEB3_URLS="http://127.0.0.1:8546,http://127.0.0.1:8547"
         "#;
         lock.set_env(config);
 
@@ -1,5 +1,8 @@
 use anyhow::Context as _;
-use zksync_config::{configs::eth_sender::SenderConfig, ETHSenderConfig, GasAdjusterConfig};
+use zksync_config::{
+    configs::eth_sender::{GasEscalationConfig, OperatorSignerConfig, SenderConfig},
+    ETHSenderConfig, GasAdjusterConfig,
+};
 
 use crate::{envy_load, FromEnv};
 
@@ -8,6 +11,8 @@ impl FromEnv for ETHSenderConfig {
         Ok(Self {
             sender: SenderConfig::from_env().context("SenderConfig")?,
             gas_adjuster: GasAdjusterConfig::from_env().context("GasAdjusterConfig")?,
+            gas_escalation: GasEscalationConfig::from_env().context("GasEscalationConfig")?,
+            signer: OperatorSignerConfig::from_env().context("OperatorSignerConfig")?,
         })
     }
 }
@@ -24,9 +29,37 @@ impl FromEnv for GasAdjusterConfig {
     }
 }
 
+impl FromEnv for GasEscalationConfig {
+    fn from_env() -> anyhow::Result<Self> {
+        Ok(Self {
+            commit: envy_load(
+                "eth_sender.gas_escalation.commit",
+                "ETH_SENDER_GAS_ESCALATION_COMMIT_",
+            )?,
+            prove: envy_load(
+                "eth_sender.gas_escalation.prove",
+                "ETH_SENDER_GAS_ESCALATION_PROVE_",
+            )?,
+            execute: envy_load(
+                "eth_sender.gas_escalation.execute",
+                "ETH_SENDER_GAS_ESCALATION_EXECUTE_",
+            )?,
+        })
+    }
+}
+
+impl FromEnv for OperatorSignerConfig {
+    fn from_env() -> anyhow::Result<Self> {
+        envy_load("eth_sender.signer", "ETH_SENDER_SIGNER_")
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use zksync_config::configs::eth_sender::{ProofLoadingMode, ProofSendingMode};
+    use zksync_config::configs::eth_sender::{
+        GasEscalationParams, GasEscalationPolicy, OperatorSignerMode, ProofLoadingMode,
+        ProofSendingMode, PubdataSendingMode, StuckTxRemediationPolicy,
+    };
 
     use super::*;
     use crate::test_utils::{hash, EnvMutex};
@@ -54,16 +87,53 @@ mod tests {
                 l1_batch_min_age_before_execute_seconds: Some(1000),
                 max_acceptable_priority_fee_in_gwei: 100_000_000_000,
                 proof_loading_mode: ProofLoadingMode::OldProofFromDb,
+                pubdata_sending_mode: PubdataSendingMode::Blobs,
+                stuck_operator_nonce_blocks: Some(50),
+                min_operator_balance_gwei: Some(100_000_000),
+                dry_run: true,
+                stuck_tx_threshold_blocks: Some(20),
+                stuck_tx_remediation: StuckTxRemediationPolicy::Replace,
             },
             gas_adjuster: GasAdjusterConfig {
                 default_priority_fee_per_gas: 20000000000,
                 max_base_fee_samples: 10000,
+                base_fee_percentile: Some(60.0),
+                max_base_fee_spike_ratio: Some(2.0),
                 pricing_formula_parameter_a: 1.5,
                 pricing_formula_parameter_b: 1.0005,
                 internal_l1_pricing_multiplier: 0.8,
                 internal_enforced_l1_gas_price: None,
                 poll_period: 15,
                 max_l1_gas_price: Some(100000000),
+                internal_pubdata_pricing_multiplier: Some(1.5),
+                max_blob_base_fee: Some(2000000000),
+                num_samples_for_blob_base_fee_estimate: Some(20),
+            },
+            gas_escalation: GasEscalationConfig {
+                commit: GasEscalationParams {
+                    policy: GasEscalationPolicy::Deadline,
+                    bump_ratio: None,
+                    resend_interval_blocks: Some(2),
+                    deadline_blocks: Some(100),
+                    max_fee_per_gas: Some(500_000_000_000),
+                },
+                prove: GasEscalationParams {
+                    policy: GasEscalationPolicy::Exponential,
+                    bump_ratio: Some(0.25),
+                    resend_interval_blocks: None,
+                    deadline_blocks: None,
+                    max_fee_per_gas: None,
+                },
+                execute: GasEscalationParams::default(),
+            },
+            signer: OperatorSignerConfig {
+                mode: OperatorSignerMode::AwsKms,
+                aws_kms_key_id: Some("arn:aws:kms:us-east-1:123456789012:key/operator".to_owned()),
+                aws_kms_region: Some("us-east-1".to_owned()),
+                gcp_kms_key_version: None,
+                web3signer_url: None,
+                web3signer_public_key: None,
+                request_timeout_ms: Some(3_000),
             },
         }
     }
@@ -80,11 +150,16 @@ mod tests {
             ETH_SENDER_SENDER_PROOF_SENDING_MODE="SkipEveryProof"
             ETH_SENDER_GAS_ADJUSTER_DEFAULT_PRIORITY_FEE_PER_GAS="20000000000"
             ETH_SENDER_GAS_ADJUSTER_MAX_BASE_FEE_SAMPLES="10000"
+            ETH_SENDER_GAS_ADJUSTER_BASE_FEE_PERCENTILE="60"
+            ETH_SENDER_GAS_ADJUSTER_MAX_BASE_FEE_SPIKE_RATIO="2"
             ETH_SENDER_GAS_ADJUSTER_PRICING_FORMULA_PARAMETER_A="1.5"
             ETH_SENDER_GAS_ADJUSTER_PRICING_FORMULA_PARAMETER_B="1.0005"
             ETH_SENDER_GAS_ADJUSTER_INTERNAL_L1_PRICING_MULTIPLIER="0.8"
             ETH_SENDER_GAS_ADJUSTER_POLL_PERIOD="15"
             ETH_SENDER_GAS_ADJUSTER_MAX_L1_GAS_PRICE="100000000"
+            ETH_SENDER_GAS_ADJUSTER_INTERNAL_PUBDATA_PRICING_MULTIPLIER="1.5"
+            ETH_SENDER_GAS_ADJUSTER_MAX_BLOB_BASE_FEE="2000000000"
+            ETH_SENDER_GAS_ADJUSTER_NUM_SAMPLES_FOR_BLOB_BASE_FEE_ESTIMATE="20"
             ETH_SENDER_WAIT_FOR_PROOFS="false"
             ETH_SENDER_SENDER_AGGREGATED_PROOF_SIZES="1,5"
             ETH_SENDER_SENDER_MAX_AGGREGATED_BLOCKS_TO_COMMIT="3"
@@ -98,6 +173,23 @@ mod tests {
             ETH_SENDER_SENDER_L1_BATCH_MIN_AGE_BEFORE_EXECUTE_SECONDS="1000"
             ETH_SENDER_SENDER_MAX_ACCEPTABLE_PRIORITY_FEE_IN_GWEI="100000000000"
             ETH_SENDER_SENDER_PROOF_LOADING_MODE="OldProofFromDb"
+            ETH_SENDER_SENDER_PUBDATA_SENDING_MODE="Blobs"
+            ETH_SENDER_SENDER_STUCK_OPERATOR_NONCE_BLOCKS="50"
+            ETH_SENDER_GAS_ESCALATION_COMMIT_POLICY="Deadline"
+            ETH_SENDER_GAS_ESCALATION_COMMIT_RESEND_INTERVAL_BLOCKS="2"
+            ETH_SENDER_GAS_ESCALATION_COMMIT_DEADLINE_BLOCKS="100"
+            ETH_SENDER_GAS_ESCALATION_COMMIT_MAX_FEE_PER_GAS="500000000000"
+            ETH_SENDER_GAS_ESCALATION_PROVE_POLICY="Exponential"
+            ETH_SENDER_GAS_ESCALATION_PROVE_BUMP_RATIO="0.25"
+            ETH_SENDER_SENDER_MIN_OPERATOR_BALANCE_GWEI="100000000"
+            ETH_SENDER_SENDER_DRY_RUN="true"
+            ETH_SENDER_SENDER_STUCK_TX_THRESHOLD_BLOCKS="20"
+            ETH_SENDER_SENDER_STUCK_TX_REMEDIATION="Replace"
+            ETH_SENDER_SIGNER_MODE="AwsKms"
+            ETH_SENDER_SIGNER_AWS_KMS_KEY_ID="arn:aws:kms:us-east-1:123456789012:key/operator"
+            ETH_SENDER_SIGNER_AWS_KMS_REGION="us-east-1"
+            ETH_SENDER_SIGNER_REQUEST_TIMEOUT_MS="3000"
+            ETH_SENDER_SENDER_OPERATOR_BACKUP_PRIVATE_KEYS="0x0000000000000000000000000000000000000000000000000000000000000001,0x0000000000000000000000000000000000000000000000000000000000000002"
         "#;
         lock.set_env(config);
 
@@ -107,5 +199,12 @@ mod tests {
             actual.sender.private_key().unwrap(),
             hash("27593fea79697e947890ecbecce7901b0008345e5d7259710d0dd5e500d040be")
         );
+        assert_eq!(
+            actual.sender.backup_private_keys(),
+            [
+                hash("0000000000000000000000000000000000000000000000000000000000000001"),
+                hash("0000000000000000000000000000000000000000000000000000000000000002")
+            ]
+        );
     }
 }
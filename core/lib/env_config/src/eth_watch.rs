@@ -18,6 +18,7 @@ mod tests {
     fn expected_config() -> ETHWatchConfig {
         ETHWatchConfig {
             confirmations_for_eth_event: Some(0),
+            confirmations_for_upgrade_event: Some(12),
             eth_node_poll_interval: 300,
         }
     }
@@ -27,6 +28,7 @@ mod tests {
         let mut lock = MUTEX.lock();
         let config = r#"
             ETH_WATCH_CONFIRMATIONS_FOR_ETH_EVENT="0"
+            ETH_WATCH_CONFIRMATIONS_FOR_UPGRADE_EVENT="12"
             ETH_WATCH_ETH_NODE_POLL_INTERVAL="300"
         "#;
         lock.set_env(config);
@@ -6,7 +6,9 @@ mod api;
 mod chain;
 mod contract_verifier;
 mod contracts;
+mod da_dispatcher;
 mod database;
+mod db_pruner;
 mod eth_client;
 mod eth_sender;
 mod eth_watch;
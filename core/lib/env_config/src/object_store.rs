@@ -40,6 +40,18 @@ impl FromEnv for SnapshotsObjectStoreConfig {
     }
 }
 
+/// Wrapper for `ObjectStoreConfig` that allows loading object store config using `DA_` prefix.
+/// This store is used as a data availability layer by the DA dispatcher.
+#[derive(Debug)]
+pub struct DAObjectStoreConfig(pub ObjectStoreConfig);
+
+impl FromEnv for DAObjectStoreConfig {
+    fn from_env() -> anyhow::Result<Self> {
+        let config = envy_load("da_object_store", "DA_OBJECT_STORE_")?;
+        Ok(Self(config))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use zksync_config::{configs::object_store::ObjectStoreMode, ObjectStoreConfig};
@@ -118,4 +130,19 @@ mod tests {
         let actual = SnapshotsObjectStoreConfig::from_env().unwrap().0;
         assert_eq!(actual, expected_config("/snapshots_base_url"));
     }
+
+    #[test]
+    fn da_bucket_config_from_env() {
+        let mut lock = MUTEX.lock();
+        let config = r#"
+            DA_OBJECT_STORE_BUCKET_BASE_URL="/da_base_url"
+            DA_OBJECT_STORE_MODE="FileBacked"
+            DA_OBJECT_STORE_FILE_BACKED_BASE_PATH="artifacts"
+            DA_OBJECT_STORE_GCS_CREDENTIAL_FILE_PATH="/path/to/credentials.json"
+            DA_OBJECT_STORE_MAX_RETRIES="5"
+        "#;
+        lock.set_env(config);
+        let actual = DAObjectStoreConfig::from_env().unwrap().0;
+        assert_eq!(actual, expected_config("/da_base_url"));
+    }
 }
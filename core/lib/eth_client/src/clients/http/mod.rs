@@ -1,14 +1,17 @@
 use std::time::Duration;
 
 use vise::{
-    Buckets, Counter, EncodeLabelSet, EncodeLabelValue, Family, Histogram, LabeledFamily, Metrics,
+    Buckets, Counter, EncodeLabelSet, EncodeLabelValue, Family, Gauge, Histogram, LabeledFamily,
+    Metrics,
 };
 
 pub use self::{
+    failover::FailoverTransport,
     query::QueryClient,
-    signing::{PKSigningClient, SigningClient},
+    signing::{OperatorSigningClient, PKSigningClient, SigningClient},
 };
 
+mod failover;
 mod query;
 mod signing;
 
@@ -20,12 +23,15 @@ enum Method {
     GetGasPrice,
     SendRawTx,
     BaseFeeHistory,
+    BlobBaseFeeHistory,
     #[metrics(name = "get_pending_block_base_fee_per_gas")]
     PendingBlockBaseFee,
     GetTxStatus,
     FailureReason,
     GetTx,
     CallContractFunction,
+    Call,
+    EstimateGas,
     TxReceipt,
     EthBalance,
     Logs,
@@ -56,3 +62,31 @@ struct ClientLatencies {
 
 #[vise::register]
 static LATENCIES: vise::Global<ClientLatencies> = vise::Global::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue)]
+#[metrics(rename_all = "snake_case")]
+enum RequestOutcome {
+    Success,
+    Failure,
+}
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "server_ethereum_gateway_endpoint")]
+struct EndpointMetrics {
+    /// Number of requests sent to an L1 endpoint, grouped by outcome. Errors returned by the L1 node
+    /// for a well-formed request (e.g., reverts) are considered successful outcomes.
+    #[metrics(labels = ["endpoint", "outcome"])]
+    requests: LabeledFamily<(String, RequestOutcome), Counter, 2>,
+    /// Latency of requests to an L1 endpoint.
+    #[metrics(labels = ["endpoint"], buckets = Buckets::LATENCIES)]
+    latency: LabeledFamily<String, Histogram<Duration>>,
+    /// Health score of an L1 endpoint in the `[0, 1]` range.
+    #[metrics(labels = ["endpoint"])]
+    health_score: LabeledFamily<String, Gauge<f64>>,
+    /// Number of requests failed over to an L1 endpoint after failing on a more preferred one.
+    #[metrics(labels = ["endpoint"])]
+    failovers: LabeledFamily<String, Counter>,
+}
+
+#[vise::register]
+static ENDPOINT_METRICS: vise::Global<EndpointMetrics> = vise::Global::new();
@@ -1,33 +1,42 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use serde::Deserialize;
 use zksync_types::web3::{
     self,
     contract::Contract,
-    ethabi,
-    transports::Http,
+    ethabi, helpers,
     types::{
-        Address, Block, BlockId, BlockNumber, Bytes, Filter, Log, Transaction, TransactionId,
-        TransactionReceipt, H256, U256, U64,
+        Address, Block, BlockId, BlockNumber, Bytes, CallRequest, Filter, Log, Transaction,
+        TransactionId, TransactionReceipt, H256, U256, U64,
     },
-    Web3,
+    Transport, Web3,
 };
 
 use crate::{
-    clients::http::{Method, COUNTERS, LATENCIES},
+    clients::http::{FailoverTransport, Method, COUNTERS, LATENCIES},
     types::{Error, ExecutedTxStatus, FailureInfo, RawTokens},
     ContractCall, EthInterface, RawTransactionBytes,
 };
 
+/// Subset of the `eth_feeHistory` response including blob base fees (EIP-4844).
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FeeHistoryWithBlobs {
+    base_fee_per_gas: Vec<U256>,
+    #[serde(default)]
+    base_fee_per_blob_gas: Vec<U256>,
+}
+
 /// An "anonymous" Ethereum client that can invoke read-only methods that aren't
 /// tied to a particular account.
 #[derive(Debug, Clone)]
 pub struct QueryClient {
-    web3: Arc<Web3<Http>>,
+    web3: Arc<Web3<FailoverTransport>>,
 }
 
-impl From<Http> for QueryClient {
-    fn from(transport: Http) -> Self {
+impl From<FailoverTransport> for QueryClient {
+    fn from(transport: FailoverTransport) -> Self {
         Self {
             web3: Arc::new(Web3::new(transport)),
         }
@@ -37,7 +46,12 @@ impl From<Http> for QueryClient {
 impl QueryClient {
     /// Creates a new HTTP client.
     pub fn new(node_url: &str) -> Result<Self, Error> {
-        let transport = Http::new(node_url)?;
+        Self::with_failover(&[node_url])
+    }
+
+    /// Creates a new HTTP client failing over among the specified L1 endpoints ordered by preference.
+    pub fn with_failover(node_urls: &[impl AsRef<str>]) -> Result<Self, Error> {
+        let transport = FailoverTransport::new(node_urls)?;
         Ok(transport.into())
     }
 }
@@ -117,6 +131,52 @@ impl EthInterface for QueryClient {
         Ok(history.into_iter().map(|fee| fee.as_u64()).collect())
     }
 
+    async fn blob_base_fee_history(
+        &self,
+        upto_block: usize,
+        block_count: usize,
+        component: &'static str,
+    ) -> Result<Vec<u64>, Error> {
+        const MAX_REQUEST_CHUNK: usize = 1024;
+
+        COUNTERS.call[&(Method::BlobBaseFeeHistory, component)].inc();
+        let latency = LATENCIES.direct[&Method::BlobBaseFeeHistory].start();
+        let mut history = Vec::with_capacity(block_count);
+        let from_block = upto_block.saturating_sub(block_count);
+
+        // `web3` doesn't support blob-related fields in `eth_feeHistory` responses, so we perform the call manually.
+        // Chunking is the same as in `base_fee_history()`, so that the returned values are aligned.
+        for chunk_start in (from_block..=upto_block).step_by(MAX_REQUEST_CHUNK) {
+            let chunk_end = (chunk_start + MAX_REQUEST_CHUNK).min(upto_block);
+            let chunk_size = chunk_end - chunk_start;
+            let params = vec![
+                helpers::serialize(&U256::from(chunk_size)),
+                helpers::serialize(&BlockNumber::from(chunk_end)),
+                helpers::serialize(&Vec::<f64>::new()),
+            ];
+            let response = self
+                .web3
+                .transport()
+                .execute("eth_feeHistory", params)
+                .await?;
+            let chunk: FeeHistoryWithBlobs = helpers::decode(response)?;
+            if chunk.base_fee_per_blob_gas.is_empty() {
+                // The L1 node doesn't support blobs; all blocks have zero blob base fee.
+                history.extend(chunk.base_fee_per_gas.iter().map(|_| 0));
+            } else {
+                history.extend(
+                    chunk
+                        .base_fee_per_blob_gas
+                        .into_iter()
+                        .map(|fee| fee.min(u64::MAX.into()).as_u64()),
+                );
+            }
+        }
+
+        latency.observe();
+        Ok(history)
+    }
+
     async fn get_pending_block_base_fee_per_gas(
         &self,
         component: &'static str,
@@ -269,6 +329,31 @@ impl EthInterface for QueryClient {
         Ok(balance)
     }
 
+    async fn call(
+        &self,
+        request: CallRequest,
+        block: Option<BlockId>,
+        component: &'static str,
+    ) -> Result<Bytes, Error> {
+        COUNTERS.call[&(Method::Call, component)].inc();
+        let latency = LATENCIES.direct[&Method::Call].start();
+        let output = self.web3.eth().call(request, block).await?;
+        latency.observe();
+        Ok(output)
+    }
+
+    async fn estimate_gas(
+        &self,
+        request: CallRequest,
+        component: &'static str,
+    ) -> Result<U256, Error> {
+        COUNTERS.call[&(Method::EstimateGas, component)].inc();
+        let latency = LATENCIES.direct[&Method::EstimateGas].start();
+        let gas = self.web3.eth().estimate_gas(request, None).await?;
+        latency.observe();
+        Ok(gas)
+    }
+
     async fn logs(&self, filter: Filter, component: &'static str) -> Result<Vec<Log>, Error> {
         COUNTERS.call[&(Method::Logs, component)].inc();
         let latency = LATENCIES.direct[&Method::Logs].start();
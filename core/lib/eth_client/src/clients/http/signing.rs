@@ -3,24 +3,25 @@ use std::{fmt, sync::Arc};
 use async_trait::async_trait;
 use zksync_config::{ContractsConfig, ETHClientConfig, ETHSenderConfig};
 use zksync_contracts::zksync_contract;
-use zksync_eth_signer::{raw_ethereum_tx::TransactionParameters, EthereumSigner, PrivateKeySigner};
+use zksync_eth_signer::{
+    raw_ethereum_tx::{signed_tx_hash, TransactionParameters},
+    EthereumSigner, OperatorSigner, PrivateKeySigner,
+};
 use zksync_types::{
     web3::{
-        self,
         contract::{tokens::Detokenize, Options},
         ethabi,
-        transports::Http,
         types::{
-            Address, Block, BlockId, BlockNumber, Filter, Log, Transaction, TransactionReceipt,
-            H160, H256, U256, U64,
+            Address, Block, BlockId, BlockNumber, Bytes, CallRequest, Filter, Log, Transaction,
+            TransactionReceipt, H160, H256, U256, U64,
         },
     },
-    L1ChainId, PackedEthSignature, EIP_1559_TX_TYPE,
+    L1ChainId, PackedEthSignature, EIP_1559_TX_TYPE, EIP_4844_TX_TYPE,
 };
 
-use super::{query::QueryClient, Method, LATENCIES};
+use super::{query::QueryClient, FailoverTransport, Method, LATENCIES};
 use crate::{
-    types::{Error, ExecutedTxStatus, FailureInfo, SignedCallResult},
+    types::{BlobTxParams, Error, ExecutedTxStatus, FailureInfo, SignedCallResult},
     BoundEthInterface, CallFunctionArgs, ContractCall, EthInterface, RawTransactionBytes,
 };
 
@@ -33,28 +34,82 @@ impl PKSigningClient {
         contracts_config: &ContractsConfig,
         eth_client: &ETHClientConfig,
     ) -> Self {
-        // Gather required data from the config.
-        // It's done explicitly to simplify getting rid of this function later.
-        let main_node_url = &eth_client.web3_url;
         let operator_private_key = eth_sender
             .sender
             .private_key()
             .expect("Operator private key is required for signing client");
-        let diamond_proxy_addr = contracts_config.diamond_proxy_addr;
-        let default_priority_fee_per_gas = eth_sender.gas_adjuster.default_priority_fee_per_gas;
-        let l1_chain_id = eth_client.chain_id;
+        Self::from_config_with_private_key(
+            eth_sender,
+            contracts_config,
+            eth_client,
+            operator_private_key,
+        )
+    }
 
-        let transport = Http::new(main_node_url).expect("Failed to create transport");
+    /// Creates a client for an operator account with the specified private key (e.g., a backup operator account).
+    pub fn from_config_with_private_key(
+        eth_sender: &ETHSenderConfig,
+        contracts_config: &ContractsConfig,
+        eth_client: &ETHClientConfig,
+        operator_private_key: H256,
+    ) -> Self {
         let operator_address = PackedEthSignature::address_from_private_key(&operator_private_key)
             .expect("Failed to get address from private key");
+        Self::from_config_with_signer(
+            eth_sender,
+            contracts_config,
+            eth_client,
+            operator_address,
+            PrivateKeySigner::new(operator_private_key),
+        )
+    }
+}
+
+/// HTTP-based Ethereum client, backed by a configurable (possibly remote) signer.
+pub type OperatorSigningClient = SigningClient<OperatorSigner>;
+
+impl OperatorSigningClient {
+    /// Creates a client for the operator account managed by the specified signer.
+    pub fn from_config_with_operator_signer(
+        eth_sender: &ETHSenderConfig,
+        contracts_config: &ContractsConfig,
+        eth_client: &ETHClientConfig,
+        signer: OperatorSigner,
+    ) -> Self {
+        let operator_address = signer.address();
+        Self::from_config_with_signer(
+            eth_sender,
+            contracts_config,
+            eth_client,
+            operator_address,
+            signer,
+        )
+    }
+}
+
+impl<S: EthereumSigner> SigningClient<S> {
+    fn from_config_with_signer(
+        eth_sender: &ETHSenderConfig,
+        contracts_config: &ContractsConfig,
+        eth_client: &ETHClientConfig,
+        operator_address: Address,
+        eth_signer: S,
+    ) -> Self {
+        // Gather required data from the config.
+        // It's done explicitly to simplify getting rid of this function later.
+        let node_urls = eth_client.web3_urls();
+        let diamond_proxy_addr = contracts_config.diamond_proxy_addr;
+        let default_priority_fee_per_gas = eth_sender.gas_adjuster.default_priority_fee_per_gas;
+        let l1_chain_id = eth_client.chain_id;
 
+        let transport = FailoverTransport::new(&node_urls).expect("Failed to create transport");
         tracing::info!("Operator address: {:?}", operator_address);
 
         SigningClient::new(
             transport,
             zksync_contract(),
             operator_address,
-            PrivateKeySigner::new(operator_private_key),
+            eth_signer,
             diamond_proxy_addr,
             default_priority_fee_per_gas.into(),
             L1ChainId(l1_chain_id),
@@ -133,6 +188,17 @@ impl<S: EthereumSigner> EthInterface for SigningClient<S> {
             .await
     }
 
+    async fn blob_base_fee_history(
+        &self,
+        upto_block: usize,
+        block_count: usize,
+        component: &'static str,
+    ) -> Result<Vec<u64>, Error> {
+        self.query_client
+            .blob_base_fee_history(upto_block, block_count, component)
+            .await
+    }
+
     async fn get_pending_block_base_fee_per_gas(
         &self,
         component: &'static str,
@@ -181,6 +247,23 @@ impl<S: EthereumSigner> EthInterface for SigningClient<S> {
         self.query_client.eth_balance(address, component).await
     }
 
+    async fn call(
+        &self,
+        request: CallRequest,
+        block: Option<BlockId>,
+        component: &'static str,
+    ) -> Result<Bytes, Error> {
+        self.query_client.call(request, block, component).await
+    }
+
+    async fn estimate_gas(
+        &self,
+        request: CallRequest,
+        component: &'static str,
+    ) -> Result<U256, Error> {
+        self.query_client.estimate_gas(request, component).await
+    }
+
     async fn logs(&self, filter: Filter, component: &'static str) -> Result<Vec<Log>, Error> {
         self.query_client.logs(filter, component).await
     }
@@ -218,6 +301,68 @@ impl<S: EthereumSigner> BoundEthInterface for SigningClient<S> {
         contract_addr: H160,
         options: Options,
         component: &'static str,
+    ) -> Result<SignedCallResult, Error> {
+        self.sign_tx(data, contract_addr, options, None, component)
+            .await
+    }
+
+    async fn sign_prepared_blob_tx_for_addr(
+        &self,
+        data: Vec<u8>,
+        contract_addr: H160,
+        options: Options,
+        blob_params: BlobTxParams,
+        component: &'static str,
+    ) -> Result<SignedCallResult, Error> {
+        self.sign_tx(data, contract_addr, options, Some(blob_params), component)
+            .await
+    }
+
+    async fn allowance_on_account(
+        &self,
+        token_address: Address,
+        address: Address,
+        erc20_abi: ethabi::Contract,
+    ) -> Result<U256, Error> {
+        let latency = LATENCIES.direct[&Method::Allowance].start();
+        let args = CallFunctionArgs::new("allowance", (self.inner.sender_account, address))
+            .for_contract(token_address, erc20_abi);
+        let res = self.call_contract_function(args).await?;
+        latency.observe();
+        Ok(U256::from_tokens(res)?)
+    }
+}
+
+impl<S: EthereumSigner> SigningClient<S> {
+    pub fn new(
+        transport: FailoverTransport,
+        contract: ethabi::Contract,
+        operator_eth_addr: H160,
+        eth_signer: S,
+        contract_eth_addr: H160,
+        default_priority_fee_per_gas: U256,
+        chain_id: L1ChainId,
+    ) -> Self {
+        Self {
+            inner: Arc::new(ETHDirectClientInner {
+                sender_account: operator_eth_addr,
+                eth_signer,
+                contract_addr: contract_eth_addr,
+                chain_id,
+                contract,
+                default_priority_fee_per_gas,
+            }),
+            query_client: transport.into(),
+        }
+    }
+
+    async fn sign_tx(
+        &self,
+        data: Vec<u8>,
+        contract_addr: H160,
+        options: Options,
+        blob_params: Option<BlobTxParams>,
+        component: &'static str,
     ) -> Result<SignedCallResult, Error> {
         let latency = LATENCIES.direct[&Method::SignPreparedTx].start();
         // Fetch current max priority fee per gas
@@ -258,6 +403,15 @@ impl<S: EthereumSigner> BoundEthInterface for SigningClient<S> {
             U256::from(FALLBACK_GAS_LIMIT)
         });
 
+        let transaction_type = if blob_params.is_some() {
+            EIP_4844_TX_TYPE
+        } else {
+            EIP_1559_TX_TYPE
+        };
+        let (max_fee_per_blob_gas, blob_sidecar) = match blob_params {
+            Some(params) => (Some(params.max_fee_per_blob_gas), Some(params.sidecar)),
+            None => (None, None),
+        };
         let tx = TransactionParameters {
             nonce,
             to: Some(contract_addr),
@@ -267,13 +421,15 @@ impl<S: EthereumSigner> BoundEthInterface for SigningClient<S> {
             chain_id: self.inner.chain_id.0,
             max_priority_fee_per_gas,
             gas_price: None,
-            transaction_type: Some(EIP_1559_TX_TYPE.into()),
+            transaction_type: Some(transaction_type.into()),
             access_list: None,
             max_fee_per_gas,
+            max_fee_per_blob_gas,
+            blob_sidecar,
         };
 
         let signed_tx = self.inner.eth_signer.sign_transaction(tx).await?;
-        let hash = web3::signing::keccak256(&signed_tx).into();
+        let hash = signed_tx_hash(&signed_tx);
         latency.observe();
         Ok(SignedCallResult {
             raw_tx: RawTransactionBytes(signed_tx),
@@ -283,42 +439,4 @@ impl<S: EthereumSigner> BoundEthInterface for SigningClient<S> {
             hash,
         })
     }
-
-    async fn allowance_on_account(
-        &self,
-        token_address: Address,
-        address: Address,
-        erc20_abi: ethabi::Contract,
-    ) -> Result<U256, Error> {
-        let latency = LATENCIES.direct[&Method::Allowance].start();
-        let args = CallFunctionArgs::new("allowance", (self.inner.sender_account, address))
-            .for_contract(token_address, erc20_abi);
-        let res = self.call_contract_function(args).await?;
-        latency.observe();
-        Ok(U256::from_tokens(res)?)
-    }
-}
-
-impl<S: EthereumSigner> SigningClient<S> {
-    pub fn new(
-        transport: Http,
-        contract: ethabi::Contract,
-        operator_eth_addr: H160,
-        eth_signer: S,
-        contract_eth_addr: H160,
-        default_priority_fee_per_gas: U256,
-        chain_id: L1ChainId,
-    ) -> Self {
-        Self {
-            inner: Arc::new(ETHDirectClientInner {
-                sender_account: operator_eth_addr,
-                eth_signer,
-                contract_addr: contract_eth_addr,
-                chain_id,
-                contract,
-                default_priority_fee_per_gas,
-            }),
-            query_client: transport.into(),
-        }
-    }
 }
@@ -9,14 +9,17 @@ use zksync_types::{
     web3::{
         contract::{tokens::Tokenize, Options},
         ethabi,
-        types::{Block, BlockId, BlockNumber, Filter, Log, Transaction, TransactionReceipt, U64},
+        types::{
+            Block, BlockId, BlockNumber, Bytes, CallRequest, Filter, Log, Transaction,
+            TransactionReceipt, U64,
+        },
         Error as Web3Error,
     },
     Address, L1ChainId, ProtocolVersionId, H160, H256, U256,
 };
 
 use crate::{
-    types::{Error, ExecutedTxStatus, FailureInfo, SignedCallResult},
+    types::{BlobTxParams, Error, ExecutedTxStatus, FailureInfo, SignedCallResult},
     BoundEthInterface, ContractCall, EthInterface, RawTransactionBytes,
 };
 
@@ -73,6 +76,7 @@ struct MockEthereumInner {
     current_nonce: u64,
     pending_nonce: u64,
     nonces: BTreeMap<u64, u64>,
+    call_requests: Vec<CallRequest>,
 }
 
 impl MockEthereumInner {
@@ -118,10 +122,12 @@ pub struct MockEthereum {
     max_fee_per_gas: U256,
     max_priority_fee_per_gas: U256,
     base_fee_history: Vec<u64>,
+    blob_base_fee_history: Vec<u64>,
     /// If true, the mock will not check the ordering nonces of the transactions.
     /// This is useful for testing the cases when the transactions are executed out of order.
     non_ordering_confirmations: bool,
     multicall_address: Address,
+    sender_account: Address,
     inner: RwLock<MockEthereumInner>,
 }
 
@@ -131,8 +137,10 @@ impl Default for MockEthereum {
             max_fee_per_gas: 100.into(),
             max_priority_fee_per_gas: 10.into(),
             base_fee_history: vec![],
+            blob_base_fee_history: vec![],
             non_ordering_confirmations: false,
             multicall_address: Address::default(),
+            sender_account: Address::repeat_byte(0x11),
             inner: RwLock::default(),
         }
     }
@@ -150,6 +158,14 @@ impl MockEthereum {
         H256::from_low_u64_ne(result)
     }
 
+    /// Gas returned by `estimate_gas()`.
+    pub const ESTIMATED_GAS: u64 = 1_000_000;
+
+    /// Returns requests passed to `call()`.
+    pub fn call_requests(&self) -> Vec<CallRequest> {
+        self.inner.read().unwrap().call_requests.clone()
+    }
+
     /// Returns the number of transactions sent via this client.
     pub fn sent_tx_count(&self) -> usize {
         self.inner.read().unwrap().sent_txs.len()
@@ -209,6 +225,14 @@ impl MockEthereum {
         }
     }
 
+    /// Sets the blob base fee history. If not set, blob base fees are zero.
+    pub fn with_blob_fee_history(self, history: Vec<u64>) -> Self {
+        Self {
+            blob_base_fee_history: history,
+            ..self
+        }
+    }
+
     pub fn with_non_ordering_confirmation(self, non_ordering_confirmations: bool) -> Self {
         Self {
             non_ordering_confirmations,
@@ -222,6 +246,13 @@ impl MockEthereum {
             ..self
         }
     }
+
+    pub fn with_sender_account(self, sender_account: Address) -> Self {
+        Self {
+            sender_account,
+            ..self
+        }
+    }
 }
 
 #[async_trait]
@@ -281,6 +312,19 @@ impl EthInterface for MockEthereum {
         Ok(self.base_fee_history[start_block..=from_block].to_vec())
     }
 
+    async fn blob_base_fee_history(
+        &self,
+        from_block: usize,
+        block_count: usize,
+        _component: &'static str,
+    ) -> Result<Vec<u64>, Error> {
+        let start_block = from_block.saturating_sub(block_count - 1);
+        if self.blob_base_fee_history.is_empty() {
+            return Ok(vec![0; from_block - start_block + 1]);
+        }
+        Ok(self.blob_base_fee_history[start_block..=from_block].to_vec())
+    }
+
     async fn get_pending_block_base_fee_per_gas(
         &self,
         _component: &'static str,
@@ -353,6 +397,24 @@ impl EthInterface for MockEthereum {
         unimplemented!("Not needed right now")
     }
 
+    async fn call(
+        &self,
+        request: CallRequest,
+        _block: Option<BlockId>,
+        _component: &'static str,
+    ) -> Result<Bytes, Error> {
+        self.inner.write().unwrap().call_requests.push(request);
+        Ok(Bytes::default())
+    }
+
+    async fn estimate_gas(
+        &self,
+        _request: CallRequest,
+        _component: &'static str,
+    ) -> Result<U256, Error> {
+        Ok(Self::ESTIMATED_GAS.into())
+    }
+
     async fn logs(&self, _filter: Filter, _component: &'static str) -> Result<Vec<Log>, Error> {
         unimplemented!("Not needed right now")
     }
@@ -381,7 +443,7 @@ impl BoundEthInterface for MockEthereum {
     }
 
     fn sender_account(&self) -> Address {
-        Address::repeat_byte(0x11)
+        self.sender_account
     }
 
     async fn sign_prepared_tx_for_addr(
@@ -394,6 +456,24 @@ impl BoundEthInterface for MockEthereum {
         self.sign_prepared_tx(data, options)
     }
 
+    async fn sign_prepared_blob_tx_for_addr(
+        &self,
+        mut data: Vec<u8>,
+        _contract_addr: H160,
+        options: Options,
+        blob_params: BlobTxParams,
+        _component: &'static str,
+    ) -> Result<SignedCallResult, Error> {
+        // Blob fee and versioned hashes are appended to distinguish blob transactions by their hash in tests.
+        data.append(&mut ethabi::encode(
+            &blob_params.max_fee_per_blob_gas.into_tokens(),
+        ));
+        for versioned_hash in blob_params.sidecar.versioned_hashes() {
+            data.extend_from_slice(versioned_hash.as_bytes());
+        }
+        self.sign_prepared_tx(data, options)
+    }
+
     async fn allowance_on_account(
         &self,
         _token_address: Address,
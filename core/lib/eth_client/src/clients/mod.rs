@@ -5,6 +5,6 @@ mod http;
 mod mock;
 
 pub use self::{
-    http::{PKSigningClient, QueryClient, SigningClient},
+    http::{FailoverTransport, OperatorSigningClient, PKSigningClient, QueryClient, SigningClient},
     mock::MockEthereum,
 };
@@ -6,16 +6,16 @@ use zksync_types::{
         contract::Options,
         ethabi,
         types::{
-            Address, Block, BlockId, BlockNumber, Filter, Log, Transaction, TransactionReceipt,
-            H160, H256, U256, U64,
+            Address, Block, BlockId, BlockNumber, Bytes, CallRequest, Filter, Log, Transaction,
+            TransactionReceipt, H160, H256, U256, U64,
         },
     },
     L1ChainId,
 };
 
 pub use crate::types::{
-    CallFunctionArgs, ContractCall, Error, ExecutedTxStatus, FailureInfo, RawTransactionBytes,
-    SignedCallResult,
+    BlobTxParams, CallFunctionArgs, ContractCall, Error, ExecutedTxStatus, FailureInfo,
+    RawTransactionBytes, SignedCallResult,
 };
 
 pub mod clients;
@@ -58,6 +58,18 @@ pub trait EthInterface: 'static + Sync + Send + fmt::Debug {
         component: &'static str,
     ) -> Result<Vec<u64>, Error>;
 
+    /// Collects the EIP-4844 blob base fee history for the specified block range. The returned values
+    /// are aligned with the ones returned by [`Self::base_fee_history()`].
+    ///
+    /// Blocks preceding the Dencun upgrade (or all blocks if the L1 node doesn't support blobs)
+    /// have zero blob base fee.
+    async fn blob_base_fee_history(
+        &self,
+        from_block: usize,
+        block_count: usize,
+        component: &'static str,
+    ) -> Result<Vec<u64>, Error>;
+
     /// Returns the `base_fee_per_gas` value for the currently pending L1 block.
     async fn get_pending_block_base_fee_per_gas(
         &self,
@@ -112,6 +124,22 @@ pub trait EthInterface: 'static + Sync + Send + fmt::Debug {
     async fn call_contract_function(&self, call: ContractCall)
         -> Result<Vec<ethabi::Token>, Error>;
 
+    /// Executes a raw call using `eth_call` at the specified block (or the latest block if `block` is `None`)
+    /// and returns its output. Reverted calls result in an error.
+    async fn call(
+        &self,
+        request: CallRequest,
+        block: Option<BlockId>,
+        component: &'static str,
+    ) -> Result<Bytes, Error>;
+
+    /// Estimates gas required to execute a call using `eth_estimateGas`.
+    async fn estimate_gas(
+        &self,
+        request: CallRequest,
+        component: &'static str,
+    ) -> Result<U256, Error>;
+
     /// Returns the logs for the specified filter.
     async fn logs(&self, filter: Filter, component: &'static str) -> Result<Vec<Log>, Error>;
 
@@ -172,6 +200,17 @@ pub trait BoundEthInterface: EthInterface {
         component: &'static str,
     ) -> Result<SignedCallResult, Error>;
 
+    /// Signs an EIP-4844 transaction publishing blobs from `blob_params`. Otherwise, the same as
+    /// [`Self::sign_prepared_tx_for_addr()`].
+    async fn sign_prepared_blob_tx_for_addr(
+        &self,
+        data: Vec<u8>,
+        contract_addr: H160,
+        options: Options,
+        blob_params: BlobTxParams,
+        component: &'static str,
+    ) -> Result<SignedCallResult, Error>;
+
     /// Returns the nonce of the `Self::sender_account()` at the specified block.
     async fn nonce_at(&self, block: BlockNumber, component: &'static str) -> Result<U256, Error> {
         self.nonce_at_for_account(self.sender_account(), block, component)
@@ -1,10 +1,13 @@
-use zksync_types::web3::{
-    contract::{
-        tokens::{Detokenize, Tokenize},
-        Error as ContractError, Options,
+use zksync_types::{
+    eth_sender::EthTxBlobSidecar,
+    web3::{
+        contract::{
+            tokens::{Detokenize, Tokenize},
+            Error as ContractError, Options,
+        },
+        ethabi,
+        types::{Address, BlockId, TransactionReceipt, H256, U256},
     },
-    ethabi,
-    types::{Address, BlockId, TransactionReceipt, H256, U256},
 };
 
 /// Wrapper for `Vec<ethabi::Token>` that doesn't wrap them in an additional array in `Tokenize` implementation.
@@ -114,6 +117,15 @@ impl AsRef<[u8]> for RawTransactionBytes {
     }
 }
 
+/// Blob-specific parameters of an EIP-4844 transaction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlobTxParams {
+    /// `max_fee_per_blob_gas` field of transaction (EIP4844).
+    pub max_fee_per_blob_gas: U256,
+    /// Blobs published by the transaction.
+    pub sidecar: EthTxBlobSidecar,
+}
+
 /// Representation of a signed transaction.
 #[derive(Debug, Clone, PartialEq)]
 pub struct SignedCallResult {
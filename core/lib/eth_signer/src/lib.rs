@@ -1,6 +1,7 @@
 use async_trait::async_trait;
 use error::SignerError;
 pub use json_rpc_signer::JsonRpcSigner;
+pub use operator_signer::OperatorSigner;
 pub use pk_signer::PrivateKeySigner;
 use zksync_types::{
     tx::primitives::PackedEthSignature, Address, EIP712TypedStructure, Eip712Domain,
@@ -10,6 +11,7 @@ pub use crate::raw_ethereum_tx::TransactionParameters;
 
 pub mod error;
 pub mod json_rpc_signer;
+pub mod operator_signer;
 pub mod pk_signer;
 pub mod raw_ethereum_tx;
 
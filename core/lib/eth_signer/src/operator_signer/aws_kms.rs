@@ -0,0 +1,102 @@
+//! Signer backed by AWS KMS.
+
+use std::{fmt, time::Duration};
+
+use async_trait::async_trait;
+use aws_config::{timeout::TimeoutConfig, BehaviorVersion};
+use aws_sdk_kms::{
+    config::Region,
+    error::DisplayErrorContext,
+    primitives::Blob,
+    types::{MessageType, SigningAlgorithmSpec},
+    Client,
+};
+use zksync_types::{Address, H256};
+
+use super::{der, EcdsaSignature, SignerKind, SigningBackend};
+use crate::SignerError;
+
+/// Signer using an asymmetric `ECC_SECG_P256K1` key stored in AWS KMS. Credentials are resolved
+/// using the default AWS credential provider chain (env vars, shared config, web identity, ECS / EC2
+/// instance metadata, etc.) and are refreshed by the SDK when they expire.
+pub struct AwsKmsSigner {
+    key_id: String,
+    client: Client,
+}
+
+impl fmt::Debug for AwsKmsSigner {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("AwsKmsSigner")
+            .field("key_id", &self.key_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AwsKmsSigner {
+    pub async fn new(key_id: &str, region: &str, timeout: Duration) -> Result<Self, SignerError> {
+        let timeout_config = TimeoutConfig::builder().operation_timeout(timeout).build();
+        let config = aws_config::defaults(BehaviorVersion::latest())
+            .region(Region::new(region.to_owned()))
+            .timeout_config(timeout_config)
+            .load()
+            .await;
+        Ok(Self {
+            key_id: key_id.to_owned(),
+            client: Client::new(&config),
+        })
+    }
+}
+
+fn request_error(err: impl std::error::Error) -> SignerError {
+    SignerError::SigningFailed(format!(
+        "AWS KMS request failed: {}",
+        DisplayErrorContext(err)
+    ))
+}
+
+fn missing_field(name: &str) -> SignerError {
+    SignerError::SigningFailed(format!("AWS KMS response doesn't contain `{name}`"))
+}
+
+#[async_trait]
+impl SigningBackend for AwsKmsSigner {
+    fn kind(&self) -> SignerKind {
+        SignerKind::AwsKms
+    }
+
+    async fn address(&self) -> Result<Address, SignerError> {
+        let response = self
+            .client
+            .get_public_key()
+            .key_id(&self.key_id)
+            .send()
+            .await
+            .map_err(request_error)?;
+        let public_key = response
+            .public_key()
+            .ok_or_else(|| missing_field("PublicKey"))?;
+        der::decode_public_key_address(public_key.as_ref())
+    }
+
+    async fn sign(
+        &self,
+        _message: &[u8],
+        message_hash: H256,
+    ) -> Result<EcdsaSignature, SignerError> {
+        let response = self
+            .client
+            .sign()
+            .key_id(&self.key_id)
+            .message(Blob::new(message_hash.as_bytes()))
+            .message_type(MessageType::Digest)
+            .signing_algorithm(SigningAlgorithmSpec::EcdsaSha256)
+            .send()
+            .await
+            .map_err(request_error)?;
+        let signature = response
+            .signature()
+            .ok_or_else(|| missing_field("Signature"))?;
+        der::decode_signature(signature.as_ref())
+    }
+}
@@ -0,0 +1,119 @@
+//! Minimal DER decoding of ECDSA signatures and public keys returned by KMS services.
+
+use zksync_types::{web3::signing::keccak256, Address, U256};
+
+use super::EcdsaSignature;
+use crate::SignerError;
+
+const SEQUENCE_TAG: u8 = 0x30;
+const INTEGER_TAG: u8 = 0x02;
+const BIT_STRING_TAG: u8 = 0x03;
+/// DER encoding of the `secp256k1` curve OID (1.3.132.0.10).
+const SECP256K1_OID: &[u8] = &[0x06, 0x05, 0x2b, 0x81, 0x04, 0x00, 0x0a];
+
+fn der_error(message: impl Into<String>) -> SignerError {
+    SignerError::CustomError(format!("malformed DER: {}", message.into()))
+}
+
+#[derive(Debug)]
+struct DerReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> DerReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], SignerError> {
+        if self.bytes.len() < len {
+            return Err(der_error("unexpected end of data"));
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    /// Reads a tag-length-value triple with the specified tag and returns its value.
+    fn read(&mut self, expected_tag: u8) -> Result<&'a [u8], SignerError> {
+        let header = self.take(2)?;
+        if header[0] != expected_tag {
+            return Err(der_error(format!(
+                "expected tag {expected_tag:#04x}, got {:#04x}",
+                header[0]
+            )));
+        }
+        let len = if header[1] < 0x80 {
+            usize::from(header[1])
+        } else {
+            let len_bytes = self.take(usize::from(header[1] & 0x7f))?;
+            if len_bytes.is_empty() || len_bytes.len() > 2 {
+                return Err(der_error("unsupported length encoding"));
+            }
+            len_bytes
+                .iter()
+                .fold(0_usize, |acc, &byte| (acc << 8) + usize::from(byte))
+        };
+        self.take(len)
+    }
+
+    fn read_unsigned_integer(&mut self) -> Result<U256, SignerError> {
+        let mut bytes = self.read(INTEGER_TAG)?;
+        // Strip the leading zero byte(s) ensuring that the integer is non-negative.
+        while let [0, rest @ ..] = bytes {
+            bytes = rest;
+        }
+        if bytes.len() > 32 {
+            return Err(der_error("integer doesn't fit into 256 bits"));
+        }
+        Ok(U256::from_big_endian(bytes))
+    }
+}
+
+/// Decodes an ECDSA signature encoded as `SEQUENCE { r INTEGER, s INTEGER }`.
+pub(super) fn decode_signature(der: &[u8]) -> Result<EcdsaSignature, SignerError> {
+    let mut reader = DerReader::new(der);
+    let mut sequence = DerReader::new(reader.read(SEQUENCE_TAG)?);
+    let r = sequence.read_unsigned_integer()?;
+    let s = sequence.read_unsigned_integer()?;
+    if !sequence.is_empty() || !reader.is_empty() {
+        return Err(der_error("trailing data after signature"));
+    }
+    Ok(EcdsaSignature { r, s })
+}
+
+/// Decodes a `secp256k1` public key encoded as `SubjectPublicKeyInfo` and returns the corresponding address.
+pub(super) fn decode_public_key_address(der: &[u8]) -> Result<Address, SignerError> {
+    let mut reader = DerReader::new(der);
+    let mut sequence = DerReader::new(reader.read(SEQUENCE_TAG)?);
+    let algorithm = sequence.read(SEQUENCE_TAG)?;
+    if !algorithm.ends_with(SECP256K1_OID) {
+        return Err(der_error(
+            "public key doesn't belong to the secp256k1 curve",
+        ));
+    }
+    match sequence.read(BIT_STRING_TAG)? {
+        [0, public_key @ ..] => public_key_to_address(public_key),
+        _ => Err(der_error("public key bit string has unused bits")),
+    }
+}
+
+/// Converts an uncompressed `secp256k1` public key (with or without the `0x04` prefix) to an address.
+pub(super) fn public_key_to_address(public_key: &[u8]) -> Result<Address, SignerError> {
+    let public_key = match public_key {
+        [0x04, rest @ ..] if rest.len() == 64 => rest,
+        _ if public_key.len() == 64 => public_key,
+        _ => {
+            return Err(SignerError::CustomError(format!(
+                "expected an uncompressed secp256k1 public key, got {} bytes",
+                public_key.len()
+            )));
+        }
+    };
+    let hash = keccak256(public_key);
+    Ok(Address::from_slice(&hash[12..]))
+}
@@ -0,0 +1,138 @@
+//! Signer backed by GCP Cloud KMS.
+
+use std::{fmt, time::Duration};
+
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use google_cloud_auth::{
+    project::{create_token_source, Config},
+    token_source::TokenSource,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::json;
+use zksync_types::{Address, H256};
+
+use super::{der, EcdsaSignature, SignerKind, SigningBackend};
+use crate::SignerError;
+
+const KMS_API_URL: &str = "https://cloudkms.googleapis.com/v1";
+const KMS_SCOPES: &[&str] = &["https://www.googleapis.com/auth/cloudkms"];
+
+#[derive(Debug, Deserialize)]
+struct PublicKeyResponse {
+    pem: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AsymmetricSignResponse {
+    signature: String,
+}
+
+/// Signer using an asymmetric `EC_SIGN_SECP256K1_SHA256` key version stored in GCP Cloud KMS.
+/// Authenticates using Application Default Credentials.
+pub struct GcpKmsSigner {
+    key_version: String,
+    token_source: Box<dyn TokenSource>,
+    client: reqwest::Client,
+}
+
+impl fmt::Debug for GcpKmsSigner {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("GcpKmsSigner")
+            .field("key_version", &self.key_version)
+            .finish_non_exhaustive()
+    }
+}
+
+impl GcpKmsSigner {
+    pub async fn new(key_version: &str, timeout: Duration) -> Result<Self, SignerError> {
+        let token_source = create_token_source(Config {
+            scopes: Some(KMS_SCOPES),
+            ..Config::default()
+        })
+        .await
+        .map_err(|err| {
+            SignerError::CustomError(format!("failed getting GCP credentials: {err}"))
+        })?;
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|err| SignerError::CustomError(err.to_string()))?;
+        Ok(Self {
+            key_version: key_version.to_owned(),
+            token_source,
+            client,
+        })
+    }
+
+    async fn request<T: DeserializeOwned>(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<T, SignerError> {
+        let token = self.token_source.token().await.map_err(|err| {
+            SignerError::SigningFailed(format!("failed getting GCP access token: {err}"))
+        })?;
+        let response = request
+            .bearer_auth(token.access_token)
+            .send()
+            .await
+            .map_err(|err| SignerError::SigningFailed(format!("GCP KMS request failed: {err}")))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_default();
+            return Err(SignerError::SigningFailed(format!(
+                "GCP KMS responded with {status}: {message}"
+            )));
+        }
+        response.json().await.map_err(|err| {
+            SignerError::SigningFailed(format!("failed parsing GCP KMS response: {err}"))
+        })
+    }
+}
+
+fn decode_base64(value: &str) -> Result<Vec<u8>, SignerError> {
+    BASE64
+        .decode(value)
+        .map_err(|err| SignerError::SigningFailed(format!("invalid base64 from GCP KMS: {err}")))
+}
+
+/// Extracts DER contents from a PEM-encoded public key.
+fn decode_pem(pem: &str) -> Result<Vec<u8>, SignerError> {
+    let contents: String = pem
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("-----"))
+        .collect();
+    decode_base64(&contents)
+}
+
+#[async_trait]
+impl SigningBackend for GcpKmsSigner {
+    fn kind(&self) -> SignerKind {
+        SignerKind::GcpKms
+    }
+
+    async fn address(&self) -> Result<Address, SignerError> {
+        let url = format!("{KMS_API_URL}/{}/publicKey", self.key_version);
+        let response: PublicKeyResponse = self.request(self.client.get(url)).await?;
+        der::decode_public_key_address(&decode_pem(&response.pem)?)
+    }
+
+    async fn sign(
+        &self,
+        _message: &[u8],
+        message_hash: H256,
+    ) -> Result<EcdsaSignature, SignerError> {
+        // KMS doesn't check how the digest was produced, so it's safe to pass a Keccak-256 digest
+        // instead of a SHA-256 one.
+        let url = format!("{KMS_API_URL}/{}:asymmetricSign", self.key_version);
+        let body = json!({
+            "digest": { "sha256": BASE64.encode(message_hash.as_bytes()) },
+        });
+        let response: AsymmetricSignResponse =
+            self.request(self.client.post(url).json(&body)).await?;
+        der::decode_signature(&decode_base64(&response.signature)?)
+    }
+}
@@ -0,0 +1,49 @@
+//! Metrics for operator signers.
+
+use std::time::Duration;
+
+use vise::{Buckets, Counter, EncodeLabelValue, Histogram, LabeledFamily, Metrics};
+
+/// Kind of [`SigningBackend`](super::SigningBackend).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue)]
+#[metrics(rename_all = "snake_case")]
+pub enum SignerKind {
+    PrivateKey,
+    AwsKms,
+    GcpKms,
+    Web3Signer,
+}
+
+impl SignerKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::PrivateKey => "private_key",
+            Self::AwsKms => "aws_kms",
+            Self::GcpKms => "gcp_kms",
+            Self::Web3Signer => "web3signer",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue)]
+#[metrics(rename_all = "snake_case")]
+pub(super) enum SignerMethod {
+    SignMessage,
+    SignTypedData,
+    SignTransaction,
+    HealthCheck,
+}
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "server_operator_signer")]
+pub(super) struct SignerMetrics {
+    /// Latency of requests to the signer.
+    #[metrics(buckets = Buckets::LATENCIES, labels = ["signer", "method"])]
+    pub latency: LabeledFamily<(SignerKind, SignerMethod), Histogram<Duration>, 2>,
+    /// Number of failed requests to the signer.
+    #[metrics(labels = ["signer", "method"])]
+    pub errors: LabeledFamily<(SignerKind, SignerMethod), Counter, 2>,
+}
+
+#[vise::register]
+pub(super) static METRICS: vise::Global<SignerMetrics> = vise::Global::new();
@@ -0,0 +1,323 @@
+//! Signers for the operator account that don't require the operator private key to be present in the process.
+
+use std::{fmt, sync::Arc, time::Instant};
+
+use async_trait::async_trait;
+use serde::Serialize;
+use zksync_config::configs::eth_sender::{OperatorSignerConfig, OperatorSignerMode};
+use zksync_health_check::{CheckHealth, Health, HealthStatus};
+use zksync_types::{
+    tx::primitives::PackedEthSignature,
+    web3::signing::{keccak256, Signature},
+    Address, EIP712TypedStructure, Eip712Domain, H256, U256,
+};
+
+use self::metrics::{SignerMethod, METRICS};
+pub use self::{
+    aws_kms::AwsKmsSigner, gcp_kms::GcpKmsSigner, metrics::SignerKind, web3signer::Web3Signer,
+};
+use crate::{
+    raw_ethereum_tx::{Transaction, TransactionParameters},
+    EthereumSigner, PrivateKeySigner, SignerError,
+};
+
+mod aws_kms;
+mod der;
+mod gcp_kms;
+mod metrics;
+#[cfg(test)]
+mod tests;
+mod web3signer;
+
+/// Order of the `secp256k1` curve.
+const SECP256K1_ORDER: U256 = U256([
+    0xbfd2_5e8c_d036_4141,
+    0xbaae_dce6_af48_a03b,
+    0xffff_ffff_ffff_fffe,
+    0xffff_ffff_ffff_ffff,
+]);
+
+/// ECDSA signature over `secp256k1` without the recovery ID, as returned by most remote signers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EcdsaSignature {
+    pub r: U256,
+    pub s: U256,
+}
+
+impl EcdsaSignature {
+    /// Converts the signature to the canonical form with low `s` value (EIP-2), which is the only form
+    /// accepted by Ethereum. KMS services are not guaranteed to return signatures in this form.
+    fn normalize(self) -> Self {
+        if self.s > SECP256K1_ORDER / 2 {
+            Self {
+                r: self.r,
+                s: SECP256K1_ORDER - self.s,
+            }
+        } else {
+            self
+        }
+    }
+}
+
+/// Backend creating `secp256k1` signatures with the operator key, which may live outside the process
+/// (e.g., in a KMS or a remote signing service).
+#[async_trait]
+pub trait SigningBackend: 'static + fmt::Debug + Send + Sync {
+    /// Returns the kind of this backend used in logs and metrics.
+    fn kind(&self) -> SignerKind;
+
+    /// Returns the address of the signing key.
+    async fn address(&self) -> Result<Address, SignerError>;
+
+    /// Signs the Keccak-256 hash of the `message`. The message is provided together with its hash
+    /// because some backends (e.g., Web3Signer) hash messages themselves.
+    async fn sign(&self, message: &[u8], message_hash: H256)
+        -> Result<EcdsaSignature, SignerError>;
+
+    /// Checks whether the backend is reachable and is able to sign with the operator key.
+    async fn check_health(&self) -> Result<(), SignerError> {
+        self.address().await.map(drop)
+    }
+}
+
+#[async_trait]
+impl SigningBackend for PrivateKeySigner {
+    fn kind(&self) -> SignerKind {
+        SignerKind::PrivateKey
+    }
+
+    async fn address(&self) -> Result<Address, SignerError> {
+        self.get_address().await
+    }
+
+    async fn sign(
+        &self,
+        _message: &[u8],
+        message_hash: H256,
+    ) -> Result<EcdsaSignature, SignerError> {
+        let signature = self.sign_hash(message_hash)?;
+        Ok(EcdsaSignature {
+            r: U256::from_big_endian(signature.r()),
+            s: U256::from_big_endian(signature.s()),
+        })
+    }
+}
+
+/// [`EthereumSigner`] for the operator account backed by an arbitrary [`SigningBackend`].
+///
+/// Besides delegating to the backend, the signer normalizes signatures, restores their recovery IDs
+/// (checking that the signature was produced by the expected key along the way) and reports metrics.
+#[derive(Debug, Clone)]
+pub struct OperatorSigner {
+    backend: Arc<dyn SigningBackend>,
+    address: Address,
+}
+
+impl OperatorSigner {
+    /// Creates a signer with the specified backend. Fetches the operator address from the backend.
+    pub async fn new(backend: Arc<dyn SigningBackend>) -> Result<Self, SignerError> {
+        let address = backend.address().await?;
+        tracing::info!(
+            "Initialized {} signer for operator address {address:?}",
+            backend.kind().as_str()
+        );
+        Ok(Self { backend, address })
+    }
+
+    /// Creates a signer based on the provided config. `private_key` is only used (and required)
+    /// in the [`OperatorSignerMode::PrivateKey`] mode.
+    pub async fn from_config(
+        config: &OperatorSignerConfig,
+        private_key: Option<H256>,
+    ) -> Result<Self, SignerError> {
+        fn required<'a>(value: &'a Option<String>, name: &str) -> Result<&'a str, SignerError> {
+            value.as_deref().ok_or_else(|| {
+                SignerError::CustomError(format!("`{name}` is required for the configured signer"))
+            })
+        }
+
+        let timeout = config.request_timeout();
+        let backend: Arc<dyn SigningBackend> = match config.mode {
+            OperatorSignerMode::PrivateKey => {
+                let private_key = private_key.ok_or(SignerError::MissingEthPrivateKey)?;
+                Arc::new(PrivateKeySigner::new(private_key))
+            }
+            OperatorSignerMode::AwsKms => Arc::new(
+                AwsKmsSigner::new(
+                    required(&config.aws_kms_key_id, "aws_kms_key_id")?,
+                    required(&config.aws_kms_region, "aws_kms_region")?,
+                    timeout,
+                )
+                .await?,
+            ),
+            OperatorSignerMode::GcpKms => Arc::new(
+                GcpKmsSigner::new(
+                    required(&config.gcp_kms_key_version, "gcp_kms_key_version")?,
+                    timeout,
+                )
+                .await?,
+            ),
+            OperatorSignerMode::Web3Signer => Arc::new(Web3Signer::new(
+                required(&config.web3signer_url, "web3signer_url")?,
+                required(&config.web3signer_public_key, "web3signer_public_key")?,
+                timeout,
+            )?),
+        };
+        Self::new(backend).await
+    }
+
+    /// Returns the operator address.
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    /// Returns the kind of the signing backend.
+    pub fn kind(&self) -> SignerKind {
+        self.backend.kind()
+    }
+
+    /// Returns a health check for the signing backend.
+    pub fn health_check(&self) -> SignerHealthCheck {
+        SignerHealthCheck {
+            signer: self.clone(),
+        }
+    }
+
+    /// Signs the Keccak-256 hash of the `message` and returns a signature with the recovery ID (0 or 1) as `v`.
+    async fn sign_hash(
+        &self,
+        method: SignerMethod,
+        message: &[u8],
+        message_hash: H256,
+    ) -> Result<PackedEthSignature, SignerError> {
+        let kind = self.backend.kind();
+        let latency = METRICS.latency[&(kind, method)].start();
+        let result = self.backend.sign(message, message_hash).await;
+        latency.observe();
+        result
+            .and_then(|signature| self.recover_signature(signature.normalize(), message_hash))
+            .map_err(|err| {
+                METRICS.errors[&(kind, method)].inc();
+                tracing::warn!("Failed signing with {} signer: {err}", kind.as_str());
+                err
+            })
+    }
+
+    fn recover_signature(
+        &self,
+        signature: EcdsaSignature,
+        message_hash: H256,
+    ) -> Result<PackedEthSignature, SignerError> {
+        let mut r = H256::zero();
+        signature.r.to_big_endian(r.as_bytes_mut());
+        let mut s = H256::zero();
+        signature.s.to_big_endian(s.as_bytes_mut());
+
+        for recovery_id in [0, 1] {
+            let candidate = PackedEthSignature::from_rsv(&r, &s, recovery_id);
+            if candidate.signature_recover_signer(&message_hash).ok() == Some(self.address) {
+                return Ok(candidate);
+            }
+        }
+        Err(SignerError::SigningFailed(format!(
+            "signature returned by {} signer doesn't match operator address {:?}",
+            self.backend.kind().as_str(),
+            self.address
+        )))
+    }
+}
+
+#[async_trait]
+impl EthereumSigner for OperatorSigner {
+    async fn sign_message(&self, message: &[u8]) -> Result<PackedEthSignature, SignerError> {
+        let message_hash = PackedEthSignature::message_to_signed_bytes(message);
+        self.sign_hash(SignerMethod::SignMessage, message, message_hash)
+            .await
+    }
+
+    async fn sign_typed_data<S: EIP712TypedStructure + Sync>(
+        &self,
+        domain: &Eip712Domain,
+        typed_struct: &S,
+    ) -> Result<PackedEthSignature, SignerError> {
+        let mut message = b"\x19\x01".to_vec();
+        message.extend_from_slice(domain.hash_struct().as_bytes());
+        message.extend_from_slice(typed_struct.hash_struct().as_bytes());
+        let message_hash = PackedEthSignature::typed_data_to_signed_bytes(domain, typed_struct);
+        self.sign_hash(SignerMethod::SignTypedData, &message, message_hash)
+            .await
+    }
+
+    async fn sign_transaction(
+        &self,
+        raw_tx: TransactionParameters,
+    ) -> Result<Vec<u8>, SignerError> {
+        let chain_id = raw_tx.chain_id;
+        let tx = Transaction::from(raw_tx);
+        let payload = tx.signing_payload(chain_id);
+        let message_hash = H256(keccak256(&payload));
+        let signature = self
+            .sign_hash(SignerMethod::SignTransaction, &payload, message_hash)
+            .await?;
+
+        let v = if tx.is_legacy() {
+            signature.v_with_chain_id(chain_id)
+        } else {
+            signature.v().into()
+        };
+        let signature = Signature {
+            v,
+            r: H256::from_slice(signature.r()),
+            s: H256::from_slice(signature.s()),
+        };
+        let signed = tx.into_signed(chain_id, message_hash, signature);
+        Ok(signed.raw_transaction.0)
+    }
+
+    async fn get_address(&self) -> Result<Address, SignerError> {
+        Ok(self.address)
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct SignerHealthDetails {
+    signer: &'static str,
+    address: Address,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Health check for an [`OperatorSigner`] querying its backend on each check.
+#[derive(Debug)]
+pub struct SignerHealthCheck {
+    signer: OperatorSigner,
+}
+
+#[async_trait]
+impl CheckHealth for SignerHealthCheck {
+    fn name(&self) -> &'static str {
+        "operator_signer"
+    }
+
+    async fn check_health(&self) -> Health {
+        let kind = self.signer.kind();
+        let started_at = Instant::now();
+        let result = self.signer.backend.check_health().await;
+        METRICS.latency[&(kind, SignerMethod::HealthCheck)].observe(started_at.elapsed());
+
+        let mut details = SignerHealthDetails {
+            signer: kind.as_str(),
+            address: self.signer.address,
+            error: None,
+        };
+        let status = match result {
+            Ok(()) => HealthStatus::Ready,
+            Err(err) => {
+                METRICS.errors[&(kind, SignerMethod::HealthCheck)].inc();
+                details.error = Some(err.to_string());
+                HealthStatus::NotReady
+            }
+        };
+        Health::from(status).with_details(details)
+    }
+}
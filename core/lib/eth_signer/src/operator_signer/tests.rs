@@ -0,0 +1,283 @@
+use std::time::Duration;
+
+use actix_web::{
+    get, post,
+    web::{self, Data},
+    App, HttpResponse, HttpServer, Responder,
+};
+use futures::future::{AbortHandle, Abortable};
+use parity_crypto::publickey::KeyPair;
+use serde::Deserialize;
+use zksync_health_check::{CheckHealth, HealthStatus};
+use zksync_types::{H160, U64};
+
+use super::*;
+
+const PRIVATE_KEY: H256 = H256([5; 32]);
+
+fn test_transaction(transaction_type: u64) -> TransactionParameters {
+    TransactionParameters {
+        nonce: U256::from(1_u32),
+        to: Some(H160::repeat_byte(0x23)),
+        gas: U256::from(100_000_u32),
+        gas_price: Some(U256::from(2_u32)),
+        max_fee_per_gas: U256::from(2_u32),
+        max_priority_fee_per_gas: U256::from(1_u32),
+        value: Default::default(),
+        data: vec![1, 2, 3],
+        chain_id: 270,
+        transaction_type: Some(U64::from(transaction_type)),
+        access_list: None,
+        max_fee_per_blob_gas: None,
+        blob_sidecar: None,
+    }
+}
+
+/// Encodes an unsigned integer as DER, adding a leading zero byte if necessary.
+fn encode_der_integer(value: U256) -> Vec<u8> {
+    let mut bytes = [0_u8; 32];
+    value.to_big_endian(&mut bytes);
+    let first_nonzero = bytes.iter().position(|&byte| byte != 0).unwrap_or(31);
+    let mut bytes = bytes[first_nonzero..].to_vec();
+    if bytes[0] >= 0x80 {
+        bytes.insert(0, 0);
+    }
+    let mut encoded = vec![0x02, bytes.len() as u8];
+    encoded.extend(bytes);
+    encoded
+}
+
+fn encode_der_signature(signature: EcdsaSignature) -> Vec<u8> {
+    let mut contents = encode_der_integer(signature.r);
+    contents.extend(encode_der_integer(signature.s));
+    let mut encoded = vec![0x30, contents.len() as u8];
+    encoded.extend(contents);
+    encoded
+}
+
+fn uncompressed_public_key(private_key: H256) -> Vec<u8> {
+    let key_pair = KeyPair::from_secret_slice(private_key.as_bytes()).unwrap();
+    key_pair.public().as_bytes().to_vec()
+}
+
+/// Backend returning signatures with high `s` values, similar to some KMS services.
+#[derive(Debug)]
+struct HighSBackend(PrivateKeySigner);
+
+#[async_trait]
+impl SigningBackend for HighSBackend {
+    fn kind(&self) -> SignerKind {
+        SignerKind::AwsKms
+    }
+
+    async fn address(&self) -> Result<Address, SignerError> {
+        SigningBackend::address(&self.0).await
+    }
+
+    async fn sign(
+        &self,
+        message: &[u8],
+        message_hash: H256,
+    ) -> Result<EcdsaSignature, SignerError> {
+        let signature = SigningBackend::sign(&self.0, message, message_hash).await?;
+        assert!(signature.s <= SECP256K1_ORDER / 2);
+        Ok(EcdsaSignature {
+            r: signature.r,
+            s: SECP256K1_ORDER - signature.s,
+        })
+    }
+}
+
+/// Backend that reports one address, but signs with a different key.
+#[derive(Debug)]
+struct MismatchedKeyBackend(PrivateKeySigner);
+
+#[async_trait]
+impl SigningBackend for MismatchedKeyBackend {
+    fn kind(&self) -> SignerKind {
+        SignerKind::GcpKms
+    }
+
+    async fn address(&self) -> Result<Address, SignerError> {
+        Ok(Address::repeat_byte(1))
+    }
+
+    async fn sign(
+        &self,
+        message: &[u8],
+        message_hash: H256,
+    ) -> Result<EcdsaSignature, SignerError> {
+        SigningBackend::sign(&self.0, message, message_hash).await
+    }
+}
+
+#[test]
+fn decoding_der_signature() {
+    let signature = EcdsaSignature {
+        r: U256::from_big_endian(&[0xff; 32]),
+        s: U256::from(0x1234_u32),
+    };
+    let der = encode_der_signature(signature);
+    assert_eq!(der::decode_signature(&der).unwrap(), signature);
+
+    let mut der_with_trailing_data = der.clone();
+    der_with_trailing_data.push(0);
+    der::decode_signature(&der_with_trailing_data).unwrap_err();
+    der::decode_signature(&der[..der.len() - 1]).unwrap_err();
+}
+
+#[tokio::test]
+async fn decoding_der_public_key() {
+    // `SubjectPublicKeyInfo` header for uncompressed `secp256k1` public keys.
+    let mut der = hex::decode("3056301006072a8648ce3d020106052b8104000a034200").unwrap();
+    der.push(0x04);
+    der.extend(uncompressed_public_key(PRIVATE_KEY));
+
+    let expected_address = PrivateKeySigner::new(PRIVATE_KEY)
+        .get_address()
+        .await
+        .unwrap();
+    assert_eq!(
+        der::decode_public_key_address(&der).unwrap(),
+        expected_address
+    );
+
+    // Change the curve OID to P-256 (1.2.840.10045.3.1.7).
+    let mut p256_der = hex::decode("3059301306072a8648ce3d020106082a8648ce3d030107034200").unwrap();
+    p256_der.extend_from_slice(&der[der.len() - 65..]);
+    der::decode_public_key_address(&p256_der).unwrap_err();
+}
+
+#[tokio::test]
+async fn operator_signer_is_equivalent_to_private_key_signer() {
+    let pk_signer = PrivateKeySigner::new(PRIVATE_KEY);
+    let signer = OperatorSigner::new(Arc::new(pk_signer.clone()))
+        .await
+        .unwrap();
+    assert_eq!(signer.address(), pk_signer.get_address().await.unwrap());
+
+    for transaction_type in [0, 1, 2] {
+        let tx = test_transaction(transaction_type);
+        let expected_raw_tx = pk_signer.sign_transaction(tx.clone()).await.unwrap();
+        let raw_tx = signer.sign_transaction(tx).await.unwrap();
+        assert_eq!(
+            raw_tx, expected_raw_tx,
+            "transaction type {transaction_type}"
+        );
+    }
+
+    let message = b"some_text_message";
+    let expected_signature = pk_signer.sign_message(message).await.unwrap();
+    let signature = signer.sign_message(message).await.unwrap();
+    assert_eq!(signature, expected_signature);
+}
+
+#[tokio::test]
+async fn operator_signer_normalizes_signatures() {
+    let pk_signer = PrivateKeySigner::new(PRIVATE_KEY);
+    let signer = OperatorSigner::new(Arc::new(HighSBackend(pk_signer.clone())))
+        .await
+        .unwrap();
+
+    for transaction_type in [0, 2] {
+        let tx = test_transaction(transaction_type);
+        let expected_raw_tx = pk_signer.sign_transaction(tx.clone()).await.unwrap();
+        let raw_tx = signer.sign_transaction(tx).await.unwrap();
+        assert_eq!(
+            raw_tx, expected_raw_tx,
+            "transaction type {transaction_type}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn operator_signer_rejects_signatures_from_unexpected_key() {
+    let pk_signer = PrivateKeySigner::new(PRIVATE_KEY);
+    let signer = OperatorSigner::new(Arc::new(MismatchedKeyBackend(pk_signer)))
+        .await
+        .unwrap();
+    let err = signer
+        .sign_transaction(test_transaction(2))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, SignerError::SigningFailed(message) if message.contains("doesn't match")),
+        "{err}"
+    );
+}
+
+#[derive(Debug, Deserialize)]
+struct Web3SignerRequest {
+    data: String,
+}
+
+#[post("/api/v1/eth1/sign/{identifier}")]
+async fn web3signer_sign(
+    identifier: web::Path<String>,
+    request: web::Json<Web3SignerRequest>,
+    public_key: Data<String>,
+) -> impl Responder {
+    if *identifier != **public_key {
+        return HttpResponse::NotFound().body("unknown key");
+    }
+    let data = hex::decode(request.data.trim_start_matches("0x")).unwrap();
+    let signature = PackedEthSignature::sign(&PRIVATE_KEY, &data).unwrap();
+    HttpResponse::Ok().body(format!("0x{}", hex::encode(signature.serialize_packed())))
+}
+
+#[get("/upcheck")]
+async fn web3signer_upcheck() -> impl Responder {
+    HttpResponse::Ok().body("OK")
+}
+
+fn run_web3signer_server(public_key: String) -> (String, AbortHandle) {
+    let server = HttpServer::new(move || {
+        App::new()
+            .app_data(Data::new(public_key.clone()))
+            .service(web3signer_sign)
+            .service(web3signer_upcheck)
+    })
+    .bind("127.0.0.1:0")
+    .expect("failed binding Web3Signer mock");
+    let address = server.addrs()[0];
+
+    let (abort_handle, abort_registration) = AbortHandle::new_pair();
+    tokio::spawn(Abortable::new(server.run(), abort_registration));
+    (format!("http://{address}/"), abort_handle)
+}
+
+#[actix_rt::test]
+async fn signing_with_web3signer() {
+    let public_key = format!("0x{}", hex::encode(uncompressed_public_key(PRIVATE_KEY)));
+    let (url, abort_handle) = run_web3signer_server(public_key.clone());
+
+    let backend = Web3Signer::new(&url, &public_key, Duration::from_secs(5)).unwrap();
+    let signer = OperatorSigner::new(Arc::new(backend)).await.unwrap();
+    let pk_signer = PrivateKeySigner::new(PRIVATE_KEY);
+    assert_eq!(signer.address(), pk_signer.get_address().await.unwrap());
+    assert_eq!(signer.kind(), SignerKind::Web3Signer);
+
+    let tx = test_transaction(2);
+    let expected_raw_tx = pk_signer.sign_transaction(tx.clone()).await.unwrap();
+    let raw_tx = signer.sign_transaction(tx).await.unwrap();
+    assert_eq!(raw_tx, expected_raw_tx);
+
+    let health = signer.health_check().check_health().await;
+    assert_eq!(health.status(), HealthStatus::Ready);
+    abort_handle.abort();
+}
+
+#[tokio::test]
+async fn unreachable_web3signer_is_not_healthy() {
+    let public_key = hex::encode(uncompressed_public_key(PRIVATE_KEY));
+    let backend =
+        Web3Signer::new("http://127.0.0.1:1/", &public_key, Duration::from_secs(1)).unwrap();
+    let signer = OperatorSigner::new(Arc::new(backend)).await.unwrap();
+
+    let health = signer.health_check().check_health().await;
+    assert_eq!(health.status(), HealthStatus::NotReady);
+    signer
+        .sign_transaction(test_transaction(2))
+        .await
+        .unwrap_err();
+}
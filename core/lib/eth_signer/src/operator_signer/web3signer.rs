@@ -0,0 +1,99 @@
+//! Signer using the remote signing API of [Web3Signer](https://docs.web3signer.consensys.io/).
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde_json::json;
+use zksync_types::{Address, H256, U256};
+
+use super::{der, EcdsaSignature, SignerKind, SigningBackend};
+use crate::SignerError;
+
+/// Signer sending signing requests to the `eth1` API of a Web3Signer instance (or any service
+/// implementing the same API). The service hashes the signed data with Keccak-256 itself.
+#[derive(Debug)]
+pub struct Web3Signer {
+    url: String,
+    public_key: String,
+    address: Address,
+    client: reqwest::Client,
+}
+
+impl Web3Signer {
+    /// Creates a signer for the key with the specified hex-encoded uncompressed public key.
+    pub fn new(url: &str, public_key: &str, timeout: Duration) -> Result<Self, SignerError> {
+        let public_key = public_key.strip_prefix("0x").unwrap_or(public_key);
+        let public_key_bytes = hex::decode(public_key)
+            .map_err(|err| SignerError::CustomError(format!("invalid public key: {err}")))?;
+        let address = der::public_key_to_address(&public_key_bytes)?;
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|err| SignerError::CustomError(err.to_string()))?;
+
+        Ok(Self {
+            url: url.trim_end_matches('/').to_owned(),
+            public_key: format!("0x{public_key}"),
+            address,
+            client,
+        })
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<String, SignerError> {
+        let response = request.send().await.map_err(|err| {
+            SignerError::SigningFailed(format!("Web3Signer request failed: {err}"))
+        })?;
+        let status = response.status();
+        let text = response.text().await.map_err(|err| {
+            SignerError::SigningFailed(format!("failed reading Web3Signer response: {err}"))
+        })?;
+        if status.is_success() {
+            Ok(text)
+        } else {
+            Err(SignerError::SigningFailed(format!(
+                "Web3Signer responded with {status}: {text}"
+            )))
+        }
+    }
+}
+
+#[async_trait]
+impl SigningBackend for Web3Signer {
+    fn kind(&self) -> SignerKind {
+        SignerKind::Web3Signer
+    }
+
+    async fn address(&self) -> Result<Address, SignerError> {
+        Ok(self.address)
+    }
+
+    async fn sign(
+        &self,
+        message: &[u8],
+        _message_hash: H256,
+    ) -> Result<EcdsaSignature, SignerError> {
+        let url = format!("{}/api/v1/eth1/sign/{}", self.url, self.public_key);
+        let body = json!({ "data": format!("0x{}", hex::encode(message)) });
+        let response = self.send(self.client.post(url).json(&body)).await?;
+
+        let response = response.trim();
+        let signature = hex::decode(response.strip_prefix("0x").unwrap_or(response))
+            .map_err(|err| SignerError::SigningFailed(format!("invalid signature hex: {err}")))?;
+        if signature.len() != 65 {
+            return Err(SignerError::SigningFailed(format!(
+                "expected 65-byte signature from Web3Signer, got {} bytes",
+                signature.len()
+            )));
+        }
+        // The recovery ID in the last byte is ignored; it's restored by the caller.
+        Ok(EcdsaSignature {
+            r: U256::from_big_endian(&signature[..32]),
+            s: U256::from_big_endian(&signature[32..64]),
+        })
+    }
+
+    async fn check_health(&self) -> Result<(), SignerError> {
+        let url = format!("{}/upcheck", self.url);
+        self.send(self.client.get(url)).await.map(drop)
+    }
+}
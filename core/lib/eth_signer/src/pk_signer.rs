@@ -23,6 +23,12 @@ impl PrivateKeySigner {
     pub fn new(private_key: H256) -> Self {
         Self { private_key }
     }
+
+    /// Signs a prehashed message.
+    pub(crate) fn sign_hash(&self, hash: H256) -> Result<PackedEthSignature, SignerError> {
+        PackedEthSignature::sign_raw(&self.private_key, &hash)
+            .map_err(|err| SignerError::SigningFailed(err.to_string()))
+    }
 }
 
 #[async_trait::async_trait]
@@ -60,26 +66,8 @@ impl EthereumSigner for PrivateKeySigner {
         raw_tx: TransactionParameters,
     ) -> Result<Vec<u8>, SignerError> {
         let key = SecretKey::from_slice(self.private_key.as_bytes()).unwrap();
-
-        // According to the code in web3 <https://docs.rs/web3/latest/src/web3/api/accounts.rs.html#86>
-        // We should use `max_fee_per_gas` as `gas_price` if we use EIP1559
-        let gas_price = raw_tx.max_fee_per_gas;
-
-        let max_priority_fee_per_gas = raw_tx.max_priority_fee_per_gas;
-
-        let tx = Transaction {
-            to: raw_tx.to,
-            nonce: raw_tx.nonce,
-            gas: raw_tx.gas,
-            gas_price,
-            value: raw_tx.value,
-            data: raw_tx.data,
-            transaction_type: raw_tx.transaction_type,
-            access_list: raw_tx.access_list.unwrap_or_default(),
-            max_priority_fee_per_gas,
-        };
-
-        let signed = tx.sign(&key, raw_tx.chain_id);
+        let chain_id = raw_tx.chain_id;
+        let signed = Transaction::from(raw_tx).sign(&key, chain_id);
         Ok(signed.raw_transaction.0)
     }
 }
@@ -107,6 +95,8 @@ mod test {
             chain_id: 270,
             transaction_type: Some(U64::from(1u32)),
             access_list: None,
+            max_fee_per_blob_gas: None,
+            blob_sidecar: None,
         };
         let raw_tx = signer
             .sign_transaction(raw_transaction.clone())
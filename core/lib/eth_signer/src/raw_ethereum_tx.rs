@@ -9,19 +9,21 @@
 //! In the case where it will be possible to use only the web3 library without copy-paste, the changes will be small and simple
 //! Link to @Deniallugo's PR to web3: https://github.com/tomusdrw/rust-web3/pull/630
 
-use rlp::RlpStream;
+use rlp::{Rlp, RlpStream};
 use zksync_types::{
+    eth_sender::EthTxBlobSidecar,
     ethabi::Address,
     web3::{
         signing::{self, Signature},
         types::{AccessList, SignedTransaction},
     },
-    U256, U64,
+    H256, U256, U64,
 };
 
 const LEGACY_TX_ID: u64 = 0;
 const ACCESSLISTS_TX_ID: u64 = 1;
 const EIP1559_TX_ID: u64 = 2;
+const EIP4844_TX_ID: u64 = 3;
 
 #[derive(Clone, Debug, PartialEq, Default)]
 pub struct TransactionParameters {
@@ -47,6 +49,30 @@ pub struct TransactionParameters {
     pub max_fee_per_gas: U256,
     /// miner bribe
     pub max_priority_fee_per_gas: U256,
+    /// Max fee per blob gas (EIP-4844 transactions only)
+    pub max_fee_per_blob_gas: Option<U256>,
+    /// Blobs published by the transaction (EIP-4844 transactions only)
+    pub blob_sidecar: Option<EthTxBlobSidecar>,
+}
+
+impl From<TransactionParameters> for Transaction {
+    fn from(raw_tx: TransactionParameters) -> Self {
+        // According to the code in web3 <https://docs.rs/web3/latest/src/web3/api/accounts.rs.html#86>
+        // We should use `max_fee_per_gas` as `gas_price` if we use EIP1559
+        Self {
+            to: raw_tx.to,
+            nonce: raw_tx.nonce,
+            gas: raw_tx.gas,
+            gas_price: raw_tx.max_fee_per_gas,
+            value: raw_tx.value,
+            data: raw_tx.data,
+            transaction_type: raw_tx.transaction_type,
+            access_list: raw_tx.access_list.unwrap_or_default(),
+            max_priority_fee_per_gas: raw_tx.max_priority_fee_per_gas,
+            max_fee_per_blob_gas: raw_tx.max_fee_per_blob_gas.unwrap_or_default(),
+            blob_sidecar: raw_tx.blob_sidecar,
+        }
+    }
 }
 
 /// A transaction used for RLP encoding, hashing and signing.
@@ -61,6 +87,8 @@ pub struct Transaction {
     pub transaction_type: Option<U64>,
     pub access_list: AccessList,
     pub max_priority_fee_per_gas: U256,
+    pub max_fee_per_blob_gas: U256,
+    pub blob_sidecar: Option<EthTxBlobSidecar>,
 }
 
 impl Transaction {
@@ -147,6 +175,70 @@ impl Transaction {
         stream
     }
 
+    fn encode_eip4844_payload(&self, chain_id: u64, signature: Option<&Signature>) -> RlpStream {
+        let mut stream = RlpStream::new();
+
+        let list_size = if signature.is_some() { 14 } else { 11 };
+        stream.begin_list(list_size);
+
+        stream.append(&chain_id);
+
+        stream.append(&self.nonce);
+        stream.append(&self.max_priority_fee_per_gas);
+        stream.append(&self.gas_price);
+        stream.append(&self.gas);
+        // Blob transactions cannot create contracts, so `to` is mandatory.
+        let to = self
+            .to
+            .expect("EIP-4844 transactions must have a recipient");
+        stream.append(&to);
+        stream.append(&self.value);
+        stream.append(&self.data);
+
+        self.rlp_append_access_list(&mut stream);
+
+        stream.append(&self.max_fee_per_blob_gas);
+        let versioned_hashes = self
+            .blob_sidecar
+            .as_ref()
+            .map(EthTxBlobSidecar::versioned_hashes)
+            .unwrap_or_default();
+        stream.append_list::<H256, _>(&versioned_hashes);
+
+        if let Some(signature) = signature {
+            self.rlp_append_signature(&mut stream, signature);
+        }
+
+        stream
+    }
+
+    /// Wraps a signed EIP-4844 payload into the network representation, which includes blobs, commitments
+    /// and proofs from the sidecar: `rlp([tx_payload, blobs, commitments, proofs])`.
+    fn encode_eip4844_network_payload(&self, signed_payload: &RlpStream) -> RlpStream {
+        let blobs = self
+            .blob_sidecar
+            .as_ref()
+            .map(|sidecar| sidecar.blobs.as_slice())
+            .unwrap_or_default();
+
+        let mut stream = RlpStream::new();
+        stream.begin_list(4);
+        stream.append_raw(signed_payload.as_raw(), 1);
+        stream.begin_list(blobs.len());
+        for blob in blobs {
+            stream.append(&blob.blob);
+        }
+        stream.begin_list(blobs.len());
+        for blob in blobs {
+            stream.append(&blob.commitment);
+        }
+        stream.begin_list(blobs.len());
+        for blob in blobs {
+            stream.append(&blob.proof);
+        }
+        stream
+    }
+
     fn rlp_append_signature(&self, stream: &mut RlpStream, signature: &Signature) {
         stream.append(&signature.v);
         stream.append(&U256::from_big_endian(signature.r.as_bytes()));
@@ -184,36 +276,61 @@ impl Transaction {
                 [&[tx_id], stream.as_raw()].concat()
             }
 
+            Some(EIP4844_TX_ID) => {
+                let tx_id: u8 = EIP4844_TX_ID as u8;
+                let mut stream = self.encode_eip4844_payload(chain_id, signature);
+                // Only signed transactions are wrapped with the sidecar; the signed hash doesn't include it.
+                if signature.is_some() {
+                    stream = self.encode_eip4844_network_payload(&stream);
+                }
+                [&[tx_id], stream.as_raw()].concat()
+            }
+
             _ => {
                 panic!("Unsupported transaction type");
             }
         }
     }
 
-    /// Sign and return a raw signed transaction.
-    pub fn sign(self, sign: impl signing::Key, chain_id: u64) -> SignedTransaction {
-        let adjust_v_value = matches!(
+    /// Returns `true` if the `v` value of the transaction signature must encode the chain ID (EIP-155).
+    pub fn is_legacy(&self) -> bool {
+        matches!(
             self.transaction_type.map(|t| t.as_u64()),
             Some(LEGACY_TX_ID) | None
-        );
-
-        let encoded = self.encode(chain_id, None);
+        )
+    }
 
-        let hash = signing::keccak256(encoded.as_ref());
+    /// Returns the unsigned payload of the transaction; its Keccak-256 hash is what should be signed.
+    pub fn signing_payload(&self, chain_id: u64) -> Vec<u8> {
+        self.encode(chain_id, None)
+    }
 
-        let signature = if adjust_v_value {
+    /// Sign and return a raw signed transaction.
+    pub fn sign(self, sign: impl signing::Key, chain_id: u64) -> SignedTransaction {
+        let hash = signing::keccak256(&self.signing_payload(chain_id));
+        let signature = if self.is_legacy() {
             sign.sign(&hash, Some(chain_id))
                 .expect("hash is non-zero 32-bytes; qed")
         } else {
             sign.sign_message(&hash)
                 .expect("hash is non-zero 32-bytes; qed")
         };
+        self.into_signed(chain_id, hash.into(), signature)
+    }
 
+    /// Assembles a raw signed transaction from a signature obtained externally (e.g., from a KMS).
+    /// The `v` value of the `signature` must already be adjusted according to [`Self::is_legacy()`].
+    pub fn into_signed(
+        self,
+        chain_id: u64,
+        message_hash: H256,
+        signature: Signature,
+    ) -> SignedTransaction {
         let signed = self.encode(chain_id, Some(&signature));
-        let transaction_hash = signing::keccak256(signed.as_ref()).into();
+        let transaction_hash = signed_tx_hash(&signed);
 
         SignedTransaction {
-            message_hash: hash.into(),
+            message_hash,
             v: signature.v,
             r: signature.r,
             s: signature.s,
@@ -222,3 +339,18 @@ impl Transaction {
         }
     }
 }
+
+/// Computes the hash of a signed transaction in its network representation. For EIP-4844 transactions,
+/// the hash covers the signed payload only, i.e. excludes the blob sidecar.
+pub fn signed_tx_hash(raw_tx: &[u8]) -> H256 {
+    if raw_tx.first() == Some(&(EIP4844_TX_ID as u8)) {
+        let network_payload = Rlp::new(&raw_tx[1..]);
+        if let Ok(signed_payload) = network_payload.at(0) {
+            if signed_payload.is_list() {
+                let tx_id: u8 = EIP4844_TX_ID as u8;
+                return signing::keccak256(&[&[tx_id], signed_payload.as_raw()].concat()).into();
+            }
+        }
+    }
+    signing::keccak256(raw_tx).into()
+}
@@ -1,7 +1,9 @@
-use std::collections::{hash_map, BTreeSet, HashMap, HashSet};
+use std::collections::{hash_map, BTreeSet, HashMap, HashSet, VecDeque};
 
 use zksync_types::{
-    l1::L1Tx, l2::L2Tx, Address, ExecuteTransactionCommon, Nonce, PriorityOpId, Transaction,
+    l1::L1Tx,
+    l2::{bundle::TransactionBundle, L2Tx},
+    Address, ExecuteTransactionCommon, Nonce, PriorityOpId, Transaction, H256,
 };
 
 use crate::types::{AccountTransactions, L2TxFilter, MempoolScore};
@@ -16,7 +18,10 @@ pub struct MempoolInfo {
 pub struct MempoolStats {
     pub l1_transaction_count: usize,
     pub l2_transaction_count: u64,
+    /// Number of L2 transactions with a nonce gap before them.
+    pub l2_queued_transaction_count: usize,
     pub l2_priority_queue_size: usize,
+    pub l2_bundle_count: usize,
 }
 
 #[derive(Debug)]
@@ -27,6 +32,8 @@ pub struct MempoolStore {
     l2_transactions_per_account: HashMap<Address, AccountTransactions>,
     /// Global priority queue for L2 transactions. Used for scoring
     l2_priority_queue: BTreeSet<MempoolScore>,
+    /// Pending L2 transaction bundles ordered by their receipt time
+    l2_bundles: VecDeque<TransactionBundle>,
     /// Next priority operation
     next_priority_id: PriorityOpId,
     stashed_accounts: Vec<Address>,
@@ -41,6 +48,7 @@ impl MempoolStore {
             l1_transactions: HashMap::new(),
             l2_transactions_per_account: HashMap::new(),
             l2_priority_queue: BTreeSet::new(),
+            l2_bundles: VecDeque::new(),
             next_priority_id,
             stashed_accounts: vec![],
             size: 0,
@@ -122,7 +130,20 @@ impl MempoolStore {
         }
     }
 
-    /// Returns `true` if there is a transaction in the mempool satisfying the filter.
+    /// Inserts transaction bundles to the mempool. Bundles are not included into the mempool size
+    /// and are returned by [`Self::next_bundle()`] in the insertion order.
+    pub fn insert_bundles(&mut self, bundles: Vec<TransactionBundle>) {
+        for bundle in bundles {
+            tracing::trace!(
+                "inserting bundle {:?} with {} transactions",
+                bundle.hash,
+                bundle.transactions.len()
+            );
+            self.l2_bundles.push_back(bundle);
+        }
+    }
+
+    /// Returns `true` if there is a transaction or a bundle in the mempool satisfying the filter.
     pub fn has_next(&self, filter: &L2TxFilter) -> bool {
         self.l1_transactions.get(&self.next_priority_id).is_some()
             || self
@@ -130,6 +151,79 @@ impl MempoolStore {
                 .iter()
                 .rfind(|el| el.matches_filter(filter))
                 .is_some()
+            || self
+                .l2_bundles
+                .iter()
+                .any(|bundle| self.is_bundle_ready(bundle, filter))
+    }
+
+    /// Checks whether all bundle transactions match the filter, and bundle nonces directly follow
+    /// the nonces of the corresponding accounts in the mempool.
+    fn is_bundle_ready(&self, bundle: &TransactionBundle, filter: &L2TxFilter) -> bool {
+        let fees_match = bundle
+            .transactions
+            .iter()
+            .all(|tx| filter.matches_fee(&tx.common_data.fee));
+        if !fees_match {
+            return false;
+        }
+
+        let mut expected_nonces = HashMap::new();
+        for (account, nonce) in bundle.account_nonces() {
+            let account_txs = self.l2_transactions_per_account.get(&account);
+            if account_txs.map_or(false, |txs| txs.contains(nonce)) {
+                return false; // The bundle conflicts with a standalone transaction
+            }
+            let expected_nonce = expected_nonces
+                .entry(account)
+                .or_insert_with(|| account_txs.map_or(nonce, |txs| txs.nonce()));
+            if *expected_nonce != nonce {
+                return false;
+            }
+            *expected_nonce += 1;
+        }
+        true
+    }
+
+    /// Returns the next bundle for execution from the mempool, advancing nonces of the affected
+    /// accounts. If the bundle is not executed, it must be returned via [`Self::return_bundle()`].
+    pub fn next_bundle(&mut self, filter: &L2TxFilter) -> Option<TransactionBundle> {
+        let position = self
+            .l2_bundles
+            .iter()
+            .position(|bundle| self.is_bundle_ready(bundle, filter))?;
+        let bundle = self.l2_bundles.remove(position)?;
+
+        for (account, nonce) in bundle.account_nonces() {
+            let account_txs = self
+                .l2_transactions_per_account
+                .entry(account)
+                .or_insert_with(|| AccountTransactions::new(nonce));
+            if let Some(score) = account_txs.advance_nonce(nonce) {
+                self.l2_priority_queue.insert(score);
+            }
+        }
+        Some(bundle)
+    }
+
+    /// Rolls back nonces advanced by [`Self::next_bundle()`] for a bundle that was not executed.
+    /// The bundle itself is discarded; use [`Self::return_bundle()`] to put it back to the mempool.
+    pub fn rollback_bundle(&mut self, bundle: &TransactionBundle) {
+        let account_nonces: Vec<_> = bundle.account_nonces().collect();
+        for &(account, nonce) in account_nonces.iter().rev() {
+            let Some(account_txs) = self.l2_transactions_per_account.get_mut(&account) else {
+                continue; // The account may have been garbage-collected
+            };
+            if let Some(score) = account_txs.reset_nonce(nonce) {
+                self.l2_priority_queue.remove(&score);
+            }
+        }
+    }
+
+    /// Rolls back a bundle and returns it to the front of the bundle queue.
+    pub fn return_bundle(&mut self, bundle: TransactionBundle) {
+        self.rollback_bundle(&bundle);
+        self.l2_bundles.push_front(bundle);
     }
 
     /// Returns next transaction for execution from mempool
@@ -138,7 +232,16 @@ impl MempoolStore {
             self.next_priority_id += 1;
             return Some(transaction.into());
         }
+        self.next_l2_transaction(filter)
+    }
+
+    /// Checks whether the next priority (L1) transaction is available for execution.
+    pub fn has_next_l1_transaction(&self) -> bool {
+        self.l1_transactions.contains_key(&self.next_priority_id)
+    }
 
+    /// Returns next L2 transaction for execution from mempool, skipping priority transactions.
+    pub fn next_l2_transaction(&mut self, filter: &L2TxFilter) -> Option<Transaction> {
         let mut removed = 0;
         // We want to fetch the next transaction that would match the fee requirements.
         let tx_pointer = self
@@ -205,6 +308,28 @@ impl MempoolStore {
         }
     }
 
+    /// Removes all L2 transactions of the specified accounts from the mempool (e.g., before evicting some
+    /// of these transactions from the storage). Returns hashes of the removed transactions. Accounts
+    /// are reported as stashed by the next [`Self::get_mempool_info()`] call, so that their remaining
+    /// transactions are reloaded from the storage.
+    pub fn remove_accounts(&mut self, accounts: &HashSet<Address>) -> HashSet<H256> {
+        let mut removed_hashes = HashSet::new();
+        for account in accounts {
+            let Some(account_txs) = self.l2_transactions_per_account.remove(account) else {
+                continue;
+            };
+            self.size = self
+                .size
+                .checked_sub(account_txs.len() as u64)
+                .expect("mempool size can't be negative");
+            removed_hashes.extend(account_txs.hashes());
+            self.stashed_accounts.push(*account);
+        }
+        self.l2_priority_queue
+            .retain(|score| !accounts.contains(&score.account));
+        removed_hashes
+    }
+
     pub fn get_mempool_info(&mut self) -> MempoolInfo {
         MempoolInfo {
             stashed_accounts: std::mem::take(&mut self.stashed_accounts),
@@ -216,7 +341,13 @@ impl MempoolStore {
         MempoolStats {
             l1_transaction_count: self.l1_transactions.len(),
             l2_transaction_count: self.size,
+            l2_queued_transaction_count: self
+                .l2_transactions_per_account
+                .values()
+                .map(AccountTransactions::queued_len)
+                .sum(),
             l2_priority_queue_size: self.l2_priority_queue.len(),
+            l2_bundle_count: self.l2_bundles.len(),
         }
     }
 
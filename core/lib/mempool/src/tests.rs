@@ -7,7 +7,7 @@ use zksync_types::{
     fee::Fee,
     helpers::unix_timestamp_ms,
     l1::{OpProcessingType, PriorityQueueType},
-    l2::L2Tx,
+    l2::{bundle::TransactionBundle, L2Tx},
     Address, Execute, ExecuteTransactionCommon, L1TxCommonData, Nonce, PriorityOpId, Transaction,
     H256, U256,
 };
@@ -101,6 +101,41 @@ fn missing_txns() {
     );
 }
 
+#[test]
+fn queued_txns() {
+    let mut mempool = MempoolStore::new(PriorityOpId(0), 100);
+    let account0 = Address::random();
+    let account1 = Address::random();
+    let transactions = vec![
+        gen_l2_tx(account0, Nonce(0)),
+        gen_l2_tx(account0, Nonce(2)),
+        gen_l2_tx(account0, Nonce(3)),
+        gen_l2_tx(account1, Nonce(1)),
+    ];
+    mempool.insert(transactions, HashMap::new());
+    let stats = mempool.stats();
+    assert_eq!(stats.l2_transaction_count, 4);
+    assert_eq!(stats.l2_queued_transaction_count, 3);
+
+    assert_eq!(
+        view(mempool.next_transaction(&L2TxFilter::default())),
+        (account0, 0)
+    );
+    assert_eq!(mempool.next_transaction(&L2TxFilter::default()), None);
+    assert_eq!(mempool.stats().l2_queued_transaction_count, 3);
+
+    // Closing the gap promotes all queued transactions of the account.
+    mempool.insert(vec![gen_l2_tx(account0, Nonce(1))], HashMap::new());
+    assert_eq!(mempool.stats().l2_queued_transaction_count, 1);
+    for expected_nonce in 1..=3 {
+        assert_eq!(
+            view(mempool.next_transaction(&L2TxFilter::default())),
+            (account0, expected_nonce)
+        );
+    }
+    assert_eq!(mempool.next_transaction(&L2TxFilter::default()), None);
+}
+
 #[test]
 fn prioritize_l1_txns() {
     let mut mempool = MempoolStore::new(PriorityOpId(0), 100);
@@ -117,6 +152,27 @@ fn prioritize_l1_txns() {
         .is_l1())
 }
 
+#[test]
+fn skipping_l1_txns() {
+    let mut mempool = MempoolStore::new(PriorityOpId(0), 100);
+    let account = Address::random();
+    let transactions = vec![gen_l2_tx(account, Nonce(0)), gen_l1_tx(PriorityOpId(0))];
+    mempool.insert(transactions, HashMap::new());
+    assert!(mempool.has_next_l1_transaction());
+    assert_eq!(
+        view(mempool.next_l2_transaction(&L2TxFilter::default())),
+        (account, 0)
+    );
+    assert_eq!(mempool.next_l2_transaction(&L2TxFilter::default()), None);
+
+    assert!(mempool.has_next_l1_transaction());
+    assert!(mempool
+        .next_transaction(&L2TxFilter::default())
+        .unwrap()
+        .is_l1());
+    assert!(!mempool.has_next_l1_transaction());
+}
+
 #[test]
 fn l1_txns_priority_id() {
     let mut mempool = MempoolStore::new(PriorityOpId(0), 100);
@@ -365,6 +421,125 @@ fn mempool_capacity() {
     );
 }
 
+#[test]
+fn bundles_basic_flow() {
+    let mut mempool = MempoolStore::new(PriorityOpId(0), 100);
+    let account0 = Address::random();
+    let account1 = Address::random();
+    mempool.insert(
+        vec![gen_l2_tx(account0, Nonce(0)), gen_l2_tx(account0, Nonce(2))],
+        HashMap::new(),
+    );
+    let bundle = gen_bundle(&[(account1, Nonce(0)), (account0, Nonce(1))]);
+    mempool.insert_bundles(vec![bundle.clone()]);
+
+    // The bundle cannot be executed until `account0` reaches nonce 1.
+    assert!(mempool.next_bundle(&L2TxFilter::default()).is_none());
+    assert_eq!(
+        view(mempool.next_transaction(&L2TxFilter::default())),
+        (account0, 0)
+    );
+    assert!(mempool.next_transaction(&L2TxFilter::default()).is_none());
+    assert!(mempool.has_next(&L2TxFilter::default()));
+
+    assert_eq!(mempool.next_bundle(&L2TxFilter::default()), Some(bundle));
+    assert!(mempool.next_bundle(&L2TxFilter::default()).is_none());
+    assert_eq!(mempool.stats().l2_bundle_count, 0);
+    // The transaction following the bundle one should be unblocked.
+    assert_eq!(
+        view(mempool.next_transaction(&L2TxFilter::default())),
+        (account0, 2)
+    );
+}
+
+#[test]
+fn bundle_with_nonce_gap_is_not_returned() {
+    let mut mempool = MempoolStore::new(PriorityOpId(0), 100);
+    let account = Address::random();
+    mempool.insert_bundles(vec![gen_bundle(&[
+        (account, Nonce(0)),
+        (account, Nonce(2)),
+    ])]);
+    assert!(!mempool.has_next(&L2TxFilter::default()));
+    assert!(mempool.next_bundle(&L2TxFilter::default()).is_none());
+    assert_eq!(mempool.stats().l2_bundle_count, 1);
+}
+
+#[test]
+fn returning_bundle() {
+    let mut mempool = MempoolStore::new(PriorityOpId(0), 100);
+    let account = Address::random();
+    mempool.insert(vec![gen_l2_tx(account, Nonce(2))], HashMap::new());
+    let first_bundle = gen_bundle(&[(account, Nonce(0)), (account, Nonce(1))]);
+    let second_bundle = gen_bundle(&[(Address::random(), Nonce(0))]);
+    mempool.insert_bundles(vec![first_bundle.clone(), second_bundle.clone()]);
+
+    let bundle = mempool.next_bundle(&L2TxFilter::default()).unwrap();
+    assert_eq!(bundle, first_bundle);
+    mempool.return_bundle(bundle);
+    // The transaction after the bundle must not be available once the bundle is rolled back.
+    assert!(mempool.next_transaction(&L2TxFilter::default()).is_none());
+    assert_eq!(
+        mempool.next_bundle(&L2TxFilter::default()),
+        Some(first_bundle)
+    );
+    assert_eq!(
+        view(mempool.next_transaction(&L2TxFilter::default())),
+        (account, 2)
+    );
+    assert_eq!(
+        mempool.next_bundle(&L2TxFilter::default()),
+        Some(second_bundle)
+    );
+}
+
+#[test]
+fn filtering_bundles() {
+    let mut mempool = MempoolStore::new(PriorityOpId(0), 100);
+    let account = Address::random();
+    let mut bundle = gen_bundle(&[(account, Nonce(0)), (account, Nonce(1))]);
+    bundle.transactions[1].common_data.fee.gas_per_pubdata_limit = U256::from(10);
+    mempool.insert_bundles(vec![bundle.clone()]);
+
+    let filter = L2TxFilter {
+        gas_per_pubdata: 100,
+        ..L2TxFilter::default()
+    };
+    assert!(!mempool.has_next(&filter));
+    assert!(mempool.next_bundle(&filter).is_none());
+    assert_eq!(mempool.next_bundle(&L2TxFilter::default()), Some(bundle));
+}
+
+#[test]
+fn removing_accounts() {
+    let mut mempool = MempoolStore::new(PriorityOpId(0), 100);
+    let account0 = Address::random();
+    let account1 = Address::random();
+    let mut transactions = vec![
+        gen_l2_tx(account0, Nonce(0)),
+        gen_l2_tx(account0, Nonce(1)),
+        gen_l2_tx(account1, Nonce(0)),
+    ];
+    for tx in &mut transactions {
+        let ExecuteTransactionCommon::L2(data) = &mut tx.common_data else {
+            unreachable!();
+        };
+        data.set_input(vec![], H256::random());
+    }
+    let account0_hashes: HashSet<_> = transactions[..2].iter().map(Transaction::hash).collect();
+    mempool.insert(transactions, HashMap::new());
+
+    let removed_hashes = mempool.remove_accounts(&HashSet::from([account0, Address::random()]));
+    assert_eq!(removed_hashes, account0_hashes);
+    assert_eq!(mempool.stats().l2_transaction_count, 1);
+    assert_eq!(mempool.get_mempool_info().stashed_accounts, [account0]);
+    assert_eq!(
+        view(mempool.next_transaction(&L2TxFilter::default())),
+        (account1, 0)
+    );
+    assert_eq!(mempool.next_transaction(&L2TxFilter::default()), None);
+}
+
 fn gen_l2_tx(address: Address, nonce: Nonce) -> Transaction {
     gen_l2_tx_with_timestamp(address, nonce, unix_timestamp_ms())
 }
@@ -384,6 +559,17 @@ fn gen_l2_tx_with_timestamp(address: Address, nonce: Nonce, received_at_ms: u64)
     txn.into()
 }
 
+fn gen_bundle(account_nonces: &[(Address, Nonce)]) -> TransactionBundle {
+    let transactions = account_nonces
+        .iter()
+        .map(|&(account, nonce)| gen_l2_tx(account, nonce).try_into().unwrap())
+        .collect();
+    TransactionBundle {
+        hash: H256::random(),
+        transactions,
+    }
+}
+
 fn gen_l1_tx(priority_id: PriorityOpId) -> Transaction {
     let execute = Execute {
         contract_address: Address::repeat_byte(0x11),
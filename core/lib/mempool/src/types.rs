@@ -1,7 +1,7 @@
 use std::{cmp::Ordering, collections::HashMap};
 
 use zksync_types::{
-    fee::Fee, fee_model::BatchFeeInput, l2::L2Tx, Address, Nonce, Transaction, U256,
+    fee::Fee, fee_model::BatchFeeInput, l2::L2Tx, Address, Nonce, Transaction, H256, U256,
 };
 
 /// Pending mempool transactions of account
@@ -64,16 +64,55 @@ impl AccountTransactions {
         let tx_nonce = transaction
             .nonce()
             .expect("nonce is not set for L2 transaction");
+        self.reset_nonce(tx_nonce)
+    }
+
+    /// Resets the account nonce to the nonce of a non-executed transaction. Returns optional score
+    /// of its successor.
+    pub fn reset_nonce(&mut self, tx_nonce: Nonce) -> Option<MempoolScore> {
         self.nonce = self.nonce.min(tx_nonce);
         self.transactions
             .get(&(tx_nonce + 1))
             .map(Self::score_for_transaction)
     }
 
+    /// Advances the account nonce past a transaction executed outside of this queue (e.g., as a part
+    /// of a bundle). Returns the score of the next transaction, if any.
+    pub fn advance_nonce(&mut self, executed_nonce: Nonce) -> Option<MempoolScore> {
+        self.nonce = executed_nonce + 1;
+        self.transactions
+            .get(&self.nonce)
+            .map(Self::score_for_transaction)
+    }
+
+    pub fn nonce(&self) -> Nonce {
+        self.nonce
+    }
+
+    pub fn contains(&self, nonce: Nonce) -> bool {
+        self.transactions.contains_key(&nonce)
+    }
+
     pub fn len(&self) -> usize {
         self.transactions.len()
     }
 
+    /// Returns the number of queued transactions, i.e. ones that cannot be executed until a nonce gap
+    /// before them is filled. Queued transactions are promoted automatically once the gap is closed.
+    pub fn queued_len(&self) -> usize {
+        let mut nonce = self.nonce;
+        let mut executable_len = 0;
+        while self.transactions.contains_key(&nonce) {
+            nonce += 1;
+            executable_len += 1;
+        }
+        self.transactions.len() - executable_len
+    }
+
+    pub fn hashes(&self) -> impl Iterator<Item = H256> + '_ {
+        self.transactions.values().map(L2Tx::hash)
+    }
+
     fn score_for_transaction(transaction: &L2Tx) -> MempoolScore {
         MempoolScore {
             account: transaction.initiator_account(),
@@ -98,8 +137,7 @@ pub struct MempoolScore {
 impl MempoolScore {
     /// Checks whether transaction matches requirements provided by state keeper.
     pub fn matches_filter(&self, filter: &L2TxFilter) -> bool {
-        self.fee_data.max_fee_per_gas >= U256::from(filter.fee_per_gas)
-            && self.fee_data.gas_per_pubdata_limit >= U256::from(filter.gas_per_pubdata)
+        filter.matches_fee(&self.fee_data)
     }
 }
 
@@ -138,6 +176,14 @@ pub struct L2TxFilter {
     pub gas_per_pubdata: u32,
 }
 
+impl L2TxFilter {
+    /// Checks whether transaction fee parameters match the filter.
+    pub fn matches_fee(&self, fee: &Fee) -> bool {
+        fee.max_fee_per_gas >= U256::from(self.fee_per_gas)
+            && fee.gas_per_pubdata_limit >= U256::from(self.gas_per_pubdata)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
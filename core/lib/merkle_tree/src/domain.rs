@@ -1,5 +1,7 @@
 //! Tying the Merkle tree implementation to the problem domain.
 
+use std::ops;
+
 use rayon::{ThreadPool, ThreadPoolBuilder};
 use zksync_crypto::hasher::blake2::Blake2Hasher;
 use zksync_types::{
@@ -15,7 +17,7 @@ use crate::{
         Key, Root, TreeEntry, TreeEntryWithProof, TreeInstruction, TreeLogEntry, ValueHash,
         TREE_DEPTH,
     },
-    BlockOutput, HashTree, MerkleTree, NoVersionError,
+    BlockOutput, Database, HashTree, MerkleTree, NoVersionError, PruneDatabase,
 };
 
 /// Metadata for the current tree state.
@@ -458,6 +460,42 @@ impl ZkSyncTreeReader {
         self.0.latest_root().leaf_count()
     }
 
+    /// Returns the earliest L1 batch for which the tree version is retained, i.e., for which proofs
+    /// can be generated. All versions starting from this L1 batch and up to the latest one are available.
+    /// Returns `None` if the tree is empty.
+    pub fn min_l1_batch_number(&self) -> Option<L1BatchNumber> {
+        let latest_version = self.0.latest_version()?;
+        // Stale keys for a version are only required by previous versions; the root node is replaced
+        // in each version, so each version has at least one stale key.
+        let db = &self.0.db;
+        let min_version = db
+            .min_stale_key_version()
+            .map_or(0, |version| version.saturating_sub(1));
+        let min_version = if db.root(min_version).is_some() {
+            min_version
+        } else {
+            // The tree was recovered from a snapshot, or all stale keys were pruned.
+            latest_version
+        };
+        let number = u32::try_from(min_version).expect("integer overflow for L1 batch number");
+        Some(L1BatchNumber(number))
+    }
+
+    /// Reads entries with the specified keys from the tree after the specified L1 batch. The entries are returned
+    /// in the same order as requested.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tree `version` is missing.
+    pub fn entries(
+        &self,
+        l1_batch_number: L1BatchNumber,
+        keys: &[Key],
+    ) -> Result<Vec<TreeEntry>, NoVersionError> {
+        let version = u64::from(l1_batch_number.0);
+        self.0.entries(version, keys)
+    }
+
     /// Reads entries together with Merkle proofs with the specified keys from the tree. The entries are returned
     /// in the same order as requested.
     ///
@@ -472,4 +510,28 @@ impl ZkSyncTreeReader {
         let version = u64::from(l1_batch_number.0);
         self.0.entries_with_proofs(version, keys)
     }
+
+    /// Returns the root hash and the number of leaves in the tree after the specified L1 batch
+    /// was applied to it. Returns `None` if the corresponding tree version is missing.
+    pub fn root_info(&self, l1_batch_number: L1BatchNumber) -> Option<(ValueHash, u64)> {
+        let version = u64::from(l1_batch_number.0);
+        let leaf_count = self.0.root(version)?.leaf_count();
+        let root_hash = self.0.root_hash(version)?;
+        Some((root_hash, leaf_count))
+    }
+
+    /// Reads all entries with hashed keys in the specified range from the tree after the specified L1 batch.
+    /// Entries are ordered by increasing key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tree `version` is missing.
+    pub fn entries_in_range(
+        &self,
+        l1_batch_number: L1BatchNumber,
+        key_range: ops::RangeInclusive<Key>,
+    ) -> Result<Vec<TreeEntry>, NoVersionError> {
+        let version = u64::from(l1_batch_number.0);
+        self.0.entries_in_range(version, key_range)
+    }
 }
@@ -1,10 +1,12 @@
 //! Getters for the Merkle tree.
 
+use std::ops;
+
 use crate::{
     hasher::HasherWithStats,
     recovery::MerkleTreeRecovery,
     storage::{LoadAncestorsResult, SortedKeys, WorkingPatchSet},
-    types::{Nibbles, Node, TreeEntry, TreeEntryWithProof},
+    types::{Nibbles, Node, NodeKey, Root, TreeEntry, TreeEntryWithProof},
     Database, HashTree, Key, MerkleTree, NoVersionError, PruneDatabase, ValueHash,
 };
 
@@ -55,6 +57,76 @@ impl<DB: Database, H: HashTree> MerkleTree<DB, H> {
             },
         )
     }
+
+    /// Reads all entries with keys in the specified range from the tree. Entries are ordered by increasing key.
+    /// This method can be used to export the tree state (e.g., to create a snapshot that can be used
+    /// for [tree recovery](crate::recovery)) by splitting the key space into ranges.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tree `version` is missing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tree is inconsistent (i.e., some of the referenced nodes are missing).
+    pub fn entries_in_range(
+        &self,
+        version: u64,
+        key_range: ops::RangeInclusive<Key>,
+    ) -> Result<Vec<TreeEntry>, NoVersionError> {
+        let root = self.db.root(version).ok_or_else(|| {
+            let manifest = self.db.manifest().unwrap_or_default();
+            NoVersionError {
+                missing_version: version,
+                version_count: manifest.version_count,
+            }
+        })?;
+        let Root::Filled { node, .. } = root else {
+            return Ok(vec![]);
+        };
+
+        let mut entries = vec![];
+        // Traverse the tree level by level, so that nodes on each level can be loaded in a single batch.
+        let mut level = vec![(Nibbles::EMPTY, node)];
+        while !level.is_empty() {
+            let mut next_level_keys: Vec<(NodeKey, bool)> = vec![];
+            for (nibbles, node) in level {
+                match node {
+                    Node::Leaf(leaf) => {
+                        if key_range.contains(&leaf.full_key) {
+                            entries.push(leaf.into());
+                        }
+                    }
+                    Node::Internal(node) => {
+                        for (nibble, child_ref) in node.children() {
+                            let child_nibbles = nibbles.push(nibble).unwrap();
+                            // ^ `unwrap()` is safe: internal nodes cannot be at the maximum depth
+                            let child_range = child_nibbles.key_range();
+                            let intersects = child_range.start() <= key_range.end()
+                                && key_range.start() <= child_range.end();
+                            if intersects {
+                                let child_key = child_nibbles.with_version(child_ref.version);
+                                next_level_keys.push((child_key, child_ref.is_leaf));
+                            }
+                        }
+                    }
+                }
+            }
+
+            let nodes = self.db.tree_nodes(&next_level_keys);
+            level = next_level_keys
+                .into_iter()
+                .zip(nodes)
+                .map(|((key, _), node)| {
+                    let node = node.unwrap_or_else(|| panic!("Node at {key} is missing"));
+                    (key.nibbles, node)
+                })
+                .collect();
+        }
+
+        entries.sort_unstable_by_key(|entry: &TreeEntry| entry.key);
+        Ok(entries)
+    }
 }
 
 fn load_and_transform_entries<T>(
@@ -149,4 +221,36 @@ mod tests {
         assert!(entries[1].base.is_empty());
         entries[1].verify(&tree.hasher, output.root_hash);
     }
+
+    #[test]
+    fn entries_in_range() {
+        let mut tree = MerkleTree::new(PatchSet::default());
+        let entries: Vec<_> = (1_u64..=100)
+            .map(|i| {
+                TreeEntry::new(
+                    Key::from(i) << 248 | Key::from(i),
+                    i,
+                    ValueHash::from_low_u64_be(i),
+                )
+            })
+            .collect();
+        tree.extend(entries.clone());
+
+        let all_entries = tree.entries_in_range(0, Key::zero()..=Key::MAX).unwrap();
+        assert_eq!(all_entries, entries);
+
+        let range = (Key::from(10) << 248)..=(Key::from(20) << 248);
+        let range_entries = tree.entries_in_range(0, range.clone()).unwrap();
+        let expected_entries: Vec<_> = entries
+            .iter()
+            .filter(|entry| range.contains(&entry.key))
+            .copied()
+            .collect();
+        assert_eq!(range_entries, expected_entries);
+        assert_eq!(range_entries.len(), 10); // entries 10..=19
+
+        let empty_range = Key::from(1)..=Key::from(2);
+        assert!(tree.entries_in_range(0, empty_range).unwrap().is_empty());
+        assert!(tree.entries_in_range(1, Key::zero()..=Key::MAX).is_err());
+    }
 }
@@ -33,6 +33,10 @@ struct HashingMetrics {
     /// Total time spent on hashing while processing a patch.
     #[metrics(buckets = Buckets::LATENCIES, unit = Unit::Seconds)]
     hashing_duration: Histogram<Duration>,
+    /// Time spent on hashing a single changed subtree (with the root at level 4) while processing a patch.
+    /// Subtrees are hashed in parallel, so the maximum duration for a patch bounds its `hashing_duration`.
+    #[metrics(buckets = Buckets::LATENCIES, unit = Unit::Seconds)]
+    subtree_hashing_duration: Histogram<Duration>,
 }
 
 /// Hashing-related statistics reported as metrics for each block of operations.
@@ -41,6 +45,7 @@ struct HashingMetrics {
 pub(crate) struct HashingStats {
     pub hashed_bytes: AtomicU64,
     pub hashing_duration: Duration,
+    pub subtree_hashing_durations: Vec<Duration>,
 }
 
 impl HashingStats {
@@ -57,6 +62,9 @@ impl HashingStats {
         HASHING_METRICS
             .hashing_duration
             .observe(self.hashing_duration);
+        for duration in self.subtree_hashing_durations {
+            HASHING_METRICS.subtree_hashing_duration.observe(duration);
+        }
     }
 }
 
@@ -2,8 +2,8 @@
 
 use std::{
     collections::{hash_map::Entry, HashMap},
-    iter,
-    time::Instant,
+    iter, mem,
+    time::{Duration, Instant},
 };
 
 use rayon::prelude::*;
@@ -345,32 +345,37 @@ impl WorkingPatchSet {
     }
 
     /// Computes hashes and serializes this change set.
+    ///
+    /// Hashes are computed for each of [`SUBTREE_COUNT`] subtrees with roots at level 4 (= 1 nibble)
+    /// in parallel; only the root node is hashed after all subtrees are processed. This avoids synchronizing
+    /// hash computations for all subtrees on each tree level.
     pub(super) fn finalize(
-        self,
+        mut self,
         manifest: Manifest,
         leaf_count: u64,
         operation: Operation,
         hasher: &dyn HashTree,
     ) -> (ValueHash, PatchSet, HashingStats) {
         let mut stats = HashingStats::default();
+        let started_at = Instant::now();
+        stats.subtree_hashing_durations = self.hash_subtrees(hasher, &stats);
+        stats.hashing_duration += started_at.elapsed();
+
         let (root_hash, patch) = self.finalize_inner(
             manifest,
             leaf_count,
             operation,
             |nibble_count, level_changes| {
                 let started_at = Instant::now();
-                let tree_level = nibble_count * 4;
-                // `into_par_iter()` below uses `rayon` to parallelize hash computations.
-                let output = level_changes
-                    .into_par_iter()
-                    .map_init(
-                        || hasher.with_stats(&stats),
-                        |hasher, (nibbles, node)| {
-                            let nibbles = Nibbles::from_parts(nibbles, nibble_count);
-                            (nibbles, Some(node.inner.hash(hasher, tree_level)), node)
-                        },
-                    )
-                    .collect::<Vec<_>>();
+                let mut hasher = hasher.with_stats(&stats);
+                let output = level_changes.into_iter().map(|(nibbles, node)| {
+                    let nibbles = Nibbles::from_parts(nibbles, nibble_count);
+                    // Hashes for non-root nodes are already computed and set in their parents.
+                    let hash = (nibble_count == 0).then(|| node.inner.hash(&mut hasher, 0));
+                    (nibbles, hash, node)
+                });
+                let output: Vec<_> = output.collect();
+                drop(hasher);
                 stats.hashing_duration += started_at.elapsed();
                 output
             },
@@ -379,6 +384,97 @@ impl WorkingPatchSet {
         (root_hash, patch, stats)
     }
 
+    /// Computes hashes for all changed non-root nodes and sets them in the parent nodes.
+    /// Returns hashing durations for changed subtrees.
+    fn hash_subtrees(&mut self, hasher: &dyn HashTree, stats: &HashingStats) -> Vec<Duration> {
+        if self.changes_by_nibble_count.len() <= 1 {
+            return vec![]; // Only the root node (if any) is changed
+        }
+
+        let mut levels = mem::take(&mut self.changes_by_nibble_count).into_iter();
+        let root_level = levels.next().unwrap();
+        let level_count = levels.len();
+        let mut subtrees = [(); SUBTREE_COUNT].map(|()| vec![HashMap::new(); level_count]);
+        for (i, level) in levels.enumerate() {
+            for (nibbles, node) in level {
+                let first_nibble = nibbles[0] >> 4;
+                subtrees[usize::from(first_nibble)][i].insert(nibbles, node);
+            }
+        }
+
+        // `into_par_iter()` below uses `rayon` to hash subtrees in parallel. Hashing within a subtree
+        // is parallelized as well.
+        let subtree_hashes: Vec<_> = subtrees
+            .into_par_iter()
+            .map(|mut subtree| {
+                let started_at = Instant::now();
+                let hash = Self::hash_subtree(&mut subtree, hasher, stats);
+                (subtree, hash, started_at.elapsed())
+            })
+            .collect();
+
+        let mut changes_by_nibble_count = vec![root_level];
+        changes_by_nibble_count.resize_with(level_count + 1, HashMap::new);
+        let mut durations = Vec::with_capacity(SUBTREE_COUNT);
+        for (subtree_idx, (subtree, hash, duration)) in subtree_hashes.into_iter().enumerate() {
+            if let Some(hash) = hash {
+                durations.push(duration);
+                let root = changes_by_nibble_count[0]
+                    .get_mut(Nibbles::EMPTY.bytes())
+                    .expect("changed subtree must have a root node as a parent");
+                let Node::Internal(root) = &mut root.inner else {
+                    unreachable!("Node parent must be an internal node");
+                };
+                let nibble = u8::try_from(subtree_idx).unwrap();
+                root.child_ref_mut(nibble).unwrap().hash = hash;
+            }
+            for (target_level, src_level) in changes_by_nibble_count.iter_mut().skip(1).zip(subtree)
+            {
+                target_level.extend(src_level);
+            }
+        }
+        self.changes_by_nibble_count = changes_by_nibble_count;
+        durations
+    }
+
+    /// Hashes changed nodes in a single subtree bottom-up. `levels[i]` contains nodes with `i + 1` nibbles.
+    /// Returns the hash of the subtree root, or `None` if the subtree is unchanged.
+    fn hash_subtree(
+        levels: &mut [HashMap<NibblesBytes, WorkingNode>],
+        hasher: &dyn HashTree,
+        stats: &HashingStats,
+    ) -> Option<ValueHash> {
+        for idx in (0..levels.len()).rev() {
+            let nibble_count = idx + 1;
+            let tree_level = nibble_count * 4;
+            let hashes: Vec<_> = levels[idx]
+                .par_iter()
+                .map_init(
+                    || hasher.with_stats(stats),
+                    |hasher, (nibbles, node)| (*nibbles, node.inner.hash(hasher, tree_level)),
+                )
+                .collect();
+
+            let Some(parent_level) = idx.checked_sub(1).map(|parent_idx| &mut levels[parent_idx])
+            else {
+                debug_assert!(hashes.len() <= 1, "subtree must have a single root");
+                return hashes.into_iter().next().map(|(_, hash)| hash);
+            };
+            for (nibbles, hash) in hashes {
+                let nibbles = Nibbles::from_parts(nibbles, nibble_count);
+                let (parent_nibbles, last_nibble) = nibbles.split_last().unwrap();
+                let parent = parent_level.get_mut(parent_nibbles.bytes()).unwrap();
+                let Node::Internal(parent) = &mut parent.inner else {
+                    unreachable!("Node parent must be an internal node");
+                };
+                // ^ `unwrap()`s are safe by construction: the parent of any changed node
+                // is an `InternalNode` that must be in the change set as well.
+                parent.child_ref_mut(last_nibble).unwrap().hash = hash;
+            }
+        }
+        None
+    }
+
     fn finalize_inner<I>(
         self,
         manifest: Manifest,
@@ -680,7 +776,8 @@ mod tests {
     use super::*;
     use crate::{
         storage::Storage,
-        types::{Key, LeafNode, TreeEntry},
+        types::{Key, LeafNode, TreeEntry, TreeInstruction},
+        MerkleTree,
     };
 
     fn patch_len(patch: &WorkingPatchSet) -> usize {
@@ -732,6 +829,32 @@ mod tests {
         assert_eq!(patch_len(&merged), all_nibbles.len() + 1);
     }
 
+    #[test]
+    fn hashing_subtrees_in_parallel() {
+        let entries: Vec<_> = (0_u8..=255)
+            .map(|i| {
+                let key = Key::from_little_endian(&[i; 32]);
+                TreeEntry::new(key, u64::from(i) + 1, ValueHash::repeat_byte(i))
+            })
+            .collect();
+
+        let mut db = PatchSet::default();
+        let mut proofs_db = PatchSet::default();
+        for chunk in entries.chunks(40) {
+            let output = MerkleTree::new(&mut db).extend(chunk.to_vec());
+            let instructions = chunk.iter().copied().map(TreeInstruction::Write).collect();
+            let proofs_output = MerkleTree::new(&mut proofs_db).extend_with_proofs(instructions);
+            // Root hashes in the full mode are computed without `WorkingPatchSet::finalize()`.
+            assert_eq!(Some(output.root_hash), proofs_output.root_hash());
+        }
+
+        let tree = MerkleTree::new(&mut db);
+        let latest_version = tree.latest_version().unwrap();
+        for version in 0..=latest_version {
+            tree.verify_consistency(version, true).unwrap();
+        }
+    }
+
     #[test]
     fn loading_greatest_key() {
         // Test empty DB.
@@ -2,7 +2,7 @@
 //! some of these types are declared as public and can be even exported using the `unstable` module.
 //! Still, logically these types are private, so adding them to new public APIs etc. is a logical error.
 
-use std::{fmt, num::NonZeroU64};
+use std::{fmt, num::NonZeroU64, ops};
 
 use crate::{
     hasher::{HashTree, InternalNodeCache},
@@ -228,6 +228,17 @@ impl Nibbles {
         Some(child)
     }
 
+    /// Returns the range of keys having these nibbles as a prefix.
+    pub fn key_range(&self) -> ops::RangeInclusive<Key> {
+        let start = Key::from_big_endian(&self.bytes);
+        let end = if self.nibble_count == KEY_SIZE * 2 {
+            start
+        } else {
+            start | (Key::MAX >> (self.nibble_count * 4))
+        };
+        start..=end
+    }
+
     /// Returns nibbles that form a common prefix between these nibbles and the provided `key`.
     pub fn common_prefix(mut self, other: &Self) -> Self {
         for i in 0..(self.nibble_count + 1) / 2 {
@@ -643,6 +654,24 @@ mod tests {
         assert_eq!(nibbles.common_prefix(&diverging_nibbles), Nibbles::EMPTY);
     }
 
+    #[test]
+    fn nibbles_key_range() {
+        let range = Nibbles::EMPTY.key_range();
+        assert_eq!(range, Key::zero()..=Key::MAX);
+
+        let nibbles = Nibbles::new(&TEST_KEY, 5);
+        let range = nibbles.key_range();
+        assert!(range.contains(&TEST_KEY));
+        assert_eq!(Nibbles::new(range.start(), 5), nibbles);
+        assert_eq!(Nibbles::new(range.end(), 5), nibbles);
+        assert_eq!(*range.start() >> 236, TEST_KEY >> 236);
+        assert_eq!(*range.start() & (Key::MAX >> 20), Key::zero());
+        assert_eq!(*range.end() & (Key::MAX >> 20), Key::MAX >> 20);
+
+        let range = Nibbles::new(&TEST_KEY, 64).key_range();
+        assert_eq!(range, TEST_KEY..=TEST_KEY);
+    }
+
     #[test]
     fn node_key_serialization() {
         let nibbles = Nibbles::new(&TEST_KEY, 6);
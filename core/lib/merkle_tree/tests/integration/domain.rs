@@ -6,7 +6,9 @@ use serde::{Deserialize, Serialize};
 use serde_with::{hex::Hex, serde_as};
 use tempfile::TempDir;
 use zksync_crypto::hasher::blake2::Blake2Hasher;
-use zksync_merkle_tree::{domain::ZkSyncTree, HashTree, TreeEntry, TreeInstruction};
+use zksync_merkle_tree::{
+    domain::ZkSyncTree, HashTree, MerkleTreePruner, RocksDBWrapper, TreeEntry, TreeInstruction,
+};
 use zksync_storage::RocksDB;
 use zksync_system_constants::ACCOUNT_CODE_STORAGE_ADDRESS;
 use zksync_types::{
@@ -111,6 +113,38 @@ fn basic_workflow_multiblock() {
     assert_eq!(tree.next_l1_batch_number(), L1BatchNumber(12));
 }
 
+#[test]
+fn retained_l1_batches_after_pruning() {
+    let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+    let db = RocksDBWrapper::from(RocksDB::new(temp_dir.as_ref()));
+    let mut tree = ZkSyncTree::new_lightweight(db.clone());
+    let reader = tree.reader();
+    assert_eq!(reader.min_l1_batch_number(), None);
+
+    let logs = gen_storage_logs();
+    for block in logs.chunks(9) {
+        tree.process_l1_batch(block);
+        tree.save();
+    }
+    assert_eq!(reader.min_l1_batch_number(), Some(L1BatchNumber(0)));
+
+    let (mut pruner, _handle) = MerkleTreePruner::new(db, 5);
+    pruner.run_once().unwrap();
+    assert_eq!(reader.min_l1_batch_number(), Some(L1BatchNumber(6)));
+
+    let hashed_key = logs[1].key().hashed_key_u256();
+    for l1_batch_number in 6..12 {
+        let entries = reader
+            .entries_with_proofs(L1BatchNumber(l1_batch_number), &[hashed_key])
+            .unwrap();
+        assert!(!entries[0].base.is_empty());
+    }
+    let err = reader
+        .entries_with_proofs(L1BatchNumber(5), &[hashed_key])
+        .unwrap_err();
+    assert!(err.to_string().contains("pruned"), "{err}");
+}
+
 #[test]
 fn filtering_out_no_op_writes() {
     let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
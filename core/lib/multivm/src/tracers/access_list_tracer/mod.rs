@@ -0,0 +1,48 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
+
+use once_cell::sync::OnceCell;
+use zksync_types::{
+    web3::types::{AccessList, AccessListItem},
+    Address, H256,
+};
+
+pub mod vm_boojum_integration;
+pub mod vm_latest;
+pub mod vm_refunds_enhancement;
+pub mod vm_virtual_blocks;
+
+/// Tracer recording storage slots read or written during execution, grouped by the contract address.
+/// Accesses made by system contracts (e.g., to nonces or ETH balances) are recorded as well.
+#[derive(Debug, Clone)]
+pub struct AccessListTracer {
+    accesses: BTreeMap<Address, BTreeSet<H256>>,
+    result: Arc<OnceCell<AccessList>>,
+}
+
+impl AccessListTracer {
+    pub fn new(result: Arc<OnceCell<AccessList>>) -> Self {
+        Self {
+            accesses: BTreeMap::new(),
+            result,
+        }
+    }
+
+    fn record_access(&mut self, address: Address, key: H256) {
+        self.accesses.entry(address).or_default().insert(key);
+    }
+
+    fn store_result(&mut self) {
+        let accesses = std::mem::take(&mut self.accesses);
+        let access_list = accesses
+            .into_iter()
+            .map(|(address, keys)| AccessListItem {
+                address,
+                storage_keys: keys.into_iter().collect(),
+            })
+            .collect();
+        self.result.set(access_list).unwrap();
+    }
+}
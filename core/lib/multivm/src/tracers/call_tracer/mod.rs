@@ -1,9 +1,9 @@
-use std::sync::Arc;
+use std::{mem, sync::Arc};
 
 use once_cell::sync::OnceCell;
 use zksync_types::vm_trace::Call;
 
-use crate::tracers::call_tracer::metrics::CALL_METRICS;
+use crate::tracers::{call_tracer::metrics::CALL_METRICS, trace_limiter::TraceLimiterState};
 
 mod metrics;
 pub mod vm_boojum_integration;
@@ -15,6 +15,7 @@ pub mod vm_virtual_blocks;
 pub struct CallTracer {
     stack: Vec<FarcallAndNearCallCount>,
     result: Arc<OnceCell<Vec<Call>>>,
+    limiter_state: Option<Arc<TraceLimiterState>>,
 
     max_stack_depth: usize,
     max_near_calls: usize,
@@ -39,13 +40,30 @@ impl CallTracer {
         Self {
             stack: vec![],
             result,
+            limiter_state: None,
             max_stack_depth: 0,
             max_near_calls: 0,
         }
     }
 
+    /// Reports the memory used by collected calls to the specified limiter state.
+    pub fn with_limiter_state(mut self, state: Arc<TraceLimiterState>) -> Self {
+        self.limiter_state = Some(state);
+        self
+    }
+
+    fn record_call_memory(&self, call: &Call) {
+        if let Some(state) = &self.limiter_state {
+            let heap_size = call.input.len()
+                + call.output.len()
+                + call.error.as_ref().map_or(0, String::len)
+                + call.revert_reason.as_ref().map_or(0, String::len);
+            state.add_memory_usage(mem::size_of::<Call>() + heap_size);
+        }
+    }
+
     fn extract_result(&mut self) -> Vec<Call> {
-        std::mem::take(&mut self.stack)
+        mem::take(&mut self.stack)
             .into_iter()
             .map(|x| x.farcall)
             .collect()
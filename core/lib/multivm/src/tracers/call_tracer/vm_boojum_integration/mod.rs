@@ -204,6 +204,7 @@ impl CallTracer {
             ret_opcode,
             &mut current_call.farcall,
         );
+        self.record_call_memory(&current_call.farcall);
 
         // If there is a parent call, push the current call to it
         // Otherwise, push the current call to the stack, because it's the top level call
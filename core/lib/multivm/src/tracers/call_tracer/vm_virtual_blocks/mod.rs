@@ -195,6 +195,7 @@ impl CallTracer {
             .saturating_sub(state.vm_local_state.callstack.current.ergs_remaining);
 
         self.save_output_virtual_blocks(state, memory, ret_opcode, &mut current_call.farcall);
+        self.record_call_memory(&current_call.farcall);
 
         // If there is a parent call, push the current call to it
         // Otherwise, push the current call to the stack, because it's the top level call
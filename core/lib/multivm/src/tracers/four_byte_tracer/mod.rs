@@ -0,0 +1,38 @@
+use std::sync::Arc;
+
+use once_cell::sync::OnceCell;
+use zksync_types::{
+    vm_trace::{record_four_byte_call, FourByteTrace},
+    Address,
+};
+
+pub mod vm_boojum_integration;
+pub mod vm_latest;
+pub mod vm_refunds_enhancement;
+pub mod vm_virtual_blocks;
+
+/// Tracer aggregating function selectors and calldata sizes of executed far calls, similar to Geth's `4byteTracer`.
+/// Unlike [`CallTracer`](crate::tracers::CallTracer), it doesn't materialize the call tree.
+#[derive(Debug, Clone)]
+pub struct FourByteTracer {
+    trace: FourByteTrace,
+    result: Arc<OnceCell<FourByteTrace>>,
+}
+
+impl FourByteTracer {
+    pub fn new(result: Arc<OnceCell<FourByteTrace>>) -> Self {
+        Self {
+            trace: FourByteTrace::new(),
+            result,
+        }
+    }
+
+    fn record_call(&mut self, from: Address, to: Address, selector: &[u8], calldata_len: usize) {
+        record_four_byte_call(&mut self.trace, from, to, selector, calldata_len);
+    }
+
+    fn store_result(&mut self) {
+        let result = std::mem::take(&mut self.trace);
+        self.result.set(result).unwrap();
+    }
+}
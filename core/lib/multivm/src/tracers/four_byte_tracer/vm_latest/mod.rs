@@ -0,0 +1,56 @@
+use zk_evm_1_4_0::{
+    tracing::{AfterExecutionData, VmLocalStateData},
+    zkevm_opcode_defs::{FarCallABI, Opcode, CALL_IMPLICIT_CALLDATA_FAT_PTR_REGISTER},
+};
+use zksync_state::{StoragePtr, WriteStorage};
+
+use crate::{
+    interface::{tracer::VmExecutionStopReason, traits::tracers::dyn_tracers::vm_1_4_0::DynTracer},
+    tracers::four_byte_tracer::FourByteTracer,
+    vm_latest::{BootloaderState, HistoryMode, SimpleMemory, VmTracer, ZkSyncVmState},
+};
+
+impl<S, H: HistoryMode> DynTracer<S, SimpleMemory<H>> for FourByteTracer {
+    fn after_execution(
+        &mut self,
+        state: VmLocalStateData<'_>,
+        data: AfterExecutionData,
+        memory: &SimpleMemory<H>,
+        _storage: StoragePtr<S>,
+    ) {
+        if !matches!(data.opcode.variant.opcode, Opcode::FarCall(_)) {
+            return;
+        }
+        let current = state.vm_local_state.callstack.current;
+        if current.code_page.0 == 0 || current.ergs_remaining == 0 {
+            return;
+        }
+
+        let packed_abi =
+            state.vm_local_state.registers[CALL_IMPLICIT_CALLDATA_FAT_PTR_REGISTER as usize];
+        let far_call_abi = FarCallABI::from_u256(packed_abi.value);
+        let calldata_ptr = far_call_abi.memory_quasi_fat_pointer;
+        let selector = memory.read_unaligned_bytes(
+            calldata_ptr.memory_page as usize,
+            calldata_ptr.start as usize,
+            calldata_ptr.length.min(4) as usize,
+        );
+        self.record_call(
+            current.msg_sender,
+            current.this_address,
+            &selector,
+            calldata_ptr.length as usize,
+        );
+    }
+}
+
+impl<S: WriteStorage, H: HistoryMode> VmTracer<S, H> for FourByteTracer {
+    fn after_vm_execution(
+        &mut self,
+        _state: &mut ZkSyncVmState<S, H>,
+        _bootloader_state: &BootloaderState,
+        _stop_reason: VmExecutionStopReason,
+    ) {
+        self.store_result()
+    }
+}
@@ -1,8 +1,16 @@
+pub mod access_list_tracer;
 pub mod call_tracer;
+pub mod four_byte_tracer;
 mod multivm_dispatcher;
 pub mod storage_invocation;
+pub mod struct_log_tracer;
+pub mod trace_limiter;
 pub mod validator;
 
+pub use access_list_tracer::AccessListTracer;
 pub use call_tracer::CallTracer;
+pub use four_byte_tracer::FourByteTracer;
 pub use multivm_dispatcher::TracerDispatcher;
 pub use storage_invocation::StorageInvocations;
+pub use struct_log_tracer::StructLogTracer;
+pub use trace_limiter::{TraceLimiter, TraceLimiterState};
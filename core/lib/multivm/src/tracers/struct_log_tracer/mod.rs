@@ -0,0 +1,120 @@
+use std::{mem, sync::Arc};
+
+use once_cell::sync::OnceCell;
+use zksync_types::vm_trace::{StructLog, StructLogConfig, StructLogLimits, TraceLimitExceeded};
+
+use crate::{
+    interface::{
+        tracer::{TracerExecutionStatus, TracerExecutionStopReason},
+        Halt,
+    },
+    tracers::trace_limiter::TraceLimiterState,
+};
+
+pub mod vm_boojum_integration;
+pub mod vm_latest;
+pub mod vm_refunds_enhancement;
+pub mod vm_virtual_blocks;
+
+/// Offset of the heap page relative to the base memory page of a call frame.
+/// Mirrors `heap_page_from_base` from the VM implementations.
+const HEAP_PAGE_OFFSET: u32 = 2;
+
+/// Size of a stack or memory word in bytes.
+const WORD_SIZE: usize = 32;
+
+/// Tracer recording every executed opcode outside the bootloader, similar to Geth's struct logger.
+///
+/// If any of [`StructLogLimits`] is exceeded, the tracer stops the VM execution and records the exceeded limit
+/// in the provided [`TraceLimiterState`].
+#[derive(Debug, Clone)]
+pub struct StructLogTracer {
+    config: StructLogConfig,
+    limits: StructLogLimits,
+    limiter_state: Arc<TraceLimiterState>,
+    logs: Vec<StructLog>,
+    captured_bytes: usize,
+    /// Index of the log for the step currently being executed, if it's recorded.
+    pending_step: Option<usize>,
+    result: Arc<OnceCell<Vec<StructLog>>>,
+}
+
+impl StructLogTracer {
+    pub fn new(
+        config: StructLogConfig,
+        limits: StructLogLimits,
+        limiter_state: Arc<TraceLimiterState>,
+        result: Arc<OnceCell<Vec<StructLog>>>,
+    ) -> Self {
+        Self {
+            config,
+            limits,
+            limiter_state,
+            logs: vec![],
+            captured_bytes: 0,
+            pending_step: None,
+            result,
+        }
+    }
+
+    fn should_record_step(&self) -> bool {
+        let within_config_limit = self.config.limit == 0 || self.logs.len() < self.config.limit;
+        within_config_limit && self.limiter_state.exceeded_limit().is_none()
+    }
+
+    /// Accounts for a step with the specified number of captured stack and memory words. Returns `false`
+    /// if the step cannot be recorded because it would exceed [`StructLogLimits`].
+    fn reserve_step(&mut self, stack_words: usize, memory_words: usize) -> bool {
+        if self.logs.len() >= self.limits.max_steps {
+            let exceeded = TraceLimitExceeded::Steps(self.limits.max_steps);
+            self.limiter_state.set_exceeded_limit(exceeded);
+            return false;
+        }
+        let step_bytes = mem::size_of::<StructLog>() + (stack_words + memory_words) * WORD_SIZE;
+        let captured_bytes = self.captured_bytes + step_bytes;
+        if captured_bytes > self.limits.max_captured_bytes {
+            let exceeded = TraceLimitExceeded::Memory(self.limits.max_captured_bytes);
+            self.limiter_state.set_exceeded_limit(exceeded);
+            return false;
+        }
+        self.captured_bytes = captured_bytes;
+        // Report captured data so that it counts towards the memory limit of the trace call, if any.
+        self.limiter_state.add_memory_usage(step_bytes);
+        true
+    }
+
+    fn execution_status(&self) -> TracerExecutionStatus {
+        match self.limiter_state.exceeded_limit() {
+            Some(exceeded) => TracerExecutionStatus::Stop(TracerExecutionStopReason::Abort(
+                Halt::TracerCustom(exceeded.to_string()),
+            )),
+            None => TracerExecutionStatus::Continue,
+        }
+    }
+
+    fn push_step(&mut self, log: StructLog) {
+        self.pending_step = Some(self.logs.len());
+        self.logs.push(log);
+    }
+
+    /// Computes the cost of the pending step based on the call stack state after its execution.
+    /// If the step has created a new frame, ergs passed to this frame are not counted as spent.
+    fn finish_step(&mut self, depth: usize, ergs_remaining: u32, parent_ergs_remaining: u32) {
+        let Some(idx) = self.pending_step.take() else {
+            return;
+        };
+        let log = &mut self.logs[idx];
+        let ergs_after = if depth > log.depth {
+            ergs_remaining + parent_ergs_remaining
+        } else {
+            ergs_remaining
+        };
+        log.gas_cost = log.gas.saturating_sub(ergs_after);
+    }
+
+    fn store_result(&mut self) {
+        let result = std::mem::take(&mut self.logs);
+        let cell = self.result.as_ref();
+        cell.set(result).unwrap();
+    }
+}
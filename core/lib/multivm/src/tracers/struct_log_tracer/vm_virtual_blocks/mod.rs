@@ -0,0 +1,96 @@
+use zk_evm_1_3_3::tracing::{AfterExecutionData, BeforeExecutionData, VmLocalStateData};
+use zksync_state::{StoragePtr, WriteStorage};
+use zksync_system_constants::BOOTLOADER_ADDRESS;
+use zksync_types::vm_trace::StructLog;
+use zksync_utils::u256_to_h256;
+
+use crate::{
+    interface::{dyn_tracers::vm_1_3_3::DynTracer, VmExecutionResultAndLogs},
+    tracers::struct_log_tracer::{StructLogTracer, HEAP_PAGE_OFFSET},
+    vm_virtual_blocks::{
+        ExecutionEndTracer, ExecutionProcessing, HistoryMode, SimpleMemory, VmTracer,
+    },
+};
+
+impl<S, H: HistoryMode> DynTracer<S, SimpleMemory<H>> for StructLogTracer {
+    fn before_execution(
+        &mut self,
+        state: VmLocalStateData<'_>,
+        data: BeforeExecutionData,
+        memory: &SimpleMemory<H>,
+        _storage: StoragePtr<S>,
+    ) {
+        let current = state.vm_local_state.callstack.current;
+        if current.this_address == BOOTLOADER_ADDRESS || !self.should_record_step() {
+            return;
+        }
+
+        let stack = (!self.config.disable_stack).then(|| {
+            state
+                .vm_local_state
+                .registers
+                .iter()
+                .map(|register| register.value)
+                .collect()
+        });
+        let heap_words = (current.heap_bound + 31) / 32;
+        let memory_words = if self.config.enable_memory {
+            heap_words as usize
+        } else {
+            0
+        };
+        if !self.reserve_step(stack.as_ref().map_or(0, Vec::len), memory_words) {
+            return;
+        }
+        let memory = self.config.enable_memory.then(|| {
+            let heap_page = current.base_memory_page.0 + HEAP_PAGE_OFFSET;
+            memory
+                .dump_page_content_as_u256_words(heap_page, 0..heap_words)
+                .into_iter()
+                .map(u256_to_h256)
+                .collect()
+        });
+        self.push_step(StructLog {
+            address: current.this_address,
+            pc: current.pc,
+            op: format!("{:?}", data.opcode.variant.opcode),
+            gas: current.ergs_remaining,
+            gas_cost: 0,
+            depth: state.vm_local_state.callstack.inner.len(),
+            stack,
+            memory,
+        });
+    }
+
+    fn after_execution(
+        &mut self,
+        state: VmLocalStateData<'_>,
+        _data: AfterExecutionData,
+        _memory: &SimpleMemory<H>,
+        _storage: StoragePtr<S>,
+    ) {
+        let callstack = &state.vm_local_state.callstack;
+        self.finish_step(
+            callstack.inner.len(),
+            callstack.current.ergs_remaining,
+            callstack
+                .inner
+                .last()
+                .map_or(0, |frame| frame.ergs_remaining),
+        );
+    }
+}
+
+impl<H: HistoryMode> ExecutionEndTracer<H> for StructLogTracer {
+    fn should_stop_execution(&self) -> bool {
+        self.limiter_state.exceeded_limit().is_some()
+    }
+}
+
+impl<S: WriteStorage, H: HistoryMode> ExecutionProcessing<S, H> for StructLogTracer {}
+
+impl<S: WriteStorage, H: HistoryMode> VmTracer<S, H> for StructLogTracer {
+    fn save_results(&mut self, _result: &mut VmExecutionResultAndLogs) {
+        self.store_result()
+    }
+}
@@ -0,0 +1,91 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+use once_cell::sync::OnceCell;
+use zksync_types::vm_trace::{TraceLimitExceeded, TraceLimits};
+
+use crate::interface::{
+    tracer::{TracerExecutionStatus, TracerExecutionStopReason},
+    Halt,
+};
+
+pub mod vm_boojum_integration;
+pub mod vm_latest;
+pub mod vm_refunds_enhancement;
+pub mod vm_virtual_blocks;
+
+/// State shared between [`TraceLimiter`] and the tracers collecting traces.
+#[derive(Debug, Default)]
+pub struct TraceLimiterState {
+    memory_usage: AtomicUsize,
+    exceeded_limit: OnceCell<TraceLimitExceeded>,
+}
+
+impl TraceLimiterState {
+    /// Records memory allocated by a tracer for the collected traces.
+    pub fn add_memory_usage(&self, bytes: usize) {
+        self.memory_usage.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Returns the limit that caused the VM execution to stop, if any.
+    pub fn exceeded_limit(&self) -> Option<TraceLimitExceeded> {
+        self.exceeded_limit.get().copied()
+    }
+
+    /// Records a limit exceeded by a tracer. If a limit is already recorded, it is retained.
+    pub fn set_exceeded_limit(&self, exceeded: TraceLimitExceeded) {
+        self.exceeded_limit.get_or_init(|| exceeded);
+    }
+}
+
+/// Tracer stopping the VM execution once any of the [`TraceLimits`] is exceeded.
+#[derive(Debug, Clone)]
+pub struct TraceLimiter {
+    limits: TraceLimits,
+    deadline: Option<Instant>,
+    state: Arc<TraceLimiterState>,
+}
+
+impl TraceLimiter {
+    /// Creates a tracer. The timeout is measured from the moment of creation.
+    pub fn new(limits: TraceLimits, state: Arc<TraceLimiterState>) -> Self {
+        Self {
+            limits,
+            deadline: limits.timeout.map(|timeout| Instant::now() + timeout),
+            state,
+        }
+    }
+
+    fn check_limits(&self) -> Option<TraceLimitExceeded> {
+        if let Some(exceeded) = self.state.exceeded_limit() {
+            return Some(exceeded);
+        }
+
+        let exceeded = if let (Some(timeout), Some(deadline)) = (self.limits.timeout, self.deadline)
+        {
+            (Instant::now() >= deadline).then_some(TraceLimitExceeded::Timeout(timeout))
+        } else {
+            None
+        };
+        let exceeded = exceeded.or_else(|| {
+            let limit = self.limits.memory_limit?;
+            let usage = self.state.memory_usage.load(Ordering::Relaxed);
+            (usage > limit).then_some(TraceLimitExceeded::Memory(limit))
+        })?;
+        Some(*self.state.exceeded_limit.get_or_init(|| exceeded))
+    }
+
+    fn execution_status(&self) -> TracerExecutionStatus {
+        match self.check_limits() {
+            Some(exceeded) => TracerExecutionStatus::Stop(TracerExecutionStopReason::Abort(
+                Halt::TracerCustom(exceeded.to_string()),
+            )),
+            None => TracerExecutionStatus::Continue,
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use zksync_state::WriteStorage;
+
+use crate::{
+    interface::{tracer::TracerExecutionStatus, traits::tracers::dyn_tracers::vm_1_3_3::DynTracer},
+    tracers::trace_limiter::TraceLimiter,
+    vm_refunds_enhancement::{BootloaderState, HistoryMode, SimpleMemory, VmTracer, ZkSyncVmState},
+};
+
+impl<S, H: HistoryMode> DynTracer<S, SimpleMemory<H>> for TraceLimiter {}
+
+impl<S: WriteStorage, H: HistoryMode> VmTracer<S, H> for TraceLimiter {
+    fn finish_cycle(
+        &mut self,
+        _state: &mut ZkSyncVmState<S, H>,
+        _bootloader_state: &mut BootloaderState,
+    ) -> TracerExecutionStatus {
+        self.execution_status()
+    }
+}
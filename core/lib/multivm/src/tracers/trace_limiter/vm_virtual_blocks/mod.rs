@@ -0,0 +1,21 @@
+use zksync_state::WriteStorage;
+
+use crate::{
+    interface::dyn_tracers::vm_1_3_3::DynTracer,
+    tracers::trace_limiter::TraceLimiter,
+    vm_virtual_blocks::{
+        ExecutionEndTracer, ExecutionProcessing, HistoryMode, SimpleMemory, VmTracer,
+    },
+};
+
+impl<H: HistoryMode> ExecutionEndTracer<H> for TraceLimiter {
+    fn should_stop_execution(&self) -> bool {
+        self.check_limits().is_some()
+    }
+}
+
+impl<S: WriteStorage, H: HistoryMode> DynTracer<S, SimpleMemory<H>> for TraceLimiter {}
+
+impl<S: WriteStorage, H: HistoryMode> ExecutionProcessing<S, H> for TraceLimiter {}
+
+impl<S: WriteStorage, H: HistoryMode> VmTracer<S, H> for TraceLimiter {}
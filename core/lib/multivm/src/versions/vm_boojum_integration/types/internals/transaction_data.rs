@@ -283,6 +283,7 @@ impl TryInto<L2Tx> for TransactionData {
                 paymaster: self.paymaster,
                 paymaster_input: self.paymaster_input,
             },
+            conditions: None,
         };
         let factory_deps = (!self.factory_deps.is_empty()).then_some(self.factory_deps);
         let execute = Execute {
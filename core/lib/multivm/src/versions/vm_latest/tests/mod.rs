@@ -16,6 +16,7 @@ mod refunds;
 mod require_eip712;
 mod rollbacks;
 mod simple_execution;
+mod struct_log_tracer;
 mod tester;
 mod tracing_execution_error;
 mod upgrade;
@@ -0,0 +1,106 @@
+use std::sync::Arc;
+
+use once_cell::sync::OnceCell;
+use zksync_types::{
+    vm_trace::{StructLogConfig, StructLogLimits, TraceLimitExceeded},
+    Address, Execute,
+};
+
+use crate::{
+    interface::{TxExecutionMode, VmExecutionMode, VmInterface},
+    tracers::{StructLogTracer, TraceLimiterState},
+    vm_latest::{
+        constants::BLOCK_GAS_LIMIT,
+        tests::{tester::VmTesterBuilder, utils::read_test_contract},
+        HistoryEnabled, ToTracerPointer,
+    },
+};
+
+fn trace_test_contract(
+    config: StructLogConfig,
+    limits: StructLogLimits,
+) -> (bool, usize, Option<TraceLimitExceeded>) {
+    let contract = read_test_contract();
+    let address = Address::random();
+    let mut vm = VmTesterBuilder::new(HistoryEnabled)
+        .with_empty_in_memory_storage()
+        .with_random_rich_accounts(1)
+        .with_deployer()
+        .with_gas_limit(BLOCK_GAS_LIMIT)
+        .with_execution_mode(TxExecutionMode::VerifyExecute)
+        .with_custom_contracts(vec![(contract, address, true)])
+        .build();
+
+    let increment_by_6_calldata =
+        "7cf5dab00000000000000000000000000000000000000000000000000000000000000006";
+    let account = &mut vm.rich_accounts[0];
+    let tx = account.get_l2_tx_for_execute(
+        Execute {
+            contract_address: address,
+            calldata: hex::decode(increment_by_6_calldata).unwrap(),
+            value: Default::default(),
+            factory_deps: None,
+        },
+        None,
+    );
+
+    let result = Arc::new(OnceCell::new());
+    let limiter_state = Arc::new(TraceLimiterState::default());
+    let tracer = StructLogTracer::new(config, limits, limiter_state.clone(), result.clone());
+    vm.vm.push_transaction(tx);
+    let res = vm
+        .vm
+        .inspect(tracer.into_tracer_pointer().into(), VmExecutionMode::OneTx);
+    let steps = result.get().unwrap().len();
+    (
+        res.result.is_failed(),
+        steps,
+        limiter_state.exceeded_limit(),
+    )
+}
+
+#[test]
+fn struct_logs_are_recorded_within_limits() {
+    let (failed, steps, exceeded) =
+        trace_test_contract(StructLogConfig::default(), StructLogLimits::default());
+    assert!(!failed);
+    assert!(steps > 0);
+    assert_eq!(exceeded, None);
+
+    // The config limit truncates logs without an error.
+    let config = StructLogConfig {
+        limit: 5,
+        ..StructLogConfig::default()
+    };
+    let (failed, steps, exceeded) = trace_test_contract(config, StructLogLimits::default());
+    assert!(!failed);
+    assert_eq!(steps, 5);
+    assert_eq!(exceeded, None);
+}
+
+#[test]
+fn struct_log_step_limit_aborts_execution() {
+    let limits = StructLogLimits {
+        max_steps: 10,
+        ..StructLogLimits::default()
+    };
+    let (failed, steps, exceeded) = trace_test_contract(StructLogConfig::default(), limits);
+    assert!(failed);
+    assert_eq!(steps, 10);
+    assert_eq!(exceeded, Some(TraceLimitExceeded::Steps(10)));
+}
+
+#[test]
+fn struct_log_size_limit_aborts_execution() {
+    let config = StructLogConfig {
+        enable_memory: true,
+        ..StructLogConfig::default()
+    };
+    let limits = StructLogLimits {
+        max_captured_bytes: 4_096,
+        ..StructLogLimits::default()
+    };
+    let (failed, _, exceeded) = trace_test_contract(config, limits);
+    assert!(failed);
+    assert_eq!(exceeded, Some(TraceLimitExceeded::Memory(4_096)));
+}
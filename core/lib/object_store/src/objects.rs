@@ -10,6 +10,7 @@ use zksync_types::{
     aggregated_operations::L1BatchProofForL1,
     proofs::{AggregationRound, PrepareBasicCircuitsJob},
     snapshots::{
+        MerkleTreeSnapshotChunk, MerkleTreeSnapshotChunkKey, MerkleTreeSnapshotHeader,
         SnapshotFactoryDependencies, SnapshotStorageLogsChunk, SnapshotStorageLogsStorageKey,
     },
     storage::witness_block_state::WitnessBlockState,
@@ -131,6 +132,37 @@ impl StoredObject for SnapshotStorageLogsChunk {
     }
 }
 
+impl StoredObject for MerkleTreeSnapshotHeader {
+    const BUCKET: Bucket = Bucket::MerkleTreeSnapshot;
+    type Key<'a> = L1BatchNumber;
+
+    fn encode_key(key: Self::Key<'_>) -> String {
+        format!("merkle_tree_snapshot_l1_batch_{key}_header.json")
+    }
+
+    fn serialize(&self) -> Result<Vec<u8>, BoxedError> {
+        serde_json::to_vec(self).map_err(From::from)
+    }
+
+    fn deserialize(bytes: Vec<u8>) -> Result<Self, BoxedError> {
+        serde_json::from_slice(&bytes).map_err(From::from)
+    }
+}
+
+impl StoredObject for MerkleTreeSnapshotChunk {
+    const BUCKET: Bucket = Bucket::MerkleTreeSnapshot;
+    type Key<'a> = MerkleTreeSnapshotChunkKey;
+
+    fn encode_key(key: Self::Key<'_>) -> String {
+        format!(
+            "merkle_tree_snapshot_l1_batch_{}_part_{:0>4}.bin",
+            key.l1_batch_number, key.chunk_id
+        )
+    }
+
+    serialize_using_bincode!();
+}
+
 impl StoredObject for WitnessBlockState {
     const BUCKET: Bucket = Bucket::WitnessInput;
     type Key<'a> = L1BatchNumber;
@@ -319,8 +351,8 @@ impl dyn ObjectStore + '_ {
 #[cfg(test)]
 mod tests {
     use zksync_types::{
-        snapshots::{SnapshotFactoryDependency, SnapshotStorageLog},
-        AccountTreeId, Bytes, StorageKey, H160, H256,
+        snapshots::{MerkleTreeSnapshotEntry, SnapshotFactoryDependency, SnapshotStorageLog},
+        AccountTreeId, Bytes, StorageKey, H160, H256, U256,
     };
 
     use super::*;
@@ -400,4 +432,51 @@ mod tests {
         let reconstructed_factory_deps = store.get(key).await.unwrap();
         assert_eq!(factory_deps, reconstructed_factory_deps);
     }
+
+    #[tokio::test]
+    async fn test_merkle_tree_snapshot_can_be_serialized_and_deserialized() {
+        let store = ObjectStoreFactory::mock().create_store().await;
+        let l1_batch_number = L1BatchNumber(123);
+        let header = MerkleTreeSnapshotHeader {
+            l1_batch_number,
+            root_hash: H256::random(),
+            leaf_count: 2,
+            chunk_count: 1,
+        };
+        let chunk = MerkleTreeSnapshotChunk {
+            entries: vec![
+                MerkleTreeSnapshotEntry {
+                    hashed_key: U256::from(1),
+                    value: H256::random(),
+                    leaf_index: 2,
+                },
+                MerkleTreeSnapshotEntry {
+                    hashed_key: U256::MAX,
+                    value: H256::random(),
+                    leaf_index: 1,
+                },
+            ],
+        };
+        let chunk_key = MerkleTreeSnapshotChunkKey {
+            l1_batch_number,
+            chunk_id: 0,
+        };
+        store.put(chunk_key, &chunk).await.unwrap();
+        store.put(l1_batch_number, &header).await.unwrap();
+
+        assert_eq!(
+            store
+                .get::<MerkleTreeSnapshotChunk>(chunk_key)
+                .await
+                .unwrap(),
+            chunk
+        );
+        assert_eq!(
+            store
+                .get::<MerkleTreeSnapshotHeader>(l1_batch_number)
+                .await
+                .unwrap(),
+            header
+        );
+    }
 }
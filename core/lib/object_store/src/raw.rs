@@ -20,6 +20,8 @@ pub enum Bucket {
     SchedulerWitnessJobsFri,
     ProofsFri,
     StorageSnapshot,
+    DataAvailability,
+    MerkleTreeSnapshot,
 }
 
 impl Bucket {
@@ -36,6 +38,8 @@ impl Bucket {
             Self::SchedulerWitnessJobsFri => "scheduler_witness_jobs_fri",
             Self::ProofsFri => "proofs_fri",
             Self::StorageSnapshot => "storage_logs_snapshots",
+            Self::DataAvailability => "data_availability",
+            Self::MerkleTreeSnapshot => "merkle_tree_snapshots",
         }
     }
 }
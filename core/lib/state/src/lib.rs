@@ -23,6 +23,7 @@ mod postgres;
 mod rocksdb;
 mod shadow_storage;
 mod storage_view;
+mod storage_with_overrides;
 #[cfg(test)]
 mod test_utils;
 mod witness;
@@ -33,6 +34,7 @@ pub use self::{
     rocksdb::RocksdbStorage,
     shadow_storage::ShadowStorage,
     storage_view::{StorageView, StorageViewMetrics},
+    storage_with_overrides::StorageWithOverrides,
     witness::WitnessStorage,
 };
 
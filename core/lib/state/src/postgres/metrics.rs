@@ -40,6 +40,7 @@ pub(super) enum Method {
     ReadValue,
     IsWriteInitial,
     LoadFactoryDep,
+    PrefetchValues,
 }
 
 #[derive(Debug, Metrics)]
@@ -1,4 +1,5 @@
 use std::{
+    collections::HashMap,
     mem,
     sync::{Arc, RwLock},
 };
@@ -338,6 +339,8 @@ pub struct PostgresStorage<'a> {
     pending_l1_batch_number: L1BatchNumber,
     consider_new_l1_batch: bool,
     caches: Option<PostgresStorageCaches>,
+    /// Values loaded by [`Self::prefetch_values()`], keyed by the hashed storage key.
+    prefetched_values: HashMap<H256, StorageValue>,
 }
 
 impl<'a> PostgresStorage<'a> {
@@ -366,6 +369,7 @@ impl<'a> PostgresStorage<'a> {
             pending_l1_batch_number: resolved.pending_l1_batch,
             consider_new_l1_batch,
             caches: None,
+            prefetched_values: HashMap::new(),
         }
     }
 
@@ -393,13 +397,62 @@ impl<'a> PostgresStorage<'a> {
     fn values_cache(&self) -> Option<&ValuesCache> {
         Some(&self.caches.as_ref()?.values.as_ref()?.cache)
     }
+
+    /// Loads values for the specified storage keys using a single Postgres query, so that subsequent
+    /// [`ReadStorage::read_value()`] calls for these keys don't hit Postgres. Keys that are already cached
+    /// are skipped. Loaded values are also put into the values cache if it's configured and holds values
+    /// for the storage miniblock.
+    ///
+    /// # Panics
+    ///
+    /// Panics on Postgres errors.
+    pub fn prefetch_values(&mut self, keys: &[StorageKey]) {
+        let values_cache = self.values_cache();
+        let keys_to_load: Vec<_> = keys
+            .iter()
+            .filter(|key| {
+                !self.prefetched_values.contains_key(&key.hashed_key())
+                    && values_cache.map_or(true, |cache| {
+                        cache.get(self.miniblock_number, key).is_none()
+                    })
+            })
+            .collect();
+        if keys_to_load.is_empty() {
+            return;
+        }
+
+        let latency = STORAGE_METRICS.storage[&Method::PrefetchValues].start();
+        let hashed_keys: Vec<_> = keys_to_load.iter().map(|key| key.hashed_key()).collect();
+        let mut dal = self.connection.storage_logs_dal();
+        let values = self
+            .rt_handle
+            .block_on(dal.get_storage_values(&hashed_keys, self.miniblock_number));
+        for key in keys_to_load {
+            let hashed_key = key.hashed_key();
+            let value = values.get(&hashed_key).copied().flatten();
+            let value = value.unwrap_or_else(H256::zero);
+            if let Some(cache) = self.values_cache() {
+                cache.insert(self.miniblock_number, *key, value);
+            }
+            self.prefetched_values.insert(hashed_key, value);
+        }
+        let elapsed = latency.observe();
+        tracing::debug!(
+            "Prefetched {} storage values for miniblock {}; took {elapsed:?}",
+            hashed_keys.len(),
+            self.miniblock_number
+        );
+    }
 }
 
 impl ReadStorage for PostgresStorage<'_> {
     fn read_value(&mut self, &key: &StorageKey) -> StorageValue {
         let latency = STORAGE_METRICS.storage[&Method::ReadValue].start();
-        let values_cache = self.values_cache();
-        let cached_value = values_cache.and_then(|cache| cache.get(self.miniblock_number, &key));
+        let prefetched_value = self.prefetched_values.get(&key.hashed_key()).copied();
+        let cached_value = prefetched_value.or_else(|| {
+            let values_cache = self.values_cache()?;
+            values_cache.get(self.miniblock_number, &key)
+        });
 
         let value = cached_value.unwrap_or_else(|| {
             let mut dal = self.connection.storage_web3_dal();
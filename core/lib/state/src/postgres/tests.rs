@@ -594,3 +594,52 @@ async fn mini_fuzz_values_cache() {
         .await
         .unwrap();
 }
+
+fn test_prefetching_values(pool: &ConnectionPool, rt_handle: Handle) {
+    let mut caches = PostgresStorageCaches::new(1_024, 1_024);
+    let _ = caches.configure_storage_values_cache(1_024 * 1_024, pool.clone(), rt_handle.clone());
+    let values_cache = caches.values.as_ref().unwrap().cache.clone();
+
+    let mut connection = rt_handle.block_on(pool.access_storage()).unwrap();
+    rt_handle.block_on(prepare_postgres(&mut connection));
+    let existing_logs = gen_storage_logs(0..20);
+    let non_existing_key = gen_storage_logs(100..120)[0].key;
+    let mut keys: Vec<_> = existing_logs[..5].iter().map(|log| log.key).collect();
+    keys.push(non_existing_key);
+
+    let mut storage =
+        PostgresStorage::new(rt_handle.clone(), connection, MiniblockNumber(0), false)
+            .with_caches(caches.clone());
+    storage.prefetch_values(&keys);
+    assert_eq!(storage.prefetched_values.len(), keys.len());
+
+    let mut expected_entries: Vec<_> = existing_logs[..5]
+        .iter()
+        .map(|log| (log.key, Some(log.value)))
+        .collect();
+    expected_entries.push((non_existing_key, Some(H256::zero())));
+    values_cache
+        .assertions(MiniblockNumber(0))
+        .assert_entries(&expected_entries);
+
+    // Prefetched values should be used by `read_value()`.
+    for (key, value) in &expected_entries {
+        assert_eq!(storage.read_value(key), value.unwrap());
+    }
+
+    // Cached keys should not be prefetched.
+    let mut storage =
+        PostgresStorage::new(rt_handle, storage.connection, MiniblockNumber(0), false)
+            .with_caches(caches);
+    storage.prefetch_values(&keys);
+    assert!(storage.prefetched_values.is_empty());
+}
+
+#[tokio::test]
+async fn prefetching_values() {
+    let pool = ConnectionPool::test_pool().await;
+    let handle = Handle::current();
+    tokio::task::spawn_blocking(move || test_prefetching_values(&pool, handle))
+        .await
+        .unwrap();
+}
@@ -23,7 +23,7 @@ use std::{collections::HashMap, convert::TryInto, mem, path::Path, time::Instant
 
 use itertools::{Either, Itertools};
 use zksync_dal::StorageProcessor;
-use zksync_storage::{db::NamedColumnFamily, RocksDB};
+use zksync_storage::{db::NamedColumnFamily, RocksDB, RocksDBOptions};
 use zksync_types::{L1BatchNumber, StorageKey, StorageValue, H256, U256};
 use zksync_utils::{h256_to_u256, u256_to_h256};
 
@@ -114,7 +114,12 @@ impl RocksdbStorage {
 
     /// Creates a new storage with the provided RocksDB `path`.
     pub fn new(path: &Path) -> Self {
-        let db = RocksDB::new(path);
+        Self::with_options(path, RocksDBOptions::default())
+    }
+
+    /// Creates a new storage with the provided RocksDB `path` and tuning `options`.
+    pub fn with_options(path: &Path, options: RocksDBOptions) -> Self {
+        let db = RocksDB::with_options(path, options);
         Self {
             db,
             pending_patch: InMemoryStorage::default(),
@@ -122,6 +127,16 @@ impl RocksdbStorage {
         }
     }
 
+    /// Creates another storage reading from the same RocksDB instance as this one. Since RocksDB is shared,
+    /// the returned storage must only be used for reads, e.g., to execute transactions on other threads.
+    pub fn clone_for_reads(&self) -> Self {
+        Self {
+            db: self.db.clone(),
+            pending_patch: self.pending_patch.clone(),
+            enum_index_migration_chunk_size: self.enum_index_migration_chunk_size,
+        }
+    }
+
     /// Enables enum indices migration.
     pub fn enable_enum_index_migration(&mut self, chunk_size: usize) {
         self.enum_index_migration_chunk_size = chunk_size;
@@ -129,6 +129,18 @@ impl<S: ReadStorage + fmt::Debug> StorageView<S> {
         }
     }
 
+    /// Caches values read from the underlying storage elsewhere (e.g., by transactions executed speculatively
+    /// on other threads), so that they are not read from the underlying storage again. Values must not be affected
+    /// by writes made via this view; already cached values are not overwritten.
+    pub fn cache_read_values(
+        &mut self,
+        values: impl IntoIterator<Item = (StorageKey, StorageValue)>,
+    ) {
+        for (key, value) in values {
+            self.read_storage_keys.entry(key).or_insert(value);
+        }
+    }
+
     /// Make a Rc RefCell ptr to the storage
     pub fn to_rc_ptr(self) -> Rc<RefCell<Self>> {
         Rc::new(RefCell::new(self))
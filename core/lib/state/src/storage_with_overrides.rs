@@ -0,0 +1,111 @@
+use std::collections::{HashMap, HashSet};
+
+use zksync_types::{AccountTreeId, StorageKey, StorageValue, H256};
+
+use crate::ReadStorage;
+
+/// [`ReadStorage`] implementation that allows to override storage values and factory dependencies
+/// on top of the underlying storage. Used to execute transactions in "what-if" scenarios, e.g. with
+/// the state override set supported by `eth_call`.
+#[derive(Debug)]
+pub struct StorageWithOverrides<S> {
+    storage_handle: S,
+    overridden_values: HashMap<StorageKey, StorageValue>,
+    overridden_factory_deps: HashMap<H256, Vec<u8>>,
+    /// Accounts with fully replaced storage. Slots of these accounts that are not present in `overridden_values`
+    /// are considered to be empty.
+    replaced_accounts: HashSet<AccountTreeId>,
+}
+
+impl<S: ReadStorage> StorageWithOverrides<S> {
+    /// Creates a new storage without any overrides.
+    pub fn new(storage_handle: S) -> Self {
+        Self {
+            storage_handle,
+            overridden_values: HashMap::new(),
+            overridden_factory_deps: HashMap::new(),
+            replaced_accounts: HashSet::new(),
+        }
+    }
+
+    /// Overrides the value of a single storage slot.
+    pub fn set_value(&mut self, key: StorageKey, value: StorageValue) {
+        self.overridden_values.insert(key, value);
+    }
+
+    /// Adds a factory dependency, overriding the one present in the underlying storage if any.
+    pub fn store_factory_dep(&mut self, hash: H256, bytecode: Vec<u8>) {
+        self.overridden_factory_deps.insert(hash, bytecode);
+    }
+
+    /// Clears the entire storage of the specified account. Values set via [`Self::set_value()`]
+    /// are still returned for the account slots.
+    pub fn clear_account_storage(&mut self, account: AccountTreeId) {
+        self.replaced_accounts.insert(account);
+    }
+}
+
+impl<S: ReadStorage> ReadStorage for StorageWithOverrides<S> {
+    fn read_value(&mut self, key: &StorageKey) -> StorageValue {
+        if let Some(value) = self.overridden_values.get(key) {
+            return *value;
+        }
+        if self.replaced_accounts.contains(key.account()) {
+            return StorageValue::zero();
+        }
+        self.storage_handle.read_value(key)
+    }
+
+    fn is_write_initial(&mut self, key: &StorageKey) -> bool {
+        self.storage_handle.is_write_initial(key)
+    }
+
+    fn load_factory_dep(&mut self, hash: H256) -> Option<Vec<u8>> {
+        self.overridden_factory_deps
+            .get(&hash)
+            .cloned()
+            .or_else(|| self.storage_handle.load_factory_dep(hash))
+    }
+
+    fn get_enumeration_index(&mut self, key: &StorageKey) -> Option<u64> {
+        self.storage_handle.get_enumeration_index(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_types::Address;
+
+    use super::*;
+    use crate::InMemoryStorage;
+
+    #[test]
+    fn overrides_are_applied_on_top_of_storage() {
+        let account = AccountTreeId::new(Address::repeat_byte(1));
+        let other_account = AccountTreeId::new(Address::repeat_byte(2));
+        let slot = StorageKey::new(account, H256::zero());
+        let other_slot = StorageKey::new(account, H256::repeat_byte(1));
+        let other_account_slot = StorageKey::new(other_account, H256::zero());
+
+        let mut raw_storage = InMemoryStorage::default();
+        raw_storage.set_value(slot, H256::repeat_byte(0xaa));
+        raw_storage.set_value(other_slot, H256::repeat_byte(0xbb));
+        raw_storage.set_value(other_account_slot, H256::repeat_byte(0xcc));
+        let mut storage = StorageWithOverrides::new(raw_storage);
+        assert_eq!(storage.read_value(&other_slot), H256::repeat_byte(0xbb));
+
+        storage.clear_account_storage(account);
+        storage.set_value(slot, H256::repeat_byte(0xdd));
+        assert_eq!(storage.read_value(&slot), H256::repeat_byte(0xdd));
+        assert_eq!(storage.read_value(&other_slot), H256::zero());
+        assert_eq!(
+            storage.read_value(&other_account_slot),
+            H256::repeat_byte(0xcc)
+        );
+
+        let bytecode_hash = H256::repeat_byte(0xee);
+        assert_eq!(storage.load_factory_dep(bytecode_hash), None);
+        storage.store_factory_dep(bytecode_hash, vec![1; 32]);
+        assert_eq!(storage.load_factory_dep(bytecode_hash), Some(vec![1; 32]));
+    }
+}
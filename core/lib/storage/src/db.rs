@@ -11,8 +11,9 @@ use std::{
 };
 
 use rocksdb::{
-    properties, BlockBasedOptions, Cache, ColumnFamily, ColumnFamilyDescriptor, DBPinnableSlice,
-    Direction, IteratorMode, Options, PrefixRange, ReadOptions, WriteOptions, DB,
+    properties, BlockBasedOptions, Cache, ColumnFamily, ColumnFamilyDescriptor, DBCompactionStyle,
+    DBPinnableSlice, Direction, Env, IteratorMode, Options, PrefixRange, ReadOptions, WriteOptions,
+    DB,
 };
 
 use crate::metrics::{RocksdbLabels, RocksdbSizeMetrics, METRICS};
@@ -156,6 +157,32 @@ impl RocksDBInner {
             if let Some(size) = index_and_filters_size {
                 metrics.index_and_filters_size[&labels].set(size);
             }
+            let block_cache_pinned_size =
+                self.int_property(cf, properties::BLOCK_CACHE_PINNED_USAGE);
+            if let Some(size) = block_cache_pinned_size {
+                metrics.block_cache_pinned_size[&labels].set(size);
+            }
+            let block_cache_capacity = self.int_property(cf, properties::BLOCK_CACHE_CAPACITY);
+            if let Some(capacity) = block_cache_capacity {
+                metrics.block_cache_capacity[&labels].set(capacity);
+            }
+            let active_mem_table_size =
+                self.int_property(cf, properties::CUR_SIZE_ACTIVE_MEM_TABLE);
+            if let Some(size) = active_mem_table_size {
+                metrics.active_mem_table_size[&labels].set(size);
+            }
+            let estimated_keys = self.int_property(cf, properties::ESTIMATE_NUM_KEYS);
+            if let Some(count) = estimated_keys {
+                metrics.estimated_keys[&labels].set(count);
+            }
+            let delayed_write_rate = self.int_property(cf, properties::ACTUAL_DELAYED_WRITE_RATE);
+            if let Some(rate) = delayed_write_rate {
+                metrics.delayed_write_rate[&labels].set(rate);
+            }
+            let background_errors = self.int_property(cf, properties::BACKGROUND_ERRORS);
+            if let Some(count) = background_errors {
+                metrics.background_errors[&labels].set(count);
+            }
         }
     }
 
@@ -260,6 +287,26 @@ impl StalledWritesRetries {
     }
 }
 
+/// Compaction style used by a [`RocksDB`] instance.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CompactionStyle {
+    /// Leveled compaction (the RocksDB default). Provides lower space amplification and is generally
+    /// recommended for large DBs.
+    #[default]
+    Level,
+    /// Universal (tiered) compaction. Lowers write amplification at the cost of space amplification.
+    Universal,
+}
+
+impl From<CompactionStyle> for DBCompactionStyle {
+    fn from(style: CompactionStyle) -> Self {
+        match style {
+            CompactionStyle::Level => Self::Level,
+            CompactionStyle::Universal => Self::Universal,
+        }
+    }
+}
+
 /// [`RocksDB`] options.
 #[derive(Debug, Clone, Copy)]
 pub struct RocksDBOptions {
@@ -274,6 +321,22 @@ pub struct RocksDBOptions {
     /// Timeout to wait for the database to run compaction on stalled writes during startup or
     /// when the corresponding RocksDB error is encountered.
     pub stalled_writes_retries: StalledWritesRetries,
+    /// Compaction style for all column families.
+    pub compaction_style: CompactionStyle,
+    /// Byte size of a single memtable for all column families. If not set, the size is derived
+    /// from `large_memtable_capacity` for large CFs, and the RocksDB default is used for other CFs.
+    pub write_buffer_size: Option<usize>,
+    /// Maximum number of memtables (both active and immutable) for all column families. If not set,
+    /// the number is derived from `large_memtable_capacity` for large CFs, and the RocksDB default is used
+    /// for other CFs.
+    pub max_write_buffer_number: Option<usize>,
+    /// Maximum number of concurrent background jobs (compactions and flushes). If not set, the number
+    /// is derived from the number of CPU cores.
+    pub max_background_jobs: Option<usize>,
+    /// Whether to keep all data in memory instead of persisting it on disk. The path provided when opening
+    /// the DB is only used as an identifier; all data is lost once the DB is dropped. Useful for tests
+    /// and ephemeral nodes.
+    pub in_memory: bool,
 }
 
 impl Default for RocksDBOptions {
@@ -282,6 +345,11 @@ impl Default for RocksDBOptions {
             block_cache_capacity: None,
             large_memtable_capacity: None,
             stalled_writes_retries: StalledWritesRetries::new(Duration::from_secs(10)),
+            compaction_style: CompactionStyle::default(),
+            write_buffer_size: None,
+            max_write_buffer_number: None,
+            max_background_jobs: None,
+            in_memory: false,
         }
     }
 }
@@ -304,15 +372,22 @@ impl<CF: NamedColumnFamily> RocksDB<CF> {
 
     pub fn with_options(path: &Path, options: RocksDBOptions) -> Self {
         let caches = RocksDBCaches::new(options.block_cache_capacity);
-        let db_options = Self::rocksdb_options(None, None);
-        let existing_cfs = DB::list_cf(&db_options, path).unwrap_or_else(|err| {
-            tracing::warn!(
-                "Failed getting column families for RocksDB `{}` at `{}`, assuming CFs are empty; {err}",
-                CF::DB_NAME,
-                path.display()
-            );
+        let mut db_options = Self::rocksdb_options(&options, None, None);
+        let existing_cfs = if options.in_memory {
+            // Each in-memory DB has its own environment, so it's always empty on creation.
+            let env = Env::mem_env().expect("failed creating in-memory RocksDB environment");
+            db_options.set_env(&env);
             vec![]
-        });
+        } else {
+            DB::list_cf(&db_options, path).unwrap_or_else(|err| {
+                tracing::warn!(
+                    "Failed getting column families for RocksDB `{}` at `{}`, assuming CFs are empty; {err}",
+                    CF::DB_NAME,
+                    path.display()
+                );
+                vec![]
+            })
+        };
 
         let cfs_and_options: HashMap<_, _> = CF::ALL
             .iter()
@@ -350,7 +425,8 @@ impl<CF: NamedColumnFamily> RocksDB<CF> {
                 block_based_options.set_block_cache(cache);
             }
             let memtable_capacity = options.large_memtable_capacity.filter(|_| requires_tuning);
-            let cf_options = Self::rocksdb_options(memtable_capacity, Some(block_based_options));
+            let cf_options =
+                Self::rocksdb_options(&options, memtable_capacity, Some(block_based_options));
             ColumnFamilyDescriptor::new(cf_name, cf_options)
         });
 
@@ -388,6 +464,7 @@ impl<CF: NamedColumnFamily> RocksDB<CF> {
     }
 
     fn rocksdb_options(
+        db_options: &RocksDBOptions,
         memtable_capacity: Option<usize>,
         block_based_options: Option<BlockBasedOptions>,
     ) -> Options {
@@ -397,12 +474,28 @@ impl<CF: NamedColumnFamily> RocksDB<CF> {
 
         let num_cpus = num_cpus::get() as i32;
         options.increase_parallelism(num_cpus);
-        if let Some(memtable_capacity) = memtable_capacity {
-            options.optimize_level_style_compaction(memtable_capacity);
+        match (db_options.compaction_style, memtable_capacity) {
+            (CompactionStyle::Level, Some(memtable_capacity)) => {
+                options.optimize_level_style_compaction(memtable_capacity);
+            }
+            (CompactionStyle::Universal, Some(memtable_capacity)) => {
+                options.optimize_universal_style_compaction(memtable_capacity);
+            }
+            (style, None) => options.set_compaction_style(style.into()),
+        }
+        // Explicitly specified memtable options override ones derived from `memtable_capacity`.
+        if let Some(write_buffer_size) = db_options.write_buffer_size {
+            options.set_write_buffer_size(write_buffer_size);
         }
+        if let Some(max_write_buffer_number) = db_options.max_write_buffer_number {
+            options.set_max_write_buffer_number(max_write_buffer_number as i32);
+        }
+
         // Settings below are taken as per PingCAP recommendations:
         // https://www.pingcap.com/blog/how-to-troubleshoot-rocksdb-write-stalls-in-tikv/
-        let max_background_jobs = (num_cpus - 1).clamp(1, 8);
+        let max_background_jobs = db_options
+            .max_background_jobs
+            .map_or((num_cpus - 1).clamp(1, 8), |jobs| jobs as i32);
         options.set_max_background_jobs(max_background_jobs);
 
         if let Some(block_based_options) = block_based_options {
@@ -702,6 +795,54 @@ mod tests {
         assert_eq!(value.unwrap(), b"value");
     }
 
+    #[test]
+    fn opening_db_with_tuned_options() {
+        let temp_dir = TempDir::new().unwrap();
+        let options = RocksDBOptions {
+            block_cache_capacity: Some(16 << 20),
+            large_memtable_capacity: Some(16 << 20),
+            compaction_style: CompactionStyle::Universal,
+            write_buffer_size: Some(4 << 20),
+            max_write_buffer_number: Some(3),
+            max_background_jobs: Some(2),
+            ..RocksDBOptions::default()
+        };
+        let db =
+            RocksDB::<NewColumnFamilies>::with_options(temp_dir.path(), options).with_sync_writes();
+        let mut batch = db.new_write_batch();
+        batch.put_cf(NewColumnFamilies::Other, b"test", b"value");
+        db.write(batch).unwrap();
+        drop(db);
+
+        // The DB should be readable with different options.
+        let db = RocksDB::<NewColumnFamilies>::new(temp_dir.path());
+        let value = db.get_cf(NewColumnFamilies::Other, b"test").unwrap();
+        assert_eq!(value.unwrap(), b"value");
+    }
+
+    #[test]
+    fn in_memory_db_does_not_touch_disk() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("db");
+        let options = RocksDBOptions {
+            in_memory: true,
+            ..RocksDBOptions::default()
+        };
+        let db = RocksDB::<NewColumnFamilies>::with_options(&path, options).with_sync_writes();
+        let mut batch = db.new_write_batch();
+        batch.put_cf(NewColumnFamilies::Other, b"test", b"value");
+        db.write(batch).unwrap();
+        let value = db.get_cf(NewColumnFamilies::Other, b"test").unwrap();
+        assert_eq!(value.unwrap(), b"value");
+        drop(db);
+        assert!(!path.exists());
+
+        // Data is not retained after the DB is dropped.
+        let db = RocksDB::<NewColumnFamilies>::with_options(&path, options);
+        let value = db.get_cf(NewColumnFamilies::Other, b"test").unwrap();
+        assert!(value.is_none());
+    }
+
     #[test]
     fn write_batch_can_be_restored_from_bytes() {
         let temp_dir = TempDir::new().unwrap();
@@ -1,5 +1,5 @@
 pub mod db;
 mod metrics;
 
-pub use db::{RocksDB, RocksDBOptions, StalledWritesRetries};
+pub use db::{CompactionStyle, RocksDB, RocksDBOptions, StalledWritesRetries};
 pub use rocksdb;
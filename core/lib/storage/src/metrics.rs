@@ -102,6 +102,18 @@ pub(crate) struct RocksdbSizeMetrics {
     pub block_cache_size: Family<RocksdbLabels, Gauge<u64>>,
     /// Total size of index and Bloom filters in the column family of a RocksDB instance.
     pub index_and_filters_size: Family<RocksdbLabels, Gauge<u64>>,
+    /// Size of pinned entries in the block cache used by the column family of a RocksDB instance.
+    pub block_cache_pinned_size: Family<RocksdbLabels, Gauge<u64>>,
+    /// Capacity of the block cache used by the column family of a RocksDB instance.
+    pub block_cache_capacity: Family<RocksdbLabels, Gauge<u64>>,
+    /// Size of the active memtable in the column family of a RocksDB instance.
+    pub active_mem_table_size: Family<RocksdbLabels, Gauge<u64>>,
+    /// Estimated number of keys in the column family of a RocksDB instance.
+    pub estimated_keys: Family<RocksdbLabels, Gauge<u64>>,
+    /// Current rate of delayed writes in bytes per second (0 if writes are not delayed).
+    pub delayed_write_rate: Family<RocksdbLabels, Gauge<u64>>,
+    /// Accumulated number of background errors.
+    pub background_errors: Family<RocksdbLabels, Gauge<u64>>,
 }
 
 /// Weak refs to DB instances registered using [`RocksdbSizeMetrics::register()`].
@@ -43,6 +43,29 @@ impl L1BatchCommitOperation {
         vec![stored_batch_info, Token::Array(l1_batches_to_commit)]
     }
 
+    /// Same as [`Self::get_eth_tx_args()`], but with pubdata of each L1 batch replaced with commitments to it
+    /// (e.g., if pubdata is published in EIP-4844 blobs).
+    pub fn get_eth_tx_args_with_pubdata_commitments(
+        &self,
+        pubdata_commitments: Vec<Vec<u8>>,
+    ) -> Vec<Token> {
+        assert_eq!(pubdata_commitments.len(), self.l1_batches.len());
+        let stored_batch_info = self.last_committed_l1_batch.l1_header_data();
+        let l1_batches_to_commit = self
+            .l1_batches
+            .iter()
+            .zip(pubdata_commitments)
+            .map(|(batch, commitments)| batch.l1_commit_data_with_pubdata_commitments(commitments))
+            .collect();
+
+        vec![stored_batch_info, Token::Array(l1_batches_to_commit)]
+    }
+
+    /// Same as [`Self::get_eth_tx_args()`], but without publishing pubdata of L1 batches (i.e., in the validium mode).
+    pub fn get_eth_tx_args_without_pubdata(&self) -> Vec<Token> {
+        self.get_eth_tx_args_with_pubdata_commitments(vec![vec![]; self.l1_batches.len()])
+    }
+
     pub fn l1_batch_range(&self) -> ops::RangeInclusive<L1BatchNumber> {
         l1_batch_range_from_batches(&self.l1_batches)
     }
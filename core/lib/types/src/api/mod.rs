@@ -1,3 +1,9 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    iter::Peekable,
+    slice,
+};
+
 use chrono::{DateTime, Utc};
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use strum::Display;
@@ -6,18 +12,25 @@ use zksync_basic_types::{
     L1BatchNumber,
 };
 use zksync_contracts::BaseSystemContractsHashes;
+use zksync_utils::u256_to_h256;
 
 pub use crate::transaction_request::{
     Eip712Meta, SerializationTransactionError, TransactionRequest,
 };
 use crate::{
+    event::{extract_l2tol1logs_from_l1_messenger, L1MessengerL2ToL1Log},
+    fee_model::{BaseTokenConversionRatio, BatchFeeInput},
     protocol_version::L1VerifierConfig,
-    vm_trace::{Call, CallType},
+    vm_trace::{Call, CallType, FourByteTrace, StructLog, StructLogConfig},
     web3::types::{AccessList, Index, H2048},
-    Address, MiniblockNumber, ProtocolVersionId,
+    Address, MiniblockNumber, ProtocolVersionId, VmEvent, EVENT_WRITER_ADDRESS,
+    L1_MESSENGER_ADDRESS,
 };
 
 pub mod en;
+pub mod raw;
+pub mod simulate;
+pub mod trace;
 
 /// Block Number
 #[derive(Copy, Clone, Debug, PartialEq, Display)]
@@ -189,6 +202,17 @@ pub struct L2ToL1LogProof {
     pub root: H256,
 }
 
+/// Request for a single proof in `zks_getL2ToL1LogProofs`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct L2ToL1LogProofRequest {
+    /// Hash of the transaction that emitted the log.
+    pub tx_hash: H256,
+    /// Index of the log among L2->L1 logs emitted by the transaction. Defaults to 0.
+    #[serde(default)]
+    pub index: Option<usize>,
+}
+
 /// A struct with the two default bridge contracts.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -199,6 +223,18 @@ pub struct BridgeAddresses {
     pub l2_weth_bridge: Option<Address>,
 }
 
+/// Token used to pay fees on the chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeToken {
+    /// L1 address of the token. For ETH, this is the zero address.
+    pub l1_address: Address,
+    /// L2 address of the contract holding token balances.
+    pub l2_address: Address,
+    /// Ratio used to convert L1 gas and pubdata prices to the token units.
+    pub conversion_ratio: BaseTokenConversionRatio,
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TransactionReceipt {
     /// Transaction hash.
@@ -555,11 +591,73 @@ pub struct GetLogsFilter {
 }
 
 /// Result of debugging block
-/// For some reasons geth returns result as {result: DebugCall}
+/// For some reasons geth returns result as {result: DebugTrace}
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ResultDebugCall {
-    pub result: DebugCall,
+    pub result: DebugTrace,
+}
+
+/// Output of a debug tracer. The shape depends on the tracer requested in [`TracerConfig`].
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum DebugTrace {
+    /// Output of `callTracer` in `debug_traceCall` with `withStateDiff` enabled. Must precede [`Self::Call`]
+    /// so that it's matched first during deserialization.
+    CallWithStateDiff(DebugCallWithStateDiff),
+    /// Output of `callTracer`.
+    Call(DebugCall),
+    /// Output of `prestateTracer`.
+    Prestate(PrestateTrace),
+    /// Output of `prestateTracer` with `diffMode` enabled.
+    PrestateDiff(PrestateDiff),
+    /// Output of the default struct logger used when no tracer is specified.
+    StructLogs(DebugStructLogs),
+    /// Output of `4byteTracer`.
+    FourByte(FourByteTrace),
+    /// Value returned by the `result()` function of a custom JavaScript tracer.
+    Custom(serde_json::Value),
+}
+
+impl From<DebugCall> for DebugTrace {
+    fn from(call: DebugCall) -> Self {
+        Self::Call(call)
+    }
+}
+
+/// State of accounts touched by a transaction before its execution, keyed by account address.
+pub type PrestateTrace = BTreeMap<Address, PrestateAccount>;
+
+/// Output of `prestateTracer` in the diff mode. Only accounts modified by the transaction are included;
+/// `post` only contains the fields that have changed.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct PrestateDiff {
+    pub pre: PrestateTrace,
+    pub post: PrestateTrace,
+}
+
+/// Opcode-level trace returned by the default struct logger.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugStructLogs {
+    pub gas: u32,
+    pub failed: bool,
+    pub return_value: Bytes,
+    pub struct_logs: Vec<StructLog>,
+}
+
+/// Account state as reported by `prestateTracer`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PrestateAccount {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub storage: BTreeMap<H256, H256>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -582,6 +680,175 @@ pub struct DebugCall {
     pub error: Option<String>,
     pub revert_reason: Option<String>,
     pub calls: Vec<DebugCall>,
+    /// Events emitted by the call; only populated if `withLog` is enabled in [`CallTracerConfig`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub logs: Vec<DebugCallLog>,
+    /// User L2-to-L1 logs sent by the call; only populated if `withLog` is enabled in [`CallTracerConfig`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub l2_to_l1_logs: Vec<DebugCallL2ToL1Log>,
+    /// Breakdown of the gas spent by the transaction. Only set for the top-level call of transactions
+    /// executed in the sandbox.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_breakdown: Option<DebugCallGasBreakdown>,
+}
+
+impl DebugCall {
+    /// Attributes events emitted by a transaction to the call frames that have emitted them. `events` must contain
+    /// all events emitted by the transaction in the emission order (e.g., as returned by the VM or stored
+    /// in the transaction receipt).
+    ///
+    /// Events are emitted by calling the event writer system contract, so each successful call to it corresponds
+    /// to a single event. User L2-to-L1 logs are attributed to the frames calling the L1 messenger based
+    /// on the `L2ToL1LogSent` events emitted by the messenger.
+    pub fn attach_logs(&mut self, events: &[VmEvent]) {
+        self.attach_logs_inner(&mut events.iter().peekable());
+    }
+
+    /// Returns user L2-to-L1 logs sent on behalf of the caller (i.e., non-empty only for calls to the L1 messenger).
+    fn attach_logs_inner(
+        &mut self,
+        events: &mut Peekable<slice::Iter<'_, VmEvent>>,
+    ) -> Vec<L1MessengerL2ToL1Log> {
+        // Events emitted by failed calls are rolled back.
+        if self.error.is_some() || self.revert_reason.is_some() {
+            return vec![];
+        }
+
+        let Self {
+            to,
+            calls,
+            logs,
+            l2_to_l1_logs,
+            ..
+        } = self;
+        let mut sent_l2_to_l1_logs = vec![];
+        for (position, call) in calls.iter_mut().enumerate() {
+            let position = U64::from(position);
+            if call.to != EVENT_WRITER_ADDRESS {
+                let child_logs = call.attach_logs_inner(events);
+                let child_logs = child_logs.into_iter().map(|log| DebugCallL2ToL1Log {
+                    sender: log.sender,
+                    key: u256_to_h256(log.key),
+                    value: u256_to_h256(log.value),
+                    is_service: log.is_service,
+                    position,
+                });
+                l2_to_l1_logs.extend(child_logs);
+                continue;
+            }
+
+            let is_successful = call.error.is_none() && call.revert_reason.is_none();
+            let Some(event) = events.next_if(|event| is_successful && event.address == *to) else {
+                continue;
+            };
+            if *to == L1_MESSENGER_ADDRESS {
+                let sent_logs = extract_l2tol1logs_from_l1_messenger(slice::from_ref(event));
+                sent_l2_to_l1_logs.extend(sent_logs);
+            }
+            logs.push(DebugCallLog {
+                address: event.address,
+                topics: event.indexed_topics.clone(),
+                data: event.value.clone().into(),
+                position,
+            });
+        }
+        sent_l2_to_l1_logs
+    }
+}
+
+/// Breakdown of the gas spent by a transaction as reported by the VM.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugCallGasBreakdown {
+    /// Gas refunded to the transaction payer.
+    pub gas_refunded: U256,
+    /// Computational gas used by the transaction, i.e., gas not spent on publishing pubdata.
+    pub computational_gas_used: U256,
+    /// Number of pubdata bytes published by the transaction.
+    pub pubdata_published: U64,
+}
+
+/// Event emitted within a call frame.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugCallLog {
+    pub address: Address,
+    pub topics: Vec<H256>,
+    pub data: Bytes,
+    /// Number of subcalls made by the frame before emitting the event.
+    pub position: U64,
+}
+
+/// User L2-to-L1 log sent within a call frame.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugCallL2ToL1Log {
+    pub sender: Address,
+    pub key: H256,
+    pub value: H256,
+    pub is_service: bool,
+    /// Number of subcalls made by the frame before sending the log.
+    pub position: U64,
+}
+
+/// Call tree returned by `debug_traceCall` together with the state changes produced by the call.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugCallWithStateDiff {
+    #[serde(flatten)]
+    pub call: DebugCall,
+    pub state_diff: CallStateDiff,
+}
+
+/// Change of a value (e.g., a storage slot or a balance) produced by a call.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct ValueChange<T> {
+    pub from: T,
+    pub to: T,
+}
+
+/// Contract deployed by a call.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeployedBytecode {
+    pub code_hash: H256,
+    /// EraVM bytecode of the contract. Only `None` if the bytecode isn't known to the node.
+    pub code: Option<Bytes>,
+}
+
+/// State changes produced by a call executed in `debug_traceCall`, as recorded in VM storage logs.
+/// Only values that have changed as the result of the call are included.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CallStateDiff {
+    /// Storage writes keyed by the account address and the storage slot. Includes writes to system contracts
+    /// (e.g., balance changes in the L2 ETH token contract).
+    pub storage: BTreeMap<Address, BTreeMap<H256, ValueChange<H256>>>,
+    /// Changes in base token balances of accounts touched by the call.
+    pub balances: BTreeMap<Address, ValueChange<U256>>,
+    /// Contracts deployed by the call, keyed by the contract address.
+    pub deployed_contracts: BTreeMap<Address, DeployedBytecode>,
+}
+
+/// Result of `debug_replayTransaction`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionReplay {
+    /// Call trace produced by re-executing the transaction.
+    pub trace: DebugCall,
+    /// Differences between the re-execution and the stored receipt / call trace. Empty if the transaction
+    /// was re-executed deterministically.
+    pub divergences: Vec<ReplayDivergence>,
+}
+
+/// Value that differs between the stored transaction data and the transaction re-execution.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayDivergence {
+    /// Path to the diverging value, e.g. `receipt.gasUsed` or `trace.calls[0].output`.
+    pub path: String,
+    pub stored: serde_json::Value,
+    pub replayed: serde_json::Value,
 }
 
 impl From<Call> for DebugCall {
@@ -604,6 +871,9 @@ impl From<Call> for DebugCall {
             error: value.error.clone(),
             revert_reason: value.revert_reason,
             calls,
+            logs: vec![],
+            l2_to_l1_logs: vec![],
+            gas_breakdown: None,
         }
     }
 }
@@ -622,24 +892,158 @@ pub struct ProtocolVersion {
     pub l2_system_upgrade_tx_hash: Option<H256>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub enum SupportedTracers {
     CallTracer,
+    PrestateTracer,
+    #[serde(rename = "4byteTracer")]
+    FourByteTracer,
+    /// Tracer not collecting any data; useful to measure execution latency without tracing overhead.
+    NoopTracer,
+}
+
+impl SupportedTracers {
+    /// Names of all supported tracers as used in [`TracerConfig`].
+    pub const NAMES: &'static [&'static str] =
+        &["callTracer", "prestateTracer", "4byteTracer", "noopTracer"];
+}
+
+/// Tracer requested in [`TracerConfig`]: either one of the built-in tracers, or the source code
+/// of a custom JavaScript tracer (same as the `tracer` option in Geth).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum TracerSpec {
+    BuiltIn(SupportedTracers),
+    JavaScript(String),
+}
+
+impl TracerSpec {
+    /// Returns the tracer name if this spec is not a supported tracer, but looks like a tracer name
+    /// rather than JavaScript tracer code.
+    pub fn unsupported_tracer_name(&self) -> Option<&str> {
+        match self {
+            Self::JavaScript(code) => {
+                let is_name = code
+                    .chars()
+                    .all(|ch| ch.is_ascii_alphanumeric() || ch == '_');
+                is_name.then_some(code.as_str())
+            }
+            Self::BuiltIn(_) => None,
+        }
+    }
+}
+
+impl From<SupportedTracers> for TracerSpec {
+    fn from(tracer: SupportedTracers) -> Self {
+        Self::BuiltIn(tracer)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct CallTracerConfig {
+    #[serde(default)]
     pub only_top_call: bool,
+    /// Whether to return state before and after the transaction; only used by `prestateTracer`.
+    #[serde(default)]
+    pub diff_mode: bool,
+    /// Whether to return state changes produced by the call alongside the call tree; only used by `callTracer`
+    /// in `debug_traceCall`.
+    #[serde(default)]
+    pub with_state_diff: bool,
+    /// Whether to return events and user L2-to-L1 logs emitted within each call frame; only used by `callTracer`.
+    #[serde(default)]
+    pub with_log: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct TracerConfig {
-    pub tracer: SupportedTracers,
+    /// Tracer to use. If not specified, `debug_traceTransaction` falls back to the struct logger
+    /// (as Geth does), while other methods use `callTracer`.
+    #[serde(default)]
+    pub tracer: Option<TracerSpec>,
     #[serde(default)]
     pub tracer_config: CallTracerConfig,
+    /// Struct logger options; ignored by other tracers.
+    #[serde(flatten)]
+    pub struct_logs: StructLogConfig,
+    /// Maximum duration of the traced execution in the Go duration format used by Geth,
+    /// e.g. `"5s"` or `"1m30s"`.
+    #[serde(default)]
+    pub timeout: Option<String>,
+    /// Maximum approximate size of collected traces in bytes.
+    #[serde(default)]
+    pub memory_limit: Option<usize>,
+    /// Overrides of the execution environment; only used by `debug_traceCall` and `debug_traceCallMany`.
+    #[serde(default)]
+    pub execution_overrides: ExecutionOverrides,
+}
+
+/// Overrides of the execution environment for traced calls. By default, calls are traced with the same
+/// fee input and operator account as used by `eth_call`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionOverrides {
+    /// L1 gas price provided to the VM.
+    #[serde(default)]
+    pub l1_gas_price: Option<U64>,
+    /// Fair L2 gas price provided to the VM.
+    #[serde(default)]
+    pub fair_l2_gas_price: Option<U64>,
+    /// Operator account receiving transaction fees.
+    #[serde(default)]
+    pub fee_account: Option<Address>,
+}
+
+impl ExecutionOverrides {
+    /// Applies the gas price overrides to the provided fee input.
+    pub fn apply_to_fee_input(&self, fee_input: BatchFeeInput) -> BatchFeeInput {
+        let l1_gas_price = self.l1_gas_price.map(|price| price.as_u64());
+        let fair_l2_gas_price = self.fair_l2_gas_price.map(|price| price.as_u64());
+        match fee_input {
+            BatchFeeInput::L1Pegged(mut input) => {
+                input.l1_gas_price = l1_gas_price.unwrap_or(input.l1_gas_price);
+                input.fair_l2_gas_price = fair_l2_gas_price.unwrap_or(input.fair_l2_gas_price);
+                BatchFeeInput::L1Pegged(input)
+            }
+            BatchFeeInput::PubdataIndependent(mut input) => {
+                input.l1_gas_price = l1_gas_price.unwrap_or(input.l1_gas_price);
+                input.fair_l2_gas_price = fair_l2_gas_price.unwrap_or(input.fair_l2_gas_price);
+                BatchFeeInput::PubdataIndependent(input)
+            }
+        }
+    }
+}
+
+/// Options for `debug_traceCall`: tracer options extended with the state override set.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceCallConfig {
+    #[serde(flatten)]
+    pub tracer: TracerConfig,
+    #[serde(default)]
+    pub state_overrides: Option<StateOverride>,
+}
+
+/// State override set applied before executing a call, keyed by the account address.
+pub type StateOverride = HashMap<Address, OverrideAccount>;
+
+/// Overrides for a single account used in `eth_call` and `debug_traceCall`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OverrideAccount {
+    /// Account balance.
+    pub balance: Option<U256>,
+    /// Transaction nonce of the account (the deployment nonce is preserved).
+    pub nonce: Option<U256>,
+    /// EraVM bytecode of the account.
+    pub code: Option<Bytes>,
+    /// Storage slots replacing the entire account storage.
+    pub state: Option<HashMap<H256, H256>>,
+    /// Storage slots to override; other slots are left intact.
+    pub state_diff: Option<HashMap<H256, H256>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -687,6 +1091,30 @@ pub struct L1BatchDetails {
     pub base: BlockDetailsBase,
 }
 
+/// Fee model inputs recorded for a sealed L1 batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct L1BatchFeeParams {
+    pub number: L1BatchNumber,
+    /// L1 gas price used by the batch.
+    pub l1_gas_price: U64,
+    /// Fair L2 gas price used by the batch.
+    pub fair_l2_gas_price: U64,
+    /// Pubdata price used by the batch.
+    pub fair_pubdata_price: U64,
+}
+
+impl L1BatchFeeParams {
+    pub fn new(number: L1BatchNumber, fee_input: BatchFeeInput) -> Self {
+        Self {
+            number,
+            l1_gas_price: fee_input.l1_gas_price().into(),
+            fair_l2_gas_price: fee_input.fair_l2_gas_price().into(),
+            fair_pubdata_price: fee_input.fair_pubdata_price().into(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct StorageProof {
@@ -702,3 +1130,422 @@ pub struct Proof {
     pub address: Address,
     pub storage_proof: Vec<StorageProof>,
 }
+
+/// Account proof returned by `eth_getProof` (EIP-1186).
+///
+/// zkSync keeps all storage slots in a single flat Merkle tree, so account fields are proven
+/// as storage slots of the corresponding system contracts, and `storage_hash` is the state root
+/// of the L1 batch the proofs are obtained for.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountProof {
+    pub address: Address,
+    /// Merkle path for the account bytecode hash slot in the `AccountCodeStorage` system contract.
+    pub account_proof: Vec<H256>,
+    pub balance: U256,
+    pub code_hash: H256,
+    pub nonce: U256,
+    pub storage_hash: H256,
+    pub storage_proof: Vec<StorageProof>,
+    /// Proof for the account balance slot in the `L2EthToken` system contract.
+    pub balance_proof: StorageProof,
+    /// Proof for the full account nonce slot in the `NonceHolder` system contract.
+    pub nonce_proof: StorageProof,
+    /// L1 batch the proofs are obtained for.
+    pub l1_batch_number: L1BatchNumber,
+}
+
+/// Result of `eth_createAccessList`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListWithGasUsed {
+    /// Storage slots touched by the call, including slots of system contracts.
+    pub access_list: AccessList,
+    pub gas_used: U256,
+}
+
+/// Information about the node returned by `admin_nodeInfo`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeInfo {
+    pub l1_chain_id: U64,
+    pub l2_chain_id: U64,
+    pub sealed_miniblock_number: MiniblockNumber,
+    pub sealed_l1_batch_number: L1BatchNumber,
+    /// Currently active log filter, or `None` if it cannot be determined.
+    pub log_filter: Option<String>,
+    /// Whether sealing is stopped, or `None` if sealing cannot be controlled by this node
+    /// (e.g., if the state keeper runs in another process).
+    pub sealing_stopped: Option<bool>,
+}
+
+/// Transaction in the mempool returned by `txpool_content`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxpoolTransaction {
+    pub hash: H256,
+    pub from: Address,
+    pub to: Address,
+    /// Nonce of an L2 transaction; `None` for L1 priority transactions.
+    pub nonce: Option<U256>,
+    /// Serial ID of an L1 priority transaction; `None` for L2 transactions.
+    pub priority_op_id: Option<U64>,
+    pub value: U256,
+    pub gas: U256,
+    pub max_fee_per_gas: U256,
+    /// Max priority fee of an L2 transaction; `None` for L1 priority transactions.
+    pub max_priority_fee_per_gas: Option<U256>,
+    pub gas_per_pubdata_limit: U256,
+    pub input: Bytes,
+    #[serde(rename = "type")]
+    pub transaction_type: U64,
+    /// Paymaster of an L2 transaction; `None` if the transaction doesn't use a paymaster.
+    pub paymaster: Option<Address>,
+    pub paymaster_input: Option<Bytes>,
+    pub received_timestamp_ms: U64,
+}
+
+impl From<&crate::Transaction> for TxpoolTransaction {
+    fn from(tx: &crate::Transaction) -> Self {
+        let (nonce, priority_op_id, max_priority_fee_per_gas, paymaster_params) =
+            match &tx.common_data {
+                crate::ExecuteTransactionCommon::L2(data) => (
+                    Some(data.nonce.0.into()),
+                    None,
+                    Some(data.fee.max_priority_fee_per_gas),
+                    Some(&data.paymaster_params),
+                ),
+                crate::ExecuteTransactionCommon::L1(data) => {
+                    (None, Some(data.serial_id.0.into()), None, None)
+                }
+                crate::ExecuteTransactionCommon::ProtocolUpgrade(_) => (None, None, None, None),
+            };
+        let paymaster_params =
+            paymaster_params.filter(|params| params.paymaster != Address::zero());
+
+        Self {
+            hash: tx.hash(),
+            from: tx.initiator_account(),
+            to: tx.recipient_account(),
+            nonce,
+            priority_op_id,
+            value: tx.execute.value,
+            gas: tx.gas_limit(),
+            max_fee_per_gas: tx.max_fee_per_gas(),
+            max_priority_fee_per_gas,
+            gas_per_pubdata_limit: tx.gas_per_pubdata_byte_limit(),
+            input: tx.execute.calldata.clone().into(),
+            transaction_type: (tx.tx_format() as u32).into(),
+            paymaster: paymaster_params.map(|params| params.paymaster),
+            paymaster_input: paymaster_params.map(|params| params.paymaster_input.clone().into()),
+            received_timestamp_ms: tx.received_timestamp_ms.into(),
+        }
+    }
+}
+
+impl TxpoolTransaction {
+    /// Returns a human-readable summary of the transaction used by `txpool_inspect`.
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "{:?}: {} wei + {} gas × {} wei",
+            self.to, self.value, self.gas, self.max_fee_per_gas
+        );
+        if let Some(paymaster) = self.paymaster {
+            summary += &format!(" via paymaster {paymaster:?}");
+        }
+        summary
+    }
+}
+
+/// Contents of the mempool returned by `txpool_content` (with `T = TxpoolTransaction`)
+/// and `txpool_inspect` (with `T = String`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TxpoolContent<T = TxpoolTransaction> {
+    /// L2 transactions that can be executed right away, grouped by the initiator account and nonce.
+    pub pending: BTreeMap<Address, BTreeMap<u32, T>>,
+    /// L2 transactions that cannot be executed because of a nonce gap, grouped by the initiator account and nonce.
+    pub queued: BTreeMap<Address, BTreeMap<u32, T>>,
+    /// L1 priority transactions keyed by their serial ID.
+    pub priority: BTreeMap<u64, T>,
+}
+
+impl<T> Default for TxpoolContent<T> {
+    fn default() -> Self {
+        Self {
+            pending: BTreeMap::new(),
+            queued: BTreeMap::new(),
+            priority: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T> TxpoolContent<T> {
+    /// Converts transactions using the provided closure.
+    pub fn map<U>(self, mut map_fn: impl FnMut(T) -> U) -> TxpoolContent<U> {
+        let mut map_accounts = |accounts: BTreeMap<Address, BTreeMap<u32, T>>| -> BTreeMap<_, _> {
+            accounts
+                .into_iter()
+                .map(|(address, txs)| {
+                    let txs = txs.into_iter().map(|(nonce, tx)| (nonce, map_fn(tx)));
+                    (address, txs.collect())
+                })
+                .collect()
+        };
+        let pending = map_accounts(self.pending);
+        let queued = map_accounts(self.queued);
+        TxpoolContent {
+            pending,
+            queued,
+            priority: self
+                .priority
+                .into_iter()
+                .map(|(id, tx)| (id, map_fn(tx)))
+                .collect(),
+        }
+    }
+
+    /// Returns the number of transactions in each category.
+    pub fn status(&self) -> TxpoolStatus {
+        let count = |accounts: &BTreeMap<Address, BTreeMap<u32, T>>| {
+            accounts.values().map(BTreeMap::len).sum::<usize>()
+        };
+        TxpoolStatus {
+            pending: count(&self.pending).into(),
+            queued: count(&self.queued).into(),
+            priority: self.priority.len().into(),
+        }
+    }
+}
+
+/// Number of transactions in the mempool returned by `txpool_status`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TxpoolStatus {
+    pub pending: U64,
+    pub queued: U64,
+    pub priority: U64,
+}
+
+/// Number of pending transactions associated with an account returned by `txpool_accountUsage`,
+/// together with the corresponding limits enforced by the node.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxpoolAccountUsage {
+    /// Number of pending transactions sent by the account.
+    pub sent: U64,
+    /// Maximum number of pending transactions per sender, or `None` if not limited.
+    pub sent_limit: Option<U64>,
+    /// Number of pending transactions using the account as a paymaster.
+    pub sponsored: U64,
+    /// Maximum number of pending transactions per paymaster, or `None` if not limited.
+    pub sponsored_limit: Option<U64>,
+}
+
+/// Internals of the L1 gas price oracle returned by `admin_getL1GasPriceStats`. All prices are in wei.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct L1GasPriceStats {
+    /// Last L1 block which base fee was added to the samples window.
+    pub last_processed_l1_block: U64,
+    /// Number of base fee samples currently in the window.
+    pub window_size: U64,
+    /// Percentile of the base fees in the window used as the base fee estimate.
+    pub base_fee_percentile: f64,
+    /// Base fee estimate, i.e., the configured percentile of the base fees in the window.
+    pub base_fee_estimate: U64,
+    /// Base fee of the last processed L1 block (after spike smoothing).
+    pub last_base_fee: U64,
+    /// Priority fee used for L1 transactions.
+    pub priority_fee: U64,
+    /// Number of base fee samples capped by spike smoothing since the node start.
+    pub smoothed_spikes: U64,
+    /// Median blob base fee over the blob base fee window.
+    pub blob_base_fee_estimate: U64,
+    /// L1 gas price used by the node to compute fees, after multipliers and bounds are applied.
+    pub effective_gas_price: U64,
+    /// Pubdata price used by the node to compute fees, after multipliers and bounds are applied.
+    pub effective_pubdata_price: U64,
+}
+
+/// Fee model settings that can be changed at runtime via `admin_updateFeeModelSettings`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeModelSettings {
+    /// Minimal fair L2 gas price. If the dynamic L2 gas price is enabled, it cannot go below this value.
+    pub minimal_l2_gas_price: U64,
+    /// Factor applied to the L1 gas price estimated by the node. Replaces the internal L1 pricing multiplier
+    /// of the gas adjuster.
+    pub l1_gas_price_scale_factor: f64,
+    /// Factor applied to the blob base fee to get the L1 pubdata price if pubdata is published in blobs. Replaces
+    /// the internal pubdata pricing multiplier of the gas adjuster.
+    pub l1_pubdata_price_scale_factor: f64,
+}
+
+/// Partial update of [`FeeModelSettings`]. Unset fields are left unchanged.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeModelSettingsUpdate {
+    pub minimal_l2_gas_price: Option<U64>,
+    pub l1_gas_price_scale_factor: Option<f64>,
+    pub l1_pubdata_price_scale_factor: Option<f64>,
+}
+
+/// Rules for aggregating L1 batches into commit, prove and execute L1 transactions. Can be changed at runtime
+/// via `admin_updateAggregationPolicy`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AggregationPolicy {
+    /// Maximum number of L1 batches committed in a single L1 transaction.
+    pub max_aggregated_blocks_to_commit: u32,
+    /// Maximum number of L1 batches proven in a single L1 transaction.
+    pub max_aggregated_blocks_to_prove: u32,
+    /// Maximum number of L1 batches executed in a single L1 transaction.
+    pub max_aggregated_blocks_to_execute: u32,
+    /// Maximum gas limit of an aggregated L1 transaction.
+    pub max_aggregated_tx_gas: u32,
+    /// Maximum size of the commit transaction calldata in bytes.
+    pub max_eth_tx_data_size: usize,
+    /// Age of the oldest uncommitted L1 batch in seconds after which all ready L1 batches are committed.
+    pub aggregated_block_commit_deadline: u64,
+    /// Age of the oldest unproven L1 batch in seconds after which all ready L1 batches are proven.
+    pub aggregated_block_prove_deadline: u64,
+    /// Age of the oldest unexecuted L1 batch in seconds after which all ready L1 batches are executed.
+    pub aggregated_block_execute_deadline: u64,
+}
+
+/// Partial update of [`AggregationPolicy`]. Unset fields are left unchanged.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AggregationPolicyUpdate {
+    pub max_aggregated_blocks_to_commit: Option<u32>,
+    pub max_aggregated_blocks_to_prove: Option<u32>,
+    pub max_aggregated_blocks_to_execute: Option<u32>,
+    pub max_aggregated_tx_gas: Option<u32>,
+    pub max_eth_tx_data_size: Option<usize>,
+    pub aggregated_block_commit_deadline: Option<u64>,
+    pub aggregated_block_prove_deadline: Option<u64>,
+    pub aggregated_block_execute_deadline: Option<u64>,
+}
+
+/// Operator revenue and expenditures for an L1 batch returned by `zks_getL1BatchRevenue`. All values are in wei.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct L1BatchRevenue {
+    pub l1_batch_number: L1BatchNumber,
+    /// Fees paid by transactions in the batch. If fees are paid in a custom base token, they are converted to wei
+    /// using the base token conversion ratio.
+    pub collected_fees: U256,
+    /// Cost of publishing the batch pubdata on L1: blob gas for blobs, or the estimated calldata cost otherwise.
+    /// Zero if pubdata is not published on L1 (e.g., for validium). This cost is not included into `commit_cost`.
+    pub pubdata_cost: U256,
+    /// Share of the batch in the cost of the L1 transaction committing it, excluding the pubdata cost.
+    pub commit_cost: U256,
+    /// Share of the batch in the cost of the L1 transaction proving it.
+    pub prove_cost: U256,
+    /// Share of the batch in the cost of the L1 transaction executing it.
+    pub execute_cost: U256,
+}
+
+impl L1BatchRevenue {
+    /// Returns the total L1 cost of the batch.
+    pub fn l1_cost(&self) -> U256 {
+        self.pubdata_cost + self.commit_cost + self.prove_cost + self.execute_cost
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn debug_call(from: Address, to: Address, calls: Vec<DebugCall>) -> DebugCall {
+        DebugCall {
+            r#type: DebugCallType::Call,
+            from,
+            to,
+            gas: U256::zero(),
+            gas_used: U256::zero(),
+            value: U256::zero(),
+            output: Bytes::default(),
+            input: Bytes::default(),
+            error: None,
+            revert_reason: None,
+            calls,
+            logs: vec![],
+            l2_to_l1_logs: vec![],
+            gas_breakdown: None,
+        }
+    }
+
+    fn event(address: Address, topic: u8) -> VmEvent {
+        VmEvent {
+            address,
+            indexed_topics: vec![H256::repeat_byte(topic)],
+            ..VmEvent::default()
+        }
+    }
+
+    #[test]
+    fn attaching_logs_to_debug_call() {
+        let sender = Address::repeat_byte(1);
+        let contract = Address::repeat_byte(2);
+        let other_contract = Address::repeat_byte(3);
+        let write_event = |from| debug_call(from, EVENT_WRITER_ADDRESS, vec![]);
+
+        let mut reverted_call =
+            debug_call(contract, other_contract, vec![write_event(other_contract)]);
+        reverted_call.revert_reason = Some("oops".to_owned());
+        let nested_call = debug_call(
+            contract,
+            other_contract,
+            vec![write_event(other_contract), write_event(other_contract)],
+        );
+        let mut call = debug_call(
+            sender,
+            contract,
+            vec![
+                write_event(contract),
+                reverted_call,
+                nested_call,
+                write_event(contract),
+            ],
+        );
+
+        let events = [
+            event(contract, 1),
+            event(other_contract, 2),
+            event(other_contract, 3),
+            event(contract, 4),
+        ];
+        call.attach_logs(&events);
+
+        let topics_and_positions: Vec<_> = call
+            .logs
+            .iter()
+            .map(|log| (log.topics[0], log.position.as_u64()))
+            .collect();
+        assert_eq!(
+            topics_and_positions,
+            [(H256::repeat_byte(1), 0), (H256::repeat_byte(4), 3)]
+        );
+        assert!(call.calls[1].logs.is_empty());
+        let nested_logs = &call.calls[2].logs;
+        assert_eq!(nested_logs.len(), 2);
+        assert!(nested_logs.iter().all(|log| log.address == other_contract));
+        assert_eq!(nested_logs[1].position, U64::from(1));
+        assert!(call.l2_to_l1_logs.is_empty());
+    }
+
+    #[test]
+    fn parsing_tracer_spec() {
+        for &name in SupportedTracers::NAMES {
+            let spec: TracerSpec = serde_json::from_value(serde_json::json!(name)).unwrap();
+            assert!(matches!(spec, TracerSpec::BuiltIn(_)), "{spec:?}");
+        }
+
+        let spec: TracerSpec = serde_json::from_value(serde_json::json!("fooTracer")).unwrap();
+        assert_eq!(spec.unsupported_tracer_name(), Some("fooTracer"));
+        let code = "{ data: [], fault() {}, step() {}, result() { return this.data; } }";
+        let spec: TracerSpec = serde_json::from_value(serde_json::json!(code)).unwrap();
+        assert_eq!(spec, TracerSpec::JavaScript(code.to_owned()));
+        assert_eq!(spec.unsupported_tracer_name(), None);
+    }
+}
@@ -0,0 +1,252 @@
+//! Byte-level encodings of blocks, transactions and receipts returned by the `debug_getRaw*` methods.
+//!
+//! The encodings follow Ethereum consensus formats where possible. Fields that have no meaning in zkSync
+//! (e.g., the state root in block headers or logs blooms) are filled with zeros, the same as in the JSON views.
+
+use rlp::RlpStream;
+use zksync_utils::{address_to_h256, bytecode::hash_bytecode, h256_to_u256};
+
+use crate::{
+    api::Log,
+    ethabi::{encode, Token},
+    web3::types::H2048,
+    Address, ExecuteTransactionCommon, Transaction, H256, U256,
+};
+
+/// Errors that can occur when encoding a stored transaction.
+#[derive(Debug, thiserror::Error)]
+pub enum RawEncodingError {
+    #[error("L2 transaction has no stored input data")]
+    MissingInputData,
+}
+
+/// Header fields of a miniblock included into its raw encoding.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RawBlockHeader {
+    pub parent_hash: H256,
+    pub uncles_hash: H256,
+    pub number: u64,
+    pub gas_limit: U256,
+    pub gas_used: U256,
+    pub timestamp: u64,
+    pub base_fee_per_gas: U256,
+}
+
+/// Returns the raw payload of a transaction. For L2 transactions, this is the signed payload as submitted
+/// by the user (RLP for Ethereum transaction types, or EIP-712 for zkSync-specific ones). L1 and protocol
+/// upgrade transactions are encoded as the ABI-encoded `L2CanonicalTransaction` struct, so that
+/// the keccak256 hash of the payload equals the canonical transaction hash.
+///
+/// # Errors
+///
+/// Returns an error if an L2 transaction doesn't have the signed payload stored.
+pub fn encode_transaction(tx: &Transaction) -> Result<Vec<u8>, RawEncodingError> {
+    let (tx_type, sender, nonce) = match &tx.common_data {
+        ExecuteTransactionCommon::L2(data) => {
+            return data
+                .input_data()
+                .map(<[u8]>::to_vec)
+                .ok_or(RawEncodingError::MissingInputData);
+        }
+        ExecuteTransactionCommon::L1(data) => {
+            (data.tx_format(), data.sender, U256::from(data.serial_id.0))
+        }
+        ExecuteTransactionCommon::ProtocolUpgrade(data) => (
+            data.tx_format(),
+            data.sender,
+            U256::from(data.upgrade_id as u16),
+        ),
+    };
+    let (gas_limit, gas_per_pubdata_limit, max_fee_per_gas, to_mint, refund_recipient) =
+        match &tx.common_data {
+            ExecuteTransactionCommon::L1(data) => (
+                data.gas_limit,
+                data.gas_per_pubdata_limit,
+                data.max_fee_per_gas,
+                data.to_mint,
+                data.refund_recipient,
+            ),
+            ExecuteTransactionCommon::ProtocolUpgrade(data) => (
+                data.gas_limit,
+                data.gas_per_pubdata_limit,
+                data.max_fee_per_gas,
+                data.to_mint,
+                data.refund_recipient,
+            ),
+            ExecuteTransactionCommon::L2(_) => unreachable!("handled above"),
+        };
+
+    let factory_deps = tx.execute.factory_deps.as_deref().unwrap_or_default();
+    let factory_dep_hashes = factory_deps
+        .iter()
+        .map(|dep| Token::Uint(h256_to_u256(hash_bytecode(dep))))
+        .collect();
+    Ok(encode(&[Token::Tuple(vec![
+        Token::Uint(U256::from(tx_type as u32)),
+        Token::Address(sender),
+        Token::Address(tx.execute.contract_address),
+        Token::Uint(gas_limit),
+        Token::Uint(gas_per_pubdata_limit),
+        Token::Uint(max_fee_per_gas),
+        Token::Uint(U256::zero()),       // max priority fee per gas
+        Token::Address(Address::zero()), // paymaster
+        Token::Uint(nonce),
+        Token::Uint(tx.execute.value),
+        Token::FixedArray(vec![
+            Token::Uint(to_mint),
+            Token::Uint(h256_to_u256(address_to_h256(&refund_recipient))),
+            Token::Uint(U256::zero()),
+            Token::Uint(U256::zero()),
+        ]),
+        Token::Bytes(tx.execute.calldata.clone()),
+        Token::Bytes(vec![]), // signature
+        Token::Array(factory_dep_hashes),
+        Token::Bytes(vec![]), // paymaster input
+        Token::Bytes(vec![]), // reserved dynamic
+    ])]))
+}
+
+/// Encodes a transaction receipt in the EIP-2718 format: receipts of non-legacy transactions are prefixed
+/// with the transaction type. `logs` must be ordered by their index in the transaction.
+pub fn encode_receipt(
+    tx_type: u8,
+    success: bool,
+    cumulative_gas_used: U256,
+    logs: &[Log],
+) -> Vec<u8> {
+    let mut rlp = RlpStream::new_list(4);
+    rlp.append(&u8::from(success));
+    rlp.append(&cumulative_gas_used);
+    rlp.append(&H2048::zero().as_bytes());
+    rlp.begin_list(logs.len());
+    for log in logs {
+        rlp.begin_list(3);
+        rlp.append(&log.address.as_bytes());
+        rlp.begin_list(log.topics.len());
+        for topic in &log.topics {
+            rlp.append(&topic.as_bytes());
+        }
+        rlp.append(&log.data.0);
+    }
+
+    let receipt = rlp.out();
+    if tx_type == 0 {
+        receipt.to_vec()
+    } else {
+        let mut typed_receipt = Vec::with_capacity(receipt.len() + 1);
+        typed_receipt.push(tx_type);
+        typed_receipt.extend_from_slice(&receipt);
+        typed_receipt
+    }
+}
+
+/// Encodes a miniblock as the RLP list of its header, transactions and (always empty) uncles.
+/// `transactions` must contain payloads returned by [`encode_transaction()`].
+///
+/// Since zkSync miniblock hashes are not computed from headers, the keccak256 hash of the encoded header
+/// does not match the miniblock hash.
+pub fn encode_block(header: &RawBlockHeader, transactions: &[Vec<u8>]) -> Vec<u8> {
+    let mut rlp = RlpStream::new_list(3);
+    rlp.begin_list(16);
+    rlp.append(&header.parent_hash.as_bytes());
+    rlp.append(&header.uncles_hash.as_bytes());
+    rlp.append(&Address::zero().as_bytes()); // coinbase
+    rlp.append(&H256::zero().as_bytes()); // state root
+    rlp.append(&H256::zero().as_bytes()); // transactions root
+    rlp.append(&H256::zero().as_bytes()); // receipts root
+    rlp.append(&H2048::zero().as_bytes());
+    rlp.append(&U256::zero()); // difficulty
+    rlp.append(&header.number);
+    rlp.append(&header.gas_limit);
+    rlp.append(&header.gas_used);
+    rlp.append(&header.timestamp);
+    rlp.append_empty_data(); // extra data
+    rlp.append(&H256::zero().as_bytes()); // mix hash
+    rlp.append(&[0_u8; 8].as_slice()); // nonce
+    rlp.append(&header.base_fee_per_gas);
+
+    rlp.begin_list(transactions.len());
+    for tx in transactions {
+        // Legacy transactions are embedded as RLP lists, while all other payloads (typed transactions
+        // and ABI-encoded L1 transactions) are embedded as byte strings.
+        if tx.first().map_or(false, |&byte| byte >= 0xc0) {
+            rlp.append_raw(tx, 1);
+        } else {
+            rlp.append(tx);
+        }
+    }
+    rlp.begin_list(0);
+    rlp.out().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use rlp::Rlp;
+
+    use super::*;
+    use crate::{web3::types::Bytes, H160};
+
+    #[test]
+    fn encoding_typed_receipt() {
+        let log = Log {
+            address: H160::repeat_byte(1),
+            topics: vec![H256::repeat_byte(2)],
+            data: Bytes(vec![3; 10]),
+            block_hash: None,
+            block_number: None,
+            l1_batch_number: None,
+            transaction_hash: None,
+            transaction_index: None,
+            log_index: None,
+            transaction_log_index: None,
+            log_type: None,
+            removed: None,
+        };
+        let receipt = encode_receipt(2, true, 21_000.into(), &[log]);
+        assert_eq!(receipt[0], 2);
+
+        let rlp = Rlp::new(&receipt[1..]);
+        assert_eq!(rlp.item_count().unwrap(), 4);
+        assert_eq!(rlp.val_at::<u8>(0).unwrap(), 1);
+        assert_eq!(rlp.val_at::<U256>(1).unwrap(), U256::from(21_000));
+        let logs = rlp.at(3).unwrap();
+        assert_eq!(logs.item_count().unwrap(), 1);
+        let log = logs.at(0).unwrap();
+        assert_eq!(log.val_at::<Vec<u8>>(0).unwrap(), [1; 20]);
+        assert_eq!(log.list_at::<Vec<u8>>(1).unwrap(), [vec![2; 32]]);
+        assert_eq!(log.val_at::<Vec<u8>>(2).unwrap(), [3; 10]);
+
+        let legacy_receipt = encode_receipt(0, false, 21_000.into(), &[]);
+        let rlp = Rlp::new(&legacy_receipt);
+        assert!(rlp.is_list());
+        assert!(rlp.at(0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn encoding_block() {
+        let header = RawBlockHeader {
+            number: 5,
+            timestamp: 100,
+            ..RawBlockHeader::default()
+        };
+        let legacy_tx = {
+            let mut rlp = RlpStream::new_list(1);
+            rlp.append(&1_u8);
+            rlp.out().to_vec()
+        };
+        let typed_tx = vec![2, 0xc0];
+        let block = encode_block(&header, &[legacy_tx.clone(), typed_tx.clone()]);
+
+        let rlp = Rlp::new(&block);
+        assert_eq!(rlp.item_count().unwrap(), 3);
+        let header = rlp.at(0).unwrap();
+        assert_eq!(header.item_count().unwrap(), 16);
+        assert_eq!(header.val_at::<u64>(8).unwrap(), 5);
+        assert_eq!(header.val_at::<u64>(11).unwrap(), 100);
+
+        let txs = rlp.at(1).unwrap();
+        assert_eq!(txs.at(0).unwrap().as_raw(), legacy_tx);
+        assert_eq!(txs.val_at::<Vec<u8>>(1).unwrap(), typed_tx);
+        assert_eq!(rlp.at(2).unwrap().item_count().unwrap(), 0);
+    }
+}
@@ -0,0 +1,70 @@
+//! Types for `eth_simulateV1`, which executes several blocks of calls on top of the same sandbox state.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api::{Log, StateOverride},
+    transaction_request::CallRequest,
+    web3::types::Bytes,
+    H256, U256, U64,
+};
+
+/// Block of calls simulated by `eth_simulateV1`. Block overrides are not supported; simulated blocks
+/// follow the base block with sequential numbers and timestamps.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedBlockCalls {
+    /// State overrides applied before executing the block. Full `state` and `code` overrides
+    /// are only supported for the first block.
+    #[serde(default)]
+    pub state_overrides: Option<StateOverride>,
+    pub calls: Vec<CallRequest>,
+}
+
+/// Payload of `eth_simulateV1`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatePayload {
+    pub block_state_calls: Vec<SimulatedBlockCalls>,
+}
+
+/// Error of a failed simulated call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedCallError {
+    pub code: i64,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Bytes>,
+}
+
+impl SimulatedCallError {
+    /// Error code for reverted calls.
+    pub const REVERT_CODE: i64 = 3;
+    /// Error code for calls halted by the VM.
+    pub const VM_ERROR_CODE: i64 = -32015;
+}
+
+/// Result of a single simulated call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedCallResult {
+    /// 1 if the call succeeded, 0 otherwise.
+    pub status: U64,
+    pub return_data: Bytes,
+    pub gas_used: U256,
+    pub logs: Vec<Log>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<SimulatedCallError>,
+}
+
+/// Simulated block returned by `eth_simulateV1`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedBlock {
+    pub number: U64,
+    pub hash: H256,
+    pub timestamp: U64,
+    pub gas_used: U256,
+    pub calls: Vec<SimulatedCallResult>,
+}
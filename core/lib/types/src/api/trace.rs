@@ -0,0 +1,321 @@
+//! Types for the OpenEthereum-style `trace` namespace. Traces are flattened from the [`Call`] tree,
+//! with the position of each call in the tree described by its `traceAddress`.
+
+use serde::{Deserialize, Serialize};
+use zk_evm::zkevm_opcode_defs::FarCallOpcode;
+
+use crate::{
+    api::BlockNumber,
+    vm_trace::{Call, CallType},
+    web3::types::Bytes,
+    Address, H256, U256, U64,
+};
+
+/// Kinds of traces that can be requested from `trace_call` and `trace_replay*` methods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TraceType {
+    /// Flat call traces.
+    Trace,
+    /// Opcode-level traces. Not supported; always returned as `null`.
+    VmTrace,
+    /// State diffs. Not supported; always returned as `null`.
+    StateDiff,
+}
+
+/// Type of a call action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CallKind {
+    Call,
+    DelegateCall,
+}
+
+/// Action performed by a call frame.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallAction {
+    pub call_type: CallKind,
+    pub from: Address,
+    pub to: Address,
+    pub gas: U256,
+    pub input: Bytes,
+    pub value: U256,
+}
+
+/// Action performed by a contract deployment frame.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateAction {
+    pub from: Address,
+    pub gas: U256,
+    pub init: Bytes,
+    pub value: U256,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Action {
+    Call(CallAction),
+    Create(CreateAction),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallResult {
+    pub gas_used: U256,
+    pub output: Bytes,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateResult {
+    pub address: Address,
+    pub code: Bytes,
+    pub gas_used: U256,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TraceOutput {
+    Call(CallResult),
+    Create(CreateResult),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ActionType {
+    Call,
+    Create,
+}
+
+/// Single call frame in the flat trace format.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Trace {
+    pub action: Action,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Result of the call; `None` if the call has failed.
+    pub result: Option<TraceOutput>,
+    /// Number of direct subcalls.
+    pub subtraces: usize,
+    /// Indices of the call and all its ancestors in the lists of subcalls of their parents.
+    /// Empty for the top-level call.
+    pub trace_address: Vec<usize>,
+    #[serde(rename = "type")]
+    pub action_type: ActionType,
+}
+
+/// Returns all frames in the call tree together with their trace addresses, in the depth-first order.
+/// The order is the same as in [`Trace::flatten()`].
+pub fn call_frames(call: &Call) -> Vec<(Vec<usize>, &Call)> {
+    fn visit<'a>(
+        call: &'a Call,
+        trace_address: &mut Vec<usize>,
+        frames: &mut Vec<(Vec<usize>, &'a Call)>,
+    ) {
+        frames.push((trace_address.clone(), call));
+        for (i, subcall) in call.calls.iter().enumerate() {
+            trace_address.push(i);
+            visit(subcall, trace_address, frames);
+            trace_address.pop();
+        }
+    }
+
+    let mut frames = vec![];
+    visit(call, &mut vec![], &mut frames);
+    frames
+}
+
+impl Trace {
+    /// Flattens the call tree in the depth-first order.
+    pub fn flatten(call: &Call) -> Vec<Self> {
+        call_frames(call)
+            .into_iter()
+            .map(|(trace_address, call)| Self::new(call, trace_address))
+            .collect()
+    }
+
+    fn new(call: &Call, trace_address: Vec<usize>) -> Self {
+        let gas = U256::from(call.gas);
+        let gas_used = U256::from(call.gas_used);
+        let (action, action_type, result) = match call.r#type {
+            CallType::Create => {
+                let action = Action::Create(CreateAction {
+                    from: call.from,
+                    gas,
+                    init: Bytes(call.input.clone()),
+                    value: call.value,
+                });
+                let result = TraceOutput::Create(CreateResult {
+                    address: call.to,
+                    code: Bytes(call.output.clone()),
+                    gas_used,
+                });
+                (action, ActionType::Create, result)
+            }
+            CallType::Call(_) | CallType::NearCall => {
+                let call_type = match call.r#type {
+                    CallType::Call(FarCallOpcode::Delegate) => CallKind::DelegateCall,
+                    _ => CallKind::Call,
+                };
+                let action = Action::Call(CallAction {
+                    call_type,
+                    from: call.from,
+                    to: call.to,
+                    gas,
+                    input: Bytes(call.input.clone()),
+                    value: call.value,
+                });
+                let result = TraceOutput::Call(CallResult {
+                    gas_used,
+                    output: Bytes(call.output.clone()),
+                });
+                (action, ActionType::Call, result)
+            }
+        };
+
+        let error = call
+            .error
+            .clone()
+            .or_else(|| call.revert_reason.as_ref().map(|_| "Reverted".to_owned()));
+        Self {
+            action,
+            result: error.is_none().then_some(result),
+            error,
+            subtraces: call.calls.len(),
+            trace_address,
+            action_type,
+        }
+    }
+}
+
+/// Trace together with the location of the traced transaction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalizedTrace {
+    #[serde(flatten)]
+    pub trace: Trace,
+    pub block_hash: H256,
+    pub block_number: U64,
+    pub transaction_hash: H256,
+    pub transaction_position: usize,
+    /// Depth-first position of the trace in the call tree of the transaction.
+    pub trace_position: usize,
+}
+
+impl LocalizedTrace {
+    /// Returns the cursor pointing at this trace. Can be used in [`TraceFilter`] to fetch the following traces.
+    pub fn cursor(&self) -> TraceCursor {
+        TraceCursor {
+            block_number: self.block_number,
+            transaction_position: self.transaction_position,
+            trace_position: self.trace_position,
+        }
+    }
+}
+
+/// Location of a trace used for keyset pagination in `trace_filter`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceCursor {
+    pub block_number: U64,
+    pub transaction_position: usize,
+    pub trace_position: usize,
+}
+
+/// Filter for `trace_filter`. Address filters are combined using AND; each address filter matches
+/// if the corresponding call address is contained in it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceFilter {
+    pub from_block: Option<BlockNumber>,
+    pub to_block: Option<BlockNumber>,
+    pub from_address: Option<Vec<Address>>,
+    pub to_address: Option<Vec<Address>>,
+    /// Number of matching traces to skip.
+    pub after: Option<usize>,
+    /// If specified, only traces located after this cursor are returned. Unlike `after`, doesn't require
+    /// scanning skipped traces, so it should be preferred for pagination. Cannot be combined with `after`.
+    pub cursor: Option<TraceCursor>,
+    /// Maximum number of traces to return.
+    pub count: Option<usize>,
+}
+
+/// Output of `trace_call` and `trace_replayTransaction`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceResults {
+    /// Output of the top-level call.
+    pub output: Bytes,
+    /// Flat traces; only present if [`TraceType::Trace`] is requested.
+    pub trace: Option<Vec<Trace>>,
+    pub vm_trace: Option<serde_json::Value>,
+    pub state_diff: Option<serde_json::Value>,
+}
+
+impl TraceResults {
+    pub fn new(call: &Call, trace_types: &[TraceType]) -> Self {
+        Self {
+            output: Bytes(call.output.clone()),
+            trace: trace_types
+                .contains(&TraceType::Trace)
+                .then(|| Trace::flatten(call)),
+            vm_trace: None,
+            state_diff: None,
+        }
+    }
+}
+
+/// Output of `trace_replayBlockTransactions` for a single transaction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceResultsWithTransactionHash {
+    #[serde(flatten)]
+    pub results: TraceResults,
+    pub transaction_hash: H256,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(to: Address, calls: Vec<Call>) -> Call {
+        let mut call = Call::new_high_level(100, 10, U256::zero(), vec![], vec![], None, calls);
+        call.to = to;
+        call
+    }
+
+    #[test]
+    fn flattening_call_tree() {
+        let mut reverted_call = call(Address::repeat_byte(3), vec![]);
+        reverted_call.revert_reason = Some("oops".to_owned());
+        let mut delegate_call = call(Address::repeat_byte(2), vec![reverted_call]);
+        delegate_call.r#type = CallType::Call(FarCallOpcode::Delegate);
+        let mut create_call = call(Address::repeat_byte(4), vec![]);
+        create_call.r#type = CallType::Create;
+        let root = call(Address::repeat_byte(1), vec![delegate_call, create_call]);
+
+        let traces = Trace::flatten(&root);
+        let trace_addresses: Vec<_> = traces.iter().map(|trace| &trace.trace_address).collect();
+        assert_eq!(trace_addresses, [vec![], vec![0], vec![0, 0], vec![1]]);
+        let subtraces: Vec<_> = traces.iter().map(|trace| trace.subtraces).collect();
+        assert_eq!(subtraces, [2, 1, 0, 0]);
+
+        assert!(matches!(
+            &traces[1].action,
+            Action::Call(CallAction {
+                call_type: CallKind::DelegateCall,
+                ..
+            })
+        ));
+        assert_eq!(traces[2].error.as_deref(), Some("Reverted"));
+        assert_eq!(traces[2].result, None);
+        assert_eq!(traces[3].action_type, ActionType::Create);
+        assert!(matches!(
+            &traces[3].result,
+            Some(TraceOutput::Create(CreateResult { address, .. })) if *address == Address::repeat_byte(4)
+        ));
+    }
+}
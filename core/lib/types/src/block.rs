@@ -217,6 +217,22 @@ impl MiniblockHasher {
         }
     }
 
+    /// Creates a new hasher with the known rolling hash of miniblock transactions (e.g., read from
+    /// the `SystemContext` storage), rather than individual transaction hashes.
+    pub fn with_txs_rolling_hash(
+        number: MiniblockNumber,
+        timestamp: u64,
+        prev_miniblock_hash: H256,
+        txs_rolling_hash: H256,
+    ) -> Self {
+        Self {
+            number,
+            timestamp,
+            prev_miniblock_hash,
+            txs_rolling_hash,
+        }
+    }
+
     /// Updates this hasher with a transaction hash. This should be called for all transactions in the block
     /// in the order of their execution.
     pub fn push_tx_hash(&mut self, tx_hash: H256) {
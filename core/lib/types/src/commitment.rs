@@ -9,6 +9,7 @@
 use std::{collections::HashMap, convert::TryFrom};
 
 use serde::{Deserialize, Serialize};
+pub use zksync_basic_types::L1BatchCommitmentMode;
 use zksync_mini_merkle_tree::MiniMerkleTree;
 use zksync_system_constants::{
     L2_TO_L1_LOGS_TREE_ROOT_KEY, STATE_DIFF_HASH_KEY, ZKPORTER_IS_AVAILABLE,
@@ -160,6 +161,25 @@ impl L1BatchWithMetadata {
 
     /// Encodes the L1Batch into CommitBatchInfo (see IExecutor.sol).
     pub fn l1_commit_data(&self) -> Token {
+        self.encode_l1_commit_data(None)
+    }
+
+    /// Encodes the L1Batch into CommitBatchInfo (see IExecutor.sol) with pubdata published separately
+    /// (e.g., in EIP-4844 blobs). In this case, `totalL2ToL1Pubdata` contains `pubdata_commitments`
+    /// instead of pubdata itself.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the batch is pre-boojum, i.e. doesn't support publishing pubdata separately.
+    pub fn l1_commit_data_with_pubdata_commitments(&self, pubdata_commitments: Vec<u8>) -> Token {
+        assert!(
+            !self.header.protocol_version.unwrap().is_pre_boojum(),
+            "Pre-boojum L1 batches cannot publish pubdata separately"
+        );
+        self.encode_l1_commit_data(Some(pubdata_commitments))
+    }
+
+    fn encode_l1_commit_data(&self, pubdata_commitments: Option<Vec<u8>>) -> Token {
         if self.header.protocol_version.unwrap().is_pre_boojum() {
             Token::Tuple(vec![
                 Token::Uint(U256::from(self.header.number.0)),
@@ -229,12 +249,7 @@ impl L1BatchWithMetadata {
                 // `systemLogs`
                 Token::Bytes(self.metadata.l2_l1_messages_compressed.clone()),
                 // `totalL2ToL1Pubdata`
-                Token::Bytes(
-                    self.header
-                        .pubdata_input
-                        .clone()
-                        .unwrap_or(self.construct_pubdata()),
-                ),
+                Token::Bytes(pubdata_commitments.unwrap_or_else(|| self.pubdata())),
             ])
         }
     }
@@ -243,6 +258,14 @@ impl L1BatchWithMetadata {
         crate::ethabi::encode(&[Token::Array(vec![self.l1_commit_data()])]).len()
     }
 
+    /// Returns pubdata of a post-boojum L1 batch, either as produced by the VM or constructed from the batch data.
+    pub fn pubdata(&self) -> Vec<u8> {
+        self.header
+            .pubdata_input
+            .clone()
+            .unwrap_or_else(|| self.construct_pubdata())
+    }
+
     /// Packs all pubdata needed for batch commitment in boojum into one bytes array. The packing contains the
     /// following: logs, messages, bytecodes, and compressed state diffs.
     /// This data is currently part of calldata but will be submitted as part of the blob section post EIP-4844.
@@ -375,6 +398,7 @@ impl L1BatchAuxiliaryOutput {
         bootloader_heap_hash: H256,
         events_state_queue_hash: H256,
         is_pre_boojum: bool,
+        commitment_mode: L1BatchCommitmentMode,
     ) -> Self {
         let state_diff_hash_from_logs = system_logs.iter().find_map(|log| {
             if log.0.key == u256_to_h256(STATE_DIFF_HASH_KEY.into()) {
@@ -416,7 +440,12 @@ impl L1BatchAuxiliaryOutput {
             )
         };
 
-        let state_diffs_compressed = compress_state_diffs(state_diffs.clone());
+        // State diffs are only compressed to be published on L1. The state diffs hash is committed to
+        // regardless of the mode, since it's verified by the circuits.
+        let state_diffs_compressed = match commitment_mode {
+            L1BatchCommitmentMode::Rollup => compress_state_diffs(state_diffs.clone()),
+            L1BatchCommitmentMode::Validium => vec![],
+        };
 
         let l2_l1_logs_linear_hash = H256::from(keccak256(&l2_l1_logs_compressed));
         let system_logs_linear_hash = H256::from(keccak256(&system_logs_compressed));
@@ -587,6 +616,7 @@ impl L1BatchCommitment {
         bootloader_heap_hash: H256,
         events_state_queue_hash: H256,
         is_pre_boojum: bool,
+        commitment_mode: L1BatchCommitmentMode,
     ) -> Self {
         let meta_parameters = L1BatchMetaParameters {
             zkporter_is_available: ZKPORTER_IS_AVAILABLE,
@@ -617,6 +647,7 @@ impl L1BatchCommitment {
                 bootloader_heap_hash,
                 events_state_queue_hash,
                 is_pre_boojum,
+                commitment_mode,
             ),
             meta_parameters,
         }
@@ -688,8 +719,8 @@ mod tests {
 
     use crate::{
         commitment::{
-            L1BatchAuxiliaryOutput, L1BatchCommitment, L1BatchMetaParameters,
-            L1BatchPassThroughData,
+            L1BatchAuxiliaryOutput, L1BatchCommitment, L1BatchCommitmentMode,
+            L1BatchMetaParameters, L1BatchPassThroughData,
         },
         l2_to_l1_log::{L2ToL1Log, UserL2ToL1Log},
         writes::{InitialStorageWrite, RepeatedStorageWrite},
@@ -776,6 +807,7 @@ mod tests {
             H256::zero(),
             H256::zero(),
             false,
+            L1BatchCommitmentMode::Rollup,
         );
 
         let commitment = L1BatchCommitment {
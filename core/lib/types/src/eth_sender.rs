@@ -1,5 +1,38 @@
+use serde::{Deserialize, Serialize};
+
 use crate::{aggregated_operations::AggregatedActionType, Address, Nonce, H256};
 
+/// Blob carried by an EIP-4844 transaction together with its KZG commitment and proof.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct SidecarBlob {
+    pub blob: Vec<u8>,
+    pub commitment: Vec<u8>,
+    pub proof: Vec<u8>,
+    pub versioned_hash: H256,
+}
+
+impl std::fmt::Debug for SidecarBlob {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Do not print `blob`
+        f.debug_struct("SidecarBlob")
+            .field("versioned_hash", &self.versioned_hash)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Sidecar of an EIP-4844 transaction, i.e. data that is propagated with the transaction, but is not
+/// a part of its signed payload.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EthTxBlobSidecar {
+    pub blobs: Vec<SidecarBlob>,
+}
+
+impl EthTxBlobSidecar {
+    pub fn versioned_hashes(&self) -> Vec<H256> {
+        self.blobs.iter().map(|blob| blob.versioned_hash).collect()
+    }
+}
+
 #[derive(Clone)]
 pub struct EthTx {
     pub id: u32,
@@ -9,6 +42,10 @@ pub struct EthTx {
     pub tx_type: AggregatedActionType,
     pub created_at_timestamp: u64,
     pub predicted_gas_cost: u64,
+    /// Blobs published by the transaction. If set, the transaction is sent as an EIP-4844 transaction.
+    pub blob_sidecar: Option<EthTxBlobSidecar>,
+    /// Operator account sending the transaction; `None` corresponds to the main operator account.
+    pub from_addr: Option<Address>,
 }
 
 impl std::fmt::Debug for EthTx {
@@ -21,6 +58,8 @@ impl std::fmt::Debug for EthTx {
             .field("tx_type", &self.tx_type)
             .field("created_at_timestamp", &self.created_at_timestamp)
             .field("predicted_gas_cost", &self.predicted_gas_cost)
+            .field("blob_sidecar", &self.blob_sidecar)
+            .field("from_addr", &self.from_addr)
             .finish()
     }
 }
@@ -34,6 +73,8 @@ pub struct TxHistory {
     pub tx_hash: H256,
     pub signed_raw_tx: Vec<u8>,
     pub sent_at_block: Option<u32>,
+    /// Max fee per blob gas; only set for EIP-4844 transactions.
+    pub blob_base_fee_per_gas: Option<u64>,
 }
 
 #[derive(Clone, Debug)]
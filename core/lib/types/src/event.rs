@@ -2,13 +2,14 @@ use std::fmt::Debug;
 
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
-use zksync_basic_types::ethabi::Token;
+use zksync_basic_types::{ethabi::Token, H2048};
 use zksync_utils::{h256_to_account_address, u256_to_bytes_be, u256_to_h256};
 
 use crate::{
     ethabi,
     l2_to_l1_log::L2ToL1Log,
     tokens::{TokenInfo, TokenMetadata},
+    web3::signing::keccak256,
     Address, L1BatchNumber, CONTRACT_DEPLOYER_ADDRESS, H256, KNOWN_CODES_STORAGE_ADDRESS,
     L1_MESSENGER_ADDRESS, U256,
 };
@@ -333,6 +334,32 @@ pub fn extract_published_bytecodes(all_generated_events: &[VmEvent]) -> Vec<H256
         .collect()
 }
 
+/// Returns indices of the 3 bits set in a logs bloom for the specified value (an address or a topic),
+/// as defined in the Ethereum yellow paper. Bit indices are counted from the least significant bit
+/// of the big-endian bloom representation.
+pub fn bloom_bit_indices(value: &[u8]) -> [usize; 3] {
+    let hash = keccak256(value);
+    [0, 1, 2].map(|i| (usize::from(hash[2 * i]) << 8 | usize::from(hash[2 * i + 1])) & 2047)
+}
+
+/// Computes the logs bloom for the provided events. Both event addresses and indexed topics
+/// are added to the bloom, same as on Ethereum.
+pub fn logs_bloom<'a>(events: impl IntoIterator<Item = &'a VmEvent>) -> H2048 {
+    let mut bloom = H2048::zero();
+    let mut accrue = |value: &[u8]| {
+        for bit in bloom_bit_indices(value) {
+            bloom.0[255 - bit / 8] |= 1 << (bit % 8);
+        }
+    };
+    for event in events {
+        accrue(event.address.as_bytes());
+        for topic in &event.indexed_topics {
+            accrue(topic.as_bytes());
+        }
+    }
+    bloom
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct VmEventGroupKey {
     pub address: Address,
@@ -343,7 +370,7 @@ pub struct VmEventGroupKey {
 mod tests {
     use zksync_basic_types::{
         ethabi::{self, Token},
-        Address, L1BatchNumber, U256,
+        Address, L1BatchNumber, H256, U256,
     };
     use zksync_system_constants::{
         BOOTLOADER_ADDRESS, KNOWN_CODES_STORAGE_ADDRESS, L1_MESSENGER_ADDRESS, L2_ETH_TOKEN_ADDRESS,
@@ -351,8 +378,8 @@ mod tests {
     use zksync_utils::u256_to_h256;
 
     use super::{
-        extract_bytecode_publication_requests_from_l1_messenger,
-        extract_l2tol1logs_from_l1_messenger, L1MessengerBytecodePublicationRequest,
+        bloom_bit_indices, extract_bytecode_publication_requests_from_l1_messenger,
+        extract_l2tol1logs_from_l1_messenger, logs_bloom, L1MessengerBytecodePublicationRequest,
         L1MessengerL2ToL1Log,
     };
     use crate::VmEvent;
@@ -502,4 +529,32 @@ mod tests {
 
         assert_eq!(expected, logs);
     }
+
+    #[test]
+    fn computing_logs_bloom() {
+        let event = VmEvent {
+            address: Address::repeat_byte(0x11),
+            indexed_topics: vec![H256::repeat_byte(0x22), H256::repeat_byte(0x33)],
+            ..VmEvent::default()
+        };
+        let bloom = logs_bloom([&event]);
+        let is_set = |bit: usize| bloom.0[255 - bit / 8] & (1 << (bit % 8)) != 0;
+
+        assert!(bloom_bit_indices(event.address.as_bytes())
+            .into_iter()
+            .all(is_set));
+        for topic in &event.indexed_topics {
+            assert!(bloom_bit_indices(topic.as_bytes()).into_iter().all(is_set));
+        }
+        let set_bits: u32 = bloom.0.iter().map(|byte| byte.count_ones()).sum();
+        assert!(set_bits > 0 && set_bits <= 9);
+
+        let other_event = VmEvent {
+            address: Address::repeat_byte(0x44),
+            ..VmEvent::default()
+        };
+        let combined_bloom = logs_bloom([&event, &other_event]);
+        assert_eq!(combined_bloom, bloom | logs_bloom([&other_event]));
+        assert_eq!(logs_bloom([]), Default::default());
+    }
 }
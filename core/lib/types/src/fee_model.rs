@@ -1,6 +1,10 @@
+use std::num::NonZeroU64;
+
 use serde::{Deserialize, Serialize};
 use zksync_system_constants::L1_GAS_PER_PUBDATA_BYTE;
 
+use crate::U256;
+
 /// Fee input to be provided into the VM. It contains two options:
 /// - `L1Pegged`: L1 gas price is provided to the VM, and the pubdata price is derived from it. Using this option is required for the
 /// versions of Era prior to 1.4.1 integration.
@@ -183,3 +187,71 @@ impl FeeParams {
         })
     }
 }
+
+/// Conversion ratio from ETH to the base token of the chain, i.e. the token used to pay fees. 1 wei is worth
+/// `numerator / denominator` of the smallest base token units. For chains paying fees in ETH, the ratio is 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BaseTokenConversionRatio {
+    pub numerator: NonZeroU64,
+    pub denominator: NonZeroU64,
+}
+
+impl Default for BaseTokenConversionRatio {
+    fn default() -> Self {
+        Self::new(1, 1).unwrap()
+    }
+}
+
+impl BaseTokenConversionRatio {
+    /// Returns `None` if either of the ratio parts is zero.
+    pub fn new(numerator: u64, denominator: u64) -> Option<Self> {
+        Some(Self {
+            numerator: NonZeroU64::new(numerator)?,
+            denominator: NonZeroU64::new(denominator)?,
+        })
+    }
+
+    /// Converts a price in wei to the base token units, rounding down. Saturates at `u64::MAX`.
+    pub fn convert(&self, wei: u64) -> u64 {
+        let converted =
+            U256::from(wei) * U256::from(self.numerator.get()) / U256::from(self.denominator.get());
+        if converted > U256::from(u64::MAX) {
+            u64::MAX
+        } else {
+            converted.as_u64()
+        }
+    }
+
+    /// Converts an amount in the base token units back to wei, rounding down.
+    pub fn convert_to_wei(&self, amount: U256) -> U256 {
+        amount * U256::from(self.denominator.get()) / U256::from(self.numerator.get())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converting_to_base_token() {
+        let ratio = BaseTokenConversionRatio::default();
+        assert_eq!(ratio.convert(1_000), 1_000);
+
+        let ratio = BaseTokenConversionRatio::new(3, 2).unwrap();
+        assert_eq!(ratio.convert(1_000), 1_500);
+        assert_eq!(ratio.convert(1), 1);
+        assert_eq!(ratio.convert(u64::MAX), u64::MAX);
+
+        let ratio = BaseTokenConversionRatio::new(1, 1_000).unwrap();
+        assert_eq!(ratio.convert(1_000_000), 1_000);
+        assert_eq!(ratio.convert(999), 0);
+        assert_eq!(ratio.convert_to_wei(1_000.into()), 1_000_000.into());
+
+        let ratio = BaseTokenConversionRatio::new(3, 2).unwrap();
+        assert_eq!(ratio.convert_to_wei(1_500.into()), 1_000.into());
+        assert_eq!(ratio.convert_to_wei(1.into()), 0.into());
+
+        assert_eq!(BaseTokenConversionRatio::new(0, 1), None);
+        assert_eq!(BaseTokenConversionRatio::new(1, 0), None);
+    }
+}
@@ -0,0 +1,52 @@
+//! Atomic bundles of L2 transactions submitted via `zks_sendBundle`.
+
+use crate::{l2::L2Tx, web3::signing::keccak256, Address, Nonce, H256};
+
+/// Ordered bundle of L2 transactions. Bundle transactions must be included into a single miniblock
+/// contiguously and in the specified order, or not included at all.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransactionBundle {
+    /// Bundle hash; see [`Self::compute_hash()`].
+    pub hash: H256,
+    pub transactions: Vec<L2Tx>,
+}
+
+impl TransactionBundle {
+    pub fn new(transactions: Vec<L2Tx>) -> Self {
+        Self {
+            hash: Self::compute_hash(transactions.iter().map(L2Tx::hash)),
+            transactions,
+        }
+    }
+
+    /// Computes the bundle hash as `keccak256` of the concatenated hashes of bundle transactions.
+    pub fn compute_hash(tx_hashes: impl Iterator<Item = H256>) -> H256 {
+        let preimage: Vec<u8> = tx_hashes.flat_map(|hash| hash.0).collect();
+        H256(keccak256(&preimage))
+    }
+
+    /// Returns initiator accounts and nonces of bundle transactions in the bundle order.
+    pub fn account_nonces(&self) -> impl Iterator<Item = (Address, Nonce)> + '_ {
+        self.transactions
+            .iter()
+            .map(|tx| (tx.initiator_account(), tx.nonce()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundle_hash_depends_on_tx_order() {
+        let first_hash = H256::repeat_byte(1);
+        let second_hash = H256::repeat_byte(2);
+        let hash = TransactionBundle::compute_hash([first_hash, second_hash].into_iter());
+        let reversed_hash = TransactionBundle::compute_hash([second_hash, first_hash].into_iter());
+        assert_ne!(hash, reversed_hash);
+
+        let mut preimage = first_hash.as_bytes().to_vec();
+        preimage.extend_from_slice(second_hash.as_bytes());
+        assert_eq!(hash, H256(keccak256(&preimage)));
+    }
+}
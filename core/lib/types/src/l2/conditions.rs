@@ -0,0 +1,220 @@
+//! Preconditions for transactions submitted via `eth_sendRawTransactionConditional`.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use zksync_utils::u256_to_h256;
+
+use crate::{
+    utils::storage_key_for_eth_balance, web3::types::U64, AccountTreeId, Address, MiniblockNumber,
+    StorageKey, H256, U256,
+};
+
+/// Conditions that must hold for a transaction to be included into a miniblock. Conditions are checked
+/// when the transaction is submitted, and once again by the state keeper right before the transaction
+/// is executed. A transaction with failed conditions is rejected.
+///
+/// Unlike on Ethereum L2s with Merkle Patricia storage, account storage roots cannot be used
+/// as a condition; only individual storage slots and balances are supported.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionConditions {
+    /// Expected values of storage slots keyed by the account address.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub known_accounts: BTreeMap<Address, BTreeMap<H256, H256>>,
+    /// Expected ETH balances of accounts.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub known_balances: BTreeMap<Address, U256>,
+    /// Minimum miniblock number (inclusive).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_number_min: Option<U64>,
+    /// Maximum miniblock number (inclusive).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_number_max: Option<U64>,
+    /// Minimum miniblock timestamp (inclusive).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp_min: Option<U64>,
+    /// Maximum miniblock timestamp (inclusive).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp_max: Option<U64>,
+}
+
+/// Error returned if [`TransactionConditions`] do not hold.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TransactionConditionsError {
+    #[error("miniblock number {0} is outside the allowed range")]
+    BlockNumber(MiniblockNumber),
+    #[error("miniblock timestamp {0} is outside the allowed range")]
+    Timestamp(u64),
+    #[error("storage slot {slot:?} of account {address:?} has an unexpected value")]
+    StorageSlot { address: Address, slot: H256 },
+    #[error("balance of account {0:?} has an unexpected value")]
+    Balance(Address),
+}
+
+impl TransactionConditions {
+    /// Returns the total number of storage slots and balances referenced by the conditions.
+    pub fn storage_conditions_count(&self) -> usize {
+        let slots_count: usize = self.known_accounts.values().map(BTreeMap::len).sum();
+        slots_count + self.known_balances.len()
+    }
+
+    /// Returns all storage keys that need to be read in order to check the conditions.
+    pub fn storage_keys(&self) -> Vec<StorageKey> {
+        let slot_keys = self.known_accounts.iter().flat_map(|(&address, slots)| {
+            let account = AccountTreeId::new(address);
+            slots
+                .keys()
+                .map(move |&slot| StorageKey::new(account, slot))
+        });
+        let balance_keys = self.known_balances.keys().map(storage_key_for_eth_balance);
+        slot_keys.chain(balance_keys).collect()
+    }
+
+    /// Checks block-related conditions for a miniblock with the specified number and timestamp.
+    pub fn check_block(
+        &self,
+        number: MiniblockNumber,
+        timestamp: u64,
+    ) -> Result<(), TransactionConditionsError> {
+        let block_number = U64::from(number.0);
+        let is_number_too_low = self
+            .block_number_min
+            .map_or(false, |min| block_number < min);
+        let is_number_too_high = self
+            .block_number_max
+            .map_or(false, |max| block_number > max);
+        if is_number_too_low || is_number_too_high {
+            return Err(TransactionConditionsError::BlockNumber(number));
+        }
+
+        let block_timestamp = U64::from(timestamp);
+        let is_timestamp_too_low = self
+            .timestamp_min
+            .map_or(false, |min| block_timestamp < min);
+        let is_timestamp_too_high = self
+            .timestamp_max
+            .map_or(false, |max| block_timestamp > max);
+        if is_timestamp_too_low || is_timestamp_too_high {
+            return Err(TransactionConditionsError::Timestamp(timestamp));
+        }
+        Ok(())
+    }
+
+    /// Checks storage-related conditions using the provided function to read storage values.
+    pub fn check_storage(
+        &self,
+        mut read_value: impl FnMut(&StorageKey) -> H256,
+    ) -> Result<(), TransactionConditionsError> {
+        for (&address, slots) in &self.known_accounts {
+            let account = AccountTreeId::new(address);
+            for (&slot, &expected_value) in slots {
+                if read_value(&StorageKey::new(account, slot)) != expected_value {
+                    return Err(TransactionConditionsError::StorageSlot { address, slot });
+                }
+            }
+        }
+        for (address, &expected_balance) in &self.known_balances {
+            let balance_key = storage_key_for_eth_balance(address);
+            if read_value(&balance_key) != u256_to_h256(expected_balance) {
+                return Err(TransactionConditionsError::Balance(*address));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn deserializing_conditions() {
+        let json = serde_json::json!({
+            "knownAccounts": {
+                "0x0000000000000000000000000000000000000001": {
+                    "0x0000000000000000000000000000000000000000000000000000000000000002":
+                        "0x0000000000000000000000000000000000000000000000000000000000000003",
+                },
+            },
+            "blockNumberMax": "0x10",
+            "timestampMin": "0x64",
+        });
+        let conditions: TransactionConditions = serde_json::from_value(json).unwrap();
+        assert_eq!(conditions.storage_conditions_count(), 1);
+        assert_eq!(conditions.block_number_max, Some(U64::from(16)));
+        assert_eq!(conditions.timestamp_min, Some(U64::from(100)));
+        assert_eq!(conditions.block_number_min, None);
+        assert!(conditions.known_balances.is_empty());
+    }
+
+    #[test]
+    fn checking_block_conditions() {
+        let conditions = TransactionConditions {
+            block_number_min: Some(10.into()),
+            block_number_max: Some(20.into()),
+            timestamp_max: Some(1_000.into()),
+            ..TransactionConditions::default()
+        };
+        conditions.check_block(MiniblockNumber(10), 1_000).unwrap();
+        conditions.check_block(MiniblockNumber(20), 0).unwrap();
+        assert_eq!(
+            conditions.check_block(MiniblockNumber(9), 0),
+            Err(TransactionConditionsError::BlockNumber(MiniblockNumber(9)))
+        );
+        assert_eq!(
+            conditions.check_block(MiniblockNumber(21), 0),
+            Err(TransactionConditionsError::BlockNumber(MiniblockNumber(21)))
+        );
+        assert_eq!(
+            conditions.check_block(MiniblockNumber(15), 1_001),
+            Err(TransactionConditionsError::Timestamp(1_001))
+        );
+    }
+
+    #[test]
+    fn checking_storage_conditions() {
+        let address = Address::repeat_byte(1);
+        let slot = H256::repeat_byte(2);
+        let conditions = TransactionConditions {
+            known_accounts: BTreeMap::from([(
+                address,
+                BTreeMap::from([(slot, H256::repeat_byte(3))]),
+            )]),
+            known_balances: BTreeMap::from([(address, U256::from(100))]),
+            ..TransactionConditions::default()
+        };
+        assert_eq!(conditions.storage_keys().len(), 2);
+
+        let mut storage = HashMap::from([
+            (
+                StorageKey::new(AccountTreeId::new(address), slot),
+                H256::repeat_byte(3),
+            ),
+            (
+                storage_key_for_eth_balance(&address),
+                u256_to_h256(100.into()),
+            ),
+        ]);
+        let read_value = |storage: &HashMap<_, _>, key: &StorageKey| {
+            storage.get(key).copied().unwrap_or_default()
+        };
+        conditions
+            .check_storage(|key| read_value(&storage, key))
+            .unwrap();
+
+        storage.insert(storage_key_for_eth_balance(&address), H256::zero());
+        assert_eq!(
+            conditions.check_storage(|key| read_value(&storage, key)),
+            Err(TransactionConditionsError::Balance(address))
+        );
+        storage.clear();
+        assert_eq!(
+            conditions.check_storage(|key| read_value(&storage, key)),
+            Err(TransactionConditionsError::StorageSlot { address, slot })
+        );
+    }
+}
@@ -4,7 +4,7 @@ use num_enum::TryFromPrimitive;
 use rlp::{Rlp, RlpStream};
 use serde::{Deserialize, Serialize};
 
-use self::error::SignError;
+use self::{conditions::TransactionConditions, error::SignError};
 use crate::{
     api,
     api::TransactionRequest,
@@ -19,6 +19,8 @@ use crate::{
     U256,
 };
 
+pub mod bundle;
+pub mod conditions;
 pub mod error;
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, TryFromPrimitive)]
@@ -50,6 +52,9 @@ pub struct L2TxCommonData {
     pub input: Option<InputData>,
 
     pub paymaster_params: PaymasterParams,
+    /// Inclusion conditions for transactions submitted via `eth_sendRawTransactionConditional`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conditions: Option<Box<TransactionConditions>>,
 }
 
 impl L2TxCommonData {
@@ -73,6 +78,7 @@ impl L2TxCommonData {
             transaction_type,
             input,
             paymaster_params,
+            conditions: None,
         }
     }
 
@@ -124,6 +130,7 @@ impl Default for L2TxCommonData {
             transaction_type: TransactionType::EIP712Transaction,
             input: Default::default(),
             paymaster_params: Default::default(),
+            conditions: None,
         }
     }
 }
@@ -163,6 +170,7 @@ impl L2Tx {
                 transaction_type: TransactionType::EIP712Transaction,
                 input: None,
                 paymaster_params,
+                conditions: None,
             },
             received_timestamp_ms: unix_timestamp_ms(),
             raw_bytes: None,
@@ -487,6 +495,7 @@ mod tests {
                 transaction_type: TransactionType::LegacyTransaction,
                 input: None,
                 paymaster_params: PaymasterParams::default(),
+                conditions: None,
             },
             received_timestamp_ms: Default::default(),
             raw_bytes: None,
@@ -196,6 +196,8 @@ pub struct GovernanceOperation {
     pub predecessor: H256,
     /// The value used for creating unique operation hashes.
     pub salt: H256,
+    /// Delay (in seconds) after scheduling before the operation can be executed.
+    pub delay: u64,
 }
 
 /// Protocol upgrade proposal from L1.
@@ -502,6 +504,13 @@ impl TryFrom<Log> for GovernanceOperation {
         let mut decoded = decode(&[ParamType::Uint(256), operation_param_type], &event.data.0)?;
         // Extract `GovernanceOperation` data.
         let mut decoded_governance_operation = decoded.remove(1).into_tuple().unwrap();
+        // Delays not fitting into `u64` are effectively infinite.
+        let delay = decoded.remove(0).into_uint().unwrap();
+        let delay = if delay > U256::from(u64::MAX) {
+            u64::MAX
+        } else {
+            delay.as_u64()
+        };
 
         let eth_hash = event
             .transaction_hash
@@ -547,6 +556,7 @@ impl TryFrom<Log> for GovernanceOperation {
             calls,
             predecessor,
             salt,
+            delay,
         })
     }
 }
@@ -722,7 +732,7 @@ mod tests {
             Token::FixedBytes(H256::random().0.to_vec()),
             Token::FixedBytes(H256::random().0.to_vec()),
         ]);
-        let event_data = encode(&[Token::Uint(U256::zero()), operation_token]);
+        let event_data = encode(&[Token::Uint(U256::from(86_400)), operation_token]);
 
         let correct_log = Log {
             address: Default::default(),
@@ -739,6 +749,7 @@ mod tests {
         };
         let decoded_op: GovernanceOperation = correct_log.clone().try_into().unwrap();
         assert_eq!(decoded_op.calls.len(), 1);
+        assert_eq!(decoded_op.delay, 86_400);
 
         let mut incorrect_log = correct_log;
         incorrect_log
@@ -2,7 +2,7 @@ use std::convert::TryFrom;
 
 use anyhow::Context;
 use serde::{Deserialize, Serialize};
-use zksync_basic_types::{AccountTreeId, L1BatchNumber, MiniblockNumber, H256};
+use zksync_basic_types::{AccountTreeId, L1BatchNumber, MiniblockNumber, H256, U256};
 use zksync_protobuf::{required, ProtoFmt};
 
 use crate::{commitment::L1BatchWithMetadata, Bytes, StorageKey, StorageValue};
@@ -76,6 +76,42 @@ pub struct SnapshotStorageLog {
     pub enumeration_index: u64,
 }
 
+/// Header of a Merkle tree snapshot persisted in the object store. The header is uploaded after
+/// all snapshot chunks, so its presence signals that the snapshot is complete.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MerkleTreeSnapshotHeader {
+    /// L1 batch for the snapshot. The snapshot captures the tree state after this batch.
+    pub l1_batch_number: L1BatchNumber,
+    /// Root hash of the tree.
+    pub root_hash: H256,
+    /// Total number of leaves in the tree.
+    pub leaf_count: u64,
+    /// Number of chunks in the snapshot. Chunks split the hashed key space into equal ranges.
+    pub chunk_count: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MerkleTreeSnapshotChunkKey {
+    pub l1_batch_number: L1BatchNumber,
+    pub chunk_id: u64,
+}
+
+/// Chunk of Merkle tree leaves with hashed keys in a certain range.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleTreeSnapshotChunk {
+    /// Entries ordered by increasing hashed key.
+    pub entries: Vec<MerkleTreeSnapshotEntry>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleTreeSnapshotEntry {
+    pub hashed_key: U256,
+    pub value: H256,
+    pub leaf_index: u64,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct SnapshotFactoryDependencies {
     pub factory_deps: Vec<SnapshotFactoryDependency>,
@@ -1,7 +1,8 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     fmt,
     fmt::Display,
+    time::Duration,
 };
 
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
@@ -9,7 +10,7 @@ use zk_evm::zkevm_opcode_defs::FarCallOpcode;
 use zksync_system_constants::BOOTLOADER_ADDRESS;
 use zksync_utils::u256_to_h256;
 
-use crate::{Address, U256};
+use crate::{Address, H256, U256};
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum VmTrace {
@@ -52,6 +53,103 @@ pub enum MemoryDirection {
     Write,
 }
 
+/// Single VM step recorded by the struct log tracer, modeled after Geth's `structLogs` entries.
+/// EraVM is a register machine, so register values are reported in place of the EVM stack.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLog {
+    /// Address of the contract executing the step.
+    pub address: Address,
+    pub pc: u16,
+    pub op: String,
+    /// Ergs remaining in the current frame before the step.
+    pub gas: u32,
+    /// Ergs spent by the step itself (not including ergs passed to a called frame).
+    pub gas_cost: u32,
+    /// Depth of the VM call stack, including near call frames.
+    pub depth: usize,
+    /// General-purpose register values before the step.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stack: Option<Vec<U256>>,
+    /// Heap words of the current frame before the step.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<Vec<H256>>,
+}
+
+/// Options of the struct log tracer, matching Geth's default logger config.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLogConfig {
+    #[serde(default)]
+    pub disable_stack: bool,
+    #[serde(default)]
+    pub enable_memory: bool,
+    /// Maximum number of recorded steps; `0` means no limit.
+    #[serde(default)]
+    pub limit: usize,
+}
+
+/// Hard caps on the data collected by the struct log tracer. Unlike [`StructLogConfig`], these are set
+/// by the node rather than by the client; exceeding any of them aborts tracing with an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StructLogLimits {
+    /// Maximum number of recorded steps.
+    pub max_steps: usize,
+    /// Maximum approximate size of recorded steps in bytes, including captured stack and memory.
+    pub max_captured_bytes: usize,
+}
+
+impl Default for StructLogLimits {
+    fn default() -> Self {
+        Self {
+            max_steps: 500_000,
+            max_captured_bytes: 256 << 20,
+        }
+    }
+}
+
+/// Output of `4byteTracer`: number of calls keyed by the function selector and the size of call arguments,
+/// e.g. `0xa9059cbb-64`.
+pub type FourByteTrace = BTreeMap<String, usize>;
+
+/// Records a call in the `4byteTracer` output. `selector` must contain the first (up to 4) bytes of calldata.
+/// Calls without a function selector, calls made by the bootloader and calls to system contracts are ignored,
+/// similarly to how Geth ignores calls to precompiles.
+pub fn record_four_byte_call(
+    trace: &mut FourByteTrace,
+    from: Address,
+    to: Address,
+    selector: &[u8],
+    calldata_len: usize,
+) {
+    let is_system_contract = to.as_bytes()[..18].iter().all(|&byte| byte == 0);
+    if selector.len() < 4 || from == BOOTLOADER_ADDRESS || is_system_contract {
+        return;
+    }
+    let key = format!("0x{}-{}", hex::encode(&selector[..4]), calldata_len - 4);
+    *trace.entry(key).or_default() += 1;
+}
+
+/// Resource limits applied when tracing a transaction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TraceLimits {
+    /// Maximum wall-clock duration of the traced execution.
+    pub timeout: Option<Duration>,
+    /// Maximum approximate size of collected traces in bytes.
+    pub memory_limit: Option<usize>,
+}
+
+/// Trace limit exceeded during execution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum TraceLimitExceeded {
+    #[error("execution timeout of {0:?} exceeded")]
+    Timeout(Duration),
+    #[error("trace memory limit of {0} bytes exceeded")]
+    Memory(usize),
+    #[error("trace step limit of {0} steps exceeded")]
+    Steps(usize),
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ContractSourceDebugInfo {
     pub assembly_code: String,
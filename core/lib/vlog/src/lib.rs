@@ -1,13 +1,19 @@
 //! This module contains the observability subsystem.
 //! It is responsible for providing a centralized interface for consistent observability configuration.
 
-use std::{backtrace::Backtrace, borrow::Cow, panic::PanicInfo};
+use std::{backtrace::Backtrace, borrow::Cow, panic::PanicInfo, sync::OnceLock};
 
 // Temporary re-export of `sentry::capture_message` aiming to simplify the transition from `vlog` to using
 // crates directly.
 pub use sentry::{capture_message, Level as AlertLevel};
 use sentry::{types::Dsn, ClientInitGuard};
-use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt};
+use tracing_subscriber::{
+    filter::ParseError, fmt, layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter,
+    Registry,
+};
+
+/// Handle allowing to change the log filter after the observability subsystem is initialized.
+static LOG_FILTER_HANDLE: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
 
 /// Specifies the format of the logs in stdout.
 #[derive(Debug, Clone, Copy, Default)]
@@ -71,18 +77,20 @@ impl ObservabilityBuilder {
 
     /// Initializes the observability subsystem.
     pub fn build(self) -> ObservabilityGuard {
-        // Initialize logs.
+        // Initialize logs. The filter is wrapped in a reloadable layer so that it can be changed at runtime.
+        let (env_filter, filter_handle) = reload::Layer::new(EnvFilter::from_default_env());
+        LOG_FILTER_HANDLE.set(filter_handle).ok();
         match self.log_format {
             LogFormat::Plain => {
                 tracing_subscriber::registry()
-                    .with(tracing_subscriber::EnvFilter::from_default_env())
+                    .with(env_filter)
                     .with(fmt::Layer::default())
                     .init();
             }
             LogFormat::Json => {
                 let timer = tracing_subscriber::fmt::time::UtcTime::rfc_3339();
                 tracing_subscriber::registry()
-                    .with(tracing_subscriber::EnvFilter::from_default_env())
+                    .with(env_filter)
                     .with(
                         fmt::Layer::default()
                             .with_file(true)
@@ -125,6 +133,53 @@ impl ObservabilityBuilder {
     }
 }
 
+/// Error returned by [`set_log_filter()`].
+#[derive(Debug)]
+pub enum LogFilterError {
+    /// Logs were not initialized using [`ObservabilityBuilder`].
+    NotInitialized,
+    /// Filter directives cannot be parsed.
+    Parse(ParseError),
+    /// Error reloading the filter.
+    Reload(reload::Error),
+}
+
+impl std::fmt::Display for LogFilterError {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotInitialized => formatter.write_str("logs are not initialized"),
+            Self::Parse(err) => write!(formatter, "invalid log filter directives: {err}"),
+            Self::Reload(err) => write!(formatter, "failed reloading log filter: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for LogFilterError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::NotInitialized => None,
+            Self::Parse(err) => Some(err),
+            Self::Reload(err) => Some(err),
+        }
+    }
+}
+
+/// Replaces the log filter set up by [`ObservabilityBuilder::build()`]. Directives have the same format
+/// as the `RUST_LOG` env variable, e.g. `zksync_core=debug,info`.
+pub fn set_log_filter(directives: &str) -> Result<(), LogFilterError> {
+    let filter = EnvFilter::try_new(directives).map_err(LogFilterError::Parse)?;
+    let handle = LOG_FILTER_HANDLE
+        .get()
+        .ok_or(LogFilterError::NotInitialized)?;
+    handle.reload(filter).map_err(LogFilterError::Reload)
+}
+
+/// Returns the currently active log filter, or `None` if logs were not initialized using [`ObservabilityBuilder`].
+pub fn log_filter() -> Option<String> {
+    let handle = LOG_FILTER_HANDLE.get()?;
+    handle.with_current(ToString::to_string).ok()
+}
+
 /// Loads the log format from the environment variable according to the existing zkSync configuration scheme.
 /// If the variable is not set, the default value is used.
 ///
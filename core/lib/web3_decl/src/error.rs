@@ -1,7 +1,10 @@
 //! Definition of errors that can occur in the zkSync Web3 API.
 
 use thiserror::Error;
-use zksync_types::api::SerializationTransactionError;
+use zksync_types::{
+    api::SerializationTransactionError, vm_trace::TraceLimitExceeded, L1BatchNumber,
+    MiniblockNumber,
+};
 
 #[derive(Debug, Error)]
 pub enum Web3Error {
@@ -33,8 +36,61 @@ pub enum Web3Error {
     NotImplemented,
     #[error("Query returned more than {0} results. Try with this block range [{1:#x}, {2:#x}].")]
     LogsLimitExceeded(usize, u32, u32),
+    #[error(
+        "Block range exceeds the limit of {0} blocks. Try with this block range [{1:#x}, {2:#x}]."
+    )]
+    LogsBlockRangeExceeded(u64, u32, u32),
     #[error("invalid filter: if blockHash is supplied fromBlock and toBlock must not be")]
     InvalidFilterBlockHash,
     #[error("Tree API is not available")]
     TreeApiUnavailable,
+    #[error("Merkle tree data for L1 batch #{0} is not available; it may be pruned or not processed yet")]
+    TreeVersionUnavailable(L1BatchNumber),
+    #[error(
+        "Merkle tree lags behind by {lag} L1 batches (at most {max_lag} is allowed); \
+         proofs would be stale, try again later"
+    )]
+    TreeLagExceeded { lag: u32, max_lag: u32 },
+    #[error("Block data is pruned; the earliest available block is #{0}")]
+    PrunedBlock(MiniblockNumber),
+    #[error("L1 batch data is pruned; the earliest available L1 batch is #{0}")]
+    PrunedL1Batch(L1BatchNumber),
+    #[error("Invalid state override: {0}")]
+    InvalidStateOverride(String),
+    #[error("Invalid tracer config: {0}")]
+    InvalidTracerConfig(String),
+    #[error("Invalid trace filter: {0}")]
+    InvalidTraceFilter(String),
+    #[error("Invalid simulation payload: {0}")]
+    InvalidSimulatePayload(String),
+    #[error("Invalid pagination params: {0}")]
+    InvalidPagination(String),
+    #[error("Too many proofs requested; at most {0} proofs can be requested at once")]
+    TooManyProofRequests(usize),
+    #[error("Too many calls to trace; at most {0} calls can be traced at once")]
+    TooManyTraceCalls(usize),
+    #[error("Tracing aborted: {0}")]
+    TraceLimitExceeded(#[from] TraceLimitExceeded),
+    #[error("Invalid log filter: {0}")]
+    InvalidLogFilter(String),
+    #[error("Sealing cannot be controlled by this node")]
+    SealingControlUnavailable,
+    #[error("Invalid fee model settings: {0}")]
+    InvalidFeeModelSettings(String),
+    #[error("Fee model settings cannot be changed on this node")]
+    FeeModelSettingsUnavailable,
+    #[error("Invalid aggregation policy: {0}")]
+    InvalidAggregationPolicy(String),
+    #[error("Aggregation policy cannot be changed on this node")]
+    AggregationPolicyUnavailable,
+    #[error("{0}")]
+    ReplacementUnderpriced(String),
+    #[error("{0}")]
+    TxPolicyViolation(String),
+    #[error("{0}")]
+    PendingTxsLimitExceeded(String),
+    #[error("dropped: {0}")]
+    TransactionDropped(String),
+    #[error("call compute limit exceeded: call has used up its gas limit of {0}")]
+    CallComputeLimitExceeded(u32),
 }
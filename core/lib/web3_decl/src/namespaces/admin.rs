@@ -0,0 +1,68 @@
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use zksync_types::{
+    api::{
+        AggregationPolicy, AggregationPolicyUpdate, FeeModelSettings, FeeModelSettingsUpdate,
+        L1GasPriceStats, NodeInfo,
+    },
+    Address, H256,
+};
+
+#[cfg_attr(
+    all(feature = "client", feature = "server"),
+    rpc(server, client, namespace = "admin")
+)]
+#[cfg_attr(
+    all(feature = "client", not(feature = "server")),
+    rpc(client, namespace = "admin")
+)]
+#[cfg_attr(
+    all(not(feature = "client"), feature = "server"),
+    rpc(server, namespace = "admin")
+)]
+pub trait AdminNamespace {
+    #[method(name = "nodeInfo")]
+    async fn node_info(&self) -> RpcResult<NodeInfo>;
+
+    #[method(name = "setLogFilter")]
+    fn set_log_filter(&self, directives: String) -> RpcResult<()>;
+
+    #[method(name = "flushMempoolSender")]
+    async fn flush_mempool_sender(&self, address: Address) -> RpcResult<Vec<H256>>;
+
+    #[method(name = "removeTransaction")]
+    async fn remove_transaction(&self, tx_hash: H256, reason: String) -> RpcResult<bool>;
+
+    #[method(name = "removeSenderTransactions")]
+    async fn remove_sender_transactions(
+        &self,
+        address: Address,
+        reason: String,
+    ) -> RpcResult<Vec<H256>>;
+
+    #[method(name = "stopSealing")]
+    fn stop_sealing(&self) -> RpcResult<bool>;
+
+    #[method(name = "resumeSealing")]
+    fn resume_sealing(&self) -> RpcResult<bool>;
+
+    #[method(name = "getL1GasPriceStats")]
+    fn get_l1_gas_price_stats(&self) -> RpcResult<L1GasPriceStats>;
+
+    #[method(name = "getFeeModelSettings")]
+    fn get_fee_model_settings(&self) -> RpcResult<FeeModelSettings>;
+
+    #[method(name = "updateFeeModelSettings")]
+    async fn update_fee_model_settings(
+        &self,
+        update: FeeModelSettingsUpdate,
+    ) -> RpcResult<FeeModelSettings>;
+
+    #[method(name = "getAggregationPolicy")]
+    fn get_aggregation_policy(&self) -> RpcResult<AggregationPolicy>;
+
+    #[method(name = "updateAggregationPolicy")]
+    fn update_aggregation_policy(
+        &self,
+        update: AggregationPolicyUpdate,
+    ) -> RpcResult<AggregationPolicy>;
+}
@@ -1,10 +1,13 @@
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
 use zksync_types::{
-    api::{BlockId, BlockNumber, DebugCall, ResultDebugCall, TracerConfig},
+    api::{
+        BlockId, BlockNumber, DebugCall, DebugTrace, ResultDebugCall, TraceCallConfig,
+        TracerConfig, TransactionReplay,
+    },
     transaction_request::CallRequest,
 };
 
-use crate::types::H256;
+use crate::types::{Bytes, H256};
 
 #[cfg_attr(
     all(feature = "client", feature = "server"),
@@ -36,12 +39,27 @@ pub trait DebugNamespace {
         &self,
         request: CallRequest,
         block: Option<BlockId>,
-        options: Option<TracerConfig>,
-    ) -> RpcResult<DebugCall>;
+        options: Option<TraceCallConfig>,
+    ) -> RpcResult<DebugTrace>;
+    #[method(name = "traceCallMany")]
+    async fn trace_call_many(
+        &self,
+        requests: Vec<CallRequest>,
+        block: Option<BlockId>,
+        options: Option<TraceCallConfig>,
+    ) -> RpcResult<Vec<DebugCall>>;
     #[method(name = "traceTransaction")]
     async fn trace_transaction(
         &self,
         tx_hash: H256,
         options: Option<TracerConfig>,
-    ) -> RpcResult<Option<DebugCall>>;
+    ) -> RpcResult<Option<DebugTrace>>;
+    #[method(name = "replayTransaction")]
+    async fn replay_transaction(&self, tx_hash: H256) -> RpcResult<Option<TransactionReplay>>;
+    #[method(name = "getRawTransaction")]
+    async fn get_raw_transaction(&self, tx_hash: H256) -> RpcResult<Option<Bytes>>;
+    #[method(name = "getRawBlock")]
+    async fn get_raw_block(&self, block: BlockId) -> RpcResult<Bytes>;
+    #[method(name = "getRawReceipts")]
+    async fn get_raw_receipts(&self, block: BlockId) -> RpcResult<Vec<Bytes>>;
 }
@@ -3,14 +3,19 @@ use jsonrpsee::{
     proc_macros::rpc,
 };
 use zksync_types::{
-    api::{BlockIdVariant, BlockNumber, Transaction, TransactionVariant},
+    api::{
+        simulate::{SimulatePayload, SimulatedBlock},
+        AccessListWithGasUsed, AccountProof, BlockId, BlockIdVariant, BlockNumber, StateOverride,
+        Transaction, TransactionVariant,
+    },
+    l2::conditions::TransactionConditions,
     transaction_request::CallRequest,
     Address, H256,
 };
 
 use crate::types::{
-    Block, Bytes, FeeHistory, Filter, FilterChanges, Index, Log, PubSubFilter, SyncState,
-    TransactionReceipt, U256, U64,
+    Block, Bytes, FeeHistory, Filter, FilterChanges, GetLogsResponse, Index, PubSubParams,
+    SyncState, TransactionReceipt, U256, U64,
 };
 
 #[cfg_attr(
@@ -33,14 +38,41 @@ pub trait EthNamespace {
     async fn chain_id(&self) -> RpcResult<U64>;
 
     #[method(name = "call")]
-    async fn call(&self, req: CallRequest, block: Option<BlockIdVariant>) -> RpcResult<Bytes>;
+    async fn call(
+        &self,
+        req: CallRequest,
+        block: Option<BlockIdVariant>,
+        state_override: Option<StateOverride>,
+    ) -> RpcResult<Bytes>;
+
+    #[method(name = "createAccessList")]
+    async fn create_access_list(
+        &self,
+        req: CallRequest,
+        block: Option<BlockIdVariant>,
+    ) -> RpcResult<AccessListWithGasUsed>;
+
+    #[method(name = "simulateV1")]
+    async fn simulate_v1(
+        &self,
+        payload: SimulatePayload,
+        block: Option<BlockIdVariant>,
+    ) -> RpcResult<Vec<SimulatedBlock>>;
 
     #[method(name = "estimateGas")]
-    async fn estimate_gas(&self, req: CallRequest, _block: Option<BlockNumber>) -> RpcResult<U256>;
+    async fn estimate_gas(
+        &self,
+        req: CallRequest,
+        block: Option<BlockNumber>,
+        state_override: Option<StateOverride>,
+    ) -> RpcResult<U256>;
 
     #[method(name = "gasPrice")]
     async fn gas_price(&self) -> RpcResult<U256>;
 
+    #[method(name = "maxPriorityFeePerGas")]
+    async fn max_priority_fee_per_gas(&self) -> RpcResult<U256>;
+
     #[method(name = "newFilter")]
     async fn new_filter(&self, filter: Filter) -> RpcResult<U256>;
 
@@ -54,7 +86,7 @@ pub trait EthNamespace {
     async fn new_pending_transaction_filter(&self) -> RpcResult<U256>;
 
     #[method(name = "getLogs")]
-    async fn get_logs(&self, filter: Filter) -> RpcResult<Vec<Log>>;
+    async fn get_logs(&self, filter: Filter) -> RpcResult<GetLogsResponse>;
 
     #[method(name = "getFilterLogs")]
     async fn get_filter_logs(&self, filter_index: U256) -> RpcResult<FilterChanges>;
@@ -103,6 +135,14 @@ pub trait EthNamespace {
         block: Option<BlockIdVariant>,
     ) -> RpcResult<H256>;
 
+    #[method(name = "getProof")]
+    async fn get_proof(
+        &self,
+        address: Address,
+        keys: Vec<H256>,
+        block: BlockIdVariant,
+    ) -> RpcResult<AccountProof>;
+
     #[method(name = "getTransactionCount")]
     async fn get_transaction_count(
         &self,
@@ -130,12 +170,25 @@ pub trait EthNamespace {
     #[method(name = "getTransactionReceipt")]
     async fn get_transaction_receipt(&self, hash: H256) -> RpcResult<Option<TransactionReceipt>>;
 
+    #[method(name = "getBlockReceipts")]
+    async fn get_block_receipts(
+        &self,
+        block: BlockId,
+    ) -> RpcResult<Option<Vec<TransactionReceipt>>>;
+
     #[method(name = "protocolVersion")]
     async fn protocol_version(&self) -> RpcResult<String>;
 
     #[method(name = "sendRawTransaction")]
     async fn send_raw_transaction(&self, tx_bytes: Bytes) -> RpcResult<H256>;
 
+    #[method(name = "sendRawTransactionConditional")]
+    async fn send_raw_transaction_conditional(
+        &self,
+        tx_bytes: Bytes,
+        conditions: TransactionConditions,
+    ) -> RpcResult<H256>;
+
     #[method(name = "syncing")]
     async fn syncing(&self) -> RpcResult<SyncState>;
 
@@ -173,6 +226,6 @@ pub trait EthNamespace {
 #[rpc(server, namespace = "eth")]
 pub trait EthPubSub {
     #[subscription(name = "subscribe" => "subscription", unsubscribe = "unsubscribe", item = PubSubResult)]
-    async fn subscribe(&self, sub_type: String, filter: Option<PubSubFilter>)
+    async fn subscribe(&self, sub_type: String, params: Option<PubSubParams>)
         -> SubscriptionResult;
 }
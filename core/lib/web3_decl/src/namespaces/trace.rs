@@ -0,0 +1,50 @@
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use zksync_types::{
+    api::{
+        trace::{
+            LocalizedTrace, TraceFilter, TraceResults, TraceResultsWithTransactionHash, TraceType,
+        },
+        BlockId,
+    },
+    transaction_request::CallRequest,
+};
+
+use crate::types::H256;
+
+#[cfg_attr(
+    all(feature = "client", feature = "server"),
+    rpc(server, client, namespace = "trace")
+)]
+#[cfg_attr(
+    all(feature = "client", not(feature = "server")),
+    rpc(client, namespace = "trace")
+)]
+#[cfg_attr(
+    all(not(feature = "client"), feature = "server"),
+    rpc(server, namespace = "trace")
+)]
+pub trait TraceNamespace {
+    #[method(name = "call")]
+    async fn call(
+        &self,
+        request: CallRequest,
+        trace_types: Vec<TraceType>,
+        block: Option<BlockId>,
+    ) -> RpcResult<TraceResults>;
+    #[method(name = "replayTransaction")]
+    async fn replay_transaction(
+        &self,
+        tx_hash: H256,
+        trace_types: Vec<TraceType>,
+    ) -> RpcResult<Option<TraceResults>>;
+    #[method(name = "replayBlockTransactions")]
+    async fn replay_block_transactions(
+        &self,
+        block: BlockId,
+        trace_types: Vec<TraceType>,
+    ) -> RpcResult<Vec<TraceResultsWithTransactionHash>>;
+    #[method(name = "block")]
+    async fn block(&self, block: BlockId) -> RpcResult<Vec<LocalizedTrace>>;
+    #[method(name = "filter")]
+    async fn filter(&self, filter: TraceFilter) -> RpcResult<Vec<LocalizedTrace>>;
+}
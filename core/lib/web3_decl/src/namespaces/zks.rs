@@ -1,11 +1,15 @@
 use std::collections::HashMap;
 
 use bigdecimal::BigDecimal;
-use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use jsonrpsee::{
+    core::{RpcResult, SubscriptionResult},
+    proc_macros::rpc,
+};
 use zksync_types::{
     api::{
-        BlockDetails, BridgeAddresses, L1BatchDetails, L2ToL1LogProof, Proof, ProtocolVersion,
-        TransactionDetails,
+        BlockDetails, BlockNumber, BridgeAddresses, FeeToken, L1BatchDetails, L1BatchFeeParams,
+        L1BatchRevenue, L2ToL1LogProof, L2ToL1LogProofRequest, Proof, ProtocolVersion,
+        StateOverride, TransactionDetails,
     },
     fee::Fee,
     fee_model::FeeParams,
@@ -13,7 +17,7 @@ use zksync_types::{
     Address, L1BatchNumber, MiniblockNumber, H256, U256, U64,
 };
 
-use crate::types::Token;
+use crate::types::{Bytes, Token};
 
 #[cfg_attr(
     all(feature = "client", feature = "server"),
@@ -28,8 +32,15 @@ use crate::types::Token;
     rpc(server, namespace = "zks")
 )]
 pub trait ZksNamespace {
+    /// Estimates the fee for a transaction. If `block` is not specified, the estimation is performed
+    /// against the pending block.
     #[method(name = "estimateFee")]
-    async fn estimate_fee(&self, req: CallRequest) -> RpcResult<Fee>;
+    async fn estimate_fee(
+        &self,
+        req: CallRequest,
+        state_override: Option<StateOverride>,
+        block: Option<BlockNumber>,
+    ) -> RpcResult<Fee>;
 
     #[method(name = "estimateGasL1ToL2")]
     async fn estimate_gas_l1_to_l2(&self, req: CallRequest) -> RpcResult<U256>;
@@ -43,6 +54,9 @@ pub trait ZksNamespace {
     #[method(name = "getBridgeContracts")]
     async fn get_bridge_contracts(&self) -> RpcResult<BridgeAddresses>;
 
+    #[method(name = "getFeeToken")]
+    async fn get_fee_token(&self) -> RpcResult<FeeToken>;
+
     #[method(name = "L1ChainId")]
     async fn l1_chain_id(&self) -> RpcResult<U64>;
 
@@ -71,6 +85,12 @@ pub trait ZksNamespace {
         index: Option<usize>,
     ) -> RpcResult<Option<L2ToL1LogProof>>;
 
+    #[method(name = "getL2ToL1LogProofs")]
+    async fn get_l2_to_l1_log_proofs(
+        &self,
+        requests: Vec<L2ToL1LogProofRequest>,
+    ) -> RpcResult<Vec<Option<L2ToL1LogProof>>>;
+
     #[method(name = "L1BatchNumber")]
     async fn get_l1_batch_number(&self) -> RpcResult<U64>;
 
@@ -90,12 +110,24 @@ pub trait ZksNamespace {
     async fn get_raw_block_transactions(
         &self,
         block_number: MiniblockNumber,
+        offset: Option<usize>,
+        limit: Option<usize>,
     ) -> RpcResult<Vec<zksync_types::Transaction>>;
 
     #[method(name = "getL1BatchDetails")]
     async fn get_l1_batch_details(&self, batch: L1BatchNumber)
         -> RpcResult<Option<L1BatchDetails>>;
 
+    #[method(name = "getL1BatchFeeParams")]
+    async fn get_l1_batch_fee_params(
+        &self,
+        batch: L1BatchNumber,
+    ) -> RpcResult<Option<L1BatchFeeParams>>;
+
+    #[method(name = "getL1BatchRevenue")]
+    async fn get_l1_batch_revenue(&self, batch: L1BatchNumber)
+        -> RpcResult<Option<L1BatchRevenue>>;
+
     #[method(name = "getBytecodeByHash")]
     async fn get_bytecode_by_hash(&self, hash: H256) -> RpcResult<Option<Vec<u8>>>;
 
@@ -111,6 +143,9 @@ pub trait ZksNamespace {
         version_id: Option<u16>,
     ) -> RpcResult<Option<ProtocolVersion>>;
 
+    #[method(name = "getScheduledProtocolUpgrades")]
+    async fn get_scheduled_protocol_upgrades(&self) -> RpcResult<Vec<ProtocolVersion>>;
+
     #[method(name = "getProof")]
     async fn get_proof(
         &self,
@@ -118,4 +153,28 @@ pub trait ZksNamespace {
         keys: Vec<H256>,
         l1_batch_number: L1BatchNumber,
     ) -> RpcResult<Proof>;
+
+    #[method(name = "sendBundle")]
+    async fn send_bundle(&self, txs: Vec<Bytes>) -> RpcResult<H256>;
+
+    #[method(name = "sendPrivateRawTransaction")]
+    async fn send_private_raw_transaction(&self, tx_bytes: Bytes) -> RpcResult<H256>;
+}
+
+/// Streaming counterparts of `zks` methods. Only available via WebSocket transport.
+#[rpc(server, namespace = "zks")]
+pub trait ZksPubSub {
+    /// Streams raw transactions of the specified miniblock in chunks of up to `chunk_size` transactions.
+    /// The last chunk contains less than `chunk_size` transactions (and may be empty), after which
+    /// the subscription is closed.
+    #[subscription(
+        name = "subscribeRawBlockTransactions" => "rawBlockTransactions",
+        unsubscribe = "unsubscribeRawBlockTransactions",
+        item = Vec<zksync_types::Transaction>
+    )]
+    async fn subscribe_raw_block_transactions(
+        &self,
+        block_number: MiniblockNumber,
+        chunk_size: Option<usize>,
+    ) -> SubscriptionResult;
 }
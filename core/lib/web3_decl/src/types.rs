@@ -24,6 +24,7 @@ pub use zksync_types::{
             Transaction, Work, H160, H256, H64, U256, U64,
         },
     },
+    L1BatchNumber,
 };
 
 /// Token in the zkSync network
@@ -105,6 +106,33 @@ pub enum FilterChanges {
     Empty([u8; 0]),
 }
 
+/// Page of logs returned by `eth_getLogs` if pagination is requested in the [`Filter`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogsPage {
+    pub logs: Vec<Log>,
+    /// Cursor to pass as `pageToken` to get the next page; `None` if there are no more logs in the filter range.
+    pub next_page_token: Option<String>,
+}
+
+/// Output of `eth_getLogs`. Logs are returned as a plain array unless pagination is requested, so that
+/// the output is compatible with Ethereum clients.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GetLogsResponse {
+    Logs(Vec<Log>),
+    Page(LogsPage),
+}
+
+impl GetLogsResponse {
+    pub fn into_logs(self) -> Vec<Log> {
+        match self {
+            Self::Logs(logs) => logs,
+            Self::Page(page) => page.logs,
+        }
+    }
+}
+
 /// Either value or array of values.
 ///
 /// A value must serialize into a string.
@@ -185,6 +213,20 @@ pub struct Filter {
     pub topics: Option<Vec<Option<ValueOrArray<H256>>>>,
     #[serde(rename = "blockHash", skip_serializing_if = "Option::is_none")]
     pub block_hash: Option<H256>,
+    /// Cursor returned in [`LogsPage::next_page_token`] by a previous `eth_getLogs` call with the same filter.
+    /// zkSync-specific extension; only used by `eth_getLogs`.
+    #[serde(rename = "pageToken", skip_serializing_if = "Option::is_none")]
+    pub page_token: Option<String>,
+    /// Maximum number of logs on a page. zkSync-specific extension; only used by `eth_getLogs`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+impl Filter {
+    /// Checks whether this filter requests paginated `eth_getLogs` output.
+    pub fn is_paginated(&self) -> bool {
+        self.page_token.is_some() || self.limit.is_some()
+    }
 }
 
 /// Filter Builder
@@ -254,10 +296,47 @@ pub struct PubSubFilter {
     pub address: Option<ValueOrArray<H160>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub topics: Option<Vec<Option<ValueOrArray<H256>>>>,
+    /// Position of the last log delivered to the client, e.g. before a reconnect. If specified, logs after
+    /// this position are backfilled before switching to live notifications, and earlier logs are never sent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<LogCursor>,
+}
+
+/// Position of a log in the chain used to resume `logs` subscriptions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogCursor {
+    pub block_number: U64,
+    pub log_index: U64,
+}
+
+impl LogCursor {
+    /// Returns the cursor pointing to the specified log, or `None` if the log is not included into a block.
+    pub fn for_log(log: &Log) -> Option<Self> {
+        Some(Self {
+            block_number: log.block_number?,
+            log_index: log.log_index?.as_u64().into(),
+        })
+    }
+}
+
+/// Optional parameter of `eth_subscribe` following the subscription type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PubSubParams {
+    /// Whether `newPendingTransactions` notifications should contain full transactions instead of hashes.
+    FullTransactions(bool),
+    /// Filter for `logs` subscriptions.
+    Filter(PubSubFilter),
 }
 
 impl PubSubFilter {
     pub fn matches(&self, log: &Log) -> bool {
+        if let Some(cursor) = self.cursor {
+            if LogCursor::for_log(log).map_or(true, |log_cursor| log_cursor <= cursor) {
+                return false;
+            }
+        }
         if let Some(addresses) = &self.address {
             if !addresses.0.contains(&log.address) {
                 return false;
@@ -345,6 +424,30 @@ pub enum PubSubResult {
     Log(Log),
     TxHash(H256),
     Syncing(bool),
+    FullTx(zksync_types::api::Transaction),
+    L1BatchStatus(L1BatchStatusEvent),
+}
+
+/// Stage of the L1 batch lifecycle reported by the `l1BatchStatus` subscription.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum L1BatchStage {
+    /// Batch is sealed by the state keeper.
+    Sealed,
+    /// Batch commitment is confirmed on L1.
+    Committed,
+    /// Batch proof is confirmed on L1.
+    Proven,
+    /// Batch is executed on L1.
+    Executed,
+}
+
+/// Event emitted by the `l1BatchStatus` subscription when an L1 batch reaches a new lifecycle stage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct L1BatchStatusEvent {
+    pub l1_batch_number: L1BatchNumber,
+    pub stage: L1BatchStage,
 }
 
 #[cfg(test)]
@@ -441,4 +544,57 @@ mod tests {
         let restored_value: ValueOrArray<Address> = serde_json::from_value(json).unwrap();
         assert_eq!(restored_value, value);
     }
+
+    #[test]
+    fn pub_sub_params_deserialization() {
+        let params: PubSubParams = serde_json::from_str("true").unwrap();
+        assert_eq!(params, PubSubParams::FullTransactions(true));
+
+        let params: PubSubParams = serde_json::from_str("{}").unwrap();
+        assert_eq!(params, PubSubParams::Filter(PubSubFilter::default()));
+
+        let params: PubSubParams =
+            serde_json::from_str(r#"{"address": "0x0000000000000000000000000000000000000001"}"#)
+                .unwrap();
+        let PubSubParams::Filter(filter) = params else {
+            panic!("Unexpected params: {params:?}");
+        };
+        assert_eq!(filter.address.unwrap().0, [Address::from_low_u64_be(1)]);
+
+        let params: PubSubParams =
+            serde_json::from_str(r#"{"cursor": {"blockNumber": "0x10", "logIndex": "0x2"}}"#)
+                .unwrap();
+        let PubSubParams::Filter(filter) = params else {
+            panic!("Unexpected params: {params:?}");
+        };
+        let cursor = filter.cursor.unwrap();
+        assert_eq!(cursor.block_number, 16.into());
+        assert_eq!(cursor.log_index, 2.into());
+    }
+
+    #[test]
+    fn pub_sub_filter_with_cursor() {
+        let filter = PubSubFilter {
+            cursor: Some(LogCursor {
+                block_number: 16.into(),
+                log_index: 2.into(),
+            }),
+            ..PubSubFilter::default()
+        };
+        let log_at = |block_number: u64, log_index: u64| -> Log {
+            serde_json::from_value(serde_json::json!({
+                "address": "0x0000000000000000000000000000000000000000",
+                "topics": [],
+                "data": "0x",
+                "blockNumber": U64::from(block_number),
+                "logIndex": U256::from(log_index),
+            }))
+            .unwrap()
+        };
+
+        assert!(!filter.matches(&log_at(15, 5)));
+        assert!(!filter.matches(&log_at(16, 2)));
+        assert!(filter.matches(&log_at(16, 3)));
+        assert!(filter.matches(&log_at(17, 0)));
+    }
 }
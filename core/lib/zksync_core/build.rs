@@ -0,0 +1,26 @@
+//! Generates Rust code for the gRPC streaming API.
+//!
+//! Protobufs are parsed with `protox` rather than `protoc`, so that building doesn't require
+//! a system-wide protobuf compiler (same as for the protobufs compiled by `zksync_protobuf_build`).
+
+use std::{env, fs, path::PathBuf};
+
+use prost::Message as _;
+
+const PROTO_ROOT: &str = "src/api_server/web3/grpc/proto";
+const PROTO_FILES: &[&str] = &["streaming.proto"];
+
+fn main() {
+    let file_descriptors = protox::compile(PROTO_FILES, [PROTO_ROOT])
+        .expect("failed parsing protobufs for gRPC streaming API");
+    let out_dir = PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR is not set"));
+    let descriptors_path = out_dir.join("streaming_descriptors.bin");
+    fs::write(&descriptors_path, file_descriptors.encode_to_vec())
+        .expect("failed writing file descriptors for gRPC streaming API");
+
+    tonic_build::configure()
+        .file_descriptor_set_path(&descriptors_path)
+        .skip_protoc_run()
+        .compile(PROTO_FILES, &[PROTO_ROOT])
+        .expect("failed generating code for gRPC streaming API");
+}
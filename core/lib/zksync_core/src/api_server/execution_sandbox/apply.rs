@@ -15,26 +15,61 @@ use multivm::{
     VmInstance,
 };
 use zksync_dal::{ConnectionPool, SqlxError, StorageProcessor};
-use zksync_state::{PostgresStorage, ReadStorage, StorageView, WriteStorage};
+use zksync_state::{
+    PostgresStorage, ReadStorage, StoragePtr, StorageView, StorageWithOverrides, WriteStorage,
+};
 use zksync_system_constants::{
     SYSTEM_CONTEXT_ADDRESS, SYSTEM_CONTEXT_CURRENT_L2_BLOCK_INFO_POSITION,
     SYSTEM_CONTEXT_CURRENT_TX_ROLLING_HASH_POSITION, ZKPORTER_IS_AVAILABLE,
 };
 use zksync_types::{
-    api,
+    api::{self, StateOverride},
     block::{pack_block_info, unpack_block_info, MiniblockHasher},
-    get_nonce_key,
+    get_code_key, get_known_code_key, get_nonce_key,
     utils::{decompose_full_nonce, nonces_to_full_nonce, storage_key_for_eth_balance},
     AccountTreeId, L1BatchNumber, MiniblockNumber, Nonce, ProtocolVersionId, StorageKey,
     Transaction, H256, U256,
 };
-use zksync_utils::{h256_to_u256, time::seconds_since_epoch, u256_to_h256};
+use zksync_utils::{
+    bytecode::hash_bytecode, h256_to_u256, time::seconds_since_epoch, u256_to_h256,
+};
 
 use super::{
     vm_metrics::{self, SandboxStage, SANDBOX_METRICS},
     BlockArgs, TxExecutionArgs, TxSharedArgs, VmPermit,
 };
 
+/// Storage used by the VM in the sandbox.
+pub(super) type SandboxStorage<'a> = StorageView<StorageWithOverrides<PostgresStorage<'a>>>;
+
+/// Sandbox environment passed to the closure executed by [`apply_vm_in_sandbox()`] together with the VM.
+#[derive(Debug)]
+pub(super) struct SandboxEnv<'a> {
+    /// L2 block the first transaction is executed in.
+    pub l2_block: L2BlockEnv,
+    pub protocol_version: ProtocolVersionId,
+    /// Storage shared with the VM. Can be used to inspect or modify the state between transactions.
+    pub storage: StoragePtr<SandboxStorage<'a>>,
+}
+
+impl SandboxEnv<'_> {
+    /// Returns the hash of the specified L2 block, assuming that it's the current block in the VM.
+    pub fn l2_block_hash(&self, l2_block: &L2BlockEnv) -> H256 {
+        let txs_rolling_hash_key = StorageKey::new(
+            AccountTreeId::new(SYSTEM_CONTEXT_ADDRESS),
+            SYSTEM_CONTEXT_CURRENT_TX_ROLLING_HASH_POSITION,
+        );
+        let txs_rolling_hash = self.storage.borrow_mut().read_value(&txs_rolling_hash_key);
+        MiniblockHasher::with_txs_rolling_hash(
+            MiniblockNumber(l2_block.number),
+            l2_block.timestamp,
+            l2_block.prev_block_hash,
+            txs_rolling_hash,
+        )
+        .finalize(self.protocol_version)
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 pub(super) fn apply_vm_in_sandbox<T>(
     vm_permit: VmPermit,
@@ -48,8 +83,9 @@ pub(super) fn apply_vm_in_sandbox<T>(
     tx: Transaction,
     block_args: BlockArgs,
     apply: impl FnOnce(
-        &mut VmInstance<StorageView<PostgresStorage<'_>>, HistoryDisabled>,
+        &mut VmInstance<SandboxStorage<'_>, HistoryDisabled>,
         Transaction,
+        SandboxEnv<'_>,
     ) -> T,
 ) -> T {
     let stage_started_at = Instant::now();
@@ -131,8 +167,25 @@ pub(super) fn apply_vm_in_sandbox<T>(
         }
     };
 
-    let storage = PostgresStorage::new(rt_handle.clone(), connection, state_l2_block_number, false)
-        .with_caches(shared_args.caches);
+    // When replaying a sealed miniblock, the L2 block context is taken from the miniblock itself,
+    // but the storage must not include its changes.
+    let storage_l2_block_number = if block_args.state_before_block {
+        state_l2_block_number - 1
+    } else {
+        state_l2_block_number
+    };
+    let mut storage = PostgresStorage::new(
+        rt_handle.clone(),
+        connection,
+        storage_l2_block_number,
+        false,
+    )
+    .with_caches(shared_args.caches);
+    storage.prefetch_values(&execution_args.prefetched_keys);
+    let mut storage = StorageWithOverrides::new(storage);
+    if let Some(state_override) = &execution_args.state_override {
+        apply_state_override(&mut storage, state_override);
+    }
     let mut storage_view = StorageView::new(storage);
 
     let storage_view_setup_started_at = Instant::now();
@@ -219,6 +272,11 @@ pub(super) fn apply_vm_in_sandbox<T>(
     };
 
     let storage_view = storage_view.to_rc_ptr();
+    let sandbox_env = SandboxEnv {
+        l2_block: l1_batch_env.first_l2_block,
+        protocol_version,
+        storage: storage_view.clone(),
+    };
     let mut vm = Box::new(VmInstance::new_with_specific_version(
         l1_batch_env,
         system_env,
@@ -235,7 +293,7 @@ pub(super) fn apply_vm_in_sandbox<T>(
         tx.nonce().unwrap_or(Nonce(0))
     );
     let execution_latency = SANDBOX_METRICS.sandbox[&SandboxStage::Execution].start();
-    let result = apply(&mut vm, tx);
+    let result = apply(&mut vm, tx, sandbox_env);
     let vm_execution_took = execution_latency.observe();
 
     let memory_metrics = vm.record_vm_memory_metrics();
@@ -250,6 +308,72 @@ pub(super) fn apply_vm_in_sandbox<T>(
     result
 }
 
+/// Applies the state override set to the sandbox storage. The overrides are expected to be validated beforehand.
+fn apply_state_override<S: ReadStorage>(
+    storage: &mut StorageWithOverrides<S>,
+    state_override: &StateOverride,
+) {
+    for (address, account) in state_override {
+        if let Some(balance) = account.balance {
+            storage.set_value(storage_key_for_eth_balance(address), u256_to_h256(balance));
+        }
+
+        if let Some(nonce) = account.nonce {
+            let nonce_key = get_nonce_key(address);
+            let full_nonce = storage.read_value(&nonce_key);
+            let (_, deployment_nonce) = decompose_full_nonce(h256_to_u256(full_nonce));
+            let new_full_nonce = nonces_to_full_nonce(nonce, deployment_nonce);
+            storage.set_value(nonce_key, u256_to_h256(new_full_nonce));
+        }
+
+        if let Some(code) = &account.code {
+            let code_hash = hash_bytecode(&code.0);
+            storage.set_value(get_code_key(address), code_hash);
+            storage.set_value(get_known_code_key(&code_hash), H256::from_low_u64_be(1));
+            storage.store_factory_dep(code_hash, code.0.clone());
+        }
+
+        let account_id = AccountTreeId::new(*address);
+        let slots = if let Some(state) = &account.state {
+            storage.clear_account_storage(account_id);
+            state
+        } else if let Some(state_diff) = &account.state_diff {
+            state_diff
+        } else {
+            continue;
+        };
+        for (&slot, &value) in slots {
+            storage.set_value(StorageKey::new(account_id, slot), value);
+        }
+    }
+}
+
+/// Applies balance, nonce and state diff overrides to the sandbox storage after the VM has been started.
+/// Unlike [`apply_state_override()`], this doesn't support replacing account code or the entire account storage.
+pub(super) fn apply_state_diff_override<S: WriteStorage>(
+    storage: &mut S,
+    state_override: &StateOverride,
+) {
+    for (address, account) in state_override {
+        if let Some(balance) = account.balance {
+            storage.set_value(storage_key_for_eth_balance(address), u256_to_h256(balance));
+        }
+
+        if let Some(nonce) = account.nonce {
+            let nonce_key = get_nonce_key(address);
+            let full_nonce = storage.read_value(&nonce_key);
+            let (_, deployment_nonce) = decompose_full_nonce(h256_to_u256(full_nonce));
+            let new_full_nonce = nonces_to_full_nonce(nonce, deployment_nonce);
+            storage.set_value(nonce_key, u256_to_h256(new_full_nonce));
+        }
+
+        let account_id = AccountTreeId::new(*address);
+        for (&slot, &value) in account.state_diff.iter().flatten() {
+            storage.set_value(StorageKey::new(account_id, slot), value);
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 struct StoredL2BlockInfo {
     pub l2_block_number: u32,
@@ -0,0 +1,178 @@
+//! Per-client accounting of VM permits.
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    net::IpAddr,
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use super::vm_metrics::SANDBOX_METRICS;
+
+tokio::task_local! {
+    /// Client on behalf of which the current task executes VM calls.
+    static VM_CLIENT: Option<VmClient>;
+}
+
+/// Client of the API server on behalf of which VM is invoked.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(in crate::api_server) enum VmClient {
+    ApiKey(String),
+    Ip(IpAddr),
+}
+
+impl VmClient {
+    /// Executes the provided future on behalf of the specified client. VM calls made by the future
+    /// will be accounted for this client by [`VmConcurrencyLimiter`](super::VmConcurrencyLimiter).
+    pub(in crate::api_server) async fn scope<F: Future>(
+        client: Option<Self>,
+        future: F,
+    ) -> F::Output {
+        VM_CLIENT.scope(client, future).await
+    }
+
+    /// Returns the client for the current task, if it was set using [`Self::scope()`].
+    pub(super) fn current() -> Option<Self> {
+        VM_CLIENT.try_with(Clone::clone).ok().flatten()
+    }
+}
+
+#[derive(Debug)]
+struct ClientSlot {
+    semaphore: Arc<Semaphore>,
+    /// Number of outstanding [`ClientPermit`]s for the client, including ones waiting for the semaphore.
+    users: usize,
+}
+
+/// Per-client sub-quotas of VM permits. Slots are allocated lazily and are removed once the client
+/// has no VM calls in flight.
+#[derive(Debug)]
+pub(super) struct ClientSlots {
+    limit: usize,
+    slots: Mutex<HashMap<VmClient, ClientSlot>>,
+}
+
+impl ClientSlots {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            slots: Mutex::default(),
+        }
+    }
+
+    /// Waits until the client has a free slot in its sub-quota.
+    pub async fn acquire(self: &Arc<Self>, client: VmClient) -> ClientPermit {
+        let semaphore = {
+            let mut slots = self.slots.lock().expect("client slots are poisoned");
+            let slot = slots.entry(client.clone()).or_insert_with(|| ClientSlot {
+                semaphore: Arc::new(Semaphore::new(self.limit)),
+                users: 0,
+            });
+            slot.users += 1;
+            let semaphore = Arc::clone(&slot.semaphore);
+            SANDBOX_METRICS.vm_clients.set(slots.len());
+            semaphore
+        };
+        // Create the permit before waiting, so that the slot is released if the returned future is dropped.
+        let mut permit = ClientPermit {
+            slots: Arc::clone(self),
+            client,
+            permit: None,
+        };
+        if semaphore.available_permits() == 0 {
+            SANDBOX_METRICS.vm_client_limit_reached.inc();
+        }
+        // Client semaphores are never closed.
+        permit.permit = Some(semaphore.acquire_owned().await.unwrap());
+        permit
+    }
+
+    fn release(&self, client: &VmClient) {
+        let mut slots = self.slots.lock().expect("client slots are poisoned");
+        let slot = slots
+            .get_mut(client)
+            .expect("released slot for unknown client");
+        slot.users -= 1;
+        if slot.users == 0 {
+            slots.remove(client);
+        }
+        SANDBOX_METRICS.vm_clients.set(slots.len());
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.slots.lock().unwrap().len()
+    }
+}
+
+/// Permit from a per-client sub-quota of VM permits.
+#[derive(Debug)]
+pub(super) struct ClientPermit {
+    slots: Arc<ClientSlots>,
+    client: VmClient,
+    permit: Option<OwnedSemaphorePermit>,
+}
+
+impl Drop for ClientPermit {
+    fn drop(&mut self) {
+        // Return the permit before potentially removing the slot, so that it isn't counted twice.
+        drop(self.permit.take());
+        self.slots.release(&self.client);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::Ipv4Addr, time::Duration};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn scoping_vm_client() {
+        assert_eq!(VmClient::current(), None);
+        let client = VmClient::ApiKey("test".to_owned());
+        let current = VmClient::scope(Some(client.clone()), async { VmClient::current() }).await;
+        assert_eq!(current, Some(client));
+        let current = VmClient::scope(None, async { VmClient::current() }).await;
+        assert_eq!(current, None);
+    }
+
+    #[tokio::test]
+    async fn client_slots_basics() {
+        let slots = Arc::new(ClientSlots::new(2));
+        let client = VmClient::Ip(Ipv4Addr::LOCALHOST.into());
+        let other_client = VmClient::ApiKey("test".to_owned());
+
+        let first_permit = slots.acquire(client.clone()).await;
+        let second_permit = slots.acquire(client.clone()).await;
+        // The client has exhausted its quota, but other clients are not affected.
+        let other_permit = slots.acquire(other_client).await;
+        assert_eq!(slots.len(), 2);
+        drop(other_permit);
+        assert_eq!(slots.len(), 1);
+
+        let third_permit = slots.acquire(client.clone());
+        tokio::pin!(third_permit);
+        tokio::time::timeout(Duration::from_millis(50), &mut third_permit)
+            .await
+            .unwrap_err();
+        drop(first_permit);
+        let third_permit = third_permit.await;
+        drop(third_permit);
+        drop(second_permit);
+        assert_eq!(slots.len(), 0);
+
+        // Check that a cancelled acquisition releases the slot.
+        let permits = [
+            slots.acquire(client.clone()).await,
+            slots.acquire(client.clone()).await,
+        ];
+        tokio::time::timeout(Duration::from_millis(10), slots.acquire(client))
+            .await
+            .unwrap_err();
+        drop(permits);
+        assert_eq!(slots.len(), 0);
+    }
+}
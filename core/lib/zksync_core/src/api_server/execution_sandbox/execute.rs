@@ -1,16 +1,17 @@
 //! Implementation of "executing" methods, e.g. `eth_call`.
 
+use std::iter;
+
 use multivm::{
-    interface::{TxExecutionMode, VmExecutionResultAndLogs, VmInterface},
+    interface::{L2BlockEnv, TxExecutionMode, VmExecutionResultAndLogs, VmInterface},
     tracers::StorageInvocations,
-    vm_latest::constants::ETH_CALL_GAS_LIMIT,
     MultiVMTracer,
 };
 use tracing::{span, Level};
 use zksync_dal::ConnectionPool;
 use zksync_types::{
-    fee::TransactionExecutionMetrics, l2::L2Tx, ExecuteTransactionCommon, Nonce,
-    PackedEthSignature, Transaction, U256,
+    api::StateOverride, fee::TransactionExecutionMetrics, l2::L2Tx, ExecuteTransactionCommon,
+    MiniblockNumber, Nonce, PackedEthSignature, StorageKey, Transaction, H256, U256,
 };
 
 use super::{apply, vm_metrics, ApiTracer, BlockArgs, TxSharedArgs, VmPermit};
@@ -22,6 +23,10 @@ pub(crate) struct TxExecutionArgs {
     pub added_balance: U256,
     pub enforced_base_fee: Option<u64>,
     pub missed_storage_invocation_limit: usize,
+    pub state_override: Option<StateOverride>,
+    /// Storage keys loaded from Postgres in a single query before execution (e.g., ones from the access list
+    /// of the executed call).
+    pub prefetched_keys: Vec<StorageKey>,
 }
 
 impl TxExecutionArgs {
@@ -32,12 +37,27 @@ impl TxExecutionArgs {
             added_balance: U256::zero(),
             enforced_base_fee: Some(tx.common_data.fee.max_fee_per_gas.as_u64()),
             missed_storage_invocation_limit: usize::MAX,
+            state_override: None,
+            prefetched_keys: vec![],
+        }
+    }
+
+    fn for_replay(enforced_base_fee: u64) -> Self {
+        Self {
+            execution_mode: TxExecutionMode::VerifyExecute,
+            enforced_nonce: None,
+            added_balance: U256::zero(),
+            enforced_base_fee: Some(enforced_base_fee),
+            missed_storage_invocation_limit: usize::MAX,
+            state_override: None,
+            prefetched_keys: vec![],
         }
     }
 
     fn for_eth_call(
         enforced_base_fee: u64,
         vm_execution_cache_misses_limit: Option<usize>,
+        state_override: Option<StateOverride>,
     ) -> Self {
         let missed_storage_invocation_limit = vm_execution_cache_misses_limit.unwrap_or(usize::MAX);
         Self {
@@ -46,6 +66,8 @@ impl TxExecutionArgs {
             added_balance: U256::zero(),
             enforced_base_fee: Some(enforced_base_fee),
             missed_storage_invocation_limit,
+            state_override,
+            prefetched_keys: vec![],
         }
     }
 
@@ -69,10 +91,13 @@ impl TxExecutionArgs {
             enforced_nonce: tx.nonce(),
             added_balance,
             enforced_base_fee: Some(base_fee),
+            state_override: None,
+            prefetched_keys: vec![],
         }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn execute_tx_eth_call(
     vm_permit: VmPermit,
     shared_args: TxSharedArgs,
@@ -80,20 +105,19 @@ pub(crate) async fn execute_tx_eth_call(
     mut tx: L2Tx,
     block_args: BlockArgs,
     vm_execution_cache_misses_limit: Option<usize>,
+    state_override: Option<StateOverride>,
+    prefetched_keys: Vec<StorageKey>,
     custom_tracers: Vec<ApiTracer>,
 ) -> VmExecutionResultAndLogs {
     let enforced_base_fee = tx.common_data.fee.max_fee_per_gas.as_u64();
-    let execution_args =
-        TxExecutionArgs::for_eth_call(enforced_base_fee, vm_execution_cache_misses_limit);
-
-    if tx.common_data.signature.is_empty() {
-        tx.common_data.signature = PackedEthSignature::default().serialize_packed().into();
-    }
+    let mut execution_args = TxExecutionArgs::for_eth_call(
+        enforced_base_fee,
+        vm_execution_cache_misses_limit,
+        state_override,
+    );
+    execution_args.prefetched_keys = prefetched_keys;
 
-    // Protection against infinite-loop eth_calls and alike:
-    // limiting the amount of gas the call can use.
-    // We can't use `BLOCK_ERGS_LIMIT` here since the VM itself has some overhead.
-    tx.common_data.fee.gas_limit = ETH_CALL_GAS_LIMIT.into();
+    prepare_eth_call_tx(&mut tx, shared_args.call_gas_limit);
     let (vm_result, _, _) = execute_tx_in_sandbox(
         vm_permit,
         shared_args,
@@ -109,6 +133,189 @@ pub(crate) async fn execute_tx_eth_call(
     vm_result
 }
 
+/// Executes a sequence of calls on top of the same sandbox state, so that each call observes changes
+/// made by the preceding ones. The state override set is applied before the first call. Returns execution results
+/// for all provided calls.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn execute_txs_eth_call(
+    vm_permit: VmPermit,
+    shared_args: TxSharedArgs,
+    connection_pool: ConnectionPool,
+    block_args: BlockArgs,
+    vm_execution_cache_misses_limit: Option<usize>,
+    state_override: Option<StateOverride>,
+    txs: Vec<(L2Tx, Vec<ApiTracer>)>,
+) -> Vec<VmExecutionResultAndLogs> {
+    let Some((first_tx, _)) = txs.first() else {
+        return vec![];
+    };
+    let enforced_base_fee = first_tx.common_data.fee.max_fee_per_gas.as_u64();
+    let execution_args = TxExecutionArgs::for_eth_call(
+        enforced_base_fee,
+        vm_execution_cache_misses_limit,
+        state_override,
+    );
+
+    let call_gas_limit = shared_args.call_gas_limit;
+    let txs = txs
+        .into_iter()
+        .map(|(mut tx, custom_tracers)| {
+            prepare_eth_call_tx(&mut tx, call_gas_limit);
+            (tx.into(), custom_tracers)
+        })
+        .collect();
+    execute_txs_in_sandbox(
+        vm_permit,
+        shared_args,
+        execution_args,
+        connection_pool,
+        block_args,
+        txs,
+    )
+    .await
+}
+
+/// Block of calls executed by [`execute_blocks_eth_call()`].
+#[derive(Debug)]
+pub(crate) struct SandboxBlock {
+    /// State override applied before executing the block. For all blocks except the first one,
+    /// only balance, nonce and state diff overrides are applied.
+    pub state_override: Option<StateOverride>,
+    /// Calls in the block. Must be non-empty.
+    pub txs: Vec<L2Tx>,
+}
+
+/// Execution results for a [`SandboxBlock`].
+#[derive(Debug)]
+pub(crate) struct SandboxBlockResult {
+    pub number: MiniblockNumber,
+    pub timestamp: u64,
+    pub hash: H256,
+    pub tx_results: Vec<VmExecutionResultAndLogs>,
+}
+
+/// Executes blocks of calls on top of the same sandbox state. The first block is executed in the context
+/// of `block_args`; each following block is executed in a new L2 block with the next number and timestamp.
+pub(crate) async fn execute_blocks_eth_call(
+    vm_permit: VmPermit,
+    shared_args: TxSharedArgs,
+    connection_pool: ConnectionPool,
+    block_args: BlockArgs,
+    vm_execution_cache_misses_limit: Option<usize>,
+    blocks: Vec<SandboxBlock>,
+) -> Vec<SandboxBlockResult> {
+    assert!(
+        blocks.iter().all(|block| !block.txs.is_empty()),
+        "Sandbox blocks must not be empty"
+    );
+    let mut blocks = blocks.into_iter();
+    let Some(first_block) = blocks.next() else {
+        return vec![];
+    };
+    let enforced_base_fee = first_block.txs[0].common_data.fee.max_fee_per_gas.as_u64();
+    let execution_args = TxExecutionArgs::for_eth_call(
+        enforced_base_fee,
+        vm_execution_cache_misses_limit,
+        first_block.state_override,
+    );
+
+    let call_gas_limit = shared_args.call_gas_limit;
+    let prepare_txs = |txs: Vec<L2Tx>| -> Vec<Transaction> {
+        txs.into_iter()
+            .map(|mut tx| {
+                prepare_eth_call_tx(&mut tx, call_gas_limit);
+                tx.into()
+            })
+            .collect()
+    };
+    let mut first_block_txs = prepare_txs(first_block.txs);
+    let first_tx = first_block_txs.remove(0);
+    let next_blocks: Vec<_> = blocks
+        .map(|block| (block.state_override, prepare_txs(block.txs)))
+        .collect();
+
+    tokio::task::spawn_blocking(move || {
+        let span = span!(Level::DEBUG, "execute_blocks_in_sandbox").entered();
+        let results = apply::apply_vm_in_sandbox(
+            vm_permit,
+            shared_args,
+            false,
+            &execution_args,
+            &connection_pool,
+            first_tx,
+            block_args,
+            |vm, first_tx, env| {
+                first_block_txs.insert(0, first_tx);
+                // The state override for the first block is already applied via `execution_args`.
+                let blocks = iter::once((None, first_block_txs)).chain(next_blocks);
+                let mut l2_block = env.l2_block;
+                let mut results: Vec<SandboxBlockResult> = vec![];
+                for (state_override, txs) in blocks {
+                    if let Some(prev_block) = results.last() {
+                        l2_block = L2BlockEnv {
+                            number: l2_block.number + 1,
+                            timestamp: l2_block.timestamp + 1,
+                            prev_block_hash: prev_block.hash,
+                            max_virtual_blocks_to_create: 1,
+                        };
+                        vm.start_new_l2_block(l2_block);
+                    }
+                    if let Some(state_override) = &state_override {
+                        let mut storage = env.storage.borrow_mut();
+                        apply::apply_state_diff_override(&mut *storage, state_override);
+                    }
+
+                    let tx_results = txs.into_iter().map(|tx| {
+                        let storage_invocation_tracer =
+                            StorageInvocations::new(execution_args.missed_storage_invocation_limit);
+                        let (_, result) = vm.inspect_transaction_with_bytecode_compression(
+                            vec![storage_invocation_tracer.into_tracer_pointer()].into(),
+                            tx,
+                            true,
+                        );
+                        result
+                    });
+                    let tx_results = tx_results.collect();
+                    results.push(SandboxBlockResult {
+                        number: MiniblockNumber(l2_block.number),
+                        timestamp: l2_block.timestamp,
+                        hash: env.l2_block_hash(&l2_block),
+                        tx_results,
+                    });
+                }
+                results
+            },
+        );
+        span.exit();
+        results
+    })
+    .await
+    .unwrap()
+}
+
+fn prepare_eth_call_tx(tx: &mut L2Tx, call_gas_limit: u32) {
+    if tx.common_data.signature.is_empty() {
+        tx.common_data.signature = PackedEthSignature::default().serialize_packed().into();
+    }
+
+    // Protection against infinite-loop eth_calls and alike:
+    // limiting the amount of gas the call can use.
+    // We can't use `BLOCK_ERGS_LIMIT` here since the VM itself has some overhead.
+    tx.common_data.fee.gas_limit = call_gas_limit.into();
+}
+
+/// Checks whether a failed read-only call has (almost) exhausted its gas limit, i.e., whether it has most likely
+/// failed because of the call compute limit rather than because of the called contract logic. Since only 63/64
+/// of the remaining gas is passed to a nested call, a call running out of gas doesn't necessarily use up
+/// the entire limit.
+pub(crate) fn exhausted_call_gas_limit(
+    result: &VmExecutionResultAndLogs,
+    call_gas_limit: u32,
+) -> bool {
+    let threshold = u64::from(call_gas_limit) * 62 / 64;
+    result.result.is_failed() && u64::from(result.statistics.gas_used) >= threshold
+}
+
 /// This method assumes that (block with number `resolved_block_number` is present in DB)
 /// or (`block_id` is `pending` and block with number `resolved_block_number - 1` is present in DB)
 #[allow(clippy::too_many_arguments)]
@@ -142,7 +349,7 @@ pub(crate) async fn execute_tx_in_sandbox(
             &connection_pool,
             tx,
             block_args,
-            |vm, tx| {
+            |vm, tx, _| {
                 let storage_invocation_tracer =
                     StorageInvocations::new(execution_args.missed_storage_invocation_limit);
                 let custom_tracers: Vec<_> = custom_tracers
@@ -167,3 +374,79 @@ pub(crate) async fn execute_tx_in_sandbox(
         published_bytecodes.is_ok(),
     )
 }
+
+/// Re-executes transactions included into a sealed miniblock. `block_args` must be obtained using
+/// [`BlockArgs::for_replay()`], and `txs` must be a prefix of the miniblock transactions, so that each transaction
+/// is executed on top of the same state as originally. Returns execution results for all provided transactions.
+#[tracing::instrument(skip_all)]
+pub(crate) async fn replay_txs_in_sandbox(
+    vm_permit: VmPermit,
+    shared_args: TxSharedArgs,
+    connection_pool: ConnectionPool,
+    block_args: BlockArgs,
+    base_fee: u64,
+    txs: Vec<(Transaction, Vec<ApiTracer>)>,
+) -> Vec<VmExecutionResultAndLogs> {
+    let execution_args = TxExecutionArgs::for_replay(base_fee);
+    execute_txs_in_sandbox(
+        vm_permit,
+        shared_args,
+        execution_args,
+        connection_pool,
+        block_args,
+        txs,
+    )
+    .await
+}
+
+/// Executes transactions sequentially in a single sandbox VM. Execution args (e.g., the state override set)
+/// are applied based on the first transaction.
+async fn execute_txs_in_sandbox(
+    vm_permit: VmPermit,
+    shared_args: TxSharedArgs,
+    execution_args: TxExecutionArgs,
+    connection_pool: ConnectionPool,
+    block_args: BlockArgs,
+    txs: Vec<(Transaction, Vec<ApiTracer>)>,
+) -> Vec<VmExecutionResultAndLogs> {
+    let mut txs = txs.into_iter();
+    let Some((first_tx, first_tx_tracers)) = txs.next() else {
+        return vec![];
+    };
+
+    tokio::task::spawn_blocking(move || {
+        let span = span!(Level::DEBUG, "execute_txs_in_sandbox").entered();
+        let results = apply::apply_vm_in_sandbox(
+            vm_permit,
+            shared_args,
+            false,
+            &execution_args,
+            &connection_pool,
+            first_tx,
+            block_args,
+            |vm, first_tx, _| {
+                let txs = iter::once((first_tx, first_tx_tracers)).chain(txs);
+                txs.map(|(tx, custom_tracers)| {
+                    let storage_invocation_tracer =
+                        StorageInvocations::new(execution_args.missed_storage_invocation_limit);
+                    let custom_tracers: Vec<_> = custom_tracers
+                        .into_iter()
+                        .map(|tracer| tracer.into_boxed())
+                        .chain([storage_invocation_tracer.into_tracer_pointer()])
+                        .collect();
+                    let (_, result) = vm.inspect_transaction_with_bytecode_compression(
+                        custom_tracers.into(),
+                        tx,
+                        true,
+                    );
+                    result
+                })
+                .collect()
+            },
+        );
+        span.exit();
+        results
+    })
+    .await
+    .unwrap()
+}
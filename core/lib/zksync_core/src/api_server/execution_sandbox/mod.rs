@@ -7,17 +7,26 @@ use zksync_system_constants::PUBLISH_BYTECODE_OVERHEAD;
 use zksync_types::{api, fee_model::BatchFeeInput, AccountTreeId, L2ChainId, MiniblockNumber};
 use zksync_utils::bytecode::{compress_bytecode, hash_bytecode};
 
-use self::vm_metrics::SandboxStage;
 pub(super) use self::{
+    clients::VmClient,
     error::SandboxExecutionError,
-    execute::{execute_tx_eth_call, execute_tx_in_sandbox, TxExecutionArgs},
+    execute::{
+        execute_blocks_eth_call, execute_tx_eth_call, execute_tx_in_sandbox, execute_txs_eth_call,
+        exhausted_call_gas_limit, replay_txs_in_sandbox, SandboxBlock, SandboxBlockResult,
+        TxExecutionArgs,
+    },
     tracers::ApiTracer,
     vm_metrics::{SubmitTxStage, SANDBOX_METRICS},
 };
+use self::{
+    clients::{ClientPermit, ClientSlots},
+    vm_metrics::{SandboxStage, VmPermitPool},
+};
 use super::tx_sender::MultiVMBaseSystemContracts;
 
 // Note: keep the modules private, and instead re-export functions that make public interface.
 mod apply;
+mod clients;
 mod error;
 mod execute;
 mod tracers;
@@ -33,6 +42,10 @@ pub struct VmPermit {
     /// A handle to the runtime that is used to query the VM storage.
     rt_handle: Handle,
     _permit: Arc<tokio::sync::OwnedSemaphorePermit>,
+    /// Permit from the heavy calls sub-quota; only set for heavy calls.
+    _heavy_permit: Option<Arc<tokio::sync::OwnedSemaphorePermit>>,
+    /// Permit from the per-client sub-quota; only set if the limiter has per-client limits and the client is known.
+    _client_permit: Option<Arc<ClientPermit>>,
 }
 
 impl VmPermit {
@@ -46,6 +59,7 @@ impl VmPermit {
 #[derive(Debug, Clone)]
 pub struct VmConcurrencyBarrier {
     limiter: Arc<tokio::sync::Semaphore>,
+    heavy_limiter: Option<Arc<tokio::sync::Semaphore>>,
     max_concurrency: usize,
 }
 
@@ -53,6 +67,9 @@ impl VmConcurrencyBarrier {
     /// Shuts down the related VM concurrency limiter so that it won't issue new permits.
     pub fn close(&self) {
         self.limiter.close();
+        if let Some(heavy_limiter) = &self.heavy_limiter {
+            heavy_limiter.close();
+        }
         tracing::info!("VM concurrency limiter closed");
     }
 
@@ -89,42 +106,107 @@ impl VmConcurrencyBarrier {
 /// Note that the actual limit on the number of VMs is a minimum of the limit in this structure,
 /// *and* the size of the blocking tokio threadpool. So, even if the limit is set to 1024, but
 /// tokio is configured to have no more than 512 blocking threads, the actual limit will be 512.
+///
+/// Heavy calls (tracing and gas estimation) can be restricted to a sub-quota of VM permits, so that
+/// they cannot take up all permits and starve other VM calls.
+///
+/// Similarly, each client (as identified by [`VmClient::scope()`]) can be restricted to a sub-quota of VM permits,
+/// so that a single client cannot take up all permits. Since permits are issued in the FIFO order, this ensures
+/// that calls from other clients are scheduled fairly. Calls without an identified client are not subject
+/// to per-client limits.
 #[derive(Debug)]
 pub struct VmConcurrencyLimiter {
     /// Semaphore that limits the number of concurrent VM executions.
     limiter: Arc<tokio::sync::Semaphore>,
+    /// Semaphore that additionally limits the number of concurrent VM executions for heavy calls.
+    heavy_limiter: Option<Arc<tokio::sync::Semaphore>>,
+    /// Per-client limits on the number of concurrent VM executions.
+    client_slots: Option<Arc<ClientSlots>>,
     rt_handle: Handle,
 }
 
 impl VmConcurrencyLimiter {
     /// Creates a limiter together with a barrier allowing to control its shutdown.
     pub fn new(max_concurrency: usize) -> (Self, VmConcurrencyBarrier) {
+        Self::with_heavy_calls_quota(max_concurrency, None)
+    }
+
+    /// Creates a limiter with the specified sub-quota for heavy calls together with a barrier allowing
+    /// to control its shutdown.
+    pub fn with_heavy_calls_quota(
+        max_concurrency: usize,
+        max_heavy_concurrency: Option<usize>,
+    ) -> (Self, VmConcurrencyBarrier) {
         tracing::info!(
-            "Initializing the VM concurrency limiter with max concurrency {max_concurrency}"
+            "Initializing the VM concurrency limiter with max concurrency {max_concurrency} \
+             (heavy calls: {max_heavy_concurrency:?})"
         );
         let limiter = Arc::new(tokio::sync::Semaphore::new(max_concurrency));
+        let heavy_limiter =
+            max_heavy_concurrency.map(|limit| Arc::new(tokio::sync::Semaphore::new(limit)));
 
         let this = Self {
             limiter: Arc::clone(&limiter),
+            heavy_limiter: heavy_limiter.clone(),
+            client_slots: None,
             rt_handle: Handle::current(),
         };
         let barrier = VmConcurrencyBarrier {
             limiter,
+            heavy_limiter,
             max_concurrency,
         };
         (this, barrier)
     }
 
+    /// Limits the number of concurrent VM executions for a single client. If `max_client_concurrency` is `None`,
+    /// clients are not limited.
+    pub fn with_per_client_limit(mut self, max_client_concurrency: Option<usize>) -> Self {
+        if let Some(limit) = max_client_concurrency {
+            tracing::info!("Limiting VM concurrency for a single client to {limit}");
+        }
+        self.client_slots = max_client_concurrency.map(|limit| Arc::new(ClientSlots::new(limit)));
+        self
+    }
+
+    /// Returns `true` if the limiter restricts the number of concurrent VM executions per client.
+    pub(super) fn has_per_client_limit(&self) -> bool {
+        self.client_slots.is_some()
+    }
+
     /// Waits until there is a free slot in the concurrency limiter.
     /// Returns a permit that should be dropped when the VM execution is finished.
     pub async fn acquire(&self) -> Option<VmPermit> {
+        let client_permit = self.acquire_client_permit().await;
+        self.acquire_shared(client_permit).await
+    }
+
+    /// Waits for a slot in the per-client sub-quota for the client of the current task, if applicable.
+    async fn acquire_client_permit(&self) -> Option<ClientPermit> {
+        let client_slots = self.client_slots.as_ref()?;
+        let client = VmClient::current()?;
+
+        let latency =
+            SANDBOX_METRICS.sandbox[&SandboxStage::ClientVmConcurrencyLimiterAcquire].start();
+        let permit = {
+            let _guard = SANDBOX_METRICS.vm_permits_queue_depth[&VmPermitPool::Client].inc_guard(1);
+            client_slots.acquire(client).await
+        };
+        latency.observe();
+        Some(permit)
+    }
+
+    async fn acquire_shared(&self, client_permit: Option<ClientPermit>) -> Option<VmPermit> {
         let available_permits = self.limiter.available_permits();
         SANDBOX_METRICS
             .sandbox_execution_permits
             .observe(available_permits);
 
         let latency = SANDBOX_METRICS.sandbox[&SandboxStage::VmConcurrencyLimiterAcquire].start();
-        let permit = Arc::clone(&self.limiter).acquire_owned().await.ok()?;
+        let permit = {
+            let _guard = SANDBOX_METRICS.vm_permits_queue_depth[&VmPermitPool::Shared].inc_guard(1);
+            Arc::clone(&self.limiter).acquire_owned().await.ok()?
+        };
         let elapsed = latency.observe();
         // We don't want to emit too many logs.
         if elapsed > Duration::from_millis(10) {
@@ -136,8 +218,33 @@ impl VmConcurrencyLimiter {
         Some(VmPermit {
             rt_handle: self.rt_handle.clone(),
             _permit: Arc::new(permit),
+            _heavy_permit: None,
+            _client_permit: client_permit.map(Arc::new),
         })
     }
+
+    /// Same as [`Self::acquire()`], but for heavy calls. If the limiter has a sub-quota for heavy calls,
+    /// waits for a slot in it first (after waiting for a slot in the per-client sub-quota, if applicable).
+    pub async fn acquire_heavy(&self) -> Option<VmPermit> {
+        let Some(heavy_limiter) = &self.heavy_limiter else {
+            return self.acquire().await;
+        };
+        // Acquire the client permit first, so that a single client cannot hold up the heavy calls sub-quota
+        // while waiting for its own slots.
+        let client_permit = self.acquire_client_permit().await;
+
+        let latency =
+            SANDBOX_METRICS.sandbox[&SandboxStage::HeavyVmConcurrencyLimiterAcquire].start();
+        let heavy_permit = {
+            let _guard = SANDBOX_METRICS.vm_permits_queue_depth[&VmPermitPool::Heavy].inc_guard(1);
+            Arc::clone(heavy_limiter).acquire_owned().await.ok()?
+        };
+        latency.observe();
+
+        let mut permit = self.acquire_shared(client_permit).await?;
+        permit._heavy_permit = Some(Arc::new(heavy_permit));
+        Some(permit)
+    }
 }
 
 async fn get_pending_state(
@@ -205,6 +312,8 @@ pub(crate) struct TxSharedArgs {
     pub base_system_contracts: MultiVMBaseSystemContracts,
     pub caches: PostgresStorageCaches,
     pub validation_computational_gas_limit: u32,
+    /// Gas limit set for read-only calls.
+    pub call_gas_limit: u32,
     pub chain_id: L2ChainId,
 }
 
@@ -214,6 +323,8 @@ pub(crate) struct BlockArgs {
     block_id: api::BlockId,
     resolved_block_number: MiniblockNumber,
     l1_batch_timestamp_s: Option<u64>,
+    /// If set, the VM storage reflects the state before the resolved miniblock rather than after it.
+    state_before_block: bool,
 }
 
 impl BlockArgs {
@@ -223,6 +334,7 @@ impl BlockArgs {
             block_id,
             resolved_block_number,
             l1_batch_timestamp_s: None,
+            state_before_block: false,
         }
     }
 
@@ -260,6 +372,21 @@ impl BlockArgs {
             block_id,
             resolved_block_number,
             l1_batch_timestamp_s,
+            state_before_block: false,
+        }))
+    }
+
+    /// Loads information necessary to re-execute transactions included into the specified sealed miniblock.
+    /// The VM will run in the context of this miniblock on top of the state before it.
+    pub async fn for_replay(
+        connection: &mut StorageProcessor<'_>,
+        miniblock_number: MiniblockNumber,
+    ) -> Result<Option<Self>, SqlxError> {
+        let block_id = api::BlockId::Number(api::BlockNumber::Number(miniblock_number.0.into()));
+        let block_args = Self::new(connection, block_id).await?;
+        Ok(block_args.map(|args| Self {
+            state_before_block: true,
+            ..args
         }))
     }
 
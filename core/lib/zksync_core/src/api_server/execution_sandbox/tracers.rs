@@ -1,14 +1,35 @@
 use std::sync::Arc;
 
-use multivm::{tracers::CallTracer, vm_latest::HistoryMode, MultiVMTracer, MultiVmTracerPointer};
+use multivm::{
+    tracers::{
+        AccessListTracer, CallTracer, FourByteTracer, StructLogTracer, TraceLimiter,
+        TraceLimiterState,
+    },
+    vm_latest::HistoryMode,
+    MultiVMTracer, MultiVmTracerPointer,
+};
 use once_cell::sync::OnceCell;
 use zksync_state::WriteStorage;
-use zksync_types::vm_trace::Call;
+use zksync_types::{
+    vm_trace::{Call, FourByteTrace, StructLog, StructLogConfig, StructLogLimits, TraceLimits},
+    web3::types::AccessList,
+};
 
 /// Custom tracers supported by our API
 #[derive(Debug)]
 pub(crate) enum ApiTracer {
-    CallTracer(Arc<OnceCell<Vec<Call>>>),
+    /// Call tracer; reports the memory used by collected calls to the limiter state, if it's provided.
+    CallTracer(Arc<OnceCell<Vec<Call>>>, Option<Arc<TraceLimiterState>>),
+    /// Struct log tracer; records the exceeded [`StructLogLimits`] in the limiter state.
+    StructLogTracer(
+        StructLogConfig,
+        StructLogLimits,
+        Arc<OnceCell<Vec<StructLog>>>,
+        Arc<TraceLimiterState>,
+    ),
+    FourByteTracer(Arc<OnceCell<FourByteTrace>>),
+    TraceLimiter(TraceLimits, Arc<TraceLimiterState>),
+    AccessListTracer(Arc<OnceCell<AccessList>>),
 }
 
 impl ApiTracer {
@@ -19,7 +40,24 @@ impl ApiTracer {
         self,
     ) -> MultiVmTracerPointer<S, H> {
         match self {
-            ApiTracer::CallTracer(tracer) => CallTracer::new(tracer.clone()).into_tracer_pointer(),
+            ApiTracer::CallTracer(tracer, limiter_state) => {
+                let tracer = CallTracer::new(tracer);
+                let tracer = match limiter_state {
+                    Some(state) => tracer.with_limiter_state(state),
+                    None => tracer,
+                };
+                tracer.into_tracer_pointer()
+            }
+            ApiTracer::StructLogTracer(config, limits, tracer, limiter_state) => {
+                StructLogTracer::new(config, limits, limiter_state, tracer).into_tracer_pointer()
+            }
+            ApiTracer::FourByteTracer(tracer) => FourByteTracer::new(tracer).into_tracer_pointer(),
+            ApiTracer::TraceLimiter(limits, state) => {
+                TraceLimiter::new(limits, state).into_tracer_pointer()
+            }
+            ApiTracer::AccessListTracer(tracer) => {
+                AccessListTracer::new(tracer).into_tracer_pointer()
+            }
         }
     }
 }
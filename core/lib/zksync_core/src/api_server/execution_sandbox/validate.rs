@@ -46,7 +46,7 @@ impl TxSharedArgs {
                 &connection_pool,
                 tx,
                 block_args,
-                |vm, tx| {
+                |vm, tx, _| {
                     let stage_latency = SANDBOX_METRICS.sandbox[&SandboxStage::Validation].start();
                     let span = tracing::debug_span!("validation").entered();
                     vm.push_transaction(tx);
@@ -1,7 +1,7 @@
 use std::time::Duration;
 
 use multivm::interface::{VmExecutionResultAndLogs, VmMemoryMetrics};
-use vise::{Buckets, EncodeLabelSet, EncodeLabelValue, Family, Gauge, Histogram, Metrics};
+use vise::{Buckets, Counter, EncodeLabelSet, EncodeLabelValue, Family, Gauge, Histogram, Metrics};
 use zksync_state::StorageViewMetrics;
 use zksync_types::{
     event::{extract_long_l2_to_l1_messages, extract_published_bytecodes},
@@ -10,7 +10,7 @@ use zksync_types::{
 };
 use zksync_utils::bytecode::bytecode_len_in_bytes;
 
-use crate::metrics::InteractionType;
+use crate::{api_server::web3::metrics::CacheResult, metrics::InteractionType};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
 #[metrics(label = "type", rename_all = "snake_case")]
@@ -75,12 +75,26 @@ static STORAGE_METRICS: vise::Global<RuntimeContextStorageMetrics> = vise::Globa
 #[metrics(label = "stage", rename_all = "snake_case")]
 pub(super) enum SandboxStage {
     VmConcurrencyLimiterAcquire,
+    HeavyVmConcurrencyLimiterAcquire,
+    ClientVmConcurrencyLimiterAcquire,
     Initialization,
     ValidateInSandbox,
     Validation,
     Execution,
 }
 
+/// Pool of VM permits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "pool", rename_all = "snake_case")]
+pub(super) enum VmPermitPool {
+    /// Permits shared by all VM calls.
+    Shared,
+    /// Sub-quota of permits for heavy calls.
+    Heavy,
+    /// Per-client sub-quota of permits.
+    Client,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
 #[metrics(label = "stage", rename_all = "snake_case")]
 pub(in crate::api_server) enum SubmitTxStage {
@@ -103,10 +117,18 @@ pub(in crate::api_server) struct SandboxMetrics {
     pub(super) sandbox: Family<SandboxStage, Histogram<Duration>>,
     #[metrics(buckets = Buckets::linear(0.0..=2_000.0, 200.0))]
     pub(super) sandbox_execution_permits: Histogram<usize>,
+    /// Number of calls waiting for a VM permit.
+    pub(super) vm_permits_queue_depth: Family<VmPermitPool, Gauge<usize>>,
+    /// Number of clients with VM calls that are in flight or waiting for a permit.
+    pub(super) vm_clients: Gauge<usize>,
+    /// Number of VM calls that had to wait because the client has reached its per-client VM concurrency limit.
+    pub(super) vm_client_limit_reached: Counter,
     #[metrics(buckets = Buckets::LATENCIES)]
     pub submit_tx: Family<SubmitTxStage, Histogram<Duration>>,
     #[metrics(buckets = Buckets::linear(0.0..=30.0, 3.0))]
     pub estimate_gas_binary_search_iterations: Histogram<usize>,
+    /// Number of lookups in the gas estimation result cache grouped by the lookup result.
+    pub estimate_gas_cache: Family<CacheResult, Counter>,
 }
 
 #[vise::register]
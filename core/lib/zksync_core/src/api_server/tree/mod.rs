@@ -60,6 +60,14 @@ impl TreeEntryWithProof {
     }
 }
 
+/// Error returned by [`TreeApiClient`] (as the context of an `anyhow::Error`) if the requested tree version
+/// is not available, e.g. because it was pruned or is not processed by the tree yet.
+#[derive(Debug, thiserror::Error)]
+#[error("Merkle tree version for L1 batch #{l1_batch_number} is not available")]
+pub(crate) struct NoTreeVersionError {
+    pub l1_batch_number: L1BatchNumber,
+}
+
 #[derive(Debug)]
 enum TreeApiError {
     NoTreeVersion(NoVersionError),
@@ -91,6 +99,11 @@ pub(crate) trait TreeApiClient {
     async fn get_info(&self) -> anyhow::Result<MerkleTreeInfo>;
 
     /// Obtains proofs for the specified `hashed_keys` at the specified tree version (= L1 batch number).
+    /// Proofs can be obtained for any retained tree version, not only the latest one.
+    ///
+    /// # Errors
+    ///
+    /// If the requested tree version is not available, the returned error has [`NoTreeVersionError`] context.
     async fn get_proofs(
         &self,
         l1_batch_number: L1BatchNumber,
@@ -112,7 +125,7 @@ impl TreeApiClient for AsyncTreeReader {
     ) -> anyhow::Result<Vec<TreeEntryWithProof>> {
         self.get_proofs_inner(l1_batch_number, hashed_keys)
             .await
-            .map_err(Into::into)
+            .map_err(|err| anyhow::Error::new(err).context(NoTreeVersionError { l1_batch_number }))
     }
 }
 
@@ -167,8 +180,17 @@ impl TreeApiClient for TreeApiHttpClient {
             .send()
             .await
             .with_context(|| format!("Failed requesting proofs for L1 batch #{l1_batch_number}"))?;
-        let response = response.error_for_status().with_context(|| {
-            format!("Requesting proofs for L1 batch #{l1_batch_number} returned non-OK response")
+        let is_not_found = response.status() == StatusCode::NOT_FOUND;
+        let response = response.error_for_status().map_err(|err| {
+            let err = anyhow::Error::new(err);
+            let err = if is_not_found {
+                err.context(NoTreeVersionError { l1_batch_number })
+            } else {
+                err
+            };
+            err.context(format!(
+                "Requesting proofs for L1 batch #{l1_batch_number} returned non-OK response"
+            ))
         })?;
         let response: TreeProofsResponse = response.json().await.with_context(|| {
             format!("Failed deserializing proofs for L1 batch #{l1_batch_number}")
@@ -37,6 +37,7 @@ async fn merkle_tree_api() {
     let tree_info = api_client.get_info().await.unwrap();
     assert!(tree_info.leaf_count > 20);
     assert_eq!(tree_info.next_l1_batch_number, L1BatchNumber(6));
+    assert_eq!(tree_info.min_l1_batch_number, Some(L1BatchNumber(0)));
 
     let mut hashed_keys: Vec<_> = gen_storage_logs(20..30, 1)[0]
         .iter()
@@ -60,6 +61,8 @@ async fn merkle_tree_api() {
         .get_proofs(L1BatchNumber(10), vec![])
         .await
         .unwrap_err();
+    let no_version_err = err.downcast_ref::<NoTreeVersionError>().unwrap();
+    assert_eq!(no_version_err.l1_batch_number, L1BatchNumber(10));
     let err = format!("{err:?}");
     // Check that the error message contains all necessary info to troubleshoot it.
     assert!(
@@ -0,0 +1,128 @@
+//! Cache for gas estimation results.
+
+use std::{num::NonZeroUsize, sync::Mutex};
+
+use lru::LruCache;
+use serde::Serialize;
+use zksync_types::{fee::Fee, web3::signing::keccak256, Transaction, H256};
+
+use crate::api_server::{execution_sandbox::SANDBOX_METRICS, web3::metrics::CacheResult};
+
+/// Key in [`EstimateGasCache`]: hash of the latest sealed miniblock and hash of the normalized estimation request.
+pub(super) type EstimateGasCacheKey = (H256, H256);
+
+/// Estimation request as used in the cache key. Includes all inputs of the binary search except for the state.
+#[derive(Debug, Serialize)]
+struct EstimateGasRequest<'a> {
+    tx: &'a Transaction,
+    base_fee: u64,
+    gas_per_pubdata_byte: u64,
+    estimated_fee_scale_factor: f64,
+    acceptable_overestimation: u32,
+}
+
+/// LRU cache for gas estimation results. Results are keyed by the state they are estimated against
+/// (i.e., the latest sealed miniblock) and the normalized estimated transaction, so an entry can only be hit
+/// until the next miniblock is sealed.
+#[derive(Debug)]
+pub(super) struct EstimateGasCache(Option<Mutex<LruCache<EstimateGasCacheKey, Fee>>>);
+
+impl EstimateGasCache {
+    /// Creates a new cache with the specified capacity. If the capacity is 0, the cache is disabled.
+    pub fn new(capacity: usize) -> Self {
+        Self(NonZeroUsize::new(capacity).map(|capacity| Mutex::new(LruCache::new(capacity))))
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Computes the cache key for estimating the specified transaction. The transaction must be normalized,
+    /// i.e., have all fee-related fields set to the values used during estimation.
+    pub fn key(
+        block_hash: H256,
+        tx: &Transaction,
+        base_fee: u64,
+        gas_per_pubdata_byte: u64,
+        estimated_fee_scale_factor: f64,
+        acceptable_overestimation: u32,
+    ) -> EstimateGasCacheKey {
+        // The receipt timestamp is set to the current time when parsing the request; it doesn't influence estimation.
+        let tx = Transaction {
+            received_timestamp_ms: 0,
+            ..tx.clone()
+        };
+        let request = EstimateGasRequest {
+            tx: &tx,
+            base_fee,
+            gas_per_pubdata_byte,
+            estimated_fee_scale_factor,
+            acceptable_overestimation,
+        };
+        let request_bytes = serde_json::to_vec(&request).expect("failed serializing request");
+        (block_hash, H256(keccak256(&request_bytes)))
+    }
+
+    pub fn get(&self, key: &EstimateGasCacheKey) -> Option<Fee> {
+        let mut cache = self
+            .0
+            .as_ref()?
+            .lock()
+            .expect("estimate gas cache is poisoned");
+        let fee = cache.get(key).cloned();
+        let result = if fee.is_some() {
+            CacheResult::Hit
+        } else {
+            CacheResult::Miss
+        };
+        SANDBOX_METRICS.estimate_gas_cache[&result].inc();
+        fee
+    }
+
+    pub fn insert(&self, key: EstimateGasCacheKey, fee: Fee) {
+        if let Some(cache) = &self.0 {
+            let mut cache = cache.lock().expect("estimate gas cache is poisoned");
+            cache.put(key, fee);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testonly::create_l2_transaction;
+
+    #[test]
+    fn estimate_gas_cache_basics() {
+        let tx: Transaction = create_l2_transaction(10, 100).into();
+        let key = EstimateGasCache::key(H256::repeat_byte(1), &tx, 10, 100, 1.2, 1_000);
+        let fee = Fee {
+            gas_limit: 1_000_000.into(),
+            ..Fee::default()
+        };
+
+        let disabled_cache = EstimateGasCache::new(0);
+        assert!(!disabled_cache.is_enabled());
+        disabled_cache.insert(key, fee.clone());
+        assert_eq!(disabled_cache.get(&key), None);
+
+        let cache = EstimateGasCache::new(1);
+        cache.insert(key, fee.clone());
+        assert_eq!(cache.get(&key), Some(fee.clone()));
+
+        let mut same_tx = tx.clone();
+        same_tx.received_timestamp_ms += 1_000;
+        let same_key = EstimateGasCache::key(H256::repeat_byte(1), &same_tx, 10, 100, 1.2, 1_000);
+        assert_eq!(same_key, key);
+
+        let other_block_key = EstimateGasCache::key(H256::repeat_byte(2), &tx, 10, 100, 1.2, 1_000);
+        assert_ne!(other_block_key, key);
+        assert_eq!(cache.get(&other_block_key), None);
+        let other_fee_key = EstimateGasCache::key(H256::repeat_byte(1), &tx, 11, 100, 1.2, 1_000);
+        assert_ne!(other_fee_key, key);
+
+        cache.insert(other_fee_key, fee);
+        // The first entry should be evicted.
+        assert_eq!(cache.get(&key), None);
+    }
+}
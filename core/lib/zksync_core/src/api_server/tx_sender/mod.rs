@@ -1,35 +1,44 @@
 //! Helper module to submit transactions into the zkSync Network.
 
-use std::{cmp, sync::Arc, time::Instant};
+use std::{cmp, collections::HashSet, sync::Arc, time::Instant};
 
 use multivm::{
     interface::VmExecutionResultAndLogs,
     utils::{adjust_pubdata_price_for_tx, derive_base_fee_and_gas_per_pubdata, derive_overhead},
-    vm_latest::constants::{BLOCK_GAS_LIMIT, MAX_PUBDATA_PER_BLOCK},
+    vm_latest::constants::{BLOCK_GAS_LIMIT, ETH_CALL_GAS_LIMIT, MAX_PUBDATA_PER_BLOCK},
 };
+use once_cell::sync::OnceCell;
 use zksync_config::configs::{api::Web3JsonRpcConfig, chain::StateKeeperConfig};
 use zksync_contracts::BaseSystemContracts;
-use zksync_dal::{transactions_dal::L2TxSubmissionResult, ConnectionPool};
+use zksync_dal::{
+    transactions_dal::{BundleInsertionError, L2TxSubmissionResult},
+    ConnectionPool, StorageProcessor,
+};
 use zksync_state::PostgresStorageCaches;
 use zksync_types::{
+    api::{AccessListWithGasUsed, StateOverride},
     fee::{Fee, TransactionExecutionMetrics},
     fee_model::BatchFeeInput,
     get_code_key, get_intrinsic_constants,
-    l2::{error::TxCheckError::TxDuplication, L2Tx},
+    l2::{bundle::TransactionBundle, error::TxCheckError::TxDuplication, L2Tx},
     utils::storage_key_for_eth_balance,
     AccountTreeId, Address, ExecuteTransactionCommon, L2ChainId, Nonce, PackedEthSignature,
-    ProtocolVersionId, Transaction, VmVersion, H160, H256, MAX_GAS_PER_PUBDATA_BYTE,
+    ProtocolVersionId, StorageKey, Transaction, VmVersion, H160, H256, MAX_GAS_PER_PUBDATA_BYTE,
     MAX_L2_TX_GAS_LIMIT, MAX_NEW_FACTORY_DEPS, U256,
 };
-use zksync_utils::h256_to_u256;
+use zksync_utils::{bytecode::hash_bytecode, h256_to_u256, time::seconds_since_epoch};
 
+use self::estimate_cache::EstimateGasCache;
+pub use self::policy::{TxPolicyHandle, TxPolicyLoader, TxPolicyRule};
 pub(super) use self::{proxy::TxProxy, result::SubmitTxError};
 use super::execution_sandbox::execute_tx_in_sandbox;
 use crate::{
     api_server::{
         execution_sandbox::{
-            execute_tx_eth_call, get_pubdata_for_factory_deps, BlockArgs, SubmitTxStage,
-            TxExecutionArgs, TxSharedArgs, VmConcurrencyLimiter, VmPermit, SANDBOX_METRICS,
+            execute_blocks_eth_call, execute_tx_eth_call, exhausted_call_gas_limit,
+            get_pubdata_for_factory_deps, ApiTracer, BlockArgs, SandboxBlock, SandboxBlockResult,
+            SubmitTxStage, TxExecutionArgs, TxSharedArgs, VmConcurrencyLimiter, VmPermit,
+            SANDBOX_METRICS,
         },
         tx_sender::result::ApiCallResult,
     },
@@ -38,9 +47,16 @@ use crate::{
     state_keeper::seal_criteria::{ConditionalSealer, NoopSealer, SealData},
 };
 
+mod estimate_cache;
+mod policy;
 mod proxy;
 mod result;
 
+/// Maximum number of storage slots and balances in conditions of a single transaction.
+const MAX_STORAGE_CONDITIONS: usize = 1_000;
+/// Maximum number of transactions in a bundle submitted via `zks_sendBundle`.
+const MAX_BUNDLE_SIZE: usize = 16;
+
 #[derive(Debug, Clone)]
 pub struct MultiVMBaseSystemContracts {
     /// Contracts to be used for pre-virtual-blocks protocol versions.
@@ -137,6 +153,8 @@ pub struct TxSenderBuilder {
     proxy: Option<TxProxy>,
     /// Batch sealer used to check whether transaction can be executed by the sequencer.
     sealer: Option<Arc<dyn ConditionalSealer>>,
+    /// Policy restricting senders, target contracts, called functions and paymasters of submitted transactions.
+    policy: Option<TxPolicyHandle>,
 }
 
 impl TxSenderBuilder {
@@ -147,6 +165,7 @@ impl TxSenderBuilder {
             master_connection_pool: None,
             proxy: None,
             sealer: None,
+            policy: None,
         }
     }
 
@@ -160,6 +179,11 @@ impl TxSenderBuilder {
         self
     }
 
+    pub fn with_policy(mut self, policy: TxPolicyHandle) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
     pub fn with_main_connection_pool(mut self, master_connection_pool: ConnectionPool) -> Self {
         self.master_connection_pool = Some(master_connection_pool);
         self
@@ -179,6 +203,7 @@ impl TxSenderBuilder {
 
         // Use noop sealer if no sealer was explicitly provided.
         let sealer = self.sealer.unwrap_or_else(|| Arc::new(NoopSealer));
+        let estimate_gas_cache = EstimateGasCache::new(self.config.estimate_gas_cache_capacity);
 
         TxSender(Arc::new(TxSenderInner {
             sender_config: self.config,
@@ -190,6 +215,8 @@ impl TxSenderBuilder {
             vm_concurrency_limiter,
             storage_caches,
             sealer,
+            policy: self.policy,
+            estimate_gas_cache,
         }))
     }
 }
@@ -203,11 +230,24 @@ pub struct TxSenderConfig {
     pub fee_account_addr: Address,
     pub gas_price_scale_factor: f64,
     pub max_nonce_ahead: u32,
+    pub replacement_fee_bump_percent: u32,
+    pub max_pending_txs_per_account: Option<u32>,
+    pub max_pending_txs_per_paymaster: Option<u32>,
     pub max_allowed_l2_tx_gas_limit: u32,
     pub fair_l2_gas_price: u64,
     pub vm_execution_cache_misses_limit: Option<usize>,
     pub validation_computational_gas_limit: u32,
     pub chain_id: L2ChainId,
+    /// Initial lower bound of the gas estimation binary search.
+    pub estimate_gas_lower_bound: u32,
+    /// Initial upper bound of the gas estimation binary search. Capped by the max gas limit of an L2 transaction.
+    pub estimate_gas_upper_bound: Option<u32>,
+    /// Maximum number of iterations of the gas estimation binary search.
+    pub estimate_gas_max_iterations: Option<usize>,
+    /// Capacity of the gas estimation result cache; 0 disables caching.
+    pub estimate_gas_cache_capacity: usize,
+    /// Gas limit for read-only calls. If not set, [`ETH_CALL_GAS_LIMIT`] is used.
+    pub call_gas_limit: Option<u32>,
 }
 
 impl TxSenderConfig {
@@ -220,14 +260,31 @@ impl TxSenderConfig {
             fee_account_addr: state_keeper_config.fee_account_addr,
             gas_price_scale_factor: web3_json_config.gas_price_scale_factor,
             max_nonce_ahead: web3_json_config.max_nonce_ahead,
+            replacement_fee_bump_percent: web3_json_config.replacement_fee_bump_percent(),
+            max_pending_txs_per_account: web3_json_config.max_pending_txs_per_account,
+            max_pending_txs_per_paymaster: web3_json_config.max_pending_txs_per_paymaster,
             max_allowed_l2_tx_gas_limit: state_keeper_config.max_allowed_l2_tx_gas_limit,
             fair_l2_gas_price: state_keeper_config.fair_l2_gas_price,
             vm_execution_cache_misses_limit: web3_json_config.vm_execution_cache_misses_limit,
             validation_computational_gas_limit: state_keeper_config
                 .validation_computational_gas_limit,
             chain_id,
+            estimate_gas_lower_bound: web3_json_config.estimate_gas_lower_bound(),
+            estimate_gas_upper_bound: web3_json_config.estimate_gas_upper_bound,
+            estimate_gas_max_iterations: web3_json_config.estimate_gas_max_iterations,
+            estimate_gas_cache_capacity: web3_json_config.estimate_gas_cache_capacity(),
+            call_gas_limit: web3_json_config.call_gas_limit,
         }
     }
+
+    /// Returns the gas limit for read-only calls. The limit is capped so that the bootloader has enough gas
+    /// left for its own overhead.
+    pub fn call_gas_limit(&self) -> u32 {
+        const MAX_CALL_GAS_LIMIT: u32 = BLOCK_GAS_LIMIT / 2;
+
+        self.call_gas_limit
+            .map_or(ETH_CALL_GAS_LIMIT, |limit| limit.min(MAX_CALL_GAS_LIMIT))
+    }
 }
 
 pub struct TxSenderInner {
@@ -245,6 +302,10 @@ pub struct TxSenderInner {
     storage_caches: PostgresStorageCaches,
     /// Batch sealer used to check whether transaction can be executed by the sequencer.
     sealer: Arc<dyn ConditionalSealer>,
+    /// Policy restricting senders, target contracts, called functions and paymasters of submitted transactions.
+    policy: Option<TxPolicyHandle>,
+    /// Cache for gas estimation results.
+    estimate_gas_cache: EstimateGasCache,
 }
 
 #[derive(Clone)]
@@ -267,8 +328,164 @@ impl TxSender {
 
     #[tracing::instrument(skip(self, tx))]
     pub async fn submit_tx(&self, tx: L2Tx) -> Result<L2TxSubmissionResult, SubmitTxError> {
+        self.submit_tx_inner(tx, false).await
+    }
+
+    /// Submits a private transaction. Private transactions are processed in the same way as ordinary ones,
+    /// but are not exposed via pending transaction subscriptions and mempool introspection until they are sealed.
+    #[tracing::instrument(skip(self, tx))]
+    pub async fn submit_private_tx(&self, tx: L2Tx) -> Result<L2TxSubmissionResult, SubmitTxError> {
+        self.submit_tx_inner(tx, true).await
+    }
+
+    async fn submit_tx_inner(
+        &self,
+        tx: L2Tx,
+        is_private: bool,
+    ) -> Result<L2TxSubmissionResult, SubmitTxError> {
+        let tx_metrics = self.validate_submitted_tx(&tx).await?;
+
+        let stage_started_at = Instant::now();
+        if let Some(proxy) = &self.0.proxy {
+            // We're running an external node: we have to proxy the transaction to the main node.
+            // But before we do that, save the tx to cache in case someone will request it
+            // Before it reaches the main node.
+            proxy.save_tx(tx.hash(), tx.clone()).await;
+            if is_private {
+                proxy.submit_private_tx(&tx).await?;
+            } else {
+                proxy.submit_tx(&tx).await?;
+            }
+            // Now, after we are sure that the tx is on the main node, remove it from cache
+            // since we don't want to store txs that might have been replaced or otherwise removed
+            // from the mempool.
+            proxy.forget_tx(tx.hash()).await;
+            SANDBOX_METRICS.submit_tx[&SubmitTxStage::TxProxy].observe(stage_started_at.elapsed());
+            APP_METRICS.processed_txs[&TxStage::Proxied].inc();
+            return Ok(L2TxSubmissionResult::Proxied);
+        } else {
+            assert!(
+                self.0.master_connection_pool.is_some(),
+                "TxSender is instantiated without both master connection pool and tx proxy"
+            );
+        }
+
+        let nonce = tx.common_data.nonce.0;
+        let hash = tx.hash();
+        let expected_nonce = self.get_expected_nonce(&tx).await;
+        let mut connection = self
+            .0
+            .master_connection_pool
+            .as_ref()
+            .unwrap() // Checked above
+            .access_storage_tagged("api")
+            .await
+            .unwrap();
+        let is_replacement = self
+            .ensure_sufficient_fee_bump(&mut connection, &tx)
+            .await?;
+        if !is_replacement {
+            self.ensure_pending_txs_limits(&mut connection, &tx).await?;
+        }
+        let mut transactions_dal = connection.transactions_dal();
+        let submission_res_handle = if is_private {
+            transactions_dal
+                .insert_private_transaction_l2(tx, tx_metrics)
+                .await
+        } else {
+            transactions_dal.insert_transaction_l2(tx, tx_metrics).await
+        };
+
+        APP_METRICS.processed_txs[&TxStage::Mempool(submission_res_handle)].inc();
+
+        match submission_res_handle {
+            L2TxSubmissionResult::AlreadyExecuted => Err(SubmitTxError::NonceIsTooLow(
+                expected_nonce.0,
+                expected_nonce.0 + self.0.sender_config.max_nonce_ahead,
+                nonce,
+            )),
+            L2TxSubmissionResult::Duplicate => Err(SubmitTxError::IncorrectTx(TxDuplication(hash))),
+            _ => {
+                SANDBOX_METRICS.submit_tx[&SubmitTxStage::DbInsert]
+                    .observe(stage_started_at.elapsed());
+                Ok(submission_res_handle)
+            }
+        }
+    }
+
+    /// Submits an ordered bundle of transactions that must be included into a single miniblock
+    /// contiguously, or not included at all. Returns the bundle hash.
+    #[tracing::instrument(skip(self, transactions))]
+    pub async fn submit_bundle(&self, transactions: Vec<L2Tx>) -> Result<H256, SubmitTxError> {
+        if transactions.is_empty() || transactions.len() > MAX_BUNDLE_SIZE {
+            return Err(SubmitTxError::InvalidBundleSize(
+                transactions.len(),
+                MAX_BUNDLE_SIZE,
+            ));
+        }
+        let mut tx_hashes = HashSet::with_capacity(transactions.len());
+        for tx in &transactions {
+            if !tx_hashes.insert(tx.hash()) {
+                return Err(SubmitTxError::IncorrectTx(TxDuplication(tx.hash())));
+            }
+        }
+
+        let mut bundle_txs = Vec::with_capacity(transactions.len());
+        for tx in transactions {
+            let tx_metrics = self.validate_submitted_tx(&tx).await?;
+            bundle_txs.push((tx, tx_metrics));
+        }
+        let bundle_hash =
+            TransactionBundle::compute_hash(bundle_txs.iter().map(|(tx, _)| tx.hash()));
+
+        let stage_started_at = Instant::now();
+        if let Some(proxy) = &self.0.proxy {
+            // We're running an external node: we have to proxy the bundle to the main node.
+            let txs: Vec<_> = bundle_txs.into_iter().map(|(tx, _)| tx).collect();
+            proxy.submit_bundle(&txs).await?;
+            SANDBOX_METRICS.submit_tx[&SubmitTxStage::TxProxy].observe(stage_started_at.elapsed());
+            APP_METRICS.processed_txs[&TxStage::Proxied].inc_by(txs.len() as u64);
+            return Ok(bundle_hash);
+        }
+
+        let bundle_len = bundle_txs.len() as u64;
+        let insertion_result = self
+            .0
+            .master_connection_pool
+            .as_ref()
+            .expect("TxSender is instantiated without both master connection pool and tx proxy")
+            .access_storage_tagged("api")
+            .await
+            .unwrap()
+            .transactions_dal()
+            .insert_transaction_bundle(bundle_hash, bundle_txs)
+            .await;
+
+        match insertion_result {
+            Ok(()) => {
+                APP_METRICS.processed_txs[&TxStage::Mempool(L2TxSubmissionResult::Added)]
+                    .inc_by(bundle_len);
+                SANDBOX_METRICS.submit_tx[&SubmitTxStage::DbInsert]
+                    .observe(stage_started_at.elapsed());
+                Ok(bundle_hash)
+            }
+            Err(BundleInsertionError {
+                tx_hash,
+                result: L2TxSubmissionResult::Duplicate,
+            }) => Err(SubmitTxError::IncorrectTx(TxDuplication(tx_hash))),
+            Err(BundleInsertionError { tx_hash, result }) => {
+                Err(SubmitTxError::BundleTxNotInserted(tx_hash, result))
+            }
+        }
+    }
+
+    /// Validates a submitted transaction and executes it in the sandbox, returning its execution metrics.
+    async fn validate_submitted_tx(
+        &self,
+        tx: &L2Tx,
+    ) -> Result<TransactionExecutionMetrics, SubmitTxError> {
         let stage_latency = SANDBOX_METRICS.submit_tx[&SubmitTxStage::Validate].start();
-        self.validate_tx(&tx).await?;
+        self.validate_tx(tx).await?;
         stage_latency.observe();
 
         let stage_latency = SANDBOX_METRICS.submit_tx[&SubmitTxStage::DryRun].start();
@@ -283,6 +500,7 @@ impl TxSender {
             .unwrap();
         let block_args = BlockArgs::pending(&mut connection).await;
         drop(connection);
+        self.validate_tx_conditions(tx, block_args).await?;
 
         let (_, tx_metrics, published_bytecodes) = execute_tx_in_sandbox(
             vm_permit.clone(),
@@ -324,59 +542,8 @@ impl TxSender {
             return Err(SubmitTxError::FailedToPublishCompressedBytecodes);
         }
 
-        let stage_started_at = Instant::now();
         self.ensure_tx_executable(tx.clone().into(), &tx_metrics, true)?;
-
-        if let Some(proxy) = &self.0.proxy {
-            // We're running an external node: we have to proxy the transaction to the main node.
-            // But before we do that, save the tx to cache in case someone will request it
-            // Before it reaches the main node.
-            proxy.save_tx(tx.hash(), tx.clone()).await;
-            proxy.submit_tx(&tx).await?;
-            // Now, after we are sure that the tx is on the main node, remove it from cache
-            // since we don't want to store txs that might have been replaced or otherwise removed
-            // from the mempool.
-            proxy.forget_tx(tx.hash()).await;
-            SANDBOX_METRICS.submit_tx[&SubmitTxStage::TxProxy].observe(stage_started_at.elapsed());
-            APP_METRICS.processed_txs[&TxStage::Proxied].inc();
-            return Ok(L2TxSubmissionResult::Proxied);
-        } else {
-            assert!(
-                self.0.master_connection_pool.is_some(),
-                "TxSender is instantiated without both master connection pool and tx proxy"
-            );
-        }
-
-        let nonce = tx.common_data.nonce.0;
-        let hash = tx.hash();
-        let expected_nonce = self.get_expected_nonce(&tx).await;
-        let submission_res_handle = self
-            .0
-            .master_connection_pool
-            .as_ref()
-            .unwrap() // Checked above
-            .access_storage_tagged("api")
-            .await
-            .unwrap()
-            .transactions_dal()
-            .insert_transaction_l2(tx, tx_metrics)
-            .await;
-
-        APP_METRICS.processed_txs[&TxStage::Mempool(submission_res_handle)].inc();
-
-        match submission_res_handle {
-            L2TxSubmissionResult::AlreadyExecuted => Err(SubmitTxError::NonceIsTooLow(
-                expected_nonce.0,
-                expected_nonce.0 + self.0.sender_config.max_nonce_ahead,
-                nonce,
-            )),
-            L2TxSubmissionResult::Duplicate => Err(SubmitTxError::IncorrectTx(TxDuplication(hash))),
-            _ => {
-                SANDBOX_METRICS.submit_tx[&SubmitTxStage::DbInsert]
-                    .observe(stage_started_at.elapsed());
-                Ok(submission_res_handle)
-            }
-        }
+        Ok(tx_metrics)
     }
 
     fn shared_args(&self) -> TxSharedArgs {
@@ -389,11 +556,16 @@ impl TxSender {
                 .0
                 .sender_config
                 .validation_computational_gas_limit,
+            call_gas_limit: self.0.sender_config.call_gas_limit(),
             chain_id: self.0.sender_config.chain_id,
         }
     }
 
     async fn validate_tx(&self, tx: &L2Tx) -> Result<(), SubmitTxError> {
+        if let Some(policy) = &self.0.policy {
+            policy.check(tx).map_err(SubmitTxError::PolicyViolation)?;
+        }
+
         let max_gas = U256::from(u32::MAX);
         if tx.common_data.fee.gas_limit > max_gas
             || tx.common_data.fee.gas_per_pubdata_limit > max_gas
@@ -476,6 +648,114 @@ impl TxSender {
         }
     }
 
+    /// Checks conditions of a transaction submitted via `eth_sendRawTransactionConditional` against
+    /// the pending miniblock. The state keeper re-checks the conditions before executing the transaction.
+    async fn validate_tx_conditions(
+        &self,
+        tx: &L2Tx,
+        block_args: BlockArgs,
+    ) -> Result<(), SubmitTxError> {
+        let Some(conditions) = &tx.common_data.conditions else {
+            return Ok(());
+        };
+        let conditions_count = conditions.storage_conditions_count();
+        if conditions_count > MAX_STORAGE_CONDITIONS {
+            return Err(SubmitTxError::TooManyConditions(
+                conditions_count,
+                MAX_STORAGE_CONDITIONS,
+            ));
+        }
+        conditions.check_block(block_args.resolved_block_number(), seconds_since_epoch())?;
+
+        let mut connection = self
+            .0
+            .replica_connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap();
+        let values = connection
+            .storage_dal()
+            .get_by_keys(&conditions.storage_keys())
+            .await;
+        conditions
+            .check_storage(|key| values.get(&key.hashed_key()).copied().unwrap_or_default())?;
+        Ok(())
+    }
+
+    /// Checks that a transaction replacing a pending transaction with the same initiator and nonce increases
+    /// both max fee per gas and max priority fee per gas by at least the configured percentage.
+    /// Returns whether the transaction replaces (or duplicates) a pending transaction.
+    async fn ensure_sufficient_fee_bump(
+        &self,
+        connection: &mut StorageProcessor<'_>,
+        tx: &L2Tx,
+    ) -> Result<bool, SubmitTxError> {
+        let pending_tx = connection
+            .transactions_dal()
+            .get_pending_l2_tx_fee(tx.initiator_account(), tx.common_data.nonce)
+            .await
+            .unwrap();
+        let Some((pending_tx_hash, pending_fee)) = pending_tx else {
+            return Ok(false);
+        };
+        if pending_tx_hash == tx.hash() {
+            return Ok(true); // Resubmitting the same transaction is allowed
+        }
+
+        let bump_percent = self.0.sender_config.replacement_fee_bump_percent;
+        let bumped = |fee: U256| fee * (100 + bump_percent) / 100;
+        let min_max_fee_per_gas = bumped(pending_fee.max_fee_per_gas);
+        let min_max_priority_fee_per_gas = bumped(pending_fee.max_priority_fee_per_gas);
+        let fee = &tx.common_data.fee;
+        if fee.max_fee_per_gas < min_max_fee_per_gas
+            || fee.max_priority_fee_per_gas < min_max_priority_fee_per_gas
+        {
+            return Err(SubmitTxError::ReplacementUnderpriced(
+                min_max_fee_per_gas,
+                min_max_priority_fee_per_gas,
+            ));
+        }
+        Ok(true)
+    }
+
+    /// Checks that adding a transaction to the mempool doesn't exceed the configured limits on the number
+    /// of pending transactions per sender and per paymaster.
+    async fn ensure_pending_txs_limits(
+        &self,
+        connection: &mut StorageProcessor<'_>,
+        tx: &L2Tx,
+    ) -> Result<(), SubmitTxError> {
+        let sender_config = &self.0.sender_config;
+        if let Some(limit) = sender_config.max_pending_txs_per_account {
+            let sender = tx.initiator_account();
+            let (sent_count, _) = connection
+                .transactions_dal()
+                .get_pending_l2_txs_count(sender)
+                .await
+                .unwrap();
+            if sent_count >= limit as usize {
+                return Err(SubmitTxError::SenderPendingTxsLimitExceeded(sender, limit));
+            }
+        }
+
+        let paymaster = tx.common_data.paymaster_params.paymaster;
+        if let Some(limit) = sender_config.max_pending_txs_per_paymaster {
+            if paymaster != Address::zero() {
+                let (_, sponsored_count) = connection
+                    .transactions_dal()
+                    .get_pending_l2_txs_count(paymaster)
+                    .await
+                    .unwrap();
+                if sponsored_count >= limit as usize {
+                    return Err(SubmitTxError::PaymasterPendingTxsLimitExceeded(
+                        paymaster, limit,
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
     async fn get_expected_nonce(&self, tx: &L2Tx) -> Nonce {
         let mut connection = self
             .0
@@ -508,7 +788,8 @@ impl TxSender {
 
         let balance = self.get_balance(&tx.common_data.initiator_address).await;
 
-        // Estimate the minimum fee price user will agree to.
+        // Estimate the minimum fee price user will agree to. Both the balance and gas prices are denominated
+        // in the fee token of the chain.
         let gas_price = cmp::min(
             tx.common_data.fee.max_fee_per_gas,
             U256::from(self.0.sender_config.fair_l2_gas_price)
@@ -528,8 +809,10 @@ impl TxSender {
         }
     }
 
+    /// Returns the balance of the account in the fee token of the chain. Balances of the base token are kept
+    /// by the same system contract as ETH balances, so this works both for ETH and for ERC-20 fee tokens.
     async fn get_balance(&self, initiator_address: &H160) -> U256 {
-        let eth_balance_key = storage_key_for_eth_balance(initiator_address);
+        let balance_key = storage_key_for_eth_balance(initiator_address);
 
         let balance = self
             .0
@@ -538,7 +821,7 @@ impl TxSender {
             .await
             .unwrap()
             .storage_dal()
-            .get_by_key(&eth_balance_key)
+            .get_by_key(&balance_key)
             .await
             .unwrap_or_default();
 
@@ -558,6 +841,7 @@ impl TxSender {
         block_args: BlockArgs,
         base_fee: u64,
         vm_version: VmVersion,
+        state_override: Option<&StateOverride>,
     ) -> (VmExecutionResultAndLogs, TransactionExecutionMetrics) {
         let gas_limit_with_overhead = tx_gas_limit
             + derive_overhead(
@@ -590,8 +874,9 @@ impl TxSender {
 
         let shared_args = self.shared_args_for_gas_estimate(fee_input);
         let vm_execution_cache_misses_limit = self.0.sender_config.vm_execution_cache_misses_limit;
-        let execution_args =
+        let mut execution_args =
             TxExecutionArgs::for_gas_estimate(vm_execution_cache_misses_limit, &tx, base_fee);
+        execution_args.state_override = state_override.cloned();
         let (exec_result, tx_metrics, _) = execute_tx_in_sandbox(
             vm_permit,
             shared_args,
@@ -614,17 +899,22 @@ impl TxSender {
             fee_input,
             // We want to bypass the computation gas limit check for gas estimation
             validation_computational_gas_limit: BLOCK_GAS_LIMIT,
+            call_gas_limit: config.call_gas_limit(),
             base_system_contracts: self.0.api_contracts.estimate_gas.clone(),
             caches: self.storage_caches(),
             chain_id: config.chain_id,
         }
     }
 
+    /// Estimates the fee for a transaction executed in the context of the specified block. The current fee input
+    /// is used regardless of the block.
     pub async fn get_txs_fee_in_wei(
         &self,
         mut tx: Transaction,
+        block_args: BlockArgs,
         estimated_fee_scale_factor: f64,
         acceptable_overestimation: u32,
+        state_override: Option<StateOverride>,
     ) -> Result<Fee, SubmitTxError> {
         let estimation_started_at = Instant::now();
 
@@ -634,7 +924,6 @@ impl TxSender {
             .access_storage_tagged("api")
             .await
             .unwrap();
-        let block_args = BlockArgs::pending(&mut connection).await;
         // If protocol version is not present, we'll use the pre-boojum one
         let protocol_version = connection
             .blocks_dal()
@@ -642,6 +931,18 @@ impl TxSender {
             .await
             .unwrap()
             .unwrap_or(ProtocolVersionId::last_pre_boojum());
+        // Estimation results are only cached for the pending block, and only if they don't depend on a state override.
+        let is_cacheable = block_args.is_pending_miniblock() && state_override.is_none();
+        let sealed_block_hash = if self.0.estimate_gas_cache.is_enabled() && is_cacheable {
+            let sealed_block_number = block_args.resolved_block_number() - 1;
+            connection
+                .blocks_web3_dal()
+                .get_miniblock_hash(sealed_block_number)
+                .await
+                .unwrap()
+        } else {
+            None
+        };
         drop(connection);
 
         let fee_input = {
@@ -672,25 +973,34 @@ impl TxSender {
             }
         }
 
-        let hashed_key = get_code_key(&tx.initiator_account());
+        let initiator_account = tx.initiator_account();
+        let initiator_override = state_override
+            .as_ref()
+            .and_then(|state_override| state_override.get(&initiator_account));
         // if the default account does not have enough funds
         // for transferring tx.value, without taking into account the fee,
         // there is no sense to estimate the fee
-        let account_code_hash = self
-            .0
-            .replica_connection_pool
-            .access_storage_tagged("api")
-            .await
-            .unwrap()
-            .storage_dal()
-            .get_by_key(&hashed_key)
-            .await
-            .unwrap_or_default();
+        let account_code_hash =
+            if let Some(code) = initiator_override.and_then(|acc| acc.code.as_ref()) {
+                hash_bytecode(&code.0)
+            } else {
+                let hashed_key = get_code_key(&initiator_account);
+                self.0
+                    .replica_connection_pool
+                    .access_storage_tagged("api")
+                    .await
+                    .unwrap()
+                    .storage_dal()
+                    .get_by_key(&hashed_key)
+                    .await
+                    .unwrap_or_default()
+            };
+        let balance = match initiator_override.and_then(|acc| acc.balance) {
+            Some(balance) => balance,
+            None => self.get_balance(&initiator_account).await,
+        };
 
-        if !tx.is_l1()
-            && account_code_hash == H256::zero()
-            && tx.execute.value > self.get_balance(&tx.initiator_account()).await
-        {
+        if !tx.is_l1() && account_code_hash == H256::zero() && tx.execute.value > balance {
             tracing::info!(
                 "fee estimation failed on validation step.
                 account: {} does not have enough funds for for transferring tx.value: {}.",
@@ -709,8 +1019,25 @@ impl TxSender {
             l2_common_data.fee.gas_per_pubdata_limit = MAX_GAS_PER_PUBDATA_BYTE.into();
         }
 
+        let cache_key = sealed_block_hash.map(|block_hash| {
+            EstimateGasCache::key(
+                block_hash,
+                &tx,
+                base_fee,
+                gas_per_pubdata_byte,
+                estimated_fee_scale_factor,
+                acceptable_overestimation,
+            )
+        });
+        let cached_fee = cache_key
+            .as_ref()
+            .and_then(|key| self.0.estimate_gas_cache.get(key));
+        if let Some(fee) = cached_fee {
+            return Ok(fee);
+        }
+
         // Acquire the vm token for the whole duration of the binary search.
-        let vm_permit = self.0.vm_concurrency_limiter.acquire().await;
+        let vm_permit = self.0.vm_concurrency_limiter.acquire_heavy().await;
         let vm_permit = vm_permit.ok_or(SubmitTxError::ServerShuttingDown)?;
 
         // We already know how many gas is needed to cover for the publishing of the bytecodes.
@@ -737,8 +1064,13 @@ impl TxSender {
 
         // We are using binary search to find the minimal values of gas_limit under which
         // the transaction succeeds
-        let mut lower_bound = 0;
-        let mut upper_bound = MAX_L2_TX_GAS_LIMIT as u32;
+        let search_config = &self.0.sender_config;
+        let mut upper_bound = search_config
+            .estimate_gas_upper_bound
+            .map_or(MAX_L2_TX_GAS_LIMIT as u32, |bound| {
+                bound.min(MAX_L2_TX_GAS_LIMIT as u32)
+            });
+        let mut lower_bound = search_config.estimate_gas_lower_bound.min(upper_bound);
         let tx_id = format!(
             "{:?}-{}",
             tx.initiator_account(),
@@ -752,6 +1084,18 @@ impl TxSender {
 
         let mut number_of_iterations = 0usize;
         while lower_bound + acceptable_overestimation < upper_bound {
+            if search_config
+                .estimate_gas_max_iterations
+                .map_or(false, |max_iterations| {
+                    number_of_iterations >= max_iterations
+                })
+            {
+                tracing::debug!(
+                    "fee estimation tx {tx_id:?}: reached max number of iterations; \
+                     lower_bound: {lower_bound}, upper_bound: {upper_bound}"
+                );
+                break;
+            }
             let mid = (lower_bound + upper_bound) / 2;
             // There is no way to distinct between errors due to out of gas
             // or normal execution errors, so we just hope that increasing the
@@ -768,6 +1112,7 @@ impl TxSender {
                     block_args,
                     base_fee,
                     protocol_version.into(),
+                    state_override.as_ref(),
                 )
                 .await;
 
@@ -807,6 +1152,7 @@ impl TxSender {
                 block_args,
                 base_fee,
                 protocol_version.into(),
+                state_override.as_ref(),
             )
             .await;
 
@@ -832,34 +1178,103 @@ impl TxSender {
                 }
             };
 
-        Ok(Fee {
+        let fee = Fee {
             max_fee_per_gas: base_fee.into(),
             max_priority_fee_per_gas: 0u32.into(),
             gas_limit: full_gas_limit.into(),
             gas_per_pubdata_limit: gas_per_pubdata_byte.into(),
-        })
+        };
+        if let Some(key) = cache_key {
+            self.0.estimate_gas_cache.insert(key, fee.clone());
+        }
+        Ok(fee)
     }
 
     pub(super) async fn eth_call(
         &self,
         block_args: BlockArgs,
         tx: L2Tx,
+        state_override: Option<StateOverride>,
+        prefetched_keys: Vec<StorageKey>,
     ) -> Result<Vec<u8>, SubmitTxError> {
         let vm_permit = self.0.vm_concurrency_limiter.acquire().await;
         let vm_permit = vm_permit.ok_or(SubmitTxError::ServerShuttingDown)?;
 
         let vm_execution_cache_misses_limit = self.0.sender_config.vm_execution_cache_misses_limit;
-        execute_tx_eth_call(
+        let call_gas_limit = self.0.sender_config.call_gas_limit();
+        let result = execute_tx_eth_call(
+            vm_permit,
+            self.shared_args(),
+            self.0.replica_connection_pool.clone(),
+            tx,
+            block_args,
+            vm_execution_cache_misses_limit,
+            state_override,
+            prefetched_keys,
+            vec![],
+        )
+        .await;
+        if exhausted_call_gas_limit(&result, call_gas_limit) {
+            return Err(SubmitTxError::CallComputeLimitExceeded(call_gas_limit));
+        }
+        result.into_api_call_result()
+    }
+
+    /// Executes blocks of calls on top of the same state, as required by `eth_simulateV1`.
+    pub(super) async fn eth_simulate(
+        &self,
+        block_args: BlockArgs,
+        blocks: Vec<SandboxBlock>,
+    ) -> Result<Vec<SandboxBlockResult>, SubmitTxError> {
+        let vm_permit = self.0.vm_concurrency_limiter.acquire().await;
+        let vm_permit = vm_permit.ok_or(SubmitTxError::ServerShuttingDown)?;
+
+        let vm_execution_cache_misses_limit = self.0.sender_config.vm_execution_cache_misses_limit;
+        Ok(execute_blocks_eth_call(
+            vm_permit,
+            self.shared_args(),
+            self.0.replica_connection_pool.clone(),
+            block_args,
+            vm_execution_cache_misses_limit,
+            blocks,
+        )
+        .await)
+    }
+
+    /// Executes a call and returns storage slots touched by it together with the gas used.
+    pub(super) async fn create_access_list(
+        &self,
+        block_args: BlockArgs,
+        tx: L2Tx,
+        state_override: Option<StateOverride>,
+    ) -> Result<AccessListWithGasUsed, SubmitTxError> {
+        let vm_permit = self.0.vm_concurrency_limiter.acquire().await;
+        let vm_permit = vm_permit.ok_or(SubmitTxError::ServerShuttingDown)?;
+
+        let access_list = Arc::new(OnceCell::default());
+        let vm_execution_cache_misses_limit = self.0.sender_config.vm_execution_cache_misses_limit;
+        let result = execute_tx_eth_call(
             vm_permit,
             self.shared_args(),
             self.0.replica_connection_pool.clone(),
             tx,
             block_args,
             vm_execution_cache_misses_limit,
+            state_override,
             vec![],
+            vec![ApiTracer::AccessListTracer(access_list.clone())],
         )
-        .await
-        .into_api_call_result()
+        .await;
+        let gas_used = result.statistics.gas_used.into();
+        result.into_api_call_result()?;
+
+        Ok(AccessListWithGasUsed {
+            access_list: Arc::try_unwrap(access_list)
+                .unwrap()
+                .take()
+                .unwrap_or_default(),
+            gas_used,
+        })
     }
 
     pub async fn gas_price(&self) -> u64 {
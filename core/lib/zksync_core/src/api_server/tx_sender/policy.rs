@@ -0,0 +1,465 @@
+//! Policy restricting transaction senders, target contracts, called functions and paymasters.
+
+use std::{
+    collections::HashSet,
+    fmt,
+    hash::Hash,
+    num::NonZeroU32,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::Context as _;
+use governor::{clock::DefaultClock, state::keyed::DefaultKeyedStateStore, Quota, RateLimiter};
+use serde::{de, Deserialize, Deserializer};
+use tokio::sync::watch;
+use vise::{Counter, EncodeLabelSet, EncodeLabelValue, Family, Metrics};
+use zksync_types::{l2::L2Tx, Address};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "rule", rename_all = "snake_case")]
+pub enum TxPolicyRule {
+    Sender,
+    Target,
+    Selector,
+    Paymaster,
+    PaymasterGasLimit,
+    PaymasterRateLimit,
+}
+
+impl fmt::Display for TxPolicyRule {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(match self {
+            Self::Sender => "sender",
+            Self::Target => "target contract",
+            Self::Selector => "function selector",
+            Self::Paymaster => "paymaster",
+            Self::PaymasterGasLimit => "paymaster gas limit",
+            Self::PaymasterRateLimit => "paymaster rate limit",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "result", rename_all = "snake_case")]
+enum ReloadResult {
+    Success,
+    Error,
+}
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "api_tx_policy")]
+struct TxPolicyMetrics {
+    /// Number of transactions rejected by the policy grouped by the violated rule.
+    rejected_txs: Family<TxPolicyRule, Counter>,
+    /// Number of policy reloads grouped by the result.
+    reloads: Family<ReloadResult, Counter>,
+}
+
+#[vise::register]
+static METRICS: vise::Global<TxPolicyMetrics> = vise::Global::new();
+
+/// Function selector (the first 4 bytes of the transaction calldata), deserialized from a hex string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Selector([u8; 4]);
+
+impl<'de> Deserialize<'de> for Selector {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        let bytes = hex::decode(s.strip_prefix("0x").unwrap_or(&s)).map_err(de::Error::custom)?;
+        let bytes = bytes.try_into().map_err(|bytes: Vec<u8>| {
+            de::Error::invalid_length(bytes.len(), &"4-byte function selector")
+        })?;
+        Ok(Self(bytes))
+    }
+}
+
+/// Allow or deny list of values.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ListRule<T: Eq + Hash> {
+    Allow(HashSet<T>),
+    Deny(HashSet<T>),
+}
+
+impl<T: Eq + Hash> Default for ListRule<T> {
+    fn default() -> Self {
+        Self::Deny(HashSet::new())
+    }
+}
+
+impl<T: Eq + Hash> ListRule<T> {
+    fn permits(&self, value: &T) -> bool {
+        match self {
+            Self::Allow(values) => values.contains(value),
+            Self::Deny(values) => !values.contains(value),
+        }
+    }
+}
+
+type KeyedRateLimiter<K> = RateLimiter<K, DefaultKeyedStateStore<K>, DefaultClock>;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PaymasterLimitsConfig {
+    #[serde(default)]
+    max_gas_limit: Option<u64>,
+    #[serde(default)]
+    max_txs_per_minute: Option<NonZeroU32>,
+}
+
+/// Limits applied to each paymaster separately.
+#[derive(Default)]
+struct PaymasterLimits {
+    /// Maximum gas limit of a single sponsored transaction.
+    max_gas_limit: Option<u64>,
+    max_txs_per_minute: Option<NonZeroU32>,
+    rate_limiter: Option<KeyedRateLimiter<Address>>,
+}
+
+impl fmt::Debug for PaymasterLimits {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("PaymasterLimits")
+            .field("max_gas_limit", &self.max_gas_limit)
+            .field("max_txs_per_minute", &self.max_txs_per_minute)
+            .finish_non_exhaustive()
+    }
+}
+
+impl From<PaymasterLimitsConfig> for PaymasterLimits {
+    fn from(config: PaymasterLimitsConfig) -> Self {
+        Self {
+            max_gas_limit: config.max_gas_limit,
+            max_txs_per_minute: config.max_txs_per_minute,
+            rate_limiter: config
+                .max_txs_per_minute
+                .map(|limit| RateLimiter::keyed(Quota::per_minute(limit))),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for PaymasterLimits {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        PaymasterLimitsConfig::deserialize(deserializer).map(Self::from)
+    }
+}
+
+impl PaymasterLimits {
+    fn check(&self, paymaster: Address, tx: &L2Tx) -> Result<(), TxPolicyRule> {
+        if let Some(max_gas_limit) = self.max_gas_limit {
+            if tx.common_data.fee.gas_limit > max_gas_limit.into() {
+                return Err(TxPolicyRule::PaymasterGasLimit);
+            }
+        }
+        if let Some(rate_limiter) = &self.rate_limiter {
+            if rate_limiter.check_key(&paymaster).is_err() {
+                return Err(TxPolicyRule::PaymasterRateLimit);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Policy for submitted transactions. Each list rule is either an allow list or a deny list, e.g.,
+///
+/// ```json
+/// {
+///   "senders": { "deny": ["0x0000000000000000000000000000000000000bad"] },
+///   "selectors": { "allow": ["0xa9059cbb", "0x095ea7b3"] },
+///   "paymasters": { "allow": ["0x000000000000000000000000000000000000face"] },
+///   "paymaster_limits": { "max_gas_limit": 5000000, "max_txs_per_minute": 600 }
+/// }
+/// ```
+///
+/// Missing rules permit all transactions. The selector rule only applies to transactions
+/// with calldata containing a function selector (i.e., at least 4 bytes long). Paymaster rules
+/// only apply to transactions using a paymaster; paymaster limits are applied to each paymaster separately.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TxPolicy {
+    #[serde(default)]
+    senders: ListRule<Address>,
+    #[serde(default)]
+    targets: ListRule<Address>,
+    #[serde(default)]
+    selectors: ListRule<Selector>,
+    #[serde(default)]
+    paymasters: ListRule<Address>,
+    #[serde(default)]
+    paymaster_limits: PaymasterLimits,
+}
+
+impl TxPolicy {
+    /// Checks the transaction against the policy. Returns the first violated rule, if any.
+    pub fn check(&self, tx: &L2Tx) -> Result<(), TxPolicyRule> {
+        self.check_inner(tx).map_err(|rule| {
+            METRICS.rejected_txs[&rule].inc();
+            rule
+        })
+    }
+
+    fn check_inner(&self, tx: &L2Tx) -> Result<(), TxPolicyRule> {
+        if !self.senders.permits(&tx.initiator_account()) {
+            return Err(TxPolicyRule::Sender);
+        }
+        if !self.targets.permits(&tx.execute.contract_address) {
+            return Err(TxPolicyRule::Target);
+        }
+        if let Some(bytes) = tx.execute.calldata.get(..4) {
+            let selector = Selector(bytes.try_into().unwrap());
+            if !self.selectors.permits(&selector) {
+                return Err(TxPolicyRule::Selector);
+            }
+        }
+
+        let paymaster = tx.common_data.paymaster_params.paymaster;
+        if paymaster != Address::zero() {
+            if !self.paymasters.permits(&paymaster) {
+                return Err(TxPolicyRule::Paymaster);
+            }
+            self.paymaster_limits.check(paymaster, tx)?;
+        }
+        Ok(())
+    }
+
+    /// Removes stale per-paymaster rate limiting state.
+    fn retain_recent(&self) {
+        if let Some(rate_limiter) = &self.paymaster_limits.rate_limiter {
+            rate_limiter.retain_recent();
+        }
+    }
+}
+
+/// Cheaply cloneable handle to the current [`TxPolicy`].
+#[derive(Debug, Clone)]
+pub struct TxPolicyHandle(watch::Receiver<Arc<TxPolicy>>);
+
+impl TxPolicyHandle {
+    pub fn check(&self, tx: &L2Tx) -> Result<(), TxPolicyRule> {
+        self.0.borrow().check(tx)
+    }
+}
+
+/// Loads [`TxPolicy`] from a JSON file and periodically reloads it if the file changes.
+#[derive(Debug)]
+pub struct TxPolicyLoader {
+    path: PathBuf,
+    reload_interval: Duration,
+    contents: Vec<u8>,
+    sender: watch::Sender<Arc<TxPolicy>>,
+}
+
+impl TxPolicyLoader {
+    /// Loads the initial policy. Errors if the file cannot be read or contains an invalid policy.
+    pub async fn new(path: impl Into<PathBuf>, reload_interval: Duration) -> anyhow::Result<Self> {
+        let path = path.into();
+        let (contents, policy) = Self::load(&path).await?;
+        tracing::info!("Loaded transaction policy from `{}`", path.display());
+        Ok(Self {
+            path,
+            reload_interval,
+            contents,
+            sender: watch::channel(Arc::new(policy)).0,
+        })
+    }
+
+    async fn load(path: &Path) -> anyhow::Result<(Vec<u8>, TxPolicy)> {
+        let contents = tokio::fs::read(path).await.with_context(|| {
+            format!(
+                "failed reading transaction policy from `{}`",
+                path.display()
+            )
+        })?;
+        let policy = serde_json::from_slice(&contents)
+            .with_context(|| format!("invalid transaction policy in `{}`", path.display()))?;
+        Ok((contents, policy))
+    }
+
+    pub fn handle(&self) -> TxPolicyHandle {
+        TxPolicyHandle(self.sender.subscribe())
+    }
+
+    /// Reloads the policy until a stop signal is received. If the updated policy is invalid,
+    /// the error is logged, and the previous policy remains in effect.
+    pub async fn run(mut self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        loop {
+            if tokio::time::timeout(self.reload_interval, stop_receiver.changed())
+                .await
+                .is_ok()
+            {
+                break;
+            }
+            self.sender.borrow().retain_recent();
+
+            let contents = match tokio::fs::read(&self.path).await {
+                Ok(contents) => contents,
+                Err(err) => {
+                    tracing::error!(
+                        "Failed reading transaction policy from `{}`, keeping the previous policy: {err}",
+                        self.path.display()
+                    );
+                    METRICS.reloads[&ReloadResult::Error].inc();
+                    continue;
+                }
+            };
+            if contents == self.contents {
+                continue;
+            }
+
+            match serde_json::from_slice::<TxPolicy>(&contents) {
+                Ok(policy) => {
+                    tracing::info!("Reloaded transaction policy: {policy:?}");
+                    METRICS.reloads[&ReloadResult::Success].inc();
+                    self.sender.send_replace(Arc::new(policy));
+                }
+                Err(err) => {
+                    tracing::error!(
+                        "Invalid transaction policy in `{}`, keeping the previous policy: {err}",
+                        self.path.display()
+                    );
+                    METRICS.reloads[&ReloadResult::Error].inc();
+                }
+            }
+            // Invalid contents are remembered as well, so that the error is logged only once.
+            self.contents = contents;
+        }
+        tracing::info!("Stop signal received, transaction policy loader is shutting down");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_types::{fee::Fee, L2ChainId, Nonce, H256, U256};
+
+    use super::*;
+
+    fn create_tx(initiator: Address, target: Address, calldata: Vec<u8>) -> L2Tx {
+        let mut tx = L2Tx::new(
+            target,
+            calldata,
+            Nonce(0),
+            Fee::default(),
+            initiator,
+            U256::zero(),
+            None,
+            Default::default(),
+        );
+        tx.set_input(H256::random().0.to_vec(), H256::random());
+        tx
+    }
+
+    #[test]
+    fn checking_policy() {
+        let policy: TxPolicy = serde_json::from_value(serde_json::json!({
+            "senders": { "deny": [format!("{:?}", Address::repeat_byte(1))] },
+            "targets": { "allow": [format!("{:?}", Address::repeat_byte(0xa))] },
+            "selectors": { "deny": ["0xa9059cbb"] },
+        }))
+        .unwrap();
+
+        let allowed_target = Address::repeat_byte(0xa);
+        let tx = create_tx(Address::repeat_byte(2), allowed_target, vec![]);
+        policy.check(&tx).unwrap();
+        let tx = create_tx(Address::repeat_byte(2), allowed_target, vec![1, 2, 3, 4, 5]);
+        policy.check(&tx).unwrap();
+
+        let tx = create_tx(Address::repeat_byte(1), allowed_target, vec![]);
+        assert_eq!(policy.check(&tx), Err(TxPolicyRule::Sender));
+        let tx = create_tx(Address::repeat_byte(2), Address::repeat_byte(0xb), vec![]);
+        assert_eq!(policy.check(&tx), Err(TxPolicyRule::Target));
+        let tx = create_tx(
+            Address::repeat_byte(2),
+            allowed_target,
+            vec![0xa9, 0x05, 0x9c, 0xbb, 0],
+        );
+        assert_eq!(policy.check(&tx), Err(TxPolicyRule::Selector));
+    }
+
+    #[test]
+    fn checking_paymaster_policy() {
+        let allowed_paymaster = Address::repeat_byte(0xa);
+        let policy: TxPolicy = serde_json::from_value(serde_json::json!({
+            "paymasters": { "allow": [format!("{allowed_paymaster:?}")] },
+            "paymaster_limits": { "max_gas_limit": 1_000_000, "max_txs_per_minute": 2 },
+        }))
+        .unwrap();
+
+        let mut tx = create_tx(Address::repeat_byte(1), Address::random(), vec![]);
+        // Transactions without a paymaster are not affected by paymaster rules.
+        tx.common_data.fee.gas_limit = 10_000_000.into();
+        for _ in 0..5 {
+            policy.check(&tx).unwrap();
+        }
+
+        tx.common_data.paymaster_params.paymaster = Address::repeat_byte(0xb);
+        tx.common_data.fee.gas_limit = 500_000.into();
+        assert_eq!(policy.check(&tx), Err(TxPolicyRule::Paymaster));
+
+        tx.common_data.paymaster_params.paymaster = allowed_paymaster;
+        tx.common_data.fee.gas_limit = 2_000_000.into();
+        assert_eq!(policy.check(&tx), Err(TxPolicyRule::PaymasterGasLimit));
+
+        tx.common_data.fee.gas_limit = 500_000.into();
+        policy.check(&tx).unwrap();
+        policy.check(&tx).unwrap();
+        assert_eq!(policy.check(&tx), Err(TxPolicyRule::PaymasterRateLimit));
+    }
+
+    #[test]
+    fn empty_policy_permits_everything() {
+        let policy: TxPolicy = serde_json::from_str("{}").unwrap();
+        let tx = create_tx(
+            Address::random(),
+            Address::random(),
+            vec![0xa9, 0x05, 0x9c, 0xbb],
+        );
+        policy.check(&tx).unwrap();
+    }
+
+    #[test]
+    fn invalid_policies() {
+        let invalid_policies = [
+            serde_json::json!({ "selectors": { "allow": ["0x0102"] } }),
+            serde_json::json!({ "senders": { "allow_all": [] } }),
+            serde_json::json!({ "receivers": { "deny": [] } }),
+            serde_json::json!({ "paymaster_limits": { "max_txs_per_minute": 0 } }),
+            serde_json::json!({ "paymaster_limits": { "max_sponsored_gas": 100 } }),
+        ];
+        for policy in invalid_policies {
+            serde_json::from_value::<TxPolicy>(policy.clone()).unwrap_err();
+        }
+    }
+
+    #[tokio::test]
+    async fn reloading_policy() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("policy.json");
+        tokio::fs::write(&path, "{}").await.unwrap();
+        let loader = TxPolicyLoader::new(&path, Duration::from_millis(10))
+            .await
+            .unwrap();
+        let mut handle = loader.handle();
+        let (stop_sender, stop_receiver) = watch::channel(false);
+        let loader_task = tokio::spawn(loader.run(stop_receiver));
+
+        let tx = create_tx(Address::repeat_byte(1), Address::random(), vec![]);
+        handle.check(&tx).unwrap();
+
+        tokio::fs::write(&path, "not a policy").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        handle.check(&tx).unwrap();
+
+        let policy = serde_json::json!({
+            "senders": { "deny": [format!("{:?}", Address::repeat_byte(1))] },
+        });
+        tokio::fs::write(&path, policy.to_string()).await.unwrap();
+        handle.0.changed().await.unwrap();
+        assert_eq!(handle.check(&tx), Err(TxPolicyRule::Sender));
+
+        stop_sender.send_replace(true);
+        loader_task.await.unwrap().unwrap();
+    }
+}
@@ -45,7 +45,33 @@ impl TxProxy {
         let input_data = tx.common_data.input_data().expect("raw tx is absent");
         let raw_tx = zksync_types::Bytes(input_data.to_vec());
         tracing::info!("Proxying tx {}", tx.hash());
-        self.client.send_raw_transaction(raw_tx).await
+        if let Some(conditions) = &tx.common_data.conditions {
+            let conditions = conditions.as_ref().clone();
+            self.client
+                .send_raw_transaction_conditional(raw_tx, conditions)
+                .await
+        } else {
+            self.client.send_raw_transaction(raw_tx).await
+        }
+    }
+
+    pub async fn submit_private_tx(&self, tx: &L2Tx) -> RpcResult<H256> {
+        let input_data = tx.common_data.input_data().expect("raw tx is absent");
+        let raw_tx = zksync_types::Bytes(input_data.to_vec());
+        tracing::info!("Proxying private tx {}", tx.hash());
+        self.client.send_private_raw_transaction(raw_tx).await
+    }
+
+    pub async fn submit_bundle(&self, transactions: &[L2Tx]) -> RpcResult<H256> {
+        let raw_txs = transactions
+            .iter()
+            .map(|tx| {
+                let input_data = tx.common_data.input_data().expect("raw tx is absent");
+                zksync_types::Bytes(input_data.to_vec())
+            })
+            .collect();
+        tracing::info!("Proxying bundle with {} txs", transactions.len());
+        self.client.send_bundle(raw_txs).await
     }
 
     pub async fn request_tx(&self, id: TransactionId) -> RpcResult<Option<Transaction>> {
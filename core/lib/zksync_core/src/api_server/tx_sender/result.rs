@@ -3,8 +3,14 @@ use multivm::{
     tracers::validator::ValidationError,
 };
 use thiserror::Error;
-use zksync_types::{l2::error::TxCheckError, U256};
+use zksync_dal::transactions_dal::L2TxSubmissionResult;
+use zksync_types::{
+    l2::{conditions::TransactionConditionsError, error::TxCheckError},
+    Address, H256, U256,
+};
+use zksync_web3_decl::error::Web3Error;
 
+use super::TxPolicyRule;
 use crate::api_server::execution_sandbox::SandboxExecutionError;
 
 #[derive(Debug, Error)]
@@ -71,6 +77,27 @@ pub enum SubmitTxError {
     ProxyError(#[from] zksync_web3_decl::jsonrpsee::core::ClientError),
     #[error("not enough gas to publish compressed bytecodes")]
     FailedToPublishCompressedBytecodes,
+    #[error("transaction conditions are not met: {0}")]
+    ConditionsNotMet(#[from] TransactionConditionsError),
+    #[error("too many storage conditions. {0} provided, while only {1} allowed")]
+    TooManyConditions(usize, usize),
+    #[error("bundle must contain from 1 to {1} transactions, got {0}")]
+    InvalidBundleSize(usize, usize),
+    #[error("bundle transaction {0:?} cannot be inserted: {1}")]
+    BundleTxNotInserted(H256, L2TxSubmissionResult),
+    #[error(
+        "replacement transaction underpriced. required max fee per gas: {0}, \
+         required max priority fee per gas: {1}"
+    )]
+    ReplacementUnderpriced(U256, U256),
+    #[error("transaction rejected by {0} policy")]
+    PolicyViolation(TxPolicyRule),
+    #[error("too many pending transactions from account {0:?}; at most {1} are allowed")]
+    SenderPendingTxsLimitExceeded(Address, u32),
+    #[error("too many pending transactions using paymaster {0:?}; at most {1} are allowed")]
+    PaymasterPendingTxsLimitExceeded(Address, u32),
+    #[error("call compute limit exceeded: call has used up its gas limit of {0}")]
+    CallComputeLimitExceeded(u32),
 }
 
 impl SubmitTxError {
@@ -102,6 +129,15 @@ impl SubmitTxError {
             Self::IntrinsicGas => "intrinsic-gas",
             Self::ProxyError(_) => "proxy-error",
             Self::FailedToPublishCompressedBytecodes => "failed-to-publish-compressed-bytecodes",
+            Self::ConditionsNotMet(_) => "conditions-not-met",
+            Self::TooManyConditions(_, _) => "too-many-conditions",
+            Self::InvalidBundleSize(_, _) => "invalid-bundle-size",
+            Self::BundleTxNotInserted(_, _) => "bundle-tx-not-inserted",
+            Self::ReplacementUnderpriced(_, _) => "replacement-underpriced",
+            Self::PolicyViolation(_) => "policy-violation",
+            Self::SenderPendingTxsLimitExceeded(_, _) => "sender-pending-txs-limit-exceeded",
+            Self::PaymasterPendingTxsLimitExceeded(_, _) => "paymaster-pending-txs-limit-exceeded",
+            Self::CallComputeLimitExceeded(_) => "call-compute-limit-exceeded",
         }
     }
 
@@ -114,6 +150,23 @@ impl SubmitTxError {
     }
 }
 
+impl From<SubmitTxError> for Web3Error {
+    fn from(err: SubmitTxError) -> Self {
+        match err {
+            SubmitTxError::ReplacementUnderpriced(_, _) => {
+                Self::ReplacementUnderpriced(err.to_string())
+            }
+            SubmitTxError::PolicyViolation(_) => Self::TxPolicyViolation(err.to_string()),
+            SubmitTxError::SenderPendingTxsLimitExceeded(_, _)
+            | SubmitTxError::PaymasterPendingTxsLimitExceeded(_, _) => {
+                Self::PendingTxsLimitExceeded(err.to_string())
+            }
+            SubmitTxError::CallComputeLimitExceeded(limit) => Self::CallComputeLimitExceeded(limit),
+            _ => Self::SubmitTransactionError(err.to_string(), err.data()),
+        }
+    }
+}
+
 impl From<SandboxExecutionError> for SubmitTxError {
     fn from(err: SandboxExecutionError) -> SubmitTxError {
         match err {
@@ -0,0 +1,250 @@
+//! HTTP middleware authenticating callers of protected API namespaces (e.g., `debug`).
+//!
+//! Callers pass credentials in the `Authorization: Bearer <token>` header; the token is either the shared secret
+//! itself, or a JWT signed with the secret, depending on the configured [`ApiAuthMethod`]. Requests not calling
+//! methods from the protected namespaces are passed through without authentication; requests that cannot be parsed
+//! are treated as calling protected methods.
+
+use std::{
+    collections::HashSet,
+    fmt,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use anyhow::Context as _;
+use futures::future::BoxFuture;
+use hyper::{header, Body, HeaderMap, Method, Request, Response, StatusCode};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::{de::value::StrDeserializer, Deserialize};
+use tower::{Layer, Service};
+use zksync_config::configs::api::{ApiAuthMethod, Web3JsonRpcConfig};
+
+use super::{
+    metrics::API_METRICS,
+    rate_limit::{json_rpc_error_response, read_body, RawRequest},
+    Namespace,
+};
+
+/// JSON-RPC error code for unauthenticated calls.
+const UNAUTHORIZED_CODE: i32 = -32_001;
+
+/// Authentication settings for the API server.
+#[derive(Clone)]
+pub struct ApiAuth {
+    namespaces: Vec<Namespace>,
+    method: ApiAuthMethod,
+    secret: String,
+}
+
+impl fmt::Debug for ApiAuth {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("ApiAuth")
+            .field("namespaces", &self.namespaces)
+            .field("method", &self.method)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ApiAuth {
+    pub fn new(
+        namespaces: Vec<Namespace>,
+        method: ApiAuthMethod,
+        secret: String,
+    ) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            !secret.is_empty(),
+            "authentication secret must not be empty"
+        );
+        anyhow::ensure!(
+            !namespaces.contains(&Namespace::Pubsub),
+            "pubsub namespace cannot be authenticated"
+        );
+        Ok(Self {
+            namespaces,
+            method,
+            secret,
+        })
+    }
+
+    /// Parses authentication settings from the config. Returns `None` if no namespaces require authentication.
+    pub fn from_config(config: &Web3JsonRpcConfig) -> anyhow::Result<Option<Self>> {
+        let Some(namespaces) = &config.authenticated_namespaces else {
+            return Ok(None);
+        };
+        if namespaces.is_empty() {
+            return Ok(None);
+        }
+
+        let namespaces = namespaces.iter().map(|name| {
+            Namespace::deserialize(StrDeserializer::<serde::de::value::Error>::new(name))
+                .with_context(|| format!("invalid authenticated namespace `{name}`"))
+        });
+        let namespaces = namespaces.collect::<anyhow::Result<_>>()?;
+        let secret = config
+            .auth_secret
+            .clone()
+            .context("`auth_secret` must be set if `authenticated_namespaces` are set")?;
+        Self::new(namespaces, config.auth_method, secret).map(Some)
+    }
+
+    pub fn namespaces(&self) -> &[Namespace] {
+        &self.namespaces
+    }
+}
+
+enum TokenVerifier {
+    SharedSecret(Vec<u8>),
+    Jwt(DecodingKey, Validation),
+}
+
+impl TokenVerifier {
+    fn verify(&self, token: &str) -> Result<(), &'static str> {
+        match self {
+            Self::SharedSecret(secret) => {
+                if constant_time_eq(secret, token.as_bytes()) {
+                    Ok(())
+                } else {
+                    Err("invalid bearer token")
+                }
+            }
+            Self::Jwt(key, validation) => {
+                jsonwebtoken::decode::<serde_json::Value>(token, key, validation)
+                    .map(drop)
+                    .map_err(|_| "invalid or expired JWT")
+            }
+        }
+    }
+}
+
+fn constant_time_eq(lhs: &[u8], rhs: &[u8]) -> bool {
+    lhs.len() == rhs.len() && lhs.iter().zip(rhs).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+struct AuthState {
+    protected_prefixes: HashSet<&'static str>,
+    verifier: TokenVerifier,
+}
+
+impl AuthState {
+    fn requires_auth(&self, request: &RawRequest) -> bool {
+        request.methods().any(|method| {
+            let prefix = method.split_once('_').map_or(method, |(prefix, _)| prefix);
+            self.protected_prefixes.contains(prefix)
+        })
+    }
+
+    fn authenticate(&self, headers: &HeaderMap) -> Result<(), &'static str> {
+        let token = headers
+            .get(header::AUTHORIZATION)
+            .ok_or("missing authorization header")?
+            .to_str()
+            .ok()
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or("authorization header must have `Bearer <token>` format")?;
+        self.verifier.verify(token.trim())
+    }
+}
+
+/// Tower layer enforcing [`ApiAuth`].
+#[derive(Clone)]
+pub(super) struct AuthLayer {
+    state: Arc<AuthState>,
+}
+
+impl AuthLayer {
+    pub fn new(auth: &ApiAuth) -> Self {
+        let verifier = match auth.method {
+            ApiAuthMethod::SharedSecret => {
+                TokenVerifier::SharedSecret(auth.secret.as_bytes().to_vec())
+            }
+            ApiAuthMethod::Jwt => TokenVerifier::Jwt(
+                DecodingKey::from_secret(auth.secret.as_bytes()),
+                Validation::new(Algorithm::HS256),
+            ),
+        };
+        Self {
+            state: Arc::new(AuthState {
+                protected_prefixes: auth
+                    .namespaces
+                    .iter()
+                    .filter_map(Namespace::method_prefix)
+                    .collect(),
+                verifier,
+            }),
+        }
+    }
+}
+
+impl<S> Layer<S> for AuthLayer {
+    type Service = AuthService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        AuthService {
+            inner,
+            state: self.state.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(super) struct AuthService<S> {
+    inner: S,
+    state: Arc<AuthState>,
+}
+
+impl<S> Service<Request<Body>> for AuthService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        if request.method() != Method::POST {
+            return Box::pin(self.inner.call(request));
+        }
+        // Take the service that was driven to readiness and leave a fresh clone in its place.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let state = self.state.clone();
+
+        Box::pin(async move {
+            let (parts, body) = request.into_parts();
+            let body = match read_body(body).await {
+                Ok(body) => body,
+                Err(status) => {
+                    let response = Response::builder().status(status).body(Body::empty());
+                    return Ok(response.unwrap());
+                }
+            };
+            // Requests that cannot be parsed require authentication, since `jsonrpsee` may still
+            // interpret them as calls to protected methods.
+            let raw_request = serde_json::from_slice::<RawRequest>(&body).ok();
+            let requires_auth = raw_request
+                .as_ref()
+                .map_or(true, |req| state.requires_auth(req));
+            if requires_auth {
+                if let Err(message) = state.authenticate(&parts.headers) {
+                    API_METRICS.unauthorized_requests.inc();
+                    return Ok(json_rpc_error_response(
+                        StatusCode::UNAUTHORIZED,
+                        raw_request.as_ref().and_then(RawRequest::id),
+                        UNAUTHORIZED_CODE,
+                        &format!("Unauthorized: {message}"),
+                    ));
+                }
+            }
+            inner
+                .call(Request::from_parts(parts, Body::from(body)))
+                .await
+        })
+    }
+}
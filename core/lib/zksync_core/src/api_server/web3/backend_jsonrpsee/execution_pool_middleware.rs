@@ -0,0 +1,168 @@
+use std::{future::Future, num::NonZeroUsize, sync::Arc, time::Duration};
+
+use futures::future::{BoxFuture, Either};
+use tokio::sync::Semaphore;
+use vise::{Buckets, EncodeLabelSet, EncodeLabelValue, Family, Gauge, Histogram, Metrics};
+use zksync_web3_decl::jsonrpsee::{
+    server::middleware::rpc::RpcServiceT, types::Request, MethodResponse,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "pool", rename_all = "snake_case")]
+enum ExecutionPoolKind {
+    Light,
+    Heavy,
+}
+
+impl ExecutionPoolKind {
+    fn for_method(method: &str) -> Self {
+        if method.starts_with("debug_") || method.starts_with("trace_") {
+            return Self::Heavy;
+        }
+        match method {
+            "eth_estimateGas" | "zks_estimateFee" | "zks_estimateGasL1ToL2" => Self::Heavy,
+            _ => Self::Light,
+        }
+    }
+}
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "api_jsonrpc_backend_execution_pool")]
+struct ExecutionPoolMetrics {
+    /// Number of calls waiting for a free slot in the pool.
+    queue_depth: Family<ExecutionPoolKind, Gauge<usize>>,
+    /// Number of calls executed in the pool.
+    in_flight: Family<ExecutionPoolKind, Gauge<usize>>,
+    /// Time spent by calls waiting for a free slot in the pool.
+    #[metrics(buckets = Buckets::LATENCIES)]
+    wait_latency: Family<ExecutionPoolKind, Histogram<Duration>>,
+}
+
+#[vise::register]
+static METRICS: vise::Global<ExecutionPoolMetrics> = vise::Global::new();
+
+/// Bounded pools executing heavy calls (`debug_*` / `trace_*` methods and gas estimation) separately
+/// from light calls, so that a burst of heavy calls doesn't affect latency of light ones.
+#[derive(Debug)]
+pub(crate) struct ExecutionPools {
+    heavy: Semaphore,
+    light: Option<Semaphore>,
+}
+
+impl ExecutionPools {
+    pub fn new(heavy_limit: NonZeroUsize, light_limit: Option<NonZeroUsize>) -> Self {
+        Self {
+            heavy: Semaphore::new(heavy_limit.get()),
+            light: light_limit.map(|limit| Semaphore::new(limit.get())),
+        }
+    }
+
+    fn semaphore(&self, kind: ExecutionPoolKind) -> Option<&Semaphore> {
+        match kind {
+            ExecutionPoolKind::Heavy => Some(&self.heavy),
+            ExecutionPoolKind::Light => self.light.as_ref(),
+        }
+    }
+
+    async fn execute<F: Future>(&self, kind: ExecutionPoolKind, call: F) -> F::Output {
+        let _permit = if let Some(semaphore) = self.semaphore(kind) {
+            let _guard = METRICS.queue_depth[&kind].inc_guard(1);
+            let latency = METRICS.wait_latency[&kind].start();
+            // Semaphores are never closed.
+            let permit = semaphore.acquire().await.unwrap();
+            latency.observe();
+            Some(permit)
+        } else {
+            None
+        };
+        let _guard = METRICS.in_flight[&kind].inc_guard(1);
+        call.await
+    }
+}
+
+/// Middleware executing calls in [`ExecutionPools`].
+///
+/// `jsonrpsee` will allocate the instance of this struct once per session.
+pub(crate) struct ExecutionPoolMiddleware<S> {
+    inner: S,
+    pools: Option<Arc<ExecutionPools>>,
+}
+
+impl<S> ExecutionPoolMiddleware<S> {
+    pub(crate) fn new(inner: S, pools: Option<Arc<ExecutionPools>>) -> Self {
+        Self { inner, pools }
+    }
+}
+
+impl<'a, S> RpcServiceT<'a> for ExecutionPoolMiddleware<S>
+where
+    S: Send + Clone + Sync + RpcServiceT<'a> + 'a,
+{
+    type Future = Either<S::Future, BoxFuture<'a, MethodResponse>>;
+
+    fn call(&self, request: Request<'a>) -> Self::Future {
+        let Some(pools) = &self.pools else {
+            return Either::Left(self.inner.call(request));
+        };
+        let kind = ExecutionPoolKind::for_method(&request.method);
+        let pools = Arc::clone(pools);
+        let inner = self.inner.clone();
+        // The call is only started once a slot in the pool is obtained; `jsonrpsee` executes
+        // synchronous methods right away.
+        Either::Right(Box::pin(async move {
+            pools
+                .execute(kind, async move { inner.call(request).await })
+                .await
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifying_methods() {
+        for method in [
+            "debug_traceCall",
+            "trace_block",
+            "eth_estimateGas",
+            "zks_estimateFee",
+        ] {
+            assert_eq!(
+                ExecutionPoolKind::for_method(method),
+                ExecutionPoolKind::Heavy,
+                "{method}"
+            );
+        }
+        for method in [
+            "eth_blockNumber",
+            "eth_getBalance",
+            "eth_call",
+            "zks_L1ChainId",
+        ] {
+            assert_eq!(
+                ExecutionPoolKind::for_method(method),
+                ExecutionPoolKind::Light,
+                "{method}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn heavy_calls_do_not_block_light_calls() {
+        let pools = ExecutionPools::new(NonZeroUsize::MIN, None);
+        let heavy_permit = pools.heavy.acquire().await.unwrap();
+
+        // Light calls are not affected by the exhausted heavy pool.
+        let light_call = pools.execute(ExecutionPoolKind::Light, async { 1 });
+        assert_eq!(light_call.await, 1);
+        let heavy_call = pools.execute(ExecutionPoolKind::Heavy, async { 2 });
+        tokio::pin!(heavy_call);
+        tokio::time::timeout(Duration::from_millis(50), &mut heavy_call)
+            .await
+            .unwrap_err();
+        drop(heavy_permit);
+        assert_eq!(heavy_call.await, 2);
+    }
+}
@@ -0,0 +1,156 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use anyhow::Context as _;
+use vise::{Counter, LabeledFamily, Metrics};
+use zksync_config::configs::api::Web3JsonRpcConfig;
+use zksync_web3_decl::jsonrpsee::{
+    server::middleware::rpc::{layer::ResponseFuture, RpcServiceT},
+    types::{error::ErrorCode, ErrorObject, Request},
+    MethodResponse,
+};
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "api_jsonrpc_backend_method_filter")]
+struct MethodFilterMetrics {
+    /// Number of calls rejected by the method filter, grouped by the matching rule.
+    #[metrics(labels = ["rule"])]
+    rejected_calls: LabeledFamily<String, Counter>,
+}
+
+#[vise::register]
+static METRICS: vise::Global<MethodFilterMetrics> = vise::Global::new();
+
+/// Method-level allow / deny rules applied on top of enabled API namespaces.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MethodFilter {
+    denied_methods: HashSet<String>,
+    /// Denied namespace wildcards keyed by the namespace prefix (e.g., `debug` for `debug_*`).
+    denied_namespaces: HashMap<String, String>,
+    allowed_methods: HashSet<String>,
+}
+
+impl MethodFilter {
+    pub fn new(denied_methods: &[String], allowed_methods: &[String]) -> anyhow::Result<Self> {
+        let mut this = Self::default();
+        for rule in denied_methods {
+            if let Some(namespace) = rule.strip_suffix("_*") {
+                anyhow::ensure!(
+                    !namespace.is_empty() && !namespace.contains(['_', '*']),
+                    "invalid namespace wildcard `{rule}`"
+                );
+                this.denied_namespaces
+                    .insert(namespace.to_owned(), rule.clone());
+            } else {
+                Self::validate_method_name(rule)?;
+                this.denied_methods.insert(rule.clone());
+            }
+        }
+        for method in allowed_methods {
+            Self::validate_method_name(method)?;
+            this.allowed_methods.insert(method.clone());
+        }
+        Ok(this)
+    }
+
+    pub fn from_config(config: &Web3JsonRpcConfig) -> anyhow::Result<Self> {
+        let denied_methods = config.denied_methods.as_deref().unwrap_or_default();
+        let allowed_methods = config.allowed_methods.as_deref().unwrap_or_default();
+        Self::new(denied_methods, allowed_methods).context("invalid method filter")
+    }
+
+    fn validate_method_name(method: &str) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            method.split_once('_').is_some() && !method.contains('*'),
+            "`{method}` is not a valid method name"
+        );
+        Ok(())
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.denied_methods.is_empty() && self.denied_namespaces.is_empty()
+    }
+
+    /// Returns the rule denying the specified method, or `None` if the method is allowed.
+    fn denying_rule(&self, method: &str) -> Option<&str> {
+        if self.allowed_methods.contains(method) {
+            return None;
+        }
+        if let Some(rule) = self.denied_methods.get(method) {
+            return Some(rule);
+        }
+        let (namespace, _) = method.split_once('_')?;
+        self.denied_namespaces.get(namespace).map(String::as_str)
+    }
+}
+
+/// Middleware rejecting calls to methods denied by a [`MethodFilter`].
+///
+/// `jsonrpsee` will allocate the instance of this struct once per session.
+pub(crate) struct MethodFilterMiddleware<S> {
+    inner: S,
+    filter: Option<Arc<MethodFilter>>,
+}
+
+impl<S> MethodFilterMiddleware<S> {
+    pub(crate) fn new(inner: S, filter: Option<Arc<MethodFilter>>) -> Self {
+        Self { inner, filter }
+    }
+}
+
+impl<'a, S> RpcServiceT<'a> for MethodFilterMiddleware<S>
+where
+    S: Send + Clone + Sync + RpcServiceT<'a>,
+{
+    type Future = ResponseFuture<S::Future>;
+
+    fn call(&self, request: Request<'a>) -> Self::Future {
+        if let Some(filter) = &self.filter {
+            if let Some(rule) = filter.denying_rule(&request.method) {
+                METRICS.rejected_calls[&rule.to_owned()].inc();
+
+                let rp = MethodResponse::error(
+                    request.id,
+                    ErrorObject::borrowed(
+                        ErrorCode::MethodNotFound.code(),
+                        "Method is disabled",
+                        None,
+                    ),
+                );
+                return ResponseFuture::ready(rp);
+            }
+        }
+        ResponseFuture::future(self.inner.call(request))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn method_filter_basics() {
+        let denied = ["debug_*".to_owned(), "eth_gasPrice".to_owned()];
+        let allowed = ["debug_traceTransaction".to_owned()];
+        let filter = MethodFilter::new(&denied, &allowed).unwrap();
+
+        assert_eq!(filter.denying_rule("eth_gasPrice"), Some("eth_gasPrice"));
+        assert_eq!(
+            filter.denying_rule("debug_traceBlockByNumber"),
+            Some("debug_*")
+        );
+        assert_eq!(filter.denying_rule("debug_traceTransaction"), None);
+        assert_eq!(filter.denying_rule("eth_chainId"), None);
+        assert_eq!(filter.denying_rule("debug"), None);
+    }
+
+    #[test]
+    fn invalid_method_filter_rules() {
+        for rule in ["*", "debug*", "_*", "debug_trace*", "eth"] {
+            MethodFilter::new(&[rule.to_owned()], &[]).unwrap_err();
+        }
+        MethodFilter::new(&[], &["debug_*".to_owned()]).unwrap_err();
+    }
+}
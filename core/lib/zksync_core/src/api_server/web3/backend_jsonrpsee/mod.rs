@@ -9,9 +9,11 @@ use zksync_web3_decl::{
     jsonrpsee::types::{error::ErrorCode, ErrorObjectOwned},
 };
 
-use crate::api_server::web3::metrics::API_METRICS;
+use crate::api_server::{tree::NoTreeVersionError, web3::metrics::API_METRICS};
 
 pub mod batch_limiter_middleware;
+pub mod execution_pool_middleware;
+pub mod method_filter_middleware;
 pub mod namespaces;
 
 pub fn from_std_error(e: impl Error) -> ErrorObjectOwned {
@@ -30,25 +32,74 @@ pub fn into_jsrpc_error(err: Web3Error) -> ErrorObjectOwned {
             | Web3Error::FilterNotFound
             | Web3Error::InvalidFeeParams(_)
             | Web3Error::InvalidFilterBlockHash
-            | Web3Error::LogsLimitExceeded(_, _, _) => ErrorCode::InvalidParams.code(),
+            | Web3Error::InvalidStateOverride(_)
+            | Web3Error::InvalidTracerConfig(_)
+            | Web3Error::InvalidTraceFilter(_)
+            | Web3Error::InvalidSimulatePayload(_)
+            | Web3Error::InvalidPagination(_)
+            | Web3Error::TooManyProofRequests(_)
+            | Web3Error::TooManyTraceCalls(_)
+            | Web3Error::InvalidLogFilter(_)
+            | Web3Error::InvalidFeeModelSettings(_)
+            | Web3Error::InvalidAggregationPolicy(_)
+            | Web3Error::LogsLimitExceeded(_, _, _)
+            | Web3Error::LogsBlockRangeExceeded(_, _, _) => ErrorCode::InvalidParams.code(),
             Web3Error::SubmitTransactionError(_, _) | Web3Error::SerializationError(_) => 3,
             Web3Error::PubSubTimeout => 4,
             Web3Error::RequestTimeout => 5,
             Web3Error::TreeApiUnavailable => 6,
+            Web3Error::TraceLimitExceeded(_) => 7,
+            Web3Error::SealingControlUnavailable => 8,
+            Web3Error::ReplacementUnderpriced(_) => 9,
+            Web3Error::TxPolicyViolation(_) => 10,
+            Web3Error::PendingTxsLimitExceeded(_) => 11,
+            Web3Error::TransactionDropped(_) => 12,
+            Web3Error::CallComputeLimitExceeded(_) => 13,
+            Web3Error::FeeModelSettingsUnavailable => 14,
+            Web3Error::AggregationPolicyUnavailable => 15,
+            Web3Error::TreeVersionUnavailable(_) => 16,
+            Web3Error::TreeLagExceeded { .. } => 17,
+            Web3Error::PrunedBlock(_) | Web3Error::PrunedL1Batch(_) => 18,
         },
         match err {
             Web3Error::SubmitTransactionError(ref message, _) => message.clone(),
             _ => err.to_string(),
         },
         match err {
-            Web3Error::SubmitTransactionError(_, data) => Some(format!("0x{}", hex::encode(data))),
+            Web3Error::SubmitTransactionError(_, data) => {
+                Some(format!("0x{}", hex::encode(data)).into())
+            }
+            Web3Error::LogsLimitExceeded(limit, from_block, to_block) => {
+                Some(suggested_block_range(limit as u64, from_block, to_block))
+            }
+            Web3Error::LogsBlockRangeExceeded(limit, from_block, to_block) => {
+                Some(suggested_block_range(limit, from_block, to_block))
+            }
             _ => None,
         },
     )
 }
 
+/// Returns error data with a narrower block range that a client can use to retry a request.
+fn suggested_block_range(limit: u64, from_block: u32, to_block: u32) -> serde_json::Value {
+    serde_json::json!({
+        "from": format!("{from_block:#x}"),
+        "to": format!("{to_block:#x}"),
+        "limit": limit,
+    })
+}
+
 pub fn internal_error(method_name: &'static str, error: impl fmt::Display) -> Web3Error {
     tracing::error!("Internal error in method {method_name}: {error}");
     API_METRICS.web3_internal_errors[&method_name].inc();
     Web3Error::InternalError
 }
+
+/// Maps an error returned by the Merkle tree API. Errors caused by a missing (e.g., pruned) tree version
+/// are returned to the caller; all other errors are treated as internal.
+pub fn map_tree_api_error(method_name: &'static str, error: anyhow::Error) -> Web3Error {
+    if let Some(err) = error.downcast_ref::<NoTreeVersionError>() {
+        return Web3Error::TreeVersionUnavailable(err.l1_batch_number);
+    }
+    internal_error(method_name, format!("{error:#}"))
+}
@@ -0,0 +1,82 @@
+use async_trait::async_trait;
+use zksync_types::{
+    api::{
+        AggregationPolicy, AggregationPolicyUpdate, FeeModelSettings, FeeModelSettingsUpdate,
+        L1GasPriceStats, NodeInfo,
+    },
+    Address, H256,
+};
+use zksync_web3_decl::{jsonrpsee::core::RpcResult, namespaces::AdminNamespaceServer};
+
+use crate::api_server::web3::{backend_jsonrpsee::into_jsrpc_error, namespaces::AdminNamespace};
+
+#[async_trait]
+impl AdminNamespaceServer for AdminNamespace {
+    async fn node_info(&self) -> RpcResult<NodeInfo> {
+        self.node_info_impl().await.map_err(into_jsrpc_error)
+    }
+
+    fn set_log_filter(&self, directives: String) -> RpcResult<()> {
+        self.set_log_filter_impl(&directives)
+            .map_err(into_jsrpc_error)
+    }
+
+    async fn flush_mempool_sender(&self, address: Address) -> RpcResult<Vec<H256>> {
+        self.flush_mempool_sender_impl(address)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+
+    async fn remove_transaction(&self, tx_hash: H256, reason: String) -> RpcResult<bool> {
+        self.remove_transaction_impl(tx_hash, &reason)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+
+    async fn remove_sender_transactions(
+        &self,
+        address: Address,
+        reason: String,
+    ) -> RpcResult<Vec<H256>> {
+        self.remove_sender_transactions_impl(address, &reason)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+
+    fn stop_sealing(&self) -> RpcResult<bool> {
+        self.stop_sealing_impl().map_err(into_jsrpc_error)
+    }
+
+    fn resume_sealing(&self) -> RpcResult<bool> {
+        self.resume_sealing_impl().map_err(into_jsrpc_error)
+    }
+
+    fn get_l1_gas_price_stats(&self) -> RpcResult<L1GasPriceStats> {
+        self.get_l1_gas_price_stats_impl().map_err(into_jsrpc_error)
+    }
+
+    fn get_fee_model_settings(&self) -> RpcResult<FeeModelSettings> {
+        self.get_fee_model_settings_impl().map_err(into_jsrpc_error)
+    }
+
+    async fn update_fee_model_settings(
+        &self,
+        update: FeeModelSettingsUpdate,
+    ) -> RpcResult<FeeModelSettings> {
+        self.update_fee_model_settings_impl(update)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+
+    fn get_aggregation_policy(&self) -> RpcResult<AggregationPolicy> {
+        self.get_aggregation_policy_impl().map_err(into_jsrpc_error)
+    }
+
+    fn update_aggregation_policy(
+        &self,
+        update: AggregationPolicyUpdate,
+    ) -> RpcResult<AggregationPolicy> {
+        self.update_aggregation_policy_impl(update)
+            .map_err(into_jsrpc_error)
+    }
+}
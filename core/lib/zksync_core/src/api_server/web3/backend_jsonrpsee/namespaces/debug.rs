@@ -1,7 +1,10 @@
 use zksync_types::{
-    api::{BlockId, BlockNumber, DebugCall, ResultDebugCall, TracerConfig},
+    api::{
+        BlockId, BlockNumber, DebugCall, DebugTrace, ResultDebugCall, TraceCallConfig,
+        TracerConfig, TransactionReplay,
+    },
     transaction_request::CallRequest,
-    H256,
+    Bytes, H256,
 };
 use zksync_web3_decl::{
     jsonrpsee::core::{async_trait, RpcResult},
@@ -34,17 +37,49 @@ impl DebugNamespaceServer for DebugNamespace {
         &self,
         request: CallRequest,
         block: Option<BlockId>,
-        options: Option<TracerConfig>,
-    ) -> RpcResult<DebugCall> {
+        options: Option<TraceCallConfig>,
+    ) -> RpcResult<DebugTrace> {
         self.debug_trace_call_impl(request, block, options)
             .await
             .map_err(into_jsrpc_error)
     }
+    async fn trace_call_many(
+        &self,
+        requests: Vec<CallRequest>,
+        block: Option<BlockId>,
+        options: Option<TraceCallConfig>,
+    ) -> RpcResult<Vec<DebugCall>> {
+        self.debug_trace_call_many_impl(requests, block, options)
+            .await
+            .map_err(into_jsrpc_error)
+    }
     async fn trace_transaction(
         &self,
         tx_hash: H256,
         options: Option<TracerConfig>,
-    ) -> RpcResult<Option<DebugCall>> {
-        Ok(self.debug_trace_transaction_impl(tx_hash, options).await)
+    ) -> RpcResult<Option<DebugTrace>> {
+        self.debug_trace_transaction_impl(tx_hash, options)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+    async fn replay_transaction(&self, tx_hash: H256) -> RpcResult<Option<TransactionReplay>> {
+        self.replay_transaction_impl(tx_hash)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+    async fn get_raw_transaction(&self, tx_hash: H256) -> RpcResult<Option<Bytes>> {
+        self.get_raw_transaction_impl(tx_hash)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+    async fn get_raw_block(&self, block: BlockId) -> RpcResult<Bytes> {
+        self.get_raw_block_impl(block)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+    async fn get_raw_receipts(&self, block: BlockId) -> RpcResult<Vec<Bytes>> {
+        self.get_raw_receipts_impl(block)
+            .await
+            .map_err(into_jsrpc_error)
     }
 }
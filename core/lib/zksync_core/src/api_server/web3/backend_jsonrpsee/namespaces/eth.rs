@@ -1,8 +1,10 @@
 use zksync_types::{
     api::{
-        Block, BlockId, BlockIdVariant, BlockNumber, Log, Transaction, TransactionId,
-        TransactionReceipt, TransactionVariant,
+        simulate::{SimulatePayload, SimulatedBlock},
+        AccessListWithGasUsed, AccountProof, Block, BlockId, BlockIdVariant, BlockNumber,
+        StateOverride, Transaction, TransactionId, TransactionReceipt, TransactionVariant,
     },
+    l2::conditions::TransactionConditions,
     transaction_request::CallRequest,
     web3::types::{FeeHistory, Index, SyncState},
     Address, Bytes, H256, U256, U64,
@@ -10,7 +12,7 @@ use zksync_types::{
 use zksync_web3_decl::{
     jsonrpsee::core::{async_trait, RpcResult},
     namespaces::eth::EthNamespaceServer,
-    types::{Filter, FilterChanges},
+    types::{Filter, FilterChanges, GetLogsResponse},
 };
 
 use crate::api_server::web3::{backend_jsonrpsee::into_jsrpc_error, EthNamespace};
@@ -25,14 +27,44 @@ impl EthNamespaceServer for EthNamespace {
         Ok(self.chain_id_impl())
     }
 
-    async fn call(&self, req: CallRequest, block: Option<BlockIdVariant>) -> RpcResult<Bytes> {
-        self.call_impl(req, block.map(Into::into))
+    async fn call(
+        &self,
+        req: CallRequest,
+        block: Option<BlockIdVariant>,
+        state_override: Option<StateOverride>,
+    ) -> RpcResult<Bytes> {
+        self.call_impl(req, block.map(Into::into), state_override)
             .await
             .map_err(into_jsrpc_error)
     }
 
-    async fn estimate_gas(&self, req: CallRequest, block: Option<BlockNumber>) -> RpcResult<U256> {
-        self.estimate_gas_impl(req, block)
+    async fn create_access_list(
+        &self,
+        req: CallRequest,
+        block: Option<BlockIdVariant>,
+    ) -> RpcResult<AccessListWithGasUsed> {
+        self.create_access_list_impl(req, block.map(Into::into))
+            .await
+            .map_err(into_jsrpc_error)
+    }
+
+    async fn simulate_v1(
+        &self,
+        payload: SimulatePayload,
+        block: Option<BlockIdVariant>,
+    ) -> RpcResult<Vec<SimulatedBlock>> {
+        self.simulate_v1_impl(payload, block.map(Into::into))
+            .await
+            .map_err(into_jsrpc_error)
+    }
+
+    async fn estimate_gas(
+        &self,
+        req: CallRequest,
+        block: Option<BlockNumber>,
+        state_override: Option<StateOverride>,
+    ) -> RpcResult<U256> {
+        self.estimate_gas_impl(req, block, state_override)
             .await
             .map_err(into_jsrpc_error)
     }
@@ -41,6 +73,12 @@ impl EthNamespaceServer for EthNamespace {
         self.gas_price_impl().await.map_err(into_jsrpc_error)
     }
 
+    async fn max_priority_fee_per_gas(&self) -> RpcResult<U256> {
+        self.max_priority_fee_per_gas_impl()
+            .await
+            .map_err(into_jsrpc_error)
+    }
+
     async fn new_filter(&self, filter: Filter) -> RpcResult<U256> {
         self.new_filter_impl(filter).await.map_err(into_jsrpc_error)
     }
@@ -50,14 +88,18 @@ impl EthNamespaceServer for EthNamespace {
     }
 
     async fn uninstall_filter(&self, idx: U256) -> RpcResult<bool> {
-        Ok(self.uninstall_filter_impl(idx).await)
+        self.uninstall_filter_impl(idx)
+            .await
+            .map_err(into_jsrpc_error)
     }
 
     async fn new_pending_transaction_filter(&self) -> RpcResult<U256> {
-        Ok(self.new_pending_transaction_filter_impl().await)
+        self.new_pending_transaction_filter_impl()
+            .await
+            .map_err(into_jsrpc_error)
     }
 
-    async fn get_logs(&self, filter: Filter) -> RpcResult<Vec<Log>> {
+    async fn get_logs(&self, filter: Filter) -> RpcResult<GetLogsResponse> {
         self.get_logs_impl(filter).await.map_err(into_jsrpc_error)
     }
 
@@ -138,6 +180,17 @@ impl EthNamespaceServer for EthNamespace {
             .map_err(into_jsrpc_error)
     }
 
+    async fn get_proof(
+        &self,
+        address: Address,
+        keys: Vec<H256>,
+        block: BlockIdVariant,
+    ) -> RpcResult<AccountProof> {
+        self.get_proof_impl(address, keys, block.into())
+            .await
+            .map_err(into_jsrpc_error)
+    }
+
     async fn get_transaction_count(
         &self,
         address: Address,
@@ -180,6 +233,15 @@ impl EthNamespaceServer for EthNamespace {
             .map_err(into_jsrpc_error)
     }
 
+    async fn get_block_receipts(
+        &self,
+        block: BlockId,
+    ) -> RpcResult<Option<Vec<TransactionReceipt>>> {
+        self.get_block_receipts_impl(block)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+
     async fn protocol_version(&self) -> RpcResult<String> {
         Ok(self.protocol_version())
     }
@@ -190,6 +252,16 @@ impl EthNamespaceServer for EthNamespace {
             .map_err(into_jsrpc_error)
     }
 
+    async fn send_raw_transaction_conditional(
+        &self,
+        tx_bytes: Bytes,
+        conditions: TransactionConditions,
+    ) -> RpcResult<H256> {
+        self.send_raw_transaction_conditional_impl(tx_bytes, conditions)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+
     async fn syncing(&self) -> RpcResult<SyncState> {
         Ok(self.syncing_impl())
     }
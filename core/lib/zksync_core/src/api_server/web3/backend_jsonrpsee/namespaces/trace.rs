@@ -0,0 +1,56 @@
+use zksync_types::{
+    api::{
+        trace::{
+            LocalizedTrace, TraceFilter, TraceResults, TraceResultsWithTransactionHash, TraceType,
+        },
+        BlockId,
+    },
+    transaction_request::CallRequest,
+    H256,
+};
+use zksync_web3_decl::{
+    jsonrpsee::core::{async_trait, RpcResult},
+    namespaces::trace::TraceNamespaceServer,
+};
+
+use crate::api_server::web3::{backend_jsonrpsee::into_jsrpc_error, namespaces::TraceNamespace};
+
+#[async_trait]
+impl TraceNamespaceServer for TraceNamespace {
+    async fn call(
+        &self,
+        request: CallRequest,
+        trace_types: Vec<TraceType>,
+        block: Option<BlockId>,
+    ) -> RpcResult<TraceResults> {
+        self.trace_call_impl(request, trace_types, block)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+    async fn replay_transaction(
+        &self,
+        tx_hash: H256,
+        trace_types: Vec<TraceType>,
+    ) -> RpcResult<Option<TraceResults>> {
+        self.trace_replay_transaction_impl(tx_hash, trace_types)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+    async fn replay_block_transactions(
+        &self,
+        block: BlockId,
+        trace_types: Vec<TraceType>,
+    ) -> RpcResult<Vec<TraceResultsWithTransactionHash>> {
+        self.trace_replay_block_transactions_impl(block, trace_types)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+    async fn block(&self, block: BlockId) -> RpcResult<Vec<LocalizedTrace>> {
+        self.trace_block_impl(block).await.map_err(into_jsrpc_error)
+    }
+    async fn filter(&self, filter: TraceFilter) -> RpcResult<Vec<LocalizedTrace>> {
+        self.trace_filter_impl(filter)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+}
@@ -0,0 +1,29 @@
+use async_trait::async_trait;
+use zksync_types::{
+    api::{TxpoolAccountUsage, TxpoolContent, TxpoolStatus},
+    Address,
+};
+use zksync_web3_decl::{jsonrpsee::core::RpcResult, namespaces::TxpoolNamespaceServer};
+
+use crate::api_server::web3::{backend_jsonrpsee::into_jsrpc_error, namespaces::TxpoolNamespace};
+
+#[async_trait]
+impl TxpoolNamespaceServer for TxpoolNamespace {
+    async fn content(&self) -> RpcResult<TxpoolContent> {
+        self.content_impl().await.map_err(into_jsrpc_error)
+    }
+
+    async fn status(&self) -> RpcResult<TxpoolStatus> {
+        self.status_impl().await.map_err(into_jsrpc_error)
+    }
+
+    async fn inspect(&self) -> RpcResult<TxpoolContent<String>> {
+        self.inspect_impl().await.map_err(into_jsrpc_error)
+    }
+
+    async fn account_usage(&self, address: Address) -> RpcResult<TxpoolAccountUsage> {
+        self.account_usage_impl(address)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+}
@@ -3,8 +3,9 @@ use std::collections::HashMap;
 use bigdecimal::BigDecimal;
 use zksync_types::{
     api::{
-        BlockDetails, BridgeAddresses, L1BatchDetails, L2ToL1LogProof, Proof, ProtocolVersion,
-        TransactionDetails,
+        BlockDetails, BlockNumber, BridgeAddresses, FeeToken, L1BatchDetails, L1BatchFeeParams,
+        L1BatchRevenue, L2ToL1LogProof, L2ToL1LogProofRequest, Proof, ProtocolVersion,
+        StateOverride, TransactionDetails,
     },
     fee::Fee,
     fee_model::FeeParams,
@@ -12,17 +13,30 @@ use zksync_types::{
     Address, L1BatchNumber, MiniblockNumber, H256, U256, U64,
 };
 use zksync_web3_decl::{
-    jsonrpsee::core::{async_trait, RpcResult},
-    namespaces::zks::ZksNamespaceServer,
-    types::Token,
+    jsonrpsee::{
+        core::{async_trait, server::SubscriptionMessage, RpcResult, SubscriptionResult},
+        PendingSubscriptionSink,
+    },
+    namespaces::zks::{ZksNamespaceServer, ZksPubSubServer},
+    types::{Bytes, Token},
 };
 
 use crate::api_server::web3::{backend_jsonrpsee::into_jsrpc_error, ZksNamespace};
 
+/// Default number of transactions in a chunk emitted by `zks_subscribeRawBlockTransactions`.
+const DEFAULT_RAW_TRANSACTIONS_CHUNK_SIZE: usize = 100;
+
 #[async_trait]
 impl ZksNamespaceServer for ZksNamespace {
-    async fn estimate_fee(&self, req: CallRequest) -> RpcResult<Fee> {
-        self.estimate_fee_impl(req).await.map_err(into_jsrpc_error)
+    async fn estimate_fee(
+        &self,
+        req: CallRequest,
+        state_override: Option<StateOverride>,
+        block: Option<BlockNumber>,
+    ) -> RpcResult<Fee> {
+        self.estimate_fee_impl(req, state_override, block)
+            .await
+            .map_err(into_jsrpc_error)
     }
 
     async fn estimate_gas_l1_to_l2(&self, req: CallRequest) -> RpcResult<U256> {
@@ -43,6 +57,10 @@ impl ZksNamespaceServer for ZksNamespace {
         Ok(self.get_bridge_contracts_impl())
     }
 
+    async fn get_fee_token(&self) -> RpcResult<FeeToken> {
+        Ok(self.get_fee_token_impl())
+    }
+
     async fn l1_chain_id(&self) -> RpcResult<U64> {
         Ok(self.l1_chain_id_impl())
     }
@@ -90,6 +108,15 @@ impl ZksNamespaceServer for ZksNamespace {
             .map_err(into_jsrpc_error)
     }
 
+    async fn get_l2_to_l1_log_proofs(
+        &self,
+        requests: Vec<L2ToL1LogProofRequest>,
+    ) -> RpcResult<Vec<Option<L2ToL1LogProof>>> {
+        self.get_l2_to_l1_log_proofs_impl(requests)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+
     async fn get_l1_batch_number(&self) -> RpcResult<U64> {
         self.get_l1_batch_number_impl()
             .await
@@ -120,8 +147,10 @@ impl ZksNamespaceServer for ZksNamespace {
     async fn get_raw_block_transactions(
         &self,
         block_number: MiniblockNumber,
+        offset: Option<usize>,
+        limit: Option<usize>,
     ) -> RpcResult<Vec<zksync_types::Transaction>> {
-        self.get_raw_block_transactions_impl(block_number)
+        self.get_raw_block_transactions_impl(block_number, offset, limit)
             .await
             .map_err(into_jsrpc_error)
     }
@@ -135,6 +164,24 @@ impl ZksNamespaceServer for ZksNamespace {
             .map_err(into_jsrpc_error)
     }
 
+    async fn get_l1_batch_fee_params(
+        &self,
+        batch_number: L1BatchNumber,
+    ) -> RpcResult<Option<L1BatchFeeParams>> {
+        self.get_l1_batch_fee_params_impl(batch_number)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+
+    async fn get_l1_batch_revenue(
+        &self,
+        batch_number: L1BatchNumber,
+    ) -> RpcResult<Option<L1BatchRevenue>> {
+        self.get_l1_batch_revenue_impl(batch_number)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+
     async fn get_bytecode_by_hash(&self, hash: H256) -> RpcResult<Option<Vec<u8>>> {
         Ok(self.get_bytecode_by_hash_impl(hash).await)
     }
@@ -154,6 +201,10 @@ impl ZksNamespaceServer for ZksNamespace {
         Ok(self.get_protocol_version_impl(version_id).await)
     }
 
+    async fn get_scheduled_protocol_upgrades(&self) -> RpcResult<Vec<ProtocolVersion>> {
+        Ok(self.get_scheduled_protocol_upgrades_impl().await)
+    }
+
     async fn get_proof(
         &self,
         address: Address,
@@ -164,4 +215,45 @@ impl ZksNamespaceServer for ZksNamespace {
             .await
             .map_err(into_jsrpc_error)
     }
+    async fn send_bundle(&self, txs: Vec<Bytes>) -> RpcResult<H256> {
+        self.send_bundle_impl(txs).await.map_err(into_jsrpc_error)
+    }
+
+    async fn send_private_raw_transaction(&self, tx_bytes: Bytes) -> RpcResult<H256> {
+        self.send_private_raw_transaction_impl(tx_bytes)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+}
+
+#[async_trait]
+impl ZksPubSubServer for ZksNamespace {
+    async fn subscribe_raw_block_transactions(
+        &self,
+        pending: PendingSubscriptionSink,
+        block_number: MiniblockNumber,
+        chunk_size: Option<usize>,
+    ) -> SubscriptionResult {
+        let chunk_size = chunk_size.unwrap_or(DEFAULT_RAW_TRANSACTIONS_CHUNK_SIZE);
+        if let Err(err) = self.validate_raw_transactions_limit(chunk_size) {
+            pending.reject(into_jsrpc_error(err)).await;
+            return Ok(());
+        }
+
+        let Ok(sink) = pending.accept().await else {
+            return Ok(());
+        };
+        let mut offset = 0;
+        loop {
+            let chunk = self
+                .get_raw_block_transactions_impl(block_number, Some(offset), Some(chunk_size))
+                .await?;
+            let chunk_len = chunk.len();
+            sink.send(SubscriptionMessage::from_json(&chunk)?).await?;
+            if chunk_len < chunk_size {
+                return Ok(());
+            }
+            offset += chunk_len;
+        }
+    }
 }
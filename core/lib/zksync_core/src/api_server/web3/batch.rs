@@ -0,0 +1,191 @@
+//! HTTP middleware executing calls in JSON-RPC batches concurrently and limiting the cumulative size
+//! of batch responses.
+//!
+//! `jsonrpsee` executes calls in a batch one by one, so a large batch of calls requiring the VM is serialized
+//! behind VM permits. This middleware splits batches into single calls passed to the server with bounded
+//! parallelism, and assembles their responses into a batch response. Batches sent via WebSocket are not affected.
+
+use std::{
+    num::NonZeroUsize,
+    task::{Context, Poll},
+};
+
+use futures::{future::BoxFuture, stream, StreamExt};
+use hyper::{header, http::request, Body, Method, Request, Response, StatusCode};
+use tower::{Layer, Service, ServiceExt};
+
+use super::rate_limit::{json_rpc_error_response, read_body};
+
+/// JSON-RPC error code for batches with too many calls. Same as the one used by `jsonrpsee`.
+const TOO_BIG_BATCH_REQUEST_CODE: i32 = -32_010;
+/// JSON-RPC error code for batches with too large cumulative response. Same as the one used by `jsonrpsee`.
+const TOO_BIG_BATCH_RESPONSE_CODE: i32 = -32_011;
+
+/// Limits and parallelism for batch HTTP requests.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct BatchConfig {
+    /// Max number of calls in a batch.
+    pub max_calls: Option<usize>,
+    /// Max cumulative size of responses to calls in a batch.
+    pub max_response_size: usize,
+    /// Max number of calls in a batch executed concurrently.
+    pub parallelism: NonZeroUsize,
+}
+
+/// Tower layer executing batch requests according to [`BatchConfig`].
+#[derive(Debug, Clone)]
+pub(super) struct BatchLayer {
+    config: BatchConfig,
+}
+
+impl BatchLayer {
+    pub fn new(config: BatchConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl<S> Layer<S> for BatchLayer {
+    type Service = BatchService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        BatchService {
+            inner,
+            config: self.config,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct BatchService<S> {
+    inner: S,
+    config: BatchConfig,
+}
+
+impl<S> BatchService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    fn single_call_request(parts: &request::Parts, call: &serde_json::Value) -> Request<Body> {
+        let mut request = Request::new(Body::from(call.to_string()));
+        *request.method_mut() = parts.method.clone();
+        *request.uri_mut() = parts.uri.clone();
+        *request.version_mut() = parts.version;
+        *request.headers_mut() = parts.headers.clone();
+        request.headers_mut().remove(header::CONTENT_LENGTH);
+        request
+    }
+
+    async fn execute_batch(
+        inner: S,
+        parts: request::Parts,
+        calls: Vec<serde_json::Value>,
+        config: BatchConfig,
+    ) -> Result<Response<Body>, S::Error> {
+        // The closure takes ownership of `inner`, so that `S` isn't required to be `Sync`.
+        let responses = stream::iter(&calls).map(move |call| {
+            let request = Self::single_call_request(&parts, call);
+            inner.clone().oneshot(request)
+        });
+        let mut responses = responses.buffered(config.parallelism.get());
+
+        let mut batch_response = vec![b'['];
+        while let Some(response) = responses.next().await {
+            let Ok(body) = hyper::body::to_bytes(response?.into_body()).await else {
+                let response = Response::builder().status(StatusCode::INTERNAL_SERVER_ERROR);
+                return Ok(response.body(Body::empty()).unwrap());
+            };
+            if body.is_empty() {
+                continue; // Notifications don't produce a response
+            }
+
+            // Account for the separating comma and the closing bracket.
+            if batch_response.len() + body.len() + 1 > config.max_response_size {
+                let message = format!(
+                    "The batch response was too large; at most {} bytes are allowed",
+                    config.max_response_size
+                );
+                return Ok(json_rpc_error_response(
+                    StatusCode::OK,
+                    None,
+                    TOO_BIG_BATCH_RESPONSE_CODE,
+                    &message,
+                ));
+            }
+            if batch_response.len() > 1 {
+                batch_response.push(b',');
+            }
+            batch_response.extend_from_slice(&body);
+        }
+
+        if batch_response.len() == 1 {
+            // The batch consists only of notifications.
+            return Ok(Response::new(Body::empty()));
+        }
+        batch_response.push(b']');
+        Ok(Response::builder()
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(batch_response))
+            .unwrap())
+    }
+}
+
+impl<S> Service<Request<Body>> for BatchService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Send + 'static,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        if request.method() != Method::POST {
+            return Box::pin(self.inner.call(request));
+        }
+        // Take the service that was driven to readiness and leave a fresh clone in its place.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let config = self.config;
+
+        Box::pin(async move {
+            let (parts, body) = request.into_parts();
+            let body = match read_body(body).await {
+                Ok(body) => body,
+                Err(status) => {
+                    let response = Response::builder().status(status).body(Body::empty());
+                    return Ok(response.unwrap());
+                }
+            };
+            // Single calls, as well as empty or malformed batches are passed through to `jsonrpsee`.
+            let calls = match serde_json::from_slice::<Vec<serde_json::Value>>(&body) {
+                Ok(calls) if !calls.is_empty() => calls,
+                _ => {
+                    return inner
+                        .call(Request::from_parts(parts, Body::from(body)))
+                        .await
+                }
+            };
+
+            if let Some(max_calls) = config.max_calls {
+                if calls.len() > max_calls {
+                    let message = format!(
+                        "The batch request was too large; at most {max_calls} calls are allowed"
+                    );
+                    return Ok(json_rpc_error_response(
+                        StatusCode::OK,
+                        None,
+                        TOO_BIG_BATCH_REQUEST_CODE,
+                        &message,
+                    ));
+                }
+            }
+            Self::execute_batch(inner, parts, calls, config).await
+        })
+    }
+}
@@ -0,0 +1,54 @@
+//! HTTP response compression for the JSON-RPC server.
+
+use futures::stream;
+use hyper::{body::HttpBody, Body, Response};
+use tower::{util::MapResponse, Layer};
+use tower_http::compression::{
+    predicate::{And, NotForContentType, Predicate, SizeAbove},
+    Compression, CompressionBody, CompressionLayer,
+};
+
+type ResponsePredicate = And<And<SizeAbove, NotForContentType>, NotForContentType>;
+
+type IntoHyperBody = fn(Response<CompressionBody<Body>>) -> Response<Body>;
+
+/// Tower layer compressing HTTP responses with an encoding negotiated via the `Accept-Encoding` header.
+/// Server-sent events and small responses are not compressed.
+#[derive(Debug, Clone)]
+pub(super) struct ResponseCompressionLayer {
+    inner: CompressionLayer<ResponsePredicate>,
+}
+
+impl ResponseCompressionLayer {
+    pub fn new(min_size: u16) -> Self {
+        let predicate = SizeAbove::new(min_size)
+            .and(NotForContentType::GRPC)
+            .and(NotForContentType::SSE);
+        Self {
+            inner: CompressionLayer::new().compress_when(predicate),
+        }
+    }
+
+    /// Converts the compressed body back to `hyper::Body`, so that the layer can wrap other middleware
+    /// operating on `hyper` requests / responses.
+    fn into_hyper_body(response: Response<CompressionBody<Body>>) -> Response<Body> {
+        response.map(|body| {
+            let chunks = stream::unfold(Box::pin(body), |mut body| async move {
+                let chunk = body.data().await?;
+                Some((chunk, body))
+            });
+            Body::wrap_stream(chunks)
+        })
+    }
+}
+
+impl<S> Layer<S> for ResponseCompressionLayer {
+    type Service = MapResponse<Compression<S, ResponsePredicate>, IntoHyperBody>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MapResponse::new(
+            self.inner.layer(inner),
+            Self::into_hyper_body as IntoHyperBody,
+        )
+    }
+}
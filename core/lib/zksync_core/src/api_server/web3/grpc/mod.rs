@@ -0,0 +1,342 @@
+//! gRPC server exposing streaming APIs for miniblocks, logs and L1 batch status transitions.
+//!
+//! The server is intended for high-throughput internal consumers that prefer a typed streaming protocol
+//! to WebSocket subscriptions. Notifications are produced by the same notifiers as Web3 pub-sub
+//! subscriptions (see [`EthSubscribe`]).
+
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+use anyhow::Context as _;
+use tokio::{
+    net::TcpListener,
+    sync::{broadcast, mpsc, watch, OwnedSemaphorePermit, Semaphore},
+};
+use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream};
+use tonic::{transport::Server, Request, Response, Status};
+use zksync_dal::ConnectionPool;
+use zksync_health_check::{HealthStatus, ReactiveHealthCheck};
+use zksync_types::{L2ChainId, H160, H256};
+use zksync_web3_decl::types::{
+    BlockHeader, L1BatchStage, L1BatchStatusEvent, Log, PubSubFilter, PubSubResult, ValueOrArray,
+};
+
+use self::proto::streaming_api_server::{StreamingApi, StreamingApiServer};
+use super::{
+    metrics::{SubscriptionType, GRPC_METRICS},
+    namespaces::eth::EVENT_TOPIC_NUMBER_LIMIT,
+    pubsub::{EthSubscribe, PubSubEvent, SubscriptionQueueConfig},
+    ApiServerHandles,
+};
+
+#[allow(clippy::all)]
+pub(super) mod proto {
+    tonic::include_proto!("zksync.api.streaming");
+}
+
+/// Capacity of the outgoing message buffer for a single stream.
+const STREAM_BUFFER_CAPACITY: usize = 1_024;
+/// Default maximum number of concurrently open streams.
+const DEFAULT_MAX_CONCURRENT_STREAMS: usize = 1_000;
+
+impl From<BlockHeader> for proto::Block {
+    fn from(header: BlockHeader) -> Self {
+        Self {
+            number: header.number.unwrap_or_default().as_u32(),
+            hash: header.hash.unwrap_or_default().as_bytes().to_vec(),
+            parent_hash: header.parent_hash.as_bytes().to_vec(),
+            timestamp: header.timestamp.as_u64(),
+        }
+    }
+}
+
+impl From<Log> for proto::Log {
+    fn from(log: Log) -> Self {
+        Self {
+            address: log.address.as_bytes().to_vec(),
+            topics: log
+                .topics
+                .iter()
+                .map(|topic| topic.as_bytes().to_vec())
+                .collect(),
+            data: log.data.0,
+            block_number: log.block_number.unwrap_or_default().as_u32(),
+            block_hash: log.block_hash.unwrap_or_default().as_bytes().to_vec(),
+            l1_batch_number: log.l1_batch_number.map(|number| number.as_u32()),
+            transaction_hash: log.transaction_hash.unwrap_or_default().as_bytes().to_vec(),
+            transaction_index: log.transaction_index.unwrap_or_default().as_u32(),
+            log_index: log.log_index.unwrap_or_default().as_u32(),
+        }
+    }
+}
+
+impl From<L1BatchStage> for proto::L1BatchStage {
+    fn from(stage: L1BatchStage) -> Self {
+        match stage {
+            L1BatchStage::Sealed => Self::Sealed,
+            L1BatchStage::Committed => Self::Committed,
+            L1BatchStage::Proven => Self::Proven,
+            L1BatchStage::Executed => Self::Executed,
+        }
+    }
+}
+
+impl From<L1BatchStatusEvent> for proto::L1BatchStatusEvent {
+    fn from(event: L1BatchStatusEvent) -> Self {
+        Self {
+            l1_batch_number: event.l1_batch_number.0,
+            stage: proto::L1BatchStage::from(event.stage).into(),
+        }
+    }
+}
+
+impl proto::SubscribeLogsRequest {
+    fn to_filter(&self) -> Result<PubSubFilter, Status> {
+        if self.topics.len() > EVENT_TOPIC_NUMBER_LIMIT {
+            return Err(Status::invalid_argument(format!(
+                "at most {EVENT_TOPIC_NUMBER_LIMIT} topics are supported"
+            )));
+        }
+
+        let address = if self.addresses.is_empty() {
+            None
+        } else {
+            let addresses = self.addresses.iter().map(|address| {
+                if address.len() == H160::len_bytes() {
+                    Ok(H160::from_slice(address))
+                } else {
+                    Err(Status::invalid_argument("addresses must be 20 bytes long"))
+                }
+            });
+            Some(ValueOrArray(addresses.collect::<Result<_, _>>()?))
+        };
+
+        let topics = self.topics.iter().map(|topic| {
+            if topic.values.is_empty() {
+                return Ok(None);
+            }
+            let values = topic.values.iter().map(|value| {
+                if value.len() == H256::len_bytes() {
+                    Ok(H256::from_slice(value))
+                } else {
+                    Err(Status::invalid_argument("topics must be 32 bytes long"))
+                }
+            });
+            Ok(Some(ValueOrArray(values.collect::<Result<_, _>>()?)))
+        });
+        let topics = topics.collect::<Result<Vec<_>, Status>>()?;
+
+        Ok(PubSubFilter {
+            address,
+            topics: (!topics.is_empty()).then_some(topics),
+            cursor: None,
+        })
+    }
+}
+
+/// Implementation of the streaming gRPC service.
+struct StreamingService {
+    pub_sub: EthSubscribe,
+    /// Limits the number of concurrently open streams; each stream holds a permit until it is terminated.
+    stream_permits: Arc<Semaphore>,
+}
+
+impl StreamingService {
+    fn acquire_stream_permit(
+        &self,
+        subscription_type: SubscriptionType,
+    ) -> Result<OwnedSemaphorePermit, Status> {
+        self.stream_permits
+            .clone()
+            .try_acquire_owned()
+            .map_err(|_| {
+                GRPC_METRICS.rejected_streams[&subscription_type].inc();
+                Status::resource_exhausted("too many concurrent streams; try again later")
+            })
+    }
+
+    /// Spawns a task forwarding notifications from `receiver` to the returned stream. `map_item` converts
+    /// notifications to stream messages, skipping those for which it returns `None`.
+    fn spawn_stream<T: Send + 'static>(
+        permit: OwnedSemaphorePermit,
+        subscription_type: SubscriptionType,
+        mut receiver: broadcast::Receiver<Vec<PubSubResult>>,
+        map_item: impl Fn(PubSubResult) -> Option<T> + Send + 'static,
+    ) -> ReceiverStream<Result<T, Status>> {
+        let (sender, stream) = mpsc::channel(STREAM_BUFFER_CAPACITY);
+        tokio::spawn(async move {
+            let _permit = permit;
+            let _guard = GRPC_METRICS.active_streams[&subscription_type].inc_guard(1);
+            loop {
+                let items = tokio::select! {
+                    () = sender.closed() => return,
+                    items = receiver.recv() => items,
+                };
+                let items = match items {
+                    Ok(items) => items,
+                    Err(broadcast::error::RecvError::Closed) => return,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        GRPC_METRICS.lagged_streams[&subscription_type].inc();
+                        let message = format!(
+                            "client is too slow; {skipped} notification batches were skipped"
+                        );
+                        sender.send(Err(Status::data_loss(message))).await.ok();
+                        return;
+                    }
+                };
+
+                for item in items.into_iter().filter_map(&map_item) {
+                    if sender.send(Ok(item)).await.is_err() {
+                        return; // The client has disconnected
+                    }
+                }
+            }
+        });
+        ReceiverStream::new(stream)
+    }
+}
+
+#[tonic::async_trait]
+impl StreamingApi for StreamingService {
+    type SubscribeBlocksStream = ReceiverStream<Result<proto::Block, Status>>;
+    type SubscribeLogsStream = ReceiverStream<Result<proto::Log, Status>>;
+    type SubscribeL1BatchStatusStream = ReceiverStream<Result<proto::L1BatchStatusEvent, Status>>;
+
+    async fn subscribe_blocks(
+        &self,
+        _request: Request<proto::SubscribeBlocksRequest>,
+    ) -> Result<Response<Self::SubscribeBlocksStream>, Status> {
+        let subscription_type = SubscriptionType::Blocks;
+        let permit = self.acquire_stream_permit(subscription_type)?;
+        let receiver = self.pub_sub.subscribe_raw(subscription_type);
+        let stream = Self::spawn_stream(permit, subscription_type, receiver, |item| match item {
+            PubSubResult::Header(header) => Some(header.into()),
+            _ => None,
+        });
+        Ok(Response::new(stream))
+    }
+
+    async fn subscribe_logs(
+        &self,
+        request: Request<proto::SubscribeLogsRequest>,
+    ) -> Result<Response<Self::SubscribeLogsStream>, Status> {
+        let filter = request.get_ref().to_filter()?;
+        let subscription_type = SubscriptionType::Logs;
+        let permit = self.acquire_stream_permit(subscription_type)?;
+        let receiver = self.pub_sub.subscribe_raw(subscription_type);
+        let stream = Self::spawn_stream(
+            permit,
+            subscription_type,
+            receiver,
+            move |item| match item {
+                PubSubResult::Log(log) if filter.matches(&log) => Some(log.into()),
+                _ => None,
+            },
+        );
+        Ok(Response::new(stream))
+    }
+
+    async fn subscribe_l1_batch_status(
+        &self,
+        _request: Request<proto::SubscribeL1BatchStatusRequest>,
+    ) -> Result<Response<Self::SubscribeL1BatchStatusStream>, Status> {
+        let subscription_type = SubscriptionType::L1Batches;
+        let permit = self.acquire_stream_permit(subscription_type)?;
+        let receiver = self.pub_sub.subscribe_raw(subscription_type);
+        let stream = Self::spawn_stream(permit, subscription_type, receiver, |item| match item {
+            PubSubResult::L1BatchStatus(event) => Some(event.into()),
+            _ => None,
+        });
+        Ok(Response::new(stream))
+    }
+}
+
+/// gRPC server providing streaming APIs.
+#[derive(Debug)]
+pub struct GrpcStreamingServer {
+    pool: ConnectionPool,
+    chain_id: L2ChainId,
+    polling_interval: Duration,
+    max_concurrent_streams: usize,
+    pub_sub_events_sender: Option<mpsc::UnboundedSender<PubSubEvent>>,
+}
+
+impl GrpcStreamingServer {
+    pub fn new(pool: ConnectionPool, chain_id: L2ChainId, polling_interval: Duration) -> Self {
+        Self {
+            pool,
+            chain_id,
+            polling_interval,
+            max_concurrent_streams: DEFAULT_MAX_CONCURRENT_STREAMS,
+            pub_sub_events_sender: None,
+        }
+    }
+
+    /// Sets the maximum number of concurrently open streams across all connections. Streams opened
+    /// after the limit is reached are rejected with the `RESOURCE_EXHAUSTED` status.
+    pub fn with_max_concurrent_streams(mut self, limit: usize) -> Self {
+        self.max_concurrent_streams = limit;
+        self
+    }
+
+    #[cfg(test)]
+    pub(super) fn with_pub_sub_events(
+        mut self,
+        sender: mpsc::UnboundedSender<PubSubEvent>,
+    ) -> Self {
+        self.pub_sub_events_sender = Some(sender);
+        self
+    }
+
+    /// Binds the server to the specified address and spawns server tasks.
+    pub async fn spawn(
+        self,
+        addr: SocketAddr,
+        mut stop_receiver: watch::Receiver<bool>,
+    ) -> anyhow::Result<ApiServerHandles> {
+        let listener = TcpListener::bind(addr)
+            .await
+            .with_context(|| format!("Failed binding gRPC server to {addr}"))?;
+        let local_addr = listener
+            .local_addr()
+            .context("Failed getting local address for gRPC server")?;
+        let (health_check, health_updater) = ReactiveHealthCheck::new("grpc_api");
+
+        let mut pub_sub = EthSubscribe::new(self.pool, SubscriptionQueueConfig::default());
+        if let Some(sender) = self.pub_sub_events_sender {
+            pub_sub.set_events_sender(sender);
+        }
+        let mut tasks =
+            pub_sub.spawn_notifiers(self.polling_interval, self.chain_id, stop_receiver.clone());
+        let service = StreamingApiServer::new(StreamingService {
+            pub_sub,
+            stream_permits: Arc::new(Semaphore::new(self.max_concurrent_streams)),
+        });
+
+        let server_task = tokio::spawn(async move {
+            let stop_signal = async move {
+                if stop_receiver.changed().await.is_err() {
+                    tracing::warn!(
+                        "Stop signal sender for gRPC server was dropped without sending a signal"
+                    );
+                }
+                tracing::info!("Stop signal received, gRPC server is shutting down");
+            };
+            health_updater.update(HealthStatus::Ready.into());
+            Server::builder()
+                .add_service(service)
+                .serve_with_incoming_shutdown(TcpListenerStream::new(listener), stop_signal)
+                .await
+                .context("gRPC server failed")?;
+            drop(health_updater);
+            tracing::info!("gRPC server stopped");
+            Ok(())
+        });
+        tasks.push(server_task);
+
+        Ok(ApiServerHandles {
+            local_addr,
+            tasks,
+            health_check,
+        })
+    }
+}
@@ -0,0 +1,135 @@
+// Driver for custom JavaScript tracers. Wraps trace events recorded by the VM into objects
+// mirroring the Geth JS tracer API and invokes the corresponding tracer callbacks.
+
+function __hexToBytes(hex) {
+    const bytes = [];
+    for (let i = 2; i < hex.length; i += 2) {
+        bytes.push(parseInt(hex.substr(i, 2), 16));
+    }
+    return bytes;
+}
+
+function toHex(bytes) {
+    return '0x' + Array.from(bytes, (byte) => ('0' + (byte & 0xff).toString(16)).slice(-2)).join('');
+}
+
+function toAddress(value) {
+    return typeof value === 'string' ? __hexToBytes(value) : value;
+}
+
+function toWord(value) {
+    const bytes = toAddress(value);
+    return new Array(Math.max(32 - bytes.length, 0)).fill(0).concat(bytes);
+}
+
+function slice(bytes, start, end) {
+    return bytes.slice(start, end);
+}
+
+const __unsupported = (name) => () => {
+    throw new Error(`db.${name}() is not supported`);
+};
+const __db = {
+    getBalance: __unsupported('getBalance'),
+    getNonce: __unsupported('getNonce'),
+    getCode: __unsupported('getCode'),
+    getState: __unsupported('getState'),
+    exists: __unsupported('exists')
+};
+
+function __makeContract(frame) {
+    return {
+        getAddress: () => __hexToBytes(frame.to),
+        getCaller: () => __hexToBytes(frame.from),
+        getValue: () => BigInt(frame.value),
+        getInput: () => __hexToBytes(frame.input)
+    };
+}
+
+function __makeStep(raw, frame) {
+    const stack = (raw.stack || []).map((word) => BigInt(word));
+    const memory = __hexToBytes('0x' + (raw.memory || []).map((word) => word.slice(2)).join(''));
+    return {
+        op: {
+            toString: () => raw.op,
+            isPush: () => false
+        },
+        stack: {
+            peek: (idx) => stack[idx],
+            length: () => stack.length
+        },
+        memory: {
+            slice: (start, end) => memory.slice(Number(start), Number(end)),
+            getUint: (offset) => BigInt(toHex(toWord(memory.slice(Number(offset), Number(offset) + 32)))),
+            length: () => memory.length
+        },
+        contract: __makeContract(frame),
+        getPC: () => raw.pc,
+        getGas: () => raw.gas,
+        getCost: () => raw.gasCost,
+        getDepth: () => raw.depth,
+        getRefund: () => 0,
+        getError: () => undefined
+    };
+}
+
+function __makeFrame(frame) {
+    return {
+        getType: () => frame.type,
+        getFrom: () => __hexToBytes(frame.from),
+        getTo: () => __hexToBytes(frame.to),
+        getInput: () => __hexToBytes(frame.input),
+        getGas: () => frame.gas,
+        getValue: () => BigInt(frame.value)
+    };
+}
+
+function __makeFrameResult(result) {
+    return {
+        getGasUsed: () => result.gasUsed,
+        getOutput: () => __hexToBytes(result.output),
+        getError: () => (result.error === null ? undefined : result.error)
+    };
+}
+
+function __runTracer(tracer, events, ctx) {
+    if (typeof tracer.result !== 'function') {
+        throw new Error('tracer must define the result() function');
+    }
+    if (typeof tracer.setup === 'function') {
+        tracer.setup({});
+    }
+
+    const frames = [];
+    for (const event of events) {
+        __checkDeadline();
+        if (event.kind === 'enter') {
+            frames.push(event.frame);
+            if (!event.topLevel && typeof tracer.enter === 'function') {
+                tracer.enter(__makeFrame(event.frame));
+            }
+        } else if (event.kind === 'exit') {
+            frames.pop();
+            if (!event.topLevel && typeof tracer.exit === 'function') {
+                tracer.exit(__makeFrameResult(event.result));
+            }
+        } else if (event.kind === 'step') {
+            const log = __makeStep(event.log, frames[frames.length - 1]);
+            if (typeof tracer.step === 'function') {
+                tracer.step(log, __db);
+            }
+            if (event.fault && typeof tracer.fault === 'function') {
+                tracer.fault(log, __db);
+            }
+        }
+    }
+
+    ctx.from = __hexToBytes(ctx.from);
+    ctx.to = __hexToBytes(ctx.to);
+    ctx.input = __hexToBytes(ctx.input);
+    ctx.output = __hexToBytes(ctx.output);
+    ctx.value = BigInt(ctx.value);
+    __checkDeadline();
+    const result = tracer.result(ctx, __db);
+    return JSON.stringify(result, (_, value) => (typeof value === 'bigint' ? value.toString() : value));
+}
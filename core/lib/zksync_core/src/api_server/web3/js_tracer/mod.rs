@@ -0,0 +1,527 @@
+//! Support of custom JavaScript tracers in the `debug` namespace.
+//!
+//! A JS tracer is run in the embedded [`boa_engine`] interpreter after the traced transaction is executed in the VM.
+//! Tracer callbacks (`step`, `fault`, `enter`, `exit` and `result`) are fed with struct logs and the call tree
+//! collected by the VM tracers. The exposed API mirrors Geth JS tracers with the following EraVM-specific differences:
+//!
+//! - `log.stack` exposes VM registers instead of the EVM stack; `log.memory` is the heap of the current frame.
+//! - `db` methods are not supported and throw an error.
+//! - `enter` / `exit` are not called for frames started by the bootloader (i.e., top-level frames).
+//!
+//! Since the interpreter has no built-in wall-clock or heap bounds, tracers always run with a timeout and a memory limit
+//! for collected VM traces (see [`js_tracer_limits()`]), and struct logs, individual loops and the tracer output are capped.
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context as _;
+use boa_engine::{Context, JsNativeError, JsValue, NativeFunction, Source};
+use multivm::{
+    interface::{ExecutionResult, VmExecutionResultAndLogs},
+    tracers::TraceLimiterState,
+};
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+use zksync_system_constants::CONTRACT_DEPLOYER_ADDRESS;
+use zksync_types::{
+    vm_trace::{
+        Call, CallType, StructLog, StructLogConfig, StructLogLimits, TraceLimitExceeded,
+        TraceLimits,
+    },
+    Address, Bytes, FarCallOpcode, MiniblockNumber, Transaction, H256, U256,
+};
+use zksync_web3_decl::error::Web3Error;
+
+use crate::api_server::execution_sandbox::ApiTracer;
+
+/// JS code invoking tracer callbacks for the recorded trace events.
+const DRIVER_CODE: &str = include_str!("driver.js");
+/// Maximum number of iterations of a single loop in a tracer. Together with the deadline checked between tracer
+/// callbacks, protects against tracers that never terminate.
+const LOOP_ITERATION_LIMIT: u64 = 1_000_000;
+/// Maximum recursion depth in a tracer.
+const RECURSION_LIMIT: usize = 512;
+/// Timeout applied to JS tracers if the request doesn't specify one (same as in Geth).
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Maximum timeout for JS tracers; larger timeouts specified in requests are capped.
+const MAX_TIMEOUT: Duration = Duration::from_secs(30);
+/// Maximum memory used by VM traces collected for a JS tracer; smaller limits specified in requests take precedence.
+const MAX_MEMORY_LIMIT: usize = 128 << 20;
+/// Limits for struct logs fed to a JS tracer. Unlike for the default struct log tracer, memory is always captured,
+/// so the limits are stricter.
+const STRUCT_LOG_LIMITS: StructLogLimits = StructLogLimits {
+    max_steps: 100_000,
+    max_captured_bytes: 64 << 20,
+};
+/// Maximum size of the tracer output in UTF-16 code units.
+const MAX_OUTPUT_LEN: usize = 16 << 20;
+
+/// Applies server-side defaults and caps to the limits requested for a JS tracer. Unlike with built-in tracers,
+/// JS tracers always run with a timeout and a memory limit, since the interpreter doesn't bound them on its own.
+pub(crate) fn js_tracer_limits(requested: TraceLimits) -> TraceLimits {
+    TraceLimits {
+        timeout: Some(
+            requested
+                .timeout
+                .unwrap_or(DEFAULT_TIMEOUT)
+                .min(MAX_TIMEOUT),
+        ),
+        memory_limit: Some(
+            requested
+                .memory_limit
+                .map_or(MAX_MEMORY_LIMIT, |limit| limit.min(MAX_MEMORY_LIMIT)),
+        ),
+    }
+}
+
+/// Outputs of VM tracers required to run a JS tracer for a single transaction.
+#[derive(Debug, Default)]
+pub(crate) struct JsTracerInputs {
+    calls: Arc<OnceCell<Vec<Call>>>,
+    struct_logs: Arc<OnceCell<Vec<StructLog>>>,
+}
+
+impl JsTracerInputs {
+    /// Returns VM tracers collecting the inputs. Limits exceeded by the tracers are recorded in `limiter_state`.
+    pub fn api_tracers(&self, limiter_state: Arc<TraceLimiterState>) -> Vec<ApiTracer> {
+        let struct_log_config = StructLogConfig {
+            disable_stack: false,
+            enable_memory: true,
+            limit: 0,
+        };
+        vec![
+            ApiTracer::CallTracer(self.calls.clone(), Some(limiter_state.clone())),
+            ApiTracer::StructLogTracer(
+                struct_log_config,
+                STRUCT_LOG_LIMITS,
+                self.struct_logs.clone(),
+                limiter_state,
+            ),
+        ]
+    }
+}
+
+/// Context passed to the `result()` callback of a JS tracer.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct JsTracerContext {
+    r#type: &'static str,
+    from: Address,
+    to: Address,
+    input: Bytes,
+    gas: u32,
+    gas_used: u32,
+    value: U256,
+    output: Bytes,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    block: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tx_hash: Option<H256>,
+}
+
+impl JsTracerContext {
+    pub fn new(tx: &Transaction, result: &VmExecutionResultAndLogs) -> Self {
+        let to = tx.recipient_account();
+        let (output, error) = match &result.result {
+            ExecutionResult::Success { output } => (output.clone(), None),
+            ExecutionResult::Revert { output } => (output.encoded_data(), Some(output.to_string())),
+            ExecutionResult::Halt { reason } => (vec![], Some(reason.to_string())),
+        };
+        Self {
+            r#type: if to == CONTRACT_DEPLOYER_ADDRESS {
+                "CREATE"
+            } else {
+                "CALL"
+            },
+            from: tx.initiator_account(),
+            to,
+            input: tx.execute.calldata.clone().into(),
+            gas: tx.gas_limit().as_u32(),
+            gas_used: result.statistics.gas_used,
+            value: tx.execute.value,
+            output: output.into(),
+            error,
+            block: None,
+            tx_hash: None,
+        }
+    }
+
+    /// Sets the location of a transaction included into a miniblock.
+    pub fn with_location(mut self, miniblock_number: MiniblockNumber, tx_hash: H256) -> Self {
+        self.block = Some(miniblock_number.0);
+        self.tx_hash = Some(tx_hash);
+        self
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsFrame {
+    r#type: &'static str,
+    from: Address,
+    to: Address,
+    input: Bytes,
+    gas: u32,
+    value: U256,
+}
+
+impl JsFrame {
+    fn new(call: Option<&Call>) -> Self {
+        let call = call.cloned().unwrap_or_default();
+        Self {
+            r#type: match call.r#type {
+                CallType::Call(FarCallOpcode::Delegate) => "DELEGATECALL",
+                CallType::Create => "CREATE",
+                CallType::Call(_) | CallType::NearCall => "CALL",
+            },
+            from: call.from,
+            to: call.to,
+            input: call.input.into(),
+            gas: call.gas,
+            value: call.value,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsFrameResult {
+    gas_used: u32,
+    output: Bytes,
+    error: Option<String>,
+}
+
+impl JsFrameResult {
+    fn new(call: Option<&Call>) -> Self {
+        let call = call.cloned().unwrap_or_default();
+        Self {
+            gas_used: call.gas_used,
+            output: call.output.into(),
+            error: call.error.or(call.revert_reason),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+enum JsTraceEvent<'a> {
+    #[serde(rename_all = "camelCase")]
+    Enter {
+        frame: JsFrame,
+        top_level: bool,
+    },
+    #[serde(rename_all = "camelCase")]
+    Exit {
+        result: JsFrameResult,
+        top_level: bool,
+    },
+    Step {
+        log: &'a StructLog,
+        fault: bool,
+    },
+}
+
+/// Call stack frame reconstructed from struct logs.
+#[derive(Debug)]
+enum Frame<'a> {
+    Near,
+    Far(Option<&'a Call>),
+}
+
+fn flatten_calls<'a>(calls: &'a [Call], output: &mut Vec<&'a Call>) {
+    for call in calls {
+        output.push(call);
+        flatten_calls(&call.calls, output);
+    }
+}
+
+/// Merges struct logs and the call tree into a single sequence of events. Far calls are matched with the call tree
+/// in the order of their execution; frames started by the bootloader are detected as gaps in the struct logs
+/// (bootloader steps are not recorded).
+fn collect_events<'a>(struct_logs: &'a [StructLog], calls: &'a [Call]) -> Vec<JsTraceEvent<'a>> {
+    let mut ordered_calls = vec![];
+    flatten_calls(calls, &mut ordered_calls);
+    let mut ordered_calls = ordered_calls.into_iter();
+
+    let mut events = vec![];
+    let mut frames = vec![];
+    for log in struct_logs {
+        if frames.is_empty() {
+            let call = ordered_calls.next();
+            events.push(JsTraceEvent::Enter {
+                frame: JsFrame::new(call),
+                top_level: true,
+            });
+            frames.push(Frame::Far(call));
+        }
+
+        let op = log.op.as_str();
+        events.push(JsTraceEvent::Step {
+            log,
+            fault: op == "Ret(Panic)",
+        });
+        if op.starts_with("FarCall") {
+            let call = ordered_calls.next();
+            events.push(JsTraceEvent::Enter {
+                frame: JsFrame::new(call),
+                top_level: false,
+            });
+            frames.push(Frame::Far(call));
+        } else if op.starts_with("NearCall") {
+            frames.push(Frame::Near);
+        } else if op.starts_with("Ret") {
+            if let Some(Frame::Far(call)) = frames.pop() {
+                events.push(JsTraceEvent::Exit {
+                    result: JsFrameResult::new(call),
+                    top_level: frames.is_empty(),
+                });
+            }
+        }
+    }
+
+    // Close frames left after the execution was halted.
+    while let Some(frame) = frames.pop() {
+        if let Frame::Far(call) = frame {
+            events.push(JsTraceEvent::Exit {
+                result: JsFrameResult::new(call),
+                top_level: frames.is_empty(),
+            });
+        }
+    }
+    events
+}
+
+/// Returns a native function throwing an error once `deadline` has passed. The driver calls it before each tracer callback.
+fn deadline_check(deadline: Option<Instant>) -> NativeFunction {
+    NativeFunction::from_copy_closure(move |_, _, _| {
+        if deadline.map_or(false, |deadline| Instant::now() >= deadline) {
+            Err(JsNativeError::error()
+                .with_message("tracer execution timed out")
+                .into())
+        } else {
+            Ok(JsValue::undefined())
+        }
+    })
+}
+
+fn execute_js(
+    code: &str,
+    events: &str,
+    tracer_context: &str,
+    deadline: Option<Instant>,
+) -> anyhow::Result<serde_json::Value> {
+    let mut context = Context::default();
+    context
+        .runtime_limits_mut()
+        .set_loop_iteration_limit(LOOP_ITERATION_LIMIT);
+    context
+        .runtime_limits_mut()
+        .set_recursion_limit(RECURSION_LIMIT);
+    context
+        .register_global_callable("__checkDeadline", 0, deadline_check(deadline))
+        .map_err(|err| anyhow::anyhow!("{err}"))
+        .context("failed initializing tracer driver")?;
+
+    let mut eval = |source: &str| {
+        context
+            .eval(Source::from_bytes(source))
+            .map_err(|err| anyhow::anyhow!("{err}"))
+    };
+    eval(DRIVER_CODE).context("failed initializing tracer driver")?;
+    eval(&format!("const __tracer = ({code}\n);")).context("failed compiling tracer")?;
+    let output = eval(&format!(
+        "__runTracer(__tracer, {events}, {tracer_context})"
+    ))?;
+
+    let Some(output) = output.as_string() else {
+        // `JSON.stringify()` returns `undefined` for values not representable in JSON.
+        return Ok(serde_json::Value::Null);
+    };
+    anyhow::ensure!(
+        output.len() <= MAX_OUTPUT_LEN,
+        "tracer output exceeds {MAX_OUTPUT_LEN} characters"
+    );
+    let output = output
+        .to_std_string()
+        .context("tracer output is not valid UTF-16")?;
+    serde_json::from_str(&output).context("tracer output is not valid JSON")
+}
+
+/// Runs a JS tracer for a single transaction. Returns the value produced by the tracer `result()` callback.
+/// If `timeout` is specified, the tracer fails if it doesn't produce the result in time; the interpreter
+/// is interrupted on the next tracer callback.
+pub(crate) async fn run_js_tracer(
+    code: String,
+    inputs: JsTracerInputs,
+    tracer_context: JsTracerContext,
+    timeout: Option<Duration>,
+) -> Result<serde_json::Value, Web3Error> {
+    // VM tracers are dropped together with the VM, so we hold the only copies of the `Arc`s.
+    let calls = Arc::try_unwrap(inputs.calls)
+        .unwrap()
+        .take()
+        .unwrap_or_default();
+    let struct_logs = Arc::try_unwrap(inputs.struct_logs)
+        .unwrap()
+        .take()
+        .unwrap_or_default();
+
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let task = tokio::task::spawn_blocking(move || {
+        let events = collect_events(&struct_logs, &calls);
+        // Serialization of these types cannot fail.
+        let events = serde_json::to_string(&events).unwrap();
+        let tracer_context = serde_json::to_string(&tracer_context).unwrap();
+        execute_js(&code, &events, &tracer_context, deadline)
+    });
+    let output = match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, task)
+            .await
+            .map_err(|_| Web3Error::TraceLimitExceeded(TraceLimitExceeded::Timeout(timeout)))?,
+        None => task.await,
+    };
+    output.unwrap().map_err(|err| match (timeout, deadline) {
+        (Some(timeout), Some(deadline)) if Instant::now() >= deadline => {
+            Web3Error::TraceLimitExceeded(TraceLimitExceeded::Timeout(timeout))
+        }
+        _ => Web3Error::InvalidTracerConfig(format!("JavaScript tracer failed: {err:#}")),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn struct_log(op: &str, depth: usize) -> StructLog {
+        StructLog {
+            address: Address::repeat_byte(1),
+            pc: 0,
+            op: op.to_owned(),
+            gas: 1_000,
+            gas_cost: 1,
+            depth,
+            stack: Some(vec![U256::from(42)]),
+            memory: None,
+        }
+    }
+
+    fn call(to: Address, calls: Vec<Call>) -> Call {
+        Call {
+            r#type: CallType::Call(FarCallOpcode::Normal),
+            to,
+            calls,
+            ..Call::default()
+        }
+    }
+
+    #[test]
+    fn events_are_collected_in_execution_order() {
+        let struct_logs = [
+            struct_log("Add", 1),
+            struct_log("NearCall(NearCallOpcode)", 1),
+            struct_log("FarCall(Normal)", 2),
+            struct_log("Ret(Ok)", 3),
+            struct_log("Ret(Ok)", 2),
+            struct_log("Ret(Ok)", 1),
+            // Next frame started by the bootloader
+            struct_log("Ret(Revert)", 1),
+        ];
+        let calls = [
+            call(
+                Address::repeat_byte(1),
+                vec![call(Address::repeat_byte(2), vec![])],
+            ),
+            call(Address::repeat_byte(3), vec![]),
+        ];
+
+        let events = collect_events(&struct_logs, &calls);
+        let events: Vec<_> = events
+            .iter()
+            .map(|event| match event {
+                JsTraceEvent::Enter { frame, top_level } => {
+                    format!("enter({:?}, {top_level})", frame.to)
+                }
+                JsTraceEvent::Exit { top_level, .. } => format!("exit({top_level})"),
+                JsTraceEvent::Step { log, .. } => log.op.clone(),
+            })
+            .collect();
+        let expected_to = |byte| format!("{:?}", Address::repeat_byte(byte));
+        assert_eq!(
+            events,
+            [
+                format!("enter({}, true)", expected_to(1)),
+                "Add".to_owned(),
+                "NearCall(NearCallOpcode)".to_owned(),
+                "FarCall(Normal)".to_owned(),
+                format!("enter({}, false)", expected_to(2)),
+                "Ret(Ok)".to_owned(),
+                "exit(false)".to_owned(),
+                "Ret(Ok)".to_owned(),
+                "Ret(Ok)".to_owned(),
+                "exit(true)".to_owned(),
+                format!("enter({}, true)", expected_to(3)),
+                "Ret(Revert)".to_owned(),
+                "exit(true)".to_owned(),
+            ]
+        );
+    }
+
+    #[test]
+    fn executing_js_tracer() {
+        let struct_logs = [struct_log("Add", 1), struct_log("Ret(Panic)", 1)];
+        let calls = [call(Address::repeat_byte(1), vec![])];
+        let events = serde_json::to_string(&collect_events(&struct_logs, &calls)).unwrap();
+        let tracer_context = r#"{
+            "type": "CALL",
+            "from": "0x0101010101010101010101010101010101010101",
+            "to": "0x0202020202020202020202020202020202020202",
+            "input": "0x01",
+            "gas": 1000,
+            "gasUsed": 100,
+            "value": "0x0",
+            "output": "0x"
+        }"#;
+        let code = r#"{
+            steps: [],
+            faults: 0,
+            step: function(log) { this.steps.push([log.op.toString(), log.stack.peek(0)]); },
+            fault: function() { this.faults++; },
+            result: function(ctx) { return { steps: this.steps, faults: this.faults, input: toHex(ctx.input) }; }
+        }"#;
+
+        let output = execute_js(code, &events, tracer_context, None).unwrap();
+        assert_eq!(
+            output,
+            serde_json::json!({
+                "steps": [["Add", "42"], ["Ret(Panic)", "42"]],
+                "faults": 1,
+                "input": "0x01",
+            })
+        );
+
+        let err = execute_js("{ step: function() {} }", &events, tracer_context, None).unwrap_err();
+        assert!(err.to_string().contains("result()"), "{err}");
+
+        let deadline = Some(Instant::now());
+        let err = execute_js(code, &events, tracer_context, deadline).unwrap_err();
+        assert!(err.to_string().contains("timed out"), "{err}");
+    }
+
+    #[test]
+    fn js_tracer_limits_are_bounded() {
+        let limits = js_tracer_limits(TraceLimits::default());
+        assert_eq!(limits.timeout, Some(DEFAULT_TIMEOUT));
+        assert_eq!(limits.memory_limit, Some(MAX_MEMORY_LIMIT));
+
+        let limits = js_tracer_limits(TraceLimits {
+            timeout: Some(Duration::from_secs(3_600)),
+            memory_limit: Some(1_024),
+        });
+        assert_eq!(limits.timeout, Some(MAX_TIMEOUT));
+        assert_eq!(limits.memory_limit, Some(1_024));
+    }
+}
@@ -9,6 +9,7 @@ use vise::{
     Buckets, Counter, EncodeLabelSet, EncodeLabelValue, Family, Gauge, Histogram, LabeledFamily,
     LatencyObserver, Metrics, Unit,
 };
+use zksync_config::configs::api::SubscriptionBackpressurePolicy;
 use zksync_types::api;
 
 use super::{ApiTransport, TypedFilter};
@@ -125,6 +126,20 @@ impl BlockCallObserver<'_> {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "result", rename_all = "snake_case")]
+pub(in crate::api_server) enum CacheResult {
+    Hit,
+    Miss,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "pool", rename_all = "snake_case")]
+pub(super) enum ReadPoolLabel {
+    Main,
+    Replica,
+}
+
 /// General-purpose API server metrics.
 #[derive(Debug, Metrics)]
 #[metrics(prefix = "api")]
@@ -140,13 +155,30 @@ pub(super) struct ApiMetrics {
     /// Number of internal errors grouped by the Web3 method.
     #[metrics(labels = ["method"])]
     pub web3_internal_errors: LabeledFamily<&'static str, Counter>,
+    /// Number of proof requests rejected because the Merkle tree lags behind the state keeper too much,
+    /// grouped by the Web3 method.
+    #[metrics(labels = ["method"])]
+    pub tree_lag_rejections: LabeledFamily<&'static str, Counter>,
     /// Number of transaction submission errors for a specific submission error reason.
     #[metrics(labels = ["reason"])]
     pub submit_tx_error: LabeledFamily<&'static str, Counter>,
     #[metrics(buckets = Buckets::linear(0.0..=10.0, 1.0))]
     pub web3_in_flight_requests: Family<ApiTransportLabel, Histogram<usize>>,
+    /// Time spent draining in-flight requests on server shutdown.
+    #[metrics(buckets = Buckets::LATENCIES)]
+    pub shutdown_drain_latency: Family<ApiTransportLabel, Histogram<Duration>>,
+    /// Number of in-flight requests remaining after the shutdown draining timeout has elapsed.
+    pub shutdown_abandoned_requests: Family<ApiTransportLabel, Counter>,
     /// Number of currently open WebSocket sessions.
     pub ws_open_sessions: Gauge,
+    /// Number of HTTP requests to authenticated namespaces rejected because of missing or invalid credentials.
+    pub unauthorized_requests: Counter,
+    /// Number of lookups in the `eth_call` result cache grouped by the lookup result.
+    pub eth_call_cache: Family<CacheResult, Counter>,
+    /// Number of read-only queries grouped by the connection pool they were routed to.
+    pub read_queries: Family<ReadPoolLabel, Counter>,
+    /// Replication lag of the read replica DB in miniblocks.
+    pub read_replica_lag: Gauge<u64>,
 }
 
 impl ApiMetrics {
@@ -178,7 +210,34 @@ pub(super) static API_METRICS: vise::Global<ApiMetrics> = vise::Global::new();
 pub(super) enum SubscriptionType {
     Blocks,
     Txs,
+    FullTxs,
     Logs,
+    L1Batches,
+}
+
+/// Action taken on a subscription queue overflow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue)]
+#[metrics(rename_all = "snake_case")]
+pub(super) enum QueueOverflowOutcome {
+    Disconnected,
+    DroppedOldest,
+    DroppedNewest,
+}
+
+impl From<SubscriptionBackpressurePolicy> for QueueOverflowOutcome {
+    fn from(policy: SubscriptionBackpressurePolicy) -> Self {
+        match policy {
+            SubscriptionBackpressurePolicy::Disconnect => Self::Disconnected,
+            SubscriptionBackpressurePolicy::DropOldest => Self::DroppedOldest,
+            SubscriptionBackpressurePolicy::DropNewest => Self::DroppedNewest,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelSet)]
+pub(super) struct QueueOverflowLabels {
+    pub subscription_type: SubscriptionType,
+    pub outcome: QueueOverflowOutcome,
 }
 
 #[derive(Debug, Metrics)]
@@ -187,7 +246,7 @@ pub(super) struct PubSubMetrics {
     /// Latency to load new events from Postgres before broadcasting them to subscribers.
     #[metrics(buckets = Buckets::LATENCIES)]
     pub db_poll_latency: Family<SubscriptionType, Histogram<Duration>>,
-    /// Latency to send an atomic batch of events to a single subscriber.
+    /// Latency to send a single event to a subscriber.
     #[metrics(buckets = Buckets::LATENCIES)]
     pub notify_subscribers_latency: Family<SubscriptionType, Histogram<Duration>>,
     /// Total number of events sent to all subscribers of a certain type.
@@ -203,13 +262,77 @@ pub(super) struct PubSubMetrics {
     /// Number of skipped broadcast messages.
     #[metrics(buckets = Buckets::exponential(1.0..=128.0, 2.0))]
     pub skipped_broadcast_messages: Family<SubscriptionType, Histogram<u64>>,
-    /// Number of subscribers dropped because of a send timeout.
-    pub subscriber_send_timeouts: Family<SubscriptionType, Counter>,
+    /// Total number of logs backfilled for resumed `logs` subscriptions.
+    pub backfilled_logs: Counter,
+    /// Latency to backfill logs for a resumed `logs` subscription.
+    #[metrics(buckets = Buckets::LATENCIES)]
+    pub logs_backfill_latency: Histogram<Duration>,
+    /// Number of subscription queue overflows split by the action taken.
+    pub queue_overflows: Family<QueueOverflowLabels, Counter>,
 }
 
 #[vise::register]
 pub(super) static PUB_SUB_METRICS: vise::Global<PubSubMetrics> = vise::Global::new();
 
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "api_grpc")]
+pub(super) struct GrpcMetrics {
+    /// Number of currently active gRPC streams split by the notification type.
+    pub active_streams: Family<SubscriptionType, Gauge>,
+    /// Number of gRPC streams terminated because the client could not keep up with notifications.
+    pub lagged_streams: Family<SubscriptionType, Counter>,
+    /// Number of gRPC streams rejected because the concurrent stream limit was reached.
+    pub rejected_streams: Family<SubscriptionType, Counter>,
+}
+
+#[vise::register]
+pub(super) static GRPC_METRICS: vise::Global<GrpcMetrics> = vise::Global::new();
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "api_sse")]
+pub(super) struct SseMetrics {
+    /// Number of currently active server-sent event streams.
+    pub active_streams: Gauge,
+    /// Number of server-sent event streams terminated because the client could not keep up with events.
+    pub lagged_streams: Counter,
+}
+
+#[vise::register]
+pub(super) static SSE_METRICS: vise::Global<SseMetrics> = vise::Global::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue)]
+#[metrics(rename_all = "snake_case")]
+pub(super) enum RateLimitClientKind {
+    Ip,
+    ApiKey,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue)]
+#[metrics(rename_all = "snake_case")]
+pub(super) enum RateLimitKind {
+    Requests,
+    ComputeUnits,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelSet)]
+pub(super) struct RateLimitLabels {
+    pub scheme: ApiTransportLabel,
+    pub client: RateLimitClientKind,
+    pub limit: RateLimitKind,
+}
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "api_rate_limit")]
+pub(super) struct RateLimitMetrics {
+    /// Number of HTTP requests rejected by the rate limiter.
+    pub rejected_requests: Family<RateLimitLabels, Counter>,
+    /// Compute units charged by the rate limiter for accepted requests.
+    pub compute_units: Family<ApiTransportLabel, Counter>,
+}
+
+#[vise::register]
+pub(super) static RATE_LIMIT_METRICS: vise::Global<RateLimitMetrics> = vise::Global::new();
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
 #[metrics(label = "type", rename_all = "snake_case")]
 pub(super) enum FilterType {
@@ -1,61 +1,99 @@
-use std::{net::SocketAddr, num::NonZeroU32, sync::Arc, time::Duration};
+use std::{
+    net::SocketAddr,
+    num::{NonZeroU32, NonZeroUsize},
+    sync::Arc,
+    time::Duration,
+};
 
 use anyhow::Context as _;
 use chrono::NaiveDateTime;
 use futures::future;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tokio::{
-    sync::{mpsc, oneshot, watch, Mutex},
+    net::TcpListener,
+    sync::{mpsc, oneshot, watch},
     task::JoinHandle,
 };
 use tower_http::{cors::CorsLayer, metrics::InFlightRequestsLayer};
-use zksync_dal::{ConnectionPool, StorageProcessor};
+use zksync_config::configs::api::SubscriptionBackpressurePolicy;
+use zksync_dal::ConnectionPool;
 use zksync_health_check::{HealthStatus, HealthUpdater, ReactiveHealthCheck};
 use zksync_types::{api, MiniblockNumber};
+use zksync_utils::bytecode::validate_bytecode;
 use zksync_web3_decl::{
     error::Web3Error,
     jsonrpsee::{
-        server::{BatchRequestConfig, RpcServiceBuilder, ServerBuilder},
+        server::{BatchRequestConfig, Methods, RpcServiceBuilder, ServerBuilder},
         RpcModule,
     },
     namespaces::{
-        DebugNamespaceServer, EnNamespaceServer, EthNamespaceServer, EthPubSubServer,
-        NetNamespaceServer, SnapshotsNamespaceServer, Web3NamespaceServer, ZksNamespaceServer,
+        AdminNamespaceServer, DebugNamespaceServer, EnNamespaceServer, EthNamespaceServer,
+        EthPubSubServer, NetNamespaceServer, SnapshotsNamespaceServer, TraceNamespaceServer,
+        TxpoolNamespaceServer, Web3NamespaceServer, ZksNamespaceServer, ZksPubSubServer,
     },
     types::Filter,
 };
 
 use self::{
-    backend_jsonrpsee::internal_error,
+    auth::{ApiAuth, AuthLayer},
+    batch::{BatchConfig, BatchLayer},
+    compression::ResponseCompressionLayer,
     metrics::API_METRICS,
     namespaces::{
-        DebugNamespace, EnNamespace, EthNamespace, NetNamespace, SnapshotsNamespace, Web3Namespace,
-        ZksNamespace,
+        AdminNamespace, DebugNamespace, EnNamespace, EthNamespace, NetNamespace,
+        SnapshotsNamespace, TraceNamespace, TxpoolNamespace, Web3Namespace, ZksNamespace,
+    },
+    pubsub::{EthSubscribe, EthSubscriptionIdProvider, PubSubEvent, SubscriptionQueueConfig},
+    rate_limit::{RateLimitLayer, RateLimits, API_KEY_HEADER},
+    sse::SseLayer,
+    state::{
+        BlockStartInfo, CallResultCache, InstalledFilters, InternalApiConfig, PriorityFeeOracle,
+        ReadReplica, RpcState, SealedMiniblockNumber,
     },
-    pubsub::{EthSubscribe, EthSubscriptionIdProvider, PubSubEvent},
-    state::{Filters, InternalApiConfig, RpcState, SealedMiniblockNumber},
+    vm_client::VmClientLayer,
 };
 use crate::{
     api_server::{
-        execution_sandbox::VmConcurrencyBarrier, tree::TreeApiHttpClient, tx_sender::TxSender,
-        web3::backend_jsonrpsee::batch_limiter_middleware::LimitMiddleware,
+        execution_sandbox::VmConcurrencyBarrier,
+        tree::TreeApiHttpClient,
+        tx_sender::TxSender,
+        web3::backend_jsonrpsee::{
+            batch_limiter_middleware::LimitMiddleware,
+            execution_pool_middleware::{ExecutionPoolMiddleware, ExecutionPools},
+            method_filter_middleware::{MethodFilter, MethodFilterMiddleware},
+        },
     },
+    eth_sender::AggregationPolicyHandle,
+    fee_model::FeeModelSettingsHandle,
+    l1_gas_price::L1GasPriceProvider,
+    state_keeper::SealingControl,
     sync_layer::SyncState,
 };
 
+pub mod auth;
 pub mod backend_jsonrpsee;
-mod metrics;
+mod batch;
+mod compression;
+pub mod grpc;
+mod js_tracer;
+pub(super) mod metrics;
 pub mod namespaces;
+mod peer_addr;
 mod pubsub;
+pub mod rate_limit;
+mod sse;
 pub mod state;
 #[cfg(test)]
 pub(crate) mod tests;
+mod vm_client;
 
 /// Timeout for graceful shutdown logic within API servers.
 const GRACEFUL_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+/// Default timeout for draining in-flight requests on API server shutdown.
+const DEFAULT_DRAINING_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// Represents all kinds of `Filter`.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) enum TypedFilter {
     // Events from some block with additional filters
     Events(Filter, MiniblockNumber),
@@ -82,6 +120,9 @@ pub enum Namespace {
     En,
     Pubsub,
     Snapshots,
+    Trace,
+    Admin,
+    Txpool,
 }
 
 impl Namespace {
@@ -93,6 +134,24 @@ impl Namespace {
         Self::En,
         Self::Pubsub,
     ];
+
+    /// Returns the prefix of method names in this namespace (e.g., `eth` for `eth_call`), or `None`
+    /// for the pubsub namespace, which doesn't have a dedicated prefix.
+    fn method_prefix(&self) -> Option<&'static str> {
+        Some(match self {
+            Self::Eth => "eth",
+            Self::Net => "net",
+            Self::Web3 => "web3",
+            Self::Debug => "debug",
+            Self::Zks => "zks",
+            Self::En => "en",
+            Self::Pubsub => return None,
+            Self::Snapshots => "snapshots",
+            Self::Trace => "trace",
+            Self::Admin => "admin",
+            Self::Txpool => "txpool",
+        })
+    }
 }
 
 /// Handles to the initialized API server.
@@ -108,12 +167,30 @@ pub struct ApiServerHandles {
 struct OptionalApiParams {
     sync_state: Option<SyncState>,
     filters_limit: Option<usize>,
+    shared_filters_pool: Option<ConnectionPool>,
+    read_replica: Option<(ConnectionPool, u32)>,
     subscriptions_limit: Option<usize>,
+    max_subscriptions_per_connection: Option<u32>,
+    subscription_queue_config: SubscriptionQueueConfig,
     batch_request_size_limit: Option<usize>,
     response_body_size_limit: Option<usize>,
+    batch_response_size_limit: Option<usize>,
+    batch_request_parallelism: Option<NonZeroUsize>,
+    heavy_calls_concurrency_limit: Option<NonZeroUsize>,
+    light_calls_concurrency_limit: Option<NonZeroUsize>,
+    response_compression_min_size: Option<u16>,
+    draining_timeout: Option<Duration>,
     websocket_requests_per_minute_limit: Option<NonZeroU32>,
+    rate_limits: RateLimits,
+    auth: Option<ApiAuth>,
+    method_filter: MethodFilter,
+    sealing_control: Option<SealingControl>,
+    l1_gas_price_provider: Option<Arc<dyn L1GasPriceProvider>>,
+    fee_model_settings: Option<FeeModelSettingsHandle>,
+    aggregation_policy: Option<AggregationPolicyHandle>,
     tree_api_url: Option<String>,
     pub_sub_events_sender: Option<mpsc::UnboundedSender<PubSubEvent>>,
+    sse_enabled: bool,
 }
 
 /// Full API server parameters.
@@ -192,11 +269,37 @@ impl ApiBuilder {
         self
     }
 
+    /// Stores installed filters in Postgres accessed via the provided pool instead of the server memory,
+    /// so that filters can be shared among API server replicas using the same database.
+    pub fn with_shared_filters(mut self, pool: ConnectionPool) -> Self {
+        self.optional.shared_filters_pool = Some(pool);
+        self
+    }
+
     pub fn with_subscriptions_limit(mut self, subscriptions_limit: usize) -> Self {
         self.optional.subscriptions_limit = Some(subscriptions_limit);
         self
     }
 
+    pub fn with_max_subscriptions_per_connection(
+        mut self,
+        max_subscriptions_per_connection: u32,
+    ) -> Self {
+        self.optional.max_subscriptions_per_connection = Some(max_subscriptions_per_connection);
+        self
+    }
+
+    /// Configures bounded notification queues for WebSocket subscriptions and the action taken
+    /// if a queue overflows. If not called, subscriptions are disconnected after 1,024 queued notifications.
+    pub fn with_subscription_queue(
+        mut self,
+        capacity: usize,
+        policy: SubscriptionBackpressurePolicy,
+    ) -> Self {
+        self.optional.subscription_queue_config = SubscriptionQueueConfig { capacity, policy };
+        self
+    }
+
     pub fn with_batch_request_size_limit(mut self, batch_request_size_limit: usize) -> Self {
         self.optional.batch_request_size_limit = Some(batch_request_size_limit);
         self
@@ -207,6 +310,55 @@ impl ApiBuilder {
         self
     }
 
+    /// Limits the cumulative size of responses to calls in a batch HTTP request. If not called, batch responses
+    /// are limited by the response body size limit.
+    pub fn with_batch_response_size_limit(mut self, batch_response_size_limit: usize) -> Self {
+        self.optional.batch_response_size_limit = Some(batch_response_size_limit);
+        self
+    }
+
+    /// Executes up to the specified number of calls in a batch HTTP request concurrently. If not called,
+    /// calls in a batch are executed one by one.
+    pub fn with_batch_request_parallelism(mut self, parallelism: NonZeroUsize) -> Self {
+        self.optional.batch_request_parallelism = Some(parallelism);
+        self
+    }
+
+    /// Executes heavy calls (`debug_*` / `trace_*` methods and gas estimation) in a dedicated pool with
+    /// the specified max concurrency, separately from light calls. Light calls are executed with
+    /// the specified max concurrency, or without limits if it's not set.
+    pub fn with_execution_pools(
+        mut self,
+        heavy_calls_limit: NonZeroUsize,
+        light_calls_limit: Option<NonZeroUsize>,
+    ) -> Self {
+        self.optional.heavy_calls_concurrency_limit = Some(heavy_calls_limit);
+        self.optional.light_calls_concurrency_limit = light_calls_limit;
+        self
+    }
+
+    /// Enables routing of read-only queries (blocks, receipts, logs and traces) to the read replica
+    /// of the main DB. Queries for the latest block are routed to the replica only if it lags behind
+    /// the main DB by no more than `max_lag` miniblocks.
+    pub fn with_read_replica(mut self, pool: ConnectionPool, max_lag: u32) -> Self {
+        self.optional.read_replica = Some((pool, max_lag));
+        self
+    }
+
+    /// Sets the timeout for draining in-flight requests on server shutdown. If not called, in-flight requests
+    /// are drained for 10 seconds.
+    pub fn with_draining_timeout(mut self, timeout: Duration) -> Self {
+        self.optional.draining_timeout = Some(timeout);
+        self
+    }
+
+    /// Enables compression of HTTP responses larger than the specified size (in bytes). Only has an effect
+    /// for the HTTP server.
+    pub fn with_response_compression(mut self, min_size: u16) -> Self {
+        self.optional.response_compression_min_size = Some(min_size);
+        self
+    }
+
     pub fn with_websocket_requests_per_minute_limit(
         mut self,
         websocket_requests_per_minute_limit: NonZeroU32,
@@ -216,6 +368,53 @@ impl ApiBuilder {
         self
     }
 
+    /// Configures per-IP and per-API-key rate limits for HTTP requests. For the WebSocket server, the limits
+    /// only apply to new connections.
+    pub fn with_rate_limits(mut self, rate_limits: RateLimits) -> Self {
+        self.optional.rate_limits = rate_limits;
+        self
+    }
+
+    /// Requires authentication for the configured namespaces. For the WebSocket server, these namespaces
+    /// are disabled since WebSocket calls cannot be authenticated.
+    pub fn with_auth(mut self, auth: ApiAuth) -> Self {
+        self.optional.auth = Some(auth);
+        self
+    }
+
+    /// Configures method-level rules rejecting calls to some methods of the enabled namespaces.
+    pub fn with_method_filter(mut self, method_filter: MethodFilter) -> Self {
+        self.optional.method_filter = method_filter;
+        self
+    }
+
+    /// Allows the `admin` namespace to stop and resume sealing. Should only be called if the state keeper
+    /// runs in the same process as the API server.
+    pub fn with_sealing_control(mut self, sealing_control: SealingControl) -> Self {
+        self.optional.sealing_control = Some(sealing_control);
+        self
+    }
+
+    /// Allows the `admin` namespace to report internals of the L1 gas price oracle.
+    pub fn with_l1_gas_price_provider(mut self, provider: Arc<dyn L1GasPriceProvider>) -> Self {
+        self.optional.l1_gas_price_provider = Some(provider);
+        self
+    }
+
+    /// Allows the `admin` namespace to change fee model settings. Should only be called if the state keeper
+    /// runs in the same process as the API server.
+    pub fn with_fee_model_settings(mut self, settings: FeeModelSettingsHandle) -> Self {
+        self.optional.fee_model_settings = Some(settings);
+        self
+    }
+
+    /// Allows the `admin` namespace to change the L1 batch aggregation policy. Should only be called
+    /// if the ETH tx aggregator runs in the same process as the API server.
+    pub fn with_aggregation_policy(mut self, policy: AggregationPolicyHandle) -> Self {
+        self.optional.aggregation_policy = Some(policy);
+        self
+    }
+
     pub fn with_sync_state(mut self, sync_state: SyncState) -> Self {
         self.optional.sync_state = Some(sync_state);
         self
@@ -231,6 +430,13 @@ impl ApiBuilder {
         self
     }
 
+    /// Enables the server-sent events endpoint with new miniblock headers and sync status. Only has an effect
+    /// for the HTTP server.
+    pub fn enable_sse_endpoint(mut self) -> Self {
+        self.optional.sse_enabled = true;
+        self
+    }
+
     pub fn with_tree_api(mut self, tree_api_url: Option<String>) -> Self {
         self.optional.tree_api_url = tree_api_url;
         self
@@ -283,14 +489,41 @@ impl FullApiParams {
             SealedMiniblockNumber::new(self.last_miniblock_pool, SEALED_MINIBLOCK_UPDATE_INTERVAL);
         // The update tasks takes care of its termination, so we don't need to retain its handle.
         tokio::spawn(update_task);
+        let read_replica = self.optional.read_replica.map(|(pool, max_lag)| {
+            let (replica, update_task) = ReadReplica::new(
+                pool,
+                max_lag,
+                last_sealed_miniblock.clone(),
+                SEALED_MINIBLOCK_UPDATE_INTERVAL,
+            );
+            tokio::spawn(update_task);
+            replica
+        });
+        let priority_fee_oracle =
+            PriorityFeeOracle::new(self.config.max_priority_fee_smoothing_factor);
+        let call_result_cache = CallResultCache::new(self.config.eth_call_cache_capacity);
+        let installed_filters = match self.optional.shared_filters_pool {
+            Some(pool) => {
+                let (filters, pruning_task) =
+                    InstalledFilters::shared(pool, self.optional.filters_limit);
+                // The pruning task takes care of its termination, so we don't need to retain its handle.
+                tokio::spawn(pruning_task);
+                filters
+            }
+            None => InstalledFilters::local(self.optional.filters_limit),
+        };
 
         RpcState {
-            installed_filters: Arc::new(Mutex::new(Filters::new(self.optional.filters_limit))),
+            installed_filters,
             connection_pool: self.pool,
             tx_sender: self.tx_sender,
             sync_state: self.optional.sync_state,
             api_config: self.config,
             last_sealed_miniblock,
+            priority_fee_oracle,
+            call_result_cache,
+            read_replica,
+            start_info: BlockStartInfo::default(),
             tree_api: self
                 .optional
                 .tree_api_url
@@ -301,6 +534,10 @@ impl FullApiParams {
     async fn build_rpc_module(self, pubsub: Option<EthSubscribe>) -> RpcModule<()> {
         let namespaces = self.namespaces.clone();
         let zksync_network_id = self.config.l2_chain_id;
+        let sealing_control = self.optional.sealing_control.clone();
+        let l1_gas_price_provider = self.optional.l1_gas_price_provider.clone();
+        let fee_model_settings = self.optional.fee_model_settings.clone();
+        let aggregation_policy = self.optional.aggregation_policy.clone();
         let rpc_state = self.build_rpc_state();
 
         // Collect all the methods into a single RPC module.
@@ -308,6 +545,11 @@ impl FullApiParams {
         if let Some(pubsub) = pubsub {
             rpc.merge(pubsub.into_rpc())
                 .expect("Can't merge eth pubsub namespace");
+            if namespaces.contains(&Namespace::Zks) {
+                let zks_pubsub = ZksNamespace::new(rpc_state.clone());
+                rpc.merge(ZksPubSubServer::into_rpc(zks_pubsub))
+                    .expect("Can't merge zks pubsub namespace");
+            }
         }
 
         if namespaces.contains(&Namespace::Eth) {
@@ -323,7 +565,8 @@ impl FullApiParams {
                 .expect("Can't merge web3 namespace");
         }
         if namespaces.contains(&Namespace::Zks) {
-            rpc.merge(ZksNamespace::new(rpc_state.clone()).into_rpc())
+            let zks = ZksNamespace::new(rpc_state.clone());
+            rpc.merge(ZksNamespaceServer::into_rpc(zks))
                 .expect("Can't merge zks namespace");
         }
         if namespaces.contains(&Namespace::En) {
@@ -334,15 +577,30 @@ impl FullApiParams {
             rpc.merge(DebugNamespace::new(rpc_state.clone()).await.into_rpc())
                 .expect("Can't merge debug namespace");
         }
+        if namespaces.contains(&Namespace::Trace) {
+            rpc.merge(TraceNamespace::new(rpc_state.clone()).await.into_rpc())
+                .expect("Can't merge trace namespace");
+        }
         if namespaces.contains(&Namespace::Snapshots) {
-            rpc.merge(SnapshotsNamespace::new(rpc_state).into_rpc())
+            rpc.merge(SnapshotsNamespace::new(rpc_state.clone()).into_rpc())
                 .expect("Can't merge snapshots namespace");
         }
+        if namespaces.contains(&Namespace::Txpool) {
+            rpc.merge(TxpoolNamespace::new(rpc_state.clone()).into_rpc())
+                .expect("Can't merge txpool namespace");
+        }
+        if namespaces.contains(&Namespace::Admin) {
+            let admin = AdminNamespace::new(rpc_state, sealing_control, l1_gas_price_provider)
+                .with_fee_model_settings(fee_model_settings)
+                .with_aggregation_policy(aggregation_policy);
+            rpc.merge(admin.into_rpc())
+                .expect("Can't merge admin namespace");
+        }
         rpc
     }
 
     async fn spawn_server(
-        self,
+        mut self,
         stop_receiver: watch::Receiver<bool>,
     ) -> anyhow::Result<ApiServerHandles> {
         if self.optional.filters_limit.is_none() {
@@ -355,6 +613,33 @@ impl FullApiParams {
             tracing::debug!("pubsub API is not supported for HTTP transport, ignoring");
         }
 
+        if self.namespaces.contains(&Namespace::Admin) {
+            let is_authenticated = self
+                .optional
+                .auth
+                .as_ref()
+                .map_or(false, |auth| auth.namespaces().contains(&Namespace::Admin));
+            anyhow::ensure!(
+                is_authenticated,
+                "admin namespace must be listed in authenticated namespaces"
+            );
+        }
+
+        if let (ApiTransport::WebSocket(_), Some(auth)) = (&self.transport, &self.optional.auth) {
+            let authenticated_namespaces = auth.namespaces();
+            if self
+                .namespaces
+                .iter()
+                .any(|namespace| authenticated_namespaces.contains(namespace))
+            {
+                tracing::info!(
+                    "Disabling namespaces {authenticated_namespaces:?} requiring authentication for WS transport"
+                );
+                self.namespaces
+                    .retain(|namespace| !authenticated_namespaces.contains(namespace));
+            }
+        }
+
         match (&self.transport, self.optional.subscriptions_limit) {
             (ApiTransport::WebSocket(_), None) => {
                 tracing::warn!(
@@ -385,6 +670,26 @@ impl FullApiParams {
         }
     }
 
+    /// Returns the batch config if batch requests should be executed by [`BatchLayer`] rather than `jsonrpsee`.
+    fn batch_config(&self) -> Option<BatchConfig> {
+        let optional = &self.optional;
+        if optional.batch_response_size_limit.is_none()
+            && optional.batch_request_parallelism.is_none()
+        {
+            return None;
+        }
+        Some(BatchConfig {
+            max_calls: optional.batch_request_size_limit,
+            max_response_size: optional
+                .batch_response_size_limit
+                .or(optional.response_body_size_limit)
+                .unwrap_or(usize::MAX),
+            parallelism: optional
+                .batch_request_parallelism
+                .unwrap_or(NonZeroUsize::MIN),
+        })
+    }
+
     async fn build_jsonrpsee(
         self,
         stop_receiver: watch::Receiver<bool>,
@@ -408,26 +713,86 @@ impl FullApiParams {
             .map_or(u32::MAX, |limit| limit as u32);
 
         let websocket_requests_per_minute_limit = self.optional.websocket_requests_per_minute_limit;
+        let draining_timeout = self
+            .optional
+            .draining_timeout
+            .unwrap_or(DEFAULT_DRAINING_TIMEOUT);
         let subscriptions_limit = self.optional.subscriptions_limit;
+        let max_subscriptions_per_connection = self.optional.max_subscriptions_per_connection;
 
         let mut tasks = vec![];
         let mut pubsub = None;
         if matches!(transport, ApiTransport::WebSocket(_))
             && self.namespaces.contains(&Namespace::Pubsub)
         {
-            let mut pub_sub = EthSubscribe::new();
+            let mut pub_sub =
+                EthSubscribe::new(self.pool.clone(), self.optional.subscription_queue_config);
             if let Some(sender) = &self.optional.pub_sub_events_sender {
                 pub_sub.set_events_sender(sender.clone());
             }
 
             tasks.extend(pub_sub.spawn_notifiers(
-                self.pool.clone(),
                 self.polling_interval,
+                self.config.l2_chain_id,
                 stop_receiver.clone(),
             ));
             pubsub = Some(pub_sub);
         }
 
+        let mut sse = None;
+        if matches!(transport, ApiTransport::Http(_)) && self.optional.sse_enabled {
+            let mut pub_sub =
+                EthSubscribe::new(self.pool.clone(), self.optional.subscription_queue_config);
+            if let Some(sender) = &self.optional.pub_sub_events_sender {
+                pub_sub.set_events_sender(sender.clone());
+            }
+            tasks.extend(pub_sub.spawn_notifiers(
+                self.polling_interval,
+                self.config.l2_chain_id,
+                stop_receiver.clone(),
+            ));
+            sse = Some(SseLayer::new(
+                pub_sub,
+                self.pool.clone(),
+                self.optional.sync_state.clone(),
+            ));
+        }
+
+        let rate_limits = &self.optional.rate_limits;
+        let rate_limit = rate_limits
+            .is_enabled()
+            .then(|| RateLimitLayer::new(rate_limits.clone(), (&transport).into()));
+        let method_filter = &self.optional.method_filter;
+        let method_filter = (!method_filter.is_empty()).then(|| Arc::new(method_filter.clone()));
+        let heavy_calls_limit = self.optional.heavy_calls_concurrency_limit;
+        let light_calls_limit = self.optional.light_calls_concurrency_limit;
+        let execution_pools =
+            heavy_calls_limit.map(|limit| Arc::new(ExecutionPools::new(limit, light_calls_limit)));
+        let auth = match transport {
+            ApiTransport::Http(_) => self.optional.auth.as_ref().map(AuthLayer::new),
+            ApiTransport::WebSocket(_) => None,
+        };
+        let batch = match transport {
+            ApiTransport::Http(_) => self.batch_config().map(BatchLayer::new),
+            ApiTransport::WebSocket(_) => None,
+        };
+        let compression = match transport {
+            ApiTransport::Http(_) => self
+                .optional
+                .response_compression_min_size
+                .map(ResponseCompressionLayer::new),
+            ApiTransport::WebSocket(_) => None,
+        };
+        let vm_client = match transport {
+            ApiTransport::Http(_) => {
+                let limiter = self.tx_sender.vm_concurrency_limiter();
+                limiter.has_per_client_limit().then(|| {
+                    VmClientLayer::new(rate_limits.api_keys.clone(), rate_limits.trusted_proxy_hops)
+                })
+            }
+            ApiTransport::WebSocket(_) => None,
+        };
+
         let rpc = self.build_rpc_module(pubsub).await;
         // Start the server in a separate tokio runtime from a dedicated thread.
         let (local_addr_sender, local_addr) = oneshot::channel();
@@ -438,10 +803,20 @@ impl FullApiParams {
             local_addr_sender,
             health_updater,
             vm_barrier,
+            rate_limit,
+            auth,
+            sse,
+            batch,
+            compression,
+            vm_client,
+            method_filter,
+            execution_pools,
             batch_request_config,
             response_body_size_limit,
             subscriptions_limit,
+            max_subscriptions_per_connection,
             websocket_requests_per_minute_limit,
+            draining_timeout,
         ));
 
         let local_addr = match local_addr.await {
@@ -471,10 +846,20 @@ impl FullApiParams {
         local_addr_sender: oneshot::Sender<SocketAddr>,
         health_updater: HealthUpdater,
         vm_barrier: VmConcurrencyBarrier,
+        rate_limit: Option<RateLimitLayer>,
+        auth: Option<AuthLayer>,
+        sse: Option<SseLayer>,
+        batch: Option<BatchLayer>,
+        compression: Option<ResponseCompressionLayer>,
+        vm_client: Option<VmClientLayer>,
+        method_filter: Option<Arc<MethodFilter>>,
+        execution_pools: Option<Arc<ExecutionPools>>,
         batch_request_config: BatchRequestConfig,
         response_body_size_limit: u32,
         subscriptions_limit: Option<usize>,
+        max_subscriptions_per_connection: Option<u32>,
         websocket_requests_per_minute_limit: Option<NonZeroU32>,
+        draining_timeout: Duration,
     ) -> anyhow::Result<()> {
         let (transport_str, is_http, addr) = match transport {
             ApiTransport::Http(addr) => ("HTTP", true, addr),
@@ -483,16 +868,34 @@ impl FullApiParams {
         let transport_label = (&transport).into();
 
         // Setup CORS.
+        let sse_enabled = sse.is_some();
+        let rate_limit_enabled = rate_limit.is_some();
+        let auth_enabled = auth.is_some();
         let cors = is_http.then(|| {
+            // Allow `POST` when accessing the resource, and `GET` for the server-sent events endpoint
+            let methods = if sse_enabled {
+                vec![reqwest::Method::POST, reqwest::Method::GET]
+            } else {
+                vec![reqwest::Method::POST]
+            };
+            // Allow the API key header if it's used by the rate limiter, and the authorization header
+            // if some namespaces require authentication
+            let mut headers = vec![reqwest::header::CONTENT_TYPE];
+            if rate_limit_enabled {
+                headers.push(reqwest::header::HeaderName::from_static(API_KEY_HEADER));
+            }
+            if auth_enabled {
+                headers.push(reqwest::header::AUTHORIZATION);
+            }
             CorsLayer::new()
-                // Allow `POST` when accessing the resource
-                .allow_methods([reqwest::Method::POST])
+                .allow_methods(methods)
                 // Allow requests from any origin
                 .allow_origin(tower_http::cors::Any)
-                .allow_headers([reqwest::header::CONTENT_TYPE])
+                .allow_headers(headers)
         });
         // Setup metrics for the number of in-flight requests.
         let (in_flight_requests, counter) = InFlightRequestsLayer::pair();
+        let in_flight_counter = counter.clone();
         tokio::spawn(
             counter.run_emitter(Duration::from_millis(100), move |count| {
                 API_METRICS.web3_in_flight_requests[&transport_label].observe(count);
@@ -502,7 +905,13 @@ impl FullApiParams {
         // Assemble server middleware.
         let middleware = tower::ServiceBuilder::new()
             .layer(in_flight_requests)
-            .option_layer(cors);
+            .option_layer(compression)
+            .option_layer(cors)
+            .option_layer(rate_limit)
+            .option_layer(auth)
+            .option_layer(sse)
+            .option_layer(vm_client)
+            .option_layer(batch);
 
         // Settings shared by HTTP and WS servers.
         let max_connections = !is_http
@@ -515,63 +924,114 @@ impl FullApiParams {
             .max_response_body_size(response_body_size_limit)
             .set_batch_request_config(batch_request_config);
 
-        let (local_addr, server_handle) = if is_http {
+        let listener = TcpListener::bind(addr)
+            .await
+            .with_context(|| format!("Failed binding {transport_str} JSON-RPC server to {addr}"))?;
+        let local_addr = listener.local_addr().with_context(|| {
+            format!("Failed getting local address for {transport_str} JSON-RPC server")
+        })?;
+        let methods = Methods::from(rpc);
+
+        let server_handle = if is_http {
             // HTTP-specific settings
-            let server = server_builder
+            let service_builder = server_builder
+                .set_rpc_middleware(
+                    RpcServiceBuilder::new()
+                        .layer_fn(move |service| {
+                            MethodFilterMiddleware::new(service, method_filter.clone())
+                        })
+                        .layer_fn(move |service| {
+                            ExecutionPoolMiddleware::new(service, execution_pools.clone())
+                        }),
+                )
                 .http_only()
-                .build(addr)
-                .await
-                .context("Failed building HTTP JSON-RPC server")?;
-            (server.local_addr(), server.start(rpc))
+                .to_service_builder();
+            peer_addr::serve(listener, move |stop_handle| {
+                service_builder.clone().build(methods.clone(), stop_handle)
+            })
         } else {
             // WS specific settings
-            let server = server_builder
-                .set_rpc_middleware(RpcServiceBuilder::new().layer_fn(move |a| {
-                    LimitMiddleware::new(a, websocket_requests_per_minute_limit)
-                }))
+            let server_builder = if let Some(limit) = max_subscriptions_per_connection {
+                server_builder.max_subscriptions_per_connection(limit)
+            } else {
+                server_builder
+            };
+            let service_builder = server_builder
+                .set_rpc_middleware(
+                    RpcServiceBuilder::new()
+                        .layer_fn(move |service| {
+                            MethodFilterMiddleware::new(service, method_filter.clone())
+                        })
+                        .layer_fn(move |a| {
+                            LimitMiddleware::new(a, websocket_requests_per_minute_limit)
+                        })
+                        .layer_fn(move |service| {
+                            ExecutionPoolMiddleware::new(service, execution_pools.clone())
+                        }),
+                )
                 .set_id_provider(EthSubscriptionIdProvider)
-                .build(addr)
-                .await
-                .context("Failed building WS JSON-RPC server")?;
-            (server.local_addr(), server.start(rpc))
+                .to_service_builder();
+            peer_addr::serve(listener, move |stop_handle| {
+                service_builder.clone().build(methods.clone(), stop_handle)
+            })
         };
-        let local_addr = local_addr.with_context(|| {
-            format!("Failed getting local address for {transport_str} JSON-RPC server")
-        })?;
         local_addr_sender.send(local_addr).ok();
 
-        let close_handle = server_handle.clone();
-        let closing_vm_barrier = vm_barrier.clone();
-        tokio::spawn(async move {
-            if stop_receiver.changed().await.is_err() {
-                tracing::warn!(
-                    "Stop signal sender for {transport_str} JSON-RPC server was dropped \
-                     without sending a signal"
-                );
-            }
-            tracing::info!(
-                "Stop signal received, {transport_str} JSON-RPC server is shutting down"
-            );
-            closing_vm_barrier.close();
-            close_handle.stop().ok();
-        });
         health_updater.update(HealthStatus::Ready.into());
 
-        server_handle.stopped().await;
+        if stop_receiver.changed().await.is_err() {
+            tracing::warn!(
+                "Stop signal sender for {transport_str} JSON-RPC server was dropped \
+                 without sending a signal"
+            );
+        }
+        tracing::info!(
+            "Stop signal received, {transport_str} JSON-RPC server is shutting down; \
+             draining in-flight requests for up to {draining_timeout:?}"
+        );
+        // Report the server as not ready, so that it's excluded from load balancing. The server stops accepting
+        // new connections, and closes existing ones once requests on them are processed.
+        health_updater.update(HealthStatus::ShutDown.into());
+        server_handle.stop().ok();
+
+        let draining_latency = API_METRICS.shutdown_drain_latency[&transport_label].start();
+        let server_stopped = tokio::time::timeout(draining_timeout, server_handle.stopped()).await;
+        if server_stopped.is_err() {
+            let abandoned_requests = in_flight_counter.get();
+            API_METRICS.shutdown_abandoned_requests[&transport_label]
+                .inc_by(abandoned_requests as u64);
+            tracing::warn!(
+                "{transport_str} JSON-RPC server didn't drain in-flight requests after {draining_timeout:?} \
+                 ({abandoned_requests} requests remaining); forcing shutdown anyway"
+            );
+        } else {
+            let elapsed = draining_latency.observe();
+            tracing::info!("{transport_str} JSON-RPC server stopped; draining took {elapsed:?}");
+        }
+        API_METRICS.web3_in_flight_requests[&transport_label].observe(in_flight_counter.get());
         drop(health_updater);
-        tracing::info!("{transport_str} JSON-RPC server stopped");
+
+        // VM permits are only revoked after draining, so that in-flight requests requiring the VM can complete.
+        vm_barrier.close();
         Self::wait_for_vm(vm_barrier, transport_str).await;
         Ok(())
     }
 }
 
-async fn resolve_block(
-    connection: &mut StorageProcessor<'_>,
-    block: api::BlockId,
-    method_name: &'static str,
-) -> Result<MiniblockNumber, Web3Error> {
-    let result = connection.blocks_web3_dal().resolve_block_id(block).await;
-    result
-        .map_err(|err| internal_error(method_name, err))?
-        .ok_or(Web3Error::NoBlock)
+/// Checks that the state override set can be applied in the sandbox.
+fn validate_state_override(state_override: &api::StateOverride) -> Result<(), Web3Error> {
+    for (address, account) in state_override {
+        if account.state.is_some() && account.state_diff.is_some() {
+            let message = format!("account {address:?} has both `state` and `stateDiff` overrides");
+            return Err(Web3Error::InvalidStateOverride(message));
+        }
+        if let Some(code) = &account.code {
+            validate_bytecode(&code.0).map_err(|err| {
+                Web3Error::InvalidStateOverride(format!(
+                    "invalid code for account {address:?}: {err}"
+                ))
+            })?;
+        }
+    }
+    Ok(())
 }
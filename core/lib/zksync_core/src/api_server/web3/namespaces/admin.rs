@@ -0,0 +1,288 @@
+use std::sync::Arc;
+
+use zksync_types::{
+    api::{
+        AggregationPolicy, AggregationPolicyUpdate, FeeModelSettings, FeeModelSettingsUpdate,
+        L1GasPriceStats, NodeInfo,
+    },
+    Address, H256, U64,
+};
+use zksync_web3_decl::error::Web3Error;
+
+use crate::{
+    api_server::web3::{backend_jsonrpsee::internal_error, metrics::API_METRICS, state::RpcState},
+    eth_sender::AggregationPolicyHandle,
+    fee_model::FeeModelSettingsHandle,
+    l1_gas_price::L1GasPriceProvider,
+    state_keeper::SealingControl,
+};
+
+/// Removal reason for transactions removed by `admin_flushMempoolSender`.
+const FLUSH_MEMPOOL_SENDER_REASON: &str = "flushed via admin API";
+
+/// Namespace for node administration. Must only be exposed with authentication enabled.
+#[derive(Debug, Clone)]
+pub struct AdminNamespace {
+    state: RpcState,
+    sealing_control: Option<SealingControl>,
+    l1_gas_price_provider: Option<Arc<dyn L1GasPriceProvider>>,
+    fee_model_settings: Option<FeeModelSettingsHandle>,
+    aggregation_policy: Option<AggregationPolicyHandle>,
+}
+
+impl AdminNamespace {
+    pub fn new(
+        state: RpcState,
+        sealing_control: Option<SealingControl>,
+        l1_gas_price_provider: Option<Arc<dyn L1GasPriceProvider>>,
+    ) -> Self {
+        Self {
+            state,
+            sealing_control,
+            l1_gas_price_provider,
+            fee_model_settings: None,
+            aggregation_policy: None,
+        }
+    }
+
+    pub fn with_fee_model_settings(mut self, settings: Option<FeeModelSettingsHandle>) -> Self {
+        self.fee_model_settings = settings;
+        self
+    }
+
+    pub fn with_aggregation_policy(mut self, policy: Option<AggregationPolicyHandle>) -> Self {
+        self.aggregation_policy = policy;
+        self
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn node_info_impl(&self) -> Result<NodeInfo, Web3Error> {
+        const METHOD_NAME: &str = "admin_node_info";
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let mut storage = self
+            .state
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        let sealed_miniblock_number = storage
+            .blocks_web3_dal()
+            .get_sealed_miniblock_number()
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        let sealed_l1_batch_number = storage
+            .blocks_web3_dal()
+            .get_sealed_l1_batch_number()
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        drop(storage);
+
+        let api_config = &self.state.api_config;
+        let info = NodeInfo {
+            l1_chain_id: U64::from(*api_config.l1_chain_id),
+            l2_chain_id: api_config.l2_chain_id.as_u64().into(),
+            sealed_miniblock_number,
+            sealed_l1_batch_number,
+            log_filter: vlog::log_filter(),
+            sealing_stopped: self
+                .sealing_control
+                .as_ref()
+                .map(SealingControl::is_stopped),
+        };
+        method_latency.observe();
+        Ok(info)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn set_log_filter_impl(&self, directives: &str) -> Result<(), Web3Error> {
+        const METHOD_NAME: &str = "admin_set_log_filter";
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let result = match vlog::set_log_filter(directives) {
+            Ok(()) => {
+                tracing::info!("Log filter changed to `{directives}` via admin API");
+                Ok(())
+            }
+            Err(err @ vlog::LogFilterError::Parse(_)) => {
+                Err(Web3Error::InvalidLogFilter(err.to_string()))
+            }
+            Err(err) => Err(internal_error(METHOD_NAME, err)),
+        };
+        method_latency.observe();
+        result
+    }
+
+    /// Requests removing all pending transactions of the specified sender from the mempool. Same as
+    /// [`Self::remove_sender_transactions_impl()`] with a generic removal reason.
+    pub async fn flush_mempool_sender_impl(
+        &self,
+        address: Address,
+    ) -> Result<Vec<H256>, Web3Error> {
+        self.remove_sender_transactions_impl(address, FLUSH_MEMPOOL_SENDER_REASON)
+            .await
+    }
+
+    /// Requests removing the specified pending transaction from the mempool. Returns `false` if the transaction
+    /// is not pending. The transaction is removed asynchronously by the state keeper; afterwards,
+    /// `eth_getTransactionByHash` reports it as dropped with the specified reason.
+    #[tracing::instrument(skip(self))]
+    pub async fn remove_transaction_impl(
+        &self,
+        tx_hash: H256,
+        reason: &str,
+    ) -> Result<bool, Web3Error> {
+        const METHOD_NAME: &str = "admin_remove_transaction";
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let Some(master_pool) = &self.state.tx_sender.0.master_connection_pool else {
+            return Err(Web3Error::NotImplemented);
+        };
+        let mut storage = master_pool
+            .access_storage_tagged("api")
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        let requested = storage
+            .transactions_dal()
+            .request_mempool_tx_removal(tx_hash, reason)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        if requested {
+            tracing::warn!(
+                "Requested removing transaction {tx_hash:?} from mempool via admin API; reason: {reason}"
+            );
+        }
+        method_latency.observe();
+        Ok(requested)
+    }
+
+    /// Requests removing all pending transactions of the specified sender from the mempool. Returns hashes
+    /// of the transactions to be removed. See [`Self::remove_transaction_impl()`] for details.
+    #[tracing::instrument(skip(self))]
+    pub async fn remove_sender_transactions_impl(
+        &self,
+        address: Address,
+        reason: &str,
+    ) -> Result<Vec<H256>, Web3Error> {
+        const METHOD_NAME: &str = "admin_remove_sender_transactions";
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let Some(master_pool) = &self.state.tx_sender.0.master_connection_pool else {
+            return Err(Web3Error::NotImplemented);
+        };
+        let mut storage = master_pool
+            .access_storage_tagged("api")
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        let tx_hashes = storage
+            .transactions_dal()
+            .request_mempool_initiator_txs_removal(address, reason)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        tracing::warn!(
+            "Requested removing {} pending transactions from {address:?} from mempool via admin API; \
+             reason: {reason}",
+            tx_hashes.len()
+        );
+        method_latency.observe();
+        Ok(tx_hashes)
+    }
+
+    fn sealing_control(&self) -> Result<&SealingControl, Web3Error> {
+        self.sealing_control
+            .as_ref()
+            .ok_or(Web3Error::SealingControlUnavailable)
+    }
+
+    /// Stops sealing. Returns `false` if sealing was already stopped.
+    pub fn stop_sealing_impl(&self) -> Result<bool, Web3Error> {
+        let changed = self.sealing_control()?.stop();
+        if changed {
+            tracing::warn!("Sealing stopped via admin API");
+        }
+        Ok(changed)
+    }
+
+    /// Resumes sealing. Returns `false` if sealing was not stopped.
+    pub fn resume_sealing_impl(&self) -> Result<bool, Web3Error> {
+        let changed = self.sealing_control()?.resume();
+        if changed {
+            tracing::info!("Sealing resumed via admin API");
+        }
+        Ok(changed)
+    }
+
+    /// Returns internals of the L1 gas price oracle. Only supported on the main node, which estimates
+    /// L1 gas prices itself rather than fetching them from the main node.
+    pub fn get_l1_gas_price_stats_impl(&self) -> Result<L1GasPriceStats, Web3Error> {
+        const METHOD_NAME: &str = "admin_get_l1_gas_price_stats";
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let stats = self
+            .l1_gas_price_provider
+            .as_ref()
+            .and_then(|provider| provider.stats())
+            .ok_or(Web3Error::NotImplemented)?;
+        method_latency.observe();
+        Ok(stats)
+    }
+
+    fn fee_model_settings(&self) -> Result<&FeeModelSettingsHandle, Web3Error> {
+        self.fee_model_settings
+            .as_ref()
+            .ok_or(Web3Error::FeeModelSettingsUnavailable)
+    }
+
+    pub fn get_fee_model_settings_impl(&self) -> Result<FeeModelSettings, Web3Error> {
+        Ok(self.fee_model_settings()?.get())
+    }
+
+    /// Updates fee model settings. Changes are persisted and take effect immediately for this process; other
+    /// processes pick them up from Postgres. The state keeper applies changes starting from the next L1 batch.
+    #[tracing::instrument(skip(self))]
+    pub async fn update_fee_model_settings_impl(
+        &self,
+        update: FeeModelSettingsUpdate,
+    ) -> Result<FeeModelSettings, Web3Error> {
+        const METHOD_NAME: &str = "admin_update_fee_model_settings";
+
+        let settings = self.fee_model_settings()?;
+        FeeModelSettingsHandle::validate_update(&update)
+            .map_err(|err| Web3Error::InvalidFeeModelSettings(err.to_string()))?;
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let Some(master_pool) = &self.state.tx_sender.0.master_connection_pool else {
+            return Err(Web3Error::FeeModelSettingsUnavailable);
+        };
+        let mut storage = master_pool
+            .access_storage_tagged("api")
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        let updated_settings = settings
+            .update(update, "admin API", &mut storage)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        method_latency.observe();
+        Ok(updated_settings)
+    }
+
+    fn aggregation_policy(&self) -> Result<&AggregationPolicyHandle, Web3Error> {
+        self.aggregation_policy
+            .as_ref()
+            .ok_or(Web3Error::AggregationPolicyUnavailable)
+    }
+
+    pub fn get_aggregation_policy_impl(&self) -> Result<AggregationPolicy, Web3Error> {
+        Ok(self.aggregation_policy()?.get())
+    }
+
+    /// Updates the L1 batch aggregation policy. The ETH sender applies the changes on the next aggregation attempt.
+    #[tracing::instrument(skip(self))]
+    pub fn update_aggregation_policy_impl(
+        &self,
+        update: AggregationPolicyUpdate,
+    ) -> Result<AggregationPolicy, Web3Error> {
+        self.aggregation_policy()?
+            .update(update, "admin API")
+            .map_err(|err| Web3Error::InvalidAggregationPolicy(err.to_string()))
+    }
+}
@@ -1,42 +1,127 @@
-use std::sync::Arc;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    future::Future,
+    mem,
+    sync::Arc,
+    time::Duration,
+};
 
-use multivm::{interface::ExecutionResult, vm_latest::constants::BLOCK_GAS_LIMIT};
+use multivm::{
+    interface::{ExecutionResult, VmExecutionResultAndLogs},
+    tracers::TraceLimiterState,
+    vm_latest::constants::BLOCK_GAS_LIMIT,
+};
 use once_cell::sync::OnceCell;
-use zksync_dal::ConnectionPool;
+use serde::Serialize;
+use zksync_dal::{ConnectionPool, StorageProcessor};
 use zksync_state::PostgresStorageCaches;
 use zksync_types::{
-    api::{BlockId, BlockNumber, DebugCall, ResultDebugCall, TracerConfig},
-    fee_model::BatchFeeInput,
+    api::{
+        BlockId, BlockNumber, CallStateDiff, CallTracerConfig, DebugCall, DebugCallGasBreakdown,
+        DebugCallWithStateDiff, DebugStructLogs, DebugTrace, DeployedBytecode, ExecutionOverrides,
+        PrestateAccount, PrestateDiff, PrestateTrace, ReplayDivergence, ResultDebugCall,
+        SupportedTracers, TraceCallConfig, TracerConfig, TracerSpec, TransactionId,
+        TransactionReplay, ValueChange,
+    },
+    get_code_key, get_nonce_key,
     l2::L2Tx,
     transaction_request::CallRequest,
-    vm_trace::Call,
-    AccountTreeId, L2ChainId, H256, USED_BOOTLOADER_MEMORY_BYTES,
+    utils::{decompose_full_nonce, storage_key_for_eth_balance},
+    vm_trace::{
+        record_four_byte_call, Call, FourByteTrace, StructLogConfig, StructLogLimits,
+        TraceLimitExceeded, TraceLimits,
+    },
+    AccountTreeId, Address, Bytes, L2ChainId, LogQuery, MiniblockNumber, StorageKey,
+    StorageLogQuery, Transaction, VmEvent, ACCOUNT_CODE_STORAGE_ADDRESS, H256,
+    USED_BOOTLOADER_MEMORY_BYTES,
 };
+use zksync_utils::{bytecode::hash_bytecode, h256_to_account_address, h256_to_u256, u256_to_h256};
 use zksync_web3_decl::error::Web3Error;
 
-use crate::api_server::{
-    execution_sandbox::{
-        execute_tx_eth_call, ApiTracer, BlockArgs, TxSharedArgs, VmConcurrencyLimiter,
-    },
-    tx_sender::ApiContracts,
-    web3::{
-        backend_jsonrpsee::internal_error,
-        metrics::API_METRICS,
-        resolve_block,
-        state::{RpcState, SealedMiniblockNumber},
+use crate::{
+    api_server::{
+        execution_sandbox::{
+            execute_tx_eth_call, execute_txs_eth_call, exhausted_call_gas_limit,
+            replay_txs_in_sandbox, ApiTracer, BlockArgs, TxSharedArgs, VmConcurrencyLimiter,
+        },
+        tx_sender::ApiContracts,
+        web3::{
+            backend_jsonrpsee::internal_error,
+            js_tracer::{js_tracer_limits, run_js_tracer, JsTracerContext, JsTracerInputs},
+            metrics::API_METRICS,
+            state::{BlockStartInfo, ReadReplica, RpcState, SealedMiniblockNumber},
+            validate_state_override,
+        },
     },
+    fee_model::BatchFeeModelInputProvider,
 };
 
+/// State snapshot used to load account data for `prestateTracer`.
+#[derive(Debug, Clone, Copy)]
+enum PrestateSnapshot {
+    /// State at the end of the specified miniblock.
+    Miniblock(MiniblockNumber),
+    /// State right before the specified storage operation in the miniblock was applied.
+    BeforeOperation(MiniblockNumber, u32),
+}
+
+/// Storage writes and bytecodes applied on top of a [`PrestateSnapshot`]. Used to compute the state
+/// after a transaction executed in the sandbox for `prestateTracer` in the diff mode.
+#[derive(Debug, Default)]
+struct StateOverlay {
+    values: HashMap<H256, H256>,
+    factory_deps: HashMap<H256, Vec<u8>>,
+}
+
+impl StateOverlay {
+    fn new(storage_logs: &[StorageLogQuery], factory_deps: &[Vec<u8>]) -> Self {
+        let mut values = HashMap::new();
+        for log in storage_logs {
+            let LogQuery {
+                address,
+                key,
+                read_value,
+                written_value,
+                rw_flag,
+                rollback,
+                ..
+            } = log.log_query;
+            if !rw_flag {
+                continue;
+            }
+            // Rollback queries restore the value read by the original write.
+            let value = if rollback { read_value } else { written_value };
+            let key = StorageKey::new(AccountTreeId::new(address), u256_to_h256(key));
+            values.insert(key.hashed_key(), u256_to_h256(value));
+        }
+
+        let factory_deps = factory_deps
+            .iter()
+            .map(|bytecode| (hash_bytecode(bytecode), bytecode.clone()))
+            .collect();
+        Self {
+            values,
+            factory_deps,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct DebugNamespace {
     connection_pool: ConnectionPool,
-    fair_l2_gas_price: u64,
+    read_replica: Option<ReadReplica>,
+    batch_fee_input_provider: Arc<dyn BatchFeeModelInputProvider>,
+    operator_account: Address,
     api_contracts: ApiContracts,
     vm_execution_cache_misses_limit: Option<usize>,
     vm_concurrency_limiter: Arc<VmConcurrencyLimiter>,
     storage_caches: PostgresStorageCaches,
     last_sealed_miniblock: SealedMiniblockNumber,
+    start_info: BlockStartInfo,
     chain_id: L2ChainId,
+    call_gas_limit: u32,
+    trace_block_reexecution_enabled: bool,
+    trace_call_many_max_calls: usize,
 }
 
 impl DebugNamespace {
@@ -46,53 +131,107 @@ impl DebugNamespace {
         let api_contracts = ApiContracts::load_from_disk();
         Self {
             connection_pool: state.connection_pool,
-            fair_l2_gas_price: sender_config.fair_l2_gas_price,
+            read_replica: state.read_replica,
+            batch_fee_input_provider: state.tx_sender.0.batch_fee_input_provider.clone(),
+            operator_account: sender_config.fee_account_addr,
             api_contracts,
             vm_execution_cache_misses_limit: sender_config.vm_execution_cache_misses_limit,
             vm_concurrency_limiter: state.tx_sender.vm_concurrency_limiter(),
             storage_caches: state.tx_sender.storage_caches(),
             last_sealed_miniblock: state.last_sealed_miniblock,
+            start_info: state.start_info,
             chain_id: sender_config.chain_id,
+            call_gas_limit: sender_config.call_gas_limit(),
+            trace_block_reexecution_enabled: state.api_config.trace_block_reexecution_enabled,
+            trace_call_many_max_calls: state.api_config.trace_call_many_max_calls,
         }
     }
 
+    /// Same as [`RpcState::read_pool()`].
+    pub(super) fn read_pool(&self, block_id: BlockId) -> &ConnectionPool {
+        self.read_replica
+            .as_ref()
+            .and_then(|replica| replica.pool_for_block(block_id))
+            .unwrap_or(&self.connection_pool)
+    }
+
     #[tracing::instrument(skip(self))]
     pub async fn debug_trace_block_impl(
         &self,
         block_id: BlockId,
         options: Option<TracerConfig>,
+    ) -> Result<Vec<ResultDebugCall>, Web3Error> {
+        let limits = match &options {
+            Some(options) => Self::parse_trace_limits(options)?,
+            None => TraceLimits::default(),
+        };
+        with_trace_timeout(limits, self.trace_block_inner(block_id, options, limits)).await
+    }
+
+    async fn trace_block_inner(
+        &self,
+        block_id: BlockId,
+        options: Option<TracerConfig>,
+        limits: TraceLimits,
     ) -> Result<Vec<ResultDebugCall>, Web3Error> {
         const METHOD_NAME: &str = "debug_trace_block";
 
         let method_latency = API_METRICS.start_block_call(METHOD_NAME, block_id);
-        let only_top_call = options
-            .map(|options| options.tracer_config.only_top_call)
-            .unwrap_or(false);
+        if let Some(options) = &options {
+            Self::validate_tracer(options)?;
+        }
+        let js_code = Self::js_tracer_code(options.as_ref());
+        let (tracer, tracer_config) = Self::parse_options(options);
         let mut connection = self
-            .connection_pool
+            .read_pool(block_id)
             .access_storage_tagged("api")
             .await
             .unwrap();
-        let block_number = resolve_block(&mut connection, block_id, METHOD_NAME).await?;
-        let call_trace = connection
-            .blocks_web3_dal()
-            .get_trace_for_miniblock(block_number)
+        let block_number = self
+            .start_info
+            .resolve_block(&mut connection, block_id, METHOD_NAME)
+            .await?;
+        drop(connection);
+        if let Some(code) = js_code {
+            let traces = self.trace_block_js(block_number, &code, limits).await?;
+            let block_diff = self.last_sealed_miniblock.diff(block_number);
+            method_latency.observe(block_diff);
+            return Ok(traces);
+        }
+
+        let call_traces = self
+            .miniblock_call_traces(block_number, METHOD_NAME, limits)
+            .await?;
+        let mut connection = self
+            .connection_pool
+            .access_storage_tagged("api")
             .await
             .unwrap();
-        let call_trace = call_trace
-            .into_iter()
-            .map(|call_trace| {
-                let mut result: DebugCall = call_trace.into();
-                if only_top_call {
-                    result.calls = vec![];
+        let mut traces = Vec::with_capacity(call_traces.len());
+        for (tx_hash, call_trace) in call_traces {
+            let result = match tracer {
+                SupportedTracers::CallTracer => {
+                    Self::map_call(call_trace, tracer_config.only_top_call).into()
                 }
-                ResultDebugCall { result }
-            })
-            .collect();
+                SupportedTracers::FourByteTracer => {
+                    DebugTrace::FourByte(Self::four_byte_trace(&call_trace))
+                }
+                SupportedTracers::NoopTracer => noop_trace(),
+                SupportedTracers::PrestateTracer => Self::stored_tx_prestate_trace(
+                    &mut connection,
+                    tx_hash,
+                    &call_trace,
+                    tracer_config.diff_mode,
+                )
+                .await
+                .map_err(|err| internal_error(METHOD_NAME, err))?,
+            };
+            traces.push(ResultDebugCall { result });
+        }
 
         let block_diff = self.last_sealed_miniblock.diff(block_number);
         method_latency.observe(block_diff);
-        Ok(call_trace)
+        Ok(traces)
     }
 
     #[tracing::instrument(skip(self))]
@@ -100,25 +239,75 @@ impl DebugNamespace {
         &self,
         tx_hash: H256,
         options: Option<TracerConfig>,
-    ) -> Option<DebugCall> {
-        let only_top_call = options
-            .map(|options| options.tracer_config.only_top_call)
-            .unwrap_or(false);
-        let call_trace = self
+    ) -> Result<Option<DebugTrace>, Web3Error> {
+        let options = options.unwrap_or_default();
+        let limits = Self::parse_trace_limits(&options)?;
+        with_trace_timeout(
+            limits,
+            self.trace_transaction_inner(tx_hash, options, limits),
+        )
+        .await
+    }
+
+    async fn trace_transaction_inner(
+        &self,
+        tx_hash: H256,
+        options: TracerConfig,
+        limits: TraceLimits,
+    ) -> Result<Option<DebugTrace>, Web3Error> {
+        const METHOD_NAME: &str = "debug_trace_transaction";
+
+        Self::validate_tracer(&options)?;
+        let tracer = match options.tracer {
+            None => {
+                let struct_logs = self
+                    .trace_stored_tx_struct_logs(tx_hash, options.struct_logs, limits)
+                    .await?;
+                return Ok(struct_logs.map(DebugTrace::StructLogs));
+            }
+            Some(TracerSpec::JavaScript(code)) => {
+                let trace = self.trace_stored_tx_js(tx_hash, code, limits).await?;
+                return Ok(trace.map(DebugTrace::Custom));
+            }
+            Some(TracerSpec::BuiltIn(tracer)) => tracer,
+        };
+        let tracer_config = options.tracer_config;
+
+        let mut connection = self
             .connection_pool
             .access_storage_tagged("api")
             .await
-            .unwrap()
-            .transactions_dal()
-            .get_call_trace(tx_hash)
-            .await;
-        call_trace.map(|call_trace| {
-            let mut result: DebugCall = call_trace.into();
-            if only_top_call {
-                result.calls = vec![];
-            }
-            result
-        })
+            .unwrap();
+        let call_trace = connection.transactions_dal().get_call_trace(tx_hash).await;
+        let Some(call_trace) = call_trace else {
+            return Ok(None);
+        };
+        check_call_traces_memory([&call_trace], limits)?;
+
+        Ok(Some(match tracer {
+            SupportedTracers::CallTracer if tracer_config.with_log => {
+                let events = Self::stored_tx_events(&mut connection, tx_hash)
+                    .await
+                    .map_err(|err| internal_error(METHOD_NAME, err))?;
+                let only_top_call = tracer_config.only_top_call;
+                Self::map_call_with_logs(call_trace, only_top_call, &events).into()
+            }
+            SupportedTracers::CallTracer => {
+                Self::map_call(call_trace, tracer_config.only_top_call).into()
+            }
+            SupportedTracers::FourByteTracer => {
+                DebugTrace::FourByte(Self::four_byte_trace(&call_trace))
+            }
+            SupportedTracers::NoopTracer => noop_trace(),
+            SupportedTracers::PrestateTracer => Self::stored_tx_prestate_trace(
+                &mut connection,
+                tx_hash,
+                &call_trace,
+                tracer_config.diff_mode,
+            )
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?,
+        }))
     }
 
     #[tracing::instrument(skip(self, request, block_id))]
@@ -126,40 +315,62 @@ impl DebugNamespace {
         &self,
         request: CallRequest,
         block_id: Option<BlockId>,
-        options: Option<TracerConfig>,
-    ) -> Result<DebugCall, Web3Error> {
+        options: Option<TraceCallConfig>,
+    ) -> Result<DebugTrace, Web3Error> {
         const METHOD_NAME: &str = "debug_trace_call";
 
         let block_id = block_id.unwrap_or(BlockId::Number(BlockNumber::Pending));
         let method_latency = API_METRICS.start_block_call(METHOD_NAME, block_id);
-        let only_top_call = options
-            .map(|options| options.tracer_config.only_top_call)
-            .unwrap_or(false);
+        let options = options.unwrap_or_default();
+        let state_override = options.state_overrides;
+        if let Some(state_override) = &state_override {
+            validate_state_override(state_override)?;
+        }
+        Self::validate_tracer(&options.tracer)?;
+        let limits = Self::parse_trace_limits(&options.tracer)?;
+        let js_tracer = Self::js_tracer_code(Some(&options.tracer))
+            .map(|code| (code, JsTracerInputs::default()));
+        let execution_overrides = options.tracer.execution_overrides.clone();
+        let (tracer, tracer_config) = Self::parse_options(Some(options.tracer));
 
         let mut connection = self
             .connection_pool
             .access_storage_tagged("api")
             .await
             .unwrap();
-        let block_args = BlockArgs::new(&mut connection, block_id)
-            .await
-            .map_err(|err| internal_error("debug_trace_call", err))?
-            .ok_or(Web3Error::NoBlock)?;
+        let block_args = self
+            .start_info
+            .resolve_block_args(&mut connection, block_id, "debug_trace_call")
+            .await?;
         drop(connection);
 
         let tx = L2Tx::from_request(request.into(), USED_BOOTLOADER_MEMORY_BYTES)?;
 
-        let shared_args = self.shared_args();
-        let vm_permit = self.vm_concurrency_limiter.acquire().await;
+        let shared_args = self.shared_args(&execution_overrides);
+        let vm_permit = self.vm_concurrency_limiter.acquire_heavy().await;
         let vm_permit = vm_permit.ok_or(Web3Error::InternalError)?;
 
         // We don't need properly trace if we only need top call
         let call_tracer_result = Arc::new(OnceCell::default());
-        let custom_tracers = if only_top_call {
+        let four_byte_tracer_result = Arc::new(OnceCell::default());
+        let limiter_state = Arc::new(TraceLimiterState::default());
+        let mut custom_tracers = if let Some((_, inputs)) = &js_tracer {
+            inputs.api_tracers(limiter_state.clone())
+        } else if tracer == SupportedTracers::FourByteTracer {
+            vec![ApiTracer::FourByteTracer(four_byte_tracer_result.clone())]
+        } else if tracer == SupportedTracers::NoopTracer
+            || (tracer_config.only_top_call
+                && !tracer_config.with_log
+                && tracer == SupportedTracers::CallTracer)
+        {
             vec![]
         } else {
-            vec![ApiTracer::CallTracer(call_tracer_result.clone())]
+            vec![ApiTracer::CallTracer(
+                call_tracer_result.clone(),
+                Some(limiter_state.clone()),
+            )]
         };
+        custom_tracers.extend(Self::trace_limiter(limits, &limiter_state));
 
         let result = execute_tx_eth_call(
             vm_permit,
@@ -168,10 +379,26 @@ impl DebugNamespace {
             tx.clone(),
             block_args,
             self.vm_execution_cache_misses_limit,
+            state_override,
+            vec![],
             custom_tracers,
         )
         .await;
 
+        if let Some(exceeded) = limiter_state.exceeded_limit() {
+            return Err(exceeded.into());
+        }
+        if exhausted_call_gas_limit(&result, self.call_gas_limit) {
+            return Err(Web3Error::CallComputeLimitExceeded(self.call_gas_limit));
+        }
+        let gas_breakdown = gas_breakdown(&result);
+        if let Some((code, inputs)) = js_tracer {
+            let tracer_context = JsTracerContext::new(&tx.into(), &result);
+            let trace = run_js_tracer(code, inputs, tracer_context, limits.timeout).await?;
+            let block_diff = self.last_sealed_miniblock.diff_with_block_args(&block_args);
+            method_latency.observe(block_diff);
+            return Ok(DebugTrace::Custom(trace));
+        }
         let (output, revert_reason) = match result.result {
             ExecutionResult::Success { output, .. } => (output, None),
             ExecutionResult::Revert { output } => (vec![], Some(output.to_string())),
@@ -183,6 +410,12 @@ impl DebugNamespace {
             }
         };
 
+        let post_state_overlay =
+            (tracer == SupportedTracers::PrestateTracer && tracer_config.diff_mode).then(|| {
+                let factory_deps = tx.execute.factory_deps.as_deref().unwrap_or_default();
+                StateOverlay::new(&result.logs.storage_logs, factory_deps)
+            });
+
         // We had only one copy of Arc this arc is already dropped it's safe to unwrap
         let trace = Arc::try_unwrap(call_tracer_result)
             .unwrap()
@@ -198,19 +431,1408 @@ impl DebugNamespace {
             trace,
         );
 
+        let mut trace = match tracer {
+            SupportedTracers::CallTracer if tracer_config.with_state_diff => {
+                let accounts = Self::touched_accounts(&call);
+                let factory_deps = tx.execute.factory_deps.as_deref().unwrap_or_default();
+                let mut state_diff =
+                    call_state_diff(&result.logs.storage_logs, &accounts, factory_deps);
+                let mut connection = self
+                    .connection_pool
+                    .access_storage_tagged("api")
+                    .await
+                    .unwrap();
+                for contract in state_diff.deployed_contracts.values_mut() {
+                    if contract.code.is_none() {
+                        let code = connection
+                            .storage_dal()
+                            .get_factory_dep(contract.code_hash)
+                            .await;
+                        contract.code = code.map(Bytes::from);
+                    }
+                }
+                let call = if tracer_config.with_log {
+                    Self::map_call_with_logs(call, false, &result.logs.events)
+                } else {
+                    call.into()
+                };
+                DebugTrace::CallWithStateDiff(DebugCallWithStateDiff { call, state_diff })
+            }
+            SupportedTracers::CallTracer if tracer_config.with_log => {
+                let events = &result.logs.events;
+                Self::map_call_with_logs(call, tracer_config.only_top_call, events).into()
+            }
+            SupportedTracers::CallTracer => DebugTrace::Call(call.into()),
+            SupportedTracers::NoopTracer => noop_trace(),
+            SupportedTracers::FourByteTracer => {
+                let trace = Arc::try_unwrap(four_byte_tracer_result)
+                    .unwrap()
+                    .take()
+                    .unwrap_or_default();
+                DebugTrace::FourByte(trace)
+            }
+            SupportedTracers::PrestateTracer => {
+                let touched_slots: BTreeSet<_> = result
+                    .logs
+                    .storage_logs
+                    .iter()
+                    .map(|log| {
+                        StorageKey::new(
+                            AccountTreeId::new(log.log_query.address),
+                            u256_to_h256(log.log_query.key),
+                        )
+                    })
+                    .collect();
+                let accounts = Self::touched_accounts(&call);
+                let snapshot = PrestateSnapshot::Miniblock(block_args.resolved_block_number());
+                let mut connection = self
+                    .connection_pool
+                    .access_storage_tagged("api")
+                    .await
+                    .unwrap();
+                let prestate = Self::load_prestate(
+                    &mut connection,
+                    &accounts,
+                    &touched_slots,
+                    snapshot,
+                    &StateOverlay::default(),
+                )
+                .await
+                .map_err(|err| internal_error(METHOD_NAME, err))?;
+
+                if let Some(overlay) = post_state_overlay {
+                    let post_state = Self::load_prestate(
+                        &mut connection,
+                        &accounts,
+                        &touched_slots,
+                        snapshot,
+                        &overlay,
+                    )
+                    .await
+                    .map_err(|err| internal_error(METHOD_NAME, err))?;
+                    DebugTrace::PrestateDiff(diff_prestate(prestate, post_state))
+                } else {
+                    DebugTrace::Prestate(prestate)
+                }
+            }
+        };
+
+        if let DebugTrace::Call(call)
+        | DebugTrace::CallWithStateDiff(DebugCallWithStateDiff { call, .. }) = &mut trace
+        {
+            call.gas_breakdown = Some(gas_breakdown);
+        }
+
+        let block_diff = self.last_sealed_miniblock.diff_with_block_args(&block_args);
+        method_latency.observe(block_diff);
+        Ok(trace)
+    }
+
+    /// Re-executes a stored transaction on top of the state before it and compares the result with the stored
+    /// receipt and call trace. The VM version is determined by the protocol version of the transaction miniblock.
+    #[tracing::instrument(skip(self))]
+    pub async fn replay_transaction_impl(
+        &self,
+        tx_hash: H256,
+    ) -> Result<Option<TransactionReplay>, Web3Error> {
+        const METHOD_NAME: &str = "debug_replay_transaction";
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let Some((miniblock_number, transactions)) =
+            self.load_replayed_txs(tx_hash, METHOD_NAME).await?
+        else {
+            return Ok(None);
+        };
+        let tx = transactions.last().unwrap().clone();
+        let call_tracer_result = Arc::new(OnceCell::default());
+        let mut txs: Vec<_> = transactions.into_iter().map(|tx| (tx, vec![])).collect();
+        txs.last_mut().unwrap().1 = vec![ApiTracer::CallTracer(call_tracer_result.clone(), None)];
+        let mut results = self
+            .replay_miniblock(miniblock_number, txs, METHOD_NAME)
+            .await?;
+        let result = results
+            .pop()
+            .expect("no result for the replayed transaction");
+
+        let mut connection = self
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap();
+        let receipt = connection
+            .transactions_web3_dal()
+            .get_transaction_receipt(tx_hash)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        let Some(receipt) = receipt else {
+            return Ok(None);
+        };
+        let stored_trace = connection.transactions_dal().get_call_trace(tx_hash).await;
+        drop(connection);
+
+        // The trace is built in the same way as by the state keeper, so that it can be compared with the stored one.
+        let calls = Arc::try_unwrap(call_tracer_result)
+            .unwrap()
+            .take()
+            .unwrap_or_default();
+        let gas_limit = tx.gas_limit().as_u32();
+        let gas_used = gas_limit.saturating_sub(result.refunds.gas_refunded);
+        let revert_reason = match &result.result {
+            ExecutionResult::Success { .. } => None,
+            ExecutionResult::Revert { output } => Some(output.to_string()),
+            ExecutionResult::Halt { reason } => Some(reason.to_string()),
+        };
+        let trace = Call::new_high_level(
+            gas_limit,
+            gas_used,
+            tx.execute.value,
+            tx.execute.calldata,
+            vec![],
+            revert_reason,
+            calls,
+        );
+
+        let mut divergences = Divergences::default();
+        let status = u64::from(!result.result.is_failed());
+        divergences.compare("receipt.status", &receipt.status.as_u64(), &status);
+        divergences.compare("receipt.gasUsed", &receipt.gas_used, &Some(gas_used.into()));
+        divergences.compare(
+            "receipt.logs.length",
+            &receipt.logs.len(),
+            &result.logs.events.len(),
+        );
+        for (i, (log, event)) in receipt.logs.iter().zip(&result.logs.events).enumerate() {
+            let path = format!("receipt.logs[{i}]");
+            divergences.compare(&format!("{path}.address"), &log.address, &event.address);
+            divergences.compare(
+                &format!("{path}.topics"),
+                &log.topics,
+                &event.indexed_topics,
+            );
+            divergences.compare(&format!("{path}.data"), &log.data.0, &event.value);
+        }
+        if let Some(stored_trace) = &stored_trace {
+            divergences.compare_calls("trace", stored_trace, &trace);
+        }
+
+        method_latency.observe(self.last_sealed_miniblock.diff(miniblock_number));
+        let mut trace = DebugCall::from(trace);
+        trace.gas_breakdown = Some(gas_breakdown(&result));
+        Ok(Some(TransactionReplay {
+            trace,
+            divergences: divergences.0,
+        }))
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_raw_transaction_impl(
+        &self,
+        tx_hash: H256,
+    ) -> Result<Option<Bytes>, Web3Error> {
+        const METHOD_NAME: &str = "debug_get_raw_transaction";
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let raw_tx = self
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap()
+            .transactions_web3_dal()
+            .get_raw_transaction(tx_hash)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+
+        method_latency.observe();
+        Ok(raw_tx.map(Bytes))
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_raw_block_impl(&self, block_id: BlockId) -> Result<Bytes, Web3Error> {
+        const METHOD_NAME: &str = "debug_get_raw_block";
+
+        let method_latency = API_METRICS.start_block_call(METHOD_NAME, block_id);
+        let mut connection = self
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap();
+        let block_number = self
+            .start_info
+            .resolve_block(&mut connection, block_id, METHOD_NAME)
+            .await?;
+        let raw_block = connection
+            .blocks_web3_dal()
+            .get_raw_block(block_number)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?
+            .ok_or(Web3Error::NoBlock)?;
+
+        let block_diff = self.last_sealed_miniblock.diff(block_number);
+        method_latency.observe(block_diff);
+        Ok(Bytes(raw_block))
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_raw_receipts_impl(&self, block_id: BlockId) -> Result<Vec<Bytes>, Web3Error> {
+        const METHOD_NAME: &str = "debug_get_raw_receipts";
+
+        let method_latency = API_METRICS.start_block_call(METHOD_NAME, block_id);
+        let mut connection = self
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap();
+        let block_number = self
+            .start_info
+            .resolve_block(&mut connection, block_id, METHOD_NAME)
+            .await?;
+        let receipts = connection
+            .transactions_web3_dal()
+            .get_raw_miniblock_receipts(block_number)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+
+        let block_diff = self.last_sealed_miniblock.diff(block_number);
+        method_latency.observe(block_diff);
+        Ok(receipts.into_iter().map(Bytes).collect())
+    }
+
+    /// Traces a sequence of calls executed on top of the same state, so that each call observes changes made
+    /// by the preceding ones. Only `callTracer` is supported; trace limits apply to each call separately, except
+    /// for the memory limit, which is shared by all calls.
+    #[tracing::instrument(skip(self, requests, block_id))]
+    pub async fn debug_trace_call_many_impl(
+        &self,
+        requests: Vec<CallRequest>,
+        block_id: Option<BlockId>,
+        options: Option<TraceCallConfig>,
+    ) -> Result<Vec<DebugCall>, Web3Error> {
+        const METHOD_NAME: &str = "debug_trace_call_many";
+
+        if requests.len() > self.trace_call_many_max_calls {
+            return Err(Web3Error::TooManyTraceCalls(self.trace_call_many_max_calls));
+        }
+        let block_id = block_id.unwrap_or(BlockId::Number(BlockNumber::Pending));
+        let method_latency = API_METRICS.start_block_call(METHOD_NAME, block_id);
+        let options = options.unwrap_or_default();
+        let state_override = options.state_overrides;
+        if let Some(state_override) = &state_override {
+            validate_state_override(state_override)?;
+        }
+        Self::validate_tracer(&options.tracer)?;
+        let limits = Self::parse_trace_limits(&options.tracer)?;
+        if !matches!(
+            options.tracer.tracer,
+            None | Some(TracerSpec::BuiltIn(SupportedTracers::CallTracer))
+        ) {
+            let message = "`debug_traceCallMany` only supports `callTracer`".to_owned();
+            return Err(Web3Error::InvalidTracerConfig(message));
+        }
+        let only_top_call = options.tracer.tracer_config.only_top_call;
+        let with_log = options.tracer.tracer_config.with_log;
+        let txs = requests
+            .into_iter()
+            .map(|request| L2Tx::from_request(request.into(), USED_BOOTLOADER_MEMORY_BYTES))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut connection = self
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap();
+        let block_args = self
+            .start_info
+            .resolve_block_args(&mut connection, block_id, METHOD_NAME)
+            .await?;
+        drop(connection);
+
+        let shared_args = self.shared_args(&options.tracer.execution_overrides);
+        let vm_permit = self.vm_concurrency_limiter.acquire_heavy().await;
+        let vm_permit = vm_permit.ok_or(Web3Error::InternalError)?;
+
+        let limiter_state = Arc::new(TraceLimiterState::default());
+        let mut call_tracer_results = Vec::with_capacity(txs.len());
+        let txs_with_tracers = txs.iter().map(|tx| {
+            let call_tracer_result = Arc::new(OnceCell::default());
+            let mut custom_tracers = if only_top_call && !with_log {
+                vec![]
+            } else {
+                vec![ApiTracer::CallTracer(
+                    call_tracer_result.clone(),
+                    Some(limiter_state.clone()),
+                )]
+            };
+            custom_tracers.extend(Self::trace_limiter(limits, &limiter_state));
+            call_tracer_results.push(call_tracer_result);
+            (tx.clone(), custom_tracers)
+        });
+        let txs_with_tracers = txs_with_tracers.collect();
+
+        let results = execute_txs_eth_call(
+            vm_permit,
+            shared_args,
+            self.connection_pool.clone(),
+            block_args,
+            self.vm_execution_cache_misses_limit,
+            state_override,
+            txs_with_tracers,
+        )
+        .await;
+
+        if let Some(exceeded) = limiter_state.exceeded_limit() {
+            return Err(exceeded.into());
+        }
+        let calls = txs.into_iter().zip(results).zip(call_tracer_results);
+        let calls = calls.map(|((tx, result), call_tracer_result)| {
+            let gas_breakdown = gas_breakdown(&result);
+            let (output, revert_reason, error) = match result.result {
+                ExecutionResult::Success { output, .. } => (output, None, None),
+                ExecutionResult::Revert { output } => (vec![], Some(output.to_string()), None),
+                ExecutionResult::Halt { reason } => (vec![], None, Some(reason.to_string())),
+            };
+            // The sandbox has finished, so all other references to the `Arc` are dropped
+            let trace = Arc::try_unwrap(call_tracer_result)
+                .unwrap()
+                .take()
+                .unwrap_or_default();
+            let mut call = Call::new_high_level(
+                tx.common_data.fee.gas_limit.as_u32(),
+                result.statistics.gas_used,
+                tx.execute.value,
+                tx.execute.calldata,
+                output,
+                revert_reason,
+                trace,
+            );
+            call.error = error;
+            let mut call = if with_log {
+                Self::map_call_with_logs(call, only_top_call, &result.logs.events)
+            } else {
+                Self::map_call(call, only_top_call)
+            };
+            call.gas_breakdown = Some(gas_breakdown);
+            call
+        });
+        let calls = calls.collect();
+
         let block_diff = self.last_sealed_miniblock.diff_with_block_args(&block_args);
         method_latency.observe(block_diff);
-        Ok(call.into())
+        Ok(calls)
+    }
+
+    /// Re-executes a stored transaction with the struct logger. The transaction is executed on top of the state
+    /// before its miniblock, after all preceding transactions from the same miniblock.
+    async fn trace_stored_tx_struct_logs(
+        &self,
+        tx_hash: H256,
+        config: StructLogConfig,
+        limits: TraceLimits,
+    ) -> Result<Option<DebugStructLogs>, Web3Error> {
+        const METHOD_NAME: &str = "debug_trace_transaction";
+
+        let Some((miniblock_number, transactions)) =
+            self.load_replayed_txs(tx_hash, METHOD_NAME).await?
+        else {
+            return Ok(None);
+        };
+        let struct_logs = Arc::new(OnceCell::default());
+        let limiter_state = Arc::new(TraceLimiterState::default());
+        let mut txs: Vec<_> = transactions.into_iter().map(|tx| (tx, vec![])).collect();
+        let tracers = &mut txs.last_mut().unwrap().1;
+        tracers.push(ApiTracer::StructLogTracer(
+            config,
+            StructLogLimits::default(),
+            struct_logs.clone(),
+            limiter_state.clone(),
+        ));
+        tracers.extend(Self::trace_limiter(limits, &limiter_state));
+        let mut results = self
+            .replay_miniblock(miniblock_number, txs, METHOD_NAME)
+            .await?;
+        if let Some(exceeded) = limiter_state.exceeded_limit() {
+            return Err(exceeded.into());
+        }
+        let result = results.pop().expect("no result for the traced transaction");
+
+        let (failed, return_value) = match result.result {
+            ExecutionResult::Success { output } => (false, output),
+            ExecutionResult::Revert { output } => (true, output.encoded_data()),
+            ExecutionResult::Halt { .. } => (true, vec![]),
+        };
+        // The tracer is dropped together with the VM, so we hold the only copy of the `Arc`.
+        let struct_logs = Arc::try_unwrap(struct_logs)
+            .unwrap()
+            .take()
+            .unwrap_or_default();
+        Ok(Some(DebugStructLogs {
+            gas: result.statistics.gas_used,
+            failed,
+            return_value: return_value.into(),
+            struct_logs,
+        }))
+    }
+
+    /// Re-executes a stored transaction with a custom JavaScript tracer.
+    async fn trace_stored_tx_js(
+        &self,
+        tx_hash: H256,
+        code: String,
+        limits: TraceLimits,
+    ) -> Result<Option<serde_json::Value>, Web3Error> {
+        const METHOD_NAME: &str = "debug_trace_transaction";
+
+        let Some((miniblock_number, transactions)) =
+            self.load_replayed_txs(tx_hash, METHOD_NAME).await?
+        else {
+            return Ok(None);
+        };
+        let tx = transactions.last().unwrap().clone();
+        let inputs = JsTracerInputs::default();
+        let limiter_state = Arc::new(TraceLimiterState::default());
+        let mut txs: Vec<_> = transactions.into_iter().map(|tx| (tx, vec![])).collect();
+        let tracers = &mut txs.last_mut().unwrap().1;
+        *tracers = inputs.api_tracers(limiter_state.clone());
+        tracers.extend(Self::trace_limiter(limits, &limiter_state));
+        let mut results = self
+            .replay_miniblock(miniblock_number, txs, METHOD_NAME)
+            .await?;
+        if let Some(exceeded) = limiter_state.exceeded_limit() {
+            return Err(exceeded.into());
+        }
+        let result = results.pop().expect("no result for the traced transaction");
+
+        let tracer_context =
+            JsTracerContext::new(&tx, &result).with_location(miniblock_number, tx_hash);
+        run_js_tracer(code, inputs, tracer_context, limits.timeout)
+            .await
+            .map(Some)
+    }
+
+    /// Re-executes all transactions in a miniblock with a custom JavaScript tracer.
+    async fn trace_block_js(
+        &self,
+        miniblock_number: MiniblockNumber,
+        code: &str,
+        limits: TraceLimits,
+    ) -> Result<Vec<ResultDebugCall>, Web3Error> {
+        const METHOD_NAME: &str = "debug_trace_block";
+
+        let mut connection = self
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap();
+        let transactions = connection
+            .transactions_web3_dal()
+            .get_raw_miniblock_transactions(miniblock_number)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        drop(connection);
+
+        let inputs: Vec<_> = transactions
+            .iter()
+            .map(|_| JsTracerInputs::default())
+            .collect();
+        let limiter_state = Arc::new(TraceLimiterState::default());
+        let txs = transactions
+            .iter()
+            .cloned()
+            .zip(&inputs)
+            .map(|(tx, inputs)| {
+                let mut tracers = inputs.api_tracers(limiter_state.clone());
+                tracers.extend(Self::trace_limiter(limits, &limiter_state));
+                (tx, tracers)
+            })
+            .collect();
+        let results = self
+            .replay_miniblock(miniblock_number, txs, METHOD_NAME)
+            .await?;
+        if let Some(exceeded) = limiter_state.exceeded_limit() {
+            return Err(exceeded.into());
+        }
+
+        let mut traces = Vec::with_capacity(transactions.len());
+        for ((tx, result), inputs) in transactions.into_iter().zip(results).zip(inputs) {
+            let tracer_context =
+                JsTracerContext::new(&tx, &result).with_location(miniblock_number, tx.hash());
+            let trace =
+                run_js_tracer(code.to_owned(), inputs, tracer_context, limits.timeout).await?;
+            traces.push(ResultDebugCall {
+                result: DebugTrace::Custom(trace),
+            });
+        }
+        Ok(traces)
+    }
+
+    /// Returns call traces for all transactions in a miniblock. If traces are not stored in the DB
+    /// and block re-execution is enabled, they are reconstructed by re-executing the miniblock.
+    /// The memory limit from `limits` applies to all returned traces.
+    pub(super) async fn miniblock_call_traces(
+        &self,
+        miniblock_number: MiniblockNumber,
+        method_name: &'static str,
+        limits: TraceLimits,
+    ) -> Result<Vec<(H256, Call)>, Web3Error> {
+        let block_id = BlockId::Number(BlockNumber::Number(miniblock_number.0.into()));
+        let mut connection = self
+            .read_pool(block_id)
+            .access_storage_tagged("api")
+            .await
+            .unwrap();
+        let call_traces = connection
+            .blocks_web3_dal()
+            .get_trace_for_miniblock(miniblock_number)
+            .await
+            .map_err(|err| internal_error(method_name, err))?;
+        drop(connection);
+
+        if call_traces.is_empty() && self.trace_block_reexecution_enabled {
+            self.reexecute_miniblock_call_traces(miniblock_number, limits)
+                .await
+        } else {
+            check_call_traces_memory(call_traces.iter().map(|(_, call)| call), limits)?;
+            Ok(call_traces)
+        }
+    }
+
+    /// Executes a call on top of the specified block with `CallTracer` and returns the resulting call tree.
+    /// Unlike `debug_traceCall`, VM halts are not treated as errors; they are recorded in the top-level call.
+    pub(super) async fn execute_call_tree(
+        &self,
+        request: CallRequest,
+        block_args: BlockArgs,
+    ) -> Result<Call, Web3Error> {
+        let tx = L2Tx::from_request(request.into(), USED_BOOTLOADER_MEMORY_BYTES)?;
+        let vm_permit = self.vm_concurrency_limiter.acquire_heavy().await;
+        let vm_permit = vm_permit.ok_or(Web3Error::InternalError)?;
+
+        let call_tracer_result = Arc::new(OnceCell::default());
+        let custom_tracers = vec![ApiTracer::CallTracer(call_tracer_result.clone(), None)];
+        let result = execute_tx_eth_call(
+            vm_permit,
+            self.shared_args(&ExecutionOverrides::default()),
+            self.connection_pool.clone(),
+            tx.clone(),
+            block_args,
+            self.vm_execution_cache_misses_limit,
+            None,
+            vec![],
+            custom_tracers,
+        )
+        .await;
+
+        let (output, revert_reason, error) = match result.result {
+            ExecutionResult::Success { output } => (output, None, None),
+            ExecutionResult::Revert { output } => (vec![], Some(output.to_string()), None),
+            ExecutionResult::Halt { reason } => (vec![], None, Some(reason.to_string())),
+        };
+        let calls = Arc::try_unwrap(call_tracer_result)
+            .unwrap()
+            .take()
+            .unwrap_or_default();
+        let mut call = Call::new_high_level(
+            tx.common_data.fee.gas_limit.as_u32(),
+            result.statistics.gas_used,
+            tx.execute.value,
+            tx.execute.calldata,
+            output,
+            revert_reason,
+            calls,
+        );
+        call.error = error;
+        Ok(call)
+    }
+
+    /// Loads a stored transaction together with all transactions preceding it in the same miniblock,
+    /// so that it can be replayed. The traced transaction is the last one in the returned list.
+    async fn load_replayed_txs(
+        &self,
+        tx_hash: H256,
+        method_name: &'static str,
+    ) -> Result<Option<(MiniblockNumber, Vec<Transaction>)>, Web3Error> {
+        let mut connection = self
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap();
+        let miniblock_number = connection
+            .transactions_web3_dal()
+            .get_transaction(TransactionId::Hash(tx_hash), self.chain_id)
+            .await
+            .map_err(|err| internal_error(method_name, err))?
+            .and_then(|tx| tx.block_number);
+        let Some(miniblock_number) = miniblock_number else {
+            return Ok(None);
+        };
+        let miniblock_number = MiniblockNumber(miniblock_number.as_u32());
+
+        let mut transactions = connection
+            .transactions_web3_dal()
+            .get_raw_miniblock_transactions(miniblock_number)
+            .await
+            .map_err(|err| internal_error(method_name, err))?;
+        let Some(tx_index) = transactions.iter().position(|tx| tx.hash() == tx_hash) else {
+            return Ok(None);
+        };
+        transactions.truncate(tx_index + 1);
+        Ok(Some((miniblock_number, transactions)))
+    }
+
+    /// Re-executes all transactions in a miniblock with `CallTracer` to reconstruct call traces missing in the DB
+    /// (e.g., for miniblocks sealed with call trace storage disabled).
+    async fn reexecute_miniblock_call_traces(
+        &self,
+        miniblock_number: MiniblockNumber,
+        limits: TraceLimits,
+    ) -> Result<Vec<(H256, Call)>, Web3Error> {
+        const METHOD_NAME: &str = "debug_trace_block";
+
+        let mut connection = self
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap();
+        let transactions = connection
+            .transactions_web3_dal()
+            .get_raw_miniblock_transactions(miniblock_number)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        drop(connection);
+
+        let call_tracer_results: Vec<_> = transactions
+            .iter()
+            .map(|_| Arc::new(OnceCell::default()))
+            .collect();
+        let limiter_state = Arc::new(TraceLimiterState::default());
+        let txs = transactions
+            .iter()
+            .cloned()
+            .zip(&call_tracer_results)
+            .map(|(tx, result)| {
+                let call_tracer =
+                    ApiTracer::CallTracer(result.clone(), Some(limiter_state.clone()));
+                let mut tracers = vec![call_tracer];
+                tracers.extend(Self::trace_limiter(limits, &limiter_state));
+                (tx, tracers)
+            })
+            .collect();
+        let execution_results = self
+            .replay_miniblock(miniblock_number, txs, METHOD_NAME)
+            .await?;
+        if let Some(exceeded) = limiter_state.exceeded_limit() {
+            return Err(exceeded.into());
+        }
+
+        let traces = transactions
+            .into_iter()
+            .zip(execution_results)
+            .zip(call_tracer_results)
+            .map(|((tx, execution_result), call_tracer_result)| {
+                let calls = Arc::try_unwrap(call_tracer_result)
+                    .unwrap()
+                    .take()
+                    .unwrap_or_default();
+                let (output, revert_reason) = match execution_result.result {
+                    ExecutionResult::Success { output } => (output, None),
+                    ExecutionResult::Revert { output } => (vec![], Some(output.to_string())),
+                    ExecutionResult::Halt { reason } => (vec![], Some(reason.to_string())),
+                };
+                let tx_hash = tx.hash();
+                let call = Call::new_high_level(
+                    tx.gas_limit().as_u32(),
+                    execution_result.statistics.gas_used,
+                    tx.execute.value,
+                    tx.execute.calldata,
+                    output,
+                    revert_reason,
+                    calls,
+                );
+                (tx_hash, call)
+            })
+            .collect();
+        Ok(traces)
+    }
+
+    /// Re-executes a prefix of transactions from a sealed miniblock with the specified tracers.
+    async fn replay_miniblock(
+        &self,
+        miniblock_number: MiniblockNumber,
+        txs: Vec<(Transaction, Vec<ApiTracer>)>,
+        method_name: &'static str,
+    ) -> Result<Vec<VmExecutionResultAndLogs>, Web3Error> {
+        let mut connection = self
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap();
+        let miniblock_header = connection
+            .blocks_dal()
+            .get_miniblock_header(miniblock_number)
+            .await
+            .map_err(|err| internal_error(method_name, err))?
+            .ok_or(Web3Error::NoBlock)?;
+        let block_args = BlockArgs::for_replay(&mut connection, miniblock_number)
+            .await
+            .map_err(|err| internal_error(method_name, err))?
+            .ok_or(Web3Error::NoBlock)?;
+        let l1_batch_number = connection
+            .blocks_web3_dal()
+            .get_l1_batch_number_of_miniblock(miniblock_number)
+            .await
+            .map_err(|err| internal_error(method_name, err))?;
+        let fee_account = if let Some(l1_batch_number) = l1_batch_number {
+            connection
+                .blocks_dal()
+                .get_fee_address_for_l1_batch(l1_batch_number)
+                .await
+                .map_err(|err| internal_error(method_name, err))?
+        } else {
+            None
+        };
+        drop(connection);
+
+        // Transactions are replayed with the fee input and the operator account they were originally
+        // executed with. If the L1 batch isn't sealed yet, it's being produced by the current operator.
+        let overrides = ExecutionOverrides {
+            fee_account,
+            ..ExecutionOverrides::default()
+        };
+        let shared_args = TxSharedArgs {
+            fee_input: miniblock_header.batch_fee_input,
+            ..self.shared_args(&overrides)
+        };
+        let vm_permit = self.vm_concurrency_limiter.acquire_heavy().await;
+        let vm_permit = vm_permit.ok_or(Web3Error::InternalError)?;
+        Ok(replay_txs_in_sandbox(
+            vm_permit,
+            shared_args,
+            self.connection_pool.clone(),
+            block_args,
+            miniblock_header.base_fee_per_gas,
+            txs,
+        )
+        .await)
+    }
+
+    /// Returns the requested built-in tracer together with its config. If no tracer is specified,
+    /// `callTracer` is used. Custom JavaScript tracers must be handled separately (see [`Self::js_tracer_code()`]).
+    fn parse_options(options: Option<TracerConfig>) -> (SupportedTracers, CallTracerConfig) {
+        options.map_or_else(
+            || (SupportedTracers::CallTracer, CallTracerConfig::default()),
+            |options| {
+                let tracer = match options.tracer {
+                    Some(TracerSpec::BuiltIn(tracer)) => tracer,
+                    None | Some(TracerSpec::JavaScript(_)) => SupportedTracers::CallTracer,
+                };
+                (tracer, options.tracer_config)
+            },
+        )
+    }
+
+    /// Checks that the requested tracer is either supported or is JavaScript tracer code.
+    fn validate_tracer(options: &TracerConfig) -> Result<(), Web3Error> {
+        let name = options
+            .tracer
+            .as_ref()
+            .and_then(TracerSpec::unsupported_tracer_name);
+        if let Some(name) = name {
+            let supported_tracers = SupportedTracers::NAMES.join(", ");
+            let message = format!(
+                "unsupported tracer `{name}`; supported tracers are: {supported_tracers}, \
+                 or JavaScript tracer code"
+            );
+            return Err(Web3Error::InvalidTracerConfig(message));
+        }
+        Ok(())
+    }
+
+    /// Returns the code of the requested custom JavaScript tracer, if any.
+    fn js_tracer_code(options: Option<&TracerConfig>) -> Option<String> {
+        match &options?.tracer {
+            Some(TracerSpec::JavaScript(code)) => Some(code.clone()),
+            _ => None,
+        }
+    }
+
+    /// Parses limits specified in tracer options. JS tracers always get server-side limits applied
+    /// (see [`js_tracer_limits()`]).
+    fn parse_trace_limits(options: &TracerConfig) -> Result<TraceLimits, Web3Error> {
+        let timeout = options
+            .timeout
+            .as_deref()
+            .map(parse_go_duration)
+            .transpose();
+        let limits = TraceLimits {
+            timeout: timeout.map_err(Web3Error::InvalidTracerConfig)?,
+            memory_limit: options.memory_limit,
+        };
+        Ok(match &options.tracer {
+            Some(TracerSpec::JavaScript(_)) => js_tracer_limits(limits),
+            _ => limits,
+        })
     }
 
-    fn shared_args(&self) -> TxSharedArgs {
+    /// Returns a tracer enforcing the specified limits during VM execution, or `None` if there are no limits.
+    fn trace_limiter(
+        limits: TraceLimits,
+        limiter_state: &Arc<TraceLimiterState>,
+    ) -> Option<ApiTracer> {
+        (limits != TraceLimits::default())
+            .then(|| ApiTracer::TraceLimiter(limits, limiter_state.clone()))
+    }
+
+    fn map_call(call: Call, only_top_call: bool) -> DebugCall {
+        let mut result: DebugCall = call.into();
+        if only_top_call {
+            result.calls = vec![];
+        }
+        result
+    }
+
+    /// Same as [`Self::map_call()`], but also attaches events and L2-to-L1 logs emitted by the transaction
+    /// to the call frames that have emitted them.
+    fn map_call_with_logs(call: Call, only_top_call: bool, events: &[VmEvent]) -> DebugCall {
+        let mut result: DebugCall = call.into();
+        result.attach_logs(events);
+        if only_top_call {
+            result.calls = vec![];
+        }
+        result
+    }
+
+    /// Loads events emitted by a stored transaction in the emission order.
+    async fn stored_tx_events(
+        connection: &mut StorageProcessor<'_>,
+        tx_hash: H256,
+    ) -> anyhow::Result<Vec<VmEvent>> {
+        let receipt = connection
+            .transactions_web3_dal()
+            .get_transaction_receipt(tx_hash)
+            .await?;
+        let logs = receipt.map(|receipt| receipt.logs).unwrap_or_default();
+        let events = logs.into_iter().map(|log| VmEvent {
+            address: log.address,
+            indexed_topics: log.topics,
+            value: log.data.0,
+            ..VmEvent::default()
+        });
+        Ok(events.collect())
+    }
+
+    /// Aggregates function selectors of the call and all nested calls in the `4byteTracer` format.
+    fn four_byte_trace(call: &Call) -> FourByteTrace {
+        fn collect(call: &Call, trace: &mut FourByteTrace) {
+            let selector = &call.input[..call.input.len().min(4)];
+            record_four_byte_call(trace, call.from, call.to, selector, call.input.len());
+            for call in &call.calls {
+                collect(call, trace);
+            }
+        }
+
+        let mut trace = FourByteTrace::new();
+        collect(call, &mut trace);
+        trace
+    }
+
+    /// Collects addresses of all accounts participating in the call, including nested calls.
+    fn touched_accounts(call: &Call) -> BTreeSet<Address> {
+        fn collect(call: &Call, accounts: &mut BTreeSet<Address>) {
+            accounts.insert(call.from);
+            accounts.insert(call.to);
+            for call in &call.calls {
+                collect(call, accounts);
+            }
+        }
+
+        let mut accounts = BTreeSet::new();
+        collect(call, &mut accounts);
+        accounts
+    }
+
+    /// Builds `prestateTracer` output for a transaction stored in the DB. Since reads are not persisted,
+    /// the storage of each account only includes slots written by the transaction.
+    async fn stored_tx_prestate_trace(
+        connection: &mut StorageProcessor<'_>,
+        tx_hash: H256,
+        call_trace: &Call,
+        diff_mode: bool,
+    ) -> anyhow::Result<DebugTrace> {
+        let writes = connection
+            .storage_logs_dal()
+            .get_tx_storage_writes(tx_hash)
+            .await?;
+        let (
+            Some(&(_, miniblock_number, first_operation_number)),
+            Some(&(.., last_operation_number)),
+        ) = (writes.first(), writes.last())
+        else {
+            return Ok(if diff_mode {
+                DebugTrace::PrestateDiff(PrestateDiff::default())
+            } else {
+                DebugTrace::Prestate(PrestateTrace::new())
+            });
+        };
+
+        let accounts = Self::touched_accounts(call_trace);
+        let touched_slots: BTreeSet<_> = writes.into_iter().map(|(key, ..)| key).collect();
+        let snapshot = PrestateSnapshot::BeforeOperation(miniblock_number, first_operation_number);
+        let no_overlay = StateOverlay::default();
+        let prestate =
+            Self::load_prestate(connection, &accounts, &touched_slots, snapshot, &no_overlay)
+                .await?;
+        if !diff_mode {
+            return Ok(DebugTrace::Prestate(prestate));
+        }
+
+        let snapshot =
+            PrestateSnapshot::BeforeOperation(miniblock_number, last_operation_number + 1);
+        let post_state =
+            Self::load_prestate(connection, &accounts, &touched_slots, snapshot, &no_overlay)
+                .await?;
+        Ok(DebugTrace::PrestateDiff(diff_prestate(
+            prestate, post_state,
+        )))
+    }
+
+    /// Loads the state of the specified accounts and their touched slots from the `snapshot`, with `overlay`
+    /// applied on top of it.
+    async fn load_prestate(
+        connection: &mut StorageProcessor<'_>,
+        accounts: &BTreeSet<Address>,
+        touched_slots: &BTreeSet<StorageKey>,
+        snapshot: PrestateSnapshot,
+        overlay: &StateOverlay,
+    ) -> anyhow::Result<PrestateTrace> {
+        let touched_slots = touched_slots
+            .iter()
+            .filter(|key| accounts.contains(key.address()));
+        let account_keys = accounts.iter().flat_map(|address| {
+            [
+                storage_key_for_eth_balance(address),
+                get_nonce_key(address),
+                get_code_key(address),
+            ]
+        });
+        let hashed_keys: Vec<_> = account_keys
+            .chain(touched_slots.clone().copied())
+            .map(|key| key.hashed_key())
+            .filter(|hashed_key| !overlay.values.contains_key(hashed_key))
+            .collect();
+
+        let values = match snapshot {
+            PrestateSnapshot::Miniblock(miniblock_number) => {
+                connection
+                    .storage_logs_dal()
+                    .get_storage_values(&hashed_keys, miniblock_number)
+                    .await
+            }
+            PrestateSnapshot::BeforeOperation(miniblock_number, operation_number) => {
+                connection
+                    .storage_logs_dal()
+                    .get_storage_values_before_operation(
+                        &hashed_keys,
+                        miniblock_number,
+                        operation_number,
+                    )
+                    .await?
+            }
+        };
+        let value_of = |key: &StorageKey| {
+            let hashed_key = key.hashed_key();
+            if let Some(value) = overlay.values.get(&hashed_key) {
+                return *value;
+            }
+            values
+                .get(&hashed_key)
+                .copied()
+                .flatten()
+                .unwrap_or_default()
+        };
+
+        let mut prestate = PrestateTrace::new();
+        for &address in accounts {
+            let balance = h256_to_u256(value_of(&storage_key_for_eth_balance(&address)));
+            let (nonce, _) = decompose_full_nonce(h256_to_u256(value_of(&get_nonce_key(&address))));
+            let code_hash = value_of(&get_code_key(&address));
+            let code = if code_hash == H256::zero() {
+                None
+            } else if let Some(code) = overlay.factory_deps.get(&code_hash) {
+                Some(code.clone())
+            } else {
+                connection.storage_dal().get_factory_dep(code_hash).await
+            };
+            let account = PrestateAccount {
+                balance: Some(balance),
+                nonce: Some(nonce.as_u64()),
+                code: code.map(Bytes::from),
+                storage: BTreeMap::new(),
+            };
+            prestate.insert(address, account);
+        }
+        for slot in touched_slots {
+            if let Some(account) = prestate.get_mut(slot.address()) {
+                account.storage.insert(*slot.key(), value_of(slot));
+            }
+        }
+        Ok(prestate)
+    }
+
+    /// Returns arguments for sandboxed execution. Unless overridden, the fee input and the operator account
+    /// are the same as used by `eth_call`.
+    fn shared_args(&self, overrides: &ExecutionOverrides) -> TxSharedArgs {
+        let fee_input = self.batch_fee_input_provider.get_batch_fee_input();
+        let operator_account = overrides.fee_account.unwrap_or(self.operator_account);
         TxSharedArgs {
-            operator_account: AccountTreeId::default(),
-            fee_input: BatchFeeInput::l1_pegged(100_000, self.fair_l2_gas_price),
+            operator_account: AccountTreeId::new(operator_account),
+            fee_input: overrides.apply_to_fee_input(fee_input),
             base_system_contracts: self.api_contracts.eth_call.clone(),
             caches: self.storage_caches.clone(),
             validation_computational_gas_limit: BLOCK_GAS_LIMIT,
+            call_gas_limit: self.call_gas_limit,
             chain_id: self.chain_id,
         }
     }
 }
+
+/// Computes `prestateTracer` output in the diff mode from the full state of touched accounts before
+/// and after a transaction. Unchanged accounts and slots are omitted from both maps; `post` additionally omits
+/// unchanged account fields.
+fn diff_prestate(pre: PrestateTrace, mut post: PrestateTrace) -> PrestateDiff {
+    let mut diff = PrestateDiff::default();
+    for (address, mut pre_account) in pre {
+        let post_account = post.remove(&address).unwrap_or_default();
+        let changed_storage: BTreeMap<_, _> = post_account
+            .storage
+            .into_iter()
+            .filter(|(slot, value)| pre_account.storage.get(slot) != Some(value))
+            .collect();
+        let changed_account = PrestateAccount {
+            balance: post_account
+                .balance
+                .filter(|balance| pre_account.balance != Some(*balance)),
+            nonce: post_account
+                .nonce
+                .filter(|nonce| pre_account.nonce != Some(*nonce)),
+            code: post_account
+                .code
+                .filter(|code| pre_account.code.as_ref() != Some(code)),
+            storage: changed_storage,
+        };
+        if changed_account == PrestateAccount::default() {
+            continue;
+        }
+
+        pre_account
+            .storage
+            .retain(|slot, _| changed_account.storage.contains_key(slot));
+        diff.pre.insert(address, pre_account);
+        diff.post.insert(address, changed_account);
+    }
+    diff
+}
+
+/// Gas breakdown of a transaction executed in the sandbox.
+fn gas_breakdown(result: &VmExecutionResultAndLogs) -> DebugCallGasBreakdown {
+    DebugCallGasBreakdown {
+        gas_refunded: result.refunds.gas_refunded.into(),
+        computational_gas_used: result.statistics.computational_gas_used.into(),
+        pubdata_published: result.statistics.pubdata_published.into(),
+    }
+}
+
+/// Output of `noopTracer`, which is an empty object (same as in Geth).
+fn noop_trace() -> DebugTrace {
+    DebugTrace::Custom(serde_json::Value::Object(serde_json::Map::new()))
+}
+
+/// Divergences between stored transaction data and its re-execution collected by `debug_replayTransaction`.
+#[derive(Debug, Default)]
+struct Divergences(Vec<ReplayDivergence>);
+
+impl Divergences {
+    fn compare<T: PartialEq + Serialize>(&mut self, path: &str, stored: &T, replayed: &T) {
+        if stored != replayed {
+            self.0.push(ReplayDivergence {
+                path: path.to_owned(),
+                stored: serde_json::to_value(stored).expect("failed serializing stored value"),
+                replayed: serde_json::to_value(replayed)
+                    .expect("failed serializing replayed value"),
+            });
+        }
+    }
+
+    /// Compares call trees frame by frame. If frames have a different number of subcalls, subcalls
+    /// are compared pairwise by their index.
+    fn compare_calls(&mut self, path: &str, stored: &Call, replayed: &Call) {
+        self.compare(&format!("{path}.type"), &stored.r#type, &replayed.r#type);
+        self.compare(&format!("{path}.from"), &stored.from, &replayed.from);
+        self.compare(&format!("{path}.to"), &stored.to, &replayed.to);
+        self.compare(&format!("{path}.gas"), &stored.gas, &replayed.gas);
+        self.compare(
+            &format!("{path}.gasUsed"),
+            &stored.gas_used,
+            &replayed.gas_used,
+        );
+        self.compare(&format!("{path}.value"), &stored.value, &replayed.value);
+        self.compare(&format!("{path}.input"), &stored.input, &replayed.input);
+        self.compare(&format!("{path}.output"), &stored.output, &replayed.output);
+        self.compare(&format!("{path}.error"), &stored.error, &replayed.error);
+        self.compare(
+            &format!("{path}.revertReason"),
+            &stored.revert_reason,
+            &replayed.revert_reason,
+        );
+        self.compare(
+            &format!("{path}.calls.length"),
+            &stored.calls.len(),
+            &replayed.calls.len(),
+        );
+        for (i, (stored, replayed)) in stored.calls.iter().zip(&replayed.calls).enumerate() {
+            self.compare_calls(&format!("{path}.calls[{i}]"), stored, replayed);
+        }
+    }
+}
+
+/// Collects state changes produced by a call executed in the sandbox from its storage logs. Balance changes
+/// are only reported for the specified accounts, since balance slots cannot be mapped back to addresses.
+/// Bytecodes of deployed contracts are only filled from `factory_deps`.
+fn call_state_diff(
+    storage_logs: &[StorageLogQuery],
+    accounts: &BTreeSet<Address>,
+    factory_deps: &[Vec<u8>],
+) -> CallStateDiff {
+    let mut storage = BTreeMap::<Address, BTreeMap<H256, ValueChange<H256>>>::new();
+    for log in storage_logs {
+        let LogQuery {
+            address,
+            key,
+            read_value,
+            written_value,
+            rw_flag,
+            rollback,
+            ..
+        } = log.log_query;
+        if !rw_flag {
+            continue;
+        }
+        // Rollback queries restore the value read by the original write.
+        let value = u256_to_h256(if rollback { read_value } else { written_value });
+        storage
+            .entry(address)
+            .or_default()
+            .entry(u256_to_h256(key))
+            .and_modify(|change| change.to = value)
+            .or_insert(ValueChange {
+                from: u256_to_h256(read_value),
+                to: value,
+            });
+    }
+    for slots in storage.values_mut() {
+        slots.retain(|_, change| change.from != change.to);
+    }
+    storage.retain(|_, slots| !slots.is_empty());
+
+    let balances = accounts
+        .iter()
+        .filter_map(|&address| {
+            let balance_key = storage_key_for_eth_balance(&address);
+            let change = storage.get(balance_key.address())?.get(balance_key.key())?;
+            let change = ValueChange {
+                from: h256_to_u256(change.from),
+                to: h256_to_u256(change.to),
+            };
+            Some((address, change))
+        })
+        .collect();
+
+    let factory_deps: HashMap<_, _> = factory_deps
+        .iter()
+        .map(|bytecode| (hash_bytecode(bytecode), bytecode))
+        .collect();
+    let deployed_contracts = storage
+        .get(&ACCOUNT_CODE_STORAGE_ADDRESS)
+        .into_iter()
+        .flatten()
+        .filter(|(_, change)| change.from == H256::zero())
+        .map(|(key, change)| {
+            let code = factory_deps
+                .get(&change.to)
+                .map(|&code| code.clone().into());
+            let contract = DeployedBytecode {
+                code_hash: change.to,
+                code,
+            };
+            (h256_to_account_address(key), contract)
+        })
+        .collect();
+
+    CallStateDiff {
+        storage,
+        balances,
+        deployed_contracts,
+    }
+}
+
+/// Runs a tracing future, failing it if it doesn't complete within the timeout from `limits`. VM execution
+/// must additionally be bounded by a `TraceLimiter`, since the sandbox runs on a blocking thread that isn't cancelled
+/// when the future is dropped.
+async fn with_trace_timeout<T>(
+    limits: TraceLimits,
+    future: impl Future<Output = Result<T, Web3Error>>,
+) -> Result<T, Web3Error> {
+    let Some(timeout) = limits.timeout else {
+        return future.await;
+    };
+    tokio::time::timeout(timeout, future)
+        .await
+        .unwrap_or_else(|_| Err(TraceLimitExceeded::Timeout(timeout).into()))
+}
+
+/// Approximate size of a call tree in bytes, estimated in the same way as by `CallTracer`.
+fn call_trace_size(call: &Call) -> usize {
+    let heap_size = call.input.len()
+        + call.output.len()
+        + call.error.as_ref().map_or(0, String::len)
+        + call.revert_reason.as_ref().map_or(0, String::len);
+    let nested_size: usize = call.calls.iter().map(call_trace_size).sum();
+    mem::size_of::<Call>() + heap_size + nested_size
+}
+
+/// Checks that stored call traces returned without VM execution fit into the memory limit.
+fn check_call_traces_memory<'a>(
+    calls: impl IntoIterator<Item = &'a Call>,
+    limits: TraceLimits,
+) -> Result<(), Web3Error> {
+    let Some(memory_limit) = limits.memory_limit else {
+        return Ok(());
+    };
+    let size: usize = calls.into_iter().map(call_trace_size).sum();
+    if size > memory_limit {
+        return Err(TraceLimitExceeded::Memory(memory_limit).into());
+    }
+    Ok(())
+}
+
+/// Parses a duration in the Go format used by Geth for tracer timeouts, e.g. `"300ms"` or `"1m30.5s"`.
+fn parse_go_duration(s: &str) -> Result<Duration, String> {
+    let err = || format!("invalid duration `{s}`");
+    if s == "0" {
+        return Ok(Duration::ZERO);
+    }
+    if s.is_empty() {
+        return Err(err());
+    }
+
+    let mut rest = s;
+    let mut duration = Duration::ZERO;
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|ch: char| !ch.is_ascii_digit() && ch != '.')
+            .unwrap_or(rest.len());
+        let (number, tail) = rest.split_at(number_len);
+        let number: f64 = number.parse().map_err(|_| err())?;
+        let unit_len = tail
+            .find(|ch: char| ch.is_ascii_digit() || ch == '.')
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_len);
+        let unit_nanos = match unit {
+            "ns" => 1.0,
+            "us" | "\u{b5}s" | "\u{3bc}s" => 1e3,
+            "ms" => 1e6,
+            "s" => 1e9,
+            "m" => 60e9,
+            "h" => 3_600e9,
+            _ => return Err(err()),
+        };
+        // Computing in nanoseconds avoids rounding errors for sub-second units (e.g., `1e-6 * 10`).
+        let nanos = (number * unit_nanos).round();
+        if !(nanos.is_finite() && nanos < u64::MAX as f64) {
+            return Err(err());
+        }
+        duration = duration
+            .checked_add(Duration::from_nanos(nanos as u64))
+            .ok_or_else(err)?;
+        rest = tail;
+    }
+    Ok(duration)
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use super::*;
+
+    #[test]
+    fn parsing_go_durations() {
+        assert_eq!(parse_go_duration("0").unwrap(), Duration::ZERO);
+        assert_eq!(
+            parse_go_duration("300ms").unwrap(),
+            Duration::from_millis(300)
+        );
+        assert_eq!(parse_go_duration("5s").unwrap(), Duration::from_secs(5));
+        assert_eq!(
+            parse_go_duration("1.5s").unwrap(),
+            Duration::from_millis(1_500)
+        );
+        assert_eq!(
+            parse_go_duration("10us").unwrap(),
+            Duration::from_micros(10)
+        );
+        assert_eq!(
+            parse_go_duration("10\u{b5}s").unwrap(),
+            Duration::from_micros(10)
+        );
+        assert_eq!(
+            parse_go_duration("100ns").unwrap(),
+            Duration::from_nanos(100)
+        );
+        assert_eq!(parse_go_duration("2h").unwrap(), Duration::from_secs(7_200));
+    }
+
+    #[test]
+    fn parsing_composite_go_durations() {
+        assert_eq!(parse_go_duration("1m30s").unwrap(), Duration::from_secs(90));
+        assert_eq!(
+            parse_go_duration("1h2m3s").unwrap(),
+            Duration::from_secs(3_600 + 120 + 3)
+        );
+        assert_eq!(
+            parse_go_duration("1m30.5s").unwrap(),
+            Duration::from_millis(90_500)
+        );
+        assert_eq!(
+            parse_go_duration("1s500ms").unwrap(),
+            Duration::from_millis(1_500)
+        );
+    }
+
+    #[test]
+    fn rejecting_invalid_go_durations() {
+        for input in ["", "1", "s", "1x", "1.2.3s", "-1s", "1 s", "1m30", "ms1"] {
+            assert!(parse_go_duration(input).is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn checking_stored_trace_memory() {
+        let call = Call {
+            input: vec![0; 100],
+            calls: vec![Call::default()],
+            ..Call::default()
+        };
+        let size = call_trace_size(&call);
+        assert_eq!(size, 2 * mem::size_of::<Call>() + 100);
+
+        let limits = TraceLimits {
+            memory_limit: Some(size),
+            ..TraceLimits::default()
+        };
+        check_call_traces_memory([&call], limits).unwrap();
+        let err = check_call_traces_memory([&call, &call], limits).unwrap_err();
+        assert_matches!(
+            err,
+            Web3Error::TraceLimitExceeded(TraceLimitExceeded::Memory(limit)) if limit == size
+        );
+    }
+}
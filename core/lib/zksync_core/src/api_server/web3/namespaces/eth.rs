@@ -1,36 +1,89 @@
+use multivm::{interface::ExecutionResult, vm_latest::constants::BLOCK_GAS_LIMIT};
+use zksync_dal::StorageProcessor;
 use zksync_types::{
     api::{
-        BlockId, BlockNumber, GetLogsFilter, Transaction, TransactionId, TransactionReceipt,
-        TransactionVariant,
+        simulate::{SimulatePayload, SimulatedBlock, SimulatedCallError, SimulatedCallResult},
+        AccessListWithGasUsed, AccountProof, BlockId, BlockNumber, GetLogsFilter, StateOverride,
+        StorageProof, Transaction, TransactionId, TransactionReceipt, TransactionVariant,
     },
-    l2::{L2Tx, TransactionType},
+    get_code_key, get_nonce_key,
+    l2::{conditions::TransactionConditions, L2Tx, TransactionType},
     transaction_request::CallRequest,
-    utils::decompose_full_nonce,
+    utils::{decompose_full_nonce, storage_key_for_eth_balance},
     web3,
-    web3::types::{FeeHistory, SyncInfo, SyncState},
+    web3::types::{FeeHistory, SyncState},
     AccountTreeId, Bytes, MiniblockNumber, StorageKey, H256, L2_ETH_TOKEN_ADDRESS,
     MAX_GAS_PER_PUBDATA_BYTE, U256,
 };
-use zksync_utils::u256_to_h256;
+use zksync_utils::{h256_to_u256, u256_to_h256};
 use zksync_web3_decl::{
     error::Web3Error,
-    types::{Address, Block, Filter, FilterChanges, Log, U64},
+    types::{Address, Block, Filter, FilterChanges, GetLogsResponse, Log, LogsPage, U64},
 };
 
 use crate::api_server::{
-    execution_sandbox::BlockArgs,
+    execution_sandbox::{BlockArgs, SandboxBlock, SandboxBlockResult},
+    tree::TreeApiClient,
     web3::{
-        backend_jsonrpsee::internal_error,
+        backend_jsonrpsee::{internal_error, map_tree_api_error},
         metrics::{BlockCallObserver, API_METRICS},
-        resolve_block,
-        state::RpcState,
-        TypedFilter,
+        state::{sync_status, CallCacheKey, CallResultCache, RpcState},
+        validate_state_override, TypedFilter,
     },
 };
 
 pub const EVENT_TOPIC_NUMBER_LIMIT: usize = 4;
 pub const PROTOCOL_VERSION: &str = "zks/1";
 
+/// Position of a log used as a pagination cursor in `eth_getLogs`. Encoded as a hex string
+/// with the miniblock number and the log index in the miniblock.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct LogsCursor {
+    miniblock_number: MiniblockNumber,
+    log_index: u32,
+}
+
+impl LogsCursor {
+    fn for_log(log: &Log) -> Self {
+        Self {
+            miniblock_number: MiniblockNumber(log.block_number.unwrap_or_default().as_u32()),
+            log_index: log.log_index.unwrap_or_default().as_u32(),
+        }
+    }
+
+    fn encode(self) -> String {
+        format!("0x{:08x}{:08x}", self.miniblock_number.0, self.log_index)
+    }
+
+    fn decode(token: &str) -> Option<Self> {
+        let token = token.strip_prefix("0x")?;
+        if token.len() != 16 {
+            return None;
+        }
+        let miniblock_number = u32::from_str_radix(token.get(..8)?, 16).ok()?;
+        let log_index = u32::from_str_radix(token.get(8..)?, 16).ok()?;
+        Some(Self {
+            miniblock_number: MiniblockNumber(miniblock_number),
+            log_index,
+        })
+    }
+}
+
+/// Max number of storage slots from the call access list that are prefetched before executing `eth_call`.
+const MAX_PREFETCHED_SLOTS: usize = 1_024;
+
+/// Returns storage keys for the slots listed in the access list of a call, capped at [`MAX_PREFETCHED_SLOTS`].
+fn access_list_storage_keys(access_list: Option<&web3::types::AccessList>) -> Vec<StorageKey> {
+    let items = access_list.into_iter().flatten();
+    let keys = items.flat_map(|item| {
+        let account = AccountTreeId::new(item.address);
+        item.storage_keys
+            .iter()
+            .map(move |&slot| StorageKey::new(account, slot))
+    });
+    keys.take(MAX_PREFETCHED_SLOTS).collect()
+}
+
 #[derive(Debug)]
 pub struct EthNamespace {
     state: RpcState,
@@ -62,14 +115,18 @@ impl EthNamespace {
         block_number
     }
 
-    #[tracing::instrument(skip(self, request, block_id))]
+    #[tracing::instrument(skip(self, request, block_id, state_override))]
     pub async fn call_impl(
         &self,
         request: CallRequest,
         block_id: Option<BlockId>,
+        state_override: Option<StateOverride>,
     ) -> Result<Bytes, Web3Error> {
         const METHOD_NAME: &str = "call";
 
+        if let Some(state_override) = &state_override {
+            validate_state_override(state_override)?;
+        }
         let block_id = block_id.unwrap_or(BlockId::Number(BlockNumber::Pending));
         let method_latency = API_METRICS.start_block_call(METHOD_NAME, block_id);
         let mut connection = self
@@ -78,38 +135,221 @@ impl EthNamespace {
             .access_storage_tagged("api")
             .await
             .unwrap();
-        let block_args = BlockArgs::new(&mut connection, block_id)
+        let block_args = self
+            .state
+            .start_info
+            .resolve_block_args(&mut connection, block_id, "eth_call")
+            .await?;
+        let cache_key = if state_override.is_none() {
+            self.call_cache_key(&mut connection, &block_args, &request)
+                .await?
+        } else {
+            None
+        };
+        drop(connection);
+
+        let cached_output = cache_key
+            .as_ref()
+            .and_then(|key| self.state.call_result_cache.get(key));
+        if let Some(output) = cached_output {
+            let block_diff = self
+                .state
+                .last_sealed_miniblock
+                .diff_with_block_args(&block_args);
+            method_latency.observe(block_diff);
+            return Ok(output.into());
+        }
+
+        // Storage slots from the access list are loaded in a single query before the execution.
+        let prefetched_keys = access_list_storage_keys(request.access_list.as_ref());
+        let tx = L2Tx::from_request(request.into(), self.state.api_config.max_tx_size)?;
+
+        let call_result = self
+            .state
+            .tx_sender
+            .eth_call(block_args, tx, state_override, prefetched_keys)
+            .await;
+        let res_bytes = call_result.map_err(Web3Error::from)?;
+        if let Some(key) = cache_key {
+            self.state.call_result_cache.insert(key, &res_bytes);
+        }
+
+        let block_diff = self
+            .state
+            .last_sealed_miniblock
+            .diff_with_block_args(&block_args);
+        method_latency.observe(block_diff);
+        Ok(res_bytes.into())
+    }
+
+    /// Returns a key in the `eth_call` result cache if the call is performed against a finalized miniblock.
+    async fn call_cache_key(
+        &self,
+        connection: &mut StorageProcessor<'_>,
+        block_args: &BlockArgs,
+        request: &CallRequest,
+    ) -> Result<Option<CallCacheKey>, Web3Error> {
+        const METHOD_NAME: &str = "call";
+
+        if !self.state.call_result_cache.is_enabled() {
+            return Ok(None);
+        }
+        // The pending miniblock is never finalized, so it doesn't need special handling.
+        let block_number = block_args.resolved_block_number();
+        let finalized_block_number = connection
+            .blocks_web3_dal()
+            .resolve_block_id(BlockId::Number(BlockNumber::Finalized))
             .await
-            .map_err(|err| internal_error("eth_call", err))?
-            .ok_or(Web3Error::NoBlock)?;
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        if finalized_block_number.map_or(true, |finalized| block_number > finalized) {
+            return Ok(None);
+        }
+
+        let block_hash = connection
+            .blocks_web3_dal()
+            .get_miniblock_hash(block_number)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        Ok(block_hash.map(|hash| CallResultCache::key(hash, request)))
+    }
+
+    #[tracing::instrument(skip(self, request, block_id))]
+    pub async fn create_access_list_impl(
+        &self,
+        request: CallRequest,
+        block_id: Option<BlockId>,
+    ) -> Result<AccessListWithGasUsed, Web3Error> {
+        const METHOD_NAME: &str = "create_access_list";
+
+        let block_id = block_id.unwrap_or(BlockId::Number(BlockNumber::Pending));
+        let method_latency = API_METRICS.start_block_call(METHOD_NAME, block_id);
+        let mut connection = self
+            .state
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap();
+        let block_args = self
+            .state
+            .start_info
+            .resolve_block_args(&mut connection, block_id, METHOD_NAME)
+            .await?;
         drop(connection);
 
         let tx = L2Tx::from_request(request.into(), self.state.api_config.max_tx_size)?;
+        let access_list = self
+            .state
+            .tx_sender
+            .create_access_list(block_args, tx, None)
+            .await
+            .map_err(|err| Web3Error::SubmitTransactionError(err.to_string(), err.data()))?;
+
+        let block_diff = self
+            .state
+            .last_sealed_miniblock
+            .diff_with_block_args(&block_args);
+        method_latency.observe(block_diff);
+        Ok(access_list)
+    }
+
+    #[tracing::instrument(skip(self, payload, block_id))]
+    pub async fn simulate_v1_impl(
+        &self,
+        payload: SimulatePayload,
+        block_id: Option<BlockId>,
+    ) -> Result<Vec<SimulatedBlock>, Web3Error> {
+        const METHOD_NAME: &str = "simulate_v1";
 
-        let call_result = self.state.tx_sender.eth_call(block_args, tx).await;
-        let res_bytes = call_result
+        let block_id = block_id.unwrap_or(BlockId::Number(BlockNumber::Pending));
+        let method_latency = API_METRICS.start_block_call(METHOD_NAME, block_id);
+        let mut blocks = Vec::with_capacity(payload.block_state_calls.len());
+        for (i, block) in payload.block_state_calls.into_iter().enumerate() {
+            if block.calls.is_empty() {
+                let message = format!("block #{i} contains no calls");
+                return Err(Web3Error::InvalidSimulatePayload(message));
+            }
+            if let Some(state_override) = &block.state_overrides {
+                validate_state_override(state_override)?;
+                let has_unsupported_overrides = state_override
+                    .values()
+                    .any(|account| account.code.is_some() || account.state.is_some());
+                if i > 0 && has_unsupported_overrides {
+                    let message =
+                        "`code` and `state` overrides are only supported for the first block";
+                    return Err(Web3Error::InvalidSimulatePayload(message.to_owned()));
+                }
+            }
+            let txs = block
+                .calls
+                .into_iter()
+                .map(|request| {
+                    L2Tx::from_request(request.into(), self.state.api_config.max_tx_size)
+                })
+                .collect::<Result<_, _>>()?;
+            blocks.push(SandboxBlock {
+                state_override: block.state_overrides,
+                txs,
+            });
+        }
+
+        let mut connection = self
+            .state
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap();
+        let block_args = self
+            .state
+            .start_info
+            .resolve_block_args(&mut connection, block_id, METHOD_NAME)
+            .await?;
+        drop(connection);
+
+        let tx_hashes: Vec<Vec<_>> = blocks
+            .iter()
+            .map(|block| block.txs.iter().map(L2Tx::hash).collect())
+            .collect();
+        let results = self
+            .state
+            .tx_sender
+            .eth_simulate(block_args, blocks)
+            .await
             .map_err(|err| Web3Error::SubmitTransactionError(err.to_string(), err.data()))?;
+        let blocks = results
+            .into_iter()
+            .zip(tx_hashes)
+            .map(|(block, tx_hashes)| simulated_block(block, &tx_hashes))
+            .collect();
 
         let block_diff = self
             .state
             .last_sealed_miniblock
             .diff_with_block_args(&block_args);
         method_latency.observe(block_diff);
-        Ok(res_bytes.into())
+        Ok(blocks)
     }
 
-    #[tracing::instrument(skip(self, request, _block))]
+    #[tracing::instrument(skip(self, request, block, state_override))]
     pub async fn estimate_gas_impl(
         &self,
         request: CallRequest,
-        _block: Option<BlockNumber>,
+        block: Option<BlockNumber>,
+        state_override: Option<StateOverride>,
     ) -> Result<U256, Web3Error> {
         const METHOD_NAME: &str = "estimate_gas";
 
+        if let Some(state_override) = &state_override {
+            validate_state_override(state_override)?;
+        }
+
         let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let block_args = self
+            .state
+            .resolve_block_args_for_estimation(block, METHOD_NAME)
+            .await?;
         let mut request_with_gas_per_pubdata_overridden = request;
         self.state
-            .set_nonce_for_call_request(&mut request_with_gas_per_pubdata_overridden)
+            .set_nonce_for_call_request(&mut request_with_gas_per_pubdata_overridden, &block_args)
             .await?;
 
         if let Some(ref mut eip712_meta) = request_with_gas_per_pubdata_overridden.eip712_meta {
@@ -147,7 +387,13 @@ impl EthNamespace {
         let fee = self
             .state
             .tx_sender
-            .get_txs_fee_in_wei(tx.into(), scale_factor, acceptable_overestimation)
+            .get_txs_fee_in_wei(
+                tx.into(),
+                block_args,
+                scale_factor,
+                acceptable_overestimation,
+                state_override,
+            )
             .await
             .map_err(|err| Web3Error::SubmitTransactionError(err.to_string(), err.data()))?;
 
@@ -165,6 +411,33 @@ impl EthNamespace {
         Ok(price.into())
     }
 
+    #[tracing::instrument(skip(self))]
+    pub async fn max_priority_fee_per_gas_impl(&self) -> Result<U256, Web3Error> {
+        const METHOD_NAME: &str = "max_priority_fee_per_gas";
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let base_fee = self.state.tx_sender.gas_price().await;
+        let mut connection = self
+            .state
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap();
+        let sealed_miniblock = connection
+            .blocks_web3_dal()
+            .get_sealed_miniblock_number()
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        let fee = self
+            .state
+            .priority_fee_oracle
+            .suggest(&mut connection, sealed_miniblock, base_fee)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        method_latency.observe();
+        Ok(fee)
+    }
+
     #[tracing::instrument(skip(self))]
     pub async fn get_balance_impl(
         &self,
@@ -181,7 +454,11 @@ impl EthNamespace {
             .access_storage_tagged("api")
             .await
             .unwrap();
-        let block_number = resolve_block(&mut connection, block_id, METHOD_NAME).await?;
+        let block_number = self
+            .state
+            .start_info
+            .resolve_block(&mut connection, block_id, METHOD_NAME)
+            .await?;
         let balance = connection
             .storage_web3_dal()
             .standard_token_historical_balance(
@@ -206,24 +483,114 @@ impl EthNamespace {
     }
 
     #[tracing::instrument(skip(self, filter))]
-    pub async fn get_logs_impl(&self, mut filter: Filter) -> Result<Vec<Log>, Web3Error> {
+    pub async fn get_logs_impl(&self, mut filter: Filter) -> Result<GetLogsResponse, Web3Error> {
         const METHOD_NAME: &str = "get_logs";
 
         let method_latency = API_METRICS.start_call(METHOD_NAME);
         self.state.resolve_filter_block_hash(&mut filter).await?;
         let (from_block, to_block) = self.state.resolve_filter_block_range(&filter).await?;
+        let mut connection = self
+            .state
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap();
+        self.state
+            .start_info
+            .ensure_block_not_pruned(&mut connection, from_block, METHOD_NAME)
+            .await?;
+        drop(connection);
 
         filter.to_block = Some(BlockNumber::Number(to_block.0.into()));
+        // Paginated queries are not subject to the block range limit since they don't load the entire range at once.
+        if filter.is_paginated() {
+            let page = self.get_logs_page(&filter, from_block).await?;
+            method_latency.observe();
+            return Ok(GetLogsResponse::Page(page));
+        }
+        if let Some(limit) = self.state.api_config.get_logs_block_range_limit {
+            let range_len = u64::from(to_block.0.saturating_sub(from_block.0)) + 1;
+            if from_block <= to_block && range_len > limit {
+                let suggested_to_block = u64::from(from_block.0) + limit.max(1) - 1;
+                return Err(Web3Error::LogsBlockRangeExceeded(
+                    limit,
+                    from_block.0,
+                    suggested_to_block as u32,
+                ));
+            }
+        }
+
         let changes = self
             .filter_changes(&mut TypedFilter::Events(filter, from_block))
             .await?;
         method_latency.observe();
         Ok(match changes {
-            FilterChanges::Logs(list) => list,
+            FilterChanges::Logs(list) => GetLogsResponse::Logs(list),
             _ => unreachable!("Unexpected `FilterChanges` type, expected `Logs`"),
         })
     }
 
+    /// Returns a page of logs for `eth_getLogs` with pagination. Unlike with non-paginated queries,
+    /// the number of logs in the filter range is not limited.
+    async fn get_logs_page(
+        &self,
+        filter: &Filter,
+        from_block: MiniblockNumber,
+    ) -> Result<LogsPage, Web3Error> {
+        const METHOD_NAME: &str = "get_logs";
+
+        let max_limit = self.state.api_config.get_logs_max_results;
+        let limit = filter.limit.unwrap_or(max_limit);
+        if limit == 0 || limit > max_limit {
+            return Err(Web3Error::InvalidPagination(format!(
+                "`limit` must be in range 1..={max_limit}"
+            )));
+        }
+
+        let start = match &filter.page_token {
+            Some(token) => {
+                let cursor = LogsCursor::decode(token).ok_or_else(|| {
+                    Web3Error::InvalidPagination("malformed `pageToken`".to_owned())
+                })?;
+                if cursor.miniblock_number < from_block {
+                    return Err(Web3Error::InvalidPagination(
+                        "`pageToken` is outside the filter block range".to_owned(),
+                    ));
+                }
+                cursor
+            }
+            None => LogsCursor {
+                miniblock_number: from_block,
+                log_index: 0,
+            },
+        };
+
+        let get_logs_filter = self.get_logs_filter(filter, start.miniblock_number).await?;
+        let to_block = BlockId::Number(BlockNumber::Number(get_logs_filter.to_block.0.into()));
+        let mut storage = self
+            .state
+            .read_pool(to_block)
+            .access_storage_tagged("api")
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        // Request an extra log to determine the start of the next page.
+        let mut logs = storage
+            .events_web3_dal()
+            .get_logs_from_index(get_logs_filter, start.log_index, limit + 1)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        let next_page_token = if logs.len() > limit {
+            let next_log = logs.pop().unwrap();
+            Some(LogsCursor::for_log(&next_log).encode())
+        } else {
+            None
+        };
+        Ok(LogsPage {
+            logs,
+            next_page_token,
+        })
+    }
+
     pub async fn get_filter_logs_impl(&self, idx: U256) -> Result<FilterChanges, Web3Error> {
         const METHOD_NAME: &str = "get_filter_logs";
 
@@ -232,9 +599,8 @@ impl EthNamespace {
         let maybe_filter = self
             .state
             .installed_filters
-            .lock()
-            .await
-            .get_and_update_stats(idx);
+            .get_and_update_stats(idx, METHOD_NAME)
+            .await?;
 
         let Some(TypedFilter::Events(filter, _)) = maybe_filter else {
             return Err(Web3Error::FilterNotFound);
@@ -268,12 +634,13 @@ impl EthNamespace {
         };
         let method_latency = API_METRICS.start_block_call(method_name, block_id);
 
-        let block = self
+        let mut connection = self
             .state
-            .connection_pool
+            .read_pool(block_id)
             .access_storage_tagged("api")
             .await
-            .unwrap()
+            .unwrap();
+        let block = connection
             .blocks_web3_dal()
             .get_block_by_web3_block_id(
                 block_id,
@@ -282,6 +649,15 @@ impl EthNamespace {
             )
             .await
             .map_err(|err| internal_error(method_name, err));
+        // Block headers are retained after pruning, but transactions are not.
+        if let Ok(Some(block)) = &block {
+            let block_number = MiniblockNumber(block.number.as_u32());
+            self.state
+                .start_info
+                .ensure_block_not_pruned(&mut connection, block_number, method_name)
+                .await?;
+        }
+        drop(connection);
 
         if let Ok(Some(block)) = &block {
             let block_number = MiniblockNumber(block.number.as_u32());
@@ -300,16 +676,24 @@ impl EthNamespace {
         const METHOD_NAME: &str = "get_block_transaction_count";
 
         let method_latency = API_METRICS.start_block_call(METHOD_NAME, block_id);
-        let tx_count = self
+        let mut connection = self
             .state
-            .connection_pool
+            .read_pool(block_id)
             .access_storage_tagged("api")
             .await
-            .unwrap()
+            .unwrap();
+        let tx_count = connection
             .blocks_web3_dal()
             .get_block_tx_count(block_id)
             .await
             .map_err(|err| internal_error(METHOD_NAME, err));
+        if let Ok(Some((block_number, _))) = &tx_count {
+            self.state
+                .start_info
+                .ensure_block_not_pruned(&mut connection, *block_number, METHOD_NAME)
+                .await?;
+        }
+        drop(connection);
 
         if let Ok(Some((block_number, _))) = &tx_count {
             self.report_latency_with_block_id(method_latency, *block_number);
@@ -335,7 +719,11 @@ impl EthNamespace {
             .access_storage_tagged("api")
             .await
             .unwrap();
-        let block_number = resolve_block(&mut connection, block_id, METHOD_NAME).await?;
+        let block_number = self
+            .state
+            .start_info
+            .resolve_block(&mut connection, block_id, METHOD_NAME)
+            .await?;
         let contract_code = connection
             .storage_web3_dal()
             .get_contract_code_unchecked(address, block_number)
@@ -369,7 +757,11 @@ impl EthNamespace {
             .access_storage_tagged("api")
             .await
             .unwrap();
-        let block_number = resolve_block(&mut connection, block_id, METHOD_NAME).await?;
+        let block_number = self
+            .state
+            .start_info
+            .resolve_block(&mut connection, block_id, METHOD_NAME)
+            .await?;
         let value = connection
             .storage_web3_dal()
             .get_historical_value_unchecked(&storage_key, block_number)
@@ -380,6 +772,113 @@ impl EthNamespace {
         Ok(value)
     }
 
+    #[tracing::instrument(skip(self))]
+    pub async fn get_proof_impl(
+        &self,
+        address: Address,
+        keys: Vec<H256>,
+        block_id: BlockId,
+    ) -> Result<AccountProof, Web3Error> {
+        const METHOD_NAME: &str = "get_proof";
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let max_keys = self.state.api_config.req_entities_limit;
+        if keys.len() > max_keys {
+            return Err(Web3Error::TooManyProofRequests(max_keys));
+        }
+        let tree_api = self
+            .state
+            .tree_api
+            .as_ref()
+            .ok_or(Web3Error::TreeApiUnavailable)?;
+        let mut connection = self
+            .state
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap();
+        self.state
+            .check_tree_lag(&mut connection, METHOD_NAME)
+            .await?;
+        // Proofs can only be obtained for L1 batches processed by the Merkle tree. Thus, the latest block
+        // is mapped to the latest L1 batch with the computed state root.
+        let l1_batch_number = match block_id {
+            BlockId::Number(
+                BlockNumber::Latest | BlockNumber::Committed | BlockNumber::Pending,
+            ) => connection
+                .blocks_dal()
+                .get_last_l1_batch_number_with_metadata()
+                .await
+                .map_err(|err| internal_error(METHOD_NAME, err))?,
+            _ => {
+                let block_number = self
+                    .state
+                    .start_info
+                    .resolve_block(&mut connection, block_id, METHOD_NAME)
+                    .await?;
+                connection
+                    .blocks_web3_dal()
+                    .get_l1_batch_number_of_miniblock(block_number)
+                    .await
+                    .map_err(|err| internal_error(METHOD_NAME, err))?
+            }
+        };
+        let l1_batch_number = l1_batch_number.ok_or(Web3Error::NoBlock)?;
+        let state_root = connection
+            .blocks_dal()
+            .get_l1_batch_state_root(l1_batch_number)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?
+            .ok_or(Web3Error::NoBlock)?;
+        drop(connection);
+
+        let account_keys = [
+            get_code_key(&address),
+            storage_key_for_eth_balance(&address),
+            get_nonce_key(&address),
+        ];
+        let storage_keys = keys
+            .iter()
+            .map(|key| StorageKey::new(AccountTreeId::new(address), *key));
+        let all_keys: Vec<_> = account_keys.into_iter().chain(storage_keys).collect();
+        let hashed_keys = all_keys.iter().map(StorageKey::hashed_key_u256).collect();
+        let entries = tree_api
+            .get_proofs(l1_batch_number, hashed_keys)
+            .await
+            .map_err(|err| map_tree_api_error(METHOD_NAME, err))?;
+        let mut proofs = entries
+            .into_iter()
+            .zip(&all_keys)
+            .map(|(entry, key)| StorageProof {
+                key: *key.key(),
+                proof: entry.merkle_path,
+                value: entry.value,
+                index: entry.index,
+            });
+        let (Some(code_proof), Some(balance_proof), Some(nonce_proof)) =
+            (proofs.next(), proofs.next(), proofs.next())
+        else {
+            let err = anyhow::anyhow!("tree API returned fewer proofs than requested");
+            return Err(internal_error(METHOD_NAME, err));
+        };
+        let (nonce, _) = decompose_full_nonce(h256_to_u256(nonce_proof.value));
+
+        let proof = AccountProof {
+            address,
+            account_proof: code_proof.proof,
+            balance: h256_to_u256(balance_proof.value),
+            code_hash: code_proof.value,
+            nonce,
+            storage_hash: state_root,
+            storage_proof: proofs.collect(),
+            balance_proof,
+            nonce_proof,
+            l1_batch_number,
+        };
+        method_latency.observe();
+        Ok(proof)
+    }
+
     /// Account nonce.
     #[tracing::instrument(skip(self))]
     pub async fn get_transaction_count_impl(
@@ -411,7 +910,11 @@ impl EthNamespace {
                 (nonce, None)
             }
             _ => {
-                let block_number = resolve_block(&mut connection, block_id, method_name).await?;
+                let block_number = self
+                    .state
+                    .start_info
+                    .resolve_block(&mut connection, block_id, method_name)
+                    .await?;
                 let nonce = connection
                     .storage_web3_dal()
                     .get_address_historical_nonce(address, block_number)
@@ -440,16 +943,24 @@ impl EthNamespace {
         const METHOD_NAME: &str = "get_transaction";
 
         let method_latency = API_METRICS.start_call(METHOD_NAME);
-        let mut transaction = self
-            .state
-            .connection_pool
-            .access_storage_tagged("api")
-            .await
-            .unwrap()
-            .transactions_web3_dal()
-            .get_transaction(id, self.state.api_config.l2_chain_id)
-            .await
-            .map_err(|err| internal_error(METHOD_NAME, err));
+        let pools: Vec<_> = match id {
+            TransactionId::Block(block_id, _) => vec![self.state.read_pool(block_id)],
+            TransactionId::Hash(_) => self.state.tx_read_pools().collect(),
+        };
+        let mut transaction = Ok(None);
+        for pool in pools {
+            transaction = pool
+                .access_storage_tagged("api")
+                .await
+                .unwrap()
+                .transactions_web3_dal()
+                .get_transaction(id, self.state.api_config.l2_chain_id)
+                .await
+                .map_err(|err| internal_error(METHOD_NAME, err));
+            if !matches!(transaction, Ok(None)) {
+                break;
+            }
+        }
 
         if let Some(proxy) = &self.state.tx_sender.0.proxy {
             // We're running an external node - check the proxy cache in
@@ -472,6 +983,23 @@ impl EthNamespace {
                         .map_err(|err| internal_error(METHOD_NAME, err));
                 }
             }
+        } else if let (Ok(None), TransactionId::Hash(hash)) = (&transaction, id) {
+            // Check whether the transaction was dropped from the mempool, so that it can be distinguished
+            // from an unknown transaction.
+            let dropped_reason = self
+                .state
+                .connection_pool
+                .access_storage_tagged("api")
+                .await
+                .unwrap()
+                .transactions_dal()
+                .get_dropped_tx_reason(hash)
+                .await
+                .map_err(|err| internal_error(METHOD_NAME, err))?;
+            if let Some(reason) = dropped_reason {
+                method_latency.observe();
+                return Err(Web3Error::TransactionDropped(reason));
+            }
         }
 
         method_latency.observe();
@@ -486,19 +1014,60 @@ impl EthNamespace {
         const METHOD_NAME: &str = "get_transaction_receipt";
 
         let method_latency = API_METRICS.start_call(METHOD_NAME);
-        let receipt = self
+        let mut receipt = Ok(None);
+        for pool in self.state.tx_read_pools() {
+            receipt = pool
+                .access_storage_tagged("api")
+                .await
+                .unwrap()
+                .transactions_web3_dal()
+                .get_transaction_receipt(hash)
+                .await
+                .map_err(|err| internal_error(METHOD_NAME, err));
+            if !matches!(receipt, Ok(None)) {
+                break;
+            }
+        }
+
+        method_latency.observe();
+        receipt
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_block_receipts_impl(
+        &self,
+        block_id: BlockId,
+    ) -> Result<Option<Vec<TransactionReceipt>>, Web3Error> {
+        const METHOD_NAME: &str = "get_block_receipts";
+
+        let method_latency = API_METRICS.start_block_call(METHOD_NAME, block_id);
+        let mut connection = self
             .state
-            .connection_pool
+            .read_pool(block_id)
             .access_storage_tagged("api")
             .await
-            .unwrap()
+            .unwrap();
+        let block_number = connection
+            .blocks_web3_dal()
+            .resolve_block_id(block_id)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        let Some(block_number) = block_number else {
+            method_latency.observe_without_diff();
+            return Ok(None);
+        };
+        self.state
+            .start_info
+            .ensure_block_not_pruned(&mut connection, block_number, METHOD_NAME)
+            .await?;
+        let receipts = connection
             .transactions_web3_dal()
-            .get_transaction_receipt(hash)
+            .get_miniblock_receipts(block_number)
             .await
-            .map_err(|err| internal_error(METHOD_NAME, err));
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
 
-        method_latency.observe();
-        receipt
+        self.report_latency_with_block_id(method_latency, block_number);
+        Ok(Some(receipts))
     }
 
     #[tracing::instrument(skip(self))]
@@ -522,9 +1091,8 @@ impl EthNamespace {
         let idx = self
             .state
             .installed_filters
-            .lock()
-            .await
-            .add(TypedFilter::Blocks(last_block_number + 1));
+            .add(TypedFilter::Blocks(last_block_number + 1), METHOD_NAME)
+            .await?;
         method_latency.observe();
         Ok(idx)
     }
@@ -545,28 +1113,25 @@ impl EthNamespace {
         let idx = self
             .state
             .installed_filters
-            .lock()
-            .await
-            .add(TypedFilter::Events(filter, from_block));
+            .add(TypedFilter::Events(filter, from_block), METHOD_NAME)
+            .await?;
         method_latency.observe();
         Ok(idx)
     }
 
     #[tracing::instrument(skip(self))]
-    pub async fn new_pending_transaction_filter_impl(&self) -> U256 {
+    pub async fn new_pending_transaction_filter_impl(&self) -> Result<U256, Web3Error> {
         const METHOD_NAME: &str = "new_pending_transaction_filter";
 
         let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let filter = TypedFilter::PendingTransactions(chrono::Utc::now().naive_utc());
         let idx = self
             .state
             .installed_filters
-            .lock()
-            .await
-            .add(TypedFilter::PendingTransactions(
-                chrono::Utc::now().naive_utc(),
-            ));
+            .add(filter, METHOD_NAME)
+            .await?;
         method_latency.observe();
-        idx
+        Ok(idx)
     }
 
     #[tracing::instrument(skip(self))]
@@ -577,23 +1142,24 @@ impl EthNamespace {
         let mut filter = self
             .state
             .installed_filters
-            .lock()
-            .await
-            .get_and_update_stats(idx)
+            .get_and_update_stats(idx, METHOD_NAME)
+            .await?
             .ok_or(Web3Error::FilterNotFound)?;
 
         let result = match self.filter_changes(&mut filter).await {
             Ok(changes) => {
                 self.state
                     .installed_filters
-                    .lock()
-                    .await
-                    .update(idx, filter);
+                    .update(idx, filter, METHOD_NAME)
+                    .await?;
                 Ok(changes)
             }
             Err(Web3Error::LogsLimitExceeded(..)) => {
                 // The filter was not being polled for a long time, so we remove it.
-                self.state.installed_filters.lock().await.remove(idx);
+                self.state
+                    .installed_filters
+                    .remove(idx, METHOD_NAME)
+                    .await?;
                 Err(Web3Error::FilterNotFound)
             }
             Err(err) => Err(err),
@@ -603,13 +1169,17 @@ impl EthNamespace {
     }
 
     #[tracing::instrument(skip(self))]
-    pub async fn uninstall_filter_impl(&self, idx: U256) -> bool {
+    pub async fn uninstall_filter_impl(&self, idx: U256) -> Result<bool, Web3Error> {
         const METHOD_NAME: &str = "uninstall_filter";
 
         let method_latency = API_METRICS.start_call(METHOD_NAME);
-        let removed = self.state.installed_filters.lock().await.remove(idx);
+        let removed = self
+            .state
+            .installed_filters
+            .remove(idx, METHOD_NAME)
+            .await?;
         method_latency.observe();
-        removed
+        Ok(removed)
     }
 
     #[tracing::instrument(skip(self))]
@@ -623,18 +1193,42 @@ impl EthNamespace {
         const METHOD_NAME: &str = "send_raw_transaction";
 
         let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let submit_result = self.submit_raw_transaction(tx_bytes, None).await;
+        method_latency.observe();
+        submit_result
+    }
+
+    #[tracing::instrument(skip(self, tx_bytes, conditions))]
+    pub async fn send_raw_transaction_conditional_impl(
+        &self,
+        tx_bytes: Bytes,
+        conditions: TransactionConditions,
+    ) -> Result<H256, Web3Error> {
+        const METHOD_NAME: &str = "send_raw_transaction_conditional";
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let submit_result = self
+            .submit_raw_transaction(tx_bytes, Some(conditions))
+            .await;
+        method_latency.observe();
+        submit_result
+    }
+
+    async fn submit_raw_transaction(
+        &self,
+        tx_bytes: Bytes,
+        conditions: Option<TransactionConditions>,
+    ) -> Result<H256, Web3Error> {
         let (mut tx, hash) = self.state.parse_transaction_bytes(&tx_bytes.0)?;
         tx.set_input(tx_bytes.0, hash);
+        tx.common_data.conditions = conditions.map(Box::new);
 
         let submit_result = self.state.tx_sender.submit_tx(tx).await;
-        let submit_result = submit_result.map(|_| hash).map_err(|err| {
+        submit_result.map(|_| hash).map_err(|err| {
             tracing::debug!("Send raw transaction error: {err}");
             API_METRICS.submit_tx_error[&err.prom_error_code()].inc();
-            Web3Error::SubmitTransactionError(err.to_string(), err.data())
-        });
-
-        method_latency.observe();
-        submit_result
+            err.into()
+        })
     }
 
     #[tracing::instrument(skip(self))]
@@ -644,21 +1238,7 @@ impl EthNamespace {
 
     #[tracing::instrument(skip(self))]
     pub fn syncing_impl(&self) -> SyncState {
-        if let Some(state) = &self.state.sync_state {
-            // Node supports syncing process (i.e. not the main node).
-            if state.is_synced() {
-                SyncState::NotSyncing
-            } else {
-                SyncState::Syncing(SyncInfo {
-                    starting_block: 0u64.into(), // We always start syncing from genesis right now.
-                    current_block: state.get_local_block().0.into(),
-                    highest_block: state.get_main_node_block().0.into(),
-                })
-            }
-        } else {
-            // If there is no sync state, then the node is the main node and it's always synced.
-            SyncState::NotSyncing
-        }
+        sync_status(self.state.sync_state.as_ref())
     }
 
     #[tracing::instrument(skip(self))]
@@ -672,6 +1252,15 @@ impl EthNamespace {
 
         let method_latency =
             API_METRICS.start_block_call(METHOD_NAME, BlockId::Number(newest_block));
+        let percentiles_are_valid = reward_percentiles
+            .iter()
+            .all(|percentile| (0.0..=100.0).contains(percentile))
+            && reward_percentiles.windows(2).all(|pair| pair[0] <= pair[1]);
+        if !percentiles_are_valid {
+            return Err(Web3Error::InvalidFeeParams(
+                "reward percentiles must be monotonically increasing values in [0, 100]".to_owned(),
+            ));
+        }
         // Limit `block_count`.
         let block_count = block_count
             .as_u64()
@@ -684,25 +1273,45 @@ impl EthNamespace {
             .access_storage_tagged("api")
             .await
             .unwrap();
-        let newest_miniblock =
-            resolve_block(&mut connection, BlockId::Number(newest_block), METHOD_NAME).await?;
+        let newest_miniblock = self
+            .state
+            .start_info
+            .resolve_block(&mut connection, BlockId::Number(newest_block), METHOD_NAME)
+            .await?;
 
-        let mut base_fee_per_gas = connection
+        let mut fee_history = connection
             .blocks_web3_dal()
             .get_fee_history(newest_miniblock, block_count)
             .await
             .map_err(|err| internal_error(METHOD_NAME, err))?;
         // DAL method returns fees in DESC order while we need ASC.
-        base_fee_per_gas.reverse();
-
-        let oldest_block = newest_miniblock.0 + 1 - base_fee_per_gas.len() as u32;
-        // We do not store gas used ratio for blocks, returns array of zeroes as a placeholder.
-        let gas_used_ratio = vec![0.0; base_fee_per_gas.len()];
-        // Effective priority gas price is currently 0.
-        let reward = Some(vec![
-            vec![U256::zero(); reward_percentiles.len()];
-            base_fee_per_gas.len()
-        ]);
+        fee_history.reverse();
+
+        let oldest_block = newest_miniblock.0 + 1 - fee_history.len() as u32;
+        let gas_used_ratio = fee_history
+            .iter()
+            .map(|(_, gas_used)| gas_used.as_u64() as f64 / f64::from(BLOCK_GAS_LIMIT))
+            .collect();
+        let reward = if reward_percentiles.is_empty() {
+            None
+        } else {
+            let priority_fees = connection
+                .blocks_web3_dal()
+                .get_priority_fees(MiniblockNumber(oldest_block)..=newest_miniblock)
+                .await
+                .map_err(|err| internal_error(METHOD_NAME, err))?;
+            let reward = (oldest_block..=newest_miniblock.0).map(|number| {
+                let block_fees = priority_fees
+                    .get(&MiniblockNumber(number))
+                    .map_or(&[][..], Vec::as_slice);
+                fee_percentiles(block_fees, &reward_percentiles)
+            });
+            Some(reward.collect())
+        };
+        let mut base_fee_per_gas: Vec<_> = fee_history
+            .into_iter()
+            .map(|(base_fee, _)| base_fee)
+            .collect();
 
         // `base_fee_per_gas` for next miniblock cannot be calculated, appending last fee as a placeholder.
         base_fee_per_gas.push(*base_fee_per_gas.last().unwrap());
@@ -767,64 +1376,27 @@ impl EthNamespace {
             }
 
             TypedFilter::Events(filter, from_block) => {
-                let addresses = if let Some(addresses) = &filter.address {
-                    addresses.0.clone()
-                } else {
-                    vec![]
-                };
-                let topics = if let Some(topics) = &filter.topics {
-                    if topics.len() > EVENT_TOPIC_NUMBER_LIMIT {
-                        return Err(Web3Error::TooManyTopics);
-                    }
-                    let topics_by_idx = topics.iter().enumerate().filter_map(|(idx, topics)| {
-                        Some((idx as u32 + 1, topics.as_ref()?.0.clone()))
-                    });
-                    topics_by_idx.collect::<Vec<_>>()
-                } else {
-                    vec![]
-                };
-
-                let mut to_block = self
-                    .state
-                    .resolve_filter_block_number(filter.to_block)
-                    .await?;
-
-                if matches!(filter.to_block, Some(BlockNumber::Number(_))) {
-                    to_block = to_block.min(
-                        self.state
-                            .resolve_filter_block_number(Some(BlockNumber::Latest))
-                            .await?,
-                    );
-                }
-
-                let get_logs_filter = GetLogsFilter {
-                    from_block: *from_block,
-                    to_block,
-                    addresses,
-                    topics,
-                };
-
+                let get_logs_filter = self.get_logs_filter(filter, *from_block).await?;
+                let to_block = get_logs_filter.to_block;
                 let mut storage = self
                     .state
-                    .connection_pool
+                    .read_pool(BlockId::Number(BlockNumber::Number(to_block.0.into())))
                     .access_storage_tagged("api")
                     .await
                     .map_err(|err| internal_error(METHOD_NAME, err))?;
 
-                // Check if there is more than one block in range and there are more than `req_entities_limit` logs that satisfies filter.
+                // Check if there is more than one block in range and there are more than `get_logs_max_results` logs that satisfies filter.
                 // In this case we should return error and suggest requesting logs with smaller block range.
+                let max_results = self.state.api_config.get_logs_max_results;
                 if *from_block != to_block {
                     if let Some(miniblock_number) = storage
                         .events_web3_dal()
-                        .get_log_block_number(
-                            &get_logs_filter,
-                            self.state.api_config.req_entities_limit,
-                        )
+                        .get_log_block_number(&get_logs_filter, max_results)
                         .await
                         .map_err(|err| internal_error(METHOD_NAME, err))?
                     {
                         return Err(Web3Error::LogsLimitExceeded(
-                            self.state.api_config.req_entities_limit,
+                            max_results,
                             from_block.0,
                             miniblock_number.0 - 1,
                         ));
@@ -843,6 +1415,50 @@ impl EthNamespace {
 
         Ok(res)
     }
+
+    async fn get_logs_filter(
+        &self,
+        filter: &Filter,
+        from_block: MiniblockNumber,
+    ) -> Result<GetLogsFilter, Web3Error> {
+        let addresses = if let Some(addresses) = &filter.address {
+            addresses.0.clone()
+        } else {
+            vec![]
+        };
+        let topics = if let Some(topics) = &filter.topics {
+            if topics.len() > EVENT_TOPIC_NUMBER_LIMIT {
+                return Err(Web3Error::TooManyTopics);
+            }
+            let topics_by_idx = topics
+                .iter()
+                .enumerate()
+                .filter_map(|(idx, topics)| Some((idx as u32 + 1, topics.as_ref()?.0.clone())));
+            topics_by_idx.collect::<Vec<_>>()
+        } else {
+            vec![]
+        };
+
+        let mut to_block = self
+            .state
+            .resolve_filter_block_number(filter.to_block)
+            .await?;
+
+        if matches!(filter.to_block, Some(BlockNumber::Number(_))) {
+            to_block = to_block.min(
+                self.state
+                    .resolve_filter_block_number(Some(BlockNumber::Latest))
+                    .await?,
+            );
+        }
+
+        Ok(GetLogsFilter {
+            from_block,
+            to_block,
+            addresses,
+            topics,
+        })
+    }
 }
 
 // Bogus methods.
@@ -884,3 +1500,127 @@ impl EthNamespace {
     // - `compile_solidity`.
     // - `compile_serpent`.
 }
+
+/// Computes reward percentiles for a miniblock the same way as Ethereum clients do: each transaction is weighted
+/// by the gas it has used. `fees` are `(gas_used, priority_fee)` pairs sorted by the ascending priority fee.
+fn fee_percentiles(fees: &[(U256, U256)], percentiles: &[f32]) -> Vec<U256> {
+    let Some(&(_, max_fee)) = fees.last() else {
+        return vec![U256::zero(); percentiles.len()];
+    };
+    let total_gas_used = fees
+        .iter()
+        .fold(U256::zero(), |acc, &(gas_used, _)| acc + gas_used);
+
+    let mut fees_iter = fees.iter();
+    let mut cumulative_gas_used = U256::zero();
+    let mut current_fee = U256::zero();
+    percentiles
+        .iter()
+        .map(|&percentile| {
+            let threshold = total_gas_used.as_u128() as f64 * f64::from(percentile) / 100.0;
+            while (cumulative_gas_used.as_u128() as f64) < threshold {
+                let Some(&(gas_used, fee)) = fees_iter.next() else {
+                    return max_fee;
+                };
+                cumulative_gas_used += gas_used;
+                current_fee = fee;
+            }
+            if cumulative_gas_used.is_zero() {
+                // Percentile 0 corresponds to the minimum fee.
+                fees[0].1
+            } else {
+                current_fee
+            }
+        })
+        .collect()
+}
+
+/// Converts a block executed in the sandbox into the `eth_simulateV1` format.
+fn simulated_block(block: SandboxBlockResult, tx_hashes: &[H256]) -> SimulatedBlock {
+    let block_number = U64::from(block.number.0);
+    let mut gas_used = U256::zero();
+    let mut log_index = 0_u64;
+    let mut calls = Vec::with_capacity(block.tx_results.len());
+    for (tx_index, (result, &tx_hash)) in block.tx_results.into_iter().zip(tx_hashes).enumerate() {
+        let call_gas_used = U256::from(result.statistics.gas_used);
+        gas_used += call_gas_used;
+
+        let (return_data, error) = match result.result {
+            ExecutionResult::Success { output } => (output, None),
+            ExecutionResult::Revert { output } => {
+                let error = SimulatedCallError {
+                    code: SimulatedCallError::REVERT_CODE,
+                    message: output.to_user_friendly_string(),
+                    data: Some(Bytes(output.encoded_data())),
+                };
+                (output.encoded_data(), Some(error))
+            }
+            ExecutionResult::Halt { reason } => {
+                let error = SimulatedCallError {
+                    code: SimulatedCallError::VM_ERROR_CODE,
+                    message: reason.to_string(),
+                    data: None,
+                };
+                (vec![], Some(error))
+            }
+        };
+        // Logs of failed calls are rolled back together with other call effects.
+        let events = if error.is_none() {
+            result.logs.events
+        } else {
+            vec![]
+        };
+        let mut logs = Vec::with_capacity(events.len());
+        for (tx_log_index, event) in events.into_iter().enumerate() {
+            logs.push(Log {
+                address: event.address,
+                topics: event.indexed_topics,
+                data: Bytes(event.value),
+                block_hash: Some(block.hash),
+                block_number: Some(block_number),
+                l1_batch_number: None,
+                transaction_hash: Some(tx_hash),
+                transaction_index: Some(tx_index.into()),
+                log_index: Some(log_index.into()),
+                transaction_log_index: Some(tx_log_index.into()),
+                log_type: None,
+                removed: Some(false),
+            });
+            log_index += 1;
+        }
+
+        calls.push(SimulatedCallResult {
+            status: U64::from(u8::from(error.is_none())),
+            return_data: Bytes(return_data),
+            gas_used: call_gas_used,
+            logs,
+            error,
+        });
+    }
+
+    SimulatedBlock {
+        number: block_number,
+        hash: block.hash,
+        timestamp: U64::from(block.timestamp),
+        gas_used,
+        calls,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computing_fee_percentiles() {
+        let fees = [
+            (U256::from(1_000), U256::from(10)),
+            (U256::from(3_000), U256::from(50)),
+        ];
+        let percentiles = fee_percentiles(&fees, &[0.0, 25.0, 26.0, 100.0]);
+        assert_eq!(percentiles, [10, 10, 50, 50].map(U256::from));
+
+        let percentiles = fee_percentiles(&[], &[50.0]);
+        assert_eq!(percentiles, [U256::zero()]);
+    }
+}
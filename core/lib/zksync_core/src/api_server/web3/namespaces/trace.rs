@@ -0,0 +1,242 @@
+use zksync_dal::ConnectionPool;
+use zksync_types::{
+    api::{
+        trace::{
+            LocalizedTrace, Trace, TraceFilter, TraceResults, TraceResultsWithTransactionHash,
+            TraceType,
+        },
+        BlockId, BlockNumber,
+    },
+    transaction_request::CallRequest,
+    vm_trace::TraceLimits,
+    H256, U64,
+};
+use zksync_web3_decl::error::Web3Error;
+
+use crate::api_server::web3::{
+    backend_jsonrpsee::internal_error,
+    metrics::API_METRICS,
+    namespaces::DebugNamespace,
+    state::{BlockStartInfo, RpcState, SealedMiniblockNumber},
+};
+
+/// OpenEthereum-style `trace` namespace. Traces are produced from the same call trees
+/// as the ones returned by `callTracer` in the `debug` namespace.
+#[derive(Debug, Clone)]
+pub struct TraceNamespace {
+    debug: DebugNamespace,
+    connection_pool: ConnectionPool,
+    last_sealed_miniblock: SealedMiniblockNumber,
+    start_info: BlockStartInfo,
+    req_entities_limit: usize,
+    trace_filter_block_range_limit: u64,
+}
+
+impl TraceNamespace {
+    pub async fn new(state: RpcState) -> Self {
+        Self {
+            connection_pool: state.connection_pool.clone(),
+            last_sealed_miniblock: state.last_sealed_miniblock.clone(),
+            start_info: state.start_info.clone(),
+            req_entities_limit: state.api_config.req_entities_limit,
+            trace_filter_block_range_limit: state.api_config.trace_filter_block_range_limit,
+            debug: DebugNamespace::new(state).await,
+        }
+    }
+
+    #[tracing::instrument(skip(self, request, block_id))]
+    pub async fn trace_call_impl(
+        &self,
+        request: CallRequest,
+        trace_types: Vec<TraceType>,
+        block_id: Option<BlockId>,
+    ) -> Result<TraceResults, Web3Error> {
+        const METHOD_NAME: &str = "trace_call";
+
+        let block_id = block_id.unwrap_or(BlockId::Number(BlockNumber::Pending));
+        let method_latency = API_METRICS.start_block_call(METHOD_NAME, block_id);
+        let mut connection = self
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap();
+        let block_args = self
+            .start_info
+            .resolve_block_args(&mut connection, block_id, METHOD_NAME)
+            .await?;
+        drop(connection);
+
+        let call = self.debug.execute_call_tree(request, block_args).await?;
+        let results = TraceResults::new(&call, &trace_types);
+        let block_diff = self.last_sealed_miniblock.diff_with_block_args(&block_args);
+        method_latency.observe(block_diff);
+        Ok(results)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn trace_replay_transaction_impl(
+        &self,
+        tx_hash: H256,
+        trace_types: Vec<TraceType>,
+    ) -> Result<Option<TraceResults>, Web3Error> {
+        const METHOD_NAME: &str = "trace_replay_transaction";
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let mut connection = self
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap();
+        let call_trace = connection.transactions_dal().get_call_trace(tx_hash).await;
+        let results = call_trace.map(|call| TraceResults::new(&call, &trace_types));
+        method_latency.observe();
+        Ok(results)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn trace_replay_block_transactions_impl(
+        &self,
+        block_id: BlockId,
+        trace_types: Vec<TraceType>,
+    ) -> Result<Vec<TraceResultsWithTransactionHash>, Web3Error> {
+        const METHOD_NAME: &str = "trace_replay_block_transactions";
+
+        let method_latency = API_METRICS.start_block_call(METHOD_NAME, block_id);
+        let mut connection = self
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap();
+        let block_number = self
+            .start_info
+            .resolve_block(&mut connection, block_id, METHOD_NAME)
+            .await?;
+        drop(connection);
+
+        let call_traces = self
+            .debug
+            .miniblock_call_traces(block_number, METHOD_NAME, TraceLimits::default())
+            .await?;
+        let results = call_traces
+            .into_iter()
+            .map(|(tx_hash, call)| TraceResultsWithTransactionHash {
+                results: TraceResults::new(&call, &trace_types),
+                transaction_hash: tx_hash,
+            })
+            .collect();
+
+        let block_diff = self.last_sealed_miniblock.diff(block_number);
+        method_latency.observe(block_diff);
+        Ok(results)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn trace_block_impl(
+        &self,
+        block_id: BlockId,
+    ) -> Result<Vec<LocalizedTrace>, Web3Error> {
+        const METHOD_NAME: &str = "trace_block";
+
+        let method_latency = API_METRICS.start_block_call(METHOD_NAME, block_id);
+        let mut connection = self
+            .debug
+            .read_pool(block_id)
+            .access_storage_tagged("api")
+            .await
+            .unwrap();
+        let block_number = self
+            .start_info
+            .resolve_block(&mut connection, block_id, METHOD_NAME)
+            .await?;
+        let block_hash = connection
+            .blocks_web3_dal()
+            .get_miniblock_hash(block_number)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?
+            .ok_or(Web3Error::NoBlock)?;
+        drop(connection);
+
+        let call_traces = self
+            .debug
+            .miniblock_call_traces(block_number, METHOD_NAME, TraceLimits::default())
+            .await?;
+        let mut traces = vec![];
+        for (transaction_position, (tx_hash, call)) in call_traces.into_iter().enumerate() {
+            traces.extend(Trace::flatten(&call).into_iter().enumerate().map(
+                |(trace_position, trace)| LocalizedTrace {
+                    trace,
+                    block_hash,
+                    block_number: U64::from(block_number.0),
+                    transaction_hash: tx_hash,
+                    transaction_position,
+                    trace_position,
+                },
+            ));
+        }
+
+        let block_diff = self.last_sealed_miniblock.diff(block_number);
+        method_latency.observe(block_diff);
+        Ok(traces)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn trace_filter_impl(
+        &self,
+        filter: TraceFilter,
+    ) -> Result<Vec<LocalizedTrace>, Web3Error> {
+        const METHOD_NAME: &str = "trace_filter";
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let count = filter.count.unwrap_or(self.req_entities_limit);
+        if count > self.req_entities_limit {
+            return Err(Web3Error::InvalidTraceFilter(format!(
+                "`count` must not exceed {}",
+                self.req_entities_limit
+            )));
+        }
+        if filter.after.is_some() && filter.cursor.is_some() {
+            return Err(Web3Error::InvalidTraceFilter(
+                "`after` and `cursor` cannot be specified together".to_owned(),
+            ));
+        }
+
+        let from_block = BlockId::Number(filter.from_block.unwrap_or(BlockNumber::Earliest));
+        let to_block = BlockId::Number(filter.to_block.unwrap_or(BlockNumber::Latest));
+        let mut connection = self
+            .debug
+            .read_pool(to_block)
+            .access_storage_tagged("api")
+            .await
+            .unwrap();
+        let from_block = self
+            .start_info
+            .resolve_block(&mut connection, from_block, METHOD_NAME)
+            .await?;
+        let to_block = self
+            .start_info
+            .resolve_block(&mut connection, to_block, METHOD_NAME)
+            .await?;
+        let block_range = u64::from(to_block.0.saturating_sub(from_block.0)) + 1;
+        if block_range > self.trace_filter_block_range_limit {
+            return Err(Web3Error::InvalidTraceFilter(format!(
+                "block range must not exceed {} blocks",
+                self.trace_filter_block_range_limit
+            )));
+        }
+
+        let traces = connection
+            .blocks_web3_dal()
+            .get_filtered_traces(
+                from_block..=to_block,
+                filter.from_address.as_deref().unwrap_or_default(),
+                filter.to_address.as_deref().unwrap_or_default(),
+                filter.cursor,
+                filter.after.unwrap_or(0),
+                count,
+            )
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        method_latency.observe();
+        Ok(traces)
+    }
+}
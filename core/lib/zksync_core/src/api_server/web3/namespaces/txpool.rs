@@ -0,0 +1,140 @@
+use std::collections::{BTreeMap, HashMap};
+
+use zksync_types::{
+    api::{TxpoolAccountUsage, TxpoolContent, TxpoolStatus, TxpoolTransaction},
+    Address, ExecuteTransactionCommon, Nonce, Transaction, U64,
+};
+use zksync_web3_decl::error::Web3Error;
+
+use crate::api_server::web3::{
+    backend_jsonrpsee::internal_error, metrics::API_METRICS, state::RpcState,
+};
+
+/// Namespace providing introspection of the mempool, modeled after the `txpool` namespace in Geth.
+#[derive(Debug, Clone)]
+pub struct TxpoolNamespace {
+    state: RpcState,
+}
+
+impl TxpoolNamespace {
+    pub fn new(state: RpcState) -> Self {
+        Self { state }
+    }
+
+    /// Splits mempool transactions into L1 priority transactions, L2 transactions that can be executed
+    /// right away (i.e., form a contiguous nonce sequence starting from the account nonce), and L2 transactions
+    /// that are blocked by a nonce gap. L2 transactions with a nonce lower than the account nonce
+    /// will never be executed, and are skipped.
+    fn group_transactions(
+        transactions: &[Transaction],
+        nonces: &HashMap<Address, Nonce>,
+    ) -> TxpoolContent {
+        let mut content = TxpoolContent::default();
+        let mut l2_transactions = BTreeMap::<_, BTreeMap<_, _>>::new();
+        for tx in transactions {
+            match &tx.common_data {
+                ExecuteTransactionCommon::L1(data) => {
+                    content.priority.insert(data.serial_id.0, tx.into());
+                }
+                ExecuteTransactionCommon::L2(data) => {
+                    l2_transactions
+                        .entry(data.initiator_address)
+                        .or_default()
+                        .insert(data.nonce.0, TxpoolTransaction::from(tx));
+                }
+                ExecuteTransactionCommon::ProtocolUpgrade(_) => { /* not a part of the mempool */ }
+            }
+        }
+
+        for (address, account_transactions) in l2_transactions {
+            let mut next_nonce = nonces.get(&address).map_or(0, |nonce| nonce.0);
+            for (nonce, tx) in account_transactions {
+                let account_pool = if nonce < next_nonce {
+                    continue;
+                } else if nonce == next_nonce {
+                    next_nonce += 1;
+                    &mut content.pending
+                } else {
+                    &mut content.queued
+                };
+                account_pool.entry(address).or_default().insert(nonce, tx);
+            }
+        }
+        content
+    }
+
+    async fn load_content(&self, method_name: &'static str) -> Result<TxpoolContent, Web3Error> {
+        let mut storage = self
+            .state
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .map_err(|err| internal_error(method_name, err))?;
+        let (transactions, nonces) = storage
+            .transactions_dal()
+            .get_mempool_transactions(self.state.api_config.req_entities_limit)
+            .await
+            .map_err(|err| internal_error(method_name, err))?;
+        Ok(Self::group_transactions(&transactions, &nonces))
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn content_impl(&self) -> Result<TxpoolContent, Web3Error> {
+        const METHOD_NAME: &str = "txpool_content";
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let content = self.load_content(METHOD_NAME).await?;
+        method_latency.observe();
+        Ok(content)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn status_impl(&self) -> Result<TxpoolStatus, Web3Error> {
+        const METHOD_NAME: &str = "txpool_status";
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let status = self.load_content(METHOD_NAME).await?.status();
+        method_latency.observe();
+        Ok(status)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn inspect_impl(&self) -> Result<TxpoolContent<String>, Web3Error> {
+        const METHOD_NAME: &str = "txpool_inspect";
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let content = self.load_content(METHOD_NAME).await?;
+        method_latency.observe();
+        Ok(content.map(|tx| tx.summary()))
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn account_usage_impl(
+        &self,
+        address: Address,
+    ) -> Result<TxpoolAccountUsage, Web3Error> {
+        const METHOD_NAME: &str = "txpool_accountUsage";
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let mut storage = self
+            .state
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        let (sent, sponsored) = storage
+            .transactions_dal()
+            .get_pending_l2_txs_count(address)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        let sender_config = &self.state.tx_sender.0.sender_config;
+        let usage = TxpoolAccountUsage {
+            sent: (sent as u64).into(),
+            sent_limit: sender_config.max_pending_txs_per_account.map(U64::from),
+            sponsored: (sponsored as u64).into(),
+            sponsored_limit: sender_config.max_pending_txs_per_paymaster.map(U64::from),
+        };
+        method_latency.observe();
+        Ok(usage)
+    }
+}
@@ -1,12 +1,16 @@
-use std::{collections::HashMap, convert::TryInto};
+use std::{
+    collections::{hash_map, HashMap},
+    convert::TryInto,
+};
 
 use bigdecimal::{BigDecimal, Zero};
 use zksync_dal::StorageProcessor;
 use zksync_mini_merkle_tree::MiniMerkleTree;
 use zksync_types::{
     api::{
-        BlockDetails, BridgeAddresses, GetLogsFilter, L1BatchDetails, L2ToL1LogProof, Proof,
-        ProtocolVersion, StorageProof, TransactionDetails,
+        BlockDetails, BlockNumber, BridgeAddresses, FeeToken, GetLogsFilter, L1BatchDetails,
+        L1BatchFeeParams, L1BatchRevenue, L2ToL1LogProof, L2ToL1LogProofRequest, Proof,
+        ProtocolVersion, StateOverride, StorageProof, TransactionDetails,
     },
     fee::Fee,
     fee_model::FeeParams,
@@ -22,14 +26,85 @@ use zksync_types::{
 use zksync_utils::{address_to_h256, ratio_to_big_decimal_normalized};
 use zksync_web3_decl::{
     error::Web3Error,
-    types::{Address, Token, H256},
+    types::{Address, Bytes, Token, H256},
 };
 
 use crate::api_server::{
+    execution_sandbox::BlockArgs,
     tree::TreeApiClient,
-    web3::{backend_jsonrpsee::internal_error, metrics::API_METRICS, RpcState},
+    web3::{
+        backend_jsonrpsee::{internal_error, map_tree_api_error},
+        metrics::API_METRICS,
+        validate_state_override, RpcState,
+    },
 };
 
+/// L2-to-L1 logs of an L1 batch together with the information necessary to build their Merkle proofs.
+#[derive(Debug)]
+struct BatchL2ToL1Logs {
+    logs: Vec<L2ToL1Log>,
+    min_tree_size: usize,
+}
+
+impl BatchL2ToL1Logs {
+    async fn load(
+        method_name: &'static str,
+        storage: &mut StorageProcessor<'_>,
+        l1_batch_number: L1BatchNumber,
+    ) -> Result<Option<Self>, Web3Error> {
+        let logs = storage
+            .blocks_web3_dal()
+            .get_l2_to_l1_logs(l1_batch_number)
+            .await
+            .map_err(|err| internal_error(method_name, err))?;
+        let Some(batch) = storage
+            .blocks_dal()
+            .get_l1_batch_header(l1_batch_number)
+            .await
+            .map_err(|err| internal_error(method_name, err))?
+        else {
+            return Ok(None);
+        };
+
+        let min_tree_size = if batch
+            .protocol_version
+            .map(|v| v.is_pre_boojum())
+            .unwrap_or(true)
+        {
+            L2ToL1Log::PRE_BOOJUM_MIN_L2_L1_LOGS_TREE_SIZE
+        } else {
+            L2ToL1Log::MIN_L2_L1_LOGS_TREE_SIZE
+        };
+        Ok(Some(Self {
+            logs,
+            min_tree_size,
+        }))
+    }
+
+    /// Builds a proof for the log with the specified index among the logs satisfying `log_filter`.
+    fn proof(
+        &self,
+        index_in_filtered_logs: usize,
+        log_filter: impl Fn(&L2ToL1Log) -> bool,
+    ) -> Option<L2ToL1LogProof> {
+        let (l1_log_index, _) = self
+            .logs
+            .iter()
+            .enumerate()
+            .filter(|(_, log)| log_filter(log))
+            .nth(index_in_filtered_logs)?;
+
+        let merkle_tree_leaves = self.logs.iter().map(L2ToL1Log::to_bytes);
+        let (root, proof) = MiniMerkleTree::new(merkle_tree_leaves, Some(self.min_tree_size))
+            .merkle_root_and_path(l1_log_index);
+        Some(L2ToL1LogProof {
+            proof,
+            root,
+            id: l1_log_index as u32,
+        })
+    }
+}
+
 #[derive(Debug)]
 pub struct ZksNamespace {
     pub state: RpcState,
@@ -40,15 +115,27 @@ impl ZksNamespace {
         Self { state }
     }
 
-    #[tracing::instrument(skip(self, request))]
-    pub async fn estimate_fee_impl(&self, request: CallRequest) -> Result<Fee, Web3Error> {
+    #[tracing::instrument(skip(self, request, state_override, block))]
+    pub async fn estimate_fee_impl(
+        &self,
+        request: CallRequest,
+        state_override: Option<StateOverride>,
+        block: Option<BlockNumber>,
+    ) -> Result<Fee, Web3Error> {
         const METHOD_NAME: &str = "estimate_fee";
 
+        if let Some(state_override) = &state_override {
+            validate_state_override(state_override)?;
+        }
         let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let block_args = self
+            .state
+            .resolve_block_args_for_estimation(block, METHOD_NAME)
+            .await?;
         let mut request_with_gas_per_pubdata_overridden = request;
 
         self.state
-            .set_nonce_for_call_request(&mut request_with_gas_per_pubdata_overridden)
+            .set_nonce_for_call_request(&mut request_with_gas_per_pubdata_overridden, &block_args)
             .await?;
 
         if let Some(ref mut eip712_meta) = request_with_gas_per_pubdata_overridden.eip712_meta {
@@ -65,7 +152,9 @@ impl ZksNamespace {
         tx.common_data.fee.max_priority_fee_per_gas = 0u64.into();
         tx.common_data.fee.gas_per_pubdata_limit = MAX_GAS_PER_PUBDATA_BYTE.into();
 
-        let fee = self.estimate_fee(tx.into()).await?;
+        let fee = self
+            .estimate_fee(tx.into(), block_args, state_override)
+            .await?;
         method_latency.observe();
         Ok(fee)
     }
@@ -91,12 +180,21 @@ impl ZksNamespace {
             .try_into()
             .map_err(Web3Error::SerializationError)?;
 
-        let fee = self.estimate_fee(tx.into()).await?;
+        let block_args = self
+            .state
+            .resolve_block_args_for_estimation(None, METHOD_NAME)
+            .await?;
+        let fee = self.estimate_fee(tx.into(), block_args, None).await?;
         method_latency.observe();
         Ok(fee.gas_limit)
     }
 
-    async fn estimate_fee(&self, tx: Transaction) -> Result<Fee, Web3Error> {
+    async fn estimate_fee(
+        &self,
+        tx: Transaction,
+        block_args: BlockArgs,
+        state_override: Option<StateOverride>,
+    ) -> Result<Fee, Web3Error> {
         let scale_factor = self.state.api_config.estimate_gas_scale_factor;
         let acceptable_overestimation =
             self.state.api_config.estimate_gas_acceptable_overestimation;
@@ -104,7 +202,13 @@ impl ZksNamespace {
         let fee = self
             .state
             .tx_sender
-            .get_txs_fee_in_wei(tx, scale_factor, acceptable_overestimation)
+            .get_txs_fee_in_wei(
+                tx,
+                block_args,
+                scale_factor,
+                acceptable_overestimation,
+                state_override,
+            )
             .await
             .map_err(|err| Web3Error::SubmitTransactionError(err.to_string(), err.data()))?;
 
@@ -126,6 +230,11 @@ impl ZksNamespace {
         self.state.api_config.bridge_addresses.clone()
     }
 
+    #[tracing::instrument(skip(self))]
+    pub fn get_fee_token_impl(&self) -> FeeToken {
+        self.state.api_config.fee_token
+    }
+
     #[tracing::instrument(skip(self))]
     pub fn l1_chain_id_impl(&self) -> U64 {
         U64::from(*self.state.api_config.l1_chain_id)
@@ -260,6 +369,10 @@ impl ZksNamespace {
             Some(number) => number,
             None => return Ok(None),
         };
+        self.state
+            .start_info
+            .ensure_l1_batch_not_pruned(&mut storage, l1_batch_number, METHOD_NAME)
+            .await?;
         let (first_miniblock_of_l1_batch, _) = storage
             .blocks_web3_dal()
             .get_miniblock_range_of_l1_batch(l1_batch_number)
@@ -320,49 +433,8 @@ impl ZksNamespace {
         index_in_filtered_logs: usize,
         log_filter: impl Fn(&L2ToL1Log) -> bool,
     ) -> Result<Option<L2ToL1LogProof>, Web3Error> {
-        let all_l1_logs_in_batch = storage
-            .blocks_web3_dal()
-            .get_l2_to_l1_logs(l1_batch_number)
-            .await
-            .map_err(|err| internal_error(method_name, err))?;
-
-        let Some((l1_log_index, _)) = all_l1_logs_in_batch
-            .iter()
-            .enumerate()
-            .filter(|(_, log)| log_filter(log))
-            .nth(index_in_filtered_logs)
-        else {
-            return Ok(None);
-        };
-
-        let Some(batch) = storage
-            .blocks_dal()
-            .get_l1_batch_header(l1_batch_number)
-            .await
-            .map_err(|err| internal_error(method_name, err))?
-        else {
-            return Ok(None);
-        };
-
-        let merkle_tree_leaves = all_l1_logs_in_batch.iter().map(L2ToL1Log::to_bytes);
-
-        let min_tree_size = if batch
-            .protocol_version
-            .map(|v| v.is_pre_boojum())
-            .unwrap_or(true)
-        {
-            Some(L2ToL1Log::PRE_BOOJUM_MIN_L2_L1_LOGS_TREE_SIZE)
-        } else {
-            Some(L2ToL1Log::MIN_L2_L1_LOGS_TREE_SIZE)
-        };
-
-        let (root, proof) = MiniMerkleTree::new(merkle_tree_leaves, min_tree_size)
-            .merkle_root_and_path(l1_log_index);
-        Ok(Some(L2ToL1LogProof {
-            proof,
-            root,
-            id: l1_log_index as u32,
-        }))
+        let batch_logs = BatchL2ToL1Logs::load(method_name, storage, l1_batch_number).await?;
+        Ok(batch_logs.and_then(|logs| logs.proof(index_in_filtered_logs, log_filter)))
     }
 
     #[tracing::instrument(skip(self))]
@@ -403,6 +475,60 @@ impl ZksNamespace {
         Ok(log_proof)
     }
 
+    #[tracing::instrument(skip(self, requests))]
+    pub async fn get_l2_to_l1_log_proofs_impl(
+        &self,
+        requests: Vec<L2ToL1LogProofRequest>,
+    ) -> Result<Vec<Option<L2ToL1LogProof>>, Web3Error> {
+        const METHOD_NAME: &str = "get_l2_to_l1_log_proofs";
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let max_requests = self.state.api_config.req_entities_limit;
+        if requests.len() > max_requests {
+            return Err(Web3Error::TooManyProofRequests(max_requests));
+        }
+
+        let mut storage = self
+            .state
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap();
+        // Withdrawals finalized together are likely to belong to the same L1 batches,
+        // so we load logs for each batch only once.
+        let mut logs_by_batch = HashMap::new();
+        let mut proofs = Vec::with_capacity(requests.len());
+        for request in requests {
+            let Some((l1_batch_number, l1_batch_tx_index)) = storage
+                .blocks_web3_dal()
+                .get_l1_batch_info_for_tx(request.tx_hash)
+                .await
+                .map_err(|err| internal_error(METHOD_NAME, err))?
+            else {
+                proofs.push(None);
+                continue;
+            };
+
+            let batch_logs = match logs_by_batch.entry(l1_batch_number) {
+                hash_map::Entry::Occupied(entry) => entry.into_mut(),
+                hash_map::Entry::Vacant(entry) => {
+                    let logs =
+                        BatchL2ToL1Logs::load(METHOD_NAME, &mut storage, l1_batch_number).await?;
+                    entry.insert(logs)
+                }
+            };
+            let proof = batch_logs.as_ref().and_then(|logs| {
+                logs.proof(request.index.unwrap_or(0), |log| {
+                    log.tx_number_in_block == l1_batch_tx_index
+                })
+            });
+            proofs.push(proof);
+        }
+
+        method_latency.observe();
+        Ok(proofs)
+    }
+
     #[tracing::instrument(skip(self))]
     pub async fn get_l1_batch_number_impl(&self) -> Result<U64, Web3Error> {
         const METHOD_NAME: &str = "get_l1_batch_number";
@@ -474,29 +600,66 @@ impl ZksNamespace {
         block_details
     }
 
+    /// Returns raw transactions from the specified miniblock. If `limit` is specified, returns at most
+    /// `limit` transactions starting from the `offset` index in the miniblock; otherwise, returns all transactions
+    /// starting from `offset`.
     #[tracing::instrument(skip(self))]
     pub async fn get_raw_block_transactions_impl(
         &self,
         block_number: MiniblockNumber,
+        offset: Option<usize>,
+        limit: Option<usize>,
     ) -> Result<Vec<Transaction>, Web3Error> {
         const METHOD_NAME: &str = "get_raw_block_transactions";
 
+        if let Some(limit) = limit {
+            self.validate_raw_transactions_limit(limit)?;
+        }
         let method_latency = API_METRICS.start_call(METHOD_NAME);
-        let transactions = self
+        let mut storage = self
             .state
             .connection_pool
             .access_storage_tagged("api")
             .await
-            .unwrap()
-            .transactions_web3_dal()
-            .get_raw_miniblock_transactions(block_number)
-            .await
-            .map_err(|err| internal_error(METHOD_NAME, err));
+            .unwrap();
+        self.state
+            .start_info
+            .ensure_block_not_pruned(&mut storage, block_number, METHOD_NAME)
+            .await?;
+        let transactions = match (offset, limit) {
+            (None, None) => {
+                storage
+                    .transactions_web3_dal()
+                    .get_raw_miniblock_transactions(block_number)
+                    .await
+            }
+            (offset, limit) => {
+                storage
+                    .transactions_web3_dal()
+                    .get_raw_miniblock_transactions_page(
+                        block_number,
+                        offset.unwrap_or(0),
+                        limit.unwrap_or(u32::MAX as usize),
+                    )
+                    .await
+            }
+        };
+        let transactions = transactions.map_err(|err| internal_error(METHOD_NAME, err));
 
         method_latency.observe();
         transactions
     }
 
+    pub(crate) fn validate_raw_transactions_limit(&self, limit: usize) -> Result<(), Web3Error> {
+        let max_limit = self.state.api_config.req_entities_limit;
+        if limit == 0 || limit > max_limit {
+            return Err(Web3Error::InvalidPagination(format!(
+                "`limit` must be in range 1..={max_limit}"
+            )));
+        }
+        Ok(())
+    }
+
     #[tracing::instrument(skip(self))]
     pub async fn get_transaction_details_impl(
         &self,
@@ -555,6 +718,52 @@ impl ZksNamespace {
         l1_batch
     }
 
+    #[tracing::instrument(skip(self))]
+    pub async fn get_l1_batch_fee_params_impl(
+        &self,
+        batch_number: L1BatchNumber,
+    ) -> Result<Option<L1BatchFeeParams>, Web3Error> {
+        const METHOD_NAME: &str = "get_l1_batch_fee_params";
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let fee_input = self
+            .state
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap()
+            .blocks_web3_dal()
+            .get_l1_batch_fee_input(batch_number)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+
+        method_latency.observe();
+        Ok(fee_input.map(|fee_input| L1BatchFeeParams::new(batch_number, fee_input)))
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_l1_batch_revenue_impl(
+        &self,
+        batch_number: L1BatchNumber,
+    ) -> Result<Option<L1BatchRevenue>, Web3Error> {
+        const METHOD_NAME: &str = "get_l1_batch_revenue";
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let revenue = self
+            .state
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap()
+            .revenue_dal()
+            .get_l1_batch_revenue(batch_number)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err));
+
+        method_latency.observe();
+        revenue
+    }
+
     #[tracing::instrument(skip(self))]
     pub async fn get_bytecode_by_hash_impl(&self, hash: H256) -> Option<Vec<u8>> {
         const METHOD_NAME: &str = "get_bytecode_by_hash";
@@ -643,6 +852,25 @@ impl ZksNamespace {
         protocol_version
     }
 
+    #[tracing::instrument(skip(self))]
+    pub async fn get_scheduled_protocol_upgrades_impl(&self) -> Vec<ProtocolVersion> {
+        const METHOD_NAME: &str = "get_scheduled_protocol_upgrades";
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let versions = self
+            .state
+            .connection_pool
+            .access_storage()
+            .await
+            .unwrap()
+            .protocol_versions_web3_dal()
+            .get_scheduled_protocol_versions()
+            .await;
+
+        method_latency.observe();
+        versions
+    }
+
     #[tracing::instrument(skip_all)]
     pub async fn get_proofs_impl(
         &self,
@@ -652,19 +880,29 @@ impl ZksNamespace {
     ) -> Result<Proof, Web3Error> {
         const METHOD_NAME: &str = "get_proofs";
 
+        let tree_api = self
+            .state
+            .tree_api
+            .as_ref()
+            .ok_or(Web3Error::TreeApiUnavailable)?;
+        let mut storage = self
+            .state
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap();
+        self.state.check_tree_lag(&mut storage, METHOD_NAME).await?;
+        drop(storage);
+
         let hashed_keys = keys
             .iter()
             .map(|key| StorageKey::new(AccountTreeId::new(address), *key).hashed_key_u256())
             .collect();
 
-        let storage_proof = self
-            .state
-            .tree_api
-            .as_ref()
-            .ok_or(Web3Error::TreeApiUnavailable)?
+        let storage_proof = tree_api
             .get_proofs(l1_batch_number, hashed_keys)
             .await
-            .map_err(|err| internal_error(METHOD_NAME, err))?
+            .map_err(|err| map_tree_api_error(METHOD_NAME, err))?
             .into_iter()
             .zip(keys)
             .map(|(proof, key)| StorageProof {
@@ -680,4 +918,45 @@ impl ZksNamespace {
             storage_proof,
         })
     }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn send_bundle_impl(&self, txs: Vec<Bytes>) -> Result<H256, Web3Error> {
+        const METHOD_NAME: &str = "send_bundle";
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let mut transactions = Vec::with_capacity(txs.len());
+        for tx_bytes in txs {
+            let (mut tx, hash) = self.state.parse_transaction_bytes(&tx_bytes.0)?;
+            tx.set_input(tx_bytes.0, hash);
+            transactions.push(tx);
+        }
+
+        let submit_result = self.state.tx_sender.submit_bundle(transactions).await;
+        method_latency.observe();
+        submit_result.map_err(|err| {
+            tracing::debug!("Send bundle error: {err}");
+            API_METRICS.submit_tx_error[&err.prom_error_code()].inc();
+            Web3Error::SubmitTransactionError(err.to_string(), err.data())
+        })
+    }
+
+    #[tracing::instrument(skip(self, tx_bytes))]
+    pub async fn send_private_raw_transaction_impl(
+        &self,
+        tx_bytes: Bytes,
+    ) -> Result<H256, Web3Error> {
+        const METHOD_NAME: &str = "send_private_raw_transaction";
+
+        let method_latency = API_METRICS.start_call(METHOD_NAME);
+        let (mut tx, hash) = self.state.parse_transaction_bytes(&tx_bytes.0)?;
+        tx.set_input(tx_bytes.0, hash);
+
+        let submit_result = self.state.tx_sender.submit_private_tx(tx).await;
+        method_latency.observe();
+        submit_result.map(|_| hash).map_err(|err| {
+            tracing::debug!("Send private raw transaction error: {err}");
+            API_METRICS.submit_tx_error[&err.prom_error_code()].inc();
+            err.into()
+        })
+    }
 }
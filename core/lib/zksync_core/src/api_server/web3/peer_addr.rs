@@ -0,0 +1,84 @@
+//! Accept loop for JSON-RPC servers exposing peer addresses of connections to HTTP middleware.
+//!
+//! `jsonrpsee::server::Server` doesn't expose peer addresses, which are required to identify clients in a way
+//! that cannot be spoofed via request headers (see [`Client::identify()`](super::rate_limit::Client::identify)).
+//! Hence, the API server accepts connections itself, mirroring the `jsonrpsee` implementation, and inserts
+//! [`PeerAddr`] into extensions of each incoming request.
+
+use std::{error::Error as StdError, net::SocketAddr};
+
+use hyper::{
+    server::conn::Http,
+    service::{service_fn, Service},
+    Body, Request, Response,
+};
+use tokio::net::TcpListener;
+use zksync_web3_decl::jsonrpsee::server::{stop_channel, ServerHandle, StopHandle};
+
+type BoxError = Box<dyn StdError + Send + Sync>;
+
+/// Socket address of the peer that has sent an HTTP request.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct PeerAddr(pub SocketAddr);
+
+/// Accepts connections on `listener` until the returned handle is stopped. Each connection is served
+/// by a service created by `make_service`; the provided stop handle must be held by the service.
+///
+/// As with `jsonrpsee` servers, [`ServerHandle::stopped()`] resolves once all stop handles are dropped,
+/// i.e., after all connections are closed.
+pub(super) fn serve<S>(
+    listener: TcpListener,
+    make_service: impl Fn(StopHandle) -> S + Send + 'static,
+) -> ServerHandle
+where
+    S: Service<Request<Body>, Response = Response<Body>, Error = BoxError> + Send + 'static,
+    S::Future: Send + 'static,
+{
+    let (stop_handle, server_handle) = stop_channel();
+    tokio::spawn(async move {
+        let stopped = stop_handle.clone().shutdown();
+        tokio::pin!(stopped);
+        loop {
+            let (socket, peer_addr) = tokio::select! {
+                () = &mut stopped => break,
+                accepted = listener.accept() => match accepted {
+                    Ok(accepted) => accepted,
+                    Err(err) => {
+                        tracing::debug!("Error while accepting a new connection: {err}");
+                        continue;
+                    }
+                },
+            };
+            if let Err(err) = socket.set_nodelay(true) {
+                tracing::warn!("Could not set NODELAY on socket: {err}");
+                continue;
+            }
+
+            let mut service = make_service(stop_handle.clone());
+            let connection_stopped = stop_handle.clone().shutdown();
+            tokio::spawn(async move {
+                let service = service_fn(move |mut request: Request<Body>| {
+                    request.extensions_mut().insert(PeerAddr(peer_addr));
+                    service.call(request)
+                });
+                let connection = Http::new()
+                    .serve_connection(socket, service)
+                    .with_upgrades();
+                tokio::pin!(connection);
+
+                let result = tokio::select! {
+                    result = &mut connection => result,
+                    () = connection_stopped => {
+                        // The connection must be polled until the graceful shutdown completes.
+                        connection.as_mut().graceful_shutdown();
+                        connection.await
+                    }
+                };
+                if let Err(err) = result {
+                    tracing::debug!("Serving connection from {peer_addr} failed: {err}");
+                }
+            });
+        }
+    });
+    server_handle
+}
@@ -1,5 +1,7 @@
 //! (Largely) backend-agnostic logic for dealing with Web3 subscriptions.
 
+use std::collections::VecDeque;
+
 use anyhow::Context as _;
 use futures::FutureExt;
 use tokio::{
@@ -7,26 +9,35 @@ use tokio::{
     task::JoinHandle,
     time::{interval, Duration},
 };
+use zksync_config::configs::api::SubscriptionBackpressurePolicy;
 use zksync_dal::ConnectionPool;
-use zksync_types::{MiniblockNumber, H128, H256};
+use zksync_types::{
+    api::{self, GetLogsFilter},
+    L1BatchNumber, L2ChainId, MiniblockNumber, H128, H256,
+};
 use zksync_web3_decl::{
     jsonrpsee::{
         core::{server::SubscriptionMessage, SubscriptionResult},
         server::IdProvider,
         types::{error::ErrorCode, ErrorObject, SubscriptionId},
-        PendingSubscriptionSink, SendTimeoutError, SubscriptionSink,
+        PendingSubscriptionSink, SubscriptionSink,
     },
     namespaces::EthPubSubServer,
-    types::{BlockHeader, Log, PubSubFilter, PubSubResult},
+    types::{
+        BlockHeader, L1BatchStage, L1BatchStatusEvent, Log, LogCursor, PubSubFilter, PubSubParams,
+        PubSubResult,
+    },
 };
 
 use super::{
-    metrics::{SubscriptionType, PUB_SUB_METRICS},
+    metrics::{QueueOverflowLabels, SubscriptionType, PUB_SUB_METRICS},
     namespaces::eth::EVENT_TOPIC_NUMBER_LIMIT,
 };
 
 const BROADCAST_CHANNEL_CAPACITY: usize = 1024;
 const SUBSCRIPTION_SINK_SEND_TIMEOUT: Duration = Duration::from_secs(1);
+/// Number of logs loaded from Postgres at once when backfilling a resumed `logs` subscription.
+const LOGS_BACKFILL_PAGE_SIZE: usize = 1_024;
 
 #[derive(Debug, Clone, Copy)]
 pub struct EthSubscriptionIdProvider;
@@ -45,6 +56,45 @@ pub(super) enum PubSubEvent {
     NotifyIterationFinished(SubscriptionType),
 }
 
+/// Last L1 batch numbers that have reached each stage of the L1 batch lifecycle.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct L1BatchStageNumbers {
+    sealed: Option<L1BatchNumber>,
+    committed: Option<L1BatchNumber>,
+    proven: Option<L1BatchNumber>,
+    executed: Option<L1BatchNumber>,
+}
+
+impl L1BatchStageNumbers {
+    fn stages(&self) -> [(L1BatchStage, Option<L1BatchNumber>); 4] {
+        [
+            (L1BatchStage::Sealed, self.sealed),
+            (L1BatchStage::Committed, self.committed),
+            (L1BatchStage::Proven, self.proven),
+            (L1BatchStage::Executed, self.executed),
+        ]
+    }
+
+    /// Returns events for all batches that have reached a new stage since `prev`, ordered by stage
+    /// and then by batch number. Since stages are always reached in order, events for a single batch
+    /// are ordered as well.
+    fn new_events(&self, prev: &Self) -> Vec<L1BatchStatusEvent> {
+        let mut events = vec![];
+        for ((stage, number), (_, prev_number)) in self.stages().into_iter().zip(prev.stages()) {
+            let Some(number) = number else {
+                continue;
+            };
+            // The genesis L1 batch is never sent to L1, so it's skipped if there are no previous batches.
+            let start = prev_number.map_or(1, |prev_number| prev_number.0 + 1);
+            events.extend((start..=number.0).map(|number| L1BatchStatusEvent {
+                l1_batch_number: L1BatchNumber(number),
+                stage,
+            }));
+        }
+        events
+    }
+}
+
 /// Manager of notifications for a certain type of subscriptions.
 #[derive(Debug)]
 struct PubSubNotifier {
@@ -158,6 +208,117 @@ impl PubSubNotifier {
             .context("get_pending_txs_hashes_after()")
     }
 
+    async fn notify_full_txs(
+        self,
+        chain_id: L2ChainId,
+        stop_receiver: watch::Receiver<bool>,
+    ) -> anyhow::Result<()> {
+        let mut last_time = chrono::Utc::now().naive_utc();
+        let mut timer = interval(self.polling_interval);
+        loop {
+            if *stop_receiver.borrow() {
+                tracing::info!("Stop signal received, pubsub_full_tx_notifier is shutting down");
+                break;
+            }
+            timer.tick().await;
+
+            // Loading full transactions is relatively expensive, so we skip it if there are no subscribers.
+            if self.sender.receiver_count() == 0 {
+                last_time = chrono::Utc::now().naive_utc();
+                self.emit_event(PubSubEvent::NotifyIterationFinished(
+                    SubscriptionType::FullTxs,
+                ));
+                continue;
+            }
+
+            let db_latency = PUB_SUB_METRICS.db_poll_latency[&SubscriptionType::FullTxs].start();
+            let (new_txs, new_last_time) = self.new_full_txs(last_time, chain_id).await?;
+            db_latency.observe();
+
+            if let Some(new_last_time) = new_last_time {
+                last_time = new_last_time;
+                let new_txs = new_txs.into_iter().map(PubSubResult::FullTx).collect();
+                self.send_pub_sub_results(new_txs, SubscriptionType::FullTxs);
+            }
+            self.emit_event(PubSubEvent::NotifyIterationFinished(
+                SubscriptionType::FullTxs,
+            ));
+        }
+        Ok(())
+    }
+
+    async fn new_full_txs(
+        &self,
+        last_time: chrono::NaiveDateTime,
+        chain_id: L2ChainId,
+    ) -> anyhow::Result<(Vec<api::Transaction>, Option<chrono::NaiveDateTime>)> {
+        self.connection_pool
+            .access_storage_tagged("api")
+            .await
+            .context("access_storage_tagged")?
+            .transactions_web3_dal()
+            .get_pending_txs_after(last_time, None, chain_id)
+            .await
+            .context("get_pending_txs_after()")
+    }
+
+    async fn notify_l1_batches(self, stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        let mut last_numbers = self.l1_batch_stage_numbers().await?;
+        let mut timer = interval(self.polling_interval);
+        loop {
+            if *stop_receiver.borrow() {
+                tracing::info!("Stop signal received, pubsub_l1_batch_notifier is shutting down");
+                break;
+            }
+            timer.tick().await;
+
+            let db_latency = PUB_SUB_METRICS.db_poll_latency[&SubscriptionType::L1Batches].start();
+            let new_numbers = self.l1_batch_stage_numbers().await?;
+            db_latency.observe();
+
+            let new_events = new_numbers.new_events(&last_numbers);
+            last_numbers = new_numbers;
+            if !new_events.is_empty() {
+                let new_events = new_events
+                    .into_iter()
+                    .map(PubSubResult::L1BatchStatus)
+                    .collect();
+                self.send_pub_sub_results(new_events, SubscriptionType::L1Batches);
+            }
+            self.emit_event(PubSubEvent::NotifyIterationFinished(
+                SubscriptionType::L1Batches,
+            ));
+        }
+        Ok(())
+    }
+
+    async fn l1_batch_stage_numbers(&self) -> anyhow::Result<L1BatchStageNumbers> {
+        let mut storage = self
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .context("access_storage_tagged")?;
+        let mut blocks_dal = storage.blocks_dal();
+        Ok(L1BatchStageNumbers {
+            sealed: blocks_dal
+                .get_sealed_l1_batch_number()
+                .await
+                .context("get_sealed_l1_batch_number()")?,
+            committed: blocks_dal
+                .get_number_of_last_l1_batch_committed_on_eth()
+                .await
+                .context("get_number_of_last_l1_batch_committed_on_eth()")?,
+            proven: blocks_dal
+                .get_number_of_last_l1_batch_proven_on_eth()
+                .await
+                .context("get_number_of_last_l1_batch_proven_on_eth()")?,
+            executed: blocks_dal
+                .get_number_of_last_l1_batch_executed_on_eth()
+                .await
+                .context("get_number_of_last_l1_batch_executed_on_eth()")?,
+        })
+    }
+
     async fn notify_logs(self, stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
         let mut last_block_number = self.sealed_miniblock_number().await?;
         let mut timer = interval(self.polling_interval);
@@ -194,24 +355,106 @@ impl PubSubNotifier {
     }
 }
 
+/// Configuration of notification queues for individual subscriptions.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct SubscriptionQueueConfig {
+    pub capacity: usize,
+    pub policy: SubscriptionBackpressurePolicy,
+}
+
+impl Default for SubscriptionQueueConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 1_024,
+            policy: SubscriptionBackpressurePolicy::Disconnect,
+        }
+    }
+}
+
+/// Bounded queue of notifications for a single subscription. Notifications are queued if the client
+/// reads them slower than they are produced; on overflow, the configured policy is applied.
+#[derive(Debug)]
+struct SubscriberQueue {
+    subscription_type: SubscriptionType,
+    config: SubscriptionQueueConfig,
+    items: VecDeque<PubSubResult>,
+}
+
+impl SubscriberQueue {
+    fn new(subscription_type: SubscriptionType, config: SubscriptionQueueConfig) -> Self {
+        Self {
+            subscription_type,
+            config,
+            items: VecDeque::new(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    fn front(&self) -> Option<&PubSubResult> {
+        self.items.front()
+    }
+
+    fn pop_front(&mut self) -> Option<PubSubResult> {
+        self.items.pop_front()
+    }
+
+    /// Adds items to the queue. Returns `false` if the subscription should be dropped.
+    fn extend(&mut self, items: impl Iterator<Item = PubSubResult>) -> bool {
+        for item in items {
+            if self.items.len() < self.config.capacity {
+                self.items.push_back(item);
+                continue;
+            }
+
+            let labels = QueueOverflowLabels {
+                subscription_type: self.subscription_type,
+                outcome: self.config.policy.into(),
+            };
+            PUB_SUB_METRICS.queue_overflows[&labels].inc();
+            match self.config.policy {
+                SubscriptionBackpressurePolicy::Disconnect => return false,
+                SubscriptionBackpressurePolicy::DropOldest => {
+                    self.items.pop_front();
+                    self.items.push_back(item);
+                }
+                SubscriptionBackpressurePolicy::DropNewest => { /* do nothing */ }
+            }
+        }
+        true
+    }
+}
+
 /// Subscription support for Web3 APIs.
 pub(super) struct EthSubscribe {
     blocks: broadcast::Sender<Vec<PubSubResult>>,
     transactions: broadcast::Sender<Vec<PubSubResult>>,
+    full_transactions: broadcast::Sender<Vec<PubSubResult>>,
     logs: broadcast::Sender<Vec<PubSubResult>>,
+    l1_batches: broadcast::Sender<Vec<PubSubResult>>,
+    connection_pool: ConnectionPool,
+    queue_config: SubscriptionQueueConfig,
     events_sender: Option<mpsc::UnboundedSender<PubSubEvent>>,
 }
 
 impl EthSubscribe {
-    pub fn new() -> Self {
+    pub fn new(connection_pool: ConnectionPool, queue_config: SubscriptionQueueConfig) -> Self {
         let (blocks, _) = broadcast::channel(BROADCAST_CHANNEL_CAPACITY);
         let (transactions, _) = broadcast::channel(BROADCAST_CHANNEL_CAPACITY);
+        let (full_transactions, _) = broadcast::channel(BROADCAST_CHANNEL_CAPACITY);
         let (logs, _) = broadcast::channel(BROADCAST_CHANNEL_CAPACITY);
+        let (l1_batches, _) = broadcast::channel(BROADCAST_CHANNEL_CAPACITY);
 
         Self {
             blocks,
             transactions,
+            full_transactions,
             logs,
+            l1_batches,
+            connection_pool,
+            queue_config,
             events_sender: None,
         }
     }
@@ -220,6 +463,20 @@ impl EthSubscribe {
         self.events_sender = Some(sender);
     }
 
+    /// Subscribes to raw notifications of the specified type, bypassing JSON-RPC subscription logic.
+    pub fn subscribe_raw(
+        &self,
+        subscription_type: SubscriptionType,
+    ) -> broadcast::Receiver<Vec<PubSubResult>> {
+        match subscription_type {
+            SubscriptionType::Blocks => self.blocks.subscribe(),
+            SubscriptionType::Txs => self.transactions.subscribe(),
+            SubscriptionType::FullTxs => self.full_transactions.subscribe(),
+            SubscriptionType::Logs => self.logs.subscribe(),
+            SubscriptionType::L1Batches => self.l1_batches.subscribe(),
+        }
+    }
+
     async fn reject(sink: PendingSubscriptionSink) {
         sink.reject(ErrorObject::borrowed(
             ErrorCode::InvalidParams.code(),
@@ -234,11 +491,13 @@ impl EthSubscribe {
         subscription_type: SubscriptionType,
         mut receiver: broadcast::Receiver<Vec<PubSubResult>>,
         filter: Option<PubSubFilter>,
+        queue_config: SubscriptionQueueConfig,
     ) {
         let _guard = PUB_SUB_METRICS.active_subscribers[&subscription_type].inc_guard(1);
         let lifetime_latency = PUB_SUB_METRICS.subscriber_lifetime[&subscription_type].start();
         let closed = sink.closed().fuse();
         tokio::pin!(closed);
+        let mut queue = SubscriberQueue::new(subscription_type, queue_config);
 
         loop {
             tokio::select! {
@@ -254,21 +513,27 @@ impl EthSubscribe {
                             PUB_SUB_METRICS
                                 .skipped_broadcast_messages[&subscription_type]
                                 .observe(message_count);
-                            break;
+                            if queue_config.policy == SubscriptionBackpressurePolicy::Disconnect {
+                                break;
+                            }
+                            continue;
                         }
                     };
 
-                    let handle_result = Self::handle_new_items(
-                        &sink,
-                        subscription_type,
-                        new_items,
-                        filter.as_ref()
-                    )
-                    .await;
-                    if handle_result.is_err() {
-                        PUB_SUB_METRICS.subscriber_send_timeouts[&subscription_type].inc();
+                    let new_items = new_items
+                        .into_iter()
+                        .filter(|item| Self::matches_filter(item, filter.as_ref()));
+                    if !queue.extend(new_items) {
+                        break;
+                    }
+                }
+                is_sent = Self::send_item(&sink, subscription_type, queue.front()), if !queue.is_empty() => {
+                    if !is_sent {
+                        // The subscription is closed by the client.
                         break;
                     }
+                    queue.pop_front();
+                    PUB_SUB_METRICS.notify[&subscription_type].inc();
                 }
                 _ = &mut closed => {
                     break;
@@ -278,34 +543,116 @@ impl EthSubscribe {
         lifetime_latency.observe();
     }
 
-    async fn handle_new_items(
-        sink: &SubscriptionSink,
-        subscription_type: SubscriptionType,
-        new_items: Vec<PubSubResult>,
-        filter: Option<&PubSubFilter>,
-    ) -> Result<(), SendTimeoutError> {
-        let notify_latency = PUB_SUB_METRICS.notify_subscribers_latency[&subscription_type].start();
-        for item in new_items {
-            if let PubSubResult::Log(log) = &item {
-                if let Some(filter) = &filter {
-                    if !filter.matches(log) {
-                        continue;
-                    }
+    async fn run_logs_subscriber(
+        connection_pool: ConnectionPool,
+        sink: SubscriptionSink,
+        receiver: broadcast::Receiver<Vec<PubSubResult>>,
+        mut filter: PubSubFilter,
+        queue_config: SubscriptionQueueConfig,
+    ) {
+        if filter.cursor.is_some() {
+            match Self::backfill_logs(&connection_pool, &sink, &mut filter).await {
+                Ok(true) => { /* continue with live notifications */ }
+                Ok(false) => return,
+                Err(err) => {
+                    tracing::warn!("Failed backfilling logs for subscription: {err:#}");
+                    return;
                 }
             }
+        }
+        Self::run_subscriber(
+            sink,
+            SubscriptionType::Logs,
+            receiver,
+            Some(filter),
+            queue_config,
+        )
+        .await;
+    }
 
-            sink.send_timeout(
-                SubscriptionMessage::from_json(&item)
-                    .expect("PubSubResult always serializable to json;qed"),
-                SUBSCRIPTION_SINK_SEND_TIMEOUT,
-            )
-            .await?;
+    /// Sends logs stored in Postgres after the filter cursor to the subscriber, advancing the cursor.
+    /// Returns `false` if the subscription was closed by the client.
+    async fn backfill_logs(
+        connection_pool: &ConnectionPool,
+        sink: &SubscriptionSink,
+        filter: &mut PubSubFilter,
+    ) -> anyhow::Result<bool> {
+        let addresses = filter
+            .address
+            .as_ref()
+            .map_or_else(Vec::new, |addresses| addresses.0.clone());
+        let topics: Vec<_> = filter
+            .topics
+            .iter()
+            .flatten()
+            .enumerate()
+            .filter_map(|(idx, topics)| Some((idx as u32 + 1, topics.as_ref()?.0.clone())))
+            .collect();
+
+        let backfill_latency = PUB_SUB_METRICS.logs_backfill_latency.start();
+        while let Some(cursor) = filter.cursor {
+            let from_block = u32::try_from(cursor.block_number.as_u64());
+            let start_log_index = u32::try_from(cursor.log_index.as_u64().saturating_add(1));
+            let (Ok(from_block), Ok(start_log_index)) = (from_block, start_log_index) else {
+                // The cursor is beyond any existing log.
+                break;
+            };
+
+            let get_logs_filter = GetLogsFilter {
+                from_block: MiniblockNumber(from_block),
+                to_block: MiniblockNumber(u32::MAX),
+                addresses: addresses.clone(),
+                topics: topics.clone(),
+            };
+            let logs = connection_pool
+                .access_storage_tagged("api")
+                .await
+                .context("access_storage_tagged")?
+                .events_web3_dal()
+                .get_logs_from_index(get_logs_filter, start_log_index, LOGS_BACKFILL_PAGE_SIZE)
+                .await
+                .context("get_logs_from_index()")?;
+
+            for log in &logs {
+                let message = SubscriptionMessage::from_json(log)
+                    .expect("Log always serializable to json;qed");
+                if sink.send(message).await.is_err() {
+                    return Ok(false);
+                }
+                PUB_SUB_METRICS.backfilled_logs.inc();
+            }
+            if let Some(last_log) = logs.last() {
+                filter.cursor = LogCursor::for_log(last_log);
+            }
+            if logs.len() < LOGS_BACKFILL_PAGE_SIZE {
+                break;
+            }
+        }
+        backfill_latency.observe();
+        Ok(true)
+    }
 
-            PUB_SUB_METRICS.notify[&subscription_type].inc();
+    fn matches_filter(item: &PubSubResult, filter: Option<&PubSubFilter>) -> bool {
+        match (item, filter) {
+            (PubSubResult::Log(log), Some(filter)) => filter.matches(log),
+            _ => true,
         }
+    }
 
+    /// Sends an item to the subscriber and returns whether it was sent. The returned future is cancel-safe;
+    /// the item is only removed from the queue by the caller once it is sent.
+    async fn send_item(
+        sink: &SubscriptionSink,
+        subscription_type: SubscriptionType,
+        item: Option<&PubSubResult>,
+    ) -> bool {
+        let item = item.expect("queue is checked to be non-empty");
+        let notify_latency = PUB_SUB_METRICS.notify_subscribers_latency[&subscription_type].start();
+        let message = SubscriptionMessage::from_json(item)
+            .expect("PubSubResult always serializable to json;qed");
+        let is_sent = sink.send(message).await.is_ok();
         notify_latency.observe();
-        Ok(())
+        is_sent
     }
 
     #[tracing::instrument(skip(self, pending_sink))]
@@ -313,7 +660,7 @@ impl EthSubscribe {
         &self,
         pending_sink: PendingSubscriptionSink,
         sub_type: String,
-        params: Option<PubSubFilter>,
+        params: Option<PubSubParams>,
     ) {
         let sub_type = match sub_type.as_str() {
             "newHeads" => {
@@ -326,6 +673,7 @@ impl EthSubscribe {
                     SubscriptionType::Blocks,
                     blocks_rx,
                     None,
+                    self.queue_config,
                 ));
 
                 Some(SubscriptionType::Blocks)
@@ -334,36 +682,67 @@ impl EthSubscribe {
                 let Ok(sink) = pending_sink.accept().await else {
                     return;
                 };
-                let transactions_rx = self.transactions.subscribe();
+                let (sub_type, transactions_rx) =
+                    if matches!(params, Some(PubSubParams::FullTransactions(true))) {
+                        let full_transactions_rx = self.full_transactions.subscribe();
+                        (SubscriptionType::FullTxs, full_transactions_rx)
+                    } else {
+                        (SubscriptionType::Txs, self.transactions.subscribe())
+                    };
                 tokio::spawn(Self::run_subscriber(
                     sink,
-                    SubscriptionType::Txs,
+                    sub_type,
                     transactions_rx,
                     None,
+                    self.queue_config,
                 ));
-                Some(SubscriptionType::Txs)
+                Some(sub_type)
             }
             "logs" => {
-                let filter = params.unwrap_or_default();
-                let topic_count = filter.topics.as_ref().map_or(0, Vec::len);
+                let filter = match params {
+                    None => Some(PubSubFilter::default()),
+                    Some(PubSubParams::Filter(filter)) => Some(filter),
+                    Some(PubSubParams::FullTransactions(_)) => None,
+                };
+                let filter = filter.filter(|filter| {
+                    let topic_count = filter.topics.as_ref().map_or(0, Vec::len);
+                    topic_count <= EVENT_TOPIC_NUMBER_LIMIT
+                });
 
-                if topic_count > EVENT_TOPIC_NUMBER_LIMIT {
-                    Self::reject(pending_sink).await;
-                    None
-                } else {
+                if let Some(filter) = filter {
                     let Ok(sink) = pending_sink.accept().await else {
                         return;
                     };
+                    // Subscribe to live notifications before backfilling so that no logs are missed
+                    // in between; duplicates are filtered out using the filter cursor.
                     let logs_rx = self.logs.subscribe();
-                    tokio::spawn(Self::run_subscriber(
+                    tokio::spawn(Self::run_logs_subscriber(
+                        self.connection_pool.clone(),
                         sink,
-                        SubscriptionType::Logs,
                         logs_rx,
-                        Some(filter),
+                        filter,
+                        self.queue_config,
                     ));
                     Some(SubscriptionType::Logs)
+                } else {
+                    Self::reject(pending_sink).await;
+                    None
                 }
             }
+            "l1BatchStatus" => {
+                let Ok(sink) = pending_sink.accept().await else {
+                    return;
+                };
+                let l1_batches_rx = self.l1_batches.subscribe();
+                tokio::spawn(Self::run_subscriber(
+                    sink,
+                    SubscriptionType::L1Batches,
+                    l1_batches_rx,
+                    None,
+                    self.queue_config,
+                ));
+                Some(SubscriptionType::L1Batches)
+            }
             "syncing" => {
                 let Ok(sink) = pending_sink.accept().await else {
                     return;
@@ -394,11 +773,12 @@ impl EthSubscribe {
     /// Spawns notifier tasks. This should be called once per instance.
     pub fn spawn_notifiers(
         &self,
-        connection_pool: ConnectionPool,
         polling_interval: Duration,
+        chain_id: L2ChainId,
         stop_receiver: watch::Receiver<bool>,
     ) -> Vec<JoinHandle<anyhow::Result<()>>> {
-        let mut notifier_tasks = Vec::with_capacity(3);
+        let connection_pool = self.connection_pool.clone();
+        let mut notifier_tasks = Vec::with_capacity(5);
 
         let notifier = PubSubNotifier {
             sender: self.blocks.clone(),
@@ -418,6 +798,24 @@ impl EthSubscribe {
         let notifier_task = tokio::spawn(notifier.notify_txs(stop_receiver.clone()));
         notifier_tasks.push(notifier_task);
 
+        let notifier = PubSubNotifier {
+            sender: self.full_transactions.clone(),
+            connection_pool: connection_pool.clone(),
+            polling_interval,
+            events_sender: self.events_sender.clone(),
+        };
+        let notifier_task = tokio::spawn(notifier.notify_full_txs(chain_id, stop_receiver.clone()));
+        notifier_tasks.push(notifier_task);
+
+        let notifier = PubSubNotifier {
+            sender: self.l1_batches.clone(),
+            connection_pool: connection_pool.clone(),
+            polling_interval,
+            events_sender: self.events_sender.clone(),
+        };
+        let notifier_task = tokio::spawn(notifier.notify_l1_batches(stop_receiver.clone()));
+        notifier_tasks.push(notifier_task);
+
         let notifier = PubSubNotifier {
             sender: self.logs.clone(),
             connection_pool,
@@ -437,9 +835,73 @@ impl EthPubSubServer for EthSubscribe {
         &self,
         pending: PendingSubscriptionSink,
         sub_type: String,
-        filter: Option<PubSubFilter>,
+        params: Option<PubSubParams>,
     ) -> SubscriptionResult {
-        self.sub(pending, sub_type, filter).await;
+        self.sub(pending, sub_type, params).await;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queued_hashes(queue: &SubscriberQueue) -> Vec<H256> {
+        queue
+            .items
+            .iter()
+            .map(|item| match item {
+                PubSubResult::TxHash(hash) => *hash,
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    fn create_queue(policy: SubscriptionBackpressurePolicy) -> SubscriberQueue {
+        let config = SubscriptionQueueConfig {
+            capacity: 2,
+            policy,
+        };
+        SubscriberQueue::new(SubscriptionType::Txs, config)
+    }
+
+    fn tx_hashes(bytes: impl IntoIterator<Item = u8>) -> impl Iterator<Item = PubSubResult> {
+        bytes
+            .into_iter()
+            .map(|byte| PubSubResult::TxHash(H256::repeat_byte(byte)))
+    }
+
+    #[test]
+    fn subscriber_queue_with_disconnect_policy() {
+        let mut queue = create_queue(SubscriptionBackpressurePolicy::Disconnect);
+        assert!(queue.extend(tx_hashes([1, 2])));
+        assert!(!queue.extend(tx_hashes([3])));
+    }
+
+    #[test]
+    fn subscriber_queue_with_drop_oldest_policy() {
+        let mut queue = create_queue(SubscriptionBackpressurePolicy::DropOldest);
+        assert!(queue.extend(tx_hashes([1, 2, 3])));
+        assert_eq!(
+            queued_hashes(&queue),
+            [H256::repeat_byte(2), H256::repeat_byte(3)]
+        );
+    }
+
+    #[test]
+    fn subscriber_queue_with_drop_newest_policy() {
+        let mut queue = create_queue(SubscriptionBackpressurePolicy::DropNewest);
+        assert!(queue.extend(tx_hashes([1, 2, 3])));
+        assert_eq!(
+            queued_hashes(&queue),
+            [H256::repeat_byte(1), H256::repeat_byte(2)]
+        );
+
+        queue.pop_front();
+        assert!(queue.extend(tx_hashes([4])));
+        assert_eq!(
+            queued_hashes(&queue),
+            [H256::repeat_byte(2), H256::repeat_byte(4)]
+        );
+    }
+}
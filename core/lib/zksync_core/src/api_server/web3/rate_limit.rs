@@ -0,0 +1,470 @@
+//! HTTP middleware limiting the number of JSON-RPC calls and compute units spent by a single client.
+//!
+//! Clients are identified either by an API key passed in the `X-API-Key` header (only keys listed in
+//! [`RateLimits::api_keys`] are recognized), or by their IP address. The IP address is the peer address
+//! of the connection, unless the server is configured to run behind [`RateLimits::trusted_proxy_hops`]
+//! reverse proxies. In the latter case, the address is taken from the `X-Forwarded-For` entry appended
+//! by the outermost trusted proxy; entries preceding it are controlled by clients and are ignored.
+//!
+//! For the WebSocket server, only connection upgrade requests are rate-limited by this middleware;
+//! calls within a connection are limited by `websocket_requests_per_minute_limit`.
+
+use std::{
+    collections::HashSet,
+    hash::Hash,
+    net::IpAddr,
+    num::NonZeroU32,
+    sync::{Arc, Weak},
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures::future::BoxFuture;
+use governor::{clock::DefaultClock, state::keyed::DefaultKeyedStateStore, Quota, RateLimiter};
+use hyper::{
+    body::{Bytes, HttpBody},
+    header::{self, HeaderMap},
+    Body, Method, Request, Response, StatusCode,
+};
+use serde::Deserialize;
+use tower::{Layer, Service};
+use zksync_config::configs::api::Web3JsonRpcConfig;
+
+use super::{
+    metrics::{
+        ApiTransportLabel, RateLimitClientKind, RateLimitKind, RateLimitLabels, RATE_LIMIT_METRICS,
+    },
+    peer_addr::PeerAddr,
+};
+
+/// Header containing the API key.
+pub(super) const API_KEY_HEADER: &str = "x-api-key";
+/// Header containing IP addresses of the client and proxies that forwarded the request.
+const FORWARDED_FOR_HEADER: &str = "x-forwarded-for";
+/// JSON-RPC error code for exceeded limits (as per EIP-1474).
+const LIMIT_EXCEEDED_CODE: i32 = -32_005;
+/// Max size of a request body read by the middleware. Matches the default `jsonrpsee` limit.
+const MAX_REQUEST_BODY_SIZE: usize = 10 * 1_024 * 1_024;
+/// Interval between removing stale client states from rate limiters.
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Returns the number of compute units spent by a call to the specified method.
+fn method_compute_units(method: &str) -> u32 {
+    if method.starts_with("debug_") || method.starts_with("trace_") {
+        return 20;
+    }
+    match method {
+        "eth_call"
+        | "eth_estimateGas"
+        | "eth_createAccessList"
+        | "eth_simulateV1"
+        | "eth_getLogs"
+        | "eth_getFilterLogs"
+        | "eth_sendRawTransaction"
+        | "eth_sendRawTransactionConditional"
+        | "zks_estimateFee"
+        | "zks_estimateGasL1ToL2"
+        | "zks_sendBundle"
+        | "zks_sendPrivateRawTransaction" => 5,
+        _ => 1,
+    }
+}
+
+/// Request and compute unit budgets for a single client.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RateLimitBudget {
+    pub requests_per_minute: Option<NonZeroU32>,
+    pub compute_units_per_minute: Option<NonZeroU32>,
+}
+
+impl RateLimitBudget {
+    fn is_limited(&self) -> bool {
+        self.requests_per_minute.is_some() || self.compute_units_per_minute.is_some()
+    }
+}
+
+/// Rate limits applied by the API server.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RateLimits {
+    /// Budget for a single client IP address.
+    pub per_ip: RateLimitBudget,
+    /// Budget for a single API key.
+    pub per_api_key: RateLimitBudget,
+    /// Recognized API keys.
+    pub api_keys: HashSet<String>,
+    /// Number of trusted reverse proxies in front of the server.
+    pub trusted_proxy_hops: usize,
+}
+
+impl From<&Web3JsonRpcConfig> for RateLimits {
+    fn from(config: &Web3JsonRpcConfig) -> Self {
+        Self {
+            per_ip: RateLimitBudget {
+                requests_per_minute: config.rate_limit_requests_per_minute_per_ip,
+                compute_units_per_minute: config.rate_limit_compute_units_per_minute_per_ip,
+            },
+            per_api_key: RateLimitBudget {
+                requests_per_minute: config.rate_limit_requests_per_minute_per_api_key,
+                compute_units_per_minute: config.rate_limit_compute_units_per_minute_per_api_key,
+            },
+            api_keys: config
+                .rate_limit_api_keys
+                .iter()
+                .flatten()
+                .cloned()
+                .collect(),
+            trusted_proxy_hops: config.rate_limit_trusted_proxy_hops.unwrap_or(0),
+        }
+    }
+}
+
+impl RateLimits {
+    pub(super) fn is_enabled(&self) -> bool {
+        self.per_ip.is_limited() || self.per_api_key.is_limited()
+    }
+}
+
+type KeyedRateLimiter<K> = RateLimiter<K, DefaultKeyedStateStore<K>, DefaultClock>;
+
+/// Costs of a single HTTP request.
+#[derive(Debug, Clone, Copy)]
+struct RequestCost {
+    requests: NonZeroU32,
+    compute_units: NonZeroU32,
+}
+
+impl RequestCost {
+    const MIN: Self = Self {
+        requests: NonZeroU32::MIN,
+        compute_units: NonZeroU32::MIN,
+    };
+
+    fn new<'a>(methods: impl Iterator<Item = &'a str>) -> Self {
+        let (requests, compute_units) =
+            methods.fold((0_u32, 0_u32), |(requests, units), method| {
+                let units = units.saturating_add(method_compute_units(method));
+                (requests.saturating_add(1), units)
+            });
+        Self {
+            requests: NonZeroU32::new(requests).unwrap_or(NonZeroU32::MIN),
+            compute_units: NonZeroU32::new(compute_units).unwrap_or(NonZeroU32::MIN),
+        }
+    }
+}
+
+/// Rate limiters for a single kind of clients.
+struct KeyedBudget<K> {
+    requests: Option<KeyedRateLimiter<K>>,
+    compute_units: Option<KeyedRateLimiter<K>>,
+}
+
+impl<K: Hash + Eq + Clone> KeyedBudget<K> {
+    fn new(budget: RateLimitBudget) -> Self {
+        Self {
+            requests: budget
+                .requests_per_minute
+                .map(|limit| RateLimiter::keyed(Quota::per_minute(limit))),
+            compute_units: budget
+                .compute_units_per_minute
+                .map(|limit| RateLimiter::keyed(Quota::per_minute(limit))),
+        }
+    }
+
+    fn check(&self, key: &K, cost: RequestCost) -> Result<(), RateLimitKind> {
+        if let Some(limiter) = &self.requests {
+            if limiter.check_key_n(key, cost.requests).is_err() {
+                return Err(RateLimitKind::Requests);
+            }
+        }
+        if let Some(limiter) = &self.compute_units {
+            if limiter.check_key_n(key, cost.compute_units).is_err() {
+                return Err(RateLimitKind::ComputeUnits);
+            }
+        }
+        Ok(())
+    }
+
+    fn retain_recent(&self) {
+        for limiter in [&self.requests, &self.compute_units].into_iter().flatten() {
+            limiter.retain_recent();
+        }
+    }
+}
+
+/// Client of the API server as identified by the rate limiter.
+#[derive(Debug)]
+pub(super) enum Client {
+    ApiKey(String),
+    /// `None` corresponds to requests without an identifiable IP address.
+    Ip(Option<IpAddr>),
+}
+
+impl Client {
+    /// Identifies the client sending the specified request. Only API keys from `api_keys` are recognized.
+    ///
+    /// If `trusted_proxy_hops` is positive, the IP address is taken from the `X-Forwarded-For` entry appended
+    /// by the outermost trusted proxy. Otherwise, or if the header doesn't contain this entry, the IP address
+    /// is the peer address of the connection.
+    pub(super) fn identify<B>(
+        api_keys: &HashSet<String>,
+        trusted_proxy_hops: usize,
+        request: &Request<B>,
+    ) -> Self {
+        let headers = request.headers();
+        let api_key = headers
+            .get(API_KEY_HEADER)
+            .and_then(|value| value.to_str().ok());
+        if let Some(api_key) = api_key {
+            if api_keys.contains(api_key) {
+                return Self::ApiKey(api_key.to_owned());
+            }
+        }
+
+        let peer_ip = request
+            .extensions()
+            .get::<PeerAddr>()
+            .map(|PeerAddr(addr)| addr.ip());
+        let forwarded_ip = if trusted_proxy_hops > 0 {
+            forwarded_ip(headers, trusted_proxy_hops)
+        } else {
+            None
+        };
+        Self::Ip(forwarded_ip.or(peer_ip))
+    }
+}
+
+/// Returns the IP address appended to the `X-Forwarded-For` header by the outermost of `trusted_proxy_hops`
+/// trusted proxies. Each proxy appends the address it has received the request from, so this address is
+/// located `trusted_proxy_hops` entries from the end of the header.
+fn forwarded_ip(headers: &HeaderMap, trusted_proxy_hops: usize) -> Option<IpAddr> {
+    // Proxies may either append to an existing header or add a new one, so all header values are considered.
+    let mut entries = vec![];
+    for value in headers.get_all(FORWARDED_FOR_HEADER) {
+        entries.extend(value.to_str().ok()?.split(','));
+    }
+    let index = entries.len().checked_sub(trusted_proxy_hops)?;
+    entries[index].trim().parse().ok()
+}
+
+struct RateLimiterState {
+    transport: ApiTransportLabel,
+    api_keys: HashSet<String>,
+    trusted_proxy_hops: usize,
+    per_ip: KeyedBudget<Option<IpAddr>>,
+    per_api_key: KeyedBudget<String>,
+}
+
+impl RateLimiterState {
+    fn check(&self, client: &Client, cost: RequestCost) -> Result<(), RateLimitLabels> {
+        let (client_kind, result) = match client {
+            Client::ApiKey(key) => (
+                RateLimitClientKind::ApiKey,
+                self.per_api_key.check(key, cost),
+            ),
+            Client::Ip(ip) => (RateLimitClientKind::Ip, self.per_ip.check(ip, cost)),
+        };
+        result.map_err(|limit| RateLimitLabels {
+            scheme: self.transport,
+            client: client_kind,
+            limit,
+        })
+    }
+
+    async fn run_cleanup(this: Weak<Self>) {
+        let mut timer = tokio::time::interval(CLEANUP_INTERVAL);
+        loop {
+            timer.tick().await;
+            let Some(this) = this.upgrade() else {
+                return; // The server has been dropped
+            };
+            this.per_ip.retain_recent();
+            this.per_api_key.retain_recent();
+        }
+    }
+}
+
+/// Minimal representation of a JSON-RPC call sufficient for HTTP middleware.
+#[derive(Debug, Deserialize)]
+pub(super) struct RawCall {
+    #[serde(default)]
+    id: Option<serde_json::Value>,
+    #[serde(default)]
+    method: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub(super) enum RawRequest {
+    Single(RawCall),
+    Batch(Vec<RawCall>),
+}
+
+impl RawRequest {
+    /// Returns the ID of a single call; batches are not assigned an ID.
+    pub(super) fn id(&self) -> Option<&serde_json::Value> {
+        match self {
+            Self::Single(call) => call.id.as_ref(),
+            Self::Batch(_) => None,
+        }
+    }
+
+    pub(super) fn methods(&self) -> impl Iterator<Item = &str> + '_ {
+        let calls = match self {
+            Self::Single(call) => std::slice::from_ref(call),
+            Self::Batch(calls) => calls.as_slice(),
+        };
+        calls.iter().map(|call| call.method.as_str())
+    }
+}
+
+/// Reads the request body, limiting its size.
+pub(super) async fn read_body(mut body: Body) -> Result<Bytes, StatusCode> {
+    let mut buffer = vec![];
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|_| StatusCode::BAD_REQUEST)?;
+        if buffer.len() + chunk.len() > MAX_REQUEST_BODY_SIZE {
+            return Err(StatusCode::PAYLOAD_TOO_LARGE);
+        }
+        buffer.extend_from_slice(&chunk);
+    }
+    Ok(buffer.into())
+}
+
+fn limit_exceeded_response(
+    id: Option<&serde_json::Value>,
+    labels: RateLimitLabels,
+) -> Response<Body> {
+    let (client, limit) = match (labels.client, labels.limit) {
+        (RateLimitClientKind::Ip, RateLimitKind::Requests) => ("IP address", "request"),
+        (RateLimitClientKind::Ip, RateLimitKind::ComputeUnits) => ("IP address", "compute unit"),
+        (RateLimitClientKind::ApiKey, RateLimitKind::Requests) => ("API key", "request"),
+        (RateLimitClientKind::ApiKey, RateLimitKind::ComputeUnits) => ("API key", "compute unit"),
+    };
+    json_rpc_error_response(
+        StatusCode::TOO_MANY_REQUESTS,
+        id,
+        LIMIT_EXCEEDED_CODE,
+        &format!("{limit} limit exceeded for {client}"),
+    )
+}
+
+/// Creates an HTTP response with the specified status containing a JSON-RPC error.
+pub(super) fn json_rpc_error_response(
+    status: StatusCode,
+    id: Option<&serde_json::Value>,
+    code: i32,
+    message: &str,
+) -> Response<Body> {
+    let body = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {
+            "code": code,
+            "message": message,
+        },
+    });
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+/// Tower layer enforcing [`RateLimits`].
+#[derive(Clone)]
+pub(super) struct RateLimitLayer {
+    state: Arc<RateLimiterState>,
+}
+
+impl RateLimitLayer {
+    /// Creates a new layer and spawns a task periodically cleaning up stale client states.
+    /// The task terminates once the layer and all services created by it are dropped.
+    pub fn new(limits: RateLimits, transport: ApiTransportLabel) -> Self {
+        let state = Arc::new(RateLimiterState {
+            transport,
+            api_keys: limits.api_keys,
+            trusted_proxy_hops: limits.trusted_proxy_hops,
+            per_ip: KeyedBudget::new(limits.per_ip),
+            per_api_key: KeyedBudget::new(limits.per_api_key),
+        });
+        tokio::spawn(RateLimiterState::run_cleanup(Arc::downgrade(&state)));
+        Self { state }
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimitService {
+            inner,
+            state: self.state.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(super) struct RateLimitService<S> {
+    inner: S,
+    state: Arc<RateLimiterState>,
+}
+
+impl<S> Service<Request<Body>> for RateLimitService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        // Take the service that was driven to readiness and leave a fresh clone in its place.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let state = self.state.clone();
+
+        Box::pin(async move {
+            let client = Client::identify(&state.api_keys, state.trusted_proxy_hops, &request);
+            if request.method() != Method::POST {
+                // E.g., WebSocket upgrades or server-sent event streams
+                return match state.check(&client, RequestCost::MIN) {
+                    Ok(()) => inner.call(request).await,
+                    Err(labels) => {
+                        RATE_LIMIT_METRICS.rejected_requests[&labels].inc();
+                        Ok(limit_exceeded_response(None, labels))
+                    }
+                };
+            }
+
+            let (parts, body) = request.into_parts();
+            let body = match read_body(body).await {
+                Ok(body) => body,
+                Err(status) => {
+                    let response = Response::builder().status(status).body(Body::empty());
+                    return Ok(response.unwrap());
+                }
+            };
+            // Malformed requests are passed through; they will be rejected by `jsonrpsee`.
+            let raw_request = serde_json::from_slice::<RawRequest>(&body).ok();
+            let id = raw_request.as_ref().and_then(RawRequest::id).cloned();
+            let cost = raw_request.map_or(RequestCost::MIN, |request| {
+                RequestCost::new(request.methods())
+            });
+
+            if let Err(labels) = state.check(&client, cost) {
+                RATE_LIMIT_METRICS.rejected_requests[&labels].inc();
+                return Ok(limit_exceeded_response(id.as_ref(), labels));
+            }
+            RATE_LIMIT_METRICS.compute_units[&state.transport]
+                .inc_by(cost.compute_units.get().into());
+            inner
+                .call(Request::from_parts(parts, Body::from(body)))
+                .await
+        })
+    }
+}
@@ -0,0 +1,246 @@
+//! Server-sent events (SSE) endpoint for new miniblock headers and sync status, served by the HTTP server
+//! on `GET /events`. Intended for environments where WebSockets are not available.
+//!
+//! Miniblock headers are emitted as `newHeads` events with the miniblock number as the event ID; clients
+//! reconnecting with the `Last-Event-ID` header receive headers of miniblocks they have missed. Sync status
+//! is emitted as `syncing` events when the stream is opened and whenever the status changes.
+
+use std::{
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use anyhow::Context as _;
+use futures::future::BoxFuture;
+use hyper::{
+    body::{Bytes, Sender},
+    header, Body, Method, Request, Response, StatusCode,
+};
+use tokio::sync::broadcast;
+use tower::{Layer, Service};
+use zksync_dal::ConnectionPool;
+use zksync_types::MiniblockNumber;
+use zksync_web3_decl::types::{BlockHeader, PubSubResult};
+
+use super::{
+    metrics::{SubscriptionType, SSE_METRICS},
+    pubsub::EthSubscribe,
+    state::sync_status,
+};
+use crate::sync_layer::SyncState;
+
+/// Path of the SSE endpoint.
+pub(super) const SSE_PATH: &str = "/events";
+/// Interval between keep-alive comments sent to idle clients.
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+/// Max number of miniblock headers sent to a client resuming the stream using `Last-Event-ID`.
+const MAX_BACKFILLED_MINIBLOCKS: u32 = 1_024;
+
+/// Shared state of the SSE endpoint.
+struct SseState {
+    pub_sub: EthSubscribe,
+    connection_pool: ConnectionPool,
+    sync_state: Option<SyncState>,
+}
+
+impl SseState {
+    fn serve(self: Arc<Self>, last_event_id: Option<MiniblockNumber>) -> Response<Body> {
+        // Subscribe to notifications before backfilling so that no headers are missed; duplicates
+        // are filtered out by the miniblock number.
+        let receiver = self.pub_sub.subscribe_raw(SubscriptionType::Blocks);
+        let (sender, body) = Body::channel();
+        tokio::spawn(async move {
+            let _guard = SSE_METRICS.active_streams.inc_guard(1);
+            if let Err(err) = self.stream_events(sender, receiver, last_event_id).await {
+                tracing::debug!("SSE stream terminated: {err:#}");
+            }
+        });
+
+        Response::builder()
+            .header(header::CONTENT_TYPE, "text/event-stream")
+            .header(header::CACHE_CONTROL, "no-cache")
+            .body(body)
+            .unwrap()
+    }
+
+    async fn stream_events(
+        &self,
+        mut sender: Sender,
+        mut receiver: broadcast::Receiver<Vec<PubSubResult>>,
+        last_event_id: Option<MiniblockNumber>,
+    ) -> anyhow::Result<()> {
+        let mut current_sync_status = sync_status(self.sync_state.as_ref());
+        sender
+            .send_data(Self::event("syncing", None, &current_sync_status))
+            .await?;
+
+        let mut last_sent_miniblock = last_event_id;
+        if let Some(last_event_id) = last_event_id {
+            for header in self.backfilled_headers(last_event_id).await? {
+                Self::send_header(&mut sender, header, &mut last_sent_miniblock).await?;
+            }
+        }
+
+        let mut keep_alive_timer = tokio::time::interval(KEEP_ALIVE_INTERVAL);
+        keep_alive_timer.tick().await; // The first tick completes immediately
+        loop {
+            let items = tokio::select! {
+                _ = keep_alive_timer.tick() => {
+                    sender.send_data(Bytes::from_static(b": keep-alive\n\n")).await?;
+                    continue;
+                }
+                items = receiver.recv() => items,
+            };
+            let items = match items {
+                Ok(items) => items,
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+                Err(broadcast::error::RecvError::Lagged(_)) => {
+                    // The client can reconnect using `Last-Event-ID` to receive skipped headers.
+                    SSE_METRICS.lagged_streams.inc();
+                    anyhow::bail!("client is too slow to process events");
+                }
+            };
+
+            for item in items {
+                if let PubSubResult::Header(header) = item {
+                    Self::send_header(&mut sender, header, &mut last_sent_miniblock).await?;
+                }
+            }
+            let new_sync_status = sync_status(self.sync_state.as_ref());
+            if new_sync_status != current_sync_status {
+                current_sync_status = new_sync_status;
+                sender
+                    .send_data(Self::event("syncing", None, &current_sync_status))
+                    .await?;
+            }
+            keep_alive_timer.reset();
+        }
+    }
+
+    async fn backfilled_headers(
+        &self,
+        last_event_id: MiniblockNumber,
+    ) -> anyhow::Result<Vec<BlockHeader>> {
+        let mut storage = self
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .context("access_storage_tagged")?;
+        let sealed_miniblock = storage
+            .blocks_web3_dal()
+            .get_sealed_miniblock_number()
+            .await
+            .context("get_sealed_miniblock_number()")?;
+        let start = sealed_miniblock
+            .0
+            .saturating_sub(MAX_BACKFILLED_MINIBLOCKS)
+            .max(last_event_id.0);
+        storage
+            .blocks_web3_dal()
+            .get_block_headers_after(MiniblockNumber(start))
+            .await
+            .with_context(|| format!("get_block_headers_after({start})"))
+    }
+
+    async fn send_header(
+        sender: &mut Sender,
+        header: BlockHeader,
+        last_sent_miniblock: &mut Option<MiniblockNumber>,
+    ) -> anyhow::Result<()> {
+        let Some(number) = header.number else {
+            return Ok(());
+        };
+        let number = MiniblockNumber(number.as_u32());
+        if last_sent_miniblock.map_or(false, |last| number <= last) {
+            return Ok(()); // The header was already sent during backfilling
+        }
+        sender
+            .send_data(Self::event("newHeads", Some(number), &header))
+            .await?;
+        *last_sent_miniblock = Some(number);
+        Ok(())
+    }
+
+    fn event(name: &str, id: Option<MiniblockNumber>, data: &impl serde::Serialize) -> Bytes {
+        let data = serde_json::to_string(data).expect("failed serializing event data");
+        let event = match id {
+            Some(id) => format!("id: {id}\nevent: {name}\ndata: {data}\n\n"),
+            None => format!("event: {name}\ndata: {data}\n\n"),
+        };
+        event.into()
+    }
+}
+
+/// Tower layer serving SSE on [`SSE_PATH`]; other requests are passed to the wrapped service.
+#[derive(Clone)]
+pub(super) struct SseLayer {
+    state: Arc<SseState>,
+}
+
+impl SseLayer {
+    pub fn new(
+        pub_sub: EthSubscribe,
+        connection_pool: ConnectionPool,
+        sync_state: Option<SyncState>,
+    ) -> Self {
+        Self {
+            state: Arc::new(SseState {
+                pub_sub,
+                connection_pool,
+                sync_state,
+            }),
+        }
+    }
+}
+
+impl<S> Layer<S> for SseLayer {
+    type Service = SseService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        SseService {
+            inner,
+            state: self.state.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(super) struct SseService<S> {
+    inner: S,
+    state: Arc<SseState>,
+}
+
+impl<S> Service<Request<Body>> for SseService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>>,
+    S::Future: Send + 'static,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        if request.method() != Method::GET || request.uri().path() != SSE_PATH {
+            return Box::pin(self.inner.call(request));
+        }
+
+        let last_event_id = request.headers().get("last-event-id").map(|value| {
+            let value = value.to_str().ok()?;
+            value.parse::<u32>().ok().map(MiniblockNumber)
+        });
+        let response = match last_event_id {
+            Some(None) => Response::builder()
+                .status(StatusCode::BAD_REQUEST)
+                .body(Body::from("Invalid Last-Event-ID header"))
+                .unwrap(),
+            Some(Some(id)) => self.state.clone().serve(Some(id)),
+            None => self.state.clone().serve(None),
+        };
+        Box::pin(async { Ok(response) })
+    }
+}
@@ -1,5 +1,6 @@
 use std::{
     future::Future,
+    num::NonZeroUsize,
     sync::{
         atomic::{AtomicU32, Ordering},
         Arc,
@@ -11,20 +12,22 @@ use lru::LruCache;
 use tokio::sync::Mutex;
 use vise::GaugeGuard;
 use zksync_config::configs::{api::Web3JsonRpcConfig, chain::NetworkConfig, ContractsConfig};
-use zksync_dal::ConnectionPool;
+use zksync_dal::{pruning_dal::PruningInfo, ConnectionPool, SqlxError, StorageProcessor};
 use zksync_types::{
-    api, l2::L2Tx, transaction_request::CallRequest, Address, L1ChainId, L2ChainId,
-    MiniblockNumber, H256, U256, U64,
+    api, fee_model::BaseTokenConversionRatio, l2::L2Tx, transaction_request::CallRequest, web3,
+    web3::signing::keccak256, Address, L1BatchNumber, L1ChainId, L2ChainId, MiniblockNumber, H256,
+    L2_ETH_TOKEN_ADDRESS, U256, U64,
 };
+use zksync_utils::{h256_to_u256, u256_to_h256};
 use zksync_web3_decl::{error::Web3Error, types::Filter};
 
-use super::metrics::{FilterType, FILTER_METRICS};
+use super::metrics::{CacheResult, FilterType, ReadPoolLabel, API_METRICS, FILTER_METRICS};
 use crate::{
     api_server::{
         execution_sandbox::BlockArgs,
         tree::TreeApiHttpClient,
         tx_sender::TxSender,
-        web3::{backend_jsonrpsee::internal_error, resolve_block, TypedFilter},
+        web3::{backend_jsonrpsee::internal_error, TypedFilter},
     },
     sync_layer::SyncState,
 };
@@ -43,8 +46,17 @@ pub struct InternalApiConfig {
     pub bridge_addresses: api::BridgeAddresses,
     pub diamond_proxy_addr: Address,
     pub l2_testnet_paymaster_addr: Option<Address>,
+    pub fee_token: api::FeeToken,
     pub req_entities_limit: usize,
     pub fee_history_limit: u64,
+    pub trace_block_reexecution_enabled: bool,
+    pub max_priority_fee_smoothing_factor: f64,
+    pub eth_call_cache_capacity: usize,
+    pub get_logs_block_range_limit: Option<u64>,
+    pub get_logs_max_results: usize,
+    pub trace_call_many_max_calls: usize,
+    pub trace_filter_block_range_limit: u64,
+    pub max_tree_lag_for_proofs: Option<u32>,
 }
 
 impl InternalApiConfig {
@@ -52,6 +64,7 @@ impl InternalApiConfig {
         eth_config: &NetworkConfig,
         web3_config: &Web3JsonRpcConfig,
         contracts_config: &ContractsConfig,
+        base_token_conversion_ratio: BaseTokenConversionRatio,
     ) -> Self {
         Self {
             l1_chain_id: eth_config.network.chain_id(),
@@ -68,8 +81,22 @@ impl InternalApiConfig {
             },
             diamond_proxy_addr: contracts_config.diamond_proxy_addr,
             l2_testnet_paymaster_addr: contracts_config.l2_testnet_paymaster_addr,
+            fee_token: api::FeeToken {
+                l1_address: contracts_config.base_token_addr.unwrap_or_default(),
+                // Base token balances are kept by the same system contract as ETH balances.
+                l2_address: L2_ETH_TOKEN_ADDRESS,
+                conversion_ratio: base_token_conversion_ratio,
+            },
             req_entities_limit: web3_config.req_entities_limit(),
             fee_history_limit: web3_config.fee_history_limit(),
+            trace_block_reexecution_enabled: web3_config.trace_block_reexecution_enabled,
+            max_priority_fee_smoothing_factor: web3_config.max_priority_fee_smoothing_factor(),
+            eth_call_cache_capacity: web3_config.eth_call_cache_capacity(),
+            get_logs_block_range_limit: web3_config.get_logs_block_range_limit,
+            get_logs_max_results: web3_config.get_logs_max_results(),
+            trace_call_many_max_calls: web3_config.trace_call_many_max_calls(),
+            trace_filter_block_range_limit: web3_config.trace_filter_block_range_limit(),
+            max_tree_lag_for_proofs: web3_config.max_tree_lag_for_proofs,
         }
     }
 }
@@ -132,6 +159,10 @@ impl SealedMiniblockNumber {
         MiniblockNumber(prev_value).max(maybe_newer_miniblock_number)
     }
 
+    pub fn get(&self) -> MiniblockNumber {
+        MiniblockNumber(self.0.load(Ordering::Relaxed))
+    }
+
     pub fn diff(&self, miniblock_number: MiniblockNumber) -> u32 {
         let sealed_miniblock_number = self.update(miniblock_number);
         sealed_miniblock_number.0.saturating_sub(miniblock_number.0)
@@ -151,19 +182,442 @@ impl SealedMiniblockNumber {
     }
 }
 
+/// Read replica of the main DB used for read-only queries (blocks, receipts, logs and traces).
+///
+/// Replication lag is tracked by comparing the last sealed miniblock in the replica with the one in the main DB.
+/// Queries for a specific miniblock are routed to the replica only if the replica contains this miniblock;
+/// queries for the latest state only if the replication lag is within the configured bound. Queries for
+/// the pending block are never routed to the replica.
+#[derive(Debug, Clone)]
+pub(crate) struct ReadReplica {
+    pool: ConnectionPool,
+    last_sealed_miniblock: SealedMiniblockNumber,
+    main_last_sealed_miniblock: SealedMiniblockNumber,
+    max_lag: u32,
+}
+
+impl ReadReplica {
+    /// Creates a replica together with a task that will update its last sealed miniblock on a schedule.
+    pub fn new(
+        pool: ConnectionPool,
+        max_lag: u32,
+        main_last_sealed_miniblock: SealedMiniblockNumber,
+        update_interval: Duration,
+    ) -> (Self, impl Future<Output = ()> + Send) {
+        let (last_sealed_miniblock, update_task) =
+            SealedMiniblockNumber::new(pool.clone(), update_interval);
+        let this = Self {
+            pool,
+            last_sealed_miniblock,
+            main_last_sealed_miniblock,
+            max_lag,
+        };
+        (this, update_task)
+    }
+
+    fn can_serve(&self, block_id: api::BlockId) -> bool {
+        let replica_miniblock = self.last_sealed_miniblock.get();
+        let lag = self.main_last_sealed_miniblock.diff(replica_miniblock);
+        API_METRICS.read_replica_lag.set(lag.into());
+
+        match block_id {
+            api::BlockId::Number(api::BlockNumber::Pending) => false,
+            api::BlockId::Number(api::BlockNumber::Earliest) => true,
+            api::BlockId::Number(api::BlockNumber::Number(number)) => {
+                number <= U64::from(replica_miniblock.0)
+            }
+            _ => lag <= self.max_lag,
+        }
+    }
+
+    /// Returns the replica pool if it can serve read-only queries for the specified block.
+    pub fn pool_for_block(&self, block_id: api::BlockId) -> Option<&ConnectionPool> {
+        if self.can_serve(block_id) {
+            API_METRICS.read_queries[&ReadPoolLabel::Replica].inc();
+            Some(&self.pool)
+        } else {
+            API_METRICS.read_queries[&ReadPoolLabel::Main].inc();
+            None
+        }
+    }
+}
+
+/// Information about the earliest miniblock and L1 batch available in Postgres, taking data pruning into account.
+///
+/// Pruning information is cached for a short period of time, since it changes rarely, but would otherwise
+/// be loaded for each request referencing a historical block.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BlockStartInfo {
+    cached_pruning_info: Arc<Mutex<Option<(Instant, PruningInfo)>>>,
+}
+
+impl BlockStartInfo {
+    const CACHE_TTL: Duration = Duration::from_secs(10);
+
+    async fn pruning_info(
+        &self,
+        connection: &mut StorageProcessor<'_>,
+        method_name: &'static str,
+    ) -> Result<PruningInfo, Web3Error> {
+        let mut cached = self.cached_pruning_info.lock().await;
+        if let Some((updated_at, info)) = *cached {
+            if updated_at.elapsed() < Self::CACHE_TTL {
+                return Ok(info);
+            }
+        }
+        let info = connection
+            .pruning_dal()
+            .get_pruning_info()
+            .await
+            .map_err(|err| internal_error(method_name, err))?;
+        *cached = Some((Instant::now(), info));
+        Ok(info)
+    }
+
+    /// Returns the earliest miniblock with available data.
+    pub async fn first_miniblock(
+        &self,
+        connection: &mut StorageProcessor<'_>,
+        method_name: &'static str,
+    ) -> Result<MiniblockNumber, Web3Error> {
+        let info = self.pruning_info(connection, method_name).await?;
+        Ok(info.first_miniblock())
+    }
+
+    /// Checks that data for the specified miniblock is not pruned.
+    pub async fn ensure_block_not_pruned(
+        &self,
+        connection: &mut StorageProcessor<'_>,
+        block_number: MiniblockNumber,
+        method_name: &'static str,
+    ) -> Result<(), Web3Error> {
+        let first_miniblock = self.first_miniblock(connection, method_name).await?;
+        if block_number < first_miniblock {
+            return Err(Web3Error::PrunedBlock(first_miniblock));
+        }
+        Ok(())
+    }
+
+    /// Checks that data for the specified L1 batch is not pruned.
+    pub async fn ensure_l1_batch_not_pruned(
+        &self,
+        connection: &mut StorageProcessor<'_>,
+        l1_batch_number: L1BatchNumber,
+        method_name: &'static str,
+    ) -> Result<(), Web3Error> {
+        let info = self.pruning_info(connection, method_name).await?;
+        let first_l1_batch = info.first_l1_batch();
+        if l1_batch_number < first_l1_batch {
+            return Err(Web3Error::PrunedL1Batch(first_l1_batch));
+        }
+        Ok(())
+    }
+
+    /// Maps the earliest block to the first miniblock with available data; other block IDs are returned as is.
+    async fn map_earliest_block(
+        &self,
+        connection: &mut StorageProcessor<'_>,
+        block_id: api::BlockId,
+        method_name: &'static str,
+    ) -> Result<api::BlockId, Web3Error> {
+        Ok(match block_id {
+            api::BlockId::Number(api::BlockNumber::Earliest) => {
+                let first_miniblock = self.first_miniblock(connection, method_name).await?;
+                api::BlockId::Number(api::BlockNumber::Number(first_miniblock.0.into()))
+            }
+            _ => block_id,
+        })
+    }
+
+    /// Resolves the specified block ID to a miniblock number, returning an error if the block doesn't exist
+    /// or its data is pruned.
+    pub async fn resolve_block(
+        &self,
+        connection: &mut StorageProcessor<'_>,
+        block_id: api::BlockId,
+        method_name: &'static str,
+    ) -> Result<MiniblockNumber, Web3Error> {
+        let block_id = self
+            .map_earliest_block(connection, block_id, method_name)
+            .await?;
+        let block_number = connection
+            .blocks_web3_dal()
+            .resolve_block_id(block_id)
+            .await
+            .map_err(|err| internal_error(method_name, err))?
+            .ok_or(Web3Error::NoBlock)?;
+        self.ensure_block_not_pruned(connection, block_number, method_name)
+            .await?;
+        Ok(block_number)
+    }
+
+    /// Loads [`BlockArgs`] for the specified block ID, returning an error if the block doesn't exist
+    /// or its data is pruned.
+    pub async fn resolve_block_args(
+        &self,
+        connection: &mut StorageProcessor<'_>,
+        block_id: api::BlockId,
+        method_name: &'static str,
+    ) -> Result<BlockArgs, Web3Error> {
+        let block_id = self
+            .map_earliest_block(connection, block_id, method_name)
+            .await?;
+        let block_args = BlockArgs::new(connection, block_id)
+            .await
+            .map_err(|err| internal_error(method_name, err))?
+            .ok_or(Web3Error::NoBlock)?;
+        if !block_args.is_pending_miniblock() {
+            self.ensure_block_not_pruned(
+                connection,
+                block_args.resolved_block_number(),
+                method_name,
+            )
+            .await?;
+        }
+        Ok(block_args)
+    }
+}
+
+/// Smoothed suggestion for `eth_maxPriorityFeePerGas`.
+///
+/// Each sample is a percentile of effective priority fees of transactions in the mempool given the base fee
+/// from the fee model. Samples are smoothed using an exponential moving average. A new sample is taken
+/// at most once per sealed miniblock and base fee; otherwise, the cached suggestion is returned.
+#[derive(Debug, Clone)]
+pub(crate) struct PriorityFeeOracle {
+    smoothing_factor: f64,
+    state: Arc<std::sync::Mutex<Option<PriorityFeeState>>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PriorityFeeState {
+    miniblock_number: MiniblockNumber,
+    base_fee: u64,
+    smoothed_fee: f64,
+}
+
+impl PriorityFeeOracle {
+    /// Percentile of mempool priority fees taken as a sample.
+    const PERCENTILE: f64 = 0.6;
+    /// Maximum number of most recently received mempool transactions considered for a sample.
+    const MEMPOOL_SAMPLE_SIZE: usize = 1_000;
+
+    pub fn new(smoothing_factor: f64) -> Self {
+        assert!(
+            smoothing_factor > 0.0 && smoothing_factor <= 1.0,
+            "Priority fee smoothing factor must be in (0, 1], got {smoothing_factor}"
+        );
+        Self {
+            smoothing_factor,
+            state: Arc::default(),
+        }
+    }
+
+    pub async fn suggest(
+        &self,
+        connection: &mut StorageProcessor<'_>,
+        sealed_miniblock: MiniblockNumber,
+        base_fee: u64,
+    ) -> Result<U256, SqlxError> {
+        if let Some(state) = self.cached_state(sealed_miniblock, base_fee) {
+            return Ok(Self::to_fee(state.smoothed_fee));
+        }
+
+        let sample = connection
+            .transactions_web3_dal()
+            .get_mempool_priority_fee_percentile(
+                base_fee,
+                Self::PERCENTILE,
+                Self::MEMPOOL_SAMPLE_SIZE,
+            )
+            .await?
+            .unwrap_or_default();
+        let sample = sample.min(U256::from(u64::MAX)).as_u64() as f64;
+
+        let mut state = self.state.lock().expect("priority fee state is poisoned");
+        let smoothed_fee = match *state {
+            // Another request has already taken the sample for this miniblock and base fee.
+            Some(prev)
+                if prev.miniblock_number == sealed_miniblock && prev.base_fee == base_fee =>
+            {
+                return Ok(Self::to_fee(prev.smoothed_fee));
+            }
+            Some(prev) => {
+                self.smoothing_factor * sample + (1.0 - self.smoothing_factor) * prev.smoothed_fee
+            }
+            None => sample,
+        };
+        *state = Some(PriorityFeeState {
+            miniblock_number: sealed_miniblock,
+            base_fee,
+            smoothed_fee,
+        });
+        Ok(Self::to_fee(smoothed_fee))
+    }
+
+    fn cached_state(
+        &self,
+        sealed_miniblock: MiniblockNumber,
+        base_fee: u64,
+    ) -> Option<PriorityFeeState> {
+        let state = self.state.lock().expect("priority fee state is poisoned");
+        state.filter(|state| {
+            state.miniblock_number == sealed_miniblock && state.base_fee == base_fee
+        })
+    }
+
+    fn to_fee(smoothed_fee: f64) -> U256 {
+        U256::from(smoothed_fee.round() as u64)
+    }
+}
+
+/// Key in [`CallResultCache`]: hash of the finalized miniblock and hash of the call request.
+pub(crate) type CallCacheKey = (H256, H256);
+
+/// LRU cache for `eth_call` results against finalized miniblocks. Such results cannot change,
+/// so repeated calls can be served without VM execution.
+#[derive(Debug, Clone)]
+pub(crate) struct CallResultCache(Option<Arc<std::sync::Mutex<LruCache<CallCacheKey, Vec<u8>>>>>);
+
+impl CallResultCache {
+    /// Maximum size of a call output to be cached.
+    const MAX_OUTPUT_SIZE: usize = 64 * 1_024;
+
+    /// Creates a new cache with the specified capacity. If the capacity is 0, the cache is disabled.
+    pub fn new(capacity: usize) -> Self {
+        let cache = NonZeroUsize::new(capacity)
+            .map(|capacity| Arc::new(std::sync::Mutex::new(LruCache::new(capacity))));
+        Self(cache)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Computes the cache key for a call against the specified miniblock.
+    pub fn key(block_hash: H256, request: &CallRequest) -> CallCacheKey {
+        let request_bytes = serde_json::to_vec(request).expect("failed serializing call request");
+        (block_hash, H256(keccak256(&request_bytes)))
+    }
+
+    pub fn get(&self, key: &CallCacheKey) -> Option<Vec<u8>> {
+        let mut cache = self.0.as_ref()?.lock().expect("call cache is poisoned");
+        let output = cache.get(key).cloned();
+        let result = if output.is_some() {
+            CacheResult::Hit
+        } else {
+            CacheResult::Miss
+        };
+        API_METRICS.eth_call_cache[&result].inc();
+        output
+    }
+
+    pub fn insert(&self, key: CallCacheKey, output: &[u8]) {
+        if output.len() > Self::MAX_OUTPUT_SIZE {
+            return;
+        }
+        if let Some(cache) = &self.0 {
+            let mut cache = cache.lock().expect("call cache is poisoned");
+            cache.put(key, output.to_vec());
+        }
+    }
+}
+
+/// Returns the sync status in the format used by `eth_syncing`.
+pub(super) fn sync_status(sync_state: Option<&SyncState>) -> web3::types::SyncState {
+    let Some(state) = sync_state else {
+        // If there is no sync state, then the node is the main node and it's always synced.
+        return web3::types::SyncState::NotSyncing;
+    };
+    // Node supports syncing process (i.e. not the main node).
+    if state.is_synced() {
+        web3::types::SyncState::NotSyncing
+    } else {
+        web3::types::SyncState::Syncing(web3::types::SyncInfo {
+            starting_block: 0u64.into(), // We always start syncing from genesis right now.
+            current_block: state.get_local_block().0.into(),
+            highest_block: state.get_main_node_block().0.into(),
+        })
+    }
+}
+
 /// Holder for the data required for the API to be functional.
 #[derive(Debug, Clone)]
 pub struct RpcState {
-    pub(crate) installed_filters: Arc<Mutex<Filters>>,
+    pub(crate) installed_filters: InstalledFilters,
     pub connection_pool: ConnectionPool,
     pub tree_api: Option<TreeApiHttpClient>,
     pub tx_sender: TxSender,
     pub sync_state: Option<SyncState>,
     pub(super) api_config: InternalApiConfig,
     pub(super) last_sealed_miniblock: SealedMiniblockNumber,
+    pub(super) priority_fee_oracle: PriorityFeeOracle,
+    pub(super) call_result_cache: CallResultCache,
+    pub(super) read_replica: Option<ReadReplica>,
+    pub(super) start_info: BlockStartInfo,
 }
 
 impl RpcState {
+    /// Returns the connection pool for read-only queries concerning the specified block: the read replica
+    /// if it's configured and is up to date with the block, or the main pool otherwise.
+    pub(crate) fn read_pool(&self, block_id: api::BlockId) -> &ConnectionPool {
+        self.read_replica
+            .as_ref()
+            .and_then(|replica| replica.pool_for_block(block_id))
+            .unwrap_or(&self.connection_pool)
+    }
+
+    /// Returns connection pools for read-only queries by transaction hash in the order they should be queried.
+    /// The main pool is always queried last, since a transaction may be pending or missing in the read replica
+    /// because of replication lag.
+    pub(crate) fn tx_read_pools(&self) -> impl Iterator<Item = &ConnectionPool> + '_ {
+        let latest_block = api::BlockId::Number(api::BlockNumber::Latest);
+        let replica_pool = self
+            .read_replica
+            .as_ref()
+            .and_then(|replica| replica.pool_for_block(latest_block));
+        replica_pool.into_iter().chain([&self.connection_pool])
+    }
+
+    /// Checks that the Merkle tree lags behind the state keeper by at most `max_tree_lag_for_proofs` L1 batches,
+    /// so that proof methods don't silently serve proofs for a stale state. The lag is measured in Postgres
+    /// as the number of sealed L1 batches without metadata computed by the tree.
+    pub(crate) async fn check_tree_lag(
+        &self,
+        connection: &mut StorageProcessor<'_>,
+        method_name: &'static str,
+    ) -> Result<(), Web3Error> {
+        let Some(max_lag) = self.api_config.max_tree_lag_for_proofs else {
+            return Ok(());
+        };
+        let sealed_l1_batch = connection
+            .blocks_dal()
+            .get_sealed_l1_batch_number()
+            .await
+            .map_err(|err| internal_error(method_name, err))?;
+        let last_l1_batch_with_metadata = connection
+            .blocks_dal()
+            .get_last_l1_batch_number_with_metadata()
+            .await
+            .map_err(|err| internal_error(method_name, err))?;
+        // If there are no L1 batches with metadata (e.g., right after snapshot recovery), there are no proofs to serve
+        // anyway; this will be handled by the proof methods themselves.
+        let (Some(sealed_l1_batch), Some(last_l1_batch_with_metadata)) =
+            (sealed_l1_batch, last_l1_batch_with_metadata)
+        else {
+            return Ok(());
+        };
+
+        let lag = sealed_l1_batch
+            .0
+            .saturating_sub(last_l1_batch_with_metadata.0);
+        if lag > max_lag {
+            API_METRICS.tree_lag_rejections[&method_name].inc();
+            return Err(Web3Error::TreeLagExceeded { lag, max_lag });
+        }
+        Ok(())
+    }
+
     pub fn parse_transaction_bytes(&self, bytes: &[u8]) -> Result<(L2Tx, H256), Web3Error> {
         let chain_id = self.api_config.l2_chain_id;
         let (tx_request, hash) = api::TransactionRequest::from_bytes(bytes, chain_id)?;
@@ -270,21 +724,47 @@ impl RpcState {
         Ok(block_number)
     }
 
+    /// Resolves the block used by gas estimation methods. If the block is not specified, estimation is performed
+    /// against the pending block.
+    pub(crate) async fn resolve_block_args_for_estimation(
+        &self,
+        block: Option<api::BlockNumber>,
+        method_name: &'static str,
+    ) -> Result<BlockArgs, Web3Error> {
+        let block_id = api::BlockId::Number(block.unwrap_or(api::BlockNumber::Pending));
+        let mut connection = self
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap();
+        self.start_info
+            .resolve_block_args(&mut connection, block_id, method_name)
+            .await
+    }
+
+    /// Sets the nonce of the call request (unless it's already set) to the sender nonce as of the specified block.
     pub(crate) async fn set_nonce_for_call_request(
         &self,
         call_request: &mut CallRequest,
+        block_args: &BlockArgs,
     ) -> Result<(), Web3Error> {
         const METHOD_NAME: &str = "set_nonce_for_call_request";
 
         if call_request.nonce.is_none() {
             let from = call_request.from.unwrap_or_default();
-            let block_id = api::BlockId::Number(api::BlockNumber::Latest);
             let mut connection = self
                 .connection_pool
                 .access_storage_tagged("api")
                 .await
                 .unwrap();
-            let block_number = resolve_block(&mut connection, block_id, METHOD_NAME).await?;
+            let block_number = if block_args.is_pending_miniblock() {
+                let block_id = api::BlockId::Number(api::BlockNumber::Latest);
+                self.start_info
+                    .resolve_block(&mut connection, block_id, METHOD_NAME)
+                    .await?
+            } else {
+                block_args.resolved_block_number()
+            };
             let address_historical_nonce = connection
                 .storage_web3_dal()
                 .get_address_historical_nonce(from, block_number)
@@ -296,6 +776,172 @@ impl RpcState {
     }
 }
 
+/// Storage for filters installed via `eth_newFilter` and similar methods.
+#[derive(Debug, Clone)]
+pub(crate) enum InstalledFilters {
+    /// Filters stored in the memory of the API server.
+    Local(Arc<Mutex<Filters>>),
+    /// Filters stored in Postgres, so that they can be accessed by any API server replica
+    /// using the same database.
+    Shared(Arc<SharedFilters>),
+}
+
+impl InstalledFilters {
+    /// Shared filters not requested for this long are removed (the same timeout as in Geth).
+    const SHARED_FILTERS_IDLE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+    /// Interval between pruning shared filters.
+    const SHARED_FILTERS_PRUNING_INTERVAL: Duration = Duration::from_secs(30);
+
+    pub fn local(limit: Option<usize>) -> Self {
+        Self::Local(Arc::new(Mutex::new(Filters::new(limit))))
+    }
+
+    /// Creates shared filters together with a task that will prune them on a schedule.
+    /// The task terminates once all handles to the filters are dropped.
+    pub fn shared(
+        pool: ConnectionPool,
+        limit: Option<usize>,
+    ) -> (Self, impl Future<Output = ()> + Send) {
+        let filters = Arc::new(SharedFilters { pool, limit });
+        let pruned_filters = filters.clone();
+        let pruning_task = async move {
+            loop {
+                if Arc::strong_count(&pruned_filters) == 1 {
+                    // The filters were dropped; there's no sense continuing pruning.
+                    tracing::debug!("Stopping shared filters pruning");
+                    break;
+                }
+                if let Err(err) = pruned_filters
+                    .prune(Self::SHARED_FILTERS_IDLE_TIMEOUT)
+                    .await
+                {
+                    tracing::warn!("Failed pruning shared filters: {err:#}");
+                }
+                tokio::time::sleep(Self::SHARED_FILTERS_PRUNING_INTERVAL).await;
+            }
+        };
+        (Self::Shared(filters), pruning_task)
+    }
+
+    async fn access_storage(
+        pool: &ConnectionPool,
+        method_name: &'static str,
+    ) -> Result<StorageProcessor<'_>, Web3Error> {
+        pool.access_storage_tagged("api")
+            .await
+            .map_err(|err| internal_error(method_name, err))
+    }
+
+    /// Adds filter to the storage and returns its key.
+    pub async fn add(
+        &self,
+        filter: TypedFilter,
+        method_name: &'static str,
+    ) -> Result<U256, Web3Error> {
+        match self {
+            Self::Local(filters) => Ok(filters.lock().await.add(filter)),
+            Self::Shared(shared) => {
+                let serialized_filter = serde_json::to_value(&filter)
+                    .map_err(|err| internal_error(method_name, err))?;
+                let id = H256::random();
+                let mut storage = Self::access_storage(&shared.pool, method_name).await?;
+                storage
+                    .api_filters_dal()
+                    .insert_filter(id, &serialized_filter)
+                    .await
+                    .map_err(|err| internal_error(method_name, err))?;
+                Ok(h256_to_u256(id))
+            }
+        }
+    }
+
+    /// Retrieves filter from the storage.
+    pub async fn get_and_update_stats(
+        &self,
+        index: U256,
+        method_name: &'static str,
+    ) -> Result<Option<TypedFilter>, Web3Error> {
+        match self {
+            Self::Local(filters) => Ok(filters.lock().await.get_and_update_stats(index)),
+            Self::Shared(shared) => {
+                let mut storage = Self::access_storage(&shared.pool, method_name).await?;
+                let serialized_filter = storage
+                    .api_filters_dal()
+                    .get_filter_and_touch(u256_to_h256(index))
+                    .await
+                    .map_err(|err| internal_error(method_name, err))?;
+                serialized_filter
+                    .map(serde_json::from_value)
+                    .transpose()
+                    .map_err(|err| internal_error(method_name, err))
+            }
+        }
+    }
+
+    /// Updates filter in the storage.
+    pub async fn update(
+        &self,
+        index: U256,
+        new_filter: TypedFilter,
+        method_name: &'static str,
+    ) -> Result<(), Web3Error> {
+        match self {
+            Self::Local(filters) => {
+                filters.lock().await.update(index, new_filter);
+                Ok(())
+            }
+            Self::Shared(shared) => {
+                let serialized_filter = serde_json::to_value(&new_filter)
+                    .map_err(|err| internal_error(method_name, err))?;
+                let mut storage = Self::access_storage(&shared.pool, method_name).await?;
+                storage
+                    .api_filters_dal()
+                    .update_filter(u256_to_h256(index), &serialized_filter)
+                    .await
+                    .map_err(|err| internal_error(method_name, err))
+            }
+        }
+    }
+
+    /// Removes filter from the storage.
+    pub async fn remove(&self, index: U256, method_name: &'static str) -> Result<bool, Web3Error> {
+        match self {
+            Self::Local(filters) => Ok(filters.lock().await.remove(index)),
+            Self::Shared(shared) => {
+                let mut storage = Self::access_storage(&shared.pool, method_name).await?;
+                storage
+                    .api_filters_dal()
+                    .remove_filter(u256_to_h256(index))
+                    .await
+                    .map_err(|err| internal_error(method_name, err))
+            }
+        }
+    }
+}
+
+/// Filters stored in Postgres. Unlike local filters, the number of shared filters is only bounded
+/// by periodic pruning, so it may temporarily exceed the limit.
+#[derive(Debug)]
+pub(crate) struct SharedFilters {
+    pool: ConnectionPool,
+    limit: Option<usize>,
+}
+
+impl SharedFilters {
+    async fn prune(&self, idle_timeout: Duration) -> anyhow::Result<()> {
+        let mut storage = self.pool.access_storage_tagged("api").await?;
+        let mut dal = storage.api_filters_dal();
+        let mut removed_count = dal.remove_idle_filters(idle_timeout).await?;
+        if let Some(limit) = self.limit {
+            removed_count += dal.prune_filters(limit).await?;
+        }
+        if removed_count > 0 {
+            tracing::debug!("Pruned {removed_count} shared filters");
+        }
+        Ok(())
+    }
+}
+
 /// Contains mapping from index to `Filter`x with optional location.
 #[derive(Debug)]
 pub(crate) struct Filters(LruCache<U256, InstalledFilter>);
@@ -428,4 +1074,35 @@ mod tests {
         assert!(filters.0.contains(&idx2));
         assert!(!filters.0.contains(&idx3));
     }
+
+    #[test]
+    fn call_result_cache_basics() {
+        use super::*;
+
+        let disabled_cache = CallResultCache::new(0);
+        assert!(!disabled_cache.is_enabled());
+        let key = CallResultCache::key(H256::repeat_byte(1), &CallRequest::default());
+        disabled_cache.insert(key, b"output");
+        assert_eq!(disabled_cache.get(&key), None);
+
+        let cache = CallResultCache::new(1);
+        cache.insert(key, b"output");
+        assert_eq!(cache.get(&key).as_deref(), Some(b"output".as_slice()));
+
+        let request = CallRequest {
+            to: Some(Address::repeat_byte(2)),
+            ..CallRequest::default()
+        };
+        let other_key = CallResultCache::key(H256::repeat_byte(1), &request);
+        assert_ne!(other_key, key);
+        assert_eq!(cache.get(&other_key), None);
+        cache.insert(other_key, b"other output");
+        // The first entry should be evicted.
+        assert_eq!(cache.get(&key), None);
+
+        let large_output = vec![0; CallResultCache::MAX_OUTPUT_SIZE + 1];
+        cache.insert(key, &large_output);
+        assert_eq!(cache.get(&key), None);
+        assert!(cache.get(&other_key).is_some());
+    }
 }
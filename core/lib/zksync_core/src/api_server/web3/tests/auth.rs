@@ -0,0 +1,283 @@
+//! Tests for authentication of protected namespaces.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use jsonwebtoken::{EncodingKey, Header};
+use reqwest::StatusCode;
+use tokio::sync::watch;
+use zksync_config::configs::{api::ApiAuthMethod, chain::NetworkConfig};
+use zksync_dal::ConnectionPool;
+
+use super::*;
+
+const SECRET: &str = "correct horse battery staple";
+
+fn auth_web3_config(method: ApiAuthMethod) -> Web3JsonRpcConfig {
+    Web3JsonRpcConfig {
+        authenticated_namespaces: Some(vec!["snapshots".to_owned()]),
+        auth_method: method,
+        auth_secret: Some(SECRET.to_owned()),
+        ..Web3JsonRpcConfig::for_tests()
+    }
+}
+
+fn create_token(method: ApiAuthMethod, secret: &str) -> String {
+    match method {
+        ApiAuthMethod::SharedSecret => secret.to_owned(),
+        ApiAuthMethod::Jwt => {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+            let claims = serde_json::json!({ "exp": now.as_secs() + 60 });
+            let key = EncodingKey::from_secret(secret.as_bytes());
+            jsonwebtoken::encode(&Header::default(), &claims, &key).unwrap()
+        }
+    }
+}
+
+async fn send_request(
+    client: &reqwest::Client,
+    url: &str,
+    token: Option<&str>,
+    body: serde_json::Value,
+) -> anyhow::Result<(StatusCode, serde_json::Value)> {
+    let mut request = client.post(url).json(&body);
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    let response = request.send().await?;
+    Ok((response.status(), response.json().await?))
+}
+
+fn call(method: &str) -> serde_json::Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": method,
+        "params": [],
+    })
+}
+
+#[async_trait]
+trait AuthTest {
+    fn web3_config(&self) -> Web3JsonRpcConfig;
+
+    async fn test(&self, client: &reqwest::Client, url: &str) -> anyhow::Result<()>;
+}
+
+async fn test_auth(test: impl AuthTest) {
+    let pool = ConnectionPool::test_pool().await;
+    let network_config = NetworkConfig::for_tests();
+    let mut storage = pool.access_storage().await.unwrap();
+    if storage.blocks_dal().is_genesis_needed().await.unwrap() {
+        ensure_genesis_state(
+            &mut storage,
+            network_config.zksync_network_id,
+            &GenesisParams::mock(),
+        )
+        .await
+        .unwrap();
+    }
+    drop(storage);
+
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let server_handles =
+        spawn_http_server(&network_config, test.web3_config(), pool, stop_receiver).await;
+    server_handles.wait_until_ready().await;
+
+    let url = format!("http://{}/", server_handles.local_addr);
+    test.test(&reqwest::Client::new(), &url).await.unwrap();
+
+    stop_sender.send_replace(true);
+    server_handles.shutdown().await;
+}
+
+#[derive(Debug)]
+struct AuthenticatedNamespaceTest(ApiAuthMethod);
+
+#[async_trait]
+impl AuthTest for AuthenticatedNamespaceTest {
+    fn web3_config(&self) -> Web3JsonRpcConfig {
+        auth_web3_config(self.0)
+    }
+
+    async fn test(&self, client: &reqwest::Client, url: &str) -> anyhow::Result<()> {
+        // Public methods don't require authentication.
+        let (status, response) = send_request(client, url, None, call("eth_blockNumber")).await?;
+        assert_eq!(status, StatusCode::OK, "{response}");
+        assert!(response.get("result").is_some(), "{response}");
+
+        let protected_call = call("snapshots_getAllSnapshots");
+        let (status, response) = send_request(client, url, None, protected_call.clone()).await?;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{response}");
+        assert_eq!(response["error"]["code"], -32_001, "{response}");
+        assert_eq!(response["id"], 1, "{response}");
+
+        let invalid_token = create_token(self.0, "wrong secret");
+        let (status, response) =
+            send_request(client, url, Some(&invalid_token), protected_call.clone()).await?;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{response}");
+
+        // A batch is rejected if any of its calls requires authentication.
+        let batch = serde_json::json!([call("eth_blockNumber"), protected_call.clone()]);
+        let (status, response) = send_request(client, url, None, batch).await?;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{response}");
+
+        // Requests that cannot be parsed by the middleware require authentication as well.
+        let malformed_batch = serde_json::json!([protected_call.clone(), 42]);
+        let (status, response) = send_request(client, url, None, malformed_batch).await?;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{response}");
+        let malformed_call = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": 42 });
+        let (status, response) = send_request(client, url, None, malformed_call).await?;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{response}");
+
+        let token = create_token(self.0, SECRET);
+        let (status, response) = send_request(client, url, Some(&token), protected_call).await?;
+        assert_eq!(status, StatusCode::OK, "{response}");
+        assert!(response.get("result").is_some(), "{response}");
+        Ok(())
+    }
+}
+
+#[test_casing(2, [ApiAuthMethod::SharedSecret, ApiAuthMethod::Jwt])]
+#[tokio::test]
+async fn authenticated_namespace(method: ApiAuthMethod) {
+    test_auth(AuthenticatedNamespaceTest(method)).await;
+}
+
+#[derive(Debug)]
+struct AdminNamespaceTest;
+
+impl AdminNamespaceTest {
+    fn call_with_params(method: &str, params: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        })
+    }
+}
+
+#[async_trait]
+impl AuthTest for AdminNamespaceTest {
+    fn web3_config(&self) -> Web3JsonRpcConfig {
+        Web3JsonRpcConfig {
+            authenticated_namespaces: Some(vec!["admin".to_owned()]),
+            ..auth_web3_config(ApiAuthMethod::SharedSecret)
+        }
+    }
+
+    async fn test(&self, client: &reqwest::Client, url: &str) -> anyhow::Result<()> {
+        let (status, response) = send_request(client, url, None, call("admin_nodeInfo")).await?;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{response}");
+
+        let token = Some(SECRET);
+        let (_, response) = send_request(client, url, token, call("admin_nodeInfo")).await?;
+        let node_info = &response["result"];
+        assert_eq!(node_info["l2ChainId"], "0x10e", "{response}");
+        assert_eq!(node_info["sealedMiniblockNumber"], 0, "{response}");
+        assert_eq!(node_info["sealingStopped"], false, "{response}");
+
+        let (_, response) = send_request(client, url, token, call("admin_stopSealing")).await?;
+        assert_eq!(response["result"], true, "{response}");
+        let (_, response) = send_request(client, url, token, call("admin_stopSealing")).await?;
+        assert_eq!(response["result"], false, "{response}");
+        let (_, response) = send_request(client, url, token, call("admin_nodeInfo")).await?;
+        assert_eq!(response["result"]["sealingStopped"], true, "{response}");
+        let (_, response) = send_request(client, url, token, call("admin_resumeSealing")).await?;
+        assert_eq!(response["result"], true, "{response}");
+
+        let invalid_filter =
+            Self::call_with_params("admin_setLogFilter", serde_json::json!(["zksync_core=??"]));
+        let (_, response) = send_request(client, url, token, invalid_filter).await?;
+        assert_eq!(response["error"]["code"], -32_602, "{response}");
+
+        let flush_call = Self::call_with_params(
+            "admin_flushMempoolSender",
+            serde_json::json!([Address::repeat_byte(1)]),
+        );
+        let (_, response) = send_request(client, url, token, flush_call).await?;
+        assert_eq!(response["result"], serde_json::json!([]), "{response}");
+
+        let remove_call = Self::call_with_params(
+            "admin_removeTransaction",
+            serde_json::json!([H256::repeat_byte(1), "test"]),
+        );
+        let (_, response) = send_request(client, url, token, remove_call).await?;
+        assert_eq!(response["result"], false, "{response}");
+
+        let (_, response) =
+            send_request(client, url, token, call("admin_getFeeModelSettings")).await?;
+        // Scale factors default to the internal pricing multipliers of the gas adjuster.
+        assert_eq!(
+            response["result"]["l1GasPriceScaleFactor"], 0.8,
+            "{response}"
+        );
+        let invalid_update = Self::call_with_params(
+            "admin_updateFeeModelSettings",
+            serde_json::json!([{ "l1GasPriceScaleFactor": 0.0 }]),
+        );
+        let (_, response) = send_request(client, url, token, invalid_update).await?;
+        assert_eq!(response["error"]["code"], -32_602, "{response}");
+        let update = Self::call_with_params(
+            "admin_updateFeeModelSettings",
+            serde_json::json!([{ "l1GasPriceScaleFactor": 1.5, "minimalL2GasPrice": "0x100" }]),
+        );
+        let (_, response) = send_request(client, url, token, update).await?;
+        let settings = &response["result"];
+        assert_eq!(settings["l1GasPriceScaleFactor"], 1.5, "{response}");
+        assert_eq!(settings["l1PubdataPriceScaleFactor"], 1.0, "{response}");
+        assert_eq!(settings["minimalL2GasPrice"], "0x100", "{response}");
+
+        let (_, response) =
+            send_request(client, url, token, call("admin_getAggregationPolicy")).await?;
+        assert_eq!(
+            response["result"]["maxAggregatedBlocksToCommit"], 10,
+            "{response}"
+        );
+        let invalid_update = Self::call_with_params(
+            "admin_updateAggregationPolicy",
+            serde_json::json!([{ "maxAggregatedBlocksToExecute": 0 }]),
+        );
+        let (_, response) = send_request(client, url, token, invalid_update).await?;
+        assert_eq!(response["error"]["code"], -32_602, "{response}");
+        let update = Self::call_with_params(
+            "admin_updateAggregationPolicy",
+            serde_json::json!([{ "maxAggregatedBlocksToCommit": 20, "aggregatedBlockCommitDeadline": 60 }]),
+        );
+        let (_, response) = send_request(client, url, token, update).await?;
+        let policy = &response["result"];
+        assert_eq!(policy["maxAggregatedBlocksToCommit"], 20, "{response}");
+        assert_eq!(policy["aggregatedBlockCommitDeadline"], 60, "{response}");
+        assert_eq!(policy["maxAggregatedBlocksToExecute"], 10, "{response}");
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn admin_namespace() {
+    test_auth(AdminNamespaceTest).await;
+}
+
+#[test]
+fn parsing_auth_config() {
+    let auth = ApiAuth::from_config(&Web3JsonRpcConfig::for_tests()).unwrap();
+    assert!(auth.is_none());
+
+    let auth = ApiAuth::from_config(&auth_web3_config(ApiAuthMethod::Jwt))
+        .unwrap()
+        .unwrap();
+    assert_eq!(auth.namespaces(), [Namespace::Snapshots]);
+
+    let config = Web3JsonRpcConfig {
+        auth_secret: None,
+        ..auth_web3_config(ApiAuthMethod::Jwt)
+    };
+    ApiAuth::from_config(&config).unwrap_err();
+
+    let config = Web3JsonRpcConfig {
+        authenticated_namespaces: Some(vec!["unknown".to_owned()]),
+        ..auth_web3_config(ApiAuthMethod::Jwt)
+    };
+    ApiAuth::from_config(&config).unwrap_err();
+}
@@ -0,0 +1,133 @@
+//! Tests for limits and parallel execution of batch HTTP requests.
+
+use std::num::NonZeroUsize;
+
+use async_trait::async_trait;
+use tokio::sync::watch;
+use zksync_config::configs::chain::NetworkConfig;
+use zksync_dal::ConnectionPool;
+
+use super::*;
+
+async fn send_batch(
+    client: &reqwest::Client,
+    url: &str,
+    calls: &[(Option<u64>, &str)],
+) -> anyhow::Result<serde_json::Value> {
+    let calls: Vec<_> = calls
+        .iter()
+        .map(|&(id, method)| {
+            let mut call = serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": method,
+                "params": [],
+            });
+            if let Some(id) = id {
+                call["id"] = id.into();
+            }
+            call
+        })
+        .collect();
+    let response = client.post(url).json(&calls).send().await?;
+    Ok(response.error_for_status()?.json().await?)
+}
+
+#[async_trait]
+trait BatchTest {
+    fn web3_config(&self) -> Web3JsonRpcConfig;
+
+    async fn test(&self, client: &reqwest::Client, url: &str) -> anyhow::Result<()>;
+}
+
+async fn test_batch(test: impl BatchTest) {
+    let pool = ConnectionPool::test_pool().await;
+    let network_config = NetworkConfig::for_tests();
+    let mut storage = pool.access_storage().await.unwrap();
+    if storage.blocks_dal().is_genesis_needed().await.unwrap() {
+        ensure_genesis_state(
+            &mut storage,
+            network_config.zksync_network_id,
+            &GenesisParams::mock(),
+        )
+        .await
+        .unwrap();
+    }
+    drop(storage);
+
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let server_handles =
+        spawn_http_server(&network_config, test.web3_config(), pool, stop_receiver).await;
+    server_handles.wait_until_ready().await;
+
+    let url = format!("http://{}/", server_handles.local_addr);
+    test.test(&reqwest::Client::new(), &url).await.unwrap();
+
+    stop_sender.send_replace(true);
+    server_handles.shutdown().await;
+}
+
+#[derive(Debug)]
+struct ParallelBatchTest;
+
+#[async_trait]
+impl BatchTest for ParallelBatchTest {
+    fn web3_config(&self) -> Web3JsonRpcConfig {
+        Web3JsonRpcConfig {
+            max_batch_request_size: Some(4),
+            batch_request_parallelism: NonZeroUsize::new(2),
+            ..Web3JsonRpcConfig::for_tests()
+        }
+    }
+
+    async fn test(&self, client: &reqwest::Client, url: &str) -> anyhow::Result<()> {
+        let calls = [
+            (Some(1), "eth_blockNumber"),
+            (None, "eth_chainId"), // notification
+            (Some(2), "eth_chainId"),
+            (Some(3), "net_version"),
+        ];
+        let response = send_batch(client, url, &calls).await?;
+        let responses = response.as_array().expect("batch response is not an array");
+        let ids: Vec<_> = responses.iter().map(|rp| rp["id"].clone()).collect();
+        assert_eq!(ids, [1, 2, 3], "{response}");
+        assert_eq!(responses[0]["result"], "0x0", "{response}");
+        assert_eq!(responses[1]["result"], "0x10e", "{response}");
+        assert_eq!(responses[2]["result"], "270", "{response}");
+
+        let calls = [(Some(1), "eth_blockNumber"); 5];
+        let response = send_batch(client, url, &calls).await?;
+        assert_eq!(response["error"]["code"], -32_010, "{response}");
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn batch_calls_are_executed_in_parallel() {
+    test_batch(ParallelBatchTest).await;
+}
+
+#[derive(Debug)]
+struct BatchResponseSizeTest;
+
+#[async_trait]
+impl BatchTest for BatchResponseSizeTest {
+    fn web3_config(&self) -> Web3JsonRpcConfig {
+        Web3JsonRpcConfig {
+            // Effectively forbids non-empty batch responses.
+            max_batch_response_size_mb: Some(0),
+            ..Web3JsonRpcConfig::for_tests()
+        }
+    }
+
+    async fn test(&self, client: &reqwest::Client, url: &str) -> anyhow::Result<()> {
+        let calls = [(Some(1), "eth_blockNumber"), (Some(2), "eth_chainId")];
+        let response = send_batch(client, url, &calls).await?;
+        assert_eq!(response["error"]["code"], -32_011, "{response}");
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn batch_response_size_is_limited() {
+    test_batch(BatchResponseSizeTest).await;
+}
@@ -0,0 +1,136 @@
+//! Tests for HTTP response compression.
+
+use async_trait::async_trait;
+use reqwest::header;
+use tokio::sync::watch;
+use zksync_config::configs::chain::NetworkConfig;
+use zksync_dal::ConnectionPool;
+
+use super::*;
+
+async fn send_call(
+    client: &reqwest::Client,
+    url: &str,
+    accept_encoding: Option<&str>,
+    method: &str,
+    params: serde_json::Value,
+) -> anyhow::Result<(Option<String>, Vec<u8>)> {
+    let mut request = client.post(url).json(&serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": method,
+        "params": params,
+    }));
+    if let Some(encoding) = accept_encoding {
+        request = request.header(header::ACCEPT_ENCODING, encoding);
+    }
+    let response = request.send().await?.error_for_status()?;
+    let content_encoding = response
+        .headers()
+        .get(header::CONTENT_ENCODING)
+        .map(|value| value.to_str().unwrap().to_owned());
+    Ok((content_encoding, response.bytes().await?.to_vec()))
+}
+
+#[async_trait]
+trait CompressionTest {
+    fn web3_config(&self) -> Web3JsonRpcConfig;
+
+    async fn test(&self, client: &reqwest::Client, url: &str) -> anyhow::Result<()>;
+}
+
+async fn test_compression(test: impl CompressionTest) {
+    let pool = ConnectionPool::test_pool().await;
+    let network_config = NetworkConfig::for_tests();
+    let mut storage = pool.access_storage().await.unwrap();
+    if storage.blocks_dal().is_genesis_needed().await.unwrap() {
+        ensure_genesis_state(
+            &mut storage,
+            network_config.zksync_network_id,
+            &GenesisParams::mock(),
+        )
+        .await
+        .unwrap();
+    }
+    drop(storage);
+
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let server_handles =
+        spawn_http_server(&network_config, test.web3_config(), pool, stop_receiver).await;
+    server_handles.wait_until_ready().await;
+
+    let url = format!("http://{}/", server_handles.local_addr);
+    // Disable automatic decompression so that raw responses can be checked.
+    let client = reqwest::Client::builder()
+        .no_gzip()
+        .no_brotli()
+        .build()
+        .unwrap();
+    test.test(&client, &url).await.unwrap();
+
+    stop_sender.send_replace(true);
+    server_handles.shutdown().await;
+}
+
+#[derive(Debug)]
+struct ResponseCompressionTest;
+
+#[async_trait]
+impl CompressionTest for ResponseCompressionTest {
+    fn web3_config(&self) -> Web3JsonRpcConfig {
+        Web3JsonRpcConfig {
+            response_compression_enabled: true,
+            response_compression_min_size: Some(128),
+            ..Web3JsonRpcConfig::for_tests()
+        }
+    }
+
+    async fn test(&self, client: &reqwest::Client, url: &str) -> anyhow::Result<()> {
+        // Small responses are not compressed.
+        let no_params = serde_json::json!([]);
+        let (encoding, body) =
+            send_call(client, url, Some("gzip"), "eth_blockNumber", no_params).await?;
+        assert_eq!(encoding, None);
+        serde_json::from_slice::<serde_json::Value>(&body)?;
+
+        let block_params = serde_json::json!(["0x0", true]);
+        let (encoding, body) = send_call(
+            client,
+            url,
+            None,
+            "eth_getBlockByNumber",
+            block_params.clone(),
+        )
+        .await?;
+        assert_eq!(encoding, None);
+        assert!(body.len() > 128, "{}", String::from_utf8_lossy(&body));
+        serde_json::from_slice::<serde_json::Value>(&body)?;
+
+        let (encoding, body) = send_call(
+            client,
+            url,
+            Some("gzip"),
+            "eth_getBlockByNumber",
+            block_params.clone(),
+        )
+        .await?;
+        assert_eq!(encoding.as_deref(), Some("gzip"));
+        assert_eq!(body[..2], [0x1f, 0x8b]); // gzip magic bytes
+
+        let (encoding, _) = send_call(
+            client,
+            url,
+            Some("br"),
+            "eth_getBlockByNumber",
+            block_params,
+        )
+        .await?;
+        assert_eq!(encoding.as_deref(), Some("br"));
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn responses_are_compressed() {
+    test_compression(ResponseCompressionTest).await;
+}
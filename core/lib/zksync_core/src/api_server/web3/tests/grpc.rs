@@ -0,0 +1,211 @@
+//! gRPC-related tests.
+
+use std::net::Ipv4Addr;
+
+use async_trait::async_trait;
+use tokio::sync::watch;
+use tonic::{transport::Channel, Code};
+use zksync_config::configs::chain::NetworkConfig;
+use zksync_dal::ConnectionPool;
+use zksync_types::Address;
+
+use super::{ws::wait_for_notifier, *};
+use crate::api_server::web3::{
+    grpc::{
+        proto::{self, streaming_api_client::StreamingApiClient},
+        GrpcStreamingServer,
+    },
+    metrics::SubscriptionType,
+    namespaces::eth::EVENT_TOPIC_NUMBER_LIMIT,
+};
+
+#[async_trait]
+trait GrpcTest {
+    fn max_concurrent_streams(&self) -> usize {
+        100
+    }
+
+    async fn test(
+        &self,
+        client: StreamingApiClient<Channel>,
+        pool: &ConnectionPool,
+        pub_sub_events: mpsc::UnboundedReceiver<PubSubEvent>,
+    ) -> anyhow::Result<()>;
+}
+
+async fn test_grpc_server(test: impl GrpcTest) {
+    let pool = ConnectionPool::test_pool().await;
+    let network_config = NetworkConfig::for_tests();
+    let mut storage = pool.access_storage().await.unwrap();
+    if storage.blocks_dal().is_genesis_needed().await.unwrap() {
+        ensure_genesis_state(
+            &mut storage,
+            network_config.zksync_network_id,
+            &GenesisParams::mock(),
+        )
+        .await
+        .unwrap();
+    }
+    drop(storage);
+
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let (pub_sub_events_sender, pub_sub_events) = mpsc::unbounded_channel();
+    let server_handles = GrpcStreamingServer::new(
+        pool.clone(),
+        network_config.zksync_network_id,
+        POLL_INTERVAL,
+    )
+    .with_pub_sub_events(pub_sub_events_sender)
+    .with_max_concurrent_streams(test.max_concurrent_streams())
+    .spawn((Ipv4Addr::LOCALHOST, 0).into(), stop_receiver)
+    .await
+    .unwrap();
+    server_handles.wait_until_ready().await;
+
+    let client = StreamingApiClient::connect(format!("http://{}", server_handles.local_addr))
+        .await
+        .unwrap();
+    test.test(client, &pool, pub_sub_events).await.unwrap();
+
+    stop_sender.send_replace(true);
+    server_handles.shutdown().await;
+}
+
+#[derive(Debug)]
+struct BlocksAndLogsStreamingTest;
+
+#[async_trait]
+impl GrpcTest for BlocksAndLogsStreamingTest {
+    async fn test(
+        &self,
+        mut client: StreamingApiClient<Channel>,
+        pool: &ConnectionPool,
+        mut pub_sub_events: mpsc::UnboundedReceiver<PubSubEvent>,
+    ) -> anyhow::Result<()> {
+        wait_for_notifier(&mut pub_sub_events, SubscriptionType::Blocks).await;
+        wait_for_notifier(&mut pub_sub_events, SubscriptionType::Logs).await;
+
+        let mut blocks = client
+            .subscribe_blocks(proto::SubscribeBlocksRequest {})
+            .await?
+            .into_inner();
+        let logs_request = proto::SubscribeLogsRequest {
+            addresses: vec![Address::repeat_byte(23).as_bytes().to_vec()],
+            topics: vec![],
+        };
+        let mut logs = client.subscribe_logs(logs_request).await?.into_inner();
+
+        let (_, events) = store_events(&mut pool.access_storage().await?, 1, 0).await?;
+
+        let block = tokio::time::timeout(TEST_TIMEOUT, blocks.message())
+            .await
+            .context("Timed out waiting for block")??
+            .context("Blocks stream terminated")?;
+        assert_eq!(block.number, 1);
+
+        let mut received_logs = vec![];
+        while received_logs.len() < 2 {
+            let log = tokio::time::timeout(TEST_TIMEOUT, logs.message())
+                .await
+                .context("Timed out waiting for log")??
+                .context("Logs stream terminated")?;
+            received_logs.push(log);
+        }
+        for (log, expected_event) in received_logs.iter().zip([&events[0], &events[3]]) {
+            assert_eq!(log.address, expected_event.address.as_bytes());
+            assert_eq!(log.data, expected_event.value);
+            assert_eq!(log.block_number, 1);
+        }
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn streaming_blocks_and_logs() {
+    test_grpc_server(BlocksAndLogsStreamingTest).await;
+}
+
+#[derive(Debug)]
+struct InvalidLogsFilterTest;
+
+#[async_trait]
+impl GrpcTest for InvalidLogsFilterTest {
+    async fn test(
+        &self,
+        mut client: StreamingApiClient<Channel>,
+        _pool: &ConnectionPool,
+        _pub_sub_events: mpsc::UnboundedReceiver<PubSubEvent>,
+    ) -> anyhow::Result<()> {
+        let logs_request = proto::SubscribeLogsRequest {
+            addresses: vec![vec![1; 10]],
+            topics: vec![],
+        };
+        let status = client.subscribe_logs(logs_request).await.unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+
+        let logs_request = proto::SubscribeLogsRequest {
+            addresses: vec![],
+            topics: vec![proto::TopicFilter { values: vec![] }; EVENT_TOPIC_NUMBER_LIMIT + 1],
+        };
+        let status = client.subscribe_logs(logs_request).await.unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn invalid_logs_filter_is_rejected() {
+    test_grpc_server(InvalidLogsFilterTest).await;
+}
+
+#[derive(Debug)]
+struct ConcurrentStreamsLimitTest;
+
+#[async_trait]
+impl GrpcTest for ConcurrentStreamsLimitTest {
+    fn max_concurrent_streams(&self) -> usize {
+        1
+    }
+
+    async fn test(
+        &self,
+        mut client: StreamingApiClient<Channel>,
+        _pool: &ConnectionPool,
+        _pub_sub_events: mpsc::UnboundedReceiver<PubSubEvent>,
+    ) -> anyhow::Result<()> {
+        let blocks = client
+            .subscribe_blocks(proto::SubscribeBlocksRequest {})
+            .await?
+            .into_inner();
+        let status = client
+            .subscribe_l1_batch_status(proto::SubscribeL1BatchStatusRequest {})
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::ResourceExhausted);
+
+        // Closing the stream should release its permit.
+        drop(blocks);
+        tokio::time::timeout(TEST_TIMEOUT, async {
+            loop {
+                let result = client
+                    .subscribe_l1_batch_status(proto::SubscribeL1BatchStatusRequest {})
+                    .await;
+                match result {
+                    Ok(_) => break,
+                    Err(status) if status.code() == Code::ResourceExhausted => {
+                        tokio::time::sleep(POLL_INTERVAL).await;
+                    }
+                    Err(status) => panic!("Unexpected error: {status}"),
+                }
+            }
+        })
+        .await
+        .context("Timed out waiting for stream permit to be released")?;
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn concurrent_streams_limit() {
+    test_grpc_server(ConcurrentStreamsLimitTest).await;
+}
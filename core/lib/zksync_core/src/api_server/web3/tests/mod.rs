@@ -2,24 +2,32 @@ use std::{sync::Arc, time::Instant};
 
 use assert_matches::assert_matches;
 use async_trait::async_trait;
+use test_casing::test_casing;
 use tokio::sync::watch;
 use zksync_config::configs::{
     api::Web3JsonRpcConfig,
     chain::{NetworkConfig, StateKeeperConfig},
+    eth_sender::ETHSenderConfig,
     ContractsConfig,
 };
-use zksync_dal::{transactions_dal::L2TxSubmissionResult, ConnectionPool};
+use zksync_dal::{
+    transactions_dal::{DroppedTxReason, L2TxSubmissionResult},
+    ConnectionPool,
+};
 use zksync_health_check::CheckHealth;
 use zksync_state::PostgresStorageCaches;
 use zksync_system_constants::L1_GAS_PER_PUBDATA_BYTE;
 use zksync_types::{
-    block::MiniblockHeader, fee::TransactionExecutionMetrics, tx::IncludedTxLocation, Address,
-    L1BatchNumber, VmEvent, H256, U64,
+    block::{BlockGasCount, MiniblockHeader},
+    fee::TransactionExecutionMetrics,
+    fee_model::BaseTokenConversionRatio,
+    tx::IncludedTxLocation,
+    Address, L1BatchNumber, Transaction, VmEvent, H256, U256, U64,
 };
 use zksync_web3_decl::{
     jsonrpsee::{core::ClientError as RpcError, http_client::HttpClient, types::error::ErrorCode},
     namespaces::{EthNamespaceClient, ZksNamespaceClient},
-    types::FilterChanges,
+    types::{FilterChanges, GetLogsResponse},
 };
 
 use super::{metrics::ApiTransportLabel, *};
@@ -27,10 +35,20 @@ use crate::{
     api_server::tx_sender::TxSenderConfig,
     genesis::{ensure_genesis_state, GenesisParams},
     l1_gas_price::L1GasPriceProvider,
-    utils::testonly::{create_l2_transaction, create_miniblock},
+    utils::testonly::{
+        create_l1_batch, create_l1_batch_metadata, create_l2_transaction, create_miniblock,
+        execute_l2_transaction,
+    },
 };
 
+mod auth;
+mod batch;
+mod compression;
+mod grpc;
+mod rate_limit;
 mod snapshots;
+mod sse;
+mod txpool;
 mod ws;
 
 const TEST_TIMEOUT: Duration = Duration::from_secs(10);
@@ -88,12 +106,14 @@ impl ApiServerHandles {
 
 pub(crate) async fn spawn_http_server(
     network_config: &NetworkConfig,
+    web3_config: Web3JsonRpcConfig,
     pool: ConnectionPool,
     stop_receiver: watch::Receiver<bool>,
 ) -> ApiServerHandles {
     spawn_server(
         ApiTransportLabel::Http,
         network_config,
+        web3_config,
         pool,
         stop_receiver,
         None,
@@ -104,6 +124,7 @@ pub(crate) async fn spawn_http_server(
 
 async fn spawn_ws_server(
     network_config: &NetworkConfig,
+    web3_config: Web3JsonRpcConfig,
     pool: ConnectionPool,
     stop_receiver: watch::Receiver<bool>,
     websocket_requests_per_minute_limit: Option<NonZeroU32>,
@@ -111,6 +132,7 @@ async fn spawn_ws_server(
     spawn_server(
         ApiTransportLabel::Ws,
         network_config,
+        web3_config,
         pool,
         stop_receiver,
         websocket_requests_per_minute_limit,
@@ -121,14 +143,19 @@ async fn spawn_ws_server(
 async fn spawn_server(
     transport: ApiTransportLabel,
     network_config: &NetworkConfig,
+    web3_config: Web3JsonRpcConfig,
     pool: ConnectionPool,
     stop_receiver: watch::Receiver<bool>,
     websocket_requests_per_minute_limit: Option<NonZeroU32>,
 ) -> (ApiServerHandles, mpsc::UnboundedReceiver<PubSubEvent>) {
     let contracts_config = ContractsConfig::for_tests();
-    let web3_config = Web3JsonRpcConfig::for_tests();
     let state_keeper_config = StateKeeperConfig::for_tests();
-    let api_config = InternalApiConfig::new(network_config, &web3_config, &contracts_config);
+    let api_config = InternalApiConfig::new(
+        network_config,
+        &web3_config,
+        &contracts_config,
+        BaseTokenConversionRatio::default(),
+    );
     let tx_sender_config =
         TxSenderConfig::new(&state_keeper_config, &web3_config, api_config.l2_chain_id);
 
@@ -142,19 +169,32 @@ async fn spawn_server(
         pool.clone(),
         gas_adjuster,
         storage_caches,
+        None,
+        None,
+        api_config.fee_token.conversion_ratio,
+        None,
     )
     .await;
     let (pub_sub_events_sender, pub_sub_events_receiver) = mpsc::unbounded_channel();
 
     let mut namespaces = Namespace::DEFAULT.to_vec();
     namespaces.push(Namespace::Snapshots);
+    namespaces.push(Namespace::Txpool);
 
-    let server_builder = match transport {
+    let shared_filters_pool = web3_config.shared_filters.then(|| pool.clone());
+    let mut server_builder = match transport {
         ApiTransportLabel::Http => ApiBuilder::jsonrpsee_backend(api_config, pool).http(0),
         ApiTransportLabel::Ws => {
             let mut builder = ApiBuilder::jsonrpsee_backend(api_config, pool)
                 .ws(0)
-                .with_subscriptions_limit(100);
+                .with_subscriptions_limit(100)
+                .with_max_subscriptions_per_connection(
+                    web3_config.max_subscriptions_per_connection(),
+                )
+                .with_subscription_queue(
+                    web3_config.subscription_queue_capacity(),
+                    web3_config.subscription_backpressure_policy,
+                );
             if let Some(websocket_requests_per_minute_limit) = websocket_requests_per_minute_limit {
                 builder = builder
                     .with_websocket_requests_per_minute_limit(websocket_requests_per_minute_limit);
@@ -162,8 +202,50 @@ async fn spawn_server(
             builder
         }
     };
+    if let Some(pool) = shared_filters_pool {
+        server_builder = server_builder.with_shared_filters(pool);
+    }
+    if web3_config.sse_enabled {
+        server_builder = server_builder.enable_sse_endpoint();
+    }
+    if let Some(limit) = web3_config.max_batch_request_size {
+        server_builder = server_builder.with_batch_request_size_limit(limit);
+    }
+    if web3_config.max_batch_response_size_mb.is_some() {
+        let limit = web3_config.max_batch_response_size();
+        server_builder = server_builder.with_batch_response_size_limit(limit);
+    }
+    if let Some(parallelism) = web3_config.batch_request_parallelism {
+        server_builder = server_builder.with_batch_request_parallelism(parallelism);
+    }
+    if let Some(limit) = web3_config.heavy_calls_concurrency_limit {
+        let light_calls_limit = web3_config.light_calls_concurrency_limit;
+        server_builder = server_builder.with_execution_pools(limit, light_calls_limit);
+    }
+    if web3_config.response_compression_enabled {
+        let min_size = web3_config.response_compression_min_size();
+        server_builder = server_builder.with_response_compression(min_size);
+    }
+    if let Some(auth) = ApiAuth::from_config(&web3_config).unwrap() {
+        if auth.namespaces().contains(&Namespace::Admin) {
+            namespaces.push(Namespace::Admin);
+            server_builder = server_builder
+                .with_sealing_control(SealingControl::default())
+                .with_fee_model_settings(FeeModelSettingsHandle::new(
+                    &state_keeper_config,
+                    &ETHSenderConfig::for_tests().gas_adjuster,
+                ))
+                .with_aggregation_policy(
+                    AggregationPolicyHandle::new(&ETHSenderConfig::for_tests().sender).unwrap(),
+                );
+        }
+        server_builder = server_builder.with_auth(auth);
+    }
     let server_handles = server_builder
+        .with_rate_limits(RateLimits::from(&web3_config))
+        .with_method_filter(MethodFilter::from_config(&web3_config).unwrap())
         .with_polling_interval(POLL_INTERVAL)
+        .with_draining_timeout(web3_config.graceful_shutdown_timeout())
         .with_tx_sender(tx_sender, vm_barrier)
         .with_pub_sub_events(pub_sub_events_sender)
         .enable_api_namespaces(namespaces)
@@ -175,6 +257,11 @@ async fn spawn_server(
 
 #[async_trait]
 trait HttpTest {
+    /// Returns the config for the tested server. The default implementation returns the config for tests.
+    fn web3_config(&self) -> Web3JsonRpcConfig {
+        Web3JsonRpcConfig::for_tests()
+    }
+
     async fn test(&self, client: &HttpClient, pool: &ConnectionPool) -> anyhow::Result<()>;
 }
 
@@ -194,7 +281,13 @@ async fn test_http_server(test: impl HttpTest) {
     drop(storage);
 
     let (stop_sender, stop_receiver) = watch::channel(false);
-    let server_handles = spawn_http_server(&network_config, pool.clone(), stop_receiver).await;
+    let server_handles = spawn_http_server(
+        &network_config,
+        test.web3_config(),
+        pool.clone(),
+        stop_receiver,
+    )
+    .await;
     server_handles.wait_until_ready().await;
 
     let client = <HttpClient>::builder()
@@ -234,6 +327,55 @@ async fn store_miniblock(
     Ok((new_miniblock, new_tx_hash))
 }
 
+async fn store_miniblock_with_transactions(
+    storage: &mut StorageProcessor<'_>,
+    tx_count: usize,
+) -> anyhow::Result<Vec<H256>> {
+    let mut tx_results = Vec::with_capacity(tx_count);
+    for _ in 0..tx_count {
+        let tx = create_l2_transaction(10, 100);
+        storage
+            .transactions_dal()
+            .insert_transaction_l2(tx.clone(), TransactionExecutionMetrics::default())
+            .await;
+        tx_results.push(execute_l2_transaction(tx));
+    }
+    let new_miniblock = MiniblockHeader {
+        l2_tx_count: tx_count as u16,
+        ..create_miniblock(1)
+    };
+    storage
+        .blocks_dal()
+        .insert_miniblock(&new_miniblock)
+        .await?;
+    storage
+        .transactions_dal()
+        .mark_txs_as_executed_in_miniblock(MiniblockNumber(1), &tx_results, 1.into())
+        .await;
+    Ok(tx_results.iter().map(|result| result.hash).collect())
+}
+
+async fn seal_l1_batch(
+    storage: &mut StorageProcessor<'_>,
+    number: L1BatchNumber,
+) -> anyhow::Result<()> {
+    let header = create_l1_batch(number.0);
+    storage
+        .blocks_dal()
+        .insert_l1_batch(&header, &[], BlockGasCount::default(), &[], &[], 0)
+        .await?;
+    storage
+        .blocks_dal()
+        .mark_miniblocks_as_executed_in_l1_batch(number)
+        .await?;
+    let metadata = create_l1_batch_metadata(number.0);
+    storage
+        .blocks_dal()
+        .save_l1_batch_metadata(number, &metadata, H256::zero(), false)
+        .await?;
+    Ok(())
+}
+
 async fn store_events(
     storage: &mut StorageProcessor<'_>,
     miniblock_number: u32,
@@ -306,6 +448,31 @@ impl HttpTest for HttpServerBasicsTest {
             .await?
             .context("No genesis L1 batch")?;
         assert!(genesis_l1_batch.base.root_hash.is_some());
+
+        let genesis_fee_params = client
+            .get_l1_batch_fee_params(L1BatchNumber(0))
+            .await?
+            .context("No fee params for genesis L1 batch")?;
+        assert_eq!(genesis_fee_params.number, L1BatchNumber(0));
+        assert_eq!(
+            genesis_fee_params.l1_gas_price,
+            genesis_l1_batch.base.l1_gas_price.into()
+        );
+        assert_eq!(
+            genesis_fee_params.fair_l2_gas_price,
+            genesis_l1_batch.base.l2_fair_gas_price.into()
+        );
+        let missing_fee_params = client.get_l1_batch_fee_params(L1BatchNumber(1)).await?;
+        assert!(missing_fee_params.is_none());
+        let revenue = client.get_l1_batch_revenue(L1BatchNumber(0)).await?;
+        assert!(revenue.is_none());
+
+        let fee_token = client.get_fee_token().await?;
+        assert_eq!(fee_token.l1_address, Address::zero());
+        assert_eq!(
+            fee_token.conversion_ratio,
+            BaseTokenConversionRatio::default()
+        );
         Ok(())
     }
 }
@@ -316,10 +483,19 @@ async fn http_server_basics() {
 }
 
 #[derive(Debug)]
-struct BasicFilterChangesTest;
+struct BasicFilterChangesTest {
+    shared_filters: bool,
+}
 
 #[async_trait]
 impl HttpTest for BasicFilterChangesTest {
+    fn web3_config(&self) -> Web3JsonRpcConfig {
+        Web3JsonRpcConfig {
+            shared_filters: self.shared_filters,
+            ..Web3JsonRpcConfig::for_tests()
+        }
+    }
+
     async fn test(&self, client: &HttpClient, pool: &ConnectionPool) -> anyhow::Result<()> {
         let block_filter_id = client.new_block_filter().await?;
         let tx_filter_id = client.new_pending_transaction_filter().await?;
@@ -358,16 +534,26 @@ impl HttpTest for BasicFilterChangesTest {
     }
 }
 
+#[test_casing(2, [false, true])]
 #[tokio::test]
-async fn basic_filter_changes() {
-    test_http_server(BasicFilterChangesTest).await;
+async fn basic_filter_changes(shared_filters: bool) {
+    test_http_server(BasicFilterChangesTest { shared_filters }).await;
 }
 
 #[derive(Debug)]
-struct LogFilterChangesTest;
+struct LogFilterChangesTest {
+    shared_filters: bool,
+}
 
 #[async_trait]
 impl HttpTest for LogFilterChangesTest {
+    fn web3_config(&self) -> Web3JsonRpcConfig {
+        Web3JsonRpcConfig {
+            shared_filters: self.shared_filters,
+            ..Web3JsonRpcConfig::for_tests()
+        }
+    }
+
     async fn test(&self, client: &HttpClient, pool: &ConnectionPool) -> anyhow::Result<()> {
         let all_logs_filter_id = client.new_filter(Filter::default()).await?;
         let address_filter = Filter {
@@ -413,9 +599,77 @@ impl HttpTest for LogFilterChangesTest {
     }
 }
 
+#[test_casing(2, [false, true])]
 #[tokio::test]
-async fn log_filter_changes() {
-    test_http_server(LogFilterChangesTest).await;
+async fn log_filter_changes(shared_filters: bool) {
+    test_http_server(LogFilterChangesTest { shared_filters }).await;
+}
+
+#[tokio::test]
+async fn filters_are_shared_among_servers() {
+    let pool = ConnectionPool::test_pool().await;
+    let network_config = NetworkConfig::for_tests();
+    let mut storage = pool.access_storage().await.unwrap();
+    ensure_genesis_state(
+        &mut storage,
+        network_config.zksync_network_id,
+        &GenesisParams::mock(),
+    )
+    .await
+    .unwrap();
+    drop(storage);
+
+    let web3_config = Web3JsonRpcConfig {
+        shared_filters: true,
+        ..Web3JsonRpcConfig::for_tests()
+    };
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let mut clients = vec![];
+    let mut all_server_handles = vec![];
+    for _ in 0..2 {
+        let server_handles = spawn_http_server(
+            &network_config,
+            web3_config.clone(),
+            pool.clone(),
+            stop_receiver.clone(),
+        )
+        .await;
+        server_handles.wait_until_ready().await;
+        let client = <HttpClient>::builder()
+            .build(format!("http://{}/", server_handles.local_addr))
+            .unwrap();
+        clients.push(client);
+        all_server_handles.push(server_handles);
+    }
+
+    let block_filter_id = clients[0].new_block_filter().await.unwrap();
+    let (new_miniblock, _) = store_miniblock(&mut pool.access_storage().await.unwrap())
+        .await
+        .unwrap();
+    let block_filter_changes = clients[1]
+        .get_filter_changes(block_filter_id)
+        .await
+        .unwrap();
+    assert_matches!(
+        block_filter_changes,
+        FilterChanges::Hashes(hashes) if hashes == [new_miniblock.hash]
+    );
+    // The filter cursor must be advanced for the other server as well.
+    let block_filter_changes = clients[0]
+        .get_filter_changes(block_filter_id)
+        .await
+        .unwrap();
+    assert_matches!(block_filter_changes, FilterChanges::Hashes(hashes) if hashes.is_empty());
+
+    let removed = clients[1].uninstall_filter(block_filter_id).await.unwrap();
+    assert!(removed);
+    let removed = clients[0].uninstall_filter(block_filter_id).await.unwrap();
+    assert!(!removed);
+
+    stop_sender.send_replace(true);
+    for server_handles in all_server_handles {
+        server_handles.shutdown().await;
+    }
 }
 
 #[derive(Debug)]
@@ -514,3 +768,495 @@ impl HttpTest for LogFilterChangesWithBlockBoundariesTest {
 async fn log_filter_changes_with_block_boundaries() {
     test_http_server(LogFilterChangesWithBlockBoundariesTest).await;
 }
+
+#[derive(Debug)]
+struct FeeHistoryTest;
+
+#[async_trait]
+impl HttpTest for FeeHistoryTest {
+    async fn test(&self, client: &HttpClient, pool: &ConnectionPool) -> anyhow::Result<()> {
+        let mut storage = pool.access_storage().await?;
+        let mut tx_results = vec![];
+        // Miniblocks have base fee 100, so the effective priority fees are 10 and 50, respectively.
+        for (max_fee_per_gas, max_priority_fee_per_gas) in [(110_u64, 50_u64), (200, 50)] {
+            let mut tx = create_l2_transaction(max_fee_per_gas, 50);
+            tx.common_data.fee.max_priority_fee_per_gas = max_priority_fee_per_gas.into();
+            storage
+                .transactions_dal()
+                .insert_transaction_l2(tx.clone(), TransactionExecutionMetrics::default())
+                .await;
+            tx_results.push(execute_l2_transaction(tx));
+        }
+        let new_miniblock = MiniblockHeader {
+            l2_tx_count: 2,
+            ..create_miniblock(1)
+        };
+        storage
+            .blocks_dal()
+            .insert_miniblock(&new_miniblock)
+            .await?;
+        storage
+            .transactions_dal()
+            .mark_txs_as_executed_in_miniblock(MiniblockNumber(1), &tx_results, 100.into())
+            .await;
+        drop(storage);
+
+        let fee_history = client
+            .fee_history(U64::from(2), api::BlockNumber::Latest, vec![25.0, 75.0])
+            .await?;
+        assert_eq!(
+            fee_history.oldest_block,
+            zksync_types::web3::types::BlockNumber::Number(0.into())
+        );
+        assert_eq!(fee_history.gas_used_ratio.len(), 2);
+        assert_eq!(fee_history.gas_used_ratio[0], 0.0);
+        assert!(fee_history.gas_used_ratio[1] > 0.0);
+        let reward = fee_history.reward.context("no reward")?;
+        assert_eq!(reward[0], [U256::zero(), U256::zero()]);
+        assert_eq!(reward[1], [U256::from(10), U256::from(50)]);
+
+        let err = client
+            .fee_history(U64::from(1), api::BlockNumber::Latest, vec![75.0, 25.0])
+            .await
+            .unwrap_err();
+        assert_matches!(err, RpcError::Call(err) if err.code() == ErrorCode::InvalidParams.code());
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn fee_history() {
+    test_http_server(FeeHistoryTest).await;
+}
+
+#[derive(Debug)]
+struct RawBlockTransactionsPaginationTest;
+
+#[async_trait]
+impl HttpTest for RawBlockTransactionsPaginationTest {
+    async fn test(&self, client: &HttpClient, pool: &ConnectionPool) -> anyhow::Result<()> {
+        let mut storage = pool.access_storage().await?;
+        let tx_hashes = store_miniblock_with_transactions(&mut storage, 5).await?;
+        drop(storage);
+
+        let all_txs = client
+            .get_raw_block_transactions(MiniblockNumber(1), None, None)
+            .await?;
+        let all_hashes: Vec<_> = all_txs.iter().map(Transaction::hash).collect();
+        assert_eq!(all_hashes, tx_hashes);
+
+        let page = client
+            .get_raw_block_transactions(MiniblockNumber(1), Some(1), Some(3))
+            .await?;
+        let page_hashes: Vec<_> = page.iter().map(Transaction::hash).collect();
+        assert_eq!(page_hashes, tx_hashes[1..4]);
+        let last_page = client
+            .get_raw_block_transactions(MiniblockNumber(1), Some(3), Some(3))
+            .await?;
+        let last_page_hashes: Vec<_> = last_page.iter().map(Transaction::hash).collect();
+        assert_eq!(last_page_hashes, tx_hashes[3..]);
+        let tail = client
+            .get_raw_block_transactions(MiniblockNumber(1), Some(4), None)
+            .await?;
+        assert_eq!(tail.len(), 1);
+
+        let err = client
+            .get_raw_block_transactions(MiniblockNumber(1), None, Some(0))
+            .await
+            .unwrap_err();
+        assert_matches!(err, RpcError::Call(err) if err.code() == ErrorCode::InvalidParams.code());
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn raw_block_transactions_pagination() {
+    test_http_server(RawBlockTransactionsPaginationTest).await;
+}
+
+#[derive(Debug)]
+struct LogsPaginationTest;
+
+#[async_trait]
+impl HttpTest for LogsPaginationTest {
+    async fn test(&self, client: &HttpClient, pool: &ConnectionPool) -> anyhow::Result<()> {
+        let mut storage = pool.access_storage().await?;
+        let (_, first_events) = store_events(&mut storage, 1, 0).await?;
+        let (_, second_events) = store_events(&mut storage, 2, 4).await?;
+        drop(storage);
+        let events: Vec<_> = first_events.iter().chain(&second_events).collect();
+
+        let filter = Filter {
+            from_block: Some(api::BlockNumber::Number(1.into())),
+            ..Filter::default()
+        };
+        let all_logs = client.get_logs(filter.clone()).await?;
+        let GetLogsResponse::Logs(all_logs) = all_logs else {
+            panic!("Unexpected getLogs output: {all_logs:?}");
+        };
+        assert_logs_match(&all_logs, &events);
+
+        let mut page_filter = Filter {
+            limit: Some(3),
+            ..filter.clone()
+        };
+        let mut pages = vec![];
+        loop {
+            let page = client.get_logs(page_filter.clone()).await?;
+            let GetLogsResponse::Page(page) = page else {
+                panic!("Unexpected getLogs output: {page:?}");
+            };
+            assert!(page.logs.len() <= 3);
+            pages.push(page.logs);
+            match page.next_page_token {
+                Some(token) => page_filter.page_token = Some(token),
+                None => break,
+            }
+        }
+        assert_eq!(pages.len(), 3);
+        let paginated_logs: Vec<_> = pages.into_iter().flatten().collect();
+        assert_logs_match(&paginated_logs, &events);
+
+        let bogus_filters = [
+            Filter {
+                page_token: Some("0x1234".to_owned()),
+                ..filter.clone()
+            },
+            Filter {
+                limit: Some(0),
+                ..filter.clone()
+            },
+            Filter {
+                from_block: Some(api::BlockNumber::Number(2.into())),
+                page_token: Some(format!("0x{:08x}{:08x}", 1, 0)),
+                ..filter
+            },
+        ];
+        for bogus_filter in bogus_filters {
+            let err = client.get_logs(bogus_filter).await.unwrap_err();
+            assert_matches!(
+                err,
+                RpcError::Call(err) if err.code() == ErrorCode::InvalidParams.code()
+            );
+        }
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn logs_pagination() {
+    test_http_server(LogsPaginationTest).await;
+}
+
+#[derive(Debug)]
+struct GetLogsLimitsTest;
+
+#[async_trait]
+impl HttpTest for GetLogsLimitsTest {
+    fn web3_config(&self) -> Web3JsonRpcConfig {
+        Web3JsonRpcConfig {
+            get_logs_block_range_limit: Some(2),
+            get_logs_max_results: Some(5),
+            ..Web3JsonRpcConfig::for_tests()
+        }
+    }
+
+    async fn test(&self, client: &HttpClient, pool: &ConnectionPool) -> anyhow::Result<()> {
+        let mut storage = pool.access_storage().await?;
+        for miniblock_number in 1..=3 {
+            store_events(&mut storage, miniblock_number, (miniblock_number - 1) * 4).await?;
+        }
+        drop(storage);
+
+        let filter = Filter {
+            from_block: Some(api::BlockNumber::Number(1.into())),
+            to_block: Some(api::BlockNumber::Number(3.into())),
+            ..Filter::default()
+        };
+        let err = client.get_logs(filter.clone()).await.unwrap_err();
+        let RpcError::Call(err) = err else {
+            panic!("Unexpected error: {err:?}");
+        };
+        assert_eq!(err.code(), ErrorCode::InvalidParams.code());
+        let data: serde_json::Value = serde_json::from_str(err.data().context("no data")?.get())?;
+        assert_eq!(
+            data,
+            serde_json::json!({ "from": "0x1", "to": "0x2", "limit": 2 })
+        );
+
+        // The range is within the limit, but the number of logs (8) exceeds the limit.
+        let filter = Filter {
+            to_block: Some(api::BlockNumber::Number(2.into())),
+            ..filter
+        };
+        let err = client.get_logs(filter.clone()).await.unwrap_err();
+        let RpcError::Call(err) = err else {
+            panic!("Unexpected error: {err:?}");
+        };
+        assert_eq!(err.code(), ErrorCode::InvalidParams.code());
+        let data: serde_json::Value = serde_json::from_str(err.data().context("no data")?.get())?;
+        assert_eq!(
+            data,
+            serde_json::json!({ "from": "0x1", "to": "0x1", "limit": 5 })
+        );
+
+        // The suggested range should work.
+        let filter = Filter {
+            to_block: Some(api::BlockNumber::Number(1.into())),
+            ..filter
+        };
+        let logs = client.get_logs(filter).await?.into_logs();
+        assert_eq!(logs.len(), 4);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn get_logs_limits() {
+    test_http_server(GetLogsLimitsTest).await;
+}
+
+#[derive(Debug)]
+struct L2ToL1LogProofsTest;
+
+#[async_trait]
+impl HttpTest for L2ToL1LogProofsTest {
+    fn web3_config(&self) -> Web3JsonRpcConfig {
+        Web3JsonRpcConfig {
+            req_entities_limit: Some(2),
+            ..Web3JsonRpcConfig::for_tests()
+        }
+    }
+
+    async fn test(&self, client: &HttpClient, _pool: &ConnectionPool) -> anyhow::Result<()> {
+        let requests: Vec<_> = (1..=2)
+            .map(|i| api::L2ToL1LogProofRequest {
+                tx_hash: H256::repeat_byte(i),
+                index: None,
+            })
+            .collect();
+        let proofs = client.get_l2_to_l1_log_proofs(requests.clone()).await?;
+        assert_eq!(proofs.len(), 2);
+        assert!(proofs.iter().all(Option::is_none));
+
+        let too_many_requests = [requests.clone(), requests].concat();
+        let err = client
+            .get_l2_to_l1_log_proofs(too_many_requests)
+            .await
+            .unwrap_err();
+        assert_matches!(
+            err,
+            RpcError::Call(err) if err.code() == ErrorCode::InvalidParams.code()
+        );
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn l2_to_l1_log_proofs() {
+    test_http_server(L2ToL1LogProofsTest).await;
+}
+
+#[derive(Debug)]
+struct GetProofKeysLimitTest;
+
+#[async_trait]
+impl HttpTest for GetProofKeysLimitTest {
+    fn web3_config(&self) -> Web3JsonRpcConfig {
+        Web3JsonRpcConfig {
+            req_entities_limit: Some(2),
+            ..Web3JsonRpcConfig::for_tests()
+        }
+    }
+
+    async fn test(&self, client: &HttpClient, _pool: &ConnectionPool) -> anyhow::Result<()> {
+        let keys: Vec<_> = (1..=3).map(H256::repeat_byte).collect();
+        let err = client
+            .get_proof(
+                Address::repeat_byte(1),
+                keys,
+                api::BlockIdVariant::BlockNumber(api::BlockNumber::Latest),
+            )
+            .await
+            .unwrap_err();
+        assert_matches!(
+            err,
+            RpcError::Call(err) if err.code() == ErrorCode::InvalidParams.code()
+        );
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn get_proof_keys_limit() {
+    test_http_server(GetProofKeysLimitTest).await;
+}
+
+#[derive(Debug)]
+struct MethodFilterTest;
+
+#[async_trait]
+impl HttpTest for MethodFilterTest {
+    fn web3_config(&self) -> Web3JsonRpcConfig {
+        Web3JsonRpcConfig {
+            denied_methods: Some(vec!["snapshots_*".to_owned(), "eth_gasPrice".to_owned()]),
+            allowed_methods: Some(vec!["snapshots_getAllSnapshots".to_owned()]),
+            ..Web3JsonRpcConfig::for_tests()
+        }
+    }
+
+    async fn test(&self, client: &HttpClient, _pool: &ConnectionPool) -> anyhow::Result<()> {
+        use zksync_web3_decl::namespaces::SnapshotsNamespaceClient;
+
+        client.chain_id().await?;
+        client.get_all_snapshots().await?;
+
+        let err = client.gas_price().await.unwrap_err();
+        assert_matches!(
+            err,
+            RpcError::Call(err) if err.code() == ErrorCode::MethodNotFound.code()
+        );
+        let err = client
+            .get_snapshot_by_l1_batch_number(L1BatchNumber(1))
+            .await
+            .unwrap_err();
+        assert_matches!(
+            err,
+            RpcError::Call(err) if err.code() == ErrorCode::MethodNotFound.code()
+        );
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn method_filter() {
+    test_http_server(MethodFilterTest).await;
+}
+
+#[derive(Debug)]
+struct DroppedTransactionTest;
+
+#[async_trait]
+impl HttpTest for DroppedTransactionTest {
+    async fn test(&self, client: &HttpClient, pool: &ConnectionPool) -> anyhow::Result<()> {
+        let tx = create_l2_transaction(10, 100);
+        let mut storage = pool.access_storage().await?;
+        storage
+            .transactions_dal()
+            .insert_transaction_l2(tx.clone(), TransactionExecutionMetrics::default())
+            .await;
+        let tx_by_hash = client.get_transaction_by_hash(tx.hash()).await?;
+        assert_eq!(tx_by_hash.unwrap().hash, tx.hash());
+
+        storage
+            .transactions_dal()
+            .drop_mempool_txs(&[tx.hash()], &DroppedTxReason::Expired)
+            .await?;
+        drop(storage);
+
+        let err = client.get_transaction_by_hash(tx.hash()).await.unwrap_err();
+        assert_matches!(
+            err,
+            RpcError::Call(err) if err.code() == 12 && err.message() == "dropped: expired"
+        );
+        let unknown_tx = client.get_transaction_by_hash(H256::repeat_byte(1)).await?;
+        assert!(unknown_tx.is_none());
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn dropped_transaction() {
+    test_http_server(DroppedTransactionTest).await;
+}
+
+#[derive(Debug)]
+struct EstimateGasAtMissingBlockTest;
+
+#[async_trait]
+impl HttpTest for EstimateGasAtMissingBlockTest {
+    async fn test(&self, client: &HttpClient, _pool: &ConnectionPool) -> anyhow::Result<()> {
+        let request = zksync_types::transaction_request::CallRequest {
+            from: Some(Address::repeat_byte(1)),
+            to: Some(Address::repeat_byte(2)),
+            ..Default::default()
+        };
+        let missing_block = api::BlockNumber::Number(100.into());
+
+        let err = client
+            .estimate_gas(request.clone(), Some(missing_block), None)
+            .await
+            .unwrap_err();
+        assert_matches!(err, RpcError::Call(err) if err.code() == ErrorCode::InvalidParams.code());
+        let err = client
+            .estimate_fee(request, None, Some(missing_block))
+            .await
+            .unwrap_err();
+        assert_matches!(err, RpcError::Call(err) if err.code() == ErrorCode::InvalidParams.code());
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn estimating_gas_at_missing_block() {
+    test_http_server(EstimateGasAtMissingBlockTest).await;
+}
+
+#[derive(Debug)]
+struct PrunedBlocksTest;
+
+#[async_trait]
+impl HttpTest for PrunedBlocksTest {
+    async fn test(&self, client: &HttpClient, pool: &ConnectionPool) -> anyhow::Result<()> {
+        let mut storage = pool.access_storage().await?;
+        store_miniblock_with_transactions(&mut storage, 2).await?;
+        seal_l1_batch(&mut storage, L1BatchNumber(1)).await?;
+        storage
+            .blocks_dal()
+            .insert_miniblock(&create_miniblock(2))
+            .await?;
+        seal_l1_batch(&mut storage, L1BatchNumber(2)).await?;
+        storage
+            .pruning_dal()
+            .prune_batches_range(L1BatchNumber(1), MiniblockNumber(1))
+            .await?;
+        drop(storage);
+
+        for number in [0, 1] {
+            let block_number = api::BlockNumber::Number(number.into());
+            let err = client
+                .get_block_by_number(block_number, false)
+                .await
+                .unwrap_err();
+            assert_matches!(
+                err,
+                RpcError::Call(err) if err.code() == 18 && err.message().contains("#2")
+            );
+        }
+        let err = client
+            .get_raw_block_transactions(MiniblockNumber(1), None, None)
+            .await
+            .unwrap_err();
+        assert_matches!(err, RpcError::Call(err) if err.code() == 18);
+
+        let block = client
+            .get_block_by_number(api::BlockNumber::Number(2.into()), false)
+            .await?
+            .context("no block #2")?;
+        assert_eq!(block.number, 2.into());
+        // The earliest block should be mapped to the first non-pruned block.
+        let nonce = client
+            .get_transaction_count(
+                Address::repeat_byte(1),
+                Some(api::BlockIdVariant::BlockNumber(api::BlockNumber::Earliest)),
+            )
+            .await?;
+        assert_eq!(nonce, 0.into());
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn pruned_blocks() {
+    test_http_server(PrunedBlocksTest).await;
+}
@@ -0,0 +1,190 @@
+//! Tests for per-IP and per-API-key rate limiting.
+
+use std::num::NonZeroU32;
+
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use tokio::sync::watch;
+use zksync_config::configs::chain::NetworkConfig;
+use zksync_dal::ConnectionPool;
+
+use super::*;
+
+#[async_trait]
+trait RateLimitTest {
+    fn web3_config(&self) -> Web3JsonRpcConfig;
+
+    async fn test(&self, client: &reqwest::Client, url: &str) -> anyhow::Result<()>;
+}
+
+async fn test_rate_limits(test: impl RateLimitTest) {
+    let pool = ConnectionPool::test_pool().await;
+    let network_config = NetworkConfig::for_tests();
+    let mut storage = pool.access_storage().await.unwrap();
+    if storage.blocks_dal().is_genesis_needed().await.unwrap() {
+        ensure_genesis_state(
+            &mut storage,
+            network_config.zksync_network_id,
+            &GenesisParams::mock(),
+        )
+        .await
+        .unwrap();
+    }
+    drop(storage);
+
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let server_handles =
+        spawn_http_server(&network_config, test.web3_config(), pool, stop_receiver).await;
+    server_handles.wait_until_ready().await;
+
+    let url = format!("http://{}/", server_handles.local_addr);
+    test.test(&reqwest::Client::new(), &url).await.unwrap();
+
+    stop_sender.send_replace(true);
+    server_handles.shutdown().await;
+}
+
+async fn send_call(
+    client: &reqwest::Client,
+    url: &str,
+    headers: &[(&str, &str)],
+    method: &str,
+) -> anyhow::Result<(StatusCode, serde_json::Value)> {
+    let mut request = client.post(url).json(&serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": method,
+        "params": [],
+    }));
+    for &(name, value) in headers {
+        request = request.header(name, value);
+    }
+    let response = request.send().await?;
+    Ok((response.status(), response.json().await?))
+}
+
+fn assert_limit_exceeded(status: StatusCode, response: &serde_json::Value) {
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response["error"]["code"], -32_005, "{response}");
+    assert_eq!(response["id"], 1, "{response}");
+}
+
+#[derive(Debug)]
+struct PerIpRequestsTest;
+
+#[async_trait]
+impl RateLimitTest for PerIpRequestsTest {
+    fn web3_config(&self) -> Web3JsonRpcConfig {
+        Web3JsonRpcConfig {
+            rate_limit_requests_per_minute_per_ip: NonZeroU32::new(2),
+            ..Web3JsonRpcConfig::for_tests()
+        }
+    }
+
+    async fn test(&self, client: &reqwest::Client, url: &str) -> anyhow::Result<()> {
+        for _ in 0..2 {
+            let (status, response) = send_call(client, url, &[], "eth_blockNumber").await?;
+            assert_eq!(status, StatusCode::OK, "{response}");
+            assert!(response.get("result").is_some(), "{response}");
+        }
+        let (status, response) = send_call(client, url, &[], "eth_blockNumber").await?;
+        assert_limit_exceeded(status, &response);
+
+        // Without trusted proxies, forwarded addresses are ignored; the client is identified
+        // by the peer address.
+        let spoofed_ip = [("X-Forwarded-For", "5.6.7.8")];
+        let (status, response) = send_call(client, url, &spoofed_ip, "eth_blockNumber").await?;
+        assert_limit_exceeded(status, &response);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn requests_are_limited_per_ip() {
+    test_rate_limits(PerIpRequestsTest).await;
+}
+
+#[derive(Debug)]
+struct PerForwardedIpRequestsTest;
+
+#[async_trait]
+impl RateLimitTest for PerForwardedIpRequestsTest {
+    fn web3_config(&self) -> Web3JsonRpcConfig {
+        Web3JsonRpcConfig {
+            rate_limit_requests_per_minute_per_ip: NonZeroU32::new(2),
+            rate_limit_trusted_proxy_hops: Some(2),
+            ..Web3JsonRpcConfig::for_tests()
+        }
+    }
+
+    async fn test(&self, client: &reqwest::Client, url: &str) -> anyhow::Result<()> {
+        // The last 2 entries are appended by trusted proxies; the first one is controlled by the client.
+        let first_ip = [("X-Forwarded-For", "6.6.6.6, 1.2.3.4, 10.0.0.1")];
+        for _ in 0..2 {
+            let (status, response) = send_call(client, url, &first_ip, "eth_blockNumber").await?;
+            assert_eq!(status, StatusCode::OK, "{response}");
+        }
+        let (status, response) = send_call(client, url, &first_ip, "eth_blockNumber").await?;
+        assert_limit_exceeded(status, &response);
+
+        // Changing the client-controlled part of the header doesn't reset the budget.
+        let spoofed_ip = [("X-Forwarded-For", "7.7.7.7, 1.2.3.4, 10.0.0.1")];
+        let (status, response) = send_call(client, url, &spoofed_ip, "eth_blockNumber").await?;
+        assert_limit_exceeded(status, &response);
+
+        // Another IP address has a separate budget.
+        let second_ip = [("X-Forwarded-For", "5.6.7.8, 10.0.0.1")];
+        let (status, _) = send_call(client, url, &second_ip, "eth_blockNumber").await?;
+        assert_eq!(status, StatusCode::OK);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn requests_are_limited_per_forwarded_ip() {
+    test_rate_limits(PerForwardedIpRequestsTest).await;
+}
+
+#[derive(Debug)]
+struct PerApiKeyComputeUnitsTest;
+
+#[async_trait]
+impl RateLimitTest for PerApiKeyComputeUnitsTest {
+    fn web3_config(&self) -> Web3JsonRpcConfig {
+        Web3JsonRpcConfig {
+            rate_limit_requests_per_minute_per_ip: NonZeroU32::new(1),
+            rate_limit_compute_units_per_minute_per_api_key: NonZeroU32::new(30),
+            rate_limit_api_keys: Some(vec!["test".to_owned()]),
+            ..Web3JsonRpcConfig::for_tests()
+        }
+    }
+
+    async fn test(&self, client: &reqwest::Client, url: &str) -> anyhow::Result<()> {
+        let api_key = [("X-API-Key", "test")];
+        // The API key is not subject to per-IP limits.
+        for _ in 0..10 {
+            let (status, response) = send_call(client, url, &api_key, "eth_blockNumber").await?;
+            assert_eq!(status, StatusCode::OK, "{response}");
+        }
+        // `debug_*` methods are much more expensive than getters, so the compute unit budget
+        // is exhausted quickly.
+        let (status, response) = send_call(client, url, &api_key, "debug_traceCall").await?;
+        assert_eq!(status, StatusCode::OK, "{response}");
+        let (status, response) = send_call(client, url, &api_key, "debug_traceCall").await?;
+        assert_limit_exceeded(status, &response);
+
+        // Unknown API keys are treated as anonymous clients.
+        let unknown_api_key = [("X-API-Key", "unknown")];
+        let (status, _) = send_call(client, url, &unknown_api_key, "eth_blockNumber").await?;
+        assert_eq!(status, StatusCode::OK);
+        let (status, response) =
+            send_call(client, url, &unknown_api_key, "eth_blockNumber").await?;
+        assert_limit_exceeded(status, &response);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn compute_units_are_limited_per_api_key() {
+    test_rate_limits(PerApiKeyComputeUnitsTest).await;
+}
@@ -0,0 +1,193 @@
+//! Tests for the server-sent events endpoint.
+
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use tokio::sync::watch;
+use zksync_config::configs::chain::NetworkConfig;
+use zksync_dal::ConnectionPool;
+use zksync_web3_decl::types::BlockHeader;
+
+use super::{ws::wait_for_notifier, *};
+use crate::api_server::web3::metrics::SubscriptionType;
+
+#[derive(Debug, PartialEq)]
+struct SseEvent {
+    id: Option<String>,
+    name: String,
+    data: String,
+}
+
+/// Minimalistic reader of server-sent events.
+#[derive(Debug)]
+struct SseReader {
+    response: reqwest::Response,
+    buffer: String,
+}
+
+impl SseReader {
+    fn new(response: reqwest::Response) -> Self {
+        Self {
+            response,
+            buffer: String::new(),
+        }
+    }
+
+    async fn next_event(&mut self) -> anyhow::Result<SseEvent> {
+        loop {
+            while let Some(pos) = self.buffer.find("\n\n") {
+                let raw_event: String = self.buffer.drain(..pos + 2).collect();
+                let mut event = SseEvent {
+                    id: None,
+                    name: String::new(),
+                    data: String::new(),
+                };
+                for line in raw_event.lines() {
+                    if let Some(id) = line.strip_prefix("id: ") {
+                        event.id = Some(id.to_owned());
+                    } else if let Some(name) = line.strip_prefix("event: ") {
+                        event.name = name.to_owned();
+                    } else if let Some(data) = line.strip_prefix("data: ") {
+                        event.data = data.to_owned();
+                    }
+                }
+                if !event.name.is_empty() {
+                    return Ok(event); // Otherwise, the event is a comment (e.g., a keep-alive one)
+                }
+            }
+
+            let chunk = tokio::time::timeout(TEST_TIMEOUT, self.response.chunk())
+                .await
+                .context("Timed out waiting for event")??
+                .context("Event stream terminated")?;
+            self.buffer.push_str(std::str::from_utf8(&chunk)?);
+        }
+    }
+}
+
+#[async_trait]
+trait SseTest {
+    async fn test(
+        &self,
+        url: &str,
+        pool: &ConnectionPool,
+        pub_sub_events: mpsc::UnboundedReceiver<PubSubEvent>,
+    ) -> anyhow::Result<()>;
+}
+
+async fn test_sse_endpoint(test: impl SseTest) {
+    let pool = ConnectionPool::test_pool().await;
+    let network_config = NetworkConfig::for_tests();
+    let mut storage = pool.access_storage().await.unwrap();
+    if storage.blocks_dal().is_genesis_needed().await.unwrap() {
+        ensure_genesis_state(
+            &mut storage,
+            network_config.zksync_network_id,
+            &GenesisParams::mock(),
+        )
+        .await
+        .unwrap();
+    }
+    drop(storage);
+
+    let web3_config = Web3JsonRpcConfig {
+        sse_enabled: true,
+        ..Web3JsonRpcConfig::for_tests()
+    };
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let (server_handles, pub_sub_events) = spawn_server(
+        ApiTransportLabel::Http,
+        &network_config,
+        web3_config,
+        pool.clone(),
+        stop_receiver,
+        None,
+    )
+    .await;
+    server_handles.wait_until_ready().await;
+
+    let url = format!("http://{}/events", server_handles.local_addr);
+    test.test(&url, &pool, pub_sub_events).await.unwrap();
+
+    stop_sender.send_replace(true);
+    server_handles.shutdown().await;
+}
+
+#[derive(Debug)]
+struct NewHeadsEventsTest;
+
+#[async_trait]
+impl SseTest for NewHeadsEventsTest {
+    async fn test(
+        &self,
+        url: &str,
+        pool: &ConnectionPool,
+        mut pub_sub_events: mpsc::UnboundedReceiver<PubSubEvent>,
+    ) -> anyhow::Result<()> {
+        wait_for_notifier(&mut pub_sub_events, SubscriptionType::Blocks).await;
+
+        let response = reqwest::get(url).await?.error_for_status()?;
+        let content_type = response.headers().get(reqwest::header::CONTENT_TYPE);
+        assert_eq!(content_type.unwrap(), "text/event-stream");
+        let mut reader = SseReader::new(response);
+        let event = reader.next_event().await?;
+        assert_eq!(event.name, "syncing");
+        assert_eq!(event.data, "false");
+
+        let (new_miniblock, _) = store_miniblock(&mut pool.access_storage().await?).await?;
+        let event = reader.next_event().await?;
+        assert_eq!(event.name, "newHeads");
+        assert_eq!(event.id.as_deref(), Some("1"));
+        let header: BlockHeader = serde_json::from_str(&event.data)?;
+        assert_eq!(header.hash, Some(new_miniblock.hash));
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn new_heads_events() {
+    test_sse_endpoint(NewHeadsEventsTest).await;
+}
+
+#[derive(Debug)]
+struct ResumedEventStreamTest;
+
+#[async_trait]
+impl SseTest for ResumedEventStreamTest {
+    async fn test(
+        &self,
+        url: &str,
+        pool: &ConnectionPool,
+        _pub_sub_events: mpsc::UnboundedReceiver<PubSubEvent>,
+    ) -> anyhow::Result<()> {
+        let (new_miniblock, _) = store_miniblock(&mut pool.access_storage().await?).await?;
+
+        let client = reqwest::Client::new();
+        let response = client
+            .get(url)
+            .header("Last-Event-ID", "0")
+            .send()
+            .await?
+            .error_for_status()?;
+        let mut reader = SseReader::new(response);
+        let event = reader.next_event().await?;
+        assert_eq!(event.name, "syncing");
+        let event = reader.next_event().await?;
+        assert_eq!(event.name, "newHeads");
+        assert_eq!(event.id.as_deref(), Some("1"));
+        let header: BlockHeader = serde_json::from_str(&event.data)?;
+        assert_eq!(header.hash, Some(new_miniblock.hash));
+
+        let response = client
+            .get(url)
+            .header("Last-Event-ID", "not a number")
+            .send()
+            .await?;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn resumed_event_stream() {
+    test_sse_endpoint(ResumedEventStreamTest).await;
+}
@@ -0,0 +1,125 @@
+//! Tests for the `txpool` Web3 namespace.
+
+use zksync_types::{l2::L2Tx, transaction_request::PaymasterParams, Nonce};
+use zksync_web3_decl::namespaces::TxpoolNamespaceClient;
+
+use super::*;
+
+fn create_account_transaction(initiator: Address, nonce: u32) -> L2Tx {
+    let mut tx = create_l2_transaction(10, 100);
+    tx.common_data.initiator_address = initiator;
+    tx.common_data.nonce = Nonce(nonce);
+    tx
+}
+
+#[derive(Debug)]
+struct TxpoolBasicsTest;
+
+#[async_trait]
+impl HttpTest for TxpoolBasicsTest {
+    async fn test(&self, client: &HttpClient, pool: &ConnectionPool) -> anyhow::Result<()> {
+        let first_account = Address::repeat_byte(1);
+        let second_account = Address::repeat_byte(2);
+        let paymaster = Address::repeat_byte(0xff);
+        let mut transactions = vec![
+            create_account_transaction(first_account, 0),
+            create_account_transaction(first_account, 1),
+            create_account_transaction(first_account, 3),
+            create_account_transaction(second_account, 2),
+        ];
+        transactions[2].common_data.paymaster_params = PaymasterParams {
+            paymaster,
+            paymaster_input: vec![1, 2, 3],
+        };
+
+        let mut storage = pool.access_storage().await?;
+        for tx in &transactions {
+            let result = storage
+                .transactions_dal()
+                .insert_transaction_l2(tx.clone(), TransactionExecutionMetrics::default())
+                .await;
+            assert_matches!(result, L2TxSubmissionResult::Added);
+        }
+        drop(storage);
+
+        let status = client.status().await?;
+        assert_eq!(status.pending, 2.into());
+        assert_eq!(status.queued, 2.into());
+        assert_eq!(status.priority, 0.into());
+
+        let content = client.content().await?;
+        let pending_nonces: Vec<_> = content.pending[&first_account].keys().copied().collect();
+        assert_eq!(pending_nonces, [0, 1]);
+        assert!(!content.pending.contains_key(&second_account));
+        let queued_tx = &content.queued[&first_account][&3];
+        assert_eq!(queued_tx.hash, transactions[2].hash());
+        assert_eq!(queued_tx.paymaster, Some(paymaster));
+        assert_eq!(queued_tx.paymaster_input, Some(vec![1, 2, 3].into()));
+        assert_eq!(content.pending[&first_account][&0].paymaster, None);
+        let queued_tx = &content.queued[&second_account][&2];
+        assert_eq!(queued_tx.hash, transactions[3].hash());
+        assert!(content.priority.is_empty());
+
+        let inspect = client.inspect().await?;
+        let summary = &inspect.queued[&first_account][&3];
+        assert!(
+            summary.ends_with(&format!("via paymaster {paymaster:?}")),
+            "{summary}"
+        );
+        assert_eq!(inspect.pending[&first_account].len(), 2);
+
+        let usage = client.account_usage(first_account).await?;
+        assert_eq!(usage.sent, 3.into());
+        assert_eq!(usage.sponsored, 0.into());
+        assert_eq!(usage.sent_limit, None);
+        let usage = client.account_usage(paymaster).await?;
+        assert_eq!(usage.sent, 0.into());
+        assert_eq!(usage.sponsored, 1.into());
+        assert_eq!(usage.sponsored_limit, None);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn txpool_basics() {
+    test_http_server(TxpoolBasicsTest).await;
+}
+
+#[derive(Debug)]
+struct TxpoolAccountUsageLimitsTest;
+
+#[async_trait]
+impl HttpTest for TxpoolAccountUsageLimitsTest {
+    fn web3_config(&self) -> Web3JsonRpcConfig {
+        Web3JsonRpcConfig {
+            max_pending_txs_per_account: Some(16),
+            max_pending_txs_per_paymaster: Some(256),
+            ..Web3JsonRpcConfig::for_tests()
+        }
+    }
+
+    async fn test(&self, client: &HttpClient, pool: &ConnectionPool) -> anyhow::Result<()> {
+        let account = Address::repeat_byte(1);
+        let mut storage = pool.access_storage().await?;
+        storage
+            .transactions_dal()
+            .insert_transaction_l2(
+                create_account_transaction(account, 0),
+                TransactionExecutionMetrics::default(),
+            )
+            .await;
+        drop(storage);
+
+        let usage = client.account_usage(account).await?;
+        assert_eq!(usage.sent, 1.into());
+        assert_eq!(usage.sent_limit, Some(16.into()));
+        assert_eq!(usage.sponsored, 0.into());
+        assert_eq!(usage.sponsored_limit, Some(256.into()));
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn txpool_account_usage_with_limits() {
+    test_http_server(TxpoolAccountUsageLimitsTest).await;
+}
@@ -6,7 +6,10 @@ use reqwest::StatusCode;
 use tokio::sync::watch;
 use zksync_config::configs::chain::NetworkConfig;
 use zksync_dal::ConnectionPool;
-use zksync_types::{api, Address, L1BatchNumber, H256, U64};
+use zksync_types::{
+    aggregated_operations::AggregatedActionType, api, Address, L1BatchNumber, MiniblockNumber,
+    Transaction, H256, U64,
+};
 use zksync_web3_decl::{
     jsonrpsee::{
         core::client::{Subscription, SubscriptionClientT},
@@ -14,7 +17,7 @@ use zksync_web3_decl::{
         ws_client::{WsClient, WsClientBuilder},
     },
     namespaces::{EthNamespaceClient, ZksNamespaceClient},
-    types::{BlockHeader, PubSubFilter},
+    types::{BlockHeader, L1BatchStage, L1BatchStatusEvent, LogCursor, PubSubFilter},
 };
 
 use super::*;
@@ -44,7 +47,7 @@ async fn wait_for_subscription(
 }
 
 #[allow(clippy::needless_pass_by_ref_mut)] // false positive
-async fn wait_for_notifier(
+pub(super) async fn wait_for_notifier(
     events: &mut mpsc::UnboundedReceiver<PubSubEvent>,
     sub_type: SubscriptionType,
 ) {
@@ -73,6 +76,11 @@ trait WsTest {
         pub_sub_events: mpsc::UnboundedReceiver<PubSubEvent>,
     ) -> anyhow::Result<()>;
 
+    /// Returns the config for the tested server. The default implementation returns the config for tests.
+    fn web3_config(&self) -> Web3JsonRpcConfig {
+        Web3JsonRpcConfig::for_tests()
+    }
+
     fn websocket_requests_per_minute_limit(&self) -> Option<NonZeroU32> {
         None
     }
@@ -96,6 +104,7 @@ async fn test_ws_server(test: impl WsTest) {
     let (stop_sender, stop_receiver) = watch::channel(false);
     let (server_handles, pub_sub_events) = spawn_ws_server(
         &network_config,
+        test.web3_config(),
         pool.clone(),
         stop_receiver,
         test.websocket_requests_per_minute_limit(),
@@ -197,6 +206,222 @@ async fn basic_subscriptions() {
     test_ws_server(BasicSubscriptionsTest).await;
 }
 
+#[derive(Debug)]
+struct FullPendingTransactionsSubscriptionTest;
+
+#[async_trait]
+impl WsTest for FullPendingTransactionsSubscriptionTest {
+    async fn test(
+        &self,
+        client: &WsClient,
+        pool: &ConnectionPool,
+        mut pub_sub_events: mpsc::UnboundedReceiver<PubSubEvent>,
+    ) -> anyhow::Result<()> {
+        wait_for_notifier(&mut pub_sub_events, SubscriptionType::FullTxs).await;
+
+        let params = rpc_params!["newPendingTransactions", true];
+        let mut full_txs_subscription = client
+            .subscribe::<api::Transaction, _>("eth_subscribe", params, "eth_unsubscribe")
+            .await?;
+        wait_for_subscription(&mut pub_sub_events, SubscriptionType::FullTxs).await;
+        let params = rpc_params!["newPendingTransactions", false];
+        let mut txs_subscription = client
+            .subscribe::<H256, _>("eth_subscribe", params, "eth_unsubscribe")
+            .await?;
+        wait_for_subscription(&mut pub_sub_events, SubscriptionType::Txs).await;
+
+        let (_, new_tx_hash) = store_miniblock(&mut pool.access_storage().await?).await?;
+
+        let received_tx = tokio::time::timeout(TEST_TIMEOUT, full_txs_subscription.next())
+            .await
+            .context("Timed out waiting for new tx")?
+            .context("Full pending txs subscription terminated")??;
+        assert_eq!(received_tx.hash, new_tx_hash);
+        let received_tx_hash = tokio::time::timeout(TEST_TIMEOUT, txs_subscription.next())
+            .await
+            .context("Timed out waiting for new tx hash")?
+            .context("Pending txs subscription terminated")??;
+        assert_eq!(received_tx_hash, new_tx_hash);
+
+        let params = rpc_params!["logs", true];
+        let err = client
+            .subscribe::<api::Log, _>("eth_subscribe", params, "eth_unsubscribe")
+            .await
+            .unwrap_err();
+        assert_matches!(err, ClientError::Call(err) if err.code() == ErrorCode::InvalidParams.code());
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn full_pending_transactions_subscription() {
+    test_ws_server(FullPendingTransactionsSubscriptionTest).await;
+}
+
+#[derive(Debug)]
+struct L1BatchStatusSubscriptionTest;
+
+#[async_trait]
+impl WsTest for L1BatchStatusSubscriptionTest {
+    async fn test(
+        &self,
+        client: &WsClient,
+        pool: &ConnectionPool,
+        mut pub_sub_events: mpsc::UnboundedReceiver<PubSubEvent>,
+    ) -> anyhow::Result<()> {
+        wait_for_notifier(&mut pub_sub_events, SubscriptionType::L1Batches).await;
+
+        let params = rpc_params!["l1BatchStatus"];
+        let mut subscription = client
+            .subscribe::<L1BatchStatusEvent, _>("eth_subscribe", params, "eth_unsubscribe")
+            .await?;
+        wait_for_subscription(&mut pub_sub_events, SubscriptionType::L1Batches).await;
+
+        let mut storage = pool.access_storage().await?;
+        seal_l1_batch(&mut storage, L1BatchNumber(1)).await?;
+        storage
+            .eth_sender_dal()
+            .insert_bogus_confirmed_eth_tx(
+                L1BatchNumber(1),
+                AggregatedActionType::Commit,
+                H256::repeat_byte(1),
+                chrono::Utc::now(),
+            )
+            .await?;
+        drop(storage);
+
+        let mut received_events = vec![];
+        while received_events.len() < 2 {
+            let event = tokio::time::timeout(TEST_TIMEOUT, subscription.next())
+                .await
+                .context("Timed out waiting for L1 batch status event")?
+                .context("L1 batch status subscription terminated")??;
+            received_events.push(event);
+        }
+        assert_eq!(
+            received_events,
+            [
+                L1BatchStatusEvent {
+                    l1_batch_number: L1BatchNumber(1),
+                    stage: L1BatchStage::Sealed,
+                },
+                L1BatchStatusEvent {
+                    l1_batch_number: L1BatchNumber(1),
+                    stage: L1BatchStage::Committed,
+                },
+            ]
+        );
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn l1_batch_status_subscription() {
+    test_ws_server(L1BatchStatusSubscriptionTest).await;
+}
+
+#[derive(Debug)]
+struct SubscriptionsPerConnectionLimitTest;
+
+#[async_trait]
+impl WsTest for SubscriptionsPerConnectionLimitTest {
+    fn web3_config(&self) -> Web3JsonRpcConfig {
+        Web3JsonRpcConfig {
+            max_subscriptions_per_connection: Some(2),
+            ..Web3JsonRpcConfig::for_tests()
+        }
+    }
+
+    async fn test(
+        &self,
+        client: &WsClient,
+        _pool: &ConnectionPool,
+        mut pub_sub_events: mpsc::UnboundedReceiver<PubSubEvent>,
+    ) -> anyhow::Result<()> {
+        let mut subscriptions = Vec::with_capacity(2);
+        for _ in 0..2 {
+            let params = rpc_params!["newHeads"];
+            let subscription = client
+                .subscribe::<BlockHeader, _>("eth_subscribe", params, "eth_unsubscribe")
+                .await?;
+            wait_for_subscription(&mut pub_sub_events, SubscriptionType::Blocks).await;
+            subscriptions.push(subscription);
+        }
+
+        let params = rpc_params!["newHeads"];
+        let err = client
+            .subscribe::<BlockHeader, _>("eth_subscribe", params, "eth_unsubscribe")
+            .await
+            .unwrap_err();
+        assert_matches!(err, ClientError::Call(_));
+        drop(subscriptions);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn subscriptions_per_connection_limit() {
+    test_ws_server(SubscriptionsPerConnectionLimitTest).await;
+}
+
+#[derive(Debug)]
+struct RawBlockTransactionsSubscriptionTest;
+
+#[async_trait]
+impl WsTest for RawBlockTransactionsSubscriptionTest {
+    async fn test(
+        &self,
+        client: &WsClient,
+        pool: &ConnectionPool,
+        _pub_sub_events: mpsc::UnboundedReceiver<PubSubEvent>,
+    ) -> anyhow::Result<()> {
+        let mut storage = pool.access_storage().await?;
+        let tx_hashes = store_miniblock_with_transactions(&mut storage, 5).await?;
+        drop(storage);
+
+        let params = rpc_params![MiniblockNumber(1), 2];
+        let mut subscription = client
+            .subscribe::<Vec<Transaction>, _>(
+                "zks_subscribeRawBlockTransactions",
+                params,
+                "zks_unsubscribeRawBlockTransactions",
+            )
+            .await?;
+        let mut chunk_sizes = vec![];
+        let mut received_hashes = vec![];
+        loop {
+            let chunk = tokio::time::timeout(TEST_TIMEOUT, subscription.next())
+                .await
+                .context("Timed out waiting for transactions chunk")?
+                .context("Raw transactions subscription terminated")??;
+            chunk_sizes.push(chunk.len());
+            received_hashes.extend(chunk.iter().map(Transaction::hash));
+            if chunk.len() < 2 {
+                break;
+            }
+        }
+        assert_eq!(chunk_sizes, [2, 2, 1]);
+        assert_eq!(received_hashes, tx_hashes);
+
+        let params = rpc_params![MiniblockNumber(1), 0];
+        let err = client
+            .subscribe::<Vec<Transaction>, _>(
+                "zks_subscribeRawBlockTransactions",
+                params,
+                "zks_unsubscribeRawBlockTransactions",
+            )
+            .await
+            .unwrap_err();
+        assert_matches!(err, ClientError::Call(err) if err.code() == ErrorCode::InvalidParams.code());
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn raw_block_transactions_subscription() {
+    test_ws_server(RawBlockTransactionsSubscriptionTest).await;
+}
+
 #[derive(Debug)]
 struct LogSubscriptionsTest;
 
@@ -223,6 +448,7 @@ impl Subscriptions {
         let address_filter = PubSubFilter {
             address: Some(Address::repeat_byte(23).into()),
             topics: None,
+            cursor: None,
         };
         let params = rpc_params!["logs", address_filter];
         let address_subscription = client
@@ -231,6 +457,7 @@ impl Subscriptions {
         let topic_filter = PubSubFilter {
             address: None,
             topics: Some(vec![Some(H256::repeat_byte(42).into())]),
+            cursor: None,
         };
         let params = rpc_params!["logs", topic_filter];
         let topic_subscription = client
@@ -440,6 +667,7 @@ impl WsTest for LogSubscriptionsWithDelayTest {
         let address_and_topic_filter = PubSubFilter {
             address: Some(Address::repeat_byte(23).into()),
             topics: Some(vec![Some(H256::repeat_byte(42).into())]),
+            cursor: None,
         };
         let params = rpc_params!["logs", address_and_topic_filter];
         let mut address_and_topic_subscription = client
@@ -476,6 +704,61 @@ async fn log_subscriptions_with_delay() {
     test_ws_server(LogSubscriptionsWithDelayTest).await;
 }
 
+#[derive(Debug)]
+struct ResumedLogSubscriptionTest;
+
+#[async_trait]
+impl WsTest for ResumedLogSubscriptionTest {
+    async fn test(
+        &self,
+        client: &WsClient,
+        pool: &ConnectionPool,
+        mut pub_sub_events: mpsc::UnboundedReceiver<PubSubEvent>,
+    ) -> anyhow::Result<()> {
+        wait_for_notifier(&mut pub_sub_events, SubscriptionType::Logs).await;
+        let mut storage = pool.access_storage().await?;
+        let (_, events) = store_events(&mut storage, 1, 0).await?;
+        drop(storage);
+        let events: Vec<_> = events.iter().collect();
+
+        // Emulate a client that has received the first 2 logs before reconnecting.
+        let filter = PubSubFilter {
+            cursor: Some(LogCursor {
+                block_number: 1.into(),
+                log_index: 1.into(),
+            }),
+            ..PubSubFilter::default()
+        };
+        let params = rpc_params!["logs", filter];
+        let mut subscription = client
+            .subscribe::<api::Log, _>("eth_subscribe", params, "eth_unsubscribe")
+            .await?;
+        wait_for_subscription(&mut pub_sub_events, SubscriptionType::Logs).await;
+
+        let backfilled_logs = collect_logs(&mut subscription, 2).await?;
+        assert_logs_match(&backfilled_logs, &events[2..]);
+
+        let mut storage = pool.access_storage().await?;
+        let (_, new_events) = store_events(&mut storage, 2, 4).await?;
+        drop(storage);
+        let new_events: Vec<_> = new_events.iter().collect();
+        let new_logs = collect_logs(&mut subscription, 4).await?;
+        assert_logs_match(&new_logs, &new_events);
+
+        // Check that logs before the cursor or already backfilled logs are not sent again.
+        wait_for_notifier(&mut pub_sub_events, SubscriptionType::Logs).await;
+        tokio::time::timeout(POLL_INTERVAL, subscription.next())
+            .await
+            .unwrap_err();
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn resumed_log_subscription() {
+    test_ws_server(ResumedLogSubscriptionTest).await;
+}
+
 #[derive(Debug)]
 struct RateLimitingTest;
 
@@ -556,3 +839,40 @@ impl WsTest for BatchGetsRateLimitedTest {
 async fn batch_rate_limiting() {
     test_ws_server(BatchGetsRateLimitedTest).await;
 }
+
+#[derive(Debug)]
+struct AuthenticatedNamespacesAreDisabledTest;
+
+#[async_trait]
+impl WsTest for AuthenticatedNamespacesAreDisabledTest {
+    async fn test(
+        &self,
+        client: &WsClient,
+        _pool: &ConnectionPool,
+        _pub_sub_events: mpsc::UnboundedReceiver<PubSubEvent>,
+    ) -> anyhow::Result<()> {
+        client.chain_id().await?;
+        let err = client
+            .request::<serde_json::Value, _>("snapshots_getAllSnapshots", rpc_params![])
+            .await
+            .unwrap_err();
+        assert_matches!(
+            err,
+            ClientError::Call(err) if err.code() == ErrorCode::MethodNotFound.code()
+        );
+        Ok(())
+    }
+
+    fn web3_config(&self) -> Web3JsonRpcConfig {
+        Web3JsonRpcConfig {
+            authenticated_namespaces: Some(vec!["snapshots".to_owned()]),
+            auth_secret: Some("secret".to_owned()),
+            ..Web3JsonRpcConfig::for_tests()
+        }
+    }
+}
+
+#[tokio::test]
+async fn authenticated_namespaces_are_disabled() {
+    test_ws_server(AuthenticatedNamespacesAreDisabledTest).await;
+}
@@ -0,0 +1,83 @@
+//! HTTP middleware identifying clients for the purposes of per-client VM concurrency limits.
+//!
+//! Clients are identified in the same way as by the rate limiter (see [`Client::identify()`]). Requests without
+//! an identifiable client (i.e., ones without a known IP address) are not subject to per-client VM concurrency
+//! limits. The WebSocket server is not affected by this middleware.
+
+use std::{
+    collections::HashSet,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use futures::future::BoxFuture;
+use hyper::{Body, Request, Response};
+use tower::{Layer, Service};
+
+use super::rate_limit::Client;
+use crate::api_server::execution_sandbox::VmClient;
+
+impl From<Client> for Option<VmClient> {
+    fn from(client: Client) -> Self {
+        match client {
+            Client::ApiKey(key) => Some(VmClient::ApiKey(key)),
+            Client::Ip(ip) => ip.map(VmClient::Ip),
+        }
+    }
+}
+
+/// Tower layer executing requests on behalf of the identified [`VmClient`].
+#[derive(Debug, Clone)]
+pub(super) struct VmClientLayer {
+    api_keys: Arc<HashSet<String>>,
+    trusted_proxy_hops: usize,
+}
+
+impl VmClientLayer {
+    pub fn new(api_keys: HashSet<String>, trusted_proxy_hops: usize) -> Self {
+        Self {
+            api_keys: Arc::new(api_keys),
+            trusted_proxy_hops,
+        }
+    }
+}
+
+impl<S> Layer<S> for VmClientLayer {
+    type Service = VmClientService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        VmClientService {
+            inner,
+            api_keys: self.api_keys.clone(),
+            trusted_proxy_hops: self.trusted_proxy_hops,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct VmClientService<S> {
+    inner: S,
+    api_keys: Arc<HashSet<String>>,
+    trusted_proxy_hops: usize,
+}
+
+impl<S> Service<Request<Body>> for VmClientService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        let client = Client::identify(&self.api_keys, self.trusted_proxy_hops, &request).into();
+        // `jsonrpsee` executes HTTP calls within the future returned by the inner service, so the client
+        // is visible to the VM concurrency limiter.
+        Box::pin(VmClient::scope(client, self.inner.call(request)))
+    }
+}
@@ -2,12 +2,18 @@ use std::{fmt, time::Duration};
 
 use anyhow::Context as _;
 use tokio::sync::watch;
+use zksync_commitment_utils::kzg;
 use zksync_contracts::PRE_BOOJUM_COMMIT_FUNCTION;
 use zksync_dal::{ConnectionPool, StorageProcessor};
 use zksync_eth_client::{clients::QueryClient, Error as L1ClientError, EthInterface};
-use zksync_types::{web3::ethabi, L1BatchNumber, H256};
+use zksync_types::{
+    commitment::{L1BatchCommitmentMode, L1BatchWithMetadata},
+    web3::ethabi,
+    L1BatchNumber, H256,
+};
 
 use crate::{
+    da_dispatcher,
     metrics::{CheckerComponent, EN_METRICS},
     utils::wait_for_l1_batch_with_metadata,
 };
@@ -56,8 +62,9 @@ enum L1DataMismatchBehavior {
 #[derive(Debug)]
 struct LocalL1BatchCommitData {
     is_pre_boojum: bool,
-    l1_commit_data: ethabi::Token,
+    l1_batch: L1BatchWithMetadata,
     commit_tx_hash: H256,
+    commitment_mode: L1BatchCommitmentMode,
 }
 
 impl LocalL1BatchCommitData {
@@ -66,6 +73,7 @@ impl LocalL1BatchCommitData {
     async fn new(
         storage: &mut StorageProcessor<'_>,
         batch_number: L1BatchNumber,
+        commitment_mode: L1BatchCommitmentMode,
     ) -> anyhow::Result<Option<Self>> {
         let Some(storage_l1_batch) = storage
             .blocks_dal()
@@ -113,10 +121,58 @@ impl LocalL1BatchCommitData {
 
         Ok(Some(Self {
             is_pre_boojum,
-            l1_commit_data: l1_batch.l1_commit_data(),
+            l1_batch,
             commit_tx_hash,
+            commitment_mode,
         }))
     }
+
+    /// Checks whether commit data published on L1 matches the local data. Pubdata of post-boojum batches may be
+    /// published either in calldata or in EIP-4844 blobs; in the latter case, commit data contains commitments
+    /// to blobs instead of pubdata. In the validium mode, pubdata is not published at all.
+    ///
+    /// If pubdata is posted to an external DA layer, commit data contains DA inclusion data, which is not available
+    /// locally; thus, everything except for the inclusion data is checked.
+    fn is_consistent_with(&self, l1_commit_data: &ethabi::Token) -> bool {
+        if self.is_pre_boojum {
+            return *l1_commit_data == self.l1_batch.l1_commit_data();
+        }
+        let l1_pubdata_commitments = Self::pubdata_commitments(l1_commit_data);
+        if l1_pubdata_commitments.first() == Some(&da_dispatcher::PUBDATA_SOURCE_CUSTOM) {
+            return *l1_commit_data
+                == self
+                    .l1_batch
+                    .l1_commit_data_with_pubdata_commitments(l1_pubdata_commitments.to_vec());
+        }
+        if self.commitment_mode == L1BatchCommitmentMode::Validium {
+            return *l1_commit_data
+                == self
+                    .l1_batch
+                    .l1_commit_data_with_pubdata_commitments(vec![]);
+        }
+        if l1_pubdata_commitments.first() != Some(&kzg::PUBDATA_SOURCE_BLOBS) {
+            return *l1_commit_data == self.l1_batch.l1_commit_data();
+        }
+        let blobs = kzg::pubdata_to_blobs(&self.l1_batch.pubdata());
+        let pubdata_commitments = kzg::encode_pubdata_commitments(&blobs);
+        *l1_commit_data
+            == self
+                .l1_batch
+                .l1_commit_data_with_pubdata_commitments(pubdata_commitments)
+    }
+
+    /// Returns the last field of the commit data, i.e. either pubdata or pubdata commitments. Pubdata published
+    /// in calldata starts with the big-endian number of L2-to-L1 logs, so its first byte is always 0 and cannot be
+    /// confused with the pubdata source of blobs or an external DA layer.
+    fn pubdata_commitments(l1_commit_data: &ethabi::Token) -> &[u8] {
+        let ethabi::Token::Tuple(fields) = l1_commit_data else {
+            return &[];
+        };
+        let Some(ethabi::Token::Bytes(pubdata)) = fields.last() else {
+            return &[];
+        };
+        pubdata
+    }
 }
 
 #[derive(Debug)]
@@ -129,13 +185,19 @@ pub struct ConsistencyChecker {
     l1_client: Box<dyn EthInterface>,
     l1_batch_updater: Box<dyn UpdateCheckedBatch>,
     l1_data_mismatch_behavior: L1DataMismatchBehavior,
+    commitment_mode: L1BatchCommitmentMode,
     pool: ConnectionPool,
 }
 
 impl ConsistencyChecker {
     const DEFAULT_SLEEP_INTERVAL: Duration = Duration::from_secs(5);
 
-    pub fn new(web3_url: &str, max_batches_to_recheck: u32, pool: ConnectionPool) -> Self {
+    pub fn new(
+        web3_url: &str,
+        max_batches_to_recheck: u32,
+        pool: ConnectionPool,
+        commitment_mode: L1BatchCommitmentMode,
+    ) -> Self {
         let web3 = QueryClient::new(web3_url).unwrap();
         Self {
             contract: zksync_contracts::zksync_contract(),
@@ -144,6 +206,7 @@ impl ConsistencyChecker {
             l1_client: Box::new(web3),
             l1_batch_updater: Box::new(()),
             l1_data_mismatch_behavior: L1DataMismatchBehavior::Log,
+            commitment_mode,
             pool,
         }
     }
@@ -187,7 +250,7 @@ impl ConsistencyChecker {
                 .with_context(|| {
                     format!("Failed extracting commit data for transaction {commit_tx_hash:?}")
                 })?;
-        Ok(commitment == local.l1_commit_data)
+        Ok(local.is_consistent_with(&commitment))
     }
 
     fn extract_commit_data(
@@ -285,7 +348,10 @@ impl ConsistencyChecker {
             // The batch might be already committed but not yet processed by the external node's tree
             // OR the batch might be processed by the external node's tree but not yet committed.
             // We need both.
-            let Some(local) = LocalL1BatchCommitData::new(&mut storage, batch_number).await? else {
+            let Some(local) =
+                LocalL1BatchCommitData::new(&mut storage, batch_number, self.commitment_mode)
+                    .await?
+            else {
                 tokio::time::sleep(self.sleep_interval).await;
                 continue;
             };
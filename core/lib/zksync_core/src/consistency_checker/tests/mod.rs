@@ -8,9 +8,11 @@ use tokio::sync::mpsc;
 use zksync_dal::StorageProcessor;
 use zksync_eth_client::clients::MockEthereum;
 use zksync_types::{
-    aggregated_operations::AggregatedActionType, block::BlockGasCount,
-    commitment::L1BatchWithMetadata, web3::contract::Options, L2ChainId, ProtocolVersion,
-    ProtocolVersionId, H256,
+    aggregated_operations::AggregatedActionType,
+    block::BlockGasCount,
+    commitment::{L1BatchCommitmentMode, L1BatchWithMetadata},
+    web3::contract::Options,
+    L2ChainId, ProtocolVersion, ProtocolVersionId, H256,
 };
 
 use super::*;
@@ -65,6 +67,7 @@ fn create_mock_checker(client: MockEthereum, pool: ConnectionPool) -> Consistenc
         l1_client: Box::new(client),
         l1_batch_updater: Box::new(()),
         l1_data_mismatch_behavior: L1DataMismatchBehavior::Bail,
+        commitment_mode: L1BatchCommitmentMode::Rollup,
         pool,
     }
 }
@@ -97,6 +100,68 @@ fn build_commit_tx_input_data_is_correct() {
     }
 }
 
+#[test]
+fn checking_commit_data_with_blob_commitments() {
+    let l1_batch = create_l1_batch_with_metadata(1);
+    let blobs = kzg::pubdata_to_blobs(&l1_batch.pubdata());
+    let pubdata_commitments = kzg::encode_pubdata_commitments(&blobs);
+    let blob_commit_data = l1_batch.l1_commit_data_with_pubdata_commitments(pubdata_commitments);
+    let calldata_commit_data = l1_batch.l1_commit_data();
+
+    let local = LocalL1BatchCommitData {
+        is_pre_boojum: false,
+        l1_batch,
+        commit_tx_hash: H256::zero(),
+        commitment_mode: L1BatchCommitmentMode::Rollup,
+    };
+    assert!(local.is_consistent_with(&calldata_commit_data));
+    assert!(local.is_consistent_with(&blob_commit_data));
+
+    // Commitments to different pubdata.
+    let blobs = kzg::pubdata_to_blobs(&[1, 2, 3]);
+    let other_commit_data = local
+        .l1_batch
+        .l1_commit_data_with_pubdata_commitments(kzg::encode_pubdata_commitments(&blobs));
+    assert!(!local.is_consistent_with(&other_commit_data));
+}
+
+#[test]
+fn checking_commit_data_in_validium_mode() {
+    let l1_batch = create_l1_batch_with_metadata(1);
+    let validium_commit_data = l1_batch.l1_commit_data_with_pubdata_commitments(vec![]);
+    let calldata_commit_data = l1_batch.l1_commit_data();
+
+    let local = LocalL1BatchCommitData {
+        is_pre_boojum: false,
+        l1_batch,
+        commit_tx_hash: H256::zero(),
+        commitment_mode: L1BatchCommitmentMode::Validium,
+    };
+    assert!(local.is_consistent_with(&validium_commit_data));
+    assert!(!local.is_consistent_with(&calldata_commit_data));
+}
+
+#[test]
+fn checking_commit_data_with_da_inclusion_data() {
+    let l1_batch = create_l1_batch_with_metadata(1);
+    let mut pubdata_commitments = vec![da_dispatcher::PUBDATA_SOURCE_CUSTOM];
+    pubdata_commitments.extend([1; 32]);
+    let commit_data = l1_batch.l1_commit_data_with_pubdata_commitments(pubdata_commitments.clone());
+    let mut other_l1_batch = create_l1_batch_with_metadata(1);
+    other_l1_batch.metadata.root_hash = H256::repeat_byte(0xff);
+    let bogus_commit_data =
+        other_l1_batch.l1_commit_data_with_pubdata_commitments(pubdata_commitments);
+
+    let local = LocalL1BatchCommitData {
+        is_pre_boojum: false,
+        l1_batch,
+        commit_tx_hash: H256::zero(),
+        commitment_mode: L1BatchCommitmentMode::Validium,
+    };
+    assert!(local.is_consistent_with(&commit_data));
+    assert!(!local.is_consistent_with(&bogus_commit_data));
+}
+
 #[test]
 fn extracting_commit_data_for_boojum_batch() {
     let contract = zksync_contracts::zksync_contract();
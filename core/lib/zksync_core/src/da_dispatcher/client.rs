@@ -0,0 +1,54 @@
+//! Abstraction over data availability layers.
+
+use std::fmt;
+
+use async_trait::async_trait;
+use zksync_types::L1BatchNumber;
+
+/// Response of the data availability layer to posting a blob.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DispatchResponse {
+    /// ID of the posted blob, which can be used to query its inclusion data.
+    pub blob_id: String,
+}
+
+/// Data proving that a blob is included into the data availability layer. It is passed to the L1 contracts
+/// in commit transactions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InclusionData {
+    pub data: Vec<u8>,
+}
+
+/// Errors returned by [`DataAvailabilityClient`] methods.
+#[derive(Debug, thiserror::Error)]
+pub enum DAError {
+    /// Error that may be resolved by retrying the call (e.g., a network error).
+    #[error("transient DA layer error: {0:#}")]
+    Transient(anyhow::Error),
+    /// Error that won't be resolved by retrying the call.
+    #[error("DA layer error: {0:#}")]
+    Fatal(anyhow::Error),
+}
+
+impl DAError {
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::Transient(_))
+    }
+}
+
+/// Client of a data availability (DA) layer used to publish pubdata of L1 batches.
+#[async_trait]
+pub trait DataAvailabilityClient: 'static + fmt::Debug + Send + Sync {
+    /// Posts pubdata of the specified L1 batch to the DA layer.
+    async fn dispatch_blob(
+        &self,
+        l1_batch_number: L1BatchNumber,
+        data: Vec<u8>,
+    ) -> Result<DispatchResponse, DAError>;
+
+    /// Returns inclusion data for the blob with the specified ID, or `None` if the blob isn't included yet.
+    async fn get_inclusion_data(&self, blob_id: &str) -> Result<Option<InclusionData>, DAError>;
+
+    /// Returns the maximum size of a blob (in bytes) supported by the DA layer, or `None` if there's no limit.
+    fn blob_size_limit(&self) -> Option<usize>;
+}
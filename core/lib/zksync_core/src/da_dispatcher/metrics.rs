@@ -0,0 +1,28 @@
+//! Metrics for the data availability dispatcher.
+
+use std::time::Duration;
+
+use vise::{Buckets, Counter, Gauge, Histogram, Metrics};
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "server_da_dispatcher")]
+pub(super) struct DataAvailabilityDispatcherMetrics {
+    /// Latency of posting a blob to the DA layer.
+    #[metrics(buckets = Buckets::LATENCIES)]
+    pub blob_dispatch_latency: Histogram<Duration>,
+    /// Latency between posting a blob and receiving its inclusion data.
+    #[metrics(buckets = Buckets::LATENCIES)]
+    pub inclusion_latency: Histogram<Duration>,
+    /// Size of posted blobs in bytes.
+    #[metrics(buckets = Buckets::exponential(1_024.0..=16_777_216.0, 4.0))]
+    pub blob_size: Histogram<usize>,
+    /// Number of retried calls to the DA layer.
+    pub call_retries: Counter,
+    /// Number of the last L1 batch posted to the DA layer.
+    pub last_dispatched_l1_batch: Gauge<u64>,
+    /// Number of the last L1 batch with received inclusion data.
+    pub last_included_l1_batch: Gauge<u64>,
+}
+
+#[vise::register]
+pub(super) static METRICS: vise::Global<DataAvailabilityDispatcherMetrics> = vise::Global::new();
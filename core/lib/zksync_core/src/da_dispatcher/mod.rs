@@ -0,0 +1,205 @@
+//! Data availability dispatcher posting pubdata of L1 batches to an external data availability (DA) layer.
+//!
+//! The dispatcher posts pubdata of sealed L1 batches to the DA layer and then polls the layer for inclusion
+//! data of posted blobs. Inclusion data is persisted in Postgres; if pubdata is configured to be sent
+//! to a DA layer, `EthTxAggregator` only commits L1 batches with known inclusion data and passes this data
+//! to the L1 contracts.
+
+use std::{future::Future, time::Duration};
+
+use anyhow::Context as _;
+use chrono::Utc;
+use tokio::sync::watch;
+use zksync_config::configs::DADispatcherConfig;
+use zksync_dal::ConnectionPool;
+
+use self::metrics::METRICS;
+pub use self::{
+    client::{DAError, DataAvailabilityClient, DispatchResponse, InclusionData},
+    object_store::ObjectStoreDAClient,
+};
+
+mod client;
+mod metrics;
+mod object_store;
+#[cfg(test)]
+mod tests;
+
+/// Prefix of the pubdata commitments in the commit calldata signalling that pubdata is posted to an external
+/// DA layer. The prefix is followed by the inclusion data received from the layer.
+pub const PUBDATA_SOURCE_CUSTOM: u8 = 2;
+/// Maximum backoff between retried calls to the DA layer.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Component posting pubdata of L1 batches to a DA layer and collecting inclusion data for it.
+#[derive(Debug)]
+pub struct DataAvailabilityDispatcher {
+    client: Box<dyn DataAvailabilityClient>,
+    pool: ConnectionPool,
+    config: DADispatcherConfig,
+}
+
+impl DataAvailabilityDispatcher {
+    pub fn new(
+        pool: ConnectionPool,
+        config: DADispatcherConfig,
+        client: Box<dyn DataAvailabilityClient>,
+    ) -> Self {
+        Self {
+            client,
+            pool,
+            config,
+        }
+    }
+
+    pub async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        loop {
+            if *stop_receiver.borrow() {
+                break;
+            }
+
+            self.dispatch().await.context("dispatch()")?;
+            self.poll_for_inclusion()
+                .await
+                .context("poll_for_inclusion()")?;
+
+            // Exit the loop early if a stop signal is received.
+            if tokio::time::timeout(self.config.polling_interval(), stop_receiver.changed())
+                .await
+                .is_ok()
+            {
+                break;
+            }
+        }
+        tracing::info!("Stop signal received, DA dispatcher is shutting down");
+        Ok(())
+    }
+
+    /// Posts pubdata of L1 batches not yet posted to the DA layer.
+    async fn dispatch(&self) -> anyhow::Result<()> {
+        let mut storage = self.pool.access_storage_tagged("da_dispatcher").await?;
+        let batches = storage
+            .data_availability_dal()
+            .get_ready_for_da_dispatch_l1_batches(self.config.max_rows_to_dispatch() as usize)
+            .await?;
+        drop(storage);
+
+        for batch in batches {
+            let l1_batch_number = batch.l1_batch_number;
+            let pubdata_len = batch.pubdata.len();
+            if let Some(limit) = self.client.blob_size_limit() {
+                anyhow::ensure!(
+                    pubdata_len <= limit,
+                    "Pubdata of L1 batch #{l1_batch_number} ({pubdata_len} bytes) exceeds the blob size limit \
+                     of the DA layer ({limit} bytes)"
+                );
+            }
+
+            let latency = METRICS.blob_dispatch_latency.start();
+            let response = self
+                .retry("dispatch_blob", || {
+                    self.client
+                        .dispatch_blob(l1_batch_number, batch.pubdata.clone())
+                })
+                .await
+                .with_context(|| {
+                    format!("failed posting L1 batch #{l1_batch_number} to DA layer")
+                })?;
+            let latency = latency.observe();
+            let sent_at = Utc::now().naive_utc();
+
+            let mut storage = self.pool.access_storage_tagged("da_dispatcher").await?;
+            storage
+                .data_availability_dal()
+                .insert_l1_batch_da(l1_batch_number, &response.blob_id, sent_at)
+                .await?;
+            drop(storage);
+
+            METRICS.blob_size.observe(pubdata_len);
+            METRICS
+                .last_dispatched_l1_batch
+                .set(l1_batch_number.0.into());
+            tracing::info!(
+                "Posted pubdata of L1 batch #{l1_batch_number} ({pubdata_len} bytes) to DA layer in {latency:?}; \
+                 blob ID: {}",
+                response.blob_id
+            );
+        }
+        Ok(())
+    }
+
+    /// Polls the DA layer for inclusion data of the earliest blob awaiting inclusion. Blobs are processed
+    /// one at a time since L1 batches are committed sequentially anyway.
+    async fn poll_for_inclusion(&self) -> anyhow::Result<()> {
+        let mut storage = self.pool.access_storage_tagged("da_dispatcher").await?;
+        let Some(blob) = storage
+            .data_availability_dal()
+            .get_first_da_blob_awaiting_inclusion()
+            .await?
+        else {
+            return Ok(());
+        };
+        drop(storage);
+
+        let inclusion_data = self
+            .retry("get_inclusion_data", || {
+                self.client.get_inclusion_data(&blob.blob_id)
+            })
+            .await
+            .with_context(|| {
+                format!(
+                    "failed getting inclusion data for blob {} (L1 batch #{})",
+                    blob.blob_id, blob.l1_batch_number
+                )
+            })?;
+        let Some(inclusion_data) = inclusion_data else {
+            return Ok(());
+        };
+
+        let mut storage = self.pool.access_storage_tagged("da_dispatcher").await?;
+        storage
+            .data_availability_dal()
+            .save_l1_batch_inclusion_data(blob.l1_batch_number, &inclusion_data.data)
+            .await?;
+        drop(storage);
+
+        let inclusion_latency = (Utc::now().naive_utc() - blob.sent_at)
+            .to_std()
+            .unwrap_or_default();
+        METRICS.inclusion_latency.observe(inclusion_latency);
+        METRICS
+            .last_included_l1_batch
+            .set(blob.l1_batch_number.0.into());
+        tracing::info!(
+            "Received inclusion data for L1 batch #{} in {inclusion_latency:?}",
+            blob.l1_batch_number
+        );
+        Ok(())
+    }
+
+    /// Retries a call to the DA layer with exponential backoff if it fails with a transient error.
+    async fn retry<T, Fut>(&self, action: &str, mut call: impl FnMut() -> Fut) -> anyhow::Result<T>
+    where
+        Fut: Future<Output = Result<T, DAError>>,
+    {
+        let mut backoff = self.config.polling_interval();
+        let mut retries = 0;
+        loop {
+            match call().await {
+                Ok(value) => return Ok(value),
+                Err(err) if err.is_transient() && retries < self.config.max_retries() => {
+                    retries += 1;
+                    METRICS.call_retries.inc();
+                    tracing::warn!(
+                        "Call `{action}` to DA layer failed: {err}; retrying in {backoff:?} \
+                         (retry {retries}/{})",
+                        self.config.max_retries()
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff = backoff.saturating_mul(2).min(MAX_BACKOFF);
+                }
+                Err(err) => return Err(anyhow::Error::new(err).context(action.to_owned())),
+            }
+        }
+    }
+}
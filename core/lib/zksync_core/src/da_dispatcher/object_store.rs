@@ -0,0 +1,66 @@
+//! Reference data availability client storing pubdata in an object store.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use zksync_object_store::{Bucket, ObjectStore, ObjectStoreError};
+use zksync_types::{web3::signing::keccak256, L1BatchNumber};
+
+use super::client::{DAError, DataAvailabilityClient, DispatchResponse, InclusionData};
+
+/// Data availability client storing pubdata of L1 batches in an object store. Inclusion data of a blob
+/// is the keccak256 hash of the stored blob.
+///
+/// This client doesn't provide any data availability guarantees beyond the ones of the underlying store;
+/// it's mostly useful for testing and as a reference implementation.
+#[derive(Debug, Clone)]
+pub struct ObjectStoreDAClient {
+    store: Arc<dyn ObjectStore>,
+}
+
+impl ObjectStoreDAClient {
+    pub fn new(store: Arc<dyn ObjectStore>) -> Self {
+        Self { store }
+    }
+
+    fn blob_key(l1_batch_number: L1BatchNumber) -> String {
+        format!("l1_batch_{l1_batch_number}_pubdata.bin")
+    }
+}
+
+fn to_da_error(err: ObjectStoreError) -> DAError {
+    match err {
+        ObjectStoreError::Other(_) => DAError::Transient(err.into()),
+        _ => DAError::Fatal(err.into()),
+    }
+}
+
+#[async_trait]
+impl DataAvailabilityClient for ObjectStoreDAClient {
+    async fn dispatch_blob(
+        &self,
+        l1_batch_number: L1BatchNumber,
+        data: Vec<u8>,
+    ) -> Result<DispatchResponse, DAError> {
+        let blob_id = Self::blob_key(l1_batch_number);
+        self.store
+            .put_raw(Bucket::DataAvailability, &blob_id, data)
+            .await
+            .map_err(to_da_error)?;
+        Ok(DispatchResponse { blob_id })
+    }
+
+    async fn get_inclusion_data(&self, blob_id: &str) -> Result<Option<InclusionData>, DAError> {
+        match self.store.get_raw(Bucket::DataAvailability, blob_id).await {
+            Ok(blob) => Ok(Some(InclusionData {
+                data: keccak256(&blob).to_vec(),
+            })),
+            Err(ObjectStoreError::KeyNotFound(_)) => Ok(None),
+            Err(err) => Err(to_da_error(err)),
+        }
+    }
+
+    fn blob_size_limit(&self) -> Option<usize> {
+        None
+    }
+}
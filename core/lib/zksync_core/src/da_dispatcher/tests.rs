@@ -0,0 +1,203 @@
+//! Tests for the DA dispatcher.
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use zksync_dal::StorageProcessor;
+use zksync_object_store::ObjectStoreFactory;
+use zksync_types::{block::BlockGasCount, web3::signing::keccak256, L1BatchNumber, L2ChainId};
+
+use super::*;
+use crate::{
+    genesis::{ensure_genesis_state, GenesisParams},
+    utils::testonly::create_l1_batch,
+};
+
+async fn insert_l1_batches(
+    storage: &mut StorageProcessor<'_>,
+    numbers: &[u32],
+    pubdata_len: usize,
+) {
+    if storage.blocks_dal().is_genesis_needed().await.unwrap() {
+        ensure_genesis_state(storage, L2ChainId::default(), &GenesisParams::mock())
+            .await
+            .unwrap();
+    }
+    for &number in numbers {
+        let mut header = create_l1_batch(number);
+        header.pubdata_input = Some(vec![number as u8; pubdata_len]);
+        storage
+            .blocks_dal()
+            .insert_l1_batch(&header, &[], BlockGasCount::default(), &[], &[], 0)
+            .await
+            .unwrap();
+    }
+}
+
+/// DA client failing the specified number of calls with the specified error before delegating
+/// to an object store client.
+#[derive(Debug)]
+struct FailingDAClient {
+    inner: ObjectStoreDAClient,
+    failures_left: AtomicUsize,
+    transient: bool,
+}
+
+impl FailingDAClient {
+    async fn new(failures: usize, transient: bool) -> Self {
+        let store = ObjectStoreFactory::mock().create_store().await;
+        Self {
+            inner: ObjectStoreDAClient::new(store),
+            failures_left: AtomicUsize::new(failures),
+            transient,
+        }
+    }
+
+    fn maybe_fail(&self) -> Result<(), DAError> {
+        let should_fail = self
+            .failures_left
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |left| {
+                left.checked_sub(1)
+            })
+            .is_ok();
+        if !should_fail {
+            return Ok(());
+        }
+        let err = anyhow::anyhow!("DA layer is unavailable");
+        Err(if self.transient {
+            DAError::Transient(err)
+        } else {
+            DAError::Fatal(err)
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl DataAvailabilityClient for FailingDAClient {
+    async fn dispatch_blob(
+        &self,
+        l1_batch_number: L1BatchNumber,
+        data: Vec<u8>,
+    ) -> Result<DispatchResponse, DAError> {
+        self.maybe_fail()?;
+        self.inner.dispatch_blob(l1_batch_number, data).await
+    }
+
+    async fn get_inclusion_data(&self, blob_id: &str) -> Result<Option<InclusionData>, DAError> {
+        self.maybe_fail()?;
+        self.inner.get_inclusion_data(blob_id).await
+    }
+
+    fn blob_size_limit(&self) -> Option<usize> {
+        Some(1_000)
+    }
+}
+
+#[tokio::test]
+async fn dispatching_pubdata_and_collecting_inclusion_data() {
+    let pool = ConnectionPool::test_pool().await;
+    let mut storage = pool.access_storage().await.unwrap();
+    insert_l1_batches(&mut storage, &[1, 2], 100).await;
+
+    let store = ObjectStoreFactory::mock().create_store().await;
+    let client = ObjectStoreDAClient::new(Arc::clone(&store));
+    let dispatcher = DataAvailabilityDispatcher::new(
+        pool.clone(),
+        DADispatcherConfig::for_tests(),
+        Box::new(client),
+    );
+    dispatcher.dispatch().await.unwrap();
+    let ready_batches = storage
+        .data_availability_dal()
+        .get_ready_for_da_dispatch_l1_batches(10)
+        .await
+        .unwrap();
+    assert!(ready_batches.is_empty());
+
+    for number in [1, 2] {
+        dispatcher.poll_for_inclusion().await.unwrap();
+        let inclusion_data = storage
+            .data_availability_dal()
+            .get_inclusion_data(L1BatchNumber(number))
+            .await
+            .unwrap()
+            .expect("no inclusion data");
+        assert_eq!(inclusion_data, keccak256(&[number as u8; 100]));
+    }
+    let awaiting_blob = storage
+        .data_availability_dal()
+        .get_first_da_blob_awaiting_inclusion()
+        .await
+        .unwrap();
+    assert!(awaiting_blob.is_none());
+}
+
+#[tokio::test]
+async fn transient_da_errors_are_retried() {
+    let pool = ConnectionPool::test_pool().await;
+    let mut storage = pool.access_storage().await.unwrap();
+    insert_l1_batches(&mut storage, &[1], 100).await;
+
+    let client = FailingDAClient::new(2, true).await;
+    let dispatcher = DataAvailabilityDispatcher::new(
+        pool.clone(),
+        DADispatcherConfig {
+            polling_interval_ms: Some(10),
+            ..DADispatcherConfig::for_tests()
+        },
+        Box::new(client),
+    );
+    dispatcher.dispatch().await.unwrap();
+    dispatcher.poll_for_inclusion().await.unwrap();
+    let inclusion_data = storage
+        .data_availability_dal()
+        .get_inclusion_data(L1BatchNumber(1))
+        .await
+        .unwrap();
+    assert!(inclusion_data.is_some());
+}
+
+#[tokio::test]
+async fn fatal_da_errors_are_not_retried() {
+    let pool = ConnectionPool::test_pool().await;
+    let mut storage = pool.access_storage().await.unwrap();
+    insert_l1_batches(&mut storage, &[1], 100).await;
+
+    let client = FailingDAClient::new(1, false).await;
+    let dispatcher = DataAvailabilityDispatcher::new(
+        pool.clone(),
+        DADispatcherConfig::for_tests(),
+        Box::new(client),
+    );
+    let err = dispatcher.dispatch().await.unwrap_err();
+    assert!(
+        format!("{err:#}").contains("DA layer is unavailable"),
+        "{err:#}"
+    );
+    // The failed batch is dispatched on the next iteration.
+    dispatcher.dispatch().await.unwrap();
+    let ready_batches = storage
+        .data_availability_dal()
+        .get_ready_for_da_dispatch_l1_batches(10)
+        .await
+        .unwrap();
+    assert!(ready_batches.is_empty());
+}
+
+#[tokio::test]
+async fn blobs_exceeding_size_limit_are_rejected() {
+    let pool = ConnectionPool::test_pool().await;
+    let mut storage = pool.access_storage().await.unwrap();
+    insert_l1_batches(&mut storage, &[1], 2_000).await;
+
+    let client = FailingDAClient::new(0, true).await;
+    let dispatcher = DataAvailabilityDispatcher::new(
+        pool.clone(),
+        DADispatcherConfig::for_tests(),
+        Box::new(client),
+    );
+    let err = dispatcher.dispatch().await.unwrap_err();
+    assert!(format!("{err:#}").contains("blob size limit"), "{err:#}");
+}
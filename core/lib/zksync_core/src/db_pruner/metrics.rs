@@ -0,0 +1,47 @@
+//! Metrics for the Postgres data pruner.
+
+use std::time::Duration;
+
+use vise::{Buckets, Counter, EncodeLabelSet, EncodeLabelValue, Family, Gauge, Histogram, Metrics};
+use zksync_dal::pruning_dal::PruningStats;
+
+/// Type of data removed by the pruner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "type", rename_all = "snake_case")]
+pub(super) enum PrunedDataType {
+    Events,
+    L2ToL1Logs,
+    CallTraces,
+    Transactions,
+    StorageLogs,
+}
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "server_db_pruner")]
+pub(super) struct DbPrunerMetrics {
+    /// Latency of pruning a chunk of L1 batches.
+    #[metrics(buckets = Buckets::LATENCIES)]
+    pub pruning_latency: Histogram<Duration>,
+    /// Number of rows deleted by the pruner.
+    pub deleted_rows: Family<PrunedDataType, Counter>,
+    /// Last L1 batch for which data was pruned.
+    pub last_pruned_l1_batch: Gauge<u64>,
+}
+
+impl DbPrunerMetrics {
+    pub fn observe_stats(&self, stats: &PruningStats) {
+        let rows = [
+            (PrunedDataType::Events, stats.deleted_events),
+            (PrunedDataType::L2ToL1Logs, stats.deleted_l2_to_l1_logs),
+            (PrunedDataType::CallTraces, stats.deleted_call_traces),
+            (PrunedDataType::Transactions, stats.deleted_transactions),
+            (PrunedDataType::StorageLogs, stats.deleted_storage_logs),
+        ];
+        for (data_type, count) in rows {
+            self.deleted_rows[&data_type].inc_by(count);
+        }
+    }
+}
+
+#[vise::register]
+pub(super) static METRICS: vise::Global<DbPrunerMetrics> = vise::Global::new();
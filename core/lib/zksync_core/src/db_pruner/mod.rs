@@ -0,0 +1,167 @@
+//! Pruning of historical data in Postgres.
+//!
+//! The pruner removes data for old L1 batches that is not required for node operation: events, L2-to-L1 logs,
+//! call traces, L2 transactions and storage logs overwritten by later logs. Miniblock and L1 batch headers
+//! are retained. Only L1 batches executed on L1 are pruned; additional retention windows (by the number
+//! of L1 batches and by age) are configurable. The API server reports pruned blocks and L1 batches
+//! with dedicated errors.
+
+use anyhow::Context as _;
+use tokio::sync::watch;
+use zksync_config::configs::DbPrunerConfig;
+use zksync_dal::{ConnectionPool, StorageProcessor};
+use zksync_types::L1BatchNumber;
+use zksync_utils::time::seconds_since_epoch;
+
+use self::metrics::METRICS;
+
+mod metrics;
+#[cfg(test)]
+mod tests;
+
+/// Component pruning historical data in Postgres according to the configured retention policies.
+#[derive(Debug)]
+pub struct DbPruner {
+    pool: ConnectionPool,
+    config: DbPrunerConfig,
+}
+
+impl DbPruner {
+    pub fn new(pool: ConnectionPool, config: DbPrunerConfig) -> Self {
+        Self { pool, config }
+    }
+
+    /// Returns the last L1 batch that can be pruned according to the retention policies, or `None`
+    /// if no L1 batches can be pruned.
+    async fn last_prunable_l1_batch(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+    ) -> anyhow::Result<Option<L1BatchNumber>> {
+        // L1 batches not executed on L1 may be reverted, which requires their data.
+        let last_executed_l1_batch = storage
+            .blocks_dal()
+            .get_number_of_last_l1_batch_executed_on_eth()
+            .await
+            .context("failed getting last L1 batch executed on L1")?;
+        let Some(mut limit) = last_executed_l1_batch else {
+            return Ok(None);
+        };
+
+        // The metadata calculator needs storage logs for L1 batches it hasn't processed yet.
+        let last_l1_batch_with_metadata = storage
+            .blocks_dal()
+            .get_last_l1_batch_number_with_metadata()
+            .await
+            .context("failed getting last L1 batch with metadata")?;
+        let Some(last_l1_batch_with_metadata) = last_l1_batch_with_metadata else {
+            return Ok(None);
+        };
+        limit = limit.min(last_l1_batch_with_metadata);
+
+        // The snapshot creator needs storage logs as of the snapshot L1 batch. Pruning this L1 batch itself is fine
+        // since the latest storage log for each key is retained.
+        let newest_snapshot = storage
+            .snapshots_dal()
+            .get_newest_snapshot_metadata()
+            .await
+            .context("failed getting newest snapshot metadata")?;
+        if let Some(snapshot) = newest_snapshot {
+            if !snapshot.is_complete() {
+                limit = limit.min(snapshot.l1_batch_number);
+            }
+        }
+
+        let Some(limit) = limit.0.checked_sub(self.config.data_retention_l1_batches()) else {
+            return Ok(None);
+        };
+        let max_timestamp =
+            seconds_since_epoch().saturating_sub(self.config.data_retention().as_secs());
+        storage
+            .pruning_dal()
+            .get_last_l1_batch_sealed_before(L1BatchNumber(limit), max_timestamp)
+            .await
+            .context("failed getting last L1 batch sealed before retention period")
+    }
+
+    /// Prunes the next chunk of L1 batches. Returns `false` if there is nothing to prune.
+    async fn run_single_iteration(&self) -> anyhow::Result<bool> {
+        let mut storage = self.pool.access_storage_tagged("db_pruner").await?;
+        let Some(last_prunable_l1_batch) = self.last_prunable_l1_batch(&mut storage).await? else {
+            return Ok(false);
+        };
+        let pruning_info = storage
+            .pruning_dal()
+            .get_pruning_info()
+            .await
+            .context("failed getting pruning info")?;
+        let first_l1_batch_to_prune = pruning_info.first_l1_batch();
+        if first_l1_batch_to_prune > last_prunable_l1_batch {
+            return Ok(false);
+        }
+
+        let chunk_size = self.config.pruned_batch_chunk_size().max(1);
+        let last_l1_batch_to_prune =
+            last_prunable_l1_batch.min(first_l1_batch_to_prune + (chunk_size - 1));
+        let (_, last_miniblock_to_prune) = storage
+            .blocks_dal()
+            .get_miniblock_range_of_l1_batch(last_l1_batch_to_prune)
+            .await
+            .context("failed getting miniblock range")?
+            .with_context(|| format!("L1 batch #{last_l1_batch_to_prune} has no miniblocks"))?;
+
+        let latency = METRICS.pruning_latency.start();
+        let stats = storage
+            .pruning_dal()
+            .prune_batches_range(last_l1_batch_to_prune, last_miniblock_to_prune)
+            .await
+            .with_context(|| {
+                format!("failed pruning L1 batches #{first_l1_batch_to_prune}..=#{last_l1_batch_to_prune}")
+            })?;
+        let latency = latency.observe();
+
+        METRICS.observe_stats(&stats);
+        METRICS
+            .last_pruned_l1_batch
+            .set(last_l1_batch_to_prune.0.into());
+        tracing::info!(
+            "Pruned data for L1 batches #{first_l1_batch_to_prune}..=#{last_l1_batch_to_prune} \
+             (up to miniblock #{last_miniblock_to_prune}) in {latency:?}: {stats:?}"
+        );
+        Ok(true)
+    }
+
+    pub async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        tracing::info!(
+            "Starting Postgres data pruning retaining {} latest L1 batches executed on L1 and data for the last {:?}",
+            self.config.data_retention_l1_batches(),
+            self.config.data_retention()
+        );
+        loop {
+            if *stop_receiver.borrow() {
+                break;
+            }
+
+            let pruned = match self.run_single_iteration().await {
+                Ok(pruned) => pruned,
+                Err(err) => {
+                    // Pruning isn't critical for node operation, so we don't stop the node on errors.
+                    tracing::warn!("Error pruning Postgres data: {err:#}");
+                    false
+                }
+            };
+            if pruned {
+                continue; // Catch up with the prunable L1 batches without waiting.
+            }
+
+            // Exit the loop early if a stop signal is received.
+            if tokio::time::timeout(self.config.polling_interval(), stop_receiver.changed())
+                .await
+                .is_ok()
+            {
+                break;
+            }
+        }
+        tracing::info!("Stop signal received, Postgres data pruner is shutting down");
+        Ok(())
+    }
+}
@@ -0,0 +1,159 @@
+//! Tests for the Postgres data pruner.
+
+use zksync_dal::pruning_dal::PruningInfo;
+use zksync_types::{
+    aggregated_operations::AggregatedActionType, block::BlockGasCount, AccountTreeId, Address,
+    L2ChainId, MiniblockNumber, StorageKey, StorageLog, H256,
+};
+
+use super::*;
+use crate::{
+    genesis::{ensure_genesis_state, GenesisParams},
+    utils::testonly::{create_l1_batch, create_l1_batch_metadata, create_miniblock},
+};
+
+/// Inserts L1 batches `1..=count`, each with a single miniblock overwriting the same storage slot.
+/// L1 batches `1..=executed_count` are marked as executed on L1.
+async fn prepare_storage(pool: &ConnectionPool, count: u32, executed_count: u32) -> StorageKey {
+    let mut storage = pool.access_storage().await.unwrap();
+    ensure_genesis_state(&mut storage, L2ChainId::default(), &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let key = StorageKey::new(AccountTreeId::new(Address::repeat_byte(1)), H256::zero());
+    for number in 1..=count {
+        storage
+            .blocks_dal()
+            .insert_miniblock(&create_miniblock(number))
+            .await
+            .unwrap();
+        let log = StorageLog::new_write_log(key, H256::from_low_u64_be(number.into()));
+        storage
+            .storage_logs_dal()
+            .insert_storage_logs(MiniblockNumber(number), &[(H256::zero(), vec![log])])
+            .await;
+        storage
+            .blocks_dal()
+            .insert_l1_batch(
+                &create_l1_batch(number),
+                &[],
+                BlockGasCount::default(),
+                &[],
+                &[],
+                0,
+            )
+            .await
+            .unwrap();
+        storage
+            .blocks_dal()
+            .mark_miniblocks_as_executed_in_l1_batch(L1BatchNumber(number))
+            .await
+            .unwrap();
+        storage
+            .blocks_dal()
+            .save_l1_batch_metadata(
+                L1BatchNumber(number),
+                &create_l1_batch_metadata(number),
+                H256::zero(),
+                false,
+            )
+            .await
+            .unwrap();
+    }
+    for number in 1..=executed_count {
+        storage
+            .eth_sender_dal()
+            .insert_bogus_confirmed_eth_tx(
+                L1BatchNumber(number),
+                AggregatedActionType::Execute,
+                H256::from_low_u64_be(number.into()),
+                chrono::Utc::now(),
+            )
+            .await
+            .unwrap();
+    }
+    key
+}
+
+#[tokio::test]
+async fn pruning_executed_l1_batches() {
+    let pool = ConnectionPool::test_pool().await;
+    let key = prepare_storage(&pool, 5, 3).await;
+    let pruner = DbPruner::new(pool.clone(), DbPrunerConfig::for_tests());
+
+    // With the retention of 1 L1 batch, L1 batches up to #2 can be pruned.
+    assert!(pruner.run_single_iteration().await.unwrap());
+    let mut storage = pool.access_storage().await.unwrap();
+    let pruning_info = storage.pruning_dal().get_pruning_info().await.unwrap();
+    assert_eq!(
+        pruning_info,
+        PruningInfo {
+            last_pruned_l1_batch: Some(L1BatchNumber(1)),
+            last_pruned_miniblock: Some(MiniblockNumber(1)),
+        }
+    );
+
+    assert!(pruner.run_single_iteration().await.unwrap());
+    assert!(!pruner.run_single_iteration().await.unwrap());
+    let pruning_info = storage.pruning_dal().get_pruning_info().await.unwrap();
+    assert_eq!(pruning_info.first_l1_batch(), L1BatchNumber(3));
+    assert_eq!(pruning_info.first_miniblock(), MiniblockNumber(3));
+
+    // Overwritten storage logs should be pruned, while the storage value should be retained.
+    for number in [1, 2] {
+        let logs = storage
+            .storage_logs_dal()
+            .get_miniblock_storage_logs(MiniblockNumber(number))
+            .await;
+        assert_eq!(logs.len(), usize::from(number == 2), "{logs:?}");
+    }
+    let value = storage
+        .storage_web3_dal()
+        .get_historical_value_unchecked(&key, MiniblockNumber(2))
+        .await
+        .unwrap();
+    assert_eq!(value, H256::from_low_u64_be(2));
+    let logs = storage
+        .storage_logs_dal()
+        .get_miniblock_storage_logs(MiniblockNumber(3))
+        .await;
+    assert_eq!(logs.len(), 1);
+}
+
+#[tokio::test]
+async fn pruning_respects_retention_period() {
+    let pool = ConnectionPool::test_pool().await;
+    prepare_storage(&pool, 5, 5).await;
+    let config = DbPrunerConfig {
+        // Test L1 batches have small timestamps, so they are all older than the retention period, unless it's huge.
+        data_retention_sec: Some(seconds_since_epoch()),
+        ..DbPrunerConfig::for_tests()
+    };
+    let pruner = DbPruner::new(pool.clone(), config);
+    assert!(!pruner.run_single_iteration().await.unwrap());
+
+    let mut storage = pool.access_storage().await.unwrap();
+    let pruning_info = storage.pruning_dal().get_pruning_info().await.unwrap();
+    assert_eq!(pruning_info, PruningInfo::default());
+}
+
+#[tokio::test]
+async fn pruner_stops_on_signal() {
+    let pool = ConnectionPool::test_pool().await;
+    prepare_storage(&pool, 5, 5).await;
+    let pruner = DbPruner::new(pool.clone(), DbPrunerConfig::for_tests());
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let pruner_task = tokio::spawn(pruner.run(stop_receiver));
+
+    loop {
+        let mut storage = pool.access_storage().await.unwrap();
+        let pruning_info = storage.pruning_dal().get_pruning_info().await.unwrap();
+        if pruning_info.last_pruned_l1_batch == Some(L1BatchNumber(4)) {
+            break;
+        }
+        drop(storage);
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    stop_sender.send_replace(true);
+    pruner_task.await.unwrap().unwrap();
+}
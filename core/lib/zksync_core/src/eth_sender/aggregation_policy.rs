@@ -0,0 +1,598 @@
+//! Runtime-configurable policy of aggregating L1 batches into L1 transactions, and its offline simulation.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use anyhow::Context as _;
+use serde::Serialize;
+use tokio::sync::watch;
+use zksync_config::configs::eth_sender::SenderConfig;
+use zksync_dal::StorageProcessor;
+use zksync_types::{
+    aggregated_operations::AggregatedActionType,
+    api::{AggregationPolicy, AggregationPolicyUpdate},
+    block::BlockGasCount,
+    L1BatchNumber,
+};
+
+use super::publish_criterion::{GAS_LIMIT_SAFETY_FACTOR, STORED_BLOCK_INFO_SIZE};
+use crate::gas_tracker::agg_l1_batch_base_cost;
+
+/// Gas limit of L1 blocks; aggregated transactions cannot exceed it.
+const MAX_L1_TX_GAS: u32 = 30_000_000;
+
+const OPERATIONS: [AggregatedActionType; 3] = [
+    AggregatedActionType::Commit,
+    AggregatedActionType::PublishProofOnchain,
+    AggregatedActionType::Execute,
+];
+
+fn policy_from_config(config: &SenderConfig) -> anyhow::Result<AggregationPolicy> {
+    let max_aggregated_blocks_to_prove = config
+        .aggregated_proof_sizes
+        .iter()
+        .max()
+        .context("`aggregated_proof_sizes` is empty")?;
+    Ok(AggregationPolicy {
+        max_aggregated_blocks_to_commit: config.max_aggregated_blocks_to_commit,
+        max_aggregated_blocks_to_prove: u32::try_from(*max_aggregated_blocks_to_prove)
+            .context("`aggregated_proof_sizes` contains a value exceeding u32")?,
+        max_aggregated_blocks_to_execute: config.max_aggregated_blocks_to_execute,
+        max_aggregated_tx_gas: config.max_aggregated_tx_gas,
+        max_eth_tx_data_size: config.max_eth_tx_data_size,
+        aggregated_block_commit_deadline: config.aggregated_block_commit_deadline,
+        aggregated_block_prove_deadline: config.aggregated_block_prove_deadline,
+        aggregated_block_execute_deadline: config.aggregated_block_execute_deadline,
+    })
+}
+
+/// Checks that the aggregation criteria built from the `policy` can publish any L1 batch.
+pub fn validate_policy(policy: &AggregationPolicy) -> anyhow::Result<()> {
+    let limits = [
+        ("commit", policy.max_aggregated_blocks_to_commit),
+        ("prove", policy.max_aggregated_blocks_to_prove),
+        ("execute", policy.max_aggregated_blocks_to_execute),
+    ];
+    for (op, limit) in limits {
+        anyhow::ensure!(
+            limit > 0,
+            "max number of L1 batches to {op} in a single L1 transaction must be positive"
+        );
+    }
+
+    anyhow::ensure!(
+        policy.max_aggregated_tx_gas <= MAX_L1_TX_GAS,
+        "max aggregated L1 transaction gas ({}) exceeds the L1 block gas limit ({MAX_L1_TX_GAS})",
+        policy.max_aggregated_tx_gas
+    );
+    let usable_gas = f64::from(policy.max_aggregated_tx_gas) * GAS_LIMIT_SAFETY_FACTOR;
+    for op in OPERATIONS {
+        let base_cost = agg_l1_batch_base_cost(op);
+        anyhow::ensure!(
+            usable_gas > f64::from(base_cost),
+            "max aggregated L1 transaction gas ({}) doesn't cover the base cost of {op} operations ({base_cost})",
+            policy.max_aggregated_tx_gas
+        );
+    }
+
+    anyhow::ensure!(
+        policy.max_eth_tx_data_size > STORED_BLOCK_INFO_SIZE,
+        "max L1 transaction data size must exceed {STORED_BLOCK_INFO_SIZE} bytes"
+    );
+    Ok(())
+}
+
+/// Handle to the [`AggregationPolicy`] shared by the ETH tx aggregator and the API server in the same process.
+/// Cloned handles refer to the same policy.
+///
+/// The policy can be changed at runtime via the admin API, e.g. to send L1 transactions less often during
+/// L1 gas price spikes. Each change is logged together with the previous policy.
+#[derive(Debug, Clone)]
+pub struct AggregationPolicyHandle {
+    sender: Arc<watch::Sender<AggregationPolicy>>,
+}
+
+impl AggregationPolicyHandle {
+    /// Creates a handle with the policy corresponding to the `config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the policy defined by the config is invalid.
+    pub fn new(config: &SenderConfig) -> anyhow::Result<Self> {
+        let policy = policy_from_config(config)?;
+        validate_policy(&policy)?;
+        Ok(Self {
+            sender: Arc::new(watch::channel(policy).0),
+        })
+    }
+
+    pub fn get(&self) -> AggregationPolicy {
+        *self.sender.borrow()
+    }
+
+    /// Applies the update to the policy and returns the updated policy. The policy is left intact
+    /// if the updated policy is invalid. `source` is used in the log entry describing the change.
+    pub fn update(
+        &self,
+        update: AggregationPolicyUpdate,
+        source: &str,
+    ) -> anyhow::Result<AggregationPolicy> {
+        let mut result = Ok(());
+        let mut prev_policy = None;
+        self.sender.send_if_modified(|policy| {
+            let new_policy = AggregationPolicy {
+                max_aggregated_blocks_to_commit: update
+                    .max_aggregated_blocks_to_commit
+                    .unwrap_or(policy.max_aggregated_blocks_to_commit),
+                max_aggregated_blocks_to_prove: update
+                    .max_aggregated_blocks_to_prove
+                    .unwrap_or(policy.max_aggregated_blocks_to_prove),
+                max_aggregated_blocks_to_execute: update
+                    .max_aggregated_blocks_to_execute
+                    .unwrap_or(policy.max_aggregated_blocks_to_execute),
+                max_aggregated_tx_gas: update
+                    .max_aggregated_tx_gas
+                    .unwrap_or(policy.max_aggregated_tx_gas),
+                max_eth_tx_data_size: update
+                    .max_eth_tx_data_size
+                    .unwrap_or(policy.max_eth_tx_data_size),
+                aggregated_block_commit_deadline: update
+                    .aggregated_block_commit_deadline
+                    .unwrap_or(policy.aggregated_block_commit_deadline),
+                aggregated_block_prove_deadline: update
+                    .aggregated_block_prove_deadline
+                    .unwrap_or(policy.aggregated_block_prove_deadline),
+                aggregated_block_execute_deadline: update
+                    .aggregated_block_execute_deadline
+                    .unwrap_or(policy.aggregated_block_execute_deadline),
+            };
+            if new_policy == *policy {
+                return false;
+            }
+            result = validate_policy(&new_policy);
+            if result.is_err() {
+                return false;
+            }
+            prev_policy = Some(std::mem::replace(policy, new_policy));
+            true
+        });
+        result?;
+
+        let policy = self.get();
+        if let Some(prev_policy) = prev_policy {
+            tracing::warn!(
+                "Aggregation policy changed via {source}: {prev_policy:?} -> {policy:?}"
+            );
+        }
+        Ok(policy)
+    }
+}
+
+/// Information about a sealed L1 batch necessary to simulate its aggregation.
+#[derive(Debug, Clone, Copy)]
+pub struct SimulatedL1Batch {
+    pub number: L1BatchNumber,
+    /// Timestamp of the L1 batch in seconds.
+    pub timestamp: u64,
+    /// Predicted L1 gas costs of the L1 batch.
+    pub predicted_gas: BlockGasCount,
+    /// Size of the L1 batch data in the commit transaction calldata.
+    pub commit_data_size: usize,
+}
+
+impl SimulatedL1Batch {
+    /// Loads information about up to `count` latest L1 batches with metadata. The genesis L1 batch is never loaded
+    /// since it's not published on L1.
+    pub async fn load_latest(
+        storage: &mut StorageProcessor<'_>,
+        count: u32,
+    ) -> anyhow::Result<Vec<Self>> {
+        let mut blocks_dal = storage.blocks_dal();
+        let last_l1_batch = blocks_dal
+            .get_last_l1_batch_number_with_metadata()
+            .await?
+            .unwrap_or_default();
+        let first_l1_batch = last_l1_batch
+            .0
+            .saturating_sub(count)
+            .saturating_add(1)
+            .max(1);
+
+        let mut l1_batches = vec![];
+        for number in first_l1_batch..=last_l1_batch.0 {
+            let number = L1BatchNumber(number);
+            let l1_batch = blocks_dal
+                .get_l1_batch_metadata(number)
+                .await?
+                .with_context(|| format!("L1 batch #{number} doesn't have metadata"))?;
+            let mut predicted_gas = BlockGasCount::default();
+            for op in OPERATIONS {
+                let gas = blocks_dal
+                    .get_l1_batches_predicted_gas(number..=number, op)
+                    .await?;
+                match op {
+                    AggregatedActionType::Commit => predicted_gas.commit = gas,
+                    AggregatedActionType::PublishProofOnchain => predicted_gas.prove = gas,
+                    AggregatedActionType::Execute => predicted_gas.execute = gas,
+                }
+            }
+            l1_batches.push(Self {
+                number,
+                timestamp: l1_batch.header.timestamp,
+                predicted_gas,
+                commit_data_size: l1_batch.l1_commit_data_size(),
+            });
+        }
+        Ok(l1_batches)
+    }
+
+    fn predicted_gas(&self, op: AggregatedActionType) -> u32 {
+        match op {
+            AggregatedActionType::Commit => self.predicted_gas.commit,
+            AggregatedActionType::PublishProofOnchain => self.predicted_gas.prove,
+            AggregatedActionType::Execute => self.predicted_gas.execute,
+        }
+    }
+}
+
+/// Simulated L1 transactions of a certain type.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct OperationSimulationReport {
+    /// Number of L1 transactions.
+    pub tx_count: usize,
+    /// Total predicted gas of L1 transactions.
+    pub total_gas: u64,
+    /// Total cost of L1 transactions in wei.
+    pub total_cost_wei: u128,
+    /// Average number of L1 batches per L1 transaction.
+    pub avg_l1_batches_per_tx: f64,
+    /// Average delay in seconds between sealing an L1 batch and sending the L1 transaction including it.
+    pub avg_delay_sec: f64,
+    /// Maximum delay in seconds between sealing an L1 batch and sending the L1 transaction including it.
+    pub max_delay_sec: u64,
+    /// Number of L1 transactions by the publish criterion that triggered sending them.
+    pub criteria: BTreeMap<&'static str, usize>,
+}
+
+/// Expected L1 transactions and their cost for an [`AggregationPolicy`] over a range of L1 batches.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AggregationSimulationReport {
+    pub first_l1_batch: L1BatchNumber,
+    pub last_l1_batch: L1BatchNumber,
+    /// L1 gas price in wei used to estimate costs.
+    pub l1_gas_price: u64,
+    pub commit: OperationSimulationReport,
+    pub prove: OperationSimulationReport,
+    pub execute: OperationSimulationReport,
+    /// Total cost of all L1 transactions in wei.
+    pub total_cost_wei: u128,
+    /// Average cost of publishing an L1 batch in wei.
+    pub cost_per_l1_batch_wei: u128,
+}
+
+/// Simulates aggregation of the specified L1 batches into L1 transactions according to the `policy`.
+///
+/// Each operation is simulated independently, assuming that L1 batches are ready for it once they are sealed
+/// (i.e., commitment generation and proving are instant), and that the ETH sender isn't lagging. L1 transactions
+/// are assumed to be sent as soon as one of the publish criteria is triggered.
+///
+/// # Errors
+///
+/// Returns an error if `l1_batches` is empty or not sequential, or if some L1 batch cannot be published
+/// under the `policy`.
+pub fn simulate_aggregation(
+    policy: &AggregationPolicy,
+    l1_batches: &[SimulatedL1Batch],
+    l1_gas_price: u64,
+) -> anyhow::Result<AggregationSimulationReport> {
+    validate_policy(policy)?;
+    let (Some(first), Some(last)) = (l1_batches.first(), l1_batches.last()) else {
+        anyhow::bail!("no L1 batches to simulate");
+    };
+    for window in l1_batches.windows(2) {
+        anyhow::ensure!(
+            window[0].number + 1 == window[1].number,
+            "L1 batches are not sequential: #{} is followed by #{}",
+            window[0].number,
+            window[1].number
+        );
+    }
+
+    let commit = simulate_operation(
+        AggregatedActionType::Commit,
+        policy,
+        l1_batches,
+        l1_gas_price,
+    )?;
+    let prove = simulate_operation(
+        AggregatedActionType::PublishProofOnchain,
+        policy,
+        l1_batches,
+        l1_gas_price,
+    )?;
+    let execute = simulate_operation(
+        AggregatedActionType::Execute,
+        policy,
+        l1_batches,
+        l1_gas_price,
+    )?;
+    let total_cost_wei = commit.total_cost_wei + prove.total_cost_wei + execute.total_cost_wei;
+    Ok(AggregationSimulationReport {
+        first_l1_batch: first.number,
+        last_l1_batch: last.number,
+        l1_gas_price,
+        commit,
+        prove,
+        execute,
+        total_cost_wei,
+        cost_per_l1_batch_wei: total_cost_wei / l1_batches.len() as u128,
+    })
+}
+
+fn simulate_operation(
+    op: AggregatedActionType,
+    policy: &AggregationPolicy,
+    l1_batches: &[SimulatedL1Batch],
+    l1_gas_price: u64,
+) -> anyhow::Result<OperationSimulationReport> {
+    let (limit, deadline) = match op {
+        AggregatedActionType::Commit => (
+            policy.max_aggregated_blocks_to_commit,
+            policy.aggregated_block_commit_deadline,
+        ),
+        AggregatedActionType::PublishProofOnchain => (
+            policy.max_aggregated_blocks_to_prove,
+            policy.aggregated_block_prove_deadline,
+        ),
+        AggregatedActionType::Execute => (
+            policy.max_aggregated_blocks_to_execute,
+            policy.aggregated_block_execute_deadline,
+        ),
+    };
+    let limit = limit as usize;
+    let base_cost = agg_l1_batch_base_cost(op);
+    // Mirrors the gas computations in `GasCriterion`.
+    let gas_limit = (f64::from(policy.max_aggregated_tx_gas) * GAS_LIMIT_SAFETY_FACTOR).round()
+        as u32
+        - base_cost;
+    let data_limit = (op == AggregatedActionType::Commit)
+        .then(|| policy.max_eth_tx_data_size - STORED_BLOCK_INFO_SIZE);
+
+    let mut report = OperationSimulationReport::default();
+    let mut total_delay = 0_u64;
+    let mut start = 0;
+    while start < l1_batches.len() {
+        let deadline_timestamp = l1_batches[start].timestamp + deadline;
+        let mut end = start;
+        let mut gas = 0_u32;
+        let mut data_size = 0_usize;
+        let mut trigger = None;
+        for l1_batch in &l1_batches[start..] {
+            if l1_batch.timestamp > deadline_timestamp {
+                trigger = Some(("timestamp", deadline_timestamp));
+                break;
+            }
+
+            let batch_gas = l1_batch.predicted_gas(op);
+            if gas + batch_gas >= gas_limit {
+                anyhow::ensure!(
+                    end > start,
+                    "L1 batch #{} requires {batch_gas} gas to {op}, which is more than the range limit of {}",
+                    l1_batch.number,
+                    policy.max_aggregated_tx_gas
+                );
+                trigger = Some(("gas", l1_batch.timestamp));
+                break;
+            }
+            if let Some(data_limit) = data_limit {
+                if data_size + l1_batch.commit_data_size > data_limit {
+                    anyhow::ensure!(
+                        end > start,
+                        "L1 batch #{} requires {} bytes of data, which is more than the range limit of {}",
+                        l1_batch.number,
+                        l1_batch.commit_data_size,
+                        policy.max_eth_tx_data_size
+                    );
+                    trigger = Some(("data_size", l1_batch.timestamp));
+                    break;
+                }
+            }
+
+            gas += batch_gas;
+            data_size += l1_batch.commit_data_size;
+            end += 1;
+            if end - start == limit {
+                trigger = Some(("number", l1_batch.timestamp));
+                break;
+            }
+        }
+        // If no criterion is triggered by the end of history, the remaining L1 batches are published
+        // on the deadline.
+        let (criterion, sent_at) = trigger.unwrap_or(("timestamp", deadline_timestamp));
+
+        let tx_gas = u64::from(base_cost + gas);
+        report.tx_count += 1;
+        report.total_gas += tx_gas;
+        report.total_cost_wei += u128::from(tx_gas) * u128::from(l1_gas_price);
+        *report.criteria.entry(criterion).or_default() += 1;
+        for l1_batch in &l1_batches[start..end] {
+            let delay = sent_at.saturating_sub(l1_batch.timestamp);
+            total_delay += delay;
+            report.max_delay_sec = report.max_delay_sec.max(delay);
+        }
+        start = end;
+    }
+
+    report.avg_l1_batches_per_tx = l1_batches.len() as f64 / report.tx_count as f64;
+    report.avg_delay_sec = total_delay as f64 / l1_batches.len() as f64;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_config::configs::eth_sender::ETHSenderConfig;
+
+    use super::*;
+
+    fn test_policy() -> AggregationPolicy {
+        AggregationPolicy {
+            max_aggregated_blocks_to_commit: 3,
+            max_aggregated_blocks_to_prove: 1,
+            max_aggregated_blocks_to_execute: 10,
+            max_aggregated_tx_gas: 4_000_000,
+            max_eth_tx_data_size: 120_000,
+            aggregated_block_commit_deadline: 10,
+            aggregated_block_prove_deadline: 10,
+            aggregated_block_execute_deadline: 100,
+        }
+    }
+
+    fn l1_batches(count: u32, interval_sec: u64) -> Vec<SimulatedL1Batch> {
+        (1..=count)
+            .map(|number| SimulatedL1Batch {
+                number: L1BatchNumber(number),
+                timestamp: 1_000 + u64::from(number) * interval_sec,
+                predicted_gas: BlockGasCount {
+                    commit: 100_000,
+                    prove: 10_000,
+                    execute: 50_000,
+                },
+                commit_data_size: 1_000,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn validating_policy() {
+        validate_policy(&test_policy()).unwrap();
+
+        let invalid_policies = [
+            AggregationPolicy {
+                max_aggregated_blocks_to_commit: 0,
+                ..test_policy()
+            },
+            AggregationPolicy {
+                max_aggregated_tx_gas: 100_000,
+                ..test_policy()
+            },
+            AggregationPolicy {
+                max_aggregated_tx_gas: 50_000_000,
+                ..test_policy()
+            },
+            AggregationPolicy {
+                max_eth_tx_data_size: 10,
+                ..test_policy()
+            },
+        ];
+        for policy in invalid_policies {
+            validate_policy(&policy).unwrap_err();
+        }
+    }
+
+    #[test]
+    fn updating_policy() {
+        let config = ETHSenderConfig::for_tests().sender;
+        let handle = AggregationPolicyHandle::new(&config).unwrap();
+        let initial_policy = handle.get();
+        assert_eq!(initial_policy.max_aggregated_blocks_to_prove, 4);
+        assert_eq!(
+            initial_policy.max_aggregated_blocks_to_commit,
+            config.max_aggregated_blocks_to_commit
+        );
+
+        let update = AggregationPolicyUpdate {
+            max_aggregated_blocks_to_commit: Some(0),
+            aggregated_block_commit_deadline: Some(60),
+            ..AggregationPolicyUpdate::default()
+        };
+        handle.update(update, "test").unwrap_err();
+        assert_eq!(handle.get(), initial_policy);
+
+        let update = AggregationPolicyUpdate {
+            max_aggregated_blocks_to_commit: Some(20),
+            aggregated_block_commit_deadline: Some(60),
+            ..AggregationPolicyUpdate::default()
+        };
+        let policy = handle.update(update, "test").unwrap();
+        assert_eq!(
+            policy,
+            AggregationPolicy {
+                max_aggregated_blocks_to_commit: 20,
+                aggregated_block_commit_deadline: 60,
+                ..initial_policy
+            }
+        );
+        assert_eq!(handle.clone().get(), policy);
+    }
+
+    #[test]
+    fn simulating_aggregation() {
+        let l1_batches = l1_batches(10, 1);
+        let report = simulate_aggregation(&test_policy(), &l1_batches, 10).unwrap();
+        assert_eq!(report.first_l1_batch, L1BatchNumber(1));
+        assert_eq!(report.last_l1_batch, L1BatchNumber(10));
+
+        // L1 batches are committed by 3; the last L1 batch is committed on the deadline.
+        assert_eq!(report.commit.tx_count, 4);
+        assert_eq!(report.commit.criteria["number"], 3);
+        assert_eq!(report.commit.criteria["timestamp"], 1);
+        let expected_gas =
+            4 * u64::from(agg_l1_batch_base_cost(AggregatedActionType::Commit)) + 10 * 100_000;
+        assert_eq!(report.commit.total_gas, expected_gas);
+        assert_eq!(report.commit.total_cost_wei, u128::from(expected_gas) * 10);
+        assert_eq!(report.commit.max_delay_sec, 10);
+        // Each L1 batch is proven separately without delays.
+        assert_eq!(report.prove.tx_count, 10);
+        assert_eq!(report.prove.criteria["number"], 10);
+        assert_eq!(report.prove.avg_delay_sec, 0.0);
+        // All L1 batches are executed in a single transaction.
+        assert_eq!(report.execute.tx_count, 1);
+        assert_eq!(report.execute.criteria["number"], 1);
+        assert_eq!(report.execute.max_delay_sec, 9);
+
+        assert_eq!(
+            report.total_cost_wei,
+            report.commit.total_cost_wei
+                + report.prove.total_cost_wei
+                + report.execute.total_cost_wei
+        );
+    }
+
+    #[test]
+    fn simulating_aggregation_with_deadlines_and_gas_limit() {
+        let policy = AggregationPolicy {
+            max_aggregated_blocks_to_commit: 100,
+            max_aggregated_blocks_to_execute: 100,
+            max_aggregated_tx_gas: 1_200_000,
+            aggregated_block_commit_deadline: 25,
+            ..test_policy()
+        };
+        let l1_batches = l1_batches(10, 10);
+        let report = simulate_aggregation(&policy, &l1_batches, 1).unwrap();
+
+        // The deadline allows committing 3 L1 batches at a time.
+        assert_eq!(report.commit.tx_count, 4);
+        assert_eq!(report.commit.criteria["timestamp"], 4);
+        assert_eq!(report.commit.max_delay_sec, 25);
+        // All L1 batches are executed on the deadline.
+        assert_eq!(report.execute.tx_count, 1);
+        assert_eq!(report.execute.criteria["timestamp"], 1);
+
+        let policy = AggregationPolicy {
+            aggregated_block_execute_deadline: 1_000,
+            ..policy
+        };
+        let l1_batches = self::l1_batches(40, 10);
+        let report = simulate_aggregation(&policy, &l1_batches, 1).unwrap();
+        // The gas limit allows executing 16 L1 batches at a time (1,080k usable gas - 241k base cost).
+        assert_eq!(report.execute.criteria["gas"], 2);
+        assert_eq!(report.execute.criteria["timestamp"], 1);
+    }
+
+    #[test]
+    fn simulating_aggregation_with_non_sequential_batches() {
+        let mut l1_batches = l1_batches(3, 1);
+        l1_batches.remove(1);
+        simulate_aggregation(&test_policy(), &l1_batches, 1).unwrap_err();
+        simulate_aggregation(&test_policy(), &[], 1).unwrap_err();
+    }
+}
@@ -1,6 +1,8 @@
 use std::sync::Arc;
 
-use zksync_config::configs::eth_sender::{ProofLoadingMode, ProofSendingMode, SenderConfig};
+use zksync_config::configs::eth_sender::{
+    ProofLoadingMode, ProofSendingMode, PubdataSendingMode, SenderConfig,
+};
 use zksync_contracts::BaseSystemContractsHashes;
 use zksync_dal::StorageProcessor;
 use zksync_object_store::{ObjectStore, ObjectStoreError};
@@ -9,15 +11,19 @@ use zksync_types::{
         AggregatedActionType, AggregatedOperation, L1BatchCommitOperation, L1BatchExecuteOperation,
         L1BatchProofForL1, L1BatchProofOperation,
     },
+    api::AggregationPolicy,
     commitment::L1BatchWithMetadata,
     helpers::unix_timestamp_ms,
     protocol_version::L1VerifierConfig,
     L1BatchNumber, ProtocolVersionId,
 };
 
-use super::publish_criterion::{
-    DataSizeCriterion, GasCriterion, L1BatchPublishCriterion, NumberCriterion,
-    TimestampDeadlineCriterion,
+use super::{
+    aggregation_policy::AggregationPolicyHandle,
+    publish_criterion::{
+        DataSizeCriterion, GasCriterion, L1BatchPublishCriterion, NumberCriterion,
+        TimestampDeadlineCriterion,
+    },
 };
 
 #[derive(Debug)]
@@ -26,69 +32,113 @@ pub struct Aggregator {
     proof_criteria: Vec<Box<dyn L1BatchPublishCriterion>>,
     execute_criteria: Vec<Box<dyn L1BatchPublishCriterion>>,
     config: SenderConfig,
+    policy_handle: AggregationPolicyHandle,
+    /// Policy used to build the publish criteria.
+    policy: AggregationPolicy,
     blob_store: Arc<dyn ObjectStore>,
 }
 
 impl Aggregator {
-    pub fn new(config: SenderConfig, blob_store: Arc<dyn ObjectStore>) -> Self {
+    pub fn new(
+        config: SenderConfig,
+        blob_store: Arc<dyn ObjectStore>,
+        policy_handle: AggregationPolicyHandle,
+    ) -> Self {
+        let policy = policy_handle.get();
+        let max_allowed_lag = config.timestamp_criteria_max_allowed_lag;
         Self {
-            commit_criteria: vec![
-                Box::from(NumberCriterion {
-                    op: AggregatedActionType::Commit,
-                    limit: config.max_aggregated_blocks_to_commit,
-                }),
-                Box::from(GasCriterion::new(
-                    AggregatedActionType::Commit,
-                    config.max_aggregated_tx_gas,
-                )),
-                Box::from(DataSizeCriterion {
-                    op: AggregatedActionType::Commit,
-                    data_limit: config.max_eth_tx_data_size,
-                }),
-                Box::from(TimestampDeadlineCriterion {
-                    op: AggregatedActionType::Commit,
-                    deadline_seconds: config.aggregated_block_commit_deadline,
-                    max_allowed_lag: Some(config.timestamp_criteria_max_allowed_lag),
-                }),
-            ],
-            proof_criteria: vec![
-                Box::from(NumberCriterion {
-                    op: AggregatedActionType::PublishProofOnchain,
-                    limit: *config.aggregated_proof_sizes.iter().max().unwrap() as u32,
-                }),
-                Box::from(GasCriterion::new(
-                    AggregatedActionType::PublishProofOnchain,
-                    config.max_aggregated_tx_gas,
-                )),
-                Box::from(TimestampDeadlineCriterion {
-                    op: AggregatedActionType::PublishProofOnchain,
-                    deadline_seconds: config.aggregated_block_prove_deadline,
-                    // Currently, we can't use this functionality for proof criterion
-                    // since we don't send dummy and real proofs in the same range,
-                    // so even small ranges must be closed.
-                    max_allowed_lag: None,
-                }),
-            ],
-            execute_criteria: vec![
-                Box::from(NumberCriterion {
-                    op: AggregatedActionType::Execute,
-                    limit: config.max_aggregated_blocks_to_execute,
-                }),
-                Box::from(GasCriterion::new(
-                    AggregatedActionType::Execute,
-                    config.max_aggregated_tx_gas,
-                )),
-                Box::from(TimestampDeadlineCriterion {
-                    op: AggregatedActionType::Execute,
-                    deadline_seconds: config.aggregated_block_execute_deadline,
-                    max_allowed_lag: Some(config.timestamp_criteria_max_allowed_lag),
-                }),
-            ],
+            commit_criteria: Self::commit_criteria(&policy, max_allowed_lag),
+            proof_criteria: Self::proof_criteria(&policy),
+            execute_criteria: Self::execute_criteria(&policy, max_allowed_lag),
             config,
+            policy_handle,
+            policy,
             blob_store,
         }
     }
 
+    fn commit_criteria(
+        policy: &AggregationPolicy,
+        max_allowed_lag: usize,
+    ) -> Vec<Box<dyn L1BatchPublishCriterion>> {
+        vec![
+            Box::from(NumberCriterion {
+                op: AggregatedActionType::Commit,
+                limit: policy.max_aggregated_blocks_to_commit,
+            }),
+            Box::from(GasCriterion::new(
+                AggregatedActionType::Commit,
+                policy.max_aggregated_tx_gas,
+            )),
+            Box::from(DataSizeCriterion {
+                op: AggregatedActionType::Commit,
+                data_limit: policy.max_eth_tx_data_size,
+            }),
+            Box::from(TimestampDeadlineCriterion {
+                op: AggregatedActionType::Commit,
+                deadline_seconds: policy.aggregated_block_commit_deadline,
+                max_allowed_lag: Some(max_allowed_lag),
+            }),
+        ]
+    }
+
+    fn proof_criteria(policy: &AggregationPolicy) -> Vec<Box<dyn L1BatchPublishCriterion>> {
+        vec![
+            Box::from(NumberCriterion {
+                op: AggregatedActionType::PublishProofOnchain,
+                limit: policy.max_aggregated_blocks_to_prove,
+            }),
+            Box::from(GasCriterion::new(
+                AggregatedActionType::PublishProofOnchain,
+                policy.max_aggregated_tx_gas,
+            )),
+            Box::from(TimestampDeadlineCriterion {
+                op: AggregatedActionType::PublishProofOnchain,
+                deadline_seconds: policy.aggregated_block_prove_deadline,
+                // Currently, we can't use this functionality for proof criterion
+                // since we don't send dummy and real proofs in the same range,
+                // so even small ranges must be closed.
+                max_allowed_lag: None,
+            }),
+        ]
+    }
+
+    fn execute_criteria(
+        policy: &AggregationPolicy,
+        max_allowed_lag: usize,
+    ) -> Vec<Box<dyn L1BatchPublishCriterion>> {
+        vec![
+            Box::from(NumberCriterion {
+                op: AggregatedActionType::Execute,
+                limit: policy.max_aggregated_blocks_to_execute,
+            }),
+            Box::from(GasCriterion::new(
+                AggregatedActionType::Execute,
+                policy.max_aggregated_tx_gas,
+            )),
+            Box::from(TimestampDeadlineCriterion {
+                op: AggregatedActionType::Execute,
+                deadline_seconds: policy.aggregated_block_execute_deadline,
+                max_allowed_lag: Some(max_allowed_lag),
+            }),
+        ]
+    }
+
+    /// Rebuilds publish criteria if the policy was changed since the last call.
+    fn sync_policy(&mut self) {
+        let policy = self.policy_handle.get();
+        if policy == self.policy {
+            return;
+        }
+
+        tracing::info!("Applying updated aggregation policy: {policy:?}");
+        let max_allowed_lag = self.config.timestamp_criteria_max_allowed_lag;
+        self.commit_criteria = Self::commit_criteria(&policy, max_allowed_lag);
+        self.proof_criteria = Self::proof_criteria(&policy);
+        self.execute_criteria = Self::execute_criteria(&policy, max_allowed_lag);
+        self.policy = policy;
+    }
+
     pub async fn get_next_ready_operation(
         &mut self,
         storage: &mut StorageProcessor<'_>,
@@ -104,11 +154,12 @@ impl Aggregator {
         else {
             return None; // No L1 batches in Postgres; no operations are ready yet
         };
+        self.sync_policy();
 
         if let Some(op) = self
             .get_execute_operations(
                 storage,
-                self.config.max_aggregated_blocks_to_execute as usize,
+                self.policy.max_aggregated_blocks_to_execute as usize,
                 last_sealed_l1_batch_number,
             )
             .await
@@ -117,7 +168,7 @@ impl Aggregator {
         } else if let Some(op) = self
             .get_proof_operation(
                 storage,
-                *self.config.aggregated_proof_sizes.iter().max().unwrap(),
+                self.policy.max_aggregated_blocks_to_prove as usize,
                 last_sealed_l1_batch_number,
                 l1_verifier_config,
             )
@@ -127,7 +178,7 @@ impl Aggregator {
         } else {
             self.get_commit_operation(
                 storage,
-                self.config.max_aggregated_blocks_to_commit as usize,
+                self.policy.max_aggregated_blocks_to_commit as usize,
                 last_sealed_l1_batch_number,
                 base_system_contracts_hashes,
                 protocol_version_id,
@@ -194,6 +245,8 @@ impl Aggregator {
                     base_system_contracts_hashes.bootloader,
                     base_system_contracts_hashes.default_aa,
                     protocol_version_id,
+                    // L1 batches can only be committed after their pubdata is included into the DA layer.
+                    self.config.pubdata_sending_mode == PubdataSendingMode::Custom,
                 )
                 .await
                 .unwrap()
@@ -1,34 +1,46 @@
-use std::{convert::TryInto, sync::Arc};
+use std::{convert::TryInto, ops, sync::Arc};
 
 use tokio::sync::watch;
-use zksync_config::configs::eth_sender::SenderConfig;
+use zksync_commitment_utils::kzg::{self, BlobInfo};
+use zksync_config::configs::eth_sender::{PubdataSendingMode, SenderConfig};
 use zksync_contracts::BaseSystemContractsHashes;
 use zksync_dal::{ConnectionPool, StorageProcessor};
-use zksync_eth_client::{BoundEthInterface, CallFunctionArgs};
+use zksync_eth_client::{BoundEthInterface, CallFunctionArgs, Error as EthClientError};
+use zksync_system_constants::L1_GAS_PER_PUBDATA_BYTE;
 use zksync_types::{
-    aggregated_operations::AggregatedOperation,
+    aggregated_operations::{AggregatedActionType, AggregatedOperation, L1BatchCommitOperation},
+    commitment::{L1BatchCommitmentMode, L1BatchWithMetadata},
     contracts::{Multicall3Call, Multicall3Result},
-    eth_sender::EthTx,
+    eth_sender::{EthTx, EthTxBlobSidecar},
     ethabi::{Contract, Token},
     protocol_version::{L1VerifierConfig, VerifierParams},
     vk_transform::l1_vk_commitment,
-    web3::contract::{
-        tokens::{Detokenize, Tokenizable},
-        Error,
+    web3::{
+        self,
+        contract::{
+            tokens::{Detokenize, Tokenizable},
+            Error,
+        },
+        types::CallRequest,
     },
-    Address, ProtocolVersionId, H256, U256,
+    Address, L1BatchNumber, ProtocolVersionId, H256, U256,
 };
 
 use crate::{
+    da_dispatcher,
     eth_sender::{
-        metrics::{PubdataKind, METRICS},
+        metrics::{BlobFallbackReason, PubdataKind, METRICS},
         zksync_functions::ZkSyncFunctions,
         Aggregator, ETHSenderError,
     },
     gas_tracker::agg_l1_batch_base_cost,
+    l1_gas_price::L1TxParamsProvider,
     metrics::BlockL1Stage,
 };
 
+/// Maximum number of blobs in a single L1 transaction (EIP-4844).
+const MAX_BLOBS_PER_TX: usize = 6;
+
 /// Data queried from L1 using multicall contract.
 #[derive(Debug)]
 pub struct MulticallData {
@@ -45,12 +57,16 @@ pub struct MulticallData {
 pub struct EthTxAggregator {
     aggregator: Aggregator,
     eth_client: Arc<dyn BoundEthInterface>,
+    gas_adjuster: Arc<dyn L1TxParamsProvider>,
     config: SenderConfig,
     timelock_contract_address: Address,
     l1_multicall3_address: Address,
     pub(super) main_zksync_contract_address: Address,
     functions: ZkSyncFunctions,
     base_nonce: u64,
+    commitment_mode: L1BatchCommitmentMode,
+    /// Last operation simulated in the dry-run mode. Used to not simulate the same operation on each iteration.
+    last_simulated_op: Option<(AggregatedActionType, ops::RangeInclusive<L1BatchNumber>)>,
 }
 
 impl EthTxAggregator {
@@ -58,21 +74,26 @@ impl EthTxAggregator {
         config: SenderConfig,
         aggregator: Aggregator,
         eth_client: Arc<dyn BoundEthInterface>,
+        gas_adjuster: Arc<dyn L1TxParamsProvider>,
         timelock_contract_address: Address,
         l1_multicall3_address: Address,
         main_zksync_contract_address: Address,
         base_nonce: u64,
+        commitment_mode: L1BatchCommitmentMode,
     ) -> Self {
         let functions = ZkSyncFunctions::default();
         Self {
             config,
             aggregator,
             eth_client,
+            gas_adjuster,
             timelock_contract_address,
             l1_multicall3_address,
             main_zksync_contract_address,
             functions,
             base_nonce,
+            commitment_mode,
+            last_simulated_op: None,
         }
     }
 
@@ -358,10 +379,15 @@ impl EthTxAggregator {
             )
             .await
         {
-            let tx = self
-                .save_eth_tx(storage, &agg_op, contracts_are_pre_boojum)
-                .await?;
-            Self::report_eth_tx_saving(storage, agg_op, &tx).await;
+            if self.config.dry_run {
+                self.simulate_eth_tx(storage, &agg_op, contracts_are_pre_boojum)
+                    .await?;
+            } else {
+                let tx = self
+                    .save_eth_tx(storage, &agg_op, contracts_are_pre_boojum)
+                    .await?;
+                Self::report_eth_tx_saving(storage, agg_op, &tx).await;
+            }
         }
         Ok(())
     }
@@ -397,16 +423,102 @@ impl EthTxAggregator {
             .await;
     }
 
+    /// Splits pubdata of L1 batches in the commit operation into blobs if pubdata should be published in blobs.
+    /// Returns `None` if pubdata should be published in calldata.
+    fn pubdata_blobs(&self, op: &L1BatchCommitOperation) -> Option<Vec<Vec<BlobInfo>>> {
+        if self.commitment_mode == L1BatchCommitmentMode::Validium
+            || self.config.pubdata_sending_mode != PubdataSendingMode::Blobs
+        {
+            return None;
+        }
+
+        let pubdata: Vec<_> = op
+            .l1_batches
+            .iter()
+            .map(L1BatchWithMetadata::pubdata)
+            .collect();
+        let pubdata_len: usize = pubdata.iter().map(Vec::len).sum();
+        let blob_count: usize = pubdata.iter().map(|data| kzg::blob_count(data.len())).sum();
+        if let Some(reason) = self.blob_fallback_reason(pubdata_len, blob_count) {
+            tracing::info!(
+                "Publishing pubdata for L1 batches {:?} in calldata: {reason:?}",
+                op.l1_batch_range()
+            );
+            METRICS.blob_fallbacks[&reason].inc();
+            return None;
+        }
+
+        METRICS.blobs_per_tx.observe(blob_count);
+        Some(
+            pubdata
+                .iter()
+                .map(|data| kzg::pubdata_to_blobs(data))
+                .collect(),
+        )
+    }
+
+    fn blob_fallback_reason(
+        &self,
+        pubdata_len: usize,
+        blob_count: usize,
+    ) -> Option<BlobFallbackReason> {
+        if blob_count > MAX_BLOBS_PER_TX {
+            return Some(BlobFallbackReason::TooManyBlobs);
+        }
+        let blob_base_fee = self.gas_adjuster.get_blob_base_fee();
+        if blob_base_fee == 0 {
+            return Some(BlobFallbackReason::NoBlobSupport);
+        }
+
+        // Each byte of a blob consumes 1 unit of blob gas, and blobs are always paid for in full.
+        let blob_cost = U256::from(blob_count * kzg::BYTES_PER_BLOB) * U256::from(blob_base_fee);
+        let calldata_cost = U256::from(pubdata_len)
+            * U256::from(L1_GAS_PER_PUBDATA_BYTE)
+            * U256::from(self.gas_adjuster.get_base_fee(0));
+        (blob_cost > calldata_cost).then_some(BlobFallbackReason::BlobFeeSpike)
+    }
+
+    /// Loads inclusion data for L1 batches in the commit operation if pubdata is posted to an external DA layer.
+    async fn load_da_inclusion_data(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        op: &AggregatedOperation,
+    ) -> Option<Vec<Vec<u8>>> {
+        let AggregatedOperation::Commit(op) = op else {
+            return None;
+        };
+        if self.config.pubdata_sending_mode != PubdataSendingMode::Custom {
+            return None;
+        }
+
+        let mut inclusion_data = Vec::with_capacity(op.l1_batches.len());
+        for batch in &op.l1_batches {
+            let l1_batch_number = batch.header.number;
+            let data = storage
+                .data_availability_dal()
+                .get_inclusion_data(l1_batch_number)
+                .await
+                .unwrap()
+                .unwrap_or_else(|| {
+                    panic!("L1 batch #{l1_batch_number} is not included into the DA layer")
+                });
+            inclusion_data.push(data);
+        }
+        Some(inclusion_data)
+    }
+
     fn encode_aggregated_op(
         &self,
         op: &AggregatedOperation,
         contracts_are_pre_boojum: bool,
-    ) -> Vec<u8> {
+        da_inclusion_data: Option<Vec<Vec<u8>>>,
+    ) -> (Vec<u8>, Option<EthTxBlobSidecar>) {
         let operation_is_pre_boojum = op.protocol_version().is_pre_boojum();
 
         // For "commit" and "prove" operations it's necessary that the contracts are of the same version as L1 batches are.
         // For "execute" it's not required, i.e. we can "execute" pre-boojum batches with post-boojum contracts.
-        match &op {
+        let mut blob_sidecar = None;
+        let calldata = match &op {
             AggregatedOperation::Commit(op) => {
                 assert_eq!(contracts_are_pre_boojum, operation_is_pre_boojum);
                 let f = if contracts_are_pre_boojum {
@@ -417,7 +529,40 @@ impl EthTxAggregator {
                         .as_ref()
                         .expect("Missing ABI for commitBatches")
                 };
-                f.encode_input(&op.get_eth_tx_args())
+                let blobs = if contracts_are_pre_boojum {
+                    None
+                } else {
+                    self.pubdata_blobs(op)
+                };
+                if let Some(inclusion_data) = da_inclusion_data {
+                    // Pubdata is published to an external DA layer; the L1 contracts receive inclusion data instead.
+                    let pubdata_commitments = inclusion_data
+                        .into_iter()
+                        .map(|data| {
+                            let mut commitment = vec![da_dispatcher::PUBDATA_SOURCE_CUSTOM];
+                            commitment.extend(data);
+                            commitment
+                        })
+                        .collect();
+                    f.encode_input(
+                        &op.get_eth_tx_args_with_pubdata_commitments(pubdata_commitments),
+                    )
+                } else if self.commitment_mode == L1BatchCommitmentMode::Validium {
+                    // Pubdata is not published in the validium mode. Pre-boojum contracts don't support this mode.
+                    f.encode_input(&op.get_eth_tx_args_without_pubdata())
+                } else if let Some(blobs) = blobs {
+                    let pubdata_commitments = blobs
+                        .iter()
+                        .map(|batch_blobs| kzg::encode_pubdata_commitments(batch_blobs))
+                        .collect();
+                    blob_sidecar =
+                        Some(kzg::blobs_to_sidecar(blobs.into_iter().flatten().collect()));
+                    f.encode_input(
+                        &op.get_eth_tx_args_with_pubdata_commitments(pubdata_commitments),
+                    )
+                } else {
+                    f.encode_input(&op.get_eth_tx_args())
+                }
             }
             AggregatedOperation::PublishProofOnchain(op) => {
                 assert_eq!(contracts_are_pre_boojum, operation_is_pre_boojum);
@@ -443,7 +588,8 @@ impl EthTxAggregator {
                 f.encode_input(&op.get_eth_tx_args())
             }
         }
-        .expect("Failed to encode transaction data")
+        .expect("Failed to encode transaction data");
+        (calldata, blob_sidecar)
     }
 
     pub(super) async fn save_eth_tx(
@@ -453,8 +599,12 @@ impl EthTxAggregator {
         contracts_are_pre_boojum: bool,
     ) -> Result<EthTx, ETHSenderError> {
         let mut transaction = storage.start_transaction().await.unwrap();
-        let nonce = self.get_next_nonce(&mut transaction).await?;
-        let calldata = self.encode_aggregated_op(aggregated_op, contracts_are_pre_boojum);
+        let (nonce, from_addr) = self.get_next_nonce(&mut transaction).await?;
+        let da_inclusion_data = self
+            .load_da_inclusion_data(&mut transaction, aggregated_op)
+            .await;
+        let (calldata, blob_sidecar) =
+            self.encode_aggregated_op(aggregated_op, contracts_are_pre_boojum, da_inclusion_data);
         let l1_batch_number_range = aggregated_op.l1_batch_range();
         let op_type = aggregated_op.get_action_type();
 
@@ -473,6 +623,8 @@ impl EthTxAggregator {
                 op_type,
                 self.timelock_contract_address,
                 eth_tx_predicted_gas,
+                blob_sidecar,
+                from_addr,
             )
             .await
             .unwrap();
@@ -486,18 +638,102 @@ impl EthTxAggregator {
         Ok(eth_tx)
     }
 
+    /// Builds the L1 transaction for the operation and simulates it via `eth_call` on behalf of the operator account
+    /// without saving, signing or sending the transaction. Used in the dry-run mode. Returns `false` if the operation
+    /// was already simulated on the previous call.
+    pub(super) async fn simulate_eth_tx(
+        &mut self,
+        storage: &mut StorageProcessor<'_>,
+        aggregated_op: &AggregatedOperation,
+        contracts_are_pre_boojum: bool,
+    ) -> Result<bool, ETHSenderError> {
+        let op_type = aggregated_op.get_action_type();
+        let l1_batch_number_range = aggregated_op.l1_batch_range();
+        let op_key = (op_type, l1_batch_number_range.clone());
+        if self.last_simulated_op.as_ref() == Some(&op_key) {
+            return Ok(false);
+        }
+
+        let da_inclusion_data = self.load_da_inclusion_data(storage, aggregated_op).await;
+        let (calldata, blob_sidecar) =
+            self.encode_aggregated_op(aggregated_op, contracts_are_pre_boojum, da_inclusion_data);
+        let predicted_gas_for_batches = storage
+            .blocks_dal()
+            .get_l1_batches_predicted_gas(l1_batch_number_range.clone(), op_type)
+            .await
+            .unwrap();
+        let predicted_gas = agg_l1_batch_base_cost(op_type) + predicted_gas_for_batches;
+        let caption = aggregated_op.get_action_caption();
+        if blob_sidecar.is_some() {
+            tracing::warn!(
+                "Dry run: {caption} transaction for L1 batches {l1_batch_number_range:?} publishes pubdata in blobs, \
+                 which cannot be attached to `eth_call`; the simulation will likely revert"
+            );
+        }
+
+        let request = CallRequest {
+            from: Some(self.eth_client.sender_account()),
+            to: Some(self.timelock_contract_address),
+            data: Some(calldata.clone().into()),
+            ..CallRequest::default()
+        };
+        METRICS.dry_run_simulations[&op_type.into()].inc();
+        let call_result = self
+            .eth_client
+            .call(request.clone(), None, "eth_tx_aggregator")
+            .await;
+        match call_result {
+            Ok(_) => {
+                let estimated_gas = self
+                    .eth_client
+                    .estimate_gas(request, "eth_tx_aggregator")
+                    .await?;
+                tracing::info!(
+                    "Dry run: {caption} transaction for L1 batches {l1_batch_number_range:?} succeeded; \
+                     predicted gas: {predicted_gas}, estimated gas: {estimated_gas}, calldata: 0x{}",
+                    hex::encode(&calldata)
+                );
+            }
+            Err(EthClientError::EthereumGateway(web3::Error::Rpc(err))) => {
+                METRICS.dry_run_reverts[&op_type.into()].inc();
+                tracing::warn!(
+                    "Dry run: {caption} transaction for L1 batches {l1_batch_number_range:?} reverted: {err}; \
+                     predicted gas: {predicted_gas}, calldata: 0x{}",
+                    hex::encode(&calldata)
+                );
+            }
+            Err(err) => return Err(err.into()),
+        }
+        self.last_simulated_op = Some(op_key);
+        Ok(true)
+    }
+
+    /// Returns the nonce for a new transaction together with the operator account sending it
+    /// (`None` corresponds to the main operator account).
     async fn get_next_nonce(
         &self,
         storage: &mut StorageProcessor<'_>,
-    ) -> Result<u64, ETHSenderError> {
+    ) -> Result<(u64, Option<Address>), ETHSenderError> {
+        let active_operator = storage
+            .eth_sender_dal()
+            .get_active_operator()
+            .await
+            .unwrap();
+        let (from_addr, mut base_nonce) = active_operator.map_or((None, 0), |operator| {
+            (operator.address, operator.base_nonce)
+        });
+        if from_addr.is_none() {
+            // Between server starts we can execute some txs using operator account or remove some txs from the database
+            // At the start we have to consider this fact and get the max nonce.
+            base_nonce = base_nonce.max(self.base_nonce);
+        }
+
         let db_nonce = storage
             .eth_sender_dal()
-            .get_next_nonce()
+            .get_next_nonce(from_addr)
             .await
             .unwrap()
             .unwrap_or(0);
-        // Between server starts we can execute some txs using operator account or remove some txs from the database
-        // At the start we have to consider this fact and get the max nonce.
-        Ok(db_nonce.max(self.base_nonce))
+        Ok((db_nonce.max(base_nonce), from_addr))
     }
 }
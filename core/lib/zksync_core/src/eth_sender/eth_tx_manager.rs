@@ -1,12 +1,19 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    sync::Arc,
+    time::Duration,
+};
 
 use anyhow::Context as _;
 use tokio::sync::watch;
-use zksync_config::configs::eth_sender::SenderConfig;
-use zksync_dal::{ConnectionPool, StorageProcessor};
+use zksync_config::configs::eth_sender::{
+    GasEscalationConfig, GasEscalationPolicy, SenderConfig, StuckTxRemediationPolicy,
+};
+use zksync_dal::{eth_sender_dal::ActiveOperator, ConnectionPool, StorageProcessor};
 use zksync_eth_client::{
-    BoundEthInterface, Error, ExecutedTxStatus, RawTransactionBytes, SignedCallResult,
+    BlobTxParams, BoundEthInterface, Error, ExecutedTxStatus, RawTransactionBytes, SignedCallResult,
 };
+use zksync_health_check::ReactiveHealthCheck;
 use zksync_types::{
     eth_sender::EthTx,
     web3::{
@@ -14,25 +21,35 @@ use zksync_types::{
         error::Error as Web3Error,
         types::{BlockId, BlockNumber},
     },
-    L1BlockNumber, Nonce, H256, U256,
+    Address, L1BlockNumber, Nonce, H256, U256,
 };
 use zksync_utils::time::seconds_since_epoch;
 
-use super::{metrics::METRICS, ETHSenderError};
+use super::{
+    gas_escalation::{Escalation, GasEscalator, TxFees},
+    metrics::{OperatorRotationReason, StuckTxRemediation, METRICS},
+    stuck_tx_tracker::{
+        InflightTxInfo, OperatorNonce, StuckTxKind, StuckTxTracker, StuckTxsReport,
+    },
+    ETHSenderError,
+};
 use crate::{l1_gas_price::L1TxParamsProvider, metrics::BlockL1Stage};
 
 #[derive(Debug)]
 struct EthFee {
     base_fee_per_gas: u64,
     priority_fee_per_gas: u64,
+    /// Max fee per blob gas; only set for blob transactions.
+    blob_base_fee_per_gas: Option<u64>,
 }
 
+/// Progress of the active operator account nonce used to detect a stuck account.
 #[derive(Debug, Clone, Copy)]
-struct OperatorNonce {
-    // Nonce on finalized block
-    finalized: Nonce,
-    // Nonce on latest block
-    latest: Nonce,
+struct NonceProgress {
+    operator: Option<Address>,
+    nonce: Nonce,
+    /// L1 block at which `nonce` was first observed.
+    since_block: L1BlockNumber,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -45,27 +62,80 @@ pub(super) struct L1BlockNumbers {
 /// Based on eth_tx queue the component generates new attempt with the minimum possible fee,
 /// save it to the database, and send it to Ethereum.
 /// Based on eth_tx_history queue the component can mark txs as stuck and create the new attempt
-/// with higher gas price
+/// with higher gas price.
+/// If backup operator accounts are configured, the component rotates the active operator account once it gets stuck
+/// or runs low on funds; unmined transactions of the previously active account are taken over by the new one.
+/// Fees of resent transactions are bumped according to the gas escalation policy configured for the transaction type,
+/// and are never raised above the configured cap.
+/// On each new L1 block, in-flight transactions are checked for nonce gaps, insufficient fees and L1 reorgs;
+/// stuck transactions are reported in the component health check and can be remediated automatically.
 #[derive(Debug)]
 pub struct EthTxManager {
+    /// Client for the main operator account.
     ethereum_gateway: Arc<dyn BoundEthInterface>,
+    backup_operators: Vec<Arc<dyn BoundEthInterface>>,
     config: SenderConfig,
+    gas_escalator: GasEscalator,
     gas_adjuster: Arc<dyn L1TxParamsProvider>,
+    nonce_progress: Option<NonceProgress>,
+    stuck_tx_tracker: StuckTxTracker,
 }
 
 impl EthTxManager {
     pub fn new(
         config: SenderConfig,
+        gas_escalation: GasEscalationConfig,
         gas_adjuster: Arc<dyn L1TxParamsProvider>,
         ethereum_gateway: Arc<dyn BoundEthInterface>,
+        backup_operators: Vec<Arc<dyn BoundEthInterface>>,
     ) -> Self {
+        let stuck_tx_tracker = StuckTxTracker::new(config.stuck_tx_threshold_blocks());
         Self {
             ethereum_gateway,
+            backup_operators,
             config,
+            gas_escalator: GasEscalator::new(gas_escalation),
             gas_adjuster,
+            nonce_progress: None,
+            stuck_tx_tracker,
+        }
+    }
+
+    /// Returns the health check reporting stuck in-flight transactions.
+    pub fn health_check(&self) -> ReactiveHealthCheck {
+        self.stuck_tx_tracker.health_check()
+    }
+
+    /// Returns the index of the operator account with the specified address; the main account has index 0.
+    fn operator_index(&self, address: Option<Address>) -> Option<usize> {
+        let Some(address) = address else {
+            return Some(0);
+        };
+        self.backup_operators
+            .iter()
+            .position(|operator| operator.sender_account() == address)
+            .map(|position| position + 1)
+    }
+
+    fn operator(&self, index: usize) -> &Arc<dyn BoundEthInterface> {
+        if index == 0 {
+            &self.ethereum_gateway
+        } else {
+            &self.backup_operators[index - 1]
         }
     }
 
+    /// Returns the address of the operator account as stored in the DB, i.e. `None` for the main account.
+    fn operator_address(&self, index: usize) -> Option<Address> {
+        (index > 0).then(|| self.operator(index).sender_account())
+    }
+
+    fn min_operator_balance(&self) -> Option<U256> {
+        const GWEI: u64 = 1_000_000_000;
+        let min_balance_gwei = self.config.min_operator_balance_gwei?;
+        Some(U256::from(min_balance_gwei) * U256::from(GWEI))
+    }
+
     async fn get_tx_status(
         &self,
         tx_hash: H256,
@@ -81,6 +151,7 @@ impl EthTxManager {
         storage: &mut StorageProcessor<'_>,
         op: &EthTx,
     ) -> Option<ExecutedTxStatus> {
+        let mut failed_status = None;
         // Checking history items, starting from most recently sent.
         for history_item in storage
             .eth_sender_dal()
@@ -92,7 +163,12 @@ impl EthTxManager {
             // because if we do and get an `Err`, we won't finish the for loop,
             // which means we might miss the transaction that actually succeeded.
             match self.get_tx_status(history_item.tx_hash).await {
-                Ok(Some(s)) => return Some(s),
+                Ok(Some(s)) if s.success => return Some(s),
+                // After operator rotation, attempts sent from different accounts may all be mined,
+                // with only one of them succeeding; hence, we check all attempts before reporting a failure.
+                Ok(Some(s)) => {
+                    failed_status.get_or_insert(s);
+                }
                 Ok(_) => continue,
                 Err(err) => tracing::warn!(
                     "Can't check transaction {:?}: {:?}",
@@ -101,7 +177,7 @@ impl EthTxManager {
                 ),
             }
         }
-        None
+        failed_status
     }
 
     async fn calculate_fee(
@@ -109,9 +185,54 @@ impl EthTxManager {
         storage: &mut StorageProcessor<'_>,
         tx: &EthTx,
         time_in_mempool: u32,
+        current_block: L1BlockNumber,
     ) -> Result<EthFee, ETHSenderError> {
         let base_fee_per_gas = self.gas_adjuster.get_base_fee(time_in_mempool);
+        let fee = if tx.blob_sidecar.is_some() {
+            self.calculate_blob_tx_fee(storage, tx, time_in_mempool, base_fee_per_gas)
+                .await?
+        } else if time_in_mempool != 0
+            && self.gas_escalator.params(tx.tx_type).policy != GasEscalationPolicy::GasAdjuster
+        {
+            self.escalate_fee(
+                storage,
+                tx,
+                time_in_mempool,
+                current_block,
+                base_fee_per_gas,
+            )
+            .await?
+        } else {
+            self.calculate_regular_tx_fee(storage, tx, time_in_mempool, base_fee_per_gas)
+                .await?
+        };
+        let fee = self.cap_fee(tx, fee, time_in_mempool != 0)?;
 
+        if time_in_mempool != 0 {
+            if let Some(previous_sent_tx) = storage
+                .eth_sender_dal()
+                .get_last_sent_eth_tx(tx.id)
+                .await
+                .unwrap()
+            {
+                let previous_max_fee = TxFees::from(&previous_sent_tx).max_fee_per_gas();
+                let max_fee = fee.base_fee_per_gas + fee.priority_fee_per_gas;
+                METRICS.fee_bumps[&tx.tx_type.into()].inc();
+                METRICS.fee_bump_ratio[&tx.tx_type.into()]
+                    .observe(max_fee as f64 / previous_max_fee.max(1) as f64);
+            }
+        }
+        Ok(fee)
+    }
+
+    /// Calculates fees for a transaction using the gas adjuster, which is the default escalation policy.
+    async fn calculate_regular_tx_fee(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        tx: &EthTx,
+        time_in_mempool: u32,
+        base_fee_per_gas: u64,
+    ) -> Result<EthFee, ETHSenderError> {
         let priority_fee_per_gas = if time_in_mempool != 0 {
             METRICS.transaction_resent.inc();
             let priority_fee_per_gas = self
@@ -140,6 +261,156 @@ impl EthTxManager {
         Ok(EthFee {
             base_fee_per_gas,
             priority_fee_per_gas,
+            blob_base_fee_per_gas: None,
+        })
+    }
+
+    /// Calculates fees for an EIP-4844 transaction. Blob transactions can only be replaced if all their fees
+    /// are at least doubled, so fees of resent blob transactions are bumped more aggressively than for other
+    /// transactions. To not overpay, a blob transaction is only resent if it's underpriced by the current fees.
+    async fn calculate_blob_tx_fee(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        tx: &EthTx,
+        time_in_mempool: u32,
+        base_fee_per_gas: u64,
+    ) -> Result<EthFee, ETHSenderError> {
+        let priority_fee_per_gas = self.gas_adjuster.get_priority_fee();
+        let blob_base_fee_per_gas = self.gas_adjuster.get_blob_base_fee();
+        if time_in_mempool == 0 {
+            return Ok(EthFee {
+                base_fee_per_gas,
+                priority_fee_per_gas,
+                blob_base_fee_per_gas: Some(blob_base_fee_per_gas),
+            });
+        }
+
+        let previous_sent_tx = storage
+            .eth_sender_dal()
+            .get_last_sent_eth_tx(tx.id)
+            .await
+            .unwrap()
+            .unwrap();
+        let previous_base_fee = previous_sent_tx.base_fee_per_gas;
+        let previous_blob_base_fee = previous_sent_tx.blob_base_fee_per_gas.unwrap_or(0);
+        if base_fee_per_gas <= previous_base_fee && blob_base_fee_per_gas <= previous_blob_base_fee
+        {
+            tracing::info!(
+                "Skipping gas adjustment for blob operation {}, suggested base_fee_per_gas {base_fee_per_gas} \
+                 and blob_base_fee_per_gas {blob_base_fee_per_gas} are not higher than previously sent \
+                 {previous_base_fee} and {previous_blob_base_fee}",
+                tx.id
+            );
+            return Err(ETHSenderError::from(Error::from(Web3Error::Internal)));
+        }
+
+        METRICS.transaction_resent.inc();
+        let fee = EthFee {
+            base_fee_per_gas: base_fee_per_gas.max(previous_base_fee * 2),
+            priority_fee_per_gas: priority_fee_per_gas
+                .max(previous_sent_tx.priority_fee_per_gas * 2),
+            blob_base_fee_per_gas: Some(blob_base_fee_per_gas.max(previous_blob_base_fee * 2)),
+        };
+        tracing::info!("Resending blob operation {} with {fee:?}", tx.id);
+        if fee.priority_fee_per_gas > self.config.max_acceptable_priority_fee_in_gwei {
+            panic!(
+                "Extremely high value of priority_fee_per_gas is suggested: {}, while max acceptable is {}",
+                fee.priority_fee_per_gas,
+                self.config.max_acceptable_priority_fee_in_gwei
+            );
+        }
+        Ok(fee)
+    }
+
+    /// Calculates fees for a resent transaction according to the configured escalation policy.
+    async fn escalate_fee(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        tx: &EthTx,
+        time_in_mempool: u32,
+        current_block: L1BlockNumber,
+        base_fee_per_gas: u64,
+    ) -> Result<EthFee, ETHSenderError> {
+        let attempts = storage
+            .eth_sender_dal()
+            .get_tx_history_to_check(tx.id)
+            .await
+            .unwrap();
+        let current_fees = TxFees {
+            base_fee_per_gas,
+            priority_fee_per_gas: self.gas_adjuster.get_priority_fee(),
+        };
+        let escalation = self.gas_escalator.escalate(
+            tx.tx_type,
+            &attempts,
+            current_block.0,
+            time_in_mempool,
+            current_fees,
+        );
+        match escalation {
+            Escalation::Bump(fees) => {
+                METRICS.transaction_resent.inc();
+                tracing::info!(
+                    "Resending operation {} with base fee {} and priority fee {} according to {:?} escalation policy",
+                    tx.id,
+                    fees.base_fee_per_gas,
+                    fees.priority_fee_per_gas,
+                    self.gas_escalator.params(tx.tx_type).policy
+                );
+                Ok(EthFee {
+                    base_fee_per_gas: fees.base_fee_per_gas,
+                    priority_fee_per_gas: fees.priority_fee_per_gas,
+                    blob_base_fee_per_gas: None,
+                })
+            }
+            Escalation::Wait => {
+                tracing::debug!(
+                    "Skipping resending operation {}: resend interval is not reached",
+                    tx.id
+                );
+                Err(ETHSenderError::from(Error::from(Web3Error::Internal)))
+            }
+            Escalation::Capped => {
+                METRICS.capped_resends[&tx.tx_type.into()].inc();
+                tracing::warn!(
+                    "Skipping resending operation {}: bumped fees would exceed max fee per gas {}",
+                    tx.id,
+                    self.gas_escalator.params(tx.tx_type).max_fee_per_gas()
+                );
+                Err(ETHSenderError::from(Error::from(Web3Error::Internal)))
+            }
+        }
+    }
+
+    /// Caps fees at the max fee per gas configured for the transaction type. A resent transaction is not resent
+    /// if its fees need to be capped, since the capped attempt would most probably be rejected as underpriced.
+    fn cap_fee(&self, tx: &EthTx, fee: EthFee, is_resend: bool) -> Result<EthFee, ETHSenderError> {
+        let fees = TxFees {
+            base_fee_per_gas: fee.base_fee_per_gas,
+            priority_fee_per_gas: fee.priority_fee_per_gas,
+        };
+        let capped_fees = self.gas_escalator.cap_fees(tx.tx_type, fees);
+        if capped_fees == fees {
+            return Ok(fee);
+        }
+
+        let max_fee_per_gas = self.gas_escalator.params(tx.tx_type).max_fee_per_gas();
+        if is_resend {
+            METRICS.capped_resends[&tx.tx_type.into()].inc();
+            tracing::warn!(
+                "Skipping resending operation {}: fees {fee:?} exceed max fee per gas {max_fee_per_gas}",
+                tx.id
+            );
+            return Err(ETHSenderError::from(Error::from(Web3Error::Internal)));
+        }
+        tracing::info!(
+            "Capping fees {fee:?} for operation {} at max fee per gas {max_fee_per_gas}",
+            tx.id
+        );
+        Ok(EthFee {
+            base_fee_per_gas: capped_fees.base_fee_per_gas,
+            priority_fee_per_gas: capped_fees.priority_fee_per_gas,
+            ..fee
         })
     }
 
@@ -189,15 +460,28 @@ impl EthTxManager {
         let EthFee {
             base_fee_per_gas,
             priority_fee_per_gas,
-        } = self.calculate_fee(storage, tx, time_in_mempool).await?;
+            blob_base_fee_per_gas,
+        } = self
+            .calculate_fee(storage, tx, time_in_mempool, current_block)
+            .await?;
 
         METRICS.used_base_fee_per_gas.observe(base_fee_per_gas);
         METRICS
             .used_priority_fee_per_gas
             .observe(priority_fee_per_gas);
+        if let Some(blob_base_fee_per_gas) = blob_base_fee_per_gas {
+            METRICS
+                .used_blob_base_fee_per_gas
+                .observe(blob_base_fee_per_gas);
+        }
 
         let signed_tx = self
-            .sign_tx(tx, base_fee_per_gas, priority_fee_per_gas)
+            .sign_tx(
+                tx,
+                base_fee_per_gas,
+                priority_fee_per_gas,
+                blob_base_fee_per_gas,
+            )
             .await;
 
         if let Some(tx_history_id) = storage
@@ -208,6 +492,7 @@ impl EthTxManager {
                 priority_fee_per_gas,
                 signed_tx.hash,
                 signed_tx.raw_tx.as_ref(),
+                blob_base_fee_per_gas,
             )
             .await
             .unwrap()
@@ -256,18 +541,16 @@ impl EthTxManager {
     }
 
     async fn get_operator_nonce(
-        &self,
+        operator: &dyn BoundEthInterface,
         block_numbers: L1BlockNumbers,
     ) -> Result<OperatorNonce, ETHSenderError> {
-        let finalized = self
-            .ethereum_gateway
+        let finalized = operator
             .nonce_at(block_numbers.finalized.0.into(), "eth_tx_manager")
             .await?
             .as_u32()
             .into();
 
-        let latest = self
-            .ethereum_gateway
+        let latest = operator
             .nonce_at(block_numbers.latest.0.into(), "eth_tx_manager")
             .await?
             .as_u32()
@@ -313,23 +596,44 @@ impl EthTxManager {
         METRICS
             .last_known_l1_block
             .set(l1_block_numbers.latest.0.into());
-        let operator_nonce = self.get_operator_nonce(l1_block_numbers).await?;
         let inflight_txs = storage.eth_sender_dal().get_inflight_txs().await.unwrap();
         METRICS.number_of_inflight_txs.set(inflight_txs.len());
 
         tracing::trace!(
             "Going through not confirmed txs. \
-             Block numbers: latest {}, finalized {}, \
-             operator's nonce: latest {}, finalized {}",
+             Block numbers: latest {}, finalized {}",
             l1_block_numbers.latest,
             l1_block_numbers.finalized,
-            operator_nonce.latest,
-            operator_nonce.finalized,
         );
 
+        // Nonces of operator accounts sending the transactions
+        let mut operator_nonces = HashMap::new();
         // Not confirmed transactions, ordered by nonce
         for tx in inflight_txs {
             tracing::trace!("Checking tx id: {}", tx.id,);
+            let operator_nonce = match operator_nonces.entry(tx.from_addr) {
+                Entry::Occupied(entry) => *entry.get(),
+                Entry::Vacant(entry) => {
+                    let Some(operator_index) = self.operator_index(tx.from_addr) else {
+                        tracing::error!(
+                            "Operator account {:?} that has sent tx {} is not configured",
+                            tx.from_addr,
+                            tx.id
+                        );
+                        continue;
+                    };
+                    let operator = self.operator(operator_index).as_ref();
+                    let operator_nonce =
+                        Self::get_operator_nonce(operator, l1_block_numbers).await?;
+                    tracing::trace!(
+                        "Operator {:?} nonce: latest {}, finalized {}",
+                        operator.sender_account(),
+                        operator_nonce.latest,
+                        operator_nonce.finalized
+                    );
+                    *entry.insert(operator_nonce)
+                }
+            };
 
             // If the `operator_nonce.latest` <= `tx.nonce`, this means
             // that `tx` is not mined and we should resend it.
@@ -385,22 +689,50 @@ impl EthTxManager {
         tx: &EthTx,
         base_fee_per_gas: u64,
         priority_fee_per_gas: u64,
+        blob_base_fee_per_gas: Option<u64>,
     ) -> SignedCallResult {
-        self.ethereum_gateway
-            .sign_prepared_tx_for_addr(
-                tx.raw_tx.clone(),
-                tx.contract_address,
-                Options::with(|opt| {
-                    // TODO Calculate gas for every operation SMA-1436
-                    opt.gas = Some(self.config.max_aggregated_tx_gas.into());
-                    opt.max_fee_per_gas = Some(U256::from(base_fee_per_gas + priority_fee_per_gas));
-                    opt.max_priority_fee_per_gas = Some(U256::from(priority_fee_per_gas));
-                    opt.nonce = Some(tx.nonce.0.into());
-                }),
-                "eth_tx_manager",
+        let options = Options::with(|opt| {
+            // TODO Calculate gas for every operation SMA-1436
+            opt.gas = Some(self.config.max_aggregated_tx_gas.into());
+            opt.max_fee_per_gas = Some(U256::from(base_fee_per_gas + priority_fee_per_gas));
+            opt.max_priority_fee_per_gas = Some(U256::from(priority_fee_per_gas));
+            opt.nonce = Some(tx.nonce.0.into());
+        });
+        let operator_index = self.operator_index(tx.from_addr).unwrap_or_else(|| {
+            panic!(
+                "Operator account {:?} sending tx {} is not configured",
+                tx.from_addr, tx.id
             )
-            .await
-            .expect("Failed to sign transaction")
+        });
+        let operator = self.operator(operator_index);
+        let signed_tx = match (&tx.blob_sidecar, blob_base_fee_per_gas) {
+            (Some(sidecar), Some(blob_base_fee_per_gas)) => {
+                let blob_params = BlobTxParams {
+                    max_fee_per_blob_gas: blob_base_fee_per_gas.into(),
+                    sidecar: sidecar.clone(),
+                };
+                operator
+                    .sign_prepared_blob_tx_for_addr(
+                        tx.raw_tx.clone(),
+                        tx.contract_address,
+                        options,
+                        blob_params,
+                        "eth_tx_manager",
+                    )
+                    .await
+            }
+            _ => {
+                operator
+                    .sign_prepared_tx_for_addr(
+                        tx.raw_tx.clone(),
+                        tx.contract_address,
+                        options,
+                        "eth_tx_manager",
+                    )
+                    .await
+            }
+        };
+        signed_tx.expect("Failed to sign transaction")
     }
 
     async fn send_unsent_txs(
@@ -508,9 +840,11 @@ impl EthTxManager {
             .gas_used
             .expect("light ETH clients are not supported");
 
+        let effective_gas_price = tx_status.receipt.effective_gas_price;
+
         storage
             .eth_sender_dal()
-            .confirm_tx(tx_status.tx_hash, gas_used)
+            .confirm_tx(tx_status.tx_hash, gas_used, effective_gas_price)
             .await
             .unwrap();
 
@@ -604,18 +938,314 @@ impl EthTxManager {
 
         if number_of_available_slots_for_eth_txs > 0 {
             // Get the new eth tx and create history item for them
-            let new_eth_tx = storage
+            let mut new_eth_tx = storage
                 .eth_sender_dal()
                 .get_new_eth_txs(number_of_available_slots_for_eth_txs)
                 .await
                 .unwrap();
 
+            let active_operator = storage
+                .eth_sender_dal()
+                .get_active_operator()
+                .await
+                .unwrap();
+            if let Some(active_operator) = active_operator {
+                if let Some(stale_tx) = new_eth_tx
+                    .iter()
+                    .find(|tx| tx.from_addr != active_operator.address)
+                {
+                    if let Err(err) = self
+                        .take_over_unsent_txs(storage, stale_tx, active_operator)
+                        .await
+                    {
+                        tracing::warn!("Failed taking over unsent txs: {err}");
+                        return;
+                    }
+                    new_eth_tx = storage
+                        .eth_sender_dal()
+                        .get_new_eth_txs(number_of_available_slots_for_eth_txs)
+                        .await
+                        .unwrap();
+                }
+            }
+
             for tx in new_eth_tx {
                 let _ = self.send_eth_tx(storage, &tx, 0, current_block).await;
             }
         }
     }
 
+    /// Returns the nonce for the next transaction sent from the specified operator account.
+    async fn next_operator_nonce(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        operator_index: usize,
+    ) -> Result<u64, ETHSenderError> {
+        let pending_nonce = self
+            .operator(operator_index)
+            .pending_nonce("eth_tx_manager")
+            .await?
+            .as_u64();
+        let db_nonce = storage
+            .eth_sender_dal()
+            .get_next_nonce(self.operator_address(operator_index))
+            .await
+            .unwrap()
+            .unwrap_or(0);
+        Ok(pending_nonce.max(db_nonce))
+    }
+
+    /// Takes over unsent transactions created by the aggregator for a previously active operator account
+    /// concurrently with the rotation.
+    async fn take_over_unsent_txs(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        stale_tx: &EthTx,
+        active_operator: ActiveOperator,
+    ) -> Result<(), ETHSenderError> {
+        let Some(active_index) = self.operator_index(active_operator.address) else {
+            tracing::error!(
+                "Active operator account {:?} is not configured",
+                active_operator.address
+            );
+            return Ok(());
+        };
+        let mut transaction = storage.start_transaction().await.unwrap();
+        let first_nonce = self
+            .next_operator_nonce(&mut transaction, active_index)
+            .await?
+            .max(active_operator.base_nonce);
+        let reassigned_txs = transaction
+            .eth_sender_dal()
+            .reassign_eth_txs(
+                stale_tx.from_addr,
+                stale_tx.nonce.0.into(),
+                active_operator.address,
+                first_nonce,
+            )
+            .await
+            .unwrap();
+        transaction.commit().await.unwrap();
+        tracing::info!(
+            "Reassigned {} unsent txs of operator account {:?} to the active account {:?}",
+            reassigned_txs.len(),
+            stale_tx.from_addr,
+            active_operator.address
+        );
+        Ok(())
+    }
+
+    /// Checks whether the active operator account should be rotated, i.e. whether it has pending transactions,
+    /// but its nonce doesn't increase, or whether it runs low on funds.
+    async fn check_operator_health(
+        &mut self,
+        storage: &mut StorageProcessor<'_>,
+        operator_index: usize,
+        latest_nonce: Nonce,
+        latest_block: L1BlockNumber,
+    ) -> Result<Option<OperatorRotationReason>, ETHSenderError> {
+        let operator = self.operator(operator_index).clone();
+        if let Some(min_balance) = self.min_operator_balance() {
+            let balance = operator.sender_eth_balance("eth_tx_manager").await?;
+            if balance < min_balance {
+                tracing::warn!(
+                    "Balance of operator account {:?} is {balance} wei, which is below the configured minimum \
+                     {min_balance} wei",
+                    operator.sender_account()
+                );
+                return Ok(Some(OperatorRotationReason::LowBalance));
+            }
+        }
+
+        let Some(stuck_nonce_blocks) = self.config.stuck_operator_nonce_blocks else {
+            return Ok(None);
+        };
+        let address = self.operator_address(operator_index);
+        let has_pending_txs = storage
+            .eth_sender_dal()
+            .get_inflight_txs()
+            .await
+            .unwrap()
+            .iter()
+            .any(|tx| tx.from_addr == address && tx.nonce >= latest_nonce);
+        if !has_pending_txs {
+            self.nonce_progress = None;
+            return Ok(None);
+        }
+
+        let since_block = match self.nonce_progress {
+            Some(progress) if progress.operator == address && progress.nonce == latest_nonce => {
+                progress.since_block
+            }
+            _ => {
+                self.nonce_progress = Some(NonceProgress {
+                    operator: address,
+                    nonce: latest_nonce,
+                    since_block: latest_block,
+                });
+                latest_block
+            }
+        };
+        let stuck_for_blocks = latest_block.0.saturating_sub(since_block.0);
+        if u64::from(stuck_for_blocks) < stuck_nonce_blocks {
+            return Ok(None);
+        }
+        tracing::warn!(
+            "Nonce of operator account {:?} didn't increase for {stuck_for_blocks} L1 blocks while the account \
+             has pending transactions",
+            operator.sender_account()
+        );
+        Ok(Some(OperatorRotationReason::StuckNonce))
+    }
+
+    /// Selects the operator account to rotate to: the next configured account after the active one
+    /// (wrapping around) with a sufficient balance.
+    async fn select_next_operator(
+        &self,
+        active_index: usize,
+    ) -> Result<Option<usize>, ETHSenderError> {
+        let operator_count = self.backup_operators.len() + 1;
+        let min_balance = self.min_operator_balance();
+        for offset in 1..operator_count {
+            let index = (active_index + offset) % operator_count;
+            if let Some(min_balance) = min_balance {
+                let operator = self.operator(index);
+                let balance = operator.sender_eth_balance("eth_tx_manager").await?;
+                if balance < min_balance {
+                    tracing::info!(
+                        "Skipping operator account {:?} with insufficient balance {balance} wei",
+                        operator.sender_account()
+                    );
+                    continue;
+                }
+            }
+            return Ok(Some(index));
+        }
+        Ok(None)
+    }
+
+    /// Rotates the active operator account if it's stuck or runs low on funds.
+    pub(super) async fn maybe_rotate_operator(
+        &mut self,
+        storage: &mut StorageProcessor<'_>,
+        l1_block_numbers: L1BlockNumbers,
+    ) -> Result<(), ETHSenderError> {
+        if self.backup_operators.is_empty() {
+            return Ok(());
+        }
+        let active_address = storage
+            .eth_sender_dal()
+            .get_active_operator()
+            .await
+            .unwrap()
+            .and_then(|operator| operator.address);
+        let Some(active_index) = self.operator_index(active_address) else {
+            tracing::error!("Active operator account {active_address:?} is not configured");
+            return Ok(());
+        };
+        let latest_nonce: Nonce = self
+            .operator(active_index)
+            .nonce_at(l1_block_numbers.latest.0.into(), "eth_tx_manager")
+            .await?
+            .as_u32()
+            .into();
+
+        let Some(reason) = self
+            .check_operator_health(storage, active_index, latest_nonce, l1_block_numbers.latest)
+            .await?
+        else {
+            return Ok(());
+        };
+        let Some(next_index) = self.select_next_operator(active_index).await? else {
+            tracing::error!(
+                "Operator account {:?} should be rotated ({}), but there are no backup accounts available",
+                self.operator(active_index).sender_account(),
+                reason.as_str()
+            );
+            return Ok(());
+        };
+        self.rotate_operator(
+            storage,
+            active_index,
+            latest_nonce,
+            next_index,
+            reason,
+            l1_block_numbers.latest,
+        )
+        .await
+    }
+
+    /// Makes the operator account with `next_index` active. All unmined transactions of the previously active
+    /// account (i.e., ones with nonces `>= latest_nonce`) are taken over by the new account and are re-sent from it.
+    /// If the old account still manages to get some of these transactions mined, transactions sent
+    /// from the new account will revert on L1, which is handled by checking all sending attempts.
+    async fn rotate_operator(
+        &mut self,
+        storage: &mut StorageProcessor<'_>,
+        active_index: usize,
+        latest_nonce: Nonce,
+        next_index: usize,
+        reason: OperatorRotationReason,
+        current_block: L1BlockNumber,
+    ) -> Result<(), ETHSenderError> {
+        let active_address = self.operator_address(active_index);
+        let next_address = self.operator_address(next_index);
+
+        let mut transaction = storage.start_transaction().await.unwrap();
+        let next_operator = ActiveOperator {
+            address: next_address,
+            base_nonce: self
+                .next_operator_nonce(&mut transaction, next_index)
+                .await?,
+        };
+        transaction
+            .eth_sender_dal()
+            .insert_operator_rotation(active_address, next_operator, reason.as_str())
+            .await
+            .unwrap();
+        let reassigned_txs = transaction
+            .eth_sender_dal()
+            .reassign_eth_txs(
+                active_address,
+                latest_nonce.0.into(),
+                next_address,
+                next_operator.base_nonce,
+            )
+            .await
+            .unwrap();
+        transaction.commit().await.unwrap();
+
+        METRICS.operator_rotations[&reason].inc();
+        self.nonce_progress = None;
+        tracing::warn!(
+            "Rotated operator account from {:?} to {:?} ({}); {} unmined txs were taken over",
+            self.operator(active_index).sender_account(),
+            self.operator(next_index).sender_account(),
+            reason.as_str(),
+            reassigned_txs.len()
+        );
+
+        // Transactions that were sent from the old account are re-sent right away; other transactions
+        // will be sent as new ones.
+        for tx in &reassigned_txs {
+            let was_sent = storage
+                .eth_sender_dal()
+                .get_block_number_on_first_sent_attempt(tx.id)
+                .await
+                .unwrap()
+                .is_some();
+            if was_sent {
+                if let Err(err) = self.send_eth_tx(storage, tx, 0, current_block).await {
+                    tracing::warn!(
+                        "Failed re-sending tx {} from the new operator: {err}",
+                        tx.id
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
     #[tracing::instrument(skip(self, storage))]
     async fn loop_iteration(
         &mut self,
@@ -632,6 +1262,9 @@ impl EthTxManager {
             return Ok(previous_block);
         }
 
+        self.maybe_rotate_operator(storage, l1_block_numbers)
+            .await?;
+
         if let Some((tx, sent_at_block)) = self
             .monitor_inflight_transactions(storage, l1_block_numbers)
             .await?
@@ -647,6 +1280,152 @@ impl EthTxManager {
                 .await;
         }
 
+        if let Err(err) = self.track_stuck_txs(storage, l1_block_numbers).await {
+            tracing::warn!("Failed checking in-flight txs for stuck ones: {err}");
+        }
         Ok(l1_block_numbers.latest)
     }
+
+    /// Checks in-flight transactions for nonce gaps, insufficient fees and L1 reorgs, and remediates
+    /// stuck transactions according to the configured policy.
+    pub(super) async fn track_stuck_txs(
+        &mut self,
+        storage: &mut StorageProcessor<'_>,
+        l1_block_numbers: L1BlockNumbers,
+    ) -> Result<StuckTxsReport, ETHSenderError> {
+        let inflight_txs = storage.eth_sender_dal().get_inflight_txs().await.unwrap();
+        let mut operator_nonces = HashMap::new();
+        let mut tx_infos = Vec::with_capacity(inflight_txs.len());
+        for tx in &inflight_txs {
+            let operator_nonce = match operator_nonces.entry(tx.from_addr) {
+                Entry::Occupied(entry) => *entry.get(),
+                Entry::Vacant(entry) => {
+                    let Some(operator_index) = self.operator_index(tx.from_addr) else {
+                        continue;
+                    };
+                    let operator = self.operator(operator_index).as_ref();
+                    *entry.insert(Self::get_operator_nonce(operator, l1_block_numbers).await?)
+                }
+            };
+
+            let first_sent_at_block = storage
+                .eth_sender_dal()
+                .get_block_number_on_first_sent_attempt(tx.id)
+                .await
+                .unwrap();
+            let last_attempt = storage
+                .eth_sender_dal()
+                .get_last_sent_eth_tx(tx.id)
+                .await
+                .unwrap();
+            // Receipts are only necessary to detect reorgs for mined transactions.
+            let receipt_block_hash = if tx.nonce < operator_nonce.latest {
+                self.check_all_sending_attempts(storage, tx)
+                    .await
+                    .and_then(|status| status.receipt.block_hash)
+            } else {
+                None
+            };
+            tx_infos.push(InflightTxInfo {
+                eth_tx_id: tx.id,
+                nonce: tx.nonce,
+                operator: tx.from_addr,
+                first_sent_at_block,
+                last_sent_at_block: last_attempt
+                    .as_ref()
+                    .and_then(|attempt| attempt.sent_at_block),
+                last_base_fee_per_gas: last_attempt.map(|attempt| attempt.base_fee_per_gas),
+                receipt_block_hash,
+            });
+        }
+
+        let report = self.stuck_tx_tracker.check(
+            l1_block_numbers.latest,
+            self.gas_adjuster.get_next_block_minimal_base_fee(),
+            &operator_nonces,
+            &tx_infos,
+        );
+        self.remediate_stuck_txs(storage, &inflight_txs, &report, l1_block_numbers.latest)
+            .await;
+        Ok(report)
+    }
+
+    async fn remediate_stuck_txs(
+        &mut self,
+        storage: &mut StorageProcessor<'_>,
+        inflight_txs: &[EthTx],
+        report: &StuckTxsReport,
+        current_block: L1BlockNumber,
+    ) {
+        let policy = self.config.stuck_tx_remediation;
+        if policy == StuckTxRemediationPolicy::Alert {
+            return;
+        }
+
+        for stuck_tx in report.txs_of_kind(StuckTxKind::FeeStarved) {
+            let Some(tx) = inflight_txs.iter().find(|tx| tx.id == stuck_tx.eth_tx_id) else {
+                continue;
+            };
+            match self
+                .send_eth_tx(storage, tx, stuck_tx.pending_blocks, current_block)
+                .await
+            {
+                Ok(tx_hash) => {
+                    tracing::info!("Replaced fee-starved tx {} with attempt {tx_hash:?}", tx.id);
+                    METRICS.stuck_tx_remediations[&StuckTxRemediation::Replace].inc();
+                }
+                Err(err) => tracing::warn!("Failed replacing fee-starved tx {}: {err}", tx.id),
+            }
+        }
+
+        if policy == StuckTxRemediationPolicy::Cancel {
+            // Limit the number of sent transactions in case the operator nonce was bumped by a third party.
+            let max_filled_nonces = self.config.max_txs_in_flight as usize;
+            let nonces_to_fill = report
+                .nonce_gaps
+                .iter()
+                .flat_map(|gap| gap.nonces.clone().map(move |nonce| (gap.operator, nonce)))
+                .take(max_filled_nonces);
+            for (operator, nonce) in nonces_to_fill {
+                match self.fill_nonce_gap(operator, nonce).await {
+                    Ok(tx_hash) => {
+                        tracing::info!(
+                            "Filled nonce gap for operator {operator:?} at nonce {nonce} with tx {tx_hash:?}"
+                        );
+                        METRICS.stuck_tx_remediations[&StuckTxRemediation::FillNonceGap].inc();
+                    }
+                    Err(err) => tracing::warn!(
+                        "Failed filling nonce gap for operator {operator:?} at nonce {nonce}: {err}"
+                    ),
+                }
+            }
+        }
+    }
+
+    /// Uses up the specified nonce of an operator account by sending a zero-value transfer to the account itself.
+    /// Such transactions are not persisted; if one isn't mined, it's re-sent once the gap is detected again.
+    async fn fill_nonce_gap(
+        &self,
+        operator: Option<Address>,
+        nonce: u32,
+    ) -> Result<H256, ETHSenderError> {
+        const TRANSFER_GAS: u64 = 21_000;
+
+        let operator_index = self
+            .operator_index(operator)
+            .expect("nonce gaps are only reported for configured operator accounts");
+        let operator = self.operator(operator_index);
+        let base_fee_per_gas = self.gas_adjuster.get_base_fee(0);
+        let priority_fee_per_gas = self.gas_adjuster.get_priority_fee();
+        let options = Options::with(|opt| {
+            opt.gas = Some(TRANSFER_GAS.into());
+            opt.max_fee_per_gas = Some(U256::from(base_fee_per_gas + priority_fee_per_gas));
+            opt.max_priority_fee_per_gas = Some(U256::from(priority_fee_per_gas));
+            opt.nonce = Some(nonce.into());
+        });
+        let signed_tx = operator
+            .sign_prepared_tx_for_addr(vec![], operator.sender_account(), options, "eth_tx_manager")
+            .await?;
+        Ok(self.ethereum_gateway.send_raw_tx(signed_tx.raw_tx).await?)
+    }
 }
@@ -0,0 +1,319 @@
+//! Policies of bumping fees of re-sent L1 transactions.
+
+use zksync_config::configs::eth_sender::{
+    GasEscalationConfig, GasEscalationParams, GasEscalationPolicy,
+};
+use zksync_types::{aggregated_operations::AggregatedActionType, eth_sender::TxHistory};
+
+/// Fees of an L1 transaction attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct TxFees {
+    pub base_fee_per_gas: u64,
+    pub priority_fee_per_gas: u64,
+}
+
+impl TxFees {
+    pub fn max_fee_per_gas(self) -> u64 {
+        self.base_fee_per_gas
+            .saturating_add(self.priority_fee_per_gas)
+    }
+}
+
+impl From<&TxHistory> for TxFees {
+    fn from(attempt: &TxHistory) -> Self {
+        Self {
+            base_fee_per_gas: attempt.base_fee_per_gas,
+            priority_fee_per_gas: attempt.priority_fee_per_gas,
+        }
+    }
+}
+
+/// Outcome of fee escalation for a transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Escalation {
+    /// The transaction should be re-sent with the specified fees.
+    Bump(TxFees),
+    /// The transaction should not be re-sent yet.
+    Wait,
+    /// Re-sending the transaction would require exceeding the fee cap.
+    Capped,
+}
+
+/// Computes fees of re-sent L1 transactions according to the policies configured for each transaction type.
+#[derive(Debug, Clone)]
+pub(super) struct GasEscalator {
+    config: GasEscalationConfig,
+}
+
+impl GasEscalator {
+    pub fn new(config: GasEscalationConfig) -> Self {
+        for params in [&config.commit, &config.prove, &config.execute] {
+            assert!(
+                params.bump_ratio() >= 0.0,
+                "Gas escalation bump ratio must be non-negative"
+            );
+            if params.policy == GasEscalationPolicy::Deadline {
+                assert!(
+                    params.deadline_blocks.map_or(false, |blocks| blocks > 0)
+                        && params.max_fee_per_gas.is_some(),
+                    "`Deadline` gas escalation policy requires positive `deadline_blocks` and `max_fee_per_gas`"
+                );
+            }
+        }
+        Self { config }
+    }
+
+    pub fn params(&self, tx_type: AggregatedActionType) -> &GasEscalationParams {
+        match tx_type {
+            AggregatedActionType::Commit => &self.config.commit,
+            AggregatedActionType::PublishProofOnchain => &self.config.prove,
+            AggregatedActionType::Execute => &self.config.execute,
+        }
+    }
+
+    /// Caps fees at the configured max fee per gas. The base fee is lowered first, so that the transaction
+    /// keeps its priority fee if possible.
+    pub fn cap_fees(&self, tx_type: AggregatedActionType, fees: TxFees) -> TxFees {
+        let max_fee_per_gas = self.params(tx_type).max_fee_per_gas();
+        let priority_fee_per_gas = fees.priority_fee_per_gas.min(max_fee_per_gas);
+        TxFees {
+            base_fee_per_gas: fees
+                .base_fee_per_gas
+                .min(max_fee_per_gas - priority_fee_per_gas),
+            priority_fee_per_gas,
+        }
+    }
+
+    /// Computes fees for re-sending a transaction according to the `Linear`, `Exponential` or `Deadline` policy.
+    ///
+    /// `attempts` are previous sending attempts of the transaction, starting from the most recent one.
+    /// Escalated fees are never lower than `current_fees` (i.e., the current fee estimates), and satisfy
+    /// the replacement rules of L1 nodes.
+    pub fn escalate(
+        &self,
+        tx_type: AggregatedActionType,
+        attempts: &[TxHistory],
+        current_block: u32,
+        blocks_in_mempool: u32,
+        current_fees: TxFees,
+    ) -> Escalation {
+        let params = self.params(tx_type);
+        let (Some(previous), Some(initial)) = (attempts.first(), attempts.last()) else {
+            return Escalation::Bump(self.cap_fees(tx_type, current_fees));
+        };
+        let last_sent_block = previous.sent_at_block.unwrap_or(0);
+        if u64::from(current_block.saturating_sub(last_sent_block))
+            < params.resend_interval_blocks()
+        {
+            return Escalation::Wait;
+        }
+
+        let initial_fees = TxFees::from(initial);
+        let previous_fees = TxFees::from(previous);
+        let multiplier = match params.policy {
+            GasEscalationPolicy::GasAdjuster => {
+                unreachable!("`GasAdjuster` escalation policy is handled by `EthTxManager`")
+            }
+            GasEscalationPolicy::Linear => 1.0 + params.bump_ratio() * attempts.len() as f64,
+            GasEscalationPolicy::Exponential => {
+                (1.0 + params.bump_ratio()).powi(attempts.len() as i32)
+            }
+            GasEscalationPolicy::Deadline => {
+                let deadline_blocks = params.deadline_blocks.unwrap_or(1);
+                let progress = (f64::from(blocks_in_mempool) / deadline_blocks as f64).min(1.0);
+                let initial_max_fee = initial_fees.max_fee_per_gas().max(1) as f64;
+                let max_multiplier = (params.max_fee_per_gas() as f64 / initial_max_fee).max(1.0);
+                max_multiplier.powf(progress)
+            }
+        };
+        let scale = |fee: u64| (fee as f64 * multiplier) as u64;
+        let mut fees = TxFees {
+            base_fee_per_gas: scale(initial_fees.base_fee_per_gas)
+                .max(current_fees.base_fee_per_gas),
+            priority_fee_per_gas: scale(initial_fees.priority_fee_per_gas)
+                .max(current_fees.priority_fee_per_gas),
+        };
+
+        // L1 nodes only accept a replacement transaction if it increases both the max fee and the priority fee
+        // by at least 10%.
+        let min_priority_fee = bump_by_10_percent(previous_fees.priority_fee_per_gas);
+        let min_max_fee = bump_by_10_percent(previous_fees.max_fee_per_gas());
+        fees.priority_fee_per_gas = fees.priority_fee_per_gas.max(min_priority_fee);
+        fees.base_fee_per_gas = fees
+            .base_fee_per_gas
+            .max(min_max_fee.saturating_sub(fees.priority_fee_per_gas));
+
+        let capped_fees = self.cap_fees(tx_type, fees);
+        if capped_fees.priority_fee_per_gas < min_priority_fee
+            || capped_fees.max_fee_per_gas() < min_max_fee
+        {
+            return Escalation::Capped;
+        }
+        Escalation::Bump(capped_fees)
+    }
+}
+
+fn bump_by_10_percent(fee: u64) -> u64 {
+    fee.saturating_add(fee / 10).saturating_add(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_types::H256;
+
+    use super::*;
+
+    fn attempt(base_fee_per_gas: u64, priority_fee_per_gas: u64, sent_at_block: u32) -> TxHistory {
+        TxHistory {
+            id: 0,
+            eth_tx_id: 0,
+            base_fee_per_gas,
+            priority_fee_per_gas,
+            tx_hash: H256::zero(),
+            signed_raw_tx: vec![],
+            sent_at_block: Some(sent_at_block),
+            blob_base_fee_per_gas: None,
+        }
+    }
+
+    fn escalator(params: GasEscalationParams) -> GasEscalator {
+        GasEscalator::new(GasEscalationConfig {
+            commit: params,
+            ..GasEscalationConfig::default()
+        })
+    }
+
+    const CURRENT_FEES: TxFees = TxFees {
+        base_fee_per_gas: 10,
+        priority_fee_per_gas: 1,
+    };
+
+    #[test]
+    fn linear_and_exponential_escalation() {
+        let attempts = [attempt(1_200, 120, 10), attempt(1_000, 100, 5)];
+        let linear = escalator(GasEscalationParams {
+            policy: GasEscalationPolicy::Linear,
+            bump_ratio: Some(0.5),
+            ..GasEscalationParams::default()
+        });
+        let escalation = linear.escalate(
+            AggregatedActionType::Commit,
+            &attempts,
+            20,
+            15,
+            CURRENT_FEES,
+        );
+        assert_eq!(
+            escalation,
+            Escalation::Bump(TxFees {
+                base_fee_per_gas: 2_000,
+                priority_fee_per_gas: 200,
+            })
+        );
+        // Resend interval isn't reached yet.
+        let escalation =
+            linear.escalate(AggregatedActionType::Commit, &attempts, 11, 6, CURRENT_FEES);
+        assert_eq!(escalation, Escalation::Wait);
+
+        let exponential = escalator(GasEscalationParams {
+            policy: GasEscalationPolicy::Exponential,
+            bump_ratio: Some(0.5),
+            ..GasEscalationParams::default()
+        });
+        let escalation = exponential.escalate(
+            AggregatedActionType::Commit,
+            &attempts,
+            20,
+            15,
+            CURRENT_FEES,
+        );
+        assert_eq!(
+            escalation,
+            Escalation::Bump(TxFees {
+                base_fee_per_gas: 2_250,
+                priority_fee_per_gas: 225,
+            })
+        );
+    }
+
+    #[test]
+    fn escalation_respects_replacement_rules_and_cap() {
+        let attempts = [attempt(1_000, 100, 10)];
+        let params = GasEscalationParams {
+            policy: GasEscalationPolicy::Linear,
+            bump_ratio: Some(0.01),
+            max_fee_per_gas: Some(1_300),
+            ..GasEscalationParams::default()
+        };
+        let escalation = escalator(params).escalate(
+            AggregatedActionType::Commit,
+            &attempts,
+            20,
+            10,
+            CURRENT_FEES,
+        );
+        assert_eq!(
+            escalation,
+            Escalation::Bump(TxFees {
+                base_fee_per_gas: 1_100,
+                priority_fee_per_gas: 111,
+            })
+        );
+
+        let capped_params = GasEscalationParams {
+            max_fee_per_gas: Some(1_150),
+            ..params
+        };
+        let escalation = escalator(capped_params).escalate(
+            AggregatedActionType::Commit,
+            &attempts,
+            20,
+            10,
+            CURRENT_FEES,
+        );
+        assert_eq!(escalation, Escalation::Capped);
+        // Other transaction types are not affected.
+        let fees = escalator(capped_params).cap_fees(
+            AggregatedActionType::Execute,
+            TxFees {
+                base_fee_per_gas: 2_000,
+                priority_fee_per_gas: 200,
+            },
+        );
+        assert_eq!(fees.max_fee_per_gas(), 2_200);
+    }
+
+    #[test]
+    fn deadline_escalation() {
+        let attempts = [attempt(1_000, 0, 0)];
+        let deadline = escalator(GasEscalationParams {
+            policy: GasEscalationPolicy::Deadline,
+            deadline_blocks: Some(10),
+            max_fee_per_gas: Some(4_000),
+            ..GasEscalationParams::default()
+        });
+        let escalation =
+            deadline.escalate(AggregatedActionType::Commit, &attempts, 5, 5, CURRENT_FEES);
+        assert_eq!(
+            escalation,
+            Escalation::Bump(TxFees {
+                base_fee_per_gas: 2_000,
+                priority_fee_per_gas: 1,
+            })
+        );
+        let escalation = deadline.escalate(
+            AggregatedActionType::Commit,
+            &attempts,
+            20,
+            20,
+            CURRENT_FEES,
+        );
+        assert_eq!(
+            escalation,
+            Escalation::Bump(TxFees {
+                base_fee_per_gas: 3_999,
+                priority_fee_per_gas: 1,
+            })
+        );
+    }
+}
@@ -7,6 +7,7 @@ use zksync_dal::StorageProcessor;
 use zksync_types::{aggregated_operations::AggregatedActionType, eth_sender::EthTx};
 use zksync_utils::time::seconds_since_epoch;
 
+use super::stuck_tx_tracker::StuckTxKind;
 use crate::metrics::{BlockL1Stage, BlockStage, APP_METRICS};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelSet, EncodeLabelValue)]
@@ -18,6 +19,47 @@ pub(super) enum PubdataKind {
     RepeatedWritesCompressed,
 }
 
+/// Reason for publishing pubdata in calldata although publishing it in blobs is enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelSet, EncodeLabelValue)]
+#[metrics(label = "reason", rename_all = "snake_case")]
+pub(super) enum BlobFallbackReason {
+    /// L1 doesn't report the blob base fee.
+    NoBlobSupport,
+    /// Pubdata of aggregated L1 batches doesn't fit into a single transaction.
+    TooManyBlobs,
+    /// Publishing pubdata in blobs is more expensive than in calldata.
+    BlobFeeSpike,
+}
+
+/// Reason for rotating the active operator account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelSet, EncodeLabelValue)]
+#[metrics(label = "reason", rename_all = "snake_case")]
+pub(super) enum OperatorRotationReason {
+    /// Account nonce doesn't increase although the account has pending transactions.
+    StuckNonce,
+    /// Account balance is below the configured minimum.
+    LowBalance,
+}
+
+impl OperatorRotationReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::StuckNonce => "stuck_nonce",
+            Self::LowBalance => "low_balance",
+        }
+    }
+}
+
+/// Action taken on a stuck L1 transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelSet, EncodeLabelValue)]
+#[metrics(label = "action", rename_all = "snake_case")]
+pub(super) enum StuckTxRemediation {
+    /// A fee-starved transaction was re-sent with current fees.
+    Replace,
+    /// A nonce gap was closed with a zero-value self-transfer.
+    FillNonceGap,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelSet, EncodeLabelValue)]
 #[metrics(label = "type")]
 pub(super) struct ActionTypeLabel(AggregatedActionType);
@@ -87,6 +129,32 @@ pub(super) struct EthSenderMetrics {
     pub l1_blocks_waited_in_mempool: Family<ActionTypeLabel, Histogram<u64>>,
     /// Number of L1 batches aggregated for publishing with a specific reason.
     pub block_aggregation_reason: Family<AggregationReasonLabels, Counter>,
+    /// Number of blobs in commit transactions publishing pubdata in blobs.
+    #[metrics(buckets = Buckets::linear(1.0..=6.0, 1.0))]
+    pub blobs_per_tx: Histogram<usize>,
+    /// Number of commit transactions that published pubdata in calldata although blobs are enabled.
+    pub blob_fallbacks: Family<BlobFallbackReason, Counter>,
+    #[metrics(buckets = FEE_BUCKETS)]
+    pub used_blob_base_fee_per_gas: Histogram<u64>,
+    /// Number of rotations of the active operator account.
+    pub operator_rotations: Family<OperatorRotationReason, Counter>,
+    /// Number of resent transactions with bumped fees.
+    pub fee_bumps: Family<ActionTypeLabel, Counter>,
+    /// Ratio of the max fee per gas of a resent transaction to the max fee per gas of its previous attempt.
+    #[metrics(buckets = Buckets::values(&[1.1, 1.2, 1.5, 2.0, 3.0, 5.0, 10.0]))]
+    pub fee_bump_ratio: Family<ActionTypeLabel, Histogram<f64>>,
+    /// Number of skipped resends because bumped fees would exceed the configured cap.
+    pub capped_resends: Family<ActionTypeLabel, Counter>,
+    /// Number of transactions simulated in the dry-run mode.
+    pub dry_run_simulations: Family<ActionTypeLabel, Counter>,
+    /// Number of transactions that reverted when simulated in the dry-run mode.
+    pub dry_run_reverts: Family<ActionTypeLabel, Counter>,
+    /// Current number of stuck in-flight transactions.
+    pub stuck_txs: Family<StuckTxKind, Gauge<usize>>,
+    /// Number of detected stuck in-flight transactions.
+    pub stuck_tx_detections: Family<StuckTxKind, Counter>,
+    /// Number of automatic remediation actions taken on stuck transactions.
+    pub stuck_tx_remediations: Family<StuckTxRemediation, Counter>,
 }
 
 impl EthSenderMetrics {
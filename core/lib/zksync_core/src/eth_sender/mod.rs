@@ -1,15 +1,24 @@
+mod aggregation_policy;
 mod aggregator;
 mod error;
 mod eth_tx_aggregator;
 mod eth_tx_manager;
+mod gas_escalation;
 mod metrics;
 mod publish_criterion;
+mod stuck_tx_tracker;
 mod zksync_functions;
 
 #[cfg(test)]
 mod tests;
 
 pub use self::{
-    aggregator::Aggregator, error::ETHSenderError, eth_tx_aggregator::EthTxAggregator,
+    aggregation_policy::{
+        simulate_aggregation, validate_policy, AggregationPolicyHandle,
+        AggregationSimulationReport, OperationSimulationReport, SimulatedL1Batch,
+    },
+    aggregator::Aggregator,
+    error::ETHSenderError,
+    eth_tx_aggregator::EthTxAggregator,
     eth_tx_manager::EthTxManager,
 };
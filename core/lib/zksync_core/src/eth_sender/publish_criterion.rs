@@ -10,6 +10,11 @@ use zksync_types::{
 use super::metrics::METRICS;
 use crate::gas_tracker::agg_l1_batch_base_cost;
 
+/// Size of the `StoredBlockInfo` Solidity struct.
+pub(super) const STORED_BLOCK_INFO_SIZE: usize = 96;
+/// We're not sure our gas predictions are accurate, so it's safer to lower the gas limit by 10%.
+pub(super) const GAS_LIMIT_SAFETY_FACTOR: f64 = 0.9;
+
 #[async_trait]
 pub trait L1BatchPublishCriterion: fmt::Debug + Send + Sync {
     // Takes `&self` receiver for the trait to be object-safe
@@ -159,8 +164,8 @@ impl L1BatchPublishCriterion for GasCriterion {
             self.gas_limit > base_cost,
             "Config max gas cost for operations is too low"
         );
-        // We're not sure our predictions are accurate, so it's safer to lower the gas limit by 10%
-        let mut gas_left = (self.gas_limit as f64 * 0.9).round() as u32 - base_cost;
+        let mut gas_left =
+            (self.gas_limit as f64 * GAS_LIMIT_SAFETY_FACTOR).round() as u32 - base_cost;
 
         let mut last_l1_batch = None;
         for (index, l1_batch) in consecutive_l1_batches.iter().enumerate() {
@@ -211,7 +216,6 @@ impl L1BatchPublishCriterion for DataSizeCriterion {
         consecutive_l1_batches: &[L1BatchWithMetadata],
         _last_sealed_l1_batch: L1BatchNumber,
     ) -> Option<L1BatchNumber> {
-        const STORED_BLOCK_INFO_SIZE: usize = 96; // size of `StoredBlockInfo` solidity struct
         let mut data_size_left = self.data_limit - STORED_BLOCK_INFO_SIZE;
 
         for (index, l1_batch) in consecutive_l1_batches.iter().enumerate() {
@@ -0,0 +1,400 @@
+//! Detection of in-flight L1 transactions that are unlikely to get mined or finalized without intervention.
+
+use std::{
+    collections::{HashMap, HashSet},
+    ops,
+};
+
+use serde::Serialize;
+use vise::{EncodeLabelSet, EncodeLabelValue};
+use zksync_health_check::{Health, HealthStatus, HealthUpdater, ReactiveHealthCheck};
+use zksync_types::{Address, L1BlockNumber, Nonce, H256};
+
+use super::metrics::METRICS;
+
+/// Kind of a problem affecting an in-flight L1 transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, EncodeLabelSet, EncodeLabelValue)]
+#[serde(rename_all = "snake_case")]
+#[metrics(label = "kind", rename_all = "snake_case")]
+pub(super) enum StuckTxKind {
+    /// The transaction cannot be mined because no transaction with a lower nonce is pending
+    /// for the operator account.
+    NonceGap,
+    /// The transaction is pending for a long time, and its base fee is below the minimum for the next L1 block.
+    FeeStarved,
+    /// The transaction was mined, but has been dropped from the canonical chain by an L1 reorg.
+    Reorged,
+}
+
+impl StuckTxKind {
+    const ALL: [Self; 3] = [Self::NonceGap, Self::FeeStarved, Self::Reorged];
+}
+
+/// Information about an in-flight transaction necessary to check whether it's stuck.
+#[derive(Debug, Clone)]
+pub(super) struct InflightTxInfo {
+    pub eth_tx_id: u32,
+    pub nonce: Nonce,
+    /// Operator account as stored in the DB, i.e. `None` for the main account.
+    pub operator: Option<Address>,
+    /// L1 block at which the first attempt was sent; `None` if the transaction wasn't sent yet.
+    pub first_sent_at_block: Option<u32>,
+    /// L1 block at which the last attempt was sent.
+    pub last_sent_at_block: Option<u32>,
+    /// Base fee per gas of the last attempt.
+    pub last_base_fee_per_gas: Option<u64>,
+    /// Hash of the L1 block containing the transaction receipt. Only queried for mined transactions.
+    pub receipt_block_hash: Option<H256>,
+}
+
+/// Nonces of an operator account on L1.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct OperatorNonce {
+    // Nonce on finalized block
+    pub finalized: Nonce,
+    // Nonce on latest block
+    pub latest: Nonce,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(super) struct StuckTx {
+    pub eth_tx_id: u32,
+    pub nonce: Nonce,
+    pub operator: Option<Address>,
+    pub kind: StuckTxKind,
+    /// Number of L1 blocks since the first attempt of the transaction was sent.
+    pub pending_blocks: u32,
+}
+
+/// Range of nonces of an operator account not used by any pending transaction, which blocks
+/// transactions with greater nonces.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct NonceGap {
+    pub operator: Option<Address>,
+    pub nonces: ops::Range<u32>,
+}
+
+#[derive(Debug, Default)]
+pub(super) struct StuckTxsReport {
+    pub stuck_txs: Vec<StuckTx>,
+    pub nonce_gaps: Vec<NonceGap>,
+}
+
+impl StuckTxsReport {
+    pub fn txs_of_kind(&self, kind: StuckTxKind) -> impl Iterator<Item = &StuckTx> + '_ {
+        self.stuck_txs.iter().filter(move |tx| tx.kind == kind)
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct StuckTxsHealthDetails<'a> {
+    l1_block_number: L1BlockNumber,
+    stuck_txs: &'a [StuckTx],
+}
+
+/// Tracks in-flight transactions across L1 blocks and reports stuck ones via logs, metrics and a health check.
+/// Stuck transactions don't affect the health status, since they don't make the node unable to serve requests.
+#[derive(Debug)]
+pub(super) struct StuckTxTracker {
+    threshold_blocks: u32,
+    health_updater: HealthUpdater,
+    /// Latest nonces of operator accounts observed during the previous check.
+    latest_nonces: HashMap<Option<Address>, Nonce>,
+    /// Receipt block hashes of mined, but not finalized transactions observed during previous checks.
+    mined_txs: HashMap<u32, H256>,
+    /// Stuck transactions reported during the previous check, used to log newly detected ones only.
+    reported_txs: HashSet<(u32, StuckTxKind)>,
+}
+
+impl StuckTxTracker {
+    pub fn new(threshold_blocks: u64) -> Self {
+        let (_, health_updater) = ReactiveHealthCheck::new("eth_tx_manager");
+        health_updater.update(HealthStatus::Ready.into());
+        Self {
+            threshold_blocks: threshold_blocks.try_into().unwrap_or(u32::MAX),
+            health_updater,
+            latest_nonces: HashMap::new(),
+            mined_txs: HashMap::new(),
+            reported_txs: HashSet::new(),
+        }
+    }
+
+    pub fn health_check(&self) -> ReactiveHealthCheck {
+        self.health_updater.subscribe()
+    }
+
+    /// Checks in-flight transactions. `txs` must contain all in-flight transactions, and `operator_nonces`
+    /// must contain nonces for all operator accounts sending them.
+    pub fn check(
+        &mut self,
+        l1_block_number: L1BlockNumber,
+        next_block_minimal_base_fee: u64,
+        operator_nonces: &HashMap<Option<Address>, OperatorNonce>,
+        txs: &[InflightTxInfo],
+    ) -> StuckTxsReport {
+        let mut txs_by_operator: HashMap<_, Vec<_>> = HashMap::new();
+        for tx in txs {
+            txs_by_operator.entry(tx.operator).or_default().push(tx);
+        }
+
+        let mut report = StuckTxsReport::default();
+        for (operator, mut operator_txs) in txs_by_operator {
+            let Some(operator_nonce) = operator_nonces.get(&operator) else {
+                continue;
+            };
+            operator_txs.sort_unstable_by_key(|tx| tx.nonce);
+            let previous_latest_nonce = self
+                .latest_nonces
+                .insert(operator, operator_nonce.latest)
+                .unwrap_or(operator_nonce.latest);
+
+            let mut expected_nonce = operator_nonce.latest;
+            let mut is_blocked = false;
+            for tx in operator_txs {
+                let pending_blocks = tx
+                    .first_sent_at_block
+                    .map_or(0, |block| l1_block_number.0.saturating_sub(block));
+                let kind = if tx.nonce < operator_nonce.latest {
+                    self.check_mined_tx(tx, operator_nonce)
+                } else {
+                    if tx.nonce > expected_nonce {
+                        report.nonce_gaps.push(NonceGap {
+                            operator,
+                            nonces: expected_nonce.0..tx.nonce.0,
+                        });
+                        is_blocked = true;
+                    }
+                    expected_nonce = tx.nonce.next();
+
+                    if tx.nonce < previous_latest_nonce {
+                        // The operator nonce has decreased, i.e., the transaction was mined in a block
+                        // that is no longer canonical.
+                        Some(StuckTxKind::Reorged)
+                    } else if is_blocked {
+                        Some(StuckTxKind::NonceGap)
+                    } else {
+                        self.is_fee_starved(tx, l1_block_number, next_block_minimal_base_fee)
+                            .then_some(StuckTxKind::FeeStarved)
+                    }
+                };
+
+                if let Some(kind) = kind {
+                    report.stuck_txs.push(StuckTx {
+                        eth_tx_id: tx.eth_tx_id,
+                        nonce: tx.nonce,
+                        operator,
+                        kind,
+                        pending_blocks,
+                    });
+                }
+            }
+        }
+
+        let inflight_ids: HashSet<_> = txs.iter().map(|tx| tx.eth_tx_id).collect();
+        self.mined_txs.retain(|id, _| inflight_ids.contains(id));
+        report.stuck_txs.sort_unstable_by_key(|tx| tx.eth_tx_id);
+        report
+            .nonce_gaps
+            .sort_unstable_by_key(|gap| (gap.operator, gap.nonces.start));
+        self.report(l1_block_number, &report);
+        report
+    }
+
+    fn check_mined_tx(
+        &mut self,
+        tx: &InflightTxInfo,
+        operator_nonce: &OperatorNonce,
+    ) -> Option<StuckTxKind> {
+        let Some(block_hash) = tx.receipt_block_hash else {
+            // Either the receipt has disappeared because of a reorg, or the nonce was consumed
+            // by a transaction not sent by the ETH tx manager. We only report a transaction
+            // if we're sure that it's not a temporary glitch of the L1 node.
+            let had_receipt = self.mined_txs.remove(&tx.eth_tx_id).is_some();
+            let is_finalized = tx.nonce < operator_nonce.finalized;
+            return (had_receipt || is_finalized).then_some(StuckTxKind::Reorged);
+        };
+
+        let previous_block_hash = self.mined_txs.insert(tx.eth_tx_id, block_hash);
+        match previous_block_hash {
+            Some(previous_hash) if previous_hash != block_hash => Some(StuckTxKind::Reorged),
+            _ => None,
+        }
+    }
+
+    fn is_fee_starved(
+        &self,
+        tx: &InflightTxInfo,
+        l1_block_number: L1BlockNumber,
+        next_block_minimal_base_fee: u64,
+    ) -> bool {
+        let (Some(first_sent_at_block), Some(last_sent_at_block), Some(base_fee_per_gas)) = (
+            tx.first_sent_at_block,
+            tx.last_sent_at_block,
+            tx.last_base_fee_per_gas,
+        ) else {
+            return false;
+        };
+        // The transaction has just been (re)sent with fees corresponding to the current L1 state.
+        if last_sent_at_block >= l1_block_number.0 {
+            return false;
+        }
+        let pending_blocks = l1_block_number.0.saturating_sub(first_sent_at_block);
+        pending_blocks >= self.threshold_blocks && base_fee_per_gas < next_block_minimal_base_fee
+    }
+
+    fn report(&mut self, l1_block_number: L1BlockNumber, report: &StuckTxsReport) {
+        for kind in StuckTxKind::ALL {
+            METRICS.stuck_txs[&kind].set(report.txs_of_kind(kind).count());
+        }
+
+        let reported_txs: HashSet<_> = report
+            .stuck_txs
+            .iter()
+            .map(|tx| (tx.eth_tx_id, tx.kind))
+            .collect();
+        for tx in &report.stuck_txs {
+            if !self.reported_txs.contains(&(tx.eth_tx_id, tx.kind)) {
+                METRICS.stuck_tx_detections[&tx.kind].inc();
+                tracing::warn!(
+                    "Detected stuck L1 tx {} (nonce {}, operator {:?}): {:?}, pending for {} L1 blocks",
+                    tx.eth_tx_id,
+                    tx.nonce,
+                    tx.operator,
+                    tx.kind,
+                    tx.pending_blocks
+                );
+            }
+        }
+        self.reported_txs = reported_txs;
+
+        let details = StuckTxsHealthDetails {
+            l1_block_number,
+            stuck_txs: &report.stuck_txs,
+        };
+        self.health_updater
+            .update(Health::from(HealthStatus::Ready).with_details(details));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_health_check::CheckHealth;
+
+    use super::*;
+
+    const THRESHOLD_BLOCKS: u64 = 10;
+
+    fn pending_tx(eth_tx_id: u32, nonce: u32, sent_at_block: u32, base_fee: u64) -> InflightTxInfo {
+        InflightTxInfo {
+            eth_tx_id,
+            nonce: Nonce(nonce),
+            operator: None,
+            first_sent_at_block: Some(sent_at_block),
+            last_sent_at_block: Some(sent_at_block),
+            last_base_fee_per_gas: Some(base_fee),
+            receipt_block_hash: None,
+        }
+    }
+
+    fn operator_nonces(finalized: u32, latest: u32) -> HashMap<Option<Address>, OperatorNonce> {
+        let nonce = OperatorNonce {
+            finalized: Nonce(finalized),
+            latest: Nonce(latest),
+        };
+        HashMap::from([(None, nonce)])
+    }
+
+    fn stuck_kinds(report: &StuckTxsReport) -> Vec<(u32, StuckTxKind)> {
+        report
+            .stuck_txs
+            .iter()
+            .map(|tx| (tx.eth_tx_id, tx.kind))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn detecting_fee_starved_txs() {
+        let mut tracker = StuckTxTracker::new(THRESHOLD_BLOCKS);
+        let txs = [
+            pending_tx(1, 0, 100, 10),
+            pending_tx(2, 1, 105, 10),
+            pending_tx(3, 2, 100, 1_000),
+        ];
+
+        let report = tracker.check(L1BlockNumber(110), 100, &operator_nonces(0, 0), &txs);
+        assert_eq!(stuck_kinds(&report), [(1, StuckTxKind::FeeStarved)]);
+        assert!(report.nonce_gaps.is_empty());
+
+        // A resent tx is not reported.
+        let mut resent_tx = txs[0].clone();
+        resent_tx.last_sent_at_block = Some(115);
+        let report = tracker.check(
+            L1BlockNumber(115),
+            100,
+            &operator_nonces(0, 0),
+            &[resent_tx, txs[1].clone(), txs[2].clone()],
+        );
+        assert_eq!(stuck_kinds(&report), [(2, StuckTxKind::FeeStarved)]);
+
+        let health = tracker.health_check().check_health().await;
+        assert_eq!(health.status(), HealthStatus::Ready);
+    }
+
+    #[test]
+    fn detecting_nonce_gaps() {
+        let mut tracker = StuckTxTracker::new(THRESHOLD_BLOCKS);
+        let txs = [pending_tx(1, 5, 100, 10), pending_tx(2, 7, 100, 10)];
+
+        let report = tracker.check(L1BlockNumber(101), 1, &operator_nonces(3, 4), &txs);
+        assert_eq!(
+            stuck_kinds(&report),
+            [(1, StuckTxKind::NonceGap), (2, StuckTxKind::NonceGap)]
+        );
+        assert_eq!(
+            report.nonce_gaps,
+            [
+                NonceGap {
+                    operator: None,
+                    nonces: 4..5,
+                },
+                NonceGap {
+                    operator: None,
+                    nonces: 6..7,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn detecting_reorged_txs() {
+        let mut tracker = StuckTxTracker::new(THRESHOLD_BLOCKS);
+        let mut mined_tx = pending_tx(1, 0, 100, 10);
+        mined_tx.receipt_block_hash = Some(H256::repeat_byte(1));
+        let txs = [
+            mined_tx.clone(),
+            pending_tx(2, 1, 100, 10),
+            pending_tx(3, 2, 101, 10),
+        ];
+
+        let report = tracker.check(L1BlockNumber(102), 1, &operator_nonces(0, 2), &txs);
+        assert!(report.stuck_txs.is_empty(), "{report:?}");
+
+        // Tx #1 is included into another block, and tx #2 is dropped from the chain.
+        mined_tx.receipt_block_hash = Some(H256::repeat_byte(2));
+        let txs = [
+            mined_tx,
+            pending_tx(2, 1, 100, 10),
+            pending_tx(3, 2, 101, 10),
+        ];
+        let report = tracker.check(L1BlockNumber(103), 1, &operator_nonces(0, 1), &txs);
+        assert_eq!(
+            stuck_kinds(&report),
+            [(1, StuckTxKind::Reorged), (2, StuckTxKind::Reorged)]
+        );
+
+        // A finalized nonce without a receipt is a sign of a deep reorg as well.
+        let txs = [pending_tx(1, 0, 100, 10)];
+        let report = tracker.check(L1BlockNumber(104), 1, &operator_nonces(1, 1), &txs);
+        assert_eq!(stuck_kinds(&report), [(1, StuckTxKind::Reorged)]);
+    }
+}
@@ -3,29 +3,37 @@ use std::sync::Arc;
 use assert_matches::assert_matches;
 use once_cell::sync::Lazy;
 use zksync_config::{
-    configs::eth_sender::{ProofSendingMode, SenderConfig},
+    configs::eth_sender::{
+        GasEscalationConfig, ProofSendingMode, PubdataSendingMode, SenderConfig,
+        StuckTxRemediationPolicy,
+    },
     ContractsConfig, ETHSenderConfig, GasAdjusterConfig,
 };
-use zksync_dal::{ConnectionPool, StorageProcessor};
-use zksync_eth_client::{clients::MockEthereum, EthInterface};
+use zksync_dal::{eth_sender_dal::ActiveOperator, ConnectionPool, StorageProcessor};
+use zksync_eth_client::{clients::MockEthereum, BoundEthInterface, EthInterface};
+use zksync_health_check::{CheckHealth, HealthStatus};
 use zksync_object_store::ObjectStoreFactory;
 use zksync_types::{
     aggregated_operations::{
         AggregatedOperation, L1BatchCommitOperation, L1BatchExecuteOperation, L1BatchProofOperation,
     },
     block::L1BatchHeader,
-    commitment::{L1BatchMetaParameters, L1BatchMetadata, L1BatchWithMetadata},
+    commitment::{
+        L1BatchCommitmentMode, L1BatchMetaParameters, L1BatchMetadata, L1BatchWithMetadata,
+    },
     ethabi::Token,
     helpers::unix_timestamp_ms,
     web3::contract::Error,
-    Address, L1BatchNumber, L1BlockNumber, ProtocolVersionId, H256,
+    Address, L1BatchNumber, L1BlockNumber, Nonce, ProtocolVersionId, H256,
 };
 
 use crate::{
+    da_dispatcher,
     eth_sender::{
-        eth_tx_manager::L1BlockNumbers, Aggregator, ETHSenderError, EthTxAggregator, EthTxManager,
+        eth_tx_manager::L1BlockNumbers, stuck_tx_tracker::StuckTxKind, AggregationPolicyHandle,
+        Aggregator, ETHSenderError, EthTxAggregator, EthTxManager,
     },
-    l1_gas_price::GasAdjuster,
+    l1_gas_price::{GasAdjuster, L1TxParamsProvider},
     utils::testonly::create_l1_batch,
 };
 
@@ -54,11 +62,29 @@ struct EthSenderTester {
 impl EthSenderTester {
     const WAIT_CONFIRMATIONS: u64 = 10;
     const MAX_BASE_FEE_SAMPLES: usize = 3;
+    const BLOB_BASE_FEE: u64 = 5;
 
     async fn new(
         connection_pool: ConnectionPool,
         history: Vec<u64>,
         non_ordering_confirmations: bool,
+    ) -> Self {
+        Self::with_pubdata_sending_mode(
+            connection_pool,
+            history,
+            non_ordering_confirmations,
+            PubdataSendingMode::Calldata,
+            L1BatchCommitmentMode::Rollup,
+        )
+        .await
+    }
+
+    async fn with_pubdata_sending_mode(
+        connection_pool: ConnectionPool,
+        history: Vec<u64>,
+        non_ordering_confirmations: bool,
+        pubdata_sending_mode: PubdataSendingMode,
+        commitment_mode: L1BatchCommitmentMode,
     ) -> Self {
         let eth_sender_config = ETHSenderConfig::for_tests();
         let contracts_config = ContractsConfig::for_tests();
@@ -66,6 +92,12 @@ impl EthSenderTester {
             aggregated_proof_sizes: vec![1],
             ..eth_sender_config.sender.clone()
         };
+        let blob_fee_history = match pubdata_sending_mode {
+            PubdataSendingMode::Calldata | PubdataSendingMode::Custom => vec![],
+            PubdataSendingMode::Blobs => {
+                vec![Self::BLOB_BASE_FEE; Self::WAIT_CONFIRMATIONS as usize + history.len()]
+            }
+        };
 
         let gateway = Arc::new(
             MockEthereum::default()
@@ -75,6 +107,7 @@ impl EthSenderTester {
                         .chain(history)
                         .collect(),
                 )
+                .with_blob_fee_history(blob_fee_history)
                 .with_non_ordering_confirmation(non_ordering_confirmations)
                 .with_multicall_address(contracts_config.l1_multicall3_addr),
         );
@@ -89,6 +122,7 @@ impl EthSenderTester {
                     pricing_formula_parameter_b: 2.0,
                     ..eth_sender_config.gas_adjuster
                 },
+                pubdata_sending_mode,
             )
             .await
             .unwrap(),
@@ -98,25 +132,31 @@ impl EthSenderTester {
         let aggregator = EthTxAggregator::new(
             SenderConfig {
                 proof_sending_mode: ProofSendingMode::SkipEveryProof,
+                pubdata_sending_mode,
                 ..eth_sender_config.sender.clone()
             },
             // Aggregator - unused
             Aggregator::new(
                 aggregator_config.clone(),
                 store_factory.create_store().await,
+                AggregationPolicyHandle::new(&aggregator_config).unwrap(),
             ),
             gateway.clone(),
+            gas_adjuster.clone(),
             // zkSync contract address
             Address::random(),
             contracts_config.l1_multicall3_addr,
             Address::random(),
             0,
+            commitment_mode,
         );
 
         let manager = EthTxManager::new(
             eth_sender_config.sender,
+            eth_sender_config.gas_escalation,
             gas_adjuster.clone(),
             gateway.clone(),
+            vec![],
         );
         Self {
             gateway,
@@ -602,6 +642,200 @@ async fn correct_order_for_confirmations() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn publishing_pubdata_in_blobs() {
+    let connection_pool = ConnectionPool::test_pool().await;
+    let mut tester = EthSenderTester::with_pubdata_sending_mode(
+        connection_pool,
+        vec![10; 100],
+        false,
+        PubdataSendingMode::Blobs,
+        L1BatchCommitmentMode::Rollup,
+    )
+    .await;
+    tester.gateway.advance_block_number(3);
+    tester.gas_adjuster.keep_updated().await.unwrap();
+    insert_genesis_protocol_version(&tester).await;
+    let genesis_l1_batch = insert_l1_batch(&tester, L1BatchNumber(0)).await;
+    let mut first_l1_batch = insert_l1_batch(&tester, L1BatchNumber(1)).await;
+    // Pubdata is large enough for blobs to be cheaper than calldata.
+    first_l1_batch.pubdata_input = Some(vec![1; 100_000]);
+
+    let operation = AggregatedOperation::Commit(L1BatchCommitOperation {
+        last_committed_l1_batch: l1_batch_with_metadata(genesis_l1_batch),
+        l1_batches: vec![l1_batch_with_metadata(first_l1_batch.clone())],
+    });
+    let tx = tester
+        .aggregator
+        .save_eth_tx(&mut tester.storage().await, &operation, false)
+        .await
+        .unwrap();
+    let sidecar = tx.blob_sidecar.as_ref().expect("no blobs in commit tx");
+    assert_eq!(sidecar.blobs.len(), 1);
+
+    let hash = tester
+        .manager
+        .send_eth_tx(
+            &mut tester.storage().await,
+            &tx,
+            0,
+            L1BlockNumber(tester.gateway.block_number("").await.unwrap().as_u32()),
+        )
+        .await
+        .unwrap();
+    let history = tester
+        .storage()
+        .await
+        .eth_sender_dal()
+        .get_tx_history_to_check(tx.id)
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].tx_hash, hash);
+    assert_eq!(
+        history[0].blob_base_fee_per_gas,
+        Some(EthSenderTester::BLOB_BASE_FEE)
+    );
+
+    // Small pubdata is cheaper to publish in calldata.
+    let second_l1_batch = insert_l1_batch(&tester, L1BatchNumber(2)).await;
+    let operation = AggregatedOperation::Commit(L1BatchCommitOperation {
+        last_committed_l1_batch: l1_batch_with_metadata(first_l1_batch),
+        l1_batches: vec![l1_batch_with_metadata(second_l1_batch)],
+    });
+    let tx = tester
+        .aggregator
+        .save_eth_tx(&mut tester.storage().await, &operation, false)
+        .await
+        .unwrap();
+    assert!(tx.blob_sidecar.is_none());
+}
+
+#[tokio::test]
+async fn not_publishing_pubdata_in_validium_mode() {
+    let connection_pool = ConnectionPool::test_pool().await;
+    let mut tester = EthSenderTester::with_pubdata_sending_mode(
+        connection_pool,
+        vec![10; 100],
+        false,
+        PubdataSendingMode::Blobs,
+        L1BatchCommitmentMode::Validium,
+    )
+    .await;
+    tester.gateway.advance_block_number(3);
+    tester.gas_adjuster.keep_updated().await.unwrap();
+    insert_genesis_protocol_version(&tester).await;
+    let genesis_l1_batch = insert_l1_batch(&tester, L1BatchNumber(0)).await;
+    let mut first_l1_batch = insert_l1_batch(&tester, L1BatchNumber(1)).await;
+    first_l1_batch.pubdata_input = Some(vec![1; 100_000]);
+
+    let operation = AggregatedOperation::Commit(L1BatchCommitOperation {
+        last_committed_l1_batch: l1_batch_with_metadata(genesis_l1_batch),
+        l1_batches: vec![l1_batch_with_metadata(first_l1_batch)],
+    });
+    let tx = tester
+        .aggregator
+        .save_eth_tx(&mut tester.storage().await, &operation, false)
+        .await
+        .unwrap();
+    // Neither blobs nor calldata should contain pubdata.
+    assert!(tx.blob_sidecar.is_none());
+    assert!(tx.raw_tx.len() < 10_000, "{}", tx.raw_tx.len());
+}
+
+#[tokio::test]
+async fn referencing_da_inclusion_data_in_commit_tx() {
+    let connection_pool = ConnectionPool::test_pool().await;
+    let tester = EthSenderTester::with_pubdata_sending_mode(
+        connection_pool,
+        vec![10; 100],
+        false,
+        PubdataSendingMode::Custom,
+        L1BatchCommitmentMode::Validium,
+    )
+    .await;
+    insert_genesis_protocol_version(&tester).await;
+    let genesis_l1_batch = insert_l1_batch(&tester, L1BatchNumber(0)).await;
+    let first_l1_batch = insert_l1_batch(&tester, L1BatchNumber(1)).await;
+    let inclusion_data = [0xab; 32];
+    let mut storage = tester.storage().await;
+    storage
+        .data_availability_dal()
+        .insert_l1_batch_da(L1BatchNumber(1), "blob", chrono::Utc::now().naive_utc())
+        .await
+        .unwrap();
+    storage
+        .data_availability_dal()
+        .save_l1_batch_inclusion_data(L1BatchNumber(1), &inclusion_data)
+        .await
+        .unwrap();
+
+    let operation = AggregatedOperation::Commit(L1BatchCommitOperation {
+        last_committed_l1_batch: l1_batch_with_metadata(genesis_l1_batch),
+        l1_batches: vec![l1_batch_with_metadata(first_l1_batch)],
+    });
+    let tx = tester
+        .aggregator
+        .save_eth_tx(&mut storage, &operation, false)
+        .await
+        .unwrap();
+    assert!(tx.blob_sidecar.is_none());
+    let mut pubdata_commitment = vec![da_dispatcher::PUBDATA_SOURCE_CUSTOM];
+    pubdata_commitment.extend_from_slice(&inclusion_data);
+    assert!(tx
+        .raw_tx
+        .windows(pubdata_commitment.len())
+        .any(|window| window == pubdata_commitment));
+}
+
+#[tokio::test]
+async fn simulating_commit_tx_in_dry_run_mode() {
+    let connection_pool = ConnectionPool::test_pool().await;
+    let mut tester = EthSenderTester::new(connection_pool, vec![10; 100], false).await;
+    insert_genesis_protocol_version(&tester).await;
+    let genesis_l1_batch = insert_l1_batch(&tester, L1BatchNumber(0)).await;
+    let first_l1_batch = insert_l1_batch(&tester, L1BatchNumber(1)).await;
+
+    let operation = AggregatedOperation::Commit(L1BatchCommitOperation {
+        last_committed_l1_batch: l1_batch_with_metadata(genesis_l1_batch),
+        l1_batches: vec![l1_batch_with_metadata(first_l1_batch)],
+    });
+    let simulated = tester
+        .aggregator
+        .simulate_eth_tx(&mut tester.storage().await, &operation, false)
+        .await
+        .unwrap();
+    assert!(simulated);
+
+    let call_requests = tester.gateway.call_requests();
+    assert_eq!(call_requests.len(), 1);
+    assert_eq!(call_requests[0].from, Some(tester.gateway.sender_account()));
+    assert!(call_requests[0]
+        .data
+        .as_ref()
+        .is_some_and(|data| !data.0.is_empty()));
+
+    // Nothing should be persisted or sent to L1.
+    assert_eq!(tester.gateway.sent_tx_count(), 0);
+    let inflight_txs = tester
+        .storage()
+        .await
+        .eth_sender_dal()
+        .get_inflight_txs()
+        .await
+        .unwrap();
+    assert!(inflight_txs.is_empty());
+
+    // The same operation must not be simulated repeatedly.
+    let simulated = tester
+        .aggregator
+        .simulate_eth_tx(&mut tester.storage().await, &operation, false)
+        .await
+        .unwrap();
+    assert!(!simulated);
+    assert_eq!(tester.gateway.call_requests().len(), 1);
+}
+
 #[tokio::test]
 async fn skipped_l1_batch_at_the_start() -> anyhow::Result<()> {
     let connection_pool = ConnectionPool::test_pool().await;
@@ -989,3 +1223,191 @@ async fn confirm_tx(tester: &mut EthSenderTester, hash: H256) {
         .await
         .unwrap();
 }
+
+#[tokio::test]
+async fn rotating_stuck_operator() -> anyhow::Result<()> {
+    let connection_pool = ConnectionPool::test_pool().await;
+    let mut tester = EthSenderTester::new(connection_pool, vec![10; 100], false).await;
+    let backup_address = Address::repeat_byte(0x33);
+    let backup_operator: Arc<dyn BoundEthInterface> =
+        Arc::new(MockEthereum::default().with_sender_account(backup_address));
+    tester.manager = EthTxManager::new(
+        SenderConfig {
+            stuck_operator_nonce_blocks: Some(5),
+            ..ETHSenderConfig::for_tests().sender
+        },
+        GasEscalationConfig::default(),
+        tester.gas_adjuster.clone(),
+        tester.gateway.clone(),
+        vec![backup_operator],
+    );
+
+    let current_block = L1BlockNumber(tester.gateway.block_number("").await?.as_u32());
+    for _ in 0..2 {
+        let tx = tester
+            .aggregator
+            .save_eth_tx(&mut tester.storage().await, &DUMMY_OPERATION, true)
+            .await?;
+        assert_eq!(tx.from_addr, None);
+        tester
+            .manager
+            .send_eth_tx(&mut tester.storage().await, &tx, 0, current_block)
+            .await?;
+    }
+
+    // The operator nonce doesn't increase, but the threshold isn't reached yet.
+    for _ in 0..5 {
+        let block_numbers = tester.get_block_numbers().await;
+        tester
+            .manager
+            .maybe_rotate_operator(&mut tester.storage().await, block_numbers)
+            .await?;
+        let active_operator = tester
+            .storage()
+            .await
+            .eth_sender_dal()
+            .get_active_operator()
+            .await
+            .unwrap();
+        assert_eq!(active_operator, None);
+        tester.gateway.advance_block_number(1);
+    }
+
+    let block_numbers = tester.get_block_numbers().await;
+    tester
+        .manager
+        .maybe_rotate_operator(&mut tester.storage().await, block_numbers)
+        .await?;
+    let active_operator = tester
+        .storage()
+        .await
+        .eth_sender_dal()
+        .get_active_operator()
+        .await
+        .unwrap();
+    assert_eq!(
+        active_operator,
+        Some(ActiveOperator {
+            address: Some(backup_address),
+            base_nonce: 0,
+        })
+    );
+
+    // In-flight transactions are taken over by the backup account.
+    let inflight_txs = tester
+        .storage()
+        .await
+        .eth_sender_dal()
+        .get_inflight_txs()
+        .await
+        .unwrap();
+    let tx_senders: Vec<_> = inflight_txs
+        .iter()
+        .map(|tx| (tx.from_addr, tx.nonce))
+        .collect();
+    assert_eq!(
+        tx_senders,
+        [
+            (Some(backup_address), Nonce(0)),
+            (Some(backup_address), Nonce(1))
+        ]
+    );
+
+    // New transactions are sent from the backup account.
+    let tx = tester
+        .aggregator
+        .save_eth_tx(&mut tester.storage().await, &DUMMY_OPERATION, true)
+        .await?;
+    assert_eq!(tx.from_addr, Some(backup_address));
+    assert_eq!(tx.nonce, Nonce(2));
+    Ok(())
+}
+
+#[tokio::test]
+async fn replacing_fee_starved_txs() -> anyhow::Result<()> {
+    let connection_pool = ConnectionPool::test_pool().await;
+    let history = std::iter::repeat(1)
+        .take(5)
+        .chain(std::iter::repeat(1_000).take(20))
+        .collect();
+    let mut tester = EthSenderTester::new(connection_pool, history, false).await;
+    tester.manager = EthTxManager::new(
+        SenderConfig {
+            stuck_tx_threshold_blocks: Some(5),
+            stuck_tx_remediation: StuckTxRemediationPolicy::Replace,
+            ..ETHSenderConfig::for_tests().sender
+        },
+        GasEscalationConfig::default(),
+        tester.gas_adjuster.clone(),
+        tester.gateway.clone(),
+        vec![],
+    );
+    let health_check = tester.manager.health_check();
+
+    let current_block = L1BlockNumber(tester.gateway.block_number("").await?.as_u32());
+    let mut txs = vec![];
+    for _ in 0..2 {
+        let tx = tester
+            .aggregator
+            .save_eth_tx(&mut tester.storage().await, &DUMMY_OPERATION, true)
+            .await?;
+        tester
+            .manager
+            .send_eth_tx(&mut tester.storage().await, &tx, 0, current_block)
+            .await?;
+        txs.push(tx);
+    }
+    assert_eq!(tester.gateway.sent_tx_count(), 2);
+
+    // L1 base fee spikes, so that the sent transactions cannot be included into blocks.
+    tester.gateway.advance_block_number(10);
+    tester.gas_adjuster.keep_updated().await?;
+    let block_numbers = tester.get_block_numbers().await;
+    let report = tester
+        .manager
+        .track_stuck_txs(&mut tester.storage().await, block_numbers)
+        .await?;
+    let stuck_txs: Vec<_> = report
+        .stuck_txs
+        .iter()
+        .map(|tx| (tx.eth_tx_id, tx.kind, tx.pending_blocks))
+        .collect();
+    assert_eq!(
+        stuck_txs,
+        txs.iter()
+            .map(|tx| (tx.id, StuckTxKind::FeeStarved, 10))
+            .collect::<Vec<_>>()
+    );
+    assert!(report.nonce_gaps.is_empty());
+
+    let health = health_check.check_health().await;
+    assert_eq!(health.status(), HealthStatus::Ready);
+    let details = serde_json::to_value(&health).unwrap()["details"].clone();
+    assert_eq!(details["stuck_txs"].as_array().unwrap().len(), 2);
+
+    // Both transactions should be replaced with new attempts.
+    assert_eq!(tester.gateway.sent_tx_count(), 4);
+    for tx in &txs {
+        let last_attempt = tester
+            .storage()
+            .await
+            .eth_sender_dal()
+            .get_last_sent_eth_tx(tx.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(last_attempt.sent_at_block, Some(block_numbers.latest.0));
+        assert!(
+            last_attempt.base_fee_per_gas >= tester.gas_adjuster.get_next_block_minimal_base_fee(),
+            "{last_attempt:?}"
+        );
+    }
+
+    let report = tester
+        .manager
+        .track_stuck_txs(&mut tester.storage().await, block_numbers)
+        .await?;
+    assert!(report.stuck_txs.is_empty(), "{report:?}");
+    assert_eq!(tester.gateway.sent_tx_count(), 4);
+    Ok(())
+}
@@ -40,8 +40,14 @@ pub trait EthClient: 'static + fmt::Debug + Send + Sync {
         to: BlockNumber,
         retries_left: usize,
     ) -> Result<Vec<Log>, Error>;
-    /// Returns finalized L1 block number.
-    async fn finalized_block_number(&self) -> Result<u64, Error>;
+    /// Returns the latest L1 block number.
+    async fn block_number(&self) -> Result<u64, Error>;
+    /// Returns finalized L1 block number, or `None` if the L1 node doesn't report finalized blocks.
+    async fn finalized_block_number(&self) -> Result<Option<u64>, Error>;
+    /// Returns the hash of the canonical L1 block with the specified number, or `None` if there is no such block.
+    async fn block_hash(&self, number: u64) -> Result<Option<H256>, Error>;
+    /// Returns the timestamp of the canonical L1 block with the specified number, or `None` if there is no such block.
+    async fn block_timestamp(&self, number: u64) -> Result<Option<u64>, Error>;
     /// Returns scheduler verification key hash by verifier address.
     async fn scheduler_vk_hash(&self, verifier_address: Address) -> Result<H256, Error>;
     /// Sets list of topics to return events for.
@@ -61,7 +67,6 @@ pub struct EthHttpQueryClient {
     /// If address is some then client will listen to events coming from it.
     governance_address: Option<Address>,
     verifier_contract_abi: Contract,
-    confirmations_for_eth_event: Option<u64>,
 }
 
 impl EthHttpQueryClient {
@@ -69,7 +74,6 @@ impl EthHttpQueryClient {
         client: Box<dyn EthInterface>,
         zksync_contract_addr: Address,
         governance_address: Option<Address>,
-        confirmations_for_eth_event: Option<u64>,
     ) -> Self {
         tracing::debug!(
             "New eth client, zkSync addr: {:x}, governance addr: {:?}",
@@ -82,7 +86,6 @@ impl EthHttpQueryClient {
             zksync_contract_addr,
             governance_address,
             verifier_contract_abi: verifier_contract(),
-            confirmations_for_eth_event,
         }
     }
 
@@ -210,22 +213,37 @@ impl EthClient for EthHttpQueryClient {
         result
     }
 
-    async fn finalized_block_number(&self) -> Result<u64, Error> {
-        if let Some(confirmations) = self.confirmations_for_eth_event {
-            let latest_block_number = self.client.block_number("watch").await?.as_u64();
-            Ok(latest_block_number.saturating_sub(confirmations))
-        } else {
-            self.client
-                .block(BlockId::Number(BlockNumber::Finalized), "watch")
-                .await
-                .map_err(Into::into)
-                .map(|res| {
-                    res.expect("Finalized block must be present on L1")
-                        .number
-                        .expect("Finalized block must contain number")
-                        .as_u64()
-                })
-        }
+    async fn block_number(&self) -> Result<u64, Error> {
+        Ok(self.client.block_number("watch").await?.as_u64())
+    }
+
+    async fn finalized_block_number(&self) -> Result<Option<u64>, Error> {
+        let block = self
+            .client
+            .block(BlockId::Number(BlockNumber::Finalized), "watch")
+            .await?;
+        Ok(block.map(|block| {
+            block
+                .number
+                .expect("Finalized block must contain number")
+                .as_u64()
+        }))
+    }
+
+    async fn block_hash(&self, number: u64) -> Result<Option<H256>, Error> {
+        let block = self
+            .client
+            .block(BlockId::Number(BlockNumber::Number(number.into())), "watch")
+            .await?;
+        Ok(block.and_then(|block| block.hash))
+    }
+
+    async fn block_timestamp(&self, number: u64) -> Result<Option<u64>, Error> {
+        let block = self
+            .client
+            .block(BlockId::Number(BlockNumber::Number(number.into())), "watch")
+            .await?;
+        Ok(block.map(|block| block.timestamp.as_u64()))
     }
 
     fn set_topics(&mut self, topics: Vec<H256>) {
@@ -8,7 +8,7 @@ use zksync_types::{
 
 use crate::eth_watch::{
     client::{Error, EthClient},
-    event_processors::EventProcessor,
+    event_processors::{rollback_protocol_versions, EventProcessor},
 };
 
 /// Listens to operation events coming from the governance contract and saves new protocol upgrade proposals to the database.
@@ -17,6 +17,8 @@ pub struct GovernanceUpgradesEventProcessor {
     diamond_proxy_address: Address,
     /// Last protocol version seen. Used to skip events for already known upgrade proposals.
     last_seen_version_id: ProtocolVersionId,
+    /// Protocol versions saved by this processor together with the L1 blocks they were proposed in.
+    proposed_versions: Vec<(u64, ProtocolVersionId)>,
     upgrade_proposal_signature: H256,
 }
 
@@ -29,29 +31,42 @@ impl GovernanceUpgradesEventProcessor {
         Self {
             diamond_proxy_address,
             last_seen_version_id,
+            proposed_versions: Vec::new(),
             upgrade_proposal_signature: governance_contract
                 .event("TransparentOperationScheduled")
                 .expect("TransparentOperationScheduled event is missing in abi")
                 .signature(),
         }
     }
-}
 
-#[async_trait::async_trait]
-impl EventProcessor for GovernanceUpgradesEventProcessor {
-    async fn process_events(
-        &mut self,
-        storage: &mut StorageProcessor<'_>,
+    /// Parses protocol upgrades from governance operation events. Returns upgrades together with the scheduler VK hash
+    /// and the L1 block they were proposed in.
+    pub(in crate::eth_watch) async fn parse_upgrades(
+        &self,
         client: &dyn EthClient,
         events: Vec<Log>,
-    ) -> Result<(), Error> {
+    ) -> Result<Vec<(ProtocolUpgrade, Option<H256>, u64)>, Error> {
         let mut upgrades = Vec::new();
         for event in events
             .into_iter()
             .filter(|event| event.topics[0] == self.upgrade_proposal_signature)
         {
+            let eth_block = event
+                .block_number
+                .expect("Event block number is missing")
+                .as_u64();
             let governance_operation = GovernanceOperation::try_from(event)
                 .map_err(|err| Error::LogParse(format!("{:?}", err)))?;
+            // The operation cannot be executed on L1 before the governance delay elapses,
+            // so upgrades in it cannot be activated earlier either.
+            let executable_at = if governance_operation.delay > 0 {
+                client
+                    .block_timestamp(eth_block)
+                    .await?
+                    .map(|timestamp| timestamp.saturating_add(governance_operation.delay))
+            } else {
+                None
+            };
             // Some calls can target other contracts than Diamond proxy, skip them.
             for call in governance_operation
                 .calls
@@ -60,35 +75,59 @@ impl EventProcessor for GovernanceUpgradesEventProcessor {
             {
                 // We might not get an upgrade operation here, but something else instead
                 // (e.g. `acceptGovernor` call), so if parsing doesn't work, just skip the call.
-                let Ok(upgrade) = ProtocolUpgrade::try_from(call) else {
+                let Ok(mut upgrade) = ProtocolUpgrade::try_from(call) else {
                     tracing::warn!(
                         "Failed to parse governance operation call as protocol upgrade, skipping"
                     );
                     continue;
                 };
+                if let Some(executable_at) = executable_at {
+                    if executable_at > upgrade.timestamp {
+                        tracing::info!(
+                            "Postponing activation of protocol version {:?} from {} to {} because of governance delay ({}s)",
+                            upgrade.id,
+                            upgrade.timestamp,
+                            executable_at,
+                            governance_operation.delay
+                        );
+                        upgrade.timestamp = executable_at;
+                    }
+                }
                 // Scheduler VK is not present in proposal event. It is hard coded in verifier contract.
                 let scheduler_vk_hash = if let Some(address) = upgrade.verifier_address {
                     Some(client.scheduler_vk_hash(address).await?)
                 } else {
                     None
                 };
-                upgrades.push((upgrade, scheduler_vk_hash));
+                upgrades.push((upgrade, scheduler_vk_hash, eth_block));
             }
         }
+        Ok(upgrades)
+    }
+}
 
+#[async_trait::async_trait]
+impl EventProcessor for GovernanceUpgradesEventProcessor {
+    async fn process_events(
+        &mut self,
+        storage: &mut StorageProcessor<'_>,
+        client: &dyn EthClient,
+        events: Vec<Log>,
+    ) -> Result<(), Error> {
+        let upgrades = self.parse_upgrades(client, events).await?;
         if upgrades.is_empty() {
             return Ok(());
         }
 
         let ids_str: Vec<_> = upgrades
             .iter()
-            .map(|(u, _)| format!("{}", u.id as u16))
+            .map(|(u, ..)| format!("{}", u.id as u16))
             .collect();
         tracing::debug!("Received upgrades with ids: {}", ids_str.join(", "));
 
         let new_upgrades: Vec<_> = upgrades
             .into_iter()
-            .skip_while(|(v, _)| v.id as u16 <= self.last_seen_version_id as u16)
+            .skip_while(|(v, ..)| v.id as u16 <= self.last_seen_version_id as u16)
             .collect();
         if new_upgrades.is_empty() {
             return Ok(());
@@ -96,7 +135,8 @@ impl EventProcessor for GovernanceUpgradesEventProcessor {
 
         let last_id = new_upgrades.last().unwrap().0.id;
         let stage_start = Instant::now();
-        for (upgrade, scheduler_vk_hash) in new_upgrades {
+        for (upgrade, scheduler_vk_hash, eth_block) in new_upgrades {
+            let version_id = upgrade.id;
             let previous_version = storage
                 .protocol_versions_dal()
                 .load_previous_version(upgrade.id)
@@ -108,10 +148,16 @@ impl EventProcessor for GovernanceUpgradesEventProcessor {
                     )
                 });
             let new_version = previous_version.apply_upgrade(upgrade, scheduler_vk_hash);
+            tracing::info!(
+                "Scheduled protocol upgrade to version {:?} (proposed in L1 block #{eth_block}), activation timestamp: {}",
+                new_version.id,
+                new_version.timestamp
+            );
             storage
                 .protocol_versions_dal()
                 .save_protocol_version_with_tx(new_version)
                 .await;
+            self.proposed_versions.push((eth_block, version_id));
         }
         metrics::histogram!("eth_watcher.poll_eth_node", stage_start.elapsed(), "stage" => "persist_upgrades");
 
@@ -120,6 +166,16 @@ impl EventProcessor for GovernanceUpgradesEventProcessor {
         Ok(())
     }
 
+    async fn rollback(
+        &mut self,
+        storage: &mut StorageProcessor<'_>,
+        from_block: u64,
+    ) -> Result<(), Error> {
+        self.last_seen_version_id =
+            rollback_protocol_versions(storage, &mut self.proposed_versions, from_block).await;
+        Ok(())
+    }
+
     fn relevant_topic(&self) -> H256 {
         self.upgrade_proposal_signature
     }
@@ -1,9 +1,12 @@
 use std::fmt;
 
 use zksync_dal::StorageProcessor;
-use zksync_types::{web3::types::Log, H256};
+use zksync_types::{web3::types::Log, ProtocolVersionId, H256};
 
-use crate::eth_watch::client::{Error, EthClient};
+use crate::eth_watch::{
+    client::{Error, EthClient},
+    metrics::METRICS,
+};
 
 pub mod governance_upgrades;
 pub mod priority_ops;
@@ -19,6 +22,48 @@ pub trait EventProcessor: 'static + fmt::Debug + Send + Sync {
         events: Vec<Log>,
     ) -> Result<(), Error>;
 
+    /// Rolls back the processor state after an L1 reorg, so that events from L1 blocks
+    /// starting from `from_block` can be processed again.
+    async fn rollback(
+        &mut self,
+        storage: &mut StorageProcessor<'_>,
+        from_block: u64,
+    ) -> Result<(), Error>;
+
     /// Relevant topic which defines what events to be processed
     fn relevant_topic(&self) -> H256;
 }
+
+/// Removes protocol versions proposed in L1 blocks starting from `from_block` unless they are already used.
+/// `proposed_versions` must be ordered by L1 block number. Returns the last protocol version present in DB afterwards.
+async fn rollback_protocol_versions(
+    storage: &mut StorageProcessor<'_>,
+    proposed_versions: &mut Vec<(u64, ProtocolVersionId)>,
+    from_block: u64,
+) -> ProtocolVersionId {
+    while let Some(&(block, version_id)) = proposed_versions.last() {
+        if block < from_block {
+            break;
+        }
+        let deleted = storage
+            .protocol_versions_dal()
+            .delete_unused_protocol_version(version_id)
+            .await;
+        if !deleted {
+            tracing::warn!(
+                "Protocol version {version_id:?} proposed in reorged L1 block #{block} is already used; \
+                 it cannot be rolled back"
+            );
+            break;
+        }
+        tracing::info!("Rolled back protocol version {version_id:?} proposed in L1 block #{block}");
+        METRICS.rolled_back_upgrades.inc();
+        proposed_versions.pop();
+    }
+
+    storage
+        .protocol_versions_dal()
+        .last_version_id()
+        .await
+        .expect("Expected at least one (genesis) version to be present in DB")
+}
@@ -2,7 +2,7 @@ use std::convert::TryFrom;
 
 use zksync_contracts::zksync_contract;
 use zksync_dal::StorageProcessor;
-use zksync_types::{l1::L1Tx, web3::types::Log, PriorityOpId, H256};
+use zksync_types::{l1::L1Tx, web3::types::Log, L1BlockNumber, PriorityOpId, H256};
 
 use crate::{
     eth_watch::{
@@ -99,6 +99,31 @@ impl EventProcessor for PriorityOpsEventProcessor {
         Ok(())
     }
 
+    async fn rollback(
+        &mut self,
+        storage: &mut StorageProcessor<'_>,
+        from_block: u64,
+    ) -> Result<(), Error> {
+        let removed_ops = storage
+            .transactions_dal()
+            .remove_unprocessed_priority_ops(L1BlockNumber(from_block as u32))
+            .await;
+        METRICS.rolled_back_priority_ops.inc_by(removed_ops as u64);
+        // Operations that are already executed or loaded into the mempool are retained; if they were
+        // re-included into the canonical chain, they will be skipped when processed again.
+        self.next_expected_priority_id = storage
+            .transactions_dal()
+            .last_priority_id()
+            .await
+            .map_or(PriorityOpId(0), |id| id + 1);
+        tracing::info!(
+            "Rolled back {removed_ops} priority ops received in L1 block #{from_block} or later; \
+             next expected priority op: {}",
+            self.next_expected_priority_id
+        );
+        Ok(())
+    }
+
     fn relevant_topic(&self) -> H256 {
         self.new_priority_request_signature
     }
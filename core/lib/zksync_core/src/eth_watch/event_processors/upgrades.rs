@@ -5,7 +5,7 @@ use zksync_types::{web3::types::Log, ProtocolUpgrade, ProtocolVersionId, H256};
 
 use crate::eth_watch::{
     client::{Error, EthClient},
-    event_processors::EventProcessor,
+    event_processors::{rollback_protocol_versions, EventProcessor},
     metrics::{PollStage, METRICS},
 };
 
@@ -18,16 +18,44 @@ pub(crate) const UPGRADE_PROPOSAL_SIGNATURE: H256 = H256([
 #[derive(Debug)]
 pub struct UpgradesEventProcessor {
     last_seen_version_id: ProtocolVersionId,
+    /// Protocol versions saved by this processor together with the L1 blocks they were proposed in.
+    proposed_versions: Vec<(u64, ProtocolVersionId)>,
 }
 
 impl UpgradesEventProcessor {
     pub fn new(last_seen_version_id: ProtocolVersionId) -> Self {
         Self {
             last_seen_version_id,
+            proposed_versions: Vec::new(),
         }
     }
 }
 
+/// Parses protocol upgrades from upgrade proposal events emitted by the diamond proxy. Returns upgrades together with
+/// the scheduler VK hash and the L1 block they were proposed in.
+pub(in crate::eth_watch) async fn parse_upgrades(
+    client: &dyn EthClient,
+    events: Vec<Log>,
+) -> Result<Vec<(ProtocolUpgrade, Option<H256>, u64)>, Error> {
+    let mut upgrades = Vec::new();
+    for event in events
+        .into_iter()
+        .filter(|event| event.topics[0] == UPGRADE_PROPOSAL_SIGNATURE)
+    {
+        let eth_block = event.block_number.expect("Event block number is missing");
+        let upgrade = ProtocolUpgrade::try_from(event)
+            .map_err(|err| Error::LogParse(format!("{:?}", err)))?;
+        // Scheduler VK is not present in proposal event. It is hard coded in verifier contract.
+        let scheduler_vk_hash = if let Some(address) = upgrade.verifier_address {
+            Some(client.scheduler_vk_hash(address).await?)
+        } else {
+            None
+        };
+        upgrades.push((upgrade, scheduler_vk_hash, eth_block.as_u64()));
+    }
+    Ok(upgrades)
+}
+
 #[async_trait::async_trait]
 impl EventProcessor for UpgradesEventProcessor {
     async fn process_events(
@@ -36,35 +64,20 @@ impl EventProcessor for UpgradesEventProcessor {
         client: &dyn EthClient,
         events: Vec<Log>,
     ) -> Result<(), Error> {
-        let mut upgrades = Vec::new();
-        for event in events
-            .into_iter()
-            .filter(|event| event.topics[0] == UPGRADE_PROPOSAL_SIGNATURE)
-        {
-            let upgrade = ProtocolUpgrade::try_from(event)
-                .map_err(|err| Error::LogParse(format!("{:?}", err)))?;
-            // Scheduler VK is not present in proposal event. It is hard coded in verifier contract.
-            let scheduler_vk_hash = if let Some(address) = upgrade.verifier_address {
-                Some(client.scheduler_vk_hash(address).await?)
-            } else {
-                None
-            };
-            upgrades.push((upgrade, scheduler_vk_hash));
-        }
-
+        let upgrades = parse_upgrades(client, events).await?;
         if upgrades.is_empty() {
             return Ok(());
         }
 
         let ids_str: Vec<_> = upgrades
             .iter()
-            .map(|(u, _)| format!("{}", u.id as u16))
+            .map(|(u, ..)| format!("{}", u.id as u16))
             .collect();
         tracing::debug!("Received upgrades with ids: {}", ids_str.join(", "));
 
         let new_upgrades: Vec<_> = upgrades
             .into_iter()
-            .skip_while(|(v, _)| v.id as u16 <= self.last_seen_version_id as u16)
+            .skip_while(|(v, ..)| v.id as u16 <= self.last_seen_version_id as u16)
             .collect();
         if new_upgrades.is_empty() {
             return Ok(());
@@ -72,7 +85,8 @@ impl EventProcessor for UpgradesEventProcessor {
 
         let last_id = new_upgrades.last().unwrap().0.id;
         let stage_latency = METRICS.poll_eth_node[&PollStage::PersistUpgrades].start();
-        for (upgrade, scheduler_vk_hash) in new_upgrades {
+        for (upgrade, scheduler_vk_hash, eth_block) in new_upgrades {
+            let version_id = upgrade.id;
             let previous_version = storage
                 .protocol_versions_dal()
                 .load_previous_version(upgrade.id)
@@ -83,12 +97,23 @@ impl EventProcessor for UpgradesEventProcessor {
                 .protocol_versions_dal()
                 .save_protocol_version_with_tx(new_version)
                 .await;
+            self.proposed_versions.push((eth_block, version_id));
         }
         stage_latency.observe();
         self.last_seen_version_id = last_id;
         Ok(())
     }
 
+    async fn rollback(
+        &mut self,
+        storage: &mut StorageProcessor<'_>,
+        from_block: u64,
+    ) -> Result<(), Error> {
+        self.last_seen_version_id =
+            rollback_protocol_versions(storage, &mut self.proposed_versions, from_block).await;
+        Ok(())
+    }
+
     fn relevant_topic(&self) -> H256 {
         UPGRADE_PROPOSAL_SIGNATURE
     }
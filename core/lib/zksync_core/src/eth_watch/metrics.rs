@@ -20,6 +20,12 @@ pub(super) struct EthWatcherMetrics {
     pub poll_eth_node: Family<PollStage, Histogram<Duration>>,
     #[metrics(buckets = Buckets::LATENCIES)]
     pub get_priority_op_events: Histogram<Duration>,
+    /// Number of detected L1 reorgs affecting processed events.
+    pub l1_reorgs: Counter,
+    /// Number of priority operations removed from the DB because of L1 reorgs.
+    pub rolled_back_priority_ops: Counter,
+    /// Number of protocol upgrade proposals removed from the DB because of L1 reorgs.
+    pub rolled_back_upgrades: Counter,
 }
 
 #[vise::register]
@@ -2,9 +2,13 @@
 //! New events are accepted to the zkSync network once they have the sufficient amount of L1 confirmations.
 //!
 //! Poll interval is configured using the `ETH_POLL_INTERVAL` constant.
-//! Number of confirmations is configured using the `CONFIRMATIONS_FOR_ETH_EVENT` environment variable.
+//! Number of confirmations is configured using the `CONFIRMATIONS_FOR_ETH_EVENT` (for priority operations)
+//! and `CONFIRMATIONS_FOR_UPGRADE_EVENT` (for protocol upgrades) environment variables.
+//!
+//! If events are processed before their L1 blocks are finalized, the watcher tracks hashes of these blocks
+//! and rolls back the processed events if the blocks are reorged.
 
-use std::time::Duration;
+use std::{collections::BTreeMap, time::Duration};
 
 use tokio::{sync::watch, task::JoinHandle};
 use zksync_config::ETHWatchConfig;
@@ -13,9 +17,10 @@ use zksync_eth_client::EthInterface;
 use zksync_system_constants::PRIORITY_EXPIRATION;
 use zksync_types::{
     ethabi::Contract, web3::types::BlockNumber as Web3BlockNumber, Address, PriorityOpId,
-    ProtocolVersionId,
+    ProtocolVersionId, H256,
 };
 
+pub use self::replay::{L1EventsReplay, L1EventsReplayReport};
 use self::{
     client::{Error, EthClient, EthHttpQueryClient, RETRY_LIMIT},
     event_processors::{
@@ -28,9 +33,38 @@ use self::{
 mod client;
 mod event_processors;
 mod metrics;
+mod replay;
 #[cfg(test)]
 mod tests;
 
+/// Depth of L1 blocks considered final if the L1 node doesn't report finalized blocks.
+const FALLBACK_FINALITY_DEPTH: u64 = 64;
+
+/// Numbers of L1 confirmations required for the watched events to be processed.
+/// If a number is not specified, the corresponding events are processed once their block is finalized.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EthWatchConfirmations {
+    pub priority_ops: Option<u64>,
+    pub upgrades: Option<u64>,
+}
+
+impl EthWatchConfirmations {
+    pub fn new(config: &ETHWatchConfig) -> Self {
+        Self {
+            priority_ops: config.confirmations_for_eth_event,
+            upgrades: config.confirmations_for_upgrade_event(),
+        }
+    }
+}
+
+/// Event processor together with its progress.
+#[derive(Debug)]
+struct WatchedProcessor {
+    processor: Box<dyn EventProcessor>,
+    confirmations: Option<u64>,
+    last_processed_ethereum_block: u64,
+}
+
 #[derive(Debug)]
 struct EthWatchState {
     last_seen_version_id: ProtocolVersionId,
@@ -42,9 +76,9 @@ struct EthWatchState {
 pub struct EthWatch {
     client: Box<dyn EthClient>,
     poll_interval: Duration,
-    event_processors: Vec<Box<dyn EventProcessor>>,
-
-    last_processed_ethereum_block: u64,
+    event_processors: Vec<WatchedProcessor>,
+    /// Hashes of processed non-finalized L1 blocks. Used to detect L1 reorgs.
+    unfinalized_blocks: BTreeMap<u64, H256>,
 }
 
 impl EthWatch {
@@ -54,6 +88,7 @@ impl EthWatch {
         mut client: Box<dyn EthClient>,
         pool: &ConnectionPool,
         poll_interval: Duration,
+        confirmations: EthWatchConfirmations,
     ) -> Self {
         let mut storage = pool.access_storage_tagged("eth_watch").await.unwrap();
 
@@ -64,9 +99,14 @@ impl EthWatch {
         let priority_ops_processor =
             PriorityOpsEventProcessor::new(state.next_expected_priority_id);
         let upgrades_processor = UpgradesEventProcessor::new(state.last_seen_version_id);
-        let mut event_processors: Vec<Box<dyn EventProcessor>> = vec![
-            Box::new(priority_ops_processor),
-            Box::new(upgrades_processor),
+        let watched = |processor: Box<dyn EventProcessor>, confirmations| WatchedProcessor {
+            processor,
+            confirmations,
+            last_processed_ethereum_block: state.last_processed_ethereum_block,
+        };
+        let mut event_processors = vec![
+            watched(Box::new(priority_ops_processor), confirmations.priority_ops),
+            watched(Box::new(upgrades_processor), confirmations.upgrades),
         ];
 
         if let Some(governance_contract) = governance_contract {
@@ -75,12 +115,15 @@ impl EthWatch {
                 state.last_seen_version_id,
                 &governance_contract,
             );
-            event_processors.push(Box::new(governance_upgrades_processor))
+            event_processors.push(watched(
+                Box::new(governance_upgrades_processor),
+                confirmations.upgrades,
+            ));
         }
 
         let topics = event_processors
             .iter()
-            .map(|p| p.relevant_topic())
+            .map(|p| p.processor.relevant_topic())
             .collect();
         client.set_topics(topics);
 
@@ -88,10 +131,20 @@ impl EthWatch {
             client,
             poll_interval,
             event_processors,
-            last_processed_ethereum_block: state.last_processed_ethereum_block,
+            unfinalized_blocks: BTreeMap::new(),
         }
     }
 
+    /// Returns the finalized L1 block number, falling back to a fixed depth if the L1 node
+    /// doesn't report finalized blocks.
+    async fn finalized_block_number(client: &dyn EthClient) -> Result<u64, Error> {
+        if let Some(number) = client.finalized_block_number().await? {
+            return Ok(number);
+        }
+        let latest_block_number = client.block_number().await?;
+        Ok(latest_block_number.saturating_sub(FALLBACK_FINALITY_DEPTH))
+    }
+
     async fn initialize_state(
         client: &dyn EthClient,
         storage: &mut StorageProcessor<'_>,
@@ -117,8 +170,7 @@ impl EthWatch {
             // but subtract 1 in case the server stopped mid-block.
             Some(block) => block.0.saturating_sub(1).into(),
             // There are no priority ops processed - to be safe, scan the last 50k blocks.
-            None => client
-                .finalized_block_number()
+            None => Self::finalized_block_number(client)
                 .await
                 .expect("cannot initialize eth watch: cannot get current ETH block")
                 .saturating_sub(PRIORITY_EXPIRATION),
@@ -151,10 +203,13 @@ impl EthWatch {
                 // This is an error because otherwise we could potentially miss a priority operation
                 // thus entering priority mode, which is not desired.
                 tracing::error!("Failed to process new blocks {}", error);
-                self.last_processed_ethereum_block =
+                let last_processed_ethereum_block =
                     Self::initialize_state(&*self.client, &mut storage)
                         .await
                         .last_processed_ethereum_block;
+                for processor in &mut self.event_processors {
+                    processor.last_processed_ethereum_block = last_processed_ethereum_block;
+                }
             }
         }
         Ok(())
@@ -163,27 +218,121 @@ impl EthWatch {
     #[tracing::instrument(skip(self, storage))]
     async fn loop_iteration(&mut self, storage: &mut StorageProcessor<'_>) -> Result<(), Error> {
         let stage_latency = METRICS.poll_eth_node[&PollStage::Request].start();
-        let to_block = self.client.finalized_block_number().await?;
-        if to_block <= self.last_processed_ethereum_block {
+        let latest_block = self.client.block_number().await?;
+        let finalized_block = Self::finalized_block_number(&*self.client).await?;
+        if let Some(reorg_start) = self.detect_reorg(finalized_block).await? {
+            self.rollback(storage, reorg_start).await?;
+        }
+        // Finalized blocks cannot be reorged, so there's no need to track them.
+        self.unfinalized_blocks
+            .retain(|&number, _| number > finalized_block);
+
+        let to_blocks: Vec<_> = self
+            .event_processors
+            .iter()
+            .map(|processor| match processor.confirmations {
+                Some(confirmations) => latest_block.saturating_sub(confirmations),
+                None => finalized_block,
+            })
+            .collect();
+        let pending_ranges = self
+            .event_processors
+            .iter()
+            .zip(&to_blocks)
+            .filter(|(processor, &to_block)| to_block > processor.last_processed_ethereum_block)
+            .map(|(processor, &to_block)| (processor.last_processed_ethereum_block, to_block));
+        let Some((from_block, to_block)) = pending_ranges
+            .reduce(|(from_a, to_a), (from_b, to_b)| (from_a.min(from_b), to_a.max(to_b)))
+        else {
             return Ok(());
+        };
+
+        // Block hashes must be obtained before the events; otherwise, events from a reorged block
+        // could be attributed to the canonical block hash, and the reorg would go unnoticed.
+        let mut unfinalized_to_blocks: Vec<_> = to_blocks
+            .iter()
+            .copied()
+            .filter(|&block| block > finalized_block)
+            .collect();
+        unfinalized_to_blocks.sort_unstable();
+        unfinalized_to_blocks.dedup();
+        let mut block_hashes = Vec::with_capacity(unfinalized_to_blocks.len());
+        for block in unfinalized_to_blocks {
+            if let Some(hash) = self.client.block_hash(block).await? {
+                block_hashes.push((block, hash));
+            }
         }
 
         let events = self
             .client
             .get_events(
-                Web3BlockNumber::Number(self.last_processed_ethereum_block.into()),
+                Web3BlockNumber::Number(from_block.into()),
                 Web3BlockNumber::Number(to_block.into()),
                 RETRY_LIMIT,
             )
             .await?;
         stage_latency.observe();
 
-        for processor in self.event_processors.iter_mut() {
+        for (processor, to_block) in self.event_processors.iter_mut().zip(to_blocks) {
+            let from_block = processor.last_processed_ethereum_block;
+            if to_block <= from_block {
+                continue;
+            }
+            let processor_events = events
+                .iter()
+                .filter(|event| {
+                    event.block_number.map_or(false, |number| {
+                        (from_block..=to_block).contains(&number.as_u64())
+                    })
+                })
+                .cloned()
+                .collect();
             processor
-                .process_events(storage, &*self.client, events.clone())
+                .processor
+                .process_events(storage, &*self.client, processor_events)
                 .await?;
+            processor.last_processed_ethereum_block = to_block;
+        }
+        self.unfinalized_blocks.extend(block_hashes);
+        Ok(())
+    }
+
+    /// Checks whether the tracked non-finalized L1 blocks are still canonical. If they are not,
+    /// returns the first L1 block that must be processed again.
+    async fn detect_reorg(&self, finalized_block: u64) -> Result<Option<u64>, Error> {
+        let mut last_canonical_block = finalized_block;
+        let mut is_reorged = false;
+        for (&number, &hash) in self.unfinalized_blocks.iter().rev() {
+            if self.client.block_hash(number).await? == Some(hash) {
+                // All ancestors of a canonical block are canonical as well.
+                last_canonical_block = last_canonical_block.max(number);
+                break;
+            }
+            is_reorged = true;
         }
-        self.last_processed_ethereum_block = to_block;
+        Ok(is_reorged.then_some(last_canonical_block + 1))
+    }
+
+    async fn rollback(
+        &mut self,
+        storage: &mut StorageProcessor<'_>,
+        from_block: u64,
+    ) -> Result<(), Error> {
+        tracing::warn!(
+            "Detected L1 reorg; rolling back events processed from L1 block #{from_block} onwards"
+        );
+        METRICS.l1_reorgs.inc();
+        for processor in &mut self.event_processors {
+            if processor.last_processed_ethereum_block < from_block {
+                continue;
+            }
+            processor.processor.rollback(storage, from_block).await?;
+            // Events are fetched starting from the last processed block inclusively, and already processed events
+            // are skipped by processors, so it's safe to re-process the block preceding the reorg.
+            processor.last_processed_ethereum_block = from_block - 1;
+        }
+        self.unfinalized_blocks
+            .retain(|&number, _| number < from_block);
         Ok(())
     }
 }
@@ -196,12 +345,7 @@ pub async fn start_eth_watch(
     governance: (Contract, Address),
     stop_receiver: watch::Receiver<bool>,
 ) -> anyhow::Result<JoinHandle<anyhow::Result<()>>> {
-    let eth_client = EthHttpQueryClient::new(
-        eth_gateway,
-        diamond_proxy_addr,
-        Some(governance.1),
-        config.confirmations_for_eth_event,
-    );
+    let eth_client = EthHttpQueryClient::new(eth_gateway, diamond_proxy_addr, Some(governance.1));
 
     let mut eth_watch = EthWatch::new(
         diamond_proxy_addr,
@@ -209,6 +353,7 @@ pub async fn start_eth_watch(
         Box::new(eth_client),
         &pool,
         config.poll_interval(),
+        EthWatchConfirmations::new(&config),
     )
     .await;
 
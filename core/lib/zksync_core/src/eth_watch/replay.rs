@@ -0,0 +1,362 @@
+//! Maintenance tool re-scanning L1 events in a block range and reconciling them with Postgres.
+//!
+//! The watcher processes each L1 block once, so events missed because of an L1 provider outage (e.g., a provider
+//! returning an incomplete list of logs) are never picked up again. The replay re-fetches priority operation and
+//! protocol upgrade events for an arbitrary L1 block range, compares them with the priority queue and protocol version
+//! tables and reports any discrepancies. Optionally, discrepancies are repaired: missing entries are inserted,
+//! and stale ones are removed provided that they weren't executed or loaded into the state keeper mempool yet.
+
+use std::{collections::HashMap, convert::TryFrom};
+
+use anyhow::Context as _;
+use serde::Serialize;
+use zksync_contracts::zksync_contract;
+use zksync_dal::{ConnectionPool, StorageProcessor};
+use zksync_eth_client::EthInterface;
+use zksync_types::{
+    ethabi::Contract, l1::L1Tx, web3::types::BlockNumber as Web3BlockNumber, Address,
+    L1BlockNumber, PriorityOpId, ProtocolUpgrade, ProtocolVersionId, H256,
+};
+
+use super::{
+    client::{EthClient, EthHttpQueryClient, RETRY_LIMIT},
+    event_processors::{
+        governance_upgrades::GovernanceUpgradesEventProcessor,
+        upgrades::{self, UPGRADE_PROPOSAL_SIGNATURE},
+        EventProcessor as _,
+    },
+};
+
+/// Discrepancies between L1 events and Postgres found by [`L1EventsReplay`].
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct L1EventsReplayReport {
+    pub from_block: u64,
+    pub to_block: u64,
+    /// Number of priority operations emitted in the scanned L1 blocks.
+    pub priority_ops_on_l1: usize,
+    /// Priority operations emitted on L1, but absent in Postgres.
+    pub missing_priority_ops: Vec<PriorityOpId>,
+    /// Priority operations present in Postgres with a hash differing from the one on L1.
+    pub mismatched_priority_ops: Vec<PriorityOpId>,
+    /// Priority operations attributed to the scanned L1 blocks in Postgres that weren't emitted on L1
+    /// (e.g., ones received from a reorged L1 block).
+    pub unexpected_priority_ops: Vec<PriorityOpId>,
+    /// Number of protocol upgrades proposed in the scanned L1 blocks.
+    pub protocol_upgrades_on_l1: usize,
+    /// Protocol upgrades proposed on L1, but absent in Postgres.
+    pub missing_protocol_upgrades: Vec<ProtocolVersionId>,
+    /// Protocol upgrades present in Postgres with base system contracts differing from the ones proposed on L1.
+    pub mismatched_protocol_upgrades: Vec<ProtocolVersionId>,
+    /// Discrepancies that were repaired. Empty unless the replay is run with repairs enabled.
+    pub repaired: Vec<String>,
+    /// Discrepancies that couldn't be repaired automatically and require manual intervention.
+    pub unrepaired: Vec<String>,
+}
+
+impl L1EventsReplayReport {
+    /// Checks whether no discrepancies were found.
+    pub fn is_consistent(&self) -> bool {
+        self.missing_priority_ops.is_empty()
+            && self.mismatched_priority_ops.is_empty()
+            && self.unexpected_priority_ops.is_empty()
+            && self.missing_protocol_upgrades.is_empty()
+            && self.mismatched_protocol_upgrades.is_empty()
+    }
+}
+
+/// Re-scans L1 events and reconciles them with the priority queue and protocol versions stored in Postgres.
+#[derive(Debug)]
+pub struct L1EventsReplay {
+    client: Box<dyn EthClient>,
+    governance_processor: Option<GovernanceUpgradesEventProcessor>,
+    new_priority_request_signature: H256,
+}
+
+impl L1EventsReplay {
+    pub fn new(
+        eth_gateway: Box<dyn EthInterface>,
+        diamond_proxy_addr: Address,
+        governance: (Contract, Address),
+    ) -> Self {
+        let client = EthHttpQueryClient::new(eth_gateway, diamond_proxy_addr, Some(governance.1));
+        Self::with_client(Box::new(client), diamond_proxy_addr, Some(governance.0))
+    }
+
+    pub(super) fn with_client(
+        mut client: Box<dyn EthClient>,
+        diamond_proxy_addr: Address,
+        governance_contract: Option<Contract>,
+    ) -> Self {
+        let governance_processor = governance_contract.map(|contract| {
+            // The last seen version is irrelevant since the processor is only used to parse events.
+            GovernanceUpgradesEventProcessor::new(
+                diamond_proxy_addr,
+                ProtocolVersionId::default(),
+                &contract,
+            )
+        });
+        let new_priority_request_signature = zksync_contract()
+            .event("NewPriorityRequest")
+            .expect("NewPriorityRequest event is missing in abi")
+            .signature();
+
+        let mut topics = vec![new_priority_request_signature, UPGRADE_PROPOSAL_SIGNATURE];
+        if let Some(processor) = &governance_processor {
+            topics.push(processor.relevant_topic());
+        }
+        client.set_topics(topics);
+
+        Self {
+            client,
+            governance_processor,
+            new_priority_request_signature,
+        }
+    }
+
+    /// Re-scans L1 events in the specified inclusive block range and reconciles them with Postgres.
+    /// If `repair` is set, fixes the found discrepancies where it's safe to do so.
+    pub async fn run(
+        &self,
+        pool: &ConnectionPool,
+        from_block: u64,
+        to_block: u64,
+        repair: bool,
+    ) -> anyhow::Result<L1EventsReplayReport> {
+        anyhow::ensure!(
+            from_block <= to_block,
+            "Invalid L1 block range: {from_block}..={to_block}"
+        );
+        let mut storage = pool.access_storage_tagged("eth_watch").await?;
+        let mut transaction = storage.start_transaction().await?;
+        let report = self
+            .reconcile(&mut transaction, from_block, to_block, repair)
+            .await?;
+        transaction.commit().await?;
+        Ok(report)
+    }
+
+    async fn reconcile(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        from_block: u64,
+        to_block: u64,
+        repair: bool,
+    ) -> anyhow::Result<L1EventsReplayReport> {
+        tracing::info!("Re-scanning L1 events in blocks {from_block}..={to_block}");
+        let events = self
+            .client
+            .get_events(
+                Web3BlockNumber::Number(from_block.into()),
+                Web3BlockNumber::Number(to_block.into()),
+                RETRY_LIMIT,
+            )
+            .await
+            .context("failed fetching L1 events")?;
+
+        let mut report = L1EventsReplayReport {
+            from_block,
+            to_block,
+            ..L1EventsReplayReport::default()
+        };
+
+        let mut priority_ops = vec![];
+        for event in &events {
+            if event.topics[0] == self.new_priority_request_signature {
+                let tx = L1Tx::try_from(event.clone()).context("failed parsing priority op")?;
+                priority_ops.push(tx);
+            }
+        }
+        self.reconcile_priority_ops(storage, priority_ops, &mut report, repair)
+            .await;
+
+        let mut protocol_upgrades = upgrades::parse_upgrades(&*self.client, events.clone())
+            .await
+            .context("failed parsing protocol upgrades")?;
+        if let Some(processor) = &self.governance_processor {
+            let governance_upgrades = processor
+                .parse_upgrades(&*self.client, events)
+                .await
+                .context("failed parsing governance protocol upgrades")?;
+            protocol_upgrades.extend(governance_upgrades);
+        }
+        self.reconcile_protocol_upgrades(storage, protocol_upgrades, &mut report, repair)
+            .await;
+
+        if report.is_consistent() {
+            tracing::info!(
+                "L1 events in blocks {from_block}..={to_block} are consistent with Postgres"
+            );
+        } else {
+            tracing::warn!("Found discrepancies between L1 events and Postgres: {report:?}");
+        }
+        Ok(report)
+    }
+
+    async fn reconcile_priority_ops(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        priority_ops: Vec<L1Tx>,
+        report: &mut L1EventsReplayReport,
+        repair: bool,
+    ) {
+        report.priority_ops_on_l1 = priority_ops.len();
+        let ids: Vec<_> = priority_ops.iter().map(L1Tx::serial_id).collect();
+        let l1_blocks =
+            L1BlockNumber(report.from_block as u32)..=L1BlockNumber(report.to_block as u32);
+        let stored_hashes: HashMap<_, _> = storage
+            .transactions_dal()
+            .get_priority_op_hashes(&ids, l1_blocks)
+            .await
+            .into_iter()
+            .collect();
+
+        for (&id, &stored_hash) in &stored_hashes {
+            if !ids.contains(&id) {
+                report.unexpected_priority_ops.push(id);
+                if repair {
+                    Self::remove_priority_op(storage, id, stored_hash, report).await;
+                }
+            }
+        }
+        report.unexpected_priority_ops.sort_unstable();
+
+        for tx in priority_ops {
+            let id = tx.serial_id();
+            match stored_hashes.get(&id) {
+                None => {
+                    report.missing_priority_ops.push(id);
+                    if repair {
+                        Self::insert_priority_op(storage, tx, report).await;
+                    }
+                }
+                Some(&stored_hash) if stored_hash != tx.hash() => {
+                    report.mismatched_priority_ops.push(id);
+                    if repair && Self::remove_priority_op(storage, id, stored_hash, report).await {
+                        Self::insert_priority_op(storage, tx, report).await;
+                    }
+                }
+                Some(_) => { /* The operation is consistent */ }
+            }
+        }
+    }
+
+    async fn insert_priority_op(
+        storage: &mut StorageProcessor<'_>,
+        tx: L1Tx,
+        report: &mut L1EventsReplayReport,
+    ) {
+        let id = tx.serial_id();
+        let eth_block = tx.eth_block();
+        storage
+            .transactions_dal()
+            .insert_transaction_l1(tx, eth_block)
+            .await;
+        tracing::info!("Inserted priority op #{id} received in L1 block #{eth_block}");
+        report.repaired.push(format!(
+            "inserted priority op #{id} from L1 block #{eth_block}"
+        ));
+    }
+
+    async fn remove_priority_op(
+        storage: &mut StorageProcessor<'_>,
+        id: PriorityOpId,
+        stored_hash: H256,
+        report: &mut L1EventsReplayReport,
+    ) -> bool {
+        let removed = storage
+            .transactions_dal()
+            .remove_unprocessed_priority_op(id)
+            .await;
+        if removed {
+            tracing::info!("Removed stale priority op #{id} with hash {stored_hash:?}");
+            report.repaired.push(format!(
+                "removed stale priority op #{id} with hash {stored_hash:?}"
+            ));
+        } else {
+            tracing::warn!(
+                "Stale priority op #{id} with hash {stored_hash:?} is already executed or loaded into mempool; \
+                 it cannot be removed"
+            );
+            report.unrepaired.push(format!(
+                "priority op #{id} with hash {stored_hash:?} is executed or loaded into mempool"
+            ));
+        }
+        removed
+    }
+
+    async fn reconcile_protocol_upgrades(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        mut protocol_upgrades: Vec<(ProtocolUpgrade, Option<H256>, u64)>,
+        report: &mut L1EventsReplayReport,
+        repair: bool,
+    ) {
+        report.protocol_upgrades_on_l1 = protocol_upgrades.len();
+        // Upgrades must be inserted in the order of their IDs since each one is applied to the previous version.
+        protocol_upgrades.sort_by_key(|(upgrade, ..)| upgrade.id);
+
+        for (upgrade, scheduler_vk_hash, eth_block) in protocol_upgrades {
+            let id = upgrade.id;
+            let stored_version = storage
+                .protocol_versions_dal()
+                .get_protocol_version(id)
+                .await;
+            let Some(stored_version) = stored_version else {
+                report.missing_protocol_upgrades.push(id);
+                if repair {
+                    Self::insert_protocol_version(storage, upgrade, scheduler_vk_hash, report)
+                        .await;
+                }
+                continue;
+            };
+
+            let stored_contracts = stored_version.base_system_contracts_hashes;
+            let bootloader_matches = upgrade
+                .bootloader_code_hash
+                .map_or(true, |hash| hash == stored_contracts.bootloader);
+            let default_aa_matches = upgrade
+                .default_account_code_hash
+                .map_or(true, |hash| hash == stored_contracts.default_aa);
+            if !bootloader_matches || !default_aa_matches {
+                tracing::warn!(
+                    "Protocol version {id:?} proposed in L1 block #{eth_block} has base system contracts \
+                     differing from the stored ones ({stored_contracts:?})"
+                );
+                report.mismatched_protocol_upgrades.push(id);
+                // Base system contracts of a version may already be used by miniblocks; replacing them is unsafe.
+                report.unrepaired.push(format!(
+                    "protocol version {id:?} has base system contracts differing from L1"
+                ));
+            }
+        }
+    }
+
+    async fn insert_protocol_version(
+        storage: &mut StorageProcessor<'_>,
+        upgrade: ProtocolUpgrade,
+        scheduler_vk_hash: Option<H256>,
+        report: &mut L1EventsReplayReport,
+    ) {
+        let id = upgrade.id;
+        let Some(previous_version) = storage
+            .protocol_versions_dal()
+            .load_previous_version(id)
+            .await
+        else {
+            report
+                .unrepaired
+                .push(format!("no protocol version preceding {id:?} in Postgres"));
+            return;
+        };
+        let new_version = previous_version.apply_upgrade(upgrade, scheduler_vk_hash);
+        storage
+            .protocol_versions_dal()
+            .save_protocol_version_with_tx(new_version)
+            .await;
+        tracing::info!("Inserted missing protocol version {id:?}");
+        report
+            .repaired
+            .push(format!("inserted protocol version {id:?}"));
+    }
+}
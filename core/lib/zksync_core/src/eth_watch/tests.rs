@@ -15,14 +15,23 @@ use zksync_types::{
 use super::client::Error;
 use crate::eth_watch::{
     client::EthClient, event_processors::upgrades::UPGRADE_PROPOSAL_SIGNATURE, EthWatch,
+    EthWatchConfirmations, L1EventsReplay,
 };
 
+/// Interval between L1 blocks used to derive block timestamps.
+const L1_BLOCK_TIME: u64 = 12;
+
 #[derive(Debug)]
 struct FakeEthClientData {
     transactions: HashMap<u64, Vec<Log>>,
     diamond_upgrades: HashMap<u64, Vec<Log>>,
     governance_upgrades: HashMap<u64, Vec<Log>>,
     last_finalized_block_number: u64,
+    last_block_number: u64,
+    /// Overridden block hashes; by default, the hash of a block is derived from its number.
+    block_hashes: HashMap<u64, H256>,
+    /// Delay of governance operations (in seconds).
+    governance_delay: u64,
 }
 
 impl FakeEthClientData {
@@ -32,6 +41,9 @@ impl FakeEthClientData {
             diamond_upgrades: Default::default(),
             governance_upgrades: Default::default(),
             last_finalized_block_number: 0,
+            last_block_number: 0,
+            block_hashes: HashMap::new(),
+            governance_delay: 0,
         }
     }
 
@@ -59,13 +71,35 @@ impl FakeEthClientData {
             self.governance_upgrades
                 .entry(*eth_block)
                 .or_default()
-                .push(upgrade_into_governor_log(upgrade.clone(), *eth_block));
+                .push(upgrade_into_governor_log(
+                    upgrade.clone(),
+                    *eth_block,
+                    self.governance_delay,
+                ));
         }
     }
 
     fn set_last_finalized_block_number(&mut self, number: u64) {
         self.last_finalized_block_number = number;
     }
+
+    fn set_last_block_number(&mut self, number: u64) {
+        self.last_block_number = number;
+    }
+
+    /// Replaces all blocks starting from `from_block` with new ones that have no events.
+    fn reorg(&mut self, from_block: u64) {
+        for logs in [
+            &mut self.transactions,
+            &mut self.diamond_upgrades,
+            &mut self.governance_upgrades,
+        ] {
+            logs.retain(|&block, _| block < from_block);
+        }
+        for block in from_block..=self.last_block_number {
+            self.block_hashes.insert(block, H256::random());
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -92,6 +126,10 @@ impl FakeEthClient {
         self.inner.write().await.add_governance_upgrades(upgrades);
     }
 
+    async fn set_governance_delay(&mut self, delay: u64) {
+        self.inner.write().await.governance_delay = delay;
+    }
+
     async fn set_last_finalized_block_number(&mut self, number: u64) {
         self.inner
             .write()
@@ -99,6 +137,14 @@ impl FakeEthClient {
             .set_last_finalized_block_number(number);
     }
 
+    async fn set_last_block_number(&mut self, number: u64) {
+        self.inner.write().await.set_last_block_number(number);
+    }
+
+    async fn reorg(&mut self, from_block: u64) {
+        self.inner.write().await.reorg(from_block);
+    }
+
     async fn block_to_number(&self, block: BlockNumber) -> u64 {
         match block {
             BlockNumber::Earliest => 0,
@@ -142,8 +188,29 @@ impl EthClient for FakeEthClient {
         Ok(H256::zero())
     }
 
-    async fn finalized_block_number(&self) -> Result<u64, Error> {
-        Ok(self.inner.read().await.last_finalized_block_number)
+    async fn block_number(&self) -> Result<u64, Error> {
+        let inner = self.inner.read().await;
+        Ok(inner
+            .last_block_number
+            .max(inner.last_finalized_block_number))
+    }
+
+    async fn finalized_block_number(&self) -> Result<Option<u64>, Error> {
+        Ok(Some(self.inner.read().await.last_finalized_block_number))
+    }
+
+    async fn block_hash(&self, number: u64) -> Result<Option<H256>, Error> {
+        let inner = self.inner.read().await;
+        let hash = inner
+            .block_hashes
+            .get(&number)
+            .copied()
+            .unwrap_or_else(|| H256::from_low_u64_be(number));
+        Ok(Some(hash))
+    }
+
+    async fn block_timestamp(&self, number: u64) -> Result<Option<u64>, Error> {
+        Ok(Some(number * L1_BLOCK_TIME))
     }
 }
 
@@ -212,6 +279,7 @@ async fn test_normal_operation_l1_txs() {
         Box::new(client.clone()),
         &connection_pool,
         std::time::Duration::from_nanos(1),
+        EthWatchConfirmations::default(),
     )
     .await;
 
@@ -260,6 +328,7 @@ async fn test_normal_operation_upgrades() {
         Box::new(client.clone()),
         &connection_pool,
         std::time::Duration::from_nanos(1),
+        EthWatchConfirmations::default(),
     )
     .await;
 
@@ -321,6 +390,7 @@ async fn test_gap_in_upgrades() {
         Box::new(client.clone()),
         &connection_pool,
         std::time::Duration::from_nanos(1),
+        EthWatchConfirmations::default(),
     )
     .await;
 
@@ -360,6 +430,7 @@ async fn test_normal_operation_governance_upgrades() {
         Box::new(client.clone()),
         &connection_pool,
         std::time::Duration::from_nanos(1),
+        EthWatchConfirmations::default(),
     )
     .await;
 
@@ -409,6 +480,69 @@ async fn test_normal_operation_governance_upgrades() {
     assert_eq!(tx.common_data.upgrade_id, ProtocolVersionId::next());
 }
 
+#[tokio::test]
+async fn governance_delay_postpones_upgrade_activation() {
+    let connection_pool = ConnectionPool::test_pool().await;
+    setup_db(&connection_pool).await;
+
+    let mut client = FakeEthClient::new();
+    let mut watcher = EthWatch::new(
+        Address::default(),
+        Some(governance_contract()),
+        Box::new(client.clone()),
+        &connection_pool,
+        std::time::Duration::from_nanos(1),
+        EthWatchConfirmations::default(),
+    )
+    .await;
+
+    let mut storage = connection_pool.access_storage().await.unwrap();
+    client.set_governance_delay(3_600).await;
+    client
+        .add_governance_upgrades(&[
+            (
+                ProtocolUpgrade {
+                    id: ProtocolVersionId::latest(),
+                    timestamp: 100,
+                    ..Default::default()
+                },
+                10,
+            ),
+            (
+                ProtocolUpgrade {
+                    id: ProtocolVersionId::next(),
+                    timestamp: 1_000_000,
+                    ..Default::default()
+                },
+                11,
+            ),
+        ])
+        .await;
+    client.set_last_finalized_block_number(15).await;
+    watcher.loop_iteration(&mut storage).await.unwrap();
+
+    // The first upgrade cannot be executed before the governance delay elapses.
+    let version = storage
+        .protocol_versions_dal()
+        .get_protocol_version(ProtocolVersionId::latest())
+        .await
+        .unwrap();
+    assert_eq!(version.timestamp, 10 * L1_BLOCK_TIME + 3_600);
+    // The second upgrade is scheduled after the delay anyway.
+    let version = storage
+        .protocol_versions_dal()
+        .get_protocol_version(ProtocolVersionId::next())
+        .await
+        .unwrap();
+    assert_eq!(version.timestamp, 1_000_000);
+
+    let next_version = storage
+        .protocol_versions_dal()
+        .next_scheduled_version(ProtocolVersionId::latest())
+        .await;
+    assert_eq!(next_version, Some((ProtocolVersionId::next(), 1_000_000)));
+}
+
 #[tokio::test]
 #[should_panic]
 async fn test_gap_in_single_batch() {
@@ -422,6 +556,7 @@ async fn test_gap_in_single_batch() {
         Box::new(client.clone()),
         &connection_pool,
         std::time::Duration::from_nanos(1),
+        EthWatchConfirmations::default(),
     )
     .await;
 
@@ -452,6 +587,7 @@ async fn test_gap_between_batches() {
         Box::new(client.clone()),
         &connection_pool,
         std::time::Duration::from_nanos(1),
+        EthWatchConfirmations::default(),
     )
     .await;
 
@@ -487,6 +623,7 @@ async fn test_overlapping_batches() {
         Box::new(client.clone()),
         &connection_pool,
         std::time::Duration::from_nanos(1),
+        EthWatchConfirmations::default(),
     )
     .await;
 
@@ -523,6 +660,171 @@ async fn test_overlapping_batches() {
     assert_eq!(tx.common_data.serial_id.0, 4);
 }
 
+#[tokio::test]
+async fn test_priority_ops_with_confirmations() {
+    let connection_pool = ConnectionPool::test_pool().await;
+    setup_db(&connection_pool).await;
+
+    let mut client = FakeEthClient::new();
+    let confirmations = EthWatchConfirmations {
+        priority_ops: Some(5),
+        upgrades: None,
+    };
+    let mut watcher = EthWatch::new(
+        Address::default(),
+        None,
+        Box::new(client.clone()),
+        &connection_pool,
+        std::time::Duration::from_nanos(1),
+        confirmations,
+    )
+    .await;
+
+    let mut storage = connection_pool.access_storage().await.unwrap();
+    client
+        .add_transactions(&[build_l1_tx(0, 10), build_l1_tx(1, 18)])
+        .await;
+    client.set_last_block_number(20).await;
+    watcher.loop_iteration(&mut storage).await.unwrap();
+    let db_txs = get_all_db_txs(&mut storage).await;
+    assert_eq!(db_txs.len(), 1);
+
+    client.set_last_block_number(23).await;
+    watcher.loop_iteration(&mut storage).await.unwrap();
+    let db_txs = get_all_db_txs(&mut storage).await;
+    assert_eq!(db_txs.len(), 2);
+}
+
+#[tokio::test]
+async fn test_rollback_on_l1_reorg() {
+    let connection_pool = ConnectionPool::test_pool().await;
+    setup_db(&connection_pool).await;
+
+    let mut client = FakeEthClient::new();
+    let confirmations = EthWatchConfirmations {
+        priority_ops: Some(0),
+        upgrades: None,
+    };
+    let mut watcher = EthWatch::new(
+        Address::default(),
+        None,
+        Box::new(client.clone()),
+        &connection_pool,
+        std::time::Duration::from_nanos(1),
+        confirmations,
+    )
+    .await;
+
+    let mut storage = connection_pool.access_storage().await.unwrap();
+    client
+        .add_transactions(&[build_l1_tx(0, 10), build_l1_tx(1, 14), build_l1_tx(2, 16)])
+        .await;
+    client.set_last_finalized_block_number(12).await;
+    client.set_last_block_number(20).await;
+    watcher.loop_iteration(&mut storage).await.unwrap();
+    let db_txs = get_all_db_txs(&mut storage).await;
+    assert_eq!(db_txs.len(), 3);
+    // Emulate the state keeper having loaded only the first of the unfinalized priority ops.
+    storage.transactions_dal().reset_mempool().await;
+    storage
+        .transactions_dal()
+        .sync_mempool(vec![], vec![], 0, 0, 2)
+        .await;
+
+    // Priority op #2 is reorged and re-included in a later block; op #3 is new.
+    client.reorg(15).await;
+    client
+        .add_transactions(&[build_l1_tx(2, 17), build_l1_tx(3, 18)])
+        .await;
+    client.set_last_block_number(21).await;
+    watcher.loop_iteration(&mut storage).await.unwrap();
+
+    let db_txs = get_all_db_txs(&mut storage).await;
+    let mut db_txs: Vec<L1Tx> = db_txs
+        .into_iter()
+        .map(|tx| tx.try_into().unwrap())
+        .collect();
+    db_txs.sort_by_key(|tx| tx.common_data.serial_id);
+    let tx_blocks: Vec<_> = db_txs
+        .iter()
+        .map(|tx| (tx.common_data.serial_id.0, tx.eth_block().0))
+        .collect();
+    assert_eq!(tx_blocks, [(0, 10), (1, 14), (2, 17), (3, 18)]);
+}
+
+#[tokio::test]
+async fn replay_reconciles_l1_events() {
+    let connection_pool = ConnectionPool::test_pool().await;
+    setup_db(&connection_pool).await;
+
+    let mut client = FakeEthClient::new();
+    client
+        .add_transactions(&[build_l1_tx(0, 10), build_l1_tx(1, 12), build_l1_tx(2, 14)])
+        .await;
+    client
+        .add_governance_upgrades(&[(
+            ProtocolUpgrade {
+                id: ProtocolVersionId::latest(),
+                tx: None,
+                ..Default::default()
+            },
+            11,
+        )])
+        .await;
+    client.set_last_finalized_block_number(20).await;
+
+    // Simulate an outage: priority op #1 and the upgrade are missed, op #2 is stored with a wrong hash,
+    // and a stale op #3 from a reorged L1 block is stored.
+    let mut storage = connection_pool.access_storage().await.unwrap();
+    let mut wrong_tx = build_l1_tx(2, 14);
+    wrong_tx.common_data.canonical_tx_hash = H256::repeat_byte(0xff);
+    for tx in [build_l1_tx(0, 10), wrong_tx, build_l1_tx(3, 13)] {
+        let eth_block = tx.eth_block();
+        storage
+            .transactions_dal()
+            .insert_transaction_l1(tx, eth_block)
+            .await;
+    }
+
+    let replay = L1EventsReplay::with_client(
+        Box::new(client.clone()),
+        Address::default(),
+        Some(governance_contract()),
+    );
+    let report = replay.run(&connection_pool, 0, 20, false).await.unwrap();
+    assert!(!report.is_consistent());
+    assert_eq!(report.priority_ops_on_l1, 3);
+    assert_eq!(report.missing_priority_ops, [PriorityOpId(1)]);
+    assert_eq!(report.mismatched_priority_ops, [PriorityOpId(2)]);
+    assert_eq!(report.unexpected_priority_ops, [PriorityOpId(3)]);
+    assert_eq!(
+        report.missing_protocol_upgrades,
+        [ProtocolVersionId::latest()]
+    );
+    assert!(report.repaired.is_empty());
+
+    let report = replay.run(&connection_pool, 0, 20, true).await.unwrap();
+    assert_eq!(report.repaired.len(), 5, "{report:?}");
+    assert!(report.unrepaired.is_empty(), "{report:?}");
+
+    let report = replay.run(&connection_pool, 0, 20, false).await.unwrap();
+    assert!(report.is_consistent(), "{report:?}");
+    let db_ids = storage.protocol_versions_dal().all_version_ids().await;
+    assert!(db_ids.contains(&ProtocolVersionId::latest()));
+    let mut db_txs: Vec<L1Tx> = get_all_db_txs(&mut storage)
+        .await
+        .into_iter()
+        .map(|tx| tx.try_into().unwrap())
+        .collect();
+    db_txs.sort_by_key(|tx| tx.common_data.serial_id);
+    let db_hashes: Vec<_> = db_txs.iter().map(L1Tx::hash).collect();
+    let expected_hashes: Vec<_> = [build_l1_tx(0, 10), build_l1_tx(1, 12), build_l1_tx(2, 14)]
+        .iter()
+        .map(L1Tx::hash)
+        .collect();
+    assert_eq!(db_hashes, expected_hashes);
+}
+
 async fn get_all_db_txs(storage: &mut StorageProcessor<'_>) -> Vec<Transaction> {
     storage.transactions_dal().reset_mempool().await;
     storage
@@ -561,7 +863,7 @@ fn tx_into_log(tx: L1Tx) -> Log {
 
     let data = encode(&[
         Token::Uint(tx.common_data.serial_id.0.into()),
-        Token::FixedBytes(H256::random().0.to_vec()),
+        Token::FixedBytes(tx.common_data.canonical_tx_hash.0.to_vec()),
         Token::Uint(u64::MAX.into()),
         tx_data_token,
         Token::Array(Vec::new()),
@@ -603,7 +905,7 @@ fn upgrade_into_diamond_proxy_log(upgrade: ProtocolUpgrade, eth_block: u64) -> L
     }
 }
 
-fn upgrade_into_governor_log(upgrade: ProtocolUpgrade, eth_block: u64) -> Log {
+fn upgrade_into_governor_log(upgrade: ProtocolUpgrade, eth_block: u64, delay: u64) -> Log {
     let diamond_cut = upgrade_into_diamond_cut(upgrade);
     let execute_upgrade_selector = zksync_contract()
         .function("executeUpgrade")
@@ -624,7 +926,7 @@ fn upgrade_into_governor_log(upgrade: ProtocolUpgrade, eth_block: u64) -> Log {
         Token::FixedBytes(vec![0u8; 32]),
         Token::FixedBytes(vec![0u8; 32]),
     ]);
-    let final_data = encode(&[Token::FixedBytes(vec![0u8; 32]), governance_operation]);
+    let final_data = encode(&[Token::Uint(delay.into()), governance_operation]);
 
     Log {
         address: Address::repeat_byte(0x1),
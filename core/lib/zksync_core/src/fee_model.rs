@@ -1,9 +1,23 @@
-use std::{fmt, sync::Arc};
+use std::{
+    cmp::Ordering,
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering as AtomicOrdering},
+        Arc,
+    },
+    time::Duration,
+};
 
+use anyhow::Context as _;
+use tokio::sync::watch;
+use zksync_config::{configs::chain::StateKeeperConfig, GasAdjusterConfig};
+use zksync_dal::{ConnectionPool, StorageProcessor};
 use zksync_types::{
+    api::{FeeModelSettings, FeeModelSettingsUpdate},
+    commitment::L1BatchCommitmentMode,
     fee_model::{
-        BatchFeeInput, FeeModelConfig, FeeModelConfigV2, FeeParams, FeeParamsV1, FeeParamsV2,
-        L1PeggedBatchFeeModelInput, PubdataIndependentBatchFeeModelInput,
+        BaseTokenConversionRatio, BatchFeeInput, FeeModelConfig, FeeModelConfigV2, FeeParams,
+        FeeParamsV1, FeeParamsV2, L1PeggedBatchFeeModelInput, PubdataIndependentBatchFeeModelInput,
     },
     U256,
 };
@@ -49,23 +63,60 @@ pub trait BatchFeeModelInputProvider: fmt::Debug + 'static + Send + Sync {
 /// The struct that represents the batch fee input provider to be used in the main node of the server, i.e.
 /// it explicitly gets the L1 gas price from the provider and uses it to calculate the batch fee input instead of getting
 /// it from other node.
+///
+/// If the chain pays fees in an ERC-20 token, L1 gas and pubdata prices are converted to the token units, so that
+/// all produced prices are denominated in the base token. The fair L2 gas price is expected to be configured
+/// in the base token units.
+///
+/// In the validium mode, pubdata isn't published on L1, so it's not charged from users.
 #[derive(Debug)]
 pub(crate) struct MainNodeFeeInputProvider {
     provider: Arc<dyn L1GasPriceProvider>,
     config: FeeModelConfig,
+    dynamic_l2_gas_price: Option<Arc<DynamicL2GasPrice>>,
+    base_token_conversion_ratio: BaseTokenConversionRatio,
+    settings: Option<FeeModelSettingsHandle>,
+    commitment_mode: L1BatchCommitmentMode,
 }
 
 impl BatchFeeModelInputProvider for MainNodeFeeInputProvider {
     fn get_fee_model_params(&self) -> FeeParams {
-        match self.config {
+        let mut config = self.config;
+        let settings = self.settings.as_ref().map(FeeModelSettingsHandle::get);
+        let mut minimal_l2_gas_price =
+            settings.map(|settings| settings.minimal_l2_gas_price.as_u64());
+        if let Some(dynamic_price) = &self.dynamic_l2_gas_price {
+            let price = dynamic_price.get();
+            minimal_l2_gas_price = Some(minimal_l2_gas_price.map_or(price, |min| min.max(price)));
+        }
+        if let Some(price) = minimal_l2_gas_price {
+            match &mut config {
+                FeeModelConfig::V1(config) => config.minimal_l2_gas_price = price,
+                FeeModelConfig::V2(config) => config.minimal_l2_gas_price = price,
+            }
+        }
+
+        let ratio = self.base_token_conversion_ratio;
+        // Scale factors from the settings are applied by the L1 gas price provider instead of its internal
+        // multipliers (see `GasAdjuster::with_fee_model_settings()`), so they must not be applied here.
+        let l1_gas_price = self.provider.estimate_effective_gas_price();
+        let mut l1_pubdata_price = self.provider.estimate_effective_pubdata_price();
+        if self.commitment_mode == L1BatchCommitmentMode::Validium {
+            l1_pubdata_price = 0;
+        }
+        match config {
+            // In the L1-pegged model, the VM derives the pubdata price from the L1 gas price, so the L1 pubdata price
+            // is not used. The L1 gas price must not be derived from the pubdata price: it's used to charge
+            // the batch overhead (i.e., L1 gas spent on committing, proving and executing the batch), and the pubdata
+            // price may be much lower than L1 gas price (e.g., if pubdata is published in blobs).
             FeeModelConfig::V1(config) => FeeParams::V1(FeeParamsV1 {
                 config,
-                l1_gas_price: self.provider.estimate_effective_gas_price(),
+                l1_gas_price: ratio.convert(l1_gas_price),
             }),
             FeeModelConfig::V2(config) => FeeParams::V2(FeeParamsV2 {
                 config,
-                l1_gas_price: self.provider.estimate_effective_gas_price(),
-                l1_pubdata_price: self.provider.estimate_effective_pubdata_price(),
+                l1_gas_price: ratio.convert(l1_gas_price),
+                l1_pubdata_price: ratio.convert(l1_pubdata_price),
             }),
         }
     }
@@ -73,7 +124,326 @@ impl BatchFeeModelInputProvider for MainNodeFeeInputProvider {
 
 impl MainNodeFeeInputProvider {
     pub(crate) fn new(provider: Arc<dyn L1GasPriceProvider>, config: FeeModelConfig) -> Self {
-        Self { provider, config }
+        Self {
+            provider,
+            config,
+            dynamic_l2_gas_price: None,
+            base_token_conversion_ratio: BaseTokenConversionRatio::default(),
+            settings: None,
+            commitment_mode: L1BatchCommitmentMode::Rollup,
+        }
+    }
+
+    /// Sets the commitment mode of L1 batches. In the validium mode, the L1 pubdata price is always zero;
+    /// the L1 gas price is not affected.
+    pub(crate) fn with_commitment_mode(mut self, mode: L1BatchCommitmentMode) -> Self {
+        self.commitment_mode = mode;
+        self
+    }
+
+    /// Applies fee model settings that can be changed at runtime. The minimal L2 gas price from the settings
+    /// overrides the one from the config. Scale factors from the settings are applied by the L1 gas price provider.
+    pub(crate) fn with_settings(mut self, settings: FeeModelSettingsHandle) -> Self {
+        self.settings = Some(settings);
+        self
+    }
+
+    /// Converts L1 prices to the base token of the chain using the specified ratio.
+    pub(crate) fn with_base_token_conversion_ratio(
+        mut self,
+        ratio: BaseTokenConversionRatio,
+    ) -> Self {
+        self.base_token_conversion_ratio = ratio;
+        self
+    }
+
+    /// Uses the dynamic L2 gas price instead of the minimal L2 gas price from the config.
+    pub(crate) fn with_dynamic_l2_gas_price(mut self, price: Arc<DynamicL2GasPrice>) -> Self {
+        self.dynamic_l2_gas_price = Some(price);
+        self
+    }
+}
+
+/// Returns the conversion ratio from ETH to the base token of the chain specified in the `config`.
+pub(crate) fn base_token_conversion_ratio(
+    config: &StateKeeperConfig,
+) -> anyhow::Result<BaseTokenConversionRatio> {
+    BaseTokenConversionRatio::new(
+        config.base_token_conversion_numerator.unwrap_or(1),
+        config.base_token_conversion_denominator.unwrap_or(1),
+    )
+    .context("base token conversion ratio must be positive")
+}
+
+/// Handle to [`FeeModelSettings`] shared by fee input providers and the L1 gas price estimator in the same process
+/// (i.e., the state keeper and the API server). Cloned handles refer to the same settings.
+///
+/// Settings can be changed at runtime via the admin API, e.g. to mitigate fee incidents without restarting
+/// the node. Changed settings are persisted in Postgres, so that they survive restarts and are picked up
+/// by other processes via [`Self::follow_storage()`]. Each change is logged together with the previous settings.
+#[derive(Debug, Clone)]
+pub struct FeeModelSettingsHandle {
+    sender: Arc<watch::Sender<FeeModelSettings>>,
+}
+
+impl FeeModelSettingsHandle {
+    /// Creates a handle with the settings corresponding to the configs. Scale factors default to the internal
+    /// pricing multipliers of the gas adjuster, which they replace.
+    pub fn new(config: &StateKeeperConfig, gas_adjuster_config: &GasAdjusterConfig) -> Self {
+        let settings = FeeModelSettings {
+            minimal_l2_gas_price: config.fair_l2_gas_price.into(),
+            l1_gas_price_scale_factor: gas_adjuster_config.internal_l1_pricing_multiplier,
+            l1_pubdata_price_scale_factor: gas_adjuster_config
+                .internal_pubdata_pricing_multiplier(),
+        };
+        Self {
+            sender: Arc::new(watch::channel(settings).0),
+        }
+    }
+
+    pub fn get(&self) -> FeeModelSettings {
+        *self.sender.borrow()
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<FeeModelSettings> {
+        self.sender.subscribe()
+    }
+
+    /// Replaces the settings with the persisted ones, if any.
+    pub async fn load(&self, storage: &mut StorageProcessor<'_>) -> anyhow::Result<()> {
+        let persisted_settings = storage
+            .fee_model_dal()
+            .get_fee_model_settings()
+            .await
+            .context("failed loading fee model settings")?;
+        if let Some(settings) = persisted_settings {
+            self.replace(settings, "storage");
+        }
+        Ok(())
+    }
+
+    /// Checks that the update is valid.
+    pub fn validate_update(update: &FeeModelSettingsUpdate) -> anyhow::Result<()> {
+        let scale_factors = [
+            update.l1_gas_price_scale_factor,
+            update.l1_pubdata_price_scale_factor,
+        ];
+        for factor in scale_factors.into_iter().flatten() {
+            anyhow::ensure!(
+                factor.is_finite() && factor > 0.0,
+                "scale factors must be positive, got {factor}"
+            );
+        }
+        if let Some(price) = update.minimal_l2_gas_price {
+            anyhow::ensure!(!price.is_zero(), "minimal L2 gas price must be positive");
+        }
+        Ok(())
+    }
+
+    /// Applies the update to the settings, persists the updated settings and returns them. `source` is used
+    /// in the log entry describing the change.
+    pub async fn update(
+        &self,
+        update: FeeModelSettingsUpdate,
+        source: &str,
+        storage: &mut StorageProcessor<'_>,
+    ) -> anyhow::Result<FeeModelSettings> {
+        Self::validate_update(&update)?;
+        let settings = self.get();
+        let new_settings = FeeModelSettings {
+            minimal_l2_gas_price: update
+                .minimal_l2_gas_price
+                .unwrap_or(settings.minimal_l2_gas_price),
+            l1_gas_price_scale_factor: update
+                .l1_gas_price_scale_factor
+                .unwrap_or(settings.l1_gas_price_scale_factor),
+            l1_pubdata_price_scale_factor: update
+                .l1_pubdata_price_scale_factor
+                .unwrap_or(settings.l1_pubdata_price_scale_factor),
+        };
+        storage
+            .fee_model_dal()
+            .set_fee_model_settings(&new_settings)
+            .await
+            .context("failed persisting fee model settings")?;
+        self.replace(new_settings, source);
+        Ok(new_settings)
+    }
+
+    fn replace(&self, new_settings: FeeModelSettings, source: &str) {
+        let mut prev_settings = None;
+        self.sender.send_if_modified(|settings| {
+            if new_settings == *settings {
+                return false;
+            }
+            prev_settings = Some(std::mem::replace(settings, new_settings));
+            true
+        });
+        if let Some(prev_settings) = prev_settings {
+            tracing::warn!(
+                "Fee model settings changed via {source}: {prev_settings:?} -> {new_settings:?}"
+            );
+        }
+    }
+
+    /// Keeps the settings in sync with the persisted settings, so that changes made via the admin API of another
+    /// process are applied.
+    pub async fn follow_storage(
+        self,
+        pool: ConnectionPool,
+        mut stop_receiver: watch::Receiver<bool>,
+    ) -> anyhow::Result<()> {
+        while !*stop_receiver.borrow_and_update() {
+            let mut storage = pool.access_storage_tagged("fee_model").await?;
+            self.load(&mut storage).await?;
+            drop(storage);
+
+            // Error here corresponds to a timeout w/o `stop_receiver` changed; we're OK with this.
+            tokio::time::timeout(FOLLOW_STORAGE_INTERVAL, stop_receiver.changed())
+                .await
+                .ok();
+        }
+        tracing::info!("Stop signal received, fee model settings follower is shutting down");
+        Ok(())
+    }
+}
+
+/// Interval between polls of Postgres by [`DynamicL2GasPrice::follow_storage()`]
+/// and [`FeeModelSettingsHandle::follow_storage()`].
+const FOLLOW_STORAGE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Parameters of [`DynamicL2GasPrice`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct DynamicL2GasPriceParams {
+    min_price: u64,
+    max_price: u64,
+    target_miniblock_gas: u64,
+    max_change_denominator: u64,
+}
+
+impl DynamicL2GasPriceParams {
+    /// Returns `None` if the dynamic L2 gas price is disabled in the `config`.
+    pub fn new(config: &StateKeeperConfig) -> Option<Self> {
+        let max_price = config.max_fair_l2_gas_price?;
+        assert!(
+            max_price >= config.fair_l2_gas_price,
+            "Max fair L2 gas price must not be less than the fair L2 gas price"
+        );
+        let params = Self {
+            min_price: config.fair_l2_gas_price,
+            max_price,
+            target_miniblock_gas: config.target_miniblock_gas(),
+            max_change_denominator: config.l2_gas_price_change_denominator(),
+        };
+        assert!(
+            params.target_miniblock_gas > 0,
+            "Target miniblock gas must be positive"
+        );
+        assert!(
+            params.max_change_denominator > 0,
+            "L2 gas price change denominator must be positive"
+        );
+        Some(params)
+    }
+
+    /// Computes the price after a miniblock that has used `gas_used` gas was sealed with the specified price.
+    fn next_price(&self, price: u64, gas_used: u64) -> u64 {
+        let target = u128::from(self.target_miniblock_gas);
+        let denominator = u128::from(self.max_change_denominator);
+        // Intermediate values may not fit into `u64`.
+        let price = u128::from(price);
+        let next_price = match gas_used.cmp(&self.target_miniblock_gas) {
+            Ordering::Equal => price,
+            Ordering::Greater => {
+                let delta = price * (u128::from(gas_used) - target) / target / denominator;
+                // Ensure that the price can grow from small values.
+                price + delta.max(1)
+            }
+            Ordering::Less => {
+                let delta = price * (target - u128::from(gas_used)) / target / denominator;
+                price - delta
+            }
+        };
+        let next_price = u64::try_from(next_price).unwrap_or(u64::MAX);
+        next_price.clamp(self.min_price, self.max_price)
+    }
+}
+
+/// Congestion-based fair L2 gas price. Similarly to the EIP-1559 base fee, the price is adjusted after each miniblock
+/// depending on whether the miniblock has used more or less gas than the target, by at most
+/// `1 / max_change_denominator` per miniblock. The price is kept within the configured bounds.
+///
+/// Since the fee input is fixed for the entire L1 batch, the adjusted price takes effect starting from the next
+/// L1 batch; base fees of miniblocks (as returned in block headers and by `eth_feeHistory`) follow the price
+/// of their batch.
+#[derive(Debug)]
+pub(crate) struct DynamicL2GasPrice {
+    params: DynamicL2GasPriceParams,
+    price: AtomicU64,
+}
+
+impl DynamicL2GasPrice {
+    pub fn new(params: DynamicL2GasPriceParams, initial_price: u64) -> Self {
+        let initial_price = initial_price.clamp(params.min_price, params.max_price);
+        Self {
+            params,
+            price: AtomicU64::new(initial_price),
+        }
+    }
+
+    /// Initializes the price from the value persisted by the state keeper. The fair L2 gas price of sealed miniblocks
+    /// cannot be used for this purpose since it may include overheads on top of the minimal price
+    /// (e.g., the compute overhead in the pubdata-independent fee model).
+    pub async fn load(
+        params: DynamicL2GasPriceParams,
+        storage: &mut StorageProcessor<'_>,
+    ) -> anyhow::Result<Self> {
+        let initial_price = storage
+            .fee_model_dal()
+            .get_dynamic_l2_gas_price()
+            .await
+            .context("failed loading dynamic L2 gas price")?;
+        Ok(Self::new(params, initial_price.unwrap_or(params.min_price)))
+    }
+
+    pub fn get(&self) -> u64 {
+        self.price.load(AtomicOrdering::Relaxed)
+    }
+
+    /// Adjusts the price after a miniblock that has used `gas_used` gas was sealed. Returns the adjusted price.
+    pub fn update(&self, gas_used: u64) -> u64 {
+        let next_price = self.params.next_price(self.get(), gas_used);
+        self.price.store(next_price, AtomicOrdering::Relaxed);
+        next_price
+    }
+
+    /// Keeps the price in sync with the price persisted by the state keeper. Used by components that don't seal
+    /// miniblocks themselves (e.g., the API server), so that they use the same price as the state keeper.
+    pub async fn follow_storage(
+        self: Arc<Self>,
+        pool: ConnectionPool,
+        mut stop_receiver: watch::Receiver<bool>,
+    ) -> anyhow::Result<()> {
+        while !*stop_receiver.borrow_and_update() {
+            let mut storage = pool.access_storage_tagged("api").await?;
+            let persisted_price = storage
+                .fee_model_dal()
+                .get_dynamic_l2_gas_price()
+                .await
+                .context("failed loading dynamic L2 gas price")?;
+            drop(storage);
+
+            if let Some(price) = persisted_price {
+                let price = price.clamp(self.params.min_price, self.params.max_price);
+                self.price.store(price, AtomicOrdering::Relaxed);
+            }
+            // Error here corresponds to a timeout w/o `stop_receiver` changed; we're OK with this.
+            tokio::time::timeout(FOLLOW_STORAGE_INTERVAL, stop_receiver.changed())
+                .await
+                .ok();
+        }
+        tracing::info!("Stop signal received, dynamic L2 gas price follower is shutting down");
+        Ok(())
     }
 }
 
@@ -160,8 +530,24 @@ fn compute_batch_fee_model_input_v2(
 
 #[cfg(test)]
 mod tests {
+    use zksync_config::configs::ETHSenderConfig;
+    use zksync_types::{fee_model::FeeModelConfigV1, MiniblockNumber};
+
     use super::*;
 
+    #[derive(Debug)]
+    struct MockL1GasPriceProvider;
+
+    impl L1GasPriceProvider for MockL1GasPriceProvider {
+        fn estimate_effective_gas_price(&self) -> u64 {
+            1_000_000_000
+        }
+
+        fn estimate_effective_pubdata_price(&self) -> u64 {
+            17_000_000_000
+        }
+    }
+
     // To test that overflow never happens, we'll use giant L1 gas price, i.e.
     // almost realistic very large value of 100k gwei. Since it is so large, we'll also
     // use it for the L1 pubdata price.
@@ -170,6 +556,299 @@ mod tests {
     // As a small small L2 gas price we'll use the value of 1 wei.
     const SMALL_L1_GAS_PRICE: u64 = 1;
 
+    #[test]
+    fn dynamic_l2_gas_price_adjustment() {
+        let params = DynamicL2GasPriceParams {
+            min_price: 1_000,
+            max_price: 2_000,
+            target_miniblock_gas: 1_000_000,
+            max_change_denominator: 8,
+        };
+        let price = DynamicL2GasPrice::new(params, 0);
+        assert_eq!(price.get(), 1_000);
+
+        // Target usage doesn't change the price.
+        assert_eq!(price.update(1_000_000), 1_000);
+        // Double the target usage increases the price by the max rate.
+        assert_eq!(price.update(2_000_000), 1_125);
+        assert_eq!(price.update(1_500_000), 1_195);
+        // Empty miniblocks decrease the price by the max rate.
+        assert_eq!(price.update(0), 1_046);
+        assert_eq!(price.update(0), 1_000);
+
+        for _ in 0..100 {
+            price.update(u64::MAX);
+        }
+        assert_eq!(price.get(), 2_000);
+
+        // The price must be able to grow from small values.
+        let params = DynamicL2GasPriceParams {
+            min_price: 0,
+            ..params
+        };
+        assert_eq!(params.next_price(0, 1_000_001), 1);
+    }
+
+    #[test]
+    fn main_node_fee_input_provider_with_dynamic_l2_gas_price() {
+        let params = DynamicL2GasPriceParams {
+            min_price: 1_000,
+            max_price: 2_000,
+            target_miniblock_gas: 1_000_000,
+            max_change_denominator: 8,
+        };
+        let price = Arc::new(DynamicL2GasPrice::new(params, 1_500));
+        let config = FeeModelConfig::V1(FeeModelConfigV1 {
+            minimal_l2_gas_price: 1_000,
+        });
+        let provider = MainNodeFeeInputProvider::new(Arc::new(MockL1GasPriceProvider), config)
+            .with_dynamic_l2_gas_price(price.clone());
+        assert_eq!(provider.get_batch_fee_input().fair_l2_gas_price(), 1_500);
+
+        price.update(0);
+        assert_eq!(provider.get_batch_fee_input().fair_l2_gas_price(), 1_313);
+    }
+
+    #[tokio::test]
+    async fn dynamic_l2_gas_price_round_trip_with_pubdata_independent_model() {
+        let params = DynamicL2GasPriceParams {
+            min_price: 1_000,
+            max_price: 2_000,
+            target_miniblock_gas: 1_000_000,
+            max_change_denominator: 8,
+        };
+        let config = FeeModelConfig::V2(FeeModelConfigV2 {
+            minimal_l2_gas_price: 1_000,
+            compute_overhead_part: 1.0,
+            pubdata_overhead_part: 0.0,
+            batch_overhead_l1_gas: 1_000_000,
+            max_gas_per_batch: 50_000_000,
+            max_pubdata_per_batch: 100_000,
+        });
+        let price = Arc::new(DynamicL2GasPrice::new(params, 1_500));
+        let provider = MainNodeFeeInputProvider::new(Arc::new(MockL1GasPriceProvider), config)
+            .with_dynamic_l2_gas_price(price.clone());
+        let fee_input = provider.get_batch_fee_input();
+        // The fair L2 gas price includes the compute overhead on top of the dynamic price.
+        assert!(fee_input.fair_l2_gas_price() > 1_500);
+
+        let pool = ConnectionPool::test_pool().await;
+        let mut storage = pool.access_storage().await.unwrap();
+        storage
+            .fee_model_dal()
+            .set_dynamic_l2_gas_price(MiniblockNumber(1), price.get())
+            .await
+            .unwrap();
+
+        // The restored price must not drift because of the overhead.
+        let restored_price = Arc::new(DynamicL2GasPrice::load(params, &mut storage).await.unwrap());
+        assert_eq!(restored_price.get(), 1_500);
+        let provider = MainNodeFeeInputProvider::new(Arc::new(MockL1GasPriceProvider), config)
+            .with_dynamic_l2_gas_price(restored_price);
+        assert_eq!(provider.get_batch_fee_input(), fee_input);
+
+        let followed_price = Arc::new(DynamicL2GasPrice::new(params, 1_000));
+        let (stop_sender, stop_receiver) = watch::channel(false);
+        let follower_task = tokio::spawn(
+            followed_price
+                .clone()
+                .follow_storage(pool.clone(), stop_receiver),
+        );
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while followed_price.get() != 1_500 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("timed out waiting for the followed price");
+        stop_sender.send_replace(true);
+        follower_task.await.unwrap().unwrap();
+    }
+
+    #[derive(Debug)]
+    struct MockBlobGasPriceProvider;
+
+    impl L1GasPriceProvider for MockBlobGasPriceProvider {
+        fn estimate_effective_gas_price(&self) -> u64 {
+            1_000_000_000
+        }
+
+        fn estimate_effective_pubdata_price(&self) -> u64 {
+            1_000
+        }
+    }
+
+    #[test]
+    fn main_node_fee_input_provider_with_cheap_pubdata() {
+        let config = FeeModelConfig::V1(FeeModelConfigV1 {
+            minimal_l2_gas_price: 1_000,
+        });
+        let provider = MainNodeFeeInputProvider::new(Arc::new(MockL1GasPriceProvider), config);
+        assert_eq!(provider.get_batch_fee_input().l1_gas_price(), 1_000_000_000);
+
+        // The L1 gas price is not lowered because of the cheap pubdata, so that the batch overhead is charged.
+        let provider = MainNodeFeeInputProvider::new(Arc::new(MockBlobGasPriceProvider), config);
+        assert_eq!(provider.get_batch_fee_input().l1_gas_price(), 1_000_000_000);
+        assert_eq!(provider.get_batch_fee_input().fair_l2_gas_price(), 1_000);
+
+        // The pubdata-independent model receives both prices as-is.
+        let config = FeeModelConfig::V2(FeeModelConfigV2 {
+            minimal_l2_gas_price: 1_000,
+            compute_overhead_part: 0.0,
+            pubdata_overhead_part: 0.0,
+            batch_overhead_l1_gas: 1_000_000,
+            max_gas_per_batch: 50_000_000,
+            max_pubdata_per_batch: 100_000,
+        });
+        let provider = MainNodeFeeInputProvider::new(Arc::new(MockBlobGasPriceProvider), config);
+        let fee_input = provider.get_batch_fee_input();
+        assert_eq!(fee_input.l1_gas_price(), 1_000_000_000);
+        assert_eq!(fee_input.fair_pubdata_price(), 1_000);
+    }
+
+    #[test]
+    fn main_node_fee_input_provider_with_base_token() {
+        let config = FeeModelConfig::V2(FeeModelConfigV2 {
+            minimal_l2_gas_price: 1_000,
+            compute_overhead_part: 0.0,
+            pubdata_overhead_part: 0.0,
+            batch_overhead_l1_gas: 1_000_000,
+            max_gas_per_batch: 50_000_000,
+            max_pubdata_per_batch: 100_000,
+        });
+        let ratio = BaseTokenConversionRatio::new(3, 2).unwrap();
+        let provider = MainNodeFeeInputProvider::new(Arc::new(MockL1GasPriceProvider), config)
+            .with_base_token_conversion_ratio(ratio);
+
+        let FeeParams::V2(params) = provider.get_fee_model_params() else {
+            panic!("Unexpected fee params");
+        };
+        assert_eq!(params.l1_gas_price, 1_500_000_000);
+        assert_eq!(params.l1_pubdata_price, 25_500_000_000);
+        assert_eq!(params.config.minimal_l2_gas_price, 1_000);
+
+        let fee_input = provider.get_batch_fee_input();
+        assert_eq!(fee_input.l1_gas_price(), 1_500_000_000);
+        assert_eq!(fee_input.fair_l2_gas_price(), 1_000);
+        assert_eq!(fee_input.fair_pubdata_price(), 25_500_000_000);
+    }
+
+    #[test]
+    fn main_node_fee_input_provider_in_validium_mode() {
+        let config = FeeModelConfig::V2(FeeModelConfigV2 {
+            minimal_l2_gas_price: 1_000,
+            compute_overhead_part: 0.0,
+            pubdata_overhead_part: 0.0,
+            batch_overhead_l1_gas: 1_000_000,
+            max_gas_per_batch: 50_000_000,
+            max_pubdata_per_batch: 100_000,
+        });
+        let provider = MainNodeFeeInputProvider::new(Arc::new(MockL1GasPriceProvider), config)
+            .with_commitment_mode(L1BatchCommitmentMode::Validium);
+        let fee_input = provider.get_batch_fee_input();
+        assert_eq!(fee_input.l1_gas_price(), 1_000_000_000);
+        assert_eq!(fee_input.fair_pubdata_price(), 0);
+    }
+
+    #[test]
+    fn main_node_fee_input_provider_in_validium_mode_with_l1_pegged_model() {
+        let config = FeeModelConfig::V1(FeeModelConfigV1 {
+            minimal_l2_gas_price: 1_000,
+        });
+        let provider = MainNodeFeeInputProvider::new(Arc::new(MockL1GasPriceProvider), config)
+            .with_commitment_mode(L1BatchCommitmentMode::Validium);
+        let fee_input = provider.get_batch_fee_input();
+        // The L1 gas price must not be lowered because of the zero pubdata price.
+        assert_eq!(fee_input.l1_gas_price(), 1_000_000_000);
+        assert_eq!(fee_input.fair_l2_gas_price(), 1_000);
+    }
+
+    #[tokio::test]
+    async fn main_node_fee_input_provider_with_settings() {
+        let state_keeper_config = StateKeeperConfig {
+            fair_l2_gas_price: 1_000,
+            ..StateKeeperConfig::for_tests()
+        };
+        let gas_adjuster_config = ETHSenderConfig::for_tests().gas_adjuster;
+        let settings = FeeModelSettingsHandle::new(&state_keeper_config, &gas_adjuster_config);
+        assert_eq!(
+            settings.get().l1_gas_price_scale_factor,
+            gas_adjuster_config.internal_l1_pricing_multiplier
+        );
+        let config = FeeModelConfig::V2(FeeModelConfigV2 {
+            minimal_l2_gas_price: 1_000,
+            compute_overhead_part: 0.0,
+            pubdata_overhead_part: 0.0,
+            batch_overhead_l1_gas: 1_000_000,
+            max_gas_per_batch: 50_000_000,
+            max_pubdata_per_batch: 100_000,
+        });
+        let provider = MainNodeFeeInputProvider::new(Arc::new(MockL1GasPriceProvider), config)
+            .with_settings(settings.clone());
+        let fee_input = provider.get_batch_fee_input();
+        assert_eq!(fee_input.l1_gas_price(), 1_000_000_000);
+        assert_eq!(fee_input.fair_l2_gas_price(), 1_000);
+        assert_eq!(fee_input.fair_pubdata_price(), 17_000_000_000);
+
+        let pool = ConnectionPool::test_pool().await;
+        let mut storage = pool.access_storage().await.unwrap();
+        let update = FeeModelSettingsUpdate {
+            minimal_l2_gas_price: Some(2_000.into()),
+            l1_pubdata_price_scale_factor: Some(0.5),
+            ..FeeModelSettingsUpdate::default()
+        };
+        let updated_settings = settings.update(update, "test", &mut storage).await.unwrap();
+        assert_eq!(updated_settings.minimal_l2_gas_price, 2_000.into());
+        assert_eq!(
+            updated_settings.l1_gas_price_scale_factor,
+            gas_adjuster_config.internal_l1_pricing_multiplier
+        );
+        assert_eq!(updated_settings.l1_pubdata_price_scale_factor, 0.5);
+
+        // Scale factors are applied by the L1 gas price provider, so they don't influence prices returned by the mock.
+        let fee_input = provider.get_batch_fee_input();
+        assert_eq!(fee_input.l1_gas_price(), 1_000_000_000);
+        assert_eq!(fee_input.fair_l2_gas_price(), 2_000);
+        assert_eq!(fee_input.fair_pubdata_price(), 17_000_000_000);
+
+        // Invalid updates are rejected as a whole.
+        let update = FeeModelSettingsUpdate {
+            minimal_l2_gas_price: Some(3_000.into()),
+            l1_gas_price_scale_factor: Some(-1.0),
+            ..FeeModelSettingsUpdate::default()
+        };
+        settings
+            .update(update, "test", &mut storage)
+            .await
+            .unwrap_err();
+        assert_eq!(settings.get(), updated_settings);
+
+        // Updated settings are persisted and are picked up by other processes.
+        let other_settings =
+            FeeModelSettingsHandle::new(&state_keeper_config, &gas_adjuster_config);
+        other_settings.load(&mut storage).await.unwrap();
+        assert_eq!(other_settings.get(), updated_settings);
+
+        let update = FeeModelSettingsUpdate {
+            l1_gas_price_scale_factor: Some(1.5),
+            ..FeeModelSettingsUpdate::default()
+        };
+        let updated_settings = settings.update(update, "test", &mut storage).await.unwrap();
+        drop(storage);
+        let (stop_sender, stop_receiver) = watch::channel(false);
+        let mut settings_receiver = other_settings.subscribe();
+        let follower_task = tokio::spawn(other_settings.follow_storage(pool, stop_receiver));
+        tokio::time::timeout(
+            Duration::from_secs(10),
+            settings_receiver.wait_for(|settings| *settings == updated_settings),
+        )
+        .await
+        .expect("timed out waiting for the followed settings")
+        .unwrap();
+        stop_sender.send_replace(true);
+        follower_task.await.unwrap().unwrap();
+    }
+
     #[test]
     fn test_compute_batch_fee_model_input_v2_giant_numbers() {
         let config = FeeModelConfigV2 {
@@ -8,7 +8,7 @@ use zksync_dal::StorageProcessor;
 use zksync_merkle_tree::domain::ZkSyncTree;
 use zksync_types::{
     block::{BlockGasCount, DeployedContract, L1BatchHeader, MiniblockHasher, MiniblockHeader},
-    commitment::{L1BatchCommitment, L1BatchMetadata},
+    commitment::{L1BatchCommitment, L1BatchCommitmentMode, L1BatchMetadata},
     fee_model::BatchFeeInput,
     get_code_key, get_system_context_init_logs,
     protocol_version::{L1VerifierConfig, ProtocolVersion},
@@ -109,6 +109,8 @@ pub async fn ensure_genesis_state(
         H256::zero(),
         H256::zero(),
         protocol_version.is_pre_boojum(),
+        // The genesis L1 batch has no state diffs, so the commitment mode doesn't influence its commitment.
+        L1BatchCommitmentMode::Rollup,
     );
 
     save_genesis_l1_batch_metadata(
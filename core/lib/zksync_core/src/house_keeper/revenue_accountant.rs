@@ -0,0 +1,307 @@
+//! Accounting of operator revenue and expenditures per L1 batch.
+
+use anyhow::Context as _;
+use async_trait::async_trait;
+use vise::{Gauge, Metrics};
+use zksync_commitment_utils::kzg;
+use zksync_dal::{revenue_dal::L1BatchRevenueInputs, ConnectionPool};
+use zksync_system_constants::L1_GAS_PER_PUBDATA_BYTE;
+use zksync_types::{
+    aggregated_operations::AggregatedActionType, api::L1BatchRevenue,
+    commitment::L1BatchCommitmentMode, fee_model::BaseTokenConversionRatio, L1BatchNumber, U256,
+};
+
+use crate::house_keeper::periodic_job::PeriodicJob;
+
+/// Maximum number of L1 batches processed in a single iteration.
+const MAX_L1_BATCHES_PER_ITERATION: u32 = 100;
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "server_revenue")]
+struct RevenueMetrics {
+    /// Total fees collected from L2 transactions, in wei.
+    collected_fees_wei: Gauge<f64>,
+    /// Total cost of publishing pubdata on L1, in wei.
+    pubdata_cost_wei: Gauge<f64>,
+    /// Total cost of L1 transactions committing, proving and executing L1 batches (including pubdata), in wei.
+    l1_cost_wei: Gauge<f64>,
+    /// Last L1 batch with computed revenue.
+    last_l1_batch: Gauge<u64>,
+}
+
+#[vise::register]
+static METRICS: vise::Global<RevenueMetrics> = vise::Global::new();
+
+fn u256_to_f64(value: U256) -> f64 {
+    // Precision loss is acceptable for metrics.
+    value.to_string().parse().unwrap_or(f64::INFINITY)
+}
+
+/// Computes revenue for an L1 batch. Returns `None` if some of the L1 transactions processing the batch
+/// are not confirmed yet.
+fn compute_revenue(
+    l1_batch_number: L1BatchNumber,
+    inputs: &L1BatchRevenueInputs,
+    commitment_mode: L1BatchCommitmentMode,
+    base_token_ratio: BaseTokenConversionRatio,
+) -> Option<L1BatchRevenue> {
+    let mut revenue = L1BatchRevenue {
+        l1_batch_number,
+        collected_fees: base_token_ratio.convert_to_wei(inputs.collected_fees),
+        pubdata_cost: U256::zero(),
+        commit_cost: U256::zero(),
+        prove_cost: U256::zero(),
+        execute_cost: U256::zero(),
+    };
+    let mut commit_tx_cost = None;
+    let mut processed_actions = 0;
+    for tx_cost in &inputs.eth_tx_costs {
+        // L1 transactions may process several L1 batches; split their cost evenly among batches.
+        let cost = U256::from(tx_cost.gas_used) * U256::from(tx_cost.gas_price)
+            / U256::from(tx_cost.l1_batch_count.max(1));
+        let target = match tx_cost.tx_type {
+            AggregatedActionType::Commit => {
+                commit_tx_cost = Some(tx_cost);
+                &mut revenue.commit_cost
+            }
+            AggregatedActionType::PublishProofOnchain => &mut revenue.prove_cost,
+            AggregatedActionType::Execute => &mut revenue.execute_cost,
+        };
+        *target = cost;
+        processed_actions += 1;
+    }
+    if processed_actions < 3 {
+        return None;
+    }
+
+    let commit_tx_cost = commit_tx_cost?;
+    if commitment_mode == L1BatchCommitmentMode::Validium || inputs.pubdata_in_da_layer {
+        // Pubdata isn't published on L1.
+        return Some(revenue);
+    }
+    if let Some(blob_base_fee_per_gas) = commit_tx_cost.blob_base_fee_per_gas {
+        // Blob gas is paid on top of the execution gas of the commit transaction, and is not shared among batches.
+        let blob_gas = kzg::blob_count(inputs.pubdata_len) * kzg::BYTES_PER_BLOB;
+        revenue.pubdata_cost = U256::from(blob_gas) * U256::from(blob_base_fee_per_gas);
+    } else {
+        // Pubdata is published in the commit transaction calldata, so its cost is carved out of the commit cost.
+        let pubdata_gas = U256::from(inputs.pubdata_len) * U256::from(L1_GAS_PER_PUBDATA_BYTE);
+        let pubdata_cost =
+            (pubdata_gas * U256::from(commit_tx_cost.gas_price)).min(revenue.commit_cost);
+        revenue.pubdata_cost = pubdata_cost;
+        revenue.commit_cost -= pubdata_cost;
+    }
+    Some(revenue)
+}
+
+/// Aggregates fees collected by the operator and its L1 expenditures for each L1 batch processed on L1.
+#[derive(Debug)]
+pub struct RevenueAccountant {
+    reporting_interval_ms: u64,
+    connection_pool: ConnectionPool,
+    commitment_mode: L1BatchCommitmentMode,
+    base_token_ratio: BaseTokenConversionRatio,
+}
+
+impl RevenueAccountant {
+    pub fn new(reporting_interval_ms: u64, connection_pool: ConnectionPool) -> Self {
+        Self {
+            reporting_interval_ms,
+            connection_pool,
+            commitment_mode: L1BatchCommitmentMode::Rollup,
+            base_token_ratio: BaseTokenConversionRatio::default(),
+        }
+    }
+
+    /// Sets the commitment mode of the chain. In the validium mode, pubdata is not published on L1.
+    pub fn with_commitment_mode(mut self, mode: L1BatchCommitmentMode) -> Self {
+        self.commitment_mode = mode;
+        self
+    }
+
+    /// Sets the conversion ratio used to convert fees paid in the base token to wei.
+    pub fn with_base_token_conversion_ratio(mut self, ratio: BaseTokenConversionRatio) -> Self {
+        self.base_token_ratio = ratio;
+        self
+    }
+
+    async fn process_l1_batches(&self) -> anyhow::Result<()> {
+        let mut conn = self
+            .connection_pool
+            .access_storage_tagged("house_keeper")
+            .await?;
+        let last_processed_l1_batch = conn
+            .revenue_dal()
+            .get_last_l1_batch_with_revenue()
+            .await
+            .context("get_last_l1_batch_with_revenue()")?;
+        // The genesis L1 batch is never processed on L1.
+        let first_l1_batch = last_processed_l1_batch.map_or(L1BatchNumber(1), |number| number + 1);
+
+        for l1_batch_number in
+            (first_l1_batch.0..first_l1_batch.0 + MAX_L1_BATCHES_PER_ITERATION).map(L1BatchNumber)
+        {
+            let Some(inputs) = conn
+                .revenue_dal()
+                .get_l1_batch_revenue_inputs(l1_batch_number)
+                .await
+                .with_context(|| format!("get_l1_batch_revenue_inputs({l1_batch_number})"))?
+            else {
+                break;
+            };
+            let Some(revenue) = compute_revenue(
+                l1_batch_number,
+                &inputs,
+                self.commitment_mode,
+                self.base_token_ratio,
+            ) else {
+                break;
+            };
+            conn.revenue_dal()
+                .insert_l1_batch_revenue(&revenue)
+                .await
+                .with_context(|| format!("insert_l1_batch_revenue({l1_batch_number})"))?;
+            tracing::debug!("Computed revenue for L1 batch #{l1_batch_number}: {revenue:?}");
+            METRICS.last_l1_batch.set(l1_batch_number.0.into());
+        }
+
+        let totals = conn
+            .revenue_dal()
+            .get_revenue_totals()
+            .await
+            .context("get_revenue_totals()")?;
+        METRICS
+            .collected_fees_wei
+            .set(u256_to_f64(totals.collected_fees));
+        METRICS
+            .pubdata_cost_wei
+            .set(u256_to_f64(totals.pubdata_cost));
+        METRICS.l1_cost_wei.set(u256_to_f64(totals.l1_cost));
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl PeriodicJob for RevenueAccountant {
+    const SERVICE_NAME: &'static str = "RevenueAccountant";
+
+    async fn run_routine_task(&mut self) -> anyhow::Result<()> {
+        self.process_l1_batches().await
+    }
+
+    fn polling_interval_ms(&self) -> u64 {
+        self.reporting_interval_ms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_dal::revenue_dal::L1BatchEthTxCost;
+
+    use super::*;
+
+    fn eth_tx_cost(tx_type: AggregatedActionType, l1_batch_count: u64) -> L1BatchEthTxCost {
+        L1BatchEthTxCost {
+            tx_type,
+            gas_used: 100_000,
+            gas_price: 10,
+            l1_batch_count,
+            blob_base_fee_per_gas: None,
+        }
+    }
+
+    fn revenue_inputs() -> L1BatchRevenueInputs {
+        L1BatchRevenueInputs {
+            collected_fees: 5_000_000.into(),
+            pubdata_len: 1_000,
+            pubdata_in_da_layer: false,
+            eth_tx_costs: vec![
+                eth_tx_cost(AggregatedActionType::Commit, 1),
+                eth_tx_cost(AggregatedActionType::PublishProofOnchain, 4),
+                eth_tx_cost(AggregatedActionType::Execute, 2),
+            ],
+        }
+    }
+
+    fn compute_rollup_revenue(inputs: &L1BatchRevenueInputs) -> Option<L1BatchRevenue> {
+        compute_revenue(
+            L1BatchNumber(1),
+            inputs,
+            L1BatchCommitmentMode::Rollup,
+            BaseTokenConversionRatio::default(),
+        )
+    }
+
+    #[test]
+    fn computing_revenue_with_calldata_pubdata() {
+        let mut inputs = revenue_inputs();
+        let execute_cost = inputs.eth_tx_costs.pop().unwrap();
+        // Not executed yet.
+        assert_eq!(compute_rollup_revenue(&inputs), None);
+
+        inputs.eth_tx_costs.push(execute_cost);
+        let revenue = compute_rollup_revenue(&inputs).unwrap();
+        assert_eq!(
+            revenue,
+            L1BatchRevenue {
+                l1_batch_number: L1BatchNumber(1),
+                collected_fees: 5_000_000.into(),
+                pubdata_cost: 170_000.into(),
+                commit_cost: 830_000.into(),
+                prove_cost: 250_000.into(),
+                execute_cost: 500_000.into(),
+            }
+        );
+        assert_eq!(revenue.l1_cost(), 1_750_000.into());
+    }
+
+    #[test]
+    fn computing_revenue_with_blob_pubdata() {
+        let mut inputs = revenue_inputs();
+        inputs.eth_tx_costs[0].blob_base_fee_per_gas = Some(3);
+        let revenue = compute_rollup_revenue(&inputs).unwrap();
+        assert_eq!(revenue.pubdata_cost, (3 * kzg::BYTES_PER_BLOB).into());
+        assert_eq!(revenue.commit_cost, 1_000_000.into());
+        assert_eq!(
+            revenue.l1_cost(),
+            (1_750_000 + 3 * kzg::BYTES_PER_BLOB).into()
+        );
+    }
+
+    #[test]
+    fn computing_revenue_without_l1_pubdata() {
+        let inputs = revenue_inputs();
+        let revenue = compute_revenue(
+            L1BatchNumber(1),
+            &inputs,
+            L1BatchCommitmentMode::Validium,
+            BaseTokenConversionRatio::default(),
+        )
+        .unwrap();
+        assert_eq!(revenue.pubdata_cost, U256::zero());
+        assert_eq!(revenue.commit_cost, 1_000_000.into());
+        assert_eq!(revenue.l1_cost(), 1_750_000.into());
+
+        let mut inputs = revenue_inputs();
+        inputs.pubdata_in_da_layer = true;
+        let revenue = compute_rollup_revenue(&inputs).unwrap();
+        assert_eq!(revenue.pubdata_cost, U256::zero());
+        assert_eq!(revenue.commit_cost, 1_000_000.into());
+    }
+
+    #[test]
+    fn computing_revenue_with_custom_base_token() {
+        let inputs = revenue_inputs();
+        // 1 wei is worth 2 base token units.
+        let ratio = BaseTokenConversionRatio::new(2, 1).unwrap();
+        let revenue = compute_revenue(
+            L1BatchNumber(1),
+            &inputs,
+            L1BatchCommitmentMode::Rollup,
+            ratio,
+        )
+        .unwrap();
+        assert_eq!(revenue.collected_fees, 2_500_000.into());
+        assert_eq!(revenue.l1_cost(), 1_750_000.into());
+    }
+}
@@ -1,12 +1,17 @@
 //! Gas adjuster metrics.
 
-use vise::{Gauge, Metrics};
+use vise::{Counter, Gauge, Metrics};
 
 #[derive(Debug, Metrics)]
 #[metrics(prefix = "server_gas_adjuster")]
 pub(super) struct GasAdjusterMetrics {
     pub current_base_fee_per_gas: Gauge<u64>,
+    /// Base fee estimate, i.e., the configured percentile of base fees (by default, the median).
     pub median_base_fee_per_gas: Gauge<u64>,
+    /// Number of base fee samples capped by spike smoothing.
+    pub smoothed_spikes: Counter,
+    pub current_blob_base_fee: Gauge<u64>,
+    pub median_blob_base_fee: Gauge<u64>,
 }
 
 #[vise::register]
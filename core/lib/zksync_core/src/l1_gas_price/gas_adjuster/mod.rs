@@ -6,9 +6,10 @@ use std::{
 };
 
 use tokio::sync::watch;
-use zksync_config::GasAdjusterConfig;
+use zksync_config::{configs::eth_sender::PubdataSendingMode, GasAdjusterConfig};
 use zksync_eth_client::{Error, EthInterface};
 use zksync_system_constants::L1_GAS_PER_PUBDATA_BYTE;
+use zksync_types::api::{FeeModelSettings, L1GasPriceStats};
 
 use self::metrics::METRICS;
 use super::{L1GasPriceProvider, L1TxParamsProvider};
@@ -18,17 +19,32 @@ mod metrics;
 #[cfg(test)]
 mod tests;
 
-/// This component keeps track of the median base_fee from the last `max_base_fee_samples` blocks.
-/// It is used to adjust the base_fee of transactions sent to L1.
+/// This component keeps track of the base_fee estimate from the last `max_base_fee_samples` blocks. The estimate
+/// is the configured percentile of base fees in this window (by default, the median); base fee spikes
+/// may be smoothed out before they get into the window. The estimate is used to adjust the base_fee
+/// of transactions sent to L1.
+///
+/// Additionally, it keeps track of the median EIP-4844 blob base fee, which is used to price pubdata
+/// if it's published in blobs.
+///
+/// Pricing multipliers are taken from the config unless the adjuster follows fee model settings changed
+/// at runtime (see [`Self::with_fee_model_settings()`]).
 #[derive(Debug)]
 pub struct GasAdjuster<E> {
     pub(super) statistics: GasStatistics,
+    pub(super) blob_base_fee_statistics: GasStatistics,
     pub(super) config: GasAdjusterConfig,
+    pubdata_sending_mode: PubdataSendingMode,
+    fee_model_settings: Option<watch::Receiver<FeeModelSettings>>,
     eth_client: E,
 }
 
 impl<E: EthInterface> GasAdjuster<E> {
-    pub async fn new(eth_client: E, config: GasAdjusterConfig) -> Result<Self, Error> {
+    pub async fn new(
+        eth_client: E,
+        config: GasAdjusterConfig,
+        pubdata_sending_mode: PubdataSendingMode,
+    ) -> Result<Self, Error> {
         // Subtracting 1 from the "latest" block number to prevent errors in case
         // the info about the latest block is not yet present on the node.
         // This sometimes happens on Infura.
@@ -40,13 +56,51 @@ impl<E: EthInterface> GasAdjuster<E> {
         let history = eth_client
             .base_fee_history(current_block, config.max_base_fee_samples, "gas_adjuster")
             .await?;
+        let blob_samples = config.num_samples_for_blob_base_fee_estimate();
+        let blob_history = eth_client
+            .blob_base_fee_history(current_block, blob_samples, "gas_adjuster")
+            .await?;
         Ok(Self {
-            statistics: GasStatistics::new(config.max_base_fee_samples, current_block, &history),
+            statistics: GasStatistics::with_params(
+                config.max_base_fee_samples,
+                EstimateParams::new(&config),
+                current_block,
+                &history,
+            ),
+            blob_base_fee_statistics: GasStatistics::new(
+                blob_samples,
+                current_block,
+                &blob_history,
+            ),
             eth_client,
             config,
+            pubdata_sending_mode,
+            fee_model_settings: None,
         })
     }
 
+    /// Makes the adjuster use scale factors from the fee model settings instead of the internal pricing multipliers
+    /// from the config.
+    pub fn with_fee_model_settings(mut self, settings: watch::Receiver<FeeModelSettings>) -> Self {
+        self.fee_model_settings = Some(settings);
+        self
+    }
+
+    fn l1_pricing_multiplier(&self) -> f64 {
+        self.fee_model_settings
+            .as_ref()
+            .map_or(self.config.internal_l1_pricing_multiplier, |settings| {
+                settings.borrow().l1_gas_price_scale_factor
+            })
+    }
+
+    fn pubdata_pricing_multiplier(&self) -> f64 {
+        self.fee_model_settings.as_ref().map_or_else(
+            || self.config.internal_pubdata_pricing_multiplier(),
+            |settings| settings.borrow().l1_pubdata_price_scale_factor,
+        )
+    }
+
     /// Performs an actualization routine for `GasAdjuster`.
     /// This method is intended to be invoked periodically.
     pub async fn keep_updated(&self) -> Result<(), Error> {
@@ -77,6 +131,22 @@ impl<E: EthInterface> GasAdjuster<E> {
                 .current_base_fee_per_gas
                 .set(*history.last().unwrap());
             self.statistics.add_samples(&history);
+
+            let blob_history = self
+                .eth_client
+                .blob_base_fee_history(
+                    current_block,
+                    current_block - last_processed_block,
+                    "gas_adjuster",
+                )
+                .await?;
+            if let Some(&current_blob_base_fee) = blob_history.last() {
+                METRICS.current_blob_base_fee.set(current_blob_base_fee);
+                self.blob_base_fee_statistics.add_samples(&blob_history);
+                METRICS
+                    .median_blob_base_fee
+                    .set(self.blob_base_fee_statistics.estimate());
+            }
         }
         Ok(())
     }
@@ -94,6 +164,18 @@ impl<E: EthInterface> GasAdjuster<E> {
         gas_price
     }
 
+    fn bound_blob_base_fee(&self, blob_base_fee: u64) -> u64 {
+        let max_blob_base_fee = self.config.max_blob_base_fee();
+        if blob_base_fee > max_blob_base_fee {
+            tracing::warn!(
+                "Effective blob base fee is too high: {blob_base_fee}, using max allowed: {max_blob_base_fee}"
+            );
+            KEEPER_METRICS.gas_price_too_high.inc();
+            return max_blob_base_fee;
+        }
+        blob_base_fee
+    }
+
     pub async fn run(self: Arc<Self>, stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
         loop {
             if *stop_receiver.borrow() {
@@ -121,16 +203,58 @@ impl<E: EthInterface> L1GasPriceProvider for GasAdjuster<E> {
 
         let effective_gas_price = self.get_base_fee(0) + self.get_priority_fee();
 
-        let calculated_price =
-            (self.config.internal_l1_pricing_multiplier * effective_gas_price as f64) as u64;
+        let calculated_price = (self.l1_pricing_multiplier() * effective_gas_price as f64) as u64;
 
         // Bound the price if it's too high.
         self.bound_gas_price(calculated_price)
     }
 
+    /// Returns the price of publishing 1 byte of pubdata, in wei. If pubdata is published in blobs, the price
+    /// is based on the median blob base fee (1 byte of blob data costs 1 unit of blob gas).
     fn estimate_effective_pubdata_price(&self) -> u64 {
-        // For now, pubdata is only sent via calldata, so its price is pegged to the L1 gas price.
-        self.estimate_effective_gas_price() * L1_GAS_PER_PUBDATA_BYTE as u64
+        let calldata_price = self.estimate_effective_gas_price() * L1_GAS_PER_PUBDATA_BYTE as u64;
+        match self.pubdata_sending_mode {
+            // Costs of external DA layers are not tracked, so pubdata is conservatively priced as if it's sent
+            // in calldata.
+            PubdataSendingMode::Calldata | PubdataSendingMode::Custom => calldata_price,
+            PubdataSendingMode::Blobs => {
+                let median = self.blob_base_fee_statistics.estimate();
+                if median == 0 {
+                    // Blob base fee is at least 1 wei after the Dencun upgrade; zero means that L1 doesn't support blobs.
+                    tracing::warn!(
+                        "L1 doesn't report blob base fees, pricing pubdata as if it's sent in calldata"
+                    );
+                    return calldata_price;
+                }
+                let calculated_price = (self.pubdata_pricing_multiplier() * median as f64) as u64;
+                self.bound_blob_base_fee(calculated_price)
+            }
+        }
+    }
+
+    fn stats(&self) -> Option<L1GasPriceStats> {
+        let (last_processed_block, window_size, base_fee_estimate, last_base_fee, smoothed_spikes) = {
+            let statistics = self.statistics.0.read().unwrap();
+            (
+                statistics.last_processed_block,
+                statistics.samples.len(),
+                statistics.estimate(),
+                statistics.last_added_value(),
+                statistics.smoothed_spikes,
+            )
+        };
+        Some(L1GasPriceStats {
+            last_processed_l1_block: (last_processed_block as u64).into(),
+            window_size: (window_size as u64).into(),
+            base_fee_percentile: self.config.base_fee_percentile(),
+            base_fee_estimate: base_fee_estimate.into(),
+            last_base_fee: last_base_fee.into(),
+            priority_fee: self.get_priority_fee().into(),
+            smoothed_spikes: smoothed_spikes.into(),
+            blob_base_fee_estimate: self.blob_base_fee_statistics.estimate().into(),
+            effective_gas_price: self.estimate_effective_gas_price().into(),
+            effective_pubdata_price: self.estimate_effective_pubdata_price().into(),
+        })
     }
 }
 
@@ -149,9 +273,9 @@ impl<E: EthInterface> L1TxParamsProvider for GasAdjuster<E> {
         // The alternative is a linear one:
         // `let scale_factor = a + b * time_in_mempool as f64;`
         let scale_factor = a * b.powf(time_in_mempool as f64);
-        let median = self.statistics.median();
-        METRICS.median_base_fee_per_gas.set(median);
-        let new_fee = median as f64 * scale_factor;
+        let estimate = self.statistics.estimate();
+        METRICS.median_base_fee_per_gas.set(estimate);
+        let new_fee = estimate as f64 * scale_factor;
         new_fee as u64
     }
 
@@ -172,24 +296,88 @@ impl<E: EthInterface> L1TxParamsProvider for GasAdjuster<E> {
     fn get_priority_fee(&self) -> u64 {
         self.config.default_priority_fee_per_gas
     }
+
+    // Blob base fee can change by 12.5% per L1 block similarly to the base fee, so we don't go below
+    // the last observed value when it's higher than the median.
+    fn get_blob_base_fee(&self) -> u64 {
+        let estimate = self.blob_base_fee_statistics.estimate();
+        estimate.max(self.blob_base_fee_statistics.last_added_value())
+    }
+}
+
+/// Parameters of the fee estimate computed by [`GasStatistics`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct EstimateParams {
+    /// Percentile of the samples used as the estimate, in 0..=100.
+    pub percentile: f64,
+    /// If set, new samples are capped at this ratio of the current estimate.
+    pub max_spike_ratio: Option<f64>,
+}
+
+impl Default for EstimateParams {
+    fn default() -> Self {
+        Self::MEDIAN
+    }
+}
+
+impl EstimateParams {
+    /// Median of the samples without spike smoothing.
+    pub const MEDIAN: Self = Self {
+        percentile: 50.0,
+        max_spike_ratio: None,
+    };
+
+    fn new(config: &GasAdjusterConfig) -> Self {
+        let percentile = config.base_fee_percentile();
+        assert!(
+            (0.0..=100.0).contains(&percentile),
+            "Base fee percentile must be in 0..=100"
+        );
+        if let Some(ratio) = config.max_base_fee_spike_ratio {
+            assert!(ratio >= 1.0, "Max base fee spike ratio must be >= 1");
+        }
+        Self {
+            percentile,
+            max_spike_ratio: config.max_base_fee_spike_ratio,
+        }
+    }
+
+    /// Returns the index of the estimate in the sorted samples.
+    fn index(&self, samples_len: usize) -> usize {
+        let index = (self.percentile / 100.0 * samples_len as f64) as usize;
+        index.min(samples_len.saturating_sub(1))
+    }
 }
 
 /// Helper structure responsible for collecting the data about recent transactions,
-/// calculating the median base fee.
+/// calculating the fee estimate (by default, the median fee) over a sliding window of L1 blocks.
 #[derive(Debug, Clone, Default)]
 pub(super) struct GasStatisticsInner {
     samples: VecDeque<u64>,
-    median_cached: u64,
+    estimate_cached: u64,
     max_samples: usize,
+    params: EstimateParams,
+    smoothed_spikes: u64,
     last_processed_block: usize,
 }
 
 impl GasStatisticsInner {
     fn new(max_samples: usize, block: usize, fee_history: &[u64]) -> Self {
+        Self::with_params(max_samples, EstimateParams::MEDIAN, block, fee_history)
+    }
+
+    fn with_params(
+        max_samples: usize,
+        params: EstimateParams,
+        block: usize,
+        fee_history: &[u64],
+    ) -> Self {
         let mut statistics = Self {
             max_samples,
             samples: VecDeque::with_capacity(max_samples),
-            median_cached: 0,
+            estimate_cached: 0,
+            params,
+            smoothed_spikes: 0,
             last_processed_block: 0,
         };
 
@@ -201,25 +389,47 @@ impl GasStatisticsInner {
         }
     }
 
-    fn median(&self) -> u64 {
-        self.median_cached
+    fn estimate(&self) -> u64 {
+        self.estimate_cached
     }
 
     fn last_added_value(&self) -> u64 {
-        self.samples.back().copied().unwrap_or(self.median_cached)
+        self.samples.back().copied().unwrap_or(self.estimate_cached)
     }
 
     fn add_samples(&mut self, fees: &[u64]) {
-        self.samples.extend(fees);
+        // Spikes are capped relative to the estimate before the new samples; the initial history is never capped.
+        let max_sample = match self.params.max_spike_ratio {
+            Some(ratio) if self.estimate_cached > 0 => {
+                Some((self.estimate_cached as f64 * ratio) as u64)
+            }
+            _ => None,
+        };
+        for &fee in fees {
+            let sample = match max_sample {
+                Some(max_sample) if fee > max_sample => {
+                    tracing::debug!("Smoothed out L1 fee spike: {fee}, capped at {max_sample}");
+                    self.smoothed_spikes += 1;
+                    METRICS.smoothed_spikes.inc();
+                    max_sample
+                }
+                _ => fee,
+            };
+            self.samples.push_back(sample);
+        }
         self.last_processed_block += fees.len();
 
         let extra = self.samples.len().saturating_sub(self.max_samples);
         self.samples.drain(..extra);
 
         let mut samples: Vec<_> = self.samples.iter().cloned().collect();
-        let (_, &mut median, _) = samples.select_nth_unstable(self.samples.len() / 2);
+        if samples.is_empty() {
+            return;
+        }
+        let index = self.params.index(samples.len());
+        let (_, &mut estimate, _) = samples.select_nth_unstable(index);
 
-        self.median_cached = median;
+        self.estimate_cached = estimate;
     }
 }
 
@@ -235,8 +445,22 @@ impl GasStatistics {
         )))
     }
 
-    pub fn median(&self) -> u64 {
-        self.0.read().unwrap().median()
+    pub fn with_params(
+        max_samples: usize,
+        params: EstimateParams,
+        block: usize,
+        fee_history: &[u64],
+    ) -> Self {
+        Self(RwLock::new(GasStatisticsInner::with_params(
+            max_samples,
+            params,
+            block,
+            fee_history,
+        )))
+    }
+
+    pub fn estimate(&self) -> u64 {
+        self.0.read().unwrap().estimate()
     }
 
     pub fn last_added_value(&self) -> u64 {
@@ -1,17 +1,84 @@
 use std::{collections::VecDeque, sync::Arc};
 
-use zksync_config::GasAdjusterConfig;
+use tokio::sync::watch;
+use zksync_config::{configs::eth_sender::PubdataSendingMode, GasAdjusterConfig};
 use zksync_eth_client::clients::MockEthereum;
+use zksync_system_constants::L1_GAS_PER_PUBDATA_BYTE;
+use zksync_types::api::FeeModelSettings;
 
-use super::{GasAdjuster, GasStatisticsInner};
+use super::{EstimateParams, GasAdjuster, GasStatisticsInner};
+use crate::l1_gas_price::L1GasPriceProvider;
+
+fn test_config() -> GasAdjusterConfig {
+    GasAdjusterConfig {
+        default_priority_fee_per_gas: 5,
+        max_base_fee_samples: 5,
+        base_fee_percentile: None,
+        max_base_fee_spike_ratio: None,
+        pricing_formula_parameter_a: 1.5,
+        pricing_formula_parameter_b: 1.0005,
+        internal_l1_pricing_multiplier: 0.8,
+        internal_enforced_l1_gas_price: None,
+        poll_period: 5,
+        max_l1_gas_price: None,
+        internal_pubdata_pricing_multiplier: None,
+        max_blob_base_fee: None,
+        num_samples_for_blob_base_fee_estimate: Some(3),
+    }
+}
 
 /// Check that we compute the median correctly
 #[test]
 fn median() {
     // sorted: 4 4 6 7 8
-    assert_eq!(GasStatisticsInner::new(5, 5, &[6, 4, 7, 8, 4]).median(), 6);
+    assert_eq!(
+        GasStatisticsInner::new(5, 5, &[6, 4, 7, 8, 4]).estimate(),
+        6
+    );
     // sorted: 4 4 8 10
-    assert_eq!(GasStatisticsInner::new(4, 4, &[8, 4, 4, 10]).median(), 8);
+    assert_eq!(GasStatisticsInner::new(4, 4, &[8, 4, 4, 10]).estimate(), 8);
+}
+
+/// Check that we compute other percentiles correctly
+#[test]
+fn percentile() {
+    let params = |percentile| EstimateParams {
+        percentile,
+        max_spike_ratio: None,
+    };
+    // sorted: 4 4 6 7 8
+    let history = [6, 4, 7, 8, 4];
+    assert_eq!(
+        GasStatisticsInner::with_params(5, params(0.0), 5, &history).estimate(),
+        4
+    );
+    assert_eq!(
+        GasStatisticsInner::with_params(5, params(75.0), 5, &history).estimate(),
+        7
+    );
+    assert_eq!(
+        GasStatisticsInner::with_params(5, params(100.0), 5, &history).estimate(),
+        8
+    );
+}
+
+/// Check that base fee spikes are capped before they get into the samples window
+#[test]
+fn spike_smoothing() {
+    let params = EstimateParams {
+        percentile: 50.0,
+        max_spike_ratio: Some(2.0),
+    };
+    // The initial history is not smoothed.
+    let mut stats = GasStatisticsInner::with_params(5, params, 5, &[6, 4, 7, 100, 4]);
+    assert_eq!(stats.estimate(), 6);
+    assert_eq!(stats.smoothed_spikes, 0);
+
+    stats.add_samples(&[50, 11, 12]);
+    assert_eq!(stats.samples, VecDeque::from([100, 4, 12, 11, 12]));
+    assert_eq!(stats.smoothed_spikes, 1);
+    assert_eq!(stats.estimate(), 12);
+    assert_eq!(stats.last_added_value(), 12);
 }
 
 /// Check that we properly manage the block base fee queue
@@ -35,26 +102,157 @@ async fn kept_updated() {
 
     let adjuster = GasAdjuster::new(
         Arc::clone(&eth_client),
-        GasAdjusterConfig {
-            default_priority_fee_per_gas: 5,
-            max_base_fee_samples: 5,
-            pricing_formula_parameter_a: 1.5,
-            pricing_formula_parameter_b: 1.0005,
-            internal_l1_pricing_multiplier: 0.8,
-            internal_enforced_l1_gas_price: None,
-            poll_period: 5,
-            max_l1_gas_price: None,
-        },
+        test_config(),
+        PubdataSendingMode::Calldata,
     )
     .await
     .unwrap();
 
     assert_eq!(adjuster.statistics.0.read().unwrap().samples.len(), 5);
-    assert_eq!(adjuster.statistics.0.read().unwrap().median(), 6);
+    assert_eq!(adjuster.statistics.0.read().unwrap().estimate(), 6);
 
     eth_client.advance_block_number(3);
     adjuster.keep_updated().await.unwrap();
 
     assert_eq!(adjuster.statistics.0.read().unwrap().samples.len(), 5);
-    assert_eq!(adjuster.statistics.0.read().unwrap().median(), 7);
+    assert_eq!(adjuster.statistics.0.read().unwrap().estimate(), 7);
+}
+
+/// Check that blob base fees are tracked and used to price pubdata only if it's published in blobs
+#[tokio::test]
+async fn pubdata_price_with_blobs() {
+    let eth_client = Arc::new(
+        MockEthereum::default()
+            .with_fee_history(vec![0, 4, 6, 8, 7, 5, 5, 8, 10, 9])
+            .with_blob_fee_history(vec![0, 1, 2, 3, 20, 30, 3, 1, 2, 4]),
+    );
+    eth_client.advance_block_number(5);
+
+    let calldata_adjuster = GasAdjuster::new(
+        Arc::clone(&eth_client),
+        test_config(),
+        PubdataSendingMode::Calldata,
+    )
+    .await
+    .unwrap();
+    let adjuster = GasAdjuster::new(
+        Arc::clone(&eth_client),
+        GasAdjusterConfig {
+            internal_pubdata_pricing_multiplier: Some(2.0),
+            ..test_config()
+        },
+        PubdataSendingMode::Blobs,
+    )
+    .await
+    .unwrap();
+
+    // Blob base fees in blocks 2..=4 are `[2, 3, 20]`.
+    assert_eq!(adjuster.blob_base_fee_statistics.estimate(), 3);
+    assert_eq!(adjuster.estimate_effective_pubdata_price(), 6);
+    assert_eq!(
+        calldata_adjuster.estimate_effective_pubdata_price(),
+        calldata_adjuster.estimate_effective_gas_price() * L1_GAS_PER_PUBDATA_BYTE as u64
+    );
+
+    eth_client.advance_block_number(3);
+    adjuster.keep_updated().await.unwrap();
+    // Blob base fees in blocks 5..=7 are `[30, 3, 1]`.
+    assert_eq!(adjuster.blob_base_fee_statistics.estimate(), 3);
+    assert_eq!(adjuster.estimate_effective_pubdata_price(), 6);
+
+    let capped_adjuster = GasAdjuster::new(
+        Arc::clone(&eth_client),
+        GasAdjusterConfig {
+            internal_pubdata_pricing_multiplier: Some(10.0),
+            max_blob_base_fee: Some(25),
+            ..test_config()
+        },
+        PubdataSendingMode::Blobs,
+    )
+    .await
+    .unwrap();
+    assert_eq!(capped_adjuster.estimate_effective_pubdata_price(), 25);
+}
+
+/// Check that fee model settings replace internal pricing multipliers from the config rather than stack on top of them
+#[tokio::test]
+async fn pricing_multipliers_from_fee_model_settings() {
+    let eth_client = Arc::new(
+        MockEthereum::default()
+            .with_fee_history(vec![0, 4, 6, 8, 7, 5, 5, 8, 10, 9])
+            .with_blob_fee_history(vec![0, 1, 2, 3, 20, 30, 3, 1, 2, 4]),
+    );
+    eth_client.advance_block_number(5);
+    let config = GasAdjusterConfig {
+        internal_pubdata_pricing_multiplier: Some(2.0),
+        ..test_config()
+    };
+
+    let config_adjuster =
+        GasAdjuster::new(Arc::clone(&eth_client), config, PubdataSendingMode::Blobs)
+            .await
+            .unwrap();
+    let (settings_sender, settings) = watch::channel(FeeModelSettings {
+        minimal_l2_gas_price: 1.into(),
+        l1_gas_price_scale_factor: 1.0,
+        l1_pubdata_price_scale_factor: 1.0,
+    });
+    let adjuster = GasAdjuster::new(Arc::clone(&eth_client), config, PubdataSendingMode::Blobs)
+        .await
+        .unwrap()
+        .with_fee_model_settings(settings);
+
+    let unscaled_gas_price = adjuster.estimate_effective_gas_price();
+    assert_eq!(
+        config_adjuster.estimate_effective_gas_price(),
+        (config.internal_l1_pricing_multiplier * unscaled_gas_price as f64) as u64
+    );
+    // The median blob base fee is 3.
+    assert_eq!(config_adjuster.estimate_effective_pubdata_price(), 6);
+    assert_eq!(adjuster.estimate_effective_pubdata_price(), 3);
+
+    settings_sender.send_modify(|settings| {
+        settings.l1_gas_price_scale_factor = 2.0;
+        settings.l1_pubdata_price_scale_factor = 0.5;
+    });
+    assert_eq!(
+        adjuster.estimate_effective_gas_price(),
+        unscaled_gas_price * 2
+    );
+    assert_eq!(adjuster.estimate_effective_pubdata_price(), 1);
+}
+
+/// Check that the oracle internals are reported
+#[tokio::test]
+async fn reporting_stats() {
+    let eth_client = Arc::new(
+        MockEthereum::default()
+            .with_fee_history(vec![0, 4, 6, 8, 7, 5, 5, 8, 10, 9])
+            .with_blob_fee_history(vec![0, 1, 2, 3, 20, 30, 3, 1, 2, 4]),
+    );
+    eth_client.advance_block_number(5);
+
+    let config = GasAdjusterConfig {
+        base_fee_percentile: Some(80.0),
+        ..test_config()
+    };
+    let adjuster = GasAdjuster::new(Arc::clone(&eth_client), config, PubdataSendingMode::Blobs)
+        .await
+        .unwrap();
+
+    let stats = adjuster.stats().unwrap();
+    assert_eq!(stats.last_processed_l1_block, 4.into());
+    assert_eq!(stats.window_size, 5.into());
+    assert_eq!(stats.base_fee_percentile, 80.0);
+    // Base fees in blocks 0..=4 are `[0, 4, 6, 8, 7]`.
+    assert_eq!(stats.base_fee_estimate, 8.into());
+    assert_eq!(stats.last_base_fee, 7.into());
+    assert_eq!(stats.priority_fee, 5.into());
+    assert_eq!(stats.smoothed_spikes, 0.into());
+    assert_eq!(stats.blob_base_fee_estimate, 3.into());
+    assert_eq!(
+        stats.effective_gas_price,
+        adjuster.estimate_effective_gas_price().into()
+    );
+    assert_eq!(stats.effective_pubdata_price, 3.into());
 }
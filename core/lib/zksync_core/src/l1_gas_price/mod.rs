@@ -5,6 +5,7 @@ use std::fmt;
 pub use gas_adjuster::GasAdjuster;
 pub use main_node_fetcher::MainNodeFeeParamsFetcher;
 pub use singleton::GasAdjusterSingleton;
+use zksync_types::api::L1GasPriceStats;
 
 mod gas_adjuster;
 mod main_node_fetcher;
@@ -20,6 +21,11 @@ pub trait L1GasPriceProvider: fmt::Debug + 'static + Send + Sync {
     /// Returns a best guess of a realistic value for the L1 pubdata price.
     /// Return value is in wei.
     fn estimate_effective_pubdata_price(&self) -> u64;
+
+    /// Returns internals of the price estimation for debugging purposes, if the provider supports it.
+    fn stats(&self) -> Option<L1GasPriceStats> {
+        None
+    }
 }
 
 /// Extended version of `L1GasPriceProvider` that can provide parameters
@@ -35,4 +41,7 @@ pub trait L1TxParamsProvider: L1GasPriceProvider {
 
     /// Returns a lower bound for the `base_fee` value for the next L1 block.
     fn get_next_block_minimal_base_fee(&self) -> u64;
+
+    /// Returns the recommended `max_fee_per_blob_gas` value (EIP4844). Returns 0 if L1 doesn't support blobs.
+    fn get_blob_base_fee(&self) -> u64;
 }
@@ -5,8 +5,9 @@ use tokio::{
     sync::{watch, OnceCell},
     task::JoinHandle,
 };
-use zksync_config::GasAdjusterConfig;
+use zksync_config::{configs::eth_sender::PubdataSendingMode, GasAdjusterConfig};
 use zksync_eth_client::clients::QueryClient;
+use zksync_types::api::FeeModelSettings;
 
 use crate::l1_gas_price::GasAdjuster;
 
@@ -14,8 +15,10 @@ use crate::l1_gas_price::GasAdjuster;
 /// This is needed only for running the server.
 #[derive(Debug)]
 pub struct GasAdjusterSingleton {
-    web3_url: String,
+    web3_urls: Vec<String>,
     gas_adjuster_config: GasAdjusterConfig,
+    pubdata_sending_mode: PubdataSendingMode,
+    fee_model_settings: Option<watch::Receiver<FeeModelSettings>>,
     singleton: OnceCell<Result<Arc<GasAdjuster<QueryClient>>, Error>>,
 }
 
@@ -30,23 +33,42 @@ impl From<anyhow::Error> for Error {
 }
 
 impl GasAdjusterSingleton {
-    pub fn new(web3_url: String, gas_adjuster_config: GasAdjusterConfig) -> Self {
+    pub fn new(
+        web3_urls: Vec<String>,
+        gas_adjuster_config: GasAdjusterConfig,
+        pubdata_sending_mode: PubdataSendingMode,
+    ) -> Self {
         Self {
-            web3_url,
+            web3_urls,
             gas_adjuster_config,
+            pubdata_sending_mode,
+            fee_model_settings: None,
             singleton: OnceCell::new(),
         }
     }
 
+    /// Makes the created `GasAdjuster` follow the fee model settings (see [`GasAdjuster::with_fee_model_settings()`]).
+    pub fn with_fee_model_settings(mut self, settings: watch::Receiver<FeeModelSettings>) -> Self {
+        self.fee_model_settings = Some(settings);
+        self
+    }
+
     pub async fn get_or_init(&mut self) -> Result<Arc<GasAdjuster<QueryClient>>, Error> {
         let adjuster = self
             .singleton
             .get_or_init(|| async {
-                let query_client =
-                    QueryClient::new(&self.web3_url).context("QueryClient::new()")?;
-                let adjuster = GasAdjuster::new(query_client.clone(), self.gas_adjuster_config)
-                    .await
-                    .context("GasAdjuster::new()")?;
+                let query_client = QueryClient::with_failover(&self.web3_urls)
+                    .context("QueryClient::with_failover()")?;
+                let mut adjuster = GasAdjuster::new(
+                    query_client.clone(),
+                    self.gas_adjuster_config,
+                    self.pubdata_sending_mode,
+                )
+                .await
+                .context("GasAdjuster::new()")?;
+                if let Some(settings) = &self.fee_model_settings {
+                    adjuster = adjuster.with_fee_model_settings(settings.clone());
+                }
                 Ok(Arc::new(adjuster))
             })
             .await;
@@ -3,7 +3,10 @@
 use std::{net::Ipv4Addr, str::FromStr, sync::Arc, time::Instant};
 
 use anyhow::Context as _;
-use fee_model::MainNodeFeeInputProvider;
+use fee_model::{
+    base_token_conversion_ratio, DynamicL2GasPrice, DynamicL2GasPriceParams,
+    FeeModelSettingsHandle, MainNodeFeeInputProvider,
+};
 use futures::channel::oneshot;
 use prometheus_exporter::PrometheusExporterConfig;
 use temp_config_store::TempConfigStore;
@@ -27,19 +30,20 @@ use zksync_config::{
 use zksync_contracts::{governance_contract, BaseSystemContracts};
 use zksync_dal::{healthcheck::ConnectionPoolHealthCheck, ConnectionPool};
 use zksync_eth_client::{
-    clients::{PKSigningClient, QueryClient},
+    clients::{OperatorSigningClient, PKSigningClient, QueryClient},
     BoundEthInterface, CallFunctionArgs, EthInterface,
 };
+use zksync_eth_signer::OperatorSigner;
 use zksync_health_check::{CheckHealth, HealthStatus, ReactiveHealthCheck};
 use zksync_object_store::{ObjectStore, ObjectStoreFactory};
 use zksync_queued_job_processor::JobProcessor;
 use zksync_state::PostgresStorageCaches;
 use zksync_types::{
-    fee_model::FeeModelConfigV1,
+    fee_model::{BaseTokenConversionRatio, FeeModelConfigV1},
     protocol_version::{L1VerifierConfig, VerifierParams},
     system_contracts::get_system_smart_contracts,
     web3::contract::tokens::Detokenize,
-    L2ChainId, PackedEthSignature, ProtocolVersionId,
+    L1BatchCommitmentMode, L2ChainId, PackedEthSignature, ProtocolVersionId,
 };
 
 use crate::{
@@ -47,12 +51,20 @@ use crate::{
         contract_verification,
         execution_sandbox::{VmConcurrencyBarrier, VmConcurrencyLimiter},
         healthcheck::HealthCheckHandle,
-        tx_sender::{ApiContracts, TxSender, TxSenderBuilder, TxSenderConfig},
+        tx_sender::{
+            ApiContracts, TxPolicyHandle, TxPolicyLoader, TxSender, TxSenderBuilder, TxSenderConfig,
+        },
         web3,
-        web3::{state::InternalApiConfig, ApiServerHandles, Namespace},
+        web3::{
+            auth::ApiAuth, backend_jsonrpsee::method_filter_middleware::MethodFilter,
+            grpc::GrpcStreamingServer, rate_limit::RateLimits, state::InternalApiConfig,
+            ApiServerHandles, Namespace,
+        },
     },
     basic_witness_input_producer::BasicWitnessInputProducer,
-    eth_sender::{Aggregator, EthTxAggregator, EthTxManager},
+    da_dispatcher::{DataAvailabilityDispatcher, ObjectStoreDAClient},
+    db_pruner::DbPruner,
+    eth_sender::{AggregationPolicyHandle, Aggregator, EthTxAggregator, EthTxManager},
     eth_watch::start_eth_watch,
     house_keeper::{
         blocks_state_reporter::L1BatchMetricsReporter,
@@ -63,14 +75,15 @@ use crate::{
         fri_scheduler_circuit_queuer::SchedulerCircuitQueuer,
         fri_witness_generator_jobs_retry_manager::FriWitnessGeneratorJobRetryManager,
         fri_witness_generator_queue_monitor::FriWitnessGeneratorStatsReporter,
-        periodic_job::PeriodicJob,
+        periodic_job::PeriodicJob, revenue_accountant::RevenueAccountant,
         waiting_to_queued_fri_witness_job_mover::WaitingToQueuedFriWitnessJobMover,
     },
     l1_gas_price::{GasAdjusterSingleton, L1GasPriceProvider},
     metadata_calculator::{MetadataCalculator, MetadataCalculatorConfig},
     metrics::{InitStage, APP_METRICS},
     state_keeper::{
-        create_state_keeper, MempoolFetcher, MempoolGuard, MiniblockSealer, SequencerSealer,
+        create_state_keeper, MempoolFetcher, MempoolGuard, MiniblockSealer, ProvingQueueMonitor,
+        SealingControl, SequencerSealer,
     },
 };
 
@@ -79,6 +92,8 @@ pub mod basic_witness_input_producer;
 pub mod block_reverter;
 pub mod consensus;
 pub mod consistency_checker;
+pub mod da_dispatcher;
+pub mod db_pruner;
 pub mod eth_sender;
 pub mod eth_watch;
 mod fee_model;
@@ -210,6 +225,8 @@ pub enum Component {
     HttpApi,
     /// Public Web3 API (including PubSub) running on WebSocket server.
     WsApi,
+    /// Streaming API for miniblocks, logs and L1 batch statuses running on gRPC server.
+    GrpcApi,
     /// REST API for contract verification.
     ContractVerificationApi,
     /// Metadata calculator.
@@ -230,6 +247,10 @@ pub enum Component {
     Housekeeper,
     /// Component for exposing APIs to prover for providing proof generation data and accepting proofs.
     ProofDataHandler,
+    /// Dispatcher posting pubdata of L1 batches to an external data availability layer.
+    DADispatcher,
+    /// Pruner of historical Postgres data for old L1 batches.
+    DbPruner,
 }
 
 #[derive(Debug)]
@@ -247,6 +268,7 @@ impl FromStr for Components {
             ])),
             "http_api" => Ok(Components(vec![Component::HttpApi])),
             "ws_api" => Ok(Components(vec![Component::WsApi])),
+            "grpc_api" => Ok(Components(vec![Component::GrpcApi])),
             "contract_verification_api" => Ok(Components(vec![Component::ContractVerificationApi])),
             "tree" => Ok(Components(vec![Component::Tree])),
             "tree_api" => Ok(Components(vec![Component::TreeApi])),
@@ -264,6 +286,8 @@ impl FromStr for Components {
             "eth_tx_aggregator" => Ok(Components(vec![Component::EthTxAggregator])),
             "eth_tx_manager" => Ok(Components(vec![Component::EthTxManager])),
             "proof_data_handler" => Ok(Components(vec![Component::ProofDataHandler])),
+            "da_dispatcher" => Ok(Components(vec![Component::DADispatcher])),
+            "db_pruner" => Ok(Components(vec![Component::DbPruner])),
             other => Err(format!("{} is not a valid component name", other)),
         }
     }
@@ -320,13 +344,53 @@ pub async fn initialize_components(
         panic!("Circuit breaker triggered: {}", err);
     });
 
-    let query_client = QueryClient::new(&eth_client_config.web3_url).unwrap();
+    let query_client = QueryClient::with_failover(&eth_client_config.web3_urls()).unwrap();
     let gas_adjuster_config = configs.gas_adjuster_config.context("gas_adjuster_config")?;
-    let mut gas_adjuster =
-        GasAdjusterSingleton::new(eth_client_config.web3_url.clone(), gas_adjuster_config);
+    let pubdata_sending_mode = configs
+        .eth_sender_config
+        .as_ref()
+        .context("eth_sender_config")?
+        .sender
+        .pubdata_sending_mode;
+    // Fee model settings shared by the state keeper, API servers and the gas adjuster in this process. Settings
+    // changed via the admin API are persisted, so they are restored on restart and followed by other processes.
+    let fee_model_settings = match &configs.state_keeper_config {
+        Some(state_keeper_config) => {
+            let settings = FeeModelSettingsHandle::new(state_keeper_config, &gas_adjuster_config);
+            let mut storage = connection_pool.access_storage().await?;
+            settings.load(&mut storage).await?;
+            Some(settings)
+        }
+        None => None,
+    };
+    let mut gas_adjuster = GasAdjusterSingleton::new(
+        eth_client_config.web3_urls(),
+        gas_adjuster_config,
+        pubdata_sending_mode,
+    );
+    if let Some(settings) = &fee_model_settings {
+        gas_adjuster = gas_adjuster.with_fee_model_settings(settings.subscribe());
+    }
 
     let (stop_sender, stop_receiver) = watch::channel(false);
     let (cb_sender, cb_receiver) = oneshot::channel();
+    // Allows stopping and resuming sealing via the admin API if the state keeper runs in this process.
+    let sealing_control = SealingControl::default();
+    let api_fee_model_settings = fee_model_settings.clone();
+    let settings_follower = fee_model_settings.clone();
+    // The L1 batch aggregation policy can only be changed via the admin API if the ETH tx aggregator
+    // runs in this process.
+    let aggregation_policy = if components.contains(&Component::EthTxAggregator) {
+        let eth_sender = configs
+            .eth_sender_config
+            .as_ref()
+            .context("eth_sender_config")?;
+        let policy = AggregationPolicyHandle::new(&eth_sender.sender)
+            .context("invalid L1 batch aggregation policy")?;
+        Some(policy)
+    } else {
+        None
+    };
 
     // Prometheus exporter and circuit breaker checker should run for every component configuration.
     let prom_config = configs
@@ -353,6 +417,7 @@ pub async fn initialize_components(
 
     if components.contains(&Component::WsApi)
         || components.contains(&Component::HttpApi)
+        || components.contains(&Component::GrpcApi)
         || components.contains(&Component::ContractVerificationApi)
     {
         let api_config = configs.api_config.clone().context("api_config")?;
@@ -370,6 +435,7 @@ pub async fn initialize_components(
             &network_config,
             &api_config.web3_json_rpc,
             &contracts_config,
+            base_token_conversion_ratio(&state_keeper_config)?,
         );
 
         // Lazily initialize storage caches only when they are needed (e.g., skip their initialization
@@ -378,6 +444,19 @@ pub async fn initialize_components(
         // program termination.
         let mut storage_caches = None;
 
+        let mut tx_policy = None;
+        let api_components = [Component::HttpApi, Component::WsApi];
+        if let Some(path) = &api_config.web3_json_rpc.tx_policy_path {
+            if api_components.iter().any(|c| components.contains(c)) {
+                let reload_interval = api_config.web3_json_rpc.tx_policy_reload_interval();
+                let loader = TxPolicyLoader::new(path, reload_interval)
+                    .await
+                    .context("failed loading transaction policy")?;
+                tx_policy = Some(loader.handle());
+                task_futures.push(tokio::spawn(loader.run(stop_receiver.clone())));
+            }
+        }
+
         if components.contains(&Component::HttpApi) {
             storage_caches = Some(
                 build_storage_caches(configs, &replica_connection_pool, &mut task_futures)
@@ -402,6 +481,12 @@ pub async fn initialize_components(
                 bounded_gas_adjuster.clone(),
                 state_keeper_config.save_call_traces,
                 storage_caches.clone().unwrap(),
+                components
+                    .contains(&Component::StateKeeper)
+                    .then(|| sealing_control.clone()),
+                tx_policy.clone(),
+                api_fee_model_settings.clone(),
+                aggregation_policy.clone(),
             )
             .await
             .context("run_http_api")?;
@@ -440,6 +525,8 @@ pub async fn initialize_components(
                 replica_connection_pool.clone(),
                 stop_receiver.clone(),
                 storage_caches,
+                tx_policy.clone(),
+                api_fee_model_settings.clone(),
             )
             .await
             .context("run_ws_api")?;
@@ -454,6 +541,33 @@ pub async fn initialize_components(
             );
         }
 
+        if components.contains(&Component::GrpcApi) {
+            let started_at = Instant::now();
+            tracing::info!("initializing gRPC streaming API");
+            let grpc_bind_addr = api_config
+                .web3_json_rpc
+                .grpc_bind_addr()
+                .context("gRPC port is not configured")?;
+            let server_handles = GrpcStreamingServer::new(
+                replica_connection_pool.clone(),
+                network_config.zksync_network_id,
+                api_config.web3_json_rpc.pubsub_interval(),
+            )
+            .with_max_concurrent_streams(api_config.web3_json_rpc.grpc_max_concurrent_streams())
+            .spawn(grpc_bind_addr, stop_receiver.clone())
+            .await
+            .context("GrpcStreamingServer::spawn()")?;
+
+            task_futures.extend(server_handles.tasks);
+            healthchecks.push(Box::new(server_handles.health_check));
+            let elapsed = started_at.elapsed();
+            APP_METRICS.init_latency[&InitStage::GrpcApi].set(elapsed);
+            tracing::info!(
+                "initialized gRPC streaming API on {:?} in {elapsed:?}",
+                server_handles.local_addr
+            );
+        }
+
         if components.contains(&Component::ContractVerificationApi) {
             let started_at = Instant::now();
             tracing::info!("initializing contract verification REST API");
@@ -495,6 +609,8 @@ pub async fn initialize_components(
             &configs.mempool_config.clone().context("mempool_config")?,
             bounded_gas_adjuster,
             store_factory.create_store().await,
+            &sealing_control,
+            fee_model_settings,
             stop_receiver.clone(),
         )
         .await
@@ -535,6 +651,24 @@ pub async fn initialize_components(
         tracing::info!("initialized ETH-Watcher in {elapsed:?}");
     }
 
+    let operator_signer = if components
+        .iter()
+        .any(|c| matches!(c, Component::EthTxAggregator | Component::EthTxManager))
+    {
+        let eth_sender = configs
+            .eth_sender_config
+            .as_ref()
+            .context("eth_sender_config")?;
+        let signer =
+            OperatorSigner::from_config(&eth_sender.signer, eth_sender.sender.private_key())
+                .await
+                .context("failed initializing operator signer")?;
+        healthchecks.push(Box::new(signer.health_check()));
+        Some(signer)
+    } else {
+        None
+    };
+
     if components.contains(&Component::EthTxAggregator) {
         let started_at = Instant::now();
         tracing::info!("initializing ETH-TxAggregator");
@@ -547,20 +681,34 @@ pub async fn initialize_components(
             .eth_sender_config
             .clone()
             .context("eth_sender_config")?;
-        let eth_client =
-            PKSigningClient::from_config(&eth_sender, &contracts_config, &eth_client_config);
+        let eth_client = OperatorSigningClient::from_config_with_operator_signer(
+            &eth_sender,
+            &contracts_config,
+            &eth_client_config,
+            operator_signer.clone().context("operator_signer")?,
+        );
         let nonce = eth_client.pending_nonce("eth_sender").await.unwrap();
         let eth_tx_aggregator_actor = EthTxAggregator::new(
             eth_sender.sender.clone(),
             Aggregator::new(
                 eth_sender.sender.clone(),
                 store_factory.create_store().await,
+                aggregation_policy.clone().context("aggregation_policy")?,
             ),
             Arc::new(eth_client),
+            gas_adjuster
+                .get_or_init()
+                .await
+                .context("gas_adjuster.get_or_init()")?,
             contracts_config.validator_timelock_addr,
             contracts_config.l1_multicall3_addr,
             main_zksync_contract_address,
             nonce.as_u64(),
+            configs
+                .state_keeper_config
+                .as_ref()
+                .context("state_keeper_config")?
+                .l1_batch_commit_data_generator_mode,
         );
         task_futures.push(tokio::spawn(
             eth_tx_aggregator_actor.run(eth_sender_pool, stop_receiver.clone()),
@@ -581,16 +729,41 @@ pub async fn initialize_components(
             .eth_sender_config
             .clone()
             .context("eth_sender_config")?;
-        let eth_client =
-            PKSigningClient::from_config(&eth_sender, &contracts_config, &eth_client_config);
+        anyhow::ensure!(
+            !eth_sender.sender.dry_run,
+            "ETH tx manager cannot run in the dry-run mode; disable it or `ETH_SENDER_SENDER_DRY_RUN`"
+        );
+        let eth_client = OperatorSigningClient::from_config_with_operator_signer(
+            &eth_sender,
+            &contracts_config,
+            &eth_client_config,
+            operator_signer.clone().context("operator_signer")?,
+        );
+        let backup_operators = eth_sender
+            .sender
+            .backup_private_keys()
+            .into_iter()
+            .map(|private_key| {
+                let client = PKSigningClient::from_config_with_private_key(
+                    &eth_sender,
+                    &contracts_config,
+                    &eth_client_config,
+                    private_key,
+                );
+                Arc::new(client) as Arc<dyn BoundEthInterface>
+            })
+            .collect();
         let eth_tx_manager_actor = EthTxManager::new(
             eth_sender.sender,
+            eth_sender.gas_escalation,
             gas_adjuster
                 .get_or_init()
                 .await
                 .context("gas_adjuster.get_or_init()")?,
             Arc::new(eth_client),
+            backup_operators,
         );
+        healthchecks.push(Box::new(eth_tx_manager_actor.health_check()));
         task_futures.extend([tokio::spawn(
             eth_tx_manager_actor.run(eth_manager_pool, stop_receiver.clone()),
         )]);
@@ -649,6 +822,35 @@ pub async fn initialize_components(
         )));
     }
 
+    if components.contains(&Component::DADispatcher) {
+        let da_dispatcher_config = configs
+            .da_dispatcher_config
+            .clone()
+            .context("da_dispatcher_config")?;
+        let da_object_store_config = configs
+            .da_object_store_config
+            .clone()
+            .context("da_object_store_config")?;
+        let da_store = ObjectStoreFactory::new(da_object_store_config)
+            .create_store()
+            .await;
+        let da_dispatcher = DataAvailabilityDispatcher::new(
+            connection_pool.clone(),
+            da_dispatcher_config,
+            Box::new(ObjectStoreDAClient::new(da_store)),
+        );
+        task_futures.push(tokio::spawn(da_dispatcher.run(stop_receiver.clone())));
+    }
+
+    if components.contains(&Component::DbPruner) {
+        let db_pruner_config = configs
+            .db_pruner_config
+            .clone()
+            .context("db_pruner_config")?;
+        let db_pruner = DbPruner::new(connection_pool.clone(), db_pruner_config);
+        task_futures.push(tokio::spawn(db_pruner.run(stop_receiver.clone())));
+    }
+
     // Run healthcheck server for all components.
     healthchecks.push(Box::new(ConnectionPoolHealthCheck::new(
         replica_connection_pool,
@@ -664,6 +866,11 @@ pub async fn initialize_components(
     if let Some(task) = gas_adjuster.run_if_initialized(stop_receiver.clone()) {
         task_futures.push(task);
     }
+    if let Some(settings) = settings_follower {
+        task_futures.push(tokio::spawn(
+            settings.follow_storage(connection_pool.clone(), stop_receiver.clone()),
+        ));
+    }
     Ok((task_futures, stop_sender, cb_receiver, health_check_handle))
 }
 
@@ -678,6 +885,8 @@ async fn add_state_keeper_to_task_futures<E: L1GasPriceProvider + Send + Sync +
     mempool_config: &MempoolConfig,
     gas_adjuster: Arc<E>,
     object_store: Arc<dyn ObjectStore>,
+    sealing_control: &SealingControl,
+    fee_model_settings: Option<FeeModelSettingsHandle>,
     stop_receiver: watch::Receiver<bool>,
 ) -> anyhow::Result<()> {
     let pool_builder = ConnectionPool::singleton(postgres_config.master_url()?);
@@ -695,12 +904,30 @@ async fn add_state_keeper_to_task_futures<E: L1GasPriceProvider + Send + Sync +
     let mempool = MempoolGuard::new(next_priority_id, mempool_config.capacity);
     mempool.register_metrics();
 
-    let batch_fee_input_provider = Arc::new(MainNodeFeeInputProvider::new(
+    let dynamic_l2_gas_price = match DynamicL2GasPriceParams::new(&state_keeper_config) {
+        Some(params) => {
+            let mut storage = state_keeper_pool.access_storage().await?;
+            let price = DynamicL2GasPrice::load(params, &mut storage).await?;
+            Some(Arc::new(price))
+        }
+        None => None,
+    };
+    let mut batch_fee_input_provider = MainNodeFeeInputProvider::new(
         gas_adjuster,
         zksync_types::fee_model::FeeModelConfig::V1(FeeModelConfigV1 {
             minimal_l2_gas_price: state_keeper_config.fair_l2_gas_price,
         }),
-    ));
+    )
+    .with_base_token_conversion_ratio(base_token_conversion_ratio(&state_keeper_config)?)
+    .with_commitment_mode(state_keeper_config.l1_batch_commit_data_generator_mode);
+    if let Some(price) = &dynamic_l2_gas_price {
+        batch_fee_input_provider =
+            batch_fee_input_provider.with_dynamic_l2_gas_price(price.clone());
+    }
+    if let Some(settings) = fee_model_settings {
+        batch_fee_input_provider = batch_fee_input_provider.with_settings(settings);
+    }
+    let batch_fee_input_provider = Arc::new(batch_fee_input_provider);
 
     let miniblock_sealer_pool = pool_builder
         .build()
@@ -712,6 +939,19 @@ async fn add_state_keeper_to_task_futures<E: L1GasPriceProvider + Send + Sync +
     );
     task_futures.push(tokio::spawn(miniblock_sealer.run()));
 
+    let proving_queue_len = if state_keeper_config.saturated_proving_queue_len.is_some() {
+        let proving_queue_pool = pool_builder
+            .build()
+            .await
+            .context("failed to build proving_queue_pool")?;
+        let monitor = ProvingQueueMonitor::new(proving_queue_pool);
+        let proving_queue_len = monitor.subscribe();
+        task_futures.push(tokio::spawn(monitor.run(stop_receiver.clone())));
+        Some(proving_queue_len)
+    } else {
+        None
+    };
+
     let state_keeper = create_state_keeper(
         contracts_config,
         state_keeper_config,
@@ -723,9 +963,12 @@ async fn add_state_keeper_to_task_futures<E: L1GasPriceProvider + Send + Sync +
         batch_fee_input_provider.clone(),
         miniblock_sealer_handle,
         object_store,
+        proving_queue_len,
+        dynamic_l2_gas_price,
         stop_receiver.clone(),
     )
-    .await;
+    .await
+    .with_sealing_control(sealing_control);
     task_futures.push(tokio::spawn(state_keeper.run()));
 
     let mempool_fetcher_pool = pool_builder
@@ -770,6 +1013,11 @@ async fn add_trees_to_task_futures(
         .context("api_config")?
         .merkle_tree;
     let postgres_config = configs.postgres_config.clone().context("postgres_config")?;
+    let commitment_mode = configs
+        .state_keeper_config
+        .as_ref()
+        .context("state_keeper_config")?
+        .l1_batch_commit_data_generator_mode;
     let api_config = components
         .contains(&Component::TreeApi)
         .then_some(&api_config);
@@ -786,6 +1034,7 @@ async fn add_trees_to_task_futures(
         &db_config.merkle_tree,
         api_config,
         &operation_config,
+        commitment_mode,
         object_store,
         stop_receiver,
     )
@@ -801,6 +1050,7 @@ async fn run_tree(
     merkle_tree_config: &MerkleTreeConfig,
     api_config: Option<&MerkleTreeApiConfig>,
     operation_manager: &OperationsManagerConfig,
+    commitment_mode: L1BatchCommitmentMode,
     object_store: Option<Arc<dyn ObjectStore>>,
     stop_receiver: watch::Receiver<bool>,
 ) -> anyhow::Result<()> {
@@ -812,7 +1062,11 @@ async fn run_tree(
     };
     tracing::info!("Initializing Merkle tree in {mode_str} mode");
 
-    let config = MetadataCalculatorConfig::for_main_node(merkle_tree_config, operation_manager);
+    let config = MetadataCalculatorConfig::for_main_node(
+        merkle_tree_config,
+        operation_manager,
+        commitment_mode,
+    );
     let metadata_calculator = MetadataCalculator::new(config, object_store).await;
     if let Some(api_config) = api_config {
         let address = (Ipv4Addr::UNSPECIFIED, api_config.port).into();
@@ -828,6 +1082,9 @@ async fn run_tree(
 
     let tree_health_check = metadata_calculator.tree_health_check();
     healthchecks.push(Box::new(tree_health_check));
+    if let Some(health_check) = metadata_calculator.consistency_check_health_check() {
+        healthchecks.push(Box::new(health_check));
+    }
     let pool = ConnectionPool::singleton(postgres_config.master_url()?)
         .build()
         .await
@@ -897,6 +1154,23 @@ async fn add_house_keeper_to_task_futures(
     .context("failed to build a prover_connection_pool")?;
     task_futures.push(tokio::spawn(l1_batch_metrics_reporter.run()));
 
+    // Revenue accounting writes to Postgres, so it cannot use the replica pool.
+    let revenue_connection_pool = ConnectionPool::singleton(postgres_config.master_url()?)
+        .build()
+        .await
+        .context("failed to build revenue_connection_pool")?;
+    let state_keeper_config = configs
+        .state_keeper_config
+        .clone()
+        .context("state_keeper_config")?;
+    let revenue_accountant = RevenueAccountant::new(
+        house_keeper_config.revenue_accounting_interval_ms(),
+        revenue_connection_pool,
+    )
+    .with_commitment_mode(state_keeper_config.l1_batch_commit_data_generator_mode)
+    .with_base_token_conversion_ratio(base_token_conversion_ratio(&state_keeper_config)?);
+    task_futures.push(tokio::spawn(revenue_accountant.run()));
+
     // All FRI Prover related components are configured below.
     let fri_prover_config = configs
         .fri_prover_config
@@ -998,6 +1272,24 @@ fn build_storage_caches(
     Ok(storage_caches)
 }
 
+/// Creates the congestion-based L2 gas price for the API server if it's enabled. The price follows the price
+/// of the latest sealed miniblock, so that the API server and the state keeper can run in different processes.
+async fn build_api_dynamic_l2_gas_price(
+    state_keeper_config: &StateKeeperConfig,
+    pool: &ConnectionPool,
+    stop_receiver: watch::Receiver<bool>,
+) -> anyhow::Result<Option<(Arc<DynamicL2GasPrice>, JoinHandle<anyhow::Result<()>>)>> {
+    let Some(params) = DynamicL2GasPriceParams::new(state_keeper_config) else {
+        return Ok(None);
+    };
+    let mut storage = pool.access_storage_tagged("api").await?;
+    let price = Arc::new(DynamicL2GasPrice::load(params, &mut storage).await?);
+    drop(storage);
+    let task = tokio::spawn(price.clone().follow_storage(pool.clone(), stop_receiver));
+    Ok(Some((price, task)))
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn build_tx_sender(
     tx_sender_config: &TxSenderConfig,
     web3_json_config: &Web3JsonRpcConfig,
@@ -1006,21 +1298,40 @@ async fn build_tx_sender(
     master_pool: ConnectionPool,
     l1_gas_price_provider: Arc<dyn L1GasPriceProvider>,
     storage_caches: PostgresStorageCaches,
+    tx_policy: Option<TxPolicyHandle>,
+    dynamic_l2_gas_price: Option<Arc<DynamicL2GasPrice>>,
+    base_token_conversion_ratio: BaseTokenConversionRatio,
+    fee_model_settings: Option<FeeModelSettingsHandle>,
 ) -> (TxSender, VmConcurrencyBarrier) {
     let sequencer_sealer = SequencerSealer::new(state_keeper_config.clone());
-    let tx_sender_builder = TxSenderBuilder::new(tx_sender_config.clone(), replica_pool)
+    let mut tx_sender_builder = TxSenderBuilder::new(tx_sender_config.clone(), replica_pool)
         .with_main_connection_pool(master_pool)
         .with_sealer(Arc::new(sequencer_sealer));
+    if let Some(tx_policy) = tx_policy {
+        tx_sender_builder = tx_sender_builder.with_policy(tx_policy);
+    }
 
     let max_concurrency = web3_json_config.vm_concurrency_limit();
-    let (vm_concurrency_limiter, vm_barrier) = VmConcurrencyLimiter::new(max_concurrency);
+    let max_heavy_concurrency = web3_json_config.heavy_calls_vm_concurrency_limit();
+    let (vm_concurrency_limiter, vm_barrier) =
+        VmConcurrencyLimiter::with_heavy_calls_quota(max_concurrency, max_heavy_concurrency);
+    let vm_concurrency_limiter = vm_concurrency_limiter
+        .with_per_client_limit(web3_json_config.vm_concurrency_limit_per_client());
 
-    let batch_fee_input_provider = MainNodeFeeInputProvider::new(
+    let mut batch_fee_input_provider = MainNodeFeeInputProvider::new(
         l1_gas_price_provider,
         zksync_types::fee_model::FeeModelConfig::V1(FeeModelConfigV1 {
             minimal_l2_gas_price: state_keeper_config.fair_l2_gas_price,
         }),
-    );
+    )
+    .with_base_token_conversion_ratio(base_token_conversion_ratio)
+    .with_commitment_mode(state_keeper_config.l1_batch_commit_data_generator_mode);
+    if let Some(price) = dynamic_l2_gas_price {
+        batch_fee_input_provider = batch_fee_input_provider.with_dynamic_l2_gas_price(price);
+    }
+    if let Some(settings) = fee_model_settings {
+        batch_fee_input_provider = batch_fee_input_provider.with_settings(settings);
+    }
 
     let tx_sender = tx_sender_builder
         .build(
@@ -1046,30 +1357,57 @@ async fn run_http_api<G: L1GasPriceProvider + Send + Sync + 'static>(
     gas_adjuster: Arc<G>,
     with_debug_namespace: bool,
     storage_caches: PostgresStorageCaches,
+    sealing_control: Option<SealingControl>,
+    tx_policy: Option<TxPolicyHandle>,
+    fee_model_settings: Option<FeeModelSettingsHandle>,
+    aggregation_policy: Option<AggregationPolicyHandle>,
 ) -> anyhow::Result<ApiServerHandles> {
+    let dynamic_l2_gas_price = build_api_dynamic_l2_gas_price(
+        state_keeper_config,
+        &replica_connection_pool,
+        stop_receiver.clone(),
+    )
+    .await?;
+    let l1_gas_price_provider: Arc<dyn L1GasPriceProvider> = gas_adjuster.clone();
     let (tx_sender, vm_barrier) = build_tx_sender(
         tx_sender_config,
         &api_config.web3_json_rpc,
         state_keeper_config,
         replica_connection_pool.clone(),
-        master_connection_pool,
+        master_connection_pool.clone(),
         gas_adjuster,
         storage_caches,
+        tx_policy,
+        dynamic_l2_gas_price
+            .as_ref()
+            .map(|(price, _)| price.clone()),
+        internal_api.fee_token.conversion_ratio,
+        fee_model_settings.clone(),
     )
     .await;
 
     let mut namespaces = Namespace::DEFAULT.to_vec();
     if with_debug_namespace {
-        namespaces.push(Namespace::Debug)
+        namespaces.push(Namespace::Debug);
+        namespaces.push(Namespace::Trace);
     }
     namespaces.push(Namespace::Snapshots);
+    namespaces.push(Namespace::Txpool);
+    let auth = ApiAuth::from_config(&api_config.web3_json_rpc)
+        .context("invalid API authentication config")?;
+    // The admin namespace is only exposed if it requires authentication.
+    if let Some(auth) = &auth {
+        if auth.namespaces().contains(&Namespace::Admin) {
+            namespaces.push(Namespace::Admin);
+        }
+    }
 
     let last_miniblock_pool = ConnectionPool::singleton(postgres_config.replica_url()?)
         .build()
         .await
         .context("failed to build last_miniblock_pool")?;
 
-    let api_builder =
+    let mut api_builder =
         web3::ApiBuilder::jsonrpsee_backend(internal_api.clone(), replica_connection_pool)
             .http(api_config.web3_json_rpc.http_port)
             .with_last_miniblock_pool(last_miniblock_pool)
@@ -1077,9 +1415,58 @@ async fn run_http_api<G: L1GasPriceProvider + Send + Sync + 'static>(
             .with_tree_api(api_config.web3_json_rpc.tree_api_url())
             .with_batch_request_size_limit(api_config.web3_json_rpc.max_batch_request_size())
             .with_response_body_size_limit(api_config.web3_json_rpc.max_response_body_size())
+            .with_rate_limits(RateLimits::from(&api_config.web3_json_rpc))
+            .with_method_filter(MethodFilter::from_config(&api_config.web3_json_rpc)?)
+            .with_draining_timeout(api_config.web3_json_rpc.graceful_shutdown_timeout())
             .with_tx_sender(tx_sender, vm_barrier)
+            .with_l1_gas_price_provider(l1_gas_price_provider)
             .enable_api_namespaces(namespaces);
-    api_builder.build(stop_receiver).await
+    if api_config.web3_json_rpc.shared_filters {
+        api_builder = api_builder.with_shared_filters(master_connection_pool);
+    }
+    if let Some(pool) = build_api_read_replica_pool(postgres_config).await? {
+        let max_lag = api_config.web3_json_rpc.read_replica_max_lag();
+        api_builder = api_builder.with_read_replica(pool, max_lag);
+    }
+    if let Some(auth) = auth {
+        api_builder = api_builder.with_auth(auth);
+    }
+    if let Some(sealing_control) = sealing_control {
+        api_builder = api_builder.with_sealing_control(sealing_control);
+    }
+    if let Some(settings) = fee_model_settings {
+        api_builder = api_builder.with_fee_model_settings(settings);
+    }
+    if let Some(policy) = aggregation_policy {
+        api_builder = api_builder.with_aggregation_policy(policy);
+    }
+    if api_config.web3_json_rpc.sse_enabled {
+        api_builder = api_builder.enable_sse_endpoint();
+    }
+    if api_config
+        .web3_json_rpc
+        .max_batch_response_size_mb
+        .is_some()
+    {
+        let limit = api_config.web3_json_rpc.max_batch_response_size();
+        api_builder = api_builder.with_batch_response_size_limit(limit);
+    }
+    if let Some(parallelism) = api_config.web3_json_rpc.batch_request_parallelism {
+        api_builder = api_builder.with_batch_request_parallelism(parallelism);
+    }
+    if let Some(limit) = api_config.web3_json_rpc.heavy_calls_concurrency_limit {
+        let light_calls_limit = api_config.web3_json_rpc.light_calls_concurrency_limit;
+        api_builder = api_builder.with_execution_pools(limit, light_calls_limit);
+    }
+    if api_config.web3_json_rpc.response_compression_enabled {
+        let min_size = api_config.web3_json_rpc.response_compression_min_size();
+        api_builder = api_builder.with_response_compression(min_size);
+    }
+    let mut server_handles = api_builder.build(stop_receiver).await?;
+    server_handles
+        .tasks
+        .extend(dynamic_l2_gas_price.map(|(_, task)| task));
+    Ok(server_handles)
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -1094,15 +1481,29 @@ async fn run_ws_api<G: L1GasPriceProvider + Send + Sync + 'static>(
     replica_connection_pool: ConnectionPool,
     stop_receiver: watch::Receiver<bool>,
     storage_caches: PostgresStorageCaches,
+    tx_policy: Option<TxPolicyHandle>,
+    fee_model_settings: Option<FeeModelSettingsHandle>,
 ) -> anyhow::Result<ApiServerHandles> {
+    let dynamic_l2_gas_price = build_api_dynamic_l2_gas_price(
+        state_keeper_config,
+        &replica_connection_pool,
+        stop_receiver.clone(),
+    )
+    .await?;
     let (tx_sender, vm_barrier) = build_tx_sender(
         tx_sender_config,
         &api_config.web3_json_rpc,
         state_keeper_config,
         replica_connection_pool.clone(),
-        master_connection_pool,
+        master_connection_pool.clone(),
         gas_adjuster,
         storage_caches,
+        tx_policy,
+        dynamic_l2_gas_price
+            .as_ref()
+            .map(|(price, _)| price.clone()),
+        internal_api.fee_token.conversion_ratio,
+        fee_model_settings,
     )
     .await;
     let last_miniblock_pool = ConnectionPool::singleton(postgres_config.replica_url()?)
@@ -1112,13 +1513,21 @@ async fn run_ws_api<G: L1GasPriceProvider + Send + Sync + 'static>(
 
     let mut namespaces = Namespace::DEFAULT.to_vec();
     namespaces.push(Namespace::Snapshots);
+    namespaces.push(Namespace::Txpool);
 
-    let api_builder =
+    let mut api_builder =
         web3::ApiBuilder::jsonrpsee_backend(internal_api.clone(), replica_connection_pool)
             .ws(api_config.web3_json_rpc.ws_port)
             .with_last_miniblock_pool(last_miniblock_pool)
             .with_filter_limit(api_config.web3_json_rpc.filters_limit())
             .with_subscriptions_limit(api_config.web3_json_rpc.subscriptions_limit())
+            .with_max_subscriptions_per_connection(
+                api_config.web3_json_rpc.max_subscriptions_per_connection(),
+            )
+            .with_subscription_queue(
+                api_config.web3_json_rpc.subscription_queue_capacity(),
+                api_config.web3_json_rpc.subscription_backpressure_policy,
+            )
             .with_batch_request_size_limit(api_config.web3_json_rpc.max_batch_request_size())
             .with_response_body_size_limit(api_config.web3_json_rpc.max_response_body_size())
             .with_websocket_requests_per_minute_limit(
@@ -1126,12 +1535,48 @@ async fn run_ws_api<G: L1GasPriceProvider + Send + Sync + 'static>(
                     .web3_json_rpc
                     .websocket_requests_per_minute_limit(),
             )
+            .with_rate_limits(RateLimits::from(&api_config.web3_json_rpc))
+            .with_method_filter(MethodFilter::from_config(&api_config.web3_json_rpc)?)
             .with_polling_interval(api_config.web3_json_rpc.pubsub_interval())
             .with_tree_api(api_config.web3_json_rpc.tree_api_url())
+            .with_draining_timeout(api_config.web3_json_rpc.graceful_shutdown_timeout())
             .with_tx_sender(tx_sender, vm_barrier)
             .enable_api_namespaces(namespaces);
+    if api_config.web3_json_rpc.shared_filters {
+        api_builder = api_builder.with_shared_filters(master_connection_pool);
+    }
+    if let Some(pool) = build_api_read_replica_pool(postgres_config).await? {
+        let max_lag = api_config.web3_json_rpc.read_replica_max_lag();
+        api_builder = api_builder.with_read_replica(pool, max_lag);
+    }
+    let auth = ApiAuth::from_config(&api_config.web3_json_rpc)
+        .context("invalid API authentication config")?;
+    if let Some(auth) = auth {
+        api_builder = api_builder.with_auth(auth);
+    }
+    if let Some(limit) = api_config.web3_json_rpc.heavy_calls_concurrency_limit {
+        let light_calls_limit = api_config.web3_json_rpc.light_calls_concurrency_limit;
+        api_builder = api_builder.with_execution_pools(limit, light_calls_limit);
+    }
+    let mut server_handles = api_builder.build(stop_receiver.clone()).await?;
+    server_handles
+        .tasks
+        .extend(dynamic_l2_gas_price.map(|(_, task)| task));
+    Ok(server_handles)
+}
 
-    api_builder.build(stop_receiver.clone()).await
+async fn build_api_read_replica_pool(
+    postgres_config: &PostgresConfig,
+) -> anyhow::Result<Option<ConnectionPool>> {
+    let Some(url) = &postgres_config.api_read_replica_url else {
+        return Ok(None);
+    };
+    let pool = ConnectionPool::builder(url, postgres_config.max_connections()?)
+        .set_statement_timeout(postgres_config.statement_timeout())
+        .build()
+        .await
+        .context("failed to build API read replica pool")?;
+    Ok(Some(pool))
 }
 
 async fn circuit_breakers_for_components(
@@ -1155,7 +1600,10 @@ async fn circuit_breakers_for_components(
     if components.iter().any(|c| {
         matches!(
             c,
-            Component::HttpApi | Component::WsApi | Component::ContractVerificationApi
+            Component::HttpApi
+                | Component::WsApi
+                | Component::GrpcApi
+                | Component::ContractVerificationApi
         )
     }) {
         let pool = ConnectionPool::singleton(postgres_config.replica_url()?)
@@ -0,0 +1,293 @@
+//! Background verification of the Merkle tree against Postgres.
+
+use std::time::Duration;
+
+use anyhow::Context as _;
+use rand::Rng;
+use serde::Serialize;
+use tokio::sync::watch;
+use zksync_dal::{ConnectionPool, StorageProcessor};
+use zksync_health_check::{Health, HealthStatus, HealthUpdater, ReactiveHealthCheck};
+use zksync_merkle_tree::{Key, TreeEntry};
+use zksync_types::{L1BatchNumber, H256};
+
+use super::{
+    helpers::AsyncTreeReader,
+    metrics::{TreeDivergenceKind, METRICS},
+};
+
+/// Divergence between the Merkle tree and Postgres detected by [`MerkleTreeConsistencyChecker`].
+#[derive(Debug, thiserror::Error)]
+pub(super) enum TreeDivergence {
+    #[error(
+        "root hash for L1 batch #{l1_batch_number} differs: tree has {tree_hash:?}, Postgres has {postgres_hash:?}"
+    )]
+    RootHash {
+        l1_batch_number: L1BatchNumber,
+        tree_hash: H256,
+        postgres_hash: H256,
+    },
+    #[error(
+        "tree leaf with hashed key {key:#066x} differs after L1 batch #{l1_batch_number}: \
+         tree has {tree_entry:?}, Postgres has {postgres_entry:?}"
+    )]
+    Leaf {
+        l1_batch_number: L1BatchNumber,
+        key: Key,
+        tree_entry: TreeEntry,
+        postgres_entry: TreeEntry,
+    },
+}
+
+impl TreeDivergence {
+    fn kind(&self) -> TreeDivergenceKind {
+        match self {
+            Self::RootHash { .. } => TreeDivergenceKind::RootHash,
+            Self::Leaf { .. } => TreeDivergenceKind::Leaf,
+        }
+    }
+}
+
+/// Health details reported by [`MerkleTreeConsistencyChecker`].
+#[derive(Debug, Default, Serialize)]
+pub(super) struct ConsistencyCheckHealthDetails {
+    /// Last L1 batch for which the tree root hash was checked against Postgres.
+    pub last_checked_l1_batch: Option<L1BatchNumber>,
+    /// Total number of tree leaves checked against Postgres.
+    pub checked_leaf_count: u64,
+    /// First detected divergence between the tree and Postgres.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub divergence: Option<String>,
+}
+
+/// Component periodically checking that the Merkle tree is consistent with Postgres.
+///
+/// On each iteration, the checker:
+///
+/// - Compares root hashes of all L1 batches processed by the tree since the previous iteration
+///   with root hashes persisted in Postgres.
+/// - Re-derives a random sample of tree leaves (values and leaf indices) after the latest processed L1 batch
+///   from Postgres storage logs and initial writes, and compares them with the tree entries.
+///
+/// Detected divergences are reported via the `tree_consistency` health check (which becomes not ready)
+/// and a metric. Divergences are sticky; once a divergence is detected, the health check stays not ready
+/// until the node is restarted.
+#[derive(Debug)]
+pub(super) struct MerkleTreeConsistencyChecker {
+    sample_size: usize,
+    poll_interval: Duration,
+    health_updater: HealthUpdater,
+    pub(super) health_details: ConsistencyCheckHealthDetails,
+    next_l1_batch_to_check: Option<L1BatchNumber>,
+}
+
+impl MerkleTreeConsistencyChecker {
+    pub fn new(sample_size: usize, poll_interval: Duration) -> Self {
+        assert!(
+            sample_size > 0,
+            "Consistency check sample size must be positive"
+        );
+        let (_, health_updater) = ReactiveHealthCheck::new("tree_consistency");
+        Self {
+            sample_size,
+            poll_interval,
+            health_updater,
+            health_details: ConsistencyCheckHealthDetails::default(),
+            next_l1_batch_to_check: None,
+        }
+    }
+
+    pub fn health_check(&self) -> ReactiveHealthCheck {
+        self.health_updater.subscribe()
+    }
+
+    pub async fn run(
+        mut self,
+        reader: AsyncTreeReader,
+        pool: &ConnectionPool,
+        mut stop_receiver: watch::Receiver<bool>,
+    ) -> anyhow::Result<()> {
+        tracing::info!(
+            "Started Merkle tree consistency checker with sample size {} and interval {:?}",
+            self.sample_size,
+            self.poll_interval
+        );
+        loop {
+            if *stop_receiver.borrow_and_update() {
+                break;
+            }
+
+            let mut storage = pool.access_storage_tagged("metadata_calculator").await?;
+            let divergence = self.check_once(&reader, &mut storage).await?;
+            drop(storage);
+
+            if let Some(divergence) = divergence {
+                tracing::error!("Merkle tree diverged from Postgres: {divergence}");
+                METRICS.consistency_check_divergences[&divergence.kind()].inc();
+                if self.health_details.divergence.is_none() {
+                    self.health_details.divergence = Some(divergence.to_string());
+                }
+            }
+            self.update_health();
+
+            let wait_for_stop = tokio::time::timeout(self.poll_interval, stop_receiver.changed());
+            if wait_for_stop.await.is_ok() {
+                break;
+            }
+        }
+
+        tracing::info!("Stop signal received, Merkle tree consistency checker is shutting down");
+        Ok(())
+    }
+
+    fn update_health(&self) {
+        let status = if self.health_details.divergence.is_some() {
+            HealthStatus::NotReady
+        } else {
+            HealthStatus::Ready
+        };
+        let health = Health::from(status).with_details(&self.health_details);
+        self.health_updater.update(health);
+    }
+
+    pub(super) async fn check_once(
+        &mut self,
+        reader: &AsyncTreeReader,
+        storage: &mut StorageProcessor<'_>,
+    ) -> anyhow::Result<Option<TreeDivergence>> {
+        let next_l1_batch_number = reader.clone().info().await.next_l1_batch_number;
+        let Some(last_l1_batch_number) = next_l1_batch_number.checked_sub(1) else {
+            tracing::debug!("Merkle tree is empty; skipping consistency check");
+            return Ok(None);
+        };
+        let last_l1_batch_number = L1BatchNumber(last_l1_batch_number);
+
+        // The tree may have been reverted since the last check, or we may not have checked anything yet.
+        let next_l1_batch_to_check = match self.next_l1_batch_to_check {
+            Some(number) => number.min(next_l1_batch_number),
+            None => last_l1_batch_number,
+        };
+        let mut l1_batch_number = next_l1_batch_to_check;
+        while l1_batch_number <= last_l1_batch_number {
+            let Some((tree_hash, _)) = reader.clone().root_info(l1_batch_number).await else {
+                // The tree version was pruned in the meantime.
+                l1_batch_number += 1;
+                continue;
+            };
+            let postgres_hash = storage
+                .blocks_dal()
+                .get_l1_batch_state_root(l1_batch_number)
+                .await
+                .with_context(|| {
+                    format!("failed getting root hash for L1 batch #{l1_batch_number}")
+                })?;
+            let Some(postgres_hash) = postgres_hash else {
+                // The root hash may not be persisted in Postgres yet; we'll check it on the next iteration.
+                break;
+            };
+            if tree_hash != postgres_hash {
+                self.next_l1_batch_to_check = Some(l1_batch_number + 1);
+                return Ok(Some(TreeDivergence::RootHash {
+                    l1_batch_number,
+                    tree_hash,
+                    postgres_hash,
+                }));
+            }
+            self.health_details.last_checked_l1_batch = Some(l1_batch_number);
+            METRICS
+                .consistency_check_l1_batch
+                .set(l1_batch_number.0.into());
+            l1_batch_number += 1;
+        }
+        self.next_l1_batch_to_check = Some(l1_batch_number);
+
+        self.check_leaf_sample(reader, storage, last_l1_batch_number)
+            .await
+    }
+
+    async fn check_leaf_sample(
+        &mut self,
+        reader: &AsyncTreeReader,
+        storage: &mut StorageProcessor<'_>,
+        l1_batch_number: L1BatchNumber,
+    ) -> anyhow::Result<Option<TreeDivergence>> {
+        let miniblock_range = storage
+            .blocks_dal()
+            .get_miniblock_range_of_l1_batch(l1_batch_number)
+            .await
+            .with_context(|| {
+                format!("failed getting miniblocks for L1 batch #{l1_batch_number}")
+            })?;
+        let Some((_, last_miniblock_number)) = miniblock_range else {
+            // E.g., the L1 batch was restored from a snapshot and has no miniblocks in Postgres.
+            tracing::debug!(
+                "L1 batch #{l1_batch_number} has no miniblocks in Postgres; skipping leaf sample check"
+            );
+            return Ok(None);
+        };
+
+        // Sample a contiguous (in terms of hashed keys) range of leaves ending at a random key,
+        // wrapping around to the greatest key if necessary.
+        let max_hashed_key = H256(rand::thread_rng().gen());
+        let mut postgres_entries = storage
+            .storage_logs_dal()
+            .get_tree_entries_up_to_key(
+                l1_batch_number,
+                last_miniblock_number,
+                max_hashed_key,
+                self.sample_size,
+            )
+            .await
+            .with_context(|| {
+                format!("failed getting tree entries for L1 batch #{l1_batch_number}")
+            })?;
+        if postgres_entries.len() < self.sample_size {
+            let remaining_entries = storage
+                .storage_logs_dal()
+                .get_tree_entries_up_to_key(
+                    l1_batch_number,
+                    last_miniblock_number,
+                    H256::repeat_byte(0xff),
+                    self.sample_size - postgres_entries.len(),
+                )
+                .await
+                .with_context(|| {
+                    format!("failed getting tree entries for L1 batch #{l1_batch_number}")
+                })?;
+            postgres_entries.extend(remaining_entries);
+            postgres_entries.sort_unstable_by_key(|entry| entry.key);
+            postgres_entries.dedup_by_key(|entry| entry.key);
+        }
+
+        let keys = postgres_entries.iter().map(|entry| entry.key).collect();
+        let Ok(tree_entries) = reader.clone().entries(l1_batch_number, keys).await else {
+            tracing::debug!(
+                "Tree version for L1 batch #{l1_batch_number} was pruned; skipping leaf sample check"
+            );
+            return Ok(None);
+        };
+
+        self.health_details.checked_leaf_count += postgres_entries.len() as u64;
+        METRICS
+            .consistency_check_leaves
+            .inc_by(postgres_entries.len() as u64);
+        for (postgres_entry, tree_entry) in postgres_entries.into_iter().zip(tree_entries) {
+            let postgres_entry = TreeEntry {
+                key: postgres_entry.key,
+                value: postgres_entry.value,
+                leaf_index: postgres_entry.leaf_index,
+            };
+            if postgres_entry.value != tree_entry.value
+                || postgres_entry.leaf_index != tree_entry.leaf_index
+            {
+                return Ok(Some(TreeDivergence::Leaf {
+                    l1_batch_number,
+                    key: postgres_entry.key,
+                    tree_entry,
+                    postgres_entry,
+                }));
+            }
+        }
+        Ok(None)
+    }
+}
@@ -3,6 +3,7 @@
 use std::{
     collections::BTreeMap,
     future::Future,
+    ops,
     path::{Path, PathBuf},
     time::Duration,
 };
@@ -18,7 +19,7 @@ use zksync_merkle_tree::{
     recovery::MerkleTreeRecovery,
     Database, Key, NoVersionError, RocksDBWrapper, TreeEntry, TreeEntryWithProof, TreeInstruction,
 };
-use zksync_storage::{RocksDB, RocksDBOptions, StalledWritesRetries};
+use zksync_storage::{RocksDB, RocksDBOptions};
 use zksync_types::{block::L1BatchHeader, L1BatchNumber, StorageKey, H256};
 
 use super::metrics::{LoadChangesStage, TreeUpdateStage, METRICS};
@@ -29,58 +30,52 @@ pub(crate) struct MerkleTreeInfo {
     pub mode: MerkleTreeMode,
     pub root_hash: H256,
     pub next_l1_batch_number: L1BatchNumber,
+    /// Earliest L1 batch for which the tree version is retained (i.e., is not pruned).
+    #[serde(default)]
+    pub min_l1_batch_number: Option<L1BatchNumber>,
     pub leaf_count: u64,
 }
 
-impl From<MerkleTreeInfo> for Health {
-    fn from(tree_info: MerkleTreeInfo) -> Self {
-        Self::from(HealthStatus::Ready).with_details(tree_info)
+/// Health details of the metadata calculator.
+#[derive(Debug, Serialize)]
+pub(super) struct MerkleTreeHealthDetails {
+    #[serde(flatten)]
+    pub info: MerkleTreeInfo,
+    /// Number of L1 batches sealed by the state keeper, but not yet processed by the tree.
+    /// `None` if there are no sealed L1 batches in Postgres.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub l1_batch_lag: Option<u32>,
+}
+
+impl From<MerkleTreeHealthDetails> for Health {
+    fn from(details: MerkleTreeHealthDetails) -> Self {
+        Self::from(HealthStatus::Ready).with_details(details)
     }
 }
 
 /// Creates a RocksDB wrapper with the specified params.
 pub(super) async fn create_db(
     path: PathBuf,
-    block_cache_capacity: usize,
-    memtable_capacity: usize,
-    stalled_writes_timeout: Duration,
+    options: RocksDBOptions,
     multi_get_chunk_size: usize,
 ) -> RocksDBWrapper {
-    tokio::task::spawn_blocking(move || {
-        create_db_sync(
-            &path,
-            block_cache_capacity,
-            memtable_capacity,
-            stalled_writes_timeout,
-            multi_get_chunk_size,
-        )
-    })
-    .await
-    .unwrap()
+    tokio::task::spawn_blocking(move || create_db_sync(&path, options, multi_get_chunk_size))
+        .await
+        .unwrap()
 }
 
 fn create_db_sync(
     path: &Path,
-    block_cache_capacity: usize,
-    memtable_capacity: usize,
-    stalled_writes_timeout: Duration,
+    options: RocksDBOptions,
     multi_get_chunk_size: usize,
 ) -> RocksDBWrapper {
     tracing::info!(
-        "Initializing Merkle tree database at `{path}` with {multi_get_chunk_size} multi-get chunk size, \
-         {block_cache_capacity}B block cache, {memtable_capacity}B memtable capacity, \
-         {stalled_writes_timeout:?} stalled writes timeout",
+        "Initializing Merkle tree database at `{path}` with {multi_get_chunk_size} multi-get chunk size \
+         and {options:?}",
         path = path.display()
     );
 
-    let mut db = RocksDB::with_options(
-        path,
-        RocksDBOptions {
-            block_cache_capacity: Some(block_cache_capacity),
-            large_memtable_capacity: Some(memtable_capacity),
-            stalled_writes_retries: StalledWritesRetries::new(stalled_writes_timeout),
-        },
-    );
+    let mut db = RocksDB::with_options(path, options);
     if cfg!(test) {
         // We need sync writes for the unit tests to execute reliably. With the default config,
         // some writes to RocksDB may occur, but not be visible to the test code.
@@ -182,6 +177,10 @@ impl AsyncTree {
     pub fn revert_logs(&mut self, last_l1_batch_to_keep: L1BatchNumber) {
         self.as_mut().revert_logs(last_l1_batch_to_keep);
     }
+
+    pub fn use_dedicated_thread_pool(&mut self, thread_count: usize) {
+        self.as_mut().use_dedicated_thread_pool(thread_count);
+    }
 }
 
 /// Async version of [`ZkSyncTreeReader`].
@@ -197,12 +196,23 @@ impl AsyncTreeReader {
             mode: self.mode,
             root_hash: self.inner.root_hash(),
             next_l1_batch_number: self.inner.next_l1_batch_number(),
+            min_l1_batch_number: self.inner.min_l1_batch_number(),
             leaf_count: self.inner.leaf_count(),
         })
         .await
         .unwrap()
     }
 
+    pub async fn entries(
+        self,
+        l1_batch_number: L1BatchNumber,
+        keys: Vec<Key>,
+    ) -> Result<Vec<TreeEntry>, NoVersionError> {
+        tokio::task::spawn_blocking(move || self.inner.entries(l1_batch_number, &keys))
+            .await
+            .unwrap()
+    }
+
     pub async fn entries_with_proofs(
         self,
         l1_batch_number: L1BatchNumber,
@@ -212,6 +222,22 @@ impl AsyncTreeReader {
             .await
             .unwrap()
     }
+
+    pub async fn root_info(self, l1_batch_number: L1BatchNumber) -> Option<(H256, u64)> {
+        tokio::task::spawn_blocking(move || self.inner.root_info(l1_batch_number))
+            .await
+            .unwrap()
+    }
+
+    pub async fn entries_in_range(
+        self,
+        l1_batch_number: L1BatchNumber,
+        key_range: ops::RangeInclusive<Key>,
+    ) -> Result<Vec<TreeEntry>, NoVersionError> {
+        tokio::task::spawn_blocking(move || self.inner.entries_in_range(l1_batch_number, key_range))
+            .await
+            .unwrap()
+    }
 }
 
 /// Async wrapper for [`MerkleTreeRecovery`].
@@ -249,6 +275,16 @@ impl AsyncTreeRecovery {
         entry
     }
 
+    /// Returns the greatest key inserted into the tree during recovery.
+    pub async fn last_processed_key(&mut self) -> Option<Key> {
+        let tree = self.inner.take().expect(Self::INCONSISTENT_MSG);
+        let (key, tree) = tokio::task::spawn_blocking(move || (tree.last_processed_key(), tree))
+            .await
+            .unwrap();
+        self.inner = Some(tree);
+        key
+    }
+
     /// Returns the current hash of the tree.
     pub async fn root_hash(&mut self) -> H256 {
         let tree = self.inner.take().expect(Self::INCONSISTENT_MSG);
@@ -272,6 +308,20 @@ impl AsyncTreeRecovery {
         self.inner = Some(tree);
     }
 
+    /// Extends the tree with a chunk of recovery entries ordered by increasing key. The first key must be
+    /// greater than [`Self::last_processed_key()`].
+    pub async fn extend_linear(&mut self, entries: Vec<TreeEntry>) {
+        let mut tree = self.inner.take().expect(Self::INCONSISTENT_MSG);
+        let tree = tokio::task::spawn_blocking(move || {
+            tree.extend_linear(entries);
+            tree
+        })
+        .await
+        .unwrap();
+
+        self.inner = Some(tree);
+    }
+
     pub async fn finalize(self) -> AsyncTree {
         let tree = self.inner.expect(Self::INCONSISTENT_MSG);
         let db = tokio::task::spawn_blocking(|| tree.finalize())
@@ -434,6 +484,7 @@ impl L1BatchWithLogs {
 mod tests {
     use tempfile::TempDir;
     use zksync_dal::ConnectionPool;
+    use zksync_storage::StalledWritesRetries;
     use zksync_types::{proofs::PrepareBasicCircuitsJob, L2ChainId, StorageKey, StorageLog};
 
     use super::*;
@@ -561,9 +612,13 @@ mod tests {
     async fn create_tree(temp_dir: &TempDir) -> AsyncTree {
         let db = create_db(
             temp_dir.path().to_owned(),
-            0,
-            16 << 20,       // 16 MiB,
-            Duration::ZERO, // writes should never be stalled in tests
+            RocksDBOptions {
+                block_cache_capacity: Some(0),
+                large_memtable_capacity: Some(16 << 20), // 16 MiB
+                // Writes should never be stalled in tests.
+                stalled_writes_retries: StalledWritesRetries::new(Duration::ZERO),
+                ..RocksDBOptions::default()
+            },
             500,
         )
         .await;
@@ -3,8 +3,8 @@
 use std::time::{Duration, Instant};
 
 use vise::{
-    Buckets, EncodeLabelSet, EncodeLabelValue, Family, Gauge, Histogram, LatencyObserver, Metrics,
-    Unit,
+    Buckets, Counter, EncodeLabelSet, EncodeLabelValue, Family, Gauge, Histogram, LatencyObserver,
+    Metrics, Unit,
 };
 use zksync_types::block::L1BatchHeader;
 use zksync_utils::time::seconds_since_epoch;
@@ -38,6 +38,14 @@ pub(super) enum LoadChangesStage {
     LoadLeafIndices,
 }
 
+/// Kind of divergence between the Merkle tree and Postgres detected by the consistency checker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "kind", rename_all = "snake_case")]
+pub(super) enum TreeDivergenceKind {
+    RootHash,
+    Leaf,
+}
+
 /// Latency metric for a certain stage of the tree update.
 #[derive(Debug)]
 #[must_use = "Tree latency should be `report`ed"]
@@ -90,6 +98,17 @@ pub(super) struct MetadataCalculatorMetrics {
     /// The lag can only be positive if Postgres was restored from a backup truncating some
     /// of the batches already processed by the tree.
     pub backup_lag: Gauge<u64>,
+    /// Number of L1 batches sealed by the state keeper, but not yet processed by the Merkle tree.
+    pub l1_batch_lag: Gauge<u64>,
+    /// Earliest Merkle tree version (= L1 batch number) that is retained by pruning based on the Postgres state
+    /// (L1 batches executed on L1, snapshots being created).
+    pub pruning_retained_version_limit: Gauge<u64>,
+    /// Last L1 batch for which the tree root hash was checked against Postgres by the consistency checker.
+    pub consistency_check_l1_batch: Gauge<u64>,
+    /// Number of tree leaves checked against Postgres by the consistency checker.
+    pub consistency_check_leaves: Counter,
+    /// Number of divergences between the tree and Postgres detected by the consistency checker.
+    pub consistency_check_divergences: Family<TreeDivergenceKind, Counter>,
     /// Number of zero values that need to be checked for L1 batch of the initial write in the process
     /// of updating the Merkle tree.
     #[metrics(buckets = COUNTS_BUCKETS)]
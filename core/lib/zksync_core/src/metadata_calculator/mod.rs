@@ -10,29 +10,37 @@ use std::{
 use tokio::sync::watch;
 use zksync_config::configs::{
     chain::OperationsManagerConfig,
-    database::{MerkleTreeConfig, MerkleTreeMode},
+    database::{MerkleTreeBackend, MerkleTreeConfig, MerkleTreeMode, RocksdbCompactionStyle},
 };
 use zksync_dal::{ConnectionPool, StorageProcessor};
 use zksync_health_check::{HealthUpdater, ReactiveHealthCheck};
 use zksync_merkle_tree::domain::TreeMetadata;
 use zksync_object_store::ObjectStore;
+use zksync_storage::{RocksDBOptions, StalledWritesRetries};
 use zksync_types::{
     block::L1BatchHeader,
-    commitment::{L1BatchCommitment, L1BatchMetadata},
+    commitment::{L1BatchCommitment, L1BatchCommitmentMode, L1BatchMetadata},
     H256,
 };
 
 pub(crate) use self::helpers::{AsyncTreeReader, L1BatchWithLogs, MerkleTreeInfo};
+pub use self::snapshot::{export_tree_snapshot, import_tree_snapshot};
 use self::{
+    consistency::MerkleTreeConsistencyChecker,
     helpers::{create_db, Delayer, GenericAsyncTree},
     metrics::{TreeUpdateStage, METRICS},
+    pruning::MerkleTreePruningTask,
+    recovery::RecoverySource,
     updater::TreeUpdater,
 };
-use crate::gas_tracker::commit_gas_count_for_l1_batch;
+use crate::{gas_tracker::commit_gas_count_for_l1_batch, utils::rocksdb_compaction_style};
 
+mod consistency;
 mod helpers;
 mod metrics;
+mod pruning;
 mod recovery;
+mod snapshot;
 #[cfg(test)]
 pub(crate) mod tests;
 mod updater;
@@ -44,6 +52,8 @@ pub struct MetadataCalculatorConfig {
     pub db_path: String,
     /// Configuration of the Merkle tree mode.
     pub mode: MerkleTreeMode,
+    /// Storage backend for the tree. If the tree is stored in memory, `db_path` is not used.
+    pub backend: MerkleTreeBackend,
     /// Interval between polling Postgres for updates if no progress was made by the tree.
     pub delay_interval: Duration,
     /// Maximum number of L1 batches to get from Postgres on a single update iteration.
@@ -58,22 +68,72 @@ pub struct MetadataCalculatorConfig {
     pub memtable_capacity: usize,
     /// Timeout to wait for the Merkle tree database to run compaction on stalled writes.
     pub stalled_writes_timeout: Duration,
+    /// Compaction style for the Merkle tree RocksDB.
+    pub compaction_style: RocksdbCompactionStyle,
+    /// Byte size of a single memtable. If not specified, the size is derived from `memtable_capacity`.
+    pub write_buffer_size: Option<usize>,
+    /// Maximum number of memtables per column family. If not specified, the number is derived
+    /// from `memtable_capacity`.
+    pub max_write_buffer_number: Option<usize>,
+    /// Maximum number of concurrent RocksDB background jobs (compactions and flushes). If not specified,
+    /// the number is derived from the number of CPU cores.
+    pub max_background_jobs: Option<usize>,
+    /// Number of threads used to compute hashes and Merkle proofs when updating the tree. If `None`,
+    /// the global `rayon` thread pool is used.
+    pub thread_pool_size: Option<usize>,
+    /// Mode of publishing L1 batch data on L1, which influences the produced L1 batch metadata.
+    pub commitment_mode: L1BatchCommitmentMode,
+    /// Number of latest L1 batches for which tree versions are retained if pruning is enabled. If set to `None`,
+    /// tree versions are not pruned.
+    pub pruning_retained_l1_batches: Option<u32>,
+    /// Interval between checks for tree versions that can be pruned.
+    pub pruning_poll_interval: Duration,
+    /// Number of tree leaves checked against Postgres on each consistency check iteration. If set to `None`,
+    /// consistency checks are disabled.
+    pub consistency_check_sample_size: Option<usize>,
+    /// Interval between consistency check iterations.
+    pub consistency_check_interval: Duration,
 }
 
 impl MetadataCalculatorConfig {
     pub(crate) fn for_main_node(
         merkle_tree_config: &MerkleTreeConfig,
         operation_config: &OperationsManagerConfig,
+        commitment_mode: L1BatchCommitmentMode,
     ) -> Self {
         Self {
             db_path: merkle_tree_config.path.clone(),
             mode: merkle_tree_config.mode,
+            backend: merkle_tree_config.backend,
             delay_interval: operation_config.delay_interval(),
             max_l1_batches_per_iter: merkle_tree_config.max_l1_batches_per_iter,
             multi_get_chunk_size: merkle_tree_config.multi_get_chunk_size,
             block_cache_capacity: merkle_tree_config.block_cache_size(),
             memtable_capacity: merkle_tree_config.memtable_capacity(),
             stalled_writes_timeout: merkle_tree_config.stalled_writes_timeout(),
+            compaction_style: merkle_tree_config.compaction_style,
+            write_buffer_size: merkle_tree_config.write_buffer_size(),
+            max_write_buffer_number: merkle_tree_config.max_write_buffer_number,
+            max_background_jobs: merkle_tree_config.max_background_jobs,
+            thread_pool_size: merkle_tree_config.thread_pool_size,
+            commitment_mode,
+            pruning_retained_l1_batches: merkle_tree_config.pruning_retained_l1_batches(),
+            pruning_poll_interval: merkle_tree_config.pruning_poll_interval(),
+            consistency_check_sample_size: merkle_tree_config.consistency_check_sample_size(),
+            consistency_check_interval: merkle_tree_config.consistency_check_interval(),
+        }
+    }
+
+    fn rocksdb_options(&self) -> RocksDBOptions {
+        RocksDBOptions {
+            block_cache_capacity: Some(self.block_cache_capacity),
+            large_memtable_capacity: Some(self.memtable_capacity),
+            stalled_writes_retries: StalledWritesRetries::new(self.stalled_writes_timeout),
+            compaction_style: rocksdb_compaction_style(self.compaction_style),
+            write_buffer_size: self.write_buffer_size,
+            max_write_buffer_number: self.max_write_buffer_number,
+            max_background_jobs: self.max_background_jobs,
+            in_memory: matches!(self.backend, MerkleTreeBackend::InMemory),
         }
     }
 }
@@ -83,9 +143,14 @@ pub struct MetadataCalculator {
     tree: GenericAsyncTree,
     tree_reader: watch::Sender<Option<AsyncTreeReader>>,
     object_store: Option<Arc<dyn ObjectStore>>,
+    snapshot_object_store: Option<Arc<dyn ObjectStore>>,
     delayer: Delayer,
     health_updater: HealthUpdater,
     max_l1_batches_per_iter: usize,
+    thread_pool_size: Option<usize>,
+    commitment_mode: L1BatchCommitmentMode,
+    pruning_task: Option<MerkleTreePruningTask>,
+    consistency_checker: Option<MerkleTreeConsistencyChecker>,
 }
 
 impl MetadataCalculator {
@@ -98,15 +163,31 @@ impl MetadataCalculator {
             config.max_l1_batches_per_iter > 0,
             "Maximum L1 batches per iteration is misconfigured to be 0; please update it to positive value"
         );
+        if matches!(config.backend, MerkleTreeBackend::InMemory) {
+            tracing::warn!(
+                "Merkle tree is stored in memory; it will be rebuilt from Postgres on each node restart. \
+                 This backend should only be used for tests and dev nodes"
+            );
+        }
 
         let db = create_db(
             config.db_path.clone().into(),
-            config.block_cache_capacity,
-            config.memtable_capacity,
-            config.stalled_writes_timeout,
+            config.rocksdb_options(),
             config.multi_get_chunk_size,
         )
         .await;
+        let pruning_task = config
+            .pruning_retained_l1_batches
+            .map(|retained_l1_batches| {
+                MerkleTreePruningTask::new(
+                    db.clone(),
+                    retained_l1_batches,
+                    config.pruning_poll_interval,
+                )
+            });
+        let consistency_checker = config.consistency_check_sample_size.map(|sample_size| {
+            MerkleTreeConsistencyChecker::new(sample_size, config.consistency_check_interval)
+        });
         let tree = GenericAsyncTree::new(db, config.mode).await;
 
         let (_, health_updater) = ReactiveHealthCheck::new("tree");
@@ -114,17 +195,36 @@ impl MetadataCalculator {
             tree,
             tree_reader: watch::channel(None).0,
             object_store,
+            snapshot_object_store: None,
             delayer: Delayer::new(config.delay_interval),
             health_updater,
             max_l1_batches_per_iter: config.max_l1_batches_per_iter,
+            thread_pool_size: config.thread_pool_size,
+            commitment_mode: config.commitment_mode,
+            pruning_task,
+            consistency_checker,
         }
     }
 
+    /// Sets the object store with node snapshots. If set, the tree is recovered by streaming snapshot storage log chunks
+    /// from this store rather than loading snapshot storage logs from Postgres.
+    pub fn with_snapshot_object_store(mut self, blob_store: Arc<dyn ObjectStore>) -> Self {
+        self.snapshot_object_store = Some(blob_store);
+        self
+    }
+
     /// Returns a health check for this calculator.
     pub fn tree_health_check(&self) -> ReactiveHealthCheck {
         self.health_updater.subscribe()
     }
 
+    /// Returns a health check for the tree consistency checker, or `None` if consistency checks are disabled.
+    pub fn consistency_check_health_check(&self) -> Option<ReactiveHealthCheck> {
+        self.consistency_checker
+            .as_ref()
+            .map(MerkleTreeConsistencyChecker::health_check)
+    }
+
     /// Returns a reference to the tree reader.
     pub(crate) fn tree_reader(&self) -> impl Future<Output = AsyncTreeReader> {
         let mut receiver = self.tree_reader.subscribe();
@@ -146,19 +246,62 @@ impl MetadataCalculator {
         pool: ConnectionPool,
         stop_receiver: watch::Receiver<bool>,
     ) -> anyhow::Result<()> {
+        let recovery_source = match self.snapshot_object_store {
+            Some(blob_store) => RecoverySource::ObjectStore(blob_store),
+            None => RecoverySource::Postgres,
+        };
         let tree = self
             .tree
-            .ensure_ready(&pool, &stop_receiver, &self.health_updater)
+            .ensure_ready(&pool, &stop_receiver, &self.health_updater, recovery_source)
             .await?;
-        let Some(tree) = tree else {
+        let Some(mut tree) = tree else {
             return Ok(()); // recovery was aborted because a stop signal was received
         };
-        self.tree_reader.send_replace(Some(tree.reader()));
+        if let Some(thread_count) = self.thread_pool_size {
+            tracing::info!(
+                "Using dedicated thread pool with {thread_count} threads for Merkle tree"
+            );
+            tree.use_dedicated_thread_pool(thread_count);
+        }
+        let tree_reader = tree.reader();
+        self.tree_reader.send_replace(Some(tree_reader.clone()));
 
-        let updater = TreeUpdater::new(tree, self.max_l1_batches_per_iter, self.object_store);
-        updater
-            .loop_updating_tree(self.delayer, &pool, stop_receiver, self.health_updater)
-            .await
+        let updater = TreeUpdater::new(
+            tree,
+            self.max_l1_batches_per_iter,
+            self.object_store,
+            self.commitment_mode,
+        );
+        let update_task = updater.loop_updating_tree(
+            self.delayer,
+            &pool,
+            stop_receiver.clone(),
+            self.health_updater,
+        );
+        // The pruner and the consistency checker are only started once the tree is ready;
+        // they must not interfere with tree recovery.
+        let pruning_task = self
+            .pruning_task
+            .map(|task| task.run(&pool, stop_receiver.clone()));
+        let consistency_task = self
+            .consistency_checker
+            .map(|checker| checker.run(tree_reader, &pool, stop_receiver));
+        tokio::try_join!(
+            update_task,
+            Self::run_optional_task(pruning_task),
+            Self::run_optional_task(consistency_task)
+        )?;
+        Ok(())
+    }
+
+    async fn run_optional_task(
+        task: Option<impl Future<Output = anyhow::Result<()>>>,
+    ) -> anyhow::Result<()> {
+        if let Some(task) = task {
+            task.await
+        } else {
+            Ok(())
+        }
     }
 
     /// This is used to improve L1 gas estimation for the commit operation. The estimations are computed
@@ -190,6 +333,7 @@ impl MetadataCalculator {
         header: &L1BatchHeader,
         events_queue_commitment: Option<H256>,
         bootloader_initial_content_commitment: Option<H256>,
+        commitment_mode: L1BatchCommitmentMode,
     ) -> L1BatchMetadata {
         let is_pre_boojum = header
             .protocol_version
@@ -211,6 +355,7 @@ impl MetadataCalculator {
             bootloader_initial_content_commitment.unwrap_or_default(),
             events_queue_commitment.unwrap_or_default(),
             is_pre_boojum,
+            commitment_mode,
         );
         let commitment_hash = commitment.hash();
         tracing::trace!("L1 batch commitment: {commitment:?}");
@@ -0,0 +1,113 @@
+//! Pruning of old Merkle tree versions.
+
+use std::time::Duration;
+
+use anyhow::Context as _;
+use tokio::sync::watch;
+use zksync_dal::{ConnectionPool, StorageProcessor};
+use zksync_merkle_tree::{MerkleTreePruner, RocksDBWrapper};
+use zksync_types::L1BatchNumber;
+
+use super::metrics::METRICS;
+
+/// Task pruning old Merkle tree versions in the background.
+///
+/// Pruning is performed by [`MerkleTreePruner`] running on a dedicated thread. The pruner retains the latest
+/// `retained_l1_batches` tree versions (e.g., to serve storage proofs for recent L1 batches). Additionally,
+/// this task periodically limits retained versions based on the Postgres state, so that the following versions
+/// are never pruned:
+///
+/// - Versions for L1 batches not executed on L1. Such batches may be reverted, which requires reverting the tree.
+/// - The version for a snapshot that is being created by the snapshot creator, so that the snapshot data
+///   can be checked against the tree.
+#[derive(Debug)]
+pub(super) struct MerkleTreePruningTask {
+    db: RocksDBWrapper,
+    retained_l1_batches: u32,
+    poll_interval: Duration,
+}
+
+impl MerkleTreePruningTask {
+    pub fn new(db: RocksDBWrapper, retained_l1_batches: u32, poll_interval: Duration) -> Self {
+        Self {
+            db,
+            retained_l1_batches,
+            poll_interval,
+        }
+    }
+
+    /// Returns the earliest L1 batch for which the tree version must be retained; all earlier versions
+    /// can be pruned. Returns `None` if no versions can be pruned. (Tree versions correspond to L1 batch numbers.)
+    pub async fn retained_version_limit(
+        storage: &mut StorageProcessor<'_>,
+    ) -> anyhow::Result<Option<L1BatchNumber>> {
+        let last_executed_l1_batch = storage
+            .blocks_dal()
+            .get_number_of_last_l1_batch_executed_on_eth()
+            .await
+            .context("failed getting last L1 batch executed on L1")?;
+        let Some(mut limit) = last_executed_l1_batch else {
+            return Ok(None);
+        };
+
+        let newest_snapshot = storage
+            .snapshots_dal()
+            .get_newest_snapshot_metadata()
+            .await
+            .context("failed getting newest snapshot metadata")?;
+        if let Some(snapshot) = newest_snapshot {
+            if !snapshot.is_complete() {
+                limit = limit.min(snapshot.l1_batch_number);
+            }
+        }
+        Ok(Some(limit))
+    }
+
+    pub async fn run(
+        self,
+        pool: &ConnectionPool,
+        mut stop_receiver: watch::Receiver<bool>,
+    ) -> anyhow::Result<()> {
+        let (mut pruner, pruner_handle) =
+            MerkleTreePruner::new(self.db, self.retained_l1_batches.into());
+        pruner.set_poll_interval(self.poll_interval);
+        // Do not prune anything until the retained version limit is loaded from Postgres.
+        pruner_handle.set_retained_version_limit(0);
+        let pruner_thread = tokio::task::spawn_blocking(|| pruner.run());
+        tracing::info!(
+            "Started Merkle tree pruning retaining {} latest L1 batches",
+            self.retained_l1_batches
+        );
+
+        let mut prev_limit = None;
+        loop {
+            if *stop_receiver.borrow_and_update() {
+                break;
+            }
+
+            let mut storage = pool.access_storage_tagged("metadata_calculator").await?;
+            let limit = Self::retained_version_limit(&mut storage).await?;
+            drop(storage);
+
+            if limit != prev_limit {
+                tracing::info!(
+                    "Merkle tree versions before L1 batch {limit:?} can be pruned; latest {} versions are retained",
+                    self.retained_l1_batches
+                );
+                prev_limit = limit;
+            }
+            let limit = limit.map_or(0, |l1_batch_number| u64::from(l1_batch_number.0));
+            pruner_handle.set_retained_version_limit(limit);
+            METRICS.pruning_retained_version_limit.set(limit);
+
+            let wait_for_stop = tokio::time::timeout(self.poll_interval, stop_receiver.changed());
+            if wait_for_stop.await.is_ok() {
+                break;
+            }
+        }
+
+        tracing::info!("Stop signal received, Merkle tree pruning is shutting down");
+        pruner_handle.abort();
+        pruner_thread.await.context("Merkle tree pruner panicked")
+    }
+}
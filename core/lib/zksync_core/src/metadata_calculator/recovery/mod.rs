@@ -21,25 +21,38 @@
 //! The recovery logic is fault-tolerant and supports graceful shutdown. If recovery is interrupted,
 //! recovery of the remaining chunks will continue when Metadata calculator is restarted.
 //!
+//! Alternatively, the tree can be recovered directly from storage log chunks of the node snapshot
+//! in the object store (see [`RecoverySource::ObjectStore`]), which doesn't require snapshot storage logs
+//! to be present in Postgres. In this case, chunks are downloaded concurrently, but are applied to the tree
+//! in the order of hashed keys. Thus, the number of chunks held in memory is bounded by the download concurrency,
+//! and chunks recovered before an interruption can be determined from the greatest key in the tree.
+//!
 //! Recovery performs basic sanity checks to ensure that the tree won't end up containing garbage data.
 //! E.g., it's checked that the tree always recovers from the same snapshot; that the tree root hash
 //! after recovery matches one in the Postgres snapshot etc.
 
 use std::{
     fmt, ops,
-    sync::atomic::{AtomicUsize, Ordering},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
 };
 
 use anyhow::Context as _;
 use async_trait::async_trait;
-use futures::future;
+use futures::{future, stream, StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use tokio::sync::{watch, Mutex, Semaphore};
 use zksync_dal::{ConnectionPool, StorageProcessor};
 use zksync_health_check::{Health, HealthStatus, HealthUpdater};
 use zksync_merkle_tree::TreeEntry;
-use zksync_types::{snapshots::SnapshotRecoveryStatus, MiniblockNumber, H256, U256};
-use zksync_utils::u256_to_h256;
+use zksync_object_store::ObjectStore;
+use zksync_types::{
+    snapshots::{SnapshotRecoveryStatus, SnapshotStorageLogsChunk, SnapshotStorageLogsStorageKey},
+    L1BatchNumber, MiniblockNumber, H256, U256,
+};
+use zksync_utils::{h256_to_u256, u256_to_h256};
 
 use super::{
     helpers::{AsyncTree, AsyncTreeRecovery, GenericAsyncTree},
@@ -151,6 +164,22 @@ impl SnapshotParameters {
     }
 }
 
+/// Source of the node snapshot data used to recover the tree.
+#[derive(Debug, Clone)]
+pub(super) enum RecoverySource {
+    /// Snapshot storage logs are loaded from Postgres.
+    Postgres,
+    /// Snapshot storage log chunks are streamed from the object store. Chunks must split the hashed key space
+    /// into equal ranges (as done by the snapshot creator).
+    ObjectStore(Arc<dyn ObjectStore>),
+}
+
+impl RecoverySource {
+    /// Maximum number of storage log chunks concurrently downloaded from the object store.
+    /// Since chunks are applied to the tree in order, this also bounds the number of chunks held in memory.
+    const OBJECT_STORE_CONCURRENCY: usize = 10;
+}
+
 /// Options for tree recovery.
 #[derive(Debug)]
 struct RecoveryOptions<'a> {
@@ -160,13 +189,14 @@ struct RecoveryOptions<'a> {
 }
 
 impl GenericAsyncTree {
-    /// Ensures that the tree is ready for the normal operation, recovering it from a Postgres snapshot
+    /// Ensures that the tree is ready for the normal operation, recovering it from a node snapshot
     /// if necessary.
     pub async fn ensure_ready(
         self,
         pool: &ConnectionPool,
         stop_receiver: &watch::Receiver<bool>,
         health_updater: &HealthUpdater,
+        source: RecoverySource,
     ) -> anyhow::Result<Option<AsyncTree>> {
         let (tree, snapshot_recovery) = match self {
             Self::Ready(tree) => return Ok(Some(tree)),
@@ -198,15 +228,37 @@ impl GenericAsyncTree {
             }
         };
 
-        let snapshot = SnapshotParameters::new(pool, &snapshot_recovery).await?;
-        tracing::debug!("Obtained snapshot parameters: {snapshot:?}");
-        let recovery_options = RecoveryOptions {
-            chunk_count: snapshot.chunk_count(),
-            concurrency_limit: pool.max_size() as usize,
-            events: Box::new(RecoveryHealthUpdater::new(health_updater)),
-        };
-        tree.recover(snapshot, recovery_options, pool, stop_receiver)
-            .await
+        let events = Box::new(RecoveryHealthUpdater::new(health_updater));
+        match source {
+            RecoverySource::Postgres => {
+                let snapshot = SnapshotParameters::new(pool, &snapshot_recovery).await?;
+                tracing::debug!("Obtained snapshot parameters: {snapshot:?}");
+                let recovery_options = RecoveryOptions {
+                    chunk_count: snapshot.chunk_count(),
+                    concurrency_limit: pool.max_size() as usize,
+                    events,
+                };
+                tree.recover(snapshot, recovery_options, pool, stop_receiver)
+                    .await
+            }
+            RecoverySource::ObjectStore(blob_store) => {
+                let chunk_count = usize::try_from(snapshot_recovery.total_chunk_count)
+                    .context("snapshot chunk count overflow")?;
+                anyhow::ensure!(chunk_count > 0, "Snapshot has no storage log chunks");
+                let recovery_options = RecoveryOptions {
+                    chunk_count,
+                    concurrency_limit: RecoverySource::OBJECT_STORE_CONCURRENCY,
+                    events,
+                };
+                tree.recover_from_object_store(
+                    snapshot_recovery.l1_batch_root_hash,
+                    recovery_options,
+                    &*blob_store,
+                    stop_receiver,
+                )
+                .await
+            }
+        }
     }
 }
 
@@ -251,19 +303,97 @@ impl AsyncTreeRecovery {
         });
         future::try_join_all(chunk_tasks).await?;
 
+        tree.into_inner()
+            .finalize_recovery(snapshot.expected_root_hash, stop_receiver)
+            .await
+    }
+
+    async fn recover_from_object_store(
+        mut self,
+        expected_root_hash: H256,
+        mut options: RecoveryOptions<'_>,
+        blob_store: &dyn ObjectStore,
+        stop_receiver: &watch::Receiver<bool>,
+    ) -> anyhow::Result<Option<AsyncTree>> {
+        let chunk_count = options.chunk_count;
+        let l1_batch_number = u32::try_from(self.recovered_version())
+            .map(L1BatchNumber)
+            .context("recovered tree version overflow")?;
+        tracing::info!(
+            "Recovering Merkle tree from object store snapshot for L1 batch #{l1_batch_number} in {chunk_count} chunks"
+        );
+
+        // Chunks are applied in the order of hashed keys, with each chunk applied atomically. Hence, all chunks
+        // up to and including the chunk containing the greatest key in the tree are fully recovered.
+        let last_processed_key = self.last_processed_key().await;
+        let remaining_chunks: Vec<_> = Self::hashed_key_ranges(chunk_count)
+            .enumerate()
+            .filter(|(_, chunk)| {
+                last_processed_key.map_or(true, |key| h256_to_u256(*chunk.start()) > key)
+            })
+            .collect();
+        let recovered_chunk_count = chunk_count - remaining_chunks.len();
+        options
+            .events
+            .recovery_started(chunk_count, recovered_chunk_count);
+        tracing::info!(
+            "Determined recovered key chunks from the tree; {} / {chunk_count} chunks remaining",
+            remaining_chunks.len()
+        );
+
+        let events = &*options.events;
+        let chunk_loads = remaining_chunks
+            .into_iter()
+            .map(|(chunk_id, key_chunk)| async move {
+                events.chunk_started().await;
+                Self::load_object_store_chunk(
+                    blob_store,
+                    l1_batch_number,
+                    chunk_id as u64,
+                    key_chunk,
+                )
+                .await
+            });
+        let mut chunk_loads = stream::iter(chunk_loads).buffered(options.concurrency_limit);
+        while let Some(entries) = chunk_loads.try_next().await? {
+            if *stop_receiver.borrow() {
+                break;
+            }
+
+            let extend_tree_latency =
+                RECOVERY_METRICS.chunk_latency[&ChunkRecoveryStage::ExtendTree].start();
+            let entry_count = entries.len();
+            if entry_count > 0 {
+                self.extend_linear(entries).await;
+            }
+            let extend_tree_latency = extend_tree_latency.observe();
+            tracing::debug!(
+                "Extended Merkle tree with {entry_count} entries from object store chunk in {extend_tree_latency:?}"
+            );
+            events.chunk_recovered().await;
+        }
+        drop(chunk_loads);
+
+        self.finalize_recovery(expected_root_hash, stop_receiver)
+            .await
+    }
+
+    async fn finalize_recovery(
+        mut self,
+        expected_root_hash: H256,
+        stop_receiver: &watch::Receiver<bool>,
+    ) -> anyhow::Result<Option<AsyncTree>> {
         if *stop_receiver.borrow() {
             return Ok(None);
         }
 
         let finalize_latency = RECOVERY_METRICS.latency[&RecoveryStage::Finalize].start();
-        let mut tree = tree.into_inner();
-        let actual_root_hash = tree.root_hash().await;
+        let actual_root_hash = self.root_hash().await;
         anyhow::ensure!(
-            actual_root_hash == snapshot.expected_root_hash,
-            "Root hash of recovered tree {actual_root_hash:?} differs from expected root hash {:?}",
-            snapshot.expected_root_hash
+            actual_root_hash == expected_root_hash,
+            "Root hash of recovered tree {actual_root_hash:?} differs from expected root hash {expected_root_hash:?}"
         );
-        let tree = tree.finalize().await;
+        let tree = self.finalize().await;
         let finalize_latency = finalize_latency.observe();
         tracing::info!(
             "Finished tree recovery in {finalize_latency:?}; resuming normal tree operation"
@@ -271,7 +401,9 @@ impl AsyncTreeRecovery {
         Ok(Some(tree))
     }
 
-    fn hashed_key_ranges(count: usize) -> impl Iterator<Item = ops::RangeInclusive<H256>> {
+    pub(super) fn hashed_key_ranges(
+        count: usize,
+    ) -> impl Iterator<Item = ops::RangeInclusive<H256>> {
         assert!(count > 0);
         let mut stride = U256::MAX / count;
         let stride_minus_one = if stride < U256::MAX {
@@ -337,6 +469,57 @@ impl AsyncTreeRecovery {
         Ok(output)
     }
 
+    /// Loads a storage log chunk from the object store and converts it to tree entries ordered by hashed key.
+    async fn load_object_store_chunk(
+        blob_store: &dyn ObjectStore,
+        l1_batch_number: L1BatchNumber,
+        chunk_id: u64,
+        key_chunk: ops::RangeInclusive<H256>,
+    ) -> anyhow::Result<Vec<TreeEntry>> {
+        let entries_latency =
+            RECOVERY_METRICS.chunk_latency[&ChunkRecoveryStage::LoadEntries].start();
+        let key = SnapshotStorageLogsStorageKey {
+            l1_batch_number,
+            chunk_id,
+        };
+        let chunk: SnapshotStorageLogsChunk = blob_store.get(key).await.with_context(|| {
+            format!("Failed getting storage logs chunk {chunk_id} for L1 batch #{l1_batch_number}")
+        })?;
+        let mut entries: Vec<_> = chunk
+            .storage_logs
+            .into_iter()
+            .map(|log| TreeEntry::new(log.key.hashed_key_u256(), log.enumeration_index, log.value))
+            .collect();
+        entries.sort_unstable_by_key(|entry| entry.key);
+        let entries_latency = entries_latency.observe();
+        tracing::debug!(
+            "Loaded {} entries for chunk {chunk_id} ({key_chunk:?}) in {entries_latency:?}",
+            entries.len()
+        );
+
+        // Sanity checks: all entry keys must be distinct and belong to the expected key range. Otherwise,
+        // chunks could be applied to the tree out of order.
+        for window in entries.windows(2) {
+            let [prev_entry, next_entry] = window else {
+                unreachable!();
+            };
+            anyhow::ensure!(
+                prev_entry.key != next_entry.key,
+                "storage logs chunk {chunk_id} in snapshot for L1 batch #{l1_batch_number} is corrupted: \
+                 entries {prev_entry:?} and {next_entry:?} have same hashed_key"
+            );
+        }
+        let key_range = h256_to_u256(*key_chunk.start())..=h256_to_u256(*key_chunk.end());
+        if let (Some(first_entry), Some(last_entry)) = (entries.first(), entries.last()) {
+            anyhow::ensure!(
+                key_range.contains(&first_entry.key) && key_range.contains(&last_entry.key),
+                "storage logs chunk {chunk_id} in snapshot for L1 batch #{l1_batch_number} contains keys \
+                 outside the expected range {key_chunk:?}; the snapshot may have been created with different chunking"
+            );
+        }
+        Ok(entries)
+    }
+
     async fn recover_key_chunk(
         tree: &Mutex<AsyncTreeRecovery>,
         snapshot_miniblock: MiniblockNumber,
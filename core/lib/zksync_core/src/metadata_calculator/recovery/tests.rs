@@ -12,10 +12,14 @@ use zksync_config::configs::{
 };
 use zksync_health_check::{CheckHealth, ReactiveHealthCheck};
 use zksync_merkle_tree::{domain::ZkSyncTree, TreeInstruction};
+use zksync_object_store::ObjectStoreFactory;
+use zksync_storage::{RocksDBOptions, StalledWritesRetries};
 use zksync_types::{
     block::{L1BatchHeader, MiniblockHeader},
+    commitment::L1BatchCommitmentMode,
     fee_model::BatchFeeInput,
-    L1BatchNumber, L2ChainId, ProtocolVersion, ProtocolVersionId, StorageLog,
+    snapshots::SnapshotStorageLog,
+    L2ChainId, ProtocolVersion, ProtocolVersionId, StorageLog,
 };
 use zksync_utils::h256_to_u256;
 
@@ -90,9 +94,13 @@ fn calculating_chunk_count() {
 async fn create_tree_recovery(path: PathBuf, l1_batch: L1BatchNumber) -> AsyncTreeRecovery {
     let db = create_db(
         path,
-        0,
-        16 << 20,       // 16 MiB,
-        Duration::ZERO, // writes should never be stalled in tests
+        RocksDBOptions {
+            block_cache_capacity: Some(0),
+            large_memtable_capacity: Some(16 << 20), // 16 MiB
+            // Writes should never be stalled in tests.
+            stalled_writes_retries: StalledWritesRetries::new(Duration::ZERO),
+            ..RocksDBOptions::default()
+        },
         500,
     )
     .await;
@@ -170,6 +178,96 @@ async fn prepare_recovery_snapshot(
     }
 }
 
+/// Uploads storage log chunks for the snapshot prepared in Postgres to a mock object store.
+async fn upload_snapshot_chunks(
+    pool: &ConnectionPool,
+    snapshot_recovery: &SnapshotRecoveryStatus,
+    chunk_count: usize,
+) -> Arc<dyn ObjectStore> {
+    let blob_store = ObjectStoreFactory::mock().create_store().await;
+    let mut storage = pool.access_storage().await.unwrap();
+    for (chunk_id, key_chunk) in AsyncTreeRecovery::hashed_key_ranges(chunk_count).enumerate() {
+        let storage_logs = storage
+            .snapshots_creator_dal()
+            .get_storage_logs_chunk(snapshot_recovery.miniblock_number, key_chunk)
+            .await
+            .unwrap();
+        let key = SnapshotStorageLogsStorageKey {
+            l1_batch_number: snapshot_recovery.l1_batch_number,
+            chunk_id: chunk_id as u64,
+        };
+        blob_store
+            .put(key, &SnapshotStorageLogsChunk { storage_logs })
+            .await
+            .unwrap();
+    }
+    blob_store
+}
+
+#[tokio::test]
+async fn basic_recovery_workflow_from_object_store() {
+    let pool = ConnectionPool::test_pool().await;
+    let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+    let snapshot_recovery = prepare_recovery_snapshot(&pool, &temp_dir).await;
+
+    let (_stop_sender, stop_receiver) = watch::channel(false);
+    for chunk_count in [1, 4, 9, 16, 60] {
+        println!("Recovering tree from object store with {chunk_count} chunks");
+
+        let blob_store = upload_snapshot_chunks(&pool, &snapshot_recovery, chunk_count).await;
+        let tree_path = temp_dir.path().join(format!("recovery-{chunk_count}"));
+        let tree = create_tree_recovery(tree_path, L1BatchNumber(1)).await;
+        let (health_check, health_updater) = ReactiveHealthCheck::new("tree");
+        let recovery_options = RecoveryOptions {
+            chunk_count,
+            concurrency_limit: 3,
+            events: Box::new(RecoveryHealthUpdater::new(&health_updater)),
+        };
+        let tree = tree
+            .recover_from_object_store(
+                snapshot_recovery.l1_batch_root_hash,
+                recovery_options,
+                &*blob_store,
+                &stop_receiver,
+            )
+            .await
+            .unwrap()
+            .expect("Tree recovery unexpectedly aborted");
+
+        assert_eq!(tree.root_hash(), snapshot_recovery.l1_batch_root_hash);
+        let health = health_check.check_health().await;
+        assert_matches!(health.status(), HealthStatus::Ready);
+    }
+}
+
+#[tokio::test]
+async fn recovery_from_object_store_with_mismatched_chunking() {
+    let pool = ConnectionPool::test_pool().await;
+    let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+    let snapshot_recovery = prepare_recovery_snapshot(&pool, &temp_dir).await;
+    let blob_store = upload_snapshot_chunks(&pool, &snapshot_recovery, 4).await;
+
+    let tree = create_tree_recovery(temp_dir.path().join("recovery"), L1BatchNumber(1)).await;
+    let (_stop_sender, stop_receiver) = watch::channel(false);
+    let (_, health_updater) = ReactiveHealthCheck::new("tree");
+    let recovery_options = RecoveryOptions {
+        chunk_count: 2,
+        concurrency_limit: 1,
+        events: Box::new(RecoveryHealthUpdater::new(&health_updater)),
+    };
+    let err = tree
+        .recover_from_object_store(
+            snapshot_recovery.l1_batch_root_hash,
+            recovery_options,
+            &*blob_store,
+            &stop_receiver,
+        )
+        .await
+        .unwrap_err();
+    let err = format!("{err:#}");
+    assert!(err.contains("outside the expected range"), "{err}");
+}
+
 #[derive(Debug)]
 struct TestEventListener {
     expected_recovered_chunks: usize,
@@ -266,6 +364,49 @@ async fn recovery_fault_tolerance(chunk_count: usize) {
     assert_eq!(tree.root_hash(), snapshot_recovery.l1_batch_root_hash);
 }
 
+#[test_casing(3, [5, 7, 8])]
+#[tokio::test]
+async fn recovery_from_object_store_fault_tolerance(chunk_count: usize) {
+    let pool = ConnectionPool::test_pool().await;
+    let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+    let snapshot_recovery = prepare_recovery_snapshot(&pool, &temp_dir).await;
+    let blob_store = upload_snapshot_chunks(&pool, &snapshot_recovery, chunk_count).await;
+    let tree_path = temp_dir.path().join("recovery");
+
+    // Recover 1 chunk, then 2 more chunks after a restart, then the remaining chunks after another restart.
+    for (stop_threshold, recovered_chunks) in [(1, 0), (2, 1), (usize::MAX, 3)] {
+        let mut tree = create_tree_recovery(tree_path.clone(), L1BatchNumber(1)).await;
+        if recovered_chunks > 0 {
+            assert_ne!(tree.root_hash().await, snapshot_recovery.l1_batch_root_hash);
+        }
+        let (stop_sender, stop_receiver) = watch::channel(false);
+        let recovery_options = RecoveryOptions {
+            chunk_count,
+            concurrency_limit: 2,
+            events: Box::new(
+                TestEventListener::new(stop_threshold, stop_sender)
+                    .expect_recovered_chunks(recovered_chunks),
+            ),
+        };
+        let tree = tree
+            .recover_from_object_store(
+                snapshot_recovery.l1_batch_root_hash,
+                recovery_options,
+                &*blob_store,
+                &stop_receiver,
+            )
+            .await
+            .unwrap();
+
+        if stop_threshold == usize::MAX {
+            let tree = tree.expect("Tree recovery unexpectedly aborted");
+            assert_eq!(tree.root_hash(), snapshot_recovery.l1_batch_root_hash);
+        } else {
+            assert!(tree.is_none());
+        }
+    }
+}
+
 #[derive(Debug)]
 enum RecoveryWorkflowCase {
     Stop,
@@ -293,6 +434,7 @@ async fn entire_recovery_workflow(case: RecoveryWorkflowCase) {
     let calculator_config = MetadataCalculatorConfig::for_main_node(
         &merkle_tree_config,
         &OperationsManagerConfig { delay_interval: 50 },
+        L1BatchCommitmentMode::Rollup,
     );
     let mut calculator = MetadataCalculator::new(calculator_config, None).await;
     let (delay_sx, mut delay_rx) = mpsc::unbounded_channel();
@@ -356,12 +498,99 @@ async fn entire_recovery_workflow(case: RecoveryWorkflowCase) {
     calculator_task.await.expect("calculator panicked").unwrap();
 }
 
+#[tokio::test]
+async fn entire_recovery_workflow_from_object_store() {
+    let pool = ConnectionPool::test_pool().await;
+    // Emulate Postgres that only contains the snapshot recovery status, without snapshot storage logs.
+    let snapshot_logs = gen_storage_logs(100..300, 1).pop().unwrap();
+    let mut storage = pool.access_storage().await.unwrap();
+    let snapshot_recovery = prepare_clean_recovery_status(&mut storage, &snapshot_logs).await;
+    drop(storage);
+
+    let chunk_count = 8;
+    let blob_store = ObjectStoreFactory::mock().create_store().await;
+    let snapshot_logs: Vec<_> = snapshot_logs
+        .iter()
+        .enumerate()
+        .map(|(i, log)| SnapshotStorageLog {
+            key: log.key,
+            value: log.value,
+            l1_batch_number_of_initial_write: snapshot_recovery.l1_batch_number,
+            enumeration_index: i as u64 + 1,
+        })
+        .collect();
+    for (chunk_id, key_chunk) in AsyncTreeRecovery::hashed_key_ranges(chunk_count).enumerate() {
+        let storage_logs = snapshot_logs
+            .iter()
+            .filter(|log| key_chunk.contains(&log.key.hashed_key()))
+            .cloned()
+            .collect();
+        let key = SnapshotStorageLogsStorageKey {
+            l1_batch_number: snapshot_recovery.l1_batch_number,
+            chunk_id: chunk_id as u64,
+        };
+        blob_store
+            .put(key, &SnapshotStorageLogsChunk { storage_logs })
+            .await
+            .unwrap();
+    }
+
+    let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+    let merkle_tree_config = MerkleTreeConfig {
+        path: temp_dir.path().to_str().unwrap().to_owned(),
+        ..MerkleTreeConfig::default()
+    };
+    let calculator_config = MetadataCalculatorConfig::for_main_node(
+        &merkle_tree_config,
+        &OperationsManagerConfig { delay_interval: 50 },
+        L1BatchCommitmentMode::Rollup,
+    );
+    let calculator = MetadataCalculator::new(calculator_config, None)
+        .await
+        .with_snapshot_object_store(blob_store);
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let tree_reader = calculator.tree_reader();
+    let calculator_task = tokio::spawn(calculator.run(pool.clone(), stop_receiver));
+
+    let tree_info = tree_reader.await.info().await;
+    assert_eq!(tree_info.root_hash, snapshot_recovery.l1_batch_root_hash);
+    assert_eq!(tree_info.leaf_count, 200);
+    assert_eq!(
+        tree_info.next_l1_batch_number,
+        snapshot_recovery.l1_batch_number + 1
+    );
+
+    stop_sender.send_replace(true);
+    calculator_task.await.expect("calculator panicked").unwrap();
+}
+
 /// Prepares a recovery snapshot without performing genesis.
 async fn prepare_clean_recovery_snapshot(
     storage: &mut StorageProcessor<'_>,
     snapshot_logs: &[StorageLog],
 ) -> SnapshotRecoveryStatus {
+    let snapshot_recovery = prepare_clean_recovery_status(storage, snapshot_logs).await;
     let written_keys: Vec<_> = snapshot_logs.iter().map(|log| log.key).collect();
+    storage
+        .storage_logs_dedup_dal()
+        .insert_initial_writes(snapshot_recovery.l1_batch_number, &written_keys)
+        .await;
+    storage
+        .storage_logs_dal()
+        .insert_storage_logs(
+            snapshot_recovery.miniblock_number,
+            &[(H256::zero(), snapshot_logs.to_vec())],
+        )
+        .await;
+    snapshot_recovery
+}
+
+/// Prepares L1 batch / miniblock headers and the recovery status for a snapshot without inserting
+/// snapshot storage logs to Postgres.
+async fn prepare_clean_recovery_status(
+    storage: &mut StorageProcessor<'_>,
+    snapshot_logs: &[StorageLog],
+) -> SnapshotRecoveryStatus {
     let tree_instructions: Vec<_> = snapshot_logs
         .iter()
         .enumerate()
@@ -404,15 +633,6 @@ async fn prepare_clean_recovery_snapshot(
         .await
         .unwrap();
 
-    storage
-        .storage_logs_dedup_dal()
-        .insert_initial_writes(l1_batch.number, &written_keys)
-        .await;
-    storage
-        .storage_logs_dal()
-        .insert_storage_logs(miniblock.number, &[(H256::zero(), snapshot_logs.to_vec())])
-        .await;
-
     let snapshot_recovery = SnapshotRecoveryStatus {
         l1_batch_number: l1_batch.number,
         l1_batch_root_hash,
@@ -0,0 +1,415 @@
+//! Exporting Merkle tree snapshots to the object store and importing them.
+//!
+//! Building the tree from scratch (or recovering it from a Postgres snapshot) may take many hours
+//! for large trees. Instead, a node may import a tree snapshot exported by another node. A snapshot
+//! consists of [`MerkleTreeSnapshotChunk`]s, each containing tree leaves with hashed keys in an equal range
+//! of the key space, and a [`MerkleTreeSnapshotHeader`] which is uploaded after all chunks.
+//!
+//! Import uses the tree recovery logic, so it is fault-tolerant: if it is interrupted, it can be restarted
+//! and will skip chunks that were already imported. The root hash of the imported tree is compared
+//! with the root hash in the snapshot header before recovery is finalized.
+
+use std::ops;
+
+use anyhow::Context as _;
+use futures::{stream, StreamExt, TryStreamExt};
+use zksync_config::configs::database::MerkleTreeMode;
+use zksync_merkle_tree::{Key, RocksDBWrapper, TreeEntry};
+use zksync_object_store::ObjectStore;
+use zksync_types::{
+    snapshots::{
+        MerkleTreeSnapshotChunk, MerkleTreeSnapshotChunkKey, MerkleTreeSnapshotEntry,
+        MerkleTreeSnapshotHeader,
+    },
+    L1BatchNumber, H256,
+};
+use zksync_utils::h256_to_u256;
+
+use super::helpers::{AsyncTree, AsyncTreeRecovery, GenericAsyncTree};
+
+/// Returns hashed key ranges for snapshot chunks. Chunks are defined in the same way as during tree recovery
+/// from Postgres.
+fn chunk_key_ranges(chunk_count: u64) -> impl Iterator<Item = ops::RangeInclusive<Key>> {
+    let chunk_count = usize::try_from(chunk_count).expect("chunk count overflow");
+    AsyncTreeRecovery::hashed_key_ranges(chunk_count)
+        .map(|range| h256_to_u256(*range.start())..=h256_to_u256(*range.end()))
+}
+
+/// Exports the Merkle tree state after the specified L1 batch to the object store.
+///
+/// The tree version for the L1 batch must be present in the tree, i.e., it must not be pruned
+/// while the export is in progress. `desired_chunk_size` is the desired number of tree leaves
+/// in a single snapshot chunk; up to `concurrency` chunks are exported concurrently.
+///
+/// # Errors
+///
+/// Returns an error if the tree version is missing, or if persisting data to the object store fails.
+pub async fn export_tree_snapshot(
+    db: RocksDBWrapper,
+    l1_batch_number: L1BatchNumber,
+    blob_store: &dyn ObjectStore,
+    desired_chunk_size: u64,
+    concurrency: usize,
+) -> anyhow::Result<MerkleTreeSnapshotHeader> {
+    anyhow::ensure!(desired_chunk_size > 0, "Chunk size must be positive");
+    anyhow::ensure!(concurrency > 0, "Concurrency must be positive");
+
+    let reader = AsyncTree::new(db, MerkleTreeMode::Lightweight).reader();
+    let (root_hash, leaf_count) = reader
+        .clone()
+        .root_info(l1_batch_number)
+        .await
+        .with_context(|| {
+            format!(
+                "Merkle tree doesn't contain version for L1 batch #{l1_batch_number}; \
+                 it may be not computed yet or pruned"
+            )
+        })?;
+    let chunk_count = zksync_utils::ceil_div(leaf_count, desired_chunk_size).max(1);
+    tracing::info!(
+        "Exporting Merkle tree snapshot for L1 batch #{l1_batch_number} with root hash {root_hash:?} \
+         and {leaf_count} leaves in {chunk_count} chunks"
+    );
+
+    let chunk_tasks = chunk_key_ranges(chunk_count)
+        .zip(0..)
+        .map(|(key_range, chunk_id)| {
+            let reader = reader.clone();
+            async move {
+                let entries = reader
+                    .entries_in_range(l1_batch_number, key_range)
+                    .await
+                    .with_context(|| format!("failed reading entries for chunk #{chunk_id}"))?;
+                let chunk = MerkleTreeSnapshotChunk {
+                    entries: entries
+                        .into_iter()
+                        .map(|entry| MerkleTreeSnapshotEntry {
+                            hashed_key: entry.key,
+                            value: entry.value,
+                            leaf_index: entry.leaf_index,
+                        })
+                        .collect(),
+                };
+                let entry_count = chunk.entries.len() as u64;
+
+                let key = MerkleTreeSnapshotChunkKey {
+                    l1_batch_number,
+                    chunk_id,
+                };
+                blob_store
+                    .put(key, &chunk)
+                    .await
+                    .with_context(|| format!("failed persisting chunk #{chunk_id}"))?;
+                tracing::info!(
+                    "Exported chunk #{chunk_id} / {chunk_count} with {entry_count} leaves"
+                );
+                anyhow::Ok(entry_count)
+            }
+        });
+    let exported_leaf_count = stream::iter(chunk_tasks)
+        .buffer_unordered(concurrency)
+        .try_fold(0, |acc, entry_count| async move {
+            anyhow::Ok(acc + entry_count)
+        })
+        .await?;
+    anyhow::ensure!(
+        exported_leaf_count == leaf_count,
+        "Number of exported leaves ({exported_leaf_count}) differs from the leaf count in the tree ({leaf_count})"
+    );
+
+    let header = MerkleTreeSnapshotHeader {
+        l1_batch_number,
+        root_hash,
+        leaf_count,
+        chunk_count,
+    };
+    blob_store
+        .put(l1_batch_number, &header)
+        .await
+        .context("failed persisting snapshot header")?;
+    tracing::info!("Exported Merkle tree snapshot for L1 batch #{l1_batch_number}");
+    Ok(header)
+}
+
+/// Imports a Merkle tree snapshot for the specified L1 batch from the object store. The tree must be empty
+/// or be in the middle of importing the same snapshot. Up to `concurrency` chunks are loaded concurrently.
+///
+/// If `expected_root_hash` is specified (e.g., taken from Postgres), it is checked against the root hash
+/// in the snapshot header before the import starts.
+///
+/// # Errors
+///
+/// Returns an error if the snapshot is missing or incomplete, if the tree is not empty, or if the root hash
+/// of the imported tree doesn't match the snapshot. In the latter case, the tree remains in the recovery state,
+/// and its data should be removed.
+pub async fn import_tree_snapshot(
+    db: RocksDBWrapper,
+    l1_batch_number: L1BatchNumber,
+    blob_store: &dyn ObjectStore,
+    expected_root_hash: Option<H256>,
+    concurrency: usize,
+) -> anyhow::Result<MerkleTreeSnapshotHeader> {
+    anyhow::ensure!(concurrency > 0, "Concurrency must be positive");
+
+    let header: MerkleTreeSnapshotHeader =
+        blob_store.get(l1_batch_number).await.with_context(|| {
+            format!(
+                "failed loading header of Merkle tree snapshot for L1 batch #{l1_batch_number}; \
+                 the snapshot may be missing or incomplete"
+            )
+        })?;
+    anyhow::ensure!(
+        header.l1_batch_number == l1_batch_number,
+        "Snapshot header is for L1 batch #{}, while #{l1_batch_number} was requested",
+        header.l1_batch_number
+    );
+    if let Some(expected_root_hash) = expected_root_hash {
+        anyhow::ensure!(
+            header.root_hash == expected_root_hash,
+            "Root hash in snapshot header {:?} differs from the expected root hash {expected_root_hash:?}",
+            header.root_hash
+        );
+    }
+
+    let recovered_version = u64::from(l1_batch_number.0);
+    let mut tree = match GenericAsyncTree::new(db, MerkleTreeMode::Lightweight).await {
+        GenericAsyncTree::Empty { db, mode } => AsyncTreeRecovery::new(db, recovered_version, mode),
+        GenericAsyncTree::Recovering(tree) => {
+            anyhow::ensure!(
+                tree.recovered_version() == recovered_version,
+                "Merkle tree is being recovered for L1 batch #{}, while the snapshot is for L1 batch #{l1_batch_number}",
+                tree.recovered_version()
+            );
+            tree
+        }
+        GenericAsyncTree::Ready(_) => {
+            anyhow::bail!(
+                "Merkle tree is not empty; a snapshot can only be imported into an empty tree"
+            );
+        }
+    };
+
+    // Chunks are imported sequentially in the key order, so all chunks starting before the last processed key
+    // are already imported.
+    let last_processed_key = tree.last_processed_key().await;
+    let remaining_chunks: Vec<_> = chunk_key_ranges(header.chunk_count)
+        .zip(0..)
+        .filter(|(key_range, _)| last_processed_key.map_or(true, |key| *key_range.start() > key))
+        .collect();
+    tracing::info!(
+        "Importing Merkle tree snapshot for L1 batch #{l1_batch_number} with root hash {:?} and {} leaves; \
+         {} / {} chunks remaining",
+        header.root_hash,
+        header.leaf_count,
+        remaining_chunks.len(),
+        header.chunk_count
+    );
+
+    let leaf_count = header.leaf_count;
+    let mut chunks = stream::iter(remaining_chunks)
+        .map(|(key_range, chunk_id)| async move {
+            let key = MerkleTreeSnapshotChunkKey {
+                l1_batch_number,
+                chunk_id,
+            };
+            let chunk: MerkleTreeSnapshotChunk = blob_store
+                .get(key)
+                .await
+                .with_context(|| format!("failed loading chunk #{chunk_id}"))?;
+            validate_chunk(&chunk, &key_range, leaf_count)
+                .with_context(|| format!("chunk #{chunk_id} is invalid"))?;
+            anyhow::Ok((chunk_id, chunk))
+        })
+        .buffered(concurrency);
+
+    while let Some((chunk_id, chunk)) = chunks.try_next().await? {
+        let entry_count = chunk.entries.len();
+        let entries = chunk
+            .entries
+            .into_iter()
+            .map(|entry| TreeEntry::new(entry.hashed_key, entry.leaf_index, entry.value))
+            .collect();
+        tree.extend(entries).await;
+        tracing::info!(
+            "Imported chunk #{chunk_id} / {} with {entry_count} leaves",
+            header.chunk_count
+        );
+    }
+
+    let root_hash = tree.root_hash().await;
+    anyhow::ensure!(
+        root_hash == header.root_hash,
+        "Root hash of the imported tree {root_hash:?} differs from the root hash in snapshot header {:?}; \
+         the snapshot may be corrupted",
+        header.root_hash
+    );
+    tree.finalize().await;
+    tracing::info!("Imported Merkle tree snapshot for L1 batch #{l1_batch_number}");
+    Ok(header)
+}
+
+fn validate_chunk(
+    chunk: &MerkleTreeSnapshotChunk,
+    key_range: &ops::RangeInclusive<Key>,
+    leaf_count: u64,
+) -> anyhow::Result<()> {
+    for entry in &chunk.entries {
+        anyhow::ensure!(
+            key_range.contains(&entry.hashed_key),
+            "entry {entry:?} is outside the chunk key range"
+        );
+        anyhow::ensure!(
+            (1..=leaf_count).contains(&entry.leaf_index),
+            "entry {entry:?} has invalid leaf index; the tree has {leaf_count} leaves"
+        );
+    }
+    for window in chunk.entries.windows(2) {
+        let [prev_entry, next_entry] = window else {
+            unreachable!();
+        };
+        anyhow::ensure!(
+            prev_entry.hashed_key < next_entry.hashed_key,
+            "entries {prev_entry:?} and {next_entry:?} are not ordered by hashed key"
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tempfile::TempDir;
+    use zksync_merkle_tree::{domain::ZkSyncTree, TreeInstruction};
+    use zksync_object_store::ObjectStoreFactory;
+    use zksync_storage::{RocksDBOptions, StalledWritesRetries};
+
+    use super::*;
+    use crate::metadata_calculator::{helpers::create_db, tests::gen_storage_logs};
+
+    async fn create_test_db(temp_dir: &TempDir) -> RocksDBWrapper {
+        create_db(
+            temp_dir.path().to_owned(),
+            RocksDBOptions {
+                block_cache_capacity: Some(0),
+                large_memtable_capacity: Some(16 << 20), // 16 MiB
+                // Writes should never be stalled in tests.
+                stalled_writes_retries: StalledWritesRetries::new(Duration::ZERO),
+                ..RocksDBOptions::default()
+            },
+            500,
+        )
+        .await
+    }
+
+    /// Creates a tree with 2 L1 batches and returns its root hash.
+    async fn prepare_tree(db: RocksDBWrapper) -> H256 {
+        let logs = gen_storage_logs(0..200, 2);
+        tokio::task::spawn_blocking(move || {
+            let mut tree = ZkSyncTree::new_lightweight(db);
+            let mut leaf_index = 0;
+            for batch_logs in logs {
+                let instructions: Vec<_> = batch_logs
+                    .iter()
+                    .map(|log| {
+                        leaf_index += 1;
+                        TreeInstruction::write(log.key, leaf_index, log.value)
+                    })
+                    .collect();
+                tree.process_l1_batch(&instructions);
+            }
+            tree.save();
+            tree.root_hash()
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn exporting_and_importing_tree_snapshot() {
+        let source_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+        let source_db = create_test_db(&source_dir).await;
+        let root_hash = prepare_tree(source_db.clone()).await;
+        let blob_store = ObjectStoreFactory::mock().create_store().await;
+
+        let l1_batch_number = L1BatchNumber(1);
+        let header = export_tree_snapshot(source_db, l1_batch_number, &*blob_store, 30, 2)
+            .await
+            .unwrap();
+        assert_eq!(header.root_hash, root_hash);
+        assert_eq!(header.leaf_count, 200);
+        assert_eq!(header.chunk_count, 7);
+
+        let target_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+        let target_db = create_test_db(&target_dir).await;
+        let imported_header = import_tree_snapshot(
+            target_db.clone(),
+            l1_batch_number,
+            &*blob_store,
+            Some(root_hash),
+            3,
+        )
+        .await
+        .unwrap();
+        assert_eq!(imported_header, header);
+
+        let tree = ZkSyncTree::new_lightweight(target_db.clone());
+        assert_eq!(tree.root_hash(), root_hash);
+        assert_eq!(tree.next_l1_batch_number(), L1BatchNumber(2));
+
+        // Importing into a non-empty tree should fail.
+        let err = import_tree_snapshot(target_db, l1_batch_number, &*blob_store, None, 1)
+            .await
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("not empty"), "{err:#}");
+    }
+
+    #[tokio::test]
+    async fn resuming_tree_snapshot_import() {
+        let source_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+        let source_db = create_test_db(&source_dir).await;
+        let root_hash = prepare_tree(source_db.clone()).await;
+        let blob_store = ObjectStoreFactory::mock().create_store().await;
+        let l1_batch_number = L1BatchNumber(1);
+        export_tree_snapshot(source_db, l1_batch_number, &*blob_store, 50, 1)
+            .await
+            .unwrap();
+
+        // Emulate an interrupted import.
+        let target_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+        let target_db = create_test_db(&target_dir).await;
+        let mut recovery =
+            AsyncTreeRecovery::new(target_db.clone(), 1, MerkleTreeMode::Lightweight);
+        for chunk_id in 0..2 {
+            let key = MerkleTreeSnapshotChunkKey {
+                l1_batch_number,
+                chunk_id,
+            };
+            let chunk: MerkleTreeSnapshotChunk = blob_store.get(key).await.unwrap();
+            let entries = chunk
+                .entries
+                .into_iter()
+                .map(|entry| TreeEntry::new(entry.hashed_key, entry.leaf_index, entry.value))
+                .collect();
+            recovery.extend(entries).await;
+        }
+        drop(recovery);
+
+        let err = import_tree_snapshot(
+            target_db.clone(),
+            l1_batch_number,
+            &*blob_store,
+            Some(H256::repeat_byte(1)),
+            1,
+        )
+        .await
+        .unwrap_err();
+        assert!(format!("{err:#}").contains("expected root hash"), "{err:#}");
+
+        import_tree_snapshot(target_db.clone(), l1_batch_number, &*blob_store, None, 2)
+            .await
+            .unwrap();
+        let tree = ZkSyncTree::new_lightweight(target_db);
+        assert_eq!(tree.root_hash(), root_hash);
+    }
+}
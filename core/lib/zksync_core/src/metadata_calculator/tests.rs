@@ -8,21 +8,27 @@ use tempfile::TempDir;
 use tokio::sync::{mpsc, watch};
 use zksync_config::configs::{
     chain::OperationsManagerConfig,
-    database::{MerkleTreeConfig, MerkleTreeMode},
+    database::{MerkleTreeBackend, MerkleTreeConfig, MerkleTreeMode},
 };
 use zksync_dal::{ConnectionPool, StorageProcessor};
 use zksync_health_check::{CheckHealth, HealthStatus};
 use zksync_merkle_tree::domain::ZkSyncTree;
 use zksync_object_store::{ObjectStore, ObjectStoreFactory};
 use zksync_types::{
+    aggregated_operations::AggregatedActionType,
     block::{BlockGasCount, L1BatchHeader},
+    commitment::L1BatchCommitmentMode,
     proofs::PrepareBasicCircuitsJob,
     AccountTreeId, Address, L1BatchNumber, L2ChainId, MiniblockNumber, StorageKey, StorageLog,
     H256,
 };
 use zksync_utils::u32_to_h256;
 
-use super::{GenericAsyncTree, L1BatchWithLogs, MetadataCalculator, MetadataCalculatorConfig};
+use super::{
+    consistency::{MerkleTreeConsistencyChecker, TreeDivergence},
+    GenericAsyncTree, L1BatchWithLogs, MerkleTreePruningTask, MetadataCalculator,
+    MetadataCalculatorConfig,
+};
 use crate::{
     genesis::{ensure_genesis_state, GenesisParams},
     utils::testonly::{create_l1_batch, create_miniblock},
@@ -84,6 +90,29 @@ async fn basic_workflow() {
     assert_eq!(tree.next_l1_batch_number(), L1BatchNumber(2));
 }
 
+#[tokio::test]
+async fn basic_workflow_with_in_memory_tree() {
+    let pool = ConnectionPool::test_pool().await;
+    let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+    let (mut merkle_tree_config, operation_config) =
+        create_config(temp_dir.path(), MerkleTreeMode::Lightweight);
+    merkle_tree_config.backend = MerkleTreeBackend::InMemory;
+
+    let calculator =
+        setup_calculator_with_options(&merkle_tree_config, &operation_config, &pool, None).await;
+    reset_db_state(&pool, 1).await;
+    let merkle_tree_hash = run_calculator(calculator, pool.clone()).await;
+    assert_eq!(merkle_tree_hash, expected_tree_hash(&pool).await);
+    assert!(!Path::new(&merkle_tree_config.path).exists());
+
+    // The tree is not persisted, so it should be rebuilt from Postgres after a restart.
+    let calculator =
+        setup_calculator_with_options(&merkle_tree_config, &operation_config, &pool, None).await;
+    assert_matches!(calculator.tree, GenericAsyncTree::Empty { .. });
+    let merkle_tree_hash = run_calculator(calculator, pool.clone()).await;
+    assert_eq!(merkle_tree_hash, expected_tree_hash(&pool).await);
+}
+
 async fn expected_tree_hash(pool: &ConnectionPool) -> H256 {
     let mut storage = pool.access_storage().await.unwrap();
     let sealed_l1_batch_number = storage
@@ -132,6 +161,9 @@ async fn status_receiver_has_correct_states() {
         other_tree_health_check.check_health().await.status(),
         HealthStatus::Ready
     );
+    let health = serde_json::to_value(tree_health_check.check_health().await).unwrap();
+    assert_eq!(health["details"]["next_l1_batch_number"], 2);
+    assert_eq!(health["details"]["l1_batch_lag"], 0);
 
     stop_sx.send(true).unwrap();
     tokio::time::timeout(RUN_TIMEOUT, calculator_handle)
@@ -358,6 +390,99 @@ async fn postgres_backup_recovery_with_excluded_metadata() {
     test_postgres_backup_recovery(false, true).await;
 }
 
+#[tokio::test]
+async fn pruning_retained_version_limit() {
+    let pool = ConnectionPool::test_pool().await;
+    let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+    setup_lightweight_calculator(temp_dir.path(), &pool).await;
+    reset_db_state(&pool, 5).await;
+
+    let mut storage = pool.access_storage().await.unwrap();
+    let limit = MerkleTreePruningTask::retained_version_limit(&mut storage)
+        .await
+        .unwrap();
+    assert_eq!(limit, None);
+
+    storage
+        .eth_sender_dal()
+        .insert_bogus_confirmed_eth_tx(
+            L1BatchNumber(3),
+            AggregatedActionType::Execute,
+            H256::repeat_byte(3),
+            chrono::Utc::now(),
+        )
+        .await
+        .unwrap();
+    let limit = MerkleTreePruningTask::retained_version_limit(&mut storage)
+        .await
+        .unwrap();
+    assert_eq!(limit, Some(L1BatchNumber(3)));
+
+    // A snapshot being created protects its tree version from pruning.
+    storage
+        .snapshots_dal()
+        .add_snapshot(L1BatchNumber(2), 2, "factory_deps")
+        .await
+        .unwrap();
+    let limit = MerkleTreePruningTask::retained_version_limit(&mut storage)
+        .await
+        .unwrap();
+    assert_eq!(limit, Some(L1BatchNumber(2)));
+
+    for chunk_id in 0..2 {
+        storage
+            .snapshots_dal()
+            .add_storage_logs_filepath_for_snapshot(L1BatchNumber(2), chunk_id, "storage_logs")
+            .await
+            .unwrap();
+    }
+    let limit = MerkleTreePruningTask::retained_version_limit(&mut storage)
+        .await
+        .unwrap();
+    assert_eq!(limit, Some(L1BatchNumber(3)));
+}
+
+#[tokio::test]
+async fn consistency_checker_detects_diverged_leaf() {
+    let pool = ConnectionPool::test_pool().await;
+    let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+    let (calculator, _) = setup_calculator(temp_dir.path(), &pool).await;
+    reset_db_state(&pool, 5).await;
+    run_calculator(calculator, pool.clone()).await;
+
+    let (calculator, _) = setup_calculator(temp_dir.path(), &pool).await;
+    let GenericAsyncTree::Ready(tree) = &calculator.tree else {
+        panic!("Unexpected tree state: {:?}", calculator.tree);
+    };
+    let reader = tree.reader();
+    let mut checker = MerkleTreeConsistencyChecker::new(10_000, Duration::from_millis(50));
+    let mut storage = pool.access_storage().await.unwrap();
+    let divergence = checker.check_once(&reader, &mut storage).await.unwrap();
+    assert!(divergence.is_none(), "{divergence:?}");
+    assert_eq!(
+        checker.health_details.last_checked_l1_batch,
+        Some(L1BatchNumber(5))
+    );
+    assert!(checker.health_details.checked_leaf_count > 0);
+
+    // Overwrite a slot from the first L1 batch in Postgres without updating the tree.
+    let diverged_key = gen_storage_logs(0..100, 5)[0][0].key;
+    let log = StorageLog::new_write_log(diverged_key, H256::repeat_byte(0xff));
+    storage
+        .storage_logs_dal()
+        .insert_storage_logs(MiniblockNumber(5), &[(H256::zero(), vec![log])])
+        .await;
+
+    let divergence = checker.check_once(&reader, &mut storage).await.unwrap();
+    assert_matches!(
+        divergence,
+        Some(TreeDivergence::Leaf { l1_batch_number, key, postgres_entry, .. })
+            if l1_batch_number == L1BatchNumber(5)
+                && key == diverged_key.hashed_key_u256()
+                && postgres_entry.value == log.value
+    );
+}
+
 pub(crate) async fn setup_calculator(
     db_path: &Path,
     pool: &ConnectionPool,
@@ -398,8 +523,11 @@ async fn setup_calculator_with_options(
     pool: &ConnectionPool,
     object_store: Option<Arc<dyn ObjectStore>>,
 ) -> MetadataCalculator {
-    let calculator_config =
-        MetadataCalculatorConfig::for_main_node(merkle_tree_config, operation_config);
+    let calculator_config = MetadataCalculatorConfig::for_main_node(
+        merkle_tree_config,
+        operation_config,
+        L1BatchCommitmentMode::Rollup,
+    );
     let metadata_calculator = MetadataCalculator::new(calculator_config, object_store).await;
 
     let mut storage = pool.access_storage().await.unwrap();
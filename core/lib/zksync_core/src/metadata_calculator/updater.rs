@@ -11,10 +11,13 @@ use zksync_dal::{ConnectionPool, StorageProcessor};
 use zksync_health_check::HealthUpdater;
 use zksync_merkle_tree::domain::TreeMetadata;
 use zksync_object_store::ObjectStore;
-use zksync_types::{block::L1BatchHeader, writes::InitialStorageWrite, L1BatchNumber, H256, U256};
+use zksync_types::{
+    block::L1BatchHeader, commitment::L1BatchCommitmentMode, writes::InitialStorageWrite,
+    L1BatchNumber, H256, U256,
+};
 
 use super::{
-    helpers::{AsyncTree, Delayer, L1BatchWithLogs},
+    helpers::{AsyncTree, Delayer, L1BatchWithLogs, MerkleTreeHealthDetails},
     metrics::{TreeUpdateStage, METRICS},
     MetadataCalculator,
 };
@@ -25,6 +28,7 @@ pub(super) struct TreeUpdater {
     tree: AsyncTree,
     max_l1_batches_per_iter: usize,
     object_store: Option<Arc<dyn ObjectStore>>,
+    commitment_mode: L1BatchCommitmentMode,
 }
 
 impl TreeUpdater {
@@ -32,11 +36,13 @@ impl TreeUpdater {
         tree: AsyncTree,
         max_l1_batches_per_iter: usize,
         object_store: Option<Arc<dyn ObjectStore>>,
+        commitment_mode: L1BatchCommitmentMode,
     ) -> Self {
         Self {
             tree,
             max_l1_batches_per_iter,
             object_store,
+            commitment_mode,
         }
     }
 
@@ -136,6 +142,7 @@ impl TreeUpdater {
                 &header,
                 events_queue_commitment,
                 bootloader_initial_content_commitment,
+                self.commitment_mode,
             );
             build_metadata_latency.observe();
 
@@ -234,11 +241,12 @@ impl TreeUpdater {
         )
     }
 
+    /// Returns the last sealed L1 batch in Postgres, or `None` if Postgres has no L1 batches.
     async fn step(
         &mut self,
         mut storage: StorageProcessor<'_>,
         next_l1_batch_to_seal: &mut L1BatchNumber,
-    ) {
+    ) -> Option<L1BatchNumber> {
         let Some(last_sealed_l1_batch) = storage
             .blocks_dal()
             .get_sealed_l1_batch_number()
@@ -246,7 +254,7 @@ impl TreeUpdater {
             .unwrap()
         else {
             tracing::trace!("No L1 batches to seal: Postgres storage is empty");
-            return;
+            return None;
         };
         let last_requested_l1_batch =
             next_l1_batch_to_seal.0 + self.max_l1_batches_per_iter as u32 - 1;
@@ -262,6 +270,17 @@ impl TreeUpdater {
                 .process_multiple_batches(&mut storage, l1_batch_numbers)
                 .await;
         }
+        Some(last_sealed_l1_batch)
+    }
+
+    /// Computes the lag of the tree relative to the state keeper and reports it in metrics.
+    fn l1_batch_lag(
+        last_sealed_l1_batch: Option<L1BatchNumber>,
+        next_l1_batch_to_seal: L1BatchNumber,
+    ) -> Option<u32> {
+        let lag = (last_sealed_l1_batch?.0 + 1).saturating_sub(next_l1_batch_to_seal.0);
+        METRICS.l1_batch_lag.set(lag.into());
+        Some(lag)
     }
 
     /// The processing loop for this updater.
@@ -308,8 +327,12 @@ impl TreeUpdater {
              last L1 batch with metadata: {last_l1_batch_with_metadata:?}",
             max_batches_per_iter = self.max_l1_batches_per_iter
         );
-        let tree_info = tree.reader().info().await;
-        health_updater.update(tree_info.into());
+        let mut l1_batch_lag = Self::l1_batch_lag(current_db_batch, next_l1_batch_to_seal);
+        let health_details = MerkleTreeHealthDetails {
+            info: tree.reader().info().await,
+            l1_batch_lag,
+        };
+        health_updater.update(health_details.into());
 
         // It may be the case that we don't have any L1 batches with metadata in Postgres, e.g. after
         // recovering from a snapshot. We cannot wait for such a batch to appear (*this* is the component
@@ -336,8 +359,12 @@ impl TreeUpdater {
                 next_l1_batch_to_seal = tree.next_l1_batch_number();
                 tracing::info!("Truncated Merkle tree to L1 batch #{next_l1_batch_to_seal}");
 
-                let tree_info = tree.reader().info().await;
-                health_updater.update(tree_info.into());
+                l1_batch_lag = Self::l1_batch_lag(current_db_batch, next_l1_batch_to_seal);
+                let health_details = MerkleTreeHealthDetails {
+                    info: tree.reader().info().await,
+                    l1_batch_lag,
+                };
+                health_updater.update(health_details.into());
             }
         }
 
@@ -349,17 +376,27 @@ impl TreeUpdater {
             let storage = pool.access_storage_tagged("metadata_calculator").await?;
 
             let snapshot = *next_l1_batch_to_seal;
-            self.step(storage, &mut next_l1_batch_to_seal).await;
-            let delay = if snapshot == *next_l1_batch_to_seal {
+            let last_sealed_l1_batch = self.step(storage, &mut next_l1_batch_to_seal).await;
+            let new_l1_batch_lag = Self::l1_batch_lag(last_sealed_l1_batch, next_l1_batch_to_seal);
+            let made_progress = snapshot != *next_l1_batch_to_seal;
+            // The lag can change without the tree making progress (e.g., if the state keeper seals new L1 batches),
+            // so we update health in this case as well.
+            if made_progress || new_l1_batch_lag != l1_batch_lag {
+                l1_batch_lag = new_l1_batch_lag;
+                let health_details = MerkleTreeHealthDetails {
+                    info: self.tree.reader().info().await,
+                    l1_batch_lag,
+                };
+                health_updater.update(health_details.into());
+            }
+
+            let delay = if !made_progress {
                 tracing::trace!(
                     "Metadata calculator (next L1 batch: #{next_l1_batch_to_seal}) \
                      didn't make any progress; delaying it using {delayer:?}"
                 );
                 delayer.wait(&self.tree).left_future()
             } else {
-                let tree_info = self.tree.reader().info().await;
-                health_updater.update(tree_info.into());
-
                 tracing::trace!(
                     "Metadata calculator (next L1 batch: #{next_l1_batch_to_seal}) made progress from #{snapshot}"
                 );
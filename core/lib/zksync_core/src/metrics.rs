@@ -11,6 +11,7 @@ use zksync_types::aggregated_operations::AggregatedActionType;
 pub(crate) enum InitStage {
     HttpApi,
     WsApi,
+    GrpcApi,
     ContractVerificationApi,
     StateKeeper,
     EthWatcher,
@@ -25,6 +26,7 @@ impl fmt::Display for InitStage {
         match self {
             Self::HttpApi => formatter.write_str("http_api"),
             Self::WsApi => formatter.write_str("ws_api"),
+            Self::GrpcApi => formatter.write_str("grpc_api"),
             Self::ContractVerificationApi => formatter.write_str("contract_verification_api"),
             Self::StateKeeper => formatter.write_str("state_keeper"),
             Self::EthWatcher => formatter.write_str("eth_watcher"),
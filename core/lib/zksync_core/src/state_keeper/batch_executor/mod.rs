@@ -1,4 +1,4 @@
-use std::{fmt, sync::Arc};
+use std::{fmt, mem, sync::Arc};
 
 use async_trait::async_trait;
 use multivm::{
@@ -15,19 +15,33 @@ use tokio::{
     sync::{mpsc, oneshot},
     task::JoinHandle,
 };
+use zksync_config::{configs::database::RocksdbCompactionStyle, DBConfig};
+use zksync_contracts::BaseSystemContracts;
 use zksync_dal::ConnectionPool;
-use zksync_state::{RocksdbStorage, StorageView, WriteStorage};
-use zksync_types::{vm_trace::Call, witness_block_state::WitnessBlockState, Transaction, U256};
+use zksync_state::{RocksdbStorage, StoragePtr, StorageView, WriteStorage};
+use zksync_storage::RocksDBOptions;
+use zksync_types::{
+    l2::conditions::TransactionConditionsError, vm_trace::Call,
+    witness_block_state::WitnessBlockState, ExecuteTransactionCommon, MiniblockNumber, Transaction,
+    U256,
+};
 use zksync_utils::bytecode::CompressedBytecodeInfo;
 
+use self::{
+    prefetcher::StoragePrefetcher,
+    shadow::{ExecutedTx, ShadowExecutor, ShadowStatus},
+};
 use crate::{
     metrics::{InteractionType, TxStage, APP_METRICS},
     state_keeper::{
         metrics::{ExecutorCommand, TxExecutionStage, EXECUTOR_METRICS, KEEPER_METRICS},
         types::ExecutionMetricsForCriteria,
     },
+    utils::rocksdb_compaction_style,
 };
 
+mod prefetcher;
+mod shadow;
 #[cfg(test)]
 mod tests;
 
@@ -45,6 +59,8 @@ pub(crate) enum TxExecutionResult {
     },
     /// The VM rejected the tx for some reason.
     RejectedByVm { reason: Halt },
+    /// Conditions of a conditional L2 tx do not hold.
+    ConditionsNotMet(TransactionConditionsError),
     /// Bootloader gas limit is not enough to execute the tx.
     BootloaderOutOfGasForTx,
     /// Bootloader gas limit is enough to run the tx but not enough to execute block tip.
@@ -55,7 +71,7 @@ impl TxExecutionResult {
     /// Returns a revert reason if either transaction was rejected or bootloader ran out of gas.
     pub(super) fn err(&self) -> Option<&Halt> {
         match self {
-            Self::Success { .. } => None,
+            Self::Success { .. } | Self::ConditionsNotMet(_) => None,
             Self::RejectedByVm {
                 reason: rejection_reason,
             } => Some(rejection_reason),
@@ -78,17 +94,61 @@ pub trait L1BatchExecutorBuilder: 'static + Send + Sync + fmt::Debug {
     ) -> BatchExecutorHandle;
 }
 
+/// Tuning options for the RocksDB instance serving as the state keeper cache.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StateKeeperDbOptions {
+    /// Capacity of the block cache in bytes. If not specified, default RocksDB cache options are used.
+    pub block_cache_capacity: Option<usize>,
+    /// Compaction style.
+    pub compaction_style: RocksdbCompactionStyle,
+    /// Size of a single memtable in bytes.
+    pub write_buffer_size: Option<usize>,
+    /// Maximum number of memtables per column family.
+    pub max_write_buffer_number: Option<usize>,
+    /// Maximum number of concurrent background jobs (compactions and flushes).
+    pub max_background_jobs: Option<usize>,
+}
+
+impl StateKeeperDbOptions {
+    pub(crate) fn for_main_node(config: &DBConfig) -> Self {
+        Self {
+            block_cache_capacity: config.state_keeper_db_block_cache_size(),
+            compaction_style: config.state_keeper_db_compaction_style,
+            write_buffer_size: config.state_keeper_db_write_buffer_size(),
+            max_write_buffer_number: config.state_keeper_db_max_write_buffer_number,
+            max_background_jobs: config.state_keeper_db_max_background_jobs,
+        }
+    }
+
+    fn rocksdb_options(&self) -> RocksDBOptions {
+        RocksDBOptions {
+            block_cache_capacity: self.block_cache_capacity,
+            compaction_style: rocksdb_compaction_style(self.compaction_style),
+            write_buffer_size: self.write_buffer_size,
+            max_write_buffer_number: self.max_write_buffer_number,
+            max_background_jobs: self.max_background_jobs,
+            ..RocksDBOptions::default()
+        }
+    }
+}
+
 /// The default implementation of [`L1BatchExecutorBuilder`].
 /// Creates a "real" batch executor which maintains the VM (as opposed to the test builder which doesn't use the VM).
 #[derive(Debug, Clone)]
 pub struct MainBatchExecutorBuilder {
     state_keeper_db_path: String,
+    state_keeper_db_options: StateKeeperDbOptions,
     pool: ConnectionPool,
     save_call_traces: bool,
     max_allowed_tx_gas_limit: U256,
     upload_witness_inputs_to_gcs: bool,
     enum_index_migration_chunk_size: usize,
     optional_bytecode_compression: bool,
+    storage_prefetch_workers: Option<usize>,
+    /// Base system contracts for the shadow VM; set if shadow execution is enabled.
+    shadow_base_system_contracts: Option<BaseSystemContracts>,
+    /// Status of the shadow VM for the last L1 batch that was shadow-executed.
+    last_shadow_status: Option<ShadowStatus>,
 }
 
 impl MainBatchExecutorBuilder {
@@ -103,13 +163,63 @@ impl MainBatchExecutorBuilder {
     ) -> Self {
         Self {
             state_keeper_db_path,
+            state_keeper_db_options: StateKeeperDbOptions::default(),
             pool,
             save_call_traces,
             max_allowed_tx_gas_limit,
             upload_witness_inputs_to_gcs,
             enum_index_migration_chunk_size,
             optional_bytecode_compression,
+            storage_prefetch_workers: None,
+            shadow_base_system_contracts: None,
+            last_shadow_status: None,
+        }
+    }
+
+    /// Sets tuning options for the RocksDB instance serving as the state keeper cache.
+    pub fn with_state_keeper_db_options(mut self, options: StateKeeperDbOptions) -> Self {
+        self.state_keeper_db_options = options;
+        self
+    }
+
+    /// Enables prefetching storage values for upcoming transactions using the specified number of worker threads.
+    pub fn with_storage_prefetch(mut self, workers: usize) -> Self {
+        self.storage_prefetch_workers = Some(workers);
+        self
+    }
+
+    /// Enables re-execution of miniblocks on the latest VM version with reporting divergences from the main VM.
+    pub fn with_shadow_execution(mut self) -> Self {
+        // Base system contracts on disk correspond to `vm_latest`.
+        self.shadow_base_system_contracts = Some(BaseSystemContracts::load_from_disk());
+        self
+    }
+
+    fn start_shadow_execution(
+        &mut self,
+        storage: &RocksdbStorage,
+        l1_batch_params: &L1BatchEnv,
+        system_env: &SystemEnv,
+    ) -> Option<ShadowExecutor> {
+        let base_system_contracts = self.shadow_base_system_contracts.clone()?;
+        if let Some(status) = &self.last_shadow_status {
+            if !status.is_finished() {
+                EXECUTOR_METRICS.shadow_skipped_batches.inc();
+                tracing::warn!(
+                    "Skipping shadow execution of L1 batch #{} since the shadow VM is busy with the previous batch",
+                    l1_batch_params.number
+                );
+                return None;
+            }
         }
+        let shadow_executor = ShadowExecutor::new(
+            storage.clone_for_reads(),
+            l1_batch_params.clone(),
+            system_env.clone(),
+            base_system_contracts,
+        );
+        self.last_shadow_status = Some(shadow_executor.status());
+        Some(shadow_executor)
     }
 }
 
@@ -120,8 +230,15 @@ impl L1BatchExecutorBuilder for MainBatchExecutorBuilder {
         l1_batch_params: L1BatchEnv,
         system_env: SystemEnv,
     ) -> BatchExecutorHandle {
-        let mut secondary_storage = RocksdbStorage::new(self.state_keeper_db_path.as_ref());
+        let mut secondary_storage = RocksdbStorage::with_options(
+            self.state_keeper_db_path.as_ref(),
+            self.state_keeper_db_options.rocksdb_options(),
+        );
         secondary_storage.enable_enum_index_migration(self.enum_index_migration_chunk_size);
+        if let Some(status) = &self.last_shadow_status {
+            // Must be performed before RocksDB is updated.
+            status.cancel_if_lagging(l1_batch_params.number);
+        }
         let mut conn = self
             .pool
             .access_storage_tagged("state_keeper")
@@ -130,6 +247,8 @@ impl L1BatchExecutorBuilder for MainBatchExecutorBuilder {
         secondary_storage.update_from_postgres(&mut conn).await;
         drop(conn);
 
+        let shadow_executor =
+            self.start_shadow_execution(&secondary_storage, &l1_batch_params, &system_env);
         BatchExecutorHandle::new(
             self.save_call_traces,
             self.max_allowed_tx_gas_limit,
@@ -138,6 +257,8 @@ impl L1BatchExecutorBuilder for MainBatchExecutorBuilder {
             system_env,
             self.upload_witness_inputs_to_gcs,
             self.optional_bytecode_compression,
+            self.storage_prefetch_workers,
+            shadow_executor,
         )
     }
 }
@@ -162,6 +283,8 @@ impl BatchExecutorHandle {
         system_env: SystemEnv,
         upload_witness_inputs_to_gcs: bool,
         optional_bytecode_compression: bool,
+        storage_prefetch_workers: Option<usize>,
+        shadow_executor: Option<ShadowExecutor>,
     ) -> Self {
         // Since we process `BatchExecutor` commands one-by-one (the next command is never enqueued
         // until a previous command is processed), capacity 1 is enough for the commands channel.
@@ -170,6 +293,8 @@ impl BatchExecutorHandle {
             save_call_traces,
             max_allowed_tx_gas_limit,
             optional_bytecode_compression,
+            storage_prefetch_workers,
+            shadow_executor,
             commands: commands_receiver,
         };
 
@@ -226,6 +351,22 @@ impl BatchExecutorHandle {
         res
     }
 
+    /// Starts prefetching storage values for the provided transactions in the background, so that their subsequent
+    /// execution via [`Self::execute_tx()`] is faster. Does not change the batch state and doesn't wait for prefetching
+    /// to complete. No-op if storage prefetching is not enabled for the executor.
+    pub(super) async fn prefetch_storage(&self, txs: Vec<Transaction>) {
+        let (response_sender, response_receiver) = oneshot::channel();
+        self.commands
+            .send(Command::PrefetchStorage(txs, response_sender))
+            .await
+            .unwrap();
+        let latency = EXECUTOR_METRICS.batch_executor_command_response_time
+            [&ExecutorCommand::PrefetchStorage]
+            .start();
+        response_receiver.await.unwrap();
+        latency.observe();
+    }
+
     pub(super) async fn start_next_miniblock(&self, miniblock_info: L2BlockEnv) {
         // While we don't get anything from the channel, it's useful to have it as a confirmation that the operation
         // indeed has been processed.
@@ -275,6 +416,7 @@ impl BatchExecutorHandle {
 #[derive(Debug)]
 pub(super) enum Command {
     ExecuteTx(Box<Transaction>, oneshot::Sender<TxExecutionResult>),
+    PrefetchStorage(Vec<Transaction>, oneshot::Sender<()>),
     StartNextMiniblock(L2BlockEnv, oneshot::Sender<()>),
     RollbackLastTx(oneshot::Sender<()>),
     FinishBatch(oneshot::Sender<(FinishedL1Batch, Option<WitnessBlockState>)>),
@@ -291,6 +433,8 @@ pub(super) struct BatchExecutor {
     save_call_traces: bool,
     max_allowed_tx_gas_limit: U256,
     optional_bytecode_compression: bool,
+    storage_prefetch_workers: Option<usize>,
+    shadow_executor: Option<ShadowExecutor>,
     commands: mpsc::Receiver<Command>,
 }
 
@@ -304,22 +448,62 @@ impl BatchExecutor {
     ) {
         tracing::info!("Starting executing batch #{:?}", &l1_batch_params.number);
 
+        // RocksDB isn't modified during batch execution, so it can be used as a snapshot of the state before the batch.
+        let prefetcher = self.storage_prefetch_workers.map(|workers| {
+            StoragePrefetcher::new(
+                workers,
+                secondary_storage.clone_for_reads(),
+                l1_batch_params.clone(),
+                system_env.clone(),
+            )
+        });
+        let shadow_executor = self.shadow_executor.take();
+        // Transactions executed in the current miniblock, in the order of snapshots in the main VM, so that they
+        // can be rolled back. Transactions not included into the miniblock (e.g., rejected ones) are recorded as `None`.
+        let mut shadow_txs: Vec<Option<ExecutedTx>> = vec![];
         let storage_view = StorageView::new(secondary_storage).to_rc_ptr();
 
+        let mut l2_block_env = l1_batch_params.first_l2_block;
         let mut vm = VmInstance::new(l1_batch_params, system_env, storage_view.clone());
 
         while let Some(cmd) = self.commands.blocking_recv() {
             match cmd {
                 Command::ExecuteTx(tx, resp) => {
-                    let result = self.execute_tx(&tx, &mut vm);
+                    if let Some(prefetcher) = &prefetcher {
+                        storage_view
+                            .borrow_mut()
+                            .cache_read_values(prefetcher.take_prefetched_values());
+                    }
+                    let result = self.execute_tx(&tx, &l2_block_env, &storage_view, &mut vm);
+                    if shadow_executor.is_some() {
+                        let executed_tx = match &result {
+                            TxExecutionResult::Success { tx_result, .. } => {
+                                Some((*tx, (**tx_result).clone()))
+                            }
+                            _ => None,
+                        };
+                        shadow_txs.push(executed_tx);
+                    }
                     resp.send(result).unwrap();
                 }
+                Command::PrefetchStorage(txs, resp) => {
+                    if let Some(prefetcher) = &prefetcher {
+                        prefetcher.prefetch(txs, storage_view.borrow().modified_storage_keys());
+                    }
+                    resp.send(()).unwrap();
+                }
                 Command::RollbackLastTx(resp) => {
                     self.rollback_last_tx(&mut vm);
+                    shadow_txs.pop();
                     resp.send(()).unwrap();
                 }
-                Command::StartNextMiniblock(l2_block_env, resp) => {
+                Command::StartNextMiniblock(next_l2_block_env, resp) => {
+                    l2_block_env = next_l2_block_env;
                     self.start_next_miniblock(l2_block_env, &mut vm);
+                    if let Some(shadow_executor) = &shadow_executor {
+                        let txs = mem::take(&mut shadow_txs).into_iter().flatten().collect();
+                        shadow_executor.execute_miniblock(txs, l2_block_env);
+                    }
                     resp.send(()).unwrap();
                 }
                 Command::FinishBatch(resp) => {
@@ -329,6 +513,11 @@ impl BatchExecutor {
                     } else {
                         None
                     };
+                    if let Some(shadow_executor) = shadow_executor {
+                        let txs = shadow_txs.into_iter().flatten().collect();
+                        let state_before_batch = storage_view.borrow().witness_block_state();
+                        shadow_executor.finish_batch(txs, &vm_block_result, state_before_batch);
+                    }
                     resp.send((vm_block_result, witness_block_state)).unwrap();
 
                     // `storage_view` cannot be accessed while borrowed by the VM,
@@ -349,6 +538,8 @@ impl BatchExecutor {
     fn execute_tx<S: WriteStorage>(
         &self,
         tx: &Transaction,
+        l2_block_env: &L2BlockEnv,
+        storage: &StoragePtr<S>,
         vm: &mut VmInstance<S, HistoryEnabled>,
     ) -> TxExecutionResult {
         // Save pre-`execute_next_tx` VM snapshot.
@@ -368,6 +559,12 @@ impl BatchExecutor {
             };
         }
 
+        // Conditions were checked when the tx was submitted, but the state may have changed since then.
+        if let Err(err) = Self::check_tx_conditions(tx, l2_block_env, storage) {
+            tracing::info!("Conditions of tx {:?} do not hold: {err}", tx.hash());
+            return TxExecutionResult::ConditionsNotMet(err);
+        }
+
         // Execute the transaction.
         let latency = KEEPER_METRICS.tx_execution_time[&TxExecutionStage::Execution].start();
         let (tx_result, compressed_bytecodes, call_tracer_result) =
@@ -413,6 +610,23 @@ impl BatchExecutor {
         }
     }
 
+    fn check_tx_conditions<S: WriteStorage>(
+        tx: &Transaction,
+        l2_block_env: &L2BlockEnv,
+        storage: &StoragePtr<S>,
+    ) -> Result<(), TransactionConditionsError> {
+        let ExecuteTransactionCommon::L2(common_data) = &tx.common_data else {
+            return Ok(());
+        };
+        let Some(conditions) = &common_data.conditions else {
+            return Ok(());
+        };
+        let miniblock_number = MiniblockNumber(l2_block_env.number);
+        conditions.check_block(miniblock_number, l2_block_env.timestamp)?;
+        let mut storage = storage.borrow_mut();
+        conditions.check_storage(|key| storage.read_value(key))
+    }
+
     fn rollback_last_tx<S: WriteStorage>(&self, vm: &mut VmInstance<S, HistoryEnabled>) {
         let latency = KEEPER_METRICS.tx_execution_time[&TxExecutionStage::TxRollback].start();
         vm.rollback_to_the_latest_snapshot();
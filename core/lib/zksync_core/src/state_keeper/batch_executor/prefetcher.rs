@@ -0,0 +1,272 @@
+//! Storage prefetching for upcoming transactions.
+//!
+//! The main VM of the batch executor must apply transactions sequentially: an L1 batch (including its bootloader
+//! memory, which is later proven) can only be produced by a single VM instance. To take RocksDB reads off
+//! the critical path, the state keeper may hand over several upcoming transactions to [`StoragePrefetcher`].
+//! The prefetcher executes each transaction speculatively in a separate VM on a background thread against
+//! the state before the batch overlaid with the batch changes known at the time, and records all values read
+//! from RocksDB. Since RocksDB isn't modified during batch execution, these values are valid regardless
+//! of the speculative execution outcome (e.g., if transactions conflict with each other). Prefetched values
+//! are cached in the storage view of the main VM once they are ready; the main VM never waits for prefetching.
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::Instant,
+};
+
+use multivm::{
+    interface::{L1BatchEnv, SystemEnv, VmInterface},
+    vm_latest::HistoryDisabled,
+    VmInstance,
+};
+use zksync_state::{ReadStorage, RocksdbStorage, StorageView};
+use zksync_system_constants::{BOOTLOADER_ADDRESS, SYSTEM_CONTEXT_ADDRESS};
+use zksync_types::{
+    utils::storage_key_for_eth_balance, StorageKey, StorageValue, Transaction, H256,
+};
+
+use crate::state_keeper::metrics::EXECUTOR_METRICS;
+
+/// Checks whether the key is written by the bootloader for each transaction (e.g., the fee balance
+/// of the bootloader). Speculative VMs start from the beginning of the batch, so changes to such keys
+/// made by the batch are not visible to them.
+fn is_batch_global_key(key: &StorageKey) -> bool {
+    *key.address() == SYSTEM_CONTEXT_ADDRESS
+        || *key == storage_key_for_eth_balance(&BOOTLOADER_ADDRESS)
+}
+
+/// Storage used by speculative VMs: the state before the batch overlaid with changes made by transactions
+/// already executed in the batch. Records all values read from the base storage.
+#[derive(Debug)]
+struct PrefetchingStorage<S> {
+    base: S,
+    overlay: Arc<HashMap<StorageKey, StorageValue>>,
+    base_reads: Rc<RefCell<HashMap<StorageKey, StorageValue>>>,
+}
+
+impl<S: ReadStorage> ReadStorage for PrefetchingStorage<S> {
+    fn read_value(&mut self, key: &StorageKey) -> StorageValue {
+        if let Some(value) = self.overlay.get(key) {
+            return *value;
+        }
+        let value = self.base.read_value(key);
+        self.base_reads.borrow_mut().insert(*key, value);
+        value
+    }
+
+    fn is_write_initial(&mut self, key: &StorageKey) -> bool {
+        self.base.is_write_initial(key)
+    }
+
+    fn load_factory_dep(&mut self, hash: H256) -> Option<Vec<u8>> {
+        self.base.load_factory_dep(hash)
+    }
+
+    fn get_enumeration_index(&mut self, key: &StorageKey) -> Option<u64> {
+        self.base.get_enumeration_index(key)
+    }
+}
+
+/// Decrements the number of running prefetching threads on drop, including if a thread panics.
+struct RunningThreadGuard(Arc<AtomicUsize>);
+
+impl Drop for RunningThreadGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Prefetches storage values for upcoming transactions on background threads.
+pub(super) struct StoragePrefetcher {
+    workers: usize,
+    /// Storage with the state before the batch.
+    base: RocksdbStorage,
+    l1_batch_env: Arc<L1BatchEnv>,
+    system_env: Arc<SystemEnv>,
+    running_threads: Arc<AtomicUsize>,
+    values_sender: mpsc::Sender<HashMap<StorageKey, StorageValue>>,
+    values_receiver: mpsc::Receiver<HashMap<StorageKey, StorageValue>>,
+}
+
+impl fmt::Debug for StoragePrefetcher {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("StoragePrefetcher")
+            .field("workers", &self.workers)
+            .field("running_threads", &self.running_threads)
+            .finish_non_exhaustive()
+    }
+}
+
+impl StoragePrefetcher {
+    pub fn new(
+        workers: usize,
+        base: RocksdbStorage,
+        l1_batch_env: L1BatchEnv,
+        system_env: SystemEnv,
+    ) -> Self {
+        assert!(
+            workers > 0,
+            "Number of storage prefetching workers must be positive"
+        );
+        let (values_sender, values_receiver) = mpsc::channel();
+        Self {
+            workers,
+            base,
+            l1_batch_env: Arc::new(l1_batch_env),
+            system_env: Arc::new(system_env),
+            running_threads: Arc::default(),
+            values_sender,
+            values_receiver,
+        }
+    }
+
+    /// Starts prefetching storage values for `txs` given the changes made by the batch so far (`batch_changes`).
+    /// Returns immediately; prefetched values can be obtained via [`Self::take_prefetched_values()`].
+    /// If the previous prefetching hasn't completed yet, `txs` are skipped, so that prefetching
+    /// never takes more than `workers` threads.
+    pub fn prefetch(
+        &self,
+        txs: Vec<Transaction>,
+        batch_changes: &HashMap<StorageKey, StorageValue>,
+    ) {
+        if txs.is_empty() {
+            return;
+        }
+        if self.running_threads.load(Ordering::SeqCst) > 0 {
+            EXECUTOR_METRICS
+                .skipped_prefetch_txs
+                .inc_by(txs.len() as u64);
+            return;
+        }
+
+        let overlay: HashMap<_, _> = batch_changes
+            .iter()
+            .filter(|(key, _)| !is_batch_global_key(key))
+            .map(|(key, value)| (*key, *value))
+            .collect();
+        let overlay = Arc::new(overlay);
+        let chunk_size = (txs.len() + self.workers - 1) / self.workers;
+        EXECUTOR_METRICS.prefetch_txs.inc_by(txs.len() as u64);
+
+        for chunk in txs.chunks(chunk_size) {
+            let chunk = chunk.to_vec();
+            let base = self.base.clone_for_reads();
+            let overlay = overlay.clone();
+            let l1_batch_env = self.l1_batch_env.clone();
+            let system_env = self.system_env.clone();
+            let values_sender = self.values_sender.clone();
+            self.running_threads.fetch_add(1, Ordering::SeqCst);
+            let guard = RunningThreadGuard(self.running_threads.clone());
+
+            let spawn_result = thread::Builder::new()
+                .name("storage-prefetcher".to_owned())
+                .spawn(move || {
+                    let _guard = guard;
+                    let started_at = Instant::now();
+                    let values =
+                        Self::prefetch_chunk(&chunk, base, &overlay, &l1_batch_env, &system_env);
+                    EXECUTOR_METRICS
+                        .prefetch_latency
+                        .observe(started_at.elapsed());
+                    // The receiver may be dropped if the batch is already finished.
+                    values_sender.send(values).ok();
+                });
+            if let Err(err) = spawn_result {
+                // The guard is dropped together with the closure, so the running thread counter is reset.
+                tracing::warn!("Failed spawning storage prefetcher thread: {err}");
+            }
+        }
+    }
+
+    /// Returns storage values prefetched since the previous call. Never blocks.
+    pub fn take_prefetched_values(&self) -> HashMap<StorageKey, StorageValue> {
+        let mut values = HashMap::new();
+        for chunk_values in self.values_receiver.try_iter() {
+            values.extend(chunk_values);
+        }
+        EXECUTOR_METRICS
+            .prefetched_storage_values
+            .inc_by(values.len() as u64);
+        values
+    }
+
+    fn prefetch_chunk(
+        txs: &[Transaction],
+        base: RocksdbStorage,
+        overlay: &Arc<HashMap<StorageKey, StorageValue>>,
+        l1_batch_env: &L1BatchEnv,
+        system_env: &SystemEnv,
+    ) -> HashMap<StorageKey, StorageValue> {
+        let base_reads = Rc::<RefCell<HashMap<_, _>>>::default();
+        for tx in txs {
+            // Each transaction is executed in a fresh VM with a fresh storage view, so that all storage reads
+            // are recorded; the outcome of the execution is irrelevant.
+            let storage = PrefetchingStorage {
+                base: base.clone_for_reads(),
+                overlay: overlay.clone(),
+                base_reads: base_reads.clone(),
+            };
+            let storage = StorageView::new(storage).to_rc_ptr();
+            let mut vm: VmInstance<_, HistoryDisabled> =
+                VmInstance::new(l1_batch_env.clone(), system_env.clone(), storage);
+            vm.execute_transaction_with_bytecode_compression(tx.clone(), true);
+        }
+        base_reads.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_state::InMemoryStorage;
+    use zksync_types::{AccountTreeId, Address};
+    use zksync_utils::bytecode::hash_bytecode;
+
+    use super::*;
+
+    fn key(byte: u8) -> StorageKey {
+        StorageKey::new(AccountTreeId::new(Address::repeat_byte(byte)), H256::zero())
+    }
+
+    #[test]
+    fn recording_base_reads() {
+        let mut base = InMemoryStorage::with_system_contracts(hash_bytecode);
+        base.set_value(key(1), H256::repeat_byte(1));
+        base.set_value(key(2), H256::repeat_byte(2));
+        let overlay = HashMap::from([(key(2), H256::repeat_byte(0x22))]);
+        let base_reads = Rc::<RefCell<HashMap<_, _>>>::default();
+        let mut storage = PrefetchingStorage {
+            base,
+            overlay: Arc::new(overlay),
+            base_reads: base_reads.clone(),
+        };
+
+        assert_eq!(storage.read_value(&key(1)), H256::repeat_byte(1));
+        assert_eq!(storage.read_value(&key(2)), H256::repeat_byte(0x22));
+        assert_eq!(storage.read_value(&key(3)), H256::zero());
+        // Values from the overlay are not recorded since they are not stored in the base storage.
+        let expected_reads =
+            HashMap::from([(key(1), H256::repeat_byte(1)), (key(3), H256::zero())]);
+        assert_eq!(*base_reads.borrow(), expected_reads);
+    }
+
+    #[test]
+    fn batch_global_keys() {
+        assert!(is_batch_global_key(&storage_key_for_eth_balance(
+            &BOOTLOADER_ADDRESS
+        )));
+        assert!(is_batch_global_key(&StorageKey::new(
+            AccountTreeId::new(SYSTEM_CONTEXT_ADDRESS),
+            H256::repeat_byte(1)
+        )));
+        assert!(!is_batch_global_key(&key(1)));
+    }
+}
@@ -0,0 +1,606 @@
+//! Shadow execution of L1 batches on the latest VM version.
+//!
+//! The main VM of the batch executor corresponds to the protocol version of the L1 batch. To vet a new VM version
+//! before a protocol upgrade switches to it, the batch executor may hand over each complete miniblock
+//! to [`ShadowExecutor`]. The shadow executor re-executes transactions of the miniblock in a separate thread
+//! on `vm_latest` and compares the outcome with the one produced by the main VM. Divergences in execution results,
+//! gas, events and storage writes are logged and reported as metrics; the shadow VM never influences the state
+//! produced by the state keeper.
+//!
+//! The shadow VM never blocks the main VM, including when the L1 batch is sealed. Once the main VM has finished
+//! the batch, the state of storage slots touched by the batch is pinned for the shadow VM, so that the RocksDB cache
+//! can be updated for the next batch while the shadow VM catches up. The shadow VM is allowed to lag behind
+//! by at most one L1 batch; if it's still running when the next batch is started, the next batch is not
+//! shadow-executed.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::Instant,
+};
+
+use multivm::{
+    interface::{
+        FinishedL1Batch, L1BatchEnv, L2BlockEnv, SystemEnv, VmExecutionMode,
+        VmExecutionResultAndLogs, VmInterface, VmInterfaceHistoryEnabled,
+    },
+    vm_latest::{HistoryEnabled, Vm},
+};
+use once_cell::sync::OnceCell;
+use zksync_contracts::BaseSystemContracts;
+use zksync_state::{ReadStorage, RocksdbStorage, StorageView};
+use zksync_types::{
+    storage_writes_deduplicator::StorageWritesDeduplicator, witness_block_state::WitnessBlockState,
+    L1BatchNumber, ProtocolVersionId, StorageKey, StorageLogQuery, StorageValue, Transaction, H256,
+    U256,
+};
+
+use crate::state_keeper::metrics::{ShadowDivergenceKind, EXECUTOR_METRICS};
+
+/// Transaction included into a miniblock together with the result of its execution by the main VM.
+pub(super) type ExecutedTx = (Transaction, VmExecutionResultAndLogs);
+
+/// Final values of modified storage slots (i.e., the state diff).
+type StorageWrites = BTreeMap<StorageKey, U256>;
+
+fn storage_writes<'a>(logs: impl IntoIterator<Item = &'a StorageLogQuery>) -> StorageWrites {
+    let mut deduplicator = StorageWritesDeduplicator::new();
+    deduplicator.apply(logs);
+    deduplicator
+        .into_modified_key_values()
+        .into_iter()
+        .map(|(key, slot)| (key, slot.value))
+        .collect()
+}
+
+/// Single divergence between the main and the shadow VMs.
+#[derive(Debug, Clone, PartialEq)]
+struct Divergence {
+    kind: ShadowDivergenceKind,
+    details: String,
+}
+
+impl Divergence {
+    fn new(kind: ShadowDivergenceKind, main: impl fmt::Debug, shadow: impl fmt::Debug) -> Self {
+        Self {
+            kind,
+            details: format!("main VM: {main:?}, shadow VM: {shadow:?}"),
+        }
+    }
+}
+
+/// Compares results of executing the same transaction (or the block tip) by the main and the shadow VMs.
+fn compare_results(
+    main: &VmExecutionResultAndLogs,
+    shadow: &VmExecutionResultAndLogs,
+) -> Vec<Divergence> {
+    let mut divergences = vec![];
+    if main.result != shadow.result {
+        divergences.push(Divergence::new(
+            ShadowDivergenceKind::Result,
+            &main.result,
+            &shadow.result,
+        ));
+    }
+
+    let main_gas = (main.statistics.gas_used, main.refunds.gas_refunded);
+    let shadow_gas = (shadow.statistics.gas_used, shadow.refunds.gas_refunded);
+    if main_gas != shadow_gas {
+        divergences.push(Divergence::new(
+            ShadowDivergenceKind::Gas,
+            format_args!("used {}, refunded {}", main_gas.0, main_gas.1),
+            format_args!("used {}, refunded {}", shadow_gas.0, shadow_gas.1),
+        ));
+    }
+
+    if main.logs.events != shadow.logs.events {
+        divergences.push(Divergence::new(
+            ShadowDivergenceKind::Events,
+            &main.logs.events,
+            &shadow.logs.events,
+        ));
+    }
+
+    let main_writes = storage_writes(&main.logs.storage_logs);
+    let shadow_writes = storage_writes(&shadow.logs.storage_logs);
+    if main_writes != shadow_writes {
+        divergences.push(Divergence::new(
+            ShadowDivergenceKind::StorageWrites,
+            main_writes,
+            shadow_writes,
+        ));
+    }
+    divergences
+}
+
+/// Storage of the shadow VM: the RocksDB cache with the state before the L1 batch. Once the main VM has finished
+/// the batch, the cache may be updated with the batch changes while the shadow VM is still running; hence,
+/// the state of slots touched by the batch is pinned to the one before the batch.
+#[derive(Debug)]
+struct ShadowStorage<S> {
+    base: S,
+    pinned_state: Arc<OnceCell<WitnessBlockState>>,
+}
+
+impl<S: ReadStorage> ReadStorage for ShadowStorage<S> {
+    fn read_value(&mut self, key: &StorageKey) -> StorageValue {
+        let pinned_value = self
+            .pinned_state
+            .get()
+            .and_then(|state| state.read_storage_key.get(key));
+        match pinned_value {
+            Some(value) => *value,
+            None => self.base.read_value(key),
+        }
+    }
+
+    fn is_write_initial(&mut self, key: &StorageKey) -> bool {
+        let pinned_value = self
+            .pinned_state
+            .get()
+            .and_then(|state| state.is_write_initial.get(key));
+        match pinned_value {
+            Some(is_initial) => *is_initial,
+            None => self.base.is_write_initial(key),
+        }
+    }
+
+    fn load_factory_dep(&mut self, hash: H256) -> Option<Vec<u8>> {
+        self.base.load_factory_dep(hash)
+    }
+
+    fn get_enumeration_index(&mut self, key: &StorageKey) -> Option<u64> {
+        let is_pinned_initial_write = self
+            .pinned_state
+            .get()
+            .and_then(|state| state.is_write_initial.get(key))
+            .copied();
+        if is_pinned_initial_write == Some(true) {
+            // The slot may have been enumerated after the batch was finished.
+            return None;
+        }
+        self.base.get_enumeration_index(key)
+    }
+}
+
+#[derive(Debug)]
+enum ShadowCommand {
+    ExecuteMiniblock {
+        txs: Vec<ExecutedTx>,
+        next_l2_block: L2BlockEnv,
+    },
+    FinishBatch {
+        txs: Vec<ExecutedTx>,
+        block_tip_result: Box<VmExecutionResultAndLogs>,
+        state_diff: StorageWrites,
+        finished_at: Instant,
+    },
+}
+
+/// Shadow execution was cancelled because the shadow VM has fallen behind the main VM.
+#[derive(Debug)]
+struct Cancelled;
+
+/// Marks the shadow VM thread as finished on drop, including if the thread panics.
+struct FinishedGuard(Arc<AtomicBool>);
+
+impl Drop for FinishedGuard {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Status of the shadow VM thread for a certain L1 batch. Used to coordinate shadow execution
+/// across L1 batches.
+#[derive(Debug, Clone)]
+pub(super) struct ShadowStatus {
+    l1_batch_number: L1BatchNumber,
+    finished: Arc<AtomicBool>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl ShadowStatus {
+    /// Checks whether the shadow VM thread has terminated.
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::SeqCst)
+    }
+
+    /// Cancels shadow execution if the shadow VM would lag behind by more than one L1 batch once `next_l1_batch`
+    /// is started. The shadow VM only pins the state touched by its own batch, so this must be called
+    /// before the RocksDB cache is updated for `next_l1_batch`.
+    pub fn cancel_if_lagging(&self, next_l1_batch: L1BatchNumber) {
+        if self.is_finished() || next_l1_batch <= self.l1_batch_number + 1 {
+            return;
+        }
+        if !self.cancelled.swap(true, Ordering::SeqCst) {
+            tracing::warn!(
+                "Cancelling shadow execution of L1 batch #{} since it lags behind the main VM (starting L1 batch #{next_l1_batch})",
+                self.l1_batch_number
+            );
+        }
+    }
+}
+
+/// Handle for the shadow VM running in a dedicated thread. Commands are processed asynchronously
+/// w.r.t. the main VM; the main VM never waits for the shadow VM.
+#[derive(Debug)]
+pub(super) struct ShadowExecutor {
+    commands: mpsc::Sender<ShadowCommand>,
+    pinned_state: Arc<OnceCell<WitnessBlockState>>,
+    status: ShadowStatus,
+}
+
+impl ShadowExecutor {
+    /// Starts the shadow VM for the L1 batch. `storage` must correspond to the state before the batch.
+    /// `system_env` is adjusted to use the latest protocol version and `base_system_contracts`, which must
+    /// correspond to `vm_latest`.
+    pub fn new(
+        storage: RocksdbStorage,
+        l1_batch_env: L1BatchEnv,
+        mut system_env: SystemEnv,
+        base_system_contracts: BaseSystemContracts,
+    ) -> Self {
+        system_env.version = ProtocolVersionId::latest();
+        system_env.base_system_smart_contracts = base_system_contracts;
+
+        let status = ShadowStatus {
+            l1_batch_number: l1_batch_env.number,
+            finished: Arc::default(),
+            cancelled: Arc::default(),
+        };
+        let pinned_state = Arc::<OnceCell<_>>::default();
+        let storage = ShadowStorage {
+            base: storage,
+            pinned_state: pinned_state.clone(),
+        };
+        let (commands, commands_receiver) = mpsc::channel();
+        let finished_guard = FinishedGuard(status.finished.clone());
+        let cancelled = status.cancelled.clone();
+        thread::Builder::new()
+            .name("shadow-vm".to_owned())
+            .spawn(move || {
+                let _guard = finished_guard;
+                let runner = ShadowRunner::new(storage, l1_batch_env, system_env, cancelled);
+                runner.run(commands_receiver);
+            })
+            .expect("failed spawning shadow VM thread");
+        Self {
+            commands,
+            pinned_state,
+            status,
+        }
+    }
+
+    /// Returns the status of the shadow VM thread.
+    pub fn status(&self) -> ShadowStatus {
+        self.status.clone()
+    }
+
+    /// Enqueues the miniblock with the specified transactions for shadow execution.
+    pub fn execute_miniblock(&self, txs: Vec<ExecutedTx>, next_l2_block: L2BlockEnv) {
+        self.send(ShadowCommand::ExecuteMiniblock { txs, next_l2_block });
+    }
+
+    /// Enqueues the last miniblock with the specified transactions and finishes the L1 batch. Returns immediately;
+    /// the outcome is reported via logs and metrics. `state_before_batch` must contain the state of all slots
+    /// touched by the main VM; it is pinned for the shadow VM, so that the state before the batch can be modified
+    /// during shadow execution.
+    pub fn finish_batch(
+        self,
+        txs: Vec<ExecutedTx>,
+        finished_batch: &FinishedL1Batch,
+        state_before_batch: WitnessBlockState,
+    ) {
+        // Pinning the state must happen before the batch is sealed, i.e., before the RocksDB cache can be updated.
+        self.pinned_state.set(state_before_batch).ok();
+        self.send(ShadowCommand::FinishBatch {
+            txs,
+            block_tip_result: Box::new(finished_batch.block_tip_execution_result.clone()),
+            state_diff: storage_writes(&finished_batch.final_execution_state.storage_log_queries),
+            finished_at: Instant::now(),
+        });
+    }
+
+    fn send(&self, command: ShadowCommand) {
+        // The shadow VM thread can only terminate prematurely if it has panicked or was cancelled;
+        // both cases are logged by the thread.
+        self.commands.send(command).ok();
+    }
+}
+
+/// Shadow VM together with the information necessary to report divergences.
+struct ShadowRunner {
+    vm: Vm<StorageView<ShadowStorage<RocksdbStorage>>, HistoryEnabled>,
+    l1_batch_number: u32,
+    miniblock_number: u32,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl fmt::Debug for ShadowRunner {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("ShadowRunner")
+            .field("l1_batch_number", &self.l1_batch_number)
+            .field("miniblock_number", &self.miniblock_number)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ShadowRunner {
+    fn new(
+        storage: ShadowStorage<RocksdbStorage>,
+        l1_batch_env: L1BatchEnv,
+        system_env: SystemEnv,
+        cancelled: Arc<AtomicBool>,
+    ) -> Self {
+        let l1_batch_number = l1_batch_env.number.0;
+        let miniblock_number = l1_batch_env.first_l2_block.number;
+        let storage = StorageView::new(storage).to_rc_ptr();
+        Self {
+            vm: Vm::new(l1_batch_env, system_env, storage),
+            l1_batch_number,
+            miniblock_number,
+            cancelled,
+        }
+    }
+
+    fn run(mut self, commands: mpsc::Receiver<ShadowCommand>) {
+        while let Ok(command) = commands.recv() {
+            match command {
+                ShadowCommand::ExecuteMiniblock { txs, next_l2_block } => {
+                    if self.execute_miniblock(txs).is_err() {
+                        self.report_cancellation();
+                        return;
+                    }
+                    self.vm.start_new_l2_block(next_l2_block);
+                    self.miniblock_number = next_l2_block.number;
+                }
+                ShadowCommand::FinishBatch {
+                    txs,
+                    block_tip_result,
+                    state_diff,
+                    finished_at,
+                } => {
+                    let result = self.execute_miniblock(txs).and_then(|()| {
+                        self.finish_batch(&block_tip_result, &state_diff, finished_at)
+                    });
+                    if result.is_err() {
+                        self.report_cancellation();
+                    }
+                    return;
+                }
+            }
+        }
+        // The batch executor can exit mid-batch because of the stop signal.
+        tracing::info!(
+            "Shadow VM for L1 batch #{} exited with an unfinished batch",
+            self.l1_batch_number
+        );
+    }
+
+    /// Checks whether shadow execution was cancelled. Must be called after each VM execution and before reporting
+    /// its results, since the execution may have read the state modified after the L1 batch.
+    fn check_cancelled(&self) -> Result<(), Cancelled> {
+        if self.cancelled.load(Ordering::SeqCst) {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn execute_miniblock(&mut self, txs: Vec<ExecutedTx>) -> Result<(), Cancelled> {
+        for (tx, main_result) in txs {
+            let tx_hash = tx.hash();
+            let shadow_result = self.execute_tx(tx);
+            self.check_cancelled()?;
+            let divergences = compare_results(&main_result, &shadow_result);
+            self.report(&format!("tx {tx_hash:?}"), &divergences);
+        }
+        EXECUTOR_METRICS.shadow_executed_miniblocks.inc();
+        Ok(())
+    }
+
+    /// Executes a transaction in the same way as the main VM, i.e. falling back to execution without
+    /// bytecode compression if compressed bytecodes cannot be published.
+    fn execute_tx(&mut self, tx: Transaction) -> VmExecutionResultAndLogs {
+        self.vm.make_snapshot();
+        if let (Ok(()), result) = self
+            .vm
+            .execute_transaction_with_bytecode_compression(tx.clone(), true)
+        {
+            self.vm.pop_snapshot_no_rollback();
+            return result;
+        }
+        self.vm.rollback_to_the_latest_snapshot();
+        self.vm
+            .execute_transaction_with_bytecode_compression(tx, false)
+            .1
+    }
+
+    fn finish_batch(
+        &mut self,
+        main_block_tip_result: &VmExecutionResultAndLogs,
+        main_state_diff: &StorageWrites,
+        main_finished_at: Instant,
+    ) -> Result<(), Cancelled> {
+        let block_tip_result = self.vm.execute(VmExecutionMode::Bootloader);
+        self.check_cancelled()?;
+        let mut divergences = compare_results(main_block_tip_result, &block_tip_result);
+        let state_diff = storage_writes(&self.vm.get_current_execution_state().storage_log_queries);
+        if *main_state_diff != state_diff {
+            divergences.push(Divergence::new(
+                ShadowDivergenceKind::StateDiff,
+                main_state_diff,
+                state_diff,
+            ));
+        }
+        self.report("block tip", &divergences);
+
+        let lag = main_finished_at.elapsed();
+        EXECUTOR_METRICS.shadow_batch_lag.observe(lag);
+        tracing::info!(
+            "Finished shadow execution of L1 batch #{} ({lag:?} after the main VM)",
+            self.l1_batch_number
+        );
+        Ok(())
+    }
+
+    fn report_cancellation(&self) {
+        EXECUTOR_METRICS.shadow_cancelled_batches.inc();
+        tracing::warn!(
+            "Shadow execution of L1 batch #{} was cancelled at miniblock #{}",
+            self.l1_batch_number,
+            self.miniblock_number
+        );
+    }
+
+    fn report(&self, subject: &str, divergences: &[Divergence]) {
+        for divergence in divergences {
+            EXECUTOR_METRICS.shadow_divergences[&divergence.kind].inc();
+            tracing::error!(
+                "Shadow VM diverged from the main VM ({:?}) for {subject} in miniblock #{}, L1 batch #{}: {}",
+                divergence.kind,
+                self.miniblock_number,
+                self.l1_batch_number,
+                divergence.details
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use multivm::interface::{ExecutionResult, Halt};
+    use zksync_state::InMemoryStorage;
+    use zksync_types::{AccountTreeId, LogQuery, StorageLogQueryType, Timestamp, H160, H256};
+
+    use super::*;
+
+    fn storage_write(key: u64, value: u64) -> StorageLogQuery {
+        StorageLogQuery {
+            log_query: LogQuery {
+                timestamp: Timestamp(0),
+                tx_number_in_block: 0,
+                aux_byte: 0,
+                shard_id: 0,
+                address: H160::repeat_byte(1),
+                key: key.into(),
+                read_value: U256::zero(),
+                written_value: value.into(),
+                rw_flag: true,
+                rollback: false,
+                is_service: false,
+            },
+            log_type: StorageLogQueryType::RepeatedWrite,
+        }
+    }
+
+    fn execution_result(writes: Vec<StorageLogQuery>) -> VmExecutionResultAndLogs {
+        let mut result = VmExecutionResultAndLogs {
+            result: ExecutionResult::Success { output: vec![] },
+            logs: Default::default(),
+            statistics: Default::default(),
+            refunds: Default::default(),
+        };
+        result.statistics.gas_used = 100_000;
+        result.logs.storage_logs = writes;
+        result
+    }
+
+    #[test]
+    fn deduplicating_storage_writes() {
+        let rolled_back_write = StorageLogQuery {
+            log_query: LogQuery {
+                rollback: true,
+                ..storage_write(2, 3).log_query
+            },
+            ..storage_write(2, 3)
+        };
+        let logs = [storage_write(1, 1), storage_write(1, 2), rolled_back_write];
+        let writes = storage_writes(&logs);
+        let key = StorageKey::new(
+            AccountTreeId::new(H160::repeat_byte(1)),
+            H256::from_low_u64_be(1),
+        );
+        let expected = BTreeMap::from([(key, U256::from(2))]);
+        assert_eq!(writes, expected);
+    }
+
+    #[test]
+    fn comparing_results() {
+        let main = execution_result(vec![storage_write(1, 1), storage_write(2, 2)]);
+        assert_eq!(compare_results(&main, &main.clone()), []);
+
+        // Equivalent storage writes.
+        let shadow = execution_result(vec![
+            storage_write(2, 1),
+            storage_write(2, 2),
+            storage_write(1, 1),
+        ]);
+        assert_eq!(compare_results(&main, &shadow), []);
+
+        let mut shadow = execution_result(vec![storage_write(1, 1)]);
+        shadow.statistics.gas_used += 1;
+        let divergences = compare_results(&main, &shadow);
+        let kinds: Vec<_> = divergences.iter().map(|div| div.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                ShadowDivergenceKind::Gas,
+                ShadowDivergenceKind::StorageWrites
+            ]
+        );
+
+        let mut shadow = main.clone();
+        shadow.result = ExecutionResult::Halt {
+            reason: Halt::BootloaderOutOfGas,
+        };
+        let divergences = compare_results(&main, &shadow);
+        assert_eq!(divergences.len(), 1);
+        assert_eq!(divergences[0].kind, ShadowDivergenceKind::Result);
+    }
+
+    #[test]
+    fn pinning_state_before_batch() {
+        let key = |byte: u64| {
+            StorageKey::new(
+                AccountTreeId::new(H160::repeat_byte(1)),
+                H256::from_low_u64_be(byte),
+            )
+        };
+        let mut base = InMemoryStorage::default();
+        base.set_value(key(1), H256::repeat_byte(1));
+        let pinned_state = Arc::<OnceCell<WitnessBlockState>>::default();
+        let mut storage = ShadowStorage {
+            base,
+            pinned_state: pinned_state.clone(),
+        };
+        assert_eq!(storage.read_value(&key(1)), H256::repeat_byte(1));
+        assert!(!storage.is_write_initial(&key(1)));
+
+        // Emulate updating the base storage after the batch.
+        storage.base.set_value(key(1), H256::repeat_byte(2));
+        storage.base.set_value(key(2), H256::repeat_byte(2));
+        pinned_state
+            .set(WitnessBlockState {
+                read_storage_key: HashMap::from([
+                    (key(1), H256::repeat_byte(1)),
+                    (key(2), H256::zero()),
+                ]),
+                is_write_initial: HashMap::from([(key(2), true)]),
+            })
+            .unwrap();
+
+        assert_eq!(storage.read_value(&key(1)), H256::repeat_byte(1));
+        assert_eq!(storage.read_value(&key(2)), H256::zero());
+        assert!(storage.is_write_initial(&key(2)));
+        assert_eq!(storage.get_enumeration_index(&key(2)), None);
+    }
+}
@@ -1,7 +1,10 @@
 use assert_matches::assert_matches;
 use zksync_dal::ConnectionPool;
 use zksync_test_account::Account;
-use zksync_types::PriorityOpId;
+use zksync_types::{
+    l2::conditions::{TransactionConditions, TransactionConditionsError},
+    ExecuteTransactionCommon, PriorityOpId, Transaction, U256,
+};
 
 use self::tester::Tester;
 use super::TxExecutionResult;
@@ -127,6 +130,63 @@ async fn rollback() {
     executor.finish_batch().await;
 }
 
+/// Checks that prefetching storage for transactions doesn't influence their subsequent execution.
+#[tokio::test]
+async fn prefetching_storage() {
+    let connection_pool = ConnectionPool::test_pool().await;
+    let mut alice = Account::random();
+    let mut bob = Account::random();
+
+    let mut tester = Tester::new(connection_pool);
+    tester.set_config(TestConfig {
+        storage_prefetch_workers: Some(2),
+        ..TestConfig::new()
+    });
+    tester.genesis().await;
+    tester.fund(&[alice.address(), bob.address()]).await;
+    let executor = tester.create_batch_executor().await;
+
+    let res = executor.execute_tx(alice.execute()).await;
+    assert_executed(&res);
+
+    // The second Alice's transaction depends on the first one.
+    let txs = vec![alice.execute(), bob.execute(), alice.execute()];
+    executor.prefetch_storage(txs.clone()).await;
+    for tx in txs {
+        let res = executor.execute_tx(tx).await;
+        assert_executed(&res);
+    }
+    executor.finish_batch().await;
+}
+
+/// Checks that shadow execution (including rolled back transactions) doesn't influence the main VM.
+#[tokio::test]
+async fn shadow_execution() {
+    let connection_pool = ConnectionPool::test_pool().await;
+    let mut alice = Account::random();
+
+    let mut tester = Tester::new(connection_pool);
+    tester.set_config(TestConfig {
+        shadow_execution: true,
+        ..TestConfig::new()
+    });
+    tester.genesis().await;
+    tester.fund(&[alice.address()]).await;
+    let executor = tester.create_batch_executor().await;
+
+    let res = executor.execute_tx(alice.execute()).await;
+    assert_executed(&res);
+    let tx = alice.execute();
+    let res = executor.execute_tx(tx.clone()).await;
+    assert_executed(&res);
+    executor.rollback_last_tx().await;
+    let res = executor.execute_tx(tx).await;
+    assert_executed(&res);
+    let res = executor.execute_tx(alice.l1_execute(PriorityOpId(1))).await;
+    assert_executed(&res);
+    executor.finish_batch().await;
+}
+
 /// Checks that incorrect transactions are marked as rejected.
 #[tokio::test]
 async fn reject_tx() {
@@ -262,6 +322,59 @@ async fn execute_reverted_tx() {
     executor.finish_batch().await;
 }
 
+/// Sets inclusion conditions for an L2 transaction.
+fn with_conditions(mut tx: Transaction, conditions: TransactionConditions) -> Transaction {
+    let ExecuteTransactionCommon::L2(common_data) = &mut tx.common_data else {
+        panic!("Conditions can only be set for L2 transactions");
+    };
+    common_data.conditions = Some(Box::new(conditions));
+    tx
+}
+
+/// Checks that conditions of L2 txs are checked against the current state of the batch.
+#[tokio::test]
+async fn checking_tx_conditions() {
+    let connection_pool = ConnectionPool::test_pool().await;
+    let mut alice = Account::random();
+
+    let tester = Tester::new(connection_pool);
+    tester.genesis().await;
+    tester.fund(&[alice.address()]).await;
+    let executor = tester.create_batch_executor().await;
+
+    let initial_balance = U256::from(10_u32).pow(32.into());
+    let conditions = TransactionConditions {
+        known_balances: [(alice.address(), initial_balance)].into(),
+        ..TransactionConditions::default()
+    };
+    let tx = with_conditions(alice.execute(), conditions.clone());
+    let res = executor.execute_tx(tx).await;
+    assert_executed(&res);
+
+    // The balance has changed because of the fee paid for the first tx.
+    let tx = with_conditions(alice.execute(), conditions);
+    let res = executor.execute_tx(tx).await;
+    assert_matches!(
+        res,
+        TxExecutionResult::ConditionsNotMet(TransactionConditionsError::Balance(address))
+            if address == alice.address()
+    );
+    executor.rollback_last_tx().await;
+
+    let conditions = TransactionConditions {
+        block_number_max: Some(0.into()),
+        ..TransactionConditions::default()
+    };
+    let tx = with_conditions(alice.execute(), conditions);
+    let res = executor.execute_tx(tx).await;
+    assert_matches!(
+        res,
+        TxExecutionResult::ConditionsNotMet(TransactionConditionsError::BlockNumber(_))
+    );
+    executor.rollback_last_tx().await;
+    executor.finish_batch().await;
+}
+
 /// Runs the batch executor through a semi-realistic basic scenario:
 /// a batch with different operations, both successful and not.
 #[tokio::test]
@@ -7,7 +7,9 @@ use multivm::{
 };
 use tempfile::TempDir;
 use zksync_config::configs::chain::StateKeeperConfig;
-use zksync_contracts::{get_loadnext_contract, test_contracts::LoadnextContractExecutionParams};
+use zksync_contracts::{
+    get_loadnext_contract, test_contracts::LoadnextContractExecutionParams, BaseSystemContracts,
+};
 use zksync_dal::ConnectionPool;
 use zksync_state::RocksdbStorage;
 use zksync_test_account::{Account, DeployContractsTx, TxType};
@@ -22,7 +24,7 @@ use zksync_utils::u256_to_h256;
 use crate::{
     genesis::create_genesis_l1_batch,
     state_keeper::{
-        batch_executor::BatchExecutorHandle,
+        batch_executor::{shadow::ShadowExecutor, BatchExecutorHandle},
         tests::{default_l1_batch_env, default_system_env, BASE_SYSTEM_CONTRACTS},
     },
 };
@@ -39,6 +41,8 @@ pub(super) struct TestConfig {
     pub(super) max_allowed_tx_gas_limit: u32,
     pub(super) validation_computational_gas_limit: u32,
     pub(super) upload_witness_inputs_to_gcs: bool,
+    pub(super) storage_prefetch_workers: Option<usize>,
+    pub(super) shadow_execution: bool,
 }
 
 impl TestConfig {
@@ -51,6 +55,8 @@ impl TestConfig {
             max_allowed_tx_gas_limit: config.max_allowed_l2_tx_gas_limit,
             validation_computational_gas_limit: config.validation_computational_gas_limit,
             upload_witness_inputs_to_gcs: false,
+            storage_prefetch_workers: None,
+            shadow_execution: false,
         }
     }
 }
@@ -103,6 +109,14 @@ impl Tester {
         secondary_storage.update_from_postgres(&mut conn).await;
         drop(conn);
 
+        let shadow_executor = self.config.shadow_execution.then(|| {
+            ShadowExecutor::new(
+                secondary_storage.clone_for_reads(),
+                l1_batch.clone(),
+                system_env.clone(),
+                BaseSystemContracts::load_from_disk(),
+            )
+        });
         // We don't use the builder because it would require us to clone the `ConnectionPool`, which is forbidden
         // for the test pool (see the doc-comment on `TestPool` for details).
         BatchExecutorHandle::new(
@@ -113,6 +127,8 @@ impl Tester {
             system_env,
             self.config.upload_witness_inputs_to_gcs,
             false,
+            self.config.storage_prefetch_workers,
+            shadow_executor,
         )
     }
 
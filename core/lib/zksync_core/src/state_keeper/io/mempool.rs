@@ -15,15 +15,15 @@ use zksync_dal::ConnectionPool;
 use zksync_mempool::L2TxFilter;
 use zksync_object_store::ObjectStore;
 use zksync_types::{
-    block::MiniblockHeader, protocol_version::ProtocolUpgradeTx,
+    block::MiniblockHeader, l2::bundle::TransactionBundle, protocol_version::ProtocolUpgradeTx,
     witness_block_state::WitnessBlockState, Address, L1BatchNumber, L2ChainId, MiniblockNumber,
     ProtocolVersionId, Transaction, U256,
 };
 // TODO (SMA-1206): use seconds instead of milliseconds.
-use zksync_utils::time::millis_since_epoch;
+use zksync_utils::time::{millis_since_epoch, seconds_since_epoch};
 
 use crate::{
-    fee_model::BatchFeeModelInputProvider,
+    fee_model::{BatchFeeModelInputProvider, DynamicL2GasPrice},
     state_keeper::{
         extractors,
         io::{
@@ -32,12 +32,53 @@ use crate::{
         },
         mempool_actor::l2_tx_filter,
         metrics::KEEPER_METRICS,
-        seal_criteria::{IoSealCriteria, TimeoutSealer},
+        seal_criteria::{
+            IoSealCriteria, ProtocolUpgradeSealer, ProverCapacitySealer, TimeoutSealer,
+        },
         updates::UpdatesManager,
         MempoolGuard,
     },
 };
 
+/// Budget for priority (L1) transactions in a single miniblock. Once the budget is exhausted, L2 transactions
+/// are preferred over priority ones until the next miniblock, so that a flood of priority transactions cannot
+/// crowd out L2 transactions. Priority transactions are still executed if there are no L2 transactions,
+/// so the budget doesn't slow down processing of the priority queue on an idle chain.
+#[derive(Debug, Default)]
+pub(super) struct PriorityTxBudget {
+    max_txs: Option<usize>,
+    max_gas: Option<U256>,
+    txs: usize,
+    gas: U256,
+}
+
+impl PriorityTxBudget {
+    pub fn new(config: &StateKeeperConfig) -> Self {
+        Self {
+            max_txs: config.max_priority_txs_per_miniblock,
+            max_gas: config.max_priority_gas_per_miniblock.map(U256::from),
+            ..Self::default()
+        }
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.max_txs.map_or(false, |max_txs| self.txs >= max_txs)
+            || self.max_gas.map_or(false, |max_gas| self.gas >= max_gas)
+    }
+
+    pub fn record(&mut self, tx: &Transaction) {
+        if tx.is_l1() {
+            self.txs += 1;
+            self.gas = self.gas.saturating_add(tx.gas_limit());
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.txs = 0;
+        self.gas = U256::zero();
+    }
+}
+
 /// Mempool-based IO for the state keeper.
 /// Receives transactions from the database through the mempool filtering logic.
 /// Decides which batch parameters should be used for the new batch.
@@ -48,6 +89,10 @@ pub(crate) struct MempoolIO {
     pool: ConnectionPool,
     object_store: Arc<dyn ObjectStore>,
     timeout_sealer: TimeoutSealer,
+    prover_capacity_sealer: Option<ProverCapacitySealer>,
+    protocol_upgrade_sealer: ProtocolUpgradeSealer,
+    priority_tx_budget: PriorityTxBudget,
+    dynamic_l2_gas_price: Option<Arc<DynamicL2GasPrice>>,
     filter: L2TxFilter,
     current_miniblock_number: MiniblockNumber,
     miniblock_sealer_handle: MiniblockSealerHandle,
@@ -62,12 +107,24 @@ pub(crate) struct MempoolIO {
 
     virtual_blocks_interval: u32,
     virtual_blocks_per_miniblock: u32,
+    fixed_miniblock_interval_sec: Option<u64>,
+    fixed_interval_empty_miniblocks: bool,
+    empty_miniblock_interval_sec: Option<u64>,
 }
 
 impl IoSealCriteria for MempoolIO {
     fn should_seal_l1_batch_unconditionally(&mut self, manager: &UpdatesManager) -> bool {
         self.timeout_sealer
             .should_seal_l1_batch_unconditionally(manager)
+            || self
+                .prover_capacity_sealer
+                .as_mut()
+                .map_or(false, |sealer| {
+                    sealer.should_seal_l1_batch_unconditionally(manager)
+                })
+            || self
+                .protocol_upgrade_sealer
+                .should_seal_l1_batch_unconditionally(manager)
     }
 
     fn should_seal_miniblock(&mut self, manager: &UpdatesManager) -> bool {
@@ -104,6 +161,9 @@ impl StateKeeperIO for MempoolIO {
             self.chain_id,
         )
         .await?;
+        self.protocol_upgrade_sealer
+            .start_batch(&mut storage, system_env.version, l1_batch_env.timestamp)
+            .await;
         // Initialize the filter for the transactions that come after the pending batch.
         // We use values from the pending block to match the filter with one used before the restart.
         let (base_fee, gas_per_pubdata) =
@@ -140,10 +200,10 @@ impl StateKeeperIO for MempoolIO {
         for _ in 0..poll_iters(self.delay_interval, max_wait) {
             // We cannot create two L1 batches or miniblocks with the same timestamp (forbidden by the bootloader).
             // Hence, we wait until the current timestamp is larger than the timestamp of the previous miniblock.
-            // We can use `timeout_at` since `sleep_past` is cancel-safe; it only uses `sleep()` async calls.
+            // We can use `timeout_at` since `next_timestamp` is cancel-safe; it only uses `sleep()` async calls.
             let current_timestamp = tokio::time::timeout_at(
                 deadline.into(),
-                sleep_past(prev_miniblock_timestamp, self.current_miniblock_number),
+                self.next_timestamp(prev_miniblock_timestamp),
             );
             let current_timestamp = current_timestamp.await.ok()?;
 
@@ -164,11 +224,15 @@ impl StateKeeperIO for MempoolIO {
                 self.batch_fee_input_provider.as_ref(),
                 protocol_version.into(),
             );
-            // We only need to get the root hash when we're certain that we have a new transaction.
-            if !self.mempool.has_next(&self.filter) {
+            // We only need to get the root hash when we're certain that we have a new transaction
+            // (unless empty miniblocks are produced when the mempool is idle).
+            if !self.produces_empty_miniblocks() && !self.mempool.has_next(&self.filter) {
                 tokio::time::sleep(self.delay_interval).await;
                 continue;
             }
+            self.protocol_upgrade_sealer
+                .start_batch(&mut storage, protocol_version, current_timestamp)
+                .await;
 
             return Some(l1_batch_params(
                 self.current_l1_batch_number,
@@ -196,14 +260,19 @@ impl StateKeeperIO for MempoolIO {
     ) -> Option<MiniblockParams> {
         // We must provide different timestamps for each miniblock.
         // If miniblock sealing interval is greater than 1 second then `sleep_past` won't actually sleep.
-        let timestamp = tokio::time::timeout(
-            max_wait,
-            sleep_past(prev_miniblock_timestamp, self.current_miniblock_number),
-        )
-        .await
-        .ok()?;
+        let timestamp =
+            tokio::time::timeout(max_wait, self.next_timestamp(prev_miniblock_timestamp))
+                .await
+                .ok()?;
 
         let virtual_blocks = self.get_virtual_blocks_count(false, self.current_miniblock_number.0);
+        // Upgrades may be scheduled while the batch is open.
+        let mut storage = self
+            .pool
+            .access_storage_tagged("state_keeper")
+            .await
+            .unwrap();
+        self.protocol_upgrade_sealer.refresh(&mut storage).await;
 
         Some(MiniblockParams {
             timestamp,
@@ -214,7 +283,7 @@ impl StateKeeperIO for MempoolIO {
     async fn wait_for_next_tx(&mut self, max_wait: Duration) -> Option<Transaction> {
         for _ in 0..poll_iters(self.delay_interval, max_wait) {
             let get_latency = KEEPER_METRICS.get_tx_from_mempool.start();
-            let res = self.mempool.next_transaction(&self.filter);
+            let res = self.next_transaction();
             get_latency.observe();
             if let Some(res) = res {
                 return Some(res);
@@ -226,6 +295,14 @@ impl StateKeeperIO for MempoolIO {
         None
     }
 
+    fn try_next_tx(&mut self) -> Option<Transaction> {
+        self.next_transaction()
+    }
+
+    fn mark_tx_included(&mut self, tx: &Transaction) {
+        self.priority_tx_budget.record(tx);
+    }
+
     async fn rollback(&mut self, tx: Transaction) {
         // Reset nonces in the mempool.
         self.mempool.rollback(&tx);
@@ -261,15 +338,46 @@ impl StateKeeperIO for MempoolIO {
             .await;
     }
 
+    fn next_bundle(&mut self) -> Option<TransactionBundle> {
+        self.mempool.next_bundle(&self.filter)
+    }
+
+    async fn rollback_bundle(&mut self, bundle: TransactionBundle) {
+        self.mempool.return_bundle(bundle);
+    }
+
+    async fn reject_bundle(&mut self, bundle: &TransactionBundle, error: &str) {
+        self.mempool.rollback_bundle(bundle);
+
+        let mut storage = self
+            .pool
+            .access_storage_tagged("state_keeper")
+            .await
+            .unwrap();
+        KEEPER_METRICS
+            .rejected_transactions
+            .inc_by(bundle.transactions.len() as u64);
+        tracing::warn!("bundle {:?} is rejected with error {error}", bundle.hash);
+        let error = format!("rejected: bundle {:?}: {error}", bundle.hash);
+        for tx in &bundle.transactions {
+            storage
+                .transactions_dal()
+                .mark_tx_as_rejected(tx.hash(), &error)
+                .await;
+        }
+    }
+
     async fn seal_miniblock(&mut self, updates_manager: &UpdatesManager) {
-        let command = updates_manager.seal_miniblock_command(
+        let mut command = updates_manager.seal_miniblock_command(
             self.current_l1_batch_number,
             self.current_miniblock_number,
             self.l2_erc20_bridge_addr,
             false,
         );
+        command.dynamic_l2_gas_price = self.update_dynamic_l2_gas_price(updates_manager);
         self.miniblock_sealer_handle.submit(command).await;
         self.current_miniblock_number += 1;
+        self.priority_tx_budget.reset();
     }
 
     async fn seal_l1_batch(
@@ -320,6 +428,7 @@ impl StateKeeperIO for MempoolIO {
             .await;
         self.current_miniblock_number += 1; // Due to fictive miniblock being sealed.
         self.current_l1_batch_number += 1;
+        self.priority_tx_budget.reset();
         Ok(())
     }
 
@@ -344,6 +453,34 @@ impl StateKeeperIO for MempoolIO {
     }
 }
 
+/// Returns the timestamp of the next miniblock in the fixed-interval mode. This is the earliest timestamp
+/// of form `prev_timestamp + k * interval` (`k >= 1`) such that the miniblock isn't overdue at `current_timestamp`,
+/// i.e., its interval hasn't fully elapsed yet. Thus, timestamps stay on the same grid even after downtime, but
+/// the state keeper doesn't produce a burst of miniblocks to catch up with the current time.
+pub(super) fn next_fixed_interval_timestamp(
+    prev_timestamp: u64,
+    interval: u64,
+    current_timestamp: u64,
+) -> u64 {
+    let elapsed_intervals = current_timestamp.saturating_sub(prev_timestamp) / interval;
+    prev_timestamp + elapsed_intervals.max(1) * interval
+}
+
+/// Sleeps until the current timestamp is greater or equal to the provided `timestamp`.
+async fn sleep_until(timestamp: u64) {
+    let target_millis = u128::from(timestamp) * 1_000;
+    loop {
+        let current_millis = millis_since_epoch();
+        if current_millis >= target_millis {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(
+            (target_millis - current_millis) as u64,
+        ))
+        .await;
+    }
+}
+
 /// Sleeps until the current timestamp is larger than the provided `timestamp`.
 ///
 /// Returns the current timestamp after the sleep. It is guaranteed to be larger than `timestamp`.
@@ -413,6 +550,11 @@ impl MempoolIO {
             config.virtual_blocks_per_miniblock > 0,
             "Virtual blocks per miniblock must be positive"
         );
+        assert_ne!(
+            config.fixed_miniblock_interval_sec,
+            Some(0),
+            "Fixed miniblock interval must be positive"
+        );
 
         let mut storage = pool.access_storage_tagged("state_keeper").await.unwrap();
         let last_sealed_l1_batch_header = storage
@@ -433,6 +575,14 @@ impl MempoolIO {
             object_store,
             pool,
             timeout_sealer: TimeoutSealer::new(config),
+            prover_capacity_sealer: None,
+            protocol_upgrade_sealer: ProtocolUpgradeSealer::new(
+                last_sealed_l1_batch_header
+                    .protocol_version
+                    .unwrap_or_default(),
+            ),
+            priority_tx_budget: PriorityTxBudget::new(config),
+            dynamic_l2_gas_price: None,
             filter: L2TxFilter::default(),
             // ^ Will be initialized properly on the first newly opened batch
             current_l1_batch_number: last_sealed_l1_batch_header.number + 1,
@@ -446,9 +596,84 @@ impl MempoolIO {
             chain_id,
             virtual_blocks_interval: config.virtual_blocks_interval,
             virtual_blocks_per_miniblock: config.virtual_blocks_per_miniblock,
+            fixed_miniblock_interval_sec: config.fixed_miniblock_interval_sec,
+            fixed_interval_empty_miniblocks: config.fixed_interval_empty_miniblocks,
+            empty_miniblock_interval_sec: config.empty_miniblock_interval_sec,
+        }
+    }
+
+    /// Enables sealing L1 batches based on the capacity of the proving pipeline.
+    pub(in crate::state_keeper) fn with_prover_capacity_sealer(
+        mut self,
+        sealer: ProverCapacitySealer,
+    ) -> Self {
+        self.prover_capacity_sealer = Some(sealer);
+        self
+    }
+
+    /// Enables updating the congestion-based L2 gas price after each sealed miniblock. The price must be used
+    /// by the fee input provider of this IO in order to have effect.
+    pub(in crate::state_keeper) fn with_dynamic_l2_gas_price(
+        mut self,
+        price: Arc<DynamicL2GasPrice>,
+    ) -> Self {
+        self.dynamic_l2_gas_price = Some(price);
+        self
+    }
+
+    /// Fictive miniblocks sealed together with L1 batches don't influence the price. Returns the adjusted price
+    /// to be persisted with the miniblock.
+    fn update_dynamic_l2_gas_price(&self, updates_manager: &UpdatesManager) -> Option<u64> {
+        let dynamic_price = self.dynamic_l2_gas_price.as_ref()?;
+        let gas_used = updates_manager.miniblock.gas_used();
+        let price = dynamic_price.update(gas_used);
+        KEEPER_METRICS.dynamic_l2_gas_price.set(price);
+        tracing::trace!(
+            "Updated dynamic L2 gas price to {price} after miniblock #{} that has used {gas_used} gas",
+            updates_manager.miniblock.number
+        );
+        Some(price)
+    }
+
+    /// Returns the next transaction from the mempool taking the priority transaction budget into account.
+    /// The budget is charged only once the transaction is included (see [`StateKeeperIO::mark_tx_included()`]),
+    /// so that fetched transactions that are rolled back don't affect it.
+    fn next_transaction(&mut self) -> Option<Transaction> {
+        if self.priority_tx_budget.is_exhausted() && self.mempool.has_next_l1_transaction() {
+            let l2_tx = self.mempool.next_l2_transaction(&self.filter);
+            if l2_tx.is_some() {
+                KEEPER_METRICS.deferred_priority_txs.inc();
+            }
+            l2_tx.or_else(|| self.mempool.next_transaction(&self.filter))
+        } else {
+            self.mempool.next_transaction(&self.filter)
+        }
+    }
+
+    fn produces_empty_miniblocks(&self) -> bool {
+        if self.fixed_miniblock_interval_sec.is_some() {
+            self.fixed_interval_empty_miniblocks
+        } else {
+            self.empty_miniblock_interval_sec.is_some()
         }
     }
 
+    /// Waits until the timestamp for the next miniblock (or L1 batch) can be assigned and returns it.
+    async fn next_timestamp(&self, prev_miniblock_timestamp: u64) -> u64 {
+        let Some(interval) = self.fixed_miniblock_interval_sec else {
+            return sleep_past(prev_miniblock_timestamp, self.current_miniblock_number).await;
+        };
+        let timestamp = next_fixed_interval_timestamp(
+            prev_miniblock_timestamp,
+            interval,
+            seconds_since_epoch(),
+        );
+        // Miniblocks may be sealed early (e.g., by seal criteria), so we may need to wait for the timestamp
+        // so that it's not in the future.
+        sleep_until(timestamp).await;
+        timestamp
+    }
+
     async fn load_previous_l1_batch_hash(&self) -> U256 {
         tracing::info!(
             "Getting previous L1 batch hash for L1 batch #{}",
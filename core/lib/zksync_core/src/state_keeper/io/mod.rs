@@ -8,9 +8,9 @@ use multivm::interface::{FinishedL1Batch, L1BatchEnv, SystemEnv};
 use tokio::sync::{mpsc, oneshot};
 use zksync_dal::ConnectionPool;
 use zksync_types::{
-    block::MiniblockExecutionData, protocol_version::ProtocolUpgradeTx,
-    witness_block_state::WitnessBlockState, L1BatchNumber, MiniblockNumber, ProtocolVersionId,
-    Transaction,
+    block::MiniblockExecutionData, l2::bundle::TransactionBundle,
+    protocol_version::ProtocolUpgradeTx, witness_block_state::WitnessBlockState, L1BatchNumber,
+    MiniblockNumber, ProtocolVersionId, Transaction,
 };
 
 pub(crate) use self::mempool::MempoolIO;
@@ -86,10 +86,35 @@ pub trait StateKeeperIO: 'static + Send + IoSealCriteria {
     /// Blocks for up to `max_wait` until the next transaction is available for execution.
     /// Returns `None` if no transaction became available until the timeout.
     async fn wait_for_next_tx(&mut self, max_wait: Duration) -> Option<Transaction>;
+    /// Returns the next transaction if it's immediately available for execution. Unlike [`Self::wait_for_next_tx()`],
+    /// this method doesn't block. Used to fetch transactions for storage prefetching; IO implementations
+    /// that don't support it never return transactions.
+    fn try_next_tx(&mut self) -> Option<Transaction> {
+        None
+    }
+    /// Marks the transaction as executed and included into the current miniblock. Unlike fetching a transaction,
+    /// this happens only once the transaction is known not to be rolled back.
+    fn mark_tx_included(&mut self, _tx: &Transaction) {}
     /// Marks the transaction as "not executed", so it can be retrieved from the IO again.
     async fn rollback(&mut self, tx: Transaction);
     /// Marks the transaction as "rejected", e.g. one that is not correct and can't be executed.
     async fn reject(&mut self, tx: &Transaction, error: &str);
+    /// Returns the next transaction bundle ready for execution, if any. Unlike [`Self::wait_for_next_tx()`],
+    /// this method doesn't block. IO implementations that don't support bundles never return them.
+    fn next_bundle(&mut self) -> Option<TransactionBundle> {
+        None
+    }
+    /// Marks the bundle as "not executed", so it can be retrieved from the IO again.
+    async fn rollback_bundle(&mut self, bundle: TransactionBundle) {
+        unreachable!("bundle {:?} is not produced by this IO", bundle.hash);
+    }
+    /// Marks all bundle transactions as "rejected".
+    async fn reject_bundle(&mut self, bundle: &TransactionBundle, error: &str) {
+        unreachable!(
+            "bundle {:?} is not produced by this IO; rejected with error {error}",
+            bundle.hash
+        );
+    }
     /// Marks the miniblock (aka L2 block) as sealed.
     /// Returns the timestamp for the next miniblock.
     async fn seal_miniblock(&mut self, updates_manager: &UpdatesManager);
@@ -15,7 +15,7 @@ use zksync_dal::StorageProcessor;
 use zksync_system_constants::ACCOUNT_CODE_STORAGE_ADDRESS;
 use zksync_types::{
     block::{unpack_block_info, L1BatchHeader, MiniblockHeader},
-    event::{extract_added_tokens, extract_long_l2_to_l1_messages},
+    event::{extract_added_tokens, extract_long_l2_to_l1_messages, logs_bloom},
     fee_model::BatchFeeInput,
     l1::L1Tx,
     l2::L2Tx,
@@ -126,6 +126,7 @@ impl UpdatesManager {
 
         let l2_to_l1_messages =
             extract_long_l2_to_l1_messages(&finished_batch.final_execution_state.events);
+        let bloom = logs_bloom(&finished_batch.final_execution_state.events);
 
         let l1_batch = L1BatchHeader {
             number: l1_batch_env.number,
@@ -137,7 +138,7 @@ impl UpdatesManager {
             l2_tx_count: l2_tx_count as u16,
             l2_to_l1_logs: finished_batch.final_execution_state.user_l2_to_l1_logs,
             l2_to_l1_messages,
-            bloom: Default::default(),
+            bloom,
             used_contract_hashes: finished_batch.final_execution_state.used_contract_hashes,
             base_fee_per_gas: get_batch_base_fee(l1_batch_env, self.protocol_version().into()),
             l1_gas_price: self.l1_gas_price(),
@@ -357,6 +358,13 @@ impl MiniblockSealCommand {
             .insert_miniblock(&miniblock_header)
             .await
             .unwrap();
+        if let Some(price) = self.dynamic_l2_gas_price {
+            transaction
+                .fee_model_dal()
+                .set_dynamic_l2_gas_price(miniblock_number, price)
+                .await
+                .unwrap();
+        }
         progress.observe(None);
 
         let progress =
@@ -2,19 +2,27 @@ use std::time::Duration;
 
 use futures::FutureExt;
 use multivm::utils::derive_base_fee_and_gas_per_pubdata;
+use zksync_config::configs::chain::StateKeeperConfig;
 use zksync_contracts::BaseSystemContractsHashes;
 use zksync_dal::ConnectionPool;
 use zksync_mempool::L2TxFilter;
 use zksync_types::{
-    block::BlockGasCount, fee_model::BatchFeeInput, tx::ExecutionMetrics, AccountTreeId, Address,
-    L1BatchNumber, MiniblockNumber, ProtocolVersionId, StorageKey, VmEvent, H256, U256,
+    block::BlockGasCount,
+    fee_model::BatchFeeInput,
+    l1::{L1Tx, L1TxCommonData},
+    tx::ExecutionMetrics,
+    AccountTreeId, Address, Execute, L1BatchNumber, MiniblockNumber, ProtocolVersionId, StorageKey,
+    Transaction, VmEvent, H256, U256,
 };
 use zksync_utils::time::seconds_since_epoch;
 
 use self::tester::Tester;
 use crate::{
     state_keeper::{
-        io::{MiniblockParams, MiniblockSealer, StateKeeperIO},
+        io::{
+            mempool::{next_fixed_interval_timestamp, PriorityTxBudget},
+            MiniblockParams, MiniblockSealer, StateKeeperIO,
+        },
         mempool_actor::l2_tx_filter,
         tests::{
             create_execution_result, create_transaction, create_updates_manager,
@@ -240,6 +248,7 @@ async fn processing_storage_logs_when_sealing_miniblock() {
         protocol_version: Some(ProtocolVersionId::latest()),
         l2_erc20_bridge_addr: Address::default(),
         pre_insert_txs: false,
+        dynamic_l2_gas_price: None,
     };
     let mut conn = connection_pool
         .access_storage_tagged("state_keeper")
@@ -316,6 +325,7 @@ async fn processing_events_when_sealing_miniblock() {
         protocol_version: Some(ProtocolVersionId::latest()),
         l2_erc20_bridge_addr: Address::default(),
         pre_insert_txs: false,
+        dynamic_l2_gas_price: None,
     };
     let mut conn = pool.access_storage_tagged("state_keeper").await.unwrap();
     conn.protocol_versions_dal()
@@ -520,3 +530,63 @@ async fn different_timestamp_for_miniblocks_in_same_batch() {
         .unwrap();
     assert!(next_timestamp > current_timestamp);
 }
+
+#[test]
+fn fixed_interval_timestamps() {
+    // Miniblock sealed early; the next timestamp is in the future.
+    assert_eq!(next_fixed_interval_timestamp(100, 2, 100), 102);
+    assert_eq!(next_fixed_interval_timestamp(100, 2, 101), 102);
+    // Miniblock sealed on time.
+    assert_eq!(next_fixed_interval_timestamp(100, 2, 102), 102);
+    assert_eq!(next_fixed_interval_timestamp(100, 2, 103), 102);
+    // Catching up after downtime; timestamps stay on the grid.
+    assert_eq!(next_fixed_interval_timestamp(100, 2, 111), 110);
+    // Clock skew.
+    assert_eq!(next_fixed_interval_timestamp(100, 2, 90), 102);
+}
+
+#[test]
+fn priority_tx_budget() {
+    let config = StateKeeperConfig {
+        max_priority_txs_per_miniblock: Some(3),
+        max_priority_gas_per_miniblock: Some(250),
+        ..StateKeeperConfig::for_tests()
+    };
+    let mut budget = PriorityTxBudget::new(&config);
+    let l1_tx: Transaction = L1Tx {
+        execute: Execute {
+            contract_address: Address::repeat_byte(1),
+            calldata: vec![],
+            value: U256::zero(),
+            factory_deps: None,
+        },
+        common_data: L1TxCommonData {
+            gas_limit: 100.into(),
+            ..L1TxCommonData::default()
+        },
+        received_timestamp_ms: 0,
+    }
+    .into();
+
+    assert!(!budget.is_exhausted());
+    budget.record(&create_transaction(10, 100));
+    assert!(
+        !budget.is_exhausted(),
+        "L2 transactions don't consume the budget"
+    );
+    budget.record(&l1_tx);
+    budget.record(&l1_tx);
+    assert!(!budget.is_exhausted());
+    budget.record(&l1_tx);
+    assert!(budget.is_exhausted(), "gas budget should be exhausted");
+    budget.reset();
+    assert!(!budget.is_exhausted());
+
+    let config = StateKeeperConfig {
+        max_priority_txs_per_miniblock: Some(1),
+        ..StateKeeperConfig::for_tests()
+    };
+    let mut budget = PriorityTxBudget::new(&config);
+    budget.record(&l1_tx);
+    assert!(budget.is_exhausted(), "tx budget should be exhausted");
+}
@@ -3,7 +3,10 @@
 use std::{sync::Arc, time::Duration};
 
 use multivm::vm_latest::constants::BLOCK_GAS_LIMIT;
-use zksync_config::{configs::chain::StateKeeperConfig, GasAdjusterConfig};
+use zksync_config::{
+    configs::{chain::StateKeeperConfig, eth_sender::PubdataSendingMode},
+    GasAdjusterConfig,
+};
 use zksync_contracts::BaseSystemContracts;
 use zksync_dal::ConnectionPool;
 use zksync_eth_client::clients::MockEthereum;
@@ -46,17 +49,26 @@ impl Tester {
         let gas_adjuster_config = GasAdjusterConfig {
             default_priority_fee_per_gas: 10,
             max_base_fee_samples: 10,
+            base_fee_percentile: None,
+            max_base_fee_spike_ratio: None,
             pricing_formula_parameter_a: 1.0,
             pricing_formula_parameter_b: 1.0,
             internal_l1_pricing_multiplier: 1.0,
             internal_enforced_l1_gas_price: None,
             poll_period: 10,
             max_l1_gas_price: None,
+            internal_pubdata_pricing_multiplier: None,
+            max_blob_base_fee: None,
+            num_samples_for_blob_base_fee_estimate: None,
         };
 
-        GasAdjuster::new(eth_client, gas_adjuster_config)
-            .await
-            .unwrap()
+        GasAdjuster::new(
+            eth_client,
+            gas_adjuster_config,
+            PubdataSendingMode::Calldata,
+        )
+        .await
+        .unwrap()
     }
 
     pub(super) async fn create_batch_fee_input_provider(&self) -> MainNodeFeeInputProvider {
@@ -1,4 +1,5 @@
 use std::{
+    collections::VecDeque,
     convert::Infallible,
     time::{Duration, Instant},
 };
@@ -7,8 +8,11 @@ use anyhow::Context as _;
 use multivm::interface::{Halt, L1BatchEnv, SystemEnv};
 use tokio::sync::watch;
 use zksync_types::{
-    block::MiniblockExecutionData, l2::TransactionType, protocol_version::ProtocolUpgradeTx,
-    storage_writes_deduplicator::StorageWritesDeduplicator, Transaction,
+    block::MiniblockExecutionData,
+    l2::{bundle::TransactionBundle, TransactionType},
+    protocol_version::ProtocolUpgradeTx,
+    storage_writes_deduplicator::StorageWritesDeduplicator,
+    ProtocolVersionId, Transaction,
 };
 
 use super::{
@@ -17,6 +21,7 @@ use super::{
     io::{MiniblockParams, PendingBatchData, StateKeeperIO},
     metrics::{AGGREGATION_METRICS, KEEPER_METRICS, L1_BATCH_METRICS},
     seal_criteria::{ConditionalSealer, SealData, SealResolution},
+    sealing_control::SealingControl,
     types::ExecutionMetricsForCriteria,
     updates::UpdatesManager,
 };
@@ -59,6 +64,11 @@ pub struct ZkSyncStateKeeper {
     io: Box<dyn StateKeeperIO>,
     batch_executor_base: Box<dyn L1BatchExecutorBuilder>,
     sealer: Box<dyn ConditionalSealer>,
+    sealing_receiver: Option<watch::Receiver<bool>>,
+    /// Max number of transactions fetched from the IO at once to prefetch storage for them.
+    tx_lookahead: usize,
+    /// Transactions fetched from the IO, but not yet executed.
+    lookahead_txs: VecDeque<Transaction>,
 }
 
 impl ZkSyncStateKeeper {
@@ -73,9 +83,25 @@ impl ZkSyncStateKeeper {
             io,
             batch_executor_base,
             sealer,
+            sealing_receiver: None,
+            tx_lookahead: 1,
+            lookahead_txs: VecDeque::new(),
         }
     }
 
+    /// Enables fetching up to `workers` transactions at once and prefetching storage for them. The batch executor
+    /// must be configured for storage prefetching as well; otherwise, prefetching is a no-op.
+    pub fn with_storage_prefetch(mut self, workers: usize) -> Self {
+        self.tx_lookahead = workers;
+        self
+    }
+
+    /// Allows to stop and resume transaction processing and sealing using the provided handle.
+    pub fn with_sealing_control(mut self, sealing_control: &SealingControl) -> Self {
+        self.sealing_receiver = Some(sealing_control.subscribe());
+        self
+    }
+
     pub async fn run(mut self) -> anyhow::Result<()> {
         match self.run_inner().await {
             Ok(_) => unreachable!(),
@@ -139,6 +165,11 @@ impl ZkSyncStateKeeper {
         let version_changed = protocol_version != previous_batch_protocol_version;
 
         let mut protocol_upgrade_tx = if pending_miniblocks.is_empty() && version_changed {
+            Self::report_protocol_upgrade(
+                previous_batch_protocol_version,
+                &system_env,
+                &l1_batch_env,
+            );
             self.io.load_upgrade_tx(protocol_version).await
         } else if !pending_miniblocks.is_empty() && version_changed {
             // Sanity check: if `txs_to_reexecute` is not empty and upgrade tx is present for this block
@@ -169,6 +200,7 @@ impl ZkSyncStateKeeper {
             // This function will run until the batch can be sealed.
             self.process_l1_batch(&batch_executor, &mut updates_manager, protocol_upgrade_tx)
                 .await?;
+            self.rollback_lookahead_txs().await;
 
             // Finish current batch.
             if !updates_manager.miniblock.executed_transactions.is_empty() {
@@ -216,6 +248,11 @@ impl ZkSyncStateKeeper {
             let version_changed = system_env.version != sealed_batch_protocol_version;
 
             protocol_upgrade_tx = if version_changed {
+                Self::report_protocol_upgrade(
+                    sealed_batch_protocol_version,
+                    &system_env,
+                    &l1_batch_env,
+                );
                 self.io.load_upgrade_tx(system_env.version).await
             } else {
                 None
@@ -224,10 +261,96 @@ impl ZkSyncStateKeeper {
         Err(Error::Canceled)
     }
 
+    fn report_protocol_upgrade(
+        previous_version: ProtocolVersionId,
+        system_env: &SystemEnv,
+        l1_batch_env: &L1BatchEnv,
+    ) {
+        let contracts = system_env.base_system_smart_contracts.hashes();
+        tracing::info!(
+            "Activating protocol upgrade from version {previous_version:?} to {:?} in L1 batch #{} \
+             with timestamp {}; bootloader hash: {:?}, default account hash: {:?}",
+            system_env.version,
+            l1_batch_env.number,
+            extractors::display_timestamp(l1_batch_env.timestamp),
+            contracts.bootloader,
+            contracts.default_aa
+        );
+        KEEPER_METRICS.protocol_upgrade_activations.inc();
+    }
+
     fn is_canceled(&self) -> bool {
         *self.stop_receiver.borrow()
     }
 
+    /// Waits until sealing is resumed if it was stopped via [`SealingControl`].
+    async fn wait_for_sealing_resumed(&mut self) -> Result<(), Error> {
+        let Some(sealing_receiver) = &mut self.sealing_receiver else {
+            return Ok(());
+        };
+        if !*sealing_receiver.borrow() {
+            return Ok(());
+        }
+
+        tracing::info!(
+            "Sealing is stopped; waiting for it to be resumed before processing miniblock #{} (L1 batch #{})",
+            self.io.current_miniblock_number(),
+            self.io.current_l1_batch_number()
+        );
+        let mut stop_receiver = self.stop_receiver.clone();
+        let sealing_control_dropped = tokio::select! {
+            res = sealing_receiver.wait_for(|&stopped| !stopped) => res.is_err(),
+            _ = stop_receiver.wait_for(|&stop| stop) => return Err(Error::Canceled),
+        };
+        if sealing_control_dropped {
+            tracing::warn!(
+                "Sealing control was dropped while sealing is stopped; resuming sealing"
+            );
+            self.sealing_receiver = None;
+        }
+        tracing::info!("Sealing is resumed");
+        Ok(())
+    }
+
+    /// Returns the next transaction to execute. If storage prefetching is enabled, fetches several transactions
+    /// from the IO at once and starts prefetching storage for them.
+    async fn wait_for_next_tx(
+        &mut self,
+        batch_executor: &BatchExecutorHandle,
+    ) -> Option<Transaction> {
+        if let Some(tx) = self.lookahead_txs.pop_front() {
+            return Some(tx);
+        }
+        let tx = self.io.wait_for_next_tx(POLL_WAIT_DURATION).await?;
+        if self.tx_lookahead <= 1 {
+            return Some(tx);
+        }
+
+        let mut txs = vec![tx];
+        while txs.len() < self.tx_lookahead {
+            let Some(tx) = self.io.try_next_tx() else {
+                break;
+            };
+            txs.push(tx);
+        }
+        if txs.len() > 1 {
+            batch_executor.prefetch_storage(txs.clone()).await;
+        }
+        let mut txs = txs.into_iter();
+        let tx = txs.next();
+        self.lookahead_txs.extend(txs);
+        tx
+    }
+
+    /// Returns fetched, but not executed transactions to the IO once the L1 batch is sealed, since the parameters
+    /// of the next batch may make them non-executable.
+    async fn rollback_lookahead_txs(&mut self) {
+        // Transactions are rolled back in the reverse order so that account nonces are reset correctly.
+        while let Some(tx) = self.lookahead_txs.pop_back() {
+            self.io.rollback(tx).await;
+        }
+    }
+
     async fn wait_for_new_batch_params(&mut self) -> Result<(SystemEnv, L1BatchEnv), Error> {
         while !self.is_canceled() {
             if let Some(params) = self.io.wait_for_new_batch_params(POLL_WAIT_DURATION).await {
@@ -326,6 +449,7 @@ impl ZkSyncStateKeeper {
                 let exec_result_status = tx_result.result.clone();
                 let initiator_account = tx.initiator_account();
 
+                self.io.mark_tx_included(&tx);
                 updates_manager.extend_from_executed_transaction(
                     tx,
                     *tx_result,
@@ -371,6 +495,8 @@ impl ZkSyncStateKeeper {
         }
 
         while !self.is_canceled() {
+            self.wait_for_sealing_resumed().await?;
+
             if self
                 .io
                 .should_seal_l1_batch_unconditionally(updates_manager)
@@ -404,8 +530,31 @@ impl ZkSyncStateKeeper {
                     .await;
             }
 
+            // Bundles are not processed until fetched transactions are executed, since they may contain
+            // transactions from the same accounts.
+            let next_bundle = if self.lookahead_txs.is_empty() {
+                self.io.next_bundle()
+            } else {
+                None
+            };
+            if let Some(bundle) = next_bundle {
+                let bundle_hash = bundle.hash;
+                let seal_resolution = self
+                    .process_bundle(batch_executor, updates_manager, bundle)
+                    .await;
+                if seal_resolution.should_seal() {
+                    tracing::debug!(
+                        "L1 batch #{} should be sealed with resolution {seal_resolution:?} after processing \
+                         bundle {bundle_hash:?}",
+                        self.io.current_l1_batch_number()
+                    );
+                    return Ok(());
+                }
+                continue;
+            }
+
             let waiting_latency = KEEPER_METRICS.waiting_for_tx.start();
-            let Some(tx) = self.io.wait_for_next_tx(POLL_WAIT_DURATION).await else {
+            let Some(tx) = self.wait_for_next_tx(batch_executor).await else {
                 waiting_latency.observe();
                 tracing::trace!("No new transactions. Waiting!");
                 continue;
@@ -435,6 +584,7 @@ impl ZkSyncStateKeeper {
                         l1_gas: tx_l1_gas_this_tx,
                         execution_metrics: tx_execution_metrics,
                     } = tx_metrics;
+                    self.io.mark_tx_included(&tx);
                     updates_manager.extend_from_executed_transaction(
                         tx,
                         *tx_result,
@@ -446,10 +596,13 @@ impl ZkSyncStateKeeper {
                 }
                 SealResolution::ExcludeAndSeal => {
                     batch_executor.rollback_last_tx().await;
+                    // Fetched transactions must be rolled back before this one to keep account nonces consistent.
+                    self.rollback_lookahead_txs().await;
                     self.io.rollback(tx).await;
                 }
                 SealResolution::Unexecutable(reason) => {
                     batch_executor.rollback_last_tx().await;
+                    self.rollback_lookahead_txs().await;
                     self.io.reject(&tx, reason).await;
                 }
             };
@@ -527,6 +680,95 @@ impl ZkSyncStateKeeper {
         };
     }
 
+    /// Executes a transaction bundle. Bundle transactions are either all included into the current miniblock
+    /// contiguously and in order, or all rolled back. A bundle is rejected if any of its transactions
+    /// is unexecutable or fails, and is returned to the IO if it doesn't fit into the current L1 batch.
+    async fn process_bundle(
+        &mut self,
+        batch_executor: &BatchExecutorHandle,
+        updates_manager: &mut UpdatesManager,
+        bundle: TransactionBundle,
+    ) -> SealResolution {
+        let bundle_len = bundle.transactions.len();
+        let mut bundle_updates = updates_manager.clone();
+        let mut executed_tx_count = 0;
+        let mut resolution = SealResolution::NoSeal;
+        for (i, tx) in bundle.transactions.iter().enumerate() {
+            let tx = Transaction::from(tx.clone());
+            let tx_hash = tx.hash();
+            let (tx_resolution, exec_result) = self
+                .process_one_tx(batch_executor, &mut bundle_updates, tx.clone())
+                .await;
+            executed_tx_count += 1;
+
+            resolution = match tx_resolution {
+                SealResolution::NoSeal | SealResolution::IncludeAndSeal => {
+                    let TxExecutionResult::Success {
+                        tx_result,
+                        tx_metrics,
+                        call_tracer_result,
+                        compressed_bytecodes,
+                        ..
+                    } = exec_result
+                    else {
+                        unreachable!(
+                            "Tx inclusion seal resolution must be a result of a successful tx execution",
+                        );
+                    };
+
+                    if tx_result.result.is_failed() {
+                        SealResolution::Unexecutable(format!("transaction {tx_hash:?} has failed"))
+                    } else if tx_resolution.should_seal() && i + 1 < bundle_len {
+                        SealResolution::ExcludeAndSeal
+                    } else {
+                        bundle_updates.extend_from_executed_transaction(
+                            tx,
+                            *tx_result,
+                            compressed_bytecodes,
+                            tx_metrics.l1_gas,
+                            tx_metrics.execution_metrics,
+                            call_tracer_result,
+                        );
+                        tx_resolution
+                    }
+                }
+                other => other,
+            };
+            if matches!(
+                resolution,
+                SealResolution::ExcludeAndSeal | SealResolution::Unexecutable(_)
+            ) {
+                break;
+            }
+        }
+
+        if matches!(resolution, SealResolution::ExcludeAndSeal)
+            && updates_manager.pending_executed_transactions_len() == 0
+        {
+            // The bundle doesn't fit even into an empty L1 batch.
+            resolution = SealResolution::Unexecutable("bundle doesn't fit into L1 batch".into());
+        }
+
+        match &resolution {
+            SealResolution::NoSeal | SealResolution::IncludeAndSeal => {
+                *updates_manager = bundle_updates;
+            }
+            SealResolution::ExcludeAndSeal => {
+                for _ in 0..executed_tx_count {
+                    batch_executor.rollback_last_tx().await;
+                }
+                self.io.rollback_bundle(bundle).await;
+            }
+            SealResolution::Unexecutable(reason) => {
+                for _ in 0..executed_tx_count {
+                    batch_executor.rollback_last_tx().await;
+                }
+                self.io.reject_bundle(&bundle, reason).await;
+            }
+        }
+        resolution
+    }
+
     /// Executes one transaction in the batch executor, and then decides whether the batch should be sealed.
     /// Batch may be sealed because of one of the following reasons:
     /// 1. The VM entered an incorrect state (e.g. out of gas). In that case, we must revert the transaction and seal
@@ -564,6 +806,9 @@ impl ZkSyncStateKeeper {
                 }
                 _ => SealResolution::Unexecutable(reason.to_string()),
             },
+            TxExecutionResult::ConditionsNotMet(err) => {
+                SealResolution::Unexecutable(err.to_string())
+            }
             TxExecutionResult::Success {
                 tx_result,
                 tx_metrics,
@@ -1,15 +1,25 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use multivm::utils::derive_base_fee_and_gas_per_pubdata;
 use tokio::sync::watch;
 use zksync_config::configs::chain::MempoolConfig;
-use zksync_dal::ConnectionPool;
+use zksync_dal::{
+    transactions_dal::{DroppedTxReason, MempoolEvictionCandidate},
+    ConnectionPool, StorageProcessor,
+};
 use zksync_mempool::L2TxFilter;
 use zksync_types::{ProtocolVersionId, VmVersion};
 
 use super::{metrics::KEEPER_METRICS, types::MempoolGuard};
 use crate::{api_server::execution_sandbox::BlockArgs, fee_model::BatchFeeModelInputProvider};
 
+/// Minimum interval between evictions of expired and excess transactions from the mempool.
+const EVICTION_INTERVAL: Duration = Duration::from_secs(10);
+
 /// Creates a mempool filter for L2 transactions based on the current L1 gas price.
 /// The filter is used to filter out transactions from the mempool that do not cover expenses
 /// to process them.
@@ -33,6 +43,8 @@ pub struct MempoolFetcher<G> {
     batch_fee_input_provider: Arc<G>,
     sync_interval: Duration,
     sync_batch_size: usize,
+    tx_ttl: Option<Duration>,
+    max_pending_txs: Option<usize>,
 }
 
 impl<G: BatchFeeModelInputProvider> MempoolFetcher<G> {
@@ -46,9 +58,101 @@ impl<G: BatchFeeModelInputProvider> MempoolFetcher<G> {
             batch_fee_input_provider,
             sync_interval: config.sync_interval(),
             sync_batch_size: config.sync_batch_size,
+            tx_ttl: config.tx_ttl(),
+            max_pending_txs: config.max_pending_txs,
+        }
+    }
+
+    /// Drops expired transactions and transactions exceeding the mempool capacity.
+    async fn evict_transactions(&mut self, storage: &mut StorageProcessor<'_>) {
+        if let Some(ttl) = self.tx_ttl {
+            let candidates = storage
+                .transactions_dal()
+                .get_expired_mempool_txs(ttl, self.sync_batch_size)
+                .await
+                .unwrap();
+            let dropped_count = self
+                .drop_transactions(storage, candidates, &DroppedTxReason::Expired)
+                .await;
+            KEEPER_METRICS
+                .expired_transactions
+                .inc_by(dropped_count as u64);
+        }
+
+        if let Some(max_pending_txs) = self.max_pending_txs {
+            let candidates = storage
+                .transactions_dal()
+                .get_lowest_fee_mempool_txs(max_pending_txs, self.sync_batch_size)
+                .await
+                .unwrap();
+            let dropped_count = self
+                .drop_transactions(storage, candidates, &DroppedTxReason::Evicted)
+                .await;
+            KEEPER_METRICS
+                .evicted_transactions
+                .inc_by(dropped_count as u64);
+        }
+    }
+
+    /// Drops transactions requested to be removed by the node operator (e.g., via the admin API).
+    async fn remove_requested_transactions(&mut self, storage: &mut StorageProcessor<'_>) {
+        let requests = storage
+            .transactions_dal()
+            .get_mempool_removal_requests(self.sync_batch_size)
+            .await
+            .unwrap();
+        let mut candidates_by_reason = HashMap::<_, Vec<_>>::new();
+        for (candidate, reason) in requests {
+            candidates_by_reason
+                .entry(reason)
+                .or_default()
+                .push(candidate);
+        }
+
+        for (reason, candidates) in candidates_by_reason {
+            let reason = DroppedTxReason::RemovedByOperator(reason);
+            let dropped_count = self.drop_transactions(storage, candidates, &reason).await;
+            KEEPER_METRICS
+                .removed_transactions
+                .inc_by(dropped_count as u64);
         }
     }
 
+    /// Drops the specified transactions from the in-memory mempool and the storage. Transactions loaded into
+    /// the mempool, but no longer present in it, are retained since they may be executed by the state keeper
+    /// at the moment.
+    async fn drop_transactions(
+        &mut self,
+        storage: &mut StorageProcessor<'_>,
+        candidates: Vec<MempoolEvictionCandidate>,
+        reason: &DroppedTxReason,
+    ) -> usize {
+        if candidates.is_empty() {
+            return 0;
+        }
+        let accounts: HashSet<_> = candidates
+            .iter()
+            .filter(|tx| tx.in_mempool)
+            .map(|tx| tx.initiator_address)
+            .collect();
+        let removed_hashes = self.mempool.remove_accounts(&accounts);
+        let tx_hashes: Vec<_> = candidates
+            .iter()
+            .filter(|tx| !tx.in_mempool || removed_hashes.contains(&tx.hash))
+            .map(|tx| tx.hash)
+            .collect();
+
+        let dropped_count = storage
+            .transactions_dal()
+            .drop_mempool_txs(&tx_hashes, reason)
+            .await
+            .unwrap();
+        tracing::info!(
+            "Dropped {dropped_count} pending transactions from mempool; reason: {reason}"
+        );
+        dropped_count
+    }
+
     pub async fn run(
         mut self,
         pool: ConnectionPool,
@@ -68,13 +172,22 @@ impl<G: BatchFeeModelInputProvider> MempoolFetcher<G> {
             storage.transactions_dal().reset_mempool().await;
         }
 
+        let mut last_eviction_at: Option<Instant> = None;
         loop {
             if *stop_receiver.borrow() {
                 tracing::info!("Stop signal received, mempool is shutting down");
                 break;
             }
-            let latency = KEEPER_METRICS.mempool_sync.start();
             let mut storage = pool.access_storage_tagged("state_keeper").await.unwrap();
+            if last_eviction_at.map_or(true, |at| at.elapsed() >= EVICTION_INTERVAL) {
+                self.evict_transactions(&mut storage).await;
+                last_eviction_at = Some(Instant::now());
+            }
+
+            // Removal requests are processed on each iteration, since they are usually urgent.
+            self.remove_requested_transactions(&mut storage).await;
+
+            let latency = KEEPER_METRICS.mempool_sync.start();
             let mempool_info = self.mempool.get_mempool_info();
 
             let latest_miniblock = BlockArgs::pending(&mut storage).await;
@@ -101,8 +214,14 @@ impl<G: BatchFeeModelInputProvider> MempoolFetcher<G> {
                     self.sync_batch_size,
                 )
                 .await;
-            let all_transactions_loaded = transactions.len() < self.sync_batch_size;
+            let bundles = storage
+                .transactions_dal()
+                .sync_mempool_bundles(self.sync_batch_size)
+                .await;
+            let all_transactions_loaded =
+                transactions.len() < self.sync_batch_size && bundles.len() < self.sync_batch_size;
             self.mempool.insert(transactions, nonces);
+            self.mempool.insert_bundles(bundles);
             latency.observe();
             if all_transactions_loaded {
                 tokio::time::sleep(self.sync_interval).await;
@@ -57,6 +57,29 @@ pub(crate) struct StateKeeperMetrics {
     pub tx_execution_time: Family<TxExecutionStage, Histogram<Duration>>,
     /// Number of times gas price was reported as too high.
     pub gas_price_too_high: Counter,
+    /// Number of pending transactions dropped from the mempool because they have expired.
+    pub expired_transactions: Counter,
+    /// Number of pending transactions evicted from the full mempool because of their low fee.
+    pub evicted_transactions: Counter,
+    /// Number of pending transactions removed from the mempool on request of the node operator.
+    pub removed_transactions: Counter,
+    /// Number of times a priority transaction was deferred in favor of an L2 transaction because
+    /// the priority transaction budget for the miniblock was exhausted.
+    pub deferred_priority_txs: Counter,
+    /// Number of L1 batches waiting for proofs as observed by the state keeper.
+    pub proving_queue_len: Gauge<usize>,
+    /// Current congestion-based fair L2 gas price.
+    pub dynamic_l2_gas_price: Gauge<u64>,
+    /// Protocol version of the currently open L1 batch.
+    pub protocol_version: Gauge<u64>,
+    /// Activation timestamp of the next scheduled protocol upgrade, or 0 if there is no scheduled upgrade.
+    pub scheduled_protocol_upgrade_timestamp: Gauge<u64>,
+    /// Number of protocol upgrades activated by the state keeper.
+    pub protocol_upgrade_activations: Counter,
+    /// Delay between the scheduled activation timestamp of a protocol upgrade and the timestamp
+    /// of the first L1 batch using it.
+    #[metrics(buckets = Buckets::exponential(1.0..=4_096.0, 4.0))]
+    pub protocol_upgrade_activation_delay: Histogram<Duration>,
 }
 
 #[vise::register]
@@ -70,6 +93,8 @@ pub(super) struct StateKeeperGauges {
     mempool_l1_size: Gauge<usize>,
     /// Current number of L2 transactions in the mempool.
     mempool_l2_size: Gauge<u64>,
+    /// Current number of L2 transactions in the mempool that are blocked by a nonce gap.
+    mempool_l2_queued_size: Gauge<usize>,
     /// Current size of the L2 priority queue.
     l2_priority_queue_size: Gauge<usize>,
 }
@@ -87,6 +112,9 @@ impl StateKeeperGauges {
                 let gauges = StateKeeperGauges::default();
                 gauges.mempool_l1_size.set(stats.l1_transaction_count);
                 gauges.mempool_l2_size.set(stats.l2_transaction_count);
+                gauges
+                    .mempool_l2_queued_size
+                    .set(stats.l2_queued_transaction_count);
                 gauges
                     .l2_priority_queue_size
                     .set(stats.l2_priority_queue_size);
@@ -343,11 +371,23 @@ impl SealProgress<'_> {
 #[metrics(label = "command", rename_all = "snake_case")]
 pub(super) enum ExecutorCommand {
     ExecuteTx,
+    PrefetchStorage,
     StartNextMiniblock,
     RollbackLastTx,
     FinishBatch,
 }
 
+/// Kind of divergence between the main and the shadow VMs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "kind", rename_all = "snake_case")]
+pub(super) enum ShadowDivergenceKind {
+    Result,
+    Gas,
+    Events,
+    StorageWrites,
+    StateDiff,
+}
+
 const GAS_PER_NANOSECOND_BUCKETS: Buckets = Buckets::values(&[
     0.01, 0.03, 0.1, 0.3, 0.5, 0.75, 1., 1.5, 3., 5., 10., 20., 50.,
 ]);
@@ -367,6 +407,26 @@ pub(super) struct ExecutorMetrics {
     pub computational_gas_per_nanosecond: Histogram<f64>,
     #[metrics(buckets = GAS_PER_NANOSECOND_BUCKETS)]
     pub failed_tx_gas_limit_per_nanosecond: Histogram<f64>,
+    /// Number of transactions speculatively executed by the storage prefetcher.
+    pub prefetch_txs: Counter,
+    /// Number of transactions skipped by the storage prefetcher because the previous prefetching was in progress.
+    pub skipped_prefetch_txs: Counter,
+    /// Number of storage values prefetched by the storage prefetcher.
+    pub prefetched_storage_values: Counter,
+    /// Latency of prefetching storage values for a chunk of transactions on a single thread.
+    #[metrics(buckets = Buckets::LATENCIES)]
+    pub prefetch_latency: Histogram<Duration>,
+    /// Number of miniblocks re-executed by the shadow VM.
+    pub shadow_executed_miniblocks: Counter,
+    /// Number of divergences between the main and the shadow VMs.
+    pub shadow_divergences: Family<ShadowDivergenceKind, Counter>,
+    /// Number of L1 batches not shadow-executed because the shadow VM was still busy with the previous batch.
+    pub shadow_skipped_batches: Counter,
+    /// Number of L1 batches for which shadow execution was cancelled because the shadow VM has fallen behind.
+    pub shadow_cancelled_batches: Counter,
+    /// Time between finishing an L1 batch by the main VM and by the shadow VM.
+    #[metrics(buckets = Buckets::LATENCIES)]
+    pub shadow_batch_lag: Histogram<Duration>,
 }
 
 #[vise::register]
@@ -8,16 +8,18 @@ use zksync_config::{
 use zksync_dal::ConnectionPool;
 use zksync_object_store::ObjectStore;
 
-use self::io::MempoolIO;
 pub use self::{
-    batch_executor::{L1BatchExecutorBuilder, MainBatchExecutorBuilder},
+    batch_executor::{L1BatchExecutorBuilder, MainBatchExecutorBuilder, StateKeeperDbOptions},
     io::{MiniblockSealer, MiniblockSealerHandle},
     keeper::ZkSyncStateKeeper,
+    seal_criteria::ProvingQueueMonitor,
+    sealing_control::SealingControl,
 };
+use self::{io::MempoolIO, seal_criteria::ProverCapacitySealer};
 pub(crate) use self::{
     mempool_actor::MempoolFetcher, seal_criteria::SequencerSealer, types::MempoolGuard,
 };
-use crate::fee_model::BatchFeeModelInputProvider;
+use crate::fee_model::{BatchFeeModelInputProvider, DynamicL2GasPrice};
 
 mod batch_executor;
 pub(crate) mod extractors;
@@ -26,6 +28,7 @@ mod keeper;
 mod mempool_actor;
 pub(crate) mod metrics;
 pub mod seal_criteria;
+mod sealing_control;
 #[cfg(test)]
 pub(crate) mod tests;
 pub(crate) mod types;
@@ -43,9 +46,11 @@ pub(crate) async fn create_state_keeper(
     batch_fee_input_provider: Arc<dyn BatchFeeModelInputProvider>,
     miniblock_sealer_handle: MiniblockSealerHandle,
     object_store: Arc<dyn ObjectStore>,
+    proving_queue_len: Option<watch::Receiver<usize>>,
+    dynamic_l2_gas_price: Option<Arc<DynamicL2GasPrice>>,
     stop_receiver: watch::Receiver<bool>,
 ) -> ZkSyncStateKeeper {
-    let batch_executor_base = MainBatchExecutorBuilder::new(
+    let mut batch_executor_base = MainBatchExecutorBuilder::new(
         db_config.state_keeper_db_path.clone(),
         pool.clone(),
         state_keeper_config.max_allowed_l2_tx_gas_limit.into(),
@@ -53,9 +58,10 @@ pub(crate) async fn create_state_keeper(
         state_keeper_config.upload_witness_inputs_to_gcs,
         state_keeper_config.enum_index_migration_chunk_size(),
         false,
-    );
+    )
+    .with_state_keeper_db_options(StateKeeperDbOptions::for_main_node(db_config));
 
-    let io = MempoolIO::new(
+    let mut io = MempoolIO::new(
         mempool,
         object_store,
         miniblock_sealer_handle,
@@ -68,12 +74,31 @@ pub(crate) async fn create_state_keeper(
         network_config.zksync_network_id,
     )
     .await;
+    let prover_capacity_sealer = proving_queue_len
+        .and_then(|queue_len| ProverCapacitySealer::new(&state_keeper_config, queue_len));
+    if let Some(sealer) = prover_capacity_sealer {
+        io = io.with_prover_capacity_sealer(sealer);
+    }
+    if let Some(price) = dynamic_l2_gas_price {
+        io = io.with_dynamic_l2_gas_price(price);
+    }
 
+    let storage_prefetch_workers = state_keeper_config.storage_prefetch_workers;
+    if let Some(workers) = storage_prefetch_workers {
+        batch_executor_base = batch_executor_base.with_storage_prefetch(workers);
+    }
+    if state_keeper_config.shadow_vm_execution {
+        batch_executor_base = batch_executor_base.with_shadow_execution();
+    }
     let sealer = SequencerSealer::new(state_keeper_config);
-    ZkSyncStateKeeper::new(
+    let state_keeper = ZkSyncStateKeeper::new(
         stop_receiver,
         Box::new(io),
         Box::new(batch_executor_base),
         Box::new(sealer),
-    )
+    );
+    match storage_prefetch_workers {
+        Some(workers) => state_keeper.with_storage_prefetch(workers),
+        None => state_keeper,
+    }
 }
@@ -9,6 +9,14 @@ use crate::state_keeper::seal_criteria::{SealCriterion, SealData, SealResolution
 // Collected vm execution metrics should fit into geometry limits.
 // Otherwise witness generation will fail and proof won't be generated.
 
+/// Maximum estimated number of circuits in an L1 batch. We subtract constant to take into account
+/// that circuits may be not fully filled. This constant should be greater than number of circuits types,
+/// but we keep it larger to be on the safe side.
+pub(in crate::state_keeper) const MAX_CIRCUITS_PER_BATCH: usize = {
+    const MARGIN_NUMBER_OF_CIRCUITS: usize = 10000;
+    (1 << 14) + (1 << 13) - MARGIN_NUMBER_OF_CIRCUITS
+};
+
 #[derive(Debug, Default)]
 pub struct CircuitsCriterion;
 
@@ -60,13 +68,7 @@ impl MetricExtractor for CircuitsCriterion {
     const PROM_METRIC_CRITERION_NAME: &'static str = "circuits";
 
     fn limit_per_block(_protocol_version_id: ProtocolVersionId) -> usize {
-        // We subtract constant to take into account that circuits may be not fully filled.
-        // This constant should be greater than number of circuits types
-        // but we keep it larger to be on the safe side.
-        const MARGIN_NUMBER_OF_CIRCUITS: usize = 10000;
-        const MAX_NUMBER_OF_CIRCUITS: usize = (1 << 14) + (1 << 13) - MARGIN_NUMBER_OF_CIRCUITS;
-
-        MAX_NUMBER_OF_CIRCUITS
+        MAX_CIRCUITS_PER_BATCH
     }
 
     fn extract(metrics: &ExecutionMetrics) -> usize {
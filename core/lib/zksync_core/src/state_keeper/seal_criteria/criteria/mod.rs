@@ -5,7 +5,9 @@ mod slots;
 mod tx_encoding_size;
 
 pub(in crate::state_keeper) use self::{
-    gas::GasCriterion, geometry_seal_criteria::CircuitsCriterion,
-    pubdata_bytes::PubDataBytesCriterion, slots::SlotsCriterion,
+    gas::GasCriterion,
+    geometry_seal_criteria::{CircuitsCriterion, MAX_CIRCUITS_PER_BATCH},
+    pubdata_bytes::PubDataBytesCriterion,
+    slots::SlotsCriterion,
     tx_encoding_size::TxEncodingSizeCriterion,
 };
@@ -24,8 +24,16 @@ use zksync_utils::time::millis_since;
 
 mod conditional_sealer;
 pub(super) mod criteria;
+mod protocol_upgrade;
+mod prover_capacity;
 
-pub use self::conditional_sealer::{ConditionalSealer, NoopSealer, SequencerSealer};
+pub use self::{
+    conditional_sealer::{ConditionalSealer, NoopSealer, SequencerSealer},
+    prover_capacity::ProvingQueueMonitor,
+};
+pub(super) use self::{
+    protocol_upgrade::ProtocolUpgradeSealer, prover_capacity::ProverCapacitySealer,
+};
 use super::{extractors, metrics::AGGREGATION_METRICS, updates::UpdatesManager};
 use crate::gas_tracker::{gas_count_from_tx_and_metrics, gas_count_from_writes};
 
@@ -133,6 +141,14 @@ pub trait IoSealCriteria {
 pub(super) struct TimeoutSealer {
     block_commit_deadline_ms: u64,
     miniblock_commit_deadline_ms: u64,
+    /// Interval of the fixed-interval miniblock production, if it's enabled.
+    fixed_miniblock_interval_ms: Option<u64>,
+    fixed_interval_empty_miniblocks: bool,
+    /// Base interval after which an empty miniblock is sealed, if empty miniblocks are produced.
+    empty_miniblock_interval_ms: Option<u64>,
+    max_idle_gap_ms: Option<u64>,
+    /// Number of consecutive empty miniblocks this sealer has decided to seal.
+    consecutive_empty_miniblocks: u32,
 }
 
 impl TimeoutSealer {
@@ -140,6 +156,28 @@ impl TimeoutSealer {
         Self {
             block_commit_deadline_ms: config.block_commit_deadline_ms,
             miniblock_commit_deadline_ms: config.miniblock_commit_deadline_ms,
+            fixed_miniblock_interval_ms: config
+                .fixed_miniblock_interval()
+                .map(|interval| interval.as_millis() as u64),
+            fixed_interval_empty_miniblocks: config.fixed_interval_empty_miniblocks,
+            empty_miniblock_interval_ms: config
+                .empty_miniblock_interval()
+                .map(|interval| interval.as_millis() as u64),
+            max_idle_gap_ms: config
+                .max_idle_gap()
+                .map(|interval| interval.as_millis() as u64),
+            consecutive_empty_miniblocks: 0,
+        }
+    }
+
+    /// Returns the time an empty miniblock should be open for before it's sealed. The gap doubles
+    /// with each consecutive empty miniblock, so that idle chains don't produce lots of miniblocks.
+    fn empty_miniblock_gap_ms(&self, interval_ms: u64) -> u64 {
+        let multiplier = 2_u64.saturating_pow(self.consecutive_empty_miniblocks);
+        let gap_ms = interval_ms.saturating_mul(multiplier);
+        match self.max_idle_gap_ms {
+            Some(max_gap_ms) => gap_ms.min(max_gap_ms.max(interval_ms)),
+            None => interval_ms,
         }
     }
 }
@@ -170,8 +208,32 @@ impl IoSealCriteria for TimeoutSealer {
     }
 
     fn should_seal_miniblock(&mut self, manager: &UpdatesManager) -> bool {
-        !manager.miniblock.executed_transactions.is_empty()
-            && millis_since(manager.miniblock.timestamp) > self.miniblock_commit_deadline_ms
+        let is_empty = manager.miniblock.executed_transactions.is_empty();
+        if let Some(interval_ms) = self.fixed_miniblock_interval_ms {
+            // In the fixed-interval mode, a miniblock spans exactly one interval starting from its timestamp.
+            return (!is_empty || self.fixed_interval_empty_miniblocks)
+                && millis_since(manager.miniblock.timestamp) >= interval_ms;
+        }
+
+        if !is_empty {
+            self.consecutive_empty_miniblocks = 0;
+            return millis_since(manager.miniblock.timestamp) > self.miniblock_commit_deadline_ms;
+        }
+        let Some(interval_ms) = self.empty_miniblock_interval_ms else {
+            return false;
+        };
+        let gap_ms = self.empty_miniblock_gap_ms(interval_ms);
+        let should_seal = millis_since(manager.miniblock.timestamp) >= gap_ms;
+        if should_seal {
+            tracing::debug!(
+                "Decided to seal empty miniblock with timestamp {} after {gap_ms}ms of idleness \
+                 ({} consecutive empty miniblocks before it)",
+                extractors::display_timestamp(manager.miniblock.timestamp),
+                self.consecutive_empty_miniblocks
+            );
+            self.consecutive_empty_miniblocks += 1;
+        }
+        should_seal
     }
 }
 
@@ -202,6 +264,11 @@ mod tests {
         let mut timeout_miniblock_sealer = TimeoutSealer {
             block_commit_deadline_ms: 10_000,
             miniblock_commit_deadline_ms: 10_000,
+            fixed_miniblock_interval_ms: None,
+            fixed_interval_empty_miniblocks: false,
+            empty_miniblock_interval_ms: None,
+            max_idle_gap_ms: None,
+            consecutive_empty_miniblocks: 0,
         };
 
         let mut manager = create_updates_manager();
@@ -228,4 +295,67 @@ mod tests {
             "Non-empty miniblock with too recent timestamp shouldn't be sealed"
         );
     }
+
+    #[test]
+    fn fixed_interval_miniblock_sealer() {
+        let mut sealer = TimeoutSealer {
+            block_commit_deadline_ms: 10_000,
+            miniblock_commit_deadline_ms: 10_000,
+            fixed_miniblock_interval_ms: Some(5_000),
+            fixed_interval_empty_miniblocks: false,
+            empty_miniblock_interval_ms: None,
+            max_idle_gap_ms: None,
+            consecutive_empty_miniblocks: 0,
+        };
+
+        let mut manager = create_updates_manager();
+        manager.miniblock.timestamp = seconds_since_epoch() - 10;
+        assert!(!sealer.should_seal_miniblock(&manager));
+        sealer.fixed_interval_empty_miniblocks = true;
+        assert!(
+            sealer.should_seal_miniblock(&manager),
+            "Empty miniblock should be sealed once the interval elapses"
+        );
+
+        apply_tx_to_manager(&mut manager);
+        manager.miniblock.timestamp = seconds_since_epoch();
+        assert!(
+            !sealer.should_seal_miniblock(&manager),
+            "Miniblock shouldn't be sealed before the interval elapses"
+        );
+    }
+
+    #[test]
+    fn empty_miniblock_sealer() {
+        let mut sealer = TimeoutSealer {
+            block_commit_deadline_ms: 10_000,
+            miniblock_commit_deadline_ms: 1_000,
+            fixed_miniblock_interval_ms: None,
+            fixed_interval_empty_miniblocks: false,
+            empty_miniblock_interval_ms: Some(5_000),
+            max_idle_gap_ms: Some(12_000),
+            consecutive_empty_miniblocks: 0,
+        };
+
+        let mut manager = create_updates_manager();
+        manager.miniblock.timestamp = seconds_since_epoch() - 2;
+        assert!(!sealer.should_seal_miniblock(&manager));
+        manager.miniblock.timestamp = seconds_since_epoch() - 6;
+        assert!(sealer.should_seal_miniblock(&manager));
+        assert_eq!(sealer.consecutive_empty_miniblocks, 1);
+
+        // The gap should double for the next empty miniblock...
+        assert!(!sealer.should_seal_miniblock(&manager));
+        manager.miniblock.timestamp = seconds_since_epoch() - 11;
+        assert!(sealer.should_seal_miniblock(&manager));
+        // ...but not exceed the max idle gap.
+        assert_eq!(sealer.empty_miniblock_gap_ms(5_000), 12_000);
+        manager.miniblock.timestamp = seconds_since_epoch() - 13;
+        assert!(sealer.should_seal_miniblock(&manager));
+
+        // A non-empty miniblock resets the gap.
+        apply_tx_to_manager(&mut manager);
+        assert!(sealer.should_seal_miniblock(&manager));
+        assert_eq!(sealer.empty_miniblock_gap_ms(5_000), 5_000);
+    }
 }
@@ -0,0 +1,158 @@
+//! Sealing of L1 batches at the activation point of scheduled protocol upgrades.
+//!
+//! Protocol upgrades proposed on L1 are persisted by `eth_watch` together with their activation timestamp.
+//! The base system contracts for a new L1 batch are chosen based on the batch timestamp, so an upgrade is activated
+//! in the first batch opened after its activation timestamp. [`ProtocolUpgradeSealer`] makes sure that the batch open
+//! at the activation point is sealed right away, so that the upgrade isn't delayed by batches sealed on timeout.
+
+use std::time::Duration;
+
+use zksync_dal::StorageProcessor;
+use zksync_types::ProtocolVersionId;
+use zksync_utils::time::seconds_since_epoch;
+
+use super::{IoSealCriteria, AGGREGATION_METRICS};
+use crate::state_keeper::{extractors, metrics::KEEPER_METRICS, updates::UpdatesManager};
+
+/// Scheduled protocol upgrade.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ScheduledUpgrade {
+    version: ProtocolVersionId,
+    timestamp: u64,
+}
+
+/// Seals L1 batches once the activation timestamp of the next scheduled protocol upgrade is reached.
+#[derive(Debug)]
+pub(in crate::state_keeper) struct ProtocolUpgradeSealer {
+    current_version: ProtocolVersionId,
+    scheduled_upgrade: Option<ScheduledUpgrade>,
+}
+
+impl ProtocolUpgradeSealer {
+    pub fn new(current_version: ProtocolVersionId) -> Self {
+        Self {
+            current_version,
+            scheduled_upgrade: None,
+        }
+    }
+
+    /// Updates the protocol version of the open L1 batch with the specified timestamp and reloads
+    /// the next scheduled upgrade.
+    pub async fn start_batch(
+        &mut self,
+        storage: &mut StorageProcessor<'_>,
+        version: ProtocolVersionId,
+        batch_timestamp: u64,
+    ) {
+        if let Some(upgrade) = self.scheduled_upgrade {
+            if version >= upgrade.version {
+                let delay = batch_timestamp.saturating_sub(upgrade.timestamp);
+                KEEPER_METRICS
+                    .protocol_upgrade_activation_delay
+                    .observe(Duration::from_secs(delay));
+                tracing::info!(
+                    "Protocol version {version:?} is activated {delay}s after the scheduled activation timestamp {}",
+                    extractors::display_timestamp(upgrade.timestamp)
+                );
+            }
+        }
+        self.current_version = version;
+        KEEPER_METRICS.protocol_version.set(version as u64);
+        self.refresh(storage).await;
+    }
+
+    /// Reloads the next scheduled upgrade from Postgres.
+    pub async fn refresh(&mut self, storage: &mut StorageProcessor<'_>) {
+        let scheduled_upgrade = storage
+            .protocol_versions_dal()
+            .next_scheduled_version(self.current_version)
+            .await
+            .map(|(version, timestamp)| ScheduledUpgrade { version, timestamp });
+        if scheduled_upgrade == self.scheduled_upgrade {
+            return;
+        }
+
+        if let Some(upgrade) = scheduled_upgrade {
+            tracing::info!(
+                "Protocol upgrade from version {:?} to {:?} is scheduled at {}",
+                self.current_version,
+                upgrade.version,
+                extractors::display_timestamp(upgrade.timestamp)
+            );
+        }
+        KEEPER_METRICS
+            .scheduled_protocol_upgrade_timestamp
+            .set(scheduled_upgrade.map_or(0, |upgrade| upgrade.timestamp));
+        self.scheduled_upgrade = scheduled_upgrade;
+    }
+}
+
+impl IoSealCriteria for ProtocolUpgradeSealer {
+    fn should_seal_l1_batch_unconditionally(&mut self, manager: &UpdatesManager) -> bool {
+        const RULE_NAME: &str = "protocol_upgrade";
+
+        let Some(upgrade) = self.scheduled_upgrade else {
+            return false;
+        };
+        if manager.pending_executed_transactions_len() == 0
+            || upgrade.version <= manager.protocol_version()
+        {
+            return false;
+        }
+
+        let should_seal = seconds_since_epoch() >= upgrade.timestamp;
+        if should_seal {
+            AGGREGATION_METRICS.inc_criterion(RULE_NAME);
+            tracing::info!(
+                "Decided to seal L1 batch using rule `{RULE_NAME}`; protocol version {:?} is active since {}",
+                upgrade.version,
+                extractors::display_timestamp(upgrade.timestamp)
+            );
+        }
+        should_seal
+    }
+
+    fn should_seal_miniblock(&mut self, _manager: &UpdatesManager) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_types::{block::BlockGasCount, tx::ExecutionMetrics};
+
+    use super::*;
+    use crate::state_keeper::tests::{
+        create_execution_result, create_transaction, create_updates_manager,
+    };
+
+    #[test]
+    fn protocol_upgrade_sealer() {
+        let mut sealer = ProtocolUpgradeSealer::new(ProtocolVersionId::latest());
+        let mut manager = create_updates_manager();
+        assert!(!sealer.should_seal_l1_batch_unconditionally(&manager));
+
+        sealer.scheduled_upgrade = Some(ScheduledUpgrade {
+            version: ProtocolVersionId::next(),
+            timestamp: seconds_since_epoch() - 1,
+        });
+        // Empty batches are never sealed.
+        assert!(!sealer.should_seal_l1_batch_unconditionally(&manager));
+
+        manager.extend_from_executed_transaction(
+            create_transaction(10, 100),
+            create_execution_result(0, []),
+            vec![],
+            BlockGasCount::default(),
+            ExecutionMetrics::default(),
+            vec![],
+        );
+        assert!(sealer.should_seal_l1_batch_unconditionally(&manager));
+
+        sealer.scheduled_upgrade = Some(ScheduledUpgrade {
+            version: ProtocolVersionId::next(),
+            timestamp: seconds_since_epoch() + 3_600,
+        });
+        assert!(!sealer.should_seal_l1_batch_unconditionally(&manager));
+    }
+}
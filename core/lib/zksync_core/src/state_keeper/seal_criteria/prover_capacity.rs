@@ -0,0 +1,179 @@
+//! Adaptive sealing of L1 batches based on the capacity of the proving pipeline.
+//!
+//! The length of the proving queue (i.e., the number of L1 batches for which proof generation data is produced,
+//! but proofs are not generated yet) is fed back to the state keeper by [`ProvingQueueMonitor`].
+//! [`ProverCapacitySealer`] uses it to seal batches early when the proving pipeline is saturated. If the pipeline
+//! has headroom, batches are only limited by the static seal criteria, i.e., are as large as possible.
+
+use std::time::Duration;
+
+use anyhow::Context as _;
+use tokio::sync::watch;
+use zksync_config::configs::chain::StateKeeperConfig;
+use zksync_dal::ConnectionPool;
+
+use super::{criteria::MAX_CIRCUITS_PER_BATCH, IoSealCriteria, AGGREGATION_METRICS};
+use crate::state_keeper::{metrics::KEEPER_METRICS, updates::UpdatesManager};
+
+/// Interval between polls of the proving queue length.
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Periodically loads the length of the proving queue from Postgres and reports it to [`ProverCapacitySealer`]s.
+#[derive(Debug)]
+pub struct ProvingQueueMonitor {
+    pool: ConnectionPool,
+    queue_len_sender: watch::Sender<usize>,
+}
+
+impl ProvingQueueMonitor {
+    pub fn new(pool: ConnectionPool) -> Self {
+        Self {
+            pool,
+            queue_len_sender: watch::channel(0).0,
+        }
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<usize> {
+        self.queue_len_sender.subscribe()
+    }
+
+    pub async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        while !*stop_receiver.borrow_and_update() {
+            let mut storage = self.pool.access_storage_tagged("state_keeper").await?;
+            let queue_len = storage
+                .proof_generation_dal()
+                .get_proving_queue_len()
+                .await
+                .context("failed getting proving queue length")?;
+            drop(storage);
+
+            KEEPER_METRICS.proving_queue_len.set(queue_len);
+            self.queue_len_sender.send_replace(queue_len);
+            // Error here corresponds to a timeout w/o `stop_receiver` changed; we're OK with this.
+            tokio::time::timeout(POLL_INTERVAL, stop_receiver.changed())
+                .await
+                .ok();
+        }
+        tracing::info!("Stop signal received, proving queue monitor is shutting down");
+        Ok(())
+    }
+}
+
+/// Seals L1 batches once their estimated number of circuits reaches the target depending on the proving queue
+/// length. The target linearly decreases from the static limit (for the empty queue) to `min_batch_circuits_fraction`
+/// of the static limit (for the queue of `saturated_proving_queue_len` batches or longer).
+#[derive(Debug)]
+pub(in crate::state_keeper) struct ProverCapacitySealer {
+    proving_queue_len: watch::Receiver<usize>,
+    saturated_queue_len: usize,
+    min_batch_fraction: f64,
+    max_circuits: f64,
+}
+
+impl ProverCapacitySealer {
+    /// Creates a sealer if adaptive sealing is enabled in the `config`.
+    pub fn new(
+        config: &StateKeeperConfig,
+        proving_queue_len: watch::Receiver<usize>,
+    ) -> Option<Self> {
+        let saturated_queue_len = config.saturated_proving_queue_len?;
+        assert!(
+            saturated_queue_len > 0,
+            "Saturated proving queue length must be positive"
+        );
+        let min_batch_fraction = config.min_batch_circuits_fraction();
+        assert!(
+            min_batch_fraction > 0.0 && min_batch_fraction <= 1.0,
+            "Minimum fraction of batch circuits must be in (0, 1]"
+        );
+
+        Some(Self {
+            proving_queue_len,
+            saturated_queue_len,
+            min_batch_fraction,
+            max_circuits: MAX_CIRCUITS_PER_BATCH as f64 * config.close_block_at_geometry_percentage,
+        })
+    }
+
+    /// Returns the fraction of the static circuits limit that L1 batches should be sealed at.
+    fn batch_fraction(&self) -> f64 {
+        let queue_len = *self.proving_queue_len.borrow();
+        let saturation = (queue_len as f64 / self.saturated_queue_len as f64).min(1.0);
+        1.0 - (1.0 - self.min_batch_fraction) * saturation
+    }
+}
+
+impl IoSealCriteria for ProverCapacitySealer {
+    fn should_seal_l1_batch_unconditionally(&mut self, manager: &UpdatesManager) -> bool {
+        const RULE_NAME: &str = "prover_capacity";
+
+        let batch_fraction = self.batch_fraction();
+        if manager.pending_executed_transactions_len() == 0 || batch_fraction >= 1.0 {
+            return false;
+        }
+
+        let circuits = manager.pending_execution_metrics().estimated_circuits_used as f64;
+        let target_circuits = self.max_circuits * batch_fraction;
+        let should_seal = circuits >= target_circuits;
+        if should_seal {
+            AGGREGATION_METRICS.inc_criterion(RULE_NAME);
+            tracing::debug!(
+                "Decided to seal L1 batch using rule `{RULE_NAME}`; estimated circuits: {circuits}, \
+                 target circuits: {target_circuits} (proving queue length: {})",
+                *self.proving_queue_len.borrow()
+            );
+        }
+        should_seal
+    }
+
+    fn should_seal_miniblock(&mut self, _manager: &UpdatesManager) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_types::{block::BlockGasCount, tx::ExecutionMetrics};
+
+    use super::*;
+    use crate::state_keeper::tests::{
+        create_execution_result, create_transaction, create_updates_manager,
+    };
+
+    #[test]
+    fn prover_capacity_sealer() {
+        let config = StateKeeperConfig {
+            saturated_proving_queue_len: Some(10),
+            min_batch_circuits_fraction: Some(0.2),
+            close_block_at_geometry_percentage: 1.0,
+            ..StateKeeperConfig::for_tests()
+        };
+        let (queue_len_sender, queue_len_receiver) = watch::channel(0);
+        let mut sealer = ProverCapacitySealer::new(&config, queue_len_receiver).unwrap();
+        assert_eq!(sealer.batch_fraction(), 1.0);
+
+        let mut manager = create_updates_manager();
+        let execution_metrics = ExecutionMetrics {
+            estimated_circuits_used: MAX_CIRCUITS_PER_BATCH as f32 * 0.5,
+            ..ExecutionMetrics::default()
+        };
+        manager.extend_from_executed_transaction(
+            create_transaction(10, 100),
+            create_execution_result(0, []),
+            vec![],
+            BlockGasCount::default(),
+            execution_metrics,
+            vec![],
+        );
+        // The pipeline has headroom, so only static criteria apply.
+        assert!(!sealer.should_seal_l1_batch_unconditionally(&manager));
+
+        queue_len_sender.send_replace(5);
+        assert!((sealer.batch_fraction() - 0.6).abs() < 1e-9);
+        assert!(!sealer.should_seal_l1_batch_unconditionally(&manager));
+
+        queue_len_sender.send_replace(100);
+        assert!((sealer.batch_fraction() - 0.2).abs() < 1e-9);
+        assert!(sealer.should_seal_l1_batch_unconditionally(&manager));
+    }
+}
@@ -0,0 +1,40 @@
+use std::sync::Arc;
+
+use tokio::sync::watch;
+
+/// Handle allowing to stop and resume processing of transactions and sealing of miniblocks / L1 batches
+/// by the state keeper at runtime. Cloned handles refer to the same state.
+#[derive(Debug, Clone)]
+pub struct SealingControl {
+    sender: Arc<watch::Sender<bool>>,
+}
+
+impl Default for SealingControl {
+    fn default() -> Self {
+        Self {
+            sender: Arc::new(watch::channel(false).0),
+        }
+    }
+}
+
+impl SealingControl {
+    /// Stops sealing. Returns `false` if sealing was already stopped.
+    pub fn stop(&self) -> bool {
+        self.sender
+            .send_if_modified(|stopped| !std::mem::replace(stopped, true))
+    }
+
+    /// Resumes sealing. Returns `false` if sealing was not stopped.
+    pub fn resume(&self) -> bool {
+        self.sender
+            .send_if_modified(|stopped| std::mem::replace(stopped, false))
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        *self.sender.borrow()
+    }
+
+    pub(super) fn subscribe(&self) -> watch::Receiver<bool> {
+        self.sender.subscribe()
+    }
+}
@@ -29,8 +29,8 @@ use zksync_types::{
 mod tester;
 
 use self::tester::{
-    bootloader_tip_out_of_gas, pending_batch_data, random_tx, rejected_exec, successful_exec,
-    successful_exec_with_metrics, TestScenario,
+    bootloader_tip_out_of_gas, pending_batch_data, random_bundle, random_tx, rejected_exec,
+    successful_exec, successful_exec_with_metrics, TestScenario,
 };
 pub(crate) use self::tester::{MockBatchExecutorBuilder, TestBatchExecutorBuilder};
 use crate::{
@@ -346,6 +346,87 @@ async fn rejected_tx() {
         .await;
 }
 
+#[tokio::test]
+async fn bundle_is_included_into_single_miniblock() {
+    let config = StateKeeperConfig {
+        transaction_slots: 3,
+        ..StateKeeperConfig::default()
+    };
+    let sealer = SequencerSealer::with_sealers(config, vec![Box::new(SlotsCriterion)]);
+
+    let bundle = random_bundle(&[1, 2]);
+    TestScenario::new()
+        .seal_miniblock_when(|updates| !updates.miniblock.executed_transactions.is_empty())
+        .next_bundle("Bundle", bundle, vec![successful_exec(), successful_exec()])
+        .miniblock_sealed_with("Miniblock with bundle", |updates| {
+            assert_eq!(updates.miniblock.executed_transactions.len(), 2);
+        })
+        .next_tx("Tx after bundle", random_tx(3), successful_exec())
+        .miniblock_sealed("Miniblock with tx")
+        .batch_sealed("Batch with bundle and tx")
+        .run(sealer)
+        .await;
+}
+
+#[tokio::test]
+async fn rejected_bundle() {
+    let config = StateKeeperConfig {
+        transaction_slots: 2,
+        ..StateKeeperConfig::default()
+    };
+    let sealer = SequencerSealer::with_sealers(config, vec![Box::new(SlotsCriterion)]);
+
+    let bundle = random_bundle(&[1, 2]);
+    TestScenario::new()
+        .seal_miniblock_when(|updates| updates.miniblock.executed_transactions.len() == 1)
+        .next_bundle(
+            "Bundle with unexecutable tx",
+            bundle.clone(),
+            vec![successful_exec(), rejected_exec()],
+        )
+        .bundle_rejected("Bundle got rejected", bundle, None)
+        .next_tx("Successful tx", random_tx(3), successful_exec())
+        .miniblock_sealed("Miniblock with successful tx")
+        .next_tx("Second successful tx", random_tx(4), successful_exec())
+        .miniblock_sealed("Second miniblock")
+        .batch_sealed("Batch with 2 successful txs")
+        .run(sealer)
+        .await;
+}
+
+#[tokio::test]
+async fn bundle_not_fitting_into_batch_is_rolled_back() {
+    let config = StateKeeperConfig {
+        transaction_slots: 2,
+        ..StateKeeperConfig::default()
+    };
+    let sealer = SequencerSealer::with_sealers(config, vec![Box::new(SlotsCriterion)]);
+
+    let bundle = random_bundle(&[2, 3]);
+    TestScenario::new()
+        .seal_miniblock_when(|updates| updates.miniblock.executed_transactions.len() == 1)
+        .next_tx("First tx", random_tx(1), successful_exec())
+        .miniblock_sealed("Miniblock with 1st tx")
+        .next_bundle(
+            "Bundle not fitting into batch",
+            bundle.clone(),
+            vec![successful_exec()],
+        )
+        .bundle_rollback("Bundle rolled back to seal the batch", bundle.clone())
+        .batch_sealed("Batch sealed with 1 tx")
+        .next_bundle(
+            "Same bundle now succeeds",
+            bundle,
+            vec![successful_exec(), successful_exec()],
+        )
+        .miniblock_sealed_with("Miniblock with bundle", |updates| {
+            assert_eq!(updates.miniblock.executed_transactions.len(), 2);
+        })
+        .batch_sealed("Batch with bundle")
+        .run(sealer)
+        .await;
+}
+
 #[tokio::test]
 async fn bootloader_tip_out_of_gas_flow() {
     let config = StateKeeperConfig {
@@ -15,9 +15,9 @@ use multivm::{
 };
 use tokio::sync::{mpsc, watch};
 use zksync_types::{
-    block::MiniblockExecutionData, fee_model::BatchFeeInput, protocol_version::ProtocolUpgradeTx,
-    witness_block_state::WitnessBlockState, Address, L1BatchNumber, L2ChainId, MiniblockNumber,
-    ProtocolVersionId, Transaction, H256,
+    block::MiniblockExecutionData, fee_model::BatchFeeInput, l2::bundle::TransactionBundle,
+    protocol_version::ProtocolUpgradeTx, witness_block_state::WitnessBlockState, Address,
+    L1BatchNumber, L2ChainId, MiniblockNumber, ProtocolVersionId, Transaction, H256,
 };
 
 use crate::{
@@ -133,6 +133,42 @@ impl TestScenario {
         self
     }
 
+    /// Expect the state keeper to request a bundle from IO. Results are provided for bundle transactions
+    /// that are expected to be executed, in the bundle order.
+    pub(crate) fn next_bundle(
+        mut self,
+        description: &'static str,
+        bundle: TransactionBundle,
+        results: Vec<TxExecutionResult>,
+    ) -> Self {
+        self.actions
+            .push_back(ScenarioItem::Bundle(description, bundle, results));
+        self
+    }
+
+    /// Expect the state keeper to rollback the bundle (i.e. return to the mempool).
+    pub(crate) fn bundle_rollback(
+        mut self,
+        description: &'static str,
+        bundle: TransactionBundle,
+    ) -> Self {
+        self.actions
+            .push_back(ScenarioItem::BundleRollback(description, bundle));
+        self
+    }
+
+    /// Expect the state keeper to reject the bundle. `err` has the same meaning as in [`Self::tx_rejected()`].
+    pub(crate) fn bundle_rejected(
+        mut self,
+        description: &'static str,
+        bundle: TransactionBundle,
+        err: Option<String>,
+    ) -> Self {
+        self.actions
+            .push_back(ScenarioItem::BundleReject(description, bundle, err));
+        self
+    }
+
     /// Expects the miniblock to be sealed.
     pub(crate) fn miniblock_sealed(mut self, description: &'static str) -> Self {
         self.actions
@@ -233,6 +269,15 @@ pub(crate) fn random_tx(tx_number: u64) -> Transaction {
     tx.into()
 }
 
+/// Creates a bundle of random transactions. Provided tx numbers are used as transaction hashes.
+pub(crate) fn random_bundle(tx_numbers: &[u64]) -> TransactionBundle {
+    let transactions = tx_numbers
+        .iter()
+        .map(|&tx_number| random_tx(tx_number).try_into().unwrap())
+        .collect();
+    TransactionBundle::new(transactions)
+}
+
 /// Creates a `TxExecutionResult` object denoting a successful tx execution.
 pub(crate) fn successful_exec() -> TxExecutionResult {
     TxExecutionResult::Success {
@@ -329,6 +374,9 @@ enum ScenarioItem {
     Tx(&'static str, Transaction, TxExecutionResult),
     Rollback(&'static str, Transaction),
     Reject(&'static str, Transaction, Option<String>),
+    Bundle(&'static str, TransactionBundle, Vec<TxExecutionResult>),
+    BundleRollback(&'static str, TransactionBundle),
+    BundleReject(&'static str, TransactionBundle, Option<String>),
     MiniblockSeal(
         &'static str,
         Option<Box<dyn FnOnce(&UpdatesManager) + Send>>,
@@ -362,6 +410,23 @@ impl std::fmt::Debug for ScenarioItem {
                 .field(tx)
                 .field(err)
                 .finish(),
+            Self::Bundle(descr, bundle, results) => f
+                .debug_tuple("Bundle")
+                .field(descr)
+                .field(&bundle.hash)
+                .field(results)
+                .finish(),
+            Self::BundleRollback(descr, bundle) => f
+                .debug_tuple("BundleRollback")
+                .field(descr)
+                .field(&bundle.hash)
+                .finish(),
+            Self::BundleReject(descr, bundle, err) => f
+                .debug_tuple("BundleReject")
+                .field(descr)
+                .field(&bundle.hash)
+                .field(err)
+                .finish(),
             Self::MiniblockSeal(descr, _) => f.debug_tuple("MiniblockSeal").field(descr).finish(),
             Self::BatchSeal(descr, _) => f.debug_tuple("BatchSeal").field(descr).finish(),
         }
@@ -420,6 +485,18 @@ impl TestBatchExecutorBuilder {
                 ScenarioItem::Reject(_, tx, _) => {
                     rollback_set.insert(tx.hash());
                 }
+                ScenarioItem::Bundle(_, bundle, results) => {
+                    for (tx, result) in bundle.transactions.iter().zip(results) {
+                        batch_txs
+                            .entry(tx.hash())
+                            .or_insert_with(VecDeque::new)
+                            .push_back(result.clone());
+                    }
+                }
+                ScenarioItem::BundleRollback(_, bundle)
+                | ScenarioItem::BundleReject(_, bundle, _) => {
+                    rollback_set.extend(bundle.transactions.iter().map(|tx| tx.hash()));
+                }
                 ScenarioItem::BatchSeal(_, _) => txs.push_back(std::mem::take(&mut batch_txs)),
                 _ => {}
             }
@@ -510,7 +587,7 @@ impl TestBatchExecutor {
                     resp.send(result).unwrap();
                     self.last_tx = tx.hash();
                 }
-                Command::StartNextMiniblock(_, resp) => {
+                Command::PrefetchStorage(_, resp) | Command::StartNextMiniblock(_, resp) => {
                     resp.send(()).unwrap();
                 }
                 Command::RollbackLastTx(resp) => {
@@ -525,8 +602,8 @@ impl TestBatchExecutor {
                         )
                     }
                     resp.send(()).unwrap();
-                    // It's OK to not update `last_executed_tx`, since state keeper never should rollback more than 1
-                    // tx in a row, and it's going to cause a panic anyway.
+                    // It's OK to not update `last_executed_tx`: state keeper only rolls back more than 1 tx in a row
+                    // for bundles, and all bundle transactions are added to the rollback set.
                 }
                 Command::FinishBatch(resp) => {
                     // Blanket result, it doesn't really matter.
@@ -719,6 +796,50 @@ impl StateKeeperIO for TestIO {
         self.skipping_txs = false;
     }
 
+    fn next_bundle(&mut self) -> Option<TransactionBundle> {
+        if !matches!(
+            self.scenario.actions.front(),
+            Some(ScenarioItem::Bundle(..))
+        ) {
+            return None;
+        }
+        let ScenarioItem::Bundle(_, bundle, _) = self.pop_next_item("next_bundle") else {
+            unreachable!();
+        };
+        Some(bundle)
+    }
+
+    async fn rollback_bundle(&mut self, bundle: TransactionBundle) {
+        let action = self.pop_next_item("rollback_bundle");
+        let ScenarioItem::BundleRollback(_, expected_bundle) = action else {
+            panic!("Unexpected action: {:?}", action);
+        };
+        assert_eq!(
+            bundle.hash, expected_bundle.hash,
+            "Incorrect bundle has been rolled back"
+        );
+        self.skipping_txs = false;
+    }
+
+    async fn reject_bundle(&mut self, bundle: &TransactionBundle, error: &str) {
+        let action = self.pop_next_item("reject_bundle");
+        let ScenarioItem::BundleReject(_, expected_bundle, expected_err) = action else {
+            panic!("Unexpected action: {:?}", action);
+        };
+        assert_eq!(
+            bundle.hash, expected_bundle.hash,
+            "Incorrect bundle has been rejected"
+        );
+        if let Some(expected_err) = expected_err {
+            assert!(
+                error.contains(&expected_err),
+                "Bundle was rejected with an unexpected error. Expected part was {expected_err}, \
+                 but the actual error was {error}"
+            );
+        }
+        self.skipping_txs = false;
+    }
+
     async fn seal_miniblock(&mut self, updates_manager: &UpdatesManager) {
         let action = self.pop_next_item("seal_miniblock");
         let ScenarioItem::MiniblockSeal(_, check_fn) = action else {
@@ -789,7 +910,9 @@ impl L1BatchExecutorBuilder for MockBatchExecutorBuilder {
             while let Some(cmd) = recv.recv().await {
                 match cmd {
                     Command::ExecuteTx(_, resp) => resp.send(successful_exec()).unwrap(),
-                    Command::StartNextMiniblock(_, resp) => resp.send(()).unwrap(),
+                    Command::PrefetchStorage(_, resp) | Command::StartNextMiniblock(_, resp) => {
+                        resp.send(()).unwrap()
+                    }
                     Command::RollbackLastTx(_) => panic!("unexpected rollback"),
                     Command::FinishBatch(resp) => {
                         // Blanket result, it doesn't really matter.
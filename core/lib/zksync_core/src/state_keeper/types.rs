@@ -1,12 +1,13 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     sync::{Arc, Mutex},
 };
 
 use multivm::interface::VmExecutionResultAndLogs;
 use zksync_mempool::{L2TxFilter, MempoolInfo, MempoolStore};
 use zksync_types::{
-    block::BlockGasCount, tx::ExecutionMetrics, Address, Nonce, PriorityOpId, Transaction,
+    block::BlockGasCount, l2::bundle::TransactionBundle, tx::ExecutionMetrics, Address, Nonce,
+    PriorityOpId, Transaction, H256,
 };
 
 use super::metrics::StateKeeperGauges;
@@ -42,6 +43,20 @@ impl MempoolGuard {
             .next_transaction(filter)
     }
 
+    pub fn has_next_l1_transaction(&self) -> bool {
+        self.0
+            .lock()
+            .expect("failed to acquire mempool lock")
+            .has_next_l1_transaction()
+    }
+
+    pub fn next_l2_transaction(&mut self, filter: &L2TxFilter) -> Option<Transaction> {
+        self.0
+            .lock()
+            .expect("failed to acquire mempool lock")
+            .next_l2_transaction(filter)
+    }
+
     pub fn rollback(&mut self, rejected: &Transaction) {
         self.0
             .lock()
@@ -49,6 +64,41 @@ impl MempoolGuard {
             .rollback(rejected);
     }
 
+    pub fn insert_bundles(&mut self, bundles: Vec<TransactionBundle>) {
+        self.0
+            .lock()
+            .expect("failed to acquire mempool lock")
+            .insert_bundles(bundles);
+    }
+
+    pub fn next_bundle(&mut self, filter: &L2TxFilter) -> Option<TransactionBundle> {
+        self.0
+            .lock()
+            .expect("failed to acquire mempool lock")
+            .next_bundle(filter)
+    }
+
+    pub fn rollback_bundle(&mut self, bundle: &TransactionBundle) {
+        self.0
+            .lock()
+            .expect("failed to acquire mempool lock")
+            .rollback_bundle(bundle);
+    }
+
+    pub fn return_bundle(&mut self, bundle: TransactionBundle) {
+        self.0
+            .lock()
+            .expect("failed to acquire mempool lock")
+            .return_bundle(bundle);
+    }
+
+    pub fn remove_accounts(&mut self, accounts: &HashSet<Address>) -> HashSet<H256> {
+        self.0
+            .lock()
+            .expect("failed to acquire mempool lock")
+            .remove_accounts(accounts)
+    }
+
     pub fn get_mempool_info(&mut self) -> MempoolInfo {
         self.0
             .lock()
@@ -146,6 +146,17 @@ impl MiniblockUpdates {
         });
     }
 
+    /// Returns the total gas used by transactions in the miniblock (i.e., the gas limit minus refunds).
+    pub(crate) fn gas_used(&self) -> u64 {
+        self.executed_transactions
+            .iter()
+            .map(|tx| {
+                let gas_limit = tx.transaction.gas_limit().low_u64();
+                gas_limit.saturating_sub(tx.refunded_gas.into())
+            })
+            .sum()
+    }
+
     /// Calculates miniblock hash based on the protocol version.
     pub(crate) fn get_miniblock_hash(&self) -> H256 {
         let mut digest = MiniblockHasher::new(
@@ -95,6 +95,7 @@ impl UpdatesManager {
             protocol_version: Some(self.protocol_version),
             l2_erc20_bridge_addr,
             pre_insert_txs,
+            dynamic_l2_gas_price: None,
         }
     }
 
@@ -184,6 +185,9 @@ pub(crate) struct MiniblockSealCommand {
     /// Should be set to `true` for EN's IO as EN doesn't store transactions in DB
     /// before they are included into miniblocks.
     pub pre_insert_txs: bool,
+    /// Congestion-based minimal L2 gas price adjusted after this miniblock. If set, it's persisted together
+    /// with the miniblock, so that the price is restored after a restart and can be followed by the API server.
+    pub dynamic_l2_gas_price: Option<u64>,
 }
 
 #[cfg(test)]
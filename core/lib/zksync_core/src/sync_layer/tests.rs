@@ -7,7 +7,7 @@ use std::{
 };
 
 use tokio::{sync::watch, task::JoinHandle};
-use zksync_config::configs::chain::NetworkConfig;
+use zksync_config::configs::{api::Web3JsonRpcConfig, chain::NetworkConfig};
 use zksync_dal::{ConnectionPool, StorageProcessor};
 use zksync_types::{
     Address, L1BatchNumber, L2ChainId, MiniblockNumber, ProtocolVersionId, Transaction, H256,
@@ -489,8 +489,13 @@ async fn fetcher_with_real_server() {
     // Start the API server.
     let network_config = NetworkConfig::for_tests();
     let (stop_sender, stop_receiver) = watch::channel(false);
-    let server_handles =
-        spawn_http_server(&network_config, pool.clone(), stop_receiver.clone()).await;
+    let server_handles = spawn_http_server(
+        &network_config,
+        Web3JsonRpcConfig::for_tests(),
+        pool.clone(),
+        stop_receiver.clone(),
+    )
+    .await;
     server_handles.wait_until_ready().await;
     let server_addr = &server_handles.local_addr;
 
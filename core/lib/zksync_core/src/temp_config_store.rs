@@ -7,8 +7,9 @@ use zksync_config::{
         },
         fri_prover_group::FriProverGroupConfig,
         house_keeper::HouseKeeperConfig,
-        FriProofCompressorConfig, FriProverConfig, FriWitnessGeneratorConfig, PrometheusConfig,
-        ProofDataHandlerConfig, WitnessGeneratorConfig,
+        DADispatcherConfig, DbPrunerConfig, FriProofCompressorConfig, FriProverConfig,
+        FriWitnessGeneratorConfig, PrometheusConfig, ProofDataHandlerConfig,
+        WitnessGeneratorConfig,
     },
     ApiConfig, ContractsConfig, DBConfig, ETHClientConfig, ETHSenderConfig, ETHWatchConfig,
     GasAdjusterConfig, ObjectStoreConfig, PostgresConfig,
@@ -44,4 +45,8 @@ pub struct TempConfigStore {
     pub eth_watch_config: Option<ETHWatchConfig>,
     pub gas_adjuster_config: Option<GasAdjusterConfig>,
     pub object_store_config: Option<ObjectStoreConfig>,
+    pub da_dispatcher_config: Option<DADispatcherConfig>,
+    /// Config of the object store used as a data availability layer by the DA dispatcher.
+    pub da_object_store_config: Option<ObjectStoreConfig>,
+    pub db_pruner_config: Option<DbPrunerConfig>,
 }
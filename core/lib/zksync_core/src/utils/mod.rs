@@ -3,7 +3,9 @@
 use std::time::Duration;
 
 use tokio::sync::watch;
+use zksync_config::configs::database::RocksdbCompactionStyle;
 use zksync_dal::ConnectionPool;
+use zksync_storage::CompactionStyle;
 use zksync_types::L1BatchNumber;
 
 #[cfg(test)]
@@ -73,6 +75,14 @@ pub(crate) async fn wait_for_l1_batch_with_metadata(
     }
 }
 
+/// Converts the RocksDB compaction style from the configuration.
+pub(crate) fn rocksdb_compaction_style(style: RocksdbCompactionStyle) -> CompactionStyle {
+    match style {
+        RocksdbCompactionStyle::Level => CompactionStyle::Level,
+        RocksdbCompactionStyle::Universal => CompactionStyle::Universal,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use zksync_types::L2ChainId;
@@ -774,7 +774,7 @@ impl Checker {
             .build();
 
         let main_node_logs = match self.main_node_client.get_logs(filter.clone()).await {
-            Ok(logs) => logs,
+            Ok(logs) => logs.into_logs(),
             Err(e) => {
                 // TODO(BFT-192): Be more specific with checking logs
                 tracing::error!("Failed to get logs from main node: {}", e);
@@ -782,7 +782,7 @@ impl Checker {
             }
         };
         let instance_logs = match instance_client.client.get_logs(filter).await {
-            Ok(logs) => logs,
+            Ok(logs) => logs.into_logs(),
             Err(e) => {
                 // TODO(BFT-192): Be more specific with checking logs
                 tracing::error!("Failed to get logs from instance: {}", e);
@@ -55,6 +55,86 @@ describe('Debug methods', () => {
         expect(txCallTrace).toEqual(expected);
     });
 
+    test('Should abort tracing once the tracer limits are exceeded', async () => {
+        const tx = { to: bob.address, data: '0x' };
+        await expect(
+            testMaster.mainAccount().provider.send('debug_traceCall', [tx, 'latest', { memoryLimit: 1 }])
+        ).rejects.toThrow('trace memory limit of 1 bytes exceeded');
+        await expect(
+            testMaster.mainAccount().provider.send('debug_traceCall', [tx, 'latest', { timeout: 'soon' }])
+        ).rejects.toThrow('Invalid tracer config');
+
+        // Generous limits must not affect the trace.
+        const trace = await testMaster
+            .mainAccount()
+            .provider.send('debug_traceCall', [tx, 'latest', { timeout: '1m', memoryLimit: 1 << 30 }]);
+        expect(trace.type).toEqual('Call');
+    });
+
+    test('Should support 4byteTracer', async () => {
+        const receipt = await aliceErc20.transfer(bob.address, 1).then((tx: any) => tx.wait());
+        const transferKey = `${aliceErc20.interface.getSighash('transfer')}-64`;
+
+        const trace = await testMaster
+            .mainAccount()
+            .provider.send('debug_traceTransaction', [receipt.transactionHash, { tracer: '4byteTracer' }]);
+        expect(trace[transferKey]).toEqual(1);
+
+        const callData = aliceErc20.interface.encodeFunctionData('transfer', [bob.address, 1]);
+        const callTrace = await testMaster
+            .mainAccount()
+            .provider.send('debug_traceCall', [
+                { from: alice.address, to: aliceErc20.address, data: callData },
+                'latest',
+                { tracer: '4byteTracer' }
+            ]);
+        expect(callTrace[transferKey]).toEqual(1);
+    });
+
+    test('Should trace dependent calls with debug_traceCallMany', async () => {
+        const balance = await aliceErc20.balanceOf(alice.address);
+        const transferAll = aliceErc20.interface.encodeFunctionData('transfer', [bob.address, balance]);
+        const transferOne = aliceErc20.interface.encodeFunctionData('transfer', [bob.address, 1]);
+        const traces = await testMaster.mainAccount().provider.send('debug_traceCallMany', [
+            [
+                { from: alice.address, to: aliceErc20.address, data: transferAll },
+                { from: alice.address, to: aliceErc20.address, data: transferOne }
+            ],
+            'latest'
+        ]);
+
+        expect(traces.length).toEqual(2);
+        expect(traces[0].revertReason).toBeNull();
+        // The second transfer must observe the balance drained by the first one.
+        expect(traces[1].revertReason).toEqual(expect.any(String));
+    });
+
+    test('Should support custom JavaScript tracers', async () => {
+        const tracer = `{
+            steps: 0,
+            calls: 0,
+            step: function() { this.steps++; },
+            enter: function() { this.calls++; },
+            result: function(ctx) { return { steps: this.steps, calls: this.calls, type: ctx.type }; }
+        }`;
+        const tx = await aliceErc20.transfer(bob.address, 1).then((tx: any) => tx.wait());
+        const trace = await testMaster
+            .mainAccount()
+            .provider.send('debug_traceTransaction', [tx.transactionHash, { tracer }]);
+        expect(trace.steps).toBeGreaterThan(0);
+        expect(trace.calls).toBeGreaterThan(0);
+        expect(trace.type).toEqual('CALL');
+
+        const callTrace = await testMaster
+            .mainAccount()
+            .provider.send('debug_traceCall', [{ to: bob.address, data: '0x' }, 'latest', { tracer }]);
+        expect(callTrace.steps).toBeGreaterThan(0);
+
+        await expect(
+            testMaster.mainAccount().provider.send('debug_traceCall', [{ to: bob.address }, 'latest', { tracer: '{' }])
+        ).rejects.toThrow('JavaScript tracer failed');
+    });
+
     test('Debug sending erc20 token in a block', async () => {
         const value = ethers.BigNumber.from(200);
         await aliceErc20.transfer(bob.address, value).then((tx: any) => tx.wait());
@@ -100,12 +180,147 @@ describe('Debug methods', () => {
             value: '0x0',
             calls: expect.any(Array)
         };
-        let txCallTrace = await testMaster.mainAccount().provider.send('debug_traceTransaction', [tx.hash]);
-        let txCallTrace_tracer = await testMaster
+        let txCallTrace = await testMaster
             .mainAccount()
             .provider.send('debug_traceTransaction', [tx.hash, { tracer: 'callTracer' }]);
         expect(txCallTrace).toEqual(expected);
-        expect(txCallTrace).toEqual(txCallTrace_tracer);
+    });
+
+    test('Should return struct logs for a transaction by default', async () => {
+        const value = ethers.BigNumber.from(200);
+        const tx = await aliceErc20.transfer(bob.address, value);
+        await tx.wait();
+
+        const trace = await testMaster.mainAccount().provider.send('debug_traceTransaction', [tx.hash]);
+        expect(trace).toEqual({
+            gas: expect.any(Number),
+            failed: false,
+            returnValue: expect.any(String),
+            structLogs: expect.any(Array)
+        });
+        expect(trace.structLogs.length).toBeGreaterThan(0);
+        expect(trace.structLogs[0]).toEqual({
+            address: expect.any(String),
+            pc: expect.any(Number),
+            op: expect.any(String),
+            gas: expect.any(Number),
+            gasCost: expect.any(Number),
+            depth: expect.any(Number),
+            stack: expect.any(Array)
+        });
+
+        const limitedTrace = await testMaster
+            .mainAccount()
+            .provider.send('debug_traceTransaction', [tx.hash, { disableStack: true, limit: 10 }]);
+        expect(limitedTrace.structLogs.length).toEqual(10);
+        expect(limitedTrace.structLogs[0].stack).toBeUndefined();
+    });
+
+    test('Should return prestate for a transaction', async () => {
+        const value = ethers.BigNumber.from(200);
+        const tx = await aliceErc20.transfer(bob.address, value);
+        await tx.wait();
+
+        const prestate = await testMaster
+            .mainAccount()
+            .provider.send('debug_traceTransaction', [tx.hash, { tracer: 'prestateTracer' }]);
+        const aliceState = prestate[alice.address.toLowerCase()];
+        expect(aliceState).toEqual({
+            balance: expect.any(String),
+            nonce: expect.any(Number)
+        });
+        const tokenState = prestate[tokenDetails.l2Address.toLowerCase()];
+        expect(tokenState.code).toEqual(expect.stringMatching(/^0x[0-9a-f]+$/));
+        expect(Object.keys(tokenState.storage).length).toBeGreaterThan(0);
+    });
+
+    test('Should return prestate diff for a transaction', async () => {
+        const value = ethers.BigNumber.from(200);
+        const tx = await aliceErc20.transfer(bob.address, value);
+        await tx.wait();
+
+        const diff = await testMaster
+            .mainAccount()
+            .provider.send('debug_traceTransaction', [
+                tx.hash,
+                { tracer: 'prestateTracer', tracerConfig: { diffMode: true } }
+            ]);
+        expect(diff).toEqual({ pre: expect.any(Object), post: expect.any(Object) });
+        const tokenAddress = tokenDetails.l2Address.toLowerCase();
+        const preSlots = Object.keys(diff.pre[tokenAddress].storage);
+        const postSlots = Object.keys(diff.post[tokenAddress].storage);
+        expect(postSlots.sort()).toEqual(preSlots.sort());
+        expect(postSlots.length).toBeGreaterThan(0);
+        // Token code is unchanged, so it must be omitted from the post state.
+        expect(diff.post[tokenAddress].code).toBeUndefined();
+    });
+
+    test('Should return raw transaction, block and receipts', async () => {
+        const tx = await aliceErc20.transfer(bob.address, 1);
+        const receipt = await tx.wait();
+        const provider = testMaster.mainAccount().provider;
+
+        const rawTx = await provider.send('debug_getRawTransaction', [tx.hash]);
+        expect(rawTx).toEqual(expect.stringMatching(/^0x[0-9a-f]+$/));
+        const missingTx = await provider.send('debug_getRawTransaction', [ethers.constants.HashZero]);
+        expect(missingTx).toBeNull();
+
+        const blockNumber = ethers.utils.hexValue(receipt.blockNumber);
+        const rawBlock = await provider.send('debug_getRawBlock', [blockNumber]);
+        expect(rawBlock).toEqual(expect.stringMatching(/^0x[0-9a-f]+$/));
+        expect(rawBlock).toContain(rawTx.slice(2));
+
+        const block = await provider.getBlock(receipt.blockNumber);
+        const rawReceipts = await provider.send('debug_getRawReceipts', [blockNumber]);
+        expect(rawReceipts.length).toEqual(block.transactions.length);
+    });
+
+    test('Should return flat traces in the trace namespace', async () => {
+        const tx = await aliceErc20.transfer(bob.address, 1);
+        const receipt = await tx.wait();
+        const provider = testMaster.mainAccount().provider;
+
+        const replayed = await provider.send('trace_replayTransaction', [tx.hash, ['trace']]);
+        expect(replayed.trace.length).toBeGreaterThan(0);
+        expect(replayed.trace[0].traceAddress).toEqual([]);
+        expect(replayed.trace[0].subtraces).toEqual(expect.any(Number));
+        expect(replayed.vmTrace).toBeNull();
+
+        const blockTraces = await provider.send('trace_block', [ethers.utils.hexValue(receipt.blockNumber)]);
+        const txTraces = blockTraces.filter((trace: any) => trace.transactionHash === tx.hash);
+        expect(txTraces.map((trace: any) => trace.traceAddress)).toEqual(
+            replayed.trace.map((trace: any) => trace.traceAddress)
+        );
+        expect(txTraces[0].blockHash).toEqual(receipt.blockHash);
+
+        const callData = aliceErc20.interface.encodeFunctionData('transfer', [bob.address, 1]);
+        const callTrace = await provider.send('trace_call', [
+            { from: alice.address, to: aliceErc20.address, data: callData },
+            ['trace'],
+            'latest'
+        ]);
+        expect(callTrace.trace[0].action.callType).toEqual('call');
+    });
+
+    test('Should filter traces by address', async () => {
+        const tx = await aliceErc20.transfer(bob.address, 1);
+        const receipt = await tx.wait();
+        const blockNumber = ethers.utils.hexValue(receipt.blockNumber);
+
+        const traces = await testMaster.mainAccount().provider.send('trace_filter', [
+            { fromBlock: blockNumber, toBlock: blockNumber, toAddress: [aliceErc20.address] }
+        ]);
+        expect(traces.length).toBeGreaterThan(0);
+        for (const trace of traces) {
+            expect(trace.action.to.toLowerCase()).toEqual(aliceErc20.address.toLowerCase());
+            expect(trace.blockNumber).toEqual(blockNumber);
+        }
+        expect(traces.map((trace: any) => trace.transactionHash)).toContain(tx.hash);
+
+        const skipped = await testMaster.mainAccount().provider.send('trace_filter', [
+            { fromBlock: blockNumber, toBlock: blockNumber, toAddress: [aliceErc20.address], after: traces.length }
+        ]);
+        expect(skipped).toEqual([]);
     });
 
     afterAll(async () => {
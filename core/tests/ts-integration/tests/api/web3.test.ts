@@ -45,6 +45,12 @@ describe('web3 API compatibility tests', () => {
         }
         expect(blockWithTxsByNumber.gasUsed).bnToBeGte(sumTxGasUsed);
 
+        // eth_getBlockReceipts
+        const blockReceipts = await alice.provider.send('eth_getBlockReceipts', [blockNumberHex]);
+        expect(blockReceipts.map((receipt: any) => receipt.transactionHash)).toEqual(
+            blockWithTxsByNumber.transactions.map((tx) => tx.hash)
+        );
+
         // eth_getBlockByHash
         await alice.provider.getBlock(blockHash);
         const blockWithTxsByHash = await alice.provider.getBlockWithTransactions(blockHash);
@@ -113,6 +119,12 @@ describe('web3 API compatibility tests', () => {
         // zks_getL1BatchDetails
         const batchDetails = await alice.provider.getL1BatchDetails(block.l1BatchNumber);
         expect(batchDetails.number).toEqual(block.l1BatchNumber);
+        // zks_getL1BatchFeeParams
+        const batchFeeParams = await alice.provider.send('zks_getL1BatchFeeParams', [block.l1BatchNumber]);
+        expect(batchFeeParams.number).toEqual(block.l1BatchNumber);
+        expect(+batchFeeParams.l1GasPrice).toEqual(batchDetails.l1GasPrice);
+        expect(+batchFeeParams.fairL2GasPrice).toEqual(batchDetails.l2FairGasPrice);
+        expect(batchFeeParams.fairPubdataPrice).toMatch(HEX_VALUE_REGEX);
         // zks_estimateFee
         const response = await alice.provider.send('zks_estimateFee', [
             { from: alice.address, to: alice.address, value: '0x1' }
@@ -659,6 +671,44 @@ describe('web3 API compatibility tests', () => {
         await expect(alice.sendTransaction(tx)).toBeRejected('gas per pub data limit is zero');
     });
 
+    test('Should check eth_sendRawTransactionConditional', async () => {
+        const tx = await alice.populateTransaction({ to: alice.address });
+        const signedTx = await alice.signTransaction(tx);
+
+        const failingConditions = { blockNumberMax: '0x0' };
+        await expect(
+            alice.provider.send('eth_sendRawTransactionConditional', [signedTx, failingConditions])
+        ).rejects.toThrow('transaction conditions are not met');
+
+        const blockNumber = await alice.provider.getBlockNumber();
+        const balance = await alice.getBalance();
+        const conditions = {
+            blockNumberMin: ethers.utils.hexValue(blockNumber),
+            knownBalances: { [alice.address]: balance.toHexString() }
+        };
+        const txHash = await alice.provider.send('eth_sendRawTransactionConditional', [signedTx, conditions]);
+        const receipt = await alice.provider.waitForTransaction(txHash);
+        expect(receipt.status).toEqual(1);
+    });
+
+    test('Should check zks_sendBundle', async () => {
+        await expect(alice.provider.send('zks_sendBundle', [[]])).rejects.toThrow('bundle must contain');
+
+        const nonce = await alice.getTransactionCount();
+        const firstTx = await alice.populateTransaction({ to: alice.address, nonce });
+        const secondTx = await alice.populateTransaction({ to: alice.address, nonce: nonce + 1 });
+        const signedTxs = [await alice.signTransaction(firstTx), await alice.signTransaction(secondTx)];
+        const bundleHash = await alice.provider.send('zks_sendBundle', [signedTxs]);
+        expect(bundleHash).toEqual(expect.stringMatching(HEX_VALUE_REGEX));
+
+        const txHashes = signedTxs.map((signedTx) => zksync.utils.parseTransaction(signedTx).hash!);
+        const receipts = await Promise.all(txHashes.map((txHash) => alice.provider.waitForTransaction(txHash)));
+        expect(receipts.map((receipt) => receipt.status)).toEqual([1, 1]);
+        // Bundle transactions must be included into the same miniblock contiguously.
+        expect(receipts[1].blockNumber).toEqual(receipts[0].blockNumber);
+        expect(receipts[1].transactionIndex).toEqual(receipts[0].transactionIndex + 1);
+    });
+
     test('Should check getLogs works with address/topics in filter', async () => {
         // We're sending a transfer from the wallet, so we'll use a new account to make event unique.
         let uniqueRecipient = testMaster.newEmptyAccount().address;
@@ -682,6 +732,23 @@ describe('web3 API compatibility tests', () => {
         expect(logs[0].transactionHash).toEqual(tx.hash);
     });
 
+    test('Should check getLogs pagination', async () => {
+        const receipt = await anyTransaction(alice);
+        const blockNumber = ethers.utils.hexlify(receipt.blockNumber);
+        const filter = { fromBlock: blockNumber, toBlock: blockNumber };
+        const allLogs = await alice.provider.send('eth_getLogs', [filter]);
+
+        const paginatedLogs = [];
+        let pageToken: string | undefined = undefined;
+        do {
+            const page: any = await alice.provider.send('eth_getLogs', [{ ...filter, limit: 1, pageToken }]);
+            expect(page.logs.length).toBeLessThanOrEqual(1);
+            paginatedLogs.push(...page.logs);
+            pageToken = page.nextPageToken ?? undefined;
+        } while (pageToken);
+        expect(paginatedLogs).toEqual(allLogs);
+    });
+
     test('Should check getLogs endpoint works properly with block tags', async () => {
         const earliestLogs = alice.provider.send('eth_getLogs', [
             {
@@ -755,6 +822,130 @@ describe('web3 API compatibility tests', () => {
             const expectedBaseFee = (await alice.provider.getBlock(receipt.blockNumber - 1 + i)).baseFeePerGas;
             expect(ethers.BigNumber.from(response.baseFeePerGas[i])).toEqual(expectedBaseFee);
         }
+        expect(response.gasUsedRatio).toHaveLength(2);
+        expect(response.gasUsedRatio[1]).toBeGreaterThan(0);
+        expect(response.reward ?? null).toBeNull();
+
+        const withRewards = await alice.provider.send('eth_feeHistory', [
+            '0x2',
+            ethers.utils.hexlify(receipt.blockNumber),
+            [25, 75]
+        ]);
+        expect(withRewards.reward).toHaveLength(2);
+        expect(withRewards.reward[1]).toHaveLength(2);
+        await expect(alice.provider.send('eth_feeHistory', ['0x1', 'latest', [75, 25]])).rejects.toThrow(
+            'reward percentiles must be monotonically increasing'
+        );
+    });
+
+    test('Should check eth_maxPriorityFeePerGas', async () => {
+        const priorityFee = await alice.provider.send('eth_maxPriorityFeePerGas', []);
+        expect(ethers.utils.isHexString(priorityFee)).toBeTruthy();
+        expect(ethers.BigNumber.from(priorityFee).gte(0)).toBeTruthy();
+    });
+
+    test('Should check eth_call with state overrides', async () => {
+        const account = ethers.Wallet.createRandom().address;
+        const overriddenBalance = ethers.utils.parseEther('123');
+        const ethToken = new ethers.Contract(
+            zksync.utils.L2_ETH_TOKEN_ADDRESS,
+            ['function balanceOf(address) view returns (uint256)'],
+            alice.provider
+        );
+        const request = {
+            to: ethToken.address,
+            data: ethToken.interface.encodeFunctionData('balanceOf', [account])
+        };
+
+        const output = await alice.provider.send('eth_call', [
+            request,
+            'latest',
+            { [account]: { balance: overriddenBalance.toHexString() } }
+        ]);
+        expect(ethers.BigNumber.from(output)).toEqual(overriddenBalance);
+
+        await expect(
+            alice.provider.send('eth_call', [request, 'latest', { [account]: { state: {}, stateDiff: {} } }])
+        ).rejects.toThrow('Invalid state override');
+    });
+
+    test('Should check fee estimation with state overrides', async () => {
+        const account = ethers.Wallet.createRandom().address;
+        const value = ethers.utils.parseEther('1');
+        const request = {
+            from: account,
+            to: alice.address,
+            value: value.toHexString()
+        };
+
+        await expect(alice.provider.send('zks_estimateFee', [request])).rejects.toThrow();
+
+        const stateOverride = { [account]: { balance: value.mul(2).toHexString() } };
+        const fee = await alice.provider.send('zks_estimateFee', [request, stateOverride]);
+        expect(ethers.BigNumber.from(fee.gas_limit).gt(0)).toBeTruthy();
+        const gasLimit = await alice.provider.send('eth_estimateGas', [request, null, stateOverride]);
+        expect(ethers.BigNumber.from(gasLimit).gt(0)).toBeTruthy();
+
+        await expect(
+            alice.provider.send('zks_estimateFee', [request, { [account]: { state: {}, stateDiff: {} } }])
+        ).rejects.toThrow('Invalid state override');
+    });
+
+    test('Should check eth_createAccessList', async () => {
+        const ethToken = new ethers.Contract(
+            zksync.utils.L2_ETH_TOKEN_ADDRESS,
+            ['function balanceOf(address) view returns (uint256)'],
+            alice.provider
+        );
+        const request = {
+            to: ethToken.address,
+            data: ethToken.interface.encodeFunctionData('balanceOf', [alice.address])
+        };
+
+        const result = await alice.provider.send('eth_createAccessList', [request, 'latest']);
+        expect(ethers.BigNumber.from(result.gasUsed).gt(0)).toBeTruthy();
+        const ethTokenEntry = result.accessList.find(
+            (entry: any) => entry.address.toLowerCase() === ethToken.address.toLowerCase()
+        );
+        expect(ethTokenEntry).toBeDefined();
+        expect(ethTokenEntry.storageKeys.length).toBeGreaterThan(0);
+    });
+
+    test('Should check eth_simulateV1', async () => {
+        const ethToken = new ethers.Contract(
+            zksync.utils.L2_ETH_TOKEN_ADDRESS,
+            ['function balanceOf(address) view returns (uint256)'],
+            alice.provider
+        );
+        const balanceRequest = {
+            to: ethToken.address,
+            data: ethToken.interface.encodeFunctionData('balanceOf', [alice.address])
+        };
+        const overriddenBalance = ethers.utils.parseEther('1000000');
+        const payload = {
+            blockStateCalls: [
+                { calls: [balanceRequest] },
+                {
+                    stateOverrides: { [alice.address]: { balance: overriddenBalance.toHexString() } },
+                    calls: [balanceRequest]
+                }
+            ]
+        };
+
+        const blocks = await alice.provider.send('eth_simulateV1', [payload, 'latest']);
+        expect(blocks.length).toEqual(2);
+        expect(+blocks[1].number).toEqual(+blocks[0].number + 1);
+        for (const block of blocks) {
+            expect(block.hash).toMatch(HEX_VALUE_REGEX);
+            expect(block.calls.length).toEqual(1);
+            expect(+block.calls[0].status).toEqual(1);
+        }
+        const overriddenResult = ethers.BigNumber.from(blocks[1].calls[0].returnData);
+        expect(overriddenResult.eq(overriddenBalance)).toBeTruthy();
+
+        await expect(
+            alice.provider.send('eth_simulateV1', [{ blockStateCalls: [{ calls: [] }] }, 'latest'])
+        ).rejects.toThrow('Invalid simulation payload');
     });
 
     test('Should check zks_getProtocolVersion endpoint', async () => {
@@ -110,6 +110,13 @@ describe('Tests for L1 behavior', () => {
         const msgProof = await alice.provider.getLogProof(tx.hash, l2ToL1LogIndex);
         expect(msgProof).toBeTruthy();
 
+        // The bulk method should return the same proof.
+        const [bulkProof, missingProof] = await alice.provider.send('zks_getL2ToL1LogProofs', [
+            [{ txHash: tx.hash, index: l2ToL1LogIndex }, { txHash: ethers.constants.HashZero }]
+        ]);
+        expect(bulkProof).toEqual(msgProof);
+        expect(missingProof).toBeNull();
+
         // Ensure that received proof matches the provided root hash.
         const { id, proof, root } = msgProof!;
         const accumutatedRoot = calculateAccumulatedRoot(alice.address, message, receipt.l1BatchTxIndex, id, proof);
@@ -9,7 +9,9 @@ const CONFIG_FILES = [
     'chain.toml',
     'contract_verifier.toml',
     'contracts.toml',
+    'da_dispatcher.toml',
     'database.toml',
+    'db_pruner.toml',
     'eth_client.toml',
     'eth_sender.toml',
     'eth_watch.toml',
@@ -181,7 +181,7 @@ where
         };
         self.wallet
             .provider
-            .estimate_fee(l2_tx.into())
+            .estimate_fee(l2_tx.into(), None, None)
             .await
             .map_err(Into::into)
     }